@@ -0,0 +1,381 @@
+/// age format module.
+///
+/// This module provides functionality for:
+/// - Encrypting files in the age format (age-encryption.org/v1), so they can be
+///   decrypted with the age CLI and other age tools
+/// - Decrypting age files, including files encrypted by the age CLI (binary or armored)
+/// - Encrypting and decrypting streams, chunk by chunk, for data piped through CRUSTy
+/// - Using CRUSTy keys as age identities, and exporting and importing identity files
+///
+/// Every CRUSTy key is also an X25519 age identity: its 32 bytes are the identity's
+/// secret key. Its recipient (`age1...`) can be given to others, and its identity
+/// (`AGE-SECRET-KEY-1...`) exported for the age CLI. Files encrypted by the age CLI to
+/// that recipient decrypt in CRUSTy with the key, and identities made by `age-keygen`
+/// can be imported as keys.
+///
+/// Files encrypted by age with a passphrase (`age -p`) use scrypt, which CRUSTy keys do
+/// not, and are not supported.
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::iter;
+use std::path::Path;
+use std::str::FromStr;
+
+use age::secrecy::ExposeSecret;
+use age::x25519;
+use bech32::{FromBase32, ToBase32, Variant};
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroizing;
+
+use crate::chunk_tuning;
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+
+/// Start of a binary age file
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1\n";
+
+/// Start of an armored (`age -a`) age file
+const ARMOR_MAGIC: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+
+/// Bech32 prefix of an age identity (written in upper case)
+const SECRET_KEY_PREFIX: &str = "age-secret-key-";
+
+/// age recipient of someone else, saved to encrypt files for them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AgeRecipient {
+    /// Who the recipient belongs to
+    pub name: String,
+    /// The recipient (`age1...`)
+    pub recipient: String,
+}
+
+/// Get the age identity of a key
+fn identity(key: &EncryptionKey) -> x25519::Identity {
+    let encoded = Zeroizing::new(
+        bech32::encode(SECRET_KEY_PREFIX, key.key.to_base32(), Variant::Bech32)
+            .expect("valid prefix")
+            .to_uppercase()
+    );
+    x25519::Identity::from_str(&encoded).expect("32-byte identity")
+}
+
+/// Get the age recipient (`age1...`) of a key, for others to encrypt files to
+pub fn recipient_string(key: &EncryptionKey) -> String {
+    identity(key).to_public().to_string()
+}
+
+/// Check an age recipient entered by the user
+pub fn parse_recipient(recipient: &str) -> Result<x25519::Recipient, EncryptionError> {
+    x25519::Recipient::from_str(recipient.trim())
+        .map_err(|e| EncryptionError::KeyError(format!("'{}' is not an age recipient ({})", recipient.trim(), e)))
+}
+
+/// Get the age identity file of a key, in the format written by `age-keygen`
+pub fn identity_file(key: &EncryptionKey) -> Zeroizing<String> {
+    Zeroizing::new(format!(
+        "# created: {}\n# public key: {}\n{}\n",
+        Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        recipient_string(key),
+        identity(key).to_string().expose_secret()
+    ))
+}
+
+/// Read the keys from an age identity file (one identity per line; lines starting with
+/// `#` are comments)
+///
+/// # Returns
+/// * `Result<Vec<EncryptionKey>, EncryptionError>` - The identities as keys, or an error
+///   if a line is not an X25519 identity or the file has none
+pub fn parse_identity_file(contents: &str) -> Result<Vec<EncryptionKey>, EncryptionError> {
+    let mut keys = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: &str| EncryptionError::KeyError(format!("Line {} of the identity file {}", number + 1, reason));
+        if line.starts_with("AGE-PLUGIN-") {
+            return Err(invalid("is a plugin identity, which CRUSTy does not support"));
+        }
+        let (prefix, data, variant) = bech32::decode(line)
+            .map_err(|_| invalid("is not an age identity"))?;
+        let bytes = Zeroizing::new(Vec::<u8>::from_base32(&data).map_err(|_| invalid("is not an age identity"))?);
+        if prefix != SECRET_KEY_PREFIX || variant != Variant::Bech32 || bytes.len() != 32 {
+            return Err(invalid("is not an age X25519 identity"));
+        }
+
+        let mut key = EncryptionKey { key: [0u8; 32], kdf: None };
+        key.key.copy_from_slice(&bytes);
+        keys.push(key);
+    }
+
+    if keys.is_empty() {
+        return Err(EncryptionError::KeyError("The file has no age identities".to_string()));
+    }
+    Ok(keys)
+}
+
+/// Check whether a file is an age file (binary or armored)
+pub fn is_age_file(path: &Path) -> bool {
+    let mut start = Vec::with_capacity(ARMOR_MAGIC.len());
+    File::open(path)
+        .and_then(|file| file.take(ARMOR_MAGIC.len() as u64).read_to_end(&mut start))
+        .is_ok_and(|_| is_age_data(&start))
+}
+
+/// Check whether data starts as an age file does (binary or armored)
+pub fn is_age_data(data: &[u8]) -> bool {
+    data.starts_with(AGE_MAGIC) || data.starts_with(ARMOR_MAGIC)
+}
+
+/// Get an encryptor to the key's own recipient and every other recipient given
+fn encryptor(key: &EncryptionKey, recipients: &[String]) -> Result<age::Encryptor, EncryptionError> {
+    let mut all_recipients = vec![identity(key).to_public()];
+    for recipient in recipients {
+        all_recipients.push(parse_recipient(recipient)?);
+    }
+    age::Encryptor::with_recipients(all_recipients.iter().map(|recipient| recipient as &dyn age::Recipient))
+        .map_err(|e| EncryptionError::Encryption(e.to_string()))
+}
+
+/// Encrypt a file in the age format
+///
+/// The file is encrypted to the key's own recipient, so CRUSTy and the key's exported
+/// identity can decrypt it, and to every other recipient given.
+///
+/// # Arguments
+/// * `source_path` - The file to encrypt
+/// * `dest_path` - The age file to create (written atomically; never replaced)
+/// * `key` - The encryption key
+/// * `recipients` - Other age recipients (`age1...`) who can decrypt the file
+/// * `progress_callback` - Called with the fraction done
+pub fn encrypt_file(
+    source_path: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    recipients: &[String],
+    progress_callback: impl Fn(f32),
+) -> Result<(), EncryptionError> {
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+
+    let encryptor = encryptor(key, recipients)?;
+
+    // Encrypted chunk by chunk as it is read, so the file is never held in memory
+    let source = File::open(source_path)?;
+    let len = source.metadata()?.len();
+    encryption::write_destination(dest_path, |dest_file| {
+        let mut writer = encryptor.wrap_output(dest_file)?;
+        chunk_tuning::copy(source, &mut writer, len, &progress_callback)?;
+        writer.finish().map(|_| ())
+    })?;
+    progress_callback(1.0);
+
+    Ok(())
+}
+
+/// Encrypt a stream in the age format, to the key's own recipient and every other
+/// recipient given
+///
+/// The data is encrypted chunk by chunk as it is read, so it is never held in memory.
+pub fn encrypt_stream(
+    mut reader: impl Read,
+    writer: impl Write,
+    key: &EncryptionKey,
+    recipients: &[String],
+) -> Result<(), EncryptionError> {
+    let mut output = encryptor(key, recipients)?.wrap_output(writer)?;
+    io::copy(&mut reader, &mut output)?;
+    output.finish()?.flush()?;
+    Ok(())
+}
+
+/// Encrypt text to the key's own recipient, as an armored age message that can be
+/// pasted (as `age -a` writes)
+pub fn encrypt_text(text: &str, key: &EncryptionKey) -> Result<String, EncryptionError> {
+    let armored = age::armor::ArmoredWriter::wrap_output(Vec::new(), age::armor::Format::AsciiArmor)?;
+    let mut output = encryptor(key, &[])?.wrap_output(armored)?;
+    output.write_all(text.as_bytes())?;
+    let message = output.finish()?.finish()?;
+    Ok(String::from_utf8(message).expect("Armored age messages are ASCII"))
+}
+
+/// Decrypt an age stream (binary or armored) with the key's identity
+///
+/// Each chunk is written once its tag is checked, so if the stream was changed or cut
+/// short, the error comes after the data before the damage has been written.
+pub fn decrypt_stream(reader: impl Read, mut writer: impl Write, key: &EncryptionKey) -> Result<(), EncryptionError> {
+    let mut reader = open_reader(BufReader::new(reader), key)?;
+    io::copy(&mut reader, &mut writer).map_err(damaged_error)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Decrypt an age file with the key's identity
+///
+/// # Arguments
+/// * `source_path` - The age file (binary or armored)
+/// * `dest_path` - The file to create (written atomically; never replaced)
+/// * `key` - The encryption key
+/// * `progress_callback` - Called with the fraction done
+pub fn decrypt_file(
+    source_path: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32),
+) -> Result<(), EncryptionError> {
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+
+    let reader = open(source_path, key)?;
+    progress_callback(0.5);
+
+    // Each chunk is written once its tag is checked; the file is only given its name if
+    // every chunk was, so damage never leaves a partly decrypted file
+    encryption::write_destination(dest_path, |dest_file| encryption::copy_sparse(reader, dest_file).map(|_| ()))
+        .map_err(|e| match e {
+            EncryptionError::Io(e) if matches!(e.kind(), io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof) => damaged_error(e),
+            e => e,
+        })?;
+    progress_callback(1.0);
+
+    Ok(())
+}
+
+/// Decrypt an age file with the key's identity into memory
+pub fn decrypt(source_path: &Path, key: &EncryptionKey) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    let mut reader = open(source_path, key)?;
+    let mut data = Zeroizing::new(Vec::new());
+    reader.read_to_end(&mut data).map_err(damaged_error)?;
+    Ok(data)
+}
+
+/// Check that an age file decrypts with the key's identity, without keeping the contents
+///
+/// The file is read chunk by chunk; each chunk's tag is checked and its contents dropped.
+pub fn verify(source_path: &Path, key: &EncryptionKey) -> Result<(), EncryptionError> {
+    let mut reader = open(source_path, key)?;
+    io::copy(&mut reader, &mut io::sink()).map_err(damaged_error)?;
+    Ok(())
+}
+
+/// Open an age file for reading its contents with the key's identity
+fn open(source_path: &Path, key: &EncryptionKey) -> Result<impl Read, EncryptionError> {
+    open_reader(BufReader::new(File::open(source_path)?), key)
+}
+
+/// Open age data for reading its contents with the key's identity
+fn open_reader<R: BufRead>(reader: R, key: &EncryptionKey) -> Result<impl Read, EncryptionError> {
+    let reader = age::armor::ArmoredReader::new(reader);
+    let decryptor = age::Decryptor::new(reader).map_err(decrypt_error)?;
+    if decryptor.is_scrypt() {
+        return Err(EncryptionError::Decryption(
+            "The age file was encrypted with a passphrase (age -p), which CRUSTy does not support".to_string()
+        ));
+    }
+
+    let identity = identity(key);
+    // Every chunk is authenticated as it is read
+    decryptor.decrypt(iter::once(&identity as &dyn age::Identity))
+        .map_err(decrypt_error)
+}
+
+/// Describe an error from reading the contents of an age file
+fn damaged_error(error: io::Error) -> EncryptionError {
+    EncryptionError::Decryption(format!("The age file is damaged or was changed ({})", error))
+}
+
+/// Describe an error from decrypting an age file
+fn decrypt_error(error: age::DecryptError) -> EncryptionError {
+    match error {
+        age::DecryptError::NoMatchingKeys => EncryptionError::Decryption(
+            "The age file was not encrypted to this key's age recipient".to_string()
+        ),
+        error => EncryptionError::Decryption(format!("Failed to read the age file: {}", error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_age_round_trip() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("notes.txt");
+        let encrypted = dir.path().join("notes.txt.age");
+        fs::write(&source, b"Readable by age").unwrap();
+
+        let key = EncryptionKey::generate();
+        let colleague = EncryptionKey::generate();
+        encrypt_file(&source, &encrypted, &key, &[recipient_string(&colleague)], |_| {}).unwrap();
+        assert!(is_age_file(&encrypted));
+        assert!(!is_age_file(&source));
+
+        // Both recipients can decrypt; other keys cannot
+        for (i, decrypting_key) in [&key, &colleague].into_iter().enumerate() {
+            let decrypted = dir.path().join(format!("decrypted{}.txt", i));
+            decrypt_file(&encrypted, &decrypted, decrypting_key, |_| {}).unwrap();
+            assert_eq!(fs::read(&decrypted).unwrap(), b"Readable by age");
+        }
+        let result = decrypt_file(&encrypted, &dir.path().join("other.txt"), &EncryptionKey::generate(), |_| {});
+        assert!(matches!(result, Err(EncryptionError::Decryption(message)) if message.contains("not encrypted to this key")));
+
+        assert!(encrypt_file(&source, &dir.path().join("bad.age"), &key, &["age1notarecipient".to_string()], |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_text() {
+        let key = EncryptionKey::generate();
+        let message = encrypt_text("Pasted text", &key).unwrap();
+        assert!(message.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+        assert!(is_age_data(message.as_bytes()));
+
+        let mut decrypted = Vec::new();
+        decrypt_stream(message.as_bytes(), &mut decrypted, &key).unwrap();
+        assert_eq!(decrypted, b"Pasted text");
+    }
+
+    #[test]
+    fn test_files_from_age_tools() {
+        let dir = tempdir().unwrap();
+
+        // An identity made by age-keygen, imported as a key
+        let age_identity = x25519::Identity::generate();
+        let identity_text = format!("# created: 2024-01-01T00:00:00Z\n# public key: {}\n{}\n",
+            age_identity.to_public(), age_identity.to_string().expose_secret());
+        let keys = parse_identity_file(&identity_text).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(recipient_string(&keys[0]), age_identity.to_public().to_string());
+
+        // A file encrypted to it by age, armored as with `age -a`
+        let encrypted = dir.path().join("report.pdf.age");
+        let encryptor = age::Encryptor::with_recipients(iter::once(&age_identity.to_public() as &dyn age::Recipient)).unwrap();
+        let armored = age::armor::ArmoredWriter::wrap_output(File::create(&encrypted).unwrap(), age::armor::Format::AsciiArmor).unwrap();
+        let mut writer = encryptor.wrap_output(armored).unwrap();
+        writer.write_all(b"From the age CLI").unwrap();
+        writer.finish().unwrap().finish().unwrap();
+
+        let decrypted = dir.path().join("report.pdf");
+        decrypt_file(&encrypted, &decrypted, &keys[0], |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), b"From the age CLI");
+
+        // An exported identity is read back as the same key
+        let key = EncryptionKey::generate();
+        let exported = identity_file(&key);
+        assert_eq!(parse_identity_file(&exported).unwrap()[0].key, key.key);
+        assert!(exported.contains(&recipient_string(&key)));
+
+        assert!(parse_identity_file("# no identities\n").is_err());
+        assert!(parse_identity_file(&recipient_string(&key)).is_err());
+    }
+}
@@ -0,0 +1,612 @@
+/// Backend abstraction for encryption operations.
+///
+/// This module provides a trait-based abstraction for different encryption backends,
+/// allowing the application to use either local (software-based) encryption or
+/// offload encryption operations to an embedded device.
+use std::ffi::OsStr;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use crate::encryption::{EncryptionKey, EncryptionError};
+use crate::embedded_protocol::{DeviceLink, DeviceProgress};
+use crate::recipients::Recipient;
+
+/// Trait defining the interface for encryption backends.
+pub trait EncryptionBackend {
+    /// Encrypts raw data using the provided key.
+    fn encrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError>;
+    
+    /// Decrypts raw data using the provided key.
+    fn decrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError>;
+    
+    /// Encrypts a file using the provided key.
+    fn encrypt_file(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError>;
+    
+    /// Encrypts a file using the provided key, storing its original name (encrypted)
+    /// in the file header.
+    fn encrypt_file_storing_name(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        name: &OsStr,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError>;
+    
+    /// Decrypts a file using the provided key.
+    fn decrypt_file(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError>;
+    
+    /// Checks that a file decrypts with the provided key (its authentication tags are
+    /// valid), without writing any decrypted data.
+    fn verify_file(
+        &self,
+        source_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError>;
+    
+    /// Encrypts everything read from `reader` using the provided key, writing the
+    /// encrypted data to `writer`, so data can be piped through without a file at
+    /// either end.
+    fn encrypt_stream(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError>;
+    
+    /// Decrypts everything read from `reader` using the provided key, writing the
+    /// decrypted data to `writer`.
+    fn decrypt_stream(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError>;
+    
+    /// Encrypts multiple files using the provided key.
+    ///
+    /// With `hide_names`, each file is saved under a random name and its original
+    /// name is stored in the file header.
+    fn encrypt_files(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        hide_names: bool,
+        progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+    ) -> Result<Vec<String>, EncryptionError>;
+    
+    /// Decrypts multiple files using the provided key.
+    fn decrypt_files(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+    ) -> Result<Vec<String>, EncryptionError>;
+}
+
+/// Local (software-based) implementation of the encryption backend.
+#[derive(Default)]
+pub struct LocalBackend {
+    /// Append parity to encrypted files, so damage to them can be repaired
+    pub add_parity: bool,
+    /// Store the SHA-256 of the original in the header, so decrypted files are checked
+    /// against it and damage is told apart from a wrong key
+    pub add_digest: bool,
+    /// Encrypt files in the age format, to the key's own age recipient and these
+    /// (None for CRUSTy's format)
+    pub age_recipients: Option<Vec<String>>,
+    /// Encrypt files as OpenPGP messages, to the key's own OpenPGP key and these armored
+    /// public keys (None for CRUSTy's format)
+    pub openpgp_recipients: Option<Vec<String>>,
+    /// Encrypt files once for the key's own age recipient and these recipients, each
+    /// sent the file's key wrapped in the header (None for the key alone)
+    pub recipients: Option<Vec<Recipient>>,
+    /// Most bytes a file may take in memory, with its encrypted or decrypted copy (None
+    /// for no limit). age files are processed in chunks; files in other formats are
+    /// held whole, and refused if they would take more
+    pub memory_limit: Option<u64>,
+}
+
+/// Configuration for the embedded device backend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddedConfig {
+    /// Device identifier or address
+    pub device_id: String,
+    /// Settings for the connection to the device
+    pub connection: ConnectionSettings,
+}
+
+impl EmbeddedConfig {
+    /// Checks the configuration before connecting to the device.
+    pub fn validate(&self) -> Result<(), ConnectionSettingsError> {
+        if self.device_id.trim().is_empty() {
+            return Err(ConnectionSettingsError::MissingDeviceId);
+        }
+        self.connection.validate()
+    }
+}
+
+/// Connection types for the embedded device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConnectionType {
+    /// USB connection
+    Usb,
+    /// Serial/UART connection
+    Serial,
+    /// Ethernet/TCP connection
+    Ethernet,
+}
+
+/// Connection settings for the embedded device, by connection type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConnectionSettings {
+    /// USB connection settings
+    Usb(UsbSettings),
+    /// Serial/UART connection settings
+    Serial(SerialSettings),
+    /// Ethernet/TCP connection settings
+    Ethernet(EthernetSettings),
+}
+
+impl ConnectionSettings {
+    /// Returns the default settings for a connection type.
+    pub fn default_for(connection_type: &ConnectionType) -> Self {
+        match connection_type {
+            ConnectionType::Usb => ConnectionSettings::Usb(UsbSettings::default()),
+            ConnectionType::Serial => ConnectionSettings::Serial(SerialSettings::default()),
+            ConnectionType::Ethernet => ConnectionSettings::Ethernet(EthernetSettings::default()),
+        }
+    }
+    
+    /// Returns the connection type these settings are for.
+    pub fn connection_type(&self) -> ConnectionType {
+        match self {
+            ConnectionSettings::Usb(_) => ConnectionType::Usb,
+            ConnectionSettings::Serial(_) => ConnectionType::Serial,
+            ConnectionSettings::Ethernet(_) => ConnectionType::Ethernet,
+        }
+    }
+    
+    /// Checks the settings before connecting to the device.
+    pub fn validate(&self) -> Result<(), ConnectionSettingsError> {
+        match self {
+            ConnectionSettings::Usb(usb) => usb.validate(),
+            ConnectionSettings::Serial(serial) => serial.validate(),
+            ConnectionSettings::Ethernet(ethernet) => ethernet.validate(),
+        }
+    }
+}
+
+/// USB connection settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsbSettings {
+    /// USB interface number
+    pub interface: u8,
+    /// Bulk endpoint address (the OUT endpoint; the IN endpoint is this | 0x80)
+    pub endpoint: u8,
+}
+
+impl Default for UsbSettings {
+    fn default() -> Self {
+        UsbSettings {
+            interface: 0,
+            endpoint: 0x01,
+        }
+    }
+}
+
+impl UsbSettings {
+    /// Checks the USB settings.
+    pub fn validate(&self) -> Result<(), ConnectionSettingsError> {
+        // Endpoint 0 is the control endpoint, and endpoint numbers only go up to 15
+        if self.endpoint == 0 || self.endpoint > 0x0F {
+            return Err(ConnectionSettingsError::InvalidEndpoint(self.endpoint));
+        }
+        Ok(())
+    }
+}
+
+/// Serial/UART connection settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerialSettings {
+    /// Baud rate
+    pub baud_rate: u32,
+    /// Parity bit
+    pub parity: Parity,
+}
+
+impl Default for SerialSettings {
+    fn default() -> Self {
+        SerialSettings {
+            baud_rate: 115_200,
+            parity: Parity::None,
+        }
+    }
+}
+
+impl SerialSettings {
+    /// Checks the serial settings.
+    pub fn validate(&self) -> Result<(), ConnectionSettingsError> {
+        if self.baud_rate == 0 {
+            return Err(ConnectionSettingsError::InvalidBaudRate(self.baud_rate));
+        }
+        Ok(())
+    }
+}
+
+/// Parity bit for serial connections.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Parity {
+    /// No parity bit
+    None,
+    /// Even parity
+    Even,
+    /// Odd parity
+    Odd,
+}
+
+/// Ethernet/TCP connection settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EthernetSettings {
+    /// Host name or IP address of the device
+    pub host: String,
+    /// TCP port
+    pub port: u16,
+    /// Use TLS for the connection
+    pub tls: bool,
+    /// Certificate of the device (PEM or DER), the only one trusted for TLS when set;
+    /// otherwise the device's certificate must be signed by a known authority
+    pub certificate: Option<PathBuf>,
+}
+
+impl Default for EthernetSettings {
+    fn default() -> Self {
+        EthernetSettings {
+            host: String::new(),
+            port: 5683,
+            tls: true,
+            certificate: None,
+        }
+    }
+}
+
+impl EthernetSettings {
+    /// Checks the Ethernet settings.
+    pub fn validate(&self) -> Result<(), ConnectionSettingsError> {
+        let host = self.host.trim();
+        if host.is_empty() {
+            return Err(ConnectionSettingsError::MissingHost);
+        }
+        if host.parse::<std::net::IpAddr>().is_err()
+            && !host.split('.').all(|label| !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')) {
+            return Err(ConnectionSettingsError::InvalidHost(host.to_string()));
+        }
+        if self.port == 0 {
+            return Err(ConnectionSettingsError::InvalidPort);
+        }
+        Ok(())
+    }
+}
+
+/// Error type for invalid connection settings
+#[derive(Debug, Error, PartialEq)]
+pub enum ConnectionSettingsError {
+    /// No device identifier was entered
+    #[error("Please enter a device ID/address")]
+    MissingDeviceId,
+
+    /// The USB endpoint is not a bulk endpoint number
+    #[error("USB endpoint must be from 1 to 15 (got {0})")]
+    InvalidEndpoint(u8),
+
+    /// The baud rate is not usable
+    #[error("Baud rate must be a positive number (got {0})")]
+    InvalidBaudRate(u32),
+
+    /// No host was entered for an Ethernet connection
+    #[error("Please enter the device's host name or IP address")]
+    MissingHost,
+
+    /// The host is neither an IP address nor a host name
+    #[error("'{0}' is not a valid host name or IP address")]
+    InvalidHost(String),
+
+    /// The TCP port is not usable
+    #[error("Port must be a number from 1 to 65535")]
+    InvalidPort,
+}
+
+/// Embedded device implementation of the encryption backend.
+pub struct EmbeddedBackend {
+    /// Configuration for the embedded device connection
+    pub config: EmbeddedConfig,
+    /// Whether the backend is currently connected
+    pub connected: bool,
+    /// Link to the device (None until `connect` opens it)
+    pub link: Option<Mutex<Box<dyn DeviceLink>>>,
+    /// Progress reported by the device for the running job (None between jobs)
+    pub device_progress: Arc<Mutex<Option<DeviceProgress>>>,
+}
+
+/// Enum-based backend that can be either local or embedded
+pub enum Backend {
+    /// Local (software-based) backend
+    Local(LocalBackend),
+    /// Embedded device backend
+    Embedded(EmbeddedBackend),
+}
+
+impl Backend {
+    /// Connects to the embedded device (the local backend has nothing to connect to).
+    pub fn connect(&mut self) -> Result<(), EncryptionError> {
+        match self {
+            Backend::Local(_) => Ok(()),
+            Backend::Embedded(backend) => backend.connect(),
+        }
+    }
+    
+    /// Encrypts raw data using the provided key.
+    pub fn encrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+        match self {
+            Backend::Local(backend) => backend.encrypt_data(data, key),
+            Backend::Embedded(backend) => backend.encrypt_data(data, key),
+        }
+    }
+    
+    /// Decrypts raw data using the provided key.
+    pub fn decrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+        match self {
+            Backend::Local(backend) => backend.decrypt_data(data, key),
+            Backend::Embedded(backend) => backend.decrypt_data(data, key),
+        }
+    }
+    
+    /// Encrypts a file using the provided key.
+    pub fn encrypt_file<F>(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: F,
+    ) -> Result<(), EncryptionError>
+    where
+        F: Fn(f32) + Send + 'static,
+    {
+        match self {
+            Backend::Local(backend) => backend.encrypt_file(source_path, dest_path, key, progress_callback),
+            Backend::Embedded(backend) => backend.encrypt_file(source_path, dest_path, key, progress_callback),
+        }
+    }
+    
+    /// Encrypts a file using the provided key, storing its original name in the header.
+    pub fn encrypt_file_storing_name<F>(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        name: &OsStr,
+        progress_callback: F,
+    ) -> Result<(), EncryptionError>
+    where
+        F: Fn(f32) + Send + 'static,
+    {
+        match self {
+            Backend::Local(backend) => backend.encrypt_file_storing_name(source_path, dest_path, key, name, progress_callback),
+            Backend::Embedded(backend) => backend.encrypt_file_storing_name(source_path, dest_path, key, name, progress_callback),
+        }
+    }
+    
+    /// Decrypts a file using the provided key.
+    pub fn decrypt_file<F>(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: F,
+    ) -> Result<(), EncryptionError>
+    where
+        F: Fn(f32) + Send + 'static,
+    {
+        match self {
+            Backend::Local(backend) => backend.decrypt_file(source_path, dest_path, key, progress_callback),
+            Backend::Embedded(backend) => backend.decrypt_file(source_path, dest_path, key, progress_callback),
+        }
+    }
+    
+    /// Checks that a file decrypts with the provided key, without writing any decrypted data.
+    pub fn verify_file<F>(
+        &self,
+        source_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: F,
+    ) -> Result<(), EncryptionError>
+    where
+        F: Fn(f32) + Send + 'static,
+    {
+        match self {
+            Backend::Local(backend) => backend.verify_file(source_path, key, progress_callback),
+            Backend::Embedded(backend) => backend.verify_file(source_path, key, progress_callback),
+        }
+    }
+    
+    /// Encrypts everything read from `reader` using the provided key, writing the
+    /// encrypted data to `writer`.
+    pub fn encrypt_stream(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        match self {
+            Backend::Local(backend) => backend.encrypt_stream(reader, writer, key),
+            Backend::Embedded(backend) => backend.encrypt_stream(reader, writer, key),
+        }
+    }
+    
+    /// Decrypts everything read from `reader` using the provided key, writing the
+    /// decrypted data to `writer`.
+    pub fn decrypt_stream(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        match self {
+            Backend::Local(backend) => backend.decrypt_stream(reader, writer, key),
+            Backend::Embedded(backend) => backend.decrypt_stream(reader, writer, key),
+        }
+    }
+    
+    /// Encrypts multiple files using the provided key.
+    pub fn encrypt_files<F>(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        hide_names: bool,
+        progress_callback: F,
+    ) -> Result<Vec<String>, EncryptionError>
+    where
+        F: Fn(usize, f32) + Clone + Send + 'static,
+    {
+        match self {
+            Backend::Local(backend) => backend.encrypt_files(
+                source_paths, dest_dir, key, hide_names, progress_callback
+            ),
+            Backend::Embedded(backend) => backend.encrypt_files(
+                source_paths, dest_dir, key, hide_names, progress_callback
+            ),
+        }
+    }
+    
+    /// Decrypts multiple files using the provided key.
+    pub fn decrypt_files<F>(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        progress_callback: F,
+    ) -> Result<Vec<String>, EncryptionError>
+    where
+        F: Fn(usize, f32) + Clone + Send + 'static,
+    {
+        match self {
+            Backend::Local(backend) => backend.decrypt_files(
+                source_paths, dest_dir, key, progress_callback
+            ),
+            Backend::Embedded(backend) => backend.decrypt_files(
+                source_paths, dest_dir, key, progress_callback
+            ),
+        }
+    }
+}
+
+/// Factory for creating encryption backends.
+pub struct BackendFactory;
+
+impl BackendFactory {
+    /// Creates a new local (software-based) encryption backend.
+    pub fn create_local() -> Backend {
+        Backend::Local(LocalBackend::default())
+    }
+    
+    /// Creates a new local encryption backend with the given output options (parity,
+    /// age or OpenPGP format).
+    pub fn create_local_with(options: LocalBackend) -> Backend {
+        Backend::Local(options)
+    }
+    
+    /// Creates a new embedded device encryption backend with the specified configuration.
+    ///
+    /// The device's progress (chunks processed, queue depth) is shared through
+    /// `device_progress`, so it can be shown while the operation runs.
+    pub fn create_embedded(config: EmbeddedConfig, device_progress: Arc<Mutex<Option<DeviceProgress>>>) -> Backend {
+        Backend::Embedded(EmbeddedBackend {
+            config,
+            connected: false,
+            link: None,
+            device_progress,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_settings_defaults() {
+        let serial = ConnectionSettings::default_for(&ConnectionType::Serial);
+        assert_eq!(serial.connection_type(), ConnectionType::Serial);
+        assert_eq!(serial, ConnectionSettings::Serial(SerialSettings { baud_rate: 115_200, parity: Parity::None }));
+        assert!(serial.validate().is_ok());
+
+        // Ethernet has no default host
+        let ethernet = ConnectionSettings::default_for(&ConnectionType::Ethernet);
+        assert_eq!(ethernet.validate(), Err(ConnectionSettingsError::MissingHost));
+    }
+
+    #[test]
+    fn test_connection_settings_validation() {
+        let usb = UsbSettings { interface: 0, endpoint: 0 };
+        assert_eq!(usb.validate(), Err(ConnectionSettingsError::InvalidEndpoint(0)));
+
+        let mut ethernet = EthernetSettings { host: "device.local".to_string(), ..Default::default() };
+        assert!(ethernet.validate().is_ok());
+        ethernet.host = "fe80::1".to_string();
+        assert!(ethernet.validate().is_ok());
+        ethernet.host = "bad host".to_string();
+        assert_eq!(ethernet.validate(), Err(ConnectionSettingsError::InvalidHost("bad host".to_string())));
+        ethernet.host = "10.0.0.2".to_string();
+        ethernet.port = 0;
+        assert_eq!(ethernet.validate(), Err(ConnectionSettingsError::InvalidPort));
+
+        let config = EmbeddedConfig {
+            device_id: " ".to_string(),
+            connection: ConnectionSettings::default_for(&ConnectionType::Usb),
+        };
+        assert_eq!(config.validate(), Err(ConnectionSettingsError::MissingDeviceId));
+    }
+
+    #[test]
+    fn test_config_serialization() {
+        let config = EmbeddedConfig {
+            device_id: "crusty-01".to_string(),
+            connection: ConnectionSettings::Serial(SerialSettings { baud_rate: 9_600, parity: Parity::Even }),
+        };
+
+        let text = toml::to_string(&config).unwrap();
+        assert_eq!(toml::from_str::<EmbeddedConfig>(&text).unwrap(), config);
+
+        // Missing settings take their defaults
+        let config: EmbeddedConfig = toml::from_str("device_id = \"crusty-01\"\n[connection]\ntype = \"ethernet\"\nhost = \"10.0.0.2\"\n").unwrap();
+        assert_eq!(config.connection, ConnectionSettings::Ethernet(EthernetSettings {
+            host: "10.0.0.2".to_string(),
+            port: 5683,
+            tls: true,
+            certificate: None,
+        }));
+    }
+}
@@ -0,0 +1,441 @@
+/// Embedded device implementation of the encryption backend.
+///
+/// Data and single files are sent to the device in chunks (see `embedded_protocol`),
+/// and the device's progress is reported after every chunk, so the progress bars
+/// move while the device works.
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use zeroize::Zeroizing;
+
+use crate::backend::{ConnectionSettings, EncryptionBackend, EmbeddedBackend};
+use crate::encryption::{name_header, split_name_header, write_destination, EncryptionKey, EncryptionError};
+use crate::embedded_protocol::{self, DeviceHealth, DeviceLink, JobOperation, ProtocolError};
+use crate::embedded_ethernet::EthernetLink;
+use crate::embedded_serial::SerialLink;
+use crate::file_names;
+use crate::embedded_usb::UsbLink;
+
+impl EmbeddedBackend {
+    /// Opens the link to the embedded device, as configured.
+    pub fn connect(&mut self) -> Result<(), EncryptionError> {
+        self.config.validate().map_err(|e| EncryptionError::Device(e.to_string()))?;
+        
+        let device_id = &self.config.device_id;
+        let link: Box<dyn DeviceLink> = match &self.config.connection {
+            ConnectionSettings::Usb(usb) => Box::new(UsbLink::open(device_id, usb)
+                .map_err(|e| EncryptionError::Device(format!("Could not open USB device {}: {}", device_id.trim(), e)))?),
+            ConnectionSettings::Serial(serial) => Box::new(SerialLink::open(device_id, serial)
+                .map_err(|e| EncryptionError::Device(format!("Could not open serial port {}: {}", device_id.trim(), e)))?),
+            ConnectionSettings::Ethernet(ethernet) => Box::new(EthernetLink::open(ethernet)
+                .map_err(|e| EncryptionError::Device(format!("Could not connect to {}:{}: {}", ethernet.host.trim(), ethernet.port, e)))?),
+        };
+        self.link = Some(Mutex::new(link));
+        self.connected = true;
+        Ok(())
+    }
+    
+    /// Checks if the backend is connected to the embedded device.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+    
+    /// Disconnects from the embedded device, closing its link.
+    pub fn disconnect(&mut self) {
+        self.link = None;
+        self.connected = false;
+    }
+
+    /// Checks that the connected device answers, and asks what it runs and has free.
+    pub fn health_check(&self) -> Result<DeviceHealth, EncryptionError> {
+        let link = match &self.link {
+            Some(link) => link,
+            None => return Err(EncryptionError::Device("Not connected to the device".to_string())),
+        };
+        
+        let mut link = link.lock().unwrap();
+        let device_error = |e: ProtocolError| EncryptionError::Device(e.to_string());
+        let round_trip = embedded_protocol::ping(link.as_mut()).map_err(device_error)?;
+        let info = embedded_protocol::query_info(link.as_mut()).map_err(device_error)?;
+        Ok(DeviceHealth { round_trip, info })
+    }
+
+    /// Runs a job on the device, sharing its progress and reporting it as a fraction.
+    fn run_job(
+        &self,
+        operation: JobOperation,
+        data: &[u8],
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32),
+    ) -> Result<Vec<u8>, EncryptionError> {
+        let error = |message: String| match operation {
+            JobOperation::Encrypt => EncryptionError::Encryption(message),
+            JobOperation::Decrypt => EncryptionError::Decryption(message),
+        };
+
+        let link = match &self.link {
+            Some(link) => link,
+            None => return Err(EncryptionError::Device("Not connected to the device".to_string())),
+        };
+
+        let mut link = link.lock().unwrap();
+        let result = embedded_protocol::run_job(link.as_mut(), operation, key, data, |progress| {
+            *self.device_progress.lock().unwrap() = Some(progress);
+            progress_callback(progress.fraction());
+        });
+        *self.device_progress.lock().unwrap() = None;
+
+        // A failed link is the device's error; anything else is the job's
+        result.map_err(|e| match e {
+            ProtocolError::Link(e) => EncryptionError::Device(e.to_string()),
+            e => error(e.to_string()),
+        })
+    }
+
+    /// Runs a job on the contents of a file, writing the result once the job is complete.
+    ///
+    /// An existing destination is never replaced, and the destination is written
+    /// atomically, so a failed job leaves no partial file. Encrypted files start with
+    /// the name header when a name is stored; it is encrypted here, as the local
+    /// backend does, and skipped before the data is sent back to the device.
+    fn run_file_job(
+        &self,
+        operation: JobOperation,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        stored_name: Option<&[u8]>,
+        progress_callback: impl Fn(f32),
+    ) -> Result<(), EncryptionError> {
+        if dest_path.exists() {
+            return Err(EncryptionError::Io(
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+            ));
+        }
+        
+        let data = Zeroizing::new(fs::read(source_path)?);
+        let header = match stored_name {
+            Some(name) => name_header(name, key)?,
+            None => Vec::new(),
+        };
+        let output = match operation {
+            JobOperation::Encrypt => Zeroizing::new(self.run_job(operation, &data, key, progress_callback)?),
+            JobOperation::Decrypt => {
+                let (_, ciphertext) = split_name_header(&data)?;
+                Zeroizing::new(self.run_job(operation, ciphertext, key, progress_callback)?)
+            }
+        };
+        write_destination(dest_path, |dest_file| {
+            dest_file.write_all(&header)?;
+            dest_file.write_all(&output)
+        })
+    }
+
+    /// Runs a job on everything read from a stream, writing the result once the job is
+    /// complete.
+    fn run_stream_job(
+        &self,
+        operation: JobOperation,
+        mut reader: impl Read,
+        mut writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        let mut data = Zeroizing::new(Vec::new());
+        reader.read_to_end(&mut data)?;
+        let output = Zeroizing::new(self.run_job(operation, &data, key, |_| {})?);
+        writer.write_all(&output)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl EncryptionBackend for EmbeddedBackend {
+    fn encrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+        self.run_job(JobOperation::Encrypt, data, key, |_| {})
+    }
+    
+    fn decrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+        self.run_job(JobOperation::Decrypt, data, key, |_| {})
+    }
+    
+    fn encrypt_file(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        self.run_file_job(JobOperation::Encrypt, source_path, dest_path, key, None, progress_callback)
+    }
+    
+    fn encrypt_file_storing_name(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        name: &OsStr,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        let name = file_names::stored_name_bytes(name);
+        self.run_file_job(JobOperation::Encrypt, source_path, dest_path, key, Some(&name), progress_callback)
+    }
+    
+    fn decrypt_file(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        self.run_file_job(JobOperation::Decrypt, source_path, dest_path, key, None, progress_callback)
+    }
+    
+    fn verify_file(
+        &self,
+        source_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        // The device checks the tag; the data it sends back is wiped without being written
+        let data = fs::read(source_path)?;
+        let (_, ciphertext) = split_name_header(&data)?;
+        self.run_job(JobOperation::Decrypt, ciphertext, key, progress_callback).map(Zeroizing::new)?;
+        Ok(())
+    }
+    
+    fn encrypt_stream(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        self.run_stream_job(JobOperation::Encrypt, reader, writer, key)
+    }
+    
+    fn decrypt_stream(
+        &self,
+        reader: impl Read,
+        writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        self.run_stream_job(JobOperation::Decrypt, reader, writer, key)
+    }
+    
+    
+    fn encrypt_files(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        hide_names: bool,
+        progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+    ) -> Result<Vec<String>, EncryptionError> {
+        let mut results = Vec::new();
+        
+        // Files are sent to the device one at a time; a failed file is reported and the
+        // rest are still encrypted
+        for (i, &source_path) in source_paths.iter().enumerate() {
+            let source_name = source_path.file_name()
+                .ok_or_else(|| EncryptionError::Io(
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid source path")
+                ))?;
+            let file_name = if hide_names {
+                file_names::opaque_encrypted_name()
+            } else {
+                file_names::encrypted_name(source_path).unwrap_or_default()
+            };
+            let dest_path = dest_dir.join(file_name);
+            
+            let progress_cb = {
+                let cb = progress_callback.clone();
+                move |p: f32| cb(i, p)
+            };
+            
+            let result = if hide_names {
+                self.encrypt_file_storing_name(source_path, &dest_path, key, source_name, progress_cb)
+            } else {
+                self.encrypt_file(source_path, &dest_path, key, progress_cb)
+            };
+            
+            match result {
+                Ok(_) if hide_names => results.push(format!(
+                    "Successfully encrypted: {} (saved as {})", source_path.display(), dest_path.display()
+                )),
+                Ok(_) => results.push(format!("Successfully encrypted: {}", source_path.display())),
+                Err(e) => results.push(format!("Failed to encrypt {}: {}", source_path.display(), e)),
+            }
+        }
+        
+        Ok(results)
+    }
+    
+    fn decrypt_files(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+    ) -> Result<Vec<String>, EncryptionError> {
+        let mut results = Vec::new();
+        
+        for (i, &source_path) in source_paths.iter().enumerate() {
+            let output_name = file_names::restored_name(source_path, key)
+                .ok_or_else(|| EncryptionError::Io(
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid source path")
+                ))?;
+            let dest_path = dest_dir.join(&output_name.name);
+            
+            let progress_cb = {
+                let cb = progress_callback.clone();
+                move |p: f32| cb(i, p)
+            };
+            
+            match self.decrypt_file(source_path, &dest_path, key, progress_cb) {
+                Ok(_) => match &output_name.renamed {
+                    Some(reason) => results.push(format!(
+                        "Successfully decrypted: {} (saved as {} because {})",
+                        source_path.display(), dest_path.display(), reason
+                    )),
+                    None => results.push(format!("Successfully decrypted: {}", source_path.display())),
+                },
+                Err(e) => results.push(format!("Failed to decrypt {}: {}", source_path.display(), e)),
+            }
+        }
+        
+        Ok(results)
+    }
+    
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+    use crate::backend::{BackendFactory, Backend, EmbeddedConfig, ConnectionType};
+    use crate::embedded_protocol::tests::SimulatedDevice;
+
+    #[test]
+    fn test_file_progress_from_device() {
+        let config = EmbeddedConfig {
+            device_id: "crusty-01".to_string(),
+            connection: ConnectionSettings::default_for(&ConnectionType::Usb),
+        };
+        let device_progress = Arc::new(Mutex::new(None));
+        let mut backend = match BackendFactory::create_embedded(config, device_progress.clone()) {
+            Backend::Embedded(backend) => backend,
+            Backend::Local(_) => unreachable!(),
+        };
+        let key = EncryptionKey::generate();
+
+        // Without a link to the device nothing is sent
+        assert!(matches!(backend.encrypt_data(b"data", &key), Err(EncryptionError::Device(_))));
+        assert!(backend.health_check().is_err());
+
+        backend.link = Some(Mutex::new(Box::new(SimulatedDevice::new())));
+        assert_eq!(backend.health_check().unwrap().info.firmware_version, "1.4.2");
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("large.bin");
+        let dest = dir.path().join("large.bin.encrypted");
+        fs::write(&source, vec![7u8; embedded_protocol::CHUNK_SIZE * 4]).unwrap();
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let seen = device_progress.clone();
+        let recorded = reports.clone();
+        backend.encrypt_file(&source, &dest, &key, move |p| {
+            // The device's progress is shared while the job runs
+            assert!(seen.lock().unwrap().is_some());
+            recorded.lock().unwrap().push(p);
+        }).unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert!(reports.iter().any(|&p| p > 0.0 && p < 1.0));
+        assert_eq!(reports.last(), Some(&1.0));
+        assert_eq!(fs::read(&dest).unwrap(), vec![!7u8; embedded_protocol::CHUNK_SIZE * 4]);
+        assert!(device_progress.lock().unwrap().is_none());
+
+        // An existing file is not replaced, and a failed job leaves no file behind
+        let existing = fs::read(&dest).unwrap();
+        assert!(matches!(
+            backend.encrypt_file(&source, &dest, &key, |_| {}),
+            Err(EncryptionError::Io(e)) if e.kind() == std::io::ErrorKind::AlreadyExists
+        ));
+        assert_eq!(fs::read(&dest).unwrap(), existing);
+
+        let mut failing = SimulatedDevice::new();
+        failing.fail_after = Some(2);
+        backend.link = Some(Mutex::new(Box::new(failing)));
+        let failed = dir.path().join("failed.bin.encrypted");
+        assert!(backend.encrypt_file(&source, &failed, &key, |_| {}).is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_batch_reports_each_file() {
+        let config = EmbeddedConfig {
+            device_id: "crusty-01".to_string(),
+            connection: ConnectionSettings::default_for(&ConnectionType::Usb),
+        };
+        let mut backend = match BackendFactory::create_embedded(config, Arc::new(Mutex::new(None))) {
+            Backend::Embedded(backend) => backend,
+            Backend::Local(_) => unreachable!(),
+        };
+        backend.link = Some(Mutex::new(Box::new(SimulatedDevice::new())));
+        let key = EncryptionKey::generate();
+        let dir = tempdir().unwrap();
+        let encrypted_dir = dir.path().join("encrypted");
+        let decrypted_dir = dir.path().join("decrypted");
+        fs::create_dir(&encrypted_dir).unwrap();
+        fs::create_dir(&decrypted_dir).unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        fs::write(&first, b"first").unwrap();
+        fs::write(&second, b"second").unwrap();
+        // The second file's destination is taken, so only it fails
+        fs::write(encrypted_dir.join("second.txt.encrypted"), b"taken").unwrap();
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let recorded = progress.clone();
+        let results = backend.encrypt_files(&[first.as_path(), second.as_path()], &encrypted_dir, &key, false, move |i, p| {
+            recorded.lock().unwrap().push((i, p));
+        }).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].starts_with("Successfully encrypted"));
+        assert!(results[1].starts_with("Failed to encrypt") && results[1].contains("already exists"));
+        assert!(progress.lock().unwrap().contains(&(0, 1.0)));
+
+        let encrypted = encrypted_dir.join("first.txt.encrypted");
+        let results = backend.decrypt_files(&[encrypted.as_path()], &decrypted_dir, &key, |_, _| {}).unwrap();
+        assert!(results[0].starts_with("Successfully decrypted"));
+        assert_eq!(fs::read(decrypted_dir.join("first.txt")).unwrap(), b"first");
+
+        // Hidden names are stored in the header and restored on decryption
+        let hidden_dir = dir.path().join("hidden");
+        fs::create_dir(&hidden_dir).unwrap();
+        let results = backend.encrypt_files(&[second.as_path()], &hidden_dir, &key, true, |_, _| {}).unwrap();
+        assert!(results[0].contains("(saved as"));
+        let hidden = fs::read_dir(&hidden_dir).unwrap().next().unwrap().unwrap().path();
+        assert_ne!(hidden.file_name().unwrap(), "second.txt.encrypted");
+        backend.verify_file(&hidden, &key, |_| {}).unwrap();
+        fs::remove_file(decrypted_dir.join("first.txt")).unwrap();
+        backend.decrypt_files(&[hidden.as_path()], &decrypted_dir, &key, |_, _| {}).unwrap();
+        assert_eq!(fs::read(decrypted_dir.join("second.txt")).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_connect_checks_config() {
+        let config = EmbeddedConfig {
+            device_id: String::new(),
+            connection: ConnectionSettings::default_for(&ConnectionType::Usb),
+        };
+        let mut backend = match BackendFactory::create_embedded(config, Arc::new(Mutex::new(None))) {
+            Backend::Embedded(backend) => backend,
+            Backend::Local(_) => unreachable!(),
+        };
+        assert!(matches!(backend.connect(), Err(EncryptionError::Device(message)) if message.contains("device ID")));
+        assert!(!backend.is_connected());
+        assert!(backend.link.is_none());
+    }
+}
@@ -0,0 +1,552 @@
+/// Local (software-based) implementation of the encryption backend.
+use std::ffi::OsStr;
+use std::path::Path;
+use std::fs;
+use std::io::{Read, Write};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::backend::{EncryptionBackend, LocalBackend};
+use crate::age_format;
+use crate::chunk_tuning;
+use crate::openpgp;
+use crate::file_names;
+use crate::parity;
+use crate::recipients;
+use crate::encryption::{
+    EncryptionKey, EncryptionError,
+    encrypt_data, decrypt_data, decrypt_payload, kdf_header, split_kdf_header, name_header,
+    split_name_header, digest_header, write_sparse, write_destination
+};
+
+/// Decrypted data, and the SHA-256 of the original if the file stores it
+type Decrypted = (Zeroizing<Vec<u8>>, Option<[u8; 32]>);
+
+impl EncryptionBackend for LocalBackend {
+    fn encrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+        encrypt_data(data, key)
+    }
+    
+    fn decrypt_data(&self, data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+        decrypt_data(data, key)
+    }
+    
+    fn encrypt_file(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        self.encrypt_file_with_name(source_path, dest_path, key, None, progress_callback)
+    }
+    
+    fn encrypt_file_storing_name(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        name: &OsStr,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        let name = file_names::stored_name_bytes(name);
+        self.encrypt_file_with_name(source_path, dest_path, key, Some(&name), progress_callback)
+    }
+    
+    fn decrypt_file(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        // Check if the destination file already exists
+        if dest_path.exists() {
+            return Err(EncryptionError::Io(
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+            ));
+        }
+
+        // Files from age tools, or encrypted by CRUSTy in the age format
+        if age_format::is_age_file(source_path) {
+            return age_format::decrypt_file(source_path, dest_path, key, progress_callback);
+        }
+        
+        // Other formats are decrypted whole
+        self.check_memory_limit(source_path)?;
+        
+        // Files from GnuPG, or encrypted by CRUSTy in the OpenPGP format
+        if openpgp::is_openpgp_file(source_path) {
+            return openpgp::decrypt_file(source_path, dest_path, key, progress_callback);
+        }
+        
+        let (decrypted_data, digest) = self.decrypt_crusty(source_path, key, &progress_callback)?;
+        
+        // Write the decrypted data to the destination file (the second half of the progress)
+        write_destination(dest_path, |dest_file| {
+            write_sparse(dest_file, &decrypted_data, |p| progress_callback(0.5 + p * 0.5))
+        })?;
+        
+        // Files that store the hash of their original are checked again as written, so
+        // a failing output drive is caught rather than leaving a bad copy
+        if let Some(digest) = digest {
+            let written = Zeroizing::new(fs::read(dest_path)?);
+            if Sha256::digest(&written[..]).as_slice() != digest {
+                let _ = fs::remove_file(dest_path);
+                return Err(EncryptionError::Corrupted(format!(
+                    "{} did not match the original once written; the output drive may be failing", dest_path.display()
+                )));
+            }
+        }
+        
+        // Final progress update
+        progress_callback(1.0);
+        
+        Ok(())
+    }
+    
+    fn verify_file(
+        &self,
+        source_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        // age files are checked chunk by chunk, without holding their contents
+        if age_format::is_age_file(source_path) {
+            age_format::verify(source_path, key)?;
+        } else {
+            // Other formats have a single tag over the whole file; the decrypted data is
+            // wiped from memory as soon as it has been checked
+            self.check_memory_limit(source_path)?;
+            self.decrypt_to_memory(source_path, key, &progress_callback)?;
+        }
+        
+        progress_callback(1.0);
+        Ok(())
+    }
+    
+    fn encrypt_stream(
+        &self,
+        mut reader: impl Read,
+        mut writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        // age encrypts chunk by chunk as the data arrives
+        if let Some(recipients) = &self.age_recipients {
+            return age_format::encrypt_stream(reader, writer, key, recipients);
+        }
+        
+        // Other formats have a single tag over all the data, so it is read first
+        let mut data = Zeroizing::new(Vec::new());
+        reader.read_to_end(&mut data)?;
+        let encrypted_data = match &self.openpgp_recipients {
+            Some(recipients) => openpgp::encrypt_message(&data, "", key, recipients, |_| {})?,
+            None => self.encrypt_crusty(&data, key, None)?,
+        };
+        writer.write_all(&encrypted_data)?;
+        writer.flush()?;
+        Ok(())
+    }
+    
+    fn decrypt_stream(
+        &self,
+        mut reader: impl Read,
+        mut writer: impl Write,
+        key: &EncryptionKey,
+    ) -> Result<(), EncryptionError> {
+        // The format is told by the start of the data, which is then read again
+        let mut start = Vec::with_capacity(64);
+        reader.by_ref().take(64).read_to_end(&mut start)?;
+        let mut reader = start.as_slice().chain(reader);
+        if age_format::is_age_data(&start) {
+            return age_format::decrypt_stream(reader, writer, key);
+        }
+        
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let decrypted_data = if openpgp::is_openpgp_data(&data) {
+            openpgp::decrypt_message(&data, key, |_| {})?
+        } else {
+            // Damage is repaired with the parity, but not logged: there is no file on
+            // failing media to copy
+            let (data, _) = parity::remove_parity(data)?;
+            self.open_crusty(&data, key)?.0
+        };
+        writer.write_all(&decrypted_data)?;
+        writer.flush()?;
+        Ok(())
+    }
+    
+    fn encrypt_files(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        hide_names: bool,
+        progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+    ) -> Result<Vec<String>, EncryptionError> {
+        let mut results = Vec::new();
+        
+        for (i, &source_path) in source_paths.iter().enumerate() {
+            let source_name = source_path.file_name()
+                .ok_or_else(|| EncryptionError::Io(
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid source path")
+                ))?;
+            let file_name = if hide_names {
+                file_names::opaque_encrypted_name()
+            } else {
+                file_names::encrypted_name(source_path).unwrap_or_default()
+            };
+                
+            let mut dest_path = dest_dir.to_path_buf();
+            dest_path.push(file_name);
+            
+            let progress_cb = {
+                let cb = progress_callback.clone();
+                let idx = i;
+                move |p: f32| cb(idx, p)
+            };
+            
+            let result = if hide_names {
+                self.encrypt_file_storing_name(source_path, &dest_path, key, source_name, progress_cb)
+            } else {
+                self.encrypt_file(source_path, &dest_path, key, progress_cb)
+            };
+            
+            match result {
+                Ok(_) if hide_names => results.push(format!(
+                    "Successfully encrypted: {} (saved as {})", source_path.display(), dest_path.display()
+                )),
+                Ok(_) => results.push(format!("Successfully encrypted: {}", source_path.display())),
+                // Nothing is left at the destination: it is only written when complete
+                Err(e) => results.push(format!("Failed to encrypt {}: {}", source_path.display(), e)),
+            }
+        }
+        
+        Ok(results)
+    }
+    
+    fn decrypt_files(
+        &self,
+        source_paths: &[&Path],
+        dest_dir: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+    ) -> Result<Vec<String>, EncryptionError> {
+        let mut results = Vec::new();
+        
+        for (i, &source_path) in source_paths.iter().enumerate() {
+            // Uses the stored name or removes the .encrypted extension, and makes the
+            // name valid on this platform
+            let output_name = file_names::restored_name(source_path, key)
+                .ok_or_else(|| EncryptionError::Io(
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid source path")
+                ))?;
+                
+            let mut dest_path = dest_dir.to_path_buf();
+            dest_path.push(&output_name.name);
+            
+            let progress_cb = {
+                let cb = progress_callback.clone();
+                let idx = i;
+                move |p: f32| cb(idx, p)
+            };
+            
+            match self.decrypt_file(source_path, &dest_path, key, progress_cb) {
+                Ok(_) => match &output_name.renamed {
+                    Some(reason) => results.push(format!(
+                        "Successfully decrypted: {} (saved as {} because {})",
+                        source_path.display(), dest_path.display(), reason
+                    )),
+                    None => results.push(format!("Successfully decrypted: {}", source_path.display())),
+                },
+                Err(e) => {
+                    // Provide a more specific error message for authentication failures
+                    let error_msg = if e.to_string().contains("Authentication failed") || 
+                                      e.to_string().contains("authentication") || 
+                                      e.to_string().contains("tag mismatch") {
+                        format!("Failed to decrypt {}: Wrong encryption key used. Please try a different key.", source_path.display())
+                    } else {
+                        format!("Failed to decrypt {}: {}", source_path.display(), e)
+                    };
+                    
+                    results.push(error_msg);
+                },
+            }
+        }
+        
+        Ok(results)
+    }
+    
+}
+
+impl LocalBackend {
+    /// Decrypts a file into memory, without writing the plaintext anywhere.
+    ///
+    /// Reads every format `decrypt_file` does: age files, OpenPGP messages, and CRUSTy
+    /// files (repaired with their parity, and opened for one of several recipients).
+    pub fn decrypt_to_memory(
+        &self,
+        source_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32),
+    ) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+        self.check_memory_limit(source_path)?;
+        if age_format::is_age_file(source_path) {
+            let data = age_format::decrypt(source_path, key)?;
+            progress_callback(0.5);
+            return Ok(data);
+        }
+        if openpgp::is_openpgp_file(source_path) {
+            return openpgp::decrypt(source_path, key, progress_callback);
+        }
+        Ok(self.decrypt_crusty(source_path, key, progress_callback)?.0)
+    }
+    
+    /// Checks that a file in a format processed whole fits in the memory limit, with
+    /// its encrypted or decrypted copy.
+    fn check_memory_limit(&self, path: &Path) -> Result<(), EncryptionError> {
+        match self.memory_limit {
+            Some(limit) if fs::metadata(path)?.len().saturating_mul(2) > limit => {
+                Err(EncryptionError::TooLarge(path.to_path_buf(), limit / (1024 * 1024)))
+            },
+            _ => Ok(()),
+        }
+    }
+    
+    /// Decrypts a CRUSTy file into memory, checking it against the hash of its original
+    /// if the file stores one (returned with the data).
+    fn decrypt_crusty(
+        &self,
+        source_path: &Path,
+        key: &EncryptionKey,
+        progress_callback: impl Fn(f32),
+    ) -> Result<Decrypted, EncryptionError> {
+        // Read the entire file into memory, repairing it if it has parity (the first half
+        // of the progress)
+        let buffer = parity::read_encrypted_file(source_path, |p| progress_callback(p * 0.5))?;
+        
+        // Update progress to indicate file read is complete
+        progress_callback(0.5);
+        
+        self.open_crusty(&buffer, key)
+    }
+    
+    /// Decrypts CRUSTy data (without parity), checking it against the hash of its
+    /// original if it stores one (returned with the data).
+    fn open_crusty(&self, buffer: &[u8], key: &EncryptionKey) -> Result<Decrypted, EncryptionError> {
+        // Files for several recipients are encrypted with their own key, wrapped for each
+        let (recipient_header, ciphertext) = recipients::split_recipient_header(buffer)?;
+        let file_key = match recipient_header {
+            Some(header) => header.open(key)?.1,
+            None => key.clone(),
+        };
+        
+        // Decrypt the data (the KDF header is only needed to derive the key again)
+        let (_, ciphertext) = split_kdf_header(ciphertext)?;
+        let (_, ciphertext) = split_name_header(ciphertext)?;
+        let (data, digest) = decrypt_payload(ciphertext, &file_key, |payload| self.decrypt_data(payload, &file_key))?;
+        Ok((Zeroizing::new(data), digest))
+    }
+    
+    /// Encrypts a file, storing its original name in the header if one is given.
+    fn encrypt_file_with_name(
+        &self,
+        source_path: &Path,
+        dest_path: &Path,
+        key: &EncryptionKey,
+        stored_name: Option<&[u8]>,
+        progress_callback: impl Fn(f32) + Send + 'static,
+    ) -> Result<(), EncryptionError> {
+        // Check if the destination file already exists
+        if dest_path.exists() {
+            return Err(EncryptionError::Io(
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+            ));
+        }
+
+        // age files have no header to store the name in
+        if let Some(recipients) = &self.age_recipients {
+            if stored_name.is_some() {
+                return Err(EncryptionError::Encryption("File names cannot be hidden in age files".to_string()));
+            }
+            return age_format::encrypt_file(source_path, dest_path, key, recipients, progress_callback);
+        }
+        if let Some(recipients) = &self.openpgp_recipients {
+            if stored_name.is_some() {
+                return Err(EncryptionError::Encryption("File names cannot be hidden in OpenPGP messages".to_string()));
+            }
+            self.check_memory_limit(source_path)?;
+            return openpgp::encrypt_file(source_path, dest_path, key, recipients, progress_callback);
+        }
+        // Names are read back with the recipient's key, which cannot open the file's own
+        if self.recipients.is_some() && stored_name.is_some() {
+            return Err(EncryptionError::Encryption("File names cannot be hidden in files for several recipients".to_string()));
+        }
+        self.check_memory_limit(source_path)?;
+
+        // Read the entire file into memory, in chunks sized to the drive
+        let buffer = chunk_tuning::read_file(source_path, |p| progress_callback(p * 0.5))?;
+        
+        // Update progress to indicate file read is complete
+        progress_callback(0.5);
+        
+        let encrypted_data = self.encrypt_crusty(&buffer, key, stored_name)?;
+        
+        // Write the encrypted data to the destination file, in chunks (the second half of
+        // the progress)
+        write_destination(dest_path, |dest_file| {
+            chunk_tuning::write_all(dest_file, &encrypted_data, |p| progress_callback(0.5 + p * 0.5))
+        })?;
+        
+        // Final progress update
+        progress_callback(1.0);
+        
+        Ok(())
+    }
+    
+    /// Encrypts data in the CRUSTy format, with the headers and parity chosen.
+    fn encrypt_crusty(
+        &self,
+        buffer: &[u8],
+        key: &EncryptionKey,
+        stored_name: Option<&[u8]>,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        // Encrypt the data, after the file's key wrapped for each recipient (for several
+        // recipients) or the KDF header for passphrase keys, and the stored name
+        let (mut encrypted_data, file_key) = match &self.recipients {
+            Some(recipients) => recipients::recipient_header(key, recipients)?,
+            None => (kdf_header(key), key.clone()),
+        };
+        if let Some(name) = stored_name {
+            encrypted_data.extend_from_slice(&name_header(name, key)?);
+        }
+        if self.add_digest {
+            encrypted_data.extend_from_slice(&digest_header(buffer, &file_key)?);
+        }
+        encrypted_data.extend_from_slice(&self.encrypt_data(buffer, &file_key)?);
+        if self.add_parity {
+            parity::append_parity(&mut encrypted_data);
+        }
+        Ok(encrypted_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_stream_round_trip() {
+        let key = EncryptionKey::generate();
+        let data = b"nightly backup ".repeat(10_000);
+        let backends = [
+            LocalBackend { add_parity: true, add_digest: true, ..LocalBackend::default() },
+            LocalBackend { age_recipients: Some(Vec::new()), ..LocalBackend::default() },
+            LocalBackend { openpgp_recipients: Some(Vec::new()), ..LocalBackend::default() },
+        ];
+
+        for backend in &backends {
+            let mut encrypted = Vec::new();
+            backend.encrypt_stream(&data[..], &mut encrypted, &key).unwrap();
+            assert_ne!(encrypted, data);
+
+            // Every format is read back by any backend, and only with the key
+            let mut decrypted = Vec::new();
+            LocalBackend::default().decrypt_stream(&encrypted[..], &mut decrypted, &key).unwrap();
+            assert_eq!(decrypted, data);
+            assert!(backend.decrypt_stream(&encrypted[..], &mut Vec::new(), &EncryptionKey::generate()).is_err());
+        }
+
+        // Streams and files are the same format
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("backup.tar");
+        let encrypted_path = dir.path().join("backup.tar.encrypted");
+        fs::write(&source, &data).unwrap();
+        backends[0].encrypt_file(&source, &encrypted_path, &key, |_| {}).unwrap();
+        let mut decrypted = Vec::new();
+        backends[0].decrypt_stream(File::open(&encrypted_path).unwrap(), &mut decrypted, &key).unwrap();
+        assert_eq!(decrypted, data);
+
+        let mut encrypted = Vec::new();
+        backends[0].encrypt_stream(&data[..], &mut encrypted, &key).unwrap();
+        let streamed_path = dir.path().join("streamed.encrypted");
+        fs::write(&streamed_path, &encrypted).unwrap();
+        assert_eq!(&backends[0].decrypt_to_memory(&streamed_path, &key, |_| {}).unwrap()[..], &data[..]);
+    }
+
+    #[test]
+    fn test_verify_file() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let source = dir.path().join("backup.tar");
+        fs::write(&source, b"nightly backup ".repeat(10_000)).unwrap();
+        let backends = [
+            LocalBackend::default(),
+            LocalBackend { add_parity: true, add_digest: true, ..LocalBackend::default() },
+            LocalBackend { age_recipients: Some(Vec::new()), ..LocalBackend::default() },
+        ];
+
+        for (i, backend) in backends.iter().enumerate() {
+            let encrypted = dir.path().join(format!("backup{}.encrypted", i));
+            backend.encrypt_file(&source, &encrypted, &key, |_| {}).unwrap();
+
+            // Any backend verifies any format, reporting when it is done
+            let done = Arc::new(Mutex::new(0.0));
+            let progress = done.clone();
+            LocalBackend::default().verify_file(&encrypted, &key, move |p| *progress.lock().unwrap() = p).unwrap();
+            assert_eq!(*done.lock().unwrap(), 1.0);
+            assert!(LocalBackend::default().verify_file(&encrypted, &EncryptionKey::generate(), |_| {}).is_err());
+
+            // A changed byte fails the tag, unless the parity repairs it
+            let mut damaged = fs::read(&encrypted).unwrap();
+            let middle = damaged.len() / 2;
+            damaged[middle] ^= 1;
+            let damaged_path = dir.path().join(format!("damaged{}.encrypted", i));
+            fs::write(&damaged_path, &damaged).unwrap();
+            assert_eq!(backend.verify_file(&damaged_path, &key, |_| {}).is_ok(), backend.add_parity);
+        }
+
+        // Nothing decrypted was written
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1 + 2 * backends.len());
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let small = dir.path().join("small.txt");
+        let large = dir.path().join("large.bin");
+        fs::write(&small, b"fits").unwrap();
+        fs::write(&large, vec![3u8; 600 * 1024]).unwrap();
+        let limited = LocalBackend { memory_limit: Some(1024 * 1024), ..LocalBackend::default() };
+
+        // Files processed whole must fit with their copy
+        limited.encrypt_file(&small, &dir.path().join("small.txt.encrypted"), &key, |_| {}).unwrap();
+        let result = limited.encrypt_file(&large, &dir.path().join("large.bin.encrypted"), &key, |_| {});
+        assert!(matches!(result, Err(EncryptionError::TooLarge(path, 1)) if path == large));
+        let encrypted = dir.path().join("unlimited.encrypted");
+        LocalBackend::default().encrypt_file(&large, &encrypted, &key, |_| {}).unwrap();
+        assert!(matches!(limited.decrypt_file(&encrypted, &dir.path().join("out.bin"), &key, |_| {}), Err(EncryptionError::TooLarge(..))));
+
+        // age files are processed in chunks, whatever their size
+        let age_backend = LocalBackend { age_recipients: Some(Vec::new()), ..limited };
+        let encrypted = dir.path().join("large.bin.age");
+        age_backend.encrypt_file(&large, &encrypted, &key, |_| {}).unwrap();
+        let decrypted = dir.path().join("large copy.bin");
+        age_backend.decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), fs::read(&large).unwrap());
+
+        // A damaged age file leaves nothing behind
+        let mut damaged = fs::read(&encrypted).unwrap();
+        let last = damaged.len() - 1;
+        damaged[last] ^= 1;
+        fs::write(&encrypted, &damaged).unwrap();
+        let result = age_backend.decrypt_file(&encrypted, &dir.path().join("damaged.bin"), &key, |_| {});
+        assert!(matches!(result, Err(EncryptionError::Decryption(message)) if message.contains("damaged")));
+        assert!(!dir.path().join("damaged.bin").exists());
+    }
+}
@@ -0,0 +1,144 @@
+/// Backend benchmark module.
+///
+/// This module provides functionality for:
+/// - Encrypting synthetic (random) buffers of a given size through a backend, for a
+///   short time
+/// - Reporting the throughput (MB/s) and the latency (time for one buffer) measured
+///
+/// Small buffers show the fixed cost of each call (for a device, the round trip), and
+/// large buffers the speed of the encryption itself, so a backend is measured at several
+/// sizes. Nothing is written to disk.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use rand::RngCore;
+
+use crate::backend::Backend;
+use crate::encryption::{EncryptionError, EncryptionKey};
+
+/// Buffer sizes offered for a benchmark: 4 KiB, 64 KiB, 1 MiB, and 16 MiB
+pub const SIZES: [usize; 4] = [4 * 1024, 64 * 1024, 1024 * 1024, 16 * 1024 * 1024];
+
+/// Time spent measuring each size (a size is always encrypted at least once)
+pub const TIME_PER_SIZE: Duration = Duration::from_secs(1);
+
+/// Most times a buffer is encrypted for one size
+const MAX_ROUNDS: u32 = 200;
+
+/// Result of benchmarking a backend at one buffer size
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    /// Size of the buffer encrypted, in bytes
+    pub size: usize,
+    /// Number of times the buffer was encrypted
+    pub rounds: u32,
+    /// Median time to encrypt the buffer once
+    pub latency: Duration,
+    /// Total time spent encrypting
+    pub elapsed: Duration,
+}
+
+impl BenchmarkResult {
+    /// Get the throughput in megabytes (10^6 bytes) per second
+    pub fn megabytes_per_sec(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        (self.size as f64 * self.rounds as f64) / seconds / 1_000_000.0
+    }
+}
+
+/// Encrypt a random buffer through a backend again and again, and measure it
+///
+/// # Arguments
+/// * `backend` - Backend to measure (an embedded backend must be connected)
+/// * `key` - Key to encrypt with
+/// * `size` - Size of the buffer, in bytes
+/// * `budget` - Time to keep encrypting for
+/// * `stop` - Set to stop after the current round
+///
+/// # Returns
+/// * `Result<BenchmarkResult, EncryptionError>` - What was measured, or the error of the
+///   first round that failed
+pub fn measure(
+    backend: &Backend,
+    key: &EncryptionKey,
+    size: usize,
+    budget: Duration,
+    stop: &AtomicBool,
+) -> Result<BenchmarkResult, EncryptionError> {
+    let mut buffer = vec![0u8; size];
+    rand::thread_rng().fill_bytes(&mut buffer);
+
+    let mut times = Vec::new();
+    let started = Instant::now();
+    loop {
+        let round = Instant::now();
+        backend.encrypt_data(&buffer, key)?;
+        times.push(round.elapsed());
+        if times.len() as u32 >= MAX_ROUNDS || started.elapsed() >= budget || stop.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+
+    let elapsed = times.iter().sum();
+    times.sort();
+    Ok(BenchmarkResult {
+        size,
+        rounds: times.len() as u32,
+        latency: times[times.len() / 2],
+        elapsed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use crate::backend::{BackendFactory, ConnectionSettings, ConnectionType, EmbeddedConfig};
+    use crate::embedded_protocol::tests::SimulatedDevice;
+
+    #[test]
+    fn test_measure_backends() {
+        let key = EncryptionKey::generate();
+        let stop = AtomicBool::new(false);
+
+        let local = BackendFactory::create_local();
+        let result = measure(&local, &key, SIZES[0], Duration::from_millis(50), &stop).unwrap();
+        assert_eq!(result.size, SIZES[0]);
+        assert!(result.rounds >= 1 && result.rounds <= MAX_ROUNDS);
+        assert!(result.latency <= result.elapsed);
+        assert!(result.megabytes_per_sec() > 0.0);
+
+        // Stopping still measures one round
+        stop.store(true, Ordering::Relaxed);
+        let result = measure(&local, &key, SIZES[1], Duration::from_secs(60), &stop).unwrap();
+        assert_eq!(result.rounds, 1);
+
+        // A device that is not connected fails the measurement
+        let config = EmbeddedConfig {
+            device_id: "crusty-01".to_string(),
+            connection: ConnectionSettings::default_for(&ConnectionType::Usb),
+        };
+        let mut embedded = BackendFactory::create_embedded(config, Arc::new(Mutex::new(None)));
+        assert!(matches!(measure(&embedded, &key, SIZES[0], Duration::ZERO, &stop), Err(EncryptionError::Device(_))));
+
+        if let Backend::Embedded(backend) = &mut embedded {
+            backend.link = Some(Mutex::new(Box::new(SimulatedDevice::new())));
+        }
+        let result = measure(&embedded, &key, SIZES[1], Duration::ZERO, &stop).unwrap();
+        assert_eq!((result.size, result.rounds), (SIZES[1], 1));
+    }
+
+    #[test]
+    fn test_megabytes_per_sec() {
+        let result = BenchmarkResult {
+            size: 1_000_000,
+            rounds: 4,
+            latency: Duration::from_millis(500),
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(result.megabytes_per_sec(), 2.0);
+        assert_eq!(BenchmarkResult { elapsed: Duration::ZERO, ..result }.megabytes_per_sec(), 0.0);
+    }
+}
@@ -0,0 +1,294 @@
+/// Chunk size tuning module.
+///
+/// This module provides functionality for:
+/// - Reading files in chunks whose size is picked from the throughput measured during
+///   the first seconds of the read, so slow hard drives and fast NVMe drives are both
+///   read efficiently without any setting
+/// - Copying from a reader to a writer in tuned chunks, holding one chunk at a time
+/// - Writing data held in memory in tuned chunks
+/// - Reporting progress as each chunk is read or written, so progress bars move
+///   smoothly through large files
+///
+/// The CRUSTy format has a single tag over the whole file, and the age format fixes its
+/// chunks at 64 KiB, so what is tuned is the size of the reads from and writes to disk,
+/// between `MIN_CHUNK_SIZE` and `MAX_CHUNK_SIZE`. Encrypted files are the same whatever size
+/// is picked. Programs can also fix the size with `set_fixed_chunk_size`.
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+/// Smallest chunk read at a time
+pub const MIN_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest chunk read at a time
+pub const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Size of the first chunk
+const START_CHUNK_SIZE: usize = 256 * 1024;
+
+/// How long chunk sizes are tried before one is kept for the rest of the file
+const TUNING_PERIOD: Duration = Duration::from_secs(2);
+
+/// Longest a chunk may take to read, so progress keeps moving on slow drives
+const MAX_READ_TIME: Duration = Duration::from_millis(250);
+
+/// Gain in throughput that makes a larger chunk worth keeping
+const MIN_GAIN: f64 = 1.1;
+
+/// Size every chunk is read at (0 to tune the size)
+static FIXED_CHUNK_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Read every chunk at a fixed size instead of tuning it, or tune it again with None
+///
+/// The size is kept between `MIN_CHUNK_SIZE` and `MAX_CHUNK_SIZE`. Applies to reads
+/// started afterwards, in every thread.
+pub fn set_fixed_chunk_size(size: Option<usize>) {
+    let size = size.map_or(0, |size| size.clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE));
+    FIXED_CHUNK_SIZE.store(size, Ordering::Relaxed);
+}
+
+/// Picks the size of each chunk from the throughput of the chunks read before it
+///
+/// Chunks double in size while that makes reading at least 10% faster. Once a larger
+/// chunk is no faster, takes longer than `MAX_READ_TIME`, or the tuning period is over,
+/// the best size is kept.
+#[derive(Debug)]
+pub struct ChunkTuner {
+    size: usize,
+    /// Bytes per second of the best size so far
+    best_rate: f64,
+    settled: bool,
+}
+
+impl ChunkTuner {
+    /// Create a tuner starting at the default size, or keeping the size set with
+    /// `set_fixed_chunk_size`
+    pub fn new() -> Self {
+        match FIXED_CHUNK_SIZE.load(Ordering::Relaxed) {
+            0 => ChunkTuner { size: START_CHUNK_SIZE, best_rate: 0.0, settled: false },
+            size => Self::fixed(size),
+        }
+    }
+
+    /// Create a tuner that keeps one size
+    pub fn fixed(size: usize) -> Self {
+        ChunkTuner { size: size.clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE), best_rate: 0.0, settled: true }
+    }
+
+    /// Get the size of the next chunk to read
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Check whether a size has been kept for the rest of the file
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// Record a chunk that was read, picking the size of the next one
+    ///
+    /// # Arguments
+    /// * `bytes` - The bytes read (fewer than the size at the end of the file)
+    /// * `elapsed` - How long the chunk took to read
+    /// * `since_start` - How long the file has been read for
+    pub fn record(&mut self, bytes: usize, elapsed: Duration, since_start: Duration) {
+        if self.settled {
+            return;
+        }
+        if since_start >= TUNING_PERIOD {
+            self.settled = true;
+            return;
+        }
+        if elapsed > MAX_READ_TIME && self.size > MIN_CHUNK_SIZE {
+            self.size /= 2;
+            self.settled = true;
+            return;
+        }
+        // The last chunk of a file is short, and says nothing about the size
+        if bytes < self.size {
+            return;
+        }
+
+        let rate = bytes as f64 / elapsed.as_secs_f64().max(1e-9);
+        if rate >= self.best_rate * MIN_GAIN {
+            self.best_rate = rate;
+            if self.size * 2 <= MAX_CHUNK_SIZE {
+                self.size *= 2;
+            } else {
+                self.settled = true;
+            }
+        } else {
+            // Back to the size before, which was as fast
+            self.size /= 2;
+            self.settled = true;
+        }
+    }
+}
+
+impl Default for ChunkTuner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Read a whole file in tuned chunks
+///
+/// # Arguments
+/// * `path` - The file to read
+/// * `progress_callback` - Called with the fraction read after each chunk
+pub fn read_file(path: &Path, progress_callback: impl Fn(f32)) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut data = Vec::with_capacity(len as usize);
+
+    let mut tuner = ChunkTuner::new();
+    let started = Instant::now();
+    loop {
+        let read_started = Instant::now();
+        let read = (&mut file).take(tuner.size() as u64).read_to_end(&mut data)?;
+        if read == 0 {
+            break;
+        }
+        tuner.record(read, read_started.elapsed(), started.elapsed());
+        if len > 0 {
+            progress_callback((data.len() as f64 / len as f64).min(1.0) as f32);
+        }
+    }
+
+    Ok(data)
+}
+
+/// Copy everything read from a reader to a writer in tuned chunks
+///
+/// # Arguments
+/// * `len` - The number of bytes expected, for progress (0 if not known)
+/// * `progress_callback` - Called with the fraction copied after each chunk
+///
+/// # Returns
+/// * `io::Result<u64>` - The number of bytes copied
+pub fn copy(mut reader: impl Read, mut writer: impl Write, len: u64, progress_callback: impl Fn(f32)) -> io::Result<u64> {
+    let mut chunk = Zeroizing::new(Vec::new());
+    let mut copied = 0;
+
+    let mut tuner = ChunkTuner::new();
+    let started = Instant::now();
+    loop {
+        chunk.clear();
+        let read_started = Instant::now();
+        let read = (&mut reader).take(tuner.size() as u64).read_to_end(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        tuner.record(read, read_started.elapsed(), started.elapsed());
+        writer.write_all(&chunk)?;
+        copied += read as u64;
+        if len > 0 {
+            progress_callback((copied as f64 / len as f64).min(1.0) as f32);
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Write data to a writer in tuned chunks
+///
+/// # Arguments
+/// * `progress_callback` - Called with the fraction written after each chunk
+pub fn write_all(mut writer: impl Write, data: &[u8], progress_callback: impl Fn(f32)) -> io::Result<()> {
+    let mut written = 0;
+
+    let mut tuner = ChunkTuner::new();
+    let started = Instant::now();
+    while written < data.len() {
+        let chunk = &data[written..data.len().min(written + tuner.size())];
+        let write_started = Instant::now();
+        writer.write_all(chunk)?;
+        tuner.record(chunk.len(), write_started.elapsed(), started.elapsed());
+        written += chunk.len();
+        progress_callback(written as f32 / data.len() as f32);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_chunk_sizes() {
+        let ms = Duration::from_millis;
+
+        // A fast drive with a cost per read: sizes double while throughput grows, and
+        // stop at the largest
+        let mut tuner = ChunkTuner::new();
+        while !tuner.is_settled() {
+            let size = tuner.size();
+            tuner.record(size, ms(1) + Duration::from_micros((size / 4096) as u64), ms(100));
+        }
+        assert_eq!(tuner.size(), MAX_CHUNK_SIZE);
+
+        // A drive that is no faster with larger chunks keeps the size before
+        let mut tuner = ChunkTuner::new();
+        tuner.record(START_CHUNK_SIZE, ms(10), ms(10));
+        tuner.record(START_CHUNK_SIZE * 2, ms(20), ms(30));
+        assert!(tuner.is_settled());
+        assert_eq!(tuner.size(), START_CHUNK_SIZE);
+
+        // A slow drive takes smaller chunks, so progress keeps moving
+        let mut tuner = ChunkTuner::new();
+        tuner.record(START_CHUNK_SIZE, ms(400), ms(400));
+        assert_eq!(tuner.size(), START_CHUNK_SIZE / 2);
+
+        // The size is kept once the tuning period is over, and the end of a file is ignored
+        let mut tuner = ChunkTuner::new();
+        tuner.record(100, ms(1), ms(1));
+        assert!(!tuner.is_settled());
+        tuner.record(START_CHUNK_SIZE, ms(1), TUNING_PERIOD);
+        assert!(tuner.is_settled());
+        assert_eq!(tuner.size(), START_CHUNK_SIZE);
+
+        // A fixed size is kept whatever the throughput
+        let mut tuner = ChunkTuner::fixed(1024 * 1024);
+        tuner.record(1024 * 1024, ms(400), ms(400));
+        assert_eq!(tuner.size(), 1024 * 1024);
+        assert_eq!(ChunkTuner::fixed(1).size(), MIN_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_read_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        let data: Vec<u8> = (0..START_CHUNK_SIZE * 5 + 123).map(|i| i as u8).collect();
+        fs::write(&path, &data).unwrap();
+
+        let reports = std::cell::RefCell::new(Vec::new());
+        assert_eq!(read_file(&path, |p| reports.borrow_mut().push(p)).unwrap(), data);
+        let reports = reports.into_inner();
+        assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(reports.last(), Some(&1.0));
+
+        fs::write(&path, b"").unwrap();
+        assert!(read_file(&path, |_| {}).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_write_all() {
+        let data: Vec<u8> = (0..START_CHUNK_SIZE * 3 + 7).map(|i| i as u8).collect();
+        let mut written = Vec::new();
+        let reports = std::cell::RefCell::new(Vec::new());
+        write_all(&mut written, &data, |p| reports.borrow_mut().push(p)).unwrap();
+        assert_eq!(written, data);
+
+        // Progress is reported after each chunk, not only at the end
+        let reports = reports.into_inner();
+        assert!(reports.len() > 1);
+        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reports.last(), Some(&1.0));
+    }
+}
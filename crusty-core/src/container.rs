@@ -0,0 +1,534 @@
+/// CRUSTy container format core.
+///
+/// This module provides functionality for:
+/// - Encryption keys: random and passphrase (Argon2id) keys, Base64, and fingerprints
+/// - The headers of an encrypted file: the KDF parameters of a passphrase key, the
+///   encrypted original name, and the encrypted SHA-256 of the original data
+/// - Encrypting and decrypting data with AES-256-GCM
+///
+/// The module works on bytes in memory only: it does no file I/O, and every function
+/// that needs randomness takes the random number generator to use (any `rand` generator
+/// suitable for cryptography). It uses pure Rust crypto crates only and nothing from
+/// the rest of CRUSTy, so the same format implementation can be built for wasm32
+/// (without the `native` feature) and used where files are not on disk, such as a
+/// browser-based receiver with a generator backed by `crypto.getRandomValues`.
+/// `encryption` adds the operating system's generator and file handling on top.
+///
+/// Layout of a container: the KDF header (passphrase keys only), the name header (only
+/// when the original name is stored), the digest header (only when the hash of the
+/// original is stored), then the payload (see `encrypt_data`).
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Key, Nonce
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use hkdf::Hkdf;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Error type for encryption operations
+///
+/// Shared with `encryption`, whose file operations also report I/O errors and
+/// unusable output folders.
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    /// Error during encryption
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    /// Error during decryption
+    #[error("Decryption error: {0}")]
+    Decryption(String),
+
+    /// Error with the encryption key
+    #[error("Key error: {0}")]
+    KeyError(String),
+
+    /// The output folder cannot be written to
+    #[error("The output folder {} is read-only or you do not have permission to write to it. Choose another output folder.", .0.display())]
+    ReadOnlyDestination(PathBuf),
+
+    /// The output folder does not exist
+    #[error("The output folder {} does not exist. Choose another output folder.", .0.display())]
+    MissingDestination(PathBuf),
+
+    /// The file opens with the key, but its data was damaged or changed
+    #[error("The file is damaged: {0}")]
+    Corrupted(String),
+
+    /// The file's format is processed whole in memory, and the file is too large for
+    /// the memory limit (in MB)
+    #[error("{} is too large to process within the memory limit of {} MB. Only age files are processed in chunks: encrypt large files in the age format, or turn off low-memory mode.", .0.display(), .1)]
+    TooLarge(PathBuf, u64),
+
+    /// The embedded device could not be reached, or its link failed
+    #[error("Embedded device error: {0}")]
+    Device(String),
+
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Marks a file encrypted with a passphrase key (followed by the KDF parameters)
+pub const KDF_MAGIC: &[u8; 8] = b"CRUSTYPK";
+
+/// Version of the KDF header
+const KDF_HEADER_VERSION: u8 = 1;
+
+/// KDF algorithm id for Argon2id (version 0x13)
+const KDF_ARGON2ID: u8 = 1;
+
+/// Length of the KDF header: magic, version, algorithm, memory, iterations, parallelism, salt
+pub const KDF_HEADER_LEN: usize = 8 + 1 + 1 + 4 + 4 + 4 + 16;
+
+/// Marks a file that stores its original name (followed by the encrypted name)
+const NAME_MAGIC: &[u8; 8] = b"CRUSTYFN";
+
+/// Longest original name stored in a file header, in bytes
+pub const MAX_STORED_NAME_LEN: usize = 4096;
+
+/// Length of the name header around the name: magic, nonce, length, authentication tag
+pub const NAME_HEADER_OVERHEAD: usize = 8 + 12 + 4 + 16;
+
+/// Marks a file that stores the SHA-256 of its original data (followed by the encrypted hash)
+const DIGEST_MAGIC: &[u8; 8] = b"CRUSTYSH";
+
+/// Length of the digest header: magic, nonce, length, SHA-256, authentication tag
+pub const DIGEST_HEADER_LEN: usize = 8 + 12 + 4 + 32 + 16;
+
+/// Largest Argon2id memory cost accepted from a file header (4 GB), so a crafted
+/// header cannot make decryption exhaust memory
+const MAX_KDF_MEMORY_KIB: u32 = 4 * 1024 * 1024;
+
+/// Largest Argon2id iteration count accepted from a file header
+const MAX_KDF_ITERATIONS: u32 = 64;
+
+/// Argon2id parameters for deriving a key from a passphrase
+///
+/// The parameters (including the salt) are not secret; they are written to the header
+/// of every file encrypted with the derived key, so the key can be derived again from
+/// the passphrase alone.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over the memory
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+    /// Random salt
+    pub salt: [u8; 16],
+}
+
+impl KdfParams {
+    /// Generate parameters for a new passphrase key (64 MB, 3 passes), with a salt from
+    /// the given generator
+    pub fn generate_with(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut salt = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        KdfParams {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+            salt,
+        }
+    }
+}
+
+/// Represents an AES-256-GCM encryption key
+#[derive(Clone)]
+pub struct EncryptionKey {
+    /// The raw key bytes
+    pub key: [u8; 32],
+    /// How the key was derived from a passphrase (None for random keys)
+    pub kdf: Option<KdfParams>,
+}
+
+impl EncryptionKey {
+    /// Generate a new random encryption key from the given generator
+    pub fn generate_with(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut key = [0u8; 32];
+        rng.fill_bytes(&mut key);
+        EncryptionKey { key, kdf: None }
+    }
+
+    /// Derive a key from a passphrase with Argon2id
+    ///
+    /// # Arguments
+    /// * `passphrase` - The passphrase
+    /// * `params` - The Argon2id parameters (`KdfParams::generate` for a new key, or the
+    ///   parameters read from an encrypted file to derive its key again)
+    pub fn from_passphrase(passphrase: &str, params: &KdfParams) -> Result<Self, EncryptionError> {
+        if passphrase.is_empty() {
+            return Err(EncryptionError::KeyError("Passphrase cannot be empty".to_string()));
+        }
+
+        let argon2_params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+            .map_err(|e| EncryptionError::KeyError(format!("Invalid key derivation parameters: {}", e)))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params)
+            .hash_password_into(passphrase.as_bytes(), &params.salt, &mut key)
+            .map_err(|e| EncryptionError::KeyError(format!("Key derivation failed: {}", e)))?;
+
+        Ok(EncryptionKey { key, kdf: Some(params.clone()) })
+    }
+
+    /// Convert the key to a Base64 string for storage
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.key)
+    }
+
+    /// Get the fingerprint of the key, to check that two people hold the same key
+    ///
+    /// The first 8 bytes of the SHA-256 of the key (with a CRUSTy context string),
+    /// in hexadecimal groups of four, e.g. `3F2A-91BC-0D4E-77A1`. The fingerprint
+    /// reveals nothing usable about the key.
+    pub fn fingerprint(&self) -> String {
+        let digest = Sha256::new()
+            .chain_update(b"CRUSTy key fingerprint")
+            .chain_update(self.key)
+            .finalize();
+        digest[..8].chunks(2)
+            .map(|pair| format!("{:02X}{:02X}", pair[0], pair[1]))
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Create a key from a Base64 string
+    pub fn from_base64(base64: &str) -> Result<Self, EncryptionError> {
+        let key_bytes = STANDARD.decode(base64.as_bytes())
+            .map_err(|e| EncryptionError::KeyError(format!("Invalid Base64 encoding: {}", e)))?;
+
+        if key_bytes.len() != 32 {
+            return Err(EncryptionError::KeyError(
+                format!("Invalid key length: expected 32 bytes, got {}", key_bytes.len())
+            ));
+        }
+
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&key_bytes);
+
+        Ok(EncryptionKey { key, kdf: None })
+    }
+
+    /// Derive the key a file's content key is wrapped with for a recipient's email address
+    ///
+    /// The address is normalised (trimmed and lower-cased), so the same key is derived
+    /// however it is typed. Anyone holding this key can derive the recipient's key, so the
+    /// recipient still needs this key: the address only labels whom the wrapped key is for.
+    pub fn for_recipient(&self, recipient_email: &str) -> Result<EncryptionKey, EncryptionError> {
+        let normalized_email = recipient_email.trim().to_lowercase();
+        let (username, domain) = normalized_email.split_once('@')
+            .filter(|(username, domain)| !username.is_empty() && !domain.is_empty())
+            .ok_or_else(|| EncryptionError::KeyError(format!("'{}' is not an email address", recipient_email.trim())))?;
+
+        let email_material = Sha256::new()
+            .chain_update(username.as_bytes())
+            .chain_update(b":")
+            .chain_update(domain.as_bytes())
+            .chain_update(b":")
+            .chain_update(b"CRUSTy-Email-Key-Derivation-Salt-v1")
+            .finalize();
+
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(&email_material), &self.key)
+            .expand(b"encryption", &mut key)
+            .expect("32 bytes is a valid HKDF output length");
+        Ok(EncryptionKey { key, kdf: None })
+    }
+}
+
+/// Get the header written before the ciphertext of a file encrypted with the key
+///
+/// Files encrypted with a passphrase key start with the KDF parameters; other files
+/// have no header.
+pub fn kdf_header(key: &EncryptionKey) -> Vec<u8> {
+    match &key.kdf {
+        Some(params) => kdf_params_header(params),
+        None => Vec::new(),
+    }
+}
+
+/// Get the KDF header holding key derivation parameters
+pub fn kdf_params_header(params: &KdfParams) -> Vec<u8> {
+    let mut header = Vec::with_capacity(KDF_HEADER_LEN);
+    header.extend_from_slice(KDF_MAGIC);
+    header.push(KDF_HEADER_VERSION);
+    header.push(KDF_ARGON2ID);
+    header.extend_from_slice(&params.memory_kib.to_be_bytes());
+    header.extend_from_slice(&params.iterations.to_be_bytes());
+    header.extend_from_slice(&params.parallelism.to_be_bytes());
+    header.extend_from_slice(&params.salt);
+    header
+}
+
+/// Split the KDF header from the contents of an encrypted file
+///
+/// # Returns
+/// * `Result<(Option<KdfParams>, &[u8]), EncryptionError>` - The KDF parameters (None if
+///   the file was not encrypted with a passphrase key) and the encrypted data
+pub fn split_kdf_header(data: &[u8]) -> Result<(Option<KdfParams>, &[u8]), EncryptionError> {
+    if !data.starts_with(KDF_MAGIC) {
+        return Ok((None, data));
+    }
+    if data.len() < KDF_HEADER_LEN {
+        return Err(EncryptionError::Decryption("Truncated key derivation header".to_string()));
+    }
+    if data[8] != KDF_HEADER_VERSION || data[9] != KDF_ARGON2ID {
+        return Err(EncryptionError::Decryption(
+            format!("Unsupported key derivation (version {}, algorithm {})", data[8], data[9])
+        ));
+    }
+
+    let read_u32 = |offset: usize| u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+    let mut salt = [0u8; 16];
+    salt.copy_from_slice(&data[22..KDF_HEADER_LEN]);
+    let params = KdfParams {
+        memory_kib: read_u32(10),
+        iterations: read_u32(14),
+        parallelism: read_u32(18),
+        salt,
+    };
+
+    if params.memory_kib > MAX_KDF_MEMORY_KIB || params.iterations > MAX_KDF_ITERATIONS {
+        return Err(EncryptionError::Decryption("Key derivation parameters are out of range".to_string()));
+    }
+
+    Ok((Some(params), &data[KDF_HEADER_LEN..]))
+}
+
+/// Get the header that stores the original name of a file, encrypted with the key
+///
+/// The header follows the KDF header (if any), so the name is only readable with the key.
+pub fn name_header(name: &[u8], key: &EncryptionKey, rng: &mut (impl RngCore + CryptoRng)) -> Result<Vec<u8>, EncryptionError> {
+    if name.is_empty() || name.len() > MAX_STORED_NAME_LEN {
+        return Err(EncryptionError::Encryption(
+            format!("File names must be 1 to {} bytes long to be stored", MAX_STORED_NAME_LEN)
+        ));
+    }
+
+    let mut header = NAME_MAGIC.to_vec();
+    header.extend_from_slice(&encrypt_data(name, key, rng)?);
+    Ok(header)
+}
+
+/// Split the name header from encrypted data (after the KDF header)
+///
+/// # Returns
+/// * `Result<(Option<&[u8]>, &[u8]), EncryptionError>` - The encrypted name (None if the
+///   file does not store its name) and the encrypted data
+pub fn split_name_header(data: &[u8]) -> Result<(Option<&[u8]>, &[u8]), EncryptionError> {
+    if !data.starts_with(NAME_MAGIC) {
+        return Ok((None, data));
+    }
+    if data.len() < NAME_HEADER_OVERHEAD {
+        return Err(EncryptionError::Decryption("Truncated file name header".to_string()));
+    }
+
+    let name_len = u32::from_be_bytes([data[20], data[21], data[22], data[23]]) as usize;
+    if name_len > MAX_STORED_NAME_LEN + 16 || data.len() < 24 + name_len {
+        return Err(EncryptionError::Decryption("Invalid file name header".to_string()));
+    }
+
+    Ok((Some(&data[8..24 + name_len]), &data[24 + name_len..]))
+}
+
+/// Get the header that stores the SHA-256 of a file's original data, encrypted with the key
+///
+/// The header follows the name header (if any). The hash is encrypted, so it cannot be
+/// used to confirm a guess of the contents without the key.
+pub fn digest_header(data: &[u8], key: &EncryptionKey, rng: &mut (impl RngCore + CryptoRng)) -> Result<Vec<u8>, EncryptionError> {
+    let mut header = DIGEST_MAGIC.to_vec();
+    header.extend_from_slice(&encrypt_data(&Sha256::digest(data), key, rng)?);
+    Ok(header)
+}
+
+/// Split the digest header from encrypted data (after the name header)
+///
+/// # Returns
+/// * `Result<(Option<&[u8]>, &[u8]), EncryptionError>` - The encrypted hash (None if the
+///   file does not store it) and the encrypted data
+pub fn split_digest_header(data: &[u8]) -> Result<(Option<&[u8]>, &[u8]), EncryptionError> {
+    if !data.starts_with(DIGEST_MAGIC) {
+        return Ok((None, data));
+    }
+    if data.len() < DIGEST_HEADER_LEN {
+        return Err(EncryptionError::Decryption("Truncated digest header".to_string()));
+    }
+    Ok((Some(&data[8..DIGEST_HEADER_LEN]), &data[DIGEST_HEADER_LEN..]))
+}
+
+/// Decrypt the data of a container (after the name header), checking it against the
+/// hash of the original if the container stores one
+///
+/// When the stored hash opens with the key, a payload that then fails is reported as
+/// `Corrupted` rather than as a wrong key.
+///
+/// # Arguments
+/// * `data` - The encrypted data, after the name header
+/// * `key` - The key to open the stored hash with
+/// * `decrypt` - Decrypts the payload with the same key (`decrypt_data`, or a backend's
+///   own implementation)
+///
+/// # Returns
+/// * `Result<(Vec<u8>, Option<[u8; 32]>), EncryptionError>` - The original data, and its
+///   SHA-256 if the container stores it (to check the data again once written)
+pub fn decrypt_payload(
+    data: &[u8],
+    key: &EncryptionKey,
+    decrypt: impl FnOnce(&[u8]) -> Result<Vec<u8>, EncryptionError>,
+) -> Result<(Vec<u8>, Option<[u8; 32]>), EncryptionError> {
+    let (digest, payload) = split_digest_header(data)?;
+    let Some(digest) = digest else {
+        return Ok((decrypt(payload)?, None));
+    };
+    let digest: [u8; 32] = decrypt_data(digest, key)?.try_into()
+        .map_err(|_| EncryptionError::Decryption("Invalid digest header".to_string()))?;
+
+    let data = decrypt(payload)
+        .map_err(|_| EncryptionError::Corrupted("the encrypted data was changed or damaged after it was written".to_string()))?;
+    if Sha256::digest(&data).as_slice() != digest {
+        return Err(EncryptionError::Corrupted("the decrypted data does not match the hash of the original".to_string()));
+    }
+    Ok((data, Some(digest)))
+}
+
+/// Encrypt raw data using AES-256-GCM, with a nonce from the given generator
+///
+/// Format: nonce (12 bytes) + ciphertext length (4 bytes) + ciphertext
+pub fn encrypt_data(data: &[u8], key: &EncryptionKey, rng: &mut (impl RngCore + CryptoRng)) -> Result<Vec<u8>, EncryptionError> {
+    // Generate a random nonce
+    let mut nonce_bytes = [0u8; 12];
+    rng.fill_bytes(&mut nonce_bytes);
+    encrypt_data_with_nonce(data, key, &nonce_bytes)
+}
+
+/// Encrypt raw data using AES-256-GCM with a given nonce
+///
+/// Encrypting the same data with the same key and nonce always gives the same result,
+/// which lets an audit reproduce a file's ciphertext from its original. A nonce must
+/// never be used for different data with the same key: new data is encrypted with
+/// `encrypt_data`.
+pub fn encrypt_data_with_nonce(data: &[u8], key: &EncryptionKey, nonce_bytes: &[u8; 12]) -> Result<Vec<u8>, EncryptionError> {
+    // Create the cipher
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.key));
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    // Encrypt the data
+    let ciphertext = cipher.encrypt(nonce, data)
+        .map_err(|e| EncryptionError::Encryption(format!("Encryption failed: {}", e)))?;
+
+    let mut result = Vec::with_capacity(12 + 4 + ciphertext.len());
+    result.extend_from_slice(nonce_bytes);
+    result.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+    result.extend_from_slice(&ciphertext);
+
+    Ok(result)
+}
+
+/// Decrypt raw data using AES-256-GCM
+pub fn decrypt_data(data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+    if data.len() < 16 {
+        return Err(EncryptionError::Decryption("Data too short".to_string()));
+    }
+
+    // Extract the nonce
+    let nonce = Nonce::from_slice(&data[0..12]);
+
+    // Extract the ciphertext length
+    let ciphertext_len = u32::from_be_bytes([data[12], data[13], data[14], data[15]]) as usize;
+
+    // Verify the data length
+    if data.len() < 16 + ciphertext_len {
+        return Err(EncryptionError::Decryption("Invalid data length".to_string()));
+    }
+
+    // Extract the ciphertext
+    let ciphertext = &data[16..16 + ciphertext_len];
+
+    // Create the cipher and decrypt the data
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.key));
+    cipher.decrypt(nonce, ciphertext)
+        .map_err(|e| EncryptionError::Decryption(format!("Authentication failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Encrypt data into a container as the file backends do
+    fn encrypt_container(data: &[u8], key: &EncryptionKey, name: Option<&[u8]>, rng: &mut StdRng) -> Vec<u8> {
+        let mut container = kdf_header(key);
+        if let Some(name) = name {
+            container.extend_from_slice(&name_header(name, key, rng).unwrap());
+        }
+        container.extend_from_slice(&encrypt_data(data, key, rng).unwrap());
+        container
+    }
+
+    #[test]
+    fn test_container_with_seeded_rng() {
+        // A deterministic generator stands in for one from another platform
+        let mut rng = StdRng::seed_from_u64(7);
+        let key = EncryptionKey::generate_with(&mut rng);
+        let container = encrypt_container(b"quarterly figures", &key, Some(b"figures.xlsx"), &mut rng);
+
+        let (kdf, rest) = split_kdf_header(&container).unwrap();
+        assert!(kdf.is_none());
+        let (name, payload) = split_name_header(rest).unwrap();
+        assert_eq!(decrypt_data(name.unwrap(), &key).unwrap(), b"figures.xlsx");
+        assert_eq!(decrypt_data(payload, &key).unwrap(), b"quarterly figures");
+        assert!(matches!(decrypt_data(payload, &EncryptionKey::generate_with(&mut rng)), Err(EncryptionError::Decryption(_))));
+
+        // The same seed gives the same container
+        let mut rng = StdRng::seed_from_u64(7);
+        let key = EncryptionKey::generate_with(&mut rng);
+        assert_eq!(encrypt_container(b"quarterly figures", &key, Some(b"figures.xlsx"), &mut rng), container);
+    }
+
+    #[test]
+    fn test_passphrase_container() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let params = KdfParams { memory_kib: 1024, iterations: 1, ..KdfParams::generate_with(&mut rng) };
+        let key = EncryptionKey::from_passphrase("correct horse", &params).unwrap();
+        let container = encrypt_container(b"notes", &key, None, &mut rng);
+
+        // The header gives back the parameters to derive the key again
+        let (kdf, payload) = split_kdf_header(&container).unwrap();
+        let derived = EncryptionKey::from_passphrase("correct horse", &kdf.unwrap()).unwrap();
+        assert_eq!(decrypt_data(payload, &derived).unwrap(), b"notes");
+    }
+
+    #[test]
+    fn test_digest_header() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let key = EncryptionKey::generate_with(&mut rng);
+        let mut container = digest_header(b"invoice 1042", &key, &mut rng).unwrap();
+        assert_eq!(container.len(), DIGEST_HEADER_LEN);
+        container.extend_from_slice(&encrypt_data(b"invoice 1042", &key, &mut rng).unwrap());
+
+        let open = |key: &EncryptionKey| decrypt_payload(&container, key, |payload| decrypt_data(payload, key));
+        let (data, digest) = open(&key).unwrap();
+        assert_eq!(data, b"invoice 1042");
+        assert_eq!(digest.unwrap().as_slice(), Sha256::digest(b"invoice 1042").as_slice());
+
+        // Another key cannot open the hash either: that is a wrong key, not damage
+        let other = EncryptionKey::generate_with(&mut rng);
+        assert!(matches!(open(&other), Err(EncryptionError::Decryption(_))));
+
+        // Damage after the header is told apart from a wrong key
+        *container.last_mut().unwrap() ^= 1;
+        assert!(matches!(decrypt_payload(&container, &key, |payload| decrypt_data(payload, &key)), Err(EncryptionError::Corrupted(_))));
+
+        // Containers without the header decrypt as before
+        let plain = encrypt_data(b"memo", &key, &mut rng).unwrap();
+        assert_eq!(decrypt_payload(&plain, &key, |payload| decrypt_data(payload, &key)).unwrap(), (b"memo".to_vec(), None));
+    }
+}
@@ -0,0 +1,484 @@
+/// Embedded device protocol module.
+///
+/// This module provides functionality for:
+/// - The messages exchanged with the embedded device for one job (encrypting or
+///   decrypting one piece of data)
+/// - Running a job over a device link, reporting the device's progress as it goes
+/// - Checking that the device answers (`Ping`), and asking what it runs and has free
+///   (`Info`)
+///
+/// Every message is one frame: a kind byte, the payload length (4 bytes, big-endian),
+/// and the payload. How frames are carried (USB, serial, Ethernet) is up to the link.
+///
+/// A job starts with `Start`, then sends the data in chunks of at most `CHUNK_SIZE`
+/// bytes. The device answers every chunk with a `Progress` message (chunks processed,
+/// and chunks still queued on the device), may send `Output` at any time, and ends the
+/// job with `Done` or `Failed`.
+///
+/// Between jobs, `Ping` is answered with `Pong` (with the same payload), and `Info` with
+/// the device's firmware version, the algorithms it supports, and its free memory,
+/// key slots, and chunk queue.
+use std::io;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+use crate::encryption::EncryptionKey;
+
+/// Largest chunk of data sent to the device at once
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Length of a frame header (kind and payload length)
+const HEADER_LEN: usize = 5;
+
+/// Largest payload accepted in a frame from the device (a longer length means the
+/// stream of bytes is out of step)
+const MAX_PAYLOAD_LEN: usize = 4 * CHUNK_SIZE;
+
+/// Frame kinds sent by CRUSTy
+const KIND_START: u8 = 0x01;
+const KIND_CHUNK: u8 = 0x02;
+const KIND_PING: u8 = 0x03;
+const KIND_INFO: u8 = 0x04;
+
+/// Frame kinds sent by the device
+const KIND_PROGRESS: u8 = 0x81;
+const KIND_OUTPUT: u8 = 0x82;
+const KIND_DONE: u8 = 0x83;
+const KIND_FAILED: u8 = 0x84;
+const KIND_PONG: u8 = 0x85;
+const KIND_INFO_REPLY: u8 = 0x86;
+
+/// Error type for the device protocol
+#[derive(Debug, Error)]
+pub enum ProtocolError {
+    /// The link to the device failed
+    #[error("Device link error: {0}")]
+    Link(#[from] io::Error),
+
+    /// The device sent a frame that could not be read
+    #[error("Invalid message from the device: {0}")]
+    InvalidMessage(String),
+
+    /// The device reported that the job failed
+    #[error("The device failed the job: {0}")]
+    JobFailed(String),
+}
+
+/// What a job does with its data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobOperation {
+    Encrypt,
+    Decrypt,
+}
+
+/// Progress of a job on the device
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DeviceProgress {
+    /// Chunks the device has processed
+    pub chunks_done: u32,
+    /// Chunks in the job
+    pub chunks_total: u32,
+    /// Chunks received but not yet processed by the device
+    pub queue_depth: u16,
+}
+
+impl DeviceProgress {
+    /// Get the progress as a fraction (0.0 to 1.0)
+    pub fn fraction(&self) -> f32 {
+        if self.chunks_total == 0 {
+            return 0.0;
+        }
+        (self.chunks_done as f32 / self.chunks_total as f32).min(1.0)
+    }
+}
+
+/// What the device runs and has free, as it reports it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceInfo {
+    /// Firmware version (e.g. "1.4.2")
+    pub firmware_version: String,
+    /// Algorithms the device can run (e.g. "AES-256-GCM")
+    pub algorithms: Vec<String>,
+    /// Free memory, in bytes
+    pub free_memory: u32,
+    /// Key slots not in use
+    pub free_key_slots: u16,
+    /// Chunks the device can hold in its queue
+    pub queue_capacity: u16,
+}
+
+impl DeviceInfo {
+    /// Read the payload of an `Info` reply: the firmware version, the number of
+    /// algorithms and each one (every string is its length as one byte, then UTF-8),
+    /// then the free memory (4 bytes), free key slots and queue capacity (2 bytes each)
+    fn parse(payload: &[u8]) -> Option<Self> {
+        fn take<'a>(rest: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+            let (taken, remaining) = rest.split_at_checked(len)?;
+            *rest = remaining;
+            Some(taken)
+        }
+        fn take_string(rest: &mut &[u8]) -> Option<String> {
+            let len = take(rest, 1)?[0] as usize;
+            Some(String::from_utf8_lossy(take(rest, len)?).into_owned())
+        }
+
+        let mut rest = payload;
+        let firmware_version = take_string(&mut rest)?;
+        let count = take(&mut rest, 1)?[0];
+        let algorithms = (0..count).map(|_| take_string(&mut rest)).collect::<Option<Vec<_>>>()?;
+        let resources = take(&mut rest, 8)?;
+        Some(DeviceInfo {
+            firmware_version,
+            algorithms,
+            free_memory: u32::from_be_bytes([resources[0], resources[1], resources[2], resources[3]]),
+            free_key_slots: u16::from_be_bytes([resources[4], resources[5]]),
+            queue_capacity: u16::from_be_bytes([resources[6], resources[7]]),
+        })
+    }
+}
+
+/// Result of a health check of the device
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceHealth {
+    /// Time the device took to answer a ping
+    pub round_trip: Duration,
+    pub info: DeviceInfo,
+}
+
+/// A message from the device
+#[derive(Debug, PartialEq)]
+enum DeviceMessage {
+    Progress(DeviceProgress),
+    Output(Vec<u8>),
+    Done,
+    Failed(String),
+    Pong(Vec<u8>),
+    Info(DeviceInfo),
+}
+
+/// Connection to the device that carries whole frames
+pub trait DeviceLink: Send {
+    /// Send one frame to the device
+    fn send(&mut self, frame: &[u8]) -> io::Result<()>;
+
+    /// Wait for the next frame from the device
+    fn receive(&mut self) -> io::Result<Vec<u8>>;
+}
+
+/// Frames received over a link that carries a stream of bytes, where a frame may
+/// arrive in pieces and several frames may arrive together
+#[derive(Debug, Default)]
+pub struct FrameBuffer {
+    received: Vec<u8>,
+}
+
+impl FrameBuffer {
+    /// Add bytes received from the device
+    pub fn push(&mut self, data: &[u8]) {
+        self.received.extend_from_slice(data);
+    }
+
+    /// Take the next complete frame
+    ///
+    /// # Returns
+    /// * `io::Result<Option<Vec<u8>>>` - The frame, None if more bytes are needed, or an
+    ///   error if the frame is longer than any the device sends
+    pub fn next_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.received.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        let length = u32::from_be_bytes([self.received[1], self.received[2], self.received[3], self.received[4]]) as usize;
+        if length > MAX_PAYLOAD_LEN {
+            self.received.clear();
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("frame of {} bytes is too long", length)));
+        }
+        if self.received.len() < HEADER_LEN + length {
+            return Ok(None);
+        }
+        Ok(Some(self.received.drain(..HEADER_LEN + length).collect()))
+    }
+}
+
+/// Build a frame
+fn frame(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.push(kind);
+    frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Build the frame that starts a job
+fn start_frame(operation: JobOperation, key: &EncryptionKey, data_len: usize, chunks_total: u32) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(45);
+    payload.push(match operation {
+        JobOperation::Encrypt => 1,
+        JobOperation::Decrypt => 2,
+    });
+    payload.extend_from_slice(&key.key);
+    payload.extend_from_slice(&(data_len as u64).to_be_bytes());
+    payload.extend_from_slice(&chunks_total.to_be_bytes());
+    frame(KIND_START, &payload)
+}
+
+/// Read a frame from the device
+fn parse_device_message(frame: &[u8]) -> Result<DeviceMessage, ProtocolError> {
+    if frame.len() < HEADER_LEN {
+        return Err(ProtocolError::InvalidMessage("frame too short".to_string()));
+    }
+    let (header, payload) = frame.split_at(HEADER_LEN);
+    let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if payload.len() != length {
+        return Err(ProtocolError::InvalidMessage(format!("frame length {} does not match its payload ({})", length, payload.len())));
+    }
+
+    match header[0] {
+        KIND_PROGRESS if payload.len() == 10 => Ok(DeviceMessage::Progress(DeviceProgress {
+            chunks_done: u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]),
+            chunks_total: u32::from_be_bytes([payload[4], payload[5], payload[6], payload[7]]),
+            queue_depth: u16::from_be_bytes([payload[8], payload[9]]),
+        })),
+        KIND_OUTPUT => Ok(DeviceMessage::Output(payload.to_vec())),
+        KIND_DONE => Ok(DeviceMessage::Done),
+        KIND_FAILED => Ok(DeviceMessage::Failed(String::from_utf8_lossy(payload).into_owned())),
+        KIND_PONG => Ok(DeviceMessage::Pong(payload.to_vec())),
+        KIND_INFO_REPLY => DeviceInfo::parse(payload)
+            .map(DeviceMessage::Info)
+            .ok_or_else(|| ProtocolError::InvalidMessage(format!("device information could not be read ({} bytes)", payload.len()))),
+        kind => Err(ProtocolError::InvalidMessage(format!("unexpected frame kind 0x{:02x} ({} bytes)", kind, payload.len()))),
+    }
+}
+
+/// Run a job on the device
+///
+/// # Arguments
+/// * `link` - Connection to the device
+/// * `operation` - Whether the device encrypts or decrypts the data
+/// * `key` - Key the device uses for the job
+/// * `data` - Data to encrypt or decrypt
+/// * `on_progress` - Called with the device's progress after every chunk
+///
+/// # Returns
+/// * `Result<Vec<u8>, ProtocolError>` - The data sent back by the device, or an error
+pub fn run_job(
+    link: &mut dyn DeviceLink,
+    operation: JobOperation,
+    key: &EncryptionKey,
+    data: &[u8],
+    mut on_progress: impl FnMut(DeviceProgress),
+) -> Result<Vec<u8>, ProtocolError> {
+    let chunks: Vec<&[u8]> = data.chunks(CHUNK_SIZE).collect();
+    link.send(&start_frame(operation, key, data.len(), chunks.len() as u32))?;
+
+    let mut output = Vec::new();
+    for chunk in chunks {
+        link.send(&frame(KIND_CHUNK, chunk))?;
+
+        // Each chunk is answered with the device's progress
+        loop {
+            match parse_device_message(&link.receive()?)? {
+                DeviceMessage::Progress(progress) => {
+                    on_progress(progress);
+                    break;
+                },
+                DeviceMessage::Output(data) => output.extend(data),
+                DeviceMessage::Done => return Err(ProtocolError::InvalidMessage("job ended before all data was sent".to_string())),
+                DeviceMessage::Failed(message) => return Err(ProtocolError::JobFailed(message)),
+                DeviceMessage::Pong(_) | DeviceMessage::Info(_) => return Err(unexpected_reply()),
+            }
+        }
+    }
+
+    loop {
+        match parse_device_message(&link.receive()?)? {
+            DeviceMessage::Progress(progress) => on_progress(progress),
+            DeviceMessage::Output(data) => output.extend(data),
+            DeviceMessage::Done => return Ok(output),
+            DeviceMessage::Failed(message) => return Err(ProtocolError::JobFailed(message)),
+            DeviceMessage::Pong(_) | DeviceMessage::Info(_) => return Err(unexpected_reply()),
+        }
+    }
+}
+
+/// Error for an answer to a command that was not sent
+fn unexpected_reply() -> ProtocolError {
+    ProtocolError::InvalidMessage("answer to a command that was not sent".to_string())
+}
+
+/// Check that the device answers
+///
+/// # Returns
+/// * `Result<Duration, ProtocolError>` - The time the device took to answer, or an error
+pub fn ping(link: &mut dyn DeviceLink) -> Result<Duration, ProtocolError> {
+    // The device sends back what it was sent, so an old answer is not taken for this one
+    let nonce: [u8; 8] = rand::random();
+    let sent = Instant::now();
+    link.send(&frame(KIND_PING, &nonce))?;
+    match parse_device_message(&link.receive()?)? {
+        DeviceMessage::Pong(payload) if payload == nonce => Ok(sent.elapsed()),
+        DeviceMessage::Pong(_) => Err(ProtocolError::InvalidMessage("the answer to the ping does not match it".to_string())),
+        DeviceMessage::Failed(message) => Err(ProtocolError::JobFailed(message)),
+        _ => Err(ProtocolError::InvalidMessage("the device did not answer the ping".to_string())),
+    }
+}
+
+/// Ask the device what it runs and has free
+///
+/// # Returns
+/// * `Result<DeviceInfo, ProtocolError>` - What the device reports, or an error
+pub fn query_info(link: &mut dyn DeviceLink) -> Result<DeviceInfo, ProtocolError> {
+    link.send(&frame(KIND_INFO, &[]))?;
+    match parse_device_message(&link.receive()?)? {
+        DeviceMessage::Info(info) => Ok(info),
+        DeviceMessage::Failed(message) => Err(ProtocolError::JobFailed(message)),
+        _ => Err(ProtocolError::InvalidMessage("the device did not send its information".to_string())),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Device stand-in that "encrypts" by inverting every byte, one chunk at a time
+    pub struct SimulatedDevice {
+        /// Frames waiting to be received by CRUSTy
+        outbox: VecDeque<Vec<u8>>,
+        chunks_total: u32,
+        chunks_done: u32,
+        /// Fail the job after this many chunks
+        pub fail_after: Option<u32>,
+    }
+
+    impl SimulatedDevice {
+        pub fn new() -> Self {
+            SimulatedDevice { outbox: VecDeque::new(), chunks_total: 0, chunks_done: 0, fail_after: None }
+        }
+
+        fn progress(&self, queue_depth: u16) -> Vec<u8> {
+            let mut payload = self.chunks_done.to_be_bytes().to_vec();
+            payload.extend_from_slice(&self.chunks_total.to_be_bytes());
+            payload.extend_from_slice(&queue_depth.to_be_bytes());
+            frame(KIND_PROGRESS, &payload)
+        }
+    }
+
+    impl DeviceLink for SimulatedDevice {
+        fn send(&mut self, frame_data: &[u8]) -> io::Result<()> {
+            let payload = &frame_data[HEADER_LEN..];
+            match frame_data[0] {
+                KIND_START => {
+                    self.chunks_total = u32::from_be_bytes(payload[41..45].try_into().unwrap());
+                    self.chunks_done = 0;
+                },
+                KIND_CHUNK => {
+                    if self.fail_after == Some(self.chunks_done) {
+                        self.outbox.push_back(frame(KIND_FAILED, b"key slot locked"));
+                        return Ok(());
+                    }
+                    // Report the chunk as queued, then processed
+                    self.outbox.push_back(self.progress(1));
+                    self.chunks_done += 1;
+                    let inverted: Vec<u8> = payload.iter().map(|b| !b).collect();
+                    self.outbox.push_back(frame(KIND_OUTPUT, &inverted));
+                    self.outbox.push_back(self.progress(0));
+                    if self.chunks_done == self.chunks_total {
+                        self.outbox.push_back(frame(KIND_DONE, &[]));
+                    }
+                },
+                KIND_PING => self.outbox.push_back(frame(KIND_PONG, payload)),
+                KIND_INFO => self.outbox.push_back(frame(KIND_INFO_REPLY, &info_payload())),
+                kind => panic!("unexpected frame kind {}", kind),
+            }
+            Ok(())
+        }
+
+        fn receive(&mut self) -> io::Result<Vec<u8>> {
+            self.outbox.pop_front()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "no frame from the device"))
+        }
+    }
+
+    /// Information the simulated device reports
+    fn info_payload() -> Vec<u8> {
+        let mut payload = vec![5];
+        payload.extend_from_slice(b"1.4.2");
+        payload.push(2);
+        for algorithm in ["AES-256-GCM", "ChaCha20-Poly1305"] {
+            payload.push(algorithm.len() as u8);
+            payload.extend_from_slice(algorithm.as_bytes());
+        }
+        payload.extend_from_slice(&(384 * 1024u32).to_be_bytes());
+        payload.extend_from_slice(&6u16.to_be_bytes());
+        payload.extend_from_slice(&8u16.to_be_bytes());
+        payload
+    }
+
+    #[test]
+    fn test_job_progress() {
+        let mut device = SimulatedDevice::new();
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 100).map(|i| i as u8).collect();
+
+        let mut reports = Vec::new();
+        let output = run_job(&mut device, JobOperation::Encrypt, &EncryptionKey::generate(), &data, |progress| reports.push(progress)).unwrap();
+
+        assert_eq!(output, data.iter().map(|b| !b).collect::<Vec<u8>>());
+        assert_eq!(reports.first(), Some(&DeviceProgress { chunks_done: 0, chunks_total: 3, queue_depth: 1 }));
+        assert_eq!(reports.last(), Some(&DeviceProgress { chunks_done: 3, chunks_total: 3, queue_depth: 0 }));
+        assert!(reports.windows(2).all(|pair| pair[0].fraction() <= pair[1].fraction()));
+        assert_eq!(reports.last().unwrap().fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_job_failure() {
+        let mut device = SimulatedDevice::new();
+        device.fail_after = Some(1);
+        let data = vec![0u8; CHUNK_SIZE * 3];
+
+        let result = run_job(&mut device, JobOperation::Decrypt, &EncryptionKey::generate(), &data, |_| {});
+        assert!(matches!(result, Err(ProtocolError::JobFailed(message)) if message == "key slot locked"));
+
+        assert!(matches!(parse_device_message(&[KIND_DONE, 0, 0, 0, 9]), Err(ProtocolError::InvalidMessage(_))));
+        assert!(matches!(parse_device_message(&[0x7f, 0, 0, 0, 0]), Err(ProtocolError::InvalidMessage(_))));
+    }
+
+    #[test]
+    fn test_ping_and_info() {
+        let mut device = SimulatedDevice::new();
+        assert!(ping(&mut device).is_ok());
+        assert_eq!(query_info(&mut device).unwrap(), DeviceInfo {
+            firmware_version: "1.4.2".to_string(),
+            algorithms: vec!["AES-256-GCM".to_string(), "ChaCha20-Poly1305".to_string()],
+            free_memory: 384 * 1024,
+            free_key_slots: 6,
+            queue_capacity: 8,
+        });
+
+        // Cut short, the information cannot be read
+        let payload = info_payload();
+        assert_eq!(DeviceInfo::parse(&payload[..payload.len() - 1]), None);
+        assert_eq!(DeviceInfo::parse(&[9, b'1']), None);
+    }
+
+    #[test]
+    fn test_frame_buffer() {
+        let mut buffer = FrameBuffer::default();
+        let output = frame(KIND_OUTPUT, b"ciphertext");
+        let done = frame(KIND_DONE, &[]);
+
+        // A frame split across reads is taken once it is complete
+        buffer.push(&output[..3]);
+        assert_eq!(buffer.next_frame().unwrap(), None);
+        buffer.push(&output[3..8]);
+        assert_eq!(buffer.next_frame().unwrap(), None);
+
+        // Frames received together are taken one at a time
+        buffer.push(&[&output[8..], done.as_slice()].concat());
+        assert_eq!(buffer.next_frame().unwrap(), Some(output));
+        assert_eq!(buffer.next_frame().unwrap(), Some(done));
+        assert_eq!(buffer.next_frame().unwrap(), None);
+
+        buffer.push(&[KIND_OUTPUT, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(buffer.next_frame().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,394 @@
+/// Embedded device serial link module.
+///
+/// This module provides functionality for:
+/// - Opening the device's serial port (the device ID is the port, e.g. `/dev/ttyACM0`
+///   or `COM3`) with the configured baud rate and parity
+/// - Carrying protocol frames in COBS-framed packets checked with a CRC32
+/// - Sending a packet again when it arrives damaged or is not acknowledged
+///
+/// Every packet is a kind byte, a sequence number, the frame, and the CRC32 of all
+/// three (big-endian), COBS-encoded and ended with a zero byte. A `Data` packet carries
+/// a frame and is answered with `Ack` (with its sequence number) once it is read
+/// intact, or `Nak` if it arrives damaged, which asks for the last `Data` packet again.
+/// A `Data` packet received twice (its `Ack` was lost) is acknowledged again, but its
+/// frame is only taken once.
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::backend::{Parity, SerialSettings};
+use crate::embedded_protocol::DeviceLink;
+
+/// Packet kinds
+const PACKET_DATA: u8 = 0x00;
+const PACKET_ACK: u8 = 0x01;
+const PACKET_NAK: u8 = 0x02;
+
+/// Length of a packet around its frame (kind, sequence number, and CRC32)
+const PACKET_OVERHEAD: usize = 6;
+
+/// Largest packet accepted, COBS-encoded (longer means the delimiter was lost)
+const MAX_ENCODED_LEN: usize = 512 * 1024;
+
+/// Time allowed for the device to acknowledge a packet before it is sent again
+const ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Times a packet is sent again before the link is given up on
+const MAX_RETRANSMITS: u32 = 3;
+
+/// Time allowed for the device to answer (it may be busy with queued chunks)
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a read of the port waits for bytes, between checks of the timeouts
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A packet read intact
+#[derive(Debug, PartialEq)]
+enum Packet {
+    Data(u8, Vec<u8>),
+    Ack(u8),
+    Nak,
+}
+
+/// COBS-encode data, so it contains no zero byte
+fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len() + data.len() / 254 + 2);
+    let mut code_index = 0;
+    let mut code = 1u8;
+    encoded.push(0);
+    for &byte in data {
+        if byte != 0 {
+            encoded.push(byte);
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            encoded[code_index] = code;
+            code_index = encoded.len();
+            encoded.push(0);
+            code = 1;
+        }
+    }
+    encoded[code_index] = code;
+    encoded
+}
+
+/// Decode COBS-encoded data (None if it is not valid COBS)
+fn cobs_decode(encoded: &[u8]) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(encoded.len());
+    let mut i = 0;
+    while i < encoded.len() {
+        let code = encoded[i] as usize;
+        if code == 0 || i + code > encoded.len() {
+            return None;
+        }
+        data.extend_from_slice(&encoded[i + 1..i + code]);
+        i += code;
+        if code < 0xFF && i < encoded.len() {
+            data.push(0);
+        }
+    }
+    Some(data)
+}
+
+/// Build a packet, ready to write (with its delimiter)
+fn encode_packet(kind: u8, seq: u8, frame: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(frame.len() + PACKET_OVERHEAD);
+    packet.push(kind);
+    packet.push(seq);
+    packet.extend_from_slice(frame);
+    packet.extend_from_slice(&crc32fast::hash(&packet).to_be_bytes());
+    let mut encoded = cobs_encode(&packet);
+    encoded.push(0);
+    encoded
+}
+
+/// Read a packet (without its delimiter)
+///
+/// # Returns
+/// * `Option<Packet>` - The packet, or None if it was damaged
+fn decode_packet(encoded: &[u8]) -> Option<Packet> {
+    let packet = cobs_decode(encoded)?;
+    if packet.len() < PACKET_OVERHEAD {
+        return None;
+    }
+    let (contents, crc) = packet.split_at(packet.len() - 4);
+    if crc32fast::hash(contents).to_be_bytes() != crc {
+        return None;
+    }
+    match contents[0] {
+        PACKET_DATA => Some(Packet::Data(contents[1], contents[2..].to_vec())),
+        PACKET_ACK => Some(Packet::Ack(contents[1])),
+        PACKET_NAK => Some(Packet::Nak),
+        _ => None,
+    }
+}
+
+/// Link to the device over a serial port
+pub struct SerialLink<P> {
+    port: P,
+    /// Bytes received after the last complete packet
+    received: Vec<u8>,
+    /// Frames from the device not yet taken (received while waiting for an `Ack`)
+    frames: VecDeque<Vec<u8>>,
+    /// Sequence number of the next `Data` packet sent
+    send_seq: u8,
+    /// Sequence number of the last `Data` packet taken from the device
+    last_received_seq: Option<u8>,
+}
+
+impl SerialLink<Box<dyn serialport::SerialPort>> {
+    /// Open the device's serial port
+    ///
+    /// # Arguments
+    /// * `port_name` - Serial port of the device (e.g. `/dev/ttyACM0` or `COM3`)
+    /// * `settings` - Baud rate and parity
+    ///
+    /// # Returns
+    /// * `io::Result<SerialLink>` - The link, or an error if the port cannot be opened
+    pub fn open(port_name: &str, settings: &SerialSettings) -> io::Result<Self> {
+        let parity = match settings.parity {
+            Parity::None => serialport::Parity::None,
+            Parity::Even => serialport::Parity::Even,
+            Parity::Odd => serialport::Parity::Odd,
+        };
+        let port = serialport::new(port_name.trim(), settings.baud_rate)
+            .parity(parity)
+            .timeout(POLL_INTERVAL)
+            .open()?;
+        // Bytes left from before are not part of any packet
+        let _ = port.clear(serialport::ClearBuffer::All);
+        Ok(SerialLink::new(port))
+    }
+}
+
+impl<P: Read + Write + Send> SerialLink<P> {
+    /// Use a port that is already open
+    pub fn new(port: P) -> Self {
+        SerialLink { port, received: Vec::new(), frames: VecDeque::new(), send_seq: 0, last_received_seq: None }
+    }
+
+    /// Write a packet
+    fn write_packet(&mut self, kind: u8, seq: u8, frame: &[u8]) -> io::Result<()> {
+        self.port.write_all(&encode_packet(kind, seq, frame))?;
+        self.port.flush()
+    }
+
+    /// Wait for the next packet, until the deadline
+    ///
+    /// # Returns
+    /// * `io::Result<Option<Option<Packet>>>` - The packet (None inside if it was
+    ///   damaged), None if nothing arrived in time, or an error if the port failed
+    fn read_packet(&mut self, deadline: Instant) -> io::Result<Option<Option<Packet>>> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            if let Some(end) = self.received.iter().position(|&byte| byte == 0) {
+                let encoded: Vec<u8> = self.received.drain(..=end).collect();
+                // Delimiters on their own are line noise, or padding
+                if end > 0 {
+                    return Ok(Some(decode_packet(&encoded[..end])));
+                }
+                continue;
+            }
+            if self.received.len() > MAX_ENCODED_LEN {
+                self.received.clear();
+                return Ok(Some(None));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            match self.port.read(&mut buffer) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the serial port was closed")),
+                Ok(read) => self.received.extend_from_slice(&buffer[..read]),
+                Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) => {},
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Acknowledge a frame from the device, and keep it unless it was already taken
+    fn accept(&mut self, seq: u8, frame: Vec<u8>) -> io::Result<()> {
+        self.write_packet(PACKET_ACK, seq, &[])?;
+        if self.last_received_seq != Some(seq) {
+            self.last_received_seq = Some(seq);
+            self.frames.push_back(frame);
+        }
+        Ok(())
+    }
+}
+
+impl<P: Read + Write + Send> DeviceLink for SerialLink<P> {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        let seq = self.send_seq;
+        self.send_seq = seq.wrapping_add(1);
+
+        for _ in 0..=MAX_RETRANSMITS {
+            self.write_packet(PACKET_DATA, seq, frame)?;
+            let deadline = Instant::now() + ACK_TIMEOUT;
+            loop {
+                match self.read_packet(deadline)? {
+                    Some(Some(Packet::Ack(acked))) if acked == seq => return Ok(()),
+                    // An answer to an earlier packet
+                    Some(Some(Packet::Ack(_))) => {},
+                    Some(Some(Packet::Data(received_seq, received))) => self.accept(received_seq, received)?,
+                    Some(None) => self.write_packet(PACKET_NAK, 0, &[])?,
+                    // Sent again
+                    Some(Some(Packet::Nak)) | None => break,
+                }
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("the device did not acknowledge a frame sent {} times", MAX_RETRANSMITS + 1),
+        ))
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<u8>> {
+        let deadline = Instant::now() + READ_TIMEOUT;
+        loop {
+            if let Some(frame) = self.frames.pop_front() {
+                return Ok(frame);
+            }
+            match self.read_packet(deadline)? {
+                Some(Some(Packet::Data(seq, frame))) => self.accept(seq, frame)?,
+                Some(Some(Packet::Ack(_) | Packet::Nak)) => {},
+                Some(None) => self.write_packet(PACKET_NAK, 0, &[])?,
+                None => return Err(io::Error::new(io::ErrorKind::TimedOut, "the device did not answer in time")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Device stand-in on a noisy line: it echoes every frame back, and the first packet
+    /// each way is damaged
+    struct NoisyDevice {
+        /// Bytes written by CRUSTy, not yet read by the device
+        inbox: Vec<u8>,
+        /// Bytes waiting to be read by CRUSTy
+        outbox: VecDeque<u8>,
+        damage_next_in: bool,
+        damage_next_out: bool,
+        /// Last packet sent by the device, for a `Nak`
+        last_sent: Vec<u8>,
+        /// Frames received intact, and `Data` packets received
+        frames: Vec<Vec<u8>>,
+        data_packets: usize,
+        seq: u8,
+    }
+
+    impl NoisyDevice {
+        fn new() -> Self {
+            NoisyDevice {
+                inbox: Vec::new(),
+                outbox: VecDeque::new(),
+                damage_next_in: true,
+                damage_next_out: true,
+                last_sent: Vec::new(),
+                frames: Vec::new(),
+                data_packets: 0,
+                seq: 0,
+            }
+        }
+
+        fn reply(&mut self, packet: Vec<u8>) {
+            self.outbox.extend(&packet);
+        }
+    }
+
+    impl Read for NoisyDevice {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            if self.outbox.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "nothing to read"));
+            }
+            let read = buffer.len().min(self.outbox.len());
+            for (slot, byte) in buffer.iter_mut().zip(self.outbox.drain(..read)) {
+                *slot = byte;
+            }
+            Ok(read)
+        }
+    }
+
+    impl Write for NoisyDevice {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.inbox.extend_from_slice(data);
+            while let Some(end) = self.inbox.iter().position(|&byte| byte == 0) {
+                let mut encoded: Vec<u8> = self.inbox.drain(..=end).collect();
+                encoded.pop();
+                if self.damage_next_in {
+                    self.damage_next_in = false;
+                    encoded[2] ^= 0x40;
+                }
+                match decode_packet(&encoded) {
+                    Some(Packet::Data(seq, frame)) => {
+                        self.data_packets += 1;
+                        self.reply(encode_packet(PACKET_ACK, seq, &[]));
+                        self.frames.push(frame.clone());
+
+                        let mut echo = encode_packet(PACKET_DATA, self.seq, &frame);
+                        self.seq = self.seq.wrapping_add(1);
+                        self.last_sent = echo.clone();
+                        if self.damage_next_out {
+                            self.damage_next_out = false;
+                            echo[3] ^= 0x40;
+                        }
+                        self.reply(echo);
+                    },
+                    Some(Packet::Nak) => {
+                        let last_sent = self.last_sent.clone();
+                        self.reply(last_sent);
+                    },
+                    Some(Packet::Ack(_)) => {},
+                    None => self.reply(encode_packet(PACKET_NAK, 0, &[])),
+                }
+            }
+            Ok(data.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_cobs_round_trip() {
+        let long: Vec<u8> = (0..600).map(|i| (i % 256) as u8).collect();
+        for data in [&b""[..], &[0], &[0, 0], &[1, 2, 0, 3], &[0xFF; 254], &[0xFF; 255], &long] {
+            let encoded = cobs_encode(data);
+            assert!(!encoded.contains(&0));
+            assert_eq!(cobs_decode(&encoded).as_deref(), Some(data));
+        }
+        assert_eq!(cobs_encode(&[0x11, 0x22, 0x00, 0x33]), vec![0x03, 0x11, 0x22, 0x02, 0x33]);
+    }
+
+    #[test]
+    fn test_damaged_packets() {
+        let packet = encode_packet(PACKET_DATA, 7, b"frame");
+        assert_eq!(decode_packet(&packet[..packet.len() - 1]), Some(Packet::Data(7, b"frame".to_vec())));
+
+        let mut damaged = packet.clone();
+        damaged[4] ^= 0x01;
+        assert_eq!(decode_packet(&damaged[..damaged.len() - 1]), None);
+        assert_eq!(decode_packet(&packet[..4]), None);
+    }
+
+    #[test]
+    fn test_retransmit_damaged_packets() {
+        let mut link = SerialLink::new(NoisyDevice::new());
+        let frame = [0x82, 0, 0, 0, 3, 0, 1, 2];
+
+        // The device gets the frame once, on the second try
+        link.send(&frame).unwrap();
+        assert_eq!(link.port.data_packets, 1);
+        assert_eq!(link.port.frames, vec![frame.to_vec()]);
+
+        // Its damaged echo is asked for again
+        assert_eq!(link.receive().unwrap(), frame.to_vec());
+
+        link.send(b"second").unwrap();
+        assert_eq!(link.receive().unwrap(), b"second".to_vec());
+        assert_eq!(link.port.frames.len(), 2);
+    }
+}
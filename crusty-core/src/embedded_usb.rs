@@ -0,0 +1,208 @@
+/// Embedded device USB link module.
+///
+/// This module provides functionality for:
+/// - Finding the embedded device on USB by its device ID: its serial number, or its
+///   vendor and product IDs (`vvvv:pppp`, in hex)
+/// - Carrying protocol frames over the device's bulk endpoints, with timeouts
+///
+/// The device claims one interface with a pair of bulk endpoints: frames are written to
+/// the OUT endpoint, and read from the IN endpoint (the same number, | 0x80).
+use std::io;
+use std::time::Duration;
+use rusb::{Context, DeviceHandle, UsbContext};
+
+use crate::backend::UsbSettings;
+use crate::embedded_protocol::{DeviceLink, FrameBuffer};
+
+/// Time allowed for the device to take a frame
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time allowed for the device to answer (it may be busy with queued chunks)
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest bulk read at once
+const READ_LEN: usize = 16 * 1024;
+
+/// Packet size of the endpoint, if the device does not say
+const DEFAULT_PACKET_SIZE: usize = 64;
+
+/// How the device is found on USB
+#[derive(Debug, Clone, PartialEq)]
+pub enum UsbDeviceId {
+    /// The device with this serial number
+    Serial(String),
+    /// The first device with these vendor and product IDs
+    VendorProduct(u16, u16),
+}
+
+impl UsbDeviceId {
+    /// Read a device ID: `vvvv:pppp` (hex vendor and product IDs), or a serial number
+    pub fn parse(device_id: &str) -> Self {
+        let device_id = device_id.trim();
+        let ids = device_id.split_once(':').and_then(|(vendor, product)| {
+            let hex = |id: &str| (id.len() == 4).then(|| u16::from_str_radix(id, 16).ok()).flatten();
+            Some((hex(vendor)?, hex(product)?))
+        });
+        match ids {
+            Some((vendor, product)) => UsbDeviceId::VendorProduct(vendor, product),
+            None => UsbDeviceId::Serial(device_id.to_string()),
+        }
+    }
+}
+
+/// Link to the device over USB bulk transfers
+pub struct UsbLink {
+    handle: DeviceHandle<Context>,
+    interface: u8,
+    out_endpoint: u8,
+    in_endpoint: u8,
+    packet_size: usize,
+    received: FrameBuffer,
+}
+
+impl UsbLink {
+    /// Open the device and claim its interface
+    ///
+    /// # Arguments
+    /// * `device_id` - Serial number, or `vvvv:pppp` vendor and product IDs
+    /// * `settings` - Interface and bulk endpoint of the device
+    ///
+    /// # Returns
+    /// * `io::Result<UsbLink>` - The link, or an error if the device is not found or
+    ///   cannot be opened
+    pub fn open(device_id: &str, settings: &UsbSettings) -> io::Result<Self> {
+        let wanted = UsbDeviceId::parse(device_id);
+        let context = Context::new().map_err(usb_error)?;
+
+        // A device that matches but cannot be opened is reported, rather than "not found"
+        let mut open_error = None;
+        for device in context.devices().map_err(usb_error)?.iter() {
+            let Ok(descriptor) = device.device_descriptor() else { continue };
+            if let UsbDeviceId::VendorProduct(vendor, product) = wanted {
+                if descriptor.vendor_id() != vendor || descriptor.product_id() != product {
+                    continue;
+                }
+            }
+            let handle = match device.open() {
+                Ok(handle) => handle,
+                Err(e) => {
+                    open_error = Some(e);
+                    continue;
+                },
+            };
+            if let UsbDeviceId::Serial(serial) = &wanted {
+                if handle.read_serial_number_string_ascii(&descriptor).ok().as_ref() != Some(serial) {
+                    continue;
+                }
+            }
+
+            let packet_size = device.active_config_descriptor().ok()
+                .and_then(|config| config.interfaces()
+                    .flat_map(|interface| interface.descriptors().collect::<Vec<_>>())
+                    .filter(|interface| interface.interface_number() == settings.interface)
+                    .flat_map(|interface| interface.endpoint_descriptors().collect::<Vec<_>>())
+                    .find(|endpoint| endpoint.address() == settings.endpoint)
+                    .map(|endpoint| endpoint.max_packet_size() as usize))
+                .filter(|&size| size > 0)
+                .unwrap_or(DEFAULT_PACKET_SIZE);
+            return Self::claim(handle, settings, packet_size);
+        }
+
+        Err(match open_error {
+            Some(e) => usb_error(e),
+            None => io::Error::new(io::ErrorKind::NotFound, format!("no USB device '{}' is connected", device_id.trim())),
+        })
+    }
+
+    /// Claim the interface of an opened device
+    fn claim(handle: DeviceHandle<Context>, settings: &UsbSettings, packet_size: usize) -> io::Result<Self> {
+        // Not every system lets a kernel driver be detached; claiming then says why
+        let _ = handle.set_auto_detach_kernel_driver(true);
+        handle.claim_interface(settings.interface).map_err(usb_error)?;
+        Ok(UsbLink {
+            handle,
+            interface: settings.interface,
+            out_endpoint: settings.endpoint,
+            in_endpoint: settings.endpoint | 0x80,
+            packet_size,
+            received: FrameBuffer::default(),
+        })
+    }
+}
+
+impl DeviceLink for UsbLink {
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        let mut sent = 0;
+        while sent < frame.len() {
+            sent += self.handle.write_bulk(self.out_endpoint, &frame[sent..], WRITE_TIMEOUT).map_err(usb_error)?;
+        }
+        // A frame that fills its last packet is ended with an empty packet, so the
+        // device does not wait for more
+        if frame.len().is_multiple_of(self.packet_size) {
+            self.handle.write_bulk(self.out_endpoint, &[], WRITE_TIMEOUT).map_err(usb_error)?;
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0u8; READ_LEN];
+        loop {
+            if let Some(frame) = self.received.next_frame()? {
+                return Ok(frame);
+            }
+            let read = self.handle.read_bulk(self.in_endpoint, &mut buffer, READ_TIMEOUT).map_err(usb_error)?;
+            self.received.push(&buffer[..read]);
+        }
+    }
+}
+
+impl Drop for UsbLink {
+    fn drop(&mut self) {
+        let _ = self.handle.release_interface(self.interface);
+    }
+}
+
+/// Get a USB error as an I/O error of the matching kind
+fn usb_error(error: rusb::Error) -> io::Error {
+    let kind = match error {
+        rusb::Error::Timeout => io::ErrorKind::TimedOut,
+        rusb::Error::NoDevice => io::ErrorKind::NotConnected,
+        rusb::Error::Access => io::ErrorKind::PermissionDenied,
+        rusb::Error::NotFound => io::ErrorKind::NotFound,
+        rusb::Error::Busy => io::ErrorKind::ResourceBusy,
+        rusb::Error::Pipe => io::ErrorKind::BrokenPipe,
+        rusb::Error::Interrupted => io::ErrorKind::Interrupted,
+        rusb::Error::NotSupported => io::ErrorKind::Unsupported,
+        _ => io::ErrorKind::Other,
+    };
+    let message = match error {
+        rusb::Error::Access => "permission denied opening the USB device (check the device's udev rules or driver)".to_string(),
+        rusb::Error::Busy => "the USB interface is in use by another program or driver".to_string(),
+        rusb::Error::Timeout => "the USB device did not answer in time".to_string(),
+        rusb::Error::NoDevice => "the USB device was disconnected".to_string(),
+        other => format!("USB error: {}", other),
+    };
+    io::Error::new(kind, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_id() {
+        assert_eq!(UsbDeviceId::parse("cafe:4010"), UsbDeviceId::VendorProduct(0xcafe, 0x4010));
+        assert_eq!(UsbDeviceId::parse(" 0483:5740 "), UsbDeviceId::VendorProduct(0x0483, 0x5740));
+        assert_eq!(UsbDeviceId::parse("crusty-01"), UsbDeviceId::Serial("crusty-01".to_string()));
+        // Anything that is not two 4-digit hex IDs is a serial number
+        assert_eq!(UsbDeviceId::parse("ab:cd"), UsbDeviceId::Serial("ab:cd".to_string()));
+        assert_eq!(UsbDeviceId::parse("CRUSTY:0001"), UsbDeviceId::Serial("CRUSTY:0001".to_string()));
+    }
+
+    #[test]
+    fn test_usb_error_kinds() {
+        assert_eq!(usb_error(rusb::Error::Timeout).kind(), io::ErrorKind::TimedOut);
+        assert_eq!(usb_error(rusb::Error::NoDevice).kind(), io::ErrorKind::NotConnected);
+        assert_eq!(usb_error(rusb::Error::Access).kind(), io::ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,620 @@
+/// Encryption module for AES-256-GCM file encryption and decryption. 
+/// 
+/// This module provides functionality for:
+/// - Generating encryption keys and passphrase salts with the operating system's
+///   random number generator
+/// - Encrypting and decrypting individual files (including empty files)
+/// - Writing output files atomically, so no partial file is ever seen under the final name
+/// - Writing decrypted files sparsely, so runs of zeros do not take disk space
+/// - Batch processing multiple files
+/// - Progress tracking during operations, as each chunk of a file is read and written
+///
+/// The format itself (keys, headers, and AES-256-GCM) is implemented in `container`,
+/// which does no I/O; the functions here give it the operating system's generator and
+/// read and write the files.
+use aes_gcm::aead::OsRng;
+use anyhow::Result;
+use std::fs::File;
+use std::io::{Read, Write, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::chunk_tuning;
+use crate::container::{self, KDF_HEADER_LEN, KDF_MAGIC, MAX_STORED_NAME_LEN, NAME_HEADER_OVERHEAD};
+pub use crate::container::{
+    decrypt_data, decrypt_payload, kdf_header, kdf_params_header, split_digest_header,
+    split_kdf_header, split_name_header, EncryptionError, EncryptionKey, KdfParams
+};
+
+/// Block size used to find runs of zeros to leave as holes in decrypted files
+const SPARSE_BLOCK_SIZE: usize = 4096;
+
+/// Bytes of a sparse file written between progress reports
+const SPARSE_PROGRESS_INTERVAL: usize = 1024 * 1024;
+
+impl KdfParams {
+    /// Generate parameters for a new passphrase key: 64 MB, 3 passes, and a random salt
+    pub fn generate() -> Self {
+        Self::generate_with(&mut OsRng)
+    }
+}
+
+impl EncryptionKey {
+    /// Generate a new random encryption key
+    pub fn generate() -> Self {
+        Self::generate_with(&mut OsRng)
+    }
+}
+
+/// Read the KDF parameters of an encrypted file
+///
+/// # Returns
+/// * `Result<Option<KdfParams>, EncryptionError>` - The parameters, or None if the file
+///   was not encrypted with a passphrase key
+pub fn read_kdf_params(path: &Path) -> Result<Option<KdfParams>, EncryptionError> {
+    let mut header = Vec::with_capacity(KDF_HEADER_LEN);
+    File::open(path)?.take(KDF_HEADER_LEN as u64).read_to_end(&mut header)?;
+    
+    // A short file without the magic has no header; a short file with it is truncated
+    if header.len() < KDF_HEADER_LEN && !header.starts_with(KDF_MAGIC) {
+        return Ok(None);
+    }
+    split_kdf_header(&header).map(|(params, _)| params)
+}
+
+/// Get the header that stores the original name of a file, encrypted with the key
+///
+/// The header follows the KDF header (if any), so the name is only readable with the key.
+pub fn name_header(name: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+    container::name_header(name, key, &mut OsRng)
+}
+
+/// Get the header that stores the SHA-256 of a file's original data, encrypted with the key
+pub fn digest_header(data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+    container::digest_header(data, key, &mut OsRng)
+}
+
+/// Read the original name stored in an encrypted file
+///
+/// Only the start of the file is read and decrypted.
+///
+/// # Returns
+/// * `Result<Option<Vec<u8>>, EncryptionError>` - The name, or None if the file does not
+///   store its name
+pub fn read_stored_name(path: &Path, key: &EncryptionKey) -> Result<Option<Vec<u8>>, EncryptionError> {
+    let limit = KDF_HEADER_LEN + NAME_HEADER_OVERHEAD + MAX_STORED_NAME_LEN;
+    let mut header = Vec::with_capacity(limit);
+    File::open(path)?.take(limit as u64).read_to_end(&mut header)?;
+    
+    let (_, rest) = split_kdf_header(&header)?;
+    match split_name_header(rest)? {
+        (Some(name), _) => decrypt_data(name, key).map(Some),
+        (None, _) => Ok(None),
+    }
+}
+
+/// Encrypt raw data using AES-256-GCM
+pub fn encrypt_data(data: &[u8], key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
+    container::encrypt_data(data, key, &mut OsRng)
+}
+
+/// Check that files can be created in the output folder
+///
+/// A file is created and removed again, which also catches read-only drives and
+/// network shares that report the folder as writable.
+pub fn check_output_dir(dir: &Path) -> Result<(), EncryptionError> {
+    if !dir.is_dir() {
+        return Err(EncryptionError::MissingDestination(dir.to_path_buf()));
+    }
+    
+    tempfile::Builder::new()
+        .prefix(".crusty-write-check")
+        .tempfile_in(dir)
+        .map(|_| ())
+        .map_err(|e| destination_error(dir, e))
+}
+
+/// Get the temporary file a destination file is written to before it is renamed into
+/// place (a hidden file next to it)
+pub fn temp_destination(dest_path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(dest_path.file_name().unwrap_or_default());
+    name.push(".crusty-tmp");
+    dest_path.with_file_name(name)
+}
+
+/// Check whether a file is a temporary file left by an interrupted write
+pub fn is_temp_destination(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with('.') && name.ends_with(".crusty-tmp"))
+}
+
+/// Write a destination file atomically
+///
+/// The contents are written to a temporary file in the destination folder, flushed to
+/// disk, and renamed to the destination. Readers, a crash, or a full disk never see a
+/// partial destination file: it is either absent or complete. The temporary file is
+/// removed on an error, and permission problems are reported as a read-only output folder.
+pub fn write_destination(
+    dest_path: &Path,
+    write: impl FnOnce(&mut File) -> std::io::Result<()>,
+) -> Result<(), EncryptionError> {
+    let dir = match dest_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    
+    // A temporary file left by an interrupted run holds nothing worth keeping
+    let temp = temp_destination(dest_path);
+    let _ = std::fs::remove_file(&temp);
+    
+    let mut file = File::create(&temp).map_err(|e| destination_error(dir, e))?;
+    let written = write(&mut file).and_then(|_| file.sync_all());
+    drop(file);
+    
+    let result = written.and_then(|_| {
+        // Checked again, as rename replaces an existing file on Unix
+        if dest_path.exists() {
+            return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists"));
+        }
+        std::fs::rename(&temp, dest_path)
+    });
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp);
+        return Err(EncryptionError::Io(e));
+    }
+    
+    sync_dir(dir);
+    Ok(())
+}
+
+/// Flush a folder's entries to disk, so a rename survives a crash
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+/// Flush a folder's entries to disk (folders cannot be flushed on Windows; NTFS
+/// journals the rename)
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+/// Map an error writing to the output folder to an error saying what to do about it
+fn destination_error(dir: &Path, error: std::io::Error) -> EncryptionError {
+    match error.kind() {
+        std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem => {
+            EncryptionError::ReadOnlyDestination(dir.to_path_buf())
+        },
+        std::io::ErrorKind::NotFound => EncryptionError::MissingDestination(dir.to_path_buf()),
+        _ => EncryptionError::Io(error),
+    }
+}
+
+/// Write decrypted data to a new file, leaving blocks of zeros as holes
+///
+/// Where the file system supports sparse files, the holes take no disk space, so a
+/// sparse file (e.g. a disk image) does not grow when it is decrypted. Elsewhere the
+/// holes read back as zeros, like the blocks they replace.
+///
+/// # Arguments
+/// * `progress_callback` - Called with the fraction written after every
+///   `SPARSE_PROGRESS_INTERVAL` bytes
+pub fn write_sparse(file: &mut File, data: &[u8], progress_callback: impl Fn(f32)) -> std::io::Result<()> {
+    mark_sparse(file);
+    
+    let blocks_per_report = SPARSE_PROGRESS_INTERVAL / SPARSE_BLOCK_SIZE;
+    for (index, block) in data.chunks(SPARSE_BLOCK_SIZE).enumerate() {
+        write_block(file, block)?;
+        if (index + 1) % blocks_per_report == 0 {
+            progress_callback(((index + 1) * SPARSE_BLOCK_SIZE) as f32 / data.len() as f32);
+        }
+    }
+    
+    // Seeking past the end does not extend the file, so include a trailing hole
+    file.set_len(data.len() as u64)?;
+    progress_callback(1.0);
+    Ok(())
+}
+
+/// Copy decrypted data from a reader to a new file as `write_sparse` writes it, holding
+/// only one block in memory at a time
+///
+/// # Returns
+/// * `std::io::Result<u64>` - The number of bytes copied
+pub fn copy_sparse(mut reader: impl Read, file: &mut File) -> std::io::Result<u64> {
+    mark_sparse(file);
+    
+    let mut block = Zeroizing::new(Vec::with_capacity(SPARSE_BLOCK_SIZE));
+    let mut len = 0;
+    loop {
+        block.clear();
+        let read = (&mut reader).take(SPARSE_BLOCK_SIZE as u64).read_to_end(&mut block)?;
+        if read == 0 {
+            break;
+        }
+        write_block(file, &block)?;
+        len += read as u64;
+    }
+    
+    file.set_len(len)?;
+    Ok(len)
+}
+
+/// Write a block of a sparse file, skipping it if it is all zeros
+fn write_block(file: &mut File, block: &[u8]) -> std::io::Result<()> {
+    if block.iter().all(|&byte| byte == 0) {
+        file.seek(SeekFrom::Current(block.len() as i64))?;
+        Ok(())
+    } else {
+        file.write_all(block)
+    }
+}
+
+/// Mark a file as sparse, so skipped blocks become holes (NTFS needs this; other file
+/// systems create holes without it)
+#[cfg(windows)]
+fn mark_sparse(file: &File) {
+    use std::os::windows::io::AsRawHandle;
+    use winapi::um::ioapiset::DeviceIoControl;
+    use winapi::um::winioctl::FSCTL_SET_SPARSE;
+    
+    // Not supported on e.g. FAT32; the skipped blocks are then written as zeros
+    let mut returned = 0;
+    unsafe {
+        DeviceIoControl(
+            file.as_raw_handle() as _,
+            FSCTL_SET_SPARSE,
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            0,
+            &mut returned,
+            std::ptr::null_mut(),
+        );
+    }
+}
+
+/// Mark a file as sparse (not needed outside Windows)
+#[cfg(not(windows))]
+fn mark_sparse(_file: &File) {}
+
+/// Encrypt a file using AES-256-GCM
+///
+/// An empty file gives a ciphertext with only the nonce, length, and authentication tag.
+pub fn encrypt_file(
+    source_path: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32) + Send + 'static,
+) -> Result<(), EncryptionError> {
+    // Check if the destination file already exists
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+
+    // Read the entire file into memory, in chunks sized to the drive (the first half
+    // of the progress)
+    let buffer = chunk_tuning::read_file(source_path, |p| progress_callback(p * 0.5))?;
+    
+    // Update progress to indicate file read is complete
+    progress_callback(0.5);
+    
+    // Encrypt the data, after the KDF header for passphrase keys
+    let mut encrypted_data = kdf_header(key);
+    encrypted_data.extend_from_slice(&encrypt_data(&buffer, key)?);
+    
+    // Write the encrypted data to the destination file, in chunks (the second half)
+    write_destination(dest_path, |dest_file| {
+        chunk_tuning::write_all(dest_file, &encrypted_data, |p| progress_callback(0.5 + p * 0.5))
+    })?;
+    
+    // Final progress update
+    progress_callback(1.0);
+    
+    Ok(())
+}
+
+/// Decrypt a file using AES-256-GCM
+pub fn decrypt_file(
+    source_path: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32) + Send + 'static,
+) -> Result<(), EncryptionError> {
+    // Check if the destination file already exists
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+
+    // Read the entire file into memory, repairing it if it has parity (the first half
+    // of the progress)
+    let buffer = crate::parity::read_encrypted_file(source_path, |p| progress_callback(p * 0.5))?;
+    
+    // Update progress to indicate file read is complete
+    progress_callback(0.5);
+    
+    // Decrypt the data (the KDF header is only needed to derive the key again)
+    let (_, ciphertext) = split_kdf_header(&buffer)?;
+    let (_, ciphertext) = split_name_header(ciphertext)?;
+    let (decrypted_data, _) = decrypt_payload(ciphertext, key, |payload| decrypt_data(payload, key))?;
+    
+    // Write the decrypted data to the destination file (the second half)
+    write_destination(dest_path, |dest_file| {
+        write_sparse(dest_file, &decrypted_data, |p| progress_callback(0.5 + p * 0.5))
+    })?;
+    
+    // Final progress update
+    progress_callback(1.0);
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    // Test helper functions
+    fn create_test_file(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    // Key generation tests
+    #[test]
+    fn test_key_generation() {
+        let key = EncryptionKey::generate();
+        assert_eq!(key.key.len(), 32);
+    }
+
+    #[test]
+    fn test_key_serialization() {
+        let key = EncryptionKey::generate();
+        let base64 = key.to_base64();
+        let restored = EncryptionKey::from_base64(&base64).unwrap();
+        assert_eq!(key.key, restored.key);
+    }
+
+    #[test]
+    fn test_key_fingerprint() {
+        let key = EncryptionKey::generate();
+        let fingerprint = key.fingerprint();
+        assert_eq!(fingerprint.len(), 19);
+        assert_eq!(fingerprint.split('-').count(), 4);
+        assert_eq!(EncryptionKey::from_base64(&key.to_base64()).unwrap().fingerprint(), fingerprint);
+        assert_ne!(EncryptionKey::generate().fingerprint(), fingerprint);
+    }
+
+    // Basic encryption/decryption tests
+    #[test]
+    fn test_encrypt_decrypt_data() {
+        let key = EncryptionKey::generate();
+        let plaintext = b"CRUSTy secret message";
+        
+        let encrypted = encrypt_data(plaintext, &key).unwrap();
+        let decrypted = decrypt_data(&encrypted, &key).unwrap();
+        
+        assert_eq!(plaintext, decrypted.as_slice());
+    }
+
+    #[test]
+    fn test_decrypt_invalid_key() {
+        let key1 = EncryptionKey::generate();
+        let key2 = EncryptionKey::generate();
+        let plaintext = b"CRUSTy secret message";
+        
+        let encrypted = encrypt_data(plaintext, &key1).unwrap();
+        let result = decrypt_data(&encrypted, &key2);
+        
+        assert!(matches!(result, Err(EncryptionError::Decryption(_)))); 
+    }
+
+    // File encryption tests
+    #[test]
+    fn test_file_encryption() {
+        let key = EncryptionKey::generate();
+        let plain_file = create_test_file("Test file contents");
+        let dir = tempfile::tempdir().unwrap();
+        let encrypted_file = dir.path().join("test.encrypted");
+        let decrypted_file = dir.path().join("test.txt");
+
+        encrypt_file(plain_file.path(), &encrypted_file, &key, |_| {}).unwrap();
+        decrypt_file(&encrypted_file, &decrypted_file, &key, |_| {}).unwrap();
+
+        let mut decrypted = String::new();
+        File::open(&decrypted_file).unwrap()
+            .read_to_string(&mut decrypted).unwrap();
+            
+        assert_eq!(decrypted, "Test file contents");
+    }
+
+    #[test]
+    fn test_progress_per_chunk() {
+        use std::sync::{Arc, Mutex};
+        
+        let dir = tempfile::tempdir().unwrap();
+        let plain_path = dir.path().join("large.bin");
+        let encrypted_path = dir.path().join("large.bin.encrypted");
+        let decrypted_path = dir.path().join("large.out");
+        let data: Vec<u8> = (0..3 * 1024 * 1024 + 17).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&plain_path, &data).unwrap();
+        let key = EncryptionKey::generate();
+        
+        // Progress moves with each chunk read and written, rather than jumping to the
+        // half and the end
+        let recording = |reports: &Arc<Mutex<Vec<f32>>>| {
+            let reports = reports.clone();
+            move |p| reports.lock().unwrap().push(p)
+        };
+        for decrypt in [false, true] {
+            let reports = Arc::new(Mutex::new(Vec::new()));
+            if decrypt {
+                decrypt_file(&encrypted_path, &decrypted_path, &key, recording(&reports)).unwrap();
+            } else {
+                encrypt_file(&plain_path, &encrypted_path, &key, recording(&reports)).unwrap();
+            }
+            let reports = reports.lock().unwrap();
+            assert!(reports.iter().filter(|&&p| p > 0.0 && p < 0.5).count() > 1, "{:?}", reports);
+            assert!(reports.iter().filter(|&&p| p > 0.5 && p < 1.0).count() > 1, "{:?}", reports);
+            assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", reports);
+            assert_eq!(reports.last(), Some(&1.0));
+        }
+        assert_eq!(std::fs::read(&decrypted_path).unwrap(), data);
+    }
+
+
+    #[test]
+    fn test_empty_file() {
+        let key = EncryptionKey::generate();
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("empty.txt");
+        let encrypted = dir.path().join("empty.txt.encrypted");
+        let decrypted = dir.path().join("empty.decrypted");
+        std::fs::write(&plain, b"").unwrap();
+        
+        // Nonce, length, and authentication tag only
+        encrypt_file(&plain, &encrypted, &key, |_| {}).unwrap();
+        assert_eq!(std::fs::metadata(&encrypted).unwrap().len(), 12 + 4 + 16);
+        
+        decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(std::fs::metadata(&decrypted).unwrap().len(), 0);
+        
+        // The tag is still checked
+        let wrong_key = EncryptionKey::generate();
+        assert!(decrypt_file(&encrypted, &dir.path().join("wrong"), &wrong_key, |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_sparse_decryption() {
+        let key = EncryptionKey::generate();
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("disk.img");
+        let encrypted = dir.path().join("disk.img.encrypted");
+        let decrypted = dir.path().join("disk.decrypted");
+        
+        // Data, 4 MB of zeros, data, then trailing zeros
+        let mut content = b"boot sector".to_vec();
+        content.resize(4 * 1024 * 1024, 0);
+        content.extend_from_slice(b"partition table");
+        content.resize(content.len() + 100_000, 0);
+        std::fs::write(&plain, &content).unwrap();
+        
+        encrypt_file(&plain, &encrypted, &key, |_| {}).unwrap();
+        decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), content);
+        
+        // The zeros are holes (assumes the temporary directory supports sparse files)
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let allocated = std::fs::metadata(&decrypted).unwrap().blocks() * 512;
+            assert!(allocated < 1024 * 1024, "{} bytes allocated", allocated);
+        }
+    }
+
+    #[test]
+    fn test_output_dir_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(check_output_dir(dir.path()).is_ok());
+        
+        let missing = dir.path().join("missing");
+        assert!(matches!(check_output_dir(&missing), Err(EncryptionError::MissingDestination(_))));
+        
+        // Permission errors name the folder and say what to do
+        let error = destination_error(dir.path(), std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        assert!(matches!(&error, EncryptionError::ReadOnlyDestination(path) if path == dir.path()));
+        assert!(error.to_string().contains("Choose another output folder"));
+        
+        let error = destination_error(dir.path(), std::io::Error::from(std::io::ErrorKind::ReadOnlyFilesystem));
+        assert!(matches!(error, EncryptionError::ReadOnlyDestination(_)));
+        let error = destination_error(dir.path(), std::io::Error::from(std::io::ErrorKind::StorageFull));
+        assert!(matches!(error, EncryptionError::Io(_)));
+    }
+
+    // Passphrase key tests
+    #[test]
+    fn test_passphrase_key() {
+        // Cheap parameters, so the test runs quickly
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1, salt: [7; 16] };
+        let key = EncryptionKey::from_passphrase("correct horse battery staple", &params).unwrap();
+        
+        // The same passphrase and salt give the same key; another salt does not
+        let again = EncryptionKey::from_passphrase("correct horse battery staple", &params).unwrap();
+        assert_eq!(key.key, again.key);
+        let other_salt = KdfParams { salt: [8; 16], ..params.clone() };
+        assert_ne!(key.key, EncryptionKey::from_passphrase("correct horse battery staple", &other_salt).unwrap().key);
+        assert!(EncryptionKey::from_passphrase("", &params).is_err());
+        
+        // The file header holds the parameters needed to derive the key again
+        let dir = tempfile::tempdir().unwrap();
+        let plain = create_test_file("Passphrase protected");
+        let encrypted = dir.path().join("plain.encrypted");
+        let decrypted = dir.path().join("plain.txt");
+        encrypt_file(plain.path(), &encrypted, &key, |_| {}).unwrap();
+        
+        let read_params = read_kdf_params(&encrypted).unwrap().unwrap();
+        assert_eq!(read_params, params);
+        let derived = EncryptionKey::from_passphrase("correct horse battery staple", &read_params).unwrap();
+        decrypt_file(&encrypted, &decrypted, &derived, |_| {}).unwrap();
+        assert_eq!(std::fs::read_to_string(&decrypted).unwrap(), "Passphrase protected");
+        
+        // Files encrypted with random keys have no header
+        let random_key_file = dir.path().join("random.encrypted");
+        encrypt_file(plain.path(), &random_key_file, &EncryptionKey::generate(), |_| {}).unwrap();
+        assert_eq!(read_kdf_params(&random_key_file).unwrap(), None);
+        
+        // Headers asking for unreasonable amounts of memory are rejected
+        let mut header = kdf_header(&key);
+        header[10..14].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(split_kdf_header(&header).is_err());
+    }
+
+    // Error condition tests
+    #[test]
+    fn test_invalid_base64_key() {
+        let result = EncryptionKey::from_base64("invalid base64");
+        assert!(matches!(result, Err(EncryptionError::KeyError(_)))); 
+    }
+
+    #[test]
+    fn test_corrupted_ciphertext() {
+        let key = EncryptionKey::generate();
+        let mut corrupted = encrypt_data(b"test", &key).unwrap();
+        corrupted[10] ^= 0xFF; // Flip a bit
+        
+        let result = decrypt_data(&corrupted, &key);
+        assert!(matches!(result, Err(EncryptionError::Decryption(_)))); 
+    }
+
+    #[test]
+    fn test_write_destination_is_atomic() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("out.encrypted");
+        
+        // A write that fails part way leaves neither the destination nor the temporary file
+        let result = write_destination(&dest, |file| {
+            file.write_all(b"partial")?;
+            Err(std::io::Error::other("disk full"))
+        });
+        assert!(result.is_err());
+        assert!(!dest.exists());
+        assert!(!temp_destination(&dest).exists());
+        
+        // The destination only appears once complete, and is never replaced
+        write_destination(&dest, |file| {
+            assert!(!dest.exists());
+            file.write_all(b"complete")
+        }).unwrap();
+        assert!(write_destination(&dest, |file| file.write_all(b"other")).is_err());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"complete");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,392 @@
+/// File naming module.
+///
+/// This module provides functionality for:
+/// - Naming encrypted and decrypted files without altering the original name
+/// - Random names for encrypted files that store their original name, and restoring
+///   the stored name on decryption
+/// - Making restored names valid on Windows (trailing dots and spaces, reserved device
+///   names, reserved characters), and saying why a name was changed
+/// - Finding output names that would collide on normalization- or case-insensitive
+///   file systems
+///
+/// Names are handled as `OsStr`, never through lossy UTF-8 conversion, so names with
+/// combining characters, emoji, or bytes in a legacy encoding (e.g. Latin-1 names on
+/// Linux) are kept byte for byte.
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
+use rand::RngCore;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::encryption::{self, EncryptionKey};
+
+/// Extension added to encrypted files
+pub const ENCRYPTED_EXTENSION: &str = "encrypted";
+
+/// Extension of files encrypted by age tools
+pub const AGE_EXTENSION: &str = "age";
+
+/// Extensions of files encrypted by OpenPGP tools (GnuPG)
+pub const OPENPGP_EXTENSIONS: [&str; 3] = ["gpg", "pgp", "asc"];
+
+/// Extension added to decrypted files whose name does not end in `.encrypted`, `.age`,
+/// or an OpenPGP extension
+pub const DECRYPTED_EXTENSION: &str = "decrypted";
+
+/// Device names Windows reserves in every directory, with or without an extension
+#[cfg_attr(not(windows), allow(dead_code))]
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters Windows does not allow in file names
+#[cfg_attr(not(windows), allow(dead_code))]
+const WINDOWS_RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Name for an output file
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputName {
+    /// The name to create
+    pub name: OsString,
+    /// Why the name differs from the original name (None if it does not)
+    pub renamed: Option<String>,
+}
+
+/// Check whether a file is encrypted (by its `.encrypted` extension)
+pub fn is_encrypted_name(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == ENCRYPTED_EXTENSION)
+}
+
+/// Check whether a file has an OpenPGP extension (`.gpg`, `.pgp`, or `.asc`)
+pub fn is_openpgp_name(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| OPENPGP_EXTENSIONS.iter().any(|openpgp| extension == *openpgp))
+}
+
+/// Get the name of the encrypted file for a source file (the name plus `.encrypted`)
+///
+/// # Returns
+/// * `Option<OsString>` - The name, or None if the path has no file name
+pub fn encrypted_name(source: &Path) -> Option<OsString> {
+    let mut name = source.file_name()?.to_os_string();
+    name.push(".");
+    name.push(ENCRYPTED_EXTENSION);
+    Some(name)
+}
+
+/// Get the name of the decrypted file for an encrypted file
+///
+/// One `.encrypted`, `.age`, or OpenPGP extension is removed; files without one get
+/// `.decrypted` added.
+/// The name is then made valid for this platform.
+///
+/// # Returns
+/// * `Option<OutputName>` - The name, or None if the path has no file name
+pub fn decrypted_name(source: &Path) -> Option<OutputName> {
+    let file_name = source.file_name()?;
+
+    let name = match (source.file_stem(), source.extension()) {
+        (Some(stem), Some(extension)) if extension == ENCRYPTED_EXTENSION || extension == AGE_EXTENSION || is_openpgp_name(source) => stem.to_os_string(),
+        _ => {
+            let mut name = file_name.to_os_string();
+            name.push(".");
+            name.push(DECRYPTED_EXTENSION);
+            name
+        },
+    };
+
+    Some(platform_safe_name(name))
+}
+
+/// Get a random name for an encrypted file that stores its original name
+///
+/// The name is a random (version 4) UUID plus `.encrypted`, e.g.
+/// `3f2b8c1e-9d4a-4f6b-a2c7-5e8d1b0f9a63.encrypted`.
+pub fn opaque_encrypted_name() -> OsString {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    OsString::from(format!(
+        "{}-{}-{}-{}-{}.{}",
+        &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..], ENCRYPTED_EXTENSION
+    ))
+}
+
+/// Get the name of the decrypted file for an encrypted file, using the original name
+/// stored in the file if there is one
+///
+/// A stored name that is not a plain file name (e.g. `../notes.txt`) is not used, so a
+/// crafted file cannot write outside the output folder. Files without a stored name,
+/// or whose header cannot be read with the key, are named by `decrypted_name`.
+///
+/// # Returns
+/// * `Option<OutputName>` - The name, or None if the path has no file name
+pub fn restored_name(source: &Path, key: &EncryptionKey) -> Option<OutputName> {
+    let stored = match encryption::read_stored_name(source, key) {
+        Ok(Some(stored)) => stored,
+        _ => return decrypted_name(source),
+    };
+
+    let name = name_from_stored_bytes(stored);
+    if is_plain_file_name(&name) {
+        Some(platform_safe_name(name))
+    } else {
+        let mut output_name = decrypted_name(source)?;
+        output_name.renamed = Some("the name stored in the file is not a valid file name".to_string());
+        Some(output_name)
+    }
+}
+
+/// Check that a name is a single path component (no separators, `.`, `..`, or NUL)
+fn is_plain_file_name(name: &OsStr) -> bool {
+    !name.is_empty()
+        && !name.to_string_lossy().contains('\0')
+        && Path::new(name).file_name() == Some(name)
+}
+
+/// Get the bytes stored in a file header for a name
+///
+/// Names are stored as they are on Unix, and as UTF-8 elsewhere.
+#[cfg(unix)]
+pub fn stored_name_bytes(name: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    name.as_bytes().to_vec()
+}
+
+/// Get the bytes stored in a file header for a name
+///
+/// Names are stored as they are on Unix, and as UTF-8 elsewhere.
+#[cfg(not(unix))]
+pub fn stored_name_bytes(name: &OsStr) -> Vec<u8> {
+    name.to_string_lossy().into_owned().into_bytes()
+}
+
+/// Get a name from the bytes stored in a file header
+#[cfg(unix)]
+fn name_from_stored_bytes(bytes: Vec<u8>) -> OsString {
+    use std::os::unix::ffi::OsStringExt;
+    OsString::from_vec(bytes)
+}
+
+/// Get a name from the bytes stored in a file header
+///
+/// Names that are not UTF-8 (stored on Unix) are converted lossily.
+#[cfg(not(unix))]
+fn name_from_stored_bytes(bytes: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Make a name valid for files on this platform
+#[cfg(windows)]
+pub fn platform_safe_name(name: OsString) -> OutputName {
+    let text = name.to_string_lossy();
+    match windows_safe_name(&text) {
+        (_, None) => OutputName { name, renamed: None },
+        (safe, Some(reason)) => OutputName { name: OsString::from(safe), renamed: Some(reason) },
+    }
+}
+
+/// Make a name valid for files on this platform
+///
+/// Any name without `/` or NUL is valid outside Windows, and names read from a path
+/// never contain them.
+#[cfg(not(windows))]
+pub fn platform_safe_name(name: OsString) -> OutputName {
+    OutputName { name, renamed: None }
+}
+
+/// Make a name valid on Windows
+///
+/// Windows silently drops trailing dots and spaces, so "report. " would be created as
+/// "report"; they are replaced with `_` instead, as are reserved characters. Reserved
+/// device names (e.g. `CON.txt`) get a `_` prefix.
+///
+/// Only used on Windows, but built everywhere so the rules are tested on every platform.
+///
+/// # Returns
+/// * `(String, Option<String>)` - The name, and why it was changed (None if it is valid)
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn windows_safe_name(name: &str) -> (String, Option<String>) {
+    let mut reasons = Vec::new();
+
+    let mut safe: String = name.chars()
+        .map(|c| if WINDOWS_RESERVED_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+        .collect();
+    if safe != name {
+        reasons.push("it contains characters Windows does not allow");
+    }
+
+    let kept = safe.trim_end_matches(['.', ' ']).len();
+    if kept < safe.len() {
+        let trailing = safe.len() - kept;
+        safe.truncate(kept);
+        safe.push_str(&"_".repeat(trailing));
+        reasons.push("Windows does not allow names ending in a dot or space");
+    }
+
+    let device = safe.split('.').next().unwrap_or_default().trim_end();
+    if WINDOWS_RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(device)) {
+        safe.insert(0, '_');
+        reasons.push("it is a reserved device name on Windows");
+    }
+
+    if reasons.is_empty() {
+        (safe, None)
+    } else {
+        (safe, Some(reasons.join("; ")))
+    }
+}
+
+/// Get the key under which a file system that ignores normalization and case (APFS,
+/// NTFS) stores a name: Unicode NFC, lower case
+pub fn comparison_key(name: &OsStr) -> String {
+    name.to_string_lossy().nfc().collect::<String>().to_lowercase()
+}
+
+/// Find names that a normalization- or case-insensitive file system treats as the same
+///
+/// For example, "café" written with a combining accent (as macOS names files) and with
+/// a precomposed "é" are different names on Linux but the same file on APFS or NTFS.
+///
+/// # Returns
+/// * `Vec<(usize, usize)>` - Index pairs of colliding names (the earlier name first)
+pub fn colliding_names(names: &[OsString]) -> Vec<(usize, usize)> {
+    let mut first_by_key: HashMap<String, usize> = HashMap::new();
+    let mut collisions = Vec::new();
+
+    for (index, name) in names.iter().enumerate() {
+        match first_by_key.get(&comparison_key(name)) {
+            Some(&first) => collisions.push((first, index)),
+            None => {
+                first_by_key.insert(comparison_key(name), index);
+            },
+        }
+    }
+
+    collisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{EncryptionBackend, LocalBackend};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_output_names() {
+        // Combining accent (NFD) and emoji are kept exactly
+        let nfd = "Cafe\u{301} 🦀 notes.txt";
+        assert_eq!(encrypted_name(Path::new(nfd)), Some(OsString::from(format!("{}.encrypted", nfd))));
+        let decrypted = decrypted_name(Path::new(&format!("dir/{}.encrypted", nfd))).unwrap();
+        assert_eq!(decrypted.name, OsString::from(nfd));
+
+        // Only one extension is removed
+        assert_eq!(decrypted_name(Path::new("a.encrypted.encrypted")).unwrap().name, OsString::from("a.encrypted"));
+        assert_eq!(decrypted_name(Path::new("archive.bin")).unwrap().name, OsString::from("archive.bin.decrypted"));
+        assert_eq!(decrypted_name(Path::new("report.pdf.age")).unwrap().name, OsString::from("report.pdf"));
+        assert_eq!(decrypted_name(Path::new("minutes.docx.gpg")).unwrap().name, OsString::from("minutes.docx"));
+        assert_eq!(decrypted_name(Path::new(".encrypted")).unwrap().name, OsString::from(".encrypted.decrypted"));
+        assert_eq!(encrypted_name(Path::new("/")), None);
+    }
+
+    #[test]
+    fn test_windows_safe_names() {
+        assert_eq!(windows_safe_name("résumé 🦀.pdf"), ("résumé 🦀.pdf".to_string(), None));
+
+        let (name, reason) = windows_safe_name("report. ");
+        assert_eq!(name, "report__");
+        assert!(reason.unwrap().contains("ending in a dot or space"));
+
+        assert_eq!(windows_safe_name("a:b?.txt").0, "a_b_.txt");
+        assert_eq!(windows_safe_name("con.txt").0, "_con.txt");
+        assert_eq!(windows_safe_name("NUL").0, "_NUL");
+        assert_eq!(windows_safe_name("console.txt").1, None);
+    }
+
+    #[test]
+    fn test_colliding_names() {
+        let names = [
+            OsString::from("Cafe\u{301}.txt.encrypted"),
+            OsString::from("notes.txt.encrypted"),
+            OsString::from("Caf\u{e9}.txt.encrypted"),
+            OsString::from("NOTES.TXT.encrypted"),
+        ];
+        assert_eq!(colliding_names(&names), vec![(0, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn test_stored_names() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let source = dir.path().join("Quarterly résumé 🦀.pdf");
+        std::fs::write(&source, b"Hidden name").unwrap();
+
+        // The output name is a random UUID and says nothing about the file
+        let opaque = opaque_encrypted_name().into_string().unwrap();
+        let (uuid, extension) = opaque.split_once('.').unwrap();
+        assert_eq!(extension, ENCRYPTED_EXTENSION);
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.as_bytes()[14], b'4');
+        assert_ne!(opaque_encrypted_name(), OsString::from(&opaque));
+
+        let encrypted = dir.path().join(&opaque);
+        LocalBackend::default().encrypt_file_storing_name(&source, &encrypted, &key, source.file_name().unwrap(), |_| {}).unwrap();
+        assert_eq!(restored_name(&encrypted, &key).unwrap().name, OsString::from("Quarterly résumé 🦀.pdf"));
+
+        // The name cannot be read without the key
+        let other_key = EncryptionKey::generate();
+        assert_eq!(restored_name(&encrypted, &other_key).unwrap().name, OsString::from(uuid));
+
+        let decrypted = dir.path().join("out");
+        encryption::decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"Hidden name");
+
+        // Files without a stored name keep the old naming
+        let plain = dir.path().join("plain.txt.encrypted");
+        encryption::encrypt_file(&source, &plain, &key, |_| {}).unwrap();
+        assert_eq!(restored_name(&plain, &key).unwrap().name, OsString::from("plain.txt"));
+
+        // Stored names cannot point outside the output folder
+        for bad_name in ["../escape.txt", "sub/escape.txt", "..", "/etc/passwd"] {
+            let crafted = dir.path().join(format!("{}.encrypted", bad_name.len()));
+            let _ = std::fs::remove_file(&crafted);
+            LocalBackend::default().encrypt_file_storing_name(&source, &crafted, &key, OsStr::new(bad_name), |_| {}).unwrap();
+            let restored = restored_name(&crafted, &key).unwrap();
+            assert_eq!(restored.name, OsString::from(bad_name.len().to_string()));
+            assert!(restored.renamed.unwrap().contains("not a valid file name"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_round_trip() {
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+
+        // "café.txt" in Latin-1, as written by older software
+        let latin1 = OsStr::from_bytes(b"caf\xe9.txt");
+        let source = dir.path().join(latin1);
+        std::fs::write(&source, b"Legacy encoding").unwrap();
+
+        let encrypted = dir.path().join(encrypted_name(&source).unwrap());
+        assert_eq!(encrypted.file_name().unwrap().as_bytes(), b"caf\xe9.txt.encrypted");
+        encryption::encrypt_file(&source, &encrypted, &key, |_| {}).unwrap();
+
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        let restored = decrypted_name(&encrypted).unwrap();
+        assert_eq!(restored.renamed, None);
+        assert_eq!(restored.name.clone().into_vec(), b"caf\xe9.txt");
+
+        let decrypted = output_dir.join(&restored.name);
+        encryption::decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"Legacy encoding");
+    }
+}
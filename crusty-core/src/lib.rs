@@ -0,0 +1,103 @@
+//! CRUSTy core
+//!
+//! The encryption engine of CRUSTy, without the GUI, for the CRUSTy app and other Rust
+//! programs:
+//! - Keys, and encrypting and decrypting data and files with AES-256-GCM
+//! - The CRUSTy file format, with encrypted names, parity, and recipients (age and
+//!   OpenPGP)
+//! - Backends: local (software) encryption, and embedded devices (over USB, a serial port, or
+//!   Ethernet with TLS), and benchmarking them against each other
+//! - Reading files in chunks sized to the drive they are on
+//! - Splitting keys into shares, and the share formats
+//! - The operation log, shared safely between running programs, optionally sent to the
+//!   system log (syslog or the Windows Event Log) too
+//!
+//! The log writes locally; a program that forwards entries elsewhere (as CRUSTy does
+//! when the admin policy requires central logging) gives the logger a `LogForwarder`.
+//!
+//! Everything but `container` is behind the default `native` feature. With
+//! `--no-default-features` the crate is the container format alone, which builds for
+//! wasm32.
+#[cfg(feature = "native")]
+pub mod chunk_tuning;
+pub mod container;
+#[cfg(feature = "native")]
+pub mod encryption;
+#[cfg(feature = "native")]
+pub mod file_names;
+#[cfg(feature = "native")]
+pub mod parity;
+#[cfg(feature = "native")]
+pub mod age_format;
+#[cfg(feature = "native")]
+pub mod openpgp;
+#[cfg(feature = "native")]
+pub mod recipients;
+#[cfg(feature = "native")]
+pub mod logger;
+#[cfg(feature = "native")]
+pub mod system_log;
+#[cfg(feature = "native")]
+pub mod shared_files;
+#[cfg(feature = "native")]
+pub mod backend;
+#[cfg(feature = "native")]
+pub mod backend_local;
+#[cfg(feature = "native")]
+pub mod backend_embedded;
+#[cfg(feature = "native")]
+pub mod embedded_protocol;
+#[cfg(feature = "native")]
+pub mod embedded_usb;
+#[cfg(feature = "native")]
+pub mod embedded_serial;
+#[cfg(feature = "native")]
+pub mod embedded_ethernet;
+#[cfg(feature = "native")]
+pub mod benchmark;
+#[cfg(feature = "native")]
+pub mod split_key;
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use std::fs;
+    use crate::backend::BackendFactory;
+    use crate::encryption::EncryptionKey;
+    use crate::logger::{read_log_file, Logger};
+    use crate::split_key::{KeyPurpose, SplitEncryptionKey};
+
+    #[test]
+    fn test_no_gui_dependencies() {
+        let manifest: toml::Table = toml::from_str(include_str!("../Cargo.toml")).unwrap();
+        let dependencies = manifest["dependencies"].as_table().unwrap();
+        for gui in ["eframe", "egui", "rfd", "tray-icon"] {
+            assert!(!dependencies.contains_key(gui), "crusty-core depends on {}", gui);
+        }
+    }
+
+    #[test]
+    fn test_engine_without_gui() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("contract.pdf");
+        let encrypted = dir.path().join("contract.pdf.encrypted");
+        let decrypted = dir.path().join("contract copy.pdf");
+        fs::write(&source, b"Signed by both parties").unwrap();
+
+        // A key split into shares, two of which give it back
+        let key = EncryptionKey::generate();
+        let split = SplitEncryptionKey::new(&key, 2, 3, KeyPurpose::Standard).unwrap();
+        let shares = vec![split.get_share(0).unwrap().clone(), split.get_share(2).unwrap().clone()];
+        let reconstructed = SplitEncryptionKey::from_shares(shares, 2).unwrap().get_key().unwrap().clone();
+        assert_eq!(reconstructed.fingerprint(), key.fingerprint());
+
+        // Files encrypted with one are decrypted with the other, and the work logged
+        let backend = BackendFactory::create_local();
+        backend.encrypt_file(&source, &encrypted, &key, |_| {}).unwrap();
+        backend.decrypt_file(&encrypted, &decrypted, &reconstructed, |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), b"Signed by both parties");
+
+        let log_path = dir.path().join("operations.log");
+        Logger::new(&log_path).unwrap().log_key_success("Decrypt", "contract.pdf.encrypted", "Done", &key.fingerprint()).unwrap();
+        assert_eq!(read_log_file(&log_path).unwrap()[0].operation, "Decrypt");
+    }
+}
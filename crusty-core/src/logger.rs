@@ -0,0 +1,978 @@
+/// Logger module for tracking encryption and decryption operations.
+///
+/// This module provides functionality for:
+/// - Logging successful and failed operations
+/// - Storing logs in a JSON format, or in a structured format for SIEM tools
+/// - Retrieving log entries for display in the UI, and exporting them (filtered) to
+///   CSV, JSON, or text files
+/// - Forwarding log entries to a central endpoint, through a forwarder the app gives it
+/// - Sending log entries to the system log (syslog or the Windows Event Log)
+/// - Redacting file paths when file names themselves are sensitive
+/// - Rotating the log file by size, keeping a number of old logs (optionally
+///   compressed) and deleting them after a number of days
+///
+/// Several running copies of CRUSTy can log to the same file: each entry is written as
+/// one line while holding the log's lock, so entries never interleave.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use chrono::{Local, NaiveDateTime, SecondsFormat, TimeZone};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::shared_files;
+use crate::system_log::SystemLog;
+
+/// Format of the timestamps of log entries
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Structure representing a single log entry
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LogEntry {
+    /// Timestamp when the log entry was created
+    pub timestamp: String,
+    /// Type of operation (e.g., "Encrypt", "Decrypt", "Load Key")
+    pub operation: String,
+    /// Path of the file that was processed
+    pub file_path: String,
+    /// Whether the operation was successful
+    pub success: bool,
+    /// Detailed message about the operation
+    pub message: String,
+    /// Reason given by the user for the operation (e.g., why a file was decrypted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Fingerprint of the key the operation used (never the key itself)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_fingerprint: Option<String>,
+    /// Time the operation took, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl LogEntry {
+    /// Create a new log entry
+    ///
+    /// # Arguments
+    /// * `operation` - Type of operation
+    /// * `file_path` - Path of the file that was processed
+    /// * `success` - Whether the operation was successful
+    /// * `message` - Detailed message about the operation
+    pub fn new(operation: &str, file_path: &str, success: bool, message: &str) -> Self {
+        LogEntry {
+            timestamp: Local::now().format(TIMESTAMP_FORMAT).to_string(),
+            operation: operation.to_string(),
+            file_path: file_path.to_string(),
+            success,
+            message: message.to_string(),
+            reason: None,
+            key_fingerprint: None,
+            duration_ms: None,
+        }
+    }
+    
+    /// Attach the user's reason for the operation
+    pub fn with_reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+    
+    /// Attach the fingerprint of the key the operation used
+    pub fn with_key_fingerprint(mut self, key_fingerprint: &str) -> Self {
+        self.key_fingerprint = Some(key_fingerprint.to_string());
+        self
+    }
+    
+    /// Attach the time the operation took
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration_ms = Some(duration.as_millis().try_into().unwrap_or(u64::MAX));
+        self
+    }
+}
+
+/// How entries are written to the log file
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// One `LogEntry` per line
+    #[default]
+    Standard,
+    /// One `StructuredLogRecord` per line, for SIEM tools
+    Structured,
+}
+
+impl LogFormat {
+    /// All formats, in the order they are offered
+    pub const ALL: [LogFormat; 2] = [LogFormat::Standard, LogFormat::Structured];
+
+    /// Get the name of the format, as shown in the settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogFormat::Standard => "CRUSTy",
+            LogFormat::Structured => "Structured (SIEM)",
+        }
+    }
+}
+
+/// Outcome of a logged operation, in structured records
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogOutcome {
+    Success,
+    Failure,
+}
+
+/// A log entry in the structured format
+///
+/// Every field is always written (null when unknown), and the timestamp is RFC 3339
+/// with the UTC offset, so SIEM tools can ingest the log without a custom parser.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StructuredLogRecord {
+    /// When the entry was created (e.g. `2025-03-04T14:05:09+01:00`)
+    pub timestamp: String,
+    /// Always "crusty", to tell CRUSTy's records from others
+    pub source: String,
+    pub operation: String,
+    pub file: String,
+    pub key_fingerprint: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub outcome: LogOutcome,
+    pub message: String,
+    pub reason: Option<String>,
+}
+
+impl StructuredLogRecord {
+    /// Get the structured record of a log entry
+    pub fn from_entry(entry: &LogEntry) -> Self {
+        let timestamp = NaiveDateTime::parse_from_str(&entry.timestamp, TIMESTAMP_FORMAT).ok()
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .map_or_else(|| entry.timestamp.clone(), |time| time.to_rfc3339_opts(SecondsFormat::Secs, false));
+        StructuredLogRecord {
+            timestamp,
+            source: "crusty".to_string(),
+            operation: entry.operation.clone(),
+            file: entry.file_path.clone(),
+            key_fingerprint: entry.key_fingerprint.clone(),
+            duration_ms: entry.duration_ms,
+            outcome: if entry.success { LogOutcome::Success } else { LogOutcome::Failure },
+            message: entry.message.clone(),
+            reason: entry.reason.clone(),
+        }
+    }
+
+    /// Get the log entry of a structured record, with its timestamp in local time
+    pub fn into_entry(self) -> LogEntry {
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+            .map_or(self.timestamp, |time| time.with_timezone(&Local).format(TIMESTAMP_FORMAT).to_string());
+        LogEntry {
+            timestamp,
+            operation: self.operation,
+            file_path: self.file,
+            success: self.outcome == LogOutcome::Success,
+            message: self.message,
+            reason: self.reason,
+            key_fingerprint: self.key_fingerprint,
+            duration_ms: self.duration_ms,
+        }
+    }
+}
+
+/// How file paths are written to the log
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PathRedaction {
+    /// Log full paths
+    #[default]
+    None,
+    /// Replace paths with an ID
+    Hash,
+    /// Keep the start of the file name and the extension, followed by an ID
+    Truncate,
+}
+
+/// Stable ID for a path, so redacted entries for the same file can be correlated
+fn path_id(path: &str, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update([0u8]);
+    hasher.update(path.as_bytes());
+    hasher.finalize()[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Redact a file path according to the redaction mode
+///
+/// # Arguments
+/// * `path` - The path to redact
+/// * `mode` - How to redact the path
+/// * `salt` - Secret mixed into path IDs so they cannot be matched against guessed paths
+///
+/// # Returns
+/// * `String` - The redacted path
+pub fn redact_path(path: &str, mode: PathRedaction, salt: &str) -> String {
+    if path.is_empty() || mode == PathRedaction::None {
+        return path.to_string();
+    }
+    
+    let id = path_id(path, salt);
+    
+    match mode {
+        PathRedaction::None => path.to_string(),
+        PathRedaction::Hash => format!("[path:{}]", id),
+        PathRedaction::Truncate => {
+            let file_name = Path::new(path).file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let (stem, extension) = match file_name.rfind('.') {
+                Some(dot) if dot > 0 => (&file_name[..dot], &file_name[dot..]),
+                _ => (file_name.as_str(), ""),
+            };
+            let prefix: String = stem.chars().take(3).collect();
+            
+            format!("{}...{} [path:{}]", prefix, extension, id)
+        }
+    }
+}
+
+/// When the log file is rotated, and how long old logs are kept
+///
+/// Old logs are named after the log with a number, the newest first
+/// (`operations.log.1`, `operations.log.2`, ...), and end in `.gz` when compressed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct LogRotation {
+    /// Size the log may reach before it is rotated, in MB (0 never rotates it)
+    pub max_size_mb: u64,
+    /// Old logs kept (the oldest are deleted)
+    pub max_files: u32,
+    /// Days old logs are kept (0 keeps them until there are more than `max_files`)
+    pub max_age_days: u32,
+    /// Compress old logs with gzip
+    pub compress: bool,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        LogRotation {
+            max_size_mb: 10,
+            max_files: 5,
+            max_age_days: 0,
+            compress: true,
+        }
+    }
+}
+
+impl LogRotation {
+    /// Get the size the log may reach, in bytes (None if it is never rotated)
+    pub fn max_bytes(&self) -> Option<u64> {
+        (self.max_size_mb > 0).then(|| self.max_size_mb.saturating_mul(1024 * 1024))
+    }
+}
+
+/// Get the old logs of a log file with their numbers, the newest first
+fn rotated_logs(log_path: &Path) -> io::Result<Vec<(u32, PathBuf)>> {
+    let (Some(dir), Some(name)) = (log_path.parent(), log_path.file_name()) else {
+        return Ok(Vec::new());
+    };
+    let prefix = format!("{}.", name.to_string_lossy());
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+
+    let mut logs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(suffix) = file_name.strip_prefix(&prefix) else { continue };
+        if let Ok(number) = suffix.trim_end_matches(".gz").parse::<u32>() {
+            logs.push((number, entry.path()));
+        }
+    }
+    logs.sort_by_key(|(number, _)| *number);
+    Ok(logs)
+}
+
+/// Get the name of an old log with another number, keeping its `.gz`
+fn renumbered(log_path: &Path, old_log: &Path, number: u32) -> PathBuf {
+    let compressed = old_log.extension().is_some_and(|extension| extension == "gz");
+    let mut name = log_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}{}", number, if compressed { ".gz" } else { "" }));
+    log_path.with_file_name(name)
+}
+
+/// Compress a file with gzip to `<file>.gz`, and delete it
+fn compress_file(path: &Path) -> io::Result<()> {
+    let mut compressed_name = path.file_name().unwrap_or_default().to_os_string();
+    compressed_name.push(".gz");
+    let compressed_path = path.with_file_name(compressed_name);
+
+    let mut encoder = GzEncoder::new(File::create(&compressed_path)?, Compression::default());
+    io::copy(&mut File::open(path)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)
+}
+
+/// Delete the old logs over the number kept, and those older than the days kept
+fn prune_rotated_logs(log_path: &Path, rotation: &LogRotation) -> io::Result<()> {
+    let max_age = (rotation.max_age_days > 0)
+        .then(|| Duration::from_secs(u64::from(rotation.max_age_days) * 24 * 3600));
+    for (number, path) in rotated_logs(log_path)? {
+        let expired = max_age.is_some_and(|max_age| {
+            fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| SystemTime::now().duration_since(modified).unwrap_or_default() > max_age)
+        });
+        if number > rotation.max_files || expired {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rotate a log file: number it as the newest old log, renumbering the others, and
+/// delete the old logs that are no longer kept
+///
+/// The caller holds the log's lock.
+fn rotate_log(log_path: &Path, rotation: &LogRotation) -> io::Result<()> {
+    for (number, path) in rotated_logs(log_path)?.into_iter().rev() {
+        fs::rename(&path, renumbered(log_path, &path, number + 1))?;
+    }
+
+    let newest = renumbered(log_path, log_path, 1);
+    fs::rename(log_path, &newest)?;
+    if rotation.compress {
+        compress_file(&newest)?;
+    }
+
+    prune_rotated_logs(log_path, rotation)
+}
+
+/// Open a log file for appending, creating it if it doesn't exist
+fn open_log(log_path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+}
+
+/// Read all entries from a log file, skipping lines that are not valid entries
+///
+/// Lines in either format are read, as the format may have been changed while the
+/// file was in use.
+///
+/// # Arguments
+/// * `log_path` - Path to the log file
+///
+/// # Returns
+/// * `io::Result<Vec<LogEntry>>` - The entries in the file or an error
+pub fn read_log_file(log_path: &Path) -> io::Result<Vec<LogEntry>> {
+    let content = std::fs::read_to_string(log_path)?;
+    Ok(content.lines()
+        .filter_map(|line| serde_json::from_str(line).ok()
+            .or_else(|| serde_json::from_str::<StructuredLogRecord>(line).ok().map(StructuredLogRecord::into_entry)))
+        .collect())
+}
+
+/// Quote a value for a CSV field
+pub fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// Write a compliance report (CSV) of decryption activity and the reasons given
+///
+/// # Arguments
+/// * `entries` - Log entries to report on
+/// * `report_path` - Path of the CSV file to write
+///
+/// # Returns
+/// * `io::Result<usize>` - Number of entries in the report or an error
+pub fn write_compliance_report(entries: &[LogEntry], report_path: &Path) -> io::Result<usize> {
+    let mut file = File::create(report_path)?;
+    writeln!(file, "Timestamp,Operation,File,Success,Reason,Message")?;
+    
+    let mut count = 0;
+    for entry in entries.iter().filter(|e| e.operation.starts_with("Decrypt")) {
+        writeln!(
+            file,
+            "{},{},{},{},{},{}",
+            csv_field(&entry.timestamp),
+            csv_field(&entry.operation),
+            csv_field(&entry.file_path),
+            entry.success,
+            csv_field(entry.reason.as_deref().unwrap_or("")),
+            csv_field(&entry.message)
+        )?;
+        count += 1;
+    }
+    
+    file.flush()?;
+    Ok(count)
+}
+
+/// Entries shown or exported from the log
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogFilter {
+    /// Text the operation, file, or message must contain, in any case (any entry when empty)
+    pub text: String,
+    /// Only failed operations
+    pub failures_only: bool,
+}
+
+impl LogFilter {
+    /// Check whether a log entry passes the filter
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        let text = self.text.trim().to_lowercase();
+        let text_matches = text.is_empty()
+            || [&entry.operation, &entry.file_path, &entry.message].iter().any(|field| field.to_lowercase().contains(&text));
+        text_matches && !(self.failures_only && entry.success)
+    }
+}
+
+/// Format of an exported log
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogExportFormat {
+    /// One line per entry, with a header
+    Csv,
+    /// An array of entries
+    Json,
+    /// One readable line per entry
+    Text,
+}
+
+impl LogExportFormat {
+    /// Get the format for a file name: CSV for `.csv`, JSON for `.json`, otherwise text
+    pub fn for_path(path: &Path) -> Self {
+        let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => LogExportFormat::Csv,
+            Some("json") => LogExportFormat::Json,
+            _ => LogExportFormat::Text,
+        }
+    }
+}
+
+/// Write log entries to an export file
+///
+/// # Arguments
+/// * `entries` - The entries to write
+/// * `export_path` - Path of the file to write
+/// * `format` - Format of the file
+///
+/// # Returns
+/// * `io::Result<()>` - Success or an error
+pub fn write_log_export(entries: &[LogEntry], export_path: &Path, format: LogExportFormat) -> io::Result<()> {
+    let mut file = io::BufWriter::new(File::create(export_path)?);
+    match format {
+        LogExportFormat::Csv => {
+            writeln!(file, "Timestamp,Operation,File,Success,Message,Reason,Key Fingerprint,Duration (ms)")?;
+            for entry in entries {
+                writeln!(
+                    file,
+                    "{},{},{},{},{},{},{},{}",
+                    csv_field(&entry.timestamp),
+                    csv_field(&entry.operation),
+                    csv_field(&entry.file_path),
+                    entry.success,
+                    csv_field(&entry.message),
+                    csv_field(entry.reason.as_deref().unwrap_or("")),
+                    csv_field(entry.key_fingerprint.as_deref().unwrap_or("")),
+                    entry.duration_ms.map(|duration| duration.to_string()).unwrap_or_default()
+                )?;
+            }
+        },
+        LogExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut file, entries)?;
+            writeln!(file)?;
+        },
+        LogExportFormat::Text => {
+            for entry in entries {
+                let mut line = format!(
+                    "{}  {}  {}",
+                    entry.timestamp,
+                    if entry.success { "OK    " } else { "FAILED" },
+                    entry.operation
+                );
+                if !entry.file_path.is_empty() {
+                    line.push_str(&format!("  {}", entry.file_path));
+                }
+                line.push_str(&format!(": {}", entry.message));
+                if let Some(reason) = &entry.reason {
+                    line.push_str(&format!(" (reason: {})", reason));
+                }
+                if let Some(key_fingerprint) = &entry.key_fingerprint {
+                    line.push_str(&format!(" [key {}]", key_fingerprint));
+                }
+                writeln!(file, "{}", line)?;
+            }
+        },
+    }
+    file.flush()
+}
+
+/// Receiver of every entry logged, such as a forwarder to a central endpoint
+pub trait LogForwarder: Send + Sync {
+    /// Queue a log entry for forwarding (after it is written to the log file)
+    fn forward(&self, entry: &LogEntry);
+}
+
+/// Logger implementation for tracking operations
+#[derive(Clone)]
+pub struct Logger {
+    /// File handle for writing logs
+    log_file: Arc<Mutex<File>>,
+    /// Path of the log file (locked while an entry is written)
+    log_path: PathBuf,
+    /// In-memory cache of log entries
+    entries: Arc<Mutex<Vec<LogEntry>>>,
+    /// Forwarder for the central logging endpoint (if enabled)
+    remote: Option<Arc<dyn LogForwarder>>,
+    /// How file paths are written to the log
+    redaction: PathRedaction,
+    /// Secret mixed into redacted path IDs
+    redaction_salt: String,
+    /// When the log file is rotated (shared by every copy of the logger)
+    rotation: Arc<Mutex<LogRotation>>,
+    /// How entries are written to the log file (shared by every copy of the logger)
+    format: Arc<Mutex<LogFormat>>,
+    /// System log entries are also sent to, if any (shared by every copy of the logger)
+    system_log: Arc<Mutex<Option<SystemLog>>>,
+}
+
+impl Logger {
+    /// Create a new logger that writes to the specified file
+    ///
+    /// # Arguments
+    /// * `log_path` - Path to the log file
+    ///
+    /// # Returns
+    /// * `io::Result<Logger>` - A new logger instance or an error
+    pub fn new(log_path: &Path) -> io::Result<Self> {
+        // Create log directory if it doesn't exist
+        if let Some(parent) = log_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        // Open log file (create if it doesn't exist, append if it does)
+        let file = open_log(log_path)?;
+            
+        Ok(Logger {
+            log_file: Arc::new(Mutex::new(file)),
+            log_path: log_path.to_path_buf(),
+            entries: Arc::new(Mutex::new(Vec::new())),
+            remote: None,
+            redaction: PathRedaction::None,
+            redaction_salt: String::new(),
+            rotation: Arc::new(Mutex::new(LogRotation { max_size_mb: 0, ..LogRotation::default() })),
+            format: Arc::new(Mutex::new(LogFormat::Standard)),
+            system_log: Arc::new(Mutex::new(None)),
+        })
+    }
+    
+    /// Redact file paths in all future log entries
+    ///
+    /// # Arguments
+    /// * `mode` - How to redact file paths
+    /// * `salt` - Secret mixed into redacted path IDs
+    pub fn set_path_redaction(&mut self, mode: PathRedaction, salt: &str) {
+        self.redaction = mode;
+        self.redaction_salt = salt.to_string();
+    }
+    
+    /// Redact a path mentioned in a log message, as file paths are redacted
+    ///
+    /// Only the entry's own file path is redacted by `log`, so any other path written
+    /// into a message (an output file, a folder) must go through this first.
+    ///
+    /// # Arguments
+    /// * `path` - The path to mention
+    ///
+    /// # Returns
+    /// * `String` - The path as it may be written to the log
+    pub fn redact_path(&self, path: &Path) -> String {
+        redact_path(&path.to_string_lossy(), self.redaction, &self.redaction_salt)
+    }
+    
+    /// Rotate the log file as specified from now on, in every copy of this logger
+    ///
+    /// Old logs that are no longer kept are deleted right away.
+    ///
+    /// # Arguments
+    /// * `rotation` - When the log is rotated, and how long old logs are kept
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Success or an error deleting old logs
+    pub fn set_rotation(&self, rotation: LogRotation) -> io::Result<()> {
+        *self.rotation.lock().unwrap() = rotation.clone();
+        
+        let _lock = shared_files::lock(&self.log_path)?;
+        prune_rotated_logs(&self.log_path, &rotation)
+    }
+    
+    /// Write entries in the specified format from now on, in every copy of this logger
+    pub fn set_format(&self, format: LogFormat) {
+        *self.format.lock().unwrap() = format;
+    }
+    
+    /// Send future log entries to the system log too, in every copy of this logger
+    ///
+    /// # Arguments
+    /// * `system_log` - A connection to the system log (None to stop sending entries)
+    pub fn set_system_log(&self, system_log: Option<SystemLog>) {
+        *self.system_log.lock().unwrap() = system_log;
+    }
+    
+    /// Check whether entries are sent to the system log
+    pub fn sends_to_system_log(&self) -> bool {
+        self.system_log.lock().unwrap().is_some()
+    }
+    
+    /// Forward all future log entries to a central endpoint
+    ///
+    /// # Arguments
+    /// * `forwarder` - A running remote log forwarder
+    pub fn set_remote_forwarder(&mut self, forwarder: impl LogForwarder + 'static) {
+        self.remote = Some(Arc::new(forwarder));
+    }
+    
+    /// Log an operation
+    ///
+    /// # Arguments
+    /// * `entry` - The log entry to record
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Success or an error
+    pub fn log(&self, mut entry: LogEntry) -> io::Result<()> {
+        // Redact the path, including any mention of it in the message
+        if self.redaction != PathRedaction::None && !entry.file_path.is_empty() {
+            let redacted = redact_path(&entry.file_path, self.redaction, &self.redaction_salt);
+            entry.message = entry.message.replace(&entry.file_path, &redacted);
+            entry.file_path = redacted;
+        }
+        
+        // Add log entry to memory cache
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries.push(entry.clone());
+        }
+        
+        // Write log entry to file, in one write under the lock shared with other
+        // running copies of CRUSTy
+        let json = match *self.format.lock().unwrap() {
+            LogFormat::Standard => serde_json::to_string(&entry)?,
+            LogFormat::Structured => serde_json::to_string(&StructuredLogRecord::from_entry(&entry))?,
+        };
+        let line = format!("{}\n", json);
+        {
+            let mut file = self.log_file.lock().unwrap();
+            let _lock = shared_files::lock(&self.log_path)?;
+            let rotation = self.rotation.lock().unwrap().clone();
+            
+            // If another running copy rotated the log, the file open here is now an
+            // old log. Entries are only written under the lock, so the file at the log
+            // path has the same size as the open one unless it is another file.
+            let current_size = fs::metadata(&self.log_path).map(|metadata| metadata.len()).ok();
+            if current_size != Some(file.metadata()?.len()) {
+                *file = open_log(&self.log_path)?;
+            }
+            
+            file.write_all(line.as_bytes())?;
+            file.flush()?;
+            
+            // Rotate after the entry is written, so it is kept even if rotating fails
+            if rotation.max_bytes().is_some_and(|max_bytes| file.metadata().is_ok_and(|metadata| metadata.len() >= max_bytes)) {
+                let rotated = rotate_log(&self.log_path, &rotation);
+                *file = open_log(&self.log_path)?;
+                rotated?;
+            }
+        }
+        
+        // Queue the entry for the central endpoint
+        if let Some(remote) = &self.remote {
+            remote.forward(&entry);
+        }
+        
+        if let Some(system_log) = &*self.system_log.lock().unwrap() {
+            system_log.forward(&entry);
+        }
+        
+        Ok(())
+    }
+    
+    /// Export the entries of the log file that pass a filter
+    ///
+    /// The format is chosen from the export file's name (see `LogExportFormat::for_path`).
+    ///
+    /// # Arguments
+    /// * `export_path` - Path of the file to write
+    /// * `filter` - Entries to export
+    ///
+    /// # Returns
+    /// * `io::Result<usize>` - Number of entries exported or an error
+    pub fn export(&self, export_path: &Path, filter: &LogFilter) -> io::Result<usize> {
+        let entries: Vec<LogEntry> = read_log_file(&self.log_path)?.into_iter()
+            .filter(|entry| filter.matches(entry))
+            .collect();
+        write_log_export(&entries, export_path, LogExportFormat::for_path(export_path))?;
+        Ok(entries.len())
+    }
+    
+    /// Get all log entries
+    ///
+    /// # Returns
+    /// * `Vec<LogEntry>` - All log entries in memory
+    pub fn get_entries(&self) -> Vec<LogEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.clone()
+    }
+    
+    /// Log a successful operation
+    ///
+    /// # Arguments
+    /// * `operation` - Type of operation
+    /// * `file_path` - Path of the file that was processed
+    /// * `message` - Detailed message about the operation
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Success or an error
+    pub fn log_success(&self, operation: &str, file_path: &str, message: &str) -> io::Result<()> {
+        self.log(LogEntry::new(operation, file_path, true, message))
+    }
+    
+    /// Log a failed operation
+    ///
+    /// # Arguments
+    /// * `operation` - Type of operation
+    /// * `file_path` - Path of the file that was processed
+    /// * `error` - Error message
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Success or an error
+    pub fn log_error(&self, operation: &str, file_path: &str, error: &str) -> io::Result<()> {
+        self.log(LogEntry::new(operation, file_path, false, error))
+    }
+    
+    /// Log a successful operation done with a key
+    ///
+    /// # Arguments
+    /// * `operation` - Type of operation
+    /// * `file_path` - Path of the file that was processed
+    /// * `message` - Detailed message about the operation
+    /// * `key_fingerprint` - Fingerprint of the key used
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Success or an error
+    pub fn log_key_success(&self, operation: &str, file_path: &str, message: &str, key_fingerprint: &str) -> io::Result<()> {
+        self.log(LogEntry::new(operation, file_path, true, message).with_key_fingerprint(key_fingerprint))
+    }
+    
+    /// Log a failed operation done with a key
+    ///
+    /// # Arguments
+    /// * `operation` - Type of operation
+    /// * `file_path` - Path of the file that was processed
+    /// * `error` - Error message
+    /// * `key_fingerprint` - Fingerprint of the key used
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - Success or an error
+    pub fn log_key_error(&self, operation: &str, file_path: &str, error: &str, key_fingerprint: &str) -> io::Result<()> {
+        self.log(LogEntry::new(operation, file_path, false, error).with_key_fingerprint(key_fingerprint))
+    }
+}
+
+// Create a singleton logger for the application
+lazy_static::lazy_static! {
+    static ref APP_LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+}
+
+/// Initialize the global logger
+///
+/// # Arguments
+/// * `logger` - The logger, with its path redaction and forwarder set
+pub fn init_logger(logger: Logger) {
+    let mut app_logger = APP_LOGGER.lock().unwrap();
+    *app_logger = Some(logger);
+}
+
+/// Get the global logger
+///
+/// # Returns
+/// * `Option<Arc<Logger>>` - The global logger or None if not initialized
+pub fn get_logger() -> Option<Arc<Logger>> {
+    let app_logger = APP_LOGGER.lock().unwrap();
+    app_logger.as_ref().map(|logger| Arc::new(logger.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_path() {
+        let path = "/home/user/Contracts/merger-plan.pdf";
+
+        let hashed = redact_path(path, PathRedaction::Hash, "salt");
+        assert!(hashed.starts_with("[path:") && !hashed.contains("merger"));
+
+        let truncated = redact_path(path, PathRedaction::Truncate, "salt");
+        assert!(truncated.starts_with("mer....pdf [path:") && !truncated.contains("Contracts"));
+
+        // The same path always gets the same ID; the salt changes it
+        assert_eq!(hashed, redact_path(path, PathRedaction::Hash, "salt"));
+        assert_ne!(hashed, redact_path(path, PathRedaction::Hash, "other"));
+        assert_eq!(redact_path(path, PathRedaction::None, "salt"), path);
+    }
+
+    #[test]
+    fn test_shared_log_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("operations.log");
+
+        // Two running copies of CRUSTy logging to the same file
+        let first = Logger::new(&log_path).unwrap();
+        let second = Logger::new(&log_path).unwrap();
+        let threads: Vec<_> = [first, second].into_iter().enumerate()
+            .map(|(copy, logger)| std::thread::spawn(move || {
+                for i in 0..50 {
+                    logger.log_success("Encrypt", &format!("copy-{}/file-{}", copy, i), "Done").unwrap();
+                }
+            }))
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let entries = read_log_file(&log_path).unwrap();
+        assert_eq!(entries.len(), 100);
+        assert!(entries.iter().any(|entry| entry.file_path == "copy-1/file-49"));
+    }
+
+    #[test]
+    fn test_structured_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("operations.log");
+        let logger = Logger::new(&log_path).unwrap();
+
+        logger.log_success("Load Key", "", "Loaded").unwrap();
+        logger.set_format(LogFormat::Structured);
+        let entry = LogEntry::new("Decrypt", "report.txt.encrypted", false, "Wrong key")
+            .with_key_fingerprint("3f2a9c1d")
+            .with_duration(Duration::from_millis(1500));
+        logger.log(entry.clone()).unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let record: serde_json::Value = serde_json::from_str(content.lines().nth(1).unwrap()).unwrap();
+        assert_eq!(record["outcome"], "failure");
+        assert_eq!(record["file"], "report.txt.encrypted");
+        assert_eq!(record["duration_ms"], 1500);
+        assert_eq!(record["key_fingerprint"], "3f2a9c1d");
+        assert!(record["reason"].is_null());
+        assert!(chrono::DateTime::parse_from_rfc3339(record["timestamp"].as_str().unwrap()).is_ok());
+
+        // Both formats are read back, with the same timestamps
+        let entries = read_log_file(&log_path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, "Load Key");
+        assert!(!entries[1].success);
+        assert_eq!(entries[1].timestamp, entry.timestamp);
+        assert_eq!(entries[1].duration_ms, Some(1500));
+    }
+
+    #[test]
+    fn test_compliance_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("operations.log");
+        let logger = Logger::new(&log_path).unwrap();
+        logger.log(LogEntry::new("Decrypt Request", "payroll.xlsx.encrypted", true, "Decryption requested")
+            .with_reason("Audit request \"42\", Q3")).unwrap();
+        logger.log_success("Encrypt", "notes.txt", "Encryption successful").unwrap();
+        // The reason is read back from either format
+        logger.set_format(LogFormat::Structured);
+        logger.log(LogEntry::new("Decrypt", "payroll.xlsx.encrypted", false, "Wrong key")
+            .with_reason("Audit request 43")).unwrap();
+
+        let entries = read_log_file(&log_path).unwrap();
+        assert_eq!(entries[0].reason.as_deref(), Some("Audit request \"42\", Q3"));
+        assert_eq!(entries[1].reason, None);
+        assert_eq!(entries[2].reason.as_deref(), Some("Audit request 43"));
+
+        // Only decryption is reported
+        let report_path = dir.path().join("compliance.csv");
+        assert_eq!(write_compliance_report(&entries, &report_path).unwrap(), 2);
+        let report = fs::read_to_string(&report_path).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Timestamp,Operation,File,Success,Reason,Message");
+        assert!(lines[1].ends_with(",\"Decrypt Request\",\"payroll.xlsx.encrypted\",true,\"Audit request \"\"42\"\", Q3\",\"Decryption requested\""));
+        assert!(lines[2].ends_with(",\"Decrypt\",\"payroll.xlsx.encrypted\",false,\"Audit request 43\",\"Wrong key\""));
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn test_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let logger = Logger::new(&dir.path().join("operations.log")).unwrap();
+        logger.log_success("Encrypt", "report, final.txt", "Encryption successful").unwrap();
+        logger.log_key_error("Decrypt", "photo.jpg.encrypted", "Wrong key", "3f2a9c1d").unwrap();
+        logger.log_error("Encrypt", "notes.txt", "Permission denied").unwrap();
+
+        let failures = LogFilter { text: String::new(), failures_only: true };
+        let csv_path = dir.path().join("failures.csv");
+        assert_eq!(logger.export(&csv_path, &failures).unwrap(), 2);
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains(",\"Decrypt\",\"photo.jpg.encrypted\",false,\"Wrong key\",\"\",\"3f2a9c1d\","));
+
+        let encrypt = LogFilter { text: "ENCRYPT".to_string(), failures_only: false };
+        let json_path = dir.path().join("encrypt.json");
+        assert_eq!(logger.export(&json_path, &encrypt).unwrap(), 3);
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json[0]["file_path"], "report, final.txt");
+
+        let report = LogFilter { text: "report".to_string(), failures_only: false };
+        let text_path = dir.path().join("report.log");
+        assert_eq!(logger.export(&text_path, &report).unwrap(), 1);
+        assert!(fs::read_to_string(&text_path).unwrap().trim_end().ends_with("OK      Encrypt  report, final.txt: Encryption successful"));
+    }
+
+    #[test]
+    fn test_log_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("operations.log");
+        let logger = Logger::new(&log_path).unwrap();
+        let other_copy = Logger::new(&log_path).unwrap();
+
+        // A log that may not reach 1 MB, and two old logs kept
+        logger.set_rotation(LogRotation { max_size_mb: 1, max_files: 2, max_age_days: 0, compress: false }).unwrap();
+        let message = "x".repeat(100 * 1024);
+        for i in 0..35 {
+            logger.log_success("Encrypt", &format!("file-{}", i), &message).unwrap();
+        }
+
+        // Rotated after the 11th and 22nd entries (and the 33rd, dropping the oldest)
+        let old_logs: Vec<u32> = rotated_logs(&log_path).unwrap().into_iter().map(|(number, _)| number).collect();
+        assert_eq!(old_logs, vec![1, 2]);
+        assert_eq!(read_log_file(&log_path).unwrap().len(), 2);
+        assert_eq!(read_log_file(&log_path.with_file_name("operations.log.1")).unwrap()[0].file_path, "file-22");
+
+        // Another copy of the logger follows the log to its new file
+        other_copy.log_success("Decrypt", "file-35", "Done").unwrap();
+        assert_eq!(read_log_file(&log_path).unwrap().len(), 3);
+
+        // Old logs are compressed when asked to
+        other_copy.set_rotation(LogRotation { max_size_mb: 1, max_files: 1, max_age_days: 0, compress: true }).unwrap();
+        assert_eq!(rotated_logs(&log_path).unwrap().len(), 1);
+        for i in 36..50 {
+            other_copy.log_success("Encrypt", &format!("file-{}", i), &message).unwrap();
+        }
+        let old_logs = rotated_logs(&log_path).unwrap();
+        assert_eq!(old_logs.len(), 1);
+        assert_eq!(old_logs[0].1, log_path.with_file_name("operations.log.1.gz"));
+
+        let mut decompressed = String::new();
+        io::Read::read_to_string(&mut flate2::read::GzDecoder::new(File::open(&old_logs[0].1).unwrap()), &mut decompressed).unwrap();
+        assert!(decompressed.lines().next().unwrap().contains("file-33"));
+        assert_eq!(read_log_file(&log_path).unwrap()[0].file_path, "file-45");
+    }
+}
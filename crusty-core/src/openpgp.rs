@@ -0,0 +1,1241 @@
+/// OpenPGP module.
+///
+/// This module provides functionality for:
+/// - Encrypting files as OpenPGP messages, so people who use GnuPG can decrypt them
+///   with `gpg -d`
+/// - Decrypting OpenPGP messages made by GnuPG (binary or armored)
+/// - Encrypting and decrypting messages in memory, for data piped through CRUSTy
+/// - Exporting a key as an OpenPGP public key, and reading other people's OpenPGP
+///   public keys to encrypt files for them
+///
+/// Every CRUSTy key has an OpenPGP key derived from it with HKDF: an Ed25519 primary
+/// key and a Curve25519 encryption subkey, created at a fixed time so the fingerprint
+/// never changes. Its public key can be imported with `gpg --import`, and files GnuPG
+/// encrypts to it decrypt in CRUSTy with the key. Its secret parts never leave CRUSTy.
+///
+/// Only what exchanging files needs is implemented: version 4 keys with RSA or
+/// Curve25519 encryption keys, and messages with integrity protection (SEIPD version 1
+/// with AES, uncompressed or ZIP/ZLIB compressed). Signatures are made on exported
+/// keys only; signatures on imported keys and messages are not checked, so compare an
+/// imported key's fingerprint with its owner. Messages encrypted with a passphrase
+/// (`gpg -c`) or in the AEAD (OCB) format are not supported.
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use aes::{Aes128, Aes192, Aes256};
+use aes_kw::{KekAes128, KekAes192, KekAes256};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use cfb_mode::cipher::{AsyncStreamCipher, BlockCipher, BlockEncryptMut, KeyInit, KeyIvInit};
+use chrono::Utc;
+use ed25519_dalek::{Signer, SigningKey};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use hkdf::Hkdf;
+use rand::RngCore;
+use rsa::{BigUint, Pkcs1v15Encrypt, RsaPublicKey};
+use serde::{Serialize, Deserialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::chunk_tuning;
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+
+/// Packet tags (RFC 9580 section 5)
+const TAG_PKESK: u8 = 1;
+const TAG_SIGNATURE: u8 = 2;
+const TAG_SKESK: u8 = 3;
+const TAG_ONE_PASS_SIGNATURE: u8 = 4;
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_COMPRESSED: u8 = 8;
+const TAG_SED: u8 = 9;
+const TAG_MARKER: u8 = 10;
+const TAG_LITERAL: u8 = 11;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_SEIPD: u8 = 18;
+const TAG_AEAD: u8 = 20;
+
+/// Public key algorithms
+const ALGO_RSA: u8 = 1;
+const ALGO_RSA_ENCRYPT: u8 = 2;
+const ALGO_ECDH: u8 = 18;
+const ALGO_EDDSA: u8 = 22;
+
+/// Symmetric algorithms
+const CIPHER_AES128: u8 = 7;
+const CIPHER_AES192: u8 = 8;
+const CIPHER_AES256: u8 = 9;
+
+/// Hash algorithms
+const HASH_SHA256: u8 = 8;
+const HASH_SHA384: u8 = 9;
+const HASH_SHA512: u8 = 10;
+
+/// Signature types
+const SIG_POSITIVE_CERTIFICATION: u8 = 0x13;
+const SIG_SUBKEY_BINDING: u8 = 0x18;
+const SIG_KEY_REVOCATION: u8 = 0x20;
+const SIG_SUBKEY_REVOCATION: u8 = 0x28;
+
+/// Signature subpackets
+const SUBPACKET_CREATION_TIME: u8 = 2;
+const SUBPACKET_KEY_EXPIRATION: u8 = 9;
+const SUBPACKET_PREFERRED_CIPHERS: u8 = 11;
+const SUBPACKET_ISSUER: u8 = 16;
+const SUBPACKET_PREFERRED_HASHES: u8 = 21;
+const SUBPACKET_PREFERRED_COMPRESSION: u8 = 22;
+const SUBPACKET_PRIMARY_USER_ID: u8 = 25;
+const SUBPACKET_KEY_FLAGS: u8 = 27;
+const SUBPACKET_FEATURES: u8 = 30;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+/// Key flags allowing encryption (of communications and of storage)
+const FLAGS_ENCRYPT: u8 = 0x04 | 0x08;
+
+/// Curve OIDs of Ed25519 (signing) and Curve25519 (encryption) keys
+const ED25519_OID: &[u8] = &[0x2B, 0x06, 0x01, 0x04, 0x01, 0xDA, 0x47, 0x0F, 0x01];
+const CV25519_OID: &[u8] = &[0x2B, 0x06, 0x01, 0x04, 0x01, 0x97, 0x55, 0x01, 0x05, 0x01];
+
+/// Creation time of every derived key (2020-01-01), fixed so its fingerprint never changes
+const KEY_CREATION_TIME: u32 = 1_577_836_800;
+
+/// Armor labels
+#[cfg(test)]
+const ARMOR_MESSAGE: &str = "PGP MESSAGE";
+const ARMOR_PUBLIC_KEY: &str = "PGP PUBLIC KEY BLOCK";
+
+/// Block size of AES
+const BLOCK_SIZE: usize = 16;
+
+/// Largest nesting of compressed packets read
+const MAX_NESTING: usize = 8;
+
+/// OpenPGP public key of someone else, saved to encrypt files for them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OpenPgpRecipient {
+    /// User ID of the key (usually "Name <email>")
+    pub name: String,
+    /// Fingerprint of the key, in hex
+    pub fingerprint: String,
+    /// The armored public key
+    pub certificate: String,
+}
+
+/// A parsed version 4 public key or subkey
+struct KeyPacket {
+    created: u32,
+    material: KeyMaterial,
+    fingerprint: [u8; 20],
+}
+
+/// Public parts of a key that CRUSTy can encrypt to
+enum KeyMaterial {
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    Cv25519 { public: [u8; 32], hash: u8, cipher: u8 },
+    /// Any other algorithm (signing only, or not supported)
+    Other,
+}
+
+impl KeyPacket {
+    /// Get the key ID (the last 8 bytes of the fingerprint)
+    fn key_id(&self) -> [u8; 8] {
+        self.fingerprint[12..].try_into().unwrap()
+    }
+}
+
+/// What the self-signatures say about a key
+#[derive(Default)]
+struct KeyStatus {
+    /// Creation time of the signature the flags and expiration were taken from
+    signed_at: u32,
+    flags: Option<u8>,
+    /// Seconds after the key's creation that it expires
+    expires_after: Option<u32>,
+    revoked: bool,
+}
+
+/// An OpenPGP packet
+struct Packet {
+    tag: u8,
+    body: Vec<u8>,
+}
+
+/// Reads the fields of a packet body
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.data.len() {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Read a multiprecision integer
+    fn mpi(&mut self) -> Option<&'a [u8]> {
+        let bits = self.u16()? as usize;
+        self.take(bits.div_ceil(8))
+    }
+}
+
+/// Error for OpenPGP data that cannot be read
+fn malformed(what: &str) -> EncryptionError {
+    EncryptionError::Decryption(format!("The {} is damaged or not in the OpenPGP format", what))
+}
+
+/// Get the CRC-24 of data, as used in the armor checksum
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xB704CE;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864CFB;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
+
+/// Armor binary OpenPGP data
+fn armor(label: &str, data: &[u8]) -> String {
+    let encoded = STANDARD.encode(data);
+    let mut text = format!("-----BEGIN {}-----\n\n", label);
+    for line in encoded.as_bytes().chunks(64) {
+        text.push_str(std::str::from_utf8(line).unwrap());
+        text.push('\n');
+    }
+    text.push_str(&format!("={}\n", STANDARD.encode(&crc24(data).to_be_bytes()[1..])));
+    text.push_str(&format!("-----END {}-----\n", label));
+    text
+}
+
+/// Get the binary data of armored OpenPGP data (binary data is returned as it is)
+fn dearmor(data: &[u8], what: &str) -> Result<Vec<u8>, EncryptionError> {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) if text.trim_start().starts_with("-----BEGIN PGP ") => text,
+        _ => return Ok(data.to_vec()),
+    };
+
+    // Armor headers end at the first empty line; the checksum line starts with '='
+    let mut lines = text.lines().map(str::trim).skip_while(|line| !line.starts_with("-----BEGIN PGP ")).skip(1);
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+    }
+    let mut encoded = String::new();
+    let mut checksum = None;
+    for line in lines {
+        if line.starts_with("-----END PGP ") {
+            break;
+        } else if let Some(value) = line.strip_prefix('=') {
+            checksum = Some(value.to_string());
+        } else {
+            encoded.push_str(line);
+        }
+    }
+
+    let decoded = STANDARD.decode(encoded).map_err(|_| malformed(what))?;
+    if let Some(checksum) = checksum {
+        let expected = STANDARD.encode(&crc24(&decoded).to_be_bytes()[1..]);
+        if checksum != expected {
+            return Err(malformed(what));
+        }
+    }
+    Ok(decoded)
+}
+
+/// Read the length of a new-format packet (or of a partial body)
+///
+/// # Returns
+/// * `Option<(usize, bool, usize)>` - The length, whether it is a partial body, and the
+///   bytes the length took
+fn new_format_length(data: &[u8]) -> Option<(usize, bool, usize)> {
+    let first = *data.first()? as usize;
+    match first {
+        0..=191 => Some((first, false, 1)),
+        192..=223 => Some((((first - 192) << 8) + *data.get(1)? as usize + 192, false, 2)),
+        224..=254 => Some((1 << (first & 0x1F), true, 1)),
+        _ => Some((u32::from_be_bytes(data.get(1..5)?.try_into().ok()?) as usize, false, 5)),
+    }
+}
+
+/// Read a sequence of packets, which must fill the data exactly
+fn read_packets(mut data: &[u8]) -> Option<Vec<Packet>> {
+    let mut packets = Vec::new();
+    while !data.is_empty() {
+        let first = data[0];
+        if first & 0x80 == 0 {
+            return None;
+        }
+
+        let mut pos = 1;
+        let mut body = Vec::new();
+        let tag = if first & 0x40 != 0 {
+            // New format, where the body may be split into partial bodies
+            loop {
+                let (len, partial, used) = new_format_length(&data[pos..])?;
+                pos += used;
+                body.extend_from_slice(data.get(pos..pos.checked_add(len)?)?);
+                pos += len;
+                if !partial {
+                    break;
+                }
+            }
+            first & 0x3F
+        } else {
+            // Old format, where the length type is in the first byte
+            let len = match first & 0x03 {
+                0 => *data.get(1)? as usize,
+                1 => u16::from_be_bytes(data.get(1..3)?.try_into().ok()?) as usize,
+                2 => u32::from_be_bytes(data.get(1..5)?.try_into().ok()?) as usize,
+                _ => data.len() - 1,
+            };
+            pos += match first & 0x03 { 0 => 1, 1 => 2, 2 => 4, _ => 0 };
+            body.extend_from_slice(data.get(pos..pos.checked_add(len)?)?);
+            pos += len;
+            (first >> 2) & 0x0F
+        };
+
+        packets.push(Packet { tag, body });
+        data = &data[pos..];
+    }
+    Some(packets)
+}
+
+/// Append a packet in the new format
+fn write_packet(out: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    out.push(0xC0 | tag);
+    let len = body.len();
+    if len < 192 {
+        out.push(len as u8);
+    } else if len < 8384 {
+        out.push((((len - 192) >> 8) + 192) as u8);
+        out.push((len - 192) as u8);
+    } else {
+        out.push(0xFF);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(body);
+}
+
+/// Append a multiprecision integer
+fn write_mpi(out: &mut Vec<u8>, value: &[u8]) {
+    let start = value.iter().position(|byte| *byte != 0).unwrap_or(value.len());
+    let value = &value[start..];
+    let bits = match value.first() {
+        Some(first) => (value.len() - 1) * 8 + (8 - first.leading_zeros() as usize),
+        None => 0,
+    };
+    out.extend_from_slice(&(bits as u16).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+/// Get the version 4 fingerprint of a key packet body
+fn key_fingerprint(body: &[u8]) -> [u8; 20] {
+    let mut hasher = Sha1::new();
+    hasher.update([0x99]);
+    hasher.update((body.len() as u16).to_be_bytes());
+    hasher.update(body);
+    hasher.finalize().into()
+}
+
+/// Format a fingerprint as shown by GnuPG (upper-case hex)
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Read a public key or subkey packet
+fn read_key_packet(body: &[u8]) -> Option<KeyPacket> {
+    let mut reader = Reader { data: body };
+    if reader.u8()? != 4 {
+        return None;
+    }
+    let created = reader.u32()?;
+    let algorithm = reader.u8()?;
+
+    let material = match algorithm {
+        ALGO_RSA | ALGO_RSA_ENCRYPT => {
+            let n = reader.mpi()?.to_vec();
+            let e = reader.mpi()?.to_vec();
+            KeyMaterial::Rsa { n, e }
+        },
+        ALGO_ECDH => {
+            let oid_len = reader.u8()? as usize;
+            let oid = reader.take(oid_len)?;
+            let point = reader.mpi()?;
+            let kdf_len = reader.u8()? as usize;
+            let kdf = reader.take(kdf_len)?;
+            if oid == CV25519_OID && point.len() == 33 && point[0] == 0x40 && kdf_len == 3 && kdf[0] == 1 {
+                KeyMaterial::Cv25519 { public: point[1..].try_into().unwrap(), hash: kdf[1], cipher: kdf[2] }
+            } else {
+                KeyMaterial::Other
+            }
+        },
+        _ => KeyMaterial::Other,
+    };
+
+    Some(KeyPacket { created, material, fingerprint: key_fingerprint(body) })
+}
+
+/// Read what a signature says about the key it was made on (None for signatures CRUSTy
+/// does not read)
+///
+/// # Returns
+/// * `Option<(u8, KeyStatus, Option<[u8; 8]>)>` - The signature type, the key status it
+///   gives, and its issuer
+fn read_signature(body: &[u8]) -> Option<(u8, KeyStatus, Option<[u8; 8]>)> {
+    let mut reader = Reader { data: body };
+    if reader.u8()? != 4 {
+        return None;
+    }
+    let sig_type = reader.u8()?;
+    reader.take(2)?;
+
+    let mut status = KeyStatus::default();
+    let mut issuer = None;
+    for hashed in [true, false] {
+        let len = reader.u16()? as usize;
+        let mut subpackets = Reader { data: reader.take(len)? };
+        while !subpackets.data.is_empty() {
+            let first = subpackets.u8()? as usize;
+            let len = match first {
+                0..=191 => first,
+                192..=254 => ((first - 192) << 8) + subpackets.u8()? as usize + 192,
+                _ => subpackets.u32()? as usize,
+            };
+            let data = subpackets.take(len)?;
+            let (kind, data) = data.split_first()?;
+            match (kind & 0x7F, hashed) {
+                (SUBPACKET_CREATION_TIME, true) if data.len() == 4 => status.signed_at = u32::from_be_bytes(data.try_into().unwrap()),
+                (SUBPACKET_KEY_EXPIRATION, true) if data.len() == 4 => status.expires_after = Some(u32::from_be_bytes(data.try_into().unwrap())),
+                (SUBPACKET_KEY_FLAGS, true) => status.flags = data.first().copied(),
+                (SUBPACKET_ISSUER, _) if data.len() == 8 => issuer = Some(data.try_into().unwrap()),
+                (SUBPACKET_ISSUER_FINGERPRINT, _) if data.len() == 21 && data[0] == 4 => issuer = Some(data[13..].try_into().unwrap()),
+                _ => {},
+            }
+        }
+    }
+
+    status.revoked = sig_type == SIG_KEY_REVOCATION || sig_type == SIG_SUBKEY_REVOCATION;
+    Some((sig_type, status, issuer))
+}
+
+/// An OpenPGP public key read from a file
+pub struct Certificate {
+    /// The first user ID (usually "Name <email>")
+    pub user_id: String,
+    /// Fingerprint of the primary key, in hex
+    pub fingerprint: String,
+    /// The key files are encrypted to
+    encryption_key: KeyPacket,
+}
+
+/// Read an OpenPGP public key (binary or armored, as from `gpg --export`)
+///
+/// The key that files are encrypted to is the newest encryption subkey (or the primary
+/// key) that is RSA or Curve25519, and is neither revoked nor expired according to its
+/// self-signatures. The signatures themselves are not checked.
+///
+/// # Returns
+/// * `Result<Certificate, EncryptionError>` - The key, or an error if it cannot be read
+///   or has no usable encryption key
+pub fn read_certificate(data: &[u8]) -> Result<Certificate, EncryptionError> {
+    let invalid = |reason: &str| EncryptionError::KeyError(reason.to_string());
+    let data = dearmor(data, "OpenPGP key").map_err(|_| invalid("The OpenPGP key is damaged or not in the OpenPGP format"))?;
+    let packets = read_packets(&data).ok_or_else(|| invalid("The OpenPGP key is damaged or not in the OpenPGP format"))?;
+
+    match packets.first() {
+        Some(packet) if packet.tag == TAG_PUBLIC_KEY => {},
+        Some(packet) if packet.tag == TAG_SECRET_KEY => {
+            return Err(invalid("This is a secret key; export the public key instead (gpg --export --armor)"));
+        },
+        _ => return Err(invalid("The file is not an OpenPGP public key")),
+    }
+
+    // Keys with the status from their newest self-signature, the primary key first
+    let primary = read_key_packet(&packets[0].body)
+        .ok_or_else(|| invalid("Only version 4 OpenPGP keys are supported"))?;
+    let primary_id = primary.key_id();
+    let mut keys: Vec<(KeyPacket, KeyStatus)> = vec![(primary, KeyStatus::default())];
+    let mut user_id = None;
+    let mut in_subkey = false;
+    for packet in &packets[1..] {
+        match packet.tag {
+            TAG_PUBLIC_KEY => break,
+            TAG_USER_ID => {
+                user_id.get_or_insert_with(|| String::from_utf8_lossy(&packet.body).into_owned());
+                in_subkey = false;
+            },
+            TAG_PUBLIC_SUBKEY => {
+                in_subkey = match read_key_packet(&packet.body) {
+                    Some(subkey) => {
+                        keys.push((subkey, KeyStatus::default()));
+                        true
+                    },
+                    None => false,
+                };
+            },
+            TAG_SIGNATURE => {
+                let Some((sig_type, status, issuer)) = read_signature(&packet.body) else { continue };
+                if issuer.is_some_and(|issuer| issuer != primary_id) {
+                    continue;
+                }
+                let target = match sig_type {
+                    SIG_SUBKEY_BINDING | SIG_SUBKEY_REVOCATION if in_subkey => keys.len() - 1,
+                    0x10..=0x13 | 0x1F | SIG_KEY_REVOCATION => 0,
+                    _ => continue,
+                };
+                let current = &mut keys[target].1;
+                if status.revoked {
+                    current.revoked = true;
+                } else if status.signed_at >= current.signed_at {
+                    *current = KeyStatus { revoked: current.revoked, ..status };
+                }
+            },
+            _ => {},
+        }
+    }
+
+    if keys[0].1.revoked {
+        return Err(invalid("The OpenPGP key has been revoked"));
+    }
+    let now = Utc::now().timestamp().max(0) as u64;
+    let expired = |key: &KeyPacket, status: &KeyStatus| status.expires_after
+        .is_some_and(|after| after > 0 && key.created as u64 + after as u64 <= now);
+    if expired(&keys[0].0, &keys[0].1) {
+        return Err(invalid("The OpenPGP key has expired"));
+    }
+
+    let fingerprint = hex(&keys[0].0.fingerprint);
+    let index = keys.iter()
+        .enumerate()
+        .filter(|(_, (key, status))| {
+            !matches!(key.material, KeyMaterial::Other)
+                && !status.revoked
+                && !expired(key, status)
+                && status.flags.is_none_or(|flags| flags & FLAGS_ENCRYPT != 0)
+        })
+        // Subkeys before the primary key, the newest first
+        .max_by_key(|(i, (key, _))| (*i > 0, key.created))
+        .map(|(i, _)| i)
+        .ok_or_else(|| invalid("The OpenPGP key has no RSA or Curve25519 encryption key that CRUSTy can use"))?;
+
+    Ok(Certificate {
+        user_id: user_id.unwrap_or_else(|| fingerprint.clone()),
+        fingerprint,
+        encryption_key: keys.swap_remove(index).0,
+    })
+}
+
+/// Get an OpenPGP public key as armored text, to save it (armored keys are kept as
+/// they are)
+pub fn armored_certificate(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) if text.trim_start().starts_with("-----BEGIN PGP ") => text.to_string(),
+        _ => armor(ARMOR_PUBLIC_KEY, data),
+    }
+}
+
+/// OpenPGP key derived from a CRUSTy key
+struct OwnKey {
+    signing: SigningKey,
+    primary: Vec<u8>,
+    secret: StaticSecret,
+    subkey: Vec<u8>,
+}
+
+/// Derive 32 bytes for an OpenPGP key from a key
+fn derive(key: &EncryptionKey, info: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, &key.key)
+        .expand(info, bytes.as_mut())
+        .expect("32 bytes is a valid HKDF output length");
+    bytes
+}
+
+/// Get the OpenPGP key of a key
+fn own_key(key: &EncryptionKey) -> OwnKey {
+    let signing = SigningKey::from_bytes(&derive(key, b"CRUSTy OpenPGP signing key"));
+    let secret = StaticSecret::from(*derive(key, b"CRUSTy OpenPGP encryption key"));
+
+    let mut primary = vec![4];
+    primary.extend_from_slice(&KEY_CREATION_TIME.to_be_bytes());
+    primary.push(ALGO_EDDSA);
+    primary.push(ED25519_OID.len() as u8);
+    primary.extend_from_slice(ED25519_OID);
+    let mut point = vec![0x40];
+    point.extend_from_slice(signing.verifying_key().as_bytes());
+    write_mpi(&mut primary, &point);
+
+    let mut subkey = vec![4];
+    subkey.extend_from_slice(&KEY_CREATION_TIME.to_be_bytes());
+    subkey.push(ALGO_ECDH);
+    subkey.push(CV25519_OID.len() as u8);
+    subkey.extend_from_slice(CV25519_OID);
+    let mut point = vec![0x40];
+    point.extend_from_slice(PublicKey::from(&secret).as_bytes());
+    write_mpi(&mut subkey, &point);
+    subkey.extend_from_slice(&[3, 1, HASH_SHA256, CIPHER_AES256]);
+
+    OwnKey { signing, primary, secret, subkey }
+}
+
+/// Append a signature subpacket (all CRUSTy writes are short)
+fn write_subpacket(out: &mut Vec<u8>, kind: u8, data: &[u8]) {
+    out.push(data.len() as u8 + 1);
+    out.push(kind);
+    out.extend_from_slice(data);
+}
+
+/// Make a self-signature of an OpenPGP key
+///
+/// # Arguments
+/// * `own` - The key
+/// * `sig_type` - The signature type
+/// * `signed` - What is signed after the primary key (the user ID or subkey, as hashed)
+/// * `subpackets` - Hashed subpackets, besides the creation time and issuer
+fn self_signature(own: &OwnKey, sig_type: u8, signed: &[u8], subpackets: &[u8]) -> Vec<u8> {
+    let fingerprint = key_fingerprint(&own.primary);
+
+    let mut hashed = Vec::new();
+    write_subpacket(&mut hashed, SUBPACKET_CREATION_TIME, &(Utc::now().timestamp() as u32).max(KEY_CREATION_TIME).to_be_bytes());
+    let mut issuer_fingerprint = vec![4];
+    issuer_fingerprint.extend_from_slice(&fingerprint);
+    write_subpacket(&mut hashed, SUBPACKET_ISSUER_FINGERPRINT, &issuer_fingerprint);
+    hashed.extend_from_slice(subpackets);
+
+    let mut signature = vec![4, sig_type, ALGO_EDDSA, HASH_SHA256];
+    signature.extend_from_slice(&(hashed.len() as u16).to_be_bytes());
+    signature.extend_from_slice(&hashed);
+
+    let mut hasher = Sha256::new();
+    hasher.update([0x99]);
+    hasher.update((own.primary.len() as u16).to_be_bytes());
+    hasher.update(&own.primary);
+    hasher.update(signed);
+    hasher.update(&signature);
+    hasher.update([4, 0xFF]);
+    hasher.update((signature.len() as u32).to_be_bytes());
+    let digest = hasher.finalize();
+
+    let mut unhashed = Vec::new();
+    write_subpacket(&mut unhashed, SUBPACKET_ISSUER, &fingerprint[12..]);
+    signature.extend_from_slice(&(unhashed.len() as u16).to_be_bytes());
+    signature.extend_from_slice(&unhashed);
+    signature.extend_from_slice(&digest[..2]);
+
+    // EdDSA signs the digest, and stores the signature's halves as integers
+    let signed_digest = own.signing.sign(&digest).to_bytes();
+    write_mpi(&mut signature, &signed_digest[..32]);
+    write_mpi(&mut signature, &signed_digest[32..]);
+    signature
+}
+
+/// Get the fingerprint of a key's OpenPGP key, as shown by GnuPG
+pub fn fingerprint(key: &EncryptionKey) -> String {
+    hex(&key_fingerprint(&own_key(key).primary))
+}
+
+/// Get the armored OpenPGP public key of a key, for `gpg --import`
+///
+/// # Arguments
+/// * `key` - The key
+/// * `user_id` - The user ID (e.g. "Name <email>") the key is published under
+pub fn public_key_file(key: &EncryptionKey, user_id: &str) -> String {
+    let own = own_key(key);
+
+    let mut certified = vec![0xB4];
+    certified.extend_from_slice(&(user_id.len() as u32).to_be_bytes());
+    certified.extend_from_slice(user_id.as_bytes());
+    // Preferences that let GnuPG encrypt in a format CRUSTy reads
+    let mut preferences = Vec::new();
+    write_subpacket(&mut preferences, SUBPACKET_KEY_FLAGS, &[0x03]);
+    write_subpacket(&mut preferences, SUBPACKET_PREFERRED_CIPHERS, &[CIPHER_AES256, CIPHER_AES128]);
+    write_subpacket(&mut preferences, SUBPACKET_PREFERRED_HASHES, &[HASH_SHA256, HASH_SHA512]);
+    write_subpacket(&mut preferences, SUBPACKET_PREFERRED_COMPRESSION, &[2, 1, 0]);
+    write_subpacket(&mut preferences, SUBPACKET_FEATURES, &[0x01]);
+    write_subpacket(&mut preferences, SUBPACKET_PRIMARY_USER_ID, &[1]);
+    let certification = self_signature(&own, SIG_POSITIVE_CERTIFICATION, &certified, &preferences);
+
+    let mut bound = vec![0x99];
+    bound.extend_from_slice(&(own.subkey.len() as u16).to_be_bytes());
+    bound.extend_from_slice(&own.subkey);
+    let mut flags = Vec::new();
+    write_subpacket(&mut flags, SUBPACKET_KEY_FLAGS, &[FLAGS_ENCRYPT]);
+    let binding = self_signature(&own, SIG_SUBKEY_BINDING, &bound, &flags);
+
+    let mut data = Vec::new();
+    write_packet(&mut data, TAG_PUBLIC_KEY, &own.primary);
+    write_packet(&mut data, TAG_USER_ID, user_id.as_bytes());
+    write_packet(&mut data, TAG_SIGNATURE, &certification);
+    write_packet(&mut data, TAG_PUBLIC_SUBKEY, &own.subkey);
+    write_packet(&mut data, TAG_SIGNATURE, &binding);
+    armor(ARMOR_PUBLIC_KEY, &data)
+}
+
+/// Derive the key-encryption key of a Curve25519 session key (RFC 6637 section 7)
+fn ecdh_kek(shared: &[u8], hash: u8, cipher: u8, fingerprint: &[u8; 20]) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    let mut param = vec![CV25519_OID.len() as u8];
+    param.extend_from_slice(CV25519_OID);
+    param.extend_from_slice(&[ALGO_ECDH, 3, 1, hash, cipher]);
+    param.extend_from_slice(b"Anonymous Sender    ");
+    param.extend_from_slice(fingerprint);
+
+    let input = [&[0, 0, 0, 1][..], shared, &param].concat();
+    let digest = Zeroizing::new(match hash {
+        HASH_SHA256 => Sha256::digest(&input).to_vec(),
+        HASH_SHA384 => Sha384::digest(&input).to_vec(),
+        HASH_SHA512 => Sha512::digest(&input).to_vec(),
+        _ => return Err(EncryptionError::KeyError(format!("OpenPGP hash algorithm {} is not supported", hash))),
+    });
+    let len = match cipher {
+        CIPHER_AES128 => 16,
+        CIPHER_AES192 => 24,
+        CIPHER_AES256 => 32,
+        _ => return Err(EncryptionError::KeyError(format!("OpenPGP key wrap algorithm {} is not supported", cipher))),
+    };
+    Ok(Zeroizing::new(digest[..len].to_vec()))
+}
+
+/// Wrap (or unwrap) a session key with AES key wrap
+fn key_wrap(kek: &[u8], data: &[u8], wrap: bool) -> Result<Vec<u8>, aes_kw::Error> {
+    match kek.len() {
+        16 => KekAes128::try_from(kek).and_then(|kek| if wrap { kek.wrap_vec(data) } else { kek.unwrap_vec(data) }),
+        24 => KekAes192::try_from(kek).and_then(|kek| if wrap { kek.wrap_vec(data) } else { kek.unwrap_vec(data) }),
+        _ => KekAes256::try_from(kek).and_then(|kek| if wrap { kek.wrap_vec(data) } else { kek.unwrap_vec(data) }),
+    }
+}
+
+/// Get a session key with its algorithm and checksum, as encrypted in key packets
+fn session_key_block(session_key: &[u8]) -> Zeroizing<Vec<u8>> {
+    let checksum = session_key.iter().fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16));
+    let mut block = Zeroizing::new(vec![CIPHER_AES256]);
+    block.extend_from_slice(session_key);
+    block.extend_from_slice(&checksum.to_be_bytes());
+    block
+}
+
+/// Make the key packet that encrypts a session key to a key
+fn encrypt_session_key(recipient: &KeyPacket, session_key: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let mut packet = vec![3];
+    packet.extend_from_slice(&recipient.key_id());
+
+    match &recipient.material {
+        KeyMaterial::Rsa { n, e } => {
+            let public = RsaPublicKey::new(BigUint::from_bytes_be(n), BigUint::from_bytes_be(e))
+                .map_err(|e| EncryptionError::KeyError(format!("The OpenPGP RSA key cannot be used: {}", e)))?;
+            let encrypted = public.encrypt(&mut rand::rngs::OsRng, Pkcs1v15Encrypt, &session_key_block(session_key))
+                .map_err(|e| EncryptionError::Encryption(format!("Failed to encrypt to the OpenPGP RSA key: {}", e)))?;
+            packet.push(ALGO_RSA);
+            write_mpi(&mut packet, &encrypted);
+        },
+        KeyMaterial::Cv25519 { public, hash, cipher } => {
+            let ephemeral = StaticSecret::random_from_rng(rand::rngs::OsRng);
+            let shared = ephemeral.diffie_hellman(&PublicKey::from(*public));
+            let kek = ecdh_kek(shared.as_bytes(), *hash, *cipher, &recipient.fingerprint)?;
+
+            // Padded to whole 8-byte blocks as in PKCS #5
+            let mut block = session_key_block(session_key);
+            let padding = 8 - block.len() % 8;
+            block.extend(std::iter::repeat_n(padding as u8, padding));
+            let wrapped = key_wrap(&kek, &block, true)
+                .map_err(|e| EncryptionError::Encryption(format!("Failed to wrap the session key: {}", e)))?;
+
+            packet.push(ALGO_ECDH);
+            let mut point = vec![0x40];
+            point.extend_from_slice(PublicKey::from(&ephemeral).as_bytes());
+            write_mpi(&mut packet, &point);
+            packet.push(wrapped.len() as u8);
+            packet.extend_from_slice(&wrapped);
+        },
+        KeyMaterial::Other => unreachable!("certificates only hold keys CRUSTy can encrypt to"),
+    }
+    Ok(packet)
+}
+
+/// Cipher and session key of a message
+type SessionKey = (u8, Zeroizing<Vec<u8>>);
+
+/// Decrypt the session key in a key packet encrypted to the key's OpenPGP key
+///
+/// # Returns
+/// * `Option<Result<SessionKey, EncryptionError>>` - The cipher and session
+///   key, an error if the packet is for the key but cannot be decrypted, or None if the
+///   packet is for another key
+fn decrypt_session_key(own: &OwnKey, body: &[u8]) -> Option<Result<SessionKey, EncryptionError>> {
+    let mut reader = Reader { data: body };
+    let subkey_fingerprint = key_fingerprint(&own.subkey);
+    if reader.u8()? != 3 {
+        return None;
+    }
+    let key_id = reader.take(8)?;
+    // An all-zero key ID hides the recipient (gpg --throw-keyids)
+    let hidden = key_id == [0u8; 8];
+    if (key_id != &subkey_fingerprint[12..] && !hidden) || reader.u8()? != ALGO_ECDH {
+        return None;
+    }
+
+    let damaged = || EncryptionError::Decryption("The OpenPGP message's key packet is damaged".to_string());
+    let result = (|| {
+        let point = reader.mpi().filter(|point| point.len() == 33 && point[0] == 0x40).ok_or_else(damaged)?;
+        let wrapped_len = reader.u8().ok_or_else(damaged)? as usize;
+        let wrapped = reader.take(wrapped_len).ok_or_else(damaged)?;
+
+        let ephemeral: [u8; 32] = point[1..].try_into().unwrap();
+        let shared = own.secret.diffie_hellman(&PublicKey::from(ephemeral));
+        let kek = ecdh_kek(shared.as_bytes(), HASH_SHA256, CIPHER_AES256, &subkey_fingerprint)?;
+        let block = Zeroizing::new(key_wrap(&kek, wrapped, false).map_err(|_| not_for_key())?);
+
+        // Remove the padding, then check the session key against its checksum
+        let padding = *block.last().ok_or_else(damaged)? as usize;
+        if !(1..=8).contains(&padding) || block.len() < padding + 3 || block[block.len() - padding..].iter().any(|byte| *byte as usize != padding) {
+            return Err(damaged());
+        }
+        let block = &block[..block.len() - padding];
+        let session_key = Zeroizing::new(block[1..block.len() - 2].to_vec());
+        let checksum = session_key.iter().fold(0u16, |sum, byte| sum.wrapping_add(*byte as u16));
+        if checksum.to_be_bytes() != block[block.len() - 2..] {
+            return Err(damaged());
+        }
+        Ok((block[0], session_key))
+    })();
+
+    // Hidden recipients are tried with every key, so a failure means another recipient
+    match result {
+        Err(_) if hidden => None,
+        result => Some(result),
+    }
+}
+
+/// Error for a message that was not encrypted to the key's OpenPGP key
+fn not_for_key() -> EncryptionError {
+    EncryptionError::Decryption("The OpenPGP message was not encrypted to this key's OpenPGP key".to_string())
+}
+
+/// Encrypt (or decrypt) data in place with CFB mode and a zero IV
+fn cfb<C: BlockEncryptMut + BlockCipher + KeyInit>(key: &[u8], data: &mut [u8], encrypt: bool) -> Result<(), EncryptionError> {
+    let iv = [0u8; BLOCK_SIZE];
+    let invalid = |_| EncryptionError::Decryption("Invalid OpenPGP session key".to_string());
+    if encrypt {
+        cfb_mode::Encryptor::<C>::new_from_slices(key, &iv).map_err(invalid)?.encrypt(data);
+    } else {
+        cfb_mode::Decryptor::<C>::new_from_slices(key, &iv).map_err(invalid)?.decrypt(data);
+    }
+    Ok(())
+}
+
+/// Check whether a file is an OpenPGP message (binary or armored)
+pub fn is_openpgp_file(path: &Path) -> bool {
+    let mut start = Vec::with_capacity(64);
+    if File::open(path).and_then(|file| file.take(64).read_to_end(&mut start)).is_err() {
+        return false;
+    }
+    match starts_message(&start) {
+        Some(armored) => armored || fs::read(path).is_ok_and(|data| is_binary_message(&data)),
+        None => false,
+    }
+}
+
+/// Check whether data is an OpenPGP message (binary or armored)
+pub fn is_openpgp_data(data: &[u8]) -> bool {
+    match starts_message(&data[..data.len().min(64)]) {
+        Some(armored) => armored || is_binary_message(data),
+        None => false,
+    }
+}
+
+/// Check the start of an OpenPGP message: Some(true) if it is armored, Some(false) if
+/// it may be a binary message (see `is_binary_message`), None if it is not a message
+fn starts_message(start: &[u8]) -> Option<bool> {
+    if String::from_utf8_lossy(start).trim_start().starts_with("-----BEGIN PGP MESSAGE-----") {
+        return Some(true);
+    }
+
+    // A binary message starts with key packets
+    let first_tag = match start.first() {
+        Some(first) if first & 0xC0 == 0xC0 => first & 0x3F,
+        Some(first) if first & 0x80 != 0 => (first >> 2) & 0x0F,
+        _ => return None,
+    };
+    [TAG_PKESK, TAG_SKESK, TAG_MARKER].contains(&first_tag).then_some(false)
+}
+
+/// Check whether data is a binary OpenPGP message: packets ending with the encrypted
+/// data, which random data (like CRUSTy's files) never is
+fn is_binary_message(data: &[u8]) -> bool {
+    read_packets(data).is_some_and(|packets| match packets.split_last() {
+        Some((last, keys)) => [TAG_SEIPD, TAG_AEAD, TAG_SED].contains(&last.tag)
+            && keys.iter().all(|packet| [TAG_PKESK, TAG_SKESK, TAG_MARKER].contains(&packet.tag)),
+        None => false,
+    })
+}
+
+/// Encrypt a file as an OpenPGP message
+///
+/// The file is encrypted to the key's own OpenPGP key, so CRUSTy can decrypt it, and to
+/// every OpenPGP public key given.
+///
+/// # Arguments
+/// * `source_path` - The file to encrypt
+/// * `dest_path` - The message to create (written atomically; never replaced)
+/// * `key` - The encryption key
+/// * `recipients` - Other people's armored OpenPGP public keys
+/// * `progress_callback` - Called with the fraction done
+pub fn encrypt_file(
+    source_path: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    recipients: &[String],
+    progress_callback: impl Fn(f32),
+) -> Result<(), EncryptionError> {
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+
+    let data = Zeroizing::new(chunk_tuning::read_file(source_path, |p| progress_callback(p * 0.3))?);
+    let name = source_path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let message = encrypt_message(&data, &name, key, recipients, &progress_callback)?;
+
+    encryption::write_destination(dest_path, |dest_file| {
+        chunk_tuning::write_all(dest_file, &message, |p| progress_callback(0.6 + p * 0.4))
+    })?;
+    progress_callback(1.0);
+
+    Ok(())
+}
+
+/// Encrypt data as an OpenPGP message in memory, to the key's own OpenPGP key and every
+/// OpenPGP public key given
+///
+/// # Arguments
+/// * `data` - The data to encrypt
+/// * `name` - The file name stored in the message (may be empty)
+/// * `key` - The encryption key
+/// * `recipients` - Other people's armored OpenPGP public keys
+/// * `progress_callback` - Called with the fraction done
+pub fn encrypt_message(
+    data: &[u8],
+    name: &str,
+    key: &EncryptionKey,
+    recipients: &[String],
+    progress_callback: impl Fn(f32),
+) -> Result<Vec<u8>, EncryptionError> {
+    let own = own_key(key);
+    let mut keys = vec![read_key_packet(&own.subkey).expect("derived keys are version 4")];
+    for recipient in recipients {
+        keys.push(read_certificate(recipient.as_bytes())?.encryption_key);
+    }
+
+    // One packet holds the whole file, and packet lengths are 32-bit
+    if data.len() > u32::MAX as usize - 1024 {
+        return Err(EncryptionError::Encryption("The file is too large for an OpenPGP message".to_string()));
+    }
+    progress_callback(0.3);
+
+    let mut session_key = Zeroizing::new(vec![0u8; 32]);
+    rand::thread_rng().fill_bytes(&mut session_key);
+    let mut message = Vec::new();
+    for recipient in &keys {
+        write_packet(&mut message, TAG_PKESK, &encrypt_session_key(recipient, &session_key)?);
+    }
+
+    // Literal data with the file's name, then the integrity check (MDC)
+    let name = &name.as_bytes()[..name.len().min(255)];
+    let mut literal = Zeroizing::new(vec![b'b', name.len() as u8]);
+    literal.extend_from_slice(name);
+    literal.extend_from_slice(&[0, 0, 0, 0]);
+    literal.extend_from_slice(data);
+
+    let mut plaintext = Zeroizing::new(vec![0u8; BLOCK_SIZE + 2]);
+    rand::thread_rng().fill_bytes(&mut plaintext[..BLOCK_SIZE]);
+    plaintext[BLOCK_SIZE] = plaintext[BLOCK_SIZE - 2];
+    plaintext[BLOCK_SIZE + 1] = plaintext[BLOCK_SIZE - 1];
+    write_packet(&mut plaintext, TAG_LITERAL, &literal);
+    plaintext.extend_from_slice(&[0xD3, 0x14]);
+    let mdc = Sha1::digest(&plaintext[..]);
+    plaintext.extend_from_slice(&mdc);
+    progress_callback(0.6);
+
+    let mut encrypted = vec![1];
+    encrypted.extend_from_slice(&plaintext);
+    cfb::<Aes256>(&session_key, &mut encrypted[1..], true)?;
+    write_packet(&mut message, TAG_SEIPD, &encrypted);
+    Ok(message)
+}
+
+/// Decrypt an OpenPGP message with the key's OpenPGP key
+///
+/// # Arguments
+/// * `source_path` - The message (binary or armored)
+/// * `dest_path` - The file to create (written atomically; never replaced)
+/// * `key` - The encryption key
+/// * `progress_callback` - Called with the fraction done
+pub fn decrypt_file(
+    source_path: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32),
+) -> Result<(), EncryptionError> {
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+
+    let contents = decrypt(source_path, key, &progress_callback)?;
+    encryption::write_destination(dest_path, |dest_file| {
+        encryption::write_sparse(dest_file, &contents, |p| progress_callback(0.6 + p * 0.4))
+    })?;
+    progress_callback(1.0);
+
+    Ok(())
+}
+
+/// Decrypt an OpenPGP message with the key's OpenPGP key into memory
+///
+/// # Returns
+/// * `Result<Zeroizing<Vec<u8>>, EncryptionError>` - The contents of the message's
+///   literal data
+pub fn decrypt(
+    source_path: &Path,
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32),
+) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    decrypt_message(&fs::read(source_path)?, key, progress_callback)
+}
+
+/// Decrypt an OpenPGP message (binary or armored) held in memory with the key's
+/// OpenPGP key
+pub fn decrypt_message(
+    message: &[u8],
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32),
+) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    let data = dearmor(message, "OpenPGP message")?;
+    let packets = read_packets(&data).ok_or_else(|| malformed("OpenPGP message"))?;
+
+    let own = own_key(key);
+    let mut session = None;
+    let mut passphrase_only = true;
+    let mut encrypted = None;
+    for packet in &packets {
+        match packet.tag {
+            TAG_PKESK => {
+                passphrase_only = false;
+                if session.is_none() {
+                    session = decrypt_session_key(&own, &packet.body).transpose()?;
+                }
+            },
+            TAG_SED => return Err(EncryptionError::Decryption(
+                "The OpenPGP message has no integrity protection, so it is not decrypted".to_string()
+            )),
+            TAG_AEAD => return Err(EncryptionError::Decryption(
+                "The OpenPGP message uses AEAD (OCB) encryption, which CRUSTy does not support".to_string()
+            )),
+            TAG_SEIPD => encrypted = Some(&packet.body),
+            _ => {},
+        }
+    }
+    let encrypted = encrypted.ok_or_else(|| malformed("OpenPGP message"))?;
+    let (cipher, session_key) = match session {
+        Some(session) => session,
+        None if passphrase_only => return Err(EncryptionError::Decryption(
+            "The OpenPGP message was encrypted with a passphrase (gpg -c), which CRUSTy does not support".to_string()
+        )),
+        None => return Err(not_for_key()),
+    };
+    if encrypted.first() != Some(&1) {
+        return Err(EncryptionError::Decryption(
+            "The OpenPGP message uses a newer encryption format, which CRUSTy does not support".to_string()
+        ));
+    }
+    progress_callback(0.3);
+
+    let mut plaintext = Zeroizing::new(encrypted[1..].to_vec());
+    match cipher {
+        CIPHER_AES128 => cfb::<Aes128>(&session_key, &mut plaintext, false)?,
+        CIPHER_AES192 => cfb::<Aes192>(&session_key, &mut plaintext, false)?,
+        CIPHER_AES256 => cfb::<Aes256>(&session_key, &mut plaintext, false)?,
+        _ => return Err(EncryptionError::Decryption(format!(
+            "The OpenPGP message uses cipher {}; CRUSTy only decrypts AES", cipher
+        ))),
+    }
+
+    // The random prefix repeats its last two bytes, and the MDC packet ends the data
+    let changed = || EncryptionError::Decryption("The OpenPGP message is damaged or was changed".to_string());
+    let len = plaintext.len();
+    if len < BLOCK_SIZE + 2 + 22 || plaintext[BLOCK_SIZE - 2..BLOCK_SIZE] != plaintext[BLOCK_SIZE..BLOCK_SIZE + 2] {
+        return Err(changed());
+    }
+    if plaintext[len - 22..len - 20] != [0xD3, 0x14] || Sha1::digest(&plaintext[..len - 20])[..] != plaintext[len - 20..] {
+        return Err(changed());
+    }
+    let contents = literal_data(&plaintext[BLOCK_SIZE + 2..len - 22], 0)?;
+    progress_callback(0.6);
+    Ok(contents)
+}
+
+/// Get the file contents from the decrypted packets of a message
+fn literal_data(data: &[u8], depth: usize) -> Result<Zeroizing<Vec<u8>>, EncryptionError> {
+    let packets = read_packets(data).ok_or_else(|| malformed("OpenPGP message"))?;
+    for packet in packets {
+        match packet.tag {
+            TAG_LITERAL => {
+                let mut reader = Reader { data: &packet.body };
+                reader.u8().ok_or_else(|| malformed("OpenPGP message"))?;
+                let name_len = reader.u8().ok_or_else(|| malformed("OpenPGP message"))? as usize;
+                reader.take(name_len + 4).ok_or_else(|| malformed("OpenPGP message"))?;
+                return Ok(Zeroizing::new(reader.data.to_vec()));
+            },
+            TAG_COMPRESSED if depth < MAX_NESTING => {
+                let (algorithm, compressed) = packet.body.split_first().ok_or_else(|| malformed("OpenPGP message"))?;
+                let mut decompressed = Zeroizing::new(Vec::new());
+                let result = match algorithm {
+                    0 => {
+                        decompressed.extend_from_slice(compressed);
+                        Ok(0)
+                    },
+                    1 => DeflateDecoder::new(compressed).read_to_end(&mut decompressed),
+                    2 => ZlibDecoder::new(compressed).read_to_end(&mut decompressed),
+                    _ => return Err(EncryptionError::Decryption(
+                        "The OpenPGP message is compressed with BZip2, which CRUSTy does not support".to_string()
+                    )),
+                };
+                result.map_err(|_| malformed("OpenPGP message"))?;
+                return literal_data(&decompressed, depth + 1);
+            },
+            // Signatures are not checked
+            TAG_ONE_PASS_SIGNATURE | TAG_SIGNATURE | TAG_MARKER => {},
+            _ => return Err(malformed("OpenPGP message")),
+        }
+    }
+    Err(malformed("OpenPGP message"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_openpgp_round_trip() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("minutes.txt");
+        let encrypted = dir.path().join("minutes.txt.gpg");
+        fs::write(&source, b"For GnuPG users").unwrap();
+
+        let key = EncryptionKey::generate();
+        let colleague = EncryptionKey::generate();
+        let colleague_key = public_key_file(&colleague, "Colleague <colleague@example.com>");
+        let certificate = read_certificate(colleague_key.as_bytes()).unwrap();
+        assert_eq!(certificate.user_id, "Colleague <colleague@example.com>");
+        assert_eq!(certificate.fingerprint, fingerprint(&colleague));
+
+        encrypt_file(&source, &encrypted, &key, &[colleague_key], |_| {}).unwrap();
+        assert!(is_openpgp_file(&encrypted));
+        assert!(!is_openpgp_file(&source));
+
+        // Both recipients can decrypt; other keys cannot
+        for (i, decrypting_key) in [&key, &colleague].into_iter().enumerate() {
+            let decrypted = dir.path().join(format!("decrypted{}.txt", i));
+            decrypt_file(&encrypted, &decrypted, decrypting_key, |_| {}).unwrap();
+            assert_eq!(fs::read(&decrypted).unwrap(), b"For GnuPG users");
+        }
+        let result = decrypt_file(&encrypted, &dir.path().join("other.txt"), &EncryptionKey::generate(), |_| {});
+        assert!(matches!(result, Err(EncryptionError::Decryption(message)) if message.contains("not encrypted to this key")));
+
+        // A changed byte is detected
+        let mut changed = fs::read(&encrypted).unwrap();
+        let last = changed.len() - 30;
+        changed[last] ^= 1;
+        let changed_path = dir.path().join("changed.gpg");
+        fs::write(&changed_path, &changed).unwrap();
+        assert!(decrypt_file(&changed_path, &dir.path().join("changed.txt"), &key, |_| {}).is_err());
+
+        assert!(read_certificate(b"not a key").is_err());
+    }
+
+    #[test]
+    fn test_armored_compressed_message() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let own = own_key(&key);
+
+        // A message as GnuPG makes it: ZLIB compressed, armored, in partial bodies
+        let mut literal = vec![b'b', 0, 0, 0, 0, 0];
+        literal.extend_from_slice(&b"Compressed by GnuPG ".repeat(20));
+        let mut inner = Vec::new();
+        write_packet(&mut inner, TAG_LITERAL, &literal);
+        let mut encoder = flate2::write::ZlibEncoder::new(vec![2], flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &inner).unwrap();
+        let mut compressed = Vec::new();
+        write_packet(&mut compressed, TAG_COMPRESSED, &encoder.finish().unwrap());
+
+        let session_key = [7u8; 16];
+        let mut plaintext = vec![9u8; BLOCK_SIZE + 2];
+        plaintext.extend_from_slice(&compressed);
+        plaintext.extend_from_slice(&[0xD3, 0x14]);
+        let mdc = Sha1::digest(&plaintext);
+        plaintext.extend_from_slice(&mdc);
+        cfb::<Aes128>(&session_key, &mut plaintext, true).unwrap();
+
+        let subkey = read_key_packet(&own.subkey).unwrap();
+        let mut key_packet = encrypt_session_key(&subkey, &session_key).unwrap();
+        // Encrypted for AES-128 instead of the AES-256 CRUSTy uses
+        let KeyMaterial::Cv25519 { public, .. } = subkey.material else { unreachable!() };
+        let ephemeral = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let kek = ecdh_kek(ephemeral.diffie_hellman(&PublicKey::from(public)).as_bytes(), HASH_SHA256, CIPHER_AES256, &subkey.fingerprint).unwrap();
+        let mut block = vec![CIPHER_AES128];
+        block.extend_from_slice(&session_key);
+        block.extend_from_slice(&(7u16 * 16).to_be_bytes());
+        block.extend_from_slice(&[5; 5]);
+        key_packet.truncate(10);
+        let mut point = vec![0x40];
+        point.extend_from_slice(PublicKey::from(&ephemeral).as_bytes());
+        write_mpi(&mut key_packet, &point);
+        let wrapped = key_wrap(&kek, &block, true).unwrap();
+        key_packet.push(wrapped.len() as u8);
+        key_packet.extend_from_slice(&wrapped);
+
+        let mut message = Vec::new();
+        write_packet(&mut message, TAG_PKESK, &key_packet);
+        // The encrypted data in a 32-byte partial body and a final part
+        let body = [&[1u8][..], &plaintext].concat();
+        message.push(0xC0 | TAG_SEIPD);
+        message.push(0xE5);
+        message.extend_from_slice(&body[..32]);
+        message.push((body.len() - 32) as u8);
+        message.extend_from_slice(&body[32..]);
+
+        let encrypted = dir.path().join("from-gpg.asc");
+        fs::write(&encrypted, armor(ARMOR_MESSAGE, &message)).unwrap();
+        assert!(is_openpgp_file(&encrypted));
+        let decrypted = dir.path().join("from-gpg.txt");
+        decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), b"Compressed by GnuPG ".repeat(20));
+    }
+}
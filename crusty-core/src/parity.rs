@@ -0,0 +1,325 @@
+/// Parity module.
+///
+/// This module provides functionality for:
+/// - Appending Reed-Solomon parity to an encrypted file, so damage from bit rot on
+///   archival media (DVDs, USB sticks) can be repaired when the file is decrypted
+///   instead of failing authentication
+/// - Checking a file with parity, repairing it, and removing the parity before it is
+///   decrypted
+///
+/// The file is taken in blocks of `DATA_SHARDS` parts of `SHARD_LEN` bytes, and every
+/// block gets `PARITY_SHARDS` parity parts. A CRC-32 of every part is kept, so damaged
+/// parts are found and rebuilt from the others: up to `PARITY_SHARDS` damaged parts per
+/// block can be repaired. The parity adds about 13% to the file.
+///
+/// The parity follows the encrypted file unchanged: the file, the parity parts of every
+/// block, the CRC-32 of every part, and a trailer ending in `PARITY_MAGIC`.
+use std::path::Path;
+use reed_solomon_erasure::galois_8::ReedSolomon;
+
+use crate::chunk_tuning;
+use crate::encryption::EncryptionError;
+use crate::logger::get_logger;
+
+/// Length of a part of a block
+const SHARD_LEN: usize = 4096;
+
+/// Data parts in a block
+const DATA_SHARDS: usize = 32;
+
+/// Parity parts in a block, and so the damaged parts a block can have and be repaired
+const PARITY_SHARDS: usize = 4;
+
+/// Ends a file with parity
+const PARITY_MAGIC: &[u8; 8] = b"CRUSTYRS";
+
+/// Length of the trailer: data length, part length, data and parity parts, CRC-32 of
+/// these fields, and the magic
+const TRAILER_LEN: usize = 8 + 4 + 1 + 1 + 4 + 8;
+
+/// How a file with parity is laid out, read from its trailer
+struct Layout {
+    data_len: usize,
+    shard_len: usize,
+    data_shards: usize,
+    parity_shards: usize,
+}
+
+impl Layout {
+    /// Number of blocks of data
+    fn blocks(&self) -> usize {
+        self.data_len.div_ceil(self.shard_len * self.data_shards)
+    }
+
+    /// Where the parity parts start
+    fn parity_start(&self) -> usize {
+        self.data_len
+    }
+
+    /// Where the CRC-32 of the parts start
+    fn checksums_start(&self) -> usize {
+        self.parity_start() + self.blocks() * self.parity_shards * self.shard_len
+    }
+
+    /// Length of the whole file
+    fn file_len(&self) -> usize {
+        self.checksums_start() + self.blocks() * (self.data_shards + self.parity_shards) * 4 + TRAILER_LEN
+    }
+}
+
+/// Error for a file whose parity cannot be used
+fn damaged_parity(detail: &str) -> EncryptionError {
+    EncryptionError::Decryption(format!("The parity of the file is damaged ({})", detail))
+}
+
+/// Split a block into data parts, the last one padded with zeros, followed by empty
+/// parity parts
+fn block_shards(block: &[u8], shard_len: usize, data_shards: usize, parity_shards: usize) -> Vec<Vec<u8>> {
+    (0..data_shards + parity_shards)
+        .map(|i| {
+            let start = (i * shard_len).min(block.len());
+            let end = ((i + 1) * shard_len).min(block.len());
+            let mut shard = if i < data_shards { block[start..end].to_vec() } else { Vec::new() };
+            shard.resize(shard_len, 0);
+            shard
+        })
+        .collect()
+}
+
+/// Append parity to an encrypted file's contents
+pub fn append_parity(data: &mut Vec<u8>) {
+    let codec = ReedSolomon::new(DATA_SHARDS, PARITY_SHARDS).expect("valid number of parts");
+    let data_len = data.len();
+
+    let mut parity = Vec::new();
+    let mut checksums = Vec::new();
+    for block in data.chunks(SHARD_LEN * DATA_SHARDS) {
+        let mut shards = block_shards(block, SHARD_LEN, DATA_SHARDS, PARITY_SHARDS);
+        codec.encode(&mut shards).expect("parts of equal length");
+        for shard in &shards {
+            checksums.extend_from_slice(&crc32fast::hash(shard).to_be_bytes());
+        }
+        for shard in &shards[DATA_SHARDS..] {
+            parity.extend_from_slice(shard);
+        }
+    }
+
+    let mut trailer = (data_len as u64).to_be_bytes().to_vec();
+    trailer.extend_from_slice(&(SHARD_LEN as u32).to_be_bytes());
+    trailer.push(DATA_SHARDS as u8);
+    trailer.push(PARITY_SHARDS as u8);
+    let trailer_checksum = crc32fast::hash(&trailer);
+    trailer.extend_from_slice(&trailer_checksum.to_be_bytes());
+    trailer.extend_from_slice(PARITY_MAGIC);
+
+    data.extend(parity);
+    data.extend(checksums);
+    data.extend(trailer);
+}
+
+/// Get the most data that fits in the given space once parity is appended (whole blocks
+/// only)
+pub fn max_data_len(capacity: u64) -> u64 {
+    let block = (SHARD_LEN * DATA_SHARDS) as u64;
+    let per_block = block + (SHARD_LEN * PARITY_SHARDS) as u64 + ((DATA_SHARDS + PARITY_SHARDS) * 4) as u64;
+    capacity.saturating_sub(TRAILER_LEN as u64) / per_block * block
+}
+
+/// Check whether a file's contents end with parity
+pub fn has_parity(data: &[u8]) -> bool {
+    data.ends_with(PARITY_MAGIC)
+}
+
+/// Read the layout from the trailer of a file with parity
+fn read_layout(data: &[u8]) -> Result<Layout, EncryptionError> {
+    let trailer_start = data.len().checked_sub(TRAILER_LEN)
+        .ok_or_else(|| damaged_parity("the file is too short"))?;
+    let trailer = &data[trailer_start..];
+    let checksum = u32::from_be_bytes([trailer[14], trailer[15], trailer[16], trailer[17]]);
+    if crc32fast::hash(&trailer[..14]) != checksum {
+        return Err(damaged_parity("the trailer is damaged"));
+    }
+
+    let data_len = u64::from_be_bytes(trailer[..8].try_into().unwrap());
+    let layout = Layout {
+        data_len: usize::try_from(data_len).map_err(|_| damaged_parity("the file is too large"))?,
+        shard_len: u32::from_be_bytes([trailer[8], trailer[9], trailer[10], trailer[11]]) as usize,
+        data_shards: trailer[12] as usize,
+        parity_shards: trailer[13] as usize,
+    };
+    if layout.shard_len == 0 || layout.data_shards == 0 || layout.parity_shards == 0 || layout.data_len > data.len() {
+        return Err(damaged_parity("the trailer is invalid"));
+    }
+    if layout.file_len() != data.len() {
+        return Err(damaged_parity("the file was cut short or has data added"));
+    }
+    Ok(layout)
+}
+
+/// Check a file's contents against its parity, repair damaged parts, and remove the
+/// parity (contents without parity are returned as they are)
+///
+/// # Returns
+/// * `Result<(Vec<u8>, usize), EncryptionError>` - The encrypted file and the number of
+///   damaged parts repaired, or an error if the file is too damaged to repair
+pub fn remove_parity(mut data: Vec<u8>) -> Result<(Vec<u8>, usize), EncryptionError> {
+    if !has_parity(&data) {
+        return Ok((data, 0));
+    }
+
+    let layout = read_layout(&data)?;
+    let Layout { data_len, shard_len, data_shards, parity_shards } = layout;
+    let total_shards = data_shards + parity_shards;
+    let codec = ReedSolomon::new(data_shards, parity_shards)
+        .map_err(|e| damaged_parity(&e.to_string()))?;
+
+    let mut repaired = 0;
+    for block in 0..layout.blocks() {
+        let block_start = block * shard_len * data_shards;
+        let block_end = (block_start + shard_len * data_shards).min(data_len);
+
+        let mut shards: Vec<Option<Vec<u8>>> = block_shards(&data[block_start..block_end], shard_len, data_shards, parity_shards)
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut shard)| {
+                if i >= data_shards {
+                    let start = layout.parity_start() + (block * parity_shards + i - data_shards) * shard_len;
+                    shard.copy_from_slice(&data[start..start + shard_len]);
+                }
+                let at = layout.checksums_start() + (block * total_shards + i) * 4;
+                let checksum = u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+                (crc32fast::hash(&shard) == checksum).then_some(shard)
+            })
+            .collect();
+
+        let damaged: Vec<usize> = (0..total_shards).filter(|&i| shards[i].is_none()).collect();
+        if damaged.is_empty() {
+            continue;
+        }
+        if damaged.len() > parity_shards {
+            return Err(EncryptionError::Decryption(format!(
+                "The file is too damaged to repair: {} of the {} parts of block {} are damaged, and its parity can repair {}",
+                damaged.len(), total_shards, block + 1, parity_shards
+            )));
+        }
+
+        codec.reconstruct_data(&mut shards)
+            .map_err(|e| EncryptionError::Decryption(format!("Failed to repair the file: {}", e)))?;
+        for &i in damaged.iter().filter(|&&i| i < data_shards) {
+            let start = (block_start + i * shard_len).min(block_end);
+            let end = (block_start + (i + 1) * shard_len).min(block_end);
+            let shard = shards[i].as_ref().expect("repaired part");
+            data[start..end].copy_from_slice(&shard[..end - start]);
+        }
+        repaired += damaged.len();
+    }
+
+    data.truncate(data_len);
+    Ok((data, repaired))
+}
+
+/// Read an encrypted file, repairing it with its parity if it has any
+///
+/// Repairs are logged, as the media the file is on is failing.
+///
+/// # Arguments
+/// * `progress_callback` - Called with the fraction read after each chunk
+pub fn read_encrypted_file(path: &Path, progress_callback: impl Fn(f32)) -> Result<Vec<u8>, EncryptionError> {
+    let (data, repaired) = remove_parity(chunk_tuning::read_file(path, progress_callback)?)?;
+    if repaired > 0 {
+        if let Some(logger) = get_logger() {
+            logger.log_success(
+                "Repair",
+                &path.to_string_lossy(),
+                &format!("Repaired {} damaged part(s) with the file's parity. Copy the file to new media", repaired),
+            ).ok();
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+    use crate::backend::{BackendFactory, LocalBackend};
+    use crate::encryption::EncryptionKey;
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_parity_round_trip() {
+        // Without parity, the contents are left alone
+        let plain = sample(1000);
+        assert_eq!(remove_parity(plain.clone()).unwrap(), (plain, 0));
+
+        let original = sample(SHARD_LEN * DATA_SHARDS + 5000);
+        let mut protected = original.clone();
+        append_parity(&mut protected);
+        assert!(has_parity(&protected));
+        assert!(protected.len() < original.len() * 115 / 100 + SHARD_LEN * PARITY_SHARDS);
+        assert_eq!(remove_parity(protected).unwrap(), (original, 0));
+
+        // Data of the largest length for a space fits in it with its parity
+        let capacity = 10 * 1024 * 1024;
+        let mut fitted = sample(max_data_len(capacity) as usize);
+        append_parity(&mut fitted);
+        assert!(fitted.len() as u64 <= capacity);
+        assert!(fitted.len() as u64 > capacity - (SHARD_LEN * (DATA_SHARDS + PARITY_SHARDS)) as u64 - 200);
+    }
+
+    #[test]
+    fn test_parity_repairs_damage() {
+        let original = sample(SHARD_LEN * DATA_SHARDS + 5000);
+        let mut protected = original.clone();
+        append_parity(&mut protected);
+
+        // Damage in two parts of the first block (one of them parity) and in the short
+        // last part of the second block
+        let mut damaged = protected.clone();
+        damaged[10] ^= 0xFF;
+        damaged[original.len() + 7] ^= 0x01;
+        damaged[original.len() - 1] ^= 0x80;
+        let (repaired, count) = remove_parity(damaged).unwrap();
+        assert_eq!(repaired, original);
+        assert_eq!(count, 3);
+
+        // More damaged parts in a block than it has parity for
+        let mut damaged = protected.clone();
+        for part in 0..=PARITY_SHARDS {
+            damaged[part * SHARD_LEN] ^= 0xFF;
+        }
+        assert!(remove_parity(damaged).is_err());
+
+        // A cut-off file cannot use its parity
+        let mut damaged = protected;
+        damaged.remove(0);
+        assert!(remove_parity(damaged).is_err());
+    }
+
+    #[test]
+    fn test_damaged_file_with_parity_decrypts() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("photos.tar");
+        let encrypted = dir.path().join("photos.tar.encrypted");
+        let decrypted = dir.path().join("restored.tar");
+        let content = sample(200_000);
+        fs::write(&source, &content).unwrap();
+
+        let key = EncryptionKey::generate();
+        let backend = BackendFactory::create_local_with(LocalBackend { add_parity: true, ..Default::default() });
+        backend.encrypt_file(&source, &encrypted, &key, |_| {}).unwrap();
+
+        // Bit rot in the ciphertext, which alone would fail authentication
+        let mut stored = fs::read(&encrypted).unwrap();
+        stored[100] ^= 0x10;
+        stored[150_000] ^= 0x01;
+        fs::write(&encrypted, &stored).unwrap();
+
+        backend.decrypt_file(&encrypted, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), content);
+    }
+}
@@ -0,0 +1,377 @@
+/// Multi-recipient module.
+///
+/// This module provides functionality for:
+/// - Parsing a list of recipients: email addresses and age recipients (`age1...`)
+/// - Writing the recipient header of a file encrypted once for several recipients
+/// - Finding the entry of the header a key can open, and unwrapping the file's key
+///
+/// The file is encrypted with a new content key, which is wrapped once per recipient in
+/// the header, so the file is not encrypted again for each person:
+/// - For an email address, with `EncryptionKey::for_recipient`: the recipient needs the
+///   same key, and the address tells the recipients' entries apart
+/// - For an age recipient, with a key from an X25519 exchange between a one-time key and
+///   the recipient: only the key the recipient belongs to can unwrap it
+///
+/// The header also holds an entry for the encrypting key's own age recipient, so the
+/// sender can always decrypt the file. The payload is encrypted with a key derived from
+/// the content key and the whole header, so entries cannot be added or removed.
+use std::fmt;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce
+};
+use bech32::{FromBase32, ToBase32, Variant};
+use hkdf::Hkdf;
+use rand::RngCore;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::encryption::{EncryptionError, EncryptionKey};
+
+/// Start of a file encrypted for several recipients
+const RECIPIENTS_MAGIC: &[u8] = b"CRUSTYMR";
+
+/// Current recipient header version
+const RECIPIENTS_VERSION: u8 = 1;
+
+/// Entry kinds
+const KIND_EMAIL: u8 = 1;
+const KIND_PUBLIC_KEY: u8 = 2;
+
+/// Nonce, content key, and tag
+const WRAPPED_LEN: usize = 12 + 32 + 16;
+
+/// Bech32 prefix of an age recipient
+const RECIPIENT_PREFIX: &str = "age";
+
+/// Context strings for the derived keys
+const WRAP_KDF_INFO: &[u8] = b"CRUSTy-Recipient-Wrap-v1";
+const PAYLOAD_KDF_INFO: &[u8] = b"CRUSTy-Recipient-Payload-v1";
+
+/// Someone a file is encrypted for
+#[derive(Clone, Debug, PartialEq)]
+pub enum Recipient {
+    /// An email address (normalised), for someone holding the same key
+    Email(String),
+    /// An X25519 public key, given as an age recipient (`age1...`)
+    PublicKey([u8; 32]),
+}
+
+impl Recipient {
+    /// Parse an email address or an age recipient
+    pub fn parse(text: &str) -> Result<Recipient, EncryptionError> {
+        let text = text.trim();
+        if text.to_lowercase().starts_with("age1") {
+            let invalid = || EncryptionError::KeyError(format!("'{}' is not an age recipient", text));
+            let (prefix, data, variant) = bech32::decode(text).map_err(|_| invalid())?;
+            let bytes = Vec::<u8>::from_base32(&data).map_err(|_| invalid())?;
+            if prefix != RECIPIENT_PREFIX || variant != Variant::Bech32 {
+                return Err(invalid());
+            }
+            return bytes.try_into().map(Recipient::PublicKey).map_err(|_| invalid());
+        }
+
+        let email = text.to_lowercase();
+        match email.split_once('@') {
+            Some((username, domain)) if !username.is_empty() && domain.contains('.')
+                && !domain.contains('@') && !email.contains(char::is_whitespace) => Ok(Recipient::Email(email)),
+            _ => Err(EncryptionError::KeyError(format!("'{}' is not an email address or age recipient", text))),
+        }
+    }
+
+    /// Get the recipient of a key's own age identity
+    pub fn of_key(key: &EncryptionKey) -> Recipient {
+        Recipient::PublicKey(public_key(key).to_bytes())
+    }
+}
+
+impl fmt::Display for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Recipient::Email(email) => write!(f, "{}", email),
+            Recipient::PublicKey(public) => write!(
+                f,
+                "{}",
+                bech32::encode(RECIPIENT_PREFIX, public.to_base32(), Variant::Bech32).expect("valid prefix")
+            ),
+        }
+    }
+}
+
+/// Parse a list of recipients separated by commas, semicolons, spaces, or new lines
+///
+/// # Returns
+/// * `Result<Vec<Recipient>, EncryptionError>` - The recipients without duplicates, or an
+///   error naming the first entry that is not a recipient, or if there are none
+pub fn parse_list(text: &str) -> Result<Vec<Recipient>, EncryptionError> {
+    let mut recipients: Vec<Recipient> = Vec::new();
+    for entry in text.split(|c: char| c == ',' || c == ';' || c.is_whitespace()).filter(|entry| !entry.is_empty()) {
+        let recipient = Recipient::parse(entry)?;
+        if !recipients.contains(&recipient) {
+            recipients.push(recipient);
+        }
+    }
+
+    if recipients.is_empty() {
+        return Err(EncryptionError::KeyError("No recipients were given".to_string()));
+    }
+    if recipients.len() >= u8::MAX as usize {
+        return Err(EncryptionError::KeyError(format!("A file can have at most {} recipients", u8::MAX - 1)));
+    }
+    Ok(recipients)
+}
+
+/// Get the X25519 public key of a key's age identity
+fn public_key(key: &EncryptionKey) -> PublicKey {
+    PublicKey::from(&StaticSecret::from(key.key))
+}
+
+/// Derive the key a content key is wrapped with for an age recipient
+fn derive_wrap_key(shared_secret: &[u8], ephemeral_public: &[u8], recipient_public: &[u8]) -> Zeroizing<[u8; 32]> {
+    // Bind the derived key to both public keys
+    let mut salt = Vec::with_capacity(64);
+    salt.extend_from_slice(ephemeral_public);
+    salt.extend_from_slice(recipient_public);
+
+    let mut okm = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(Some(&salt), shared_secret)
+        .expand(WRAP_KDF_INFO, &mut *okm)
+        .expect("32 bytes is a valid HKDF output length");
+    okm
+}
+
+/// Derive the key the payload is encrypted with from the content key and the header
+fn derive_payload_key(content_key: &[u8], header: &[u8]) -> EncryptionKey {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(header), content_key)
+        .expand(PAYLOAD_KDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF output length");
+    EncryptionKey { key, kdf: None }
+}
+
+/// Wrap the content key with AES-256-GCM (the entry's label is authenticated with it)
+fn wrap(wrap_key: &[u8], content_key: &[u8], label: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let wrapped = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(wrap_key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: content_key, aad: label })
+        .map_err(|e| EncryptionError::Encryption(format!("Failed to wrap the key: {}", e)))?;
+
+    let mut entry = nonce.to_vec();
+    entry.extend_from_slice(&wrapped);
+    Ok(entry)
+}
+
+/// Unwrap a content key (None if the wrap key is not the one it was wrapped with)
+fn unwrap(wrap_key: &[u8], wrapped: &[u8], label: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(wrap_key))
+        .decrypt(Nonce::from_slice(&wrapped[..12]), Payload { msg: &wrapped[12..], aad: label })
+        .ok()
+        .filter(|content_key| content_key.len() == 32)
+        .map(Zeroizing::new)
+}
+
+/// Create the recipient header of a file encrypted for several recipients
+///
+/// # Arguments
+/// * `key` - The encrypting key (for email recipients, and its own entry)
+/// * `recipients` - The other recipients
+///
+/// # Returns
+/// * `Result<(Vec<u8>, EncryptionKey), EncryptionError>` - The header, written before the
+///   ciphertext, and the key the file's contents are encrypted with
+pub fn recipient_header(key: &EncryptionKey, recipients: &[Recipient]) -> Result<(Vec<u8>, EncryptionKey), EncryptionError> {
+    let own = Recipient::of_key(key);
+    let mut all_recipients = vec![own.clone()];
+    all_recipients.extend(recipients.iter().filter(|recipient| **recipient != own).cloned());
+    if all_recipients.len() > u8::MAX as usize {
+        return Err(EncryptionError::KeyError(format!("A file can have at most {} recipients", u8::MAX - 1)));
+    }
+
+    let mut content_key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(&mut *content_key);
+
+    let mut header = RECIPIENTS_MAGIC.to_vec();
+    header.push(RECIPIENTS_VERSION);
+    header.push(all_recipients.len() as u8);
+    for recipient in &all_recipients {
+        match recipient {
+            Recipient::Email(email) => {
+                let wrap_key = key.for_recipient(email)?;
+                header.push(KIND_EMAIL);
+                header.extend_from_slice(&(email.len() as u16).to_be_bytes());
+                header.extend_from_slice(email.as_bytes());
+                header.extend_from_slice(&wrap(&wrap_key.key, &*content_key, email.as_bytes())?);
+            },
+            Recipient::PublicKey(public) => {
+                let ephemeral = StaticSecret::random_from_rng(OsRng);
+                let ephemeral_public = PublicKey::from(&ephemeral);
+                let shared = ephemeral.diffie_hellman(&PublicKey::from(*public));
+                let wrap_key = derive_wrap_key(shared.as_bytes(), ephemeral_public.as_bytes(), public);
+                header.push(KIND_PUBLIC_KEY);
+                header.extend_from_slice(public);
+                header.extend_from_slice(ephemeral_public.as_bytes());
+                header.extend_from_slice(&wrap(&*wrap_key, &*content_key, public)?);
+            },
+        }
+    }
+
+    let payload_key = derive_payload_key(&*content_key, &header);
+    Ok((header, payload_key))
+}
+
+/// One recipient's entry in a recipient header
+struct Entry {
+    recipient: Recipient,
+    /// One-time public key (for age recipients)
+    ephemeral_public: Option<[u8; 32]>,
+    wrapped: Vec<u8>,
+}
+
+/// Recipient header read from a file
+pub struct RecipientHeader {
+    entries: Vec<Entry>,
+    /// The header as written, which the payload key is derived from
+    bytes: Vec<u8>,
+}
+
+impl RecipientHeader {
+    /// Unwrap the file's key with a key
+    ///
+    /// # Returns
+    /// * `Result<(Recipient, EncryptionKey), EncryptionError>` - The recipient whose entry
+    ///   the key opened, and the key the file's contents are encrypted with, or an error if
+    ///   the file was not encrypted for the key
+    pub fn open(&self, key: &EncryptionKey) -> Result<(Recipient, EncryptionKey), EncryptionError> {
+        let own_public = public_key(key);
+        for entry in &self.entries {
+            let content_key = match (&entry.recipient, &entry.ephemeral_public) {
+                (Recipient::Email(email), _) => {
+                    let wrap_key = key.for_recipient(email)?;
+                    unwrap(&wrap_key.key, &entry.wrapped, email.as_bytes())
+                },
+                (Recipient::PublicKey(public), Some(ephemeral_public)) if *public == own_public.to_bytes() => {
+                    let shared = StaticSecret::from(key.key).diffie_hellman(&PublicKey::from(*ephemeral_public));
+                    let wrap_key = derive_wrap_key(shared.as_bytes(), ephemeral_public, public);
+                    unwrap(&*wrap_key, &entry.wrapped, public)
+                },
+                _ => None,
+            };
+            if let Some(content_key) = content_key {
+                return Ok((entry.recipient.clone(), derive_payload_key(&content_key, &self.bytes)));
+            }
+        }
+
+        Err(EncryptionError::Decryption(
+            "Authentication failed: the file was not encrypted for this key or any of its recipients".to_string()
+        ))
+    }
+}
+
+/// Split the recipient header from the start of an encrypted file
+///
+/// # Returns
+/// * `Result<(Option<RecipientHeader>, &[u8]), EncryptionError>` - The header (None for
+///   files encrypted with the key alone) and the data after it
+pub fn split_recipient_header(data: &[u8]) -> Result<(Option<RecipientHeader>, &[u8]), EncryptionError> {
+    let rest = match data.strip_prefix(RECIPIENTS_MAGIC) {
+        Some(rest) => rest,
+        None => return Ok((None, data)),
+    };
+
+    let invalid = || EncryptionError::Decryption("The recipient header is damaged".to_string());
+    let take = |data: &mut &[u8], len: usize| -> Result<Vec<u8>, EncryptionError> {
+        if data.len() < len {
+            return Err(invalid());
+        }
+        let (taken, rest) = data.split_at(len);
+        *data = rest;
+        Ok(taken.to_vec())
+    };
+
+    let mut rest = rest;
+    let start = take(&mut rest, 2)?;
+    if start[0] != RECIPIENTS_VERSION {
+        return Err(EncryptionError::Decryption(format!("Unsupported recipient header version {}", start[0])));
+    }
+
+    let mut entries = Vec::with_capacity(start[1] as usize);
+    for _ in 0..start[1] {
+        let kind = take(&mut rest, 1)?[0];
+        let entry = match kind {
+            KIND_EMAIL => {
+                let len = take(&mut rest, 2)?;
+                let email = take(&mut rest, u16::from_be_bytes([len[0], len[1]]) as usize)?;
+                let email = String::from_utf8(email).map_err(|_| invalid())?;
+                Entry { recipient: Recipient::Email(email), ephemeral_public: None, wrapped: take(&mut rest, WRAPPED_LEN)? }
+            },
+            KIND_PUBLIC_KEY => {
+                let public = take(&mut rest, 32)?.try_into().map_err(|_| invalid())?;
+                let ephemeral_public = take(&mut rest, 32)?.try_into().map_err(|_| invalid())?;
+                Entry {
+                    recipient: Recipient::PublicKey(public),
+                    ephemeral_public: Some(ephemeral_public),
+                    wrapped: take(&mut rest, WRAPPED_LEN)?,
+                }
+            },
+            _ => return Err(invalid()),
+        };
+        entries.push(entry);
+    }
+
+    let header_len = data.len() - rest.len();
+    Ok((Some(RecipientHeader { entries, bytes: data[..header_len].to_vec() }), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::age_format;
+    use crate::encryption::{decrypt_data, encrypt_data};
+
+    #[test]
+    fn test_any_recipient_opens_the_file() {
+        let sender = EncryptionKey::generate();
+        let colleague = EncryptionKey::generate();
+        let recipients = parse_list(&format!(
+            "Alice@Example.com; bob@example.com,\n{}", age_format::recipient_string(&colleague)
+        )).unwrap();
+        assert_eq!(recipients[0], Recipient::Email("alice@example.com".to_string()));
+        assert_eq!(recipients[2].to_string(), age_format::recipient_string(&colleague));
+
+        let (header, payload_key) = recipient_header(&sender, &recipients).unwrap();
+        let mut file = header.clone();
+        file.extend_from_slice(&encrypt_data(b"Quarterly report", &payload_key).unwrap());
+
+        let (found, ciphertext) = split_recipient_header(&file).unwrap();
+        let found = found.unwrap();
+        assert_eq!(found.entries.len(), 4);
+
+        // The sender opens its own entry, and the colleague the entry for their recipient
+        for (key, expected) in [(&sender, Recipient::of_key(&sender)), (&colleague, recipients[2].clone())] {
+            let (recipient, payload_key) = found.open(key).unwrap();
+            assert_eq!(recipient, expected);
+            assert_eq!(decrypt_data(ciphertext, &payload_key).unwrap(), b"Quarterly report");
+        }
+        assert!(found.open(&EncryptionKey::generate()).is_err());
+
+        // Removing a recipient changes the payload key
+        let mut tampered = file.clone();
+        tampered[RECIPIENTS_MAGIC.len() + 1] -= 1;
+        let (found, ciphertext) = split_recipient_header(&tampered).unwrap();
+        let (_, payload_key) = found.unwrap().open(&sender).unwrap();
+        assert!(decrypt_data(ciphertext, &payload_key).is_err());
+
+        assert!(split_recipient_header(b"plain ciphertext").unwrap().0.is_none());
+    }
+
+    #[test]
+    fn test_parse_list_rejects_invalid_entries() {
+        assert!(parse_list("").is_err());
+        assert!(parse_list("alice@example.com, not-an-address").is_err());
+        assert!(parse_list("age1notarecipient").is_err());
+        assert_eq!(parse_list("alice@example.com ALICE@example.com").unwrap().len(), 1);
+    }
+}
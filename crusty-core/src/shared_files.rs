@@ -0,0 +1,113 @@
+/// Shared files module.
+///
+/// This module provides functionality for:
+/// - Locking a file shared by several running copies of CRUSTy (the GUI, a second
+///   window, or a command-line run), so one copy's changes are not lost to another's
+/// - Writing a file atomically, so a crash or a reader never sees it half written
+///
+/// Files that are changed piece by piece (registries and records) are updated by
+/// taking the lock, loading the file again, applying the change, and saving it, so
+/// changes made meanwhile by another copy are kept rather than overwritten.
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Exclusive lock on a shared file, released when dropped (or when the process exits)
+pub struct FileLock {
+    _file: File,
+}
+
+/// Get the lock file of a shared file (`<name>.lock` next to it)
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.file_name().unwrap_or_default());
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+/// Lock a shared file, waiting while another copy of CRUSTy holds the lock
+///
+/// The lock is advisory and taken on a separate lock file, so the shared file itself
+/// can still be replaced by `write_atomic` while it is held.
+pub fn lock(path: &Path) -> io::Result<FileLock> {
+    let lock_path = lock_path(path);
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+    file.lock()?;
+    Ok(FileLock { _file: file })
+}
+
+/// Write a file atomically: the contents are written to a temporary file in the same
+/// folder, flushed to disk, and renamed over the file
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Unique per process and call, so two copies saving at once never share a file
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}-{:08x}.tmp", std::process::id(), rand::random::<u32>()));
+    let temp = path.with_file_name(name);
+
+    let result = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_atomic() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("registry.json");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+
+        // No temporary files are left behind
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_lock_waits_for_holder() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("registry.json");
+        assert_eq!(lock_path(&path), dir.path().join("registry.json.lock"));
+
+        let held = lock(&path).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let waiter_path = path.clone();
+        let waiter = thread::spawn(move || {
+            let _lock = lock(&waiter_path).unwrap();
+            sender.send(()).unwrap();
+        });
+
+        // The second lock is only granted once the first is dropped
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(held);
+        receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+        waiter.join().unwrap();
+    }
+}
@@ -0,0 +1,1837 @@
+/// Split-key functionality for enhanced security.
+///
+/// This module provides Shamir's Secret Sharing implementation for splitting
+/// encryption keys into multiple shares, allowing for more secure key management
+/// and multi-party authorization for decryption. Custodians can periodically
+/// verify that their stored shares are still readable without reconstructing the key.
+/// Share phrases can be checked word by word as they are typed, and shares can be read
+/// out over the phone as short numbered groups that each carry a check character.
+/// Saved and relayed transfer shares carry a seal of their package, so a changed, cut
+/// short, or mismatched share is reported as such. A transfer package can be bound to
+/// its recipient (a passphrase or their machine identity), so that even all of its
+/// shares do not give the key without the recipient's credential.
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use sharks::{Share, Sharks};
+use keyring::Entry;
+use qrcode::{QrCode, render::svg};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use data_encoding::BASE32;
+use chrono::{Local, NaiveDateTime};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use hkdf::Hkdf;
+use rand::RngCore;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::encryption::{self, EncryptionKey, KdfParams};
+use crate::shared_files;
+
+/// Length of a share of a CRUSTy key (the share number and the Base64 key)
+pub const KEY_SHARE_LEN: usize = 1 + 44;
+
+/// Number of words in the phrase of a key share (one per character of its share text:
+/// the 5 header bytes and the share, in Base32)
+pub const KEY_SHARE_PHRASE_WORDS: usize = (5 + KEY_SHARE_LEN) * 8 / 5;
+
+/// Number of words holding the share header (format version, share number, threshold)
+/// and its checksum
+const HEADER_WORDS: usize = 8;
+
+/// Characters of the share text format (Base32 and its padding), the only characters a
+/// share phrase stands for
+const SHARE_TEXT_ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567=";
+
+/// Number of share text characters in a dictation group (followed by its check character)
+pub const DICTATION_GROUP_LEN: usize = 4;
+
+/// Number of dictation groups of a key share
+pub const KEY_SHARE_DICTATION_GROUPS: usize = KEY_SHARE_PHRASE_WORDS.div_ceil(DICTATION_GROUP_LEN);
+
+/// Spelling alphabet used to read dictation groups aloud
+const SPELLING_ALPHABET: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India",
+    "Juliett", "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo",
+    "Sierra", "Tango", "Uniform", "Victor", "Whiskey", "X-ray", "Yankee", "Zulu",
+];
+
+/// Error type for split key operations
+#[derive(Debug)]
+pub enum SplitKeyError {
+    /// Error related to Shamir's Secret Sharing
+    Sharing(String),
+    /// Error related to key storage
+    Storage(String),
+    /// Error related to QR code generation
+    QrCode(String),
+    /// Error related to file I/O
+    Io(std::io::Error),
+    /// Error related to key operations
+    Key(String),
+    /// Error related to share encoding/decoding
+    Encoding(String),
+    /// Error related to transfer operations
+    Transfer(String),
+}
+
+impl fmt::Display for SplitKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitKeyError::Sharing(msg) => write!(f, "Sharing error: {}", msg),
+            SplitKeyError::Storage(msg) => write!(f, "Storage error: {}", msg),
+            SplitKeyError::QrCode(msg) => write!(f, "QR code error: {}", msg),
+            SplitKeyError::Io(err) => write!(f, "I/O error: {}", err),
+            SplitKeyError::Key(msg) => write!(f, "Key error: {}", msg),
+            SplitKeyError::Encoding(msg) => write!(f, "Encoding error: {}", msg),
+            SplitKeyError::Transfer(msg) => write!(f, "Transfer error: {}", msg),
+        }
+    }
+}
+
+impl Error for SplitKeyError {}
+
+impl From<std::io::Error> for SplitKeyError {
+    fn from(err: std::io::Error) -> Self {
+        SplitKeyError::Io(err)
+    }
+}
+
+/// Calculate CRC16 checksum
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            if (crc & 0x8000) != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    
+    crc
+}
+
+// A small subset of common words for mnemonic encoding
+// In a real implementation, you would use a larger wordlist like BIP39
+const WORDLIST: [&str; 232] = [
+    "apple", "banana", "cherry", "dog", "elephant", "fox", "grape", "horse", "igloo", "jacket",
+    "kite", "lemon", "mango", "nest", "orange", "pear", "queen", "rabbit", "sun", "tree",
+    "umbrella", "violet", "water", "xylophone", "yellow", "zebra", "air", "book", "cat", "door",
+    "earth", "fire", "gold", "hat", "ice", "jar", "key", "lamp", "moon", "nail",
+    "ocean", "paper", "quilt", "river", "star", "table", "uncle", "vase", "wind", "box",
+    "yard", "zoo", "ant", "bear", "cow", "duck", "egg", "fish", "goat", "hen",
+    "ink", "jam", "king", "lion", "milk", "nut", "owl", "pig", "quail", "rat",
+    "sheep", "tiger", "urn", "van", "wolf", "yak", "zebra", "arrow", "ball", "coin",
+    "dice", "eye", "flag", "gift", "hand", "iron", "jewel", "knife", "leaf", "map",
+    "needle", "oar", "pen", "quartz", "rope", "sail", "tea", "urn", "veil", "wheel",
+    "yarn", "zest", "arch", "bell", "cake", "desk", "egg", "fork", "gate", "hill",
+    "ink", "jug", "kite", "lock", "mask", "net", "oven", "pot", "quilt", "ring",
+    "sock", "toy", "urn", "vase", "well", "box", "yarn", "zone", "atom", "boat",
+    "card", "drum", "eel", "flute", "gear", "harp", "ink", "jade", "keel", "lens",
+    "mast", "note", "opal", "pipe", "quill", "reed", "sail", "tube", "urn", "valve",
+    "wire", "xray", "yarn", "zinc", "ace", "bat", "cap", "dart", "ear", "fan",
+    "gem", "hat", "ice", "jet", "key", "lid", "mat", "net", "orb", "pin",
+    "queen", "rod", "saw", "tag", "urn", "vat", "web", "box", "yam", "zip",
+    "arc", "bin", "cup", "dot", "elf", "fin", "gun", "hut", "ink", "jar",
+    "kit", "log", "mug", "nut", "oil", "pan", "quip", "rag", "sip", "tin",
+    "urn", "van", "wig", "box", "yew", "zap", "arm", "bug", "cog", "den",
+    "eel", "fog", "gum", "hog", "ink", "jaw", "kit", "leg", "map", "nap",
+    "oak", "peg", "quiz", "rib", "sap", "toe", "urn", "vet", "wax", "box",
+    "yak", "zip"
+];
+
+/// Convert text to a mnemonic phrase
+fn text_to_mnemonic(text: &str) -> Result<String, String> {
+    // Remove dashes and whitespace
+    let clean_text = text.replace(['-', ' '], "");
+    
+    // Convert to bytes
+    let bytes = clean_text.as_bytes();
+    
+    // Convert each byte to a word
+    let mut words = Vec::with_capacity(bytes.len());
+    for &byte in bytes {
+        words.push(WORDLIST[byte as usize]);
+    }
+    
+    // Join with spaces
+    Ok(words.join(" "))
+}
+
+/// Convert a mnemonic phrase back to text
+fn mnemonic_to_text(mnemonic: &str) -> Result<String, String> {
+    mnemonic.split_whitespace()
+        .map(|word| share_word_char(word).ok_or_else(|| format!("Unknown word in mnemonic: {}", word)))
+        .collect()
+}
+
+/// Get the share text character a word of a share phrase stands for
+///
+/// Only the words of share text characters are looked up, as the word list repeats
+/// some words: "zebra" stands for "L" in a phrase, not for its first place in the list.
+fn share_word_char(word: &str) -> Option<char> {
+    let word = word.to_lowercase();
+    SHARE_TEXT_ALPHABET.chars().find(|&c| WORDLIST[c as usize] == word)
+}
+
+/// Get the words share phrases are made of (for suggestions while a phrase is typed)
+pub fn share_words() -> impl Iterator<Item = &'static str> {
+    SHARE_TEXT_ALPHABET.chars().map(|c| WORDLIST[c as usize])
+}
+
+/// State of a share phrase being typed
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhraseStatus {
+    /// More words are needed (the number of words so far)
+    Incomplete(usize),
+    /// The phrase is a complete share
+    Valid,
+    /// The phrase cannot be a share
+    Invalid(String),
+}
+
+/// Result of checking a share phrase as it is typed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhraseCheck {
+    /// Positions of the wrong words: words not in the word list, or the header words
+    /// when their checksum fails
+    pub wrong_words: Vec<usize>,
+    pub status: PhraseStatus,
+}
+
+/// Check a share phrase word by word, as it is typed
+///
+/// The first 8 words hold the share header and its checksum, so a mistake there is
+/// caught as soon as they are entered. When changing a single word would make the
+/// checksum match, only that word is marked wrong; otherwise all 8 are. The rest of the
+/// phrase has no checksum, so its words can only be checked against the word list.
+pub fn check_share_phrase(phrase: &str) -> PhraseCheck {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    let chars: Vec<Option<char>> = words.iter().map(|word| share_word_char(word)).collect();
+    
+    let unknown: Vec<usize> = (0..words.len()).filter(|&i| chars[i].is_none()).collect();
+    if let Some(&first) = unknown.first() {
+        return PhraseCheck {
+            status: PhraseStatus::Invalid(format!("\"{}\" (word {}) is not in the word list", words[first], first + 1)),
+            wrong_words: unknown,
+        };
+    }
+    
+    let text: Vec<char> = chars.into_iter().flatten().collect();
+    if text.len() >= HEADER_WORDS && !header_is_valid(&text[..HEADER_WORDS]) {
+        let fixable: Vec<usize> = (0..HEADER_WORDS)
+            .filter(|&i| SHARE_TEXT_ALPHABET.chars().any(|c| {
+                let mut header = text[..HEADER_WORDS].to_vec();
+                header[i] = c;
+                c != text[i] && header_is_valid(&header)
+            }))
+            .collect();
+        return PhraseCheck {
+            wrong_words: if fixable.len() == 1 { fixable } else { (0..HEADER_WORDS).collect() },
+            status: PhraseStatus::Invalid(format!("The first {} words do not match their checksum", HEADER_WORDS)),
+        };
+    }
+    
+    let status = match text.len().cmp(&KEY_SHARE_PHRASE_WORDS) {
+        Ordering::Less => PhraseStatus::Incomplete(text.len()),
+        Ordering::Greater => PhraseStatus::Invalid(format!("Too many words: a share phrase has {}", KEY_SHARE_PHRASE_WORDS)),
+        Ordering::Equal => match SplitEncryptionKey::share_from_text(&text.iter().collect::<String>()) {
+            Ok(_) => PhraseStatus::Valid,
+            Err(e) => PhraseStatus::Invalid(e.to_string()),
+        },
+    };
+    PhraseCheck { wrong_words: Vec::new(), status }
+}
+
+/// Check the header words of a share (format version and checksum)
+fn header_is_valid(header: &[char]) -> bool {
+    let header: String = header.iter().collect();
+    match BASE32.decode(header.as_bytes()) {
+        Ok(bytes) if bytes.len() == 5 => {
+            bytes[0] == 1 && crc16(&bytes[..3]) == u16::from_be_bytes([bytes[3], bytes[4]])
+        },
+        _ => false,
+    }
+}
+
+/// Read a share text character the way it was probably meant
+///
+/// Share text has no 0, 1 or 8, so these are taken for the letters they are confused
+/// with (O, I and B), whether misread by OCR or misheard over the phone.
+pub fn normalize_share_char(c: char) -> char {
+    match c.to_ascii_uppercase() {
+        '0' => 'O',
+        '1' => 'I',
+        '8' => 'B',
+        '—' | '–' => '-',
+        c => c,
+    }
+}
+
+/// A numbered group of share text, as read out over the phone
+#[derive(Debug, Clone, PartialEq)]
+pub struct DictationGroup {
+    /// Group number, from 1
+    pub number: usize,
+    /// The share text characters
+    pub chars: String,
+    /// Check character of the number and the characters
+    pub check: char,
+}
+
+impl DictationGroup {
+    /// Spell the characters and the check character with the spelling alphabet
+    /// ("Mike Two Romeo Golf, check X-ray")
+    pub fn spelled(&self) -> String {
+        let spell = |c: char| match c {
+            'A'..='Z' => SPELLING_ALPHABET[c as usize - 'A' as usize].to_string(),
+            '2' => "Two".to_string(),
+            '3' => "Three".to_string(),
+            '4' => "Four".to_string(),
+            '5' => "Five".to_string(),
+            '6' => "Six".to_string(),
+            '7' => "Seven".to_string(),
+            '=' => "Equals".to_string(),
+            c => c.to_string(),
+        };
+        let chars: Vec<String> = self.chars.chars().map(spell).collect();
+        format!("{}, check {}", chars.join(" "), spell(self.check))
+    }
+}
+
+impl fmt::Display for DictationGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02} {} {}", self.number, self.chars, self.check)
+    }
+}
+
+/// Compute the check character of a dictation group
+///
+/// The characters are weighted by odd numbers, so any single wrong character changes
+/// the check character, as do most swapped neighbours; the group number is included,
+/// so a group entered on the wrong line is caught too.
+fn dictation_check(number: usize, chars: &str) -> Option<char> {
+    let mut sum = number;
+    for (i, c) in chars.chars().enumerate() {
+        sum += (2 * i + 1) * SHARE_TEXT_ALPHABET.find(c)?;
+    }
+    SHARE_TEXT_ALPHABET.chars().nth(sum % 32)
+}
+
+/// Split share text into numbered dictation groups
+pub fn share_text_to_dictation(text: &str) -> Vec<DictationGroup> {
+    let clean: Vec<char> = text.chars().filter(|c| !matches!(c, '-' | ' ')).collect();
+    clean.chunks(DICTATION_GROUP_LEN)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let chars: String = chunk.iter().collect();
+            let check = dictation_check(i + 1, &chars).unwrap_or('?');
+            DictationGroup { number: i + 1, chars, check }
+        })
+        .collect()
+}
+
+/// Check one dictation group as it is entered
+///
+/// # Arguments
+/// * `number` - The group number (from 1)
+/// * `entry` - The characters followed by the check character; spaces and dashes are
+///   ignored, and letters may be in either case
+///
+/// # Returns
+/// * `Result<String, String>` - The share text characters of the group, or what is
+///   wrong with it
+pub fn check_dictation_group(number: usize, entry: &str) -> Result<String, String> {
+    let entry: String = entry.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(normalize_share_char)
+        .collect();
+    if let Some(c) = entry.chars().find(|&c| !SHARE_TEXT_ALPHABET.contains(c)) {
+        return Err(format!("\"{}\" is not used in shares", c));
+    }
+    
+    let len = entry.chars().count();
+    if !(2..=DICTATION_GROUP_LEN + 1).contains(&len) {
+        return Err(format!("A group has {} characters and a check character", DICTATION_GROUP_LEN));
+    }
+    
+    let (chars, check) = entry.split_at(len - 1);
+    if dictation_check(number, chars) == check.chars().next() {
+        Ok(chars.to_string())
+    } else {
+        Err(format!("Group {} does not match its check character; ask for it again", number))
+    }
+}
+
+/// Put share text back together from its dictation groups, one per line
+/// ("07 MFRG X"), checking each group
+fn share_text_from_dictation(dictation: &str) -> Result<String, SplitKeyError> {
+    let mut text = String::new();
+    for (i, line) in dictation.lines().filter(|line| !line.trim().is_empty()).enumerate() {
+        let line = line.trim();
+        let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(line.len());
+        if line[..digits].parse::<usize>().ok() != Some(i + 1) {
+            return Err(SplitKeyError::Encoding(format!("Expected dictation group {} on line {}", i + 1, i + 1)));
+        }
+        let chars = check_dictation_group(i + 1, line[digits..].trim_start_matches([':', '.']))
+            .map_err(SplitKeyError::Encoding)?;
+        text.push_str(&chars);
+    }
+    Ok(text)
+}
+
+/// Share format type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareFormat {
+    /// Binary format (raw bytes)
+    Binary,
+    /// Text format (Base32 encoded)
+    Text,
+    /// Mnemonic format (word-based)
+    Mnemonic,
+}
+
+/// Purpose of the split key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyPurpose {
+    /// Standard key for personal use
+    Standard,
+    /// Transfer key for sending to others
+    Transfer,
+}
+
+/// Represents a split encryption key using Shamir's Secret Sharing
+pub struct SplitEncryptionKey {
+    /// The threshold number of shares needed to reconstruct the key
+    threshold: u8,
+    /// The total number of shares created
+    shares_count: u8,
+    /// The shares of the key (if available)
+    shares: Vec<Share>,
+    /// The reconstructed key (if available)
+    key: Option<EncryptionKey>,
+    /// The purpose of this split key
+    purpose: KeyPurpose,
+}
+
+impl SplitEncryptionKey {
+    /// Create a new split key from an existing encryption key
+    pub fn new(key: &EncryptionKey, threshold: u8, shares_count: u8, purpose: KeyPurpose) -> Result<Self, SplitKeyError> {
+        if threshold < 2 {
+            return Err(SplitKeyError::Sharing("Threshold must be at least 2".to_string()));
+        }
+        
+        if shares_count < threshold {
+            return Err(SplitKeyError::Sharing("Shares count must be at least equal to threshold".to_string()));
+        }
+        
+        // Get the key as bytes
+        let key_bytes = key.to_base64().into_bytes();
+        
+        // Create the Shamir's Secret Sharing scheme
+        let sharks = Sharks(threshold);
+        
+        // Split the key into shares
+        let dealer = sharks.dealer(&key_bytes);
+        let shares: Vec<Share> = dealer.take(shares_count as usize).collect();
+        
+        Ok(SplitEncryptionKey {
+            threshold,
+            shares_count,
+            shares,
+            key: Some(key.clone()),
+            purpose,
+        })
+    }
+    
+    /// Create a new split key specifically for transfer
+    pub fn new_for_transfer(key: &EncryptionKey, threshold: u8, shares_count: u8) -> Result<Self, SplitKeyError> {
+        Self::new(key, threshold, shares_count, KeyPurpose::Transfer)
+    }
+    
+    /// Reconstruct a key from shares
+    pub fn from_shares(shares: Vec<Share>, threshold: u8) -> Result<Self, SplitKeyError> {
+        if shares.len() < threshold as usize {
+            return Err(SplitKeyError::Sharing(
+                format!("Not enough shares: got {}, need at least {}", shares.len(), threshold)
+            ));
+        }
+        
+        // Create the Shamir's Secret Sharing scheme
+        let sharks = Sharks(threshold);
+        
+        // Reconstruct the secret
+        let key_bytes = sharks.recover(&shares)
+            .map_err(|e| SplitKeyError::Sharing(format!("Failed to recover key: {}", e)))?;
+        
+        // Convert back to a string and then to an EncryptionKey
+        let key_base64 = String::from_utf8(key_bytes)
+            .map_err(|e| SplitKeyError::Key(format!("Invalid key data: {}", e)))?;
+        
+        let key = EncryptionKey::from_base64(&key_base64)
+            .map_err(|e| SplitKeyError::Key(format!("Invalid key: {}", e)))?;
+        
+        Ok(SplitEncryptionKey {
+            threshold,
+            shares_count: shares.len() as u8,
+            shares,
+            key: Some(key),
+            purpose: KeyPurpose::Standard, // Default to standard purpose for reconstructed keys
+        })
+    }
+    
+    /// Get the reconstructed key
+    pub fn get_key(&self) -> Option<&EncryptionKey> {
+        self.key.as_ref()
+    }
+    
+    /// Get a specific share
+    pub fn get_share(&self, index: usize) -> Option<&Share> {
+        self.shares.get(index)
+    }
+    
+    /// Get all shares
+    pub fn get_shares(&self) -> &[Share] {
+        &self.shares
+    }
+    
+    /// Get the threshold
+    pub fn get_threshold(&self) -> u8 {
+        self.threshold
+    }
+    
+    /// Get the total number of shares
+    pub fn get_shares_count(&self) -> u8 {
+        self.shares_count
+    }
+    
+    /// Get the purpose of this split key
+    pub fn get_purpose(&self) -> KeyPurpose {
+        self.purpose
+    }
+    
+    /// Convert a share to a text representation
+    pub fn share_to_text(&self, index: usize) -> Result<String, SplitKeyError> {
+        if index >= self.shares.len() {
+            return Err(SplitKeyError::Encoding(format!("Share index {} out of bounds", index)));
+        }
+        
+        let share = &self.shares[index];
+        
+        // Format: version-index-threshold-checksum-data
+        // Version: 1 byte
+        // Index: 1 byte
+        // Threshold: 1 byte
+        // Checksum: 2 bytes (CRC16)
+        // Data: variable length
+        
+        // Get the bytes from the share
+        let share_bytes = Vec::from(share);
+        let mut buffer = Vec::with_capacity(5 + share_bytes.len());
+        
+        // Version (1)
+        buffer.push(1);
+        
+        // Index
+        buffer.push(index as u8);
+        
+        // Threshold
+        buffer.push(self.threshold);
+        
+        // Placeholder for checksum (will be filled later)
+        buffer.push(0);
+        buffer.push(0);
+        
+        // Share data
+        buffer.extend_from_slice(&share_bytes);
+        
+        // Calculate checksum (CRC16)
+        let checksum = crc16(&buffer[0..3]) as u16;
+        buffer[3] = (checksum >> 8) as u8;
+        buffer[4] = (checksum & 0xFF) as u8;
+        
+        // Encode as Base32
+        let encoded = BASE32.encode(&buffer);
+        
+        // Format with dashes every 5 characters for readability
+        let mut formatted = String::with_capacity(encoded.len() + (encoded.len() / 5));
+        for (i, c) in encoded.chars().enumerate() {
+            if i > 0 && i % 5 == 0 {
+                formatted.push('-');
+            }
+            formatted.push(c);
+        }
+        
+        Ok(formatted)
+    }
+    
+    /// Convert a text representation back to a share
+    pub fn share_from_text(text: &str) -> Result<Share, SplitKeyError> {
+        // Remove dashes and whitespace
+        let clean_text = text.replace(['-', ' '], "");
+        
+        // Decode from Base32
+        let buffer = BASE32.decode(clean_text.as_bytes())
+            .map_err(|e| SplitKeyError::Encoding(format!("Invalid Base32 encoding: {}", e)))?;
+        
+        // Check minimum length
+        if buffer.len() < 5 {
+            return Err(SplitKeyError::Encoding("Share text too short".to_string()));
+        }
+        
+        // Check version
+        if buffer[0] != 1 {
+            return Err(SplitKeyError::Encoding(format!("Unsupported share version: {}", buffer[0])));
+        }
+        
+        // Verify checksum
+        let stored_checksum = ((buffer[3] as u16) << 8) | (buffer[4] as u16);
+        let calculated_checksum = crc16(&buffer[0..3]);
+        
+        if stored_checksum != calculated_checksum {
+            return Err(SplitKeyError::Encoding("Invalid checksum, share may be corrupted".to_string()));
+        }
+        
+        // Extract share data
+        let share_data = buffer[5..].to_vec();
+        
+        // Create a new Share from the data
+        Share::try_from(&share_data[..])
+            .map_err(|e| SplitKeyError::Encoding(format!("Failed to create share: {}", e)))
+    }
+    
+    /// Convert a share to a mnemonic phrase
+    pub fn share_to_mnemonic(&self, index: usize) -> Result<String, SplitKeyError> {
+        if index >= self.shares.len() {
+            return Err(SplitKeyError::Encoding(format!("Share index {} out of bounds", index)));
+        }
+        
+        // First convert to text format
+        let text = self.share_to_text(index)?;
+        
+        // Then convert to mnemonic using BIP39 wordlist
+        let mnemonic = text_to_mnemonic(&text)
+            .map_err(|e| SplitKeyError::Encoding(format!("Failed to create mnemonic: {}", e)))?;
+        
+        Ok(mnemonic)
+    }
+    
+    /// Convert a mnemonic phrase back to a share
+    pub fn share_from_mnemonic(mnemonic: &str) -> Result<Share, SplitKeyError> {
+        // Convert mnemonic to text
+        let text = mnemonic_to_text(mnemonic)
+            .map_err(|e| SplitKeyError::Encoding(format!("Failed to parse mnemonic: {}", e)))?;
+        
+        // Convert text to share
+        Self::share_from_text(&text)
+    }
+    
+    /// Convert a share to numbered dictation groups, for reading it out over the phone
+    pub fn share_to_dictation(&self, index: usize) -> Result<Vec<DictationGroup>, SplitKeyError> {
+        Ok(share_text_to_dictation(&self.share_to_text(index)?))
+    }
+    
+    /// Convert dictation groups (one per line, as written by `DictationGroup`) back to a share
+    pub fn share_from_dictation(dictation: &str) -> Result<Share, SplitKeyError> {
+        Self::share_from_text(&share_text_from_dictation(dictation)?)
+    }
+    
+    /// Store a share in the OS credential store
+    pub fn store_share_in_credential_store(&self, index: usize, service_name: &str) -> Result<(), SplitKeyError> {
+        if index >= self.shares.len() {
+            return Err(SplitKeyError::Storage(format!("Share index {} out of bounds", index)));
+        }
+        
+        let share = &self.shares[index];
+        // Get the bytes from the share
+        let share_bytes = Vec::from(share);
+        let share_data = STANDARD.encode(&share_bytes);
+        
+        let entry = Entry::new(service_name, &format!("crusty-share-{}", index))
+            .map_err(|e| SplitKeyError::Storage(format!("Failed to create keyring entry: {}", e)))?;
+            
+        entry.set_password(&share_data)
+            .map_err(|e| SplitKeyError::Storage(format!("Failed to store share: {}", e)))?;
+            
+        Ok(())
+    }
+    
+    /// Retrieve a share from the OS credential store
+    pub fn retrieve_share_from_credential_store(service_name: &str, index: usize) -> Result<Share, SplitKeyError> {
+        let entry = Entry::new(service_name, &format!("crusty-share-{}", index))
+            .map_err(|e| SplitKeyError::Storage(format!("Failed to create keyring entry: {}", e)))?;
+            
+        let share_data = entry.get_password()
+            .map_err(|e| SplitKeyError::Storage(format!("Failed to retrieve share: {}", e)))?;
+            
+        let share_bytes = STANDARD.decode(&share_data)
+            .map_err(|e| SplitKeyError::Storage(format!("Invalid share data: {}", e)))?;
+            
+        // Create a new Share from the data
+        Share::try_from(&share_bytes[..])
+            .map_err(|e| SplitKeyError::Storage(format!("Failed to create share: {}", e)))
+    }
+    
+    /// Save a share to a file
+    pub fn save_share_to_file(&self, index: usize, path: &Path, format: ShareFormat) -> Result<(), SplitKeyError> {
+        if index >= self.shares.len() {
+            return Err(SplitKeyError::Storage(format!("Share index {} out of bounds", index)));
+        }
+        
+        let mut file = File::create(path)?;
+        
+        match format {
+            ShareFormat::Binary => {
+                let share = &self.shares[index];
+                // Get the bytes from the share
+                let share_bytes = Vec::from(share);
+                let share_data = STANDARD.encode(&share_bytes);
+                file.write_all(share_data.as_bytes())?;
+            },
+            ShareFormat::Text => {
+                let text = self.share_to_text(index)?;
+                file.write_all(text.as_bytes())?;
+            },
+            ShareFormat::Mnemonic => {
+                let mnemonic = self.share_to_mnemonic(index)?;
+                file.write_all(mnemonic.as_bytes())?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Load a share from a file
+    pub fn load_share_from_file(path: &Path) -> Result<Share, SplitKeyError> {
+        let mut file = File::open(path)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        
+        // Try to determine the format and parse accordingly
+        if content.contains('-') || content.chars().all(|c| c.is_ascii_alphanumeric() || c.is_whitespace()) {
+            // Looks like text format
+            Self::share_from_text(&content)
+        } else if content.split_whitespace().count() > 1 {
+            // Looks like mnemonic format
+            Self::share_from_mnemonic(&content)
+        } else {
+            // Assume base64 binary format (legacy)
+            let share_bytes = STANDARD.decode(&content)
+                .map_err(|e| SplitKeyError::Storage(format!("Invalid share data: {}", e)))?;
+                
+            // Create a new Share from the data
+            Share::try_from(&share_bytes[..])
+                .map_err(|e| SplitKeyError::Storage(format!("Failed to create share: {}", e)))
+        }
+    }
+    
+    /// Parse a share in any supported format (text, mnemonic, dictation groups, or the
+    /// Base64 content of a QR code); a transfer seal following it is ignored
+    pub fn parse_share(content: &str) -> Result<Share, SplitKeyError> {
+        let content: String = content.lines()
+            .filter(|line| !is_seal_line(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = content.trim();
+        
+        if let Ok(share) = Self::share_from_text(content) {
+            return Ok(share);
+        }
+        
+        if content.starts_with(|c: char| c.is_ascii_digit()) && content.contains(char::is_whitespace) {
+            return Self::share_from_dictation(content);
+        }
+        
+        if content.split_whitespace().count() > 1 {
+            return Self::share_from_mnemonic(content);
+        }
+        
+        let share_bytes = STANDARD.decode(content)
+            .map_err(|_| SplitKeyError::Encoding("Not a recognized share format".to_string()))?;
+        Share::try_from(&share_bytes[..])
+            .map_err(|e| SplitKeyError::Encoding(format!("Failed to create share: {}", e)))
+    }
+    
+    /// Generate a QR code for a share
+    pub fn generate_share_qr_code(&self, index: usize) -> Result<String, SplitKeyError> {
+        if index >= self.shares.len() {
+            return Err(SplitKeyError::QrCode(format!("Share index {} out of bounds", index)));
+        }
+        
+        let share = &self.shares[index];
+        // Get the bytes from the share
+        let share_bytes = Vec::from(share);
+        let share_data = STANDARD.encode(&share_bytes);
+        
+        let code = QrCode::new(share_data.as_bytes())
+            .map_err(|e| SplitKeyError::QrCode(format!("Failed to generate QR code: {}", e)))?;
+            
+        let svg = code.render()
+            .min_dimensions(200, 200)
+            .dark_color(svg::Color("#000000"))
+            .light_color(svg::Color("#ffffff"))
+            .build();
+            
+        Ok(svg)
+    }
+    
+    /// Save a QR code for a share to a file
+    pub fn save_share_qr_code_to_file(&self, index: usize, path: &Path) -> Result<(), SplitKeyError> {
+        let svg = self.generate_share_qr_code(index)?;
+        
+        let mut file = File::create(path)?;
+        file.write_all(svg.as_bytes())?;
+        
+        Ok(())
+    }
+}
+
+/// Start of the seal line that follows a saved or relayed transfer share
+const SEAL_PREFIX: &str = "CRUSTY-SEAL-1:";
+
+/// Start of the seal line of a share of a package bound to its recipient
+const BOUND_SEAL_PREFIX: &str = "CRUSTY-SEAL-2:";
+
+/// Kinds of recipient binding in a bound seal
+const BINDING_PASSPHRASE: u8 = 1;
+const BINDING_IDENTITY: u8 = 2;
+
+/// Recipient a transfer package is bound to, when it is created
+pub enum TransferRecipient {
+    /// A passphrase agreed with the recipient, as a passphrase key
+    Passphrase(EncryptionKey),
+    /// The recipient's machine identity
+    Identity(PublicKey),
+}
+
+/// The recipient's credential, to unwrap the key of a bound transfer package
+pub enum RecipientCredential {
+    /// The passphrase agreed with the sender
+    Passphrase(String),
+    /// This machine's identity secret
+    Identity(StaticSecret),
+}
+
+/// How a transfer package is bound to its recipient, as its seal says
+///
+/// The shares of a bound package split the transfer key masked with a value only the
+/// recipient can derive: from the passphrase, or from an X25519 exchange between a
+/// one-time key and the recipient's machine identity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipientBinding {
+    /// Bound to a passphrase, derived with these parameters
+    Passphrase(KdfParams),
+    /// Bound to a machine identity, with this one-time public key
+    Identity([u8; 32]),
+}
+
+impl TransferRecipient {
+    /// Bind a package to the recipient
+    ///
+    /// # Returns
+    /// * `Result<(RecipientBinding, [u8; 32]), SplitKeyError>` - The binding for the
+    ///   seal, and the mask of the transfer key
+    fn bind(&self, package_id: &[u8; 8]) -> Result<(RecipientBinding, [u8; 32]), SplitKeyError> {
+        match self {
+            TransferRecipient::Passphrase(key) => {
+                let params = key.kdf.clone()
+                    .ok_or_else(|| SplitKeyError::Transfer("The recipient key must be derived from a passphrase".to_string()))?;
+                Ok((RecipientBinding::Passphrase(params), binding_mask(&key.key, package_id)))
+            },
+            TransferRecipient::Identity(identity) => {
+                let ephemeral_secret = StaticSecret::random_from_rng(rand::rngs::OsRng);
+                let shared = ephemeral_secret.diffie_hellman(identity);
+                let ephemeral_public = PublicKey::from(&ephemeral_secret);
+                Ok((RecipientBinding::Identity(ephemeral_public.to_bytes()), binding_mask(shared.as_bytes(), package_id)))
+            },
+        }
+    }
+}
+
+impl RecipientBinding {
+    /// Describe the binding to the recipient
+    pub fn describe(&self) -> &'static str {
+        match self {
+            RecipientBinding::Passphrase(_) => "a passphrase",
+            RecipientBinding::Identity(_) => "the recipient's machine identity",
+        }
+    }
+
+    /// Encode the binding for the seal
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            RecipientBinding::Passphrase(params) => {
+                let mut bytes = vec![BINDING_PASSPHRASE];
+                bytes.extend(encryption::kdf_params_header(params));
+                bytes
+            },
+            RecipientBinding::Identity(ephemeral_public) => {
+                let mut bytes = vec![BINDING_IDENTITY];
+                bytes.extend_from_slice(ephemeral_public);
+                bytes
+            },
+        }
+    }
+
+    /// Decode the binding of a seal (None if it is damaged)
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes.split_first()? {
+            (&BINDING_PASSPHRASE, header) => match encryption::split_kdf_header(header) {
+                Ok((Some(params), [])) => Some(RecipientBinding::Passphrase(params)),
+                _ => None,
+            },
+            (&BINDING_IDENTITY, ephemeral_public) => ephemeral_public.try_into().ok().map(RecipientBinding::Identity),
+            _ => None,
+        }
+    }
+
+    /// Get the mask of the transfer key from the recipient's credential
+    fn mask(&self, credential: &RecipientCredential, package_id: &[u8; 8]) -> Result<[u8; 32], SplitKeyError> {
+        match (self, credential) {
+            (RecipientBinding::Passphrase(params), RecipientCredential::Passphrase(passphrase)) => {
+                let key = EncryptionKey::from_passphrase(passphrase, params)
+                    .map_err(|e| SplitKeyError::Key(e.to_string()))?;
+                Ok(binding_mask(&key.key, package_id))
+            },
+            (RecipientBinding::Identity(ephemeral_public), RecipientCredential::Identity(secret)) => {
+                let shared = secret.diffie_hellman(&PublicKey::from(*ephemeral_public));
+                Ok(binding_mask(shared.as_bytes(), package_id))
+            },
+            _ => Err(SplitKeyError::Transfer(format!("The transfer package is bound to {}", self.describe()))),
+        }
+    }
+}
+
+/// Derive the mask of the transfer key from the recipient's secret (HKDF-SHA256)
+fn binding_mask(secret: &[u8; 32], package_id: &[u8; 8]) -> [u8; 32] {
+    let mut info = Vec::from(&b"CRUSTy transfer binding"[..]);
+    info.extend_from_slice(package_id);
+
+    let mut mask = [0u8; 32];
+    Hkdf::<Sha256>::new(None, secret)
+        .expand(&info, &mut mask)
+        .expect("32 bytes is a valid HKDF output length");
+    mask
+}
+
+/// Apply or remove the mask of a transfer key
+fn mask_key(key: &EncryptionKey, mask: &[u8; 32]) -> EncryptionKey {
+    let mut masked = EncryptionKey { key: key.key, kdf: None };
+    masked.key.iter_mut().zip(mask).for_each(|(byte, m)| *byte ^= m);
+    masked
+}
+
+/// Seal of a transfer package, as saved and sent with one of its shares
+///
+/// The package fields are authenticated with a MAC keyed with the transfer key, which
+/// can only be checked once the key is reconstructed. The digest of the share it came
+/// with is checked as soon as the share is entered, which catches a share changed or
+/// cut short in transit (the share text checksum covers only its header).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShareSeal {
+    /// Random ID of the package, the same in the seals of all its shares
+    pub package_id: [u8; 8],
+    /// Number of shares needed to reconstruct the key
+    pub threshold: u8,
+    /// Number of shares in the package
+    pub shares_count: u8,
+    /// Digest of the share (first 8 bytes of its SHA-256)
+    share_digest: [u8; 8],
+    /// MAC of the package ID, threshold, number of shares and binding
+    mac: [u8; 16],
+    /// How the package is bound to its recipient (None if it is not)
+    pub binding: Option<RecipientBinding>,
+}
+
+impl ShareSeal {
+    /// Length of an encoded seal
+    const LEN: usize = 8 + 1 + 1 + 8 + 16;
+
+    /// Get the package ID as shown to users (hexadecimal)
+    pub fn package_id_text(&self) -> String {
+        self.package_id.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Write the seal as a line to follow its share
+    fn to_line(&self) -> String {
+        let mut bytes = Vec::with_capacity(Self::LEN);
+        bytes.extend_from_slice(&self.package_id);
+        bytes.push(self.threshold);
+        bytes.push(self.shares_count);
+        bytes.extend_from_slice(&self.share_digest);
+        bytes.extend_from_slice(&self.mac);
+        match &self.binding {
+            Some(binding) => {
+                bytes.extend(binding.to_bytes());
+                format!("{}{}", BOUND_SEAL_PREFIX, BASE32.encode(&bytes))
+            },
+            None => format!("{}{}", SEAL_PREFIX, BASE32.encode(&bytes)),
+        }
+    }
+
+    /// Read a seal line
+    fn from_line(line: &str) -> Result<Self, SplitKeyError> {
+        let damaged = || SplitKeyError::Transfer("The transfer seal is damaged or cut short".to_string());
+        let line = line.trim();
+        let (encoded, bound) = match (line.strip_prefix(SEAL_PREFIX), line.strip_prefix(BOUND_SEAL_PREFIX)) {
+            (Some(encoded), _) => (encoded, false),
+            (_, Some(encoded)) => (encoded, true),
+            _ => return Err(damaged()),
+        };
+        let bytes = BASE32.decode(encoded.as_bytes()).map_err(|_| damaged())?;
+        let binding = match (bound, bytes.len()) {
+            (false, Self::LEN) => None,
+            (true, len) if len > Self::LEN => Some(RecipientBinding::from_bytes(&bytes[Self::LEN..]).ok_or_else(damaged)?),
+            _ => return Err(damaged()),
+        };
+
+        Ok(ShareSeal {
+            package_id: bytes[0..8].try_into().unwrap(),
+            threshold: bytes[8],
+            shares_count: bytes[9],
+            share_digest: bytes[10..18].try_into().unwrap(),
+            mac: bytes[18..34].try_into().unwrap(),
+            binding,
+        })
+    }
+
+    /// Check whether the seal authenticates with a reconstructed key
+    fn authenticates(&self, key: &EncryptionKey) -> bool {
+        package_mac(key, &self.package_id, self.threshold, self.shares_count, self.binding.as_ref()) == self.mac
+    }
+}
+
+/// Compute the MAC of a transfer package, keyed with its transfer key (HKDF-SHA256)
+fn package_mac(key: &EncryptionKey, package_id: &[u8; 8], threshold: u8, shares_count: u8, binding: Option<&RecipientBinding>) -> [u8; 16] {
+    let mut info = Vec::from(&b"CRUSTy transfer seal"[..]);
+    info.extend_from_slice(package_id);
+    info.push(threshold);
+    info.push(shares_count);
+    if let Some(binding) = binding {
+        info.extend(binding.to_bytes());
+    }
+
+    let mut mac = [0u8; 16];
+    Hkdf::<Sha256>::new(None, &key.key)
+        .expand(&info, &mut mac)
+        .expect("16 bytes is a valid HKDF output length");
+    mac
+}
+
+/// Get the digest of a share that its seal carries
+fn share_digest(share: &Share) -> [u8; 8] {
+    Sha256::digest(Vec::from(share))[..8].try_into().unwrap()
+}
+
+/// Check whether a line is a transfer seal (including a damaged one)
+fn is_seal_line(line: &str) -> bool {
+    line.trim().to_ascii_uppercase().starts_with("CRUSTY-SEAL")
+}
+
+/// Separate a share from its seal line
+///
+/// # Returns
+/// * `Result<(String, Option<ShareSeal>), SplitKeyError>` - The share without the seal
+///   and the seal (None if the share has none), or an error if the seal is damaged
+pub fn split_sealed_share(content: &str) -> Result<(String, Option<ShareSeal>), SplitKeyError> {
+    let (seal_lines, share_lines): (Vec<&str>, Vec<&str>) = content.lines()
+        .partition(|line| is_seal_line(line));
+    let share = share_lines.join("\n").trim().to_string();
+
+    match seal_lines.as_slice() {
+        [] => Ok((share, None)),
+        [line] => Ok((share, Some(ShareSeal::from_line(line)?))),
+        _ => Err(SplitKeyError::Transfer("A share has more than one transfer seal".to_string())),
+    }
+}
+
+/// Check received transfer shares against their seals, before the key is reconstructed
+///
+/// Each sealed share must match the digest in its seal, and all seals must be of the
+/// same package. Unsealed shares (typed phrases, dictated groups) are not checked.
+///
+/// # Returns
+/// * `Result<Option<ShareSeal>, SplitKeyError>` - The seal of the package (None if no
+///   share is sealed), or what is wrong with the shares
+pub fn check_sealed_shares(contents: &[String]) -> Result<Option<ShareSeal>, SplitKeyError> {
+    let mut package_seal: Option<ShareSeal> = None;
+
+    for (i, content) in contents.iter().enumerate().filter(|(_, content)| !content.trim().is_empty()) {
+        let (share, seal) = split_sealed_share(content)?;
+        let seal = match seal {
+            Some(seal) => seal,
+            None => continue,
+        };
+
+        let share = SplitEncryptionKey::parse_share(&share)?;
+        if share_digest(&share) != seal.share_digest {
+            return Err(SplitKeyError::Transfer(format!(
+                "Share {} does not match its seal: it was changed or cut short after it was sent", i + 1
+            )));
+        }
+
+        match &package_seal {
+            Some(first) if (first.package_id, first.threshold, first.shares_count, &first.binding) != (seal.package_id, seal.threshold, seal.shares_count, &seal.binding) => {
+                return Err(SplitKeyError::Transfer(
+                    "The shares are from different transfer packages".to_string()
+                ));
+            },
+            Some(_) => {},
+            None => package_seal = Some(seal),
+        }
+    }
+
+    Ok(package_seal)
+}
+
+/// Separate several shares pasted into one share field
+///
+/// Shares are taken to be separated by blank lines or to end at their seal line, or
+/// else to be one per line. The content is only split when every part is a share, so a
+/// single share over several lines (dictation groups) is left whole.
+///
+/// # Returns
+/// * `Vec<String>` - The shares pasted, or the content alone if it is not several shares
+pub fn split_pasted_shares(content: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    for line in content.lines() {
+        if !line.trim().is_empty() {
+            block.push(line.trim());
+        }
+        if (line.trim().is_empty() || is_seal_line(line)) && !block.is_empty() {
+            blocks.push(block.join("\n"));
+            block.clear();
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block.join("\n"));
+    }
+
+    let lines: Vec<String> = content.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    for parts in [blocks, lines] {
+        if parts.len() > 1 && parts.iter().all(|part| SplitEncryptionKey::parse_share(part).is_ok()) {
+            return parts;
+        }
+    }
+    vec![content.to_string()]
+}
+
+/// Transfer package for out-of-band file transfers
+#[derive(Clone)]
+pub struct TransferPackage {
+    /// The shares for the transfer
+    shares: Vec<String>,
+    /// The threshold required to reconstruct the key
+    threshold: u8,
+    /// The format of the shares
+    format: ShareFormat,
+    /// Random ID of the package, in the seal of each share
+    package_id: [u8; 8],
+    /// MAC of the package, in the seal of each share
+    mac: [u8; 16],
+    /// How the package is bound to its recipient, in the seal of each share
+    binding: Option<RecipientBinding>,
+}
+
+impl TransferPackage {
+    /// Create a new transfer package from a split key
+    pub fn new(split_key: &SplitEncryptionKey) -> Result<Self, SplitKeyError> {
+        let key = split_key.get_key()
+            .ok_or_else(|| SplitKeyError::Transfer("The transfer key is not available to seal the package".to_string()))?;
+        
+        let mut package_id = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut package_id);
+        Self::sealed(split_key, key, package_id, None)
+    }
+    
+    /// Create a transfer package from a split key, sealed with the transfer key (which
+    /// differs from the split key for a package bound to its recipient)
+    fn sealed(
+        split_key: &SplitEncryptionKey,
+        key: &EncryptionKey,
+        package_id: [u8; 8],
+        binding: Option<RecipientBinding>
+    ) -> Result<Self, SplitKeyError> {
+        if split_key.get_purpose() != KeyPurpose::Transfer {
+            return Err(SplitKeyError::Transfer(
+                "Cannot create transfer package from non-transfer key".to_string()
+            ));
+        }
+        
+        let mut shares = Vec::with_capacity(split_key.shares.len());
+        
+        // Convert all shares to text format
+        for i in 0..split_key.shares.len() {
+            let share_text = split_key.share_to_text(i)?;
+            shares.push(share_text);
+        }
+        
+        let mac = package_mac(key, &package_id, split_key.threshold, shares.len() as u8, binding.as_ref());
+        
+        Ok(TransferPackage {
+            shares,
+            threshold: split_key.threshold,
+            format: ShareFormat::Text,
+            package_id,
+            mac,
+            binding,
+        })
+    }
+    
+    /// Get how the package is bound to its recipient (None if it is not)
+    pub fn binding(&self) -> Option<&RecipientBinding> {
+        self.binding.as_ref()
+    }
+    
+    /// Get a specific share as text followed by the seal of the package (as saved and
+    /// sent via the relay)
+    pub fn get_sealed_share_text(&self, index: usize) -> Result<String, SplitKeyError> {
+        let share_text = self.get_share_text(index)?;
+        let seal = ShareSeal {
+            package_id: self.package_id,
+            threshold: self.threshold,
+            shares_count: self.shares.len() as u8,
+            share_digest: share_digest(&SplitEncryptionKey::share_from_text(share_text)?),
+            mac: self.mac,
+            binding: self.binding.clone(),
+        };
+        Ok(format!("{}\n{}", share_text, seal.to_line()))
+    }
+    
+    /// Get a specific share as text
+    pub fn get_share_text(&self, index: usize) -> Result<&str, SplitKeyError> {
+        self.shares.get(index)
+            .ok_or_else(|| SplitKeyError::Transfer(format!("Share index {} out of bounds", index)))
+            .map(|s| s.as_str())
+    }
+    
+    /// Get a specific share as a mnemonic phrase
+    pub fn get_share_mnemonic(&self, index: usize) -> Result<String, SplitKeyError> {
+        let text = self.get_share_text(index)?;
+        text_to_mnemonic(text)
+            .map_err(|e| SplitKeyError::Encoding(format!("Failed to create mnemonic: {}", e)))
+    }
+    
+    /// Get a specific share as numbered dictation groups
+    pub fn get_share_dictation(&self, index: usize) -> Result<Vec<DictationGroup>, SplitKeyError> {
+        Ok(share_text_to_dictation(self.get_share_text(index)?))
+    }
+    
+    /// Get the threshold
+    pub fn get_threshold(&self) -> u8 {
+        self.threshold
+    }
+    
+    /// Get the number of shares
+    pub fn get_shares_count(&self) -> usize {
+        self.shares.len()
+    }
+    
+    /// Save a share to a file, with the seal of the package
+    pub fn save_share_to_file(&self, index: usize, path: &Path) -> Result<(), SplitKeyError> {
+        let share_text = self.get_sealed_share_text(index)?;
+        
+        let mut file = File::create(path)?;
+        file.write_all(share_text.as_bytes())?;
+        
+        Ok(())
+    }
+    
+    /// Reconstruct the key from a set of shares
+    pub fn reconstruct_key(&self, share_indices: &[usize]) -> Result<EncryptionKey, SplitKeyError> {
+        if share_indices.len() < self.threshold as usize {
+            return Err(SplitKeyError::Sharing(
+                format!("Not enough shares: got {}, need at least {}", share_indices.len(), self.threshold)
+            ));
+        }
+        
+        let mut shares = Vec::with_capacity(share_indices.len());
+        
+        // Convert text shares back to Share objects
+        for &index in share_indices {
+            let share_text = self.get_share_text(index)?;
+            let share = SplitEncryptionKey::share_from_text(share_text)?;
+            shares.push(share);
+        }
+        
+        // Reconstruct the key
+        let split_key = SplitEncryptionKey::from_shares(shares, self.threshold)?;
+        
+        // Get the reconstructed key
+        split_key.get_key()
+            .cloned()
+            .ok_or_else(|| SplitKeyError::Key("Failed to reconstruct key".to_string()))
+    }
+}
+
+/// Fingerprint of a share, used to verify it without reconstructing the key
+pub fn share_fingerprint(share: &Share) -> String {
+    let share_bytes = Vec::from(share);
+    Sha256::digest(&share_bytes)[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Number of a share in its split key (1 for the first share)
+pub fn share_number(share: &Share) -> u8 {
+    Vec::from(share)[0]
+}
+
+/// Verification record for one stored share
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShareRecord {
+    /// Index of the share in the split key
+    pub index: usize,
+    /// Where the share is kept
+    pub label: String,
+    /// Fingerprint of the share
+    pub fingerprint: String,
+    /// When the share was created
+    pub created_at: String,
+    /// When the share was last verified
+    pub last_verified: Option<String>,
+}
+
+impl ShareRecord {
+    /// Check whether the share has not been verified within the given number of days
+    pub fn is_due(&self, interval_days: u64) -> bool {
+        let since = self.last_verified.as_ref().unwrap_or(&self.created_at);
+        
+        match NaiveDateTime::parse_from_str(since, "%Y-%m-%d %H:%M:%S") {
+            Ok(since) => (Local::now().naive_local() - since).num_days() >= interval_days as i64,
+            Err(_) => true,
+        }
+    }
+}
+
+/// Key share storage manager
+pub struct KeyShareManager {
+    /// Application name for credential store
+    app_name: String,
+    /// Directory for storing share files
+    share_dir: PathBuf,
+}
+
+impl KeyShareManager {
+    /// Create a new key share manager
+    pub fn new(app_name: &str, share_dir: &Path) -> Result<Self, SplitKeyError> {
+        // Create the share directory if it doesn't exist
+        if !share_dir.exists() {
+            fs::create_dir_all(share_dir)?;
+        }
+        
+        Ok(KeyShareManager {
+            app_name: app_name.to_string(),
+            share_dir: share_dir.to_path_buf(),
+        })
+    }
+    
+    /// Open the key share manager for the application data directory
+    pub fn open_default() -> Result<Self, SplitKeyError> {
+        let share_dir = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        Self::new("CRUSTy", &share_dir.join("crusty").join("shares"))
+    }
+    
+    /// Path of the share verification records
+    fn records_path(&self) -> PathBuf {
+        self.share_dir.join("verification.json")
+    }
+    
+    /// Load the share verification records (empty if no split key has been stored)
+    pub fn load_share_records(&self) -> Result<Vec<ShareRecord>, SplitKeyError> {
+        let path = self.records_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| SplitKeyError::Storage(format!("Invalid share records: {}", e)))
+    }
+    
+    /// Save the share verification records
+    fn save_share_records(&self, records: &[ShareRecord]) -> Result<(), SplitKeyError> {
+        let content = serde_json::to_string_pretty(records)
+            .map_err(|e| SplitKeyError::Storage(format!("Failed to serialize share records: {}", e)))?;
+        shared_files::write_atomic(&self.records_path(), content)?;
+        Ok(())
+    }
+    
+    /// Start tracking the shares of a newly stored split key (replaces earlier records)
+    pub fn record_shares(&self, split_key: &SplitEncryptionKey) -> Result<(), SplitKeyError> {
+        let _lock = shared_files::lock(&self.records_path())?;
+        let created_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let records: Vec<ShareRecord> = split_key.get_shares().iter().enumerate()
+            .map(|(index, share)| ShareRecord {
+                index,
+                label: match index {
+                    0 => "Primary share (credential store)".to_string(),
+                    1 => "Secondary share (file)".to_string(),
+                    2 => "Recovery share".to_string(),
+                    _ => format!("Share {}", index + 1),
+                },
+                fingerprint: share_fingerprint(share),
+                created_at: created_at.clone(),
+                last_verified: None,
+            })
+            .collect();
+        
+        self.save_share_records(&records)
+    }
+    
+    /// Verify a share entered by its custodian and record the verification date
+    ///
+    /// The share is only compared with the recorded fingerprints; the key is never
+    /// reconstructed and the stored shares are not modified.
+    ///
+    /// # Arguments
+    /// * `content` - The share as text, mnemonic, or QR code content
+    ///
+    /// # Returns
+    /// * `Result<ShareRecord, SplitKeyError>` - The record of the verified share, or an
+    ///   error if the share is unreadable or does not belong to the stored split key
+    pub fn verify_share(&self, content: &str) -> Result<ShareRecord, SplitKeyError> {
+        let share = SplitEncryptionKey::parse_share(content)?;
+        self.mark_verified(&share_fingerprint(&share))
+    }
+    
+    /// Verify the primary share in the OS credential store
+    pub fn verify_primary_share(&self) -> Result<ShareRecord, SplitKeyError> {
+        let share = self.retrieve_primary_share()?;
+        self.mark_verified(&share_fingerprint(&share))
+    }
+    
+    /// Record the verification date of the share with the given fingerprint
+    fn mark_verified(&self, fingerprint: &str) -> Result<ShareRecord, SplitKeyError> {
+        // Held while the records are loaded and saved, so another running copy of CRUSTy
+        // cannot verify a share in between and have its date overwritten
+        let _lock = shared_files::lock(&self.records_path())?;
+        let mut records = self.load_share_records()?;
+        
+        let record = records.iter_mut()
+            .find(|record| record.fingerprint == fingerprint)
+            .ok_or_else(|| SplitKeyError::Sharing("Share does not belong to the stored split key".to_string()))?;
+        record.last_verified = Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        let record = record.clone();
+        
+        self.save_share_records(&records)?;
+        Ok(record)
+    }
+    
+    /// Store the primary share in the OS credential store
+    pub fn store_primary_share(&self, split_key: &SplitEncryptionKey) -> Result<(), SplitKeyError> {
+        split_key.store_share_in_credential_store(0, &self.app_name)
+    }
+    
+    /// Retrieve the primary share from the OS credential store
+    pub fn retrieve_primary_share(&self) -> Result<Share, SplitKeyError> {
+        SplitEncryptionKey::retrieve_share_from_credential_store(&self.app_name, 0)
+    }
+    
+    /// Create a transfer package for out-of-band file transfer
+    pub fn create_transfer_package(
+        &self,
+        key: &EncryptionKey,
+        threshold: u8,
+        shares_count: u8
+    ) -> Result<TransferPackage, SplitKeyError> {
+        // Create a split key specifically for transfer
+        let split_key = SplitEncryptionKey::new_for_transfer(key, threshold, shares_count)?;
+        
+        // Create a transfer package
+        let package = TransferPackage::new(&split_key)?;
+        
+        Ok(package)
+    }
+    
+    /// Create a transfer package bound to its recipient
+    ///
+    /// The shares split the key masked with a value derived from the recipient's
+    /// passphrase or machine identity, so even all of them do not give the key without
+    /// the recipient's credential.
+    pub fn create_bound_transfer_package(
+        &self,
+        key: &EncryptionKey,
+        threshold: u8,
+        shares_count: u8,
+        recipient: &TransferRecipient
+    ) -> Result<TransferPackage, SplitKeyError> {
+        let mut package_id = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut package_id);
+        let (binding, mask) = recipient.bind(&package_id)?;
+        
+        let split_key = SplitEncryptionKey::new_for_transfer(&mask_key(key, &mask), threshold, shares_count)?;
+        TransferPackage::sealed(&split_key, key, package_id, Some(binding))
+    }
+    
+    /// Save the secondary share to a file
+    pub fn save_secondary_share(
+        &self, 
+        split_key: &SplitEncryptionKey, 
+        filename: &str,
+        format: ShareFormat
+    ) -> Result<PathBuf, SplitKeyError> {
+        let path = self.share_dir.join(filename);
+        split_key.save_share_to_file(1, &path, format)?;
+        Ok(path)
+    }
+    
+    /// Load the secondary share from a file
+    pub fn load_secondary_share(&self, path: &Path) -> Result<Share, SplitKeyError> {
+        SplitEncryptionKey::load_share_from_file(path)
+    }
+    
+    /// Generate and save a recovery share in the specified format
+    pub fn save_recovery_share(
+        &self, 
+        split_key: &SplitEncryptionKey, 
+        filename: &str,
+        format: ShareFormat
+    ) -> Result<PathBuf, SplitKeyError> {
+        let path = self.share_dir.join(filename);
+        
+        match format {
+            ShareFormat::Binary => {
+                split_key.save_share_to_file(2, &path, ShareFormat::Binary)?;
+            },
+            ShareFormat::Text => {
+                split_key.save_share_to_file(2, &path, ShareFormat::Text)?;
+            },
+            ShareFormat::Mnemonic => {
+                split_key.save_share_to_file(2, &path, ShareFormat::Mnemonic)?;
+            }
+        }
+        
+        Ok(path)
+    }
+    
+    /// Generate and save a QR code for the recovery share (legacy method)
+    pub fn save_recovery_share_qr_code(&self, split_key: &SplitEncryptionKey, filename: &str) -> Result<PathBuf, SplitKeyError> {
+        let path = self.share_dir.join(filename);
+        split_key.save_share_qr_code_to_file(2, &path)?;
+        Ok(path)
+    }
+    
+    /// Reconstruct a key from available shares
+    pub fn reconstruct_key(&self, secondary_share_path: &Path) -> Result<EncryptionKey, SplitKeyError> {
+        // Retrieve the primary share
+        let primary_share = self.retrieve_primary_share()?;
+        
+        // Load the secondary share
+        let secondary_share = self.load_secondary_share(secondary_share_path)?;
+        
+        // Reconstruct the key
+        let shares = vec![primary_share, secondary_share];
+        let split_key = SplitEncryptionKey::from_shares(shares, 2)?;
+        
+        // Get the reconstructed key
+        split_key.get_key()
+            .cloned()
+            .ok_or_else(|| SplitKeyError::Key("Failed to reconstruct key".to_string()))
+    }
+    
+    /// Reconstruct a key from text shares
+    ///
+    /// Shares saved or relayed with a transfer seal are checked against it first, and the
+    /// reconstructed key must authenticate the seal, so a changed share or shares of
+    /// different packages are reported as such rather than giving a wrong key.
+    pub fn reconstruct_key_from_text_shares(&self, share_texts: &[String]) -> Result<EncryptionKey, SplitKeyError> {
+        self.reconstruct_bound_key_from_text_shares(share_texts, None)
+    }
+    
+    /// Reconstruct a transfer key from text shares, unwrapping it with the recipient's
+    /// credential if the package is bound to its recipient
+    ///
+    /// The binding is read from the seal, so a bound package needs at least one sealed
+    /// share; without one, the shares give the masked key rather than the transfer key.
+    pub fn reconstruct_bound_key_from_text_shares(
+        &self,
+        share_texts: &[String],
+        credential: Option<&RecipientCredential>
+    ) -> Result<EncryptionKey, SplitKeyError> {
+        let seal = check_sealed_shares(share_texts)?;
+        let mask = match (seal.as_ref().and_then(|seal| seal.binding.as_ref().map(|binding| (seal, binding))), credential) {
+            (Some((seal, binding)), Some(credential)) => Some(binding.mask(credential, &seal.package_id)?),
+            (Some((_, binding)), None) => return Err(SplitKeyError::Transfer(format!(
+                "The transfer package is bound to {}; unwrap the key with it", binding.describe()
+            ))),
+            (None, _) => None,
+        };
+        let threshold = seal.as_ref().map_or(2, |seal| seal.threshold);
+        if share_texts.len() < threshold as usize {
+            return Err(SplitKeyError::Sharing(
+                format!("Not enough shares: got {}, need at least {}", share_texts.len(), threshold)
+            ));
+        }
+        
+        let mut shares = Vec::with_capacity(share_texts.len());
+        
+        // Convert the shares (text, mnemonic, or QR code content) to Share objects
+        for text in share_texts {
+            let share = SplitEncryptionKey::parse_share(text)?;
+            shares.push(share);
+        }
+        
+        // Reconstruct the key
+        let key = SplitEncryptionKey::from_shares(shares, threshold).and_then(|split_key| {
+            split_key.get_key()
+                .cloned()
+                .ok_or_else(|| SplitKeyError::Key("Failed to reconstruct key".to_string()))
+        });
+        
+        let key = match &mask {
+            Some(mask) => key.map(|key| mask_key(&key, mask)),
+            None => key,
+        };
+        
+        // Mixed shares rarely even decode to a key; with a seal, say why
+        match (key, seal) {
+            (Ok(key), Some(seal)) if seal.authenticates(&key) => Ok(key),
+            (_, Some(_)) if mask.is_some() => Err(SplitKeyError::Transfer(
+                "The key could not be unwrapped: the passphrase or machine is not the recipient's, or a share was changed or is from another package".to_string()
+            )),
+            (_, Some(_)) => Err(SplitKeyError::Transfer(
+                "The shares do not reconstruct the key of their transfer package: a share was changed, or is from another package".to_string()
+            )),
+            (key, None) => key,
+        }
+    }
+    
+    /// Reconstruct a key from primary share and recovery share
+    pub fn reconstruct_key_with_recovery(&self, recovery_share: Share) -> Result<EncryptionKey, SplitKeyError> {
+        // Retrieve the primary share
+        let primary_share = self.retrieve_primary_share()?;
+        
+        // Reconstruct the key
+        let shares = vec![primary_share, recovery_share];
+        let split_key = SplitEncryptionKey::from_shares(shares, 2)?;
+        
+        // Get the reconstructed key
+        split_key.get_key()
+            .cloned()
+            .ok_or_else(|| SplitKeyError::Key("Failed to reconstruct key".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_key() -> SplitEncryptionKey {
+        SplitEncryptionKey::new(&EncryptionKey::generate(), 2, 3, KeyPurpose::Standard).unwrap()
+    }
+
+    #[test]
+    fn test_share_phrase_round_trip() {
+        // Every share character has its own word, including those whose word also
+        // appears earlier in the word list
+        let words: Vec<&str> = share_words().collect();
+        assert_eq!(words.len(), SHARE_TEXT_ALPHABET.len());
+        assert!(words.iter().enumerate().all(|(i, word)| !words[..i].contains(word)));
+        assert_eq!(mnemonic_to_text("ZEBRA arrow").unwrap(), "LM");
+
+        let split_key = split_key();
+        for index in 0..3 {
+            let mnemonic = split_key.share_to_mnemonic(index).unwrap();
+            assert_eq!(mnemonic.split_whitespace().count(), KEY_SHARE_PHRASE_WORDS);
+            let share = SplitEncryptionKey::share_from_mnemonic(&mnemonic).unwrap();
+            assert_eq!(share_fingerprint(&share), share_fingerprint(split_key.get_share(index).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_check_share_phrase() {
+        let mnemonic = split_key().share_to_mnemonic(1).unwrap();
+        let words: Vec<&str> = mnemonic.split_whitespace().collect();
+
+        assert_eq!(check_share_phrase("").status, PhraseStatus::Incomplete(0));
+        assert_eq!(check_share_phrase(&words[..20].join(" ")).status, PhraseStatus::Incomplete(20));
+        assert_eq!(check_share_phrase(&mnemonic).status, PhraseStatus::Valid);
+        let too_long = format!("{} {}", mnemonic, words[0]);
+        assert!(matches!(check_share_phrase(&too_long).status, PhraseStatus::Invalid(_)));
+
+        // A word not in the list is pointed out
+        let mut typo = words.clone();
+        typo[30] = "zebar";
+        let check = check_share_phrase(&typo.join(" "));
+        assert_eq!(check.wrong_words, vec![30]);
+
+        // A wrong header word fails the checksum as soon as the header is entered
+        let mut wrong = words[..10].to_vec();
+        wrong[2] = if wrong[2] == "nut" { "owl" } else { "nut" };
+        let check = check_share_phrase(&wrong.join(" "));
+        assert!(matches!(check.status, PhraseStatus::Invalid(_)));
+        assert!(check.wrong_words.contains(&2));
+    }
+
+    #[test]
+    fn test_share_dictation() {
+        let split_key = split_key();
+        let groups = split_key.share_to_dictation(2).unwrap();
+        assert_eq!(groups.len(), KEY_SHARE_DICTATION_GROUPS);
+        assert_eq!(groups[6].to_string(), format!("07 {} {}", groups[6].chars, groups[6].check));
+
+        // The groups, one per line, are accepted wherever a share is
+        let dictation: Vec<String> = groups.iter().map(|group| group.to_string()).collect();
+        let share = SplitEncryptionKey::parse_share(&dictation.join("\n")).unwrap();
+        assert_eq!(share_fingerprint(&share), share_fingerprint(split_key.get_share(2).unwrap()));
+
+        // Each group is checked on its own, in either case and with a misheard digit
+        let group = &groups[0];
+        let entry = format!("{}{}", group.chars.to_lowercase(), group.check);
+        assert_eq!(check_dictation_group(1, &entry).unwrap(), group.chars);
+        let misheard = format!("{}{}", group.chars.replace('O', "0"), group.check);
+        assert_eq!(check_dictation_group(1, &misheard).unwrap(), group.chars);
+
+        // Any single wrong character is caught, as is a group on the wrong line
+        for position in 0..DICTATION_GROUP_LEN {
+            for c in SHARE_TEXT_ALPHABET.chars().filter(|&c| c != '=') {
+                let mut chars: Vec<char> = group.chars.chars().collect();
+                if chars[position] == c {
+                    continue;
+                }
+                chars[position] = c;
+                let entry = format!("{}{}", chars.iter().collect::<String>(), group.check);
+                assert!(check_dictation_group(1, &entry).is_err());
+            }
+        }
+        assert!(check_dictation_group(2, &entry).is_err());
+        let mut swapped = dictation.clone();
+        swapped.swap(3, 4);
+        assert!(SplitEncryptionKey::parse_share(&swapped.join("\n")).is_err());
+    }
+
+    #[test]
+    fn test_transfer_seal() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = KeyShareManager::new("CRUSTy-test", dir.path()).unwrap();
+        let key = EncryptionKey::generate();
+        let package = manager.create_transfer_package(&key, 2, 3).unwrap();
+        let sealed: Vec<String> = (0..3).map(|i| package.get_sealed_share_text(i).unwrap()).collect();
+
+        let seal = check_sealed_shares(&sealed[..2]).unwrap().unwrap();
+        assert_eq!((seal.threshold, seal.shares_count), (2, 3));
+        let reconstructed = manager.reconstruct_key_from_text_shares(&sealed[1..]).unwrap();
+        assert_eq!(reconstructed.key, key.key);
+
+        // Sealed shares are still accepted wherever a share is, and unsealed shares are
+        // not checked
+        assert!(SplitEncryptionKey::parse_share(&sealed[0]).is_ok());
+        let unsealed = vec![package.get_share_text(0).unwrap().to_string(), sealed[2].clone()];
+        assert!(manager.reconstruct_key_from_text_shares(&unsealed).is_ok());
+
+        // A changed share body (which the share checksum does not cover) fails its seal
+        let (share, seal_line) = sealed[0].split_once('\n').unwrap();
+        let last = share.chars().last().unwrap();
+        let changed = format!("{}{}\n{}", &share[..share.len() - 1], if last == 'A' { 'B' } else { 'A' }, seal_line);
+        let error = check_sealed_shares(&[changed, sealed[1].clone()]).unwrap_err();
+        assert!(error.to_string().contains("does not match its seal"));
+
+        // A seal cut short, and shares of two packages, are reported
+        let truncated = sealed[0][..sealed[0].len() - 10].to_string();
+        assert!(check_sealed_shares(&[truncated]).unwrap_err().to_string().contains("damaged"));
+        let other = manager.create_transfer_package(&key, 2, 3).unwrap();
+        let mixed = vec![sealed[0].clone(), other.get_sealed_share_text(1).unwrap()];
+        assert!(check_sealed_shares(&mixed).unwrap_err().to_string().contains("different transfer packages"));
+
+        // The digest needs no key, but a replaced share then reconstructs a key that
+        // does not authenticate the seal
+        let other_key = manager.create_transfer_package(&EncryptionKey::generate(), 2, 3).unwrap();
+        let replacement = other_key.get_share_text(1).unwrap();
+        let mut forged_seal = split_sealed_share(&sealed[1]).unwrap().1.unwrap();
+        forged_seal.share_digest = share_digest(&SplitEncryptionKey::share_from_text(replacement).unwrap());
+        let forged = format!("{}\n{}", replacement, forged_seal.to_line());
+        let shares = vec![sealed[0].clone(), forged];
+        assert!(check_sealed_shares(&shares).is_ok());
+        let error = manager.reconstruct_key_from_text_shares(&shares).err().unwrap();
+        assert!(error.to_string().contains("do not reconstruct the key"));
+    }
+
+    #[test]
+    fn test_recipient_binding() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = KeyShareManager::new("CRUSTy-test", dir.path()).unwrap();
+        let key = EncryptionKey::generate();
+
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1, salt: [5; 16] };
+        let recipient_key = EncryptionKey::from_passphrase("recipient passphrase", &params).unwrap();
+        let package = manager.create_bound_transfer_package(&key, 2, 3, &TransferRecipient::Passphrase(recipient_key)).unwrap();
+        let sealed: Vec<String> = (0..3).map(|i| package.get_sealed_share_text(i).unwrap()).collect();
+        let seal = check_sealed_shares(&sealed).unwrap().unwrap();
+        assert_eq!(seal.binding, Some(RecipientBinding::Passphrase(params)));
+
+        // A full quorum of shares alone does not give the key
+        let error = manager.reconstruct_key_from_text_shares(&sealed).err().unwrap();
+        assert!(error.to_string().contains("bound to a passphrase"));
+        let unsealed: Vec<String> = (0..2).map(|i| package.get_share_text(i).unwrap().to_string()).collect();
+        assert_ne!(manager.reconstruct_key_from_text_shares(&unsealed).unwrap().key, key.key);
+
+        let passphrase = RecipientCredential::Passphrase("recipient passphrase".to_string());
+        let reconstructed = manager.reconstruct_bound_key_from_text_shares(&sealed[1..], Some(&passphrase)).unwrap();
+        assert_eq!(reconstructed.key, key.key);
+        let wrong = RecipientCredential::Passphrase("wrong passphrase".to_string());
+        let error = manager.reconstruct_bound_key_from_text_shares(&sealed, Some(&wrong)).err().unwrap();
+        assert!(error.to_string().contains("could not be unwrapped"));
+
+        // Bound to a machine identity, only that machine's secret unwraps the key
+        let machine_secret = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let recipient = TransferRecipient::Identity(PublicKey::from(&machine_secret));
+        let package = manager.create_bound_transfer_package(&key, 2, 3, &recipient).unwrap();
+        let sealed: Vec<String> = (0..2).map(|i| package.get_sealed_share_text(i).unwrap()).collect();
+        let reconstructed = manager.reconstruct_bound_key_from_text_shares(&sealed, Some(&RecipientCredential::Identity(machine_secret))).unwrap();
+        assert_eq!(reconstructed.key, key.key);
+        let other_machine = RecipientCredential::Identity(StaticSecret::random_from_rng(rand::rngs::OsRng));
+        assert!(manager.reconstruct_bound_key_from_text_shares(&sealed, Some(&other_machine)).is_err());
+        let error = manager.reconstruct_bound_key_from_text_shares(&sealed, Some(&passphrase)).err().unwrap();
+        assert!(error.to_string().contains("machine identity"));
+    }
+
+    #[test]
+    fn test_share_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = KeyShareManager::new("CRUSTy-test", dir.path()).unwrap();
+        assert!(manager.load_share_records().unwrap().is_empty());
+
+        let (split_key, other_key) = (split_key(), split_key());
+        manager.record_shares(&split_key).unwrap();
+        let records = manager.load_share_records().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[2].label, "Recovery share");
+        assert!(records.iter().all(|record| record.last_verified.is_none()));
+        assert!(!records[1].is_due(90));
+        assert!(records[1].is_due(0));
+
+        // A share is verified in any format, without the others
+        let record = manager.verify_share(&split_key.share_to_mnemonic(1).unwrap()).unwrap();
+        assert_eq!(record.index, 1);
+        let record = manager.verify_share(&split_key.share_to_text(2).unwrap()).unwrap();
+        assert_eq!(record.index, 2);
+        let records = manager.load_share_records().unwrap();
+        assert!(records[0].last_verified.is_none());
+        assert!(records[1].last_verified.is_some() && records[2].last_verified.is_some());
+
+        // Shares of another key, and text that is not a share, are not verified
+        assert!(manager.verify_share(&other_key.share_to_text(1).unwrap()).is_err());
+        assert!(manager.verify_share("not a share").is_err());
+
+        // A share last verified long ago, or on an unreadable date, is due
+        let mut record = records[1].clone();
+        record.last_verified = Some("2020-01-01 09:00:00".to_string());
+        assert!(record.is_due(90));
+        record.last_verified = Some("yesterday".to_string());
+        assert!(record.is_due(90));
+    }
+}
@@ -0,0 +1,206 @@
+/// System log module.
+///
+/// This module provides functionality for:
+/// - Sending log entries to the operating system's log: syslog on Unix (through its
+///   local socket), and the Windows Event Log (as the "CRUSTy" source)
+///
+/// Entries are sent after they are written to the log file, with their path already
+/// redacted if the logger redacts paths. Failed operations are sent as errors, the
+/// others as information.
+use std::io;
+
+use crate::logger::{LogEntry, LogForwarder};
+
+/// Name entries are sent under
+const SOURCE_NAME: &str = "CRUSTy";
+
+/// Get the text of an entry, as sent to the system log
+fn entry_text(entry: &LogEntry) -> String {
+    let mut text = entry.operation.clone();
+    if !entry.file_path.is_empty() {
+        text.push_str(&format!(" {}", entry.file_path));
+    }
+    text.push_str(&format!(": {}", entry.message));
+    if let Some(reason) = &entry.reason {
+        text.push_str(&format!(" (reason: {})", reason));
+    }
+    if let Some(key_fingerprint) = &entry.key_fingerprint {
+        text.push_str(&format!(" [key {}]", key_fingerprint));
+    }
+    text
+}
+
+/// Connection to the system log
+#[cfg(unix)]
+pub struct SystemLog {
+    socket: std::os::unix::net::UnixDatagram,
+}
+
+#[cfg(unix)]
+impl SystemLog {
+    /// Sockets of the syslog daemon (Linux, macOS, and the BSDs)
+    const SOCKET_PATHS: [&'static str; 3] = ["/dev/log", "/var/run/syslog", "/var/run/log"];
+
+    /// Facility of the entries (security/authorization messages)
+    const FACILITY_AUTHPRIV: u8 = 10;
+
+    /// Connect to the system log
+    ///
+    /// # Returns
+    /// * `io::Result<SystemLog>` - The connection, or an error if no syslog daemon
+    ///   is listening
+    pub fn open() -> io::Result<Self> {
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "No syslog socket found");
+        for path in Self::SOCKET_PATHS {
+            match Self::connect(std::path::Path::new(path)) {
+                Ok(system_log) => return Ok(system_log),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Connect to a syslog socket
+    fn connect(path: &std::path::Path) -> io::Result<Self> {
+        let socket = std::os::unix::net::UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(SystemLog { socket })
+    }
+
+    /// Send an entry to the system log
+    pub fn send(&self, entry: &LogEntry) -> io::Result<()> {
+        // Severity 6 is informational, 3 is error
+        let severity = if entry.success { 6 } else { 3 };
+        let message = format!(
+            "<{}>{} {}[{}]: {}",
+            Self::FACILITY_AUTHPRIV * 8 + severity,
+            chrono::Local::now().format("%b %e %H:%M:%S"),
+            SOURCE_NAME,
+            std::process::id(),
+            entry_text(entry)
+        );
+        self.socket.send(message.as_bytes()).map(|_| ())
+    }
+}
+
+/// Connection to the Windows Event Log
+#[cfg(windows)]
+pub struct SystemLog {
+    source: winapi::um::winnt::HANDLE,
+}
+
+// The event source handle may be used from any thread
+#[cfg(windows)]
+unsafe impl Send for SystemLog {}
+#[cfg(windows)]
+unsafe impl Sync for SystemLog {}
+
+#[cfg(windows)]
+impl SystemLog {
+    /// ID of every event (the Application log shows the text of the entry with it)
+    const EVENT_ID: u32 = 1;
+
+    /// Register CRUSTy as an event source of the Application log
+    ///
+    /// # Returns
+    /// * `io::Result<SystemLog>` - The event source, or an error
+    pub fn open() -> io::Result<Self> {
+        let name = wide(SOURCE_NAME);
+        let source = unsafe { winapi::um::winbase::RegisterEventSourceW(std::ptr::null(), name.as_ptr()) };
+        if source.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(SystemLog { source })
+    }
+
+    /// Send an entry to the Event Log
+    pub fn send(&self, entry: &LogEntry) -> io::Result<()> {
+        use winapi::um::winnt::{EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE};
+
+        let text = wide(&entry_text(entry));
+        let mut strings = [text.as_ptr()];
+        let event_type = if entry.success { EVENTLOG_INFORMATION_TYPE } else { EVENTLOG_ERROR_TYPE };
+        let reported = unsafe {
+            winapi::um::winbase::ReportEventW(
+                self.source,
+                event_type,
+                0,
+                Self::EVENT_ID,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_mut_ptr(),
+                std::ptr::null_mut(),
+            )
+        };
+        if reported == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for SystemLog {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::winbase::DeregisterEventSource(self.source);
+        }
+    }
+}
+
+/// Get a string as a null-terminated UTF-16 string
+#[cfg(windows)]
+fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Connection to the system log (not available on this system)
+#[cfg(not(any(unix, windows)))]
+pub struct SystemLog;
+
+#[cfg(not(any(unix, windows)))]
+impl SystemLog {
+    /// Connect to the system log (always fails on this system)
+    pub fn open() -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "No system log on this system"))
+    }
+
+    /// Send an entry to the system log
+    pub fn send(&self, _entry: &LogEntry) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogForwarder for SystemLog {
+    fn forward(&self, entry: &LogEntry) {
+        // The entry is still in the log file if the system log does not take it
+        let _ = self.send(entry);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn test_send_to_syslog_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("log");
+        let daemon = UnixDatagram::bind(&socket_path).unwrap();
+
+        let system_log = SystemLog::connect(&socket_path).unwrap();
+        let entry = LogEntry::new("Decrypt", "report.txt.encrypted", false, "Wrong key")
+            .with_key_fingerprint("3f2a9c1d");
+        system_log.send(&entry).unwrap();
+
+        let mut buffer = [0u8; 1024];
+        let length = daemon.recv(&mut buffer).unwrap();
+        let message = String::from_utf8_lossy(&buffer[..length]);
+        // authpriv (10) errors (3)
+        assert!(message.starts_with("<83>"), "{}", message);
+        assert!(message.contains(&format!(" CRUSTy[{}]: ", std::process::id())));
+        assert!(message.ends_with("Decrypt report.txt.encrypted: Wrong key [key 3f2a9c1d]"));
+    }
+}
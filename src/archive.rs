@@ -0,0 +1,457 @@
+/// Folder archive module.
+///
+/// This module provides functionality for:
+/// - Packing a folder, with its subfolders and files, into one encrypted `.crusty`
+///   archive, so a whole project can be shared as a single file
+/// - Extracting an archive into a folder, which only appears once it is complete
+///
+/// An archive is laid out like any encrypted file (KDF header, name header, AES-256-GCM
+/// ciphertext), with the folder's name stored in the name header. The plaintext is a
+/// simple tar-like stream:
+///
+/// ```text
+/// "CRUSTYAR" version(1)
+/// entries: kind(1) path_len(2) path (UTF-8, '/' between folders) [size(8) data]
+/// end:     kind 0
+/// ```
+///
+/// Paths are relative to the archived folder. Symbolic links are not followed, so only
+/// what is inside the folder is packed. The stream is encrypted and decrypted whole in
+/// memory, so in low-memory mode a folder or archive is only processed if it fits in the
+/// memory limit with its encrypted or decrypted copy.
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+use crate::file_names::{self, OutputName};
+use crate::parity;
+
+/// Extension of archive files
+pub const ARCHIVE_EXTENSION: &str = "crusty";
+
+/// Marks the start of an archive's plaintext
+const ARCHIVE_MAGIC: &[u8; 8] = b"CRUSTYAR";
+
+/// Version of the archive format
+const ARCHIVE_VERSION: u8 = 1;
+
+/// Entry kinds
+const ENTRY_END: u8 = 0;
+const ENTRY_FOLDER: u8 = 1;
+const ENTRY_FILE: u8 = 2;
+
+/// A file or subfolder found in the folder being packed
+struct PackEntry {
+    /// Path from the archived folder, with '/' between folders
+    path: String,
+    /// The file on disk (None for a folder)
+    file: Option<PathBuf>,
+    size: u64,
+}
+
+/// A file or subfolder read from an archive
+enum ArchiveEntry<'a> {
+    Folder(&'a str),
+    File(&'a str, &'a [u8]),
+}
+
+/// Check whether a file is an archive (by its `.crusty` extension)
+pub fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == ARCHIVE_EXTENSION)
+}
+
+/// Get the name of the archive for a folder: the folder's name plus `.crusty`, or a
+/// random name when names are hidden (the folder's name is stored in the archive)
+///
+/// # Returns
+/// * `Option<OsString>` - The name, or None if the path has no name
+pub fn archive_name(folder: &Path, hide_name: bool) -> Option<OsString> {
+    if hide_name {
+        return Some(Path::new(&file_names::opaque_encrypted_name())
+            .with_extension(ARCHIVE_EXTENSION)
+            .into_os_string());
+    }
+
+    let mut name = folder.file_name()?.to_os_string();
+    name.push(".");
+    name.push(ARCHIVE_EXTENSION);
+    Some(name)
+}
+
+/// Get the name of the folder an archive extracts to: the folder name stored in the
+/// archive, or else the archive's name without `.crusty`
+pub fn extracted_name(source: &Path, key: &EncryptionKey) -> Option<OutputName> {
+    match encryption::read_stored_name(source, key) {
+        Ok(Some(_)) => file_names::restored_name(source, key),
+        _ => source.file_stem().map(|stem| file_names::platform_safe_name(stem.to_os_string())),
+    }
+}
+
+/// Pack a folder into an encrypted archive
+///
+/// # Arguments
+/// * `folder` - The folder to pack, with its subfolders
+/// * `dest_path` - The archive to create (written atomically; never replaced)
+/// * `key` - The encryption key
+/// * `add_parity` - Whether to append parity, so damage to the archive can be repaired
+/// * `memory_limit` - Most memory the archive may take (None for no limit)
+/// * `progress_callback` - Called with the fraction done
+///
+/// # Returns
+/// * `Result<usize, EncryptionError>` - The number of files packed
+pub fn create_archive(
+    folder: &Path,
+    dest_path: &Path,
+    key: &EncryptionKey,
+    add_parity: bool,
+    memory_limit: Option<u64>,
+    progress_callback: impl Fn(f32),
+) -> Result<usize, EncryptionError> {
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            io::Error::new(io::ErrorKind::AlreadyExists, "Destination file already exists")
+        ));
+    }
+    let name = folder.file_name()
+        .ok_or_else(|| EncryptionError::Encryption(format!("{} cannot be archived", folder.display())))?;
+
+    let entries = folder_entries(folder)?;
+    let total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+    check_memory_limit(folder, total_size, memory_limit)?;
+
+    // Reading the files is most of the work
+    let mut packed = Zeroizing::new(ARCHIVE_MAGIC.to_vec());
+    packed.push(ARCHIVE_VERSION);
+    let mut packed_size = 0;
+    for entry in &entries {
+        let path_len = u16::try_from(entry.path.len())
+            .map_err(|_| EncryptionError::Encryption(format!("{} has too long a path to be archived", entry.path)))?;
+        packed.push(if entry.file.is_some() { ENTRY_FILE } else { ENTRY_FOLDER });
+        packed.extend_from_slice(&path_len.to_be_bytes());
+        packed.extend_from_slice(entry.path.as_bytes());
+
+        if let Some(file) = &entry.file {
+            let start = packed.len() + 8;
+            packed.extend_from_slice(&0u64.to_be_bytes());
+            File::open(file)?.read_to_end(&mut packed)?;
+            // The size read, in case the file changed since it was listed
+            let size = (packed.len() - start) as u64;
+            packed[start - 8..start].copy_from_slice(&size.to_be_bytes());
+
+            packed_size += entry.size;
+            if total_size > 0 {
+                progress_callback(0.8 * packed_size as f32 / total_size as f32);
+            }
+        }
+    }
+    packed.push(ENTRY_END);
+
+    let mut data = encryption::kdf_header(key);
+    data.extend(encryption::name_header(&file_names::stored_name_bytes(name), key)?);
+    data.extend(encryption::encrypt_data(&packed, key)?);
+    if add_parity {
+        parity::append_parity(&mut data);
+    }
+    progress_callback(0.9);
+
+    encryption::write_destination(dest_path, |dest_file| dest_file.write_all(&data))?;
+    progress_callback(1.0);
+
+    Ok(entries.iter().filter(|entry| entry.file.is_some()).count())
+}
+
+/// Check that an archive of the given size fits in the memory limit, with its encrypted
+/// or decrypted copy
+fn check_memory_limit(path: &Path, size: u64, memory_limit: Option<u64>) -> Result<(), EncryptionError> {
+    match memory_limit {
+        Some(limit) if size.saturating_mul(2) > limit => {
+            Err(EncryptionError::TooLarge(path.to_path_buf(), limit / (1024 * 1024)))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// List the subfolders and files of a folder, each folder before what it contains
+///
+/// Symbolic links are skipped, and so are temporary files left by an interrupted write.
+fn folder_entries(folder: &Path) -> Result<Vec<PackEntry>, EncryptionError> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![(folder.to_path_buf(), String::new())];
+
+    while let Some((dir, prefix)) = dirs.pop() {
+        let mut children: Vec<_> = fs::read_dir(&dir)?.collect::<io::Result<_>>()?;
+        children.sort_by_key(|child| child.file_name());
+
+        for child in children {
+            let file_type = child.file_type()?;
+            let path = child.path();
+            let name = child.file_name().into_string().map_err(|_| EncryptionError::Encryption(
+                format!("{} has a name that cannot be stored in an archive", path.display())
+            ))?;
+            let relative = format!("{}{}", prefix, name);
+
+            if file_type.is_dir() {
+                entries.push(PackEntry { path: relative.clone(), file: None, size: 0 });
+                dirs.push((path, format!("{}/", relative)));
+            } else if file_type.is_file() && !encryption::is_temp_destination(&path) {
+                let size = child.metadata()?.len();
+                entries.push(PackEntry { path: relative, file: Some(path), size });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Extract an encrypted archive
+///
+/// The folder is built under a temporary name in the output folder and renamed into
+/// place once every file is written, so an interrupted or failed extraction never
+/// leaves a partial folder. An existing folder of the same name is never replaced.
+///
+/// # Arguments
+/// * `source_path` - The archive
+/// * `output_dir` - The folder to extract into
+/// * `key` - The encryption key
+/// * `memory_limit` - Most memory the archive may take (None for no limit)
+/// * `progress_callback` - Called with the fraction done
+///
+/// # Returns
+/// * `Result<PathBuf, EncryptionError>` - The extracted folder
+pub fn extract_archive(
+    source_path: &Path,
+    output_dir: &Path,
+    key: &EncryptionKey,
+    memory_limit: Option<u64>,
+    progress_callback: impl Fn(f32),
+) -> Result<PathBuf, EncryptionError> {
+    let name = extracted_name(source_path, key)
+        .ok_or_else(|| EncryptionError::Decryption(format!("{} is not an archive", source_path.display())))?;
+    let dest_path = output_dir.join(&name.name);
+    if dest_path.exists() {
+        return Err(EncryptionError::Io(
+            io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", dest_path.display()))
+        ));
+    }
+
+    check_memory_limit(source_path, fs::metadata(source_path)?.len(), memory_limit)?;
+    let data = parity::read_encrypted_file(source_path, |p| progress_callback(p * 0.4))?;
+    let (_, ciphertext) = encryption::split_kdf_header(&data)?;
+    let (_, ciphertext) = encryption::split_name_header(ciphertext)?;
+    let packed = Zeroizing::new(encryption::decrypt_data(ciphertext, key)?);
+    progress_callback(0.5);
+
+    let entries = read_entries(&packed)?;
+
+    // A temporary folder left by an interrupted run holds nothing worth keeping
+    let temp = encryption::temp_destination(&dest_path);
+    if temp.exists() {
+        fs::remove_dir_all(&temp)?;
+    }
+    fs::create_dir(&temp)?;
+
+    let result = write_entries(&temp, &entries, &progress_callback).and_then(|_| {
+        // Checked again, as rename replaces an empty folder on Unix
+        if dest_path.exists() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", dest_path.display())));
+        }
+        fs::rename(&temp, &dest_path)
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&temp);
+        return Err(EncryptionError::Io(e));
+    }
+
+    progress_callback(1.0);
+    Ok(dest_path)
+}
+
+/// Read the entries of an archive's plaintext, checking its structure
+fn read_entries(packed: &[u8]) -> Result<Vec<ArchiveEntry<'_>>, EncryptionError> {
+    let invalid = |reason: &str| EncryptionError::Decryption(format!("Invalid archive: {}", reason));
+
+    let rest = packed.strip_prefix(ARCHIVE_MAGIC.as_slice())
+        .ok_or_else(|| invalid("this is an encrypted file, not an archive"))?;
+    let (&version, mut rest) = rest.split_first().ok_or_else(|| invalid("cut short"))?;
+    if version != ARCHIVE_VERSION {
+        return Err(invalid(&format!("unsupported version {}", version)));
+    }
+
+    let mut entries = Vec::new();
+    loop {
+        let (&kind, after_kind) = rest.split_first().ok_or_else(|| invalid("cut short"))?;
+        if kind == ENTRY_END {
+            if !after_kind.is_empty() {
+                return Err(invalid("data after the last entry"));
+            }
+            return Ok(entries);
+        }
+
+        let (path_len, after_len) = split_number::<2>(after_kind).ok_or_else(|| invalid("cut short"))?;
+        let path_len = path_len as usize;
+        if after_len.len() < path_len {
+            return Err(invalid("cut short"));
+        }
+        let path = std::str::from_utf8(&after_len[..path_len]).map_err(|_| invalid("a path is not UTF-8"))?;
+        rest = &after_len[path_len..];
+
+        match kind {
+            ENTRY_FOLDER => entries.push(ArchiveEntry::Folder(path)),
+            ENTRY_FILE => {
+                let (size, after_size) = split_number::<8>(rest).ok_or_else(|| invalid("cut short"))?;
+                let size = usize::try_from(size).ok()
+                    .filter(|&size| size <= after_size.len())
+                    .ok_or_else(|| invalid("cut short"))?;
+                entries.push(ArchiveEntry::File(path, &after_size[..size]));
+                rest = &after_size[size..];
+            },
+            _ => return Err(invalid(&format!("unknown entry kind {}", kind))),
+        }
+    }
+}
+
+/// Split a big-endian number of N bytes from the front of the data
+fn split_number<const N: usize>(data: &[u8]) -> Option<(u64, &[u8])> {
+    if data.len() < N {
+        return None;
+    }
+    let value = data[..N].iter().fold(0u64, |value, &byte| (value << 8) | u64::from(byte));
+    Some((value, &data[N..]))
+}
+
+/// Write the entries of an archive under a folder
+fn write_entries(root: &Path, entries: &[ArchiveEntry<'_>], progress_callback: &impl Fn(f32)) -> io::Result<()> {
+    for (index, entry) in entries.iter().enumerate() {
+        match entry {
+            ArchiveEntry::Folder(path) => fs::create_dir_all(entry_path(root, path)?)?,
+            ArchiveEntry::File(path, contents) => {
+                let path = entry_path(root, path)?;
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                // Never follows or replaces what an earlier entry created
+                let mut file = File::options().write(true).create_new(true).open(&path)?;
+                encryption::write_sparse(&mut file, contents, |_| {})?;
+            },
+        }
+        progress_callback(0.5 + 0.5 * (index + 1) as f32 / entries.len() as f32);
+    }
+    Ok(())
+}
+
+/// Get where an entry is written under the extracted folder
+///
+/// Each folder and file name must be a plain name (no `..`, root, or drive), so a
+/// crafted archive cannot write outside the folder. Names are made valid for this
+/// platform.
+fn entry_path(root: &Path, path: &str) -> io::Result<PathBuf> {
+    let mut entry_path = root.to_path_buf();
+    for part in path.split('/') {
+        let name = file_names::platform_safe_name(OsString::from(part)).name;
+        if !is_plain_name(&name) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid path in archive: {}", path)));
+        }
+        entry_path.push(name);
+    }
+    Ok(entry_path)
+}
+
+/// Check that a name is a single ordinary path component
+fn is_plain_name(name: &OsStr) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(component)), None) if component == name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir_all(project.join("src/nested")).unwrap();
+        fs::create_dir(project.join("empty")).unwrap();
+        fs::write(project.join("README.md"), b"Read me").unwrap();
+        fs::write(project.join("src/nested/main.rs"), b"fn main() {}").unwrap();
+        fs::write(project.join("src/zeros.bin"), vec![0u8; 10000]).unwrap();
+
+        let key = EncryptionKey::generate();
+        let archive = dir.path().join(archive_name(&project, false).unwrap());
+        assert_eq!(archive, dir.path().join("project.crusty"));
+        assert!(is_archive(&archive));
+        assert_eq!(create_archive(&project, &archive, &key, false, None, |_| {}).unwrap(), 3);
+
+        // The archive is extracted under the stored folder name, whatever it is called
+        let hidden = dir.path().join(archive_name(&project, true).unwrap());
+        fs::rename(&archive, &hidden).unwrap();
+        let output = dir.path().join("out");
+        fs::create_dir(&output).unwrap();
+        let extracted = extract_archive(&hidden, &output, &key, None, |_| {}).unwrap();
+        assert_eq!(extracted, output.join("project"));
+        assert_eq!(fs::read(extracted.join("README.md")).unwrap(), b"Read me");
+        assert_eq!(fs::read(extracted.join("src/nested/main.rs")).unwrap(), b"fn main() {}");
+        assert_eq!(fs::read(extracted.join("src/zeros.bin")).unwrap(), vec![0u8; 10000]);
+        assert!(extracted.join("empty").is_dir());
+
+        // An existing folder is not replaced, and a wrong key extracts nothing
+        assert!(extract_archive(&hidden, &output, &key, None, |_| {}).is_err());
+        fs::remove_dir_all(&extracted).unwrap();
+        assert!(extract_archive(&hidden, &output, &EncryptionKey::generate(), None, |_| {}).is_err());
+        assert_eq!(fs::read_dir(&output).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_archive_memory_limit() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("video.bin"), vec![0u8; 1024 * 1024]).unwrap();
+        let key = EncryptionKey::generate();
+        let archive = dir.path().join("project.crusty");
+
+        // Packed whole in memory, the folder does not fit in 1 MB with its encrypted copy
+        let limit = Some(1024 * 1024);
+        let result = create_archive(&project, &archive, &key, false, limit, |_| {});
+        assert!(matches!(result, Err(EncryptionError::TooLarge(path, 1)) if path == project));
+        assert!(!archive.exists());
+
+        create_archive(&project, &archive, &key, false, None, |_| {}).unwrap();
+        let output = dir.path().join("out");
+        fs::create_dir(&output).unwrap();
+        assert!(matches!(extract_archive(&archive, &output, &key, limit, |_| {}), Err(EncryptionError::TooLarge(..))));
+        assert!(extract_archive(&archive, &output, &key, Some(4 * 1024 * 1024), |_| {}).is_ok());
+    }
+
+    #[test]
+    fn test_crafted_paths_are_rejected() {
+        let dir = tempdir().unwrap();
+        for path in ["../escape.txt", "/etc/passwd", "a//b", "."] {
+            let mut packed = ARCHIVE_MAGIC.to_vec();
+            packed.push(ARCHIVE_VERSION);
+            packed.push(ENTRY_FILE);
+            packed.extend_from_slice(&(path.len() as u16).to_be_bytes());
+            packed.extend_from_slice(path.as_bytes());
+            packed.extend_from_slice(&1u64.to_be_bytes());
+            packed.extend_from_slice(b"x");
+            packed.push(ENTRY_END);
+
+            let entries = read_entries(&packed).unwrap();
+            assert!(write_entries(dir.path(), &entries, &|_| {}).is_err(), "{} was written", path);
+        }
+        assert!(!dir.path().join("../escape.txt").exists());
+
+        // Entries cut short are caught before anything is written
+        let mut packed = ARCHIVE_MAGIC.to_vec();
+        packed.extend_from_slice(&[ARCHIVE_VERSION, ENTRY_FILE, 0, 1, b'a']);
+        packed.extend_from_slice(&100u64.to_be_bytes());
+        assert!(read_entries(&packed).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+/// Re-encryption audit module.
+///
+/// This module provides functionality for:
+/// - Checking that an encrypted file decrypts to exactly a given original, as vendor
+///   audits need to show that archives match their sources
+/// - Reporting the evidence: the SHA-256 and size of the original, of the encrypted
+///   file, and of what it decrypts to, with the fingerprint of the key used
+///
+/// For CRUSTy files the audit also encrypts the original again, with the file's key and
+/// the nonce stored in the file: the result must be byte-for-byte the file's encrypted
+/// data, which shows the file holds the original and nothing else. age files and
+/// OpenPGP messages are compared by their decrypted contents only. Nothing decrypted is
+/// written to disk.
+use chrono::Local;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::age_format;
+use crate::backend::LocalBackend;
+use crate::container;
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+use crate::openpgp;
+use crate::parity;
+use crate::recipients;
+
+/// Hash and size of some data
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evidence {
+    /// SHA-256, in lower-case hexadecimal
+    pub sha256: String,
+    /// Size in bytes
+    pub len: u64,
+}
+
+impl Evidence {
+    /// Get the evidence for data
+    pub fn of(data: &[u8]) -> Self {
+        Evidence {
+            sha256: Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect(),
+            len: data.len() as u64,
+        }
+    }
+}
+
+/// Result of auditing an encrypted file against its original
+#[derive(Debug, Clone)]
+pub struct AuditReport {
+    pub original_path: PathBuf,
+    pub encrypted_path: PathBuf,
+    /// Fingerprint of the key the file was decrypted with
+    pub key_fingerprint: String,
+    /// Format of the encrypted file ("CRUSTy", "age" or "OpenPGP")
+    pub format: &'static str,
+    pub original: Evidence,
+    pub encrypted: Evidence,
+    pub decrypted: Evidence,
+    /// Offset of the first byte where the decrypted data differs from the original
+    /// (None if they are the same)
+    pub first_difference: Option<u64>,
+    /// Whether encrypting the original again gives the file's encrypted data (None for
+    /// formats that cannot be reproduced)
+    pub reencryption_matches: Option<bool>,
+    pub audited_at: String,
+}
+
+impl AuditReport {
+    /// Check whether the encrypted file holds exactly the original
+    pub fn matches(&self) -> bool {
+        self.first_difference.is_none() && self.reencryption_matches != Some(false)
+    }
+
+    /// Write the report as text, to keep with the audit records
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str("CRUSTy re-encryption audit\n");
+        text.push_str(&format!("Audited: {}\n", self.audited_at));
+        text.push_str(&format!("Result: {}\n\n", if self.matches() { "MATCH" } else { "MISMATCH" }));
+        text.push_str(&format!("Original: {}\n", self.original_path.display()));
+        text.push_str(&format!("  SHA-256: {}\n  Size: {} bytes\n", self.original.sha256, self.original.len));
+        text.push_str(&format!("Encrypted ({}): {}\n", self.format, self.encrypted_path.display()));
+        text.push_str(&format!("  SHA-256: {}\n  Size: {} bytes\n", self.encrypted.sha256, self.encrypted.len));
+        text.push_str("Decrypted:\n");
+        text.push_str(&format!("  SHA-256: {}\n  Size: {} bytes\n", self.decrypted.sha256, self.decrypted.len));
+        text.push_str(&format!("Key: {}\n\n", self.key_fingerprint));
+        match self.first_difference {
+            None => text.push_str("The decrypted data is identical to the original.\n"),
+            Some(offset) => text.push_str(&format!("The decrypted data differs from the original at byte {}.\n", offset)),
+        }
+        match self.reencryption_matches {
+            Some(true) => text.push_str("Encrypting the original again with the file's nonce reproduces the encrypted data exactly.\n"),
+            Some(false) => text.push_str("Encrypting the original again with the file's nonce does not reproduce the encrypted data.\n"),
+            None => text.push_str(&format!("{} files cannot be reproduced; only the decrypted data was compared.\n", self.format)),
+        }
+        text
+    }
+}
+
+/// Audit an encrypted file against its original
+///
+/// # Arguments
+/// * `original_path` - The original (unencrypted) file
+/// * `encrypted_path` - The encrypted file to check
+/// * `key` - The key the file was encrypted with (or a recipient's key)
+///
+/// # Returns
+/// * `Result<AuditReport, EncryptionError>` - The evidence, or an error if either file
+///   cannot be read or the encrypted file cannot be decrypted with the key
+pub fn audit_file(original_path: &Path, encrypted_path: &Path, key: &EncryptionKey) -> Result<AuditReport, EncryptionError> {
+    let original = Zeroizing::new(fs::read(original_path)?);
+    let encrypted = fs::read(encrypted_path)?;
+    let decrypted = LocalBackend::default().decrypt_to_memory(encrypted_path, key, |_| {})?;
+
+    let format = if age_format::is_age_file(encrypted_path) {
+        "age"
+    } else if openpgp::is_openpgp_file(encrypted_path) {
+        "OpenPGP"
+    } else {
+        "CRUSTy"
+    };
+    let reencryption_matches = match format {
+        "CRUSTy" => Some(reencryption_matches(encrypted_path, &original, key)?),
+        _ => None,
+    };
+
+    let first_difference = original.iter().zip(decrypted.iter())
+        .position(|(a, b)| a != b)
+        .or_else(|| (original.len() != decrypted.len()).then(|| original.len().min(decrypted.len())))
+        .map(|offset| offset as u64);
+
+    Ok(AuditReport {
+        original_path: original_path.to_path_buf(),
+        encrypted_path: encrypted_path.to_path_buf(),
+        key_fingerprint: key.fingerprint(),
+        format,
+        original: Evidence::of(&original),
+        encrypted: Evidence::of(&encrypted),
+        decrypted: Evidence::of(&decrypted),
+        first_difference,
+        reencryption_matches,
+        audited_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    })
+}
+
+/// Check whether encrypting the original with a CRUSTy file's key and nonce gives the
+/// file's encrypted data
+fn reencryption_matches(encrypted_path: &Path, original: &[u8], key: &EncryptionKey) -> Result<bool, EncryptionError> {
+    let contents = parity::read_encrypted_file(encrypted_path, |_| {})?;
+    let (recipient_header, rest) = recipients::split_recipient_header(&contents)?;
+    let file_key = match recipient_header {
+        Some(header) => header.open(key)?.1,
+        None => key.clone(),
+    };
+    let (_, rest) = encryption::split_kdf_header(rest)?;
+    let (_, rest) = encryption::split_name_header(rest)?;
+    let (_, payload) = encryption::split_digest_header(rest)?;
+
+    let Some(nonce) = payload.get(..12) else {
+        return Ok(false);
+    };
+    let nonce: [u8; 12] = nonce.try_into().expect("12 bytes");
+    Ok(container::encrypt_data_with_nonce(original, &file_key, &nonce)? == payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use crate::backend::BackendFactory;
+
+    #[test]
+    fn test_audit_file() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let original = dir.path().join("source.tar");
+        fs::write(&original, b"release 4.2 sources").unwrap();
+        let encrypted = dir.path().join("source.tar.encrypted");
+        BackendFactory::create_local_with(LocalBackend { add_parity: true, ..LocalBackend::default() })
+            .encrypt_file_storing_name(&original, &encrypted, &key, std::ffi::OsStr::new("source.tar"), |_| {})
+            .unwrap();
+
+        let report = audit_file(&original, &encrypted, &key).unwrap();
+        assert!(report.matches());
+        assert_eq!(report.reencryption_matches, Some(true));
+        assert_eq!(report.original, report.decrypted);
+        assert_eq!(report.original, Evidence::of(b"release 4.2 sources"));
+        assert!(report.to_text().contains("MATCH"));
+
+        // A changed original is caught, with where it differs
+        fs::write(&original, b"release 4.3 sources").unwrap();
+        let report = audit_file(&original, &encrypted, &key).unwrap();
+        assert!(!report.matches());
+        assert_eq!(report.first_difference, Some(10));
+        assert_eq!(report.reencryption_matches, Some(false));
+
+        // A file that does not open with the key is not audited
+        assert!(audit_file(&original, &encrypted, &EncryptionKey::generate()).is_err());
+    }
+}
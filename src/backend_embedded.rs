@@ -1,107 +0,0 @@
-/// Embedded device implementation of the encryption backend.
-use std::path::Path;
-
-use crate::backend::{EncryptionBackend, EmbeddedBackend};
-use crate::encryption::{EncryptionKey, EncryptionError};
-
-impl EmbeddedBackend {
-    /// Attempts to connect to the embedded device.
-    pub fn connect(&mut self) -> Result<(), EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // connection logic when the embedded system integration is implemented.
-        
-        // For now, just set the connected flag to true
-        self.connected = true;
-        Ok(())
-    }
-    
-    /// Checks if the backend is connected to the embedded device.
-    pub fn is_connected(&self) -> bool {
-        self.connected
-    }
-    
-    /// Disconnects from the embedded device.
-    pub fn disconnect(&mut self) {
-        // This is a placeholder implementation that will be replaced with actual
-        // disconnection logic when the embedded system integration is implemented.
-        
-        // For now, just set the connected flag to false
-        self.connected = false;
-    }
-}
-
-impl EncryptionBackend for EmbeddedBackend {
-    fn encrypt_data(&self, _data: &[u8], _key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // embedded device encryption logic when the embedded system integration is implemented.
-        
-        // For now, return an error indicating that the embedded backend is not implemented
-        Err(EncryptionError::Encryption("Embedded backend not implemented".to_string()))
-    }
-    
-    fn decrypt_data(&self, _data: &[u8], _key: &EncryptionKey) -> Result<Vec<u8>, EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // embedded device decryption logic when the embedded system integration is implemented.
-        
-        // For now, return an error indicating that the embedded backend is not implemented
-        Err(EncryptionError::Decryption("Embedded backend not implemented".to_string()))
-    }
-    
-    fn encrypt_file(
-        &self,
-        _source_path: &Path,
-        _dest_path: &Path,
-        _key: &EncryptionKey,
-        _progress_callback: impl Fn(f32) + Send + 'static,
-    ) -> Result<(), EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // embedded device encryption logic when the embedded system integration is implemented.
-        
-        // For now, return an error indicating that the embedded backend is not implemented
-        Err(EncryptionError::Encryption("Embedded backend not implemented".to_string()))
-    }
-    
-    fn decrypt_file(
-        &self,
-        _source_path: &Path,
-        _dest_path: &Path,
-        _key: &EncryptionKey,
-        _progress_callback: impl Fn(f32) + Send + 'static,
-    ) -> Result<(), EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // embedded device decryption logic when the embedded system integration is implemented.
-        
-        // For now, return an error indicating that the embedded backend is not implemented
-        Err(EncryptionError::Decryption("Embedded backend not implemented".to_string()))
-    }
-    
-    
-    fn encrypt_files(
-        &self,
-        _source_paths: &[&Path],
-        _dest_dir: &Path,
-        _key: &EncryptionKey,
-        _progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
-    ) -> Result<Vec<String>, EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // embedded device encryption logic when the embedded system integration is implemented.
-        
-        // For now, return an error indicating that the embedded backend is not implemented
-        Err(EncryptionError::Encryption("Embedded backend not implemented".to_string()))
-    }
-    
-    fn decrypt_files(
-        &self,
-        _source_paths: &[&Path],
-        _dest_dir: &Path,
-        _key: &EncryptionKey,
-        _progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
-    ) -> Result<Vec<String>, EncryptionError> {
-        // This is a placeholder implementation that will be replaced with actual
-        // embedded device decryption logic when the embedded system integration is implemented.
-        
-        // For now, return an error indicating that the embedded backend is not implemented
-        Err(EncryptionError::Decryption("Embedded backend not implemented".to_string()))
-    }
-    
-}
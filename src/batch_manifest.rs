@@ -0,0 +1,305 @@
+/// Batch manifest module.
+///
+/// This module provides functionality for:
+/// - Writing a manifest of the files a batch encryption saved in its output directory:
+///   each file's name, size, and SHA-256, with the key's fingerprint and the time
+/// - Signing the manifest, and checking the signature and every file against it later
+///
+/// The signature is an HMAC-SHA256 with a key derived from the encryption key, so only
+/// someone holding the key can write a manifest that checks; a changed, added, or
+/// removed entry breaks the signature. The manifest is text, so it can be read (and
+/// the hashes checked with other tools) without CRUSTy.
+use chrono::Local;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::locale::STORED_TIMESTAMP_FORMAT;
+
+/// Name of the manifest, in the output directory of the batch
+pub const MANIFEST_NAME: &str = "CRUSTY-MANIFEST.txt";
+
+/// Start of the line holding the signature, which ends the manifest
+const SIGNATURE_PREFIX: &str = "Signature (HMAC-SHA256): ";
+
+/// A file listed in the manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    /// Path of the file under the manifest's folder, with `/` between folders
+    pub file_name: String,
+    /// Size of the encrypted file
+    pub len: u64,
+    /// SHA-256 of the encrypted file, in hex
+    pub sha256: String,
+}
+
+/// Contents of a batch manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchManifest {
+    /// When the batch was encrypted (`STORED_TIMESTAMP_FORMAT`)
+    pub created_at: String,
+    /// Fingerprint of the key the files were encrypted with
+    pub key_fingerprint: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+impl BatchManifest {
+    /// Get the manifest as text, signed with the key
+    pub fn to_text(&self, key: &EncryptionKey) -> String {
+        let body = self.body();
+        format!("{}{}{}\n", body, SIGNATURE_PREFIX, signature(&body, key))
+    }
+
+    /// Get the signed part of the manifest
+    fn body(&self) -> String {
+        let mut text = String::new();
+        text.push_str("CRUSTy Batch Manifest\n");
+        text.push_str("=====================\n\n");
+        text.push_str(&format!("Created:          {}\n", self.created_at));
+        text.push_str(&format!("Key fingerprint:  {}\n", self.key_fingerprint));
+        text.push_str(&format!("Files:            {}\n\n", self.files.len()));
+        for entry in &self.files {
+            text.push_str(&format!("File: {}\n", entry.file_name));
+            text.push_str(&format!("  Bytes:   {}\n", entry.len));
+            text.push_str(&format!("  SHA-256: {}\n", entry.sha256));
+        }
+        text.push('\n');
+        text
+    }
+
+    /// Read a manifest from its text (as written by `to_text`), checking its signature
+    ///
+    /// # Returns
+    /// * `Result<BatchManifest, EncryptionError>` - The manifest, or an error if it was
+    ///   written with another key, or was changed or damaged
+    pub fn from_text(text: &str, key: &EncryptionKey) -> Result<BatchManifest, EncryptionError> {
+        let damaged = |field: &str| EncryptionError::Decryption(
+            format!("The manifest is damaged or not a batch manifest ({} is missing or unreadable)", field)
+        );
+        if !text.starts_with("CRUSTy Batch Manifest") {
+            return Err(EncryptionError::Decryption("The file is not a batch manifest".to_string()));
+        }
+        let (body, signature_line) = text.split_once(SIGNATURE_PREFIX).ok_or_else(|| damaged("Signature"))?;
+
+        let mut key_fingerprint = None;
+        let mut created_at = None;
+        let mut count = None;
+        let mut files = Vec::new();
+        let mut lines = body.lines();
+        while let Some(line) = lines.next() {
+            if let Some(file_name) = line.strip_prefix("File: ") {
+                let len = lines.next()
+                    .and_then(|line| line.trim().strip_prefix("Bytes:"))
+                    .and_then(|len| len.trim().parse().ok())
+                    .ok_or_else(|| damaged("a file's size"))?;
+                let sha256 = lines.next()
+                    .and_then(|line| line.trim().strip_prefix("SHA-256:"))
+                    .map(|sha256| sha256.trim().to_lowercase())
+                    .ok_or_else(|| damaged("a file's SHA-256"))?;
+                files.push(ManifestEntry { file_name: file_name.to_string(), len, sha256 });
+            } else if let Some((name, value)) = line.split_once(':') {
+                match name {
+                    "Created" => created_at = Some(value.trim().to_string()),
+                    "Key fingerprint" => key_fingerprint = Some(value.trim().to_string()),
+                    "Files" => count = value.trim().parse::<usize>().ok(),
+                    _ => {},
+                }
+            }
+        }
+
+        let key_fingerprint = key_fingerprint.ok_or_else(|| damaged("Key fingerprint"))?;
+        if key_fingerprint != key.fingerprint() {
+            return Err(EncryptionError::KeyError(format!(
+                "The manifest was written for key {}, not the selected key ({})", key_fingerprint, key.fingerprint()
+            )));
+        }
+        if !same_signature(body, key, signature_line.trim()) {
+            return Err(EncryptionError::Decryption(
+                "The manifest's signature does not match: it was changed after it was written".to_string()
+            ));
+        }
+        if count != Some(files.len()) {
+            return Err(damaged("Files"));
+        }
+        // Files are looked for by name, which must stay under the manifest's folder
+        if files.iter().any(|entry| !is_relative_name(&entry.file_name)) {
+            return Err(damaged("a file name"));
+        }
+
+        Ok(BatchManifest {
+            created_at: created_at.ok_or_else(|| damaged("Created"))?,
+            key_fingerprint,
+            files,
+        })
+    }
+}
+
+/// State of a file listed in a manifest
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryStatus {
+    /// Same size and SHA-256 as in the manifest
+    Intact,
+    /// Different from the file in the manifest
+    Changed,
+    /// Not found
+    Missing,
+}
+
+/// Result of checking files against their manifest
+#[derive(Debug, Clone)]
+pub struct ManifestCheck {
+    pub manifest_path: PathBuf,
+    pub manifest: BatchManifest,
+    /// Every file of the manifest, in order, with its state
+    pub files: Vec<(ManifestEntry, EntryStatus)>,
+}
+
+impl ManifestCheck {
+    /// Count the files in a state
+    pub fn count(&self, status: &EntryStatus) -> usize {
+        self.files.iter().filter(|(_, file_status)| file_status == status).count()
+    }
+
+    /// Check whether every file is intact
+    pub fn is_intact(&self) -> bool {
+        self.files.iter().all(|(_, status)| *status == EntryStatus::Intact)
+    }
+}
+
+/// Write the manifest of encrypted files into a folder
+///
+/// # Arguments
+/// * `output_dir` - The output directory of the batch, where the manifest is written
+/// * `files` - The encrypted files saved in the directory (or its subfolders)
+/// * `key` - The key the files were encrypted with, which signs the manifest
+///
+/// # Returns
+/// * `Result<PathBuf, EncryptionError>` - The path of the manifest
+pub fn write_manifest(output_dir: &Path, files: &[PathBuf], key: &EncryptionKey) -> Result<PathBuf, EncryptionError> {
+    let mut entries = Vec::with_capacity(files.len());
+    for file in files {
+        let file_name = file.strip_prefix(output_dir).ok()
+            .and_then(|relative| relative.components()
+                .map(|component| component.as_os_str().to_str())
+                .collect::<Option<Vec<_>>>())
+            .map(|parts| parts.join("/"))
+            .filter(|name| is_relative_name(name))
+            .ok_or_else(|| EncryptionError::Encryption(
+                format!("{} cannot be listed in the manifest of {}", file.display(), output_dir.display())
+            ))?;
+        let (len, sha256) = hash_file(file)?;
+        entries.push(ManifestEntry { file_name, len, sha256 });
+    }
+
+    let manifest = BatchManifest {
+        created_at: Local::now().format(STORED_TIMESTAMP_FORMAT).to_string(),
+        key_fingerprint: key.fingerprint(),
+        files: entries,
+    };
+    let path = output_dir.join(MANIFEST_NAME);
+    fs::write(&path, manifest.to_text(key))?;
+    Ok(path)
+}
+
+/// Check the files listed in a manifest, in the manifest's folder
+///
+/// # Returns
+/// * `Result<ManifestCheck, EncryptionError>` - The state of each file, or an error if
+///   the manifest cannot be read or its signature does not check with the key
+pub fn check_manifest(manifest_path: &Path, key: &EncryptionKey) -> Result<ManifestCheck, EncryptionError> {
+    let manifest = BatchManifest::from_text(&fs::read_to_string(manifest_path)?, key)?;
+    let folder = manifest_path.parent().unwrap_or(Path::new("."));
+
+    let files = manifest.files.iter()
+        .map(|entry| {
+            let status = match hash_file(&folder.join(&entry.file_name)) {
+                Ok((len, sha256)) if len == entry.len && sha256 == entry.sha256 => EntryStatus::Intact,
+                Ok(_) => EntryStatus::Changed,
+                Err(_) => EntryStatus::Missing,
+            };
+            (entry.clone(), status)
+        })
+        .collect();
+
+    Ok(ManifestCheck {
+        manifest_path: manifest_path.to_path_buf(),
+        manifest,
+        files,
+    })
+}
+
+/// Get the size and SHA-256 of a file, reading it in blocks
+fn hash_file(path: &Path) -> Result<(u64, String), EncryptionError> {
+    let mut hasher = Sha256::new();
+    let len = io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok((len, hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()))
+}
+
+/// Check that a name in a manifest is a path under its folder
+fn is_relative_name(name: &str) -> bool {
+    !name.is_empty() && Path::new(name).components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Get the HMAC of a manifest's text
+fn mac(body: &str, key: &EncryptionKey) -> Hmac<Sha256> {
+    let mut mac_key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, &key.key)
+        .expand(b"CRUSTy batch manifest", &mut mac_key)
+        .expect("32 bytes is a valid HKDF output length");
+    let mut mac = Hmac::<Sha256>::new_from_slice(&mac_key).expect("HMAC takes keys of any length");
+    mac.update(body.as_bytes());
+    mac
+}
+
+/// Sign a manifest's text, in hex
+fn signature(body: &str, key: &EncryptionKey) -> String {
+    mac(body, key).finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Check a manifest's signature (in constant time)
+fn same_signature(body: &str, key: &EncryptionKey, signature: &str) -> bool {
+    let bytes: Option<Vec<u8>> = (0..signature.len())
+        .step_by(2)
+        .map(|i| signature.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect();
+    bytes.is_some_and(|bytes| mac(body, key).verify_slice(&bytes).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        fs::create_dir(dir.path().join("Reports")).unwrap();
+        let first = dir.path().join("a.txt.encrypted");
+        let second = dir.path().join("Reports").join("b.txt.encrypted");
+        fs::write(&first, b"first ciphertext").unwrap();
+        fs::write(&second, b"second ciphertext").unwrap();
+
+        let path = write_manifest(dir.path(), &[first.clone(), second.clone()], &key).unwrap();
+        let check = check_manifest(&path, &key).unwrap();
+        assert!(check.is_intact());
+        assert_eq!(check.manifest.files[1].file_name, "Reports/b.txt.encrypted");
+
+        // Changed and missing files are found
+        fs::write(&first, b"other ciphertext").unwrap();
+        fs::remove_file(&second).unwrap();
+        let check = check_manifest(&path, &key).unwrap();
+        assert_eq!(check.files[0].1, EntryStatus::Changed);
+        assert_eq!(check.files[1].1, EntryStatus::Missing);
+
+        // The manifest cannot be changed, or checked with another key
+        assert!(matches!(check_manifest(&path, &EncryptionKey::generate()), Err(EncryptionError::KeyError(_))));
+        let text = fs::read_to_string(&path).unwrap();
+        fs::write(&path, text.replace("Bytes:   16", "Bytes:   17")).unwrap();
+        assert!(check_manifest(&path, &key).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+/// Batch report module.
+///
+/// This module provides functionality for:
+/// - Listing each file of a finished operation: its input, the file written, whether
+///   it succeeded (with its message), how long it took, its size, and the fingerprint
+///   of the key it was processed with
+/// - Saving the report as CSV or JSON
+///
+/// The report is made from the results the operation thread reports for each file, so
+/// it lists exactly what the file list shows. Only key fingerprints go in the report,
+/// never keys.
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::gui::file_list::{FileEntry, FileOperationType, FileStatus};
+use crate::locale::STORED_TIMESTAMP_FORMAT;
+use crate::logger::csv_field;
+
+/// Whether a file of the report succeeded
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportStatus {
+    Succeeded,
+    Failed,
+}
+
+impl ReportStatus {
+    /// Get the status as written in the CSV
+    fn name(self) -> &'static str {
+        match self {
+            ReportStatus::Succeeded => "succeeded",
+            ReportStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One file of a report
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ReportEntry {
+    /// "encrypt" or "decrypt"
+    pub operation: &'static str,
+    pub input: PathBuf,
+    /// File written (None if the file failed)
+    pub output: Option<PathBuf>,
+    pub status: ReportStatus,
+    /// Success or failure message
+    pub message: String,
+    /// Time the file took, in seconds
+    pub duration_secs: Option<f64>,
+    /// Size of the input in bytes
+    pub size: Option<u64>,
+    pub key_fingerprint: Option<String>,
+}
+
+/// Report of the files of a finished operation
+#[derive(Serialize, Clone, Debug)]
+pub struct BatchReport {
+    pub created_at: String,
+    pub files: Vec<ReportEntry>,
+}
+
+impl BatchReport {
+    /// Make the report of the finished files of the file list
+    ///
+    /// # Returns
+    /// * `Option<BatchReport>` - The report, or None if no file is finished
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a FileEntry>) -> Option<Self> {
+        let files: Vec<ReportEntry> = entries.into_iter()
+            .filter_map(|entry| {
+                let (status, message) = match entry.status {
+                    FileStatus::Completed => (ReportStatus::Succeeded, entry.result.clone()),
+                    FileStatus::Failed => (ReportStatus::Failed, entry.error.clone()),
+                    _ => return None,
+                };
+                Some(ReportEntry {
+                    operation: match entry.operation_type {
+                        FileOperationType::Decrypt => "decrypt",
+                        _ => "encrypt",
+                    },
+                    input: entry.path.clone(),
+                    output: entry.output_path.clone(),
+                    status,
+                    message: message.unwrap_or_default(),
+                    duration_secs: entry.elapsed_time().map(|duration| duration.as_secs_f64()),
+                    size: entry.file_size,
+                    key_fingerprint: entry.key_fingerprint.clone(),
+                })
+            })
+            .collect();
+
+        (!files.is_empty()).then(|| BatchReport {
+            created_at: Local::now().format(STORED_TIMESTAMP_FORMAT).to_string(),
+            files,
+        })
+    }
+
+    /// Count the files that succeeded and failed
+    pub fn counts(&self) -> (usize, usize) {
+        let succeeded = self.files.iter().filter(|entry| entry.status == ReportStatus::Succeeded).count();
+        (succeeded, self.files.len() - succeeded)
+    }
+
+    /// Get the report as CSV, one line per file
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("operation,input,output,status,message,duration_secs,size,key_fingerprint\n");
+        for entry in &self.files {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                entry.operation,
+                csv_field(&entry.input.to_string_lossy()),
+                entry.output.as_ref().map(|output| csv_field(&output.to_string_lossy())).unwrap_or_default(),
+                entry.status.name(),
+                csv_field(&entry.message),
+                entry.duration_secs.map(|secs| format!("{:.3}", secs)).unwrap_or_default(),
+                entry.size.map(|size| size.to_string()).unwrap_or_default(),
+                entry.key_fingerprint.as_deref().unwrap_or(""),
+            ));
+        }
+        csv
+    }
+
+    /// Save the report: as JSON if the file name ends in `.json`, otherwise as CSV
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let contents = if json {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.to_csv()
+        };
+        fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn finished_entries() -> Vec<FileEntry> {
+        let mut completed = FileEntry::new(PathBuf::from("report, final.txt"), FileOperationType::Encrypt);
+        completed.file_size = Some(1200);
+        completed.output_path = Some(PathBuf::from("out/report, final.txt.encrypted"));
+        completed.key_fingerprint = Some("0123456789abcdef".to_string());
+        completed.set_completed("Successfully encrypted: report, final.txt".to_string());
+
+        let mut failed = FileEntry::new(PathBuf::from("photo.jpg.encrypted"), FileOperationType::Decrypt);
+        failed.key_fingerprint = Some("0123456789abcdef".to_string());
+        failed.set_failed("Failed to decrypt photo.jpg.encrypted: Wrong encryption key used".to_string());
+
+        let pending = FileEntry::new(PathBuf::from("later.txt"), FileOperationType::Encrypt);
+        vec![completed, failed, pending]
+    }
+
+    #[test]
+    fn test_report_from_entries() {
+        let report = BatchReport::from_entries(&finished_entries()).unwrap();
+
+        // Files still pending are left out
+        assert_eq!(report.files.len(), 2);
+        assert_eq!(report.counts(), (1, 1));
+        assert_eq!(report.files[0].output.as_deref(), Some(Path::new("out/report, final.txt.encrypted")));
+        assert_eq!(report.files[1].operation, "decrypt");
+        assert_eq!(report.files[1].output, None);
+
+        assert!(BatchReport::from_entries(&[FileEntry::new(PathBuf::from("a.txt"), FileOperationType::Encrypt)]).is_none());
+    }
+
+    #[test]
+    fn test_save_csv_and_json() {
+        let dir = tempdir().unwrap();
+        let report = BatchReport::from_entries(&finished_entries()).unwrap();
+
+        let csv_path = dir.path().join("report.csv");
+        report.save(&csv_path).unwrap();
+        let csv = fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "operation,input,output,status,message,duration_secs,size,key_fingerprint");
+        assert!(lines[1].starts_with("encrypt,\"report, final.txt\",\"out/report, final.txt.encrypted\",succeeded,"));
+        assert!(lines[1].ends_with(",1200,0123456789abcdef"));
+        assert!(lines[2].starts_with("decrypt,\"photo.jpg.encrypted\",,failed,"));
+
+        let json_path = dir.path().join("report.json");
+        report.save(&json_path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["files"][0]["status"], "succeeded");
+        assert_eq!(json["files"][0]["size"], 1200);
+        assert_eq!(json["files"][1]["key_fingerprint"], "0123456789abcdef");
+    }
+}
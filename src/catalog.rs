@@ -0,0 +1,264 @@
+/// Metadata catalog module.
+///
+/// This module provides functionality for:
+/// - Listing the encrypted files of a folder and its subfolders with their size, date,
+///   format, and the key they open with, as a catalog that can be shared unencrypted
+/// - Including the original names, their SHA-256 only, or no names at all
+/// - Saving the catalog as CSV or JSON
+///
+/// Each file's ID is the start of the SHA-256 of the encrypted file, so IDs reveal
+/// nothing about the contents or names, stay the same when the catalog is made again,
+/// and let a collaborator check that the file they are sent is the one they asked for.
+/// The key is found by trying the saved keys on the name stored in the file, or by
+/// verifying the file with them; nothing decrypted is written, and only key
+/// fingerprints go in the catalog.
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::age_format;
+use crate::backend::BackendFactory;
+use crate::encryption::{self, EncryptionKey};
+use crate::file_names;
+use crate::logger::csv_field;
+use crate::openpgp;
+use crate::secured_folders;
+
+/// Hexadecimal digits of the SHA-256 used as a file's ID
+const ID_LEN: usize = 16;
+
+/// What the catalog says about file names
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum CatalogNames {
+    /// No names: files are only known by their ID
+    Omitted,
+    /// The SHA-256 of each original name, so a collaborator can check for a name they know
+    Hashed,
+    /// The original names, with the subfolder they are in
+    Included,
+}
+
+impl CatalogNames {
+    /// Every choice, in the order they are offered
+    pub const ALL: [CatalogNames; 3] = [CatalogNames::Omitted, CatalogNames::Hashed, CatalogNames::Included];
+
+    /// Get the name shown for the choice
+    pub fn name(self) -> &'static str {
+        match self {
+            CatalogNames::Omitted => "No names",
+            CatalogNames::Hashed => "Hashed names (SHA-256)",
+            CatalogNames::Included => "Original names",
+        }
+    }
+}
+
+/// One encrypted file of a catalog
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct CatalogEntry {
+    /// Start of the SHA-256 of the encrypted file
+    pub id: String,
+    /// The file, for finding requested items (never written to the catalog)
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// The original name, or its SHA-256, as chosen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Size of the encrypted file in bytes
+    pub size: u64,
+    /// When the encrypted file was last changed
+    pub modified: String,
+    /// "CRUSTy", "age" or "OpenPGP"
+    pub format: &'static str,
+    /// Fingerprint of the saved key the file opens with (None if none of them does)
+    pub key_id: Option<String>,
+}
+
+/// Catalog of the encrypted files of a folder
+#[derive(Serialize, Clone, Debug)]
+pub struct Catalog {
+    /// The folder (never written to the catalog)
+    #[serde(skip)]
+    pub folder: PathBuf,
+    pub names: CatalogNames,
+    pub created_at: String,
+    pub files: Vec<CatalogEntry>,
+}
+
+impl Catalog {
+    /// Find the file with an ID, or the start of one
+    pub fn find(&self, id: &str) -> Option<&CatalogEntry> {
+        let id = id.trim().to_lowercase();
+        if id.is_empty() {
+            return None;
+        }
+        self.files.iter().find(|entry| entry.id.starts_with(&id))
+    }
+
+    /// Get the catalog as CSV, one line per file
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        let name_column = if self.names == CatalogNames::Omitted { "" } else { ",name" };
+        csv.push_str(&format!("id{},size,modified,format,key_id\n", name_column));
+        for entry in &self.files {
+            let name = entry.name.as_deref().map(|name| format!(",{}", csv_field(name))).unwrap_or_default();
+            csv.push_str(&format!(
+                "{}{},{},{},{},{}\n",
+                entry.id, name, entry.size, csv_field(&entry.modified), entry.format,
+                entry.key_id.as_deref().unwrap_or("")
+            ));
+        }
+        csv
+    }
+
+    /// Save the catalog: as JSON if the file name ends in `.json`, otherwise as CSV
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let contents = if json {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.to_csv()
+        };
+        fs::write(path, contents)
+    }
+}
+
+/// Check whether a file is encrypted, by its name (CRUSTy and OpenPGP) or start (age)
+pub fn is_encrypted_file(path: &Path) -> bool {
+    file_names::is_encrypted_name(path) || file_names::is_openpgp_name(path) || age_format::is_age_file(path)
+}
+
+/// Get the SHA-256 of a file, in lower-case hexadecimal
+fn file_sha256(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Find the key a file opens with: first by the name stored in it, then by verifying it
+fn find_key<'a>(path: &Path, keys: &'a [EncryptionKey]) -> Option<&'a EncryptionKey> {
+    if let Some(key) = keys.iter().find(|key| matches!(encryption::read_stored_name(path, key), Ok(Some(_)))) {
+        return Some(key);
+    }
+    let backend = BackendFactory::create_local();
+    keys.iter().find(|key| backend.verify_file(path, key, |_| {}).is_ok())
+}
+
+/// Make the catalog of the encrypted files in a folder and its subfolders
+///
+/// # Arguments
+/// * `folder` - The folder of encrypted files
+/// * `names` - What the catalog says about file names
+/// * `keys` - The saved keys, to find the key of each file
+/// * `progress` - Called with the number of files done and the number to do
+///
+/// # Returns
+/// * `io::Result<Catalog>` - The catalog, or an error if the folder or a file cannot be read
+pub fn build_catalog(
+    folder: &Path,
+    names: CatalogNames,
+    keys: &[EncryptionKey],
+    progress: impl Fn(usize, usize),
+) -> io::Result<Catalog> {
+    let mut files: Vec<PathBuf> = secured_folders::folder_files(folder)?.into_iter()
+        .filter(|file| is_encrypted_file(file))
+        .collect();
+    files.sort();
+
+    let mut entries = Vec::with_capacity(files.len());
+    for (i, path) in files.iter().enumerate() {
+        progress(i, files.len());
+        let metadata = fs::metadata(path)?;
+        let key = find_key(path, keys);
+        let format = if age_format::is_age_file(path) {
+            "age"
+        } else if openpgp::is_openpgp_file(path) {
+            "OpenPGP"
+        } else {
+            "CRUSTy"
+        };
+
+        let original = match key {
+            Some(key) => file_names::restored_name(path, key),
+            None => file_names::decrypted_name(path),
+        };
+        let original = original.map(|name| name.name.to_string_lossy().to_string()).unwrap_or_default();
+        let name = match names {
+            CatalogNames::Omitted => None,
+            CatalogNames::Hashed => Some(Sha256::digest(original.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()),
+            CatalogNames::Included => {
+                let subfolder = path.parent().and_then(|parent| parent.strip_prefix(folder).ok()).unwrap_or(Path::new(""));
+                Some(subfolder.join(&original).to_string_lossy().to_string())
+            },
+        };
+
+        entries.push(CatalogEntry {
+            id: file_sha256(path)?[..ID_LEN].to_string(),
+            path: path.clone(),
+            name,
+            size: metadata.len(),
+            modified: metadata.modified()
+                .map(|modified| DateTime::<Local>::from(modified).format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default(),
+            format,
+            key_id: key.map(EncryptionKey::fingerprint),
+        });
+    }
+    progress(files.len(), files.len());
+
+    Ok(Catalog {
+        folder: folder.to_path_buf(),
+        names,
+        created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        files: entries,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build_catalog() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path().join("collection");
+        fs::create_dir_all(folder.join("2023")).unwrap();
+        let key = EncryptionKey::generate();
+        let backend = BackendFactory::create_local();
+
+        // A file with its name stored behind an opaque name, one without, and a plain file
+        let source = dir.path().join("merger plan.pdf");
+        fs::write(&source, b"%PDF merger").unwrap();
+        backend.encrypt_file_storing_name(&source, &folder.join("a8f3.encrypted"), &key, OsStr::new("merger plan.pdf"), |_| {}).unwrap();
+        backend.encrypt_file(&source, &folder.join("2023").join("minutes.txt.encrypted"), &key, |_| {}).unwrap();
+        fs::write(folder.join("notes.txt"), b"not encrypted").unwrap();
+
+        let catalog = build_catalog(&folder, CatalogNames::Included, &[EncryptionKey::generate(), key.clone()], |_, _| {}).unwrap();
+        let names: Vec<String> = catalog.files.iter().map(|entry| entry.name.clone().unwrap()).collect();
+        let subfolder_name = Path::new("2023").join("minutes.txt").to_string_lossy().to_string();
+        assert_eq!(names, [subfolder_name, "merger plan.pdf".to_string()]);
+        assert!(catalog.files.iter().all(|entry| entry.key_id == Some(key.fingerprint()) && entry.format == "CRUSTy"));
+        let id = &catalog.files[1].id;
+        assert_eq!(id.len(), ID_LEN);
+        assert_eq!(catalog.find(&id[..6]).unwrap().path, folder.join("a8f3.encrypted"));
+
+        // Hashed and omitted names, and the CSV, give nothing of the names away
+        let catalog = build_catalog(&folder, CatalogNames::Hashed, &[], |_, _| {}).unwrap();
+        assert!(catalog.files.iter().all(|entry| entry.key_id.is_none()));
+        let catalog = build_catalog(&folder, CatalogNames::Omitted, &[key], |_, _| {}).unwrap();
+        let csv = catalog.to_csv();
+        assert!(csv.starts_with("id,size,modified,format,key_id\n"));
+        assert_eq!(csv.lines().count(), 3);
+        assert!(!csv.contains("minutes") && !csv.contains("collection"));
+
+        let json_path = dir.path().join("catalog.json");
+        catalog.save(&json_path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["files"].as_array().unwrap().len(), 2);
+        assert!(json["files"][0].get("name").is_none());
+    }
+}
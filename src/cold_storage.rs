@@ -0,0 +1,846 @@
+/// Cold-storage module.
+///
+/// This module provides functionality for:
+/// - Packing a folder into an encrypted archive split into volumes sized for the
+///   target media (CD, DVD, Blu-ray, or a custom size)
+/// - Appending parity to every volume, so damage to the media can be repaired
+/// - Writing a manifest with the SHA-256 of every volume and of the archive, to print
+///   and to keep with each volume
+/// - Writing a recovery-share sheet: the set's key split into shares, to print and
+///   give to custodians
+/// - Restoring a set: finding its volumes from the manifest, checking and repairing
+///   them, rebuilding the key from the shares, and extracting the folder
+///
+/// The set is written to a folder named after its label: one folder per volume (the
+/// contents of one disc), the manifest, and the recovery sheet. Everything is built
+/// under a temporary name and renamed into place once complete. The recovery sheet
+/// holds the key and belongs on paper, not on the media: delete it once printed.
+///
+/// Joined in order and with their parity removed, the volumes are a folder archive
+/// (see the archive module), so a set can be restored with any CRUSTy version that
+/// reads archives.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::archive;
+use crate::encryption::{self, EncryptionError, EncryptionKey, KdfParams};
+use crate::locale::{self, STORED_TIMESTAMP_FORMAT};
+use crate::parity;
+use crate::split_key::{self, KeyPurpose, SplitEncryptionKey};
+
+/// Name of the manifest, at the top of the set and in every volume
+pub const MANIFEST_NAME: &str = "MANIFEST.txt";
+
+/// Name of the recovery sheet, at the top of the set only
+pub const RECOVERY_SHEET_NAME: &str = "RECOVERY-SHEET.txt";
+
+/// Space left free on every volume for the file system and the manifest
+const VOLUME_RESERVE: u64 = 16 * 1024 * 1024;
+
+/// Media the volumes of a set are sized for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Media {
+    /// CD-R (700 MB)
+    Cd,
+    /// Single-layer DVD±R (4.7 GB)
+    Dvd,
+    /// Dual-layer DVD±R (8.5 GB)
+    DvdDualLayer,
+    /// Single-layer BD-R (25 GB)
+    BluRay,
+    /// Any other size, in MB (1 MB = 1024 × 1024 bytes)
+    Custom(u64),
+}
+
+impl Media {
+    /// Media offered in the wizard (a custom size can also be entered)
+    pub const PRESETS: [Media; 4] = [Media::Cd, Media::Dvd, Media::DvdDualLayer, Media::BluRay];
+
+    /// Get the capacity in bytes
+    pub fn capacity(&self) -> u64 {
+        match self {
+            Media::Cd => 700 * 1024 * 1024,
+            Media::Dvd => 4_700_000_000,
+            Media::DvdDualLayer => 8_500_000_000,
+            Media::BluRay => 25_000_000_000,
+            Media::Custom(megabytes) => megabytes.saturating_mul(1024 * 1024),
+        }
+    }
+
+    /// Get the name shown to the user
+    pub fn describe(&self) -> String {
+        match self {
+            Media::Cd => "CD (700 MB)".to_string(),
+            Media::Dvd => "DVD (4.7 GB)".to_string(),
+            Media::DvdDualLayer => "Dual-layer DVD (8.5 GB)".to_string(),
+            Media::BluRay => "Blu-ray (25 GB)".to_string(),
+            Media::Custom(megabytes) => format!("Custom ({} MB)", megabytes),
+        }
+    }
+}
+
+/// Options of a cold-storage set
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Name of the set (also the name of its folder and volume files)
+    pub label: String,
+    /// Media the volumes are sized for
+    pub media: Media,
+    /// Shares needed to recover the key
+    pub threshold: u8,
+    /// Shares printed on the recovery sheet
+    pub shares: u8,
+}
+
+/// A volume of a set, as listed in the manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeEntry {
+    /// Name of the volume file
+    pub file_name: String,
+    /// Length of the volume's part of the archive (without parity)
+    pub len: u64,
+    /// SHA-256 of the volume's part of the archive, in hex
+    pub sha256: String,
+}
+
+/// Contents of a set's manifest
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub label: String,
+    /// When the set was written (`STORED_TIMESTAMP_FORMAT`)
+    pub created_at: String,
+    /// Name of the folder archived
+    pub source_name: String,
+    /// Number of files archived
+    pub file_count: usize,
+    /// Fingerprint of the set's key
+    pub key_fingerprint: String,
+    /// Shares needed to recover the key, and shares printed
+    pub threshold: u8,
+    pub shares: u8,
+    /// Length and SHA-256 of the whole archive
+    pub archive_len: u64,
+    pub archive_sha256: String,
+    /// Capacity the volumes were sized for
+    pub media_capacity: u64,
+    pub volumes: Vec<VolumeEntry>,
+}
+
+impl Manifest {
+    /// Get the manifest as printable text
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str("CRUSTy Cold-Storage Manifest\n");
+        text.push_str("============================\n\n");
+        text.push_str(&format!("Label:            {}\n", self.label));
+        text.push_str(&format!("Created:          {}\n", self.created_at));
+        text.push_str(&format!("Source folder:    {}\n", self.source_name));
+        text.push_str(&format!("Files:            {}\n", self.file_count));
+        text.push_str(&format!("Key fingerprint:  {}\n", self.key_fingerprint));
+        text.push_str(&format!("Recovery shares:  {} of {}\n", self.threshold, self.shares));
+        text.push_str(&format!("Archive bytes:    {} ({})\n", self.archive_len, locale::format_size(self.archive_len)));
+        text.push_str(&format!("Archive SHA-256:  {}\n", self.archive_sha256));
+        text.push_str(&format!("Media bytes:      {} ({})\n", self.media_capacity, locale::format_size(self.media_capacity)));
+        text.push_str(&format!("Volumes:          {}\n\n", self.volumes.len()));
+
+        for (i, volume) in self.volumes.iter().enumerate() {
+            text.push_str(&format!("Volume {} of {}\n", i + 1, self.volumes.len()));
+            text.push_str(&format!("  File:    {}\n", volume.file_name));
+            text.push_str(&format!("  Bytes:   {}\n", volume.len));
+            text.push_str(&format!("  SHA-256: {}\n", volume.sha256));
+        }
+
+        text.push_str("\nTo restore\n----------\n");
+        text.push_str("1. Copy the volume files from every disc into one folder, with this manifest.\n");
+        text.push_str(&format!("2. Collect {} of the {} shares from the recovery sheet.\n", self.threshold, self.shares));
+        text.push_str("3. In CRUSTy, restore the cold-storage set from this manifest and enter the shares.\n\n");
+        text.push_str("Each volume file ends with Reed-Solomon parity (a trailer ending in CRUSTYRS) that\n");
+        text.push_str("repairs damage to the media. The SHA-256 values above are of the volumes without\n");
+        text.push_str("their parity. Joined in order, those are a CRUSTy folder archive (AES-256-GCM),\n");
+        text.push_str("encrypted with the key the recovery shares rebuild.\n");
+        text
+    }
+
+    /// Read a manifest from its text (as written by `to_text`)
+    ///
+    /// # Returns
+    /// * `Result<Manifest, EncryptionError>` - The manifest, or an error if a field is
+    ///   missing or damaged
+    pub fn from_text(text: &str) -> Result<Manifest, EncryptionError> {
+        let damaged = |field: &str| EncryptionError::Decryption(
+            format!("The manifest is damaged or not a cold-storage manifest ({} is missing or unreadable)", field)
+        );
+        if !text.starts_with("CRUSTy Cold-Storage Manifest") {
+            return Err(EncryptionError::Decryption("The file is not a cold-storage manifest".to_string()));
+        }
+
+        // "Name: value" fields, and the File, Bytes, and SHA-256 of each volume in order
+        let mut fields = Vec::new();
+        let mut volume_fields = Vec::new();
+        for line in text.lines() {
+            let Some((name, value)) = line.split_once(':') else { continue };
+            if line.starts_with("  ") {
+                volume_fields.push((name.trim(), value.trim()));
+            } else {
+                fields.push((name.trim(), value.trim()));
+            }
+        }
+        let field = |name: &str| fields.iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| damaged(name));
+        // Sizes are followed by the size for people, in brackets
+        let number = |name: &str| field(name)?
+            .split_whitespace()
+            .next()
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or_else(|| damaged(name));
+        let (threshold, shares) = field("Recovery shares")?
+            .split_once(" of ")
+            .and_then(|(threshold, shares)| Some((threshold.parse().ok()?, shares.parse().ok()?)))
+            .ok_or_else(|| damaged("Recovery shares"))?;
+
+        let mut volumes = Vec::new();
+        for entry in volume_fields.chunks(3) {
+            let volume = match entry {
+                [("File", file_name), ("Bytes", len), ("SHA-256", sha256)] => VolumeEntry {
+                    file_name: file_name.to_string(),
+                    len: len.parse().map_err(|_| damaged("a volume's size"))?,
+                    sha256: sha256.to_lowercase(),
+                },
+                _ => return Err(damaged("a volume")),
+            };
+            // Volumes are looked for by name, which must not lead elsewhere
+            if Path::new(&volume.file_name).file_name() != Some(volume.file_name.as_ref()) {
+                return Err(damaged("a volume's file name"));
+            }
+            volumes.push(volume);
+        }
+        if volumes.is_empty() || volumes.len() as u64 != number("Volumes")? {
+            return Err(damaged("Volumes"));
+        }
+
+        Ok(Manifest {
+            label: field("Label")?.to_string(),
+            created_at: field("Created")?.to_string(),
+            source_name: field("Source folder")?.to_string(),
+            file_count: number("Files")? as usize,
+            key_fingerprint: field("Key fingerprint")?.to_string(),
+            threshold,
+            shares,
+            archive_len: number("Archive bytes")?,
+            archive_sha256: field("Archive SHA-256")?.to_lowercase(),
+            media_capacity: number("Media bytes")?,
+            volumes,
+        })
+    }
+}
+
+/// What was written for a set
+#[derive(Debug, Clone)]
+pub struct ExportReport {
+    /// Folder of the set
+    pub set_dir: PathBuf,
+    /// The set's manifest
+    pub manifest: Manifest,
+    /// The recovery sheet, to print and delete
+    pub recovery_sheet: PathBuf,
+}
+
+/// State of a volume found for a restore
+#[derive(Debug, Clone, PartialEq)]
+pub enum VolumeStatus {
+    /// Not found in any of the folders searched
+    Missing,
+    /// Read without damage, and matching the manifest
+    Intact,
+    /// Damaged parts rebuilt from the parity, then matching the manifest
+    Repaired(usize),
+    /// Too damaged to repair, or not matching the manifest
+    Damaged(String),
+}
+
+impl VolumeStatus {
+    /// Check whether the volume can be used for the restore
+    pub fn is_usable(&self) -> bool {
+        matches!(self, VolumeStatus::Intact | VolumeStatus::Repaired(_))
+    }
+}
+
+impl fmt::Display for VolumeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VolumeStatus::Missing => f.write_str("Missing"),
+            VolumeStatus::Intact => f.write_str("Intact"),
+            VolumeStatus::Repaired(parts) => write!(f, "Repaired ({} damaged part(s) rebuilt)", parts),
+            VolumeStatus::Damaged(reason) => write!(f, "Damaged: {}", reason),
+        }
+    }
+}
+
+/// A volume of a set, as found for a restore
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeCheck {
+    /// Name of the volume file
+    pub file_name: String,
+    /// Where it was found
+    pub path: Option<PathBuf>,
+    pub status: VolumeStatus,
+}
+
+/// Result of checking the volumes of a set
+#[derive(Debug, Clone)]
+pub struct SetCheck {
+    /// Every volume of the manifest, in order
+    pub volumes: Vec<VolumeCheck>,
+    /// How the set's key was derived from a passphrase (None if it is rebuilt from shares)
+    pub kdf: Option<KdfParams>,
+}
+
+impl SetCheck {
+    /// Check whether every volume can be used for the restore
+    pub fn is_complete(&self) -> bool {
+        self.volumes.iter().all(|volume| volume.status.is_usable())
+    }
+}
+
+/// What a restore wrote, and what it found
+#[derive(Debug, Clone)]
+pub struct RestoreReport {
+    /// The restored folder
+    pub restored_dir: PathBuf,
+    /// The set's manifest
+    pub manifest: Manifest,
+    /// The volumes read, and the repairs made
+    pub volumes: Vec<VolumeCheck>,
+    /// Files written to the restored folder
+    pub files_restored: usize,
+}
+
+impl RestoreReport {
+    /// Check whether every file listed in the manifest was restored
+    pub fn is_complete(&self) -> bool {
+        self.files_restored == self.manifest.file_count
+    }
+
+    /// Get the report as printable text
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str("CRUSTy Cold-Storage Restore Report\n");
+        text.push_str("==================================\n\n");
+        text.push_str(&format!("Set:              {}\n", self.manifest.label));
+        text.push_str(&format!("Written:          {}\n", self.manifest.created_at));
+        text.push_str(&format!("Restored:         {}\n", Local::now().format(STORED_TIMESTAMP_FORMAT)));
+        text.push_str(&format!("Restored to:      {}\n", self.restored_dir.display()));
+        text.push_str(&format!("Key fingerprint:  {} (matches the manifest)\n", self.manifest.key_fingerprint));
+        text.push_str(&format!("Archive SHA-256:  {} (matches the manifest)\n", self.manifest.archive_sha256));
+        text.push_str(&format!("Files:            {} of {} restored\n\n", self.files_restored, self.manifest.file_count));
+
+        for (i, volume) in self.volumes.iter().enumerate() {
+            text.push_str(&format!("Volume {} of {}\n", i + 1, self.volumes.len()));
+            text.push_str(&format!("  File:    {}\n", volume.file_name));
+            if let Some(path) = &volume.path {
+                text.push_str(&format!("  Read:    {}\n", path.display()));
+            }
+            text.push_str(&format!("  State:   {}\n", volume.status));
+        }
+
+        if self.volumes.iter().any(|volume| matches!(volume.status, VolumeStatus::Repaired(_))) {
+            text.push_str("\nSome volumes were repaired: their media is decaying. Write the set to new media.\n");
+        }
+        if !self.is_complete() {
+            text.push_str("\nThe restored folder does not hold as many files as the manifest lists.\n");
+        }
+        text
+    }
+}
+
+/// Get the SHA-256 of data in hex
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Check a set's label, which names its folder and volume files
+fn check_label(label: &str) -> Result<&str, EncryptionError> {
+    let label = label.trim();
+    let valid = !label.is_empty()
+        && label.len() <= 64
+        && label.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_');
+    if !valid {
+        return Err(EncryptionError::Encryption(
+            "The label must be 1 to 64 letters, digits, spaces, dashes, or underscores".to_string()
+        ));
+    }
+    Ok(label)
+}
+
+/// Get the text of the recovery sheet
+fn recovery_sheet(manifest: &Manifest, split_key: &SplitEncryptionKey) -> Result<Zeroizing<String>, EncryptionError> {
+    let share_error = |e: split_key::SplitKeyError| EncryptionError::KeyError(e.to_string());
+    let separator = "-".repeat(72);
+
+    let mut text = Zeroizing::new(String::new());
+    text.push_str("CRUSTy Cold-Storage Recovery Sheet\n");
+    text.push_str("==================================\n\n");
+    text.push_str(&format!("Set:              {}\n", manifest.label));
+    text.push_str(&format!("Created:          {}\n", manifest.created_at));
+    text.push_str(&format!("Key fingerprint:  {}\n\n", manifest.key_fingerprint));
+    text.push_str(&format!(
+        "Any {} of these {} shares restore the set; fewer reveal nothing about its key.\n",
+        manifest.threshold, manifest.shares
+    ));
+    text.push_str("Print this sheet, cut it apart along the lines, and keep each share in a different\n");
+    text.push_str("place or with a different custodian, away from the media. Then delete this file.\n");
+
+    for index in 0..split_key.get_shares_count() as usize {
+        let share = split_key.get_share(index)
+            .ok_or_else(|| EncryptionError::KeyError(format!("Share {} is missing", index + 1)))?;
+        text.push_str(&format!("\n{}\n", separator));
+        text.push_str(&format!("Share {} of {} for cold-storage set \"{}\"\n", index + 1, manifest.shares, manifest.label));
+        text.push_str(&format!("Share fingerprint: {}\n\n", split_key::share_fingerprint(share)));
+        text.push_str(&format!("Text:\n{}\n\n", split_key.share_to_text(index).map_err(share_error)?));
+        text.push_str(&format!("Phrase:\n{}\n", split_key.share_to_mnemonic(index).map_err(share_error)?));
+    }
+    text.push_str(&format!("{}\n", separator));
+    Ok(text)
+}
+
+/// Write a cold-storage set for a folder
+///
+/// # Arguments
+/// * `folder` - The folder to archive
+/// * `dest_dir` - Where the set's folder is created
+/// * `key` - The key the archive is encrypted with (split into the recovery shares)
+/// * `options` - Label, media, and shares of the set
+/// * `progress_callback` - Called with the fraction done
+///
+/// # Returns
+/// * `Result<ExportReport, EncryptionError>` - What was written, or an error (nothing is
+///   left behind on an error)
+pub fn export_set(
+    folder: &Path,
+    dest_dir: &Path,
+    key: &EncryptionKey,
+    options: &ExportOptions,
+    progress_callback: impl Fn(f32),
+) -> Result<ExportReport, EncryptionError> {
+    let label = check_label(&options.label)?;
+    let volume_len = parity::max_data_len(options.media.capacity().saturating_sub(VOLUME_RESERVE));
+    if volume_len == 0 {
+        return Err(EncryptionError::Encryption(format!(
+            "{} is too small for a volume; use at least {} MB", options.media.describe(), VOLUME_RESERVE / (1024 * 1024) + 1
+        )));
+    }
+    let split_key = SplitEncryptionKey::new(key, options.threshold, options.shares, KeyPurpose::Standard)
+        .map_err(|e| EncryptionError::KeyError(e.to_string()))?;
+
+    encryption::check_output_dir(dest_dir)?;
+    let set_dir = dest_dir.join(label);
+    if set_dir.exists() {
+        return Err(EncryptionError::Io(
+            io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", set_dir.display()))
+        ));
+    }
+
+    // A temporary folder left by an interrupted run holds nothing worth keeping
+    let temp = encryption::temp_destination(&set_dir);
+    if temp.exists() {
+        fs::remove_dir_all(&temp)?;
+    }
+    fs::create_dir(&temp)?;
+
+    let result = write_set(folder, &temp, label, key, options, &split_key, volume_len, &progress_callback)
+        .and_then(|manifest| {
+            // Checked again, as rename replaces an empty folder on Unix
+            if set_dir.exists() {
+                return Err(EncryptionError::Io(
+                    io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", set_dir.display()))
+                ));
+            }
+            fs::rename(&temp, &set_dir)?;
+            Ok(manifest)
+        });
+    let manifest = match result {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&temp);
+            return Err(e);
+        },
+    };
+    progress_callback(1.0);
+
+    Ok(ExportReport {
+        recovery_sheet: set_dir.join(RECOVERY_SHEET_NAME),
+        set_dir,
+        manifest,
+    })
+}
+
+/// Write the volumes, manifest, and recovery sheet of a set into a folder
+#[allow(clippy::too_many_arguments)]
+fn write_set(
+    folder: &Path,
+    set_dir: &Path,
+    label: &str,
+    key: &EncryptionKey,
+    options: &ExportOptions,
+    split_key: &SplitEncryptionKey,
+    volume_len: u64,
+    progress_callback: &impl Fn(f32),
+) -> Result<Manifest, EncryptionError> {
+    // The archive is written inside the set's temporary folder and read back
+    let archive_path = set_dir.join(format!("{}.{}", label, archive::ARCHIVE_EXTENSION));
+    let file_count = archive::create_archive(folder, &archive_path, key, false, None, |fraction| progress_callback(0.6 * fraction))?;
+    let archive_data = fs::read(&archive_path)?;
+    fs::remove_file(&archive_path)?;
+
+    let parts: Vec<&[u8]> = archive_data.chunks(volume_len as usize).collect();
+    let mut volumes = Vec::with_capacity(parts.len());
+    for (i, part) in parts.iter().enumerate() {
+        let volume_dir = set_dir.join(format!("Volume {:02}", i + 1));
+        fs::create_dir(&volume_dir)?;
+
+        let file_name = format!("{}.vol{:02}", label, i + 1);
+        let mut data = part.to_vec();
+        parity::append_parity(&mut data);
+        encryption::write_destination(&volume_dir.join(&file_name), |file| io::Write::write_all(file, &data))?;
+
+        volumes.push(VolumeEntry { file_name, len: part.len() as u64, sha256: sha256_hex(part) });
+        progress_callback(0.6 + 0.35 * (i + 1) as f32 / parts.len() as f32);
+    }
+
+    let manifest = Manifest {
+        label: label.to_string(),
+        created_at: Local::now().format(STORED_TIMESTAMP_FORMAT).to_string(),
+        source_name: folder.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+        file_count,
+        key_fingerprint: key.fingerprint(),
+        threshold: options.threshold,
+        shares: options.shares,
+        archive_len: archive_data.len() as u64,
+        archive_sha256: sha256_hex(&archive_data),
+        media_capacity: options.media.capacity(),
+        volumes,
+    };
+
+    // The manifest goes on every disc, so each one identifies its set
+    let manifest_text = manifest.to_text();
+    fs::write(set_dir.join(MANIFEST_NAME), &manifest_text)?;
+    for i in 0..manifest.volumes.len() {
+        fs::write(set_dir.join(format!("Volume {:02}", i + 1)).join(MANIFEST_NAME), &manifest_text)?;
+    }
+    fs::write(set_dir.join(RECOVERY_SHEET_NAME), recovery_sheet(&manifest, split_key)?.as_bytes())?;
+
+    Ok(manifest)
+}
+
+/// Read a set's manifest
+pub fn read_manifest(path: &Path) -> Result<Manifest, EncryptionError> {
+    Manifest::from_text(&fs::read_to_string(path)?)
+}
+
+/// Find a volume file in folders, or in their subfolders (the folder of each disc, as
+/// when the set's folder was written)
+pub fn find_volume(folders: &[PathBuf], file_name: &str) -> Option<PathBuf> {
+    folders.iter().find_map(|folder| {
+        let path = folder.join(file_name);
+        if path.is_file() {
+            return Some(path);
+        }
+        let mut subfolders: Vec<PathBuf> = fs::read_dir(folder).ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        subfolders.sort();
+        subfolders.into_iter().map(|subfolder| subfolder.join(file_name)).find(|path| path.is_file())
+    })
+}
+
+/// Read a volume, repair it with its parity, and check it against the manifest
+///
+/// # Returns
+/// * `(VolumeCheck, Option<Vec<u8>>)` - What was found, and the volume's part of the
+///   archive if it can be used
+fn read_volume(folders: &[PathBuf], entry: &VolumeEntry) -> (VolumeCheck, Option<Vec<u8>>) {
+    let path = find_volume(folders, &entry.file_name);
+    let result = match &path {
+        None => Err(VolumeStatus::Missing),
+        Some(path) => fs::read(path)
+            .map_err(|e| VolumeStatus::Damaged(e.to_string()))
+            .and_then(|data| {
+                if !parity::has_parity(&data) {
+                    return Err(VolumeStatus::Damaged("the parity is missing; the file may be cut off".to_string()));
+                }
+                parity::remove_parity(data).map_err(|e| VolumeStatus::Damaged(e.to_string()))
+            })
+            .and_then(|(contents, repaired)| {
+                if contents.len() as u64 != entry.len || sha256_hex(&contents) != entry.sha256 {
+                    return Err(VolumeStatus::Damaged("it does not match the manifest".to_string()));
+                }
+                let status = if repaired > 0 { VolumeStatus::Repaired(repaired) } else { VolumeStatus::Intact };
+                Ok((contents, status))
+            }),
+    };
+
+    let (status, contents) = match result {
+        Ok((contents, status)) => (status, Some(contents)),
+        Err(status) => (status, None),
+    };
+    (VolumeCheck { file_name: entry.file_name.clone(), path, status }, contents)
+}
+
+/// Check every volume of a set: find it, repair it with its parity, and compare it with
+/// the manifest
+///
+/// # Arguments
+/// * `manifest` - The set's manifest
+/// * `folders` - Where to look for the volumes (and in their subfolders)
+/// * `progress_callback` - Called with the fraction done
+pub fn check_set(manifest: &Manifest, folders: &[PathBuf], progress_callback: impl Fn(f32)) -> SetCheck {
+    let mut volumes = Vec::with_capacity(manifest.volumes.len());
+    let mut kdf = None;
+    for (i, entry) in manifest.volumes.iter().enumerate() {
+        let (check, contents) = read_volume(folders, entry);
+        // The archive, and so its KDF header, starts in the first volume
+        if let (0, Some(contents)) = (i, &contents) {
+            kdf = encryption::split_kdf_header(contents).ok().and_then(|(params, _)| params);
+        }
+        volumes.push(check);
+        progress_callback((i + 1) as f32 / manifest.volumes.len() as f32);
+    }
+    SetCheck { volumes, kdf }
+}
+
+/// Rebuild a set's key from the shares of its recovery sheet
+///
+/// # Arguments
+/// * `manifest` - The set's manifest
+/// * `shares` - The shares entered (text, phrase, or dictation groups)
+///
+/// # Returns
+/// * `Result<EncryptionKey, EncryptionError>` - The key, or an error if the shares are
+///   unreadable, too few, or rebuild another key
+pub fn recover_key(manifest: &Manifest, shares: &[String]) -> Result<EncryptionKey, EncryptionError> {
+    let parsed = shares.iter()
+        .enumerate()
+        .map(|(i, share)| SplitEncryptionKey::parse_share(share)
+            .map_err(|e| EncryptionError::KeyError(format!("Share {}: {}", i + 1, e))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let split_key = SplitEncryptionKey::from_shares(parsed, manifest.threshold)
+        .map_err(|e| EncryptionError::KeyError(e.to_string()))?;
+    let key = split_key.get_key()
+        .cloned()
+        .ok_or_else(|| EncryptionError::KeyError("The shares did not rebuild a key".to_string()))?;
+    if key.fingerprint() != manifest.key_fingerprint {
+        return Err(EncryptionError::KeyError(format!(
+            "The shares rebuild key {}, but the set was encrypted with key {}; check that they are from this set's recovery sheet",
+            key.fingerprint(), manifest.key_fingerprint
+        )));
+    }
+    Ok(key)
+}
+
+/// Derive the key of a set encrypted with a passphrase key
+pub fn passphrase_key(manifest: &Manifest, passphrase: &str, params: &KdfParams) -> Result<EncryptionKey, EncryptionError> {
+    let key = EncryptionKey::from_passphrase(passphrase, params)?;
+    if key.fingerprint() != manifest.key_fingerprint {
+        return Err(EncryptionError::KeyError("The passphrase is not the set's passphrase".to_string()));
+    }
+    Ok(key)
+}
+
+/// Count the files in a folder and its subfolders
+fn count_files(folder: &Path) -> io::Result<usize> {
+    let mut count = 0;
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            count += count_files(&entry.path())?;
+        } else {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Restore a cold-storage set: join its volumes, repaired and checked against the
+/// manifest, and extract the folder
+///
+/// # Arguments
+/// * `manifest` - The set's manifest
+/// * `folders` - Where to look for the volumes (and in their subfolders)
+/// * `key` - The set's key
+/// * `dest_dir` - The folder to restore into (the set's folder is created in it)
+/// * `progress_callback` - Called with the fraction done
+///
+/// # Returns
+/// * `Result<RestoreReport, EncryptionError>` - What was restored, or an error (nothing
+///   is left behind on an error)
+pub fn restore_set(
+    manifest: &Manifest,
+    folders: &[PathBuf],
+    key: &EncryptionKey,
+    dest_dir: &Path,
+    progress_callback: impl Fn(f32),
+) -> Result<RestoreReport, EncryptionError> {
+    encryption::check_output_dir(dest_dir)?;
+
+    let mut volumes = Vec::with_capacity(manifest.volumes.len());
+    let mut archive_data = Zeroizing::new(Vec::with_capacity(manifest.archive_len as usize));
+    for (i, entry) in manifest.volumes.iter().enumerate() {
+        let (check, contents) = read_volume(folders, entry);
+        match contents {
+            Some(contents) => archive_data.extend(contents),
+            None => return Err(EncryptionError::Decryption(format!("Volume {} ({}): {}", i + 1, check.file_name, check.status))),
+        }
+        volumes.push(check);
+        progress_callback(0.5 * (i + 1) as f32 / manifest.volumes.len() as f32);
+    }
+    if sha256_hex(&archive_data) != manifest.archive_sha256 {
+        return Err(EncryptionError::Decryption("The joined volumes do not match the archive in the manifest".to_string()));
+    }
+
+    // The archive is extracted from a hidden file in the destination, removed afterwards
+    let archive_path = encryption::temp_destination(&dest_dir.join(format!("{}.{}", manifest.label, archive::ARCHIVE_EXTENSION)));
+    fs::write(&archive_path, archive_data.as_slice())?;
+    let result = archive::extract_archive(&archive_path, dest_dir, key, None, |fraction| progress_callback(0.5 + 0.5 * fraction));
+    let _ = fs::remove_file(&archive_path);
+    let restored_dir = result?;
+    let files_restored = count_files(&restored_dir)?;
+    progress_callback(1.0);
+
+    Ok(RestoreReport { restored_dir, manifest: manifest.clone(), volumes, files_restored })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_export_set() {
+        let dir = tempdir().unwrap();
+        let photos = dir.path().join("Photos");
+        fs::create_dir_all(photos.join("2020")).unwrap();
+        fs::write(photos.join("2020").join("beach.jpg"), vec![7u8; 3 * 1024 * 1024]).unwrap();
+        fs::write(photos.join("notes.txt"), b"Holiday notes").unwrap();
+        let output = dir.path().join("out");
+        fs::create_dir(&output).unwrap();
+
+        // 18 MB volumes hold 1.75 MB of the archive each after the reserve and parity
+        let options = ExportOptions { label: "Photos 2020".to_string(), media: Media::Custom(18), threshold: 2, shares: 3 };
+        let key = EncryptionKey::generate();
+        let report = export_set(&photos, &output, &key, &options, |_| {}).unwrap();
+
+        let manifest = &report.manifest;
+        assert_eq!(manifest.file_count, 2);
+        assert_eq!(manifest.volumes.len(), 2);
+        assert_eq!(manifest.key_fingerprint, key.fingerprint());
+        assert_eq!(manifest.volumes.iter().map(|volume| volume.len).sum::<u64>(), manifest.archive_len);
+
+        // Each volume fits its media, carries the manifest, and with its parity removed
+        // matches the manifest; joined, the volumes are the archive
+        let mut joined = Vec::new();
+        for (i, volume) in manifest.volumes.iter().enumerate() {
+            let volume_dir = report.set_dir.join(format!("Volume {:02}", i + 1));
+            let stored = fs::read(volume_dir.join(&volume.file_name)).unwrap();
+            assert!(stored.len() as u64 <= options.media.capacity() - VOLUME_RESERVE);
+            assert!(volume_dir.join(MANIFEST_NAME).exists());
+
+            let (contents, repaired) = parity::remove_parity(stored).unwrap();
+            assert_eq!(repaired, 0);
+            assert_eq!(sha256_hex(&contents), volume.sha256);
+            joined.extend(contents);
+        }
+        assert_eq!(sha256_hex(&joined), manifest.archive_sha256);
+
+        let archive_path = dir.path().join("joined.crusty");
+        fs::write(&archive_path, &joined).unwrap();
+        let restore_dir = dir.path().join("restored");
+        fs::create_dir(&restore_dir).unwrap();
+        let restored = archive::extract_archive(&archive_path, &restore_dir, &key, None, |_| {}).unwrap();
+        assert_eq!(fs::read(restored.join("notes.txt")).unwrap(), b"Holiday notes");
+
+        // The recovery sheet holds shares that rebuild the key
+        let sheet = fs::read_to_string(&report.recovery_sheet).unwrap();
+        let shares: Vec<String> = sheet.lines()
+            .skip_while(|line| *line != "Text:")
+            .nth(1)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(shares.len(), 1);
+        assert!(SplitEncryptionKey::parse_share(&shares[0]).is_ok());
+        assert_eq!(sheet.matches("Share fingerprint:").count(), 3);
+        assert!(fs::read_to_string(report.set_dir.join(MANIFEST_NAME)).unwrap().contains(&manifest.archive_sha256));
+
+        // An existing set is never replaced, and a bad label is refused
+        assert!(export_set(&photos, &output, &key, &options, |_| {}).is_err());
+        let bad_label = ExportOptions { label: "../elsewhere".to_string(), ..options };
+        assert!(export_set(&photos, &output, &key, &bad_label, |_| {}).is_err());
+        assert_eq!(fs::read_dir(&output).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_restore_set() {
+        let dir = tempdir().unwrap();
+        let photos = dir.path().join("Photos");
+        fs::create_dir_all(photos.join("2020")).unwrap();
+        fs::write(photos.join("2020").join("beach.jpg"), vec![7u8; 3 * 1024 * 1024]).unwrap();
+        fs::write(photos.join("notes.txt"), b"Holiday notes").unwrap();
+        let options = ExportOptions { label: "Photos 2020".to_string(), media: Media::Custom(18), threshold: 2, shares: 3 };
+        let report = export_set(&photos, dir.path(), &EncryptionKey::generate(), &options, |_| {}).unwrap();
+
+        let manifest = read_manifest(&report.set_dir.join(MANIFEST_NAME)).unwrap();
+        assert_eq!(manifest, report.manifest);
+        assert!(Manifest::from_text(&manifest.to_text().replace("Archive SHA-256", "Archive")).is_err());
+
+        // The shares on the sheet rebuild the key; one is too few
+        let sheet = fs::read_to_string(&report.recovery_sheet).unwrap();
+        let lines: Vec<&str> = sheet.lines().collect();
+        let shares: Vec<String> = lines.windows(2)
+            .filter(|pair| pair[0] == "Text:")
+            .map(|pair| pair[1].to_string())
+            .collect();
+        assert_eq!(shares.len(), 3);
+        let key = recover_key(&manifest, &shares[1..]).unwrap();
+        assert_eq!(key.fingerprint(), manifest.key_fingerprint);
+        assert!(recover_key(&manifest, &shares[..1]).is_err());
+
+        // Volumes are found in the disc folders; a damaged one is repaired, and one that
+        // is missing stops the restore
+        let volume_2 = report.set_dir.join("Volume 02").join(&manifest.volumes[1].file_name);
+        let mut stored = fs::read(&volume_2).unwrap();
+        stored[1000] ^= 0xFF;
+        fs::write(&volume_2, &stored).unwrap();
+        let folders = vec![report.set_dir.clone()];
+        let check = check_set(&manifest, &folders, |_| {});
+        assert!(check.is_complete());
+        assert!(check.kdf.is_none());
+        assert_eq!(check.volumes[0].status, VolumeStatus::Intact);
+        assert_eq!(check.volumes[1].status, VolumeStatus::Repaired(1));
+
+        let restore_dir = dir.path().join("restored");
+        fs::create_dir(&restore_dir).unwrap();
+        let moved = dir.path().join("volume.tmp");
+        fs::rename(&volume_2, &moved).unwrap();
+        assert_eq!(check_set(&manifest, &folders, |_| {}).volumes[1].status, VolumeStatus::Missing);
+        assert!(restore_set(&manifest, &folders, &key, &restore_dir, |_| {}).is_err());
+        assert_eq!(fs::read_dir(&restore_dir).unwrap().count(), 0);
+        fs::rename(&moved, &volume_2).unwrap();
+
+        let restored = restore_set(&manifest, &folders, &key, &restore_dir, |_| {}).unwrap();
+        assert!(restored.is_complete());
+        assert_eq!(restored.restored_dir, restore_dir.join("Photos"));
+        assert_eq!(fs::read(restored.restored_dir.join("notes.txt")).unwrap(), b"Holiday notes");
+        assert_eq!(fs::read(restored.restored_dir.join("2020").join("beach.jpg")).unwrap().len(), 3 * 1024 * 1024);
+        assert!(restored.to_text().contains("Repaired (1 damaged part(s) rebuilt)"));
+        assert_eq!(fs::read_dir(&restore_dir).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,579 @@
+/// Local control API module.
+///
+/// This module provides functionality for:
+/// - Serving a small HTTP API on the loopback interface, so other desktop tools and
+///   test automation can drive CRUSTy without clicking through the window
+/// - Queuing encrypt and decrypt jobs, and reporting their state and results
+///
+/// Every request must carry the token as `Authorization: Bearer <token>`. A new random
+/// token is made each time the API starts, and written with the port to
+/// `control_api.json` in the application data directory, where only programs run by the
+/// same user can read it (the file and its folder are private to the user). The API only queues jobs: the app runs them one at a time with its
+/// current key (see `ControlApiService`).
+///
+/// Endpoints (JSON in and out):
+/// - `GET /v1/status`: whether a key is selected, and the number of queued jobs
+/// - `POST /v1/jobs`: queue a job, `{"operation": "encrypt" | "decrypt", "files": [...],
+///   "output_dir": "...", "reason": "..."}` (the reason is for decryption, where the
+///   admin policy requires one)
+/// - `GET /v1/jobs`: every job since the API started
+/// - `GET /v1/jobs/<id>`: one job
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+
+use crate::policy::get_policy;
+
+/// Port the API listens on unless another is chosen
+pub const DEFAULT_PORT: u16 = 47048;
+
+/// Name of the file with the port and token, in the application data directory
+const INFO_FILE: &str = "control_api.json";
+
+/// How long a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request body accepted
+const MAX_BODY_LEN: usize = 1024 * 1024;
+
+/// Largest number of files in one job
+const MAX_JOB_FILES: usize = 10_000;
+
+/// Finished jobs kept for status requests (older ones are forgotten)
+const MAX_FINISHED_JOBS: usize = 200;
+
+/// What a job does with its files
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobOperation {
+    Encrypt,
+    Decrypt,
+}
+
+/// Where a job is
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// Waiting for the app to run it
+    Queued,
+    /// Being encrypted or decrypted
+    Running,
+    /// Every file was processed (see the results for each file)
+    Finished,
+    /// The job could not run, or stopped (see the error)
+    Failed,
+}
+
+/// Job queued through the API
+#[derive(Serialize, Clone, Debug)]
+pub struct Job {
+    pub id: u64,
+    pub operation: JobOperation,
+    pub files: Vec<PathBuf>,
+    pub output_dir: PathBuf,
+    /// Reason for decryption, written to the log (never reported back)
+    #[serde(skip)]
+    pub reason: Option<String>,
+    pub state: JobState,
+    /// Overall progress, from 0.0 to 1.0
+    pub progress: f32,
+    /// Result of each file, once finished
+    pub results: Vec<String>,
+    /// Why the job failed
+    pub error: Option<String>,
+}
+
+/// Job as sent by a client
+#[derive(Deserialize)]
+struct JobRequest {
+    operation: JobOperation,
+    files: Vec<PathBuf>,
+    output_dir: PathBuf,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// State of the app, as reported by `GET /v1/status`
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct AppStatus {
+    /// Whether a key is selected to run jobs with
+    pub key_selected: bool,
+    /// Whether the keys are locked (jobs fail until they are unlocked)
+    pub keys_locked: bool,
+}
+
+/// Where the API listens (control_api.json)
+#[derive(Serialize, Deserialize)]
+struct ApiInfo {
+    port: u16,
+    token: String,
+}
+
+/// Jobs and app state shared with the server thread
+#[derive(Default)]
+struct Board {
+    jobs: Vec<Job>,
+    next_id: u64,
+    status: AppStatus,
+}
+
+/// Running control API; stops when dropped
+pub struct ControlApi {
+    board: Arc<Mutex<Board>>,
+    stop: Arc<AtomicBool>,
+    info_path: PathBuf,
+    port: u16,
+    token: String,
+}
+
+/// Start serving the API on the loopback interface
+///
+/// # Arguments
+/// * `port` - Port to listen on (0 for any free port)
+/// * `dir` - Folder to write control_api.json to
+/// * `wake` - Called after a job is queued (to repaint the window)
+///
+/// # Returns
+/// * `io::Result<ControlApi>` - The running API, or an error if the port is taken
+pub fn start(port: u16, dir: &Path, wake: impl Fn() + Send + 'static) -> io::Result<ControlApi> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    listener.set_nonblocking(true)?;
+
+    let mut token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token);
+    let info = ApiInfo {
+        port: listener.local_addr()?.port(),
+        token: token.iter().map(|b| format!("{:02x}", b)).collect(),
+    };
+    let content = serde_json::to_string(&info)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    private_dir(dir)?;
+    let info_path = dir.join(INFO_FILE);
+    write_private(&info_path, content.as_bytes())?;
+
+    let board = Arc::new(Mutex::new(Board { next_id: 1, ..Board::default() }));
+    let stop = Arc::new(AtomicBool::new(false));
+    let server_board = board.clone();
+    let server_stop = stop.clone();
+    let server_token = info.token.clone();
+    thread::spawn(move || {
+        while !server_stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Ok(true) = serve(stream, &server_token, &server_board) {
+                        wake();
+                    }
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(50)),
+                Err(_) => thread::sleep(Duration::from_millis(50)),
+            }
+        }
+    });
+
+    Ok(ControlApi { board, stop, info_path, port: info.port, token: info.token })
+}
+
+impl ControlApi {
+    /// Get the port the API listens on
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Get the token clients must send
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Update the app state reported to clients
+    pub fn set_status(&self, status: AppStatus) {
+        self.board.lock().unwrap().status = status;
+    }
+
+    /// Take the oldest queued job, marking it as running
+    pub fn next_job(&self) -> Option<Job> {
+        let mut board = self.board.lock().unwrap();
+        let job = board.jobs.iter_mut().find(|job| job.state == JobState::Queued)?;
+        job.state = JobState::Running;
+        Some(job.clone())
+    }
+
+    /// Update the progress of a running job
+    pub fn set_progress(&self, id: u64, progress: f32) {
+        self.update(id, |job| job.progress = progress);
+    }
+
+    /// Record the results of a job that ran
+    pub fn finish_job(&self, id: u64, results: Vec<String>) {
+        self.update(id, |job| {
+            job.state = JobState::Finished;
+            job.progress = 1.0;
+            job.results = results;
+        });
+    }
+
+    /// Record why a job could not run
+    pub fn fail_job(&self, id: u64, error: &str) {
+        self.update(id, |job| {
+            job.state = JobState::Failed;
+            job.error = Some(error.to_string());
+        });
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut Job)) {
+        let mut board = self.board.lock().unwrap();
+        if let Some(job) = board.jobs.iter_mut().find(|job| job.id == id) {
+            change(job);
+        }
+    }
+}
+
+/// Create a folder (and its parents) only the user can open, and close an existing one
+/// to others
+fn private_dir(path: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)?;
+    #[cfg(unix)]
+    fs::set_permissions(path, std::os::unix::fs::PermissionsExt::from_mode(0o700))?;
+    Ok(())
+}
+
+/// Write a file only the user can read, replacing it atomically
+///
+/// The file is created private (rather than made private after it is written), so the
+/// token is never readable by others.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp = path.with_file_name(format!(".{}.{}-{:08x}.tmp", INFO_FILE, std::process::id(), rand::random::<u32>()));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let result = options.open(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+impl Drop for ControlApi {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = fs::remove_file(&self.info_path);
+    }
+}
+
+/// Answer one request
+///
+/// # Returns
+/// * `io::Result<bool>` - Whether a job was queued
+fn serve(stream: TcpStream, token: &str, board: &Mutex<Board>) -> io::Result<bool> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let (status, body, queued) = match read_request(&stream) {
+        Ok(request) if !request.authorized(token) => {
+            (401, json!({ "error": "Missing or wrong token" }), false)
+        },
+        Ok(request) => route(&request, board),
+        Err(e) => (400, json!({ "error": e.to_string() }), false),
+    };
+
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status, reason, body.len()
+    );
+    if status == 401 {
+        response.push_str("WWW-Authenticate: Bearer\r\n");
+    }
+    response.push_str("\r\n");
+    response.push_str(&body);
+    (&stream).write_all(response.as_bytes())?;
+    Ok(queued)
+}
+
+/// HTTP request, as far as the API needs it
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Check the bearer token, taking the same time wherever the tokens differ
+    fn authorized(&self, token: &str) -> bool {
+        let sent = match self.authorization.as_deref().and_then(|value| value.strip_prefix("Bearer ")) {
+            Some(sent) => sent.trim().as_bytes(),
+            None => return false,
+        };
+        sent.len() == token.len()
+            && sent.iter().zip(token.as_bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+/// Read the request line, the headers the API uses, and the body
+fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut reader = BufReader::new(stream.take(MAX_BODY_LEN as u64 + 64 * 1024));
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(invalid("Invalid request line")),
+    };
+
+    let mut authorization = None;
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Err(invalid("Incomplete request"));
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_string());
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| invalid("Invalid Content-Length"))?;
+            }
+        }
+    }
+
+    if content_length > MAX_BODY_LEN {
+        return Err(invalid("Request body too large"));
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request { method, path, authorization, body })
+}
+
+/// Answer an authorized request
+///
+/// # Returns
+/// * `(u16, serde_json::Value, bool)` - The status code, the body, and whether a job
+///   was queued
+fn route(request: &Request, board: &Mutex<Board>) -> (u16, serde_json::Value, bool) {
+    let path = request.path.split('?').next().unwrap_or_default().trim_end_matches('/');
+    let mut board = board.lock().unwrap();
+
+    match (request.method.as_str(), path) {
+        ("GET", "/v1/status") => {
+            let queued = board.jobs.iter().filter(|job| job.state == JobState::Queued).count();
+            let running = board.jobs.iter().find(|job| job.state == JobState::Running).map(|job| job.id);
+            (200, json!({
+                "key_selected": board.status.key_selected,
+                "keys_locked": board.status.keys_locked,
+                "queued_jobs": queued,
+                "running_job": running,
+            }), false)
+        },
+        ("GET", "/v1/jobs") => (200, json!({ "jobs": board.jobs }), false),
+        ("POST", "/v1/jobs") => match parse_job(&request.body) {
+            Ok(request) => {
+                let job = Job {
+                    id: board.next_id,
+                    operation: request.operation,
+                    files: request.files,
+                    output_dir: request.output_dir,
+                    reason: request.reason,
+                    state: JobState::Queued,
+                    progress: 0.0,
+                    results: Vec::new(),
+                    error: None,
+                };
+                board.next_id += 1;
+                forget_old_jobs(&mut board.jobs);
+                board.jobs.push(job.clone());
+                (202, json!(job), true)
+            },
+            Err(error) => (400, json!({ "error": error }), false),
+        },
+        ("GET", _) => {
+            let job = path.strip_prefix("/v1/jobs/")
+                .and_then(|id| id.parse::<u64>().ok())
+                .and_then(|id| board.jobs.iter().find(|job| job.id == id));
+            match job {
+                Some(job) => (200, json!(job), false),
+                None => (404, json!({ "error": "No such job" }), false),
+            }
+        },
+        (_, "/v1/status") | (_, "/v1/jobs") => (405, json!({ "error": "Method not allowed" }), false),
+        _ => (404, json!({ "error": "Not found" }), false),
+    }
+}
+
+/// Check a queued job before accepting it
+fn parse_job(body: &[u8]) -> Result<JobRequest, String> {
+    let request: JobRequest = serde_json::from_slice(body)
+        .map_err(|e| format!("Invalid job: {}", e))?;
+
+    if request.files.is_empty() {
+        return Err("The job has no files".to_string());
+    }
+    if request.files.len() > MAX_JOB_FILES {
+        return Err(format!("A job can have at most {} files", MAX_JOB_FILES));
+    }
+    // Relative paths would depend on CRUSTy's working directory
+    if let Some(file) = request.files.iter().find(|file| !file.is_absolute() || !file.is_file()) {
+        return Err(format!("{} is not an absolute path to a file", file.display()));
+    }
+    if !request.output_dir.is_absolute() || !request.output_dir.is_dir() {
+        return Err(format!("{} is not an absolute path to a folder", request.output_dir.display()));
+    }
+    if request.operation == JobOperation::Decrypt
+        && get_policy().require_decrypt_reason
+        && request.reason.as_deref().unwrap_or_default().trim().is_empty() {
+        return Err("The admin policy requires a reason for decryption (the \"reason\" field)".to_string());
+    }
+    Ok(request)
+}
+
+/// Forget the oldest finished jobs once there are too many
+fn forget_old_jobs(jobs: &mut Vec<Job>) {
+    let finished = jobs.iter()
+        .filter(|job| matches!(job.state, JobState::Finished | JobState::Failed))
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS - 1);
+    jobs.retain(|job| {
+        if excess > 0 && matches!(job.state, JobState::Finished | JobState::Failed) {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// Send a request and return the status code and the JSON body
+    fn send(port: u16, request: &str) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_queue_job_with_token() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("report.pdf");
+        fs::write(&file, b"report").unwrap();
+        let api = start(0, dir.path(), || {}).unwrap();
+
+        // The port and token are written for clients
+        let info: ApiInfo = serde_json::from_str(&fs::read_to_string(dir.path().join(INFO_FILE)).unwrap()).unwrap();
+        assert_eq!((info.port, info.token.as_str()), (api.port(), api.token()));
+
+        let (status, _) = send(api.port(), "GET /v1/status HTTP/1.1\r\nAuthorization: Bearer guess\r\n\r\n");
+        assert_eq!(status, 401);
+
+        let body = json!({
+            "operation": "encrypt",
+            "files": [file],
+            "output_dir": dir.path(),
+        }).to_string();
+        let (status, job) = send(api.port(), &format!(
+            "POST /v1/jobs HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+            api.token(), body.len(), body
+        ));
+        assert_eq!(status, 202);
+        assert_eq!(job["state"], "queued");
+
+        // The app takes the job and reports its results
+        let running = api.next_job().unwrap();
+        assert_eq!(running.files, vec![file]);
+        assert!(api.next_job().is_none());
+        api.finish_job(running.id, vec!["Successfully encrypted".to_string()]);
+
+        let (status, job) = send(api.port(), &format!(
+            "GET /v1/jobs/{} HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", running.id, api.token()
+        ));
+        assert_eq!(status, 200);
+        assert_eq!(job["state"], "finished");
+        assert_eq!(job["results"][0], "Successfully encrypted");
+
+        drop(api);
+        assert!(!dir.path().join(INFO_FILE).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_info_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let data_dir = dir.path().join("data");
+        fs::create_dir(&data_dir).unwrap();
+        fs::set_permissions(&data_dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let api = start(0, &data_dir, || {}).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&data_dir.join(INFO_FILE)), 0o600);
+        assert_eq!(mode(&data_dir), 0o700);
+        drop(api);
+
+        // A new folder is created private too
+        let new_dir = dir.path().join("new").join("data");
+        let _api = start(0, &new_dir, || {}).unwrap();
+        assert_eq!(mode(&new_dir.join(INFO_FILE)), 0o600);
+        assert_eq!(mode(&new_dir), 0o700);
+    }
+
+    #[test]
+    fn test_invalid_jobs_are_rejected() {
+        let dir = tempdir().unwrap();
+        let api = start(0, dir.path(), || {}).unwrap();
+
+        let body = json!({
+            "operation": "encrypt",
+            "files": ["relative/report.pdf"],
+            "output_dir": dir.path(),
+        }).to_string();
+        let (status, answer) = send(api.port(), &format!(
+            "POST /v1/jobs HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+            api.token(), body.len(), body
+        ));
+        assert_eq!(status, 400);
+        assert!(answer["error"].as_str().unwrap().contains("relative/report.pdf"));
+        assert!(api.next_job().is_none());
+    }
+}
@@ -0,0 +1,194 @@
+/// Completion cues for long-running operations.
+///
+/// This module provides functionality for:
+/// - Playing a sound when an operation completes
+/// - Showing operation progress on the taskbar button (Windows ITaskbarList3) or
+///   launcher icon (Unity LauncherEntry API, used by Ubuntu Dock, Plank, and KDE)
+///
+/// Cues are best effort: when the platform offers no way to show them, they are
+/// silently skipped.
+use std::path::Path;
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+
+/// Desktop entry the launcher progress is attached to
+#[cfg(all(unix, not(target_os = "macos")))]
+const DESKTOP_ENTRY: &str = "application://crusty.desktop";
+
+/// Play the completion sound in the background
+///
+/// # Arguments
+/// * `file` - Sound file to play, or None for the system notification sound
+pub fn play_completion_sound(file: Option<&Path>) {
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::um::playsoundapi::{PlaySoundW, SND_ASYNC, SND_FILENAME};
+        use winapi::um::winuser::{MessageBeep, MB_OK};
+
+        // SAFETY: the path is a NUL-terminated wide string that outlives the call
+        unsafe {
+            match file {
+                Some(file) => {
+                    let wide: Vec<u16> = file.as_os_str().encode_wide().chain(Some(0)).collect();
+                    PlaySoundW(wide.as_ptr(), std::ptr::null_mut(), SND_FILENAME | SND_ASYNC);
+                },
+                None => {
+                    MessageBeep(MB_OK);
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let file = file.unwrap_or_else(|| Path::new("/System/Library/Sounds/Glass.aiff"));
+        spawn_quietly(Command::new("afplay").arg(file));
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        match file {
+            Some(file) => spawn_quietly(Command::new("paplay").arg(file)),
+            None => spawn_quietly(Command::new("canberra-gtk-play").args(["-i", "complete"])),
+        }
+    }
+}
+
+/// Run a helper program without waiting for it or showing its output
+#[cfg(unix)]
+fn spawn_quietly(command: &mut Command) {
+    let _ = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+/// Progress indicator on the taskbar button or launcher icon
+pub struct TaskbarProgress {
+    /// Last progress shown, in percent (None when hidden)
+    shown: Option<u32>,
+    /// Taskbar interface of the application window
+    #[cfg(windows)]
+    taskbar: Option<windows_taskbar::Taskbar>,
+}
+
+impl TaskbarProgress {
+    /// Create a hidden progress indicator
+    pub fn new() -> Self {
+        TaskbarProgress {
+            shown: None,
+            #[cfg(windows)]
+            taskbar: None,
+        }
+    }
+
+    /// Show the progress (0.0 to 1.0), or hide the indicator with None
+    pub fn set(&mut self, progress: Option<f32>) {
+        let percent = progress.map(|p| (p.clamp(0.0, 1.0) * 100.0) as u32);
+        if percent == self.shown {
+            return;
+        }
+        self.shown = percent;
+
+        #[cfg(windows)]
+        {
+            if self.taskbar.is_none() {
+                self.taskbar = windows_taskbar::Taskbar::open("CRUSTy");
+            }
+            if let Some(taskbar) = &self.taskbar {
+                taskbar.set(percent);
+            }
+        }
+
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            let properties = match percent {
+                Some(percent) => format!("{{'progress': <{:.2}>, 'progress-visible': <true>}}", percent as f64 / 100.0),
+                None => "{'progress-visible': <false>}".to_string(),
+            };
+            spawn_quietly(Command::new("gdbus").args([
+                "emit", "--session",
+                "--object-path", "/com/canonical/unity/launcherentry/crusty",
+                "--signal", "com.canonical.Unity.LauncherEntry.Update",
+                DESKTOP_ENTRY, &properties,
+            ]));
+        }
+    }
+}
+
+#[cfg(windows)]
+mod windows_taskbar {
+    use std::ptr;
+    use winapi::Interface;
+    use winapi::shared::windef::HWND;
+    use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+    use winapi::um::combaseapi::{CoCreateInstance, CoInitializeEx};
+    use winapi::um::objbase::COINIT_APARTMENTTHREADED;
+    use winapi::um::shobjidl_core::{CLSID_TaskbarList, ITaskbarList3, TBPF_NOPROGRESS, TBPF_NORMAL};
+    use winapi::um::winuser::FindWindowW;
+
+    /// ITaskbarList3 bound to the application window
+    pub struct Taskbar {
+        list: *mut ITaskbarList3,
+        hwnd: HWND,
+    }
+
+    impl Taskbar {
+        /// Find the window with the given title and open its taskbar interface
+        pub fn open(title: &str) -> Option<Self> {
+            let title: Vec<u16> = title.encode_utf16().chain(Some(0)).collect();
+
+            // SAFETY: all pointers passed are valid for the duration of each call
+            unsafe {
+                let hwnd = FindWindowW(ptr::null(), title.as_ptr());
+                if hwnd.is_null() {
+                    return None;
+                }
+
+                // The GUI thread may already have initialized COM; either way it is usable
+                CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED);
+
+                let mut list: *mut ITaskbarList3 = ptr::null_mut();
+                let hr = CoCreateInstance(
+                    &CLSID_TaskbarList,
+                    ptr::null_mut(),
+                    CLSCTX_INPROC_SERVER,
+                    &ITaskbarList3::uuidof(),
+                    &mut list as *mut *mut ITaskbarList3 as *mut _,
+                );
+                if hr < 0 || list.is_null() || (*list).HrInit() < 0 {
+                    return None;
+                }
+
+                Some(Taskbar { list, hwnd })
+            }
+        }
+
+        /// Show the progress in percent, or hide it with None
+        pub fn set(&self, percent: Option<u32>) {
+            // SAFETY: `list` is a live COM interface owned by this struct
+            unsafe {
+                match percent {
+                    Some(percent) => {
+                        (*self.list).SetProgressState(self.hwnd, TBPF_NORMAL);
+                        (*self.list).SetProgressValue(self.hwnd, percent as u64, 100);
+                    },
+                    None => {
+                        (*self.list).SetProgressState(self.hwnd, TBPF_NOPROGRESS);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Drop for Taskbar {
+        fn drop(&mut self) {
+            // SAFETY: releases the reference obtained from CoCreateInstance
+            unsafe {
+                (*self.list).Release();
+            }
+        }
+    }
+}
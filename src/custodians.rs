@@ -0,0 +1,214 @@
+/// Share custodian directory.
+///
+/// This module provides functionality for:
+/// - Recording who holds each share of a split key (name, contact, storage location)
+/// - Keeping that directory in an encrypted registry file
+/// - Writing key ceremony reports that list each share with its custodian
+///
+/// The registry is encrypted with AES-256-GCM under a key kept in the OS credential
+/// store (protected according to the admin policy), since it tells an attacker
+/// exactly whom to approach for shares.
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use keyring::Entry;
+use serde::{Serialize, Deserialize};
+
+use crate::encryption::{self, EncryptionKey, EncryptionError};
+use crate::locale;
+use crate::policy::{get_policy, KeyStoreKind};
+use crate::shared_files;
+use crate::split_key::ShareRecord;
+use crate::tpm;
+
+/// Keyring entry holding the registry key
+const REGISTRY_SERVICE: &str = "CRUSTy";
+const REGISTRY_USER: &str = "custodian-registry";
+
+/// Person or device holding a share
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Custodian {
+    /// Fingerprint of the share held by the custodian
+    pub share_fingerprint: String,
+    /// Name of the person or device
+    pub name: String,
+    /// How to reach the custodian
+    pub contact: String,
+    /// Where the share is stored
+    pub location: String,
+}
+
+/// Encrypted directory of share custodians
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CustodianRegistry {
+    /// Custodians, one per share
+    pub custodians: Vec<Custodian>,
+}
+
+impl CustodianRegistry {
+    /// Default location of the registry file
+    pub fn default_path() -> PathBuf {
+        let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("crusty");
+        path.push("shares");
+        path.push("custodians.enc");
+        path
+    }
+
+    /// Load the registry from the specified file (empty if it does not exist)
+    pub fn load_from(path: &Path) -> Result<Self, EncryptionError> {
+        if !path.exists() {
+            return Ok(CustodianRegistry::default());
+        }
+
+        let encrypted = fs::read(path)?;
+        let content = encryption::decrypt_data(&encrypted, &registry_key()?)?;
+        serde_json::from_slice(&content)
+            .map_err(|e| EncryptionError::Decryption(format!("Invalid custodian registry: {}", e)))
+    }
+
+    /// Save the registry to the specified file
+    pub fn save_to(&self, path: &Path) -> Result<(), EncryptionError> {
+        let content = serde_json::to_vec(self)
+            .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize custodian registry: {}", e)))?;
+        let encrypted = encryption::encrypt_data(&content, &registry_key()?)?;
+
+        shared_files::write_atomic(path, encrypted)?;
+        Ok(())
+    }
+
+    /// Get the custodian of a share
+    pub fn for_share(&self, share_fingerprint: &str) -> Option<&Custodian> {
+        self.custodians.iter().find(|c| c.share_fingerprint == share_fingerprint)
+    }
+
+    /// Record the custodian of a share, replacing any earlier entry
+    pub fn set(&mut self, custodian: Custodian) {
+        self.custodians.retain(|c| c.share_fingerprint != custodian.share_fingerprint);
+        self.custodians.push(custodian);
+    }
+}
+
+/// Get the registry key from the OS credential store, creating it on first use
+fn registry_key() -> Result<EncryptionKey, EncryptionError> {
+    let entry = Entry::new(REGISTRY_SERVICE, REGISTRY_USER)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+    let protection = &get_policy().protection_for(KeyStoreKind::Custodians);
+
+    match entry.get_password() {
+        Ok(stored) => {
+            // Stored again if the policy now requires a different level
+            let bytes = tpm::unprotect_and_upgrade(REGISTRY_USER, &stored, protection, |stored| {
+                entry.set_password(stored)
+                    .map_err(|e| EncryptionError::KeyError(format!("Failed to store registry key: {}", e)))
+            })?;
+            let key: [u8; 32] = bytes.try_into()
+                .map_err(|_| EncryptionError::KeyError("Invalid registry key length".to_string()))?;
+            Ok(EncryptionKey { key, kdf: None })
+        },
+        Err(keyring::Error::NoEntry) => {
+            let key = EncryptionKey::generate();
+            store_registry_key(&entry, &key, protection)?;
+            Ok(key)
+        },
+        Err(e) => Err(EncryptionError::KeyError(format!("Failed to read registry key: {}", e))),
+    }
+}
+
+/// Store the registry key in the credential store at the given protection level
+fn store_registry_key(entry: &Entry, key: &EncryptionKey, protection: &tpm::ProtectionLevel) -> Result<(), EncryptionError> {
+    let stored = tpm::protect(REGISTRY_USER, &key.key, protection)?;
+    entry.set_password(&stored)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to store registry key: {}", e)))
+}
+
+/// Write a key ceremony report listing each share with its custodian
+///
+/// # Arguments
+/// * `records` - The shares of the split key
+/// * `registry` - The custodian directory
+/// * `writer` - Where the report is written
+pub fn write_ceremony_report<W: Write>(
+    records: &[ShareRecord],
+    registry: &CustodianRegistry,
+    writer: &mut W
+) -> io::Result<()> {
+    writeln!(writer, "CRUSTy Key Ceremony Report")?;
+    writeln!(writer, "Generated: {}", locale::format_datetime(&Local::now()))?;
+    writeln!(writer, "Shares: {}", records.len())?;
+
+    for record in records {
+        let custodian = registry.for_share(&record.fingerprint);
+
+        writeln!(writer)?;
+        writeln!(writer, "{}", record.label)?;
+        writeln!(writer, "  Fingerprint:   {}", record.fingerprint)?;
+        writeln!(writer, "  Created:       {}", locale::format_stored_timestamp(&record.created_at))?;
+        writeln!(writer, "  Last verified: {}", record.last_verified.as_deref().map_or("Never".to_string(), locale::format_stored_timestamp))?;
+        writeln!(writer, "  Custodian:     {}", custodian.map_or("Unassigned", |c| c.name.as_str()))?;
+        writeln!(writer, "  Contact:       {}", custodian.map_or("", |c| c.contact.as_str()))?;
+        writeln!(writer, "  Location:      {}", custodian.map_or("", |c| c.location.as_str()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custodian(share_fingerprint: &str, name: &str) -> Custodian {
+        Custodian {
+            share_fingerprint: share_fingerprint.to_string(),
+            name: name.to_string(),
+            contact: format!("{}@example.com", name.to_lowercase()),
+            location: "Office safe".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_set_custodian() {
+        let mut registry = CustodianRegistry::default();
+        registry.set(custodian("3f2a9c1d", "Alice"));
+        registry.set(custodian("8b7e6d5c", "Bob"));
+        assert_eq!(registry.for_share("3f2a9c1d").unwrap().name, "Alice");
+        assert!(registry.for_share("00000000").is_none());
+
+        // Recording a share's custodian again replaces the entry
+        registry.set(custodian("3f2a9c1d", "Carol"));
+        assert_eq!(registry.custodians.len(), 2);
+        assert_eq!(registry.for_share("3f2a9c1d").unwrap().name, "Carol");
+    }
+
+    #[test]
+    fn test_ceremony_report() {
+        let record = |index: usize, label: &str, fingerprint: &str, last_verified: Option<&str>| ShareRecord {
+            index,
+            label: label.to_string(),
+            fingerprint: fingerprint.to_string(),
+            created_at: "2024-05-01 09:30:00".to_string(),
+            last_verified: last_verified.map(str::to_string),
+        };
+        let records = vec![
+            record(1, "Secondary share (file)", "3f2a9c1d", Some("2024-06-01 10:00:00")),
+            record(2, "Recovery share", "8b7e6d5c", None),
+        ];
+        let mut registry = CustodianRegistry::default();
+        registry.set(custodian("3f2a9c1d", "Alice"));
+
+        let mut report = Vec::new();
+        write_ceremony_report(&records, &registry, &mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("CRUSTy Key Ceremony Report\n"));
+        assert!(report.contains("Shares: 2\n"));
+
+        let (first, second) = report.split_once("Recovery share").unwrap();
+        assert!(first.contains("  Custodian:     Alice\n  Contact:       alice@example.com\n  Location:      Office safe\n"));
+        assert!(!first.contains("Never"));
+        assert!(second.contains("  Fingerprint:   8b7e6d5c\n"));
+        assert!(second.contains("  Last verified: Never\n"));
+        assert!(second.contains("  Custodian:     Unassigned\n"));
+    }
+}
@@ -0,0 +1,433 @@
+/// Deployment bundles for zero-touch rollout of keys and policy.
+///
+/// This module provides functionality for:
+/// - Managing this machine's deployment identity (an X25519 key pair)
+/// - Creating sealed deployment bundles (keys + admin policy) for a target machine
+/// - Installing a bundle's policy (an administrator's step) and importing its keys on
+///   first run (the user's step), then unwrapping the keys at every startup
+///
+/// Bundle contents are encrypted with AES-256-GCM under a key derived (HKDF-SHA256)
+/// from an X25519 exchange between a one-time key and the target machine's identity,
+/// so only the machine the bundle was created for can open it.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::Local;
+use hkdf::Hkdf;
+use keyring::Entry;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::encryption::{EncryptionKey, EncryptionError};
+use crate::policy::{get_policy, AdminPolicy, KeyStoreKind};
+use crate::tpm;
+
+/// Current bundle format version
+const BUNDLE_VERSION: u8 = 1;
+
+/// Context string for deriving the bundle encryption key
+const BUNDLE_KDF_INFO: &[u8] = b"CRUSTy-Deployment-Bundle-v1";
+
+/// Keyring entry holding this machine's identity
+const IDENTITY_SERVICE: &str = "CRUSTy";
+const IDENTITY_USER: &str = "machine-identity";
+
+/// A key carried inside a deployment bundle
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BundledKey {
+    /// Display name of the key
+    pub name: String,
+    /// Base64 encoded key
+    pub key: String,
+}
+
+/// Decrypted contents of a deployment bundle
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BundleContents {
+    /// Policy installed on the target machine
+    pub policy: AdminPolicy,
+    /// Keys made available on the target machine
+    pub keys: Vec<BundledKey>,
+}
+
+/// A sealed deployment bundle as stored on disk
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DeploymentBundle {
+    /// Bundle format version
+    pub version: u8,
+    /// Random identifier for auditing which bundle a machine received
+    pub bundle_id: String,
+    /// When the bundle was created
+    pub created_at: String,
+    /// Identity of the machine the bundle is sealed to (Base64)
+    pub machine_identity: String,
+    /// One-time public key used for the key exchange (Base64)
+    pub ephemeral_public: String,
+    /// AES-GCM nonce (Base64)
+    pub nonce: String,
+    /// Encrypted BundleContents (Base64)
+    pub ciphertext: String,
+}
+
+/// Record written once a bundle's keys have been imported for this user
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InstallRecord {
+    /// Identifier of the imported bundle
+    pub bundle_id: String,
+    /// When the bundle was imported
+    pub imported_at: String,
+}
+
+/// Derive the AES key used to seal a bundle from an X25519 shared secret
+fn derive_bundle_key(shared_secret: &[u8], ephemeral_public: &[u8], machine_public: &[u8]) -> Result<Key<Aes256Gcm>, EncryptionError> {
+    // Bind the derived key to both public keys
+    let mut salt = Vec::with_capacity(64);
+    salt.extend_from_slice(ephemeral_public);
+    salt.extend_from_slice(machine_public);
+
+    let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared_secret);
+    let mut okm = [0u8; 32];
+    hkdf.expand(BUNDLE_KDF_INFO, &mut okm)
+        .map_err(|_| EncryptionError::KeyError("Key derivation failed".to_string()))?;
+
+    Ok(*Key::<Aes256Gcm>::from_slice(&okm))
+}
+
+/// Decode a Base64 encoded X25519 public key
+pub fn decode_identity(identity: &str) -> Result<PublicKey, EncryptionError> {
+    let bytes = STANDARD.decode(identity.trim().as_bytes())
+        .map_err(|e| EncryptionError::KeyError(format!("Invalid machine identity: {}", e)))?;
+
+    let bytes: [u8; 32] = bytes.try_into()
+        .map_err(|_| EncryptionError::KeyError("Invalid machine identity length".to_string()))?;
+
+    Ok(PublicKey::from(bytes))
+}
+
+impl DeploymentBundle {
+    /// Seal bundle contents to the target machine's identity
+    ///
+    /// # Arguments
+    /// * `machine_identity` - Public identity of the target machine
+    /// * `contents` - Policy and keys to deploy
+    pub fn create(machine_identity: &PublicKey, contents: &BundleContents) -> Result<Self, EncryptionError> {
+        let ephemeral_secret = StaticSecret::random_from_rng(OsRng);
+        let ephemeral_public = PublicKey::from(&ephemeral_secret);
+        let shared_secret = ephemeral_secret.diffie_hellman(machine_identity);
+
+        let key = derive_bundle_key(shared_secret.as_bytes(), ephemeral_public.as_bytes(), machine_identity.as_bytes())?;
+        let cipher = Aes256Gcm::new(&key);
+
+        let mut nonce_bytes = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce_bytes);
+
+        let plaintext = serde_json::to_vec(contents)
+            .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize bundle: {}", e)))?;
+
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_slice())
+            .map_err(|e| EncryptionError::Encryption(format!("Encryption failed: {}", e)))?;
+
+        let mut bundle_id = [0u8; 16];
+        OsRng.fill_bytes(&mut bundle_id);
+
+        Ok(DeploymentBundle {
+            version: BUNDLE_VERSION,
+            bundle_id: bundle_id.iter().map(|b| format!("{:02x}", b)).collect(),
+            created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            machine_identity: STANDARD.encode(machine_identity.as_bytes()),
+            ephemeral_public: STANDARD.encode(ephemeral_public.as_bytes()),
+            nonce: STANDARD.encode(nonce_bytes),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Open the bundle with this machine's identity secret
+    pub fn open(&self, machine_secret: &StaticSecret) -> Result<BundleContents, EncryptionError> {
+        if self.version != BUNDLE_VERSION {
+            return Err(EncryptionError::Decryption(format!("Unsupported bundle version: {}", self.version)));
+        }
+
+        let machine_public = PublicKey::from(machine_secret);
+        if STANDARD.encode(machine_public.as_bytes()) != self.machine_identity {
+            return Err(EncryptionError::KeyError("Bundle was created for a different machine".to_string()));
+        }
+
+        let ephemeral_public = decode_identity(&self.ephemeral_public)?;
+        let shared_secret = machine_secret.diffie_hellman(&ephemeral_public);
+        let key = derive_bundle_key(shared_secret.as_bytes(), ephemeral_public.as_bytes(), machine_public.as_bytes())?;
+        let cipher = Aes256Gcm::new(&key);
+
+        let nonce = STANDARD.decode(&self.nonce)
+            .map_err(|e| EncryptionError::Decryption(format!("Invalid bundle nonce: {}", e)))?;
+        if nonce.len() != 12 {
+            return Err(EncryptionError::Decryption("Invalid bundle nonce length".to_string()));
+        }
+        let ciphertext = STANDARD.decode(&self.ciphertext)
+            .map_err(|e| EncryptionError::Decryption(format!("Invalid bundle data: {}", e)))?;
+
+        let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|e| EncryptionError::Decryption(format!("Authentication failed: {}", e)))?;
+
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| EncryptionError::Decryption(format!("Invalid bundle contents: {}", e)))
+    }
+
+    /// Load a bundle from a file
+    pub fn load(path: &Path) -> Result<Self, EncryptionError> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| EncryptionError::Decryption(format!("Invalid bundle file: {}", e)))
+    }
+
+    /// Save the bundle to a file
+    pub fn save(&self, path: &Path) -> Result<(), EncryptionError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize bundle: {}", e)))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+impl BundleContents {
+    /// Decode the bundled keys
+    pub fn decode_keys(&self) -> Result<Vec<(String, EncryptionKey)>, EncryptionError> {
+        self.keys.iter()
+            .map(|k| EncryptionKey::from_base64(&k.key).map(|key| (k.name.clone(), key)))
+            .collect()
+    }
+}
+
+/// Protection level the admin policy requires for the machine identity
+///
+/// A policy file that cannot be read applies its restricted defaults here too.
+fn identity_protection() -> tpm::ProtectionLevel {
+    get_policy().protection_for(KeyStoreKind::MachineIdentity)
+}
+
+/// Store the machine identity with the required protection level
+fn store_identity(entry: &Entry, secret: &StaticSecret, protection: &tpm::ProtectionLevel) -> Result<(), EncryptionError> {
+    let stored = tpm::protect(IDENTITY_USER, &secret.to_bytes(), protection)?;
+    entry.set_password(&stored)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to store machine identity: {}", e)))
+}
+
+/// Load this machine's identity secret, creating it on first use
+pub fn machine_identity_secret() -> Result<StaticSecret, EncryptionError> {
+    let entry = Entry::new(IDENTITY_SERVICE, IDENTITY_USER)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+    let protection = identity_protection();
+
+    match entry.get_password() {
+        Ok(stored) => {
+            // Stored again if the policy now requires a different level
+            let bytes = tpm::unprotect_and_upgrade(IDENTITY_USER, &stored, &protection, |stored| {
+                entry.set_password(stored)
+                    .map_err(|e| EncryptionError::KeyError(format!("Failed to store machine identity: {}", e)))
+            })?;
+            let bytes: [u8; 32] = bytes.try_into()
+                .map_err(|_| EncryptionError::KeyError("Invalid machine identity length".to_string()))?;
+            Ok(StaticSecret::from(bytes))
+        },
+        Err(keyring::Error::NoEntry) => {
+            let secret = StaticSecret::random_from_rng(OsRng);
+            store_identity(&entry, &secret, &protection)?;
+            Ok(secret)
+        },
+        Err(e) => Err(EncryptionError::KeyError(format!("Failed to read machine identity: {}", e))),
+    }
+}
+
+/// Get this machine's public identity (Base64), shared with administrators
+pub fn machine_identity() -> Result<String, EncryptionError> {
+    let secret = machine_identity_secret()?;
+    Ok(STANDARD.encode(PublicKey::from(&secret).as_bytes()))
+}
+
+/// Directory holding the installed bundle and install record
+fn deployment_dir() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("crusty");
+    path
+}
+
+/// Path where administrators place the bundle for this machine
+pub fn bundle_path() -> PathBuf {
+    deployment_dir().join("deployment.bundle")
+}
+
+/// Path of the record written after a successful import
+fn install_record_path() -> PathBuf {
+    deployment_dir().join("deployment.json")
+}
+
+/// Install the policy of a deployment bundle as the admin policy file
+///
+/// The administrator's step of an import (`crusty --install-deployment-policy <bundle>`).
+/// The policy folder can only be written by administrators, so users cannot loosen it;
+/// this runs elevated, from the user account the bundle is sealed to, whose credential
+/// store holds the machine identity. The keys are imported by `import_on_first_run`
+/// when that user next starts CRUSTy.
+///
+/// # Returns
+/// * `Result<PathBuf, EncryptionError>` - Where the policy was installed, or an error if
+///   the bundle could not be opened or the policy could not be written
+pub fn install_bundle_policy(bundle_path: &Path) -> Result<PathBuf, EncryptionError> {
+    let bundle = DeploymentBundle::load(bundle_path)?;
+    let contents = bundle.open(&machine_identity_secret()?)?;
+
+    // Validate the keys before installing anything
+    contents.decode_keys()?;
+
+    let policy_path = AdminPolicy::default_path();
+    install_policy_to(&contents.policy, &policy_path)?;
+    Ok(policy_path)
+}
+
+/// Write a bundle's policy as the policy file
+fn install_policy_to(policy: &AdminPolicy, policy_path: &Path) -> Result<(), EncryptionError> {
+    let policy_json = serde_json::to_string_pretty(policy)
+        .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize policy: {}", e)))?;
+    policy_path.parent().map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| fs::write(policy_path, policy_json))
+        .map_err(|e| EncryptionError::Encryption(format!(
+            "Failed to install the policy as {} (install it as an administrator): {}",
+            policy_path.display(), e
+        )))
+}
+
+/// Import the keys of the deployment bundle if one is present and has not been
+/// imported yet
+///
+/// The user's step of an import, which needs no administrator rights. The keys are
+/// only imported once the administrator has installed the bundle's policy (see
+/// `install_bundle_policy`), so they are never used without the restrictions that
+/// came with them.
+///
+/// # Returns
+/// * `Result<Option<InstallRecord>, EncryptionError>` - The new install record, None if
+///   there was nothing to import, or an error if the bundle could not be opened or its
+///   policy is not installed
+pub fn import_on_first_run() -> Result<Option<InstallRecord>, EncryptionError> {
+    let path = bundle_path();
+    if !path.exists() || install_record_path().exists() {
+        return Ok(None);
+    }
+
+    let bundle = DeploymentBundle::load(&path)?;
+    let contents = bundle.open(&machine_identity_secret()?)?;
+    import_keys(&bundle, &contents, &AdminPolicy::default_path(), &install_record_path()).map(Some)
+}
+
+/// Record the import of a bundle's keys, once its policy is the installed policy
+fn import_keys(
+    bundle: &DeploymentBundle,
+    contents: &BundleContents,
+    policy_path: &Path,
+    record_path: &Path,
+) -> Result<InstallRecord, EncryptionError> {
+    contents.decode_keys()?;
+
+    let installed = AdminPolicy::load_from(policy_path)
+        .ok()
+        .and_then(|installed| serde_json::to_value(installed).ok());
+    if installed.is_none() || installed != serde_json::to_value(&contents.policy).ok() {
+        return Err(EncryptionError::KeyError(format!(
+            "The bundle's policy is not installed at {}; run \"crusty --install-deployment-policy {}\" as an administrator first",
+            policy_path.display(), bundle_path().display()
+        )));
+    }
+
+    let record = InstallRecord {
+        bundle_id: bundle.bundle_id.clone(),
+        imported_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    let record_json = serde_json::to_string_pretty(&record)
+        .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize install record: {}", e)))?;
+    fs::write(record_path, record_json)?;
+
+    Ok(record)
+}
+
+/// Unwrap the keys of the installed bundle (if any)
+pub fn load_deployed_keys() -> Result<Vec<(String, EncryptionKey)>, EncryptionError> {
+    let path = bundle_path();
+    if !path.exists() || !install_record_path().exists() {
+        return Ok(Vec::new());
+    }
+
+    let bundle = DeploymentBundle::load(&path)?;
+    bundle.open(&machine_identity_secret()?)?.decode_keys()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_contents() -> BundleContents {
+        BundleContents {
+            policy: AdminPolicy { restricted_mode: true, ..Default::default() },
+            keys: vec![BundledKey { name: "Fleet Key".to_string(), key: EncryptionKey::generate().to_base64() }],
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let machine_secret = StaticSecret::random_from_rng(OsRng);
+        let contents = test_contents();
+
+        let bundle = DeploymentBundle::create(&PublicKey::from(&machine_secret), &contents).unwrap();
+        let opened = bundle.open(&machine_secret).unwrap();
+
+        assert!(opened.policy.restricted_mode);
+        assert_eq!(opened.keys[0].key, contents.keys[0].key);
+    }
+
+    #[test]
+    fn test_bundle_wrong_machine() {
+        let machine_secret = StaticSecret::random_from_rng(OsRng);
+        let other_secret = StaticSecret::random_from_rng(OsRng);
+
+        let bundle = DeploymentBundle::create(&PublicKey::from(&machine_secret), &test_contents()).unwrap();
+        assert!(matches!(bundle.open(&other_secret), Err(EncryptionError::KeyError(_))));
+    }
+
+    #[test]
+    fn test_bundle_tampered() {
+        let machine_secret = StaticSecret::random_from_rng(OsRng);
+        let mut bundle = DeploymentBundle::create(&PublicKey::from(&machine_secret), &test_contents()).unwrap();
+
+        let mut ciphertext = STANDARD.decode(&bundle.ciphertext).unwrap();
+        ciphertext[0] ^= 0xFF;
+        bundle.ciphertext = STANDARD.encode(ciphertext);
+
+        assert!(matches!(bundle.open(&machine_secret), Err(EncryptionError::Decryption(_))));
+    }
+
+    #[test]
+    fn test_keys_wait_for_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy_path = dir.path().join("policy").join("policy.json");
+        let record_path = dir.path().join("deployment.json");
+        let machine_secret = StaticSecret::random_from_rng(OsRng);
+        let bundle = DeploymentBundle::create(&PublicKey::from(&machine_secret), &test_contents()).unwrap();
+        let contents = bundle.open(&machine_secret).unwrap();
+
+        // The user's step does not install the policy, and waits for the administrator's
+        assert!(import_keys(&bundle, &contents, &policy_path, &record_path).is_err());
+        assert!(!policy_path.exists());
+        assert!(!record_path.exists());
+
+        install_policy_to(&contents.policy, &policy_path).unwrap();
+        assert!(AdminPolicy::load_from(&policy_path).unwrap().restricted_mode);
+        let record = import_keys(&bundle, &contents, &policy_path, &record_path).unwrap();
+        assert_eq!(record.bundle_id, bundle.bundle_id);
+        assert!(record_path.exists());
+    }
+}
@@ -0,0 +1,135 @@
+/// Duplicate files module.
+///
+/// This module provides functionality for:
+/// - Finding the files of a batch with the same contents, so users notice they are
+///   encrypting and sending the same document several times
+/// - Describing the groups of duplicates for the log, with the paths as the log writes them
+///
+/// Files are compared by size first, and only files of the same size are hashed
+/// (SHA-256), reporting the fraction of their bytes hashed as the scan goes. The hashes are only held in memory while the groups are found; the report
+/// names the files, never their hashes, so nothing about the plaintexts is written.
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Size of the blocks files are hashed in, between progress reports
+const HASH_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Get the SHA-256 of a file
+///
+/// `hashed` is called with the number of bytes hashed after each block.
+fn file_sha256(path: &Path, mut hashed: impl FnMut(u64)) -> io::Result<[u8; 32]> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut block = vec![0u8; HASH_BLOCK_SIZE];
+    loop {
+        let read = file.read(&mut block)?;
+        if read == 0 {
+            return Ok(hasher.finalize().into());
+        }
+        hasher.update(&block[..read]);
+        hashed(read as u64);
+    }
+}
+
+/// Find the files with the same contents
+///
+/// Files that cannot be read are left out (encrypting them reports the error).
+///
+/// # Arguments
+/// * `files` - The files to compare
+/// * `progress_callback` - Called with the fraction of the files to hash hashed, after
+///   each block (the caller may wait in it, e.g. while the operation is paused)
+///
+/// # Returns
+/// * `Vec<Vec<PathBuf>>` - Groups of two or more files with the same contents, each in
+///   the order of `files`, ordered by their first file
+pub fn find_duplicates(files: &[PathBuf], progress_callback: impl Fn(f32)) -> Vec<Vec<PathBuf>> {
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        if let Ok(metadata) = fs::metadata(file) {
+            by_size.entry(metadata.len()).or_default().push(i);
+        }
+    }
+
+    let candidates: Vec<(u64, Vec<usize>)> = by_size.into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .collect();
+    let total_size: u64 = candidates.iter().map(|(size, indices)| size * indices.len() as u64).sum();
+    let mut hashed_size = 0;
+    progress_callback(0.0);
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (_, same_size) in candidates {
+        let mut by_hash: HashMap<[u8; 32], Vec<usize>> = HashMap::new();
+        for i in same_size {
+            let hash = file_sha256(&files[i], |read| {
+                hashed_size += read;
+                progress_callback((hashed_size as f32 / total_size as f32).min(1.0));
+            });
+            if let Ok(hash) = hash {
+                by_hash.entry(hash).or_default().push(i);
+            }
+        }
+        groups.extend(by_hash.into_values().filter(|indices| indices.len() > 1));
+    }
+    progress_callback(1.0);
+
+    groups.sort();
+    groups.into_iter()
+        .map(|indices| indices.into_iter().map(|i| files[i].clone()).collect())
+        .collect()
+}
+
+/// Describe groups of duplicates: files of a group are separated by " = ", and groups by "; "
+///
+/// # Arguments
+/// * `groups` - Groups of duplicates, as found by `find_duplicates`
+/// * `name` - Writes a file's path (e.g. redacted for the log)
+pub fn describe(groups: &[Vec<PathBuf>], name: impl Fn(&Path) -> String) -> String {
+    groups.iter()
+        .map(|group| group.iter().map(|file| name(file)).collect::<Vec<_>>().join(" = "))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_duplicates() {
+        let dir = tempdir().unwrap();
+        let write = |name: &str, contents: &[u8]| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            path
+        };
+        let contract = write("contract.pdf", b"%PDF contract");
+        let contract_copy = write("contract (1).pdf", b"%PDF contract");
+        let same_size = write("invoice.pdf", b"%PDF invoice!");
+        let photo = write("photo.jpg", b"JPEG");
+        let photo_copy = write("IMG_0001.jpg", b"JPEG");
+        let contract_again = write("contract final.pdf", b"%PDF contract");
+        let missing = dir.path().join("missing.txt");
+
+        let files = vec![contract.clone(), photo.clone(), contract_copy.clone(), same_size, missing, photo_copy.clone(), contract_again.clone()];
+        let reported = std::cell::RefCell::new(Vec::new());
+        let groups = find_duplicates(&files, |fraction| reported.borrow_mut().push(fraction));
+        assert_eq!(groups, vec![vec![contract.clone(), contract_copy.clone(), contract_again.clone()], vec![photo.clone(), photo_copy.clone()]]);
+        assert_eq!(describe(&groups[1..], |file| file.display().to_string()), format!("{} = {}", photo.display(), photo_copy.display()));
+        let file_names = describe(&groups, |file| file.file_name().unwrap().to_string_lossy().into_owned());
+        assert_eq!(file_names, "contract.pdf = contract (1).pdf = contract final.pdf; photo.jpg = IMG_0001.jpg");
+
+        // Only the files of the same size are hashed, from none to all of their bytes
+        let reported = reported.into_inner();
+        assert_eq!(reported.first(), Some(&0.0));
+        assert_eq!(reported.last(), Some(&1.0));
+        assert!(reported.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        assert!(find_duplicates(&[contract, photo], |_| {}).is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+/// Folder watch module.
+///
+/// This module provides functionality for:
+/// - Watching folders for new files in the background
+/// - Encrypting each new file, once it has stopped changing, into its folder's output
+///   folder with the folder's key (the original is left in place)
+/// - Logging each file encrypted or failed, and reporting it for the status panel
+///
+/// Only files created in or moved into a folder while it is watched are encrypted.
+/// Files already there, subfolders, hidden files, encrypted files, and partial
+/// downloads are left alone.
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::encryption::{self, EncryptionKey};
+use crate::file_names;
+use crate::logger;
+use crate::settings::WatchedFolder;
+
+/// Time a new file must stay unchanged before it is encrypted, as it may still be
+/// being written
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// How often new files are looked at for changes
+const CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Extensions of files still being downloaded or written
+const PARTIAL_EXTENSIONS: [&str; 4] = ["part", "partial", "crdownload", "tmp"];
+
+/// What happened in a watched folder
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// A new file was encrypted
+    Encrypted { file: PathBuf, output: PathBuf },
+    /// A new file could not be encrypted, or a folder could not be watched
+    Failed { path: PathBuf, error: String },
+}
+
+/// A watched folder with its key
+struct WatchRule {
+    folder: PathBuf,
+    output_dir: PathBuf,
+    key: EncryptionKey,
+}
+
+/// Folders watched for new files in the background
+pub struct FolderWatch {
+    /// The folders as configured when the watch started
+    folders: Vec<WatchedFolder>,
+    receiver: Receiver<WatchEvent>,
+    /// Watches the folders until it is dropped, which also stops the background thread
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl FolderWatch {
+    /// Start watching folders in the background
+    ///
+    /// Folders that cannot be watched, or whose key is not available, are reported as
+    /// failed; the others are still watched.
+    ///
+    /// # Arguments
+    /// * `folders` - Each folder, with its key (None if the key is not available)
+    /// * `wake` - Called after an event (to repaint the window)
+    pub fn start(folders: Vec<(WatchedFolder, Option<EncryptionKey>)>, wake: impl Fn() + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (changes, changed) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(changes);
+
+        let mut rules = Vec::new();
+        let mut failures = Vec::new();
+        for (folder, key) in &folders {
+            let watched = match (key, &mut watcher) {
+                (None, _) => Err(format!(
+                    "Not watched: the key {} ({}) is not among the saved keys",
+                    folder.key_name, folder.fingerprint
+                )),
+                (Some(_), Err(e)) => Err(format!("Not watched: {}", e)),
+                (Some(key), Ok(watcher)) => watcher.watch(&folder.folder, RecursiveMode::NonRecursive)
+                    .map(|()| key.clone())
+                    .map_err(|e| format!("Not watched: {}", e)),
+            };
+            match watched {
+                Ok(key) => rules.push(WatchRule {
+                    folder: folder.folder.clone(),
+                    output_dir: folder.output_dir.clone(),
+                    key,
+                }),
+                Err(error) => failures.push(WatchEvent::Failed { path: folder.folder.clone(), error }),
+            }
+        }
+
+        thread::spawn(move || {
+            let report = |event: WatchEvent| {
+                let sent = sender.send(event).is_ok();
+                wake();
+                sent
+            };
+            for failure in failures {
+                log_event(&failure, None);
+                if !report(failure) {
+                    return;
+                }
+            }
+
+            let mut pending = HashMap::new();
+            loop {
+                match changed.recv_timeout(CHECK_INTERVAL) {
+                    Ok(Ok(event)) => note_event(&mut pending, &rules, event),
+                    Ok(Err(e)) => eprintln!("Failed to watch folders: {}", e),
+                    Err(RecvTimeoutError::Timeout) => {},
+                    // The watch was stopped
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+
+                for file in take_settled(&mut pending) {
+                    let Some(rule) = rule_for(&rules, &file) else { continue };
+                    if !report(encrypt_new_file(rule, &file)) {
+                        return;
+                    }
+                }
+            }
+        });
+
+        FolderWatch { folders: folders.into_iter().map(|(folder, _)| folder).collect(), receiver, _watcher: watcher.ok() }
+    }
+
+    /// Get the folders as configured when the watch started
+    pub fn folders(&self) -> &[WatchedFolder] {
+        &self.folders
+    }
+
+    /// Take the first event since the last call (None if nothing happened)
+    pub fn take_event(&self) -> Option<WatchEvent> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Note new files, and changes to new files, from a file system event
+fn note_event(pending: &mut HashMap<PathBuf, (Option<u64>, Instant)>, rules: &[WatchRule], event: Event) {
+    let now = Instant::now();
+    let new_files = match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => event.paths.get(..),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => event.paths.get(1..),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) | EventKind::Remove(_) => {
+            for path in &event.paths {
+                pending.remove(path);
+            }
+            return;
+        },
+        EventKind::Modify(_) => {
+            // Files still being written wait longer
+            for path in &event.paths {
+                if let Some((_, since)) = pending.get_mut(path) {
+                    *since = now;
+                }
+            }
+            return;
+        },
+        _ => return,
+    };
+
+    for path in new_files.unwrap_or_default() {
+        if rule_for(rules, path).is_some() && !is_ignored(path) {
+            pending.insert(path.clone(), (None, now));
+        }
+    }
+}
+
+/// Take the new files that have not changed for the settle time
+///
+/// Files that disappeared, and folders, are forgotten.
+fn take_settled(pending: &mut HashMap<PathBuf, (Option<u64>, Instant)>) -> Vec<PathBuf> {
+    let now = Instant::now();
+    let mut settled = Vec::new();
+    pending.retain(|path, (size, since)| {
+        let current = match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => Some(metadata.len()),
+            _ => return false,
+        };
+        if current != *size {
+            *size = current;
+            *since = now;
+            true
+        } else if now.duration_since(*since) < SETTLE_TIME {
+            true
+        } else {
+            settled.push(path.clone());
+            false
+        }
+    });
+    settled
+}
+
+/// Get the watched folder a file is directly in
+fn rule_for<'a>(rules: &'a [WatchRule], file: &Path) -> Option<&'a WatchRule> {
+    rules.iter().find(|rule| file.parent() == Some(rule.folder.as_path()))
+}
+
+/// Check whether a new file is left alone: hidden and temporary files (including
+/// CRUSTy's own), files that are already encrypted, and partial downloads
+fn is_ignored(path: &Path) -> bool {
+    let hidden = path.file_name()
+        .map(|name| name.to_string_lossy())
+        .is_some_and(|name| name.starts_with('.') || name.starts_with("~$"));
+    let partial = path.extension()
+        .is_some_and(|extension| PARTIAL_EXTENSIONS.iter().any(|partial| extension.eq_ignore_ascii_case(partial)));
+    hidden || partial || file_names::is_encrypted_name(path) || file_names::is_openpgp_name(path)
+        || path.extension().is_some_and(|extension| extension == "age")
+}
+
+/// Encrypt a new file into its folder's output folder, and log the result
+fn encrypt_new_file(rule: &WatchRule, file: &Path) -> WatchEvent {
+    let event = match file_names::encrypted_name(file) {
+        Some(name) => {
+            let output = rule.output_dir.join(name);
+            match encryption::encrypt_file(file, &output, &rule.key, |_| {}) {
+                Ok(()) => WatchEvent::Encrypted { file: file.to_path_buf(), output },
+                Err(e) => WatchEvent::Failed { path: file.to_path_buf(), error: e.to_string() },
+            }
+        },
+        None => WatchEvent::Failed { path: file.to_path_buf(), error: "The file has no name".to_string() },
+    };
+    log_event(&event, Some(&rule.key.fingerprint()));
+    event
+}
+
+/// Record an event in the operation log, with the key of the folder if it has one
+fn log_event(event: &WatchEvent, key_fingerprint: Option<&str>) {
+    let Some(logger) = logger::get_logger() else { return };
+    match event {
+        WatchEvent::Encrypted { file, output } => {
+            let message = format!("Encrypted new file to {}", logger.redact_path(output));
+            let file = file.to_string_lossy();
+            match key_fingerprint {
+                Some(fingerprint) => logger.log_key_success("Watch Folder", &file, &message, fingerprint),
+                None => logger.log_success("Watch Folder", &file, &message),
+            }.ok();
+        },
+        WatchEvent::Failed { path, error } => {
+            logger.log_error("Watch Folder", &path.to_string_lossy(), error).ok();
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::decrypt_file;
+    use tempfile::tempdir;
+
+    fn watched(folder: &Path, output_dir: &Path, key: &EncryptionKey) -> WatchedFolder {
+        WatchedFolder {
+            folder: folder.to_path_buf(),
+            output_dir: output_dir.to_path_buf(),
+            key_name: "Inbox Key".to_string(),
+            fingerprint: key.fingerprint(),
+        }
+    }
+
+    fn wait_for_event(watch: &FolderWatch) -> WatchEvent {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(20) {
+            if let Some(event) = watch.take_event() {
+                return event;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("No event from the folder watch");
+    }
+
+    #[test]
+    fn test_ignored_files() {
+        for ignored in [".report.txt.crusty-tmp", "~$report.docx", "report.txt.encrypted", "report.gpg", "report.age", "video.mp4.part", "setup.exe.crdownload"] {
+            assert!(is_ignored(Path::new(ignored)), "{}", ignored);
+        }
+        for encrypted in ["report.txt", "report.docx", "archive.zip"] {
+            assert!(!is_ignored(Path::new(encrypted)), "{}", encrypted);
+        }
+    }
+
+    #[test]
+    fn test_new_file_is_encrypted() {
+        let dir = tempdir().unwrap();
+        let inbox = dir.path().join("inbox");
+        let output_dir = dir.path().join("encrypted");
+        fs::create_dir(&inbox).unwrap();
+        fs::create_dir(&output_dir).unwrap();
+        fs::write(inbox.join("before.txt"), b"Already there").unwrap();
+
+        let key = EncryptionKey::generate();
+        let watch = FolderWatch::start(vec![(watched(&inbox, &output_dir, &key), Some(key.clone()))], || {});
+        assert_eq!(watch.folders()[0].folder, inbox);
+        thread::sleep(Duration::from_millis(200));
+        fs::write(inbox.join("new.txt"), b"Dropped into the inbox").unwrap();
+
+        let output = output_dir.join("new.txt.encrypted");
+        assert_eq!(wait_for_event(&watch), WatchEvent::Encrypted { file: inbox.join("new.txt"), output: output.clone() });
+        let decrypted = dir.path().join("decrypted.txt");
+        decrypt_file(&output, &decrypted, &key, |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), b"Dropped into the inbox");
+
+        // Files there before the watch started are left alone
+        assert!(!output_dir.join("before.txt.encrypted").exists());
+    }
+
+    #[test]
+    fn test_missing_key_is_reported() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let watch = FolderWatch::start(vec![(watched(dir.path(), dir.path(), &key), None)], || {});
+        assert!(matches!(wait_for_event(&watch), WatchEvent::Failed { path, error } if path == dir.path() && error.contains("Inbox Key")));
+    }
+}
@@ -1,8 +1,7 @@
 use eframe::egui::{self, Ui, RichText, Button, Rounding, Layout, Align};
 use crate::gui::app_core::CrustyApp;
-use crate::gui::file_list::FileOperationType;
-use crate::start_operation::FileOperation;
 use crate::gui::app_state::AppState;
+use crate::i18n::tr;
 
 /// Action bar trait for displaying the top action buttons
 pub trait ActionBar {
@@ -33,23 +32,11 @@ impl ActionBar for CrustyApp {
             // Add label under the button
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.add_space(-25.0); // Adjust spacing to position the label under the button
-                ui.label(RichText::new("Encrypt").size(text_size));
+                ui.label(RichText::new(tr("action-encrypt")).size(text_size));
             });
             
             if encrypt_button.clicked() {
-                if !self.selected_files.is_empty() && self.current_key.is_some() {
-                    self.operation = FileOperation::Encrypt;
-                    
-                    // Add files to the file list
-                    let files_to_add = self.selected_files.clone();
-                    for file in files_to_add {
-                        self.add_file_entry(file, FileOperationType::Encrypt);
-                    }
-                    
-                    self.show_status("Starting encryption...");
-                } else {
-                    self.show_error("Please select files and encryption key");
-                }
+                self.begin_encrypt();
             }
             
             // Decrypt button
@@ -65,22 +52,14 @@ impl ActionBar for CrustyApp {
             // Add label under the button
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.add_space(-25.0);
-                ui.label(RichText::new("Decrypt").size(text_size));
+                ui.label(RichText::new(tr("action-decrypt")).size(text_size));
             });
             
             if decrypt_button.clicked() {
-                if !self.selected_files.is_empty() && self.current_key.is_some() {
-                    self.operation = FileOperation::Decrypt;
-                    
-                    // Add files to the file list
-                    let files_to_add = self.selected_files.clone();
-                    for file in files_to_add {
-                        self.add_file_entry(file, FileOperationType::Decrypt);
-                    }
-                    
-                    self.show_status("Starting decryption...");
+                if !self.operations.selected_files().is_empty() && self.keys.has_key() {
+                    self.begin_decrypt();
                 } else {
-                    self.show_error("Please select files and encryption key");
+                    self.show_error(&tr("error-select-files-and-key"));
                 }
             }
             
@@ -97,12 +76,11 @@ impl ActionBar for CrustyApp {
             // Add label under the button
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.add_space(-25.0);
-                ui.label(RichText::new("Stop").size(text_size));
+                ui.label(RichText::new(tr("action-stop")).size(text_size));
             });
             
             if stop_button.clicked() {
-                self.operation = FileOperation::None;
-                self.show_status("Operation stopped");
+                self.operations.stop();
             }
             
             // Key Management button
@@ -118,12 +96,12 @@ impl ActionBar for CrustyApp {
             // Add label under the button
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.add_space(-25.0);
-                ui.label(RichText::new("Keys").size(text_size));
+                ui.label(RichText::new(tr("action-keys")).size(text_size));
             });
             
             if key_button.clicked() {
                 self.state = AppState::KeyManagement;
-                self.show_status("Key management");
+                self.show_status(&tr("status-key-management"));
             }
             
             // Advanced Options button
@@ -139,17 +117,17 @@ impl ActionBar for CrustyApp {
             // Add label under the button
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.add_space(-25.0);
-                ui.label(RichText::new("Advanced").size(text_size));
+                ui.label(RichText::new(tr("action-advanced")).size(text_size));
             });
             
             if advanced_button.clicked() {
                 // Toggle between main screen and advanced options
                 if self.state == AppState::MainScreen {
                     self.state = AppState::Dashboard;
-                    self.show_status("Advanced options");
+                    self.show_status(&tr("status-advanced-options"));
                 } else {
                     self.state = AppState::MainScreen;
-                    self.show_status("Main screen");
+                    self.show_status(&tr("status-main-screen"));
                 }
             }
             
@@ -166,7 +144,7 @@ impl ActionBar for CrustyApp {
             // Add label under the button
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.add_space(-25.0);
-                ui.label(RichText::new("Open").size(text_size));
+                ui.label(RichText::new(tr("action-open")).size(text_size));
             });
             
             if open_button.clicked() {
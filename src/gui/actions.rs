@@ -1,9 +1,48 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::{Local, NaiveDate};
 use rfd::FileDialog;
 
-use crate::encryption::EncryptionKey;
-use crate::gui::file_list::{FileEntry, FileOperationType, FileStatus};
+use zeroize::Zeroizing;
+
+use crate::gui::file_list::FileOperationType;
 use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::{AppState, BatchWarning, KeyPinWarning, PinPrompt, PreflightSummary, ProxyDialog};
+use crate::gui::theme::AppTheme;
+use crate::gui::utils;
+use crate::i18n::{tr, tr_args};
+use crate::logger::LogEntry;
+use crate::smartcard::TokenError;
+use crate::system_log::SystemLog;
+use crate::policy::get_policy;
+use crate::settings::{KeyPin, OpenAction, Settings, WatchedFolder};
+use crate::network;
+use crate::age_format::{self, AgeRecipient};
+use crate::openpgp::{self, OpenPgpRecipient};
+use crate::recipients;
+use crate::archive;
+use crate::catalog;
+use crate::chunk_tuning;
+use crate::file_names;
+use crate::history::{HistoryEntry, HistoryOperation};
+use crate::in_place;
+use crate::keystore::{self, KeyProvenance};
+use crate::secured_folders::FolderStatus;
+use crate::paper_import::{self, Scan, ScanMethod, ScannedBackup};
+use crate::deployment::{self, BundleContents, BundledKey, DeploymentBundle};
+use crate::instance;
+use crate::key_agreement;
+use crate::encryption::EncryptionKey;
+use crate::services::ServiceEvent;
+use crate::session_watch;
+use crate::services::key_service::TokenUnlock;
+use crate::start_operation::{start_operation, FileOperation};
+use crate::tray::Tray;
+use crate::folder_watch::{FolderWatch, WatchEvent};
+use crate::locale::{self, STORED_TIMESTAMP_FORMAT};
+
+/// Most events from the watched folders kept for the status panel
+const WATCH_ACTIVITY_KEPT: usize = 20;
 
 /// Implementation of action methods for CrustyApp
 impl CrustyApp {
@@ -19,50 +58,1250 @@ impl CrustyApp {
         self.error_time = std::time::Instant::now();
     }
     
+    /// Show the events raised by the service commands
+    ///
+    /// # Returns
+    /// * `bool` - Whether there were any events
+    pub fn show_service_events(&mut self) -> bool {
+        let mut events = self.keys.take_events();
+        events.extend(self.operations.take_events());
+        events.extend(self.transfer.take_events());
+        events.extend(self.folders.take_events());
+        events.extend(self.cold_storage.take_events());
+        events.extend(self.cold_storage_restore.take_events());
+        events.extend(self.key_agreement.take_events());
+        events.extend(self.key_rotation.take_events());
+        events.extend(self.audit.take_events());
+        events.extend(self.benchmark.take_events());
+        events.extend(self.catalog.take_events());
+        events.extend(self.viewer.take_events());
+        events.extend(self.control_api.take_events());
+        
+        let shown = !events.is_empty();
+        for event in events {
+            match event {
+                ServiceEvent::Status(message) => self.show_status(&message),
+                ServiceEvent::Error(message) => self.show_error(&message),
+            }
+        }
+        shown
+    }
+    
+    /// Copy text to the clipboard, remembering it so it is cleared when the keys are locked
+    pub fn copy_to_clipboard(&mut self, ui: &eframe::egui::Ui, text: String) {
+        self.clipboard_text = Some(Zeroizing::new(text.clone()));
+        ui.output_mut(|o| o.copied_text = text);
+    }
+    
+    /// Lock the keys, and wipe what else CRUSTy holds that could give them away: text it
+    /// copied to the clipboard, entered shares and passphrases, and the PIN prompt
+    ///
+    /// # Arguments
+    /// * `reason` - Why the keys are locked (for the log)
+    pub fn lock_session(&mut self, reason: &str) {
+        self.keys.lock_keys(reason);
+        self.transfer.clear_secrets();
+        self.cold_storage_restore.clear_secrets();
+        self.key_agreement.clear_secrets();
+        self.viewer.close_all("Keys locked");
+        self.pin_prompt = None;
+        self.new_key_passphrase = Zeroizing::new(String::new());
+        self.new_key_passphrase_confirm = Zeroizing::new(String::new());
+        self.backup_passphrase = Zeroizing::new(String::new());
+        self.backup_passphrase_confirm = Zeroizing::new(String::new());
+        if let Some(text) = self.clipboard_text.take() {
+            session_watch::clear_clipboard(&text);
+        }
+        self.last_input = std::time::Instant::now();
+    }
+    
+    /// Load the locked keys again
+    pub fn unlock_session(&mut self) {
+        self.keys.unlock_keys();
+        self.last_input = std::time::Instant::now();
+    }
+    
+    /// Replace the text on the clipboard with an armored age message of it, encrypted
+    /// with the current key (the tray icon's "Encrypt Clipboard")
+    pub fn encrypt_clipboard(&mut self) {
+        let Some(key) = self.keys.current_key() else {
+            self.show_error(&tr("error-clipboard-no-key"));
+            return;
+        };
+        
+        let mut clipboard = match arboard::Clipboard::new() {
+            Ok(clipboard) => clipboard,
+            Err(e) => {
+                self.show_error(&tr_args("error-clipboard-open", &[("error", e.to_string().into())]));
+                return;
+            }
+        };
+        let text = match clipboard.get_text() {
+            Ok(text) if !text.is_empty() => Zeroizing::new(text),
+            _ => {
+                self.show_error(&tr("error-clipboard-empty"));
+                return;
+            }
+        };
+        if age_format::is_age_data(text.trim_start().as_bytes()) {
+            self.show_error(&tr("error-clipboard-encrypted"));
+            return;
+        }
+        
+        let fingerprint = key.fingerprint();
+        let result = age_format::encrypt_text(&text, key)
+            .map_err(|e| e.to_string())
+            .and_then(|message| clipboard.set_text(message).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => {
+                self.logger.log_success(
+                    "Encrypt Clipboard",
+                    "clipboard",
+                    &format!("Encrypted {} characters with key {}", text.chars().count(), fingerprint)
+                ).ok();
+                self.show_status(&tr("status-clipboard-encrypted"));
+            },
+            Err(e) => self.show_error(&tr_args("error-clipboard-encrypt", &[("error", e.into())])),
+        }
+    }
+    
+    /// Save the settings changed in the GUI
+    pub fn save_settings(&mut self) {
+        if let Err(e) = self.settings.save_to(&Settings::path_in(&self.data_dir)) {
+            self.show_error(&tr_args("error-settings-save", &[("error", e.to_string().into())]));
+        }
+    }
+    
+    /// Apply the theme and read size from the settings
+    ///
+    /// Colors changed in the theme editor and not saved are discarded.
+    pub fn apply_appearance_settings(&mut self) {
+        self.theme = match self.settings.saved_theme() {
+            Some(saved) => AppTheme::from_saved(saved),
+            None => AppTheme::for_theme(self.settings.theme),
+        };
+        chunk_tuning::set_fixed_chunk_size(self.settings.chunk_size());
+    }
+    
+    /// Save the colors of the theme editor under a name, and use them
+    pub fn save_edited_theme(&mut self) {
+        let name = self.theme_name.trim().to_string();
+        if name.is_empty() {
+            self.show_error(&tr("error-theme-name"));
+            return;
+        }
+        self.settings.save_theme(self.theme.to_saved(&name));
+        self.save_settings();
+        self.show_status(&tr_args("status-theme-saved", &[("name", name.into())]));
+    }
+    
+    /// Delete the saved theme in use, going back to the built-in theme
+    pub fn delete_saved_theme(&mut self) {
+        let Some(name) = self.settings.saved_theme.take() else { return };
+        self.settings.saved_themes.retain(|theme| theme.name != name);
+        self.apply_appearance_settings();
+        self.save_settings();
+        self.show_status(&tr_args("status-theme-deleted", &[("name", name.into())]));
+    }
+    
+    /// Make the main screen's choices those saved as defaults
+    ///
+    /// An output directory that no longer exists is not selected.
+    pub fn apply_operation_defaults(&mut self) {
+        let defaults = self.settings.defaults.clone();
+        self.operations.batch_mode = defaults.batch_mode;
+        self.operations.backend.use_embedded = defaults.use_embedded;
+        if let Some(config) = &defaults.embedded {
+            self.operations.backend.set_config(config);
+        }
+        if let Some(dir) = defaults.output_dir.filter(|dir| dir.is_dir()) {
+            self.operations.set_output_dir(dir);
+        }
+    }
+    
+    /// Turn offline mode on or off and save the setting
+    pub fn set_offline(&mut self, offline: bool) {
+        self.settings.offline = offline;
+        network::set_offline(offline);
+        self.save_settings();
+        
+        if offline {
+            self.show_status(&tr("status-offline-on"));
+        } else {
+            self.show_status(&tr("status-offline-off"));
+        }
+    }
+    
+    /// Start or stop the control API to match the settings and the admin policy
+    pub fn apply_control_api_setting(&mut self) {
+        if self.settings.control_api.enabled && !get_policy().disable_control_api {
+            self.control_api.start(self.settings.control_api.port, &instance::default_dir());
+        } else {
+            self.control_api.stop();
+        }
+    }
+    
+    /// Start or stop sending log entries to the system log, as set in the settings and
+    /// the admin policy
+    pub fn apply_system_log_setting(&mut self) {
+        if !self.settings.system_log && !get_policy().system_log {
+            self.logger.set_system_log(None);
+        } else if !self.logger.sends_to_system_log() {
+            match SystemLog::open() {
+                Ok(system_log) => self.logger.set_system_log(Some(system_log)),
+                Err(e) => self.show_error(&tr_args("error-system-log-open", &[("error", e.to_string().into())])),
+            }
+        }
+    }
+    
+    /// Show or remove the tray icon, as chosen in the settings
+    pub fn apply_tray_setting(&mut self, ctx: &eframe::egui::Context) {
+        if !self.settings.tray_icon {
+            self.tray = None;
+        } else if self.tray.is_none() {
+            let ctx = ctx.clone();
+            self.tray = Tray::start(move || ctx.request_repaint());
+        }
+    }
+    
+    /// Start, restart, or stop watching folders for new files, as set in the settings
+    ///
+    /// Folders are only watched while the keys are unlocked, with the saved keys they
+    /// were added with.
+    pub fn update_folder_watch(&mut self, ctx: &eframe::egui::Context) {
+        let wanted = if self.keys.is_locked() { &[][..] } else { self.settings.watched_folders.as_slice() };
+        let watching = self.folder_watch.as_ref().map_or(&[][..], FolderWatch::folders);
+        if wanted == watching {
+            return;
+        }
+        
+        self.folder_watch = None;
+        if wanted.is_empty() {
+            return;
+        }
+        let folders = wanted.iter()
+            .map(|folder| {
+                let key = self.keys.saved_keys().iter()
+                    .find(|(_, key)| key.fingerprint() == folder.fingerprint)
+                    .map(|(_, key)| key.clone());
+                (folder.clone(), key)
+            })
+            .collect();
+        let ctx = ctx.clone();
+        self.folder_watch = Some(FolderWatch::start(folders, move || ctx.request_repaint()));
+    }
+    
+    /// Add what happened in a watched folder to the activity shown, and to the status
+    pub fn note_watch_event(&mut self, event: WatchEvent) {
+        match &event {
+            WatchEvent::Encrypted { file, .. } => self.show_status(&tr_args("status-watch-encrypted", &[("path", file.display().to_string().into())])),
+            WatchEvent::Failed { path, error } => self.show_error(&tr_args("error-in-file", &[("path", path.display().to_string().into()), ("error", error.as_str().into())])),
+        }
+        let time = Local::now().format(STORED_TIMESTAMP_FORMAT).to_string();
+        self.watch_activity.insert(0, (time, event));
+        self.watch_activity.truncate(WATCH_ACTIVITY_KEPT);
+    }
+    
+    /// Watch a folder for new files, encrypting them into an output folder with the
+    /// current key
+    pub fn add_watched_folder(&mut self) {
+        let Some(fingerprint) = self.keys.current_key().map(|key| key.fingerprint()) else {
+            self.show_error(&tr("error-watch-no-key"));
+            return;
+        };
+        if !self.keys.saved_keys().iter().any(|(_, key)| key.fingerprint() == fingerprint) {
+            self.show_error(&tr("error-watch-unsaved-key"));
+            return;
+        }
+        let key_name = self.keys.current_key_name().unwrap_or_else(|| "Unknown key".to_string());
+        
+        let Some(folder) = FileDialog::new()
+            .set_title("Select the Folder to Watch")
+            .pick_folder() else { return };
+        let Some(output_dir) = FileDialog::new()
+            .set_title("Select the Folder for the Encrypted Files")
+            .pick_folder() else { return };
+        if self.settings.watched_folders.iter().any(|watched| watched.folder == folder) {
+            self.show_error(&tr_args("error-watch-already", &[("folder", folder.display().to_string().into())]));
+            return;
+        }
+        
+        self.show_status(&tr_args("status-watch-started", &[("folder", folder.display().to_string().into())]));
+        self.settings.watched_folders.push(WatchedFolder { folder, output_dir, key_name, fingerprint });
+        self.save_settings();
+    }
+    
+    /// Open the proxy settings dialog with the current settings
+    pub fn open_proxy_dialog(&mut self) {
+        self.proxy_dialog = Some(ProxyDialog {
+            settings: self.settings.proxy.clone(),
+            password: Zeroizing::new(String::new()),
+            clear_password: false,
+        });
+    }
+    
+    /// Save the proxy settings from the dialog and use them for the following requests
+    pub fn save_proxy_settings(&mut self) {
+        let dialog = match self.proxy_dialog.take() {
+            Some(dialog) => dialog,
+            None => return,
+        };
+        
+        // The password goes to the credential store, never to the settings file
+        let result = network::validate_proxy(&dialog.settings).and_then(|()| {
+            if dialog.clear_password {
+                network::set_proxy_password("")
+            } else if !dialog.password.is_empty() {
+                network::set_proxy_password(&dialog.password)
+            } else {
+                Ok(())
+            }
+        });
+        
+        if let Err(e) = result {
+            self.show_error(&e.to_string());
+            self.proxy_dialog = Some(dialog);
+            return;
+        }
+        
+        self.settings.proxy = dialog.settings;
+        self.save_settings();
+        
+        match network::apply_proxy(&self.settings.proxy) {
+            Ok(()) => self.show_status(&tr("status-proxy-saved")),
+            Err(e) => self.show_error(&tr_args("error-proxy-apply", &[("error", e.to_string().into())])),
+        }
+    }
+    
+    /// Check whether the admin policy allows managing keys (generate, import, export)
+    pub fn key_management_allowed(&self) -> bool {
+        self.keys.management_allowed()
+    }
+    
+    /// Start encrypting the selected files with the current key
+    pub fn begin_encrypt(&mut self) {
+        if !self.operations.is_ready_to_encrypt() || self.keys.current_key().is_none() {
+            self.show_error(&tr("error-select-files"));
+            return;
+        }
+        
+        if !self.backend_settings_valid()
+            || !self.operations.archive_options_valid(FileOperationType::Encrypt)
+            || !self.operations.recipient_options_valid()
+            || !self.operations.encrypt_destination_writable() {
+            return;
+        }
+        
+        if self.key_pin_unconfirmed()
+            || self.large_batch_unconfirmed(FileOperationType::Encrypt)
+            || self.preflight_unconfirmed(FileOperationType::Encrypt) {
+            return;
+        }
+        
+        self.start_confirmed_batch();
+        self.operations.begin_encrypt();
+        self.start_operation();
+    }
+    
+    /// Start decrypting the selected files, unlocking the smartcard key first if needed
+    pub fn begin_decrypt(&mut self) {
+        if self.operations.decrypt_reason_missing() {
+            self.state = AppState::Decrypting;
+            self.show_error(&tr("error-decrypt-reason"));
+            return;
+        }
+        
+        if !self.backend_settings_valid()
+            || !self.operations.archive_options_valid(FileOperationType::Decrypt)
+            || !self.operations.output_dir_writable() {
+            return;
+        }
+        
+        if self.keys.current_key().is_none() {
+            if let Some(index) = self.keys.selected_token_key() {
+                self.unlock_token_key(index);
+                return;
+            }
+        }
+        
+        // Asked after the PIN, so the PIN prompt does not ask again once confirmed
+        if self.large_batch_unconfirmed(FileOperationType::Decrypt)
+            || self.preflight_unconfirmed(FileOperationType::Decrypt) {
+            return;
+        }
+        
+        self.start_confirmed_batch();
+        
+        // Record the request (and the reason, if given) for auditing
+        let reason = self.operations.decrypt_reason.trim();
+        for file in self.operations.selected_files() {
+            let mut entry = LogEntry::new("Decrypt Request", &file.to_string_lossy(), true, "Decryption requested");
+            if !reason.is_empty() {
+                entry = entry.with_reason(reason);
+            }
+            self.logger.log(entry).ok();
+        }
+        
+        self.operations.begin_decrypt();
+        self.start_operation();
+    }
+    
+    /// Check the embedded backend settings, showing what is wrong with them
+    fn backend_settings_valid(&mut self) -> bool {
+        match self.operations.backend.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                self.show_error(&tr_args("error-embedded-settings", &[("error", e.to_string().into())]));
+                false
+            }
+        }
+    }
+    
+    /// Check whether the selected files are over the batch limits and not yet confirmed
+    ///
+    /// Shows the large batch warning if so; confirming it starts the operation again.
+    fn large_batch_unconfirmed(&mut self, operation: FileOperationType) -> bool {
+        if self.batch_warning.as_ref().is_some_and(|warning| warning.confirmed) {
+            return false;
+        }
+        
+        let file_count = self.operations.selected_files().len();
+        let total_size = self.operations.selected_size();
+        if !self.settings.batch_limits.exceeded_by(file_count, total_size) {
+            return false;
+        }
+        
+        self.batch_warning = Some(BatchWarning {
+            operation,
+            file_count,
+            total_size,
+            confirmed: false,
+        });
+        true
+    }
+    
+    /// Start the operation held back by the large batch warning
+    pub fn confirm_large_batch(&mut self) {
+        let operation = match self.batch_warning.as_mut() {
+            Some(warning) => {
+                warning.confirmed = true;
+                warning.operation.clone()
+            },
+            None => return,
+        };
+        
+        match operation {
+            FileOperationType::Encrypt => self.begin_encrypt(),
+            FileOperationType::Decrypt => self.begin_decrypt(),
+            FileOperationType::None => self.batch_warning = None,
+        }
+    }
+    
+    /// Check whether the summary of a batch was not confirmed yet
+    ///
+    /// Shows the pre-flight summary if so; confirming it starts the operation again.
+    /// Single files start without it, and so does every batch when the summary is
+    /// turned off in the settings.
+    fn preflight_unconfirmed(&mut self, operation: FileOperationType) -> bool {
+        if self.preflight.as_ref().is_some_and(|preflight| preflight.confirmed) {
+            return false;
+        }
+        
+        let file_count = self.operations.selected_files().len();
+        if !self.settings.preflight_summary || file_count < 2 {
+            return false;
+        }
+        
+        let key_name = self.keys.current_key_name().unwrap_or_else(|| "Unknown key".to_string());
+        let key = match self.keys.current_key() {
+            Some(key) => format!("{} [{}]", key_name, key.fingerprint()),
+            None => key_name,
+        };
+        let (summary, changes) = self.operations.run_summary(&operation, &key);
+        self.preflight = Some(PreflightSummary {
+            operation,
+            summary,
+            changes,
+            file_count,
+            total_size: self.operations.selected_size(),
+            confirmed: false,
+        });
+        true
+    }
+    
+    /// Start the batch held back by the pre-flight summary
+    pub fn confirm_preflight(&mut self) {
+        let operation = match self.preflight.as_mut() {
+            Some(preflight) => {
+                preflight.confirmed = true;
+                preflight.operation.clone()
+            },
+            None => return,
+        };
+        
+        match operation {
+            FileOperationType::Encrypt => self.begin_encrypt(),
+            FileOperationType::Decrypt => self.begin_decrypt(),
+            FileOperationType::None => self.preflight = None,
+        }
+    }
+    
+    /// Close the warnings and summary of an operation that is not started
+    pub fn cancel_confirmations(&mut self) {
+        self.key_pin_warning = None;
+        self.batch_warning = None;
+        self.preflight = None;
+    }
+    
+    /// Forget the answered warnings of the operation that is starting, recording its
+    /// summary to compare the next batch with
+    fn start_confirmed_batch(&mut self) {
+        if let Some(preflight) = self.preflight.take() {
+            self.operations.record_run(preflight.summary);
+        }
+        self.cancel_confirmations();
+    }
+    
+    /// Get the pin the current key does not match, of the folder of a selected file or
+    /// of the output directory
+    pub fn key_pin_mismatch(&self) -> Option<&KeyPin> {
+        let fingerprint = self.keys.current_key()?.fingerprint();
+        self.operations.selected_files().iter()
+            .map(PathBuf::as_path)
+            .chain(self.operations.output_dir())
+            .filter_map(|path| self.settings.key_pin(path))
+            .find(|pin| pin.fingerprint != fingerprint)
+    }
+    
+    /// Check whether the current key is not the pinned key and this was not confirmed
+    ///
+    /// Shows the key pin warning if so; confirming it starts the operation again.
+    fn key_pin_unconfirmed(&mut self) -> bool {
+        if self.key_pin_warning.as_ref().is_some_and(|warning| warning.confirmed) {
+            return false;
+        }
+        
+        let Some(pin) = self.key_pin_mismatch().cloned() else {
+            self.key_pin_warning = None;
+            return false;
+        };
+        self.key_pin_warning = Some(KeyPinWarning {
+            pin,
+            key_name: self.keys.current_key_name().unwrap_or_else(|| "Unknown key".to_string()),
+            confirmed: false,
+        });
+        true
+    }
+    
+    /// Encrypt with the current key although another key is pinned, logging the choice
+    pub fn confirm_key_pin_override(&mut self) {
+        let Some(warning) = self.key_pin_warning.as_mut() else { return };
+        warning.confirmed = true;
+        let entry = LogEntry::new(
+            "Key Pin Override",
+            &warning.pin.folder.to_string_lossy(),
+            true,
+            &format!("Encrypting with key '{}' instead of the pinned key '{}'", warning.key_name, warning.pin.key_name),
+        );
+        self.logger.log(entry).ok();
+        self.begin_encrypt();
+    }
+    
+    /// Select the pinned key of the key pin warning and encrypt with it
+    pub fn use_pinned_key(&mut self) {
+        let Some(warning) = self.key_pin_warning.take() else { return };
+        let index = self.keys.saved_keys().iter().position(|(_, key)| key.fingerprint() == warning.pin.fingerprint);
+        match index {
+            Some(index) => {
+                self.keys.select_key(index);
+                self.begin_encrypt();
+            },
+            None => self.show_error(&tr_args("error-pinned-key-missing", &[("name", warning.pin.key_name.into())])),
+        }
+    }
+    
+    /// Pin the current key to a folder
+    pub fn pin_current_key(&mut self, folder: PathBuf) {
+        let Some(fingerprint) = self.keys.current_key().map(|key| key.fingerprint()) else {
+            self.show_error(&tr("error-pin-no-key"));
+            return;
+        };
+        let key_name = self.keys.current_key_name().unwrap_or_else(|| "Unknown key".to_string());
+        self.show_status(&tr_args("status-key-pinned", &[("name", key_name.as_str().into()), ("folder", folder.display().to_string().into())]));
+        self.settings.pin_key(KeyPin { folder, key_name, fingerprint });
+        self.save_settings();
+    }
+    
+    /// Run the queued operation on the selected files in the background
+    fn start_operation(&mut self) {
+        let ready = match self.operations.operation() {
+            FileOperation::Encrypt | FileOperation::BatchEncrypt => self.operations.is_ready_to_encrypt(),
+            _ => self.operations.is_ready(),
+        };
+        
+        match self.keys.current_key().cloned() {
+            Some(key) if ready => {
+                self.keys.mark_key_used(&key);
+                self.operations.memory_limit = self.settings.memory_limit();
+                start_operation(&mut self.operations, key);
+            },
+            _ => self.show_error(&tr("error-select-files")),
+        }
+    }
+    
+    /// Unwrap a smartcard key with the cached PIN, or ask for the PIN
+    fn unlock_token_key(&mut self, index: usize) {
+        match self.keys.unlock_token_key(index) {
+            TokenUnlock::Unlocked => self.begin_decrypt(),
+            TokenUnlock::PinRequired(status) => {
+                self.pin_prompt = Some(PinPrompt {
+                    key_index: index,
+                    pin: Zeroizing::new(String::new()),
+                    failed_attempts: 0,
+                    status,
+                });
+            },
+            TokenUnlock::Failed => {},
+        }
+    }
+    
+    /// Check the PIN entered in the smartcard prompt
+    pub fn submit_pin(&mut self) {
+        let (index, pin) = match &self.pin_prompt {
+            Some(prompt) => (prompt.key_index, prompt.pin.clone()),
+            None => return,
+        };
+        
+        match self.keys.unlock_token_key_with_pin(index, &pin) {
+            Ok(()) => {
+                self.pin_prompt = None;
+                self.begin_decrypt();
+            },
+            Err(TokenError::PinIncorrect(status)) => {
+                if let Some(prompt) = self.pin_prompt.as_mut() {
+                    prompt.failed_attempts += 1;
+                    prompt.status = status;
+                    prompt.pin = Zeroizing::new(String::new());
+                }
+            },
+            Err(_) => self.pin_prompt = None,
+        }
+    }
+    
+    /// Look up this machine's deployment identity
+    pub fn show_machine_identity(&mut self) {
+        match deployment::machine_identity() {
+            Ok(identity) => self.machine_identity = Some(identity),
+            Err(e) => self.show_error(&tr_args("error-machine-identity", &[("error", e.to_string().into())])),
+        }
+    }
+    
+    /// Agree a transfer key with a partner's computer: wait for it, or connect to it
+    pub fn start_key_agreement(&mut self, wait_for_partner: bool) {
+        match deployment::machine_identity_secret() {
+            Ok(identity) if wait_for_partner => self.key_agreement.start_listening(identity),
+            Ok(identity) => self.key_agreement.start_connecting(identity),
+            Err(e) => self.show_error(&tr_args("error-machine-identity", &[("error", e.to_string().into())])),
+        }
+    }
+    
+    /// Save the agreed key and make it the current key, saving a new partner too
+    pub fn accept_agreed_key(&mut self) {
+        let partner = self.key_agreement.agreement()
+            .and_then(|agreement| key_agreement::find_partner(&self.settings.partners, &agreement.partner_identity))
+            .cloned();
+        if let Some(accepted) = self.key_agreement.accept(partner.as_ref()) {
+            if !self.keys.add_key(&accepted.name, accepted.key, accepted.provenance) {
+                return;
+            }
+            if let Some(partner) = accepted.new_partner {
+                self.settings.partners.push(partner);
+                self.save_settings();
+            }
+        }
+    }
+    
+    /// Forget a saved partner, so the codes are compared again in the next agreement
+    pub fn remove_partner(&mut self, index: usize) {
+        if index < self.settings.partners.len() {
+            let partner = self.settings.partners.remove(index);
+            self.save_settings();
+            self.show_status(&tr_args("status-partner-forgotten", &[("name", partner.name.into())]));
+        }
+    }
+    
+    /// Re-encrypt the files chosen in the key rotation wizard, generating the new key
+    /// first if asked
+    pub fn start_key_rotation(&mut self) {
+        let Some((old_name, old_key)) = self.key_rotation.old_key
+            .and_then(|index| self.keys.saved_keys().get(index))
+            .cloned() else {
+            self.show_error(&tr("error-select-key-encrypted-with"));
+            return;
+        };
+
+        let new_key = match self.key_rotation.new_key {
+            Some(index) => match self.keys.saved_keys().get(index) {
+                Some((_, key)) => key.clone(),
+                None => {
+                    self.show_error(&tr("error-select-new-key"));
+                    return;
+                },
+            },
+            None => {
+                if !self.key_management_allowed() {
+                    self.show_error(&tr("error-generation-disabled"));
+                    return;
+                }
+                let name = self.key_rotation.new_key_name.trim().to_string();
+                if name.is_empty() {
+                    self.show_error(&tr("error-new-key-name"));
+                    return;
+                }
+                let key = EncryptionKey::generate();
+                let provenance = KeyProvenance::new(&format!("Generated to replace key '{}'", old_name))
+                    .with_origin(&old_key.fingerprint());
+                self.keys.add_key(&name, key.clone(), provenance);
+                // Chosen from now on, so running the rotation again does not generate another key
+                self.key_rotation.new_key = Some(self.keys.saved_keys().len() - 1);
+                key
+            },
+        };
+
+        self.key_rotation.start(old_key, new_key);
+    }
+    
+    /// Check the encrypted files listed in a batch manifest, with the current key
+    pub fn check_against_manifest(&mut self, manifest_path: PathBuf) {
+        let Some(key) = self.keys.current_key().cloned() else {
+            self.show_error(&tr("error-select-manifest-key"));
+            return;
+        };
+        self.operations.check_manifest(manifest_path, key);
+    }
+    
+    /// Decrypt the selected file to the secure viewer's folder and open it
+    pub fn view_securely(&mut self) {
+        if self.operations.decrypt_reason_missing() {
+            self.show_error(&tr("error-decrypt-reason"));
+            return;
+        }
+        let (Some(key), [source]) = (self.keys.current_key().cloned(), self.operations.selected_files()) else {
+            self.show_error(&tr("error-view-selection"));
+            return;
+        };
+        let source = source.clone();
+        
+        // Record the request (and the reason, if given) for auditing
+        let mut entry = LogEntry::new("View Request", &source.to_string_lossy(), true, "Secure viewing requested");
+        let reason = self.operations.decrypt_reason.trim();
+        if !reason.is_empty() {
+            entry = entry.with_reason(reason);
+        }
+        self.logger.log(entry).ok();
+        
+        let timeout = Duration::from_secs(u64::from(self.settings.viewer_minutes) * 60);
+        if let Some(path) = self.viewer.view(&source, &key, timeout) {
+            utils::open_file(&path);
+        }
+    }
+    
+    /// Audit the encrypted file chosen on the audit screen against its original
+    pub fn start_audit(&mut self) {
+        let Some((_, key)) = self.audit.key
+            .and_then(|index| self.keys.saved_keys().get(index))
+            .cloned() else {
+            self.show_error(&tr("error-select-audit-key"));
+            return;
+        };
+        self.audit.start(key);
+    }
+    
+    /// Verify the files chosen on the audit screen, without writing any decrypted data
+    pub fn start_verification(&mut self) {
+        let Some((_, key)) = self.audit.key
+            .and_then(|index| self.keys.saved_keys().get(index))
+            .cloned() else {
+            self.show_error(&tr("error-select-key-encrypted-with"));
+            return;
+        };
+        self.audit.start_verify(key);
+    }
+    
+    /// Make the catalog of the folder chosen on the catalog screen
+    pub fn start_catalog(&mut self) {
+        let keys: Vec<EncryptionKey> = self.keys.saved_keys().iter().map(|(_, key)| key.clone()).collect();
+        self.catalog.start(keys);
+    }
+    
+    /// Check the integrity of the locked secured folders with the saved keys
+    pub fn start_integrity_check(&mut self) {
+        let keys: Vec<EncryptionKey> = self.keys.saved_keys().iter().map(|(_, key)| key.clone()).collect();
+        self.folders.check_integrity(&keys);
+    }
+    
+    /// Create a deployment bundle with the saved keys and current policy for another machine
+    pub fn create_deployment_bundle(&mut self) {
+        if !self.key_management_allowed() {
+            self.show_error(&tr("error-export-disabled"));
+            return;
+        }
+        
+        if self.keys.saved_keys().is_empty() {
+            self.show_error(&tr("error-deploy-no-keys"));
+            return;
+        }
+        
+        let machine_identity = match deployment::decode_identity(&self.deployment_target_identity) {
+            Ok(identity) => identity,
+            Err(e) => {
+                self.show_error(&e.to_string());
+                return;
+            }
+        };
+        
+        let contents = BundleContents {
+            policy: get_policy().clone(),
+            keys: self.keys.saved_keys().iter()
+                .map(|(name, key)| BundledKey { name: name.clone(), key: key.to_base64() })
+                .collect(),
+        };
+        
+        if let Some(path) = FileDialog::new()
+            .set_title("Save Deployment Bundle")
+            .set_file_name("deployment.bundle")
+            .save_file() {
+            let result = DeploymentBundle::create(&machine_identity, &contents)
+                .and_then(|bundle| bundle.save(&path).map(|_| bundle));
+            
+            match result {
+                Ok(bundle) => {
+                    self.logger.log_success(
+                        "Create Deployment",
+                        &path.to_string_lossy(),
+                        &format!("Created deployment bundle {} with {} key(s)", bundle.bundle_id, contents.keys.len())
+                    ).ok();
+                    self.show_status(&tr_args("status-deployment-saved", &[("path", path.display().to_string().into())]));
+                },
+                Err(e) => self.show_error(&tr_args("error-deployment-create", &[("error", e.to_string().into())])),
+            }
+        }
+    }
+    
+    /// Derive a new key from the passphrase entered on the key management screen
+    pub fn derive_passphrase_key(&mut self) {
+        if self.new_key_name.is_empty() {
+            self.show_error(&tr("error-key-name"));
+            return;
+        }
+        if *self.new_key_passphrase != *self.new_key_passphrase_confirm {
+            self.show_error(&tr("error-passphrases-differ"));
+            return;
+        }
+        
+        let name = self.new_key_name.clone();
+        self.keys.derive_key(&name, &self.new_key_passphrase);
+        if self.keys.current_key_name().as_deref() == Some(name.as_str()) {
+            self.new_key_name.clear();
+            self.new_key_passphrase = Zeroizing::new(String::new());
+            self.new_key_passphrase_confirm = Zeroizing::new(String::new());
+        }
+    }
+    
+    /// Derive the key of an encrypted file from the passphrase entered on the key management screen
+    pub fn derive_key_from_file(&mut self) {
+        if self.new_key_passphrase.is_empty() {
+            self.show_error(&tr("error-file-passphrase"));
+            return;
+        }
+        
+        if let Some(path) = FileDialog::new()
+            .set_title("Select a File Encrypted with the Passphrase")
+            .pick_file() {
+            let name = if self.new_key_name.is_empty() {
+                "Passphrase Key".to_string()
+            } else {
+                self.new_key_name.clone()
+            };
+            
+            self.keys.derive_key_for_file(&name, &self.new_key_passphrase, &path);
+            if self.keys.current_key_name().as_deref() == Some(name.as_str()) {
+                self.new_key_name.clear();
+                self.new_key_passphrase = Zeroizing::new(String::new());
+                self.new_key_passphrase_confirm = Zeroizing::new(String::new());
+            }
+        }
+    }
+    
+    /// Set the expiry date entered on the key management screen for the chosen key, or
+    /// clear it if the date is empty
+    pub fn set_key_expiry(&mut self) {
+        let index = match self.expiry_key {
+            Some(index) => index,
+            None => {
+                self.show_error(&tr("error-expiry-key"));
+                return;
+            }
+        };
+        
+        let date = self.expiry_date.trim();
+        let expires_on = if date.is_empty() {
+            None
+        } else {
+            match NaiveDate::parse_from_str(date, keystore::DATE_FORMAT) {
+                Ok(date) => Some(date),
+                Err(_) => {
+                    self.show_error(&tr("error-expiry-date"));
+                    return;
+                }
+            }
+        };
+        
+        self.keys.set_expiry(index, expires_on);
+        self.expiry_date.clear();
+    }
+    
+    /// Turn on key backups with the backup passphrase entered on the key management screen
+    pub fn enable_key_backups(&mut self) {
+        if *self.backup_passphrase != *self.backup_passphrase_confirm {
+            self.show_error(&tr("error-passphrases-differ"));
+            return;
+        }
+        
+        if self.keys.set_backup_passphrase(&self.backup_passphrase) {
+            self.backup_passphrase = Zeroizing::new(String::new());
+            self.backup_passphrase_confirm = Zeroizing::new(String::new());
+        }
+    }
+    
+    /// Restore the key backup selected on the key management screen
+    pub fn restore_key_backup(&mut self) {
+        let generation = match self.selected_backup {
+            Some(generation) => generation,
+            None => {
+                self.show_error(&tr("error-select-backup"));
+                return;
+            }
+        };
+        if self.backup_passphrase.is_empty() {
+            self.show_error(&tr("error-backup-passphrase"));
+            return;
+        }
+        
+        self.keys.restore_backup(generation, &self.backup_passphrase);
+        self.backup_passphrase = Zeroizing::new(String::new());
+        self.selected_backup = None;
+    }
+    
+    /// Export a key ceremony report with the shares and their custodians
+    pub fn export_ceremony_report(&mut self) {
+        if let Some(path) = FileDialog::new()
+            .set_title("Save Ceremony Report")
+            .set_file_name("ceremony_report.txt")
+            .save_file() {
+            self.keys.write_ceremony_report(&path);
+        }
+    }
+    
     /// Select files using a file dialog
     pub fn select_files(&mut self) {
         let mut dialog = FileDialog::new();
         
-        if self.batch_mode {
+        if self.operations.batch_mode {
             dialog = dialog.set_title("Select Files to Process");
         } else {
             dialog = dialog.set_title("Select File to Process");
         }
         
-        if self.batch_mode {
+        if self.operations.batch_mode {
             if let Some(files) = dialog.pick_files() {
-                self.selected_files = files;
-                self.show_status(&format!("Selected {} file(s)", self.selected_files.len()));
+                self.operations.select_files(files);
             }
         } else {
             if let Some(file) = dialog.pick_file() {
-                self.selected_files = vec![file];
-                self.show_status("Selected 1 file");
+                self.operations.select_files(vec![file]);
             }
         }
     }
     
+    /// Select every file in a folder and its subfolders using a folder dialog
+    pub fn select_folder(&mut self, operation_type: FileOperationType) {
+        if let Some(folder) = FileDialog::new()
+            .set_title("Select Folder to Process")
+            .pick_folder() {
+            self.operations.batch_mode = true;
+            self.operations.select_folder(folder, operation_type);
+        }
+    }
+    
+    /// Pick an encrypted file received with a transfer and decrypt it with the
+    /// reconstructed transfer key
+    pub fn decrypt_received_file(&mut self) {
+        if let Some(file) = FileDialog::new()
+            .set_title("Select Encrypted File to Decrypt")
+            .add_filter("Encrypted files", &["encrypted", archive::ARCHIVE_EXTENSION, file_names::AGE_EXTENSION, "gpg", "pgp", "asc"])
+            .add_filter("All files", &["*"])
+            .pick_file() {
+            self.decrypt_received(file);
+        }
+    }
+    
+    /// Decrypt a received file next to it with the selected key (the transfer key once
+    /// it is reconstructed); its folder is opened when the decryption finishes
+    ///
+    /// The usual decryption checks and the audit log entry apply, as on the Decrypt
+    /// screen.
+    pub fn decrypt_received(&mut self, file: PathBuf) {
+        if self.operations.overall_progress().is_some() {
+            self.show_error(&tr("error-operation-running"));
+            return;
+        }
+        if self.operations.decrypt_reason_missing() {
+            self.show_error(&tr("error-decrypt-reason"));
+            return;
+        }
+        let key = match self.keys.current_key() {
+            Some(key) => key.clone(),
+            None => {
+                self.show_error(&tr("error-transfer-key-first"));
+                return;
+            }
+        };
+        
+        // Decrypted files never replace existing ones, so a file left from an earlier
+        // attempt would make the decryption fail
+        let folder = in_place::original_dir(&file);
+        let name = if archive::is_archive(&file) {
+            archive::extracted_name(&file, &key)
+        } else {
+            file_names::restored_name(&file, &key)
+        };
+        let output = match name {
+            Some(name) => folder.join(name.name),
+            None => return,
+        };
+        if output.exists() {
+            self.show_error(&tr_args("error-output-exists", &[("path", output.display().to_string().into())]));
+            return;
+        }
+        
+        self.operations.batch_mode = false;
+        self.operations.select_files(vec![file]);
+        self.operations.set_output_dir(folder);
+        self.begin_decrypt();
+        
+        // The decryption may not have started (e.g. a reason is needed first)
+        if self.operations.overall_progress().is_some() {
+            self.received_output = Some(output);
+        }
+    }
+    
+    /// Report a finished decrypt-and-open, and open the folder of the decrypted file
+    pub fn finish_received_decryption(&mut self) {
+        let output = match self.received_output.take() {
+            Some(output) => output,
+            None => return,
+        };
+        
+        if output.exists() {
+            self.show_status(&tr_args("status-decrypted-to", &[("path", output.display().to_string().into())]));
+            self.folder_to_open = output.parent().map(PathBuf::from);
+        } else {
+            self.show_error(&tr("error-received-decrypt"));
+        }
+    }
+    
+    /// Load the keys saved in earlier sessions
+    pub fn load_saved_keys(&mut self) {
+        self.keys.load_keystore(keystore::default_path());
+        self.show_service_events();
+    }
+    
+    /// Select the files CRUSTy was started with, or that a later start of CRUSTy handed
+    /// to this window, and show the screen that processes them (decryption when they
+    /// are all encrypted, otherwise encryption)
+    pub fn open_files(&mut self, files: Vec<PathBuf>) {
+        let (found, missing): (Vec<PathBuf>, Vec<PathBuf>) = files.into_iter()
+            .partition(|file| file.is_file());
+        
+        if let Some(file) = missing.first() {
+            self.show_error(&tr_args("error-file-not-found", &[("path", file.display().to_string().into())]));
+        }
+        if found.is_empty() {
+            return;
+        }
+        
+        if let [file] = found.as_slice() {
+            self.open_with_default_action(file.clone());
+            return;
+        }
+        self.operations.batch_mode = true;
+        self.state = if found.iter().all(|file| catalog::is_encrypted_file(file)) {
+            AppState::Decrypting
+        } else {
+            AppState::Encrypting
+        };
+        self.operations.select_files(found);
+    }
+    
+    /// Select a file, then run the action set in the settings for the extension of the
+    /// name it decrypts to (files without an action are shown on their screen)
+    fn open_with_default_action(&mut self, file: PathBuf) {
+        let original_name = match self.keys.current_key() {
+            Some(key) => file_names::restored_name(&file, key),
+            None => file_names::decrypted_name(&file),
+        };
+        let action = original_name
+            .map_or(OpenAction::Select, |name| self.settings.open_action(Path::new(&name.name)));
+        
+        let encrypted = catalog::is_encrypted_file(&file);
+        self.operations.select_files(vec![file]);
+        match action {
+            OpenAction::Select => {
+                self.state = if encrypted { AppState::Decrypting } else { AppState::Encrypting };
+            },
+            OpenAction::ViewSecurely => {
+                self.state = AppState::Decrypting;
+                self.view_securely();
+            },
+            OpenAction::Decrypt => {
+                self.operations.set_operation(FileOperation::Decrypt);
+                self.state = AppState::Decrypting;
+                self.begin_decrypt();
+            },
+        }
+    }
+    
+    /// Select a file from the history again, with the output folder it was saved in
+    /// last time, and show the screen of its operation
+    pub fn process_again(&mut self, entry: HistoryEntry) {
+        if !entry.input.is_file() {
+            self.show_error(&tr_args("error-file-not-found", &[("path", entry.input.display().to_string().into())]));
+            return;
+        }
+        
+        self.operations.batch_mode = false;
+        self.operations.select_files(vec![entry.input]);
+        if let Some(folder) = entry.output_folder.filter(|folder| folder.is_dir()) {
+            self.operations.set_output_dir(folder);
+        }
+        self.state = match entry.operation {
+            HistoryOperation::Encrypt => AppState::Encrypting,
+            // An upgraded file is still encrypted
+            HistoryOperation::Decrypt | HistoryOperation::Migrate => AppState::Decrypting,
+        };
+        self.show_service_events();
+    }
+    
     /// Select output directory using a file dialog
     pub fn select_output_dir(&mut self) {
         if let Some(dir) = FileDialog::new()
             .set_title("Select Output Directory")
             .pick_folder() {
-            self.output_dir = Some(dir.clone());
-            self.show_status(&format!("Selected output directory: {}", dir.display()));
+            self.operations.set_output_dir(dir);
+        }
+    }
+    
+    /// Choose a folder to add to the secured folders
+    pub fn add_secured_folder(&mut self) {
+        if let Some(dir) = FileDialog::new()
+            .set_title("Select Folder to Secure")
+            .pick_folder() {
+            self.folders.add_folder(dir);
+        }
+    }
+    
+    /// Lock a secured folder with the key bound to it (or else the current key)
+    pub fn lock_secured_folder(&mut self, index: usize) {
+        match self.secured_folder_key(index, true) {
+            Ok(key) => self.folders.lock(index, key),
+            Err(e) => self.show_error(&e),
+        }
+    }
+    
+    /// Unlock a secured folder with the key it was locked with (or else the current key)
+    pub fn unlock_secured_folder(&mut self, index: usize) {
+        match self.secured_folder_key(index, false) {
+            Ok(key) => self.folders.unlock(index, key),
+            Err(e) => self.show_error(&e),
+        }
+    }
+    
+    /// Lock every secured folder that is not locked, or unlock every locked folder, one
+    /// after the other
+    ///
+    /// Folders whose key is not available are skipped and reported.
+    pub fn lock_all_secured_folders(&mut self, lock: bool) {
+        let mut jobs = Vec::new();
+        let mut skipped = Vec::new();
+        for index in 0..self.folders.folders().len() {
+            let to_do = match self.folders.status(index) {
+                FolderStatus::Unlocked => lock,
+                FolderStatus::Locked => !lock,
+                FolderStatus::PartlyLocked { .. } => true,
+                FolderStatus::Empty | FolderStatus::Missing => false,
+            };
+            if !to_do {
+                continue;
+            }
+            match self.secured_folder_key(index, lock) {
+                Ok(key) => jobs.push((index, key)),
+                Err(e) => skipped.push(e),
+            }
+        }
+        
+        if jobs.is_empty() && skipped.is_empty() {
+            self.show_status(&tr(if lock { "status-secured-all-locked" } else { "status-secured-all-unlocked" }));
+            return;
+        }
+        self.folders.queue(jobs, lock);
+        self.show_service_events();
+        if let Some(first) = skipped.first() {
+            self.show_error(&tr_args("error-secured-skipped", &[("count", skipped.len().into()), ("error", first.as_str().into())]));
         }
     }
     
-    /// Generate a new encryption key
-    pub fn generate_key(&mut self, name: &str) {
-        let key = EncryptionKey::generate();
-        self.current_key = Some(key.clone());
-        self.saved_keys.push((name.to_string(), key));
-        self.show_status(&format!("Generated new key: {}", name));
+    /// Get the key to lock or unlock a secured folder with
+    ///
+    /// A folder is locked with the key bound to it and unlocked with the key it was
+    /// locked with; without one, the current key is used.
+    ///
+    /// # Returns
+    /// * `Result<EncryptionKey, String>` - The key, or why it is not available
+    fn secured_folder_key(&self, index: usize, lock: bool) -> Result<EncryptionKey, String> {
+        let folder = self.folders.folders().get(index)
+            .ok_or_else(|| tr("error-secured-folder-gone"))?;
+        let fingerprint = if lock { folder.bound_key.as_deref() } else { folder.unlock_key() };
+        
+        match fingerprint {
+            Some(fingerprint) => self.keys.saved_keys().iter()
+                .map(|(_, key)| key)
+                .chain(self.keys.current_key())
+                .find(|key| key.fingerprint() == fingerprint)
+                .cloned()
+                .ok_or_else(|| tr_args(
+                    if lock { "error-secured-folder-bound-key" } else { "error-secured-folder-locked-key" },
+                    &[("folder", folder.path.display().to_string().into()), ("fingerprint", fingerprint.into())]
+                )),
+            None => self.keys.current_key().cloned().ok_or_else(|| {
+                tr(if lock { "error-secured-folder-lock-key" } else { "error-secured-folder-unlock-key" })
+            }),
+        }
     }
     
-    /// Save the current key to a file
-    pub fn save_key_to_file(&mut self) {
-        if let Some(key) = &self.current_key {
+    /// Save a saved key to a file
+    pub fn save_key_to_file(&mut self, index: usize) {
+        if !self.key_management_allowed() {
+            self.show_error(&tr("error-export-disabled"));
+            return;
+        }
+        
+        if let Some((name, key)) = self.keys.saved_keys().get(index) {
+            if self.keys.in_credential_store(key) {
+                self.show_error(&tr_args("error-key-in-credential-store", &[("name", name.as_str().into())]));
+                return;
+            }
+            
             if let Some(path) = FileDialog::new()
                 .set_title("Save Encryption Key")
                 .set_file_name("encryption_key.key")
@@ -70,17 +1309,22 @@ impl CrustyApp {
                 // Save the key to a file
                 let key_base64 = key.to_base64();
                 match std::fs::write(&path, key_base64) {
-                    Ok(_) => self.show_status(&format!("Key saved to: {}", path.display())),
-                    Err(e) => self.show_error(&format!("Failed to save key: {}", e)),
+                    Ok(_) => self.show_status(&tr_args("status-key-saved-to", &[("path", path.display().to_string().into())])),
+                    Err(e) => self.show_error(&tr_args("error-key-save", &[("error", e.to_string().into())])),
                 }
             }
         } else {
-            self.show_error("No key selected");
+            self.show_error(&tr("error-no-key-selected"));
         }
     }
     
     /// Load a key from a file
     pub fn load_key_from_file(&mut self) {
+        if !self.key_management_allowed() {
+            self.show_error(&tr("error-import-disabled"));
+            return;
+        }
+        
         if let Some(path) = FileDialog::new()
             .set_title("Load Encryption Key")
             .add_filter("Key Files", &["key"])
@@ -96,118 +1340,391 @@ impl CrustyApp {
                                 .unwrap_or("Loaded Key")
                                 .to_string();
                             
-                            self.current_key = Some(key.clone());
-                            self.saved_keys.push((name.clone(), key));
-                            self.show_status(&format!("Loaded key: {}", name));
+                            let provenance = KeyProvenance::new("Loaded from a key file")
+                                .with_origin(&path.to_string_lossy());
+                            if self.keys.add_key(&name, key, provenance) {
+                                self.show_status(&tr_args("status-key-loaded", &[("name", name.as_str().into())]));
+                            }
                         },
-                        Err(e) => self.show_error(&format!("Failed to load key: {}", e)),
+                        Err(e) => self.show_error(&tr_args("error-key-load", &[("error", e.to_string().into())])),
                     }
                 },
-                Err(e) => self.show_error(&format!("Failed to read key file: {}", e)),
+                Err(e) => self.show_error(&tr_args("error-key-file-read", &[("error", e.to_string().into())])),
             }
         }
     }
     
-    /// Add a file entry to the file list
-    pub fn add_file_entry(&mut self, path: PathBuf, operation_type: FileOperationType) {
-        let entry = FileEntry::new(path, operation_type);
-        self.file_entries.push(entry);
+    /// Export a saved key as an age identity file, for the age CLI
+    pub fn export_age_identity(&mut self, index: usize) {
+        if !self.key_management_allowed() {
+            self.show_error(&tr("error-export-disabled"));
+            return;
+        }
+        
+        if let Some((name, key)) = self.keys.saved_keys().get(index) {
+            if self.keys.in_credential_store(key) {
+                self.show_error(&tr_args("error-key-in-credential-store", &[("name", name.as_str().into())]));
+                return;
+            }
+            
+            if let Some(path) = FileDialog::new()
+                .set_title("Export age Identity")
+                .set_file_name(format!("{}.txt", name))
+                .save_file() {
+                match std::fs::write(&path, age_format::identity_file(key).as_bytes()) {
+                    Ok(_) => self.show_status(&tr_args("status-age-identity-saved", &[("path", path.display().to_string().into())])),
+                    Err(e) => self.show_error(&tr_args("error-age-identity-save", &[("error", e.to_string().into())])),
+                }
+            }
+        } else {
+            self.show_error(&tr("error-no-key-selected"));
+        }
     }
     
-    /// Update file status
-    pub fn update_file_status(&mut self, index: usize, status: FileStatus) {
-        if index < self.file_entries.len() {
-            self.file_entries[index].status = status;
+    /// Import the identities in an age identity file (e.g. from `age-keygen`) as keys
+    pub fn import_age_identities(&mut self) {
+        if !self.key_management_allowed() {
+            self.show_error(&tr("error-import-disabled"));
+            return;
+        }
+        
+        if let Some(path) = FileDialog::new()
+            .set_title("Import age Identities")
+            .add_filter("age identity files", &["txt", "key"])
+            .add_filter("All files", &["*"])
+            .pick_file() {
+            let keys = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| age_format::parse_identity_file(&contents).map_err(|e| e.to_string()));
+            match keys {
+                Ok(keys) => {
+                    let stem = path.file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("age identity")
+                        .to_string();
+                    let count = keys.len();
+                    for (i, key) in keys.into_iter().enumerate() {
+                        let name = if count == 1 { stem.clone() } else { format!("{} {}", stem, i + 1) };
+                        let provenance = KeyProvenance::new("Imported from an age identity file")
+                            .with_origin(&path.to_string_lossy());
+                        if !self.keys.add_key(&name, key, provenance) {
+                            return;
+                        }
+                    }
+                    self.show_status(&tr_args("status-age-identities-imported", &[("count", count.into())]));
+                },
+                Err(e) => self.show_error(&tr_args("error-age-identities-import", &[("error", e.to_string().into())])),
+            }
         }
     }
     
-    /// Set file progress
-    pub fn set_file_progress(&mut self, index: usize, progress: f32) {
-        if index < self.file_entries.len() {
-            self.file_entries[index].set_progress(progress);
+    /// Save the age recipient entered in the key manager
+    pub fn add_age_recipient(&mut self) {
+        let name = self.age_recipient_name.trim().to_string();
+        if name.is_empty() {
+            self.show_error(&tr("error-age-recipient-name"));
+            return;
+        }
+        
+        match age_format::parse_recipient(&self.age_recipient_input) {
+            Ok(recipient) => {
+                let recipient = recipient.to_string();
+                if self.settings.age_recipients.iter().any(|saved| saved.recipient == recipient) {
+                    self.show_error(&tr("error-age-recipient-saved"));
+                    return;
+                }
+                self.settings.age_recipients.push(AgeRecipient { name: name.clone(), recipient });
+                self.save_settings();
+                self.age_recipient_name.clear();
+                self.age_recipient_input.clear();
+                self.show_status(&tr_args("status-age-recipient-saved", &[("name", name.as_str().into())]));
+            },
+            Err(e) => self.show_error(&e.to_string()),
         }
     }
     
-    /// Set file completed
-    pub fn set_file_completed(&mut self, index: usize, result: String) {
-        if index < self.file_entries.len() {
-            self.file_entries[index].set_completed(result);
+    /// Remove a saved age recipient
+    pub fn remove_age_recipient(&mut self, index: usize) {
+        if index < self.settings.age_recipients.len() {
+            let removed = self.settings.age_recipients.remove(index);
+            self.operations.age_recipients.retain(|recipient| *recipient != removed.recipient);
+            self.save_settings();
         }
     }
     
-    /// Set file failed
-    pub fn set_file_failed(&mut self, index: usize, error: String) {
-        if index < self.file_entries.len() {
-            self.file_entries[index].set_failed(error);
+    /// Choose which saved age recipients age files are also encrypted to
+    pub fn show_age_recipient_choice(&mut self, ui: &mut eframe::egui::Ui) {
+        ui.label(tr("recipients-age-choice"));
+        if self.settings.age_recipients.is_empty() {
+            ui.label(eframe::egui::RichText::new(tr("recipients-no-age")).weak());
+        }
+        for saved in &self.settings.age_recipients {
+            let mut selected = self.operations.age_recipients.contains(&saved.recipient);
+            if ui.checkbox(&mut selected, &saved.name).on_hover_text(&saved.recipient).changed() {
+                if selected {
+                    self.operations.age_recipients.push(saved.recipient.clone());
+                } else {
+                    self.operations.age_recipients.retain(|recipient| *recipient != saved.recipient);
+                }
+            }
         }
     }
     
-    /// Remove a file entry from the file list
-    pub fn remove_file_entry(&mut self, index: usize) {
-        if index < self.file_entries.len() {
-            self.file_entries.remove(index);
+    /// Enter the recipients a file is encrypted for, adding saved age recipients by name
+    pub fn show_recipient_list(&mut self, ui: &mut eframe::egui::Ui) {
+        ui.label(tr("recipients-list"));
+        ui.add(eframe::egui::TextEdit::multiline(&mut self.operations.recipient_list)
+            .hint_text("alice@example.com, bob@example.com")
+            .desired_rows(3)
+            .desired_width(400.0));
+        
+        if !self.settings.age_recipients.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(tr("recipients-add-saved"));
+                for saved in &self.settings.age_recipients {
+                    if ui.button(&saved.name).on_hover_text(&saved.recipient).clicked()
+                        && !self.operations.recipient_list.contains(&saved.recipient) {
+                        if !self.operations.recipient_list.trim().is_empty() {
+                            self.operations.recipient_list.push('\n');
+                        }
+                        self.operations.recipient_list.push_str(&saved.recipient);
+                    }
+                }
+            });
         }
+        
+        match recipients::parse_list(&self.operations.recipient_list) {
+            Ok(recipients) => ui.label(eframe::egui::RichText::new(tr_args("recipients-count", &[("count", recipients.len().into())])).weak()),
+            Err(e) => ui.label(eframe::egui::RichText::new(e.to_string()).color(self.theme.error)),
+        };
     }
     
-    /// Clear all file entries
-    pub fn clear_file_entries(&mut self) {
-        self.file_entries.clear();
+    /// Export the OpenPGP public key of a saved key, for `gpg --import`
+    pub fn export_openpgp_key(&mut self, index: usize) {
+        if let Some((name, key)) = self.keys.saved_keys().get(index) {
+            if let Some(path) = FileDialog::new()
+                .set_title("Export OpenPGP Public Key")
+                .set_file_name(format!("{}.asc", name))
+                .save_file() {
+                match std::fs::write(&path, openpgp::public_key_file(key, name)) {
+                    Ok(_) => self.show_status(&tr_args("status-openpgp-key-saved-to", &[("path", path.display().to_string().into())])),
+                    Err(e) => self.show_error(&tr_args("error-openpgp-key-save", &[("error", e.to_string().into())])),
+                }
+            }
+        } else {
+            self.show_error(&tr("error-no-key-selected"));
+        }
     }
     
-    /// Show the file list in the UI
-    pub fn show_file_list(&mut self, ui: &mut eframe::egui::Ui) {
-        if self.file_entries.is_empty() {
-            ui.label("No files in the list");
+    /// Save someone else's OpenPGP public key (e.g. from `gpg --export --armor`) to
+    /// encrypt files for them
+    pub fn import_openpgp_key(&mut self) {
+        if let Some(path) = FileDialog::new()
+            .set_title("Import OpenPGP Public Key")
+            .add_filter("OpenPGP keys", &["asc", "gpg", "pgp", "pub", "key"])
+            .add_filter("All files", &["*"])
+            .pick_file() {
+            let result = std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| {
+                    let certificate = openpgp::read_certificate(&data).map_err(|e| e.to_string())?;
+                    Ok((certificate, data))
+                });
+            match result {
+                Ok((certificate, data)) => {
+                    if self.settings.openpgp_recipients.iter().any(|saved| saved.fingerprint == certificate.fingerprint) {
+                        self.show_error(&tr("error-openpgp-key-saved"));
+                        return;
+                    }
+                    self.show_status(&tr_args("status-openpgp-key-saved", &[("user", certificate.user_id.as_str().into()), ("fingerprint", certificate.fingerprint.as_str().into())]));
+                    self.settings.openpgp_recipients.push(OpenPgpRecipient {
+                        name: certificate.user_id,
+                        fingerprint: certificate.fingerprint,
+                        certificate: openpgp::armored_certificate(&data),
+                    });
+                    self.save_settings();
+                },
+                Err(e) => self.show_error(&tr_args("error-openpgp-key-import", &[("error", e.to_string().into())])),
+            }
+        }
+    }
+    
+    /// Remove a saved OpenPGP public key
+    pub fn remove_openpgp_recipient(&mut self, index: usize) {
+        if index < self.settings.openpgp_recipients.len() {
+            let removed = self.settings.openpgp_recipients.remove(index);
+            self.operations.openpgp_recipients.retain(|certificate| *certificate != removed.certificate);
+            self.save_settings();
+        }
+    }
+    
+    /// Choose which saved OpenPGP keys OpenPGP messages are also encrypted to
+    pub fn show_openpgp_recipient_choice(&mut self, ui: &mut eframe::egui::Ui) {
+        ui.label(tr("recipients-openpgp-choice"));
+        if self.settings.openpgp_recipients.is_empty() {
+            ui.label(eframe::egui::RichText::new(tr("recipients-no-openpgp")).weak());
+        }
+        for saved in &self.settings.openpgp_recipients {
+            let mut selected = self.operations.openpgp_recipients.contains(&saved.certificate);
+            if ui.checkbox(&mut selected, &saved.name).on_hover_text(&saved.fingerprint).changed() {
+                if selected {
+                    self.operations.openpgp_recipients.push(saved.certificate.clone());
+                } else {
+                    self.operations.openpgp_recipients.retain(|certificate| *certificate != saved.certificate);
+                }
+            }
+        }
+    }
+    
+    /// Read a paper backup from a photo or scan picked by the user
+    ///
+    /// # Returns
+    /// * `Option<Scan>` - What was read, or None if the dialog was cancelled or nothing
+    ///   valid was found (the error is shown)
+    fn scan_paper_backup(&mut self) -> Option<Scan> {
+        let path = FileDialog::new()
+            .set_title("Select a Photo or Scan of the Paper Backup")
+            .add_filter("Images", &["png", "jpg", "jpeg", "tif", "tiff", "bmp", "gif", "webp"])
+            .pick_file()?;
+        
+        match paper_import::scan_backup(&path) {
+            Ok(scan) => Some(scan),
+            Err(e) => {
+                self.show_error(&e.to_string());
+                None
+            }
+        }
+    }
+    
+    /// Status message for a share read from a paper backup
+    fn scanned_share_status(method: ScanMethod) -> String {
+        match method {
+            ScanMethod::QrCode => tr("status-share-scanned-qr"),
+            ScanMethod::Text => tr("status-share-scanned-text"),
+        }
+    }
+    
+    /// Import a key share from a photo or scan of its paper backup into the first empty
+    /// share field of the receive screen
+    pub fn import_share_from_scan(&mut self) {
+        match self.scan_paper_backup() {
+            Some(Scan { backup: ScannedBackup::Share(share), method }) => {
+                *self.transfer.empty_share_field() = share;
+                self.show_status(&Self::scanned_share_status(method));
+            },
+            Some(Scan { backup: ScannedBackup::Key(_), .. }) => {
+                self.show_error(&tr("error-scan-key-not-share-import"));
+            },
+            None => {},
+        }
+    }
+    
+    /// Verify a key share from a photo or scan of its paper backup
+    pub fn verify_share_from_scan(&mut self) {
+        match self.scan_paper_backup() {
+            Some(Scan { backup: ScannedBackup::Share(share), .. }) => {
+                self.keys.share_verification_input = share;
+                self.keys.verify_share();
+            },
+            Some(Scan { backup: ScannedBackup::Key(_), .. }) => {
+                self.show_error(&tr("error-scan-key-not-share"));
+            },
+            None => {},
+        }
+    }
+    
+    /// Import a key from a photo or scan of its paper backup (QR code only)
+    pub fn import_key_from_scan(&mut self) {
+        if !self.key_management_allowed() {
+            self.show_error(&tr("error-import-disabled"));
+            return;
+        }
+        
+        match self.scan_paper_backup() {
+            Some(Scan { backup: ScannedBackup::Key(key), .. }) => {
+                let name = "Scanned Key";
+                if self.keys.add_key(name, key, KeyProvenance::new("Imported from the QR code of a scanned key sheet")) {
+                    self.show_status(&tr_args("status-key-scanned", &[("name", name.into())]));
+                }
+            },
+            Some(Scan { backup: ScannedBackup::Share(_), .. }) => {
+                self.show_error(&tr("error-scan-share-not-key"));
+            },
+            None => {},
+        }
+    }
+    
+    /// Show the Pause or Resume button of the running operation (nothing when none is running)
+    pub fn show_pause_control(&mut self, ui: &mut eframe::egui::Ui) {
+        if self.operations.overall_progress().is_none() {
+            return;
+        }
+        
+        ui.horizontal(|ui| {
+            if self.operations.is_paused() {
+                if ui.button(tr("progress-resume")).clicked() {
+                    self.operations.resume();
+                }
+                ui.label(eframe::egui::RichText::new(tr("progress-paused")).color(self.theme.accent));
+            } else if ui.button(tr("progress-pause"))
+                .on_hover_text(tr("progress-pause-hint"))
+                .clicked() {
+                self.operations.pause();
+            }
+        });
+    }
+    
+    /// Show what the embedded device reports about the job it is running
+    pub fn show_device_progress(&self, ui: &mut eframe::egui::Ui) {
+        if let Some(device) = self.operations.device_progress() {
+            ui.label(eframe::egui::RichText::new(tr_args("device-progress", &[
+                ("done", device.chunks_done.into()),
+                ("total", device.chunks_total.into()),
+                ("queued", device.queue_depth.into()),
+            ])).color(self.theme.text_secondary));
+        }
+    }
+    
+    /// Show how far the batch's files have been checked for duplicates, while they are
+    pub fn show_duplicate_scan(&self, ui: &mut eframe::egui::Ui) {
+        if let Some(fraction) = self.operations.duplicate_scan() {
+            ui.label(eframe::egui::RichText::new(tr_args("duplicate-scan-progress", &[
+                ("percent", locale::format_percent(fraction).into()),
+            ])).color(self.theme.text_secondary));
+        }
+    }
+    
+    /// Show the result of each file of the last operation, with the button to export
+    /// its report once it is finished (nothing before the first result)
+    pub fn show_operation_results(&mut self, ui: &mut eframe::egui::Ui) {
+        if self.operations.results().is_empty() {
             return;
         }
         
         ui.group(|ui| {
-            ui.heading("File List");
-            
-            let mut entry_to_remove = None;
-            
-            for (i, entry) in self.file_entries.iter().enumerate() {
-                ui.horizontal(|ui| {
-                    // File name
-                    ui.label(&entry.file_name());
-                    
-                    // Status with color
-                    ui.label(eframe::egui::RichText::new(entry.status_text())
-                        .color(entry.status_color(&self.theme)));
-                    
-                    // Operation type
-                    let op_text = match entry.operation_type {
-                        FileOperationType::Encrypt => "Encrypt",
-                        FileOperationType::Decrypt => "Decrypt",
-                        FileOperationType::None => "",
-                    };
-                    if !op_text.is_empty() {
-                        ui.label(op_text);
-                    }
-                    
-                    // Result or error message
-                    if let Some(result) = &entry.result {
-                        ui.label(eframe::egui::RichText::new(result).color(self.theme.success));
-                    } else if let Some(error) = &entry.error {
-                        ui.label(eframe::egui::RichText::new(error).color(self.theme.error));
-                    }
-                    
-                    // Remove button
-                    if ui.button("❌").clicked() {
-                        entry_to_remove = Some(i);
+            ui.horizontal(|ui| {
+                ui.heading(tr("ops-results"));
+                if ui.add_enabled(self.operations.report_ready(), eframe::egui::Button::new(tr("ops-export-report")))
+                    .on_hover_text(tr("ops-export-report-hint"))
+                    .clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .set_title("Export Report")
+                        .set_file_name("crusty_report.csv")
+                        .add_filter("CSV", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .save_file() {
+                        self.operations.save_report(&path);
+                        self.show_service_events();
                     }
-                });
-            }
-            
-            // Handle removal outside the loop
-            if let Some(index) = entry_to_remove {
-                self.remove_file_entry(index);
-            }
+                }
+            });
             
-            // Clear all button
-            if ui.button("Clear All").clicked() {
-                self.clear_file_entries();
-            }
+            eframe::egui::ScrollArea::vertical().id_source("operation_results").max_height(100.0).show(ui, |ui| {
+                for result in self.operations.results() {
+                    let color = if result.contains("Error") || result.contains("Failed") { self.theme.error } else { self.theme.success };
+                    ui.label(eframe::egui::RichText::new(result).color(color));
+                }
+            });
         });
     }
 }
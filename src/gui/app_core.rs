@@ -1,14 +1,30 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use eframe::egui::{self, Context};
+use zeroize::Zeroizing;
 
-use crate::encryption::EncryptionKey;
 use crate::gui::theme::AppTheme;
-use crate::gui::app_state::{AppState, EncryptionWorkflowStep};
-use crate::gui::file_list::{FileEntry, EnhancedFileList};
+use crate::gui::app_state::{AppState, BatchWarning, ColdStorageRestoreStep, ColdStorageStep, EncryptionWorkflowStep, KeyPinWarning, KeyRotationStep, MainTab, PinPrompt, PreflightSummary, ProxyDialog};
+use crate::gui::file_list::{FileEntry, FileOperationType, EnhancedFileList};
+use crate::gui::help;
+use crate::gui::utils;
+use crate::logger::{LogFilter, LogFormat, Logger, get_logger};
+use crate::settings::{OpenAction, Settings};
+use crate::locale::{self, Language};
+use crate::i18n::{tr, tr_args};
+use crate::policy::{self, get_policy};
+use crate::speed_history::SpeedHistory;
+use crate::run_summary::LastRuns;
+use crate::secured_folders::SecuredFolders;
+use crate::cues::{self, TaskbarProgress};
+use crate::services::{AuditService, BenchmarkService, CatalogService, ColdStorageRestoreService, ColdStorageService, ControlApiService, FolderService, KeyAgreementService, KeyRotationService, KeyService, OperationService, TransferService, ViewerService};
+use crate::instance::Handoff;
+use crate::session_watch::SessionWatch;
+use crate::folder_watch::{FolderWatch, WatchEvent};
 use crate::start_operation::FileOperation;
-use crate::logger::{Logger, get_logger};
+use crate::tray::{Tray, TrayAction};
+use crate::screen_capture::CaptureExclusion;
 
 
 use crate::gui::screens::*;
@@ -22,38 +38,123 @@ pub struct CrustyApp {
     pub status_time: Instant,
     pub error_message: Option<String>,
     pub error_time: Instant,
+    pub settings: Settings,
+    pub taskbar_progress: TaskbarProgress,
+    pub capture_exclusion: CaptureExclusion,
+    // Whether key shares were shown this frame (the window is then kept out of captures)
+    pub secrets_shown: bool,
+    pub operation_active: bool,
+    pub exit_requested: bool,
+    pub help_open: bool,
     
-    // File operations
-    pub selected_files: Vec<PathBuf>,
-    pub output_dir: Option<PathBuf>,
-    pub batch_mode: bool,
-    pub operation: FileOperation,
-    pub progress: Arc<Mutex<Vec<f32>>>,
-    pub operation_results: Vec<String>,
+    // Services
+    pub keys: KeyService,
+    pub operations: OperationService,
+    pub transfer: TransferService,
+    pub folders: FolderService,
+    pub cold_storage: ColdStorageService,
+    pub cold_storage_restore: ColdStorageRestoreService,
+    pub key_agreement: KeyAgreementService,
+    pub key_rotation: KeyRotationService,
+    pub audit: AuditService,
+    pub benchmark: BenchmarkService,
+    pub catalog: CatalogService,
+    pub viewer: ViewerService,
+    pub control_api: ControlApiService,
     
-    // File list
-    pub file_entries: Vec<FileEntry>,
-    
-    // Encryption
-    pub current_key: Option<EncryptionKey>,
-    pub saved_keys: Vec<(String, EncryptionKey)>,
+    // Key management forms
     pub new_key_name: String,
+    pub new_key_passphrase: Zeroizing<String>,
+    pub new_key_passphrase_confirm: Zeroizing<String>,
+    pub backup_passphrase: Zeroizing<String>,
+    pub backup_passphrase_confirm: Zeroizing<String>,
+    pub selected_backup: Option<usize>,
+    pub expiry_key: Option<usize>,
+    pub expiry_date: String,
+    pub deployment_target_identity: String,
+    pub machine_identity: Option<String>,
+    pub age_recipient_name: String,
+    pub age_recipient_input: String,
+    
+    // Extension to add a default action for (Settings menu)
+    pub open_action_extension: String,
+    
+    // Name to save the theme editor's colors under (Settings screen)
+    pub theme_name: String,
+    
+    
+    // Smartcard PIN prompt
+    pub pin_prompt: Option<PinPrompt>,
+    
+    // Proxy settings dialog (open when Some)
+    pub proxy_dialog: Option<ProxyDialog>,
+    
+    // Large batch warning (open when Some)
+    pub batch_warning: Option<BatchWarning>,
+    
+    // Warning before encrypting with another key than the folder's pinned key (open
+    // when Some and not confirmed)
+    pub key_pin_warning: Option<KeyPinWarning>,
+    
+    // Summary of a batch before it starts (open when Some and not confirmed)
+    pub preflight: Option<PreflightSummary>,
+    
+    // Decrypt-and-open of a received transfer: the file being decrypted, and the
+    // folder to open in the file manager once it is
+    pub received_output: Option<PathBuf>,
+    pub folder_to_open: Option<PathBuf>,
     
-    // Embedded backend options
-    pub use_embedded_backend: bool,
-    pub embedded_connection_type: crate::backend::ConnectionType,
-    pub embedded_device_id: String,
+    // Main screen tab
+    pub main_tab: MainTab,
     
     // Workflow
     pub encryption_workflow_step: EncryptionWorkflowStep,
     pub encryption_workflow_complete: bool,
-    
-    // Status tracking
-    pub last_status: Option<String>,
-    pub last_error: Option<String>,
+    pub cold_storage_step: ColdStorageStep,
+    pub cold_storage_restore_step: ColdStorageRestoreStep,
+    pub key_rotation_step: KeyRotationStep,
     
     // Logger
     pub logger: Arc<Logger>,
+    
+    // Entries shown and exported on the Logs screen
+    pub log_filter: LogFilter,
+    
+    // Files from later starts of CRUSTy (None when another copy was already running)
+    pub handoff: Option<Handoff>,
+    
+    // Session locks and suspends (None when not watched, e.g. in the headless GUI tests)
+    pub session_watch: Option<SessionWatch>,
+    
+    // Icon in the system tray (None when not shown, e.g. in the headless GUI tests)
+    pub tray: Option<Tray>,
+    
+    // Whether the window was minimized last frame (it is hidden to the tray when it
+    // becomes minimized)
+    pub window_minimized: bool,
+    
+    // Folders whose new files are encrypted (None while no folder is watched, e.g. when
+    // the keys are locked or in the headless GUI tests)
+    pub folder_watch: Option<FolderWatch>,
+    
+    // What happened in the watched folders, newest first, with the time
+    pub watch_activity: Vec<(String, WatchEvent)>,
+    
+    // Text CRUSTy copied to the clipboard, cleared when the keys are locked
+    pub clipboard_text: Option<Zeroizing<String>>,
+    
+    // Last keyboard or mouse input, for locking the keys when idle
+    pub last_input: Instant,
+    
+    // Folder the settings, log and other app data are kept in
+    pub data_dir: PathBuf,
+}
+
+/// Folder CRUSTy keeps its settings, log and other app data in
+pub fn default_data_dir() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("crusty");
+    path
 }
 
 // Implement AsRef<AppTheme> for CrustyApp to support EnhancedFileList trait
@@ -66,61 +167,225 @@ impl AsRef<AppTheme> for CrustyApp {
 // Implement AsMut<Vec<FileEntry>> for CrustyApp to support EnhancedFileList trait
 impl AsMut<Vec<FileEntry>> for CrustyApp {
     fn as_mut(&mut self) -> &mut Vec<FileEntry> {
-        &mut self.file_entries
+        self.operations.file_entries_mut()
     }
 }
 
 impl Default for CrustyApp {
     fn default() -> Self {
-        Self {
+        let data_dir = default_data_dir();
+        let settings = Settings::load_from(&Settings::path_in(&data_dir)).unwrap_or_else(|e| {
+            eprintln!("Failed to load settings, using defaults: {}", e);
+            Settings::default()
+        });
+        let mut app = Self::with_data_dir(data_dir, settings);
+        
+        // Changes to the saved keys are backed up once key backups are turned on
+        app.keys.load_backup_key();
+        
+        // Keys distributed by the administrator
+        app.keys.load_provisioned_keys();
+        app.keys.load_deployed_keys();
+        
+        // Shares are checked at every start so lost shares are found early
+        app.keys.check_share_verification();
+        
+        app.show_service_events();
+        app
+    }
+}
+
+impl CrustyApp {
+    /// Create the app with its data kept in a folder, and the given settings
+    ///
+    /// The speed history, last runs, secured folders and log are read from and written
+    /// to the folder, and the settings are saved there. Keys from the credential store
+    /// and the administrator are not loaded (see `Default`), so the headless GUI tests
+    /// can run in a temporary folder without touching the user's data.
+    pub fn with_data_dir(data_dir: PathBuf, settings: Settings) -> Self {
+        let mut app = Self {
             theme: AppTheme::default(),
             state: AppState::Dashboard,
             status_message: None,
             status_time: Instant::now(),
             error_message: None,
             error_time: Instant::now(),
+            settings,
+            taskbar_progress: TaskbarProgress::new(),
+            capture_exclusion: CaptureExclusion::new(),
+            secrets_shown: false,
+            operation_active: false,
+            exit_requested: false,
+            help_open: false,
             
-            selected_files: Vec::new(),
-            output_dir: None,
-            batch_mode: false,
-            operation: FileOperation::None,
-            progress: Arc::new(Mutex::new(Vec::new())),
-            operation_results: Vec::new(),
-            
-            file_entries: Vec::new(),
+            keys: KeyService::from_policy(),
+            operations: OperationService::new(),
+            transfer: TransferService::new(),
+            folders: FolderService::new(),
+            cold_storage: ColdStorageService::new(),
+            cold_storage_restore: ColdStorageRestoreService::new(),
+            key_agreement: KeyAgreementService::new(),
+            key_rotation: KeyRotationService::new(),
+            audit: AuditService::new(),
+            benchmark: BenchmarkService::new(),
+            catalog: CatalogService::new(),
+            viewer: ViewerService::new(),
+            control_api: ControlApiService::new(),
             
-            current_key: None,
-            saved_keys: Vec::new(),
             new_key_name: String::new(),
+            new_key_passphrase: Zeroizing::new(String::new()),
+            new_key_passphrase_confirm: Zeroizing::new(String::new()),
+            backup_passphrase: Zeroizing::new(String::new()),
+            backup_passphrase_confirm: Zeroizing::new(String::new()),
+            selected_backup: None,
+            expiry_key: None,
+            expiry_date: String::new(),
+            deployment_target_identity: String::new(),
+            machine_identity: None,
+            age_recipient_name: String::new(),
+            age_recipient_input: String::new(),
+            
+            open_action_extension: String::new(),
+            theme_name: String::new(),
+            
+            pin_prompt: None,
+            proxy_dialog: None,
+            batch_warning: None,
+            key_pin_warning: None,
+            preflight: None,
             
-            use_embedded_backend: false,
-            embedded_connection_type: crate::backend::ConnectionType::Usb,
-            embedded_device_id: String::new(),
+            received_output: None,
+            folder_to_open: None,
+            
+            main_tab: MainTab::RecentFiles,
             
             encryption_workflow_step: EncryptionWorkflowStep::Files,
             encryption_workflow_complete: false,
-            
-            last_status: None,
-            last_error: None,
+            cold_storage_step: ColdStorageStep::Folder,
+            cold_storage_restore_step: ColdStorageRestoreStep::Media,
+            key_rotation_step: KeyRotationStep::Files,
             
             logger: get_logger().unwrap_or_else(|| {
-                let mut log_path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
-                log_path.push("crusty");
-                log_path.push("logs");
+                let log_path = data_dir.join("logs");
                 std::fs::create_dir_all(&log_path).expect("Failed to create log directory");
-                log_path.push("operations.log");
                 
-                Arc::new(Logger::new(&log_path).expect("Failed to initialize logger"))
+                Arc::new(Logger::new(&log_path.join("operations.log")).expect("Failed to initialize logger"))
             }),
+            log_filter: LogFilter::default(),
+            
+            handoff: None,
+            session_watch: None,
+            tray: None,
+            window_minimized: false,
+            folder_watch: None,
+            watch_activity: Vec::new(),
+            clipboard_text: None,
+            last_input: Instant::now(),
+            data_dir,
+        };
+        
+        // Format numbers and dates for the selected language
+        locale::set_language(app.settings.language);
+        
+        // Colors, read size and the main screen's choices saved in the settings
+        app.apply_appearance_settings();
+        app.apply_operation_defaults();
+        
+        // Speeds of earlier operations, for time estimates
+        app.operations.load_speed_history(SpeedHistory::path_in(&app.data_dir));
+        
+        // Choices of the last batches, to show what changed before the next one
+        app.operations.load_last_runs(LastRuns::path_in(&app.data_dir));
+        
+        // Folders kept encrypted, and whether each is locked
+        app.folders.load_folders(SecuredFolders::path_in(&app.data_dir));
+        
+        app.show_service_events();
+        
+        // A policy file that cannot be read is not silently ignored
+        if let Some(error) = policy::load_error() {
+            app.show_error(&tr_args("error-policy-load", &[("error", error.into())]));
         }
+        app
     }
 }
 
 impl eframe::App for CrustyApp {
-    fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &Context, frame: &mut eframe::Frame) {
+        // Completion cues, so long operations are noticed without watching the window
+        let overall_progress = self.operations.overall_progress();
+        self.taskbar_progress.set(if self.settings.taskbar_progress { overall_progress } else { None });
+        if overall_progress.is_some() {
+            self.operation_active = true;
+        } else if self.operation_active {
+            self.operation_active = false;
+            if self.settings.completion_sound {
+                cues::play_completion_sound(self.settings.completion_sound_file.as_deref());
+            }
+            frame.request_user_attention(egui::UserAttentionType::Informational);
+        }
+        
+        if let Some(folder) = self.folder_to_open.take() {
+            utils::open_folder(&folder);
+        }
+        
+        // Files from CRUSTy started again while this window is open
+        if let Some(files) = self.handoff.as_ref().and_then(|handoff| handoff.take_files()) {
+            self.open_files(files);
+            frame.focus();
+        }
+        
+        // Quick actions from the tray icon, which stays while the window is hidden
+        if let Some(tray) = &mut self.tray {
+            let tooltip = tray_tooltip(self.operations.operation(), &self.operations.file_progress());
+            tray.set_status(tooltip, overall_progress.map(|p| (p.clamp(0.0, 1.0) * 100.0) as u32));
+        }
+        while let Some(action) = self.tray.as_ref().and_then(Tray::take_action) {
+            match action {
+                TrayAction::Open => {
+                    frame.set_visible(true);
+                    frame.set_minimized(false);
+                    frame.focus();
+                },
+                TrayAction::EncryptClipboard => self.encrypt_clipboard(),
+                TrayAction::Exit => self.exit_requested = true,
+            }
+        }
+        let minimized = frame.info().window_info.minimized;
+        if minimized && !self.window_minimized && self.tray.is_some() && self.settings.minimize_to_tray {
+            frame.set_visible(false);
+        }
+        self.window_minimized = minimized;
+        
+        // Folders whose new files are encrypted, watched while the keys are unlocked
+        self.update_folder_watch(ctx);
+        while let Some(event) = self.folder_watch.as_ref().and_then(FolderWatch::take_event) {
+            self.note_watch_event(event);
+        }
+        
+        self.show_ui(ctx);
+        
+        // Keep key shares out of screenshots and screen sharing
+        self.capture_exclusion.set(self.settings.protect_shares && self.secrets_shown);
+        
+        if self.exit_requested {
+            frame.close();
+        }
+    }
+}
+
+impl CrustyApp {
+    /// Show the application for one frame
+    ///
+    /// Everything except the window integration (completion cues, closing the window)
+    /// happens here, so the GUI can also be driven without a window.
+    pub fn show_ui(&mut self, ctx: &Context) {
         // Apply theme to context
         self.theme.apply_to_context(ctx);
         
+        // Set again by the screens that show key shares
+        self.secrets_shown = false;
+        
         // Handle status and error message timeouts
         let now = Instant::now();
         if let Some(_) = &self.status_message {
@@ -134,37 +399,358 @@ impl eframe::App for CrustyApp {
             }
         }
         
-        // Handle last status and error messages from closures
-        if let Some(status) = self.last_status.take() {
-            self.show_status(&status);
+        // Smartcard PINs and unwrapped keys do not outlive their session
+        self.keys.expire_token_session(self.operations.is_idle());
+        
+        // Lock the keys when the computer is locked or suspended, or after the idle time
+        if ctx.input(|i| !i.events.is_empty()) {
+            self.last_input = now;
         }
-        if let Some(error) = self.last_error.take() {
-            self.show_error(&error);
+        if let Some(event) = self.session_watch.as_ref().and_then(SessionWatch::take_event) {
+            if self.settings.auto_lock.on_session_lock {
+                self.lock_session(event.describe());
+            }
+        }
+        let idle_minutes = self.settings.auto_lock.idle_minutes;
+        if idle_minutes > 0 && !self.keys.is_locked() {
+            let idle_limit = Duration::from_secs(u64::from(idle_minutes) * 60);
+            let idle = now.duration_since(self.last_input);
+            if idle >= idle_limit {
+                self.lock_session(&format!("no activity for {} minutes", idle_minutes));
+            } else {
+                ctx.request_repaint_after(idle_limit - idle);
+            }
         }
         
         // Menu bar
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("Open").clicked() {
+                ui.menu_button(tr("menu-file"), |ui| {
+                    if ui.button(tr("menu-open")).clicked() {
                         self.select_files();
                         ui.close_menu();
                     }
-                    if ui.button("Exit").clicked() {
-                        _frame.close();
+                    if ui.button(tr("menu-open-folder")).clicked() {
+                        self.select_folder(FileOperationType::None);
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-cold-storage-export")).clicked() {
+                        self.state = AppState::ColdStorageExport;
+                        self.cold_storage_step = ColdStorageStep::Folder;
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-cold-storage-restore")).clicked() {
+                        self.state = AppState::ColdStorageRestore;
+                        self.cold_storage_restore_step = ColdStorageRestoreStep::Media;
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-audit")).clicked() {
+                        self.state = AppState::Audit;
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-export-catalog")).clicked() {
+                        self.state = AppState::Catalog;
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-benchmark")).clicked() {
+                        self.state = AppState::Benchmark;
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-lock-keys")).clicked() {
+                        self.lock_session("locked from the menu");
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-exit")).clicked() {
+                        self.exit_requested = true;
+                    }
+                });
+                
+                ui.menu_button(tr("menu-settings"), |ui| {
+                    if ui.button(tr("menu-settings-screen")).clicked() {
+                        self.state = AppState::Settings;
+                        ui.close_menu();
+                    }
+                    
+                    ui.separator();
+                    
+                    let mut changed = ui.checkbox(&mut self.settings.completion_sound, tr("menu-completion-sound")).changed();
+                    
+                    if self.settings.completion_sound {
+                        let sound_label = match &self.settings.completion_sound_file {
+                            Some(file) => tr_args("menu-sound-file", &[("file", file.file_name().unwrap_or_default().to_string_lossy().into())]),
+                            None => tr("menu-sound-system"),
+                        };
+                        ui.label(sound_label);
+                        
+                        if ui.button(tr("menu-choose-sound")).clicked() {
+                            if let Some(file) = rfd::FileDialog::new()
+                                .set_title("Select Completion Sound")
+                                .add_filter("Sound", &["wav", "ogg", "oga", "aiff"])
+                                .pick_file() {
+                                self.settings.completion_sound_file = Some(file);
+                                changed = true;
+                            }
+                            ui.close_menu();
+                        }
+                        
+                        if self.settings.completion_sound_file.is_some() && ui.button(tr("menu-use-system-sound")).clicked() {
+                            self.settings.completion_sound_file = None;
+                            changed = true;
+                            ui.close_menu();
+                        }
+                    }
+                    
+                    changed |= ui.checkbox(&mut self.settings.taskbar_progress, tr("menu-taskbar-progress")).changed();
+                    if ui.checkbox(&mut self.settings.tray_icon, tr("menu-tray-icon")).changed() {
+                        self.apply_tray_setting(ctx);
+                        changed = true;
+                    }
+                    if self.settings.tray_icon {
+                        changed |= ui.add_enabled(self.tray.is_some(), egui::Checkbox::new(&mut self.settings.minimize_to_tray, tr("menu-minimize-to-tray")))
+                            .on_hover_text(tr("menu-minimize-to-tray-hint"))
+                            .on_disabled_hover_text(tr("menu-tray-unavailable"))
+                            .changed();
+                    }
+                    changed |= ui.checkbox(&mut self.settings.protect_shares, tr("menu-protect-shares"))
+                        .on_hover_text(tr("menu-protect-shares-hint"))
+                        .changed();
+                    
+                    ui.separator();
+                    
+                    let auto_lock = &mut self.settings.auto_lock;
+                    changed |= ui.checkbox(&mut auto_lock.on_session_lock, tr("menu-lock-on-session-lock")).changed();
+                    ui.horizontal(|ui| {
+                        ui.label(tr("menu-lock-after"));
+                        changed |= ui.add(egui::DragValue::new(&mut auto_lock.idle_minutes)
+                            .clamp_range(0..=1440)
+                            .suffix(format!(" {}", tr("menu-idle-minutes")))).changed();
+                    }).response.on_hover_text(tr("menu-lock-idle-hint"));
+                    ui.horizontal(|ui| {
+                        ui.label(tr("menu-shred-viewed-after"));
+                        changed |= ui.add(egui::DragValue::new(&mut self.settings.viewer_minutes)
+                            .clamp_range(1..=1440)
+                            .suffix(format!(" {}", tr("menu-minutes")))).changed();
+                    }).response.on_hover_text(tr("menu-shred-viewed-hint"));
+                    ui.horizontal(|ui| {
+                        ui.label(tr("menu-check-folders-every"));
+                        changed |= ui.add(egui::DragValue::new(&mut self.settings.integrity_check_hours)
+                            .clamp_range(0..=720)
+                            .suffix(format!(" {}", tr("menu-hours")))).changed();
+                    }).response.on_hover_text(tr("menu-check-folders-hint"));
+                    
+                    ui.menu_button(tr("menu-open-actions"), |ui| {
+                        ui.label(tr("menu-open-actions-explained"));
+                        let mut remove = None;
+                        for (i, rule) in self.settings.open_actions.iter_mut().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!(".{}", rule.extension));
+                                egui::ComboBox::from_id_source(("open_action", i))
+                                    .selected_text(rule.action.name())
+                                    .show_ui(ui, |ui| {
+                                        for action in OpenAction::ALL {
+                                            changed |= ui.selectable_value(&mut rule.action, action, action.name()).changed();
+                                        }
+                                    });
+                                if ui.button(tr("button-remove")).clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                        }
+                        if let Some(index) = remove {
+                            self.settings.open_actions.remove(index);
+                            changed = true;
+                        }
+                        
+                        ui.horizontal(|ui| {
+                            ui.add(egui::TextEdit::singleline(&mut self.open_action_extension)
+                                .hint_text(tr("menu-extension-hint"))
+                                .desired_width(120.0));
+                            if ui.add_enabled(!self.open_action_extension.trim().is_empty(), egui::Button::new(tr("button-add"))).clicked() {
+                                self.settings.set_open_action(&self.open_action_extension, OpenAction::ViewSecurely);
+                                self.open_action_extension.clear();
+                                changed = true;
+                            }
+                        });
+                    }).response.on_hover_text(tr("menu-view-securely-hint"));
+                    
+                    ui.separator();
+                    
+                    let timeouts = &mut self.settings.timeouts;
+                    ui.label(tr("menu-stop-without-progress"));
+                    ui.horizontal(|ui| {
+                        changed |= ui.add(egui::DragValue::new(&mut timeouts.embedded_secs)
+                            .clamp_range(0..=86_400)
+                            .suffix(format!(" {}", tr("menu-seconds-device")))).changed();
+                        changed |= ui.add(egui::DragValue::new(&mut timeouts.local_secs)
+                            .clamp_range(0..=86_400)
+                            .suffix(format!(" {}", tr("menu-seconds-software")))).changed();
+                    }).response.on_hover_text(tr("menu-stop-hint"));
+                    changed |= ui.checkbox(&mut self.settings.low_memory, tr("menu-low-memory"))
+                        .on_hover_text(tr("menu-low-memory-hint"))
+                        .changed();
+                    
+                    ui.separator();
+                    
+                    changed |= ui.checkbox(&mut self.settings.preflight_summary, tr("menu-preflight-summary"))
+                        .on_hover_text(tr("menu-preflight-summary-hint"))
+                        .changed();
+                    let limits = &mut self.settings.batch_limits;
+                    changed |= ui.checkbox(&mut limits.enabled, tr("menu-batch-limits")).changed();
+                    if limits.enabled {
+                        ui.horizontal(|ui| {
+                            ui.label(tr("menu-batch-limits-over"));
+                            changed |= ui.add(egui::DragValue::new(&mut limits.max_total_gb)
+                                .clamp_range(1..=u32::MAX as u64)
+                                .suffix(" GB")).changed();
+                            ui.label(tr("menu-batch-limits-or"));
+                            changed |= ui.add(egui::DragValue::new(&mut limits.max_files)
+                                .clamp_range(1..=u32::MAX as usize)
+                                .suffix(format!(" {}", tr("menu-files")))).changed();
+                        });
+                    }
+                    
+                    ui.separator();
+                    
+                    let mut log_format = self.settings.log_format;
+                    egui::ComboBox::from_label(tr("menu-log-format"))
+                        .selected_text(log_format.name())
+                        .show_ui(ui, |ui| {
+                            for format in LogFormat::ALL {
+                                ui.selectable_value(&mut log_format, format, format.name());
+                            }
+                        })
+                        .response.on_hover_text(tr("menu-log-format-hint"));
+                    if log_format != self.settings.log_format {
+                        self.settings.log_format = log_format;
+                        self.logger.set_format(log_format);
+                        changed = true;
+                    }
+                    
+                    let system_log_required = get_policy().system_log;
+                    let mut system_log = self.settings.system_log || system_log_required;
+                    if ui.add_enabled(!system_log_required, egui::Checkbox::new(&mut system_log, tr("menu-system-log")))
+                        .on_hover_text(tr("menu-system-log-hint"))
+                        .on_disabled_hover_text(tr("menu-system-log-required"))
+                        .changed() {
+                        self.settings.system_log = system_log;
+                        self.apply_system_log_setting();
+                        changed = true;
+                    }
+                    
+                    let rotation = &mut self.settings.log_rotation;
+                    let mut rotation_changed = false;
+                    ui.horizontal(|ui| {
+                        ui.label(tr("menu-rotate-log-at"));
+                        rotation_changed |= ui.add(egui::DragValue::new(&mut rotation.max_size_mb)
+                            .clamp_range(0..=10_240)
+                            .suffix(" MB")).changed();
+                    }).response.on_hover_text(tr("menu-rotate-log-hint"));
+                    if rotation.max_size_mb > 0 {
+                        ui.horizontal(|ui| {
+                            ui.label(tr("menu-keep-old-logs"));
+                            rotation_changed |= ui.add(egui::DragValue::new(&mut rotation.max_files)
+                                .clamp_range(0..=1000)
+                                .suffix(format!(" {}", tr("menu-files")))).changed();
+                            ui.label(tr("menu-keep-old-logs-for"));
+                            rotation_changed |= ui.add(egui::DragValue::new(&mut rotation.max_age_days)
+                                .clamp_range(0..=3650)
+                                .suffix(format!(" {}", tr("menu-days")))).changed();
+                        }).response.on_hover_text(tr("menu-keep-old-logs-hint"));
+                        rotation_changed |= ui.checkbox(&mut rotation.compress, tr("menu-compress-old-logs")).changed();
+                    }
+                    if rotation_changed {
+                        if let Err(e) = self.logger.set_rotation(rotation.clone()) {
+                            self.show_error(&tr_args("error-old-logs-delete", &[("error", e.to_string().into())]));
+                        }
+                        changed = true;
+                    }
+                    
+                    if changed {
+                        self.save_settings();
+                    }
+                    
+                    ui.separator();
+                    
+                    let language_name = self.settings.language.map_or_else(|| tr("menu-system-language"), |language| language.name().to_string());
+                    egui::ComboBox::from_label(tr("menu-language"))
+                        .selected_text(language_name)
+                        .show_ui(ui, |ui| {
+                            let mut language = self.settings.language;
+                            ui.selectable_value(&mut language, None, tr("menu-system-language"));
+                            for option in Language::ALL {
+                                ui.selectable_value(&mut language, Some(option), option.name());
+                            }
+                            
+                            if language != self.settings.language {
+                                self.settings.language = language;
+                                locale::set_language(language);
+                                self.save_settings();
+                            }
+                        });
+                    
+                    ui.separator();
+                    
+                    if ui.checkbox(&mut self.settings.offline, tr("menu-offline")).changed() {
+                        self.set_offline(self.settings.offline);
+                    }
+                    
+                    if ui.button(tr("menu-network-proxy")).clicked() {
+                        self.open_proxy_dialog();
+                        ui.close_menu();
+                    }
+                    
+                    if !get_policy().disable_control_api {
+                        ui.separator();
+                        
+                        let api = &mut self.settings.control_api;
+                        let mut restart = ui.checkbox(&mut api.enabled, tr("menu-control-api"))
+                            .on_hover_text(tr("menu-control-api-hint"))
+                            .changed();
+                        if api.enabled {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("menu-control-api-port"));
+                                let response = ui.add(egui::DragValue::new(&mut api.port).clamp_range(1024..=65535));
+                                // Restarted once the port is chosen, not for every value dragged past
+                                restart |= response.drag_released() || response.lost_focus();
+                            });
+                            if let Some(port) = self.control_api.port() {
+                                ui.label(tr_args("menu-control-api-listening", &[("port", port.to_string().into())]));
+                            }
+                            if let Some(token) = self.control_api.token().map(str::to_string) {
+                                if ui.button(tr("menu-copy-api-token")).clicked() {
+                                    self.copy_to_clipboard(ui, token);
+                                    ui.close_menu();
+                                }
+                            }
+                        }
+                        if restart {
+                            self.save_settings();
+                            self.apply_control_api_setting();
+                        }
                     }
                 });
                 
-                ui.menu_button("Help", |ui| {
-                    if ui.button("About").clicked() {
+                ui.menu_button(tr("menu-help"), |ui| {
+                    if ui.button(tr("menu-help-f1")).clicked() {
+                        self.help_open = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("menu-about")).clicked() {
                         self.state = AppState::About;
                         ui.close_menu();
                     }
-                    if ui.button("View Logs").clicked() {
+                    if ui.button(tr("menu-view-logs")).clicked() {
                         self.state = AppState::Logs;
                         ui.close_menu();
                     }
                 });
+                
+                if self.settings.offline {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(egui::RichText::new(tr("menu-offline-badge")).strong().color(self.theme.error))
+                            .on_hover_text(tr("menu-offline-hint"));
+                    });
+                }
             });
         });
         
@@ -183,6 +769,12 @@ impl eframe::App for CrustyApp {
         
         // Main central panel
         egui::CentralPanel::default().show(ctx, |ui| {
+            // Locked keys hide every screen until they are unlocked
+            if self.keys.is_locked() {
+                self.show_locked(ui);
+                return;
+            }
+            
             // Display the current screen based on the application state
             match self.state {
                 AppState::Dashboard => self.show_dashboard(ui),
@@ -191,9 +783,155 @@ impl eframe::App for CrustyApp {
                 AppState::Encrypting => self.show_encrypt_screen(ui),
                 AppState::Decrypting => self.show_decrypt_screen(ui),
                 AppState::KeyManagement => self.show_key_management(ui),
+                AppState::SplitKeyManagement => self.show_split_key_management(ui),
+                AppState::TransferPreparation => self.show_transfer_preparation(ui),
+                AppState::TransferReceive => self.show_transfer_receive(ui),
+                AppState::ColdStorageExport => self.show_cold_storage_export(ui),
+                AppState::ColdStorageRestore => self.show_cold_storage_restore(ui),
+                AppState::KeyAgreement => self.show_key_agreement(ui),
+                AppState::KeyRotation => self.show_key_rotation(ui),
+                AppState::Audit => self.show_audit(ui),
+                AppState::Catalog => self.show_catalog(ui),
+                AppState::Benchmark => self.show_benchmark(ui),
+                AppState::Settings => self.show_settings(ui),
                 AppState::Logs => self.show_logs(ui),
                 AppState::About => self.show_about(ui),
             }
         });
+        
+        // Help for the current screen
+        if ctx.input(|i| i.key_pressed(egui::Key::F1)) {
+            self.help_open = !self.help_open;
+        }
+        egui::Window::new(tr("help-title"))
+            .open(&mut self.help_open)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(480.0).show(ui, |ui| {
+                    help::show_markdown(ui, help::screen_help(&self.state));
+                });
+            });
+        
+        // Smartcard PIN prompt
+        self.show_pin_prompt(ctx);
+        
+        // Keep the processing times of running files live
+        if self.operations.sync_file_progress() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Stop an operation whose backend stopped making progress
+        let timeout = self.settings.timeouts.for_backend(self.operations.running_backend());
+        if let Some(time_left) = self.operations.check_stalled(timeout) {
+            ctx.request_repaint_after(time_left);
+        }
+        
+        // Open the folder of a received file once it is decrypted
+        if self.received_output.is_some() && self.operations.overall_progress().is_none() {
+            self.finish_received_decryption();
+        }
+        
+        // Follow the secured folder being locked or unlocked
+        if self.folders.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Check the integrity of the locked secured folders when it is due
+        let interval = Duration::from_secs(u64::from(self.settings.integrity_check_hours) * 3600);
+        if let Some(wait) = self.folders.time_to_integrity_check(interval) {
+            if wait.is_zero() && !self.keys.is_locked() {
+                self.start_integrity_check();
+            } else if !wait.is_zero() {
+                ctx.request_repaint_after(wait);
+            }
+        }
+        
+        // Follow the cold-storage set being written
+        if self.cold_storage.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the cold-storage set being checked or restored
+        if self.cold_storage_restore.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the key agreement with a partner's computer
+        if self.key_agreement.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the files being re-encrypted under a new key
+        if self.key_rotation.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the files being checked against their manifest
+        if self.operations.poll_manifest_check() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the connection to the embedded device being tested
+        if self.operations.poll_connection_test() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the encrypted file being audited
+        if self.audit.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the backends being benchmarked
+        if self.benchmark.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Follow the catalog being made
+        if self.catalog.poll() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Shred the viewed files whose time has run out
+        if self.viewer.poll() {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        }
+        
+        // Run the jobs queued through the control API
+        if self.control_api.poll(self.keys.current_key(), self.keys.is_locked()) {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+        
+        // Proxy settings dialog
+        self.show_proxy_settings(ctx);
+        
+        // Large batch warning
+        self.show_batch_warning(ctx);
+        self.show_key_pin_warning(ctx);
+        self.show_preflight(ctx);
+        
+        // Show what the service commands run in this frame did
+        if self.show_service_events() {
+            ctx.request_repaint();
+        }
     }
 }
+
+/// Describe the running operation for the tray icon's tooltip
+fn tray_tooltip(operation: &FileOperation, file_progress: &[f32]) -> String {
+    if file_progress.is_empty() {
+        return "CRUSTy".to_string();
+    }
+    
+    let overall = file_progress.iter().sum::<f32>() / file_progress.len() as f32;
+    let operation = match operation {
+        FileOperation::Decrypt | FileOperation::BatchDecrypt | FileOperation::ExtractArchive => "decrypt",
+        _ => "encrypt",
+    };
+    tr_args("tray-progress", &[
+        ("operation", operation.into()),
+        ("percent", locale::format_percent(overall).into()),
+        ("done", file_progress.iter().filter(|&&progress| progress >= 1.0).count().into()),
+        ("total", file_progress.len().into()),
+    ])
+}
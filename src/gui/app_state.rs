@@ -1,3 +1,12 @@
+use std::fmt;
+
+use zeroize::Zeroizing;
+
+use crate::gui::file_list::FileOperationType;
+use crate::run_summary::{RunChange, RunSummary};
+use crate::settings::{KeyPin, ProxySettings};
+use crate::smartcard::PinStatus;
+
 /// Application state enum
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppState {
@@ -7,10 +16,28 @@ pub enum AppState {
     Encrypting,
     Decrypting,
     KeyManagement,
+    SplitKeyManagement,
+    TransferPreparation,
+    TransferReceive,
+    ColdStorageExport,
+    ColdStorageRestore,
+    KeyAgreement,
+    KeyRotation,
+    Audit,
+    Catalog,
+    Benchmark,
+    Settings,
     Logs,
     About,
 }
 
+/// Tab shown on the main screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MainTab {
+    RecentFiles,
+    SecuredFolders,
+}
+
 /// Encryption workflow step enum
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EncryptionWorkflowStep {
@@ -20,6 +47,92 @@ pub enum EncryptionWorkflowStep {
     Execute,
 }
 
+/// Cold-storage export wizard step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColdStorageStep {
+    Folder,
+    Media,
+    Recovery,
+    Export,
+}
+
+/// Cold-storage restore wizard step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColdStorageRestoreStep {
+    Media,
+    Verify,
+    Key,
+    Restore,
+}
+
+/// Key rotation wizard step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyRotationStep {
+    Files,
+    Keys,
+    Rotate,
+}
+
+/// State of the smartcard PIN prompt
+pub struct PinPrompt {
+    /// Index of the token key being unlocked
+    pub key_index: usize,
+    /// PIN being entered
+    pub pin: Zeroizing<String>,
+    /// Incorrect PINs entered in this prompt
+    pub failed_attempts: u32,
+    /// Retry state reported by the token
+    pub status: PinStatus,
+}
+
+/// State of the proxy settings dialog
+pub struct ProxyDialog {
+    /// Proxy settings being edited
+    pub settings: ProxySettings,
+    /// New proxy password (the stored password is kept when empty)
+    pub password: Zeroizing<String>,
+    /// Remove the stored proxy password
+    pub clear_password: bool,
+}
+
+/// State of the large batch warning
+pub struct BatchWarning {
+    /// Operation to start when confirmed
+    pub operation: FileOperationType,
+    /// Number of selected files
+    pub file_count: usize,
+    /// Total size of the selected files in bytes
+    pub total_size: u64,
+    /// The user chose to start the operation anyway
+    pub confirmed: bool,
+}
+
+/// State of the summary shown before a batch starts
+pub struct PreflightSummary {
+    /// Operation to start when confirmed
+    pub operation: FileOperationType,
+    /// Key, backend, destination and options of the batch
+    pub summary: RunSummary,
+    /// What changed since the last batch of the same operation
+    pub changes: Vec<RunChange>,
+    /// Number of selected files
+    pub file_count: usize,
+    /// Total size of the selected files in bytes
+    pub total_size: u64,
+    /// The user chose to start the batch
+    pub confirmed: bool,
+}
+
+/// State of the warning shown before encrypting with another key than the pinned one
+pub struct KeyPinWarning {
+    /// Pin of the folder of the files or the output directory
+    pub pin: KeyPin,
+    /// Name of the selected key
+    pub key_name: String,
+    /// The user chose to encrypt with the selected key anyway
+    pub confirmed: bool,
+}
+
 impl EncryptionWorkflowStep {
     /// Get the next step in the workflow
     pub fn next(&self) -> Self {
@@ -52,3 +165,111 @@ impl ToString for EncryptionWorkflowStep {
         }
     }
 }
+
+impl ColdStorageStep {
+    /// Steps of the wizard, in order
+    pub const ALL: [ColdStorageStep; 4] = [Self::Folder, Self::Media, Self::Recovery, Self::Export];
+    
+    /// Get the next step in the wizard
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Folder => Self::Media,
+            Self::Media => Self::Recovery,
+            Self::Recovery => Self::Export,
+            Self::Export => Self::Export,
+        }
+    }
+    
+    /// Get the previous step in the wizard
+    pub fn previous(&self) -> Self {
+        match self {
+            Self::Folder => Self::Folder,
+            Self::Media => Self::Folder,
+            Self::Recovery => Self::Media,
+            Self::Export => Self::Recovery,
+        }
+    }
+}
+
+impl fmt::Display for ColdStorageStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Folder => "Folder",
+            Self::Media => "Media",
+            Self::Recovery => "Recovery",
+            Self::Export => "Export",
+        };
+        f.write_str(name)
+    }
+}
+
+impl ColdStorageRestoreStep {
+    /// Steps of the wizard, in order
+    pub const ALL: [ColdStorageRestoreStep; 4] = [Self::Media, Self::Verify, Self::Key, Self::Restore];
+    
+    /// Get the next step in the wizard
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Media => Self::Verify,
+            Self::Verify => Self::Key,
+            Self::Key => Self::Restore,
+            Self::Restore => Self::Restore,
+        }
+    }
+    
+    /// Get the previous step in the wizard
+    pub fn previous(&self) -> Self {
+        match self {
+            Self::Media => Self::Media,
+            Self::Verify => Self::Media,
+            Self::Key => Self::Verify,
+            Self::Restore => Self::Key,
+        }
+    }
+}
+
+impl fmt::Display for ColdStorageRestoreStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Media => "Media",
+            Self::Verify => "Verify",
+            Self::Key => "Key",
+            Self::Restore => "Restore",
+        };
+        f.write_str(name)
+    }
+}
+
+impl KeyRotationStep {
+    /// Steps of the wizard, in order
+    pub const ALL: [KeyRotationStep; 3] = [Self::Files, Self::Keys, Self::Rotate];
+    
+    /// Get the next step in the wizard
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Files => Self::Keys,
+            Self::Keys => Self::Rotate,
+            Self::Rotate => Self::Rotate,
+        }
+    }
+    
+    /// Get the previous step in the wizard
+    pub fn previous(&self) -> Self {
+        match self {
+            Self::Files => Self::Files,
+            Self::Keys => Self::Files,
+            Self::Rotate => Self::Keys,
+        }
+    }
+}
+
+impl fmt::Display for KeyRotationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Files => "Files",
+            Self::Keys => "Keys",
+            Self::Rotate => "Rotate",
+        };
+        f.write_str(name)
+    }
+}
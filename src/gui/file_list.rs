@@ -1,8 +1,10 @@
 use std::path::PathBuf;
-use std::time::{SystemTime, Duration};
-use eframe::egui::{Color32, Ui, RichText, Button, Rounding, ScrollArea};
+use std::time::{SystemTime, Duration, Instant};
+use eframe::egui::{Color32, Ui, RichText, Button, Label, Response, Rounding, ScrollArea, Sense};
 
 use crate::gui::theme::AppTheme;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
 
 // File status enum for the list-based design
 #[derive(Debug, Clone, PartialEq)]
@@ -47,6 +49,18 @@ pub struct FileEntry {
     pub timestamp: SystemTime,
     pub operation_type: FileOperationType,
     pub file_size: Option<u64>,
+    // When processing started and finished, for the duration and throughput
+    pub started: Option<Instant>,
+    pub finished: Option<Instant>,
+    // Index of the file in the progress of the running operation (None once another operation starts)
+    pub progress_index: Option<usize>,
+    // Name shown instead of the file name (the path inside a selected folder)
+    pub display_name: Option<String>,
+    // Folder the output is saved in (None if not known)
+    pub output_folder: Option<PathBuf>,
+    // File written, and the fingerprint of the key used, once the operation thread reports them
+    pub output_path: Option<PathBuf>,
+    pub key_fingerprint: Option<String>,
 }
 
 impl FileEntry {
@@ -62,54 +76,77 @@ impl FileEntry {
             timestamp: SystemTime::now(),
             operation_type,
             file_size,
+            started: None,
+            finished: None,
+            progress_index: None,
+            display_name: None,
+            output_folder: None,
+            output_path: None,
+            key_fingerprint: None,
         }
     }
     
     pub fn file_name(&self) -> String {
+        if let Some(name) = &self.display_name {
+            return name.clone();
+        }
         self.path.file_name()
             .map(|name| name.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Unknown file".to_string())
+            .unwrap_or_else(|| tr("files-unknown"))
     }
     
     pub fn file_size_text(&self) -> String {
         match self.file_size {
-            Some(size) => {
-                if size < 1024 {
-                    format!("{} B", size)
-                } else if size < 1024 * 1024 {
-                    format!("{:.1} KB", size as f64 / 1024.0)
-                } else if size < 1024 * 1024 * 1024 {
-                    format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
-                } else {
-                    format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
-                }
-            },
+            Some(size) => locale::format_size(size),
             None => "--".to_string(),
         }
     }
     
     pub fn set_progress(&mut self, progress: f32) {
         self.status = FileStatus::InProgress(progress);
+        self.started.get_or_insert_with(Instant::now);
     }
     
     pub fn set_completed(&mut self, result: String) {
         self.status = FileStatus::Completed;
         self.result = Some(result);
         self.timestamp = SystemTime::now();
+        self.finish();
     }
     
     pub fn set_failed(&mut self, error: String) {
         self.status = FileStatus::Failed;
         self.error = Some(error);
         self.timestamp = SystemTime::now();
+        self.finish();
+    }
+    
+    // Record the end of processing (a file that finished without reporting progress took no time)
+    fn finish(&mut self) {
+        let now = Instant::now();
+        self.started.get_or_insert(now);
+        self.finished.get_or_insert(now);
+    }
+    
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+    
+    // Fraction of the file processed (a failed file has nothing left to process)
+    pub fn fraction_done(&self) -> f32 {
+        match self.status {
+            FileStatus::Pending => 0.0,
+            FileStatus::InProgress(progress) => progress,
+            FileStatus::Completed | FileStatus::Failed => 1.0,
+        }
     }
     
     pub fn status_text(&self) -> String {
         match &self.status {
-            FileStatus::Pending => "Pending".to_string(),
-            FileStatus::InProgress(progress) => format!("In Progress: {:.1}%", progress * 100.0),
-            FileStatus::Completed => "Completed".to_string(),
-            FileStatus::Failed => "Failed".to_string(),
+            FileStatus::Pending => tr("files-pending"),
+            FileStatus::InProgress(progress) => tr_args("files-in-progress", &[("percent", locale::format_percent(*progress).into())]),
+            FileStatus::Completed => tr("files-completed"),
+            FileStatus::Failed => tr("files-failed"),
         }
     }
     
@@ -122,22 +159,33 @@ impl FileEntry {
         }
     }
     
+    // Time spent processing the file: live while running, the total once finished
     pub fn elapsed_time(&self) -> Option<Duration> {
-        SystemTime::now().duration_since(self.timestamp).ok()
+        let started = self.started?;
+        Some(self.finished.unwrap_or_else(Instant::now).duration_since(started))
     }
     
     pub fn elapsed_text(&self) -> String {
-        if let Some(duration) = self.elapsed_time() {
-            let seconds = duration.as_secs();
-            if seconds < 60 {
-                format!("{} seconds ago", seconds)
-            } else if seconds < 3600 {
-                format!("{} minutes ago", seconds / 60)
-            } else {
-                format!("{} hours ago", seconds / 3600)
-            }
-        } else {
-            "Just now".to_string()
+        match (self.elapsed_time(), &self.status) {
+            (Some(duration), FileStatus::Completed) => match self.throughput() {
+                Some(speed) => format!("{} ({}/s)", locale::format_duration(duration), locale::format_size(speed as u64)),
+                None => locale::format_duration(duration),
+            },
+            (Some(duration), _) => locale::format_duration(duration),
+            (None, FileStatus::Pending) => tr("files-waiting"),
+            (None, _) => "--".to_string(),
+        }
+    }
+    
+    // Bytes processed per second, once the file has been processed successfully
+    pub fn throughput(&self) -> Option<f64> {
+        if self.status != FileStatus::Completed {
+            return None;
+        }
+        let seconds = self.elapsed_time()?.as_secs_f64();
+        match self.file_size {
+            Some(size) if seconds > 0.0 => Some(size as f64 / seconds),
+            _ => None,
         }
     }
     
@@ -157,58 +205,66 @@ impl FileEntry {
 
 // Enhanced file list trait
 pub trait EnhancedFileList {
-    fn show_enhanced_file_list(&mut self, ui: &mut Ui);
+    /// Show the file list, returning the file double-clicked in it (if any)
+    fn show_enhanced_file_list(&mut self, ui: &mut Ui) -> Option<PathBuf>;
 }
 
 impl<T> EnhancedFileList for T 
 where 
     T: AsMut<Vec<FileEntry>> + AsRef<AppTheme>
 {
-    fn show_enhanced_file_list(&mut self, ui: &mut Ui) {
+    fn show_enhanced_file_list(&mut self, ui: &mut Ui) -> Option<PathBuf> {
+        // The theme is copied so the entries can be borrowed mutably
+        let theme = &self.as_ref().clone();
         let file_entries = self.as_mut();
-        let theme = self.as_ref();
+        let mut opened = None;
         
         ui.group(|ui| {
-            ui.heading("File List");
+            ui.heading(tr("files-title"));
             
             // Column headers
             ui.horizontal(|ui| {
-                ui.label(RichText::new("File").strong()).min_width(200.0);
-                ui.label(RichText::new("Size").strong()).min_width(80.0);
-                ui.label(RichText::new("Status").strong()).min_width(100.0);
-                ui.label(RichText::new("Algorithm").strong()).min_width(80.0);
-                ui.label(RichText::new("Date").strong()).min_width(100.0);
-                ui.label(RichText::new("Actions").strong()).min_width(100.0);
+                column(ui, 200.0, Label::new(RichText::new(tr("files-file")).strong()));
+                column(ui, 80.0, Label::new(RichText::new(tr("catalog-size")).strong()));
+                column(ui, 100.0, Label::new(RichText::new(tr("keys-status")).strong()));
+                column(ui, 80.0, Label::new(RichText::new(tr("files-algorithm")).strong()));
+                column(ui, 100.0, Label::new(RichText::new(tr("files-time")).strong()));
+                column(ui, 100.0, Label::new(RichText::new(tr("keys-actions")).strong()));
             });
             
             ui.separator();
             
             // File entries
             if file_entries.is_empty() {
-                ui.label("No files in the list. Use the Open button to select files.");
+                ui.label(tr("files-none"));
             } else {
                 ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
                     let mut entry_to_remove = None;
                     
                     for (i, entry) in file_entries.iter().enumerate() {
                         ui.horizontal(|ui| {
-                            // File name
-                            ui.label(&entry.file_name()).min_width(200.0);
+                            // File name (double-clicking opens it with its default action)
+                            if column(ui, 200.0, Label::new(entry.file_name()).sense(Sense::click())).double_clicked() {
+                                opened = Some(entry.path.clone());
+                            }
                             
                             // File size
-                            ui.label(&entry.file_size_text()).min_width(80.0);
+                            column(ui, 80.0, Label::new(entry.file_size_text()));
                             
-                            // Status with color
-                            ui.label(
+                            // Status with color (why a file failed on hover)
+                            let status = column(ui, 100.0, Label::new(
                                 RichText::new(entry.status_text())
                                 .color(entry.status_color(theme))
-                            ).min_width(100.0);
+                            ));
+                            if let Some(error) = &entry.error {
+                                status.on_hover_text(error);
+                            }
                             
                             // Algorithm
-                            ui.label(&entry.algorithm_text()).min_width(80.0);
+                            column(ui, 80.0, Label::new(entry.algorithm_text()));
                             
-                            // Date
-                            ui.label(entry.elapsed_text()).min_width(100.0);
+                            // Processing time
+                            column(ui, 100.0, Label::new(entry.elapsed_text()));
                             
                             // Actions
                             if ui.add(Button::new(RichText::new("❌").color(theme.button_text))
@@ -223,9 +279,9 @@ where
                         if let FileStatus::InProgress(progress) = entry.status {
                             ui.horizontal(|ui| {
                                 ui.add_space(20.0);
-                                ui.label(format!("[{}] {:.1}%", 
+                                ui.label(format!("[{}] {}", 
                                     FileStatus::InProgress(progress).progress_bar(20.0), 
-                                    progress * 100.0
+                                    locale::format_percent(progress)
                                 ));
                             });
                         }
@@ -240,10 +296,10 @@ where
             
             // Bottom controls for file list
             ui.horizontal(|ui| {
-                ui.label(format!("Total: {} file(s)", file_entries.len()));
+                ui.label(tr_args("main-total-files", &[("count", file_entries.len().into())]));
                 
                 if !file_entries.is_empty() {
-                    if ui.add(Button::new(RichText::new("Clear All").color(theme.button_text))
+                    if ui.add(Button::new(RichText::new(tr("files-clear-all")).color(theme.button_text))
                         .fill(theme.button_normal)
                         .rounding(Rounding::same(5.0))
                     ).clicked() {
@@ -252,5 +308,13 @@ where
                 }
             });
         });
+        
+        opened
     }
 }
+
+/// Show a label in a column of the file list, at least `width` wide so the columns line up
+fn column(ui: &mut Ui, width: f32, label: Label) -> Response {
+    let height = ui.spacing().interact_size.y;
+    ui.add_sized([width, height], label)
+}
@@ -0,0 +1,522 @@
+/// Headless GUI test harness.
+///
+/// Drives `CrustyApp` without a window: every frame runs the real screens in an egui
+/// context, and widgets are found by their accessibility name (the button text) and
+/// clicked with synthetic pointer events, so a button that stops working fails a test.
+///
+/// Native dialogs cannot run headlessly; tests pick files and folders through the
+/// services instead (e.g. `harness.app.operations.select_files`).
+use std::time::{Duration, Instant};
+use eframe::egui::{self, accesskit, Context, Event, Key, Modifiers, PointerButton, Pos2, RawInput, Rect};
+use tempfile::TempDir;
+
+use crate::gui::app_core::CrustyApp;
+use crate::settings::Settings;
+use crate::locale::{self, Language};
+
+/// Time advanced per frame
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
+/// A widget shown in the last frame
+struct Widget {
+    name: Option<String>,
+    role: accesskit::Role,
+    rect: Rect,
+}
+
+/// Runs the app frame by frame and interacts with its widgets
+pub struct Harness {
+    pub app: CrustyApp,
+    // The app's data folder, removed when the test ends
+    _data_dir: TempDir,
+    ctx: Context,
+    time: f64,
+    events: Vec<Event>,
+    widgets: Vec<Widget>,
+}
+
+impl Harness {
+    /// Create the app, with default settings and its data in a temporary folder, and run
+    /// its first frame
+    pub fn new() -> Self {
+        let ctx = Context::default();
+        ctx.enable_accesskit();
+
+        let data_dir = TempDir::new().expect("Failed to create the app's data folder");
+        let app = CrustyApp::with_data_dir(data_dir.path().to_path_buf(), Settings::default());
+        // Widgets are found by their English text
+        locale::set_language(Some(Language::EnglishUs));
+
+        let mut harness = Self {
+            app,
+            _data_dir: data_dir,
+            ctx,
+            time: 0.0,
+            events: Vec::new(),
+            widgets: Vec::new(),
+        };
+        harness.run();
+        harness
+    }
+
+    /// Run one frame with the pending input events
+    pub fn run(&mut self) {
+        let input = RawInput {
+            // Tall enough that the screens fit without scrolling
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, egui::vec2(1280.0, 2400.0))),
+            time: Some(self.time),
+            events: std::mem::take(&mut self.events),
+            ..Default::default()
+        };
+        self.time += FRAME_TIME;
+
+        let output = self.ctx.run(input, |ctx| self.app.show_ui(ctx));
+
+        self.widgets = output.platform_output.accesskit_update
+            .map(|update| update.nodes.into_iter()
+                .filter_map(|(_, node)| {
+                    let bounds = node.bounds()?;
+                    Some(Widget {
+                        name: node.name().map(str::to_string),
+                        role: node.role(),
+                        rect: Rect::from_min_max(
+                            Pos2::new(bounds.x0 as f32, bounds.y0 as f32),
+                            Pos2::new(bounds.x1 as f32, bounds.y1 as f32),
+                        ),
+                    })
+                })
+                .collect())
+            .unwrap_or_default();
+    }
+
+    /// Run frames until the condition holds
+    ///
+    /// # Panics
+    /// Panics if the condition does not hold within the timeout.
+    pub fn run_until(&mut self, timeout: Duration, mut condition: impl FnMut(&CrustyApp) -> bool) {
+        let start = Instant::now();
+        while !condition(&self.app) {
+            assert!(start.elapsed() < timeout, "condition not met within {:?}", timeout);
+            std::thread::sleep(Duration::from_millis(20));
+            self.run();
+        }
+    }
+
+    /// Check whether a widget with the name was shown in the last frame
+    pub fn has(&self, name: &str) -> bool {
+        self.widgets.iter().any(|widget| widget.name.as_deref() == Some(name))
+    }
+
+    /// Click the widget with the name, then run a frame so the click takes effect
+    ///
+    /// # Panics
+    /// Panics if no widget with the name was shown in the last frame.
+    pub fn click(&mut self, name: &str) {
+        let rect = self.widgets.iter()
+            .find(|widget| widget.name.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("no widget named {:?}", name))
+            .rect;
+        self.click_at(rect.center());
+    }
+
+    /// Click the text field (the first is 0, in the order shown) and type the text
+    ///
+    /// # Panics
+    /// Panics if fewer text fields were shown in the last frame.
+    pub fn type_text(&mut self, field: usize, text: &str) {
+        // Accessibility nodes are not in screen order, so sort the fields top to bottom
+        let mut fields: Vec<Rect> = self.widgets.iter()
+            .filter(|widget| widget.role == accesskit::Role::TextField)
+            .map(|widget| widget.rect)
+            .collect();
+        fields.sort_by(|a, b| a.min.y.total_cmp(&b.min.y).then(a.min.x.total_cmp(&b.min.x)));
+        let rect = *fields.get(field)
+            .unwrap_or_else(|| panic!("no text field {}", field));
+        self.click_at(rect.center());
+
+        self.events.push(Event::Text(text.to_string()));
+        self.run();
+    }
+
+    /// Press and release the key, then run a frame so the key press takes effect
+    pub fn press(&mut self, key: Key) {
+        for pressed in [true, false] {
+            self.events.push(Event::Key {
+                key,
+                pressed,
+                repeat: false,
+                modifiers: Modifiers::default(),
+            });
+        }
+        self.run();
+
+        // Show the state the key press led to
+        self.run();
+    }
+
+    /// Press and release the primary button at the position
+    fn click_at(&mut self, pos: Pos2) {
+        self.events.push(Event::PointerMoved(pos));
+        self.events.push(Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed: true,
+            modifiers: Modifiers::default(),
+        });
+        self.run();
+
+        self.events.push(Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed: false,
+            modifiers: Modifiers::default(),
+        });
+        self.run();
+
+        // Show the state the click led to
+        self.run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::{decrypt_file, encrypt_file, EncryptionKey};
+    use crate::gui::app_state::AppState;
+    use crate::keystore::KeyProvenance;
+    use crate::logger::{read_log_file, write_compliance_report, Logger};
+    use crate::backend::{ConnectionSettings, ConnectionType, EmbeddedConfig, EthernetSettings};
+    use crate::settings::{KeyPin, OperationDefaults};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_generate_key() {
+        let mut harness = Harness::new();
+
+        harness.click("🔑");
+        assert_eq!(harness.app.state, AppState::KeyManagement);
+
+        harness.type_text(0, "Harness Key");
+        harness.click("Generate Key");
+
+        assert_eq!(harness.app.keys.current_key_name().as_deref(), Some("Harness Key"));
+        assert!(harness.has("Harness Key"));
+    }
+
+    #[test]
+    fn test_data_kept_in_temporary_folder() {
+        let mut harness = Harness::new();
+        assert_ne!(harness.app.data_dir, crate::gui::app_core::default_data_dir());
+        assert_eq!(harness.app.settings.language, Settings::default().language);
+
+        harness.app.save_settings();
+        assert!(Settings::path_in(&harness.app.data_dir).exists());
+        assert!(harness.app.data_dir.starts_with(std::env::temp_dir()));
+    }
+
+    #[test]
+    fn test_connection_settings_editor() {
+        let mut harness = Harness::new();
+
+        harness.click("⚙️");
+        assert_eq!(harness.app.state, AppState::MainScreen);
+
+        harness.click("Use embedded system for cryptographic operations");
+        harness.click("Ethernet");
+        harness.click("Connection Settings");
+        assert!(harness.has("Please enter the device's host name or IP address"));
+
+        // Device ID, then the host
+        harness.type_text(0, "crusty-01");
+        harness.type_text(1, "bad host");
+        assert!(harness.has("'bad host' is not a valid host name or IP address"));
+
+        harness.app.operations.backend.ethernet.host = "192.168.1.30".to_string();
+        harness.run();
+        assert!(!harness.has("'bad host' is not a valid host name or IP address"));
+        assert_eq!(harness.app.operations.backend.validate(), Ok(()));
+
+        // Settings entered for Ethernet are kept when switching to serial
+        harness.click("Serial/UART");
+        assert!(harness.has("Baud Rate:"));
+        assert_eq!(harness.app.operations.backend.ethernet.host, "192.168.1.30");
+    }
+
+    #[test]
+    fn test_help_overlay() {
+        let mut harness = Harness::new();
+        assert!(!harness.has("Dashboard"));
+
+        harness.press(Key::F1);
+        assert!(harness.has("Dashboard"));
+
+        harness.press(Key::F1);
+        assert!(!harness.has("Dashboard"));
+    }
+
+    #[test]
+    fn test_encrypt_selected_file() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("plain.txt");
+        let output_dir = dir.path().join("out");
+        std::fs::write(&input, b"Encrypted by the GUI").unwrap();
+        std::fs::create_dir(&output_dir).unwrap();
+
+        let mut harness = Harness::new();
+
+        // Stand-ins for the file dialogs
+        harness.app.operations.select_files(vec![input]);
+        harness.app.operations.set_output_dir(output_dir.clone());
+        harness.app.keys.generate_key("Harness Key");
+
+        harness.click("🔒");
+
+        let output = output_dir.join("plain.txt.encrypted");
+        harness.run_until(Duration::from_secs(10), |_| output.exists());
+        harness.run_until(Duration::from_secs(10), |app| app.operations.overall_progress().is_none());
+
+        let decrypted = dir.path().join("decrypted.txt");
+        let key = harness.app.keys.current_key().unwrap();
+        decrypt_file(&output, &decrypted, key, |_| {}).unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"Encrypted by the GUI");
+    }
+
+    #[test]
+    fn test_open_files() {
+        let dir = tempdir().unwrap();
+        let plain: Vec<_> = ["a.txt", "b.txt"].iter().map(|name| dir.path().join(name)).collect();
+        let encrypted: Vec<_> = ["a.txt.encrypted", "b.txt.encrypted"].iter().map(|name| dir.path().join(name)).collect();
+        for file in plain.iter().chain(&encrypted) {
+            std::fs::write(file, b"Opened from the command line").unwrap();
+        }
+
+        // Stand-in for files CRUSTy is started with
+        let mut harness = Harness::new();
+        harness.app.open_files(plain.clone());
+        harness.run();
+        assert_eq!(harness.app.state, AppState::Encrypting);
+        assert_eq!(harness.app.operations.selected_files(), plain.as_slice());
+
+        harness.app.open_files(encrypted.clone());
+        harness.run();
+        assert_eq!(harness.app.state, AppState::Decrypting);
+        assert_eq!(harness.app.operations.selected_files(), encrypted.as_slice());
+    }
+
+    #[test]
+    fn test_large_batch_warning() {
+        let dir = tempdir().unwrap();
+        let inputs: Vec<_> = (0..3).map(|i| dir.path().join(format!("{}.txt", i))).collect();
+        for input in &inputs {
+            std::fs::write(input, b"One of many").unwrap();
+        }
+
+        let mut harness = Harness::new();
+        harness.app.settings.batch_limits.max_files = 2;
+        harness.app.settings.preflight_summary = false;
+        harness.app.operations.select_files(inputs);
+        harness.app.operations.set_output_dir(dir.path().to_path_buf());
+        harness.app.keys.generate_key("Harness Key");
+
+        // Nothing starts until the warning is confirmed
+        harness.click("🔒");
+        assert!(harness.has("Start Anyway"));
+        harness.click("Cancel");
+        assert!(harness.app.batch_warning.is_none());
+        assert!(harness.app.operations.overall_progress().is_none());
+
+        harness.click("🔒");
+        harness.click("Start Anyway");
+        assert!(!harness.has("Start Anyway"));
+
+        let output = dir.path().join("0.txt.encrypted");
+        harness.run_until(Duration::from_secs(10), |_| output.exists());
+    }
+
+    #[test]
+    fn test_preflight_summary() {
+        let dir = tempdir().unwrap();
+        let inputs: Vec<_> = (0..2).map(|i| dir.path().join(format!("{}.txt", i))).collect();
+        for input in &inputs {
+            std::fs::write(input, b"Part of a batch").unwrap();
+        }
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+
+        let mut harness = Harness::new();
+        harness.app.settings.preflight_summary = true;
+        harness.app.operations.load_last_runs(dir.path().join("last_runs.json"));
+        harness.app.operations.select_files(inputs);
+        harness.app.operations.set_output_dir(output_dir.clone());
+        harness.app.keys.generate_key("Harness Key");
+
+        // The first batch has nothing to compare with
+        harness.click("🔒");
+        assert!(harness.has("Hide file names"));
+        assert!(!harness.has("Since the last batch:"));
+        harness.click("Start");
+
+        let output = output_dir.join("1.txt.encrypted");
+        harness.run_until(Duration::from_secs(10), |_| output.exists());
+        harness.run_until(Duration::from_secs(10), |app| app.operations.overall_progress().is_none());
+
+        // A changed option is pointed out before the next batch
+        harness.app.operations.hide_file_names = true;
+        harness.click("🔒");
+        assert!(harness.has("Note: Hide file names is now On (was Off)"));
+        harness.click("Cancel");
+        assert!(harness.app.preflight.is_none());
+        assert!(harness.app.operations.overall_progress().is_none());
+    }
+
+    #[test]
+    fn test_key_pin_warning() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("delivery.txt");
+        std::fs::write(&input, b"For the client").unwrap();
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+
+        let mut harness = Harness::new();
+        harness.app.keys.generate_key("Client Key");
+        let client_key = harness.app.keys.current_key().unwrap().clone();
+        harness.app.settings.pin_key(KeyPin {
+            folder: dir.path().to_path_buf(),
+            key_name: "Client Key".to_string(),
+            fingerprint: client_key.fingerprint(),
+        });
+        harness.app.keys.generate_key("Personal Key");
+        harness.app.operations.select_files(vec![input]);
+        harness.app.operations.set_output_dir(output_dir.clone());
+
+        // Nothing starts with the personal key until the warning is answered
+        harness.click("🔒");
+        assert!(harness.has("Encrypt Anyway"));
+        harness.click("Cancel");
+        assert!(harness.app.key_pin_warning.is_none());
+        assert!(harness.app.operations.overall_progress().is_none());
+
+        // Switching to the pinned key encrypts with it
+        harness.click("🔒");
+        harness.click("Use 'Client Key'");
+        assert!(!harness.has("Encrypt Anyway"));
+
+        let output = output_dir.join("delivery.txt.encrypted");
+        harness.run_until(Duration::from_secs(10), |_| output.exists());
+        harness.run_until(Duration::from_secs(10), |app| app.operations.overall_progress().is_none());
+        let decrypted = dir.path().join("decrypted.txt");
+        decrypt_file(&output, &decrypted, &client_key, |_| {}).unwrap();
+        assert_eq!(std::fs::read(&decrypted).unwrap(), b"For the client");
+    }
+
+    #[test]
+    fn test_shares_hidden_until_revealed() {
+        let mut harness = Harness::new();
+        harness.app.settings.protect_shares = true;
+        harness.app.transfer.create_package(&EncryptionKey::generate());
+        harness.app.state = AppState::TransferPreparation;
+        harness.run();
+
+        assert!(harness.has("Hold to Reveal"));
+        assert!(harness.app.secrets_shown);
+
+        harness.app.settings.protect_shares = false;
+        harness.run();
+        assert!(!harness.has("Hold to Reveal"));
+
+        // Leaving the shares allows screen captures again
+        harness.app.state = AppState::Dashboard;
+        harness.run();
+        assert!(!harness.app.secrets_shown);
+    }
+
+    #[test]
+    fn test_decrypt_reason_logged() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let input = dir.path().join("salaries.txt.encrypted");
+        std::fs::write(dir.path().join("salaries.txt.orig"), b"Payroll").unwrap();
+        encrypt_file(&dir.path().join("salaries.txt.orig"), &input, &key, |_| {}).unwrap();
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        let log_path = dir.path().join("operations.log");
+
+        let mut harness = Harness::new();
+        harness.app.logger = Arc::new(Logger::new(&log_path).unwrap());
+        harness.app.keys.add_key("Payroll Key", key, KeyProvenance::new("Loaded from a key file"));
+        harness.app.operations.select_files(vec![input]);
+        harness.app.operations.set_output_dir(output_dir.clone());
+        harness.app.operations.decrypt_reason = "  Audit request 42 ".to_string();
+
+        harness.app.begin_decrypt();
+        let output = output_dir.join("salaries.txt");
+        harness.run_until(Duration::from_secs(10), |_| output.exists());
+        harness.run_until(Duration::from_secs(10), |app| app.operations.overall_progress().is_none());
+
+        // The reason is logged with the request and applies to this decryption only
+        let entries = read_log_file(&log_path).unwrap();
+        let request = entries.iter().find(|entry| entry.operation == "Decrypt Request").unwrap();
+        assert_eq!(request.reason.as_deref(), Some("Audit request 42"));
+        assert!(harness.app.operations.decrypt_reason.is_empty());
+
+        let report = dir.path().join("compliance.csv");
+        assert!(write_compliance_report(&entries, &report).unwrap() >= 1);
+        assert!(std::fs::read_to_string(&report).unwrap().contains(",\"Decrypt Request\","));
+    }
+
+    #[test]
+    fn test_operation_defaults() {
+        let dir = tempdir().unwrap();
+        let mut harness = Harness::new();
+        harness.app.settings.defaults = OperationDefaults {
+            output_dir: Some(dir.path().to_path_buf()),
+            batch_mode: true,
+            use_embedded: true,
+            embedded: Some(EmbeddedConfig {
+                device_id: "crusty-01".to_string(),
+                connection: ConnectionSettings::Ethernet(EthernetSettings::default()),
+            }),
+        };
+        harness.app.apply_operation_defaults();
+        harness.run();
+        assert!(harness.app.operations.batch_mode);
+        assert!(harness.app.operations.backend.use_embedded);
+        assert_eq!(harness.app.operations.backend.device_id, "crusty-01");
+        assert_eq!(harness.app.operations.backend.connection_type, ConnectionType::Ethernet);
+        assert_eq!(harness.app.operations.output_dir(), Some(dir.path()));
+
+        // An output directory that no longer exists is not selected
+        harness.app.operations.batch_mode = false;
+        harness.app.settings.defaults.output_dir = Some(dir.path().join("gone"));
+        harness.app.apply_operation_defaults();
+        assert!(harness.app.operations.batch_mode);
+        assert_eq!(harness.app.operations.output_dir(), Some(dir.path()));
+    }
+
+    #[test]
+    fn test_decrypt_received_file() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let received = dir.path().join("report.txt.encrypted");
+        std::fs::write(dir.path().join("report.txt.orig"), b"Sent by transfer").unwrap();
+        encrypt_file(&dir.path().join("report.txt.orig"), &received, &key, |_| {}).unwrap();
+
+        let mut harness = Harness::new();
+        harness.app.keys.add_key("Transfer Key", key, KeyProvenance::new("Reconstructed from transfer shares"));
+
+        // Stand-in for the file dialog
+        harness.app.decrypt_received(received.clone());
+        harness.run_until(Duration::from_secs(10), |app| app.received_output.is_none());
+
+        let output = dir.path().join("report.txt");
+        assert_eq!(std::fs::read(&output).unwrap(), b"Sent by transfer");
+        assert_eq!(harness.app.folder_to_open.as_deref(), Some(dir.path()));
+
+        // A second decryption would not replace the file, so it is not started
+        harness.app.folder_to_open = None;
+        harness.app.decrypt_received(received);
+        assert!(harness.app.received_output.is_none());
+        assert!(harness.app.error_message.as_deref().is_some_and(|e| e.contains("already exists")));
+    }
+}
@@ -0,0 +1,162 @@
+/// In-app help.
+///
+/// This module provides functionality for:
+/// - Help text for each screen, shown in the F1 help overlay
+/// - "?" icons with tooltips on options that need explaining
+///
+/// The help is markdown embedded in the binary (src/gui/help/*.md), so it works offline.
+/// Only the markdown the help files use is rendered: headings, paragraphs, bullet and
+/// numbered lists, **bold**, and `code`.
+use eframe::egui::{Ui, RichText, Label, Sense, Response};
+use crate::gui::app_state::AppState;
+
+/// Help for encrypting a file once for several recipients
+pub const RECIPIENT_MODE: &str = include_str!("help/recipient_mode.md");
+
+/// Help for the split-key threshold
+pub const SPLIT_KEY_THRESHOLD: &str = include_str!("help/split_key_threshold.md");
+
+/// Help for the embedded (hardware) encryption backend
+pub const EMBEDDED_BACKEND: &str = include_str!("help/embedded_backend.md");
+
+/// Help for replacing the original files with the encrypted files
+pub const REPLACE_ORIGINALS: &str = include_str!("help/replace_originals.md");
+
+/// Help for secured folders
+pub const SECURED_FOLDERS: &str = include_str!("help/secured_folders.md");
+
+/// Help for hiding the names of encrypted files
+pub const HIDE_FILE_NAMES: &str = include_str!("help/hide_file_names.md");
+
+/// Help for appending parity to encrypted files
+pub const PARITY: &str = include_str!("help/parity.md");
+
+/// Help for storing the hash of the original in encrypted files
+pub const PLAINTEXT_DIGEST: &str = include_str!("help/plaintext_digest.md");
+
+/// Help for the signed manifest of a batch
+pub const BATCH_MANIFEST: &str = include_str!("help/batch_manifest.md");
+
+/// Help for viewing a file without a decrypted copy left behind
+pub const VIEW_SECURELY: &str = include_str!("help/view_securely.md");
+
+/// Help for age files, identities, and recipients
+pub const AGE_FORMAT: &str = include_str!("help/age_format.md");
+
+/// Help for OpenPGP keys and messages
+pub const OPENPGP: &str = include_str!("help/openpgp.md");
+
+/// Help for key backups
+pub const KEY_BACKUPS: &str = include_str!("help/key_backups.md");
+
+/// Get the help for a screen
+pub fn screen_help(state: &AppState) -> &'static str {
+    match state {
+        AppState::Dashboard => include_str!("help/dashboard.md"),
+        AppState::MainScreen => include_str!("help/main_screen.md"),
+        AppState::EncryptionWorkflow => include_str!("help/workflow.md"),
+        AppState::Encrypting => include_str!("help/encrypt.md"),
+        AppState::Decrypting => include_str!("help/decrypt.md"),
+        AppState::KeyManagement => include_str!("help/key_management.md"),
+        AppState::SplitKeyManagement => include_str!("help/split_key.md"),
+        AppState::TransferPreparation => include_str!("help/transfer_preparation.md"),
+        AppState::TransferReceive => include_str!("help/transfer_receive.md"),
+        AppState::ColdStorageExport => include_str!("help/cold_storage.md"),
+        AppState::ColdStorageRestore => include_str!("help/cold_storage_restore.md"),
+        AppState::KeyAgreement => include_str!("help/key_agreement.md"),
+        AppState::KeyRotation => include_str!("help/key_rotation.md"),
+        AppState::Audit => include_str!("help/audit.md"),
+        AppState::Catalog => include_str!("help/catalog.md"),
+        AppState::Benchmark => include_str!("help/benchmark.md"),
+        AppState::Settings => include_str!("help/settings.md"),
+        AppState::Logs => include_str!("help/logs.md"),
+        AppState::About => include_str!("help/about.md"),
+    }
+}
+
+/// Show a "?" icon that shows the help as a tooltip when hovered
+pub fn help_icon(ui: &mut Ui, help: &'static str) -> Response {
+    ui.add(Label::new(RichText::new("?").strong()).sense(Sense::hover()))
+        .on_hover_ui(|ui| {
+            ui.set_max_width(360.0);
+            show_markdown(ui, help);
+        })
+}
+
+/// Show help markdown
+pub fn show_markdown(ui: &mut Ui, markdown: &str) {
+    let mut paragraph = String::new();
+
+    for line in markdown.lines().map(str::trim) {
+        let block = if let Some(heading) = line.strip_prefix("# ") {
+            Some(Block::Heading(heading))
+        } else if let Some(item) = line.strip_prefix("- ") {
+            Some(Block::Item("•", item))
+        } else if let Some((number, item)) = line.split_once(". ").filter(|(n, _)| n.parse::<u32>().is_ok()) {
+            Some(Block::Item(number, item))
+        } else if line.is_empty() {
+            Some(Block::Break)
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(line);
+            None
+        };
+
+        if let Some(block) = block {
+            if !paragraph.is_empty() {
+                show_inline(ui, &paragraph);
+                paragraph.clear();
+            }
+
+            match block {
+                Block::Heading(heading) => {
+                    ui.heading(heading);
+                },
+                Block::Item(marker, item) => {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(format!("{}{}", marker, if marker == "•" { "" } else { "." }));
+                        show_inline(ui, item);
+                    });
+                },
+                Block::Break => ui.add_space(4.0),
+            }
+        }
+    }
+
+    if !paragraph.is_empty() {
+        show_inline(ui, &paragraph);
+    }
+}
+
+/// Block-level markdown element
+enum Block<'a> {
+    Heading(&'a str),
+    Item(&'a str, &'a str),
+    Break,
+}
+
+/// Show text with **bold** and `code` spans
+fn show_inline(ui: &mut Ui, text: &str) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+
+        for (i, bold_span) in text.split("**").enumerate() {
+            for (j, span) in bold_span.split('`').enumerate() {
+                if span.is_empty() {
+                    continue;
+                }
+
+                let mut rich = RichText::new(span);
+                if i % 2 == 1 {
+                    rich = rich.strong();
+                }
+                if j % 2 == 1 {
+                    rich = rich.code();
+                }
+                ui.label(rich);
+            }
+        }
+    });
+}
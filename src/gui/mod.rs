@@ -7,9 +7,9 @@ pub mod file_list;
 pub mod utils;
 pub mod screens;
 pub mod action_bar;
+pub mod help;
+#[cfg(test)]
+pub mod harness;
 
 // Re-export main app struct
 pub use app_core::CrustyApp;
-
-// Re-export app state types
-pub use app_state::AppState;
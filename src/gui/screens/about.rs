@@ -1,6 +1,7 @@
 use eframe::egui::{Ui, RichText, Button, Rounding};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::AppState;
+use crate::i18n::tr;
 
 /// About screen trait
 pub trait AboutScreen {
@@ -11,47 +12,47 @@ impl AboutScreen for CrustyApp {
     fn show_about(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
-            ui.heading(RichText::new("About CRUSTy").size(28.0));
+            ui.heading(RichText::new(tr("about-title")).size(28.0));
             ui.add_space(10.0);
             
-            ui.label("CRUSTy - Cryptographic Rust Utility");
-            ui.label("Version 1.0.0");
+            ui.label(tr("about-name"));
+            ui.label(tr("about-version"));
             ui.add_space(20.0);
             
             ui.group(|ui| {
-                ui.heading("Description");
-                ui.label("CRUSTy is a secure file encryption application using AES-256-GCM encryption.");
-                ui.label("It provides a user-friendly interface for encrypting and decrypting files.");
+                ui.heading(tr("about-description"));
+                ui.label(tr("about-description-1"));
+                ui.label(tr("about-description-2"));
                 ui.add_space(10.0);
                 
-                ui.label("Features:");
-                ui.label("• Encrypt and decrypt individual files");
-                ui.label("• Batch processing of multiple files");
-                ui.label("• Key management (generation, saving, loading)");
-                ui.label("• Split-key functionality for enhanced security");
-                ui.label("• Recipient-specific encryption");
-                ui.label("• Operation logging");
-                ui.label("• Progress tracking");
-                ui.label("• Support for hardware encryption via embedded devices");
+                ui.label(tr("about-features"));
+                ui.label(tr("about-feature-files"));
+                ui.label(tr("about-feature-batch"));
+                ui.label(tr("about-feature-keys"));
+                ui.label(tr("about-feature-split-key"));
+                ui.label(tr("about-feature-recipients"));
+                ui.label(tr("about-feature-logging"));
+                ui.label(tr("about-feature-progress"));
+                ui.label(tr("about-feature-hardware"));
             });
             
             ui.add_space(20.0);
             
             ui.group(|ui| {
-                ui.heading("Technical Details");
-                ui.label("• Built with Rust and eframe/egui for the GUI");
-                ui.label("• Uses AES-256-GCM for authenticated encryption");
-                ui.label("• Implements HKDF for recipient-specific key derivation");
-                ui.label("• Supports both software-based and hardware-based encryption backends");
-                ui.label("• File operations are performed with progress tracking");
+                ui.heading(tr("about-technical"));
+                ui.label(tr("about-technical-rust"));
+                ui.label(tr("about-technical-aes"));
+                ui.label(tr("about-technical-hkdf"));
+                ui.label(tr("about-technical-backends"));
+                ui.label(tr("about-technical-progress"));
             });
             
             ui.add_space(20.0);
             
             ui.group(|ui| {
-                ui.heading("License");
-                ui.label("This software is licensed under the MIT License.");
-                ui.label("Copyright © 2025 CRUSTy Team");
+                ui.heading(tr("about-license"));
+                ui.label(tr("about-license-mit"));
+                ui.label(tr("about-copyright"));
             });
             
             ui.add_space(20.0);
@@ -59,7 +60,7 @@ impl AboutScreen for CrustyApp {
             // Back button
             if ui.add_sized(
                 [120.0, 30.0],
-                Button::new(RichText::new("Back").color(self.theme.button_text))
+                Button::new(RichText::new(tr("button-back")).color(self.theme.button_text))
                     .fill(self.theme.button_normal)
                     .rounding(Rounding::same(5.0))
             ).clicked() {
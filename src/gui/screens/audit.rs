@@ -0,0 +1,239 @@
+use eframe::egui::{Ui, Button, ComboBox, Grid, ProgressBar, RichText, Rounding, ScrollArea, Spinner};
+
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::i18n::{tr, tr_args};
+
+/// Audit screen trait
+pub trait AuditScreen {
+    fn show_audit(&mut self, ui: &mut Ui);
+    fn show_audit_key(&mut self, ui: &mut Ui);
+    fn show_audit_compare(&mut self, ui: &mut Ui);
+    fn show_audit_verify(&mut self, ui: &mut Ui);
+}
+
+impl AuditScreen for CrustyApp {
+    fn show_audit(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("audit-title")).size(28.0));
+            ui.add_space(20.0);
+
+            self.show_audit_key(ui);
+            ui.add_space(10.0);
+            self.show_audit_compare(ui);
+            ui.add_space(10.0);
+            self.show_audit_verify(ui);
+
+            ui.add_space(20.0);
+            if ui.add_enabled(
+                !self.audit.is_running(),
+                Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([120.0, 40.0].into())
+            ).clicked() {
+                self.state = AppState::MainScreen;
+            }
+        });
+    }
+
+    // Key the files are encrypted with, for both checks
+    fn show_audit_key(&mut self, ui: &mut Ui) {
+        let key_names: Vec<String> = self.keys.saved_keys().iter()
+            .map(|(name, key)| format!("{} ({})", name, key.fingerprint()))
+            .collect();
+
+        ui.horizontal(|ui| {
+            ui.label(tr("audit-key"));
+            let selected_name = self.audit.key
+                .and_then(|i| key_names.get(i).cloned())
+                .unwrap_or_else(|| tr("audit-choose-key"));
+            ComboBox::from_id_source("audit_key")
+                .selected_text(selected_name)
+                .width(300.0)
+                .show_ui(ui, |ui| {
+                    for (i, name) in key_names.iter().enumerate() {
+                        ui.selectable_value(&mut self.audit.key, Some(i), name);
+                    }
+                });
+        });
+    }
+
+    // Comparison of an encrypted file with its original
+    fn show_audit_compare(&mut self, ui: &mut Ui) {
+        let running = self.audit.is_running();
+        let mut start = false;
+        let mut save_to = None;
+
+        ui.group(|ui| {
+            ui.heading(tr("audit-compare"));
+            ui.label(tr("audit-compare-explained"));
+            ui.add_space(10.0);
+
+            Grid::new("audit_compare").num_columns(3).spacing([20.0, 10.0]).show(ui, |ui| {
+                ui.label(tr("audit-original-file"));
+                ui.label(self.audit.original.as_ref().map(|path| path.display().to_string()).unwrap_or_else(|| tr("settings-none")));
+                if ui.add_enabled(!running, Button::new(tr("settings-choose"))).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Select the Original File")
+                        .pick_file() {
+                        self.audit.original = Some(path);
+                    }
+                }
+                ui.end_row();
+
+                ui.label(tr("audit-encrypted-file"));
+                ui.label(self.audit.encrypted.as_ref().map(|path| path.display().to_string()).unwrap_or_else(|| tr("settings-none")));
+                if ui.add_enabled(!running, Button::new(tr("settings-choose"))).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Select the Encrypted File")
+                        .pick_file() {
+                        self.audit.encrypted = Some(path);
+                    }
+                }
+                ui.end_row();
+            });
+
+            ui.add_space(10.0);
+            let ready = self.audit.original.is_some() && self.audit.encrypted.is_some() && self.audit.key.is_some();
+            if running && self.audit.verify_progress().is_none() {
+                ui.horizontal(|ui| {
+                    ui.add(Spinner::new());
+                    ui.label(tr("audit-auditing"));
+                });
+            } else if ui.add_enabled(
+                ready && !running,
+                Button::new(RichText::new(tr("audit-start")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([200.0, 40.0].into())
+            ).clicked() {
+                start = true;
+            }
+
+            if let Some(report) = self.audit.report() {
+                ui.add_space(10.0);
+                let (text, color) = if report.matches() {
+                    (tr("audit-match"), self.theme.success)
+                } else {
+                    (tr("audit-mismatch"), self.theme.error)
+                };
+                ui.label(RichText::new(text).color(color).strong());
+                if let Some(offset) = report.first_difference {
+                    ui.label(RichText::new(tr_args("audit-differs-at", &[("offset", offset.into())])).color(self.theme.error));
+                }
+                ui.add_space(5.0);
+
+                Grid::new("audit_report").num_columns(3).spacing([20.0, 4.0]).show(ui, |ui| {
+                    for (label, evidence) in [
+                        (tr("audit-original"), &report.original),
+                        (tr("audit-encrypted"), &report.encrypted),
+                        (tr("audit-decrypted"), &report.decrypted),
+                    ] {
+                        ui.label(label);
+                        ui.monospace(&evidence.sha256);
+                        ui.label(tr_args("audit-bytes", &[("count", evidence.len.into())]));
+                        ui.end_row();
+                    }
+                });
+                ui.label(tr_args("audit-format-key", &[("format", report.format.to_string().into()), ("fingerprint", report.key_fingerprint.as_str().into())]));
+                match report.reencryption_matches {
+                    Some(true) => ui.label(tr("audit-reproduced")),
+                    Some(false) => ui.label(RichText::new(tr("audit-not-reproduced")).color(self.theme.error)),
+                    None => ui.label(RichText::new(tr("audit-cannot-reproduce")).color(self.theme.text_secondary)),
+                };
+
+                ui.add_space(10.0);
+                if ui.button(tr("audit-save-report")).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Save Audit Report")
+                        .set_file_name("audit report.txt")
+                        .save_file() {
+                        save_to = Some(path);
+                    }
+                }
+            }
+        });
+
+        if start {
+            self.start_audit();
+        }
+        if let Some(path) = save_to {
+            self.audit.save_report(&path);
+        }
+    }
+
+    // Verification of encrypted files without decrypting them to disk
+    fn show_audit_verify(&mut self, ui: &mut Ui) {
+        let running = self.audit.is_running();
+        let mut start = false;
+        let mut clear = false;
+
+        ui.group(|ui| {
+            ui.heading(tr("audit-verify-only"));
+            ui.label(tr("audit-verify-explained"));
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!running, Button::new(tr("audit-add-files"))).clicked() {
+                    if let Some(files) = rfd::FileDialog::new()
+                        .set_title("Select Files to Verify")
+                        .pick_files() {
+                        self.audit.add_verify_files(files);
+                    }
+                }
+                if ui.add_enabled(!running, Button::new(tr("audit-add-folder"))).clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select a Folder of Encrypted Files")
+                        .pick_folder() {
+                        self.audit.add_verify_folder(&folder);
+                    }
+                }
+                if !self.audit.verify_files().is_empty() && ui.add_enabled(!running, Button::new(tr("settings-clear"))).clicked() {
+                    clear = true;
+                }
+            });
+
+            ui.add_space(10.0);
+            if self.audit.verify_files().is_empty() {
+                ui.label(RichText::new(tr("audit-no-files")).color(self.theme.text_secondary));
+                return;
+            }
+
+            ScrollArea::vertical().max_height(200.0).id_source("audit_verify_files").show(ui, |ui| {
+                Grid::new("audit_verify_results").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                    for file in self.audit.verify_files() {
+                        ui.label(file.display().to_string());
+                        match self.audit.verifications().iter().find(|result| &result.path == file).map(|result| &result.result) {
+                            Some(Ok(())) => ui.label(RichText::new(tr("audit-authentic")).color(self.theme.success)),
+                            Some(Err(error)) => ui.label(RichText::new(tr_args("audit-failed", &[("error", error.to_string().into())])).color(self.theme.error)),
+                            None => ui.label(""),
+                        };
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.add_space(10.0);
+            if let Some(fraction) = self.audit.verify_progress() {
+                ui.add(ProgressBar::new(fraction).show_percentage().animate(true));
+            } else if ui.add_enabled(
+                self.audit.key.is_some() && !running,
+                Button::new(RichText::new(tr("audit-verify")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([200.0, 40.0].into())
+            ).clicked() {
+                start = true;
+            }
+        });
+
+        if start {
+            self.start_verification();
+        }
+        if clear {
+            self.audit.clear_verify_files();
+        }
+    }
+}
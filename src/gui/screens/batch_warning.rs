@@ -0,0 +1,86 @@
+use eframe::egui::{self, Context, RichText, Button, Rounding, Align2};
+use crate::gui::app_core::CrustyApp;
+use crate::gui::file_list::FileOperationType;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+
+/// Large batch warning dialog trait
+pub trait BatchWarningScreen {
+    fn show_batch_warning(&mut self, ctx: &Context);
+}
+
+impl BatchWarningScreen for CrustyApp {
+    fn show_batch_warning(&mut self, ctx: &Context) {
+        let warning = match &self.batch_warning {
+            Some(warning) if !warning.confirmed => warning,
+            _ => return,
+        };
+
+        let operation = match warning.operation {
+            FileOperationType::Decrypt => "decrypt",
+            _ => "encrypt",
+        };
+        let total_size = locale::format_size(warning.total_size);
+        let limits = &self.settings.batch_limits;
+
+        let mut start = false;
+        let mut cancel = false;
+
+        egui::Window::new(tr("batch-warning-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(tr_args("batch-warning-about-to", &[
+                    ("operation", operation.into()),
+                    ("count", warning.file_count.into()),
+                    ("size", total_size.as_str().into()),
+                ])).strong());
+                ui.label(tr_args("batch-warning-over-limit", &[
+                    ("size", limits.max_total_gb.into()),
+                    ("count", limits.max_files.into()),
+                ]));
+
+                ui.add_space(5.0);
+
+                ui.label(tr("batch-warning-before"));
+                ui.label(tr_args("batch-warning-free-space", &[("size", total_size.as_str().into())]));
+                ui.label(tr("batch-warning-memory"));
+                ui.label(tr("batch-warning-split"));
+                if let Some(estimate) = self.operations.estimated_duration() {
+                    ui.label(tr_args("batch-warning-duration", &[("duration", locale::format_duration(estimate).into())]));
+                }
+
+                ui.add_space(5.0);
+                ui.label(RichText::new(tr("batch-warning-settings")).weak());
+
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.add_sized(
+                        [120.0, 30.0],
+                        Button::new(RichText::new(tr("batch-warning-start")).color(self.theme.button_text))
+                            .fill(self.theme.accent)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        start = true;
+                    }
+
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("button-cancel")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.cancel_confirmations();
+        } else if start {
+            self.confirm_large_batch();
+        }
+    }
+}
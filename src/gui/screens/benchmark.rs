@@ -0,0 +1,152 @@
+use std::time::Duration;
+use eframe::egui::{Ui, Button, Grid, RichText, Rounding, Spinner};
+
+use crate::benchmark::{self, BenchmarkResult};
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+use crate::speed_history::BackendKind;
+
+/// Benchmark screen trait
+pub trait BenchmarkScreen {
+    fn show_benchmark(&mut self, ui: &mut Ui);
+    fn show_benchmark_options(&mut self, ui: &mut Ui);
+    fn show_benchmark_results(&mut self, ui: &mut Ui);
+}
+
+impl BenchmarkScreen for CrustyApp {
+    fn show_benchmark(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("benchmark-title")).size(28.0));
+            ui.add_space(10.0);
+            ui.label(tr("benchmark-explained"));
+            ui.add_space(20.0);
+
+            self.show_benchmark_options(ui);
+            ui.add_space(10.0);
+            self.show_benchmark_results(ui);
+
+            ui.add_space(20.0);
+            if ui.add_enabled(
+                !self.benchmark.is_running(),
+                Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([120.0, 40.0].into())
+            ).clicked() {
+                self.state = AppState::MainScreen;
+            }
+        });
+    }
+
+    // Buffer sizes and backends to measure
+    fn show_benchmark_options(&mut self, ui: &mut Ui) {
+        let running = self.benchmark.is_running();
+
+        ui.group(|ui| {
+            ui.add_enabled_ui(!running, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr("benchmark-sizes"));
+                    for (size, measured) in benchmark::SIZES.iter().zip(self.benchmark.sizes.iter_mut()) {
+                        ui.checkbox(measured, locale::format_size(*size as u64));
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.benchmark.include_device, tr("benchmark-include-device"));
+                    let device_id = self.operations.backend.device_id.trim();
+                    if device_id.is_empty() {
+                        ui.label(RichText::new(tr("benchmark-no-device")).color(self.theme.text_secondary));
+                    } else {
+                        ui.label(RichText::new(tr_args("benchmark-device", &[
+                            ("device", device_id.to_string().into()),
+                            ("connection", format!("{:?}", self.operations.backend.connection_type).into()),
+                        ])).color(self.theme.text_secondary));
+                    }
+                });
+            });
+
+            ui.add_space(10.0);
+            if running {
+                ui.horizontal(|ui| {
+                    ui.add(Spinner::new());
+                    ui.label(tr("benchmark-running"));
+                    if ui.button(tr("benchmark-stop")).clicked() {
+                        self.benchmark.stop();
+                    }
+                });
+            } else if ui.add(
+                Button::new(RichText::new(tr("benchmark-run")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([200.0, 40.0].into())
+            ).clicked() {
+                self.benchmark.start(self.operations.backend.config());
+            }
+        });
+    }
+
+    // Speed of each backend at each size, as measured so far
+    fn show_benchmark_results(&mut self, ui: &mut Ui) {
+        if self.benchmark.rows().is_empty() {
+            return;
+        }
+
+        ui.group(|ui| {
+            Grid::new("benchmark_results").num_columns(6).striped(true).spacing([20.0, 4.0]).show(ui, |ui| {
+                for heading in ["benchmark-size", "benchmark-software", "benchmark-latency", "benchmark-hardware", "benchmark-latency", "benchmark-faster"] {
+                    ui.label(RichText::new(tr(heading)).strong());
+                }
+                ui.end_row();
+
+                for size in benchmark::SIZES {
+                    let local = self.benchmark.result(BackendKind::Local, size);
+                    let device = self.benchmark.result(BackendKind::Embedded, size);
+                    if local.is_none() && device.is_none() {
+                        continue;
+                    }
+
+                    ui.label(locale::format_size(size as u64));
+                    for result in [&local, &device] {
+                        match result {
+                            Some(result) => {
+                                ui.label(format!("{:.1} MB/s", result.megabytes_per_sec()));
+                                ui.label(format_latency(result.latency));
+                            },
+                            None => {
+                                ui.label("—");
+                                ui.label("—");
+                            },
+                        }
+                    }
+                    match faster(local.as_ref(), device.as_ref()) {
+                        Some(BackendKind::Local) => ui.label(tr("benchmark-software")),
+                        Some(BackendKind::Embedded) => ui.label(RichText::new(tr("benchmark-hardware")).color(self.theme.success)),
+                        None => ui.label(""),
+                    };
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+/// Get the backend with the higher throughput, if both were measured
+fn faster(local: Option<&BenchmarkResult>, device: Option<&BenchmarkResult>) -> Option<BackendKind> {
+    let (local, device) = (local?, device?);
+    Some(if device.megabytes_per_sec() > local.megabytes_per_sec() { BackendKind::Embedded } else { BackendKind::Local })
+}
+
+/// Format the time to encrypt one buffer
+fn format_latency(latency: Duration) -> String {
+    let micros = latency.as_micros();
+    if micros < 1_000 {
+        format!("{} µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1} ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.2} s", latency.as_secs_f64())
+    }
+}
@@ -0,0 +1,164 @@
+use eframe::egui::{Ui, Button, ComboBox, Grid, ProgressBar, RichText, Rounding, ScrollArea, TextEdit};
+
+use crate::catalog::CatalogNames;
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::gui::utils;
+use crate::i18n::{tr, tr_args};
+
+/// Catalog screen trait
+pub trait CatalogScreen {
+    fn show_catalog(&mut self, ui: &mut Ui);
+    fn show_catalog_files(&mut self, ui: &mut Ui);
+}
+
+impl CatalogScreen for CrustyApp {
+    fn show_catalog(&mut self, ui: &mut Ui) {
+        let running = self.catalog.is_running();
+        let mut start = false;
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("catalog-title")).size(28.0));
+            ui.add_space(10.0);
+            ui.label(tr("catalog-explained"));
+            ui.add_space(20.0);
+
+            Grid::new("catalog_options").num_columns(3).spacing([20.0, 10.0]).show(ui, |ui| {
+                ui.label(tr("catalog-folder"));
+                ui.label(self.catalog.folder.as_ref().map(|path| path.display().to_string()).unwrap_or_else(|| tr("settings-none")));
+                if ui.add_enabled(!running, Button::new(tr("settings-choose"))).clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select a Folder of Encrypted Files")
+                        .pick_folder() {
+                        self.catalog.folder = Some(folder);
+                    }
+                }
+                ui.end_row();
+
+                ui.label(tr("catalog-names"));
+                ComboBox::from_id_source("catalog_names")
+                    .selected_text(self.catalog.names.name())
+                    .width(220.0)
+                    .show_ui(ui, |ui| {
+                        for names in CatalogNames::ALL {
+                            ui.selectable_value(&mut self.catalog.names, names, names.name());
+                        }
+                    });
+                ui.label("");
+                ui.end_row();
+            });
+
+            ui.add_space(10.0);
+            if let Some(fraction) = self.catalog.progress() {
+                ui.add(ProgressBar::new(fraction).show_percentage().animate(true));
+            } else if ui.add_enabled(
+                self.catalog.folder.is_some(),
+                Button::new(RichText::new(tr("catalog-make")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([200.0, 40.0].into())
+            ).clicked() {
+                start = true;
+            }
+        });
+
+        if start {
+            self.start_catalog();
+        }
+
+        ui.add_space(10.0);
+        self.show_catalog_files(ui);
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            if ui.add_enabled(
+                !running,
+                Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([120.0, 40.0].into())
+            ).clicked() {
+                self.state = AppState::MainScreen;
+            }
+        });
+    }
+
+    // The files of the last catalog, and finding a requested one
+    fn show_catalog_files(&mut self, ui: &mut Ui) {
+        if self.catalog.catalog().is_none() {
+            return;
+        }
+        let mut find_id = std::mem::take(&mut self.catalog.find_id);
+        let catalog = self.catalog.catalog().unwrap();
+        let mut save_to = None;
+        let mut open_folder = None;
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.heading(tr_args("catalog-file-count", &[("count", catalog.files.len().into())]));
+                if ui.button(tr("catalog-save")).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Save Catalog")
+                        .set_file_name("catalog.csv")
+                        .add_filter("CSV", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .save_file() {
+                        save_to = Some(path);
+                    }
+                }
+            });
+            ui.label(RichText::new(tr("catalog-locations-not-saved")).color(self.theme.text_secondary));
+            ui.add_space(5.0);
+
+            ScrollArea::vertical().max_height(250.0).id_source("catalog_files").show(ui, |ui| {
+                Grid::new("catalog_entries").num_columns(5).striped(true).spacing([20.0, 4.0]).show(ui, |ui| {
+                    for heading in ["catalog-id", "catalog-name", "catalog-size", "catalog-modified", "catalog-key"].map(tr) {
+                        ui.label(RichText::new(heading).strong());
+                    }
+                    ui.end_row();
+                    for entry in &catalog.files {
+                        ui.monospace(&entry.id);
+                        ui.label(entry.name.as_deref().unwrap_or("-"));
+                        ui.label(utils::format_file_size(entry.size));
+                        ui.label(&entry.modified);
+                        match &entry.key_id {
+                            Some(key_id) => ui.label(key_id),
+                            None => ui.label(RichText::new(tr("catalog-no-saved-key")).color(self.theme.error)),
+                        };
+                        ui.end_row();
+                    }
+                });
+            });
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label(tr("catalog-find-by-id"));
+                ui.add(TextEdit::singleline(&mut find_id).desired_width(180.0).font(eframe::egui::TextStyle::Monospace));
+            });
+            if !find_id.trim().is_empty() {
+                match catalog.find(&find_id) {
+                    Some(entry) => {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(entry.path.display().to_string()).color(self.theme.success));
+                            if ui.button(tr("catalog-show-in-folder")).clicked() {
+                                open_folder = entry.path.parent().map(|parent| parent.to_path_buf());
+                            }
+                        });
+                    },
+                    None => {
+                        ui.label(RichText::new(tr("catalog-no-such-id")).color(self.theme.error));
+                    },
+                }
+            }
+        });
+
+        self.catalog.find_id = find_id;
+        if let Some(path) = save_to {
+            self.catalog.save(&path);
+        }
+        if let Some(folder) = open_folder {
+            utils::open_folder(&folder);
+        }
+    }
+}
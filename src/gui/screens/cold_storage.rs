@@ -0,0 +1,302 @@
+use eframe::egui::{Ui, Button, DragValue, Grid, ProgressBar, RichText, Rounding, TextEdit};
+
+use crate::cold_storage::Media;
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::{AppState, ColdStorageStep};
+use crate::gui::utils;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+
+/// Cold-storage export screen trait
+pub trait ColdStorageScreen {
+    fn show_cold_storage_export(&mut self, ui: &mut Ui);
+    fn show_cold_storage_folder_step(&mut self, ui: &mut Ui);
+    fn show_cold_storage_media_step(&mut self, ui: &mut Ui);
+    fn show_cold_storage_recovery_step(&mut self, ui: &mut Ui);
+    fn show_cold_storage_export_step(&mut self, ui: &mut Ui);
+}
+
+impl ColdStorageScreen for CrustyApp {
+    fn show_cold_storage_export(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("cold-storage-title")).size(28.0));
+            ui.add_space(10.0);
+
+            // Wizard steps indicator
+            ui.horizontal(|ui| {
+                let current = ColdStorageStep::ALL.iter().position(|step| *step == self.cold_storage_step).unwrap_or(0);
+                for (i, step) in ColdStorageStep::ALL.into_iter().enumerate() {
+                    let is_completed = i < current;
+                    let text_color = if i == current {
+                        self.theme.accent
+                    } else if is_completed {
+                        self.theme.success
+                    } else {
+                        self.theme.text_secondary
+                    };
+
+                    // Steps cannot be revisited while the set is being written
+                    if ui.add(Button::new(RichText::new(step.to_string()).color(text_color).strong())
+                        .fill(self.theme.background)
+                        .rounding(Rounding::same(5.0))
+                    ).clicked() && is_completed && self.cold_storage.job_progress().is_none() {
+                        self.cold_storage_step = step;
+                    }
+
+                    if step != ColdStorageStep::Export {
+                        ui.label(RichText::new(" → ").color(self.theme.text_secondary));
+                    }
+                }
+            });
+
+            ui.add_space(20.0);
+
+            match self.cold_storage_step {
+                ColdStorageStep::Folder => self.show_cold_storage_folder_step(ui),
+                ColdStorageStep::Media => self.show_cold_storage_media_step(ui),
+                ColdStorageStep::Recovery => self.show_cold_storage_recovery_step(ui),
+                ColdStorageStep::Export => self.show_cold_storage_export_step(ui),
+            }
+
+            ui.add_space(20.0);
+
+            // Navigation buttons
+            let running = self.cold_storage.job_progress().is_some();
+            ui.horizontal(|ui| {
+                if self.cold_storage_step != ColdStorageStep::Folder && !running && ui.add_sized(
+                    [120.0, 40.0],
+                    Button::new(RichText::new(tr("button-previous")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    self.cold_storage_step = self.cold_storage_step.previous();
+                }
+
+                if ui.add_enabled(
+                    !running,
+                    Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                        .min_size([120.0, 40.0].into())
+                ).clicked() {
+                    self.state = AppState::Dashboard;
+                }
+
+                if self.cold_storage_step != ColdStorageStep::Export {
+                    let next_enabled = match self.cold_storage_step {
+                        ColdStorageStep::Folder => {
+                            self.cold_storage.folder.is_some()
+                                && self.cold_storage.output_dir.is_some()
+                                && !self.cold_storage.label.trim().is_empty()
+                        },
+                        _ => self.cold_storage.is_ready(),
+                    };
+
+                    if ui.add_sized(
+                        [120.0, 40.0],
+                        Button::new(RichText::new(tr("button-next")).color(self.theme.button_text))
+                            .fill(if next_enabled { self.theme.accent } else { self.theme.button_normal })
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        if next_enabled {
+                            self.cold_storage_step = self.cold_storage_step.next();
+                        } else if self.cold_storage_step == ColdStorageStep::Folder {
+                            self.show_error(&tr("error-cold-storage-choices"));
+                        } else {
+                            self.show_error(&tr("error-cold-storage-step"));
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    // Folder step
+    fn show_cold_storage_folder_step(&mut self, ui: &mut Ui) {
+        ui.group(|ui| {
+            ui.heading(tr("cold-storage-step-folder"));
+            ui.label(tr("cold-storage-folder-explained"));
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("cold-storage-select-folder")).clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select Folder to Archive")
+                        .pick_folder() {
+                        self.cold_storage.select_folder(folder);
+                    }
+                }
+                match &self.cold_storage.folder {
+                    Some(folder) => ui.label(folder.display().to_string()),
+                    None => ui.label(RichText::new(tr("cold-storage-no-folder")).color(self.theme.text_secondary)),
+                };
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(tr("cold-storage-label"));
+                ui.add(TextEdit::singleline(&mut self.cold_storage.label)
+                    .desired_width(250.0)
+                    .hint_text(tr("cold-storage-label-hint")));
+            });
+            ui.label(RichText::new(tr("cold-storage-label-explained")).color(self.theme.text_secondary));
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("cold-storage-write-to")).clicked() {
+                    if let Some(dir) = rfd::FileDialog::new()
+                        .set_title("Select Where to Write the Set")
+                        .pick_folder() {
+                        self.cold_storage.output_dir = Some(dir);
+                    }
+                }
+                match &self.cold_storage.output_dir {
+                    Some(dir) => ui.label(dir.display().to_string()),
+                    None => ui.label(RichText::new(tr("cold-storage-no-folder")).color(self.theme.text_secondary)),
+                };
+            });
+        });
+    }
+
+    // Media step
+    fn show_cold_storage_media_step(&mut self, ui: &mut Ui) {
+        ui.group(|ui| {
+            ui.heading(tr("cold-storage-step-media"));
+            ui.label(tr("cold-storage-media-explained"));
+            ui.add_space(10.0);
+
+            for media in Media::PRESETS {
+                ui.radio_value(&mut self.cold_storage.media, media, media.describe());
+            }
+
+            ui.horizontal(|ui| {
+                let custom = matches!(self.cold_storage.media, Media::Custom(_));
+                if ui.radio(custom, tr("cold-storage-custom-size")).clicked() && !custom {
+                    self.cold_storage.media = Media::Custom(1024);
+                }
+                if let Media::Custom(megabytes) = &mut self.cold_storage.media {
+                    ui.add(DragValue::new(megabytes)
+                        .clamp_range(17..=u64::MAX / (1024 * 1024))
+                        .suffix(" MB"));
+                }
+            });
+
+            ui.add_space(5.0);
+            ui.label(RichText::new(tr("cold-storage-parity-explained")).color(self.theme.text_secondary));
+        });
+    }
+
+    // Recovery step
+    fn show_cold_storage_recovery_step(&mut self, ui: &mut Ui) {
+        ui.group(|ui| {
+            ui.heading(tr("cold-storage-step-shares"));
+            ui.label(tr("cold-storage-shares-explained"));
+            ui.label(tr("cold-storage-shares-custodians"));
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.label(tr("cold-storage-shares-needed"));
+                ui.add(DragValue::new(&mut self.cold_storage.threshold).clamp_range(2..=255));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("cold-storage-shares-printed"));
+                let min_shares = self.cold_storage.threshold;
+                ui.add(DragValue::new(&mut self.cold_storage.shares).clamp_range(min_shares..=255));
+            });
+
+            if self.cold_storage.shares < self.cold_storage.threshold {
+                ui.label(RichText::new(tr("cold-storage-shares-too-few")).color(self.theme.error));
+            }
+        });
+    }
+
+    // Export step
+    fn show_cold_storage_export_step(&mut self, ui: &mut Ui) {
+        let mut start = false;
+        let mut delete_sheet = false;
+        let mut another = false;
+
+        ui.group(|ui| {
+            ui.heading(tr("cold-storage-step-write"));
+            ui.add_space(10.0);
+
+            Grid::new("cold_storage_summary").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                ui.label(tr("catalog-folder"));
+                ui.label(self.cold_storage.folder.as_ref().map(|folder| folder.display().to_string()).unwrap_or_default());
+                ui.end_row();
+                ui.label(tr("cold-storage-label"));
+                ui.label(self.cold_storage.label.trim());
+                ui.end_row();
+                ui.label(tr("cold-storage-media"));
+                ui.label(self.cold_storage.media.describe());
+                ui.end_row();
+                ui.label(tr("cold-storage-recovery-shares"));
+                ui.label(tr_args("cold-storage-shares-of", &[("needed", self.cold_storage.threshold.into()), ("total", self.cold_storage.shares.into())]));
+                ui.end_row();
+            });
+
+            ui.add_space(10.0);
+
+            if let Some(fraction) = self.cold_storage.job_progress() {
+                ui.add(ProgressBar::new(fraction).show_percentage().animate(true));
+            } else if let Some(report) = self.cold_storage.report() {
+                ui.label(RichText::new(tr_args("cold-storage-wrote", &[("folder", report.set_dir.display().to_string().into())])).color(self.theme.success));
+                ui.label(tr_args("cold-storage-key-fingerprint", &[("fingerprint", report.manifest.key_fingerprint.as_str().into())]));
+                ui.add_space(5.0);
+
+                Grid::new("cold_storage_volumes").num_columns(3).spacing([20.0, 4.0]).show(ui, |ui| {
+                    ui.label(RichText::new(tr("cold-storage-volume")).strong());
+                    ui.label(RichText::new(tr("catalog-size")).strong());
+                    ui.label(RichText::new("SHA-256").strong());
+                    ui.end_row();
+                    for volume in &report.manifest.volumes {
+                        ui.label(&volume.file_name);
+                        ui.label(locale::format_size(volume.len));
+                        ui.label(RichText::new(&volume.sha256).monospace());
+                        ui.end_row();
+                    }
+                });
+
+                ui.add_space(10.0);
+                ui.label(tr("cold-storage-burn"));
+                ui.label(tr("cold-storage-hand-out"));
+                ui.add_space(5.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button(tr("cold-storage-open-set")).clicked() {
+                        utils::open_folder(&report.set_dir);
+                    }
+                    if report.recovery_sheet.exists() && ui.button(tr("cold-storage-delete-sheet")).clicked() {
+                        delete_sheet = true;
+                    }
+                    if ui.button(tr("cold-storage-start-another")).clicked() {
+                        another = true;
+                    }
+                });
+
+                if report.recovery_sheet.exists() {
+                    ui.label(RichText::new(tr("cold-storage-sheet-warning")).color(self.theme.error));
+                }
+            } else if ui.add_sized(
+                [200.0, 40.0],
+                Button::new(RichText::new(tr("cold-storage-write")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+            ).clicked() {
+                start = true;
+            }
+        });
+
+        if start {
+            self.cold_storage.start();
+        }
+        if delete_sheet {
+            self.cold_storage.delete_recovery_sheet();
+        }
+        if another {
+            self.cold_storage.reset();
+            self.cold_storage_step = ColdStorageStep::Folder;
+        }
+    }
+}
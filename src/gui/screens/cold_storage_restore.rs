@@ -0,0 +1,392 @@
+use eframe::egui::{Ui, Button, Grid, ProgressBar, RichText, Rounding, TextEdit};
+
+use crate::cold_storage::VolumeStatus;
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::{AppState, ColdStorageRestoreStep};
+use crate::gui::utils;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+
+/// Cold-storage restore screen trait
+pub trait ColdStorageRestoreScreen {
+    fn show_cold_storage_restore(&mut self, ui: &mut Ui);
+    fn show_cold_storage_media_select_step(&mut self, ui: &mut Ui);
+    fn show_cold_storage_verify_step(&mut self, ui: &mut Ui);
+    fn show_cold_storage_key_step(&mut self, ui: &mut Ui);
+    fn show_cold_storage_restore_step(&mut self, ui: &mut Ui);
+}
+
+impl ColdStorageRestoreScreen for CrustyApp {
+    fn show_cold_storage_restore(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("restore-title")).size(28.0));
+            ui.add_space(10.0);
+
+            // Wizard steps indicator
+            let running = self.cold_storage_restore.job_progress().is_some();
+            ui.horizontal(|ui| {
+                let current = ColdStorageRestoreStep::ALL.iter().position(|step| *step == self.cold_storage_restore_step).unwrap_or(0);
+                for (i, step) in ColdStorageRestoreStep::ALL.into_iter().enumerate() {
+                    let is_completed = i < current;
+                    let text_color = if i == current {
+                        self.theme.accent
+                    } else if is_completed {
+                        self.theme.success
+                    } else {
+                        self.theme.text_secondary
+                    };
+
+                    // Steps cannot be revisited while the set is being checked or restored
+                    if ui.add(Button::new(RichText::new(step.to_string()).color(text_color).strong())
+                        .fill(self.theme.background)
+                        .rounding(Rounding::same(5.0))
+                    ).clicked() && is_completed && !running {
+                        self.cold_storage_restore_step = step;
+                    }
+
+                    if step != ColdStorageRestoreStep::Restore {
+                        ui.label(RichText::new(" → ").color(self.theme.text_secondary));
+                    }
+                }
+            });
+
+            ui.add_space(20.0);
+
+            match self.cold_storage_restore_step {
+                ColdStorageRestoreStep::Media => self.show_cold_storage_media_select_step(ui),
+                ColdStorageRestoreStep::Verify => self.show_cold_storage_verify_step(ui),
+                ColdStorageRestoreStep::Key => self.show_cold_storage_key_step(ui),
+                ColdStorageRestoreStep::Restore => self.show_cold_storage_restore_step(ui),
+            }
+
+            ui.add_space(20.0);
+
+            // Navigation buttons
+            let running = self.cold_storage_restore.job_progress().is_some();
+            ui.horizontal(|ui| {
+                if self.cold_storage_restore_step != ColdStorageRestoreStep::Media && !running && ui.add_sized(
+                    [120.0, 40.0],
+                    Button::new(RichText::new(tr("button-previous")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    self.cold_storage_restore_step = self.cold_storage_restore_step.previous();
+                }
+
+                if ui.add_enabled(
+                    !running,
+                    Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                        .min_size([120.0, 40.0].into())
+                ).clicked() {
+                    self.state = AppState::Dashboard;
+                }
+
+                if self.cold_storage_restore_step != ColdStorageRestoreStep::Restore {
+                    let restore = &self.cold_storage_restore;
+                    let next_enabled = !running && match self.cold_storage_restore_step {
+                        ColdStorageRestoreStep::Media => restore.manifest().is_some(),
+                        ColdStorageRestoreStep::Verify => restore.check().is_some_and(|check| check.is_complete()),
+                        _ => restore.has_key(),
+                    };
+
+                    if ui.add_sized(
+                        [120.0, 40.0],
+                        Button::new(RichText::new(tr("button-next")).color(self.theme.button_text))
+                            .fill(if next_enabled { self.theme.accent } else { self.theme.button_normal })
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        if next_enabled {
+                            self.cold_storage_restore_step = self.cold_storage_restore_step.next();
+                        } else {
+                            let message = match self.cold_storage_restore_step {
+                                ColdStorageRestoreStep::Media => "error-restore-manifest",
+                                ColdStorageRestoreStep::Verify => "error-restore-volumes",
+                                _ => "error-restore-key",
+                            };
+                            self.show_error(&tr(message));
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    // Media step
+    fn show_cold_storage_media_select_step(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+
+        ui.group(|ui| {
+            ui.heading(tr("restore-step-find"));
+            ui.label(tr("restore-find-explained"));
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("restore-open-manifest")).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Open the Set's Manifest")
+                        .add_filter("Manifest", &["txt"])
+                        .pick_file() {
+                        self.cold_storage_restore.select_manifest(path);
+                    }
+                }
+                match self.cold_storage_restore.manifest_path() {
+                    Some(path) => ui.label(path.display().to_string()),
+                    None => ui.label(RichText::new(tr("restore-no-manifest")).color(self.theme.text_secondary)),
+                };
+            });
+
+            let Some(manifest) = self.cold_storage_restore.manifest() else { return };
+            ui.add_space(10.0);
+            Grid::new("cold_storage_restore_set").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                ui.label(tr("restore-set"));
+                ui.label(&manifest.label);
+                ui.end_row();
+                ui.label(tr("restore-written"));
+                ui.label(&manifest.created_at);
+                ui.end_row();
+                ui.label(tr("catalog-folder"));
+                ui.label(tr_args("restore-folder-files", &[("folder", manifest.source_name.as_str().into()), ("count", manifest.file_count.into())]));
+                ui.end_row();
+            });
+
+            ui.add_space(10.0);
+            ui.label(RichText::new(tr("restore-folders-searched")).strong());
+            for (i, folder) in self.cold_storage_restore.folders().iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(folder.display().to_string());
+                    if ui.small_button(tr("button-remove")).clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if ui.button(tr("restore-add-disc")).clicked() {
+                if let Some(folder) = rfd::FileDialog::new()
+                    .set_title("Select a Disc or Folder with Volumes")
+                    .pick_folder() {
+                    self.cold_storage_restore.add_folder(folder);
+                }
+            }
+
+            ui.add_space(10.0);
+            Grid::new("cold_storage_restore_found").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                for (file_name, path) in self.cold_storage_restore.found_volumes() {
+                    ui.label(file_name);
+                    match path {
+                        Some(path) => ui.label(RichText::new(path.display().to_string()).color(self.theme.success)),
+                        None => ui.label(RichText::new(tr("restore-not-found")).color(self.theme.error)),
+                    };
+                    ui.end_row();
+                }
+            });
+        });
+
+        if let Some(index) = removed {
+            self.cold_storage_restore.remove_folder(index);
+        }
+    }
+
+    // Verify step
+    fn show_cold_storage_verify_step(&mut self, ui: &mut Ui) {
+        let mut start = false;
+
+        ui.group(|ui| {
+            ui.heading(tr("restore-step-check"));
+            ui.label(tr("restore-check-explained"));
+            ui.add_space(10.0);
+
+            if let Some(fraction) = self.cold_storage_restore.job_progress() {
+                ui.add(ProgressBar::new(fraction).show_percentage().animate(true));
+                return;
+            }
+
+            if let Some(check) = self.cold_storage_restore.check() {
+                Grid::new("cold_storage_restore_check").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                    for volume in &check.volumes {
+                        ui.label(&volume.file_name);
+                        let color = if volume.status.is_usable() { self.theme.success } else { self.theme.error };
+                        ui.label(RichText::new(volume.status.to_string()).color(color));
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(5.0);
+                if !check.is_complete() {
+                    ui.label(RichText::new(tr("restore-add-missing")).color(self.theme.error));
+                }
+            }
+
+            if ui.add_sized(
+                [200.0, 40.0],
+                Button::new(RichText::new(tr("restore-check")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+            ).clicked() {
+                start = true;
+            }
+        });
+
+        if start {
+            self.cold_storage_restore.start_check();
+        }
+    }
+
+    // Key step
+    fn show_cold_storage_key_step(&mut self, ui: &mut Ui) {
+        let mut recover = false;
+        let mut changed_share = None;
+
+        ui.group(|ui| {
+            ui.heading(tr("restore-step-key"));
+            ui.add_space(10.0);
+
+            if self.cold_storage_restore.has_key() {
+                ui.label(RichText::new(tr("restore-key-rebuilt")).color(self.theme.success));
+                return;
+            }
+
+            if self.cold_storage_restore.needs_passphrase() {
+                ui.label(tr("restore-enter-passphrase"));
+                ui.add(TextEdit::singleline(&mut *self.cold_storage_restore.passphrase)
+                    .password(true)
+                    .desired_width(250.0));
+                self.secrets_shown |= !self.cold_storage_restore.passphrase.is_empty();
+            } else {
+                let threshold = self.cold_storage_restore.manifest().map_or(0, |manifest| manifest.threshold);
+                ui.label(tr_args("restore-enter-shares", &[("count", threshold.into())]));
+                ui.add_space(5.0);
+
+                if self.cold_storage_restore.shares.iter().any(|share| !share.trim().is_empty()) {
+                    self.secrets_shown = true;
+                }
+                for (i, share) in self.cold_storage_restore.shares.iter_mut().enumerate() {
+                    ui.label(tr_args("restore-share-number", &[("number", (i + 1).into())]));
+                    if ui.add(TextEdit::multiline(share)
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(2)
+                        .hint_text(tr("restore-share-hint"))
+                    ).changed() {
+                        changed_share = Some(i);
+                    }
+                }
+                if ui.button(tr("restore-add-share-field")).clicked() {
+                    self.cold_storage_restore.add_share_field();
+                }
+            }
+
+            ui.add_space(10.0);
+            if ui.add_sized(
+                [200.0, 40.0],
+                Button::new(RichText::new(tr("restore-rebuild-key")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+            ).clicked() {
+                recover = true;
+            }
+        });
+
+        if let Some(index) = changed_share {
+            self.cold_storage_restore.share_changed(index);
+        }
+        if recover {
+            self.cold_storage_restore.recover_key();
+        }
+    }
+
+    // Restore step
+    fn show_cold_storage_restore_step(&mut self, ui: &mut Ui) {
+        let mut start = false;
+        let mut save_to = None;
+        let mut another = false;
+
+        ui.group(|ui| {
+            ui.heading(tr("restore-step-restore"));
+            ui.add_space(10.0);
+
+            if let Some(fraction) = self.cold_storage_restore.job_progress() {
+                ui.add(ProgressBar::new(fraction).show_percentage().animate(true));
+            } else if let Some(report) = self.cold_storage_restore.report() {
+                // Verification report
+                let color = if report.is_complete() { self.theme.success } else { self.theme.error };
+                ui.label(RichText::new(tr_args("restore-restored", &[("folder", report.restored_dir.display().to_string().into())])).color(color));
+                ui.add_space(5.0);
+
+                Grid::new("cold_storage_restore_report").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                    ui.label(tr("restore-files"));
+                    ui.label(tr_args("restore-files-restored", &[("restored", report.files_restored.into()), ("total", report.manifest.file_count.into())]));
+                    ui.end_row();
+                    ui.label(tr("restore-archive"));
+                    ui.label(tr_args("restore-archive-matches", &[("size", locale::format_size(report.manifest.archive_len).into())]));
+                    ui.end_row();
+                    ui.label(tr("audit-key"));
+                    ui.label(tr_args("restore-key-matches", &[("fingerprint", report.manifest.key_fingerprint.as_str().into())]));
+                    ui.end_row();
+                    for volume in &report.volumes {
+                        ui.label(&volume.file_name);
+                        ui.label(volume.status.to_string());
+                        ui.end_row();
+                    }
+                });
+
+                if report.volumes.iter().any(|volume| matches!(volume.status, VolumeStatus::Repaired(_))) {
+                    ui.label(RichText::new(tr("restore-repaired")).color(self.theme.error));
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button(tr("restore-open-folder")).clicked() {
+                        utils::open_folder(&report.restored_dir);
+                    }
+                    if ui.button(tr("audit-save-report")).clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_title("Save Restore Report")
+                            .set_file_name(format!("{} restore report.txt", report.manifest.label))
+                            .save_file() {
+                            save_to = Some(path);
+                        }
+                    }
+                    if ui.button(tr("restore-another")).clicked() {
+                        another = true;
+                    }
+                });
+            } else {
+                ui.horizontal(|ui| {
+                    if ui.button(tr("restore-to")).clicked() {
+                        if let Some(dir) = rfd::FileDialog::new()
+                            .set_title("Select Where to Restore the Folder")
+                            .pick_folder() {
+                            self.cold_storage_restore.output_dir = Some(dir);
+                        }
+                    }
+                    match &self.cold_storage_restore.output_dir {
+                        Some(dir) => ui.label(dir.display().to_string()),
+                        None => ui.label(RichText::new(tr("cold-storage-no-folder")).color(self.theme.text_secondary)),
+                    };
+                });
+
+                ui.add_space(10.0);
+                if ui.add_enabled(
+                    self.cold_storage_restore.output_dir.is_some(),
+                    Button::new(RichText::new(tr("restore-start")).color(self.theme.button_text))
+                        .fill(self.theme.accent)
+                        .rounding(Rounding::same(8.0))
+                        .min_size([200.0, 40.0].into())
+                ).clicked() {
+                    start = true;
+                }
+            }
+        });
+
+        if start {
+            self.cold_storage_restore.start_restore();
+        }
+        if let Some(path) = save_to {
+            self.cold_storage_restore.save_report(&path);
+        }
+        if another {
+            self.cold_storage_restore.reset();
+            self.cold_storage_restore_step = ColdStorageRestoreStep::Media;
+        }
+    }
+}
@@ -1,10 +1,16 @@
-use eframe::egui::{Ui, RichText, Button, Rounding, TopBottomPanel};
+use eframe::egui::{Ui, RichText, Button, Rounding, ScrollArea, TopBottomPanel};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::AppState;
 use crate::gui::action_bar::ActionBar;
 use crate::gui::file_list::EnhancedFileList;
+use crate::history::{HistoryEntry, HistoryOperation};
+use crate::i18n::{tr, tr_args};
+use crate::locale;
 use crate::start_operation::FileOperation;
 
+/// Most files of the history shown on the dashboard
+const HISTORY_SHOWN: usize = 10;
+
 /// Dashboard screen trait
 pub trait DashboardScreen {
     fn show_dashboard(&mut self, ui: &mut Ui);
@@ -21,30 +27,61 @@ impl DashboardScreen for CrustyApp {
         
         ui.vertical_centered(|ui| {
             ui.add_space(10.0);
-            ui.heading(RichText::new("CRUSTy Dashboard").size(24.0));
-            ui.label("Secure file encryption with AES-256-GCM");
+            ui.heading(RichText::new(tr("dashboard-title")).size(24.0));
+            ui.label(tr("dashboard-subtitle"));
             ui.add_space(20.0);
             
+            // Secured folders that failed their last integrity check
+            if !self.folders.integrity_alerts().is_empty() {
+                let mut dismiss = false;
+                ui.group(|ui| {
+                    ui.label(RichText::new(tr("dashboard-integrity-failed")).color(self.theme.error).strong());
+                    for report in self.folders.integrity_alerts() {
+                        ui.label(tr_args("dashboard-integrity-report", &[
+                            ("folder", report.folder.display().to_string().into()),
+                            ("failed", report.failed.len().into()),
+                            ("missing", report.missing.len().into()),
+                        ]));
+                        for (file, error) in &report.failed {
+                            ui.label(RichText::new(format!("{}: {}", file.display(), error)).color(self.theme.error));
+                        }
+                        for file in &report.missing {
+                            ui.label(RichText::new(tr_args("dashboard-file-missing", &[("file", file.display().to_string().into())])).color(self.theme.error));
+                        }
+                    }
+                    ui.label(RichText::new(tr("dashboard-integrity-explained")).color(self.theme.text_secondary));
+                    if ui.button(tr("ops-dismiss"))
+                        .on_hover_text(tr("dashboard-dismiss-hint"))
+                        .clicked() {
+                        dismiss = true;
+                    }
+                });
+                if dismiss {
+                    self.folders.dismiss_integrity_alerts();
+                }
+                ui.add_space(20.0);
+            }
+            
             // Main actions section
             ui.horizontal(|ui| {
                 ui.vertical(|ui| {
                     ui.add_space(10.0);
-                    ui.heading("Encryption");
+                    ui.heading(tr("dashboard-encryption"));
                     ui.add_space(5.0);
-                    ui.label("Encrypt files with AES-256-GCM");
+                    ui.label(tr("dashboard-encryption-explained"));
                     ui.add_space(10.0);
                     
                     if ui.add_sized(
                         [200.0, 40.0],
-                        Button::new(RichText::new("🔒 Encrypt Files").color(self.theme.button_text))
+                        Button::new(RichText::new(tr("dashboard-encrypt")).color(self.theme.button_text))
                             .fill(self.theme.accent)
                             .rounding(Rounding::same(8.0))
                     ).clicked() {
-                        self.operation = FileOperation::Encrypt;
+                        self.operations.set_operation(FileOperation::Encrypt);
                         self.state = AppState::EncryptionWorkflow;
                         self.encryption_workflow_step = crate::gui::app_state::EncryptionWorkflowStep::Files;
                         self.encryption_workflow_complete = false;
-                        self.show_status("Starting encryption workflow");
+                        self.show_status(&tr("status-workflow-started"));
                     }
                 });
                 
@@ -52,41 +89,110 @@ impl DashboardScreen for CrustyApp {
                 
                 ui.vertical(|ui| {
                     ui.add_space(10.0);
-                    ui.heading("Decryption");
+                    ui.heading(tr("dashboard-decryption"));
                     ui.add_space(5.0);
-                    ui.label("Decrypt previously encrypted files");
+                    ui.label(tr("dashboard-decryption-explained"));
                     ui.add_space(10.0);
                     
                     if ui.add_sized(
                         [200.0, 40.0],
-                        Button::new(RichText::new("🔓 Decrypt Files").color(self.theme.button_text))
+                        Button::new(RichText::new(tr("dashboard-decrypt")).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(8.0))
                     ).clicked() {
-                        self.operation = FileOperation::Decrypt;
+                        self.operations.set_operation(FileOperation::Decrypt);
                         self.state = AppState::Decrypting;
-                        self.show_status("Starting decryption");
+                        self.show_status(&tr("status-decryption-started"));
                     }
                 });
             });
             
             ui.add_space(40.0);
             
+            // Files processed in this and earlier sessions
+            self.show_history(ui);
+            
+            ui.add_space(20.0);
+            
             // Use the enhanced file list
-            self.show_enhanced_file_list(ui);
+            if let Some(file) = self.show_enhanced_file_list(ui) {
+                self.open_files(vec![file]);
+            }
             
             ui.add_space(10.0);
             
             // Switch to main screen button
             if ui.add_sized(
                 [200.0, 35.0],
-                Button::new(RichText::new("Go to Main Screen").color(self.theme.button_text))
+                Button::new(RichText::new(tr("dashboard-main-screen")).color(self.theme.button_text))
                     .fill(self.theme.button_normal)
                     .rounding(Rounding::same(8.0))
             ).clicked() {
                 self.state = AppState::MainScreen;
-                self.show_status("Switched to main screen");
+                self.show_status(&tr("status-switched-to-main"));
             }
         });
     }
 }
+
+impl CrustyApp {
+    /// Show the newest files of the history, with buttons to process them again and to
+    /// open their output folder
+    fn show_history(&mut self, ui: &mut Ui) {
+        let mut again: Option<HistoryEntry> = None;
+        let mut clear = false;
+        
+        ui.group(|ui| {
+            ui.heading(tr("dashboard-history"));
+            let entries = &self.operations.history().entries;
+            if entries.is_empty() {
+                ui.label(RichText::new(tr("dashboard-history-empty")).color(self.theme.text_secondary));
+                return;
+            }
+            
+            ScrollArea::vertical().id_source("dashboard_history").max_height(250.0).show(ui, |ui| {
+                for entry in entries.iter().take(HISTORY_SHOWN) {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(locale::format_stored_timestamp(&entry.finished_at)).color(self.theme.text_secondary));
+                        let (operation, again_label) = match entry.operation {
+                            HistoryOperation::Encrypt => (tr("action-encrypt"), tr("dashboard-encrypt-again")),
+                            HistoryOperation::Decrypt => (tr("action-decrypt"), tr("dashboard-decrypt-again")),
+                            HistoryOperation::Migrate => (tr("dashboard-upgraded"), tr("action-decrypt")),
+                        };
+                        ui.label(operation);
+                        let name = entry.input.file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| entry.input.display().to_string());
+                        ui.label(RichText::new(name).strong())
+                            .on_hover_text(entry.input.display().to_string());
+                        
+                        if ui.add_enabled(entry.input.is_file(), Button::new(again_label))
+                            .on_hover_text(tr("dashboard-again-hint"))
+                            .clicked() {
+                            again = Some(entry.clone());
+                        }
+                        if let Some(folder) = &entry.output_folder {
+                            if ui.add_enabled(folder.is_dir(), Button::new(tr("dashboard-open-output")))
+                                .on_hover_text(folder.display().to_string())
+                                .clicked() {
+                                self.folder_to_open = Some(folder.clone());
+                            }
+                        }
+                    });
+                }
+            });
+            
+            if ui.button(tr("dashboard-clear-history")).clicked() {
+                clear = true;
+            }
+        });
+        
+        if let Some(entry) = again {
+            self.process_again(entry);
+        }
+        if clear {
+            self.operations.clear_history();
+            self.show_service_events();
+        }
+    }
+}
@@ -1,9 +1,13 @@
-use eframe::egui::{Ui, RichText, Button, Rounding, ProgressBar, ScrollArea};
+use eframe::egui::{Ui, RichText, Button, Rounding, ProgressBar, ScrollArea, TextEdit};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::AppState;
-use crate::start_operation::FileOperation;
 use crate::gui::file_list::FileOperationType;
-use std::path::PathBuf;
+use crate::batch_manifest::EntryStatus;
+use crate::gui::help;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+use crate::start_operation::FileOperation;
+use crate::policy::get_policy;
 
 /// Decrypt screen trait
 pub trait DecryptScreen {
@@ -14,18 +18,18 @@ impl DecryptScreen for CrustyApp {
     fn show_decrypt_screen(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
-            ui.heading(RichText::new("Decrypt Files").size(28.0));
+            ui.heading(RichText::new(tr("decrypt-title")).size(28.0));
             ui.add_space(10.0);
             
             // File selection section
             ui.group(|ui| {
-                ui.heading("File Selection");
+                ui.heading(tr("ops-file-selection"));
                 
                 ui.horizontal(|ui| {
-                    let select_text = if self.batch_mode {
-                        "Select Encrypted Files"
+                    let select_text = if self.operations.batch_mode {
+                        tr("decrypt-select-files")
                     } else {
-                        "Select Encrypted File"
+                        tr("decrypt-select-file")
                     };
                     
                     if ui.add_sized(
@@ -37,20 +41,30 @@ impl DecryptScreen for CrustyApp {
                         self.select_files();
                     }
                     
-                    ui.checkbox(&mut self.batch_mode, "Batch Mode");
+                    if ui.add_sized(
+                        [150.0, 30.0],
+                        Button::new(RichText::new(tr("ops-select-folder")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).on_hover_text(tr("ops-select-folder-hint"))
+                        .clicked() {
+                        self.select_folder(FileOperationType::Decrypt);
+                    }
+                    
+                    ui.checkbox(&mut self.operations.batch_mode, tr("ops-batch-mode"));
                 });
                 
                 ui.add_space(5.0);
                 
                 // Display selected files
-                if self.selected_files.is_empty() {
-                    ui.label("No files selected");
+                if self.operations.selected_files().is_empty() {
+                    ui.label(tr("ops-no-files"));
                 } else {
-                    ui.label(format!("Selected {} file(s)", self.selected_files.len()));
+                    ui.label(tr_args("ops-selected-count", &[("count", self.operations.selected_files().len().into())]));
                     
                     ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
-                        for file in &self.selected_files {
-                            ui.label(format!("• {}", file.file_name().unwrap_or_default().to_string_lossy()));
+                        for file in self.operations.selected_files() {
+                            ui.label(format!("• {}", self.operations.relative_path(file).to_string_lossy()));
                         }
                     });
                 }
@@ -60,21 +74,21 @@ impl DecryptScreen for CrustyApp {
             
             // Output directory selection
             ui.group(|ui| {
-                ui.heading("Output Directory");
+                ui.heading(tr("main-output-dir"));
                 
                 if ui.add_sized(
                     [200.0, 30.0],
-                    Button::new(RichText::new("Select Output Directory").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("main-select-output-dir")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     self.select_output_dir();
                 }
                 
-                if let Some(dir) = &self.output_dir {
-                    ui.label(format!("Output directory: {}", dir.display()));
+                if let Some(dir) = self.operations.output_dir() {
+                    ui.label(tr_args("ops-output-dir-is", &[("dir", dir.display().to_string().into())]));
                 } else {
-                    ui.label("No output directory selected");
+                    ui.label(tr("ops-no-output-dir"));
                 }
             });
             
@@ -82,45 +96,33 @@ impl DecryptScreen for CrustyApp {
             
             // Decryption options
             ui.group(|ui| {
-                ui.heading("Decryption Options");
+                ui.heading(tr("decrypt-options"));
                 
                 // Key selection
                 ui.horizontal(|ui| {
-                    ui.label("Decryption Key:");
+                    ui.label(tr("decrypt-key"));
                     
-                    if self.current_key.is_none() {
-                        ui.label(RichText::new("No key selected").color(self.theme.error));
+                    if !self.keys.has_key() {
+                        ui.label(RichText::new(tr("main-no-key-selected")).color(self.theme.error));
                         
                         if ui.add_sized(
                             [120.0, 24.0],
-                            Button::new(RichText::new("Select Key").color(self.theme.button_text))
+                            Button::new(RichText::new(tr("ops-select-key")).color(self.theme.button_text))
                                 .fill(self.theme.button_normal)
                                 .rounding(Rounding::same(5.0))
                         ).clicked() {
                             self.state = AppState::KeyManagement;
                         }
                     } else {
-                        // Find the name of the current key
-                        let key_name = self.current_key.as_ref().map_or_else(
-                            || "Unknown key".to_string(),
-                            |current_key| {
-                                self.saved_keys.iter()
-                                    .find_map(|(name, key)| {
-                                        if key.to_base64() == current_key.to_base64() {
-                                            Some(name.clone())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .unwrap_or_else(|| "Unknown key".to_string())
-                            }
-                        );
+                        // Find the name of the current key (saved keys first, then the smartcard key)
+                        let key_name = self.keys.current_key_name()
+                            .unwrap_or_else(|| tr("ops-unknown-key"));
                         
                         ui.label(RichText::new(&key_name).color(self.theme.success));
                         
                         if ui.add_sized(
                             [120.0, 24.0],
-                            Button::new(RichText::new("Change Key").color(self.theme.button_text))
+                            Button::new(RichText::new(tr("ops-change-key")).color(self.theme.button_text))
                                 .fill(self.theme.button_normal)
                                 .rounding(Rounding::same(5.0))
                         ).clicked() {
@@ -129,15 +131,31 @@ impl DecryptScreen for CrustyApp {
                     }
                 });
                 
+                // Reason for decryption (recorded in the audit log)
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label(if get_policy().require_decrypt_reason {
+                        tr("decrypt-reason-required")
+                    } else {
+                        tr("decrypt-reason")
+                    });
+                    ui.add(TextEdit::singleline(&mut self.operations.decrypt_reason)
+                        .hint_text(tr("decrypt-reason-hint"))
+                        .desired_width(300.0));
+                });
+                
                 // Backend options
                 ui.add_space(5.0);
-                ui.checkbox(&mut self.use_embedded_backend, "Use hardware decryption");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.backend.use_embedded, tr("decrypt-use-hardware"));
+                    help::help_icon(ui, help::EMBEDDED_BACKEND);
+                });
                 
-                if self.use_embedded_backend {
+                if self.operations.backend.use_embedded {
                     ui.horizontal(|ui| {
-                        ui.label("Connection Type:");
-                        ui.radio_value(&mut self.embedded_connection_type, crate::backend::ConnectionType::Usb, "USB");
-                        ui.radio_value(&mut self.embedded_connection_type, crate::backend::ConnectionType::Serial, "Serial");
+                        ui.label(tr("main-connection-type"));
+                        ui.radio_value(&mut self.operations.backend.connection_type, crate::backend::ConnectionType::Usb, "USB");
+                        ui.radio_value(&mut self.operations.backend.connection_type, crate::backend::ConnectionType::Serial, tr("ops-serial"));
                     });
                 }
             });
@@ -145,31 +163,29 @@ impl DecryptScreen for CrustyApp {
             ui.add_space(20.0);
             
             // Progress section (only shown during decryption)
-            if matches!(self.operation, FileOperation::Decrypt) && !self.progress.lock().unwrap().is_empty() {
+            if let (FileOperation::Decrypt | FileOperation::ExtractArchive, Some(overall_progress)) = (self.operations.operation(), self.operations.overall_progress()) {
                 ui.group(|ui| {
-                    ui.heading("Decryption Progress");
+                    ui.heading(tr("decrypt-progress"));
                     
-                    let progress = self.progress.lock().unwrap();
+                    let progress = self.operations.file_progress();
                     
                     // Overall progress
-                    let overall_progress = if progress.is_empty() {
-                        0.0
-                    } else {
-                        progress.iter().sum::<f32>() / progress.len() as f32
-                    };
                     
-                    ui.label(format!("Overall Progress: {:.1}%", overall_progress * 100.0));
+                    ui.label(tr_args("ops-overall-progress", &[("percent", locale::format_percent(overall_progress).into())]));
                     ui.add(ProgressBar::new(overall_progress)
                         .show_percentage()
                         .animate(true));
+                    self.show_pause_control(ui);
+                    self.show_device_progress(ui);
                     
                     ui.add_space(10.0);
                     
                     // Individual file progress
-                    if !self.selected_files.is_empty() && progress.len() == self.selected_files.len() {
+                    let selected_files = self.operations.selected_files();
+                    if !selected_files.is_empty() && progress.len() == selected_files.len() {
                         ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                            for (i, (file, &prog)) in self.selected_files.iter().zip(progress.iter()).enumerate() {
-                                ui.label(format!("File {}: {}", i + 1, file.file_name().unwrap_or_default().to_string_lossy()));
+                            for (i, (file, &prog)) in selected_files.iter().zip(progress.iter()).enumerate() {
+                                ui.label(tr_args("ops-file-number", &[("number", (i + 1).into()), ("file", self.operations.relative_path(file).to_string_lossy().into())]));
                                 ui.add(ProgressBar::new(prog)
                                     .show_percentage()
                                     .animate(true));
@@ -180,64 +196,128 @@ impl DecryptScreen for CrustyApp {
                 });
                 
                 ui.add_space(10.0);
+            }
+            
+            // Results of the last decryption, kept once it is finished
+            if matches!(self.operations.operation(), FileOperation::Decrypt | FileOperation::BatchDecrypt | FileOperation::ExtractArchive) {
+                self.show_operation_results(ui);
+            }
+            
+            // Result of the last manifest check
+            if let Some(check) = self.operations.manifest_check() {
+                ui.group(|ui| {
+                    ui.heading(tr("decrypt-manifest-check"));
+                    ui.label(tr_args("decrypt-manifest-written", &[
+                        ("manifest", check.manifest_path.display().to_string().into()),
+                        ("date", check.manifest.created_at.as_str().into()),
+                        ("fingerprint", check.manifest.key_fingerprint.as_str().into()),
+                    ]));
+                    
+                    ScrollArea::vertical().max_height(150.0).id_source("manifest_check").show(ui, |ui| {
+                        for (entry, status) in &check.files {
+                            let (text, color) = match status {
+                                EntryStatus::Intact => (tr("decrypt-manifest-intact"), self.theme.success),
+                                EntryStatus::Changed => (tr("decrypt-manifest-changed"), self.theme.error),
+                                EntryStatus::Missing => (tr("decrypt-manifest-missing"), self.theme.error),
+                            };
+                            ui.label(RichText::new(format!("{}: {}", entry.file_name, text)).color(color));
+                        }
+                    });
+                });
                 
-                // Results section
-                if !self.operation_results.is_empty() {
-                    ui.group(|ui| {
-                        ui.heading("Results");
-                        
-                        ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
-                            for result in &self.operation_results {
-                                if result.contains("Error") || result.contains("Failed") {
-                                    ui.label(RichText::new(result).color(self.theme.error));
-                                } else {
-                                    ui.label(RichText::new(result).color(self.theme.success));
-                                }
+                ui.add_space(10.0);
+            }
+            
+            // Files decrypted for viewing, until they are shredded
+            if !self.viewer.files().is_empty() {
+                let mut done = None;
+                ui.group(|ui| {
+                    ui.heading(tr("decrypt-viewed-files"));
+                    if !self.viewer.in_memory() {
+                        ui.label(RichText::new(tr("decrypt-viewed-on-disk")).color(self.theme.text_secondary));
+                    }
+                    
+                    let now = std::time::Instant::now();
+                    for (i, file) in self.viewer.files().iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let minutes = file.expires_at.saturating_duration_since(now).as_secs().div_ceil(60);
+                            ui.label(tr_args("decrypt-viewed-file", &[("file", file.source.display().to_string().into()), ("minutes", minutes.into())]));
+                            if ui.button(tr("decrypt-viewed-done")).on_hover_text(tr("decrypt-viewed-done-hint")).clicked() {
+                                done = Some(i);
                             }
                         });
-                    });
+                    }
+                });
+                if let Some(index) = done {
+                    self.viewer.close(index);
                 }
+                
+                ui.add_space(10.0);
             }
             
             ui.add_space(20.0);
             
             // Action buttons
             ui.horizontal(|ui| {
-                let can_decrypt = !self.selected_files.is_empty() && 
-                                 self.output_dir.is_some() && 
-                                 self.current_key.is_some();
+                let can_decrypt = self.operations.is_ready() && 
+                                 self.keys.has_key() &&
+                                 !self.operations.decrypt_reason_missing();
                 
                 if ui.add_sized(
                     [150.0, 40.0],
-                    Button::new(RichText::new("🔓 Decrypt").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("decrypt-start")).color(self.theme.button_text))
                         .fill(if can_decrypt { self.theme.accent } else { self.theme.button_normal })
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     if can_decrypt {
-                        self.operation = FileOperation::Decrypt;
-                        
-                        // Add files to the file list
-                        let files_to_add: Vec<PathBuf> = self.selected_files.clone();
-                        for file in files_to_add {
-                            self.add_file_entry(file, FileOperationType::Decrypt);
-                        }
-                        
-                        // Start decryption
-                        self.show_status("Starting decryption...");
+                        self.begin_decrypt();
+                    } else if self.operations.decrypt_reason_missing() {
+                        self.show_error(&tr("error-decrypt-reason"));
                     } else {
-                        self.show_error("Please select files, output directory, and decryption key");
+                        self.show_error(&tr("error-select-files-decrypt"));
+                    }
+                }
+                
+                // Decrypt one file to a private folder and open it, without an output copy
+                let can_view = self.operations.selected_files().len() == 1 &&
+                               self.keys.current_key().is_some() &&
+                               !self.operations.decrypt_reason_missing();
+                if ui.add_enabled(
+                    can_view,
+                    Button::new(RichText::new(tr("decrypt-view-securely")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                        .min_size([150.0, 40.0].into())
+                ).clicked() {
+                    self.view_securely();
+                }
+                help::help_icon(ui, help::VIEW_SECURELY);
+                
+                // Check encrypted files against the manifest of their batch
+                if ui.add_enabled(
+                    !self.operations.is_checking_manifest(),
+                    Button::new(RichText::new(tr("decrypt-verify-manifest")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                        .min_size([200.0, 40.0].into())
+                ).clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Select the Batch Manifest")
+                        .add_filter("Batch Manifest", &["txt"])
+                        .pick_file() {
+                        self.check_against_manifest(path);
                     }
                 }
                 
                 // Back button
                 if ui.add_sized(
                     [120.0, 40.0],
-                    Button::new(RichText::new("Back").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("button-back")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     self.state = AppState::Dashboard;
-                    self.operation = FileOperation::None;
+                    self.operations.set_operation(FileOperation::None);
                 }
             });
         });
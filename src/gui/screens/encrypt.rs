@@ -1,31 +1,34 @@
 use eframe::egui::{Ui, RichText, Button, Rounding, ProgressBar, ScrollArea};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::AppState;
-use crate::start_operation::FileOperation;
 use crate::gui::file_list::FileOperationType;
-use std::path::PathBuf;
+use crate::gui::help;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+use crate::start_operation::FileOperation;
 
 /// Encrypt screen trait
 pub trait EncryptScreen {
     fn show_encrypt_screen(&mut self, ui: &mut Ui);
+    fn show_replace_originals_options(&mut self, ui: &mut Ui);
 }
 
 impl EncryptScreen for CrustyApp {
     fn show_encrypt_screen(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
-            ui.heading(RichText::new("Encrypt Files").size(28.0));
+            ui.heading(RichText::new(tr("encrypt-title")).size(28.0));
             ui.add_space(10.0);
             
             // File selection section
             ui.group(|ui| {
-                ui.heading("File Selection");
+                ui.heading(tr("ops-file-selection"));
                 
                 ui.horizontal(|ui| {
-                    let select_text = if self.batch_mode {
-                        "Select Files"
+                    let select_text = if self.operations.batch_mode {
+                        tr("ops-select-files")
                     } else {
-                        "Select File"
+                        tr("ops-select-file")
                     };
                     
                     if ui.add_sized(
@@ -37,22 +40,37 @@ impl EncryptScreen for CrustyApp {
                         self.select_files();
                     }
                     
-                    ui.checkbox(&mut self.batch_mode, "Batch Mode");
+                    if ui.add_sized(
+                        [150.0, 30.0],
+                        Button::new(RichText::new(tr("ops-select-folder")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).on_hover_text(tr("ops-select-folder-hint"))
+                        .clicked() {
+                        self.select_folder(FileOperationType::Encrypt);
+                    }
+                    
+                    ui.checkbox(&mut self.operations.batch_mode, tr("ops-batch-mode"));
                 });
                 
                 ui.add_space(5.0);
                 
                 // Display selected files
-                if self.selected_files.is_empty() {
-                    ui.label("No files selected");
+                if self.operations.selected_files().is_empty() {
+                    ui.label(tr("ops-no-files"));
                 } else {
-                    ui.label(format!("Selected {} file(s)", self.selected_files.len()));
+                    ui.label(tr_args("ops-selected-count", &[("count", self.operations.selected_files().len().into())]));
                     
                     ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
-                        for file in &self.selected_files {
-                            ui.label(format!("• {}", file.file_name().unwrap_or_default().to_string_lossy()));
+                        for file in self.operations.selected_files() {
+                            ui.label(format!("• {}", self.operations.relative_path(file).to_string_lossy()));
                         }
                     });
+                    
+                    if self.operations.selected_folder().is_some() {
+                        ui.checkbox(&mut self.operations.archive_folder, tr("encrypt-archive-folder"))
+                            .on_hover_text(tr("encrypt-archive-folder-hint"));
+                    }
                 }
             });
             
@@ -60,40 +78,42 @@ impl EncryptScreen for CrustyApp {
             
             // Output directory selection
             ui.group(|ui| {
-                ui.heading("Output Directory");
+                ui.heading(tr("main-output-dir"));
                 
                 if ui.add_sized(
                     [200.0, 30.0],
-                    Button::new(RichText::new("Select Output Directory").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("main-select-output-dir")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     self.select_output_dir();
                 }
                 
-                if let Some(dir) = &self.output_dir {
-                    ui.label(format!("Output directory: {}", dir.display()));
+                if let Some(dir) = self.operations.output_dir() {
+                    ui.label(tr_args("ops-output-dir-is", &[("dir", dir.display().to_string().into())]));
                 } else {
-                    ui.label("No output directory selected");
+                    ui.label(tr("ops-no-output-dir"));
                 }
+                
+                self.show_replace_originals_options(ui);
             });
             
             ui.add_space(10.0);
             
             // Encryption options
             ui.group(|ui| {
-                ui.heading("Encryption Options");
+                ui.heading(tr("encrypt-options"));
                 
                 // Key selection
                 ui.horizontal(|ui| {
-                    ui.label("Encryption Key:");
+                    ui.label(tr("encrypt-key"));
                     
-                    if self.current_key.is_none() {
-                        ui.label(RichText::new("No key selected").color(self.theme.error));
+                    if self.keys.current_key().is_none() {
+                        ui.label(RichText::new(tr("main-no-key-selected")).color(self.theme.error));
                         
                         if ui.add_sized(
                             [120.0, 24.0],
-                            Button::new(RichText::new("Select Key").color(self.theme.button_text))
+                            Button::new(RichText::new(tr("ops-select-key")).color(self.theme.button_text))
                                 .fill(self.theme.button_normal)
                                 .rounding(Rounding::same(5.0))
                         ).clicked() {
@@ -101,26 +121,14 @@ impl EncryptScreen for CrustyApp {
                         }
                     } else {
                         // Find the name of the current key
-                        let key_name = self.current_key.as_ref().map_or_else(
-                            || "Unknown key".to_string(),
-                            |current_key| {
-                                self.saved_keys.iter()
-                                    .find_map(|(name, key)| {
-                                        if key.to_base64() == current_key.to_base64() {
-                                            Some(name.clone())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .unwrap_or_else(|| "Unknown key".to_string())
-                            }
-                        );
+                        let key_name = self.keys.current_key_name()
+                            .unwrap_or_else(|| tr("ops-unknown-key"));
                         
                         ui.label(RichText::new(&key_name).color(self.theme.success));
                         
                         if ui.add_sized(
                             [120.0, 24.0],
-                            Button::new(RichText::new("Change Key").color(self.theme.button_text))
+                            Button::new(RichText::new(tr("ops-change-key")).color(self.theme.button_text))
                                 .fill(self.theme.button_normal)
                                 .rounding(Rounding::same(5.0))
                         ).clicked() {
@@ -129,15 +137,76 @@ impl EncryptScreen for CrustyApp {
                     }
                 });
                 
+                if let Some(expires_on) = self.keys.current_key_expired_on() {
+                    ui.label(RichText::new(tr_args("encrypt-key-expired", &[("date", expires_on.into())]))
+                        .color(self.theme.error));
+                }
+                if let Some(pin) = self.key_pin_mismatch() {
+                    ui.label(RichText::new(tr_args("encrypt-key-pinned", &[("name", pin.key_name.as_str().into()), ("folder", pin.folder.display().to_string().into())]))
+                        .strong()
+                        .color(self.theme.error));
+                }
+                
+                // File name options
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.hide_file_names, tr("encrypt-hide-names"));
+                    help::help_icon(ui, help::HIDE_FILE_NAMES);
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.add_parity, tr("encrypt-parity"));
+                    help::help_icon(ui, help::PARITY);
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.add_digest, tr("encrypt-digest"));
+                    help::help_icon(ui, help::PLAINTEXT_DIGEST);
+                });
+                if self.operations.selected_files().len() > 1 && !self.operations.replace_originals {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.operations.write_manifest, tr("encrypt-manifest"));
+                        help::help_icon(ui, help::BATCH_MANIFEST);
+                    });
+                }
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut self.operations.age_format, tr("encrypt-age-format")).changed() && self.operations.age_format {
+                        self.operations.openpgp_format = false;
+                    }
+                    help::help_icon(ui, help::AGE_FORMAT);
+                });
+                if self.operations.age_format {
+                    self.show_age_recipient_choice(ui);
+                } else if let Some(limit) = self.settings.memory_limit() {
+                    ui.label(RichText::new(tr_args("encrypt-low-memory-age", &[("size", (limit / (2 * 1024 * 1024)).into())])).color(self.theme.text_secondary));
+                }
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut self.operations.openpgp_format, tr("encrypt-openpgp-format")).changed() && self.operations.openpgp_format {
+                        self.operations.age_format = false;
+                    }
+                    help::help_icon(ui, help::OPENPGP);
+                });
+                if self.operations.openpgp_format {
+                    self.show_openpgp_recipient_choice(ui);
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.use_recipient, tr("encrypt-for-recipients"));
+                    help::help_icon(ui, help::RECIPIENT_MODE);
+                });
+                if self.operations.use_recipient {
+                    self.show_recipient_list(ui);
+                }
+                
                 // Backend options
                 ui.add_space(5.0);
-                ui.checkbox(&mut self.use_embedded_backend, "Use hardware encryption");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.backend.use_embedded, tr("ops-use-hardware"));
+                    help::help_icon(ui, help::EMBEDDED_BACKEND);
+                });
                 
-                if self.use_embedded_backend {
+                if self.operations.backend.use_embedded {
                     ui.horizontal(|ui| {
-                        ui.label("Connection Type:");
-                        ui.radio_value(&mut self.embedded_connection_type, crate::backend::ConnectionType::Usb, "USB");
-                        ui.radio_value(&mut self.embedded_connection_type, crate::backend::ConnectionType::Serial, "Serial");
+                        ui.label(tr("main-connection-type"));
+                        ui.radio_value(&mut self.operations.backend.connection_type, crate::backend::ConnectionType::Usb, "USB");
+                        ui.radio_value(&mut self.operations.backend.connection_type, crate::backend::ConnectionType::Serial, tr("ops-serial"));
                     });
                 }
             });
@@ -145,31 +214,30 @@ impl EncryptScreen for CrustyApp {
             ui.add_space(20.0);
             
             // Progress section (only shown during encryption)
-            if matches!(self.operation, FileOperation::Encrypt) && !self.progress.lock().unwrap().is_empty() {
+            if let (FileOperation::Encrypt | FileOperation::Archive, Some(overall_progress)) = (self.operations.operation(), self.operations.overall_progress()) {
                 ui.group(|ui| {
-                    ui.heading("Encryption Progress");
+                    ui.heading(tr("encrypt-progress"));
                     
-                    let progress = self.progress.lock().unwrap();
+                    let progress = self.operations.file_progress();
                     
                     // Overall progress
-                    let overall_progress = if progress.is_empty() {
-                        0.0
-                    } else {
-                        progress.iter().sum::<f32>() / progress.len() as f32
-                    };
                     
-                    ui.label(format!("Overall Progress: {:.1}%", overall_progress * 100.0));
+                    ui.label(tr_args("ops-overall-progress", &[("percent", locale::format_percent(overall_progress).into())]));
                     ui.add(ProgressBar::new(overall_progress)
                         .show_percentage()
                         .animate(true));
+                    self.show_pause_control(ui);
+                    self.show_device_progress(ui);
+                    self.show_duplicate_scan(ui);
                     
                     ui.add_space(10.0);
                     
                     // Individual file progress
-                    if !self.selected_files.is_empty() && progress.len() == self.selected_files.len() {
+                    let selected_files = self.operations.selected_files();
+                    if !selected_files.is_empty() && progress.len() == selected_files.len() {
                         ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                            for (i, (file, &prog)) in self.selected_files.iter().zip(progress.iter()).enumerate() {
-                                ui.label(format!("File {}: {}", i + 1, file.file_name().unwrap_or_default().to_string_lossy()));
+                            for (i, (file, &prog)) in selected_files.iter().zip(progress.iter()).enumerate() {
+                                ui.label(tr_args("ops-file-number", &[("number", (i + 1).into()), ("file", self.operations.relative_path(file).to_string_lossy().into())]));
                                 ui.add(ProgressBar::new(prog)
                                     .show_percentage()
                                     .animate(true));
@@ -180,22 +248,36 @@ impl EncryptScreen for CrustyApp {
                 });
                 
                 ui.add_space(10.0);
-                
-                // Results section
-                if !self.operation_results.is_empty() {
-                    ui.group(|ui| {
-                        ui.heading("Results");
-                        
-                        ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
-                            for result in &self.operation_results {
-                                if result.contains("Error") || result.contains("Failed") {
-                                    ui.label(RichText::new(result).color(self.theme.error));
-                                } else {
-                                    ui.label(RichText::new(result).color(self.theme.success));
-                                }
-                            }
-                        });
+            }
+            
+            // Results of the last encryption, kept once it is finished
+            if matches!(self.operations.operation(), FileOperation::Encrypt | FileOperation::BatchEncrypt | FileOperation::Archive) {
+                self.show_operation_results(ui);
+            }
+            
+            // Files of the last batch with the same contents
+            let duplicates = self.operations.duplicates();
+            if !duplicates.is_empty() {
+                let mut dismiss = false;
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading(tr("encrypt-duplicates"));
+                        dismiss = ui.button(tr("ops-dismiss")).clicked();
                     });
+                    ui.label(RichText::new(tr("encrypt-duplicates-explained"))
+                        .color(self.theme.text_secondary));
+                    
+                    ScrollArea::vertical().max_height(150.0).id_source("duplicates").show(ui, |ui| {
+                        for group in &duplicates {
+                            let names: Vec<String> = group.iter()
+                                .map(|file| self.operations.relative_path(file).to_string_lossy().to_string())
+                                .collect();
+                            ui.label(format!("• {}", names.join(" = ")));
+                        }
+                    });
+                });
+                if dismiss {
+                    self.operations.dismiss_duplicates();
                 }
             }
             
@@ -203,43 +285,46 @@ impl EncryptScreen for CrustyApp {
             
             // Action buttons
             ui.horizontal(|ui| {
-                let can_encrypt = !self.selected_files.is_empty() && 
-                                 self.output_dir.is_some() && 
-                                 self.current_key.is_some();
+                let can_encrypt = self.operations.is_ready_to_encrypt() && self.keys.current_key().is_some();
                 
                 if ui.add_sized(
                     [150.0, 40.0],
-                    Button::new(RichText::new("🔒 Encrypt").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("encrypt-start")).color(self.theme.button_text))
                         .fill(if can_encrypt { self.theme.accent } else { self.theme.button_normal })
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     if can_encrypt {
-                        self.operation = FileOperation::Encrypt;
-                        
-                        // Add files to the file list
-                        let files_to_add: Vec<PathBuf> = self.selected_files.clone();
-                        for file in files_to_add {
-                            self.add_file_entry(file, FileOperationType::Encrypt);
-                        }
-                        
-                        // Start encryption
-                        self.show_status("Starting encryption...");
+                        self.begin_encrypt();
                     } else {
-                        self.show_error("Please select files, output directory, and encryption key");
+                        self.show_error(&tr("error-select-files"));
                     }
                 }
                 
                 // Back button
                 if ui.add_sized(
                     [120.0, 40.0],
-                    Button::new(RichText::new("Back").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("button-back")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     self.state = AppState::Dashboard;
-                    self.operation = FileOperation::None;
+                    self.operations.set_operation(FileOperation::None);
                 }
             });
         });
+    }    
+    // Options for encrypting in place (also used by the encryption workflow)
+    fn show_replace_originals_options(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.operations.replace_originals, tr("encrypt-replace-originals"));
+            help::help_icon(ui, help::REPLACE_ORIGINALS);
+        });
+        
+        if self.operations.replace_originals {
+            ui.indent("shred_originals", |ui| {
+                ui.checkbox(&mut self.operations.shred_originals, tr("encrypt-shred-originals"));
+                ui.label(RichText::new(tr("encrypt-replace-originals-explained")).weak());
+            });
+        }
     }
 }
@@ -0,0 +1,185 @@
+use eframe::egui::{Ui, Button, DragValue, RichText, Rounding, TextEdit};
+
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::i18n::{tr, tr_args};
+use crate::key_agreement;
+use crate::network;
+
+/// Partner key agreement screen trait
+pub trait KeyAgreementScreen {
+    fn show_key_agreement(&mut self, ui: &mut Ui);
+}
+
+impl KeyAgreementScreen for CrustyApp {
+    fn show_key_agreement(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(10.0);
+            ui.heading(tr("agreement-title"));
+            ui.add_space(20.0);
+
+            ui.group(|ui| {
+                ui.heading(tr("agreement-about"));
+                ui.label(tr("agreement-about-1"));
+                ui.label(tr("agreement-about-2"));
+                ui.label(tr("agreement-about-3"));
+                ui.label(tr("agreement-about-4"));
+            });
+
+            ui.add_space(20.0);
+
+            if network::is_offline() {
+                ui.label(RichText::new(tr("agreement-offline")).color(self.theme.error));
+            } else if let Some(listening) = self.key_agreement.running() {
+                // Agreement in progress
+                ui.group(|ui| {
+                    ui.spinner();
+                    if listening {
+                        ui.label(tr_args("agreement-waiting", &[("port", self.key_agreement.port.to_string().into())]));
+                    } else {
+                        ui.label(tr_args("agreement-connecting", &[("address", self.key_agreement.address.trim().into())]));
+                    }
+                    if listening && ui.button(tr("button-cancel")).clicked() {
+                        self.key_agreement.cancel();
+                    }
+                });
+            } else if let Some(agreement) = self.key_agreement.agreement() {
+                // Agreed key awaiting comparison
+                let partner = key_agreement::find_partner(&self.settings.partners, &agreement.partner_identity).cloned();
+                let (address, fingerprint, sas) = (agreement.partner_address.clone(), agreement.key.fingerprint(), agreement.sas.clone());
+                let mut accept = false;
+                let mut reject = false;
+
+                ui.group(|ui| {
+                    ui.heading(tr("agreement-agreed-key"));
+                    ui.label(tr_args("agreement-partner-computer", &[("address", address.to_string().into())]));
+                    ui.label(tr_args("cold-storage-key-fingerprint", &[("fingerprint", fingerprint.to_string().into())]));
+                    ui.add_space(10.0);
+
+                    match &partner {
+                        Some(partner) => {
+                            ui.label(RichText::new(tr_args("agreement-recognised", &[("name", partner.name.as_str().into()), ("date", partner.verified_at.as_str().into())]))
+                                .color(self.theme.success));
+                            ui.add_space(5.0);
+                            accept = ui.add_sized(
+                                [180.0, 35.0],
+                                Button::new(RichText::new(tr("agreement-save-key")).color(self.theme.button_text))
+                                    .fill(self.theme.button_normal)
+                                    .rounding(Rounding::same(8.0))
+                            ).clicked();
+                        },
+                        None => {
+                            ui.label(tr("agreement-read-code"));
+                            ui.label(RichText::new(&sas).size(36.0).monospace().strong());
+                            ui.add_space(10.0);
+                            ui.horizontal(|ui| {
+                                ui.label(tr("agreement-partner-name"));
+                                ui.add(TextEdit::singleline(&mut self.key_agreement.partner_name)
+                                    .desired_width(200.0)
+                                    .hint_text(tr("agreement-partner-name-hint")));
+                            });
+                            ui.add_space(5.0);
+                            ui.horizontal(|ui| {
+                                accept = ui.add_sized(
+                                    [200.0, 35.0],
+                                    Button::new(RichText::new(tr("agreement-codes-match")).color(self.theme.button_text))
+                                        .fill(self.theme.button_normal)
+                                        .rounding(Rounding::same(8.0))
+                                ).clicked();
+                                reject = ui.add_sized(
+                                    [200.0, 35.0],
+                                    Button::new(RichText::new(tr("agreement-codes-differ")).color(self.theme.button_text))
+                                        .fill(self.theme.error)
+                                        .rounding(Rounding::same(8.0))
+                                ).clicked();
+                            });
+                        },
+                    }
+                });
+
+                if accept {
+                    self.accept_agreed_key();
+                } else if reject {
+                    self.key_agreement.reject();
+                }
+            } else {
+                // Start an agreement
+                let mut wait_for_partner = None;
+
+                ui.group(|ui| {
+                    ui.heading(tr("agreement-wait"));
+                    ui.horizontal(|ui| {
+                        ui.label(tr("main-port"));
+                        ui.add(DragValue::new(&mut self.key_agreement.port).clamp_range(1024..=65535));
+                    });
+                    ui.label(RichText::new(tr("agreement-firewall"))
+                        .color(self.theme.text_secondary));
+                    if ui.add_sized(
+                        [180.0, 35.0],
+                        Button::new(RichText::new(tr("agreement-wait-button")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        wait_for_partner = Some(true);
+                    }
+                });
+
+                ui.add_space(10.0);
+
+                ui.group(|ui| {
+                    ui.heading(tr("agreement-connect-heading"));
+                    ui.horizontal(|ui| {
+                        ui.label(tr("agreement-partner-address"));
+                        ui.add(TextEdit::singleline(&mut self.key_agreement.address)
+                            .desired_width(250.0)
+                            .hint_text(tr_args("agreement-address-hint", &[("port", key_agreement::DEFAULT_PORT.to_string().into())])));
+                    });
+                    if ui.add_sized(
+                        [180.0, 35.0],
+                        Button::new(RichText::new(tr("agreement-connect")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        wait_for_partner = Some(false);
+                    }
+                });
+
+                if let Some(wait_for_partner) = wait_for_partner {
+                    self.start_key_agreement(wait_for_partner);
+                }
+            }
+
+            ui.add_space(20.0);
+
+            // Saved partners
+            ui.group(|ui| {
+                ui.heading(tr("agreement-saved-partners"));
+                if self.settings.partners.is_empty() {
+                    ui.label(tr("agreement-no-partners"));
+                }
+                let mut forget = None;
+                for (i, partner) in self.settings.partners.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(tr_args("agreement-saved-partner", &[("name", partner.name.as_str().into()), ("date", partner.verified_at.as_str().into())]));
+                        if ui.small_button(tr("agreement-forget")).on_hover_text(tr("agreement-forget-hint")).clicked() {
+                            forget = Some(i);
+                        }
+                    });
+                }
+                if let Some(index) = forget {
+                    self.remove_partner(index);
+                }
+            });
+
+            ui.add_space(20.0);
+
+            // Back button
+            if ui.add(Button::new(RichText::new(tr("agreement-back")).color(self.theme.button_text))
+                .fill(self.theme.button_normal)
+                .rounding(Rounding::same(5.0))
+            ).clicked() {
+                self.state = AppState::KeyManagement;
+            }
+        });
+    }
+}
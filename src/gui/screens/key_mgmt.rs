@@ -1,6 +1,16 @@
-use eframe::egui::{Ui, RichText, Button, Rounding, TextEdit, Grid};
+use eframe::egui::{Ui, RichText, Button, Rounding, TextEdit, Grid, ComboBox};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::AppState;
+use crate::age_format;
+use crate::openpgp;
+use crate::gui::help;
+use crate::i18n::{tr, tr_args};
+use crate::key_backups::BACKUP_GENERATIONS;
+use crate::keystore::KeyMetadata;
+use crate::locale;
+use crate::policy::{get_policy, KeyStoreKind};
+use crate::services::key_service::MIN_PASSPHRASE_LENGTH;
+use crate::tpm;
 
 /// Key management screen trait
 pub trait KeyManagementScreen {
@@ -11,60 +21,116 @@ impl KeyManagementScreen for CrustyApp {
     fn show_key_management(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
-            ui.heading(RichText::new("Key Management").size(28.0));
+            ui.heading(RichText::new(tr("keys-title")).size(28.0));
             ui.add_space(10.0);
             
+            // Key management is only available when the admin policy allows it
+            let can_manage_keys = self.key_management_allowed();
+            
             // Create new key section
-            ui.group(|ui| {
-                ui.heading("Create New Key");
+            if can_manage_keys {
+                ui.group(|ui| {
+                    ui.heading(tr("workflow-create-key"));
                 
-                ui.horizontal(|ui| {
-                    ui.label("Key Name:");
-                    ui.add(TextEdit::singleline(&mut self.new_key_name)
-                        .hint_text("Enter a name for the new key")
-                        .desired_width(250.0));
-                });
+                    ui.horizontal(|ui| {
+                        ui.label(tr("workflow-key-name"));
+                        ui.add(TextEdit::singleline(&mut self.new_key_name)
+                            .hint_text(tr("workflow-key-name-hint"))
+                            .desired_width(250.0));
+                    });
                 
-                ui.add_space(5.0);
+                    ui.add_space(5.0);
                 
-                if ui.add_sized(
-                    [150.0, 30.0],
-                    Button::new(RichText::new("Generate Key").color(self.theme.button_text))
-                        .fill(self.theme.accent)
-                        .rounding(Rounding::same(8.0))
-                ).clicked() {
-                    if self.new_key_name.is_empty() {
-                        self.show_error("Please enter a name for the key");
-                    } else {
-                        let key_name = self.new_key_name.clone();
-                        self.generate_key(&key_name);
-                        self.new_key_name.clear();
+                    if ui.add_sized(
+                        [150.0, 30.0],
+                        Button::new(RichText::new(tr("workflow-generate-key")).color(self.theme.button_text))
+                            .fill(self.theme.accent)
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        if self.new_key_name.is_empty() {
+                            self.show_error(&tr("error-key-name"));
+                        } else {
+                            let key_name = self.new_key_name.clone();
+                            self.keys.generate_key(&key_name);
+                            self.new_key_name.clear();
+                        }
                     }
-                }
-            });
+                    
+                    ui.add_space(10.0);
+                    ui.label(tr("keys-or-passphrase"));
+                    
+                    Grid::new("passphrase_grid").num_columns(2).show(ui, |ui| {
+                        ui.label(tr("transfer-passphrase-label"));
+                        ui.add(TextEdit::singleline(&mut *self.new_key_passphrase)
+                            .password(true)
+                            .hint_text(tr_args("keys-passphrase-hint", &[("count", MIN_PASSPHRASE_LENGTH.into())]))
+                            .desired_width(250.0));
+                        ui.end_row();
+                        
+                        ui.label(tr("keys-confirm"));
+                        ui.add(TextEdit::singleline(&mut *self.new_key_passphrase_confirm)
+                            .password(true)
+                            .desired_width(250.0));
+                        ui.end_row();
+                    });
+                    
+                    ui.add_space(5.0);
+                    
+                    ui.horizontal(|ui| {
+                        if ui.add_sized(
+                            [150.0, 30.0],
+                            Button::new(RichText::new(tr("keys-derive")).color(self.theme.button_text))
+                                .fill(self.theme.accent)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.derive_passphrase_key();
+                        }
+                        
+                        if ui.add_sized(
+                            [200.0, 30.0],
+                            Button::new(RichText::new(tr("keys-derive-for-file")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).on_hover_text(tr("keys-derive-for-file-hint"))
+                            .clicked() {
+                            self.derive_key_from_file();
+                        }
+                    });
+                });
             
-            ui.add_space(20.0);
+                ui.add_space(20.0);
+            } else {
+                ui.label(RichText::new(tr("keys-disabled")).color(self.theme.text_secondary));
+                ui.add_space(20.0);
+            }
             
             // Saved keys section
             ui.group(|ui| {
-                ui.heading("Saved Keys");
+                ui.heading(tr("keys-saved"));
                 
-                if self.saved_keys.is_empty() {
-                    ui.label("No saved keys. Create a new key or load one from a file.");
+                match self.keys.keystore_path() {
+                    Some(path) => ui.label(tr_args("keys-kept-in", &[("path", path.display().to_string().into())])),
+                    None => ui.label(RichText::new(tr("keys-not-saved")).color(self.theme.error)),
+                };
+                
+                if self.keys.saved_keys().is_empty() {
+                    ui.label(tr("keys-none-saved"));
                 } else {
-                    // Display current key
-                    let current_key_base64 = self.current_key.as_ref().map(|k| k.to_base64());
-                    
                     // Create a table for the keys
                     Grid::new("keys_grid")
-                        .num_columns(4)
+                        .num_columns(8)
                         .spacing([20.0, 10.0])
                         .striped(true)
                         .show(ui, |ui| {
                             // Header row
-                            ui.label(RichText::new("Key Name").strong());
-                            ui.label(RichText::new("Status").strong());
-                            ui.label(RichText::new("Actions").strong());
+                            ui.label(RichText::new(tr("keys-key-name")).strong());
+                            ui.label(RichText::new(tr("keys-fingerprint")).strong())
+                                .on_hover_text(tr("keys-fingerprint-hint"));
+                            ui.label(RichText::new(tr("keys-created")).strong());
+                            ui.label(RichText::new(tr("keys-expires")).strong());
+                            ui.label(RichText::new(tr("keys-last-used")).strong());
+                            ui.label(RichText::new(tr("keys-status")).strong());
+                            ui.label(RichText::new(tr("keys-actions")).strong());
                             ui.label(RichText::new("").strong());
                             ui.end_row();
                             
@@ -72,65 +138,84 @@ impl KeyManagementScreen for CrustyApp {
                             let mut key_to_remove = None;
                             
                     // Create a temporary vector of key data for the grid
-                    let key_data: Vec<(usize, String, String, bool)> = self.saved_keys.iter().enumerate()
+                    let key_data: Vec<(usize, String, String, String, KeyMetadata, bool)> = self.keys.saved_keys().iter().enumerate()
                         .map(|(i, (name, key))| {
-                            let is_current = current_key_base64.as_ref().map_or(false, |current| current == &key.to_base64());
-                            (i, name.clone(), key.to_base64(), is_current)
+                            let provenance = self.keys.provenance(key)
+                                .map(|provenance| provenance.describe())
+                                .unwrap_or_else(|| tr("keys-provenance-not-recorded"));
+                            let metadata = self.keys.metadata(key).cloned().unwrap_or_default();
+                            (i, name.clone(), key.fingerprint(), provenance, metadata, self.keys.is_current(i))
                         })
                         .collect();
                     
-                    for (i, name, _key_base64, is_current) in key_data {
-                        // Key name
+                    for (i, name, fingerprint, provenance, metadata, is_current) in key_data {
+                        // Key name, with where the key came from
                         ui.label(if is_current {
                             RichText::new(&name).strong().color(self.theme.success)
                         } else {
                             RichText::new(&name)
-                        });
+                        }).on_hover_text(provenance);
+                        
+                        // Fingerprint
+                        ui.monospace(&fingerprint);
+                        
+                        // Creation, expiry, and last use
+                        match &metadata.created_at {
+                            Some(created_at) => ui.label(&created_at[..created_at.len().min(10)]).on_hover_text(created_at),
+                            None => ui.label(RichText::new(tr("keys-unknown")).color(self.theme.text_secondary)),
+                        };
+                        match &metadata.expires_on {
+                            Some(expires_on) if metadata.is_expired() => ui.label(RichText::new(tr_args("keys-expired-on", &[("date", expires_on.as_str().into())])).color(self.theme.error)),
+                            Some(expires_on) => ui.label(expires_on),
+                            None => ui.label(RichText::new(tr("keys-never")).color(self.theme.text_secondary)),
+                        };
+                        match &metadata.last_used {
+                            Some(last_used) => ui.label(last_used),
+                            None => ui.label(RichText::new(tr("keys-never")).color(self.theme.text_secondary)),
+                        };
                         
                         // Status
                         ui.label(if is_current {
-                            RichText::new("Current").color(self.theme.success)
+                            RichText::new(tr("keys-current")).color(self.theme.success)
+                        } else if metadata.credential_name.is_some() {
+                            RichText::new(tr("keys-credential-store"))
                         } else {
-                            RichText::new("Saved")
+                            RichText::new(tr("keys-saved-status"))
                         });
                         
                         // Select button
                         ui.horizontal(|ui| {
                             if ui.add_sized(
                                 [80.0, 24.0],
-                                Button::new(RichText::new("Select").color(self.theme.button_text))
+                                Button::new(RichText::new(tr("main-select-key")).color(self.theme.button_text))
                                     .fill(self.theme.button_normal)
                                     .rounding(Rounding::same(5.0))
                             ).clicked() {
-                                if i < self.saved_keys.len() {
-                                    let (_, key) = &self.saved_keys[i];
-                                    self.current_key = Some(key.clone());
-                                    self.show_status(&format!("Selected key: {}", name));
-                                }
+                                self.keys.select_key(i);
                             }
                             
-                            if ui.add_sized(
+                            if can_manage_keys && ui.add_sized(
                                 [80.0, 24.0],
-                                Button::new(RichText::new("Save").color(self.theme.button_text))
+                                Button::new(RichText::new(tr("button-save")).color(self.theme.button_text))
                                     .fill(self.theme.button_normal)
                                     .rounding(Rounding::same(5.0))
                             ).clicked() {
-                                if i < self.saved_keys.len() {
-                                    let (_, key) = &self.saved_keys[i];
-                                    self.current_key = Some(key.clone());
-                                    self.save_key_to_file();
-                                }
+                                self.save_key_to_file(i);
                             }
                         });
                         
                         // Delete button
-                        if ui.add_sized(
-                            [80.0, 24.0],
-                            Button::new(RichText::new("Delete").color(self.theme.button_text))
-                                .fill(self.theme.error)
-                                .rounding(Rounding::same(5.0))
-                        ).clicked() {
-                            key_to_remove = Some(i);
+                        if can_manage_keys {
+                            if ui.add_sized(
+                                [80.0, 24.0],
+                                Button::new(RichText::new(tr("keys-delete")).color(self.theme.button_text))
+                                    .fill(self.theme.error)
+                                    .rounding(Rounding::same(5.0))
+                            ).clicked() {
+                                key_to_remove = Some(i);
+                            }
+                        } else {
+                            ui.label("");
                         }
                         
                         ui.end_row();
@@ -138,74 +223,537 @@ impl KeyManagementScreen for CrustyApp {
                             
                             // Handle key removal outside the closure
                             if let Some(idx) = key_to_remove {
-                                if idx < self.saved_keys.len() {
-                                    // Store the name and key_base64 before removing
-                                    let name = self.saved_keys[idx].0.clone();
-                                    let key_base64 = self.saved_keys[idx].1.to_base64();
-                                    
-                                    // Remove the key
-                                    self.saved_keys.remove(idx);
-                                    
-                                    // If we removed the current key, clear it
-                                    if let Some(current) = &self.current_key {
-                                        if current.to_base64() == key_base64 {
-                                            self.current_key = None;
+                                self.keys.remove_key(idx);
+                            }
+                        });
+                    
+                    // Expiry dates
+                    if can_manage_keys {
+                        ui.collapsing(tr("keys-expiry"), |ui| {
+                            let key_names: Vec<String> = self.keys.saved_keys().iter()
+                                .map(|(name, _)| name.clone())
+                                .collect();
+                            let selected_name = self.expiry_key
+                                .and_then(|i| key_names.get(i).cloned())
+                                .unwrap_or_else(|| tr("audit-choose-key"));
+                            
+                            ui.horizontal(|ui| {
+                                ComboBox::from_id_source("expiry_key")
+                                    .selected_text(selected_name)
+                                    .width(200.0)
+                                    .show_ui(ui, |ui| {
+                                        for (i, name) in key_names.iter().enumerate() {
+                                            ui.selectable_value(&mut self.expiry_key, Some(i), name);
                                         }
-                                    }
-                                    
-                                    self.show_status(&format!("Removed key: {}", name));
+                                    });
+                                
+                                ui.label(tr("keys-expires-on"));
+                                ui.add(TextEdit::singleline(&mut self.expiry_date)
+                                    .hint_text(tr("keys-expires-on-hint"))
+                                    .desired_width(150.0));
+                                
+                                if ui.add_sized(
+                                    [100.0, 24.0],
+                                    Button::new(RichText::new(tr("keys-set-expiry")).color(self.theme.button_text))
+                                        .fill(self.theme.button_normal)
+                                        .rounding(Rounding::same(5.0))
+                                ).clicked() {
+                                    self.set_key_expiry();
+                                }
+                            });
+                            ui.label(RichText::new(tr("keys-expired-still-decrypt")).weak());
+                        });
+                        
+                        // Keys kept in the OS credential store instead of the key store file
+                        ui.collapsing(tr("keys-credential-store"), |ui| {
+                            let mut moved = None;
+                            for (i, (name, key)) in self.keys.saved_keys().iter().enumerate() {
+                                let mut in_credential_store = self.keys.in_credential_store(key);
+                                if ui.checkbox(&mut in_credential_store, name).changed() {
+                                    moved = Some((i, in_credential_store));
                                 }
                             }
+                            if let Some((index, in_credential_store)) = moved {
+                                self.keys.set_in_credential_store(index, in_credential_store);
+                            }
+                            ui.label(RichText::new(tr("keys-credential-store-explained")).weak());
                         });
+                    }
+                    
+                    // Where each key came from, for audits
+                    ui.collapsing(tr("keys-provenance"), |ui| {
+                        for (name, key) in self.keys.saved_keys() {
+                            let provenance = self.keys.provenance(key)
+                                .map(|provenance| provenance.describe())
+                                .unwrap_or_else(|| tr("keys-provenance-old"));
+                            ui.label(format!("{} [{}]: {}", name, key.fingerprint(), provenance));
+                        }
+                    });
                 }
                 
                 ui.add_space(10.0);
                 
-                // Load key from file button
-                if ui.add_sized(
-                    [150.0, 30.0],
-                    Button::new(RichText::new("Load Key from File").color(self.theme.button_text))
-                        .fill(self.theme.button_normal)
-                        .rounding(Rounding::same(8.0))
-                ).clicked() {
-                    self.load_key_from_file();
+                if can_manage_keys {
+                    ui.horizontal(|ui| {
+                        // Load key from file button
+                        if ui.add_sized(
+                            [150.0, 30.0],
+                            Button::new(RichText::new(tr("workflow-load-key")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.load_key_from_file();
+                        }
+                        
+                        // Import key from the QR code of a printed key sheet
+                        if ui.add_sized(
+                            [170.0, 30.0],
+                            Button::new(RichText::new(tr("keys-import-scan")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).on_hover_text(tr("keys-import-scan-hint"))
+                            .clicked() {
+                            self.import_key_from_scan();
+                        }
+                        
+                        // Import identities made by age-keygen
+                        if ui.add_sized(
+                            [170.0, 30.0],
+                            Button::new(RichText::new(tr("keys-import-age")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).on_hover_text(tr("keys-import-age-hint"))
+                            .clicked() {
+                            self.import_age_identities();
+                        }
+                    });
                 }
             });
             
             ui.add_space(20.0);
             
-            // Advanced key operations
+            // age identities of the saved keys, and other people's age recipients
             ui.group(|ui| {
-                ui.heading("Advanced Key Operations");
+                ui.horizontal(|ui| {
+                    ui.heading("age");
+                    help::help_icon(ui, help::AGE_FORMAT);
+                });
                 
+                let mut copied = None;
+                let mut exported = None;
+                ui.collapsing(tr("keys-your-age"), |ui| {
+                    ui.label(RichText::new(tr("keys-your-age-explained")).weak());
+                    Grid::new("age_key_grid").num_columns(3).spacing([10.0, 4.0]).show(ui, |ui| {
+                        for (i, (name, key)) in self.keys.saved_keys().iter().enumerate() {
+                            let recipient = age_format::recipient_string(key);
+                            ui.label(name);
+                            ui.monospace(&recipient);
+                            ui.horizontal(|ui| {
+                                if ui.button(tr("transfer-copy")).clicked() {
+                                    copied = Some(recipient);
+                                }
+                                if can_manage_keys && ui.button(tr("keys-export-identity"))
+                                    .on_hover_text(tr("keys-export-identity-hint"))
+                                    .clicked() {
+                                    exported = Some(i);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(recipient) = copied {
+                    self.copy_to_clipboard(ui, recipient);
+                }
+                if let Some(index) = exported {
+                    self.export_age_identity(index);
+                }
+                
+                let mut removed = None;
+                ui.collapsing(tr("keys-other-age"), |ui| {
+                    for (i, saved) in self.settings.age_recipients.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(&saved.name);
+                            ui.monospace(&saved.recipient);
+                            if ui.button(tr("button-remove")).clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add(TextEdit::singleline(&mut self.age_recipient_name)
+                            .hint_text(tr("keys-name-hint"))
+                            .desired_width(120.0));
+                        ui.add(TextEdit::singleline(&mut self.age_recipient_input)
+                            .hint_text("age1...")
+                            .desired_width(300.0));
+                        if ui.button(tr("button-add")).clicked() {
+                            self.add_age_recipient();
+                        }
+                    });
+                });
+                if let Some(index) = removed {
+                    self.remove_age_recipient(index);
+                }
+            });
+            
+            ui.add_space(20.0);
+            
+            // OpenPGP keys of the saved keys, and other people's OpenPGP keys
+            ui.group(|ui| {
                 ui.horizontal(|ui| {
+                    ui.heading("OpenPGP");
+                    help::help_icon(ui, help::OPENPGP);
+                });
+                
+                let mut copied = None;
+                let mut exported = None;
+                ui.collapsing(tr("keys-your-openpgp"), |ui| {
+                    ui.label(RichText::new(tr("keys-your-openpgp-explained")).weak());
+                    Grid::new("openpgp_key_grid").num_columns(3).spacing([10.0, 4.0]).show(ui, |ui| {
+                        for (i, (name, key)) in self.keys.saved_keys().iter().enumerate() {
+                            let fingerprint = openpgp::fingerprint(key);
+                            ui.label(name);
+                            ui.monospace(&fingerprint);
+                            ui.horizontal(|ui| {
+                                if ui.button(tr("transfer-copy")).clicked() {
+                                    copied = Some(fingerprint);
+                                }
+                                if ui.button(tr("keys-export-public"))
+                                    .on_hover_text(tr("keys-export-public-hint"))
+                                    .clicked() {
+                                    exported = Some(i);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(fingerprint) = copied {
+                    self.copy_to_clipboard(ui, fingerprint);
+                }
+                if let Some(index) = exported {
+                    self.export_openpgp_key(index);
+                }
+                
+                let mut removed = None;
+                ui.collapsing(tr("keys-other-openpgp"), |ui| {
+                    for (i, saved) in self.settings.openpgp_recipients.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(&saved.name);
+                            ui.monospace(&saved.fingerprint);
+                            if ui.button(tr("button-remove")).clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                    }
+                    if ui.button(tr("keys-import-openpgp"))
+                        .on_hover_text(tr("keys-import-openpgp-hint"))
+                        .clicked() {
+                        self.import_openpgp_key();
+                    }
+                });
+                if let Some(index) = removed {
+                    self.remove_openpgp_recipient(index);
+                }
+            });
+            
+            ui.add_space(20.0);
+            
+            // Backups of the saved keys
+            if can_manage_keys {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading(tr("keys-backups"));
+                        help::help_icon(ui, help::KEY_BACKUPS);
+                    });
+                    
+                    if self.keys.backups_enabled() {
+                        ui.label(tr_args("keys-backups-on", &[("count", BACKUP_GENERATIONS.into())]));
+                    } else {
+                        ui.label(tr("keys-backups-off"));
+                    }
+                    
+                    Grid::new("backup_passphrase_grid").num_columns(2).show(ui, |ui| {
+                        ui.label(tr("keys-backup-passphrase"));
+                        ui.add(TextEdit::singleline(&mut *self.backup_passphrase)
+                            .password(true)
+                            .hint_text(tr_args("keys-passphrase-hint", &[("count", MIN_PASSPHRASE_LENGTH.into())]))
+                            .desired_width(250.0));
+                        ui.end_row();
+                        
+                        ui.label(tr("keys-confirm"));
+                        ui.add(TextEdit::singleline(&mut *self.backup_passphrase_confirm)
+                            .password(true)
+                            .desired_width(250.0));
+                        ui.end_row();
+                    });
+                    
+                    let enable_label = if self.keys.backups_enabled() { tr("keys-change-passphrase") } else { tr("keys-turn-on-backups") };
                     if ui.add_sized(
-                        [180.0, 35.0],
-                        Button::new(RichText::new("Split Key Management").color(self.theme.button_text))
+                        [180.0, 30.0],
+                        Button::new(RichText::new(enable_label).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(8.0))
                     ).clicked() {
-                        self.state = AppState::SplitKeyManagement;
+                        self.enable_key_backups();
+                    }
+                    
+                    ui.add_space(10.0);
+                    
+                    let backups = self.keys.key_backups();
+                    if backups.is_empty() {
+                        ui.label(tr("keys-no-backups"));
+                    } else {
+                        ui.label(tr("keys-select-backup"));
+                        
+                        Grid::new("key_backups_grid")
+                            .num_columns(2)
+                            .spacing([20.0, 5.0])
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for backup in &backups {
+                                    let label = tr_args("keys-backup-number", &[("number", backup.generation.into())]);
+                                    ui.radio_value(&mut self.selected_backup, Some(backup.generation), label);
+                                    ui.label(backup.modified
+                                        .map(|modified| locale::language().format_datetime(&modified))
+                                        .unwrap_or_default());
+                                    ui.end_row();
+                                }
+                            });
+                        
+                        if ui.add_sized(
+                            [150.0, 30.0],
+                            Button::new(RichText::new(tr("keys-restore")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).on_hover_text(tr("keys-restore-hint"))
+                            .clicked() {
+                            self.restore_key_backup();
+                        }
+                    }
+                });
+                
+                ui.add_space(20.0);
+            }
+            
+            // Smartcard keys from the admin policy
+            if !self.keys.token_keys().is_empty() {
+                ui.group(|ui| {
+                    ui.heading(tr("keys-smartcard"));
+                    ui.label(tr("keys-smartcard-explained"));
+                    
+                    let mut selected = None;
+                    
+                    Grid::new("token_keys_grid")
+                        .num_columns(3)
+                        .spacing([20.0, 10.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(tr("keys-key-name")).strong());
+                            ui.label(RichText::new(tr("keys-smartcard-heading")).strong());
+                            ui.label(RichText::new(tr("keys-actions")).strong());
+                            ui.end_row();
+                            
+                            for (i, token_key) in self.keys.token_keys().iter().enumerate() {
+                                let is_current = self.keys.selected_token_key() == Some(i);
+                                
+                                ui.label(if is_current {
+                                    RichText::new(&token_key.name).strong().color(self.theme.success)
+                                } else {
+                                    RichText::new(&token_key.name)
+                                });
+                                
+                                ui.label(&token_key.token_label);
+                                
+                                if ui.add_sized(
+                                    [80.0, 24.0],
+                                    Button::new(RichText::new(tr("main-select-key")).color(self.theme.button_text))
+                                        .fill(self.theme.button_normal)
+                                        .rounding(Rounding::same(5.0))
+                                ).clicked() {
+                                    selected = Some(i);
+                                }
+                                
+                                ui.end_row();
+                            }
+                        });
+                    
+                    if let Some(i) = selected {
+                        self.keys.select_token_key(i);
+                    }
+                });
+                
+                ui.add_space(20.0);
+            }
+            
+            // Advanced key operations
+            if can_manage_keys {
+                ui.group(|ui| {
+                    ui.heading(tr("keys-advanced"));
+                
+                    ui.horizontal(|ui| {
+                        if ui.add_sized(
+                            [180.0, 35.0],
+                            Button::new(RichText::new(tr("keys-split")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.state = AppState::SplitKeyManagement;
+                        }
+                    
+                        if ui.add_sized(
+                            [180.0, 35.0],
+                            Button::new(RichText::new(tr("keys-transfer")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.state = AppState::TransferPreparation;
+                        }
+                    
+                        if ui.add_sized(
+                            [180.0, 35.0],
+                            Button::new(RichText::new(tr("transfer-receive-title")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.state = AppState::TransferReceive;
+                        }
+                    
+                        if ui.add_sized(
+                            [180.0, 35.0],
+                            Button::new(RichText::new(tr("agreement-title")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.state = AppState::KeyAgreement;
+                        }
+                    
+                        if ui.add_sized(
+                            [180.0, 35.0],
+                            Button::new(RichText::new(tr("rotation-title")).color(self.theme.button_text))
+                                .fill(self.theme.button_normal)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            self.state = AppState::KeyRotation;
+                        }
+                    });
+                });
+            
+                ui.add_space(20.0);
+            }
+            
+            // Key share verification
+            if !self.keys.share_records().is_empty() {
+                ui.group(|ui| {
+                    ui.heading(tr("keys-share-backups"));
+                    
+                    let interval = get_policy().share_verification_interval();
+                    ui.label(tr_args("keys-verify-every", &[("days", interval.into())]));
+                    
+                    Grid::new("share_records_grid")
+                        .num_columns(3)
+                        .spacing([20.0, 10.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(tr("split-share")).strong());
+                            ui.label(RichText::new(tr("keys-last-verified")).strong());
+                            ui.label(RichText::new(tr("keys-status")).strong());
+                            ui.end_row();
+                            
+                            for record in self.keys.share_records() {
+                                ui.label(&record.label);
+                                ui.label(record.last_verified.clone().unwrap_or_else(|| tr("keys-never")));
+                                ui.label(if record.is_due(interval) {
+                                    RichText::new(tr("keys-verification-due")).color(self.theme.error)
+                                } else {
+                                    RichText::new(tr("keys-ok")).color(self.theme.success)
+                                });
+                                ui.end_row();
+                            }
+                        });
+                    
+                    ui.add_space(10.0);
+                    
+                    ui.label(tr("keys-paste-share"));
+                    if !self.keys.share_verification_input.trim().is_empty() {
+                        self.secrets_shown = true;
                     }
+                    ui.add(TextEdit::multiline(&mut self.keys.share_verification_input)
+                        .desired_rows(2)
+                        .desired_width(400.0));
                     
                     if ui.add_sized(
-                        [180.0, 35.0],
-                        Button::new(RichText::new("Transfer Preparation").color(self.theme.button_text))
+                        [150.0, 30.0],
+                        Button::new(RichText::new(tr("keys-verify-share")).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(8.0))
                     ).clicked() {
-                        self.state = AppState::TransferPreparation;
+                        self.keys.verify_share();
                     }
                     
                     if ui.add_sized(
-                        [180.0, 35.0],
-                        Button::new(RichText::new("Receive Transfer").color(self.theme.button_text))
+                        [150.0, 30.0],
+                        Button::new(RichText::new(tr("keys-verify-scan")).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(8.0))
-                    ).clicked() {
-                        self.state = AppState::TransferReceive;
+                    ).on_hover_text(tr("keys-verify-scan-hint"))
+                        .clicked() {
+                        self.verify_share_from_scan();
+                    }
+                });
+                
+                ui.add_space(20.0);
+            }
+            
+            // Deployment bundles
+            ui.group(|ui| {
+                ui.heading(tr("keys-deployment"));
+                
+                ui.label(tr("keys-deployment-explained"));
+                
+                ui.horizontal(|ui| {
+                    match self.machine_identity.clone() {
+                        Some(identity) => {
+                            ui.monospace(&identity);
+                            
+                            if ui.button(tr("transfer-copy")).clicked() {
+                                self.copy_to_clipboard(ui, identity);
+                                self.show_status(&tr("status-identity-copied"));
+                            }
+                        },
+                        None => {
+                            if ui.button(tr("keys-show-identity")).clicked() {
+                                self.show_machine_identity();
+                            }
+                        }
                     }
                 });
+                
+                let protection = &get_policy().protection_for(KeyStoreKind::MachineIdentity);
+                if protection != &tpm::ProtectionLevel::Software && !tpm::is_available() {
+                    ui.colored_label(self.theme.error, tr_args("keys-protection-no-tpm", &[("protection", protection.to_string().into())]));
+                } else {
+                    ui.label(tr_args("keys-protection", &[("protection", protection.to_string().into())]));
+                }
+                
+                if can_manage_keys {
+                    ui.add_space(10.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(tr("keys-target-identity"));
+                        ui.text_edit_singleline(&mut self.deployment_target_identity);
+                    });
+                    
+                    if ui.add_sized(
+                        [220.0, 30.0],
+                        Button::new(RichText::new(tr("keys-create-bundle")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        self.create_deployment_bundle();
+                    }
+                }
             });
             
             ui.add_space(20.0);
@@ -213,7 +761,7 @@ impl KeyManagementScreen for CrustyApp {
             // Back button
             if ui.add_sized(
                 [120.0, 30.0],
-                Button::new(RichText::new("Back").color(self.theme.button_text))
+                Button::new(RichText::new(tr("button-back")).color(self.theme.button_text))
                     .fill(self.theme.button_normal)
                     .rounding(Rounding::same(5.0))
             ).clicked() {
@@ -0,0 +1,76 @@
+use eframe::egui::{self, Context, RichText, Button, Rounding, Align2};
+use crate::gui::app_core::CrustyApp;
+use crate::i18n::{tr, tr_args};
+
+/// Key pin warning dialog trait
+pub trait KeyPinWarningScreen {
+    fn show_key_pin_warning(&mut self, ctx: &Context);
+}
+
+impl KeyPinWarningScreen for CrustyApp {
+    fn show_key_pin_warning(&mut self, ctx: &Context) {
+        let warning = match &self.key_pin_warning {
+            Some(warning) if !warning.confirmed => warning,
+            _ => return,
+        };
+
+        let mut use_pinned = false;
+        let mut encrypt_anyway = false;
+        let mut cancel = false;
+
+        egui::Window::new(tr("key-pin-warning-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(tr_args("key-pin-warning-mismatch", &[
+                    ("name", warning.pin.key_name.as_str().into()),
+                    ("folder", warning.pin.folder.display().to_string().into()),
+                    ("selected", warning.key_name.as_str().into()),
+                ])).strong().color(self.theme.error));
+                ui.label(tr("key-pin-warning-explained"));
+
+                ui.add_space(5.0);
+                ui.label(RichText::new(tr("key-pin-warning-logged")).weak());
+
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.add_sized(
+                        [180.0, 30.0],
+                        Button::new(RichText::new(tr_args("key-pin-warning-use-pinned", &[("name", warning.pin.key_name.as_str().into())])).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        use_pinned = true;
+                    }
+
+                    if ui.add_sized(
+                        [140.0, 30.0],
+                        Button::new(RichText::new(tr("key-pin-warning-encrypt-anyway")).color(self.theme.button_text))
+                            .fill(self.theme.error)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        encrypt_anyway = true;
+                    }
+
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("button-cancel")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.cancel_confirmations();
+        } else if use_pinned {
+            self.use_pinned_key();
+        } else if encrypt_anyway {
+            self.confirm_key_pin_override();
+        }
+    }
+}
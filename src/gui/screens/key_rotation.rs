@@ -0,0 +1,322 @@
+use eframe::egui::{Ui, Button, ComboBox, Grid, ProgressBar, RichText, Rounding, ScrollArea, TextEdit};
+
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::{AppState, KeyRotationStep};
+use crate::i18n::{tr, tr_args};
+use crate::key_rotation::RotationOutcome;
+
+/// Key rotation screen trait
+pub trait KeyRotationScreen {
+    fn show_key_rotation(&mut self, ui: &mut Ui);
+    fn show_key_rotation_files_step(&mut self, ui: &mut Ui);
+    fn show_key_rotation_keys_step(&mut self, ui: &mut Ui);
+    fn show_key_rotation_rotate_step(&mut self, ui: &mut Ui);
+}
+
+impl KeyRotationScreen for CrustyApp {
+    fn show_key_rotation(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("rotation-title")).size(28.0));
+            ui.add_space(10.0);
+
+            // Wizard steps indicator
+            let running = self.key_rotation.job_progress().is_some();
+            ui.horizontal(|ui| {
+                let current = KeyRotationStep::ALL.iter().position(|step| *step == self.key_rotation_step).unwrap_or(0);
+                for (i, step) in KeyRotationStep::ALL.into_iter().enumerate() {
+                    let is_completed = i < current;
+                    let text_color = if i == current {
+                        self.theme.accent
+                    } else if is_completed {
+                        self.theme.success
+                    } else {
+                        self.theme.text_secondary
+                    };
+
+                    // Steps cannot be revisited while the files are being re-encrypted
+                    if ui.add(Button::new(RichText::new(step.to_string()).color(text_color).strong())
+                        .fill(self.theme.background)
+                        .rounding(Rounding::same(5.0))
+                    ).clicked() && is_completed && !running {
+                        self.key_rotation_step = step;
+                    }
+
+                    if step != KeyRotationStep::Rotate {
+                        ui.label(RichText::new(" → ").color(self.theme.text_secondary));
+                    }
+                }
+            });
+
+            ui.add_space(20.0);
+
+            match self.key_rotation_step {
+                KeyRotationStep::Files => self.show_key_rotation_files_step(ui),
+                KeyRotationStep::Keys => self.show_key_rotation_keys_step(ui),
+                KeyRotationStep::Rotate => self.show_key_rotation_rotate_step(ui),
+            }
+
+            ui.add_space(20.0);
+
+            // Navigation buttons
+            let running = self.key_rotation.job_progress().is_some();
+            ui.horizontal(|ui| {
+                if self.key_rotation_step != KeyRotationStep::Files && !running && ui.add_sized(
+                    [120.0, 40.0],
+                    Button::new(RichText::new(tr("button-previous")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    self.key_rotation_step = self.key_rotation_step.previous();
+                }
+
+                if ui.add_enabled(
+                    !running,
+                    Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                        .min_size([120.0, 40.0].into())
+                ).clicked() {
+                    self.state = AppState::KeyManagement;
+                }
+
+                if self.key_rotation_step != KeyRotationStep::Rotate {
+                    let rotation = &self.key_rotation;
+                    let next_enabled = !running && match self.key_rotation_step {
+                        KeyRotationStep::Files => !rotation.files().is_empty(),
+                        _ => rotation.old_key.is_some()
+                            && rotation.old_key != rotation.new_key
+                            && (rotation.new_key.is_some() || !rotation.new_key_name.trim().is_empty()),
+                    };
+
+                    if ui.add_sized(
+                        [120.0, 40.0],
+                        Button::new(RichText::new(tr("button-next")).color(self.theme.button_text))
+                            .fill(if next_enabled { self.theme.accent } else { self.theme.button_normal })
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        if next_enabled {
+                            self.key_rotation_step = self.key_rotation_step.next();
+                        } else {
+                            let message = match self.key_rotation_step {
+                                KeyRotationStep::Files => "error-rotation-files",
+                                _ => "error-rotation-keys",
+                            };
+                            self.show_error(&tr(message));
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    // Files step
+    fn show_key_rotation_files_step(&mut self, ui: &mut Ui) {
+        let mut removed = None;
+        let mut clear = false;
+
+        ui.group(|ui| {
+            ui.heading(tr("rotation-step-files"));
+            ui.label(tr("rotation-files-explained"));
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("audit-add-files")).clicked() {
+                    if let Some(files) = rfd::FileDialog::new()
+                        .set_title("Select Files to Re-encrypt")
+                        .add_filter("Encrypted Files", &["encrypted", "crusty"])
+                        .pick_files() {
+                        self.key_rotation.add_files(files);
+                    }
+                }
+                if ui.button(tr("audit-add-folder")).clicked() {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_title("Select a Folder of Encrypted Files")
+                        .pick_folder() {
+                        self.key_rotation.add_folder(&folder);
+                    }
+                }
+                if !self.key_rotation.files().is_empty() && ui.button(tr("settings-clear")).clicked() {
+                    clear = true;
+                }
+            });
+
+            ui.add_space(10.0);
+            if self.key_rotation.files().is_empty() {
+                ui.label(RichText::new(tr("audit-no-files")).color(self.theme.text_secondary));
+                return;
+            }
+            ui.label(tr_args("catalog-file-count", &[("count", self.key_rotation.files().len().into())]));
+            ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                for (i, file) in self.key_rotation.files().iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(file.display().to_string());
+                        if ui.small_button(tr("button-remove")).clicked() {
+                            removed = Some(i);
+                        }
+                    });
+                }
+            });
+        });
+
+        if let Some(index) = removed {
+            self.key_rotation.remove_file(index);
+        }
+        if clear {
+            self.key_rotation.clear_files();
+        }
+    }
+
+    // Keys step
+    fn show_key_rotation_keys_step(&mut self, ui: &mut Ui) {
+        let key_names: Vec<String> = self.keys.saved_keys().iter()
+            .map(|(name, key)| format!("{} ({})", name, key.fingerprint()))
+            .collect();
+        let can_generate = self.key_management_allowed();
+
+        ui.group(|ui| {
+            ui.heading(tr("rotation-step-keys"));
+            ui.add_space(10.0);
+
+            Grid::new("key_rotation_keys").num_columns(2).spacing([20.0, 10.0]).show(ui, |ui| {
+                ui.label(tr("rotation-old-key"));
+                let selected_name = self.key_rotation.old_key
+                    .and_then(|i| key_names.get(i).cloned())
+                    .unwrap_or_else(|| tr("audit-choose-key"));
+                ComboBox::from_id_source("key_rotation_old_key")
+                    .selected_text(selected_name)
+                    .width(300.0)
+                    .show_ui(ui, |ui| {
+                        for (i, name) in key_names.iter().enumerate() {
+                            ui.selectable_value(&mut self.key_rotation.old_key, Some(i), name);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(tr("rotation-new-key"));
+                let selected_name = match self.key_rotation.new_key {
+                    Some(i) => key_names.get(i).cloned().unwrap_or_default(),
+                    None => tr("rotation-generate"),
+                };
+                ComboBox::from_id_source("key_rotation_new_key")
+                    .selected_text(selected_name)
+                    .width(300.0)
+                    .show_ui(ui, |ui| {
+                        if can_generate {
+                            ui.selectable_value(&mut self.key_rotation.new_key, None, tr("rotation-generate"));
+                        }
+                        for (i, name) in key_names.iter().enumerate() {
+                            ui.selectable_value(&mut self.key_rotation.new_key, Some(i), name);
+                        }
+                    });
+                ui.end_row();
+
+                if self.key_rotation.new_key.is_none() {
+                    ui.label(tr("rotation-new-key-name"));
+                    ui.add(TextEdit::singleline(&mut self.key_rotation.new_key_name)
+                        .desired_width(300.0)
+                        .hint_text(tr("rotation-new-key-name-hint")));
+                    ui.end_row();
+                }
+            });
+
+            ui.add_space(10.0);
+            if self.key_rotation.new_key.is_none() && !can_generate {
+                ui.label(RichText::new(tr("rotation-generation-disabled")).color(self.theme.error));
+            } else if self.key_rotation.old_key.is_some() && self.key_rotation.old_key == self.key_rotation.new_key {
+                ui.label(RichText::new(tr("rotation-keys-same")).color(self.theme.error));
+            } else if self.key_rotation.new_key.is_none() {
+                ui.label(RichText::new(tr("rotation-generated-on-start"))
+                    .color(self.theme.text_secondary));
+            }
+        });
+    }
+
+    // Rotate step
+    fn show_key_rotation_rotate_step(&mut self, ui: &mut Ui) {
+        let mut start = false;
+        let mut save_to = None;
+        let mut more = false;
+
+        ui.group(|ui| {
+            ui.heading(tr("rotation-step-reencrypt"));
+            ui.add_space(10.0);
+
+            if let Some(fraction) = self.key_rotation.job_progress() {
+                ui.add(ProgressBar::new(fraction).show_percentage().animate(true));
+            } else if let Some(report) = self.key_rotation.report() {
+                let color = if report.is_complete() { self.theme.success } else { self.theme.error };
+                ui.label(RichText::new(tr_args("rotation-outcome", &[
+                    ("rotated", report.count(&RotationOutcome::Rotated).into()),
+                    ("already", report.count(&RotationOutcome::AlreadyRotated).into()),
+                    ("failed", report.count(&RotationOutcome::Failed(String::new())).into()),
+                ])).color(color));
+                ui.label(tr_args("rotation-old-key-is", &[("fingerprint", report.old_fingerprint.as_str().into())]));
+                ui.label(tr_args("rotation-new-key-is", &[("fingerprint", report.new_fingerprint.as_str().into())]));
+                ui.add_space(5.0);
+
+                ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                    Grid::new("key_rotation_report").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                        for file in &report.files {
+                            ui.label(file.path.display().to_string());
+                            let color = match file.outcome {
+                                RotationOutcome::Failed(_) => self.theme.error,
+                                _ => self.theme.success,
+                            };
+                            ui.label(RichText::new(file.outcome.to_string()).color(color));
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.add_space(5.0);
+                if report.is_complete() {
+                    ui.label(tr("rotation-complete"));
+                } else {
+                    ui.label(RichText::new(tr("rotation-failed-keep-old")).color(self.theme.error));
+                }
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button(tr("audit-save-report")).clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_title("Save Rotation Report")
+                            .set_file_name("key rotation report.txt")
+                            .save_file() {
+                            save_to = Some(path);
+                        }
+                    }
+                    if !report.is_complete() && ui.button(tr("rotation-retry")).clicked() {
+                        start = true;
+                    }
+                    if ui.button(tr("rotation-more")).clicked() {
+                        more = true;
+                    }
+                });
+            } else {
+                ui.label(tr_args("rotation-will-rotate", &[("count", self.key_rotation.files().len().into())]));
+                ui.label(tr("rotation-checked-first"));
+                ui.add_space(10.0);
+                if ui.add_sized(
+                    [200.0, 40.0],
+                    Button::new(RichText::new(tr("rotation-start")).color(self.theme.button_text))
+                        .fill(self.theme.accent)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    start = true;
+                }
+            }
+        });
+
+        if start {
+            self.start_key_rotation();
+        }
+        if let Some(path) = save_to {
+            self.key_rotation.save_report(&path);
+        }
+        if more {
+            self.key_rotation.reset();
+            self.key_rotation_step = KeyRotationStep::Files;
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use eframe::egui::{Ui, RichText, Button, Rounding};
+use crate::gui::app_core::CrustyApp;
+use crate::i18n::tr;
+
+/// Locked keys screen trait
+pub trait LockedScreen {
+    fn show_locked(&mut self, ui: &mut Ui);
+}
+
+impl LockedScreen for CrustyApp {
+    fn show_locked(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(80.0);
+            ui.heading(RichText::new(tr("locked-title")).size(28.0));
+            ui.add_space(10.0);
+
+            ui.label(tr("locked-explained"));
+            ui.label(RichText::new(tr("locked-running")).color(self.theme.text_secondary));
+
+            ui.add_space(20.0);
+
+            if ui.add_sized(
+                [150.0, 36.0],
+                Button::new(RichText::new(tr("locked-unlock")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(8.0))
+            ).clicked() {
+                self.unlock_session();
+            }
+        });
+    }
+}
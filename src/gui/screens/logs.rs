@@ -1,8 +1,11 @@
 use eframe::egui::{Ui, RichText, Button, Rounding, ScrollArea, TextEdit, TextStyle};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::AppState;
-use crate::logger::get_logger;
-use std::path::PathBuf;
+use crate::gui::utils;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+use crate::logger::{get_logger, read_log_file, write_compliance_report, LogEntry};
+use rfd::FileDialog;
 
 /// Logs screen trait
 pub trait LogsScreen {
@@ -13,56 +16,54 @@ impl LogsScreen for CrustyApp {
     fn show_logs(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
-            ui.heading(RichText::new("Operation Logs").size(28.0));
+            ui.heading(RichText::new(tr("logs-title")).size(28.0));
             ui.add_space(10.0);
             
             // Get log path
-            let mut log_path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
-            log_path.push("crusty");
-            log_path.push("logs");
-            log_path.push("operations.log");
+            let log_path = self.data_dir.join("logs").join("operations.log");
             
             // Display log path
             ui.horizontal(|ui| {
-                ui.label("Log file location:");
+                ui.label(tr("logs-location"));
                 ui.label(RichText::new(format!("{}", log_path.display())).monospace());
                 
-                if ui.add(Button::new(RichText::new("Open Log Directory").color(self.theme.button_text))
+                if ui.add(Button::new(RichText::new(tr("logs-open-dir")).color(self.theme.button_text))
                     .fill(self.theme.button_normal)
                     .rounding(Rounding::same(5.0))
                 ).clicked() {
                     if let Some(parent) = log_path.parent() {
-                        #[cfg(target_os = "windows")]
-                        let _ = std::process::Command::new("explorer")
-                            .arg(parent)
-                            .spawn();
-                        
-                        #[cfg(target_os = "macos")]
-                        let _ = std::process::Command::new("open")
-                            .arg(parent)
-                            .spawn();
-                        
-                        #[cfg(target_os = "linux")]
-                        let _ = std::process::Command::new("xdg-open")
-                            .arg(parent)
-                            .spawn();
+                        utils::open_folder(parent);
                     }
                 }
             });
             
             ui.add_space(10.0);
             
+            // Entries shown and exported
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.log_filter.text)
+                    .hint_text(tr("logs-filter-hint"))
+                    .desired_width(250.0));
+                ui.checkbox(&mut self.log_filter.failures_only, tr("logs-failures-only"));
+            });
+            
+            ui.add_space(10.0);
+            
             // Display log content
             ui.group(|ui| {
-                ui.heading("Recent Logs");
+                ui.heading(tr("logs-recent"));
                 
                 let log_content = if log_path.exists() {
-                    match std::fs::read_to_string(&log_path) {
-                        Ok(content) => content,
-                        Err(e) => format!("Error reading log file: {}", e),
+                    match read_log_file(&log_path) {
+                        Ok(entries) => entries.iter()
+                            .filter(|entry| self.log_filter.matches(entry))
+                            .map(format_log_entry)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        Err(e) => tr_args("logs-read-error", &[("error", e.to_string().into())]),
                     }
                 } else {
-                    "No log file found.".to_string()
+                    tr("logs-no-file")
                 };
                 
                 // Display log content in a scrollable area with monospace font
@@ -83,17 +84,17 @@ impl LogsScreen for CrustyApp {
             ui.horizontal(|ui| {
                 if ui.add_sized(
                     [120.0, 30.0],
-                    Button::new(RichText::new("Refresh Logs").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("logs-refresh")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(5.0))
                 ).clicked() {
                     // Just refresh the UI to show updated logs
-                    self.show_status("Logs refreshed");
+                    self.show_status(&tr("status-logs-refreshed"));
                 }
                 
                 if ui.add_sized(
                     [120.0, 30.0],
-                    Button::new(RichText::new("Clear Logs").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("logs-clear")).color(self.theme.button_text))
                         .fill(self.theme.error)
                         .rounding(Rounding::same(5.0))
                 ).clicked() {
@@ -101,19 +102,59 @@ impl LogsScreen for CrustyApp {
                     if let Some(_logger) = get_logger() {
                         // We'll just truncate the file instead of calling clear_logs
                         if let Err(e) = std::fs::write(&log_path, "") {
-                            self.show_error(&format!("Failed to clear logs: {}", e));
+                            self.show_error(&tr_args("error-logs-clear", &[("error", e.to_string().into())]));
                         } else {
-                            self.show_status("Logs cleared successfully");
+                            self.show_status(&tr("status-logs-cleared"));
                         }
                     } else {
-                        self.show_error("Logger not initialized");
+                        self.show_error(&tr("error-logger-missing"));
+                    }
+                }
+                
+                if ui.add_sized(
+                    [160.0, 30.0],
+                    Button::new(RichText::new(tr("logs-compliance-report")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(5.0))
+                ).clicked() {
+                    if let Some(report_path) = FileDialog::new()
+                        .set_title("Save Compliance Report")
+                        .set_file_name("compliance_report.csv")
+                        .save_file() {
+                        let result = read_log_file(&log_path)
+                            .and_then(|entries| write_compliance_report(&entries, &report_path));
+                        
+                        match result {
+                            Ok(count) => self.show_status(&tr_args("status-compliance-report-saved", &[("count", count.into())])),
+                            Err(e) => self.show_error(&tr_args("error-compliance-report-save", &[("error", e.to_string().into())])),
+                        }
+                    }
+                }
+                
+                if ui.add_sized(
+                    [120.0, 30.0],
+                    Button::new(RichText::new(tr("logs-export")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(5.0))
+                ).on_hover_text(tr("logs-export-hint")).clicked() {
+                    if let Some(export_path) = FileDialog::new()
+                        .set_title("Export Logs")
+                        .set_file_name("crusty_logs.csv")
+                        .add_filter("CSV", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .add_filter("Text", &["txt", "log"])
+                        .save_file() {
+                        match self.logger.export(&export_path, &self.log_filter) {
+                            Ok(count) => self.show_status(&tr_args("status-logs-exported", &[("count", count.into()), ("path", export_path.display().to_string().into())])),
+                            Err(e) => self.show_error(&tr_args("error-logs-export", &[("error", e.to_string().into())])),
+                        }
                     }
                 }
                 
                 // Back button
                 if ui.add_sized(
                     [120.0, 30.0],
-                    Button::new(RichText::new("Back").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("button-back")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(5.0))
                 ).clicked() {
@@ -123,3 +164,24 @@ impl LogsScreen for CrustyApp {
         });
     }
 }
+
+/// Format a log entry as a line for the logs screen, with the time in the selected language
+fn format_log_entry(entry: &LogEntry) -> String {
+    let mut line = format!(
+        "{}  {}  {}",
+        locale::format_stored_timestamp(&entry.timestamp),
+        if entry.success { "OK    " } else { "FAILED" },
+        entry.operation
+    );
+    if !entry.file_path.is_empty() {
+        line.push_str(&format!("  {}", entry.file_path));
+    }
+    line.push_str(&format!(": {}", entry.message));
+    if let Some(reason) = &entry.reason {
+        line.push_str(&format!(" (reason: {})", reason));
+    }
+    if let Some(key_fingerprint) = &entry.key_fingerprint {
+        line.push_str(&format!(" [key {}]", key_fingerprint));
+    }
+    line
+}
@@ -1,7 +1,13 @@
-use eframe::egui::{Ui, RichText, Button, Rounding, ScrollArea, ComboBox, Label, TopBottomPanel};
+use eframe::egui::{Ui, RichText, Button, Rounding, ScrollArea, ComboBox, Label, TopBottomPanel, TextEdit, DragValue};
+use crate::backend::{ConnectionType, Parity};
 use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::MainTab;
 use crate::gui::file_list::{FileOperationType, EnhancedFileList};
 use crate::gui::action_bar::ActionBar;
+use crate::gui::help;
+use crate::gui::screens::SecuredFoldersScreen;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
 use std::path::PathBuf;
 
 /// Main screen trait
@@ -23,27 +29,30 @@ impl MainScreen for CrustyApp {
             
             // Tabs for Recent Files and Secured Folders
             ui.horizontal(|ui| {
-                if ui.selectable_label(true, "Recent Files").clicked() {
-                    // Already on Recent Files tab
-                }
-                if ui.selectable_label(false, "Secured Folders").clicked() {
-                    // Switch to Secured Folders tab (not implemented yet)
-                }
+                ui.selectable_value(&mut self.main_tab, MainTab::RecentFiles, tr("main-tab-recent-files"));
+                ui.selectable_value(&mut self.main_tab, MainTab::SecuredFolders, tr("main-tab-secured-folders"));
             });
             
             ui.separator();
             
+            if self.main_tab == MainTab::SecuredFolders {
+                self.show_secured_folders(ui);
+                ui.separator();
+                self.show_watched_folders(ui);
+                return;
+            }
+            
             // Operation mode selection (moved to a more compact area)
             ui.horizontal(|ui| {
-                ui.label("Processing Mode:");
-                ui.radio_value(&mut self.batch_mode, false, "Single File");
-                ui.radio_value(&mut self.batch_mode, true, "Multiple Files");
+                ui.label(tr("main-processing-mode"));
+                ui.radio_value(&mut self.operations.batch_mode, false, tr("main-single-file"));
+                ui.radio_value(&mut self.operations.batch_mode, true, tr("main-multiple-files"));
                 
                 ui.separator();
                 
                 if ui.add_sized(
                     [150.0, 24.0], 
-                    Button::new(RichText::new("Select Output Directory").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("main-select-output-dir")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(5.0))
                 ).clicked() {
@@ -54,16 +63,16 @@ impl MainScreen for CrustyApp {
             ui.add_space(5.0);
             
             // Display selected files
-            if !self.selected_files.is_empty() {
+            if !self.operations.selected_files().is_empty() {
                 ui.group(|ui| {
-                    ui.heading("Selected Files");
+                    ui.heading(tr("main-selected-files"));
                     
                     let mut file_to_remove = None;
                     
                     ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                        for (i, file) in self.selected_files.iter().enumerate() {
+                        for (i, file) in self.operations.selected_files().iter().enumerate() {
                             ui.horizontal(|ui| {
-                                ui.label(format!("{}. {}", i + 1, file.file_name().unwrap_or_default().to_string_lossy()));
+                                ui.label(format!("{}. {}", i + 1, self.operations.relative_path(file).to_string_lossy()));
                                 
                                 if ui.add(Button::new(RichText::new("❌").color(self.theme.button_text))
                                     .fill(self.theme.error)
@@ -77,84 +86,71 @@ impl MainScreen for CrustyApp {
                     
                     // Handle file removal outside the closure
                     if let Some(idx) = file_to_remove {
-                        self.selected_files.remove(idx);
-                        if self.selected_files.is_empty() {
-                            self.show_status("All files removed");
-                        } else {
-                            self.show_status(&format!("Removed file, {} remaining", self.selected_files.len()));
-                        }
+                        self.operations.remove_selected_file(idx);
                     }
                     
                     ui.add_space(5.0);
                     ui.horizontal(|ui| {
-                        ui.label(format!("Total: {} file(s)", self.selected_files.len()));
+                        ui.label(tr_args("main-total-files", &[("count", self.operations.selected_files().len().into())]));
                         
-                        if ui.add(Button::new(RichText::new("Clear All").color(self.theme.button_text))
+                        if ui.add(Button::new(RichText::new(tr("main-clear-all")).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(5.0))
                         ).clicked() {
-                            self.selected_files.clear();
-                            self.show_status("All files cleared");
+                            self.operations.clear_selected_files();
                         }
                     });
                 });
             }
             
             // Display output directory
-            if let Some(dir) = &self.output_dir {
+            if let Some(dir) = self.operations.output_dir() {
                 ui.group(|ui| {
-                    ui.heading("Output Directory");
+                    ui.heading(tr("main-output-dir"));
                     ui.label(format!("{}", dir.display()));
                 });
             }
             
             // Use the enhanced file list
-            self.show_enhanced_file_list(ui);
+            if let Some(file) = self.show_enhanced_file_list(ui) {
+                self.open_files(vec![file]);
+            }
             
             // Key selection in a more compact form
             ui.horizontal(|ui| {
                 ui.group(|ui| {
                     ui.horizontal(|ui| {
-                        ui.label("Current Key:");
+                        ui.label(tr("main-current-key"));
                         
-                        let current_key_name = self.current_key.as_ref().map_or_else(
-                            || "No key selected".to_string(),
-                            |current_key| {
-                                self.saved_keys.iter()
-                                    .find_map(|(name, key)| {
-                                        if key.to_base64() == current_key.to_base64() {
-                                            Some(name.clone())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .unwrap_or_else(|| "Unknown key".to_string())
-                            }
-                        );
+                        let current_key_name = self.keys.current_key_name()
+                            .unwrap_or_else(|| tr("main-no-key-selected"));
                         
-                        ui.add_sized(
+                        let current_key_label = ui.add_sized(
                             [150.0, 24.0],
                             Label::new(
                                 RichText::new(&current_key_name)
-                                    .color(if self.current_key.is_some() { self.theme.success } else { self.theme.error })
+                                    .color(if self.keys.has_key() { self.theme.success } else { self.theme.error })
                                     .strong()
                             )
                         );
+                        if let Some(key) = self.keys.current_key() {
+                            current_key_label.on_hover_text(tr_args("main-fingerprint", &[("fingerprint", key.fingerprint().into())]));
+                        }
                         
                         // Dropdown for key selection
                         let mut selected_key_index = None;
-                        let key_names: Vec<String> = self.saved_keys.iter()
-                            .map(|(name, _)| name.clone())
+                        let key_names: Vec<(String, String)> = self.keys.saved_keys().iter()
+                            .map(|(name, key)| (name.clone(), key.fingerprint()))
                             .collect();
                         
-                        ComboBox::from_label("Select")
+                        ComboBox::from_label(tr("main-select-key"))
                             .selected_text(&current_key_name)
                             .width(150.0)
                             .show_ui(ui, |ui| {
-                                for (i, name) in key_names.iter().enumerate() {
+                                for (i, (name, fingerprint)) in key_names.iter().enumerate() {
                                     if ui.selectable_label(
                                         current_key_name == *name,
-                                        name
+                                        format!("{}  ({})", name, fingerprint)
                                     ).clicked() {
                                         selected_key_index = Some(i);
                                     }
@@ -163,27 +159,172 @@ impl MainScreen for CrustyApp {
                         
                         // Handle key selection
                         if let Some(idx) = selected_key_index {
-                            if idx < self.saved_keys.len() {
-                                let (_, key) = &self.saved_keys[idx];
-                                self.current_key = Some(key.clone());
-                                self.show_status(&format!("Selected key: {}", key_names[idx]));
-                            }
+                            self.keys.select_key(idx);
                         }
                         
-                        if ui.add_sized(
+                        if self.key_management_allowed() && ui.add_sized(
                             [100.0, 24.0],
-                            Button::new(RichText::new("New Key").color(self.theme.button_text))
+                            Button::new(RichText::new(tr("main-new-key")).color(self.theme.button_text))
                                 .fill(self.theme.button_normal)
                                 .rounding(Rounding::same(5.0))
                         ).clicked() {
-                            self.new_key_name = format!("Key {}", self.saved_keys.len() + 1);
+                            self.new_key_name = format!("Key {}", self.keys.saved_keys().len() + 1);
                             let key_name = self.new_key_name.clone();
-                            self.generate_key(&key_name);
+                            self.keys.generate_key(&key_name);
                             self.new_key_name.clear();
                         }
                     });
                 });
             });
+            
+            ui.add_space(10.0);
+            
+            // Embedded backend configuration
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.operations.backend.use_embedded, tr("main-use-embedded"));
+                    help::help_icon(ui, help::EMBEDDED_BACKEND);
+                });
+                
+                if self.operations.backend.use_embedded {
+                    ui.add_space(5.0);
+                    
+                    // Connection type selection
+                    ui.horizontal(|ui| {
+                        ui.label(tr("main-connection-type"));
+                        ui.radio_value(&mut self.operations.backend.connection_type, ConnectionType::Usb, "USB");
+                        ui.radio_value(&mut self.operations.backend.connection_type, ConnectionType::Serial, tr("main-serial"));
+                        ui.radio_value(&mut self.operations.backend.connection_type, ConnectionType::Ethernet, "Ethernet");
+                    });
+                    
+                    // Device ID input
+                    ui.horizontal(|ui| {
+                        ui.label(tr("main-device-id"));
+                        ui.text_edit_singleline(&mut self.operations.backend.device_id);
+                    });
+                    
+                    ui.collapsing(tr("main-connection-settings"), |ui| {
+                        self.show_connection_settings(ui);
+                    });
+                    
+                    self.show_connection_test(ui);
+                }
+            });
         });
     }
 }
+
+impl CrustyApp {
+    /// Show the Test Connection button and what the device reported
+    fn show_connection_test(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let testing = self.operations.is_testing_connection();
+            let enabled = !testing && self.operations.overall_progress().is_none();
+            let label = if testing { tr("main-testing-connection") } else { tr("main-test-connection") };
+            if ui.add_enabled(enabled, Button::new(RichText::new(label).color(self.theme.button_text))
+                .fill(self.theme.button_normal)
+                .rounding(Rounding::same(5.0))
+            ).on_hover_text(tr("main-test-connection-hint")).clicked() {
+                self.operations.test_connection();
+            }
+            if testing {
+                ui.spinner();
+            }
+        });
+        
+        if let Some(health) = self.operations.device_health() {
+            ui.label(RichText::new(tr_args("main-device-firmware", &[
+                ("version", health.info.firmware_version.clone().into()),
+                ("milliseconds", (health.round_trip.as_millis() as u64).into()),
+            ])).color(self.theme.success));
+            ui.label(tr_args("main-device-algorithms", &[("algorithms", health.info.algorithms.join(", ").into())]));
+            ui.label(tr_args("main-device-resources", &[
+                ("memory", locale::format_size(health.info.free_memory as u64).into()),
+                ("slots", health.info.free_key_slots.into()),
+                ("queue", health.info.queue_capacity.into()),
+            ]));
+        }
+    }
+    
+    /// Show the settings for the selected connection type
+    fn show_connection_settings(&mut self, ui: &mut Ui) {
+        let backend = &mut self.operations.backend;
+        
+        match backend.connection_type {
+            ConnectionType::Usb => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("main-usb-interface"));
+                    ui.add(DragValue::new(&mut backend.usb.interface));
+                    ui.label(tr("main-usb-endpoint"));
+                    ui.add(DragValue::new(&mut backend.usb.endpoint));
+                });
+            },
+            ConnectionType::Serial => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("main-baud-rate"));
+                    ComboBox::from_id_source("baud_rate")
+                        .selected_text(backend.serial.baud_rate.to_string())
+                        .show_ui(ui, |ui| {
+                            for baud_rate in [9_600, 19_200, 38_400, 57_600, 115_200, 230_400, 460_800, 921_600] {
+                                ui.selectable_value(&mut backend.serial.baud_rate, baud_rate, baud_rate.to_string());
+                            }
+                        });
+                    ui.label(tr("main-parity"));
+                    ComboBox::from_id_source("parity")
+                        .selected_text(parity_name(backend.serial.parity))
+                        .show_ui(ui, |ui| {
+                            for parity in [Parity::None, Parity::Even, Parity::Odd] {
+                                ui.selectable_value(&mut backend.serial.parity, parity, parity_name(parity));
+                            }
+                        });
+                });
+            },
+            ConnectionType::Ethernet => {
+                ui.horizontal(|ui| {
+                    ui.label(tr("main-host"));
+                    ui.add(TextEdit::singleline(&mut backend.ethernet.host)
+                        .hint_text("e.g. 192.168.1.30")
+                        .desired_width(160.0));
+                    ui.label(tr("main-port"));
+                    ui.add(DragValue::new(&mut backend.ethernet.port));
+                });
+                ui.checkbox(&mut backend.ethernet.tls, tr("main-use-tls"));
+                if backend.ethernet.tls {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("main-device-certificate"))
+                            .on_hover_text(tr("main-device-certificate-hint"));
+                        match &backend.ethernet.certificate {
+                            Some(certificate) => ui.label(certificate.display().to_string()),
+                            None => ui.label(RichText::new(tr("main-no-certificate")).color(self.theme.text_secondary)),
+                        };
+                        if ui.button(tr("main-choose-certificate")).clicked() {
+                            if let Some(path) = rfd::FileDialog::new()
+                                .set_title("Choose the Device Certificate")
+                                .add_filter("Certificate", &["pem", "crt", "cer", "der"])
+                                .pick_file() {
+                                backend.ethernet.certificate = Some(path);
+                            }
+                        }
+                        if backend.ethernet.certificate.is_some() && ui.button(tr("main-clear-certificate")).clicked() {
+                            backend.ethernet.certificate = None;
+                        }
+                    });
+                }
+            },
+        }
+        
+        // Validate while the settings are edited
+        if let Err(e) = backend.connection_settings().validate() {
+            ui.label(RichText::new(e.to_string()).color(self.theme.error));
+        }
+    }
+}
+
+/// Get the display name of a parity setting
+fn parity_name(parity: Parity) -> String {
+    match parity {
+        Parity::None => tr("parity-none"),
+        Parity::Even => tr("parity-even"),
+        Parity::Odd => tr("parity-odd"),
+    }
+}
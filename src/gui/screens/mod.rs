@@ -7,6 +7,23 @@ pub mod key_mgmt;
 pub mod encrypt;
 pub mod decrypt;
 pub mod workflow;
+pub mod pin_prompt;
+pub mod proxy_settings;
+pub mod batch_warning;
+pub mod key_pin_warning;
+pub mod preflight;
+pub mod secured_folders;
+pub mod split_key;
+pub mod transfer;
+pub mod locked;
+pub mod cold_storage;
+pub mod cold_storage_restore;
+pub mod key_agreement;
+pub mod key_rotation;
+pub mod audit;
+pub mod catalog;
+pub mod benchmark;
+pub mod settings;
 
 // Re-export screen traits
 pub use dashboard::DashboardScreen;
@@ -17,3 +34,20 @@ pub use key_mgmt::KeyManagementScreen;
 pub use encrypt::EncryptScreen;
 pub use decrypt::DecryptScreen;
 pub use workflow::EncryptionWorkflowScreen;
+pub use pin_prompt::PinPromptScreen;
+pub use proxy_settings::ProxySettingsScreen;
+pub use batch_warning::BatchWarningScreen;
+pub use key_pin_warning::KeyPinWarningScreen;
+pub use preflight::PreflightScreen;
+pub use secured_folders::SecuredFoldersScreen;
+pub use split_key::SplitKeyScreen;
+pub use transfer::TransferScreen;
+pub use locked::LockedScreen;
+pub use cold_storage::ColdStorageScreen;
+pub use cold_storage_restore::ColdStorageRestoreScreen;
+pub use key_agreement::KeyAgreementScreen;
+pub use key_rotation::KeyRotationScreen;
+pub use audit::AuditScreen;
+pub use catalog::CatalogScreen;
+pub use benchmark::BenchmarkScreen;
+pub use settings::SettingsScreen;
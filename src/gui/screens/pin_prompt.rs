@@ -0,0 +1,102 @@
+use eframe::egui::{self, Context, RichText, Button, Rounding, TextEdit, Align2, Key};
+use crate::gui::app_core::CrustyApp;
+use crate::i18n::{tr, tr_args};
+use crate::smartcard::PinStatus;
+
+/// Smartcard PIN prompt trait
+pub trait PinPromptScreen {
+    fn show_pin_prompt(&mut self, ctx: &Context);
+}
+
+impl PinPromptScreen for CrustyApp {
+    fn show_pin_prompt(&mut self, ctx: &Context) {
+        let prompt = match self.pin_prompt.as_mut() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        
+        let token_key = match self.keys.token_keys().get(prompt.key_index) {
+            Some(token_key) => token_key,
+            None => {
+                self.pin_prompt = None;
+                return;
+            }
+        };
+        
+        let mut submit = false;
+        let mut cancel = false;
+        
+        egui::Window::new(tr("pin-prompt-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(tr_args("pin-prompt-enter", &[
+                    ("card", token_key.token_label.as_str().into()),
+                    ("name", token_key.name.as_str().into()),
+                ]));
+                
+                ui.add_space(5.0);
+                
+                // Retry state reported by the card
+                match prompt.status {
+                    PinStatus::FinalTry => {
+                        ui.label(RichText::new(tr("pin-prompt-final-try"))
+                            .color(self.theme.error)
+                            .strong());
+                    },
+                    PinStatus::CountLow => {
+                        ui.label(RichText::new(tr("pin-prompt-count-low"))
+                            .color(self.theme.error));
+                    },
+                    PinStatus::Ok | PinStatus::Locked => {},
+                }
+                
+                if prompt.failed_attempts > 0 {
+                    ui.label(RichText::new(tr_args("pin-prompt-incorrect", &[("count", prompt.failed_attempts.into())]))
+                        .color(self.theme.error));
+                }
+                
+                ui.add_space(5.0);
+                
+                let response = ui.add(TextEdit::singleline(&mut *prompt.pin)
+                    .password(true)
+                    .hint_text(tr("pin-prompt-pin"))
+                    .desired_width(200.0));
+                response.request_focus();
+                
+                if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                    submit = true;
+                }
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("pin-prompt-unlock")).color(self.theme.button_text))
+                            .fill(self.theme.accent)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        submit = true;
+                    }
+                    
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("button-cancel")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if cancel {
+            self.pin_prompt = None;
+            self.show_status(&tr("status-pin-cancelled"));
+        } else if submit {
+            self.submit_pin();
+        }
+    }
+}
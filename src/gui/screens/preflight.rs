@@ -0,0 +1,92 @@
+use eframe::egui::{self, Context, Grid, RichText, Button, Rounding, Align2};
+use crate::gui::app_core::CrustyApp;
+use crate::gui::file_list::FileOperationType;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+
+/// Pre-flight summary dialog trait
+pub trait PreflightScreen {
+    fn show_preflight(&mut self, ctx: &Context);
+}
+
+impl PreflightScreen for CrustyApp {
+    fn show_preflight(&mut self, ctx: &Context) {
+        let preflight = match &self.preflight {
+            Some(preflight) if !preflight.confirmed => preflight,
+            _ => return,
+        };
+
+        let operation = match preflight.operation {
+            FileOperationType::Decrypt => "decrypt",
+            _ => "encrypt",
+        };
+
+        let mut start = false;
+        let mut cancel = false;
+
+        egui::Window::new(tr("preflight-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(RichText::new(tr_args("preflight-about-to", &[
+                    ("operation", operation.into()),
+                    ("count", preflight.file_count.into()),
+                    ("size", locale::format_size(preflight.total_size).into()),
+                ])).strong());
+                ui.add_space(5.0);
+
+                Grid::new("preflight_summary").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+                    for (name, value) in &preflight.summary.items {
+                        let changed = preflight.changes.iter().any(|change| &change.name == name);
+                        ui.label(name);
+                        if changed {
+                            ui.label(RichText::new(value).strong().color(self.theme.accent));
+                        } else {
+                            ui.label(value);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                if !preflight.changes.is_empty() {
+                    ui.add_space(5.0);
+                    ui.label(tr("preflight-since-last"));
+                    for change in &preflight.changes {
+                        ui.label(RichText::new(tr_args("preflight-note", &[("note", change.note.as_str().into())])).strong().color(self.theme.error));
+                    }
+                }
+
+                ui.add_space(5.0);
+                ui.label(RichText::new(tr("preflight-turn-off")).weak());
+
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.add_sized(
+                        [120.0, 30.0],
+                        Button::new(RichText::new(tr("preflight-start")).color(self.theme.button_text))
+                            .fill(self.theme.accent)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        start = true;
+                    }
+
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("button-cancel")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.cancel_confirmations();
+        } else if start {
+            self.confirm_preflight();
+        }
+    }
+}
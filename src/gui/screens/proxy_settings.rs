@@ -0,0 +1,96 @@
+use eframe::egui::{self, Context, RichText, Button, Rounding, TextEdit, Align2};
+use crate::gui::app_core::CrustyApp;
+use crate::i18n::tr;
+use crate::network;
+use crate::settings::ProxyMode;
+
+/// Proxy settings dialog trait
+pub trait ProxySettingsScreen {
+    fn show_proxy_settings(&mut self, ctx: &Context);
+}
+
+impl ProxySettingsScreen for CrustyApp {
+    fn show_proxy_settings(&mut self, ctx: &Context) {
+        let dialog = match self.proxy_dialog.as_mut() {
+            Some(dialog) => dialog,
+            None => return,
+        };
+        
+        let mut save = false;
+        let mut cancel = false;
+        
+        egui::Window::new(tr("proxy-title"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(tr("proxy-explained"));
+                
+                ui.add_space(5.0);
+                
+                ui.radio_value(&mut dialog.settings.mode, ProxyMode::System, tr("proxy-system"));
+                ui.radio_value(&mut dialog.settings.mode, ProxyMode::Manual, tr("proxy-manual"));
+                ui.radio_value(&mut dialog.settings.mode, ProxyMode::None, tr("proxy-none"));
+                
+                if dialog.settings.mode == ProxyMode::Manual {
+                    ui.add_space(5.0);
+                    
+                    egui::Grid::new("proxy_grid").num_columns(2).show(ui, |ui| {
+                        ui.label(tr("proxy-address"));
+                        ui.add(TextEdit::singleline(&mut dialog.settings.address)
+                            .hint_text("proxy.example.com:3128")
+                            .desired_width(220.0));
+                        ui.end_row();
+                        
+                        ui.label(tr("proxy-user"));
+                        ui.add(TextEdit::singleline(&mut dialog.settings.username)
+                            .hint_text(tr("proxy-optional"))
+                            .desired_width(220.0));
+                        ui.end_row();
+                        
+                        ui.label(tr("proxy-password"));
+                        ui.add_enabled(!dialog.clear_password, TextEdit::singleline(&mut *dialog.password)
+                            .password(true)
+                            .hint_text(tr("proxy-unchanged"))
+                            .desired_width(220.0));
+                        ui.end_row();
+                    });
+                    
+                    ui.checkbox(&mut dialog.clear_password, tr("proxy-clear-password"));
+                    
+                    // Validate while the proxy is entered
+                    if let Err(e) = network::validate_proxy(&dialog.settings) {
+                        ui.label(RichText::new(e.to_string()).color(self.theme.error));
+                    }
+                }
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("button-save")).color(self.theme.button_text))
+                            .fill(self.theme.accent)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        save = true;
+                    }
+                    
+                    if ui.add_sized(
+                        [100.0, 30.0],
+                        Button::new(RichText::new(tr("button-cancel")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+        
+        if cancel {
+            self.proxy_dialog = None;
+        } else if save {
+            self.save_proxy_settings();
+        }
+    }
+}
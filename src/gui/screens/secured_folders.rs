@@ -0,0 +1,277 @@
+use eframe::egui::{Ui, RichText, Button, ComboBox, Rounding, ProgressBar, ScrollArea};
+use crate::gui::app_core::CrustyApp;
+use crate::gui::help;
+use crate::folder_watch::WatchEvent;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+use crate::secured_folders::FolderStatus;
+
+/// Secured folders tab trait
+pub trait SecuredFoldersScreen {
+    fn show_secured_folders(&mut self, ui: &mut Ui);
+    fn show_watched_folders(&mut self, ui: &mut Ui);
+}
+
+impl SecuredFoldersScreen for CrustyApp {
+    fn show_secured_folders(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.add_sized(
+                [120.0, 30.0],
+                Button::new(RichText::new(tr("folders-add")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(5.0))
+            ).clicked() {
+                self.add_secured_folder();
+            }
+            
+            if ui.add_sized(
+                [100.0, 30.0],
+                Button::new(RichText::new(tr("folders-refresh")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(5.0))
+            ).clicked() {
+                self.folders.refresh();
+            }
+            
+            if ui.add_enabled(
+                !self.folders.is_checking_integrity() && !self.keys.is_locked(),
+                Button::new(RichText::new(tr("folders-check")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(5.0))
+                    .min_size([120.0, 30.0].into())
+            ).on_hover_text(tr("folders-check-hint")).clicked() {
+                self.start_integrity_check();
+            }
+            
+            let has_folders = !self.folders.folders().is_empty();
+            if ui.add_enabled(
+                has_folders,
+                Button::new(RichText::new(tr("folders-lock-all")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(5.0))
+                    .min_size([100.0, 30.0].into())
+            ).on_hover_text(tr("folders-lock-all-hint")).clicked() {
+                self.lock_all_secured_folders(true);
+            }
+            
+            if ui.add_enabled(
+                has_folders,
+                Button::new(RichText::new(tr("folders-unlock-all")).color(self.theme.button_text))
+                    .fill(self.theme.accent)
+                    .rounding(Rounding::same(5.0))
+                    .min_size([100.0, 30.0].into())
+            ).on_hover_text(tr("folders-unlock-all-hint")).clicked() {
+                self.lock_all_secured_folders(false);
+            }
+            
+            help::help_icon(ui, help::SECURED_FOLDERS);
+        });
+        
+        ui.add_space(5.0);
+        
+        if self.folders.folders().is_empty() {
+            ui.label(tr("folders-none"));
+            return;
+        }
+        
+        let key_name = self.keys.current_key_name()
+            .unwrap_or_else(|| tr("folders-no-key"));
+        ui.label(tr_args("folders-current-key", &[("name", key_name.into())]));
+        
+        ui.add_space(5.0);
+        
+        let job = self.folders.job_progress();
+        let mut lock_folder = None;
+        let mut unlock_folder = None;
+        let mut remove_folder = None;
+        let mut bind_key = None;
+        
+        // Saved keys a folder can be bound to, by name and fingerprint
+        let key_choices: Vec<(String, String)> = self.keys.saved_keys().iter()
+            .map(|(name, key)| (name.clone(), key.fingerprint()))
+            .collect();
+        
+        ScrollArea::vertical().max_height(350.0).show(ui, |ui| {
+            for (index, folder) in self.folders.folders().iter().enumerate() {
+                let status = self.folders.status(index);
+                let (icon, text, color) = match &status {
+                    FolderStatus::Locked => ("🔒", tr("folders-locked"), self.theme.success),
+                    FolderStatus::Unlocked => ("🔓", tr("folders-unlocked"), self.theme.text_primary),
+                    FolderStatus::PartlyLocked { locked, total } => (
+                        "⚠",
+                        tr_args("folders-partly-locked", &[("locked", (*locked).into()), ("total", (*total).into())]),
+                        self.theme.error,
+                    ),
+                    FolderStatus::Empty => ("📁", tr("folders-empty"), self.theme.text_secondary),
+                    FolderStatus::Missing => ("❓", tr("folders-missing"), self.theme.error),
+                };
+                
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(icon).size(20.0));
+                        ui.vertical(|ui| {
+                            ui.label(RichText::new(folder.path.display().to_string()).strong());
+                            ui.label(RichText::new(text).color(color));
+                        });
+                    });
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(tr("folders-key"));
+                        let bound_name = match &folder.bound_key {
+                            Some(fingerprint) => key_choices.iter()
+                                .find(|(_, choice)| choice == fingerprint)
+                                .map_or_else(|| tr("folders-key-missing"), |(name, _)| name.clone()),
+                            None => tr("folders-key-current"),
+                        };
+                        ComboBox::from_id_source(("folder_key", index))
+                            .selected_text(bound_name)
+                            .show_ui(ui, |ui| {
+                                if ui.selectable_label(folder.bound_key.is_none(), tr("folders-key-current")).clicked()
+                                    && folder.bound_key.is_some() {
+                                    bind_key = Some((index, None));
+                                }
+                                for (name, fingerprint) in &key_choices {
+                                    let selected = folder.bound_key.as_ref() == Some(fingerprint);
+                                    if ui.selectable_label(selected, name).clicked() && !selected {
+                                        bind_key = Some((index, Some(fingerprint.clone())));
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text(tr("folders-key-hint"));
+                    });
+                    
+                    match job {
+                        Some((job_index, fraction)) if job_index == index => {
+                            ui.add(ProgressBar::new(fraction)
+                                .show_percentage()
+                                .animate(true));
+                        },
+                        _ if self.folders.is_queued(index) => {
+                            ui.label(RichText::new(tr("folders-queued")).color(self.theme.text_secondary));
+                        },
+                        _ => {
+                            ui.horizontal(|ui| {
+                                let idle = job.is_none();
+                                let can_lock = matches!(status, FolderStatus::Unlocked | FolderStatus::PartlyLocked { .. });
+                                let can_unlock = matches!(status, FolderStatus::Locked | FolderStatus::PartlyLocked { .. });
+                                
+                                if can_lock && ui.add_enabled(
+                                    idle,
+                                    Button::new(RichText::new(tr("folders-lock")).color(self.theme.button_text))
+                                        .fill(self.theme.accent)
+                                        .rounding(Rounding::same(5.0))
+                                ).clicked() {
+                                    lock_folder = Some(index);
+                                }
+                                
+                                if can_unlock && ui.add_enabled(
+                                    idle,
+                                    Button::new(RichText::new(tr("pin-prompt-unlock")).color(self.theme.button_text))
+                                        .fill(self.theme.accent)
+                                        .rounding(Rounding::same(5.0))
+                                ).clicked() {
+                                    unlock_folder = Some(index);
+                                }
+                                
+                                if ui.add(
+                                    Button::new(RichText::new(tr("button-remove")).color(self.theme.button_text))
+                                        .fill(self.theme.button_normal)
+                                        .rounding(Rounding::same(5.0))
+                                ).clicked() {
+                                    remove_folder = Some(index);
+                                }
+                            });
+                        },
+                    }
+                });
+            }
+        });
+        
+        // Handle the actions outside the closure
+        if let Some(index) = lock_folder {
+            self.lock_secured_folder(index);
+        }
+        if let Some(index) = unlock_folder {
+            self.unlock_secured_folder(index);
+        }
+        if let Some(index) = remove_folder {
+            self.folders.remove_folder(index);
+        }
+        if let Some((index, fingerprint)) = bind_key {
+            self.folders.bind_key(index, fingerprint);
+        }
+    }
+    
+    fn show_watched_folders(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.heading(tr("watch-heading"));
+            
+            if ui.add_enabled(
+                self.keys.current_key().is_some(),
+                Button::new(RichText::new(tr("watch-add")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(5.0))
+                    .min_size([140.0, 30.0].into())
+            ).on_hover_text(tr("watch-add-hint")).clicked() {
+                self.add_watched_folder();
+            }
+        });
+        
+        if self.settings.watched_folders.is_empty() {
+            ui.label(tr("watch-none"));
+            return;
+        }
+        
+        if self.keys.is_locked() {
+            ui.label(RichText::new(tr("watch-paused")).color(self.theme.error));
+        }
+        
+        let mut remove_folder = None;
+        for (index, watched) in self.settings.watched_folders.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(watched.folder.display().to_string()).strong());
+                ui.label("→");
+                ui.label(watched.output_dir.display().to_string());
+                ui.label(RichText::new(tr_args("watch-key", &[("name", watched.key_name.clone().into())]))
+                    .color(self.theme.text_secondary));
+                if ui.add(
+                    Button::new(RichText::new(tr("button-remove")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(5.0))
+                ).clicked() {
+                    remove_folder = Some(index);
+                }
+            });
+        }
+        
+        if !self.watch_activity.is_empty() {
+            ui.add_space(5.0);
+            ui.label(RichText::new(tr("watch-activity")).strong());
+            ScrollArea::vertical().id_source("watch_activity").max_height(150.0).show(ui, |ui| {
+                for (time, event) in &self.watch_activity {
+                    let time = locale::format_stored_timestamp(time);
+                    match event {
+                        WatchEvent::Encrypted { file, output } => ui.label(tr_args("watch-encrypted", &[
+                            ("time", time.into()),
+                            ("file", file.display().to_string().into()),
+                            ("output", output.display().to_string().into()),
+                        ])),
+                        WatchEvent::Failed { path, error } => ui.label(RichText::new(tr_args("watch-failed", &[
+                            ("time", time.into()),
+                            ("file", path.display().to_string().into()),
+                            ("error", error.clone().into()),
+                        ])).color(self.theme.error)),
+                    };
+                }
+            });
+        }
+        
+        // Handle the action outside the loop
+        if let Some(index) = remove_folder {
+            let watched = self.settings.watched_folders.remove(index);
+            self.show_status(&tr_args("status-watch-stopped", &[("folder", watched.folder.display().to_string().into())]));
+            self.save_settings();
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use eframe::egui::{Ui, Button, Color32, ComboBox, Grid, RichText, Rounding, TextEdit};
+
+use crate::backend::{ConnectionSettings, EmbeddedConfig};
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::i18n::{tr, tr_args};
+use crate::settings::Theme;
+
+/// Read sizes offered, in KiB (0 picks the size from the measured throughput)
+const CHUNK_SIZES_KB: [u32; 6] = [0, 64, 256, 1024, 4096, 16384];
+
+/// Settings screen trait
+pub trait SettingsScreen {
+    fn show_settings(&mut self, ui: &mut Ui);
+    fn show_theme_editor(&mut self, ui: &mut Ui);
+    fn show_key_pins(&mut self, ui: &mut Ui);
+}
+
+impl SettingsScreen for CrustyApp {
+    fn show_settings(&mut self, ui: &mut Ui) {
+        let mut appearance_changed = false;
+        let mut changed = false;
+
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.heading(RichText::new(tr("settings-title")).size(28.0));
+            ui.add_space(10.0);
+            ui.label(tr("settings-explained"));
+            ui.add_space(20.0);
+
+            ui.group(|ui| {
+                ui.heading(tr("settings-appearance"));
+                Grid::new("settings_appearance").num_columns(2).spacing([20.0, 10.0]).show(ui, |ui| {
+                    ui.label(tr("settings-theme"));
+                    let selected_theme = self.settings.saved_theme().map_or_else(|| theme_name(self.settings.theme), |saved| saved.name.clone());
+                    ComboBox::from_id_source("settings_theme")
+                        .selected_text(selected_theme)
+                        .width(220.0)
+                        .show_ui(ui, |ui| {
+                            for theme in Theme::ALL {
+                                let selected = self.settings.saved_theme.is_none() && self.settings.theme == theme;
+                                if ui.selectable_label(selected, theme_name(theme)).clicked() {
+                                    self.settings.theme = theme;
+                                    self.settings.saved_theme = None;
+                                    appearance_changed = true;
+                                }
+                            }
+                            for saved in &self.settings.saved_themes {
+                                let selected = self.settings.saved_theme.as_ref() == Some(&saved.name);
+                                if ui.selectable_label(selected, &saved.name).clicked() {
+                                    self.settings.saved_theme = Some(saved.name.clone());
+                                    appearance_changed = true;
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(tr("settings-chunk-size"));
+                    ComboBox::from_id_source("settings_chunk_size")
+                        .selected_text(chunk_size_name(self.settings.chunk_size_kb))
+                        .width(220.0)
+                        .show_ui(ui, |ui| {
+                            for size in CHUNK_SIZES_KB {
+                                appearance_changed |= ui.selectable_value(&mut self.settings.chunk_size_kb, size, chunk_size_name(size)).changed();
+                            }
+                        }).response.on_hover_text(tr("settings-chunk-size-hint"));
+                    ui.end_row();
+                });
+            });
+
+            ui.add_space(10.0);
+            self.show_theme_editor(ui);
+            ui.add_space(10.0);
+
+            ui.group(|ui| {
+                ui.heading(tr("settings-startup"));
+                Grid::new("settings_defaults").num_columns(3).spacing([20.0, 10.0]).show(ui, |ui| {
+                    let defaults = &mut self.settings.defaults;
+
+                    ui.label(tr("settings-output-dir"));
+                    ui.label(defaults.output_dir.as_ref().map(|dir| dir.display().to_string()).unwrap_or_else(|| tr("settings-none")));
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("settings-choose")).clicked() {
+                            if let Some(dir) = rfd::FileDialog::new()
+                                .set_title("Select the Default Output Directory")
+                                .pick_folder() {
+                                defaults.output_dir = Some(dir);
+                                changed = true;
+                            }
+                        }
+                        if ui.add_enabled(defaults.output_dir.is_some(), Button::new(tr("settings-clear"))).clicked() {
+                            defaults.output_dir = None;
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label(tr("settings-file-selection"));
+                    changed |= ui.checkbox(&mut defaults.batch_mode, tr("settings-multiple-files")).changed();
+                    ui.label("");
+                    ui.end_row();
+
+                    ui.label(tr("settings-embedded-device"));
+                    ui.label(defaults.embedded.as_ref().map(describe_device).unwrap_or_else(|| tr("settings-none-saved")));
+                    ui.horizontal(|ui| {
+                        let current = self.operations.backend.config();
+                        if ui.add_enabled(current.validate().is_ok(), Button::new(tr("settings-save-device")))
+                            .on_hover_text(tr("settings-save-device-hint"))
+                            .clicked() {
+                            defaults.embedded = Some(current);
+                            changed = true;
+                        }
+                        if ui.add_enabled(defaults.embedded.is_some(), Button::new(tr("settings-clear"))).clicked() {
+                            defaults.embedded = None;
+                            defaults.use_embedded = false;
+                            changed = true;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("");
+                    ui.add_enabled_ui(defaults.embedded.is_some(), |ui| {
+                        changed |= ui.checkbox(&mut defaults.use_embedded, tr("settings-use-device")).changed();
+                    });
+                    ui.label("");
+                    ui.end_row();
+                });
+            });
+
+            ui.add_space(10.0);
+            self.show_key_pins(ui);
+
+            ui.add_space(20.0);
+            if ui.add(
+                Button::new(RichText::new(tr("button-close")).color(self.theme.button_text))
+                    .fill(self.theme.button_normal)
+                    .rounding(Rounding::same(8.0))
+                    .min_size([120.0, 40.0].into())
+            ).clicked() {
+                self.state = AppState::MainScreen;
+            }
+        });
+
+        if appearance_changed {
+            self.apply_appearance_settings();
+        }
+        if changed || appearance_changed {
+            self.save_settings();
+        }
+    }
+
+    // Colors of the theme in use, to change and save as a named theme
+    fn show_theme_editor(&mut self, ui: &mut Ui) {
+        let mut save = false;
+        let mut discard = false;
+        let mut delete = false;
+
+        ui.group(|ui| {
+            ui.collapsing(tr("settings-theme-editor"), |ui| {
+                ui.label(RichText::new(tr("settings-theme-editor-explained")).color(self.theme.text_secondary));
+                ui.add_space(5.0);
+
+                Grid::new("theme_colors").num_columns(4).spacing([20.0, 6.0]).show(ui, |ui| {
+                    for (i, (_, label, color)) in self.theme.colors_mut().into_iter().enumerate() {
+                        ui.label(label);
+                        let mut rgb = [color.r(), color.g(), color.b()];
+                        if ui.color_edit_button_srgb(&mut rgb).changed() {
+                            *color = Color32::from_rgb(rgb[0], rgb[1], rgb[2]);
+                        }
+                        if i % 2 == 1 {
+                            ui.end_row();
+                        }
+                    }
+                });
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label(tr("settings-theme-name"));
+                    ui.add(TextEdit::singleline(&mut self.theme_name).hint_text(tr("settings-theme-name-hint")).desired_width(160.0));
+                    save = ui.add_enabled(!self.theme_name.trim().is_empty(), Button::new(tr("settings-save-theme")))
+                        .on_hover_text(tr("settings-save-theme-hint"))
+                        .clicked();
+                    discard = ui.button(tr("settings-discard-theme")).clicked();
+                    if self.settings.saved_theme().is_some() {
+                        delete = ui.button(tr("settings-delete-theme")).clicked();
+                    }
+                });
+            });
+        });
+
+        if save {
+            self.save_edited_theme();
+        } else if discard {
+            self.apply_appearance_settings();
+        } else if delete {
+            self.delete_saved_theme();
+        }
+    }
+
+    // Keys pinned to folders, with a warning before files there are encrypted with another key
+    fn show_key_pins(&mut self, ui: &mut Ui) {
+        let mut pin_to = None;
+        let mut remove = None;
+
+        ui.group(|ui| {
+            ui.heading(tr("settings-key-pins"));
+            ui.label(RichText::new(tr("settings-key-pins-explained"))
+                .color(self.theme.text_secondary));
+            ui.add_space(5.0);
+
+            Grid::new("key_pins").num_columns(3).striped(true).spacing([20.0, 4.0]).show(ui, |ui| {
+                for (i, pin) in self.settings.key_pins.iter().enumerate() {
+                    ui.label(pin.folder.display().to_string());
+                    ui.label(&pin.key_name);
+                    if ui.button(tr("button-remove")).clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            let key_name = self.keys.current_key_name();
+            let label = match &key_name {
+                Some(name) => tr_args("keys-pin-selected", &[("name", name.as_str().into())]),
+                None => tr("keys-pin-selected-key"),
+            };
+            if ui.add_enabled(self.keys.current_key().is_some(), Button::new(label)).clicked() {
+                pin_to = rfd::FileDialog::new()
+                    .set_title("Select the Folder to Pin the Key To")
+                    .pick_folder();
+            }
+        });
+
+        if let Some(index) = remove {
+            self.settings.key_pins.remove(index);
+            self.save_settings();
+        }
+        if let Some(folder) = pin_to {
+            self.pin_current_key(folder);
+        }
+    }
+}
+
+/// Get the name of a theme, as shown in the settings
+fn theme_name(theme: Theme) -> String {
+    match theme {
+        Theme::System => tr("settings-theme-system"),
+        Theme::Light => tr("settings-theme-light"),
+        Theme::Dark => tr("settings-theme-dark"),
+    }
+}
+
+/// Get the name of a read size, as shown in the settings
+fn chunk_size_name(size_kb: u32) -> String {
+    match size_kb {
+        0 => tr("settings-chunk-size-tuned"),
+        size_kb if size_kb >= 1024 => format!("{} MiB", size_kb / 1024),
+        size_kb => format!("{} KiB", size_kb),
+    }
+}
+
+/// Describe a saved device, e.g. "crusty-01 over USB"
+fn describe_device(config: &EmbeddedConfig) -> String {
+    let connection = match config.connection {
+        ConnectionSettings::Usb(_) => "usb",
+        ConnectionSettings::Serial(_) => "serial",
+        ConnectionSettings::Ethernet(_) => "ethernet",
+    };
+    tr_args("settings-device-over", &[("device", config.device_id.as_str().into()), ("connection", connection.into())])
+}
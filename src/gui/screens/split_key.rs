@@ -0,0 +1,150 @@
+use eframe::egui::{Ui, Button, RichText, Rounding, TextEdit, Grid};
+use std::path::PathBuf;
+
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::gui::help;
+use crate::i18n::tr;
+
+/// Split-key management screen trait
+pub trait SplitKeyScreen {
+    fn show_split_key_management(&mut self, ui: &mut Ui);
+}
+
+impl SplitKeyScreen for CrustyApp {
+    fn show_split_key_management(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(10.0);
+            ui.heading(tr("split-title"));
+            ui.add_space(20.0);
+            
+            // Explanation of split-key functionality
+            ui.group(|ui| {
+                ui.heading(tr("split-about"));
+                ui.label(tr("split-about-1"));
+                ui.label(tr("split-about-2"));
+                ui.label(tr("split-about-3"));
+                
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    ui.label(tr("split-scheme"));
+                    help::help_icon(ui, help::SPLIT_KEY_THRESHOLD);
+                });
+                ui.label(tr("split-primary"));
+                ui.label(tr("split-secondary"));
+                ui.label(tr("split-recovery"));
+                
+                ui.add_space(10.0);
+                ui.label(tr("split-any-two"));
+            });
+            
+            ui.add_space(20.0);
+            
+            // Create split key section
+            ui.group(|ui| {
+                ui.heading(tr("split-create"));
+                
+                if self.keys.current_key().is_none() {
+                    ui.label(RichText::new(tr("split-select-key-first")).color(self.theme.error));
+                } else {
+                    if ui.add_sized(
+                        [220.0, 40.0],
+                        Button::new(RichText::new(tr("split-create")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        self.keys.create_split_key();
+                    }
+                }
+            });
+            
+            ui.add_space(20.0);
+            
+            // Share custodians section
+            if !self.keys.custodians.is_empty() {
+                ui.group(|ui| {
+                    ui.heading(tr("split-custodians"));
+                    
+                    Grid::new("custodians_grid")
+                        .num_columns(4)
+                        .spacing([10.0, 5.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(RichText::new(tr("split-share")).strong());
+                            ui.label(RichText::new(tr("split-custodian")).strong());
+                            ui.label(RichText::new(tr("split-contact")).strong());
+                            ui.label(RichText::new(tr("split-storage")).strong());
+                            ui.end_row();
+                            
+                            let labels: Vec<String> = self.keys.share_records().iter()
+                                .map(|record| record.label.clone())
+                                .collect();
+                            
+                            for (label, custodian) in labels.iter().zip(self.keys.custodians.iter_mut()) {
+                                ui.label(label);
+                                ui.add(TextEdit::singleline(&mut custodian.name).desired_width(120.0));
+                                ui.add(TextEdit::singleline(&mut custodian.contact).desired_width(120.0));
+                                ui.add(TextEdit::singleline(&mut custodian.location).desired_width(160.0));
+                                ui.end_row();
+                            }
+                        });
+                    
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("split-save-custodians")).clicked() {
+                            self.keys.save_custodians();
+                        }
+                        
+                        if ui.button(tr("split-export-report")).clicked() {
+                            self.export_ceremony_report();
+                        }
+                    });
+                });
+                
+                ui.add_space(20.0);
+            }
+            
+            // Reconstruct key section
+            ui.group(|ui| {
+                ui.heading(tr("split-reconstruct"));
+                
+                ui.label(tr("split-reconstruct-need"));
+                ui.label(tr("split-reconstruct-primary"));
+                ui.label(tr("split-reconstruct-other"));
+                
+                if ui.add_sized(
+                    [220.0, 40.0],
+                    Button::new(RichText::new(tr("split-select-secondary")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    // This would normally use a native file dialog
+                    // For now, we'll just use a placeholder path
+                    let secondary_share_path = PathBuf::from("secondary_share.key");
+                    
+                    self.keys.reconstruct_key(&secondary_share_path);
+                }
+                
+                ui.add_space(10.0);
+                
+                if ui.add_sized(
+                    [220.0, 40.0],
+                    Button::new(RichText::new(tr("split-scan-qr")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    self.show_status(&tr("status-qr-scan-unavailable"));
+                }
+            });
+            
+            ui.add_space(20.0);
+            
+            // Back button
+            if ui.add(Button::new(RichText::new(tr("agreement-back")).color(self.theme.button_text))
+                .fill(self.theme.button_normal)
+                .rounding(Rounding::same(5.0))
+            ).clicked() {
+                self.state = AppState::KeyManagement;
+            }
+        });
+    }
+}
@@ -0,0 +1,589 @@
+use eframe::egui::{Ui, Button, Grid, ProgressBar, RichText, Rounding, TextEdit, TextStyle, ScrollArea};
+
+use crate::gui::app_core::CrustyApp;
+use crate::gui::app_state::AppState;
+use crate::gui::help;
+use crate::gui::theme::AppTheme;
+use crate::gui::utils;
+use crate::i18n::{tr, tr_args};
+use crate::network;
+use crate::policy::get_policy;
+use crate::services::transfer_service::{BindTo, TransferReceiveState, TransferService, TransferState};
+use crate::split_key::{self, PhraseStatus, RecipientBinding, DICTATION_GROUP_LEN, KEY_SHARE_DICTATION_GROUPS, KEY_SHARE_PHRASE_WORDS};
+
+/// Largest number of word suggestions shown while a share phrase is typed
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Transfer screens trait
+pub trait TransferScreen {
+    fn show_transfer_preparation(&mut self, ui: &mut Ui);
+    fn show_transfer_receive(&mut self, ui: &mut Ui);
+}
+
+impl TransferScreen for CrustyApp {
+    fn show_transfer_preparation(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(10.0);
+            ui.heading(tr("transfer-title"));
+            ui.add_space(20.0);
+            
+            // Explanation of transfer functionality
+            ui.group(|ui| {
+                ui.heading(tr("transfer-about"));
+                ui.label(tr("transfer-about-1"));
+                ui.label(tr("transfer-about-2"));
+                ui.label(tr("transfer-about-3"));
+                
+                ui.add_space(10.0);
+                ui.label(tr("transfer-process"));
+                ui.label(tr("transfer-process-1"));
+                ui.label(tr("transfer-process-2"));
+                ui.label(tr("transfer-process-3"));
+                ui.label(tr("transfer-process-4"));
+                ui.label(tr("transfer-process-5"));
+                
+                ui.add_space(10.0);
+                ui.label(tr("transfer-about-4"));
+            });
+            
+            ui.add_space(20.0);
+            
+            // Create transfer package section
+            ui.group(|ui| {
+                ui.heading(tr("transfer-create"));
+                
+                if self.keys.current_key().is_none() {
+                    ui.label(RichText::new(tr("split-select-key-first")).color(self.theme.error));
+                } else {
+                    if let Some(key) = self.keys.current_key() {
+                        ui.label(tr_args("transfer-key-fingerprint", &[("fingerprint", key.fingerprint().into())]));
+                    }
+                    
+                    // Optional binding, so that intercepted shares are of no use alone
+                    ui.label(tr("transfer-bind"));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.transfer.bind_to, BindTo::Nobody, tr("transfer-not-bound"));
+                        ui.radio_value(&mut self.transfer.bind_to, BindTo::Passphrase, tr("transfer-passphrase"));
+                        ui.radio_value(&mut self.transfer.bind_to, BindTo::Identity, tr("transfer-identity"));
+                    });
+                    match self.transfer.bind_to {
+                        BindTo::Nobody => {},
+                        BindTo::Passphrase => {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("transfer-passphrase-label"));
+                                ui.add(TextEdit::singleline(&mut *self.transfer.recipient_passphrase)
+                                    .password(true)
+                                    .desired_width(250.0));
+                            });
+                            ui.label(RichText::new(tr("transfer-passphrase-channel")).color(self.theme.text_secondary));
+                        },
+                        BindTo::Identity => {
+                            ui.horizontal(|ui| {
+                                ui.label(tr("transfer-identity-label"));
+                                ui.add(TextEdit::singleline(&mut self.transfer.recipient_identity)
+                                    .desired_width(300.0)
+                                    .hint_text(tr("transfer-identity-hint")));
+                            });
+                        },
+                    }
+                    ui.add_space(5.0);
+                    
+                    if ui.add_sized(
+                        [220.0, 40.0],
+                        Button::new(RichText::new(tr("transfer-create")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(8.0))
+                    ).clicked() {
+                        if let Some(key) = self.keys.current_key().cloned() {
+                            self.transfer.create_package(&key);
+                        }
+                    }
+                }
+            });
+            
+            ui.add_space(20.0);
+            
+            // Display shares section (only shown if package is created)
+            if *self.transfer.state() == TransferState::Created || 
+               *self.transfer.state() == TransferState::SharesSaved {
+                if let Some(package) = self.transfer.package() {
+                    let mut save_share = None;
+                    let mut copy_link = None;
+                    let mut relay_share = None;
+                    let mut mnemonic_toggle = None;
+                    let mut dictation_toggle = None;
+                    let cover = self.settings.protect_shares;
+                    self.secrets_shown = true;
+                    
+                    ui.group(|ui| {
+                        ui.heading(tr("transfer-shares"));
+                        
+                        ui.horizontal(|ui| {
+                            ui.label(tr_args("transfer-threshold", &[
+                                ("needed", package.get_threshold().into()),
+                                ("total", package.get_shares_count().into()),
+                            ]));
+                            help::help_icon(ui, help::SPLIT_KEY_THRESHOLD);
+                        });
+                        if let Some(binding) = package.binding() {
+                            ui.label(tr_args("transfer-bound-to", &[("binding", binding.describe().into())]));
+                        }
+                        
+                        ui.add_space(10.0);
+                        
+                        // Display each share
+                        for i in 0..package.get_shares_count() {
+                            ui.group(|ui| {
+                                ui.heading(tr_args("transfer-share-number", &[("number", (i + 1).into())]));
+                                
+                                let share_text_result = package.get_share_text(i);
+                                let mnemonic_result = package.get_share_mnemonic(i);
+                                
+                                if let Ok(share_text) = share_text_result {
+                                    // Display the share text and its seal in a scrollable area
+                                    let mut sealed_text = package.get_sealed_share_text(i).unwrap_or_else(|_| share_text.to_string());
+                                    utils::hold_to_reveal(ui, &self.theme, cover, |ui| {
+                                        ScrollArea::vertical().max_height(80.0).show(ui, |ui| {
+                                            ui.add(TextEdit::multiline(&mut sealed_text)
+                                                .desired_width(f32::INFINITY)
+                                                .desired_rows(3)
+                                                .interactive(false));
+                                        });
+                                    });
+                                    
+                                    // Option to save this share
+                                    if ui.add_sized(
+                                        [150.0, 30.0],
+                                        Button::new(RichText::new(tr("transfer-save-share")).color(self.theme.button_text))
+                                            .fill(self.theme.button_normal)
+                                            .rounding(Rounding::same(5.0))
+                                    ).clicked() {
+                                        // This would normally use a native file dialog
+                                        // For now, we'll just use the default share path
+                                        save_share = Some(i);
+                                    }
+                                    
+                                    // Option to view as mnemonic
+                                    if let Ok(mnemonic) = mnemonic_result {
+                                        let viewing = self.transfer.mnemonic_share == Some(i);
+                                        
+                                        let mnemonic_button = ui.add_sized(
+                                            [150.0, 30.0],
+                                            Button::new(RichText::new(if viewing { tr("transfer-hide-mnemonic") } else { tr("transfer-view-mnemonic") }).color(self.theme.button_text))
+                                                .fill(self.theme.button_normal)
+                                                .rounding(Rounding::same(5.0))
+                                        );
+                                        
+                                        if mnemonic_button.clicked() {
+                                            mnemonic_toggle = Some(i);
+                                        }
+                                        
+                                        if viewing {
+                                            utils::hold_to_reveal(ui, &self.theme, cover, |ui| {
+                                                ui.monospace(&mnemonic);
+                                            });
+                                        }
+                                    }
+                                    
+                                    // Option to read this share out over the phone
+                                    let dictating = self.transfer.dictation_share == Some(i);
+                                    if ui.add_sized(
+                                        [150.0, 30.0],
+                                        Button::new(RichText::new(if dictating { tr("transfer-hide-dictation") } else { tr("transfer-read-out") }).color(self.theme.button_text))
+                                            .fill(self.theme.button_normal)
+                                            .rounding(Rounding::same(5.0))
+                                    ).on_hover_text(tr("transfer-dictation-hint"))
+                                        .clicked() {
+                                        dictation_toggle = Some(i);
+                                    }
+                                    
+                                    if dictating {
+                                        if let Ok(groups) = package.get_share_dictation(i) {
+                                            utils::hold_to_reveal(ui, &self.theme, cover, |ui| {
+                                                Grid::new(format!("dictation_grid_{}", i))
+                                                    .num_columns(2)
+                                                    .spacing([20.0, 4.0])
+                                                    .striped(true)
+                                                    .show(ui, |ui| {
+                                                        for group in &groups {
+                                                            ui.monospace(group.to_string());
+                                                            ui.label(RichText::new(group.spelled()).color(self.theme.text_secondary));
+                                                            ui.end_row();
+                                                        }
+                                                    });
+                                            });
+                                        }
+                                    }
+                                    
+                                    // Option to send this share as a one-time relay link
+                                    if get_policy().relay.is_some() && ui.add_enabled_ui(!network::is_offline(), |ui| {
+                                        ui.add_sized(
+                                            [150.0, 30.0],
+                                            Button::new(RichText::new(tr("transfer-send-relay")).color(self.theme.button_text))
+                                                .fill(self.theme.button_normal)
+                                                .rounding(Rounding::same(5.0))
+                                        ).on_disabled_hover_text(tr("transfer-offline"))
+                                    }).inner.clicked() {
+                                        relay_share = Some(i);
+                                    }
+                                } else {
+                                    ui.label(RichText::new(tr("transfer-share-error")).color(self.theme.error));
+                                }
+                            });
+                        }
+                        
+                        // Link for the last share sent via the relay
+                        if let Some(link) = self.transfer.relay_link().map(str::to_string) {
+                            ui.add_space(10.0);
+                            ui.label(tr("transfer-one-time-link"));
+                            ui.horizontal(|ui| {
+                                ui.monospace(&link);
+                                if ui.button(tr("transfer-copy")).clicked() {
+                                    copy_link = Some(link);
+                                }
+                            });
+                        }
+                    });
+                    
+                    if let Some(link) = copy_link {
+                        self.copy_to_clipboard(ui, link);
+                    }
+                    
+                    if let Some(index) = save_share {
+                        self.transfer.save_share(index, &TransferService::share_path(index));
+                    }
+                    
+                    if let Some(index) = relay_share {
+                        self.transfer.send_share_via_relay(index);
+                    }
+                    
+                    if let Some(index) = mnemonic_toggle {
+                        self.transfer.mnemonic_share = if self.transfer.mnemonic_share == Some(index) { None } else { Some(index) };
+                    }
+                    
+                    if let Some(index) = dictation_toggle {
+                        self.transfer.dictation_share = if self.transfer.dictation_share == Some(index) { None } else { Some(index) };
+                    }
+                }
+            }
+            
+            ui.add_space(20.0);
+            
+            // Back button
+            if ui.add(Button::new(RichText::new(tr("agreement-back")).color(self.theme.button_text))
+                .fill(self.theme.button_normal)
+                .rounding(Rounding::same(5.0))
+            ).clicked() {
+                self.state = AppState::KeyManagement;
+            }
+        });
+    }
+    
+    fn show_transfer_receive(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.add_space(10.0);
+            ui.heading(tr("transfer-receive-title"));
+            ui.add_space(20.0);
+            
+            // Explanation of receive functionality
+            ui.group(|ui| {
+                ui.heading(tr("transfer-receive-about"));
+                ui.label(tr("transfer-receive-about-1"));
+                ui.label(tr("transfer-receive-about-2"));
+                ui.label(tr("transfer-receive-about-3"));
+                
+                ui.add_space(10.0);
+                ui.label(tr("transfer-process"));
+                ui.label(tr("transfer-receive-process-1"));
+                ui.label(tr("transfer-receive-process-2"));
+                ui.label(tr("transfer-receive-process-3"));
+            });
+            
+            ui.add_space(20.0);
+            
+            // Enter shares section
+            ui.group(|ui| {
+                ui.heading(tr("transfer-enter-shares"));
+                
+                ui.label(tr_args("transfer-shares-needed", &[("count", self.transfer.shares_needed().into())]));
+                ui.add_space(5.0);
+                
+                // One input per share, as many as the package needs
+                if self.transfer.shares.iter().any(|share| !share.trim().is_empty()) {
+                    self.secrets_shown = true;
+                }
+                let can_remove = self.transfer.shares.len() > 2;
+                let mut changed_share = None;
+                let mut removed_share = None;
+                for i in 0..self.transfer.shares.len() {
+                    ui.horizontal(|ui| {
+                        ui.label(tr_args("restore-share-number", &[("number", (i + 1).into())]));
+                        if can_remove && ui.small_button(tr("button-remove")).clicked() {
+                            removed_share = Some(i);
+                        }
+                    });
+                    if ui.add(TextEdit::multiline(&mut self.transfer.shares[i])
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(3)
+                        .hint_text(tr("transfer-share-hint"))
+                    ).changed() {
+                        changed_share = Some(i);
+                    }
+                    show_phrase_check(ui, &mut self.transfer.shares[i], &self.theme);
+                    ui.add_space(10.0);
+                }
+                
+                if ui.button(tr("restore-add-share-field")).clicked() {
+                    self.transfer.add_share_field();
+                }
+                if let Some(i) = changed_share {
+                    self.transfer.share_changed(i);
+                }
+                if let Some(i) = removed_share {
+                    self.transfer.remove_share_field(i);
+                }
+                
+                // Shares saved or relayed from a package carry its seal
+                match self.transfer.seal_status() {
+                    Some(Ok(seal)) => {
+                        ui.label(RichText::new(tr_args("transfer-sealed", &[
+                            ("package", seal.package_id_text().into()),
+                            ("needed", seal.threshold.into()),
+                            ("total", seal.shares_count.into()),
+                        ])).color(self.theme.success));
+                    },
+                    Some(Err(e)) => {
+                        ui.label(RichText::new(e).color(self.theme.error));
+                    },
+                    None => {},
+                }
+                
+                ui.add_space(10.0);
+                
+                // Share read out over the phone, checked group by group
+                ui.collapsing(tr("transfer-dictation"), |ui| {
+                    ui.label(tr("transfer-dictation-explained"));
+                    
+                    let mut checked = 0;
+                    Grid::new("dictation_entry_grid")
+                        .num_columns(3)
+                        .spacing([10.0, 4.0])
+                        .show(ui, |ui| {
+                            for (i, group) in self.transfer.dictation_groups.iter_mut().enumerate() {
+                                ui.label(format!("{:02}", i + 1));
+                                ui.add(TextEdit::singleline(group)
+                                    .desired_width(90.0)
+                                    .font(TextStyle::Monospace)
+                                    .hint_text("ABCD X"));
+                                
+                                // A group is only checked once all its characters are in
+                                let entered = group.chars().filter(|c| c.is_alphanumeric()).count();
+                                if entered > DICTATION_GROUP_LEN {
+                                    match split_key::check_dictation_group(i + 1, group) {
+                                        Ok(_) => {
+                                            checked += 1;
+                                            ui.label(RichText::new("✓").color(self.theme.success));
+                                        },
+                                        Err(e) => {
+                                            ui.label(RichText::new(e).color(self.theme.error));
+                                        },
+                                    }
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    
+                    ui.label(RichText::new(tr_args("transfer-groups-checked", &[("checked", checked.into()), ("total", KEY_SHARE_DICTATION_GROUPS.into())])).color(self.theme.text_secondary));
+                    if ui.add_enabled(checked == KEY_SHARE_DICTATION_GROUPS, Button::new(tr("transfer-use-dictated"))).clicked() {
+                        self.transfer.use_dictated_share();
+                    }
+                });
+                
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    // Option to load from file
+                    if ui.add_sized(
+                        [150.0, 30.0],
+                        Button::new(RichText::new(tr("transfer-load-share")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        // This would normally use a native file dialog
+                        // For now, we'll just show a message
+                        self.show_status(&tr("status-share-file-dialog"));
+                    }
+                    
+                    // Option to read a printed share card
+                    if ui.add_sized(
+                        [180.0, 30.0],
+                        Button::new(RichText::new(tr("transfer-import-scan")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).on_hover_text(tr("transfer-import-scan-hint"))
+                        .clicked() {
+                        self.import_share_from_scan();
+                    }
+                });
+                
+                // Option to fetch a share from a relay link
+                if get_policy().relay.is_some() {
+                    ui.add_space(10.0);
+                    
+                    ui.horizontal(|ui| {
+                        ui.label(tr("transfer-relay-link"));
+                        ui.add(TextEdit::singleline(&mut self.transfer.relay_link_input)
+                            .desired_width(300.0)
+                            .hint_text(tr("transfer-relay-link-hint")));
+                        
+                        if ui.add_enabled(!network::is_offline(), Button::new(tr("transfer-fetch-share")))
+                            .on_disabled_hover_text(tr("transfer-offline"))
+                            .clicked() {
+                            self.transfer.fetch_shares_from_relay();
+                        }
+                    });
+                }
+                
+                ui.add_space(10.0);
+                
+                // Extra unwrap step for a package bound to its recipient
+                match self.transfer.received_binding() {
+                    Some(RecipientBinding::Passphrase(_)) => {
+                        ui.label(tr("transfer-bound-passphrase"));
+                        ui.add(TextEdit::singleline(&mut *self.transfer.unwrap_passphrase)
+                            .password(true)
+                            .desired_width(250.0));
+                        ui.add_space(10.0);
+                    },
+                    Some(RecipientBinding::Identity(_)) => {
+                        ui.label(tr("transfer-bound-identity"));
+                        ui.add_space(10.0);
+                    },
+                    None => {},
+                }
+                
+                // Reconstruct key button
+                if ui.add_sized(
+                    [220.0, 40.0],
+                    Button::new(RichText::new(tr("transfer-reconstruct")).color(self.theme.button_text))
+                        .fill(self.theme.button_normal)
+                        .rounding(Rounding::same(8.0))
+                ).clicked() {
+                    if let Some((key, provenance)) = self.transfer.reconstruct_key() {
+                        let name = "Transfer Key";
+                        let fingerprint = key.fingerprint();
+                        if self.keys.add_key(name, key, provenance) {
+                            self.show_status(&tr_args("status-key-reconstructed-check", &[("name", name.into()), ("fingerprint", fingerprint.into())]));
+                        }
+                    }
+                }
+            });
+            
+            // Decrypt-and-open, once the key is reconstructed
+            if *self.transfer.receive_state() == TransferReceiveState::Reconstructed {
+                ui.add_space(10.0);
+                ui.group(|ui| {
+                    ui.heading(tr("transfer-decrypt-received"));
+                    ui.label(tr("transfer-decrypt-received-explained"));
+                    
+                    if get_policy().require_decrypt_reason {
+                        ui.horizontal(|ui| {
+                            ui.label(tr("decrypt-reason-required"));
+                            ui.add(TextEdit::singleline(&mut self.operations.decrypt_reason)
+                                .hint_text(tr("decrypt-reason-hint"))
+                                .desired_width(300.0));
+                        });
+                    }
+                    
+                    ui.add_space(5.0);
+                    let running = self.received_output.is_some();
+                    if ui.add_enabled(
+                        !running,
+                        Button::new(RichText::new(tr("transfer-select-received")).color(self.theme.button_text))
+                            .fill(self.theme.button_normal)
+                            .rounding(Rounding::same(5.0))
+                    ).clicked() {
+                        self.decrypt_received_file();
+                    }
+                    if let (true, Some(progress)) = (running, self.operations.overall_progress()) {
+                        ui.add(ProgressBar::new(progress).show_percentage());
+                    }
+                });
+            }
+            
+            ui.add_space(20.0);
+            
+            // Back button
+            if ui.add(Button::new(RichText::new(tr("agreement-back")).color(self.theme.button_text))
+                .fill(self.theme.button_normal)
+                .rounding(Rounding::same(5.0))
+            ).clicked() {
+                self.state = AppState::KeyManagement;
+            }
+        });
+    }
+}
+
+/// Help with a share phrase as it is typed: suggestions for the word being typed, the
+/// number of words entered, and the wrong words (shown for phrases only, not share text)
+fn show_phrase_check(ui: &mut Ui, share: &mut String, theme: &AppTheme) {
+    let is_phrase = share.chars().any(|c| c.is_ascii_lowercase())
+        && share.chars().all(|c| c.is_ascii_alphabetic() || c.is_whitespace());
+    if !is_phrase {
+        return;
+    }
+    
+    // The word being typed is only checked once it is finished
+    let last_word_start = share.trim_end().rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let typing = share[last_word_start..].to_lowercase();
+    let suggestions: Vec<&str> = if share.ends_with(char::is_whitespace) {
+        Vec::new()
+    } else {
+        split_key::share_words().filter(|word| word.starts_with(&typing)).collect()
+    };
+    let finished = if suggestions.is_empty() || suggestions.contains(&typing.as_str()) {
+        share.as_str()
+    } else {
+        &share[..last_word_start]
+    };
+    let check = split_key::check_share_phrase(finished);
+    
+    if !check.wrong_words.is_empty() {
+        ui.horizontal_wrapped(|ui| {
+            for (i, word) in share.split_whitespace().enumerate() {
+                if check.wrong_words.contains(&i) {
+                    ui.label(RichText::new(word).color(theme.error).strong().underline())
+                        .on_hover_text(tr_args("transfer-word-number", &[("number", (i + 1).into())]));
+                } else {
+                    ui.label(word);
+                }
+            }
+        });
+    }
+    
+    match &check.status {
+        PhraseStatus::Incomplete(words) => {
+            ui.label(RichText::new(tr_args("transfer-words", &[("words", (*words).into()), ("total", KEY_SHARE_PHRASE_WORDS.into())])).color(theme.text_secondary));
+        },
+        PhraseStatus::Valid => {
+            ui.label(RichText::new(tr("transfer-valid-phrase")).color(theme.success));
+        },
+        PhraseStatus::Invalid(message) => {
+            ui.label(RichText::new(message).color(theme.error));
+        },
+    }
+    
+    if suggestions.len() > 1 || suggestions.first().is_some_and(|&word| word != typing) {
+        ui.horizontal_wrapped(|ui| {
+            for word in suggestions.iter().take(MAX_SUGGESTIONS) {
+                if ui.small_button(*word).clicked() {
+                    share.truncate(last_word_start);
+                    share.push_str(word);
+                    share.push(' ');
+                }
+            }
+        });
+    }
+}
@@ -1,9 +1,11 @@
 use eframe::egui::{Ui, RichText, Button, Rounding, ProgressBar, TextEdit, ScrollArea, ComboBox};
 use crate::gui::app_core::CrustyApp;
 use crate::gui::app_state::{AppState, EncryptionWorkflowStep};
+use crate::gui::help;
+use crate::gui::screens::EncryptScreen;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
 use crate::start_operation::FileOperation;
-use crate::gui::file_list::FileOperationType;
-use std::path::PathBuf;
 
 /// Encryption workflow screen trait
 pub trait EncryptionWorkflowScreen {
@@ -18,7 +20,7 @@ impl EncryptionWorkflowScreen for CrustyApp {
     fn show_encryption_workflow(&mut self, ui: &mut Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
-            ui.heading(RichText::new("Encryption Workflow").size(28.0));
+            ui.heading(RichText::new(tr("workflow-title")).size(28.0));
             ui.add_space(10.0);
             
             // Workflow steps indicator
@@ -83,7 +85,7 @@ impl EncryptionWorkflowScreen for CrustyApp {
                 if self.encryption_workflow_step != EncryptionWorkflowStep::Files {
                     if ui.add_sized(
                         [120.0, 40.0],
-                        Button::new(RichText::new("← Previous").color(self.theme.button_text))
+                        Button::new(RichText::new(tr("button-previous")).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(8.0))
                     ).clicked() {
@@ -94,30 +96,30 @@ impl EncryptionWorkflowScreen for CrustyApp {
                 // Cancel button
                 if ui.add_sized(
                     [120.0, 40.0],
-                    Button::new(RichText::new("Cancel").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("button-cancel")).color(self.theme.button_text))
                         .fill(self.theme.button_normal)
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     self.state = AppState::Dashboard;
-                    self.operation = FileOperation::None;
+                    self.operations.set_operation(FileOperation::None);
                 }
                 
                 // Next/Finish button
                 let (next_text, next_enabled) = match self.encryption_workflow_step {
                     EncryptionWorkflowStep::Files => (
-                        "Next →",
-                        !self.selected_files.is_empty() && self.output_dir.is_some()
+                        tr("button-next"),
+                        self.operations.is_ready_to_encrypt()
                     ),
                     EncryptionWorkflowStep::Keys => (
-                        "Next →",
-                        self.current_key.is_some()
+                        tr("button-next"),
+                        self.keys.current_key().is_some()
                     ),
                     EncryptionWorkflowStep::Options => (
-                        "Next →",
+                        tr("button-next"),
                         true
                     ),
                     EncryptionWorkflowStep::Execute => (
-                        "Finish",
+                        tr("workflow-finish"),
                         self.encryption_workflow_complete
                     ),
                 };
@@ -132,7 +134,7 @@ impl EncryptionWorkflowScreen for CrustyApp {
                         if self.encryption_workflow_step == EncryptionWorkflowStep::Execute {
                             // Finish the workflow
                             self.state = AppState::Dashboard;
-                            self.operation = FileOperation::None;
+                            self.operations.set_operation(FileOperation::None);
                         } else {
                             // Go to next step
                             self.encryption_workflow_step = self.encryption_workflow_step.next();
@@ -141,10 +143,10 @@ impl EncryptionWorkflowScreen for CrustyApp {
                         // Show error message based on current step
                         match self.encryption_workflow_step {
                             EncryptionWorkflowStep::Files => {
-                                self.show_error("Please select files and output directory");
+                                self.show_error(&tr("error-select-files-and-output"));
                             },
                             EncryptionWorkflowStep::Keys => {
-                                self.show_error("Please select or create an encryption key");
+                                self.show_error(&tr("error-select-or-create-key"));
                             },
                             _ => {}
                         }
@@ -157,16 +159,16 @@ impl EncryptionWorkflowScreen for CrustyApp {
     // Files step
     fn show_workflow_files_step(&mut self, ui: &mut Ui) {
         ui.group(|ui| {
-            ui.heading("Step 1: Select Files");
+            ui.heading(tr("workflow-step-files"));
             
             ui.add_space(10.0);
             
             // File selection
             ui.horizontal(|ui| {
-                let select_text = if self.batch_mode {
-                    "Select Files"
+                let select_text = if self.operations.batch_mode {
+                    tr("ops-select-files")
                 } else {
-                    "Select File"
+                    tr("ops-select-file")
                 };
                 
                 if ui.add_sized(
@@ -178,19 +180,19 @@ impl EncryptionWorkflowScreen for CrustyApp {
                     self.select_files();
                 }
                 
-                ui.checkbox(&mut self.batch_mode, "Batch Mode");
+                ui.checkbox(&mut self.operations.batch_mode, tr("ops-batch-mode"));
             });
             
             ui.add_space(5.0);
             
             // Display selected files
-            if self.selected_files.is_empty() {
-                ui.label("No files selected");
+            if self.operations.selected_files().is_empty() {
+                ui.label(tr("ops-no-files"));
             } else {
-                ui.label(format!("Selected {} file(s)", self.selected_files.len()));
+                ui.label(tr_args("ops-selected-count", &[("count", self.operations.selected_files().len().into())]));
                 
                 ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                    for file in &self.selected_files {
+                    for file in self.operations.selected_files() {
                         ui.label(format!("• {}", file.file_name().unwrap_or_default().to_string_lossy()));
                     }
                 });
@@ -199,54 +201,44 @@ impl EncryptionWorkflowScreen for CrustyApp {
             ui.add_space(10.0);
             
             // Output directory selection
-            ui.heading("Output Directory");
+            ui.heading(tr("main-output-dir"));
             
             if ui.add_sized(
                 [200.0, 30.0],
-                Button::new(RichText::new("Select Output Directory").color(self.theme.button_text))
+                Button::new(RichText::new(tr("main-select-output-dir")).color(self.theme.button_text))
                     .fill(self.theme.button_normal)
                     .rounding(Rounding::same(8.0))
             ).clicked() {
                 self.select_output_dir();
             }
             
-            if let Some(dir) = &self.output_dir {
-                ui.label(format!("Output directory: {}", dir.display()));
+            if let Some(dir) = self.operations.output_dir() {
+                ui.label(tr_args("ops-output-dir-is", &[("dir", dir.display().to_string().into())]));
             } else {
-                ui.label("No output directory selected");
+                ui.label(tr("ops-no-output-dir"));
             }
+            
+            self.show_replace_originals_options(ui);
         });
     }
     
     // Keys step
     fn show_workflow_keys_step(&mut self, ui: &mut Ui) {
         ui.group(|ui| {
-            ui.heading("Step 2: Select Encryption Key");
+            ui.heading(tr("workflow-step-key"));
             
             ui.add_space(10.0);
             
             // Current key display
-            if self.current_key.is_none() {
-                ui.label(RichText::new("No key selected").color(self.theme.error));
+            if self.keys.current_key().is_none() {
+                ui.label(RichText::new(tr("main-no-key-selected")).color(self.theme.error));
             } else {
                 // Find the name of the current key
-                let key_name = self.current_key.as_ref().map_or_else(
-                    || "Unknown key".to_string(),
-                    |current_key| {
-                        self.saved_keys.iter()
-                            .find_map(|(name, key)| {
-                                if key.to_base64() == current_key.to_base64() {
-                                    Some(name.clone())
-                                } else {
-                                    None
-                                }
-                            })
-                            .unwrap_or_else(|| "Unknown key".to_string())
-                    }
-                );
+                let key_name = self.keys.current_key_name()
+                    .unwrap_or_else(|| tr("ops-unknown-key"));
                 
                 ui.horizontal(|ui| {
-                    ui.label("Current Key:");
+                    ui.label(tr("main-current-key"));
                     ui.label(RichText::new(&key_name).color(self.theme.success).strong());
                 });
             }
@@ -255,74 +247,64 @@ impl EncryptionWorkflowScreen for CrustyApp {
             
             // Key selection options
             ui.horizontal(|ui| {
-                // Create new key
-                ui.vertical(|ui| {
-                    ui.heading("Create New Key");
+                // Create new key (not available when key management is disabled by policy)
+                if self.key_management_allowed() {
+                    ui.vertical(|ui| {
+                        ui.heading(tr("workflow-create-key"));
                     
-                    ui.horizontal(|ui| {
-                        ui.label("Key Name:");
-                        ui.add(TextEdit::singleline(&mut self.new_key_name)
-                            .hint_text("Enter a name for the new key")
-                            .desired_width(200.0));
-                    });
+                        ui.horizontal(|ui| {
+                            ui.label(tr("workflow-key-name"));
+                            ui.add(TextEdit::singleline(&mut self.new_key_name)
+                                .hint_text(tr("workflow-key-name-hint"))
+                                .desired_width(200.0));
+                        });
                     
-                    ui.add_space(5.0);
+                        ui.add_space(5.0);
                     
-                    if ui.add_sized(
-                        [150.0, 30.0],
-                        Button::new(RichText::new("Generate Key").color(self.theme.button_text))
-                            .fill(self.theme.accent)
-                            .rounding(Rounding::same(8.0))
-                    ).clicked() {
-                        if self.new_key_name.is_empty() {
-                            self.show_error("Please enter a name for the key");
-                        } else {
-                    let key_name = self.new_key_name.clone();
-                    self.generate_key(&key_name);
-                            self.new_key_name.clear();
+                        if ui.add_sized(
+                            [150.0, 30.0],
+                            Button::new(RichText::new(tr("workflow-generate-key")).color(self.theme.button_text))
+                                .fill(self.theme.accent)
+                                .rounding(Rounding::same(8.0))
+                        ).clicked() {
+                            if self.new_key_name.is_empty() {
+                                self.show_error(&tr("error-key-name"));
+                            } else {
+                                let key_name = self.new_key_name.clone();
+                                self.keys.generate_key(&key_name);
+                                self.new_key_name.clear();
+                            }
                         }
-                    }
-                });
+                    });
                 
-                ui.add_space(20.0);
+                    ui.add_space(20.0);
+                }
                 
                 // Select existing key
                 ui.vertical(|ui| {
-                    ui.heading("Select Existing Key");
+                    ui.heading(tr("workflow-select-existing"));
                     
-                    if self.saved_keys.is_empty() {
-                        ui.label("No saved keys available");
+                    if self.keys.saved_keys().is_empty() {
+                        ui.label(tr("workflow-no-saved-keys"));
                     } else {
                         // Create a temporary vector of key names for the dropdown
-                        let key_names: Vec<String> = self.saved_keys.iter()
-                            .map(|(name, _)| name.clone())
+                        let key_names: Vec<(String, String)> = self.keys.saved_keys().iter()
+                            .map(|(name, key)| (name.clone(), key.fingerprint()))
                             .collect();
                         
-                        let current_key_name = self.current_key.as_ref().map_or_else(
-                            || "Select a key".to_string(),
-                            |current_key| {
-                                self.saved_keys.iter()
-                                    .find_map(|(name, key)| {
-                                        if key.to_base64() == current_key.to_base64() {
-                                            Some(name.clone())
-                                        } else {
-                                            None
-                                        }
-                                    })
-                                    .unwrap_or_else(|| "Unknown key".to_string())
-                            }
-                        );
+                        let current_key_name = self.keys.current_key_name()
+                            .unwrap_or_else(|| tr("workflow-select-a-key"));
                         
                         let mut selected_key_index = None;
                         
-                        ComboBox::from_label("Select Key")
+                        ComboBox::from_label(tr("ops-select-key"))
                             .selected_text(&current_key_name)
                             .width(250.0)
                             .show_ui(ui, |ui| {
-                                for (i, name) in key_names.iter().enumerate() {
+                                for (i, (name, fingerprint)) in key_names.iter().enumerate() {
                                     if ui.selectable_label(
                                         current_key_name == *name,
-                                        name
+                                        format!("{}  ({})", name, fingerprint)
                                     ).clicked() {
                                         selected_key_index = Some(i);
                                     }
@@ -331,19 +313,25 @@ impl EncryptionWorkflowScreen for CrustyApp {
                         
                         // Handle key selection outside the closure
                         if let Some(idx) = selected_key_index {
-                            if idx < self.saved_keys.len() {
-                                let (name, key) = &self.saved_keys[idx];
-                                self.current_key = Some(key.clone());
-                                self.show_status(&format!("Selected key: {}", name));
-                            }
+                            self.keys.select_key(idx);
+                        }
+                        
+                        if let Some(expires_on) = self.keys.current_key_expired_on() {
+                            ui.label(RichText::new(tr_args("encrypt-key-expired", &[("date", expires_on.into())]))
+                                .color(self.theme.error));
+                        }
+                        if let Some(pin) = self.key_pin_mismatch() {
+                            ui.label(RichText::new(tr_args("encrypt-key-pinned", &[("name", pin.key_name.as_str().into()), ("folder", pin.folder.display().to_string().into())]))
+                                .strong()
+                                .color(self.theme.error));
                         }
                     }
                     
                     ui.add_space(5.0);
                     
-                    if ui.add_sized(
+                    if self.key_management_allowed() && ui.add_sized(
                         [150.0, 30.0],
-                        Button::new(RichText::new("Load Key from File").color(self.theme.button_text))
+                        Button::new(RichText::new(tr("workflow-load-key")).color(self.theme.button_text))
                             .fill(self.theme.button_normal)
                             .rounding(Rounding::same(8.0))
                     ).clicked() {
@@ -357,46 +345,89 @@ impl EncryptionWorkflowScreen for CrustyApp {
     // Options step
     fn show_workflow_options_step(&mut self, ui: &mut Ui) {
         ui.group(|ui| {
-            ui.heading("Step 3: Encryption Options");
+            ui.heading(tr("workflow-step-options"));
             
             ui.add_space(10.0);
             
             // Recipient options
-            ui.heading("Recipient Options");
-            ui.checkbox(&mut self.use_recipient, "Encrypt for specific recipient");
+            ui.heading(tr("workflow-recipient-options"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.operations.use_recipient, tr("encrypt-for-recipients"));
+                help::help_icon(ui, help::RECIPIENT_MODE);
+            });
             
-            if self.use_recipient {
-                ui.horizontal(|ui| {
-                    ui.label("Recipient Email:");
-                    ui.add(TextEdit::singleline(&mut self.recipient_email)
-                        .hint_text("Enter recipient's email address")
-                        .desired_width(250.0));
-                });
-                
-                ui.label("The recipient will need the same key to decrypt the files.");
+            if self.operations.use_recipient {
+                self.show_recipient_list(ui);
+            }
+            
+            ui.add_space(10.0);
+            
+            // File name options
+            ui.heading(tr("workflow-file-names"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.operations.hide_file_names, tr("encrypt-hide-names"));
+                help::help_icon(ui, help::HIDE_FILE_NAMES);
+            });
+            
+            if self.operations.hide_file_names {
+                ui.label(tr("workflow-random-names"));
+            }
+            
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.operations.add_parity, tr("encrypt-parity"));
+                help::help_icon(ui, help::PARITY);
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.operations.add_digest, tr("encrypt-digest"));
+                help::help_icon(ui, help::PLAINTEXT_DIGEST);
+            });
+            
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut self.operations.age_format, tr("encrypt-age-format")).changed() && self.operations.age_format {
+                    self.operations.openpgp_format = false;
+                }
+                help::help_icon(ui, help::AGE_FORMAT);
+            });
+            
+            if self.operations.age_format {
+                self.show_age_recipient_choice(ui);
+            }
+            
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut self.operations.openpgp_format, tr("encrypt-openpgp-format")).changed() && self.operations.openpgp_format {
+                    self.operations.age_format = false;
+                }
+                help::help_icon(ui, help::OPENPGP);
+            });
+            
+            if self.operations.openpgp_format {
+                self.show_openpgp_recipient_choice(ui);
             }
             
             ui.add_space(10.0);
             
             // Backend options
-            ui.heading("Encryption Backend");
-            ui.checkbox(&mut self.use_embedded_backend, "Use hardware encryption");
+            ui.heading(tr("workflow-backend-heading"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.operations.backend.use_embedded, tr("ops-use-hardware"));
+                help::help_icon(ui, help::EMBEDDED_BACKEND);
+            });
             
-            if self.use_embedded_backend {
+            if self.operations.backend.use_embedded {
                 ui.horizontal(|ui| {
-                    ui.label("Connection Type:");
-                    ui.radio_value(&mut self.embedded_connection_type, crate::backend::ConnectionType::Usb, "USB");
-                    ui.radio_value(&mut self.embedded_connection_type, crate::backend::ConnectionType::Serial, "Serial");
+                    ui.label(tr("main-connection-type"));
+                    ui.radio_value(&mut self.operations.backend.connection_type, crate::backend::ConnectionType::Usb, "USB");
+                    ui.radio_value(&mut self.operations.backend.connection_type, crate::backend::ConnectionType::Serial, tr("ops-serial"));
                 });
                 
                 ui.horizontal(|ui| {
-                    ui.label("Device ID:");
-                    ui.text_edit_singleline(&mut self.embedded_device_id);
+                    ui.label(tr("workflow-device-id"));
+                    ui.text_edit_singleline(&mut self.operations.backend.device_id);
                 });
                 
-                ui.label("Hardware encryption offloads cryptographic operations to a dedicated device.");
+                ui.label(tr("workflow-hardware-explained"));
             } else {
-                ui.label("Software encryption uses your computer's CPU for cryptographic operations.");
+                ui.label(tr("workflow-software-explained"));
             }
         });
     }
@@ -404,95 +435,85 @@ impl EncryptionWorkflowScreen for CrustyApp {
     // Execute step
     fn show_workflow_execute_step(&mut self, ui: &mut Ui) {
         ui.group(|ui| {
-            ui.heading("Step 4: Execute Encryption");
+            ui.heading(tr("workflow-step-execute"));
             
             ui.add_space(10.0);
             
             // Summary
-            ui.heading("Encryption Summary");
+            ui.heading(tr("workflow-summary"));
             
-            ui.label(format!("Files to encrypt: {} file(s)", self.selected_files.len()));
-            ui.label(format!("Output directory: {}", self.output_dir.as_ref().unwrap_or(&PathBuf::from("")).display()));
+            ui.label(tr_args("workflow-files-to-encrypt", &[("count", self.operations.selected_files().len().into())]));
+            if self.operations.replace_originals {
+                ui.label(tr("workflow-replaced"));
+            } else {
+                ui.label(tr_args("ops-output-dir-is", &[("dir", self.operations.output_dir().map(|dir| dir.display().to_string()).unwrap_or_default().into())]));
+            }
             
             // Find the name of the current key
-            let key_name = self.current_key.as_ref().map_or_else(
-                || "Unknown key".to_string(),
-                |current_key| {
-                    self.saved_keys.iter()
-                        .find_map(|(name, key)| {
-                            if key.to_base64() == current_key.to_base64() {
-                                Some(name.clone())
-                            } else {
-                                None
-                            }
-                        })
-                        .unwrap_or_else(|| "Unknown key".to_string())
-                }
-            );
+            let key_name = self.keys.current_key_name()
+                .unwrap_or_else(|| tr("ops-unknown-key"));
             
-            ui.label(format!("Encryption key: {}", key_name));
+            ui.label(tr_args("workflow-encryption-key", &[("name", key_name.into())]));
             
-            if self.use_recipient {
-                ui.label(format!("Recipient: {}", self.recipient_email));
+            if let Some(recipients) = self.operations.recipients() {
+                ui.label(tr_args("workflow-recipients", &[("recipients", recipients.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ").into())]));
             }
             
-            ui.label(format!("Backend: {}", if self.use_embedded_backend { "Hardware" } else { "Software" }));
+            ui.label(tr_args("workflow-backend", &[("backend", if self.operations.backend.use_embedded { "hardware" } else { "software" }.into())]));
+            
+            if !self.encryption_workflow_complete {
+                match self.operations.estimated_duration() {
+                    Some(estimate) => ui.label(tr_args("workflow-estimated-time", &[("duration", locale::format_duration(estimate).into())])),
+                    None => ui.label(tr("workflow-estimate-unknown")),
+                };
+            }
             
             ui.add_space(20.0);
             
             // Execute button
-            let can_encrypt = !self.selected_files.is_empty() && 
-                             self.output_dir.is_some() && 
-                             self.current_key.is_some();
+            let can_encrypt = self.operations.is_ready_to_encrypt() && self.keys.current_key().is_some();
             
             if !self.encryption_workflow_complete {
                 if ui.add_sized(
                     [200.0, 40.0],
-                    Button::new(RichText::new("🔒 Start Encryption").color(self.theme.button_text))
+                    Button::new(RichText::new(tr("workflow-start")).color(self.theme.button_text))
                         .fill(if can_encrypt { self.theme.accent } else { self.theme.button_normal })
                         .rounding(Rounding::same(8.0))
                 ).clicked() {
                     if can_encrypt {
-                        self.operation = FileOperation::Encrypt;
-                        
-                        // Add files to the file list
-                        let files_to_add: Vec<PathBuf> = self.selected_files.clone();
-                        for file in files_to_add {
-                            self.add_file_entry(file, FileOperationType::Encrypt);
-                        }
-                        
-                        // Start encryption
-                        self.show_status("Starting encryption...");
+                        self.begin_encrypt();
                         self.encryption_workflow_complete = true;
                     } else {
-                        self.show_error("Please complete all previous steps");
+                        self.show_error(&tr("error-previous-steps"));
                     }
                 }
             } else {
                 // Progress section
-                ui.heading("Encryption Progress");
+                ui.heading(tr("encrypt-progress"));
                 
-                let progress = self.progress.lock().unwrap();
+                let progress = self.operations.file_progress();
                 
                 // Overall progress
-                let overall_progress = if progress.is_empty() {
-                    0.0
-                } else {
-                    progress.iter().sum::<f32>() / progress.len() as f32
-                };
+                let overall_progress = self.operations.overall_progress().unwrap_or(0.0);
                 
-                ui.label(format!("Overall Progress: {:.1}%", overall_progress * 100.0));
+                ui.label(tr_args("ops-overall-progress", &[("percent", locale::format_percent(overall_progress).into())]));
                 ui.add(ProgressBar::new(overall_progress)
                     .show_percentage()
                     .animate(true));
+                self.show_pause_control(ui);
+                
+                if let Some(remaining) = self.operations.time_remaining().filter(|_| !self.operations.is_paused()) {
+                    ui.label(tr_args("workflow-time-remaining", &[("duration", locale::format_duration(remaining).into())]));
+                }
                 
                 ui.add_space(10.0);
                 
                 // Individual file progress
-                if !self.selected_files.is_empty() && progress.len() == self.selected_files.len() {
+                let selected_files = self.operations.selected_files();
+                if !selected_files.is_empty() && progress.len() == selected_files.len() {
                     ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                        for (i, (file, &prog)) in self.selected_files.iter().zip(progress.iter()).enumerate() {
-                            ui.label(format!("File {}: {}", i + 1, file.file_name().unwrap_or_default().to_string_lossy()));
+                        for (i, (file, &prog)) in selected_files.iter().zip(progress.iter()).enumerate() {
+                            ui.label(tr_args("ops-file-number", &[("number", (i + 1).into()), ("file", file.file_name().unwrap_or_default().to_string_lossy().into())]));
                             ui.add(ProgressBar::new(prog)
                                 .show_percentage()
                                 .animate(true));
@@ -504,11 +525,11 @@ impl EncryptionWorkflowScreen for CrustyApp {
                 ui.add_space(10.0);
                 
                 // Results section
-                if !self.operation_results.is_empty() {
-                    ui.heading("Results");
+                if !self.operations.results().is_empty() {
+                    ui.heading(tr("ops-results"));
                     
                     ScrollArea::vertical().max_height(100.0).show(ui, |ui| {
-                        for result in &self.operation_results {
+                        for result in self.operations.results() {
                             if result.contains("Error") || result.contains("Failed") {
                                 ui.label(RichText::new(result).color(self.theme.error));
                             } else {
@@ -1,6 +1,11 @@
 use eframe::egui::{Color32, Visuals, Stroke, Rounding, Style};
 
+use std::collections::BTreeMap;
+
+use crate::settings::{SavedTheme, Theme};
+
 // Define color theme for the application
+#[derive(Clone)]
 pub struct AppTheme {
     pub background: Color32,
     pub accent: Color32,
@@ -17,6 +22,8 @@ pub struct AppTheme {
     pub tab_inactive: Color32,
     pub separator: Color32,
     pub header_bg: Color32,
+    // Theme chosen in the settings, which picks egui's light or dark visuals
+    pub theme: Theme,
 }
 
 impl Default for AppTheme {
@@ -37,11 +44,77 @@ impl Default for AppTheme {
             tab_inactive: Color32::from_rgb(200, 200, 200), // Light gray for inactive tab
             separator: Color32::from_rgb(220, 220, 220),  // Light gray for separators
             header_bg: Color32::from_rgb(240, 240, 240),  // Slightly darker background for headers
+            theme: Theme::System,
         }
     }
 }
 
 impl AppTheme {
+    // Get the colors of a theme chosen in the settings
+    pub fn for_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::System | Theme::Light => AppTheme { theme, ..Self::default() },
+            Theme::Dark => Self::dark(),
+        }
+    }
+    
+    // Dark colors, with the same accent and buttons as the light theme
+    pub fn dark() -> Self {
+        AppTheme {
+            background: Color32::from_rgb(32, 33, 36),    // Dark gray background
+            text_primary: Color32::from_rgb(230, 230, 230), // Off-white text
+            text_secondary: Color32::from_rgb(160, 160, 160), // Light gray text
+            error: Color32::from_rgb(240, 90, 90),        // Lighter red, readable on dark
+            success: Color32::from_rgb(90, 200, 90),      // Lighter green, readable on dark
+            tab_inactive: Color32::from_rgb(70, 70, 70),  // Dark gray for inactive tab
+            separator: Color32::from_rgb(60, 60, 60),     // Dark gray for separators
+            header_bg: Color32::from_rgb(45, 46, 50),     // Slightly lighter background for headers
+            theme: Theme::Dark,
+            ..Self::default()
+        }
+    }
+    
+    // Get the colors of a theme saved from the theme editor
+    pub fn from_saved(saved: &SavedTheme) -> Self {
+        let mut theme = Self::for_theme(if saved.dark { Theme::Dark } else { Theme::Light });
+        for (name, _, color) in theme.colors_mut() {
+            if let Some(saved_color) = saved.colors.get(name).and_then(|hex| color_from_hex(hex)) {
+                *color = saved_color;
+            }
+        }
+        theme
+    }
+    
+    // Save these colors under a name
+    pub fn to_saved(&self, name: &str) -> SavedTheme {
+        let dark = self.theme == Theme::Dark;
+        let colors: BTreeMap<String, String> = self.clone().colors_mut().into_iter()
+            .map(|(key, _, color)| (key.to_string(), color_to_hex(*color)))
+            .collect();
+        SavedTheme { name: name.to_string(), dark, colors }
+    }
+    
+    // Get every color with the name it is saved under and its label in the theme editor
+    pub fn colors_mut(&mut self) -> [(&'static str, &'static str, &mut Color32); 15] {
+        [
+            ("background", "Background", &mut self.background),
+            ("accent", "Accent", &mut self.accent),
+            ("text_primary", "Text", &mut self.text_primary),
+            ("text_secondary", "Secondary text", &mut self.text_secondary),
+            ("button_text", "Button text", &mut self.button_text),
+            ("button_normal", "Buttons", &mut self.button_normal),
+            ("button_hovered", "Hovered buttons", &mut self.button_hovered),
+            ("button_active", "Clicked buttons", &mut self.button_active),
+            ("button_selected", "Selected buttons", &mut self.button_selected),
+            ("error", "Errors", &mut self.error),
+            ("success", "Success", &mut self.success),
+            ("tab_active", "Active tab", &mut self.tab_active),
+            ("tab_inactive", "Inactive tabs", &mut self.tab_inactive),
+            ("separator", "Separators", &mut self.separator),
+            ("header_bg", "Header background", &mut self.header_bg),
+        ]
+    }
+    
     // Apply theme to egui context
     pub fn apply_to_context(&self, ctx: &eframe::egui::Context) {
         let mut style = (*ctx.style()).clone();
@@ -57,6 +130,13 @@ impl AppTheme {
         visuals.widgets.active.bg_fill = self.button_active;
         visuals.widgets.active.fg_stroke = Stroke::new(2.0, self.button_text);
         
+        // Follow the system's light or dark mode unless a theme was chosen
+        match self.theme {
+            Theme::System => {},
+            Theme::Light => style.visuals = Visuals::light(),
+            Theme::Dark => style.visuals = Visuals::dark(),
+        }
+        
         // Set button rounding
         style.visuals.widgets.noninteractive.rounding = Rounding::same(5.0);
         style.visuals.widgets.inactive.rounding = Rounding::same(5.0);
@@ -67,3 +147,48 @@ impl AppTheme {
         ctx.set_style(style);
     }
 }
+
+// Write a color as #RRGGBB
+fn color_to_hex(color: Color32) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.r(), color.g(), color.b())
+}
+
+// Read a color written as #RRGGBB
+fn color_from_hex(hex: &str) -> Option<Color32> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_theme() {
+        let mut theme = AppTheme::dark();
+        theme.accent = Color32::from_rgb(255, 255, 0);
+        let saved = theme.to_saved("High contrast");
+        assert!(saved.dark);
+        assert_eq!(saved.colors["accent"], "#FFFF00");
+
+        let loaded = AppTheme::from_saved(&saved);
+        assert_eq!(loaded.accent, theme.accent);
+        assert_eq!(loaded.background, theme.background);
+        assert_eq!(loaded.theme, Theme::Dark);
+
+        // Colors that are left out or cannot be read are those of the base theme
+        let saved = SavedTheme {
+            name: "Partial".to_string(),
+            dark: false,
+            colors: BTreeMap::from([("error".to_string(), "#C00".to_string()), ("success".to_string(), "#00AA00".to_string())]),
+        };
+        let loaded = AppTheme::from_saved(&saved);
+        assert_eq!(loaded.error, AppTheme::default().error);
+        assert_eq!(loaded.success, Color32::from_rgb(0, 0xAA, 0));
+        assert_eq!(loaded.theme, Theme::Light);
+    }
+}
@@ -1,5 +1,7 @@
-use eframe::egui::{Ui, Button, RichText, Rounding, Response};
+use std::path::Path;
+use eframe::egui::{Ui, Button, RichText, Rounding, Response, Align2, FontId};
 use crate::gui::theme::AppTheme;
+use crate::i18n::tr;
 
 /// Create a styled button with consistent appearance
 pub fn styled_button(ui: &mut Ui, text: &str, theme: &AppTheme, size: Option<[f32; 2]>) -> Response {
@@ -40,19 +42,57 @@ pub fn styled_error_button(ui: &mut Ui, text: &str, theme: &AppTheme, size: Opti
     }
 }
 
-/// Format a file size in human-readable format
-pub fn format_file_size(size_bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+/// Show secret content (a key share, its words, or its dictation groups) covered until
+/// the "Hold to Reveal" button under it is held down
+///
+/// # Arguments
+/// * `cover` - Whether to cover the content (false shows it as is, without the button)
+pub fn hold_to_reveal(ui: &mut Ui, theme: &AppTheme, cover: bool, add_contents: impl FnOnce(&mut Ui)) {
+    let content = ui.scope(add_contents).response.rect;
+    if !cover {
+        return;
+    }
     
-    if size_bytes >= GB {
-        format!("{:.2} GB", size_bytes as f64 / GB as f64)
-    } else if size_bytes >= MB {
-        format!("{:.2} MB", size_bytes as f64 / MB as f64)
-    } else if size_bytes >= KB {
-        format!("{:.2} KB", size_bytes as f64 / KB as f64)
-    } else {
-        format!("{} bytes", size_bytes)
+    let revealed = styled_button(ui, &tr("reveal-hold"), theme, Some([150.0, 24.0]))
+        .on_hover_text(tr("reveal-hold-hint"))
+        .is_pointer_button_down_on();
+    if !revealed {
+        ui.painter().rect_filled(content, Rounding::same(4.0), theme.button_normal);
+        ui.painter().text(content.center(), Align2::CENTER_CENTER, tr("reveal-hidden"), FontId::proportional(14.0), theme.button_text);
     }
 }
+
+/// Format a file size in human-readable format for the selected language
+pub fn format_file_size(size_bytes: u64) -> String {
+    crate::locale::format_size(size_bytes)
+}
+
+/// Open a file with its default app (best effort; failures are ignored)
+pub fn open_file(path: &Path) {
+    #[cfg(target_os = "windows")]
+    let _ = std::process::Command::new("cmd")
+        .args(["/C", "start", ""])
+        .arg(path)
+        .spawn();
+    
+    #[cfg(not(target_os = "windows"))]
+    open_folder(path);
+}
+
+/// Open a folder in the system file manager (best effort; failures are ignored)
+pub fn open_folder(folder: &Path) {
+    #[cfg(target_os = "windows")]
+    let _ = std::process::Command::new("explorer")
+        .arg(folder)
+        .spawn();
+    
+    #[cfg(target_os = "macos")]
+    let _ = std::process::Command::new("open")
+        .arg(folder)
+        .spawn();
+    
+    #[cfg(target_os = "linux")]
+    let _ = std::process::Command::new("xdg-open")
+        .arg(folder)
+        .spawn();
+}
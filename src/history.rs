@@ -0,0 +1,152 @@
+/// Operation history module.
+///
+/// This module provides functionality for:
+/// - Recording each file encrypted or decrypted: its path, the folder its output was
+///   saved in, and when it finished (and each file upgraded to the current format)
+/// - Saving the history (history.json) in the application data directory, so recent
+///   files are still listed after CRUSTy is restarted
+///
+/// Only paths and times are kept; never keys or file contents.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use serde::{Serialize, Deserialize};
+
+use crate::locale::STORED_TIMESTAMP_FORMAT;
+use crate::shared_files;
+
+/// Most files kept in the history (the oldest are dropped)
+const MAX_ENTRIES: usize = 100;
+
+/// Operation a file went through
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOperation {
+    Encrypt,
+    Decrypt,
+    /// Upgraded to the current format where it is (see `migration`)
+    Migrate,
+}
+
+/// A file that was encrypted or decrypted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub operation: HistoryOperation,
+    /// File that was processed
+    pub input: PathBuf,
+    /// Folder the output was saved in (None if it is not known)
+    pub output_folder: Option<PathBuf>,
+    /// When the file finished (`STORED_TIMESTAMP_FORMAT`)
+    pub finished_at: String,
+}
+
+impl HistoryEntry {
+    /// Create an entry for a file that finished now
+    pub fn new(operation: HistoryOperation, input: PathBuf, output_folder: Option<PathBuf>) -> Self {
+        HistoryEntry {
+            operation,
+            input,
+            output_folder,
+            finished_at: Local::now().format(STORED_TIMESTAMP_FORMAT).to_string(),
+        }
+    }
+}
+
+/// Files encrypted and decrypted in earlier operations, newest first
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct History {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Default location of the history
+    pub fn default_path() -> PathBuf {
+        let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("crusty");
+        path.push("history.json");
+        path
+    }
+
+    /// Load the history from the specified file
+    ///
+    /// # Returns
+    /// * `io::Result<History>` - The history, an empty history if the file does not
+    ///   exist, or an error if the file cannot be read or parsed
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(History::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid history: {}", e)))
+    }
+
+    /// Save the history to the specified file
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        shared_files::write_atomic(path, content)
+    }
+
+    /// Record a finished file
+    ///
+    /// A file processed again with the same operation moves to the top instead of
+    /// being listed twice.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        self.entries.retain(|earlier| earlier.input != entry.input || earlier.operation != entry.operation);
+        self.entries.insert(0, entry);
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    /// Forget every file
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record() {
+        let mut history = History::default();
+        history.record(HistoryEntry::new(HistoryOperation::Encrypt, PathBuf::from("a.txt"), None));
+        history.record(HistoryEntry::new(HistoryOperation::Decrypt, PathBuf::from("b.txt.encrypted"), None));
+        history.record(HistoryEntry::new(HistoryOperation::Encrypt, PathBuf::from("a.txt"), Some(PathBuf::from("out"))));
+
+        // The file encrypted again moved to the top, with its new output folder
+        let inputs: Vec<_> = history.entries.iter().map(|entry| entry.input.as_path()).collect();
+        assert_eq!(inputs, vec![Path::new("a.txt"), Path::new("b.txt.encrypted")]);
+        assert_eq!(history.entries[0].output_folder.as_deref(), Some(Path::new("out")));
+
+        // Only the newest files are kept
+        for i in 0..MAX_ENTRIES {
+            history.record(HistoryEntry::new(HistoryOperation::Encrypt, PathBuf::from(format!("{}.txt", i)), None));
+        }
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert_eq!(history.entries[0].input, PathBuf::from(format!("{}.txt", MAX_ENTRIES - 1)));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        assert!(History::load_from(&path).unwrap().entries.is_empty());
+
+        let mut history = History::default();
+        history.record(HistoryEntry::new(HistoryOperation::Decrypt, dir.path().join("report.txt.encrypted"), Some(dir.path().to_path_buf())));
+        history.save_to(&path).unwrap();
+
+        let loaded = History::load_from(&path).unwrap();
+        assert_eq!(loaded.entries, history.entries);
+
+        fs::write(&path, "not json").unwrap();
+        assert!(History::load_from(&path).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+/// Translation module.
+///
+/// This module provides functionality for:
+/// - Looking up the text of the UI in the selected language (see `locale::language`)
+///   from Fluent message files, `src/i18n/<language tag>.ftl`
+/// - Filling in the values of a message, e.g. the name of a key
+/// - Falling back to English for languages, and messages, not translated yet
+///
+/// English and German are translated. French and Spanish format numbers and dates for
+/// their language (see `locale`), and show the English text until their message files
+/// are added. Log files and other machine-readable output are never translated.
+use fluent::{FluentResource, FluentValue};
+use fluent::concurrent::FluentBundle;
+
+use crate::locale::{self, Language};
+
+/// English messages, used for every message missing from another language
+const ENGLISH: &str = include_str!("i18n/en-US.ftl");
+
+/// German messages
+const GERMAN: &str = include_str!("i18n/de-DE.ftl");
+
+/// Get the messages of a language, or None if it is not translated
+fn messages(language: Language) -> Option<&'static str> {
+    match language {
+        Language::EnglishUs | Language::EnglishUk => Some(ENGLISH),
+        Language::German => Some(GERMAN),
+        Language::French | Language::Spanish => None,
+    }
+}
+
+/// Build the bundle of a language's messages
+fn bundle(language: Language, source: &str) -> FluentBundle<FluentResource> {
+    let mut bundle = FluentBundle::new_concurrent(vec![
+        language.code().parse().expect("Language tags are valid"),
+    ]);
+    // Values are shown as they are, without Unicode isolation marks around them
+    bundle.set_use_isolating(false);
+
+    // Messages that cannot be parsed are left out, and fall back to English
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+    bundle.add_resource(resource).ok();
+    bundle
+}
+
+// Bundles of the translated languages, built the first time a message is looked up
+lazy_static::lazy_static! {
+    static ref BUNDLES: Vec<(Language, FluentBundle<FluentResource>)> = Language::ALL.iter()
+        .filter_map(|&language| messages(language).map(|source| (language, bundle(language, source))))
+        .collect();
+}
+
+/// Format a message from a language's bundle
+fn format(language: Language, id: &str, args: &[(&str, FluentValue)]) -> Option<String> {
+    let (_, bundle) = BUNDLES.iter().find(|(bundle_language, _)| *bundle_language == language)?;
+    let pattern = bundle.get_message(id)?.value()?;
+
+    let mut fluent_args = fluent::FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    let mut errors = Vec::new();
+    Some(bundle.format_pattern(pattern, Some(&fluent_args), &mut errors).into_owned())
+}
+
+/// Get the text of a message in a language
+///
+/// # Arguments
+/// * `id` - The message, e.g. `menu-file`
+/// * `args` - Values of the message, e.g. `("name", "Client Key".into())`
+///
+/// # Returns
+/// * `String` - The text in the language, in English if the language does not have the
+///   message, or the message ID if neither has it
+pub fn translate(language: Language, id: &str, args: &[(&str, FluentValue)]) -> String {
+    format(language, id, args)
+        .or_else(|| format(Language::EnglishUs, id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Get the text of a message in the selected language
+pub fn tr(id: &str) -> String {
+    translate(locale::language(), id, &[])
+}
+
+/// Get the text of a message with values in the selected language
+pub fn tr_args(id: &str, args: &[(&str, FluentValue)]) -> String {
+    translate(locale::language(), id, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// IDs of the messages in a message file
+    fn message_ids(source: &str) -> Vec<String> {
+        assert!(FluentResource::try_new(source.to_string()).is_ok(), "Message file parses");
+        source.lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| line.split_once(" =").map(|(id, _)| id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate(Language::EnglishUs, "menu-file", &[]), "File");
+        assert_eq!(translate(Language::German, "menu-file", &[]), "Datei");
+        assert_eq!(translate(Language::German, "keys-pin-selected", &[("name", "Client Key".into())]), "„Client Key“ an einen Ordner binden...");
+        assert_eq!(translate(Language::EnglishUs, "main-total-files", &[("count", 1.into())]), "Total: 1 file");
+        assert_eq!(translate(Language::German, "main-total-files", &[("count", 3.into())]), "Gesamt: 3 Dateien");
+
+        // Languages and messages not translated fall back to English, then to the ID
+        assert_eq!(translate(Language::French, "menu-file", &[]), "File");
+        assert_eq!(translate(Language::German, "no-such-message", &[]), "no-such-message");
+    }
+
+    /// Visit the source files in a folder and its subfolders
+    fn for_each_source(dir: &Path, visit: &mut dyn FnMut(&Path, &str)) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                for_each_source(&path, visit);
+            } else {
+                visit(&path, &fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+
+    /// Folders with the code that shows text in the UI
+    fn ui_source_dirs() -> Vec<std::path::PathBuf> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        vec![root.join("src/gui"), root.join("src/services")]
+    }
+
+    #[test]
+    fn test_message_files() {
+        let english = message_ids(ENGLISH);
+        let german = message_ids(GERMAN);
+        assert_eq!(german, english, "German has the English messages, in the same order");
+
+        // Every message the UI looks up is in the English file, including status and
+        // error messages chosen before they are looked up
+        for dir in ui_source_dirs() {
+            for_each_source(&dir, &mut |path, source| {
+                let mut ids: Vec<String> = source.split("tr(\"").skip(1).chain(source.split("tr_args(\"").skip(1))
+                    .map(|call| call.split('"').next().unwrap().to_string())
+                    .collect();
+                for prefix in ["status-", "error-"] {
+                    ids.extend(source.split(&format!("\"{}", prefix)).skip(1)
+                        .map(|rest| format!("{}{}", prefix, rest.split('"').next().unwrap()))
+                        .filter(|id| id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')));
+                }
+                for id in ids {
+                    assert!(english.contains(&id), "{} uses a missing message {}", path.display(), id);
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn test_ui_messages_translated() {
+        // Status and error messages are looked up in the message files, not written out
+        let untranslated = [
+            "show_status(\"", "show_error(\"", "show_status(&format!", "show_error(&format!",
+            "show_status(format!", "show_error(format!", "events.status(\"", "events.error(\"",
+            "events.status(format!", "events.error(format!",
+        ];
+        for dir in ui_source_dirs() {
+            for_each_source(&dir, &mut |path, source| {
+                for (number, line) in source.lines().enumerate() {
+                    let call = untranslated.iter().find(|call| line.contains(*call));
+                    assert!(call.is_none(), "{}:{} shows untranslated text: {}", path.display(), number + 1, line.trim());
+                }
+            });
+        }
+    }
+}
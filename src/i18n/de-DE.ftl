@@ -0,0 +1,1413 @@
+# German messages of the CRUSTy window. See src/i18n.rs.
+
+## Common
+
+button-remove = Entfernen
+button-add = Hinzufügen
+parity-none = Keine
+parity-even = Gerade
+parity-odd = Ungerade
+button-back = Zurück
+reveal-hold = Zum Anzeigen halten
+reveal-hidden = Verborgen
+reveal-hold-hint = Zeigt den Anteil, solange die Schaltfläche gedrückt ist
+
+## Menu bar
+
+menu-file = Datei
+menu-open = Öffnen
+menu-open-folder = Ordner öffnen...
+menu-cold-storage-export = Cold-Storage-Export...
+menu-cold-storage-restore = Cold-Storage-Wiederherstellung...
+menu-audit = Verschlüsselte Dateien prüfen...
+menu-export-catalog = Katalog exportieren...
+menu-benchmark = Backends vergleichen...
+menu-lock-keys = Schlüssel sperren
+menu-exit = Beenden
+menu-settings = Einstellungen
+menu-settings-screen = Design, Standardwerte und Leistung...
+menu-completion-sound = Signalton nach Abschluss
+menu-choose-sound = Tondatei wählen...
+menu-use-system-sound = Systemton verwenden
+menu-taskbar-progress = Fortschritt in der Taskleiste anzeigen
+menu-protect-shares = Schlüsselanteile bis zum Anzeigen verbergen
+menu-lock-on-session-lock = Schlüssel sperren, wenn der Computer gesperrt wird oder in den Ruhezustand geht
+menu-lock-after = Schlüssel sperren nach
+menu-shred-viewed-after = Angesehene Dateien vernichten nach
+menu-check-folders-every = Gesicherte Ordner prüfen alle
+menu-open-actions = Standardaktionen nach Dateiendung
+menu-open-actions-explained = Beim Öffnen einer verschlüsselten Datei aus „Zuletzt verwendet“ oder dem Dateimanager:
+menu-stop-without-progress = Vorgänge ohne Fortschritt abbrechen nach
+menu-low-memory = Sparmodus für wenig Arbeitsspeicher (64 MB pro Datei)
+menu-preflight-summary = Zusammenfassung vor jedem Stapel
+menu-batch-limits = Vor sehr großen Stapeln warnen
+menu-language = Sprache
+menu-system-language = Systemsprache
+menu-offline = Offline-Modus
+menu-network-proxy = Netzwerk-Proxy...
+menu-control-api = Lokale Steuerungs-API
+menu-copy-api-token = API-Token kopieren
+menu-help = Hilfe
+menu-help-f1 = Hilfe (F1)
+menu-about = Über
+menu-view-logs = Protokolle anzeigen
+menu-offline-badge = OFFLINE
+menu-sound-file = Ton: { $file }
+menu-sound-system = Ton: Systemstandard
+menu-batch-limits-over = Über
+menu-batch-limits-or = oder
+menu-control-api-port = Port
+menu-control-api-listening = Wartet auf 127.0.0.1:{ $port }
+
+## Help
+
+help-title = Hilfe
+
+## Action bar
+
+action-encrypt = Verschlüsseln
+action-decrypt = Entschlüsseln
+action-stop = Anhalten
+action-keys = Schlüssel
+action-advanced = Erweitert
+action-open = Öffnen
+
+## Main screen
+
+main-total-files =
+    Gesamt: { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    }
+main-no-key-selected = Kein Schlüssel ausgewählt
+main-fingerprint = Fingerabdruck: { $fingerprint }
+main-tab-recent-files = Zuletzt verwendet
+main-tab-secured-folders = Gesicherte Ordner
+main-processing-mode = Verarbeitung:
+main-single-file = Einzelne Datei
+main-multiple-files = Mehrere Dateien
+main-select-output-dir = Zielordner wählen
+main-selected-files = Ausgewählte Dateien
+main-clear-all = Alle entfernen
+main-output-dir = Zielordner
+main-current-key = Aktueller Schlüssel:
+main-select-key = Auswählen
+main-new-key = Neuer Schlüssel
+main-use-embedded = Eingebettetes System für kryptografische Vorgänge verwenden
+main-connection-type = Verbindungsart:
+main-serial = Seriell/UART
+main-device-id = Geräte-ID/Adresse:
+main-connection-settings = Verbindungseinstellungen
+main-usb-interface = Schnittstelle:
+main-usb-endpoint = Endpunkt:
+main-baud-rate = Baudrate:
+main-parity = Parität:
+main-host = Host:
+main-port = Port:
+main-use-tls = TLS verwenden
+main-device-certificate = Gerätezertifikat:
+main-no-certificate = Keines (bekannten Zertifizierungsstellen vertrauen)
+main-choose-certificate = Wählen...
+main-clear-certificate = Leeren
+main-device-certificate-hint = Nur dieses Zertifikat wird vom Gerät akzeptiert. Ohne Zertifikat muss das Zertifikat des Geräts von einer bekannten Zertifizierungsstelle signiert sein.
+main-test-connection = Verbindung testen
+main-testing-connection = Verbindung wird getestet...
+main-test-connection-hint = Prüft, ob das Gerät antwortet und was darauf läuft, bevor ein Vorgang darauf gestartet wird.
+main-device-firmware = Firmware { $version }, Antwort in { $milliseconds } ms
+main-device-algorithms = Algorithmen: { $algorithms }
+main-device-resources = Frei: { $memory } Speicher, { $slots } Schlüsselplätze, { $queue } Blöcke in der Warteschlange
+
+## Encrypt and decrypt screens
+
+ops-selected-count =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } ausgewählt
+ops-output-dir-is = Zielordner: { $dir }
+ops-unknown-key = Unbekannter Schlüssel
+encrypt-key-expired = Dieser Schlüssel ist am { $date } abgelaufen. Verschlüsseln Sie besser mit einem neueren Schlüssel.
+encrypt-key-pinned = ⚠ Der Schlüssel „{ $name }“ ist an { $folder } gebunden. Prüfen Sie, ob es der richtige Schlüssel ist.
+encrypt-low-memory-age = Sparmodus: Dateien über { $size } MB können nur im age-Format verschlüsselt werden
+ops-overall-progress = Gesamtfortschritt: { $percent }
+ops-file-number = Datei { $number }: { $file }
+encrypt-title = Dateien verschlüsseln
+ops-file-selection = Dateiauswahl
+ops-select-files = Dateien wählen
+ops-select-file = Datei wählen
+ops-select-folder = Ordner wählen
+ops-select-folder-hint = Alle Dateien eines Ordners und seiner Unterordner auswählen
+ops-batch-mode = Stapelmodus
+ops-no-files = Keine Dateien ausgewählt
+encrypt-archive-folder = Ordner in ein Archiv packen (.crusty)
+encrypt-archive-folder-hint = Eine verschlüsselte Datei mit dem ganzen Ordner speichern statt einer pro Datei
+ops-no-output-dir = Kein Zielordner ausgewählt
+encrypt-options = Verschlüsselungsoptionen
+encrypt-key = Schlüssel zum Verschlüsseln:
+ops-select-key = Schlüssel wählen
+ops-change-key = Schlüssel wechseln
+encrypt-hide-names = Dateinamen verbergen
+encrypt-parity = Parität für Archivmedien hinzufügen
+encrypt-digest = Prüfsumme zum Prüfen entschlüsselter Dateien speichern
+encrypt-manifest = Signiertes Manifest des Stapels schreiben
+encrypt-age-format = Im age-Format speichern
+encrypt-openpgp-format = Im OpenPGP-Format speichern (für GnuPG)
+encrypt-for-recipients = Für Empfänger verschlüsseln
+ops-use-hardware = Hardware-Verschlüsselung verwenden
+ops-serial = Seriell
+encrypt-progress = Fortschritt der Verschlüsselung
+ops-results = Ergebnisse
+ops-export-report = Bericht exportieren…
+ops-export-report-hint = Eingabe, Ausgabe, Status, Dauer, Größe und Schlüssel-Fingerabdruck jeder Datei als CSV oder JSON speichern
+encrypt-duplicates = Doppelte Dateien
+ops-dismiss = Schließen
+encrypt-duplicates-explained = Diese Dateien des letzten Stapels haben denselben Inhalt, jede Gruppe wurde also mehrfach verschlüsselt:
+encrypt-start = 🔒 Verschlüsseln
+encrypt-replace-originals = Stattdessen Originaldateien ersetzen
+encrypt-shred-originals = Originale vor dem Löschen überschreiben
+encrypt-replace-originals-explained = Verschlüsselte Dateien werden neben den Originalen gespeichert, die danach gelöscht werden.
+decrypt-manifest-written = { $manifest } (geschrieben am { $date }, Schlüssel { $fingerprint })
+decrypt-manifest-intact = unverändert
+decrypt-manifest-changed = geändert
+decrypt-manifest-missing = fehlt
+decrypt-viewed-file = { $file } (wird in { $minutes } Min. vernichtet)
+decrypt-title = Dateien entschlüsseln
+decrypt-select-files = Verschlüsselte Dateien wählen
+decrypt-select-file = Verschlüsselte Datei wählen
+decrypt-options = Entschlüsselungsoptionen
+decrypt-key = Schlüssel zum Entschlüsseln:
+decrypt-reason-required = Grund der Entschlüsselung (erforderlich):
+decrypt-reason = Grund der Entschlüsselung:
+decrypt-reason-hint = z. B. Aktenanfrage Nr. 1234
+decrypt-use-hardware = Hardware-Entschlüsselung verwenden
+decrypt-progress = Fortschritt der Entschlüsselung
+decrypt-manifest-check = Manifestprüfung
+decrypt-viewed-files = Angesehene Dateien
+decrypt-viewed-on-disk = Kein speicherbasierter Ordner gefunden; angesehene Dateien liegen bis zur Vernichtung auf dem Datenträger
+decrypt-viewed-done = Fertig
+decrypt-viewed-done-hint = Die entschlüsselte Kopie jetzt vernichten
+decrypt-start = 🔓 Entschlüsseln
+decrypt-view-securely = Sicher ansehen
+decrypt-verify-manifest = Mit Manifest abgleichen...
+
+## About
+
+about-title = Über CRUSTy
+about-name = CRUSTy - Cryptographic Rust Utility
+about-version = Version 1.0.0
+about-description = Beschreibung
+about-description-1 = CRUSTy ist eine sichere Anwendung zur Dateiverschlüsselung mit AES-256-GCM.
+about-description-2 = Sie bietet eine benutzerfreundliche Oberfläche zum Ver- und Entschlüsseln von Dateien.
+about-features = Funktionen:
+about-feature-files = • Einzelne Dateien ver- und entschlüsseln
+about-feature-batch = • Stapelverarbeitung mehrerer Dateien
+about-feature-keys = • Schlüsselverwaltung (Erzeugen, Speichern, Laden)
+about-feature-split-key = • Geteilte Schlüssel für mehr Sicherheit
+about-feature-recipients = • Empfängerbezogene Verschlüsselung
+about-feature-logging = • Protokollierung der Vorgänge
+about-feature-progress = • Fortschrittsanzeige
+about-feature-hardware = • Hardware-Verschlüsselung über eingebettete Geräte
+about-technical = Technische Details
+about-technical-rust = • Entwickelt mit Rust und eframe/egui für die Oberfläche
+about-technical-aes = • Verwendet AES-256-GCM für authentifizierte Verschlüsselung
+about-technical-hkdf = • Verwendet HKDF zur empfängerbezogenen Schlüsselableitung
+about-technical-backends = • Unterstützt software- und hardwarebasierte Verschlüsselung
+about-technical-progress = • Dateivorgänge werden mit Fortschrittsanzeige ausgeführt
+about-license = Lizenz
+about-license-mit = Diese Software steht unter der MIT-Lizenz.
+about-copyright = Copyright © 2025 CRUSTy Team
+
+## Locked keys
+
+locked-title = 🔒 Schlüssel gesperrt
+locked-explained = Ihre gespeicherten Schlüssel wurden gesperrt, und von CRUSTy in die Zwischenablage kopierter Text wurde gelöscht.
+locked-running = Bereits laufende Vorgänge werden fortgesetzt.
+locked-unlock = Schlüssel entsperren
+
+## Confirmations
+
+batch-warning-about-to =
+    Sie sind dabei, { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } mit insgesamt { $size } zu { $operation ->
+        [decrypt] entschlüsseln
+       *[encrypt] verschlüsseln
+    }.
+batch-warning-over-limit = Das liegt über der Warngrenze von { $size } GB oder { $count } Dateien.
+batch-warning-free-space = • Stellen Sie sicher, dass im Zielordner mindestens { $size } frei sind
+batch-warning-duration = • Der Vorgang dauert voraussichtlich etwa { $duration }
+batch-warning-title = Sehr großer Stapel
+batch-warning-before = Vor dem Start:
+batch-warning-memory = • Jede Datei wird vollständig in den Arbeitsspeicher gelesen; schließen Sie daher andere Programme, bevor Sie sehr große Dateien verarbeiten
+batch-warning-split = • Dateien werden nacheinander verarbeitet; teilen Sie den Auftrag eventuell in kleinere Stapel auf
+batch-warning-settings = Die Grenzen lassen sich in den Einstellungen ändern.
+batch-warning-start = Trotzdem starten
+button-cancel = Abbrechen
+key-pin-warning-mismatch = Der Schlüssel „{ $name }“ ist an { $folder } gebunden, ausgewählt ist aber „{ $selected }“.
+key-pin-warning-use-pinned = „{ $name }“ verwenden
+key-pin-warning-title = Anderer Schlüssel als gebunden
+key-pin-warning-explained = Dateien in diesem Ordner oder für diesen Ordner sollen mit dem gebundenen Schlüssel verschlüsselt werden. Die Empfänger können sie sonst eventuell nicht öffnen.
+key-pin-warning-logged = Trotzdem zu verschlüsseln wird protokolliert. Bindungen lassen sich auf dem Einstellungsbildschirm ändern.
+key-pin-warning-encrypt-anyway = Trotzdem verschlüsseln
+preflight-about-to =
+    Sie sind dabei, { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } mit insgesamt { $size } zu { $operation ->
+        [decrypt] entschlüsseln
+       *[encrypt] verschlüsseln
+    }, mit:
+preflight-note = Hinweis: { $note }
+preflight-title = Bereit zum Start
+preflight-since-last = Seit dem letzten Stapel:
+preflight-turn-off = Diese Zusammenfassung lässt sich in den Einstellungen abschalten.
+preflight-start = Starten
+
+## Settings screen
+
+settings-none = Keine
+settings-none-saved = Nichts gespeichert
+settings-theme-system = System
+settings-theme-light = Hell
+settings-theme-dark = Dunkel
+settings-chunk-size-tuned = An das Laufwerk angepasst
+settings-device-over = { $device } über { $connection ->
+    [usb] USB
+    [serial] serielle Schnittstelle
+   *[ethernet] Ethernet
+}
+settings-title = Einstellungen
+settings-explained = Werden beim Ändern gespeichert und bei jedem Start von CRUSTy verwendet. Weitere Einstellungen finden Sie im Menü „Einstellungen“.
+settings-appearance = Darstellung und Leistung
+settings-theme = Design:
+settings-chunk-size = Dateien lesen in Blöcken von:
+settings-chunk-size-hint = „Angepasst“ wählt die Größe nach der Lesegeschwindigkeit des Laufwerks. Verschlüsselte Dateien sind bei jeder Größe gleich
+settings-startup = Beim Start von CRUSTy
+settings-output-dir = Zielordner:
+settings-choose = Wählen...
+settings-clear = Leeren
+settings-file-selection = Dateiauswahl:
+settings-multiple-files = Mehrere Dateien
+settings-embedded-device = Eingebettetes Gerät:
+settings-save-device = Aktuelles Gerät speichern
+settings-save-device-hint = Das Gerät und die Verbindungseinstellungen vom Hauptbildschirm
+settings-use-device = Gerät für Vorgänge verwenden
+button-close = Schließen
+settings-theme-editor = Design-Editor
+settings-theme-editor-explained = Änderungen werden sofort angezeigt. Speichern Sie sie als Design, um sie zu behalten.
+settings-theme-name = Name:
+settings-theme-name-hint = z. B. Hoher Kontrast
+settings-save-theme = Design speichern
+settings-save-theme-hint = Ersetzt ein gespeichertes Design mit demselben Namen
+settings-discard-theme = Änderungen verwerfen
+settings-delete-theme = Gespeichertes Design löschen
+settings-key-pins = Schlüsselbindungen
+settings-key-pins-explained = Wer Dateien in einem gebundenen Ordner oder für ihn mit einem anderen Schlüssel verschlüsselt, wird zuerst um Bestätigung gebeten.
+keys-pin-selected = „{ $name }“ an einen Ordner binden...
+keys-pin-selected-key = Ausgewählten Schlüssel an einen Ordner binden...
+
+## Dashboard
+
+dashboard-file-missing = { $file }: fehlt
+dashboard-title = CRUSTy-Übersicht
+dashboard-subtitle = Sichere Dateiverschlüsselung mit AES-256-GCM
+dashboard-integrity-failed = ⚠ Integritätsprüfung eines gesicherten Ordners fehlgeschlagen
+dashboard-integrity-explained = Die Fehler stehen in den Protokollen. Stellen Sie die Dateien aus einer Sicherung wieder her, falls sie nicht absichtlich entfernt wurden.
+dashboard-encryption = Verschlüsselung
+dashboard-encryption-explained = Dateien mit AES-256-GCM verschlüsseln
+dashboard-encrypt = 🔒 Dateien verschlüsseln
+dashboard-decryption = Entschlüsselung
+dashboard-decryption-explained = Zuvor verschlüsselte Dateien entschlüsseln
+dashboard-decrypt = 🔓 Dateien entschlüsseln
+dashboard-main-screen = Zum Hauptbildschirm
+dashboard-integrity-report = { $folder }: { $failed ->
+    [one] { $failed } Datei
+   *[other] { $failed } Dateien
+} nicht bestanden, { $missing } fehlen
+dashboard-dismiss-hint = Fehlende Dateien werden nicht mehr gesucht; beschädigte Dateien meldet die nächste Prüfung erneut
+dashboard-history = Letzte Aktivität
+dashboard-history-empty = Hier stehen die Dateien, die Sie ver- oder entschlüsseln.
+dashboard-encrypt-again = Erneut verschlüsseln
+dashboard-decrypt-again = Erneut entschlüsseln
+dashboard-upgraded = Aktualisiert
+dashboard-again-hint = Diese Datei erneut auswählen, mit demselben Zielordner
+dashboard-open-output = Zielordner öffnen
+dashboard-clear-history = Verlauf löschen
+
+## Logs
+
+logs-read-error = Fehler beim Lesen der Protokolldatei: { $error }
+logs-no-file = Keine Protokolldatei gefunden.
+logs-title = Vorgangsprotokolle
+logs-location = Speicherort der Protokolldatei:
+logs-open-dir = Protokollordner öffnen
+logs-recent = Neueste Protokolle
+logs-refresh = Protokolle aktualisieren
+logs-clear = Protokolle löschen
+logs-compliance-report = Compliance-Bericht
+logs-filter-hint = Nach Vorgang, Datei oder Meldung filtern
+logs-failures-only = Nur Fehler
+logs-export = Protokolle exportieren...
+logs-export-hint = Die angezeigten Einträge als CSV, JSON oder Text speichern (nach der Dateiendung)
+
+## Smartcard PIN
+
+pin-prompt-enter = Geben Sie die PIN der Smartcard „{ $card }“ ein, um den Schlüssel „{ $name }“ zu verwenden.
+pin-prompt-incorrect =
+    Falsche PIN ({ $count ->
+        [one] { $count } Fehlversuch
+       *[other] { $count } Fehlversuche
+    })
+pin-prompt-title = Smartcard-PIN
+pin-prompt-final-try = Letzter Versuch: Eine weitere falsche PIN sperrt die Karte.
+pin-prompt-count-low = Auf dieser Karte wurde bereits eine falsche PIN eingegeben.
+pin-prompt-pin = PIN
+pin-prompt-unlock = Entsperren
+
+## Network proxy
+
+proxy-title = Netzwerk-Proxy
+proxy-explained = Der Proxy wird für Relay-Links und die zentrale Protokollierung verwendet.
+proxy-system = Proxy-Einstellungen des Systems verwenden
+proxy-manual = Manueller Proxy
+proxy-none = Kein Proxy
+proxy-address = Adresse:
+proxy-user = Benutzername:
+proxy-optional = Optional
+proxy-password = Passwort:
+proxy-unchanged = Unverändert
+proxy-clear-password = Gespeichertes Passwort entfernen
+button-save = Speichern
+
+## Audit
+
+audit-differs-at = Die entschlüsselten Daten weichen ab Byte { $offset } vom Original ab
+audit-bytes = { $count ->
+    [one] { $count } Byte
+   *[other] { $count } Bytes
+}
+audit-format-key = Format: { $format }    Schlüssel: { $fingerprint }
+audit-failed = Fehlgeschlagen: { $error }
+audit-title = Verschlüsselte Dateien prüfen
+audit-key = Schlüssel:
+audit-choose-key = Schlüssel wählen
+audit-compare = Mit Original vergleichen
+audit-compare-explained = Prüfen, ob eine verschlüsselte Datei genau zu ihrem Original entschlüsselt wird. Es wird nichts Entschlüsseltes geschrieben.
+audit-original-file = Originaldatei:
+audit-encrypted-file = Verschlüsselte Datei:
+audit-auditing = Wird geprüft...
+audit-start = Prüfen
+audit-match = ÜBEREINSTIMMUNG: Die verschlüsselte Datei enthält genau das Original
+audit-mismatch = ABWEICHUNG: Die verschlüsselte Datei enthält nicht das Original
+audit-original = Original
+audit-encrypted = Verschlüsselt
+audit-decrypted = Entschlüsselt
+audit-reproduced = Das erneute Verschlüsseln des Originals mit der Nonce der Datei ergibt genau die verschlüsselten Daten.
+audit-not-reproduced = Das erneute Verschlüsseln des Originals mit der Nonce der Datei ergibt nicht die verschlüsselten Daten.
+audit-cannot-reproduce = Dieses Format lässt sich nicht reproduzieren; nur die entschlüsselten Daten wurden verglichen.
+audit-save-report = Bericht speichern...
+audit-verify-only = Nur überprüfen
+audit-verify-explained = Prüfen, ob verschlüsselte Dateien, etwa Sicherungen, unversehrt sind und sich mit dem Schlüssel öffnen lassen. Es wird nichts Entschlüsseltes geschrieben.
+audit-add-files = Dateien hinzufügen...
+audit-add-folder = Ordner hinzufügen...
+audit-no-files = Keine Dateien hinzugefügt
+audit-authentic = Echt
+audit-verify = Überprüfen
+
+## Catalog
+
+catalog-file-count = { $count ->
+    [one] { $count } Datei
+   *[other] { $count } Dateien
+}
+catalog-id = ID
+catalog-name = Name
+catalog-size = Größe
+catalog-modified = Geändert
+catalog-key = Schlüssel
+catalog-title = Katalog exportieren
+catalog-explained = Die verschlüsselten Dateien eines Ordners auflisten, um sie mit Mitarbeitenden zu teilen, damit diese bestimmte Dateien anfragen können. Es wird nichts Entschlüsseltes geschrieben.
+catalog-folder = Ordner:
+catalog-names = Namen:
+catalog-make = Katalog erstellen
+catalog-save = Katalog speichern...
+catalog-locations-not-saved = Der Ordner und die Speicherorte der Dateien werden nur hier angezeigt; sie werden nicht im Katalog gespeichert.
+catalog-no-saved-key = Kein gespeicherter Schlüssel
+catalog-find-by-id = Angefragte Datei nach ID suchen:
+catalog-show-in-folder = Im Ordner anzeigen
+catalog-no-such-id = Keine Datei hat diese ID
+
+## Benchmark
+
+benchmark-title = Backends vergleichen
+benchmark-explained = Verschlüsselt Zufallsdaten verschiedener Größen per Software und mit dem eingebetteten Gerät, um zu sehen, was für Ihre Dateien schneller ist.
+benchmark-sizes = Puffergrößen:
+benchmark-include-device = Eingebettetes Gerät einbeziehen
+benchmark-device = { $device } ({ $connection }), mit den Einstellungen des Hauptbildschirms
+benchmark-no-device = Richten Sie das Gerät zuerst auf dem Hauptbildschirm ein
+benchmark-run = Vergleich starten
+benchmark-running = Vergleich läuft...
+benchmark-stop = Anhalten
+benchmark-size = Größe
+benchmark-software = Software
+benchmark-hardware = Gerät
+benchmark-latency = Latenz
+benchmark-faster = Schneller
+
+## Cold-storage export
+
+cold-storage-shares-of = { $needed } von { $total }
+cold-storage-wrote = { $folder } geschrieben
+cold-storage-key-fingerprint = Fingerabdruck des Schlüssels: { $fingerprint }
+cold-storage-title = Cold-Storage-Export
+button-previous = ← Zurück
+button-next = Weiter →
+cold-storage-step-folder = Schritt 1: Ordner wählen
+cold-storage-folder-explained = Der Ordner und seine Unterordner werden in ein verschlüsseltes Archiv gepackt, das in Teile aufgeteilt wird.
+cold-storage-select-folder = Ordner wählen...
+cold-storage-no-folder = Kein Ordner ausgewählt
+cold-storage-label = Bezeichnung:
+cold-storage-label-hint = z. B. Fotos 2020
+cold-storage-label-explained = Die Bezeichnung benennt den Ordner und die Teildateien des Satzes; schreiben Sie sie auf jede Disc.
+cold-storage-write-to = Satz schreiben nach...
+cold-storage-step-media = Schritt 2: Medium wählen
+cold-storage-media-explained = Jeder Teil passt mit seiner Parität und dem Manifest auf eine Disc dieser Größe.
+cold-storage-custom-size = Eigene Größe
+cold-storage-parity-explained = Die Parität auf jedem Teil repariert Kratzer und unlesbare Sektoren; wählen Sie für lange Lagerung archivtaugliche Discs.
+cold-storage-step-shares = Schritt 3: Wiederherstellungsanteile
+cold-storage-shares-explained = Der Satz wird mit einem eigenen neuen Schlüssel verschlüsselt, der nur als Anteile auf dem Wiederherstellungsblatt aufbewahrt wird.
+cold-storage-shares-custodians = Geben Sie die Anteile verschiedenen Verwahrern oder bewahren Sie sie an verschiedenen Orten auf.
+cold-storage-shares-needed = Zur Wiederherstellung nötige Anteile:
+cold-storage-shares-printed = Gedruckte Anteile:
+cold-storage-shares-too-few = Es müssen mindestens so viele Anteile gedruckt werden, wie nötig sind
+cold-storage-step-write = Schritt 4: Satz schreiben
+cold-storage-media = Medium:
+cold-storage-recovery-shares = Wiederherstellungsanteile:
+cold-storage-volume = Teil
+cold-storage-burn = 1. MANIFEST.txt drucken und jeden „Volume“-Ordner auf eine eigene Disc brennen.
+cold-storage-hand-out = 2. RECOVERY-SHEET.txt drucken, die Anteile verteilen und das Blatt danach löschen.
+cold-storage-open-set = Ordner des Satzes öffnen
+cold-storage-delete-sheet = Wiederherstellungsblatt löschen
+cold-storage-start-another = Weiteren Satz beginnen
+cold-storage-sheet-warning = Das Wiederherstellungsblatt enthält den Schlüssel des Satzes: Brennen Sie es nicht mit den Teilen.
+cold-storage-write = Cold-Storage-Satz schreiben
+
+## Cold-storage restore
+
+restore-folder-files = { $folder } ({ $count ->
+    [one] { $count } Datei
+   *[other] { $count } Dateien
+})
+restore-enter-shares = Geben Sie { $count } Anteile vom Wiederherstellungsblatt ein (Text oder Wortfolge). Mehrere in ein Feld eingefügte Anteile werden auf eigene Felder verteilt.
+restore-share-number = Anteil { $number }:
+restore-restored = { $folder } wiederhergestellt
+restore-files-restored = { $restored } von { $total } wiederhergestellt
+restore-archive-matches = { $size }, SHA-256 stimmt mit dem Manifest überein
+restore-key-matches = { $fingerprint } stimmt mit dem Manifest überein
+restore-title = Cold-Storage-Wiederherstellung
+restore-step-find = Schritt 1: Teile finden
+restore-find-explained = Öffnen Sie das Manifest von einer beliebigen Disc des Satzes. Die Teile werden daneben und in den unten hinzugefügten Ordnern gesucht.
+restore-open-manifest = Manifest öffnen...
+restore-no-manifest = Kein Manifest geöffnet
+restore-set = Satz:
+restore-written = Geschrieben:
+restore-folders-searched = Durchsuchte Ordner (mit Unterordnern):
+restore-add-disc = Disc-Ordner hinzufügen...
+restore-not-found = Nicht gefunden
+restore-step-check = Schritt 2: Teile prüfen
+restore-check-explained = Jeder Teil wird gelesen, bei Bedarf mit seiner Parität repariert und mit dem Manifest verglichen.
+restore-add-missing = Fügen Sie die fehlenden Discs im vorigen Schritt hinzu oder kopieren Sie einen guten Teil aus einer anderen Kopie des Satzes.
+restore-check = Teile prüfen
+restore-step-key = Schritt 3: Schlüssel wiederherstellen
+restore-key-rebuilt = Der Schlüssel des Satzes wurde wiederhergestellt und stimmt mit dem Manifest überein.
+restore-enter-passphrase = Der Satz wurde mit einem Passphrasen-Schlüssel verschlüsselt. Geben Sie die Passphrase ein:
+restore-share-hint = Anteil hier eingeben oder einfügen...
+restore-add-share-field = Anteilsfeld hinzufügen
+restore-rebuild-key = Schlüssel wiederherstellen
+restore-step-restore = Schritt 4: Ordner wiederherstellen
+restore-files = Dateien:
+restore-archive = Archiv:
+restore-repaired = Einige Teile wurden repariert: Ihre Discs altern. Schreiben Sie den Satz auf neue Medien.
+restore-open-folder = Wiederhergestellten Ordner öffnen
+restore-another = Weiteren Satz wiederherstellen
+restore-to = Wiederherstellen nach...
+restore-start = Satz wiederherstellen
+
+## Key agreement
+
+agreement-waiting = Warte auf die Verbindung des Partners an Port { $port }...
+agreement-connecting = Verbinde mit { $address }...
+agreement-partner-computer = Computer des Partners: { $address }
+agreement-recognised = Erkannt als { $name } (geprüft am { $date })
+agreement-address-hint = Host oder Host:Port (Standard { $port })
+agreement-saved-partner = { $name } (geprüft am { $date })
+agreement-title = Schlüsselvereinbarung mit Partner
+agreement-about = Über die Schlüsselvereinbarung
+agreement-about-1 = Zwei CRUSTy-Computer können direkt einen Transferschlüssel vereinbaren, ohne Anteile zu versenden.
+agreement-about-2 = Ein Computer wartet auf den Partner; der andere verbindet sich mit ihm.
+agreement-about-3 = Beide zeigen dann denselben sechsstelligen Code: Vergleichen Sie ihn am Telefon, bevor Sie den Schlüssel speichern.
+agreement-about-4 = Ein nach einem Vergleich gespeicherter Partner wird beim nächsten Mal erkannt und braucht keinen Code.
+agreement-offline = Schlüsselvereinbarung ist im Offline-Modus nicht verfügbar
+agreement-agreed-key = Vereinbarter Schlüssel
+agreement-save-key = Schlüssel speichern
+agreement-read-code = Lesen Sie dem Partner diesen Code vor. Sein Computer muss denselben Code zeigen:
+agreement-partner-name = Name des Partners:
+agreement-partner-name-hint = z. B. Zweigstelle
+agreement-codes-match = Codes stimmen überein – Schlüssel speichern
+agreement-codes-differ = Codes weichen ab
+agreement-wait = Auf den Partner warten
+agreement-firewall = Der Partner verbindet sich mit Adresse und Port dieses Computers; lassen Sie die Verbindung in der Firewall zu.
+agreement-wait-button = Auf Partner warten
+agreement-connect-heading = Mit dem Partner verbinden
+agreement-partner-address = Adresse des Partners:
+agreement-connect = Verbinden
+agreement-saved-partners = Gespeicherte Partner
+agreement-no-partners = Noch keine Partner gespeichert
+agreement-forget = Vergessen
+agreement-forget-hint = Bei der nächsten Vereinbarung die Codes erneut vergleichen
+agreement-back = Zurück zur Schlüsselverwaltung
+
+## Key rotation
+
+rotation-outcome = { $rotated } neu verschlüsselt, { $already } verwendeten bereits den neuen Schlüssel, { $failed } fehlgeschlagen
+rotation-old-key-is = Alter Schlüssel: { $fingerprint }
+rotation-new-key-is = Neuer Schlüssel: { $fingerprint }
+rotation-will-rotate = { $count ->
+    [one] { $count } Datei wird
+   *[other] { $count } Dateien werden
+} im Arbeitsspeicher mit dem alten Schlüssel entschlüsselt und mit dem neuen Schlüssel verschlüsselt.
+rotation-title = Schlüssel wechseln
+rotation-step-files = Schritt 1: Dateien wählen
+rotation-files-explained = Fügen Sie die .encrypted-Dateien und .crusty-Archive hinzu, die auf den neuen Schlüssel umgestellt werden sollen. Jede Datei wird an ihrem Ort ersetzt.
+rotation-step-keys = Schritt 2: Schlüssel wählen
+rotation-old-key = Alter Schlüssel:
+rotation-new-key = Neuer Schlüssel:
+rotation-generate = Neuen Schlüssel erzeugen
+rotation-new-key-name = Name des neuen Schlüssels:
+rotation-new-key-name-hint = z. B. Finanzen 2026
+rotation-generation-disabled = Das Erzeugen von Schlüsseln ist per Richtlinie deaktiviert; wählen Sie einen gespeicherten Schlüssel als neuen Schlüssel.
+rotation-keys-same = Der neue Schlüssel muss sich vom alten unterscheiden.
+rotation-generated-on-start = Der neue Schlüssel wird beim Start des Wechsels erzeugt und gespeichert. Sichern Sie ihn, bevor Sie den alten Schlüssel ausmustern.
+rotation-step-reencrypt = Schritt 3: Dateien neu verschlüsseln
+rotation-complete = Alle Dateien verwenden jetzt den neuen Schlüssel. Sobald auch die mit dem alten Schlüssel erstellten Sicherungen umgestellt sind, kann der alte Schlüssel entfernt werden.
+rotation-failed-keep-old = Die fehlgeschlagenen Dateien verwenden noch den alten Schlüssel: Behalten Sie ihn, bis sie neu verschlüsselt sind.
+rotation-retry = Erneut versuchen
+rotation-more = Weitere Dateien umstellen
+rotation-checked-first = Jede Datei wird erst ersetzt, nachdem sie mit dem neuen Schlüssel geprüft wurde; es wird keine entschlüsselte Kopie geschrieben.
+rotation-start = Schlüssel wechseln
+
+## Secured folders
+
+folders-no-key = kein Schlüssel ausgewählt
+folders-current-key = Ordner ohne eigenen Schlüssel werden mit dem aktuellen Schlüssel ({ $name }) gesperrt. Gesperrte Ordner werden mit dem Schlüssel entsperrt, mit dem sie gesperrt wurden.
+folders-partly-locked = Teilweise gesperrt: { $locked } von { $total } Dateien verschlüsselt. Sperren oder entsperren Sie erneut, um abzuschließen.
+folders-add = Ordner hinzufügen
+folders-refresh = Aktualisieren
+folders-check = Integrität prüfen
+folders-check-hint = Die Dateien der gesperrten Ordner mit ihren Schlüsseln prüfen und nach fehlenden Dateien suchen
+folders-none = Keine gesicherten Ordner. Fügen Sie einen Ordner hinzu, um seine Dateien verschlüsselt zu halten.
+folders-locked = Gesperrt
+folders-unlocked = Entsperrt
+folders-empty = Keine Dateien
+folders-missing = Ordner nicht gefunden
+folders-lock = Sperren
+folders-lock-all = Alle sperren
+folders-unlock-all = Alle entsperren
+folders-lock-all-hint = Alle nicht gesperrten Ordner nacheinander sperren
+folders-unlock-all-hint = Alle gesperrten Ordner nacheinander entsperren
+folders-key = Schlüssel:
+folders-key-current = Aktueller Schlüssel
+folders-key-missing = Schlüssel nicht gespeichert
+folders-key-hint = Diesen Ordner immer mit diesem Schlüssel sperren, egal welcher Schlüssel ausgewählt ist
+folders-queued = Wartet auf die anderen Ordner…
+
+## Watched folders
+
+watch-heading = Überwachte Ordner
+watch-add = Ordner überwachen…
+watch-add-hint = Jede neue Datei in einem Ordner mit dem aktuellen Schlüssel in einen anderen Ordner verschlüsseln
+watch-none = Keine überwachten Ordner. Neue Dateien in einem überwachten Ordner werden automatisch verschlüsselt, solange CRUSTy läuft.
+watch-paused = Die Schlüssel sind gesperrt: Die Ordner werden erst nach dem Entsperren überwacht.
+watch-key = Schlüssel: { $name }
+watch-activity = Letzte Aktivität
+watch-encrypted = { $time }: { $file } verschlüsselt nach { $output }
+watch-failed = { $time }: { $file }: { $error }
+
+## Split keys
+
+split-title = Verwaltung geteilter Schlüssel
+split-about = Über die Verschlüsselung mit geteilten Schlüsseln
+split-about-1 = Bei geteilten Schlüsseln wird Ihr Schlüssel in mehrere Anteile aufgeteilt.
+split-about-2 = Zum Wiederherstellen des Schlüssels ist eine Mindestzahl an Anteilen nötig.
+split-about-3 = Das erhöht die Sicherheit durch Freigabe durch mehrere Personen.
+split-scheme = CRUSTy verwendet ein 2-aus-3-Verfahren:
+split-primary = • Primärer Anteil: im Anmeldeinformationsspeicher des Betriebssystems
+split-secondary = • Sekundärer Anteil: als Datei an einem Ort Ihrer Wahl
+split-recovery = • Wiederherstellungsanteil: als QR-Code zum Drucken oder Speichern
+split-any-two = Zum Entschlüsseln Ihrer Dateien brauchen Sie zwei beliebige dieser drei Anteile.
+split-create = Geteilten Schlüssel erstellen
+split-select-key-first = Wählen oder erstellen Sie zuerst einen Schlüssel
+split-custodians = Verwahrer der Anteile
+split-share = Anteil
+split-custodian = Verwahrer
+split-contact = Kontakt
+split-storage = Aufbewahrungsort
+split-save-custodians = Verwahrer speichern
+split-export-report = Zeremoniebericht exportieren
+split-reconstruct = Schlüssel aus Anteilen wiederherstellen
+split-reconstruct-need = Zum Wiederherstellen Ihres Schlüssels brauchen Sie:
+split-reconstruct-primary = 1. Ihren primären Anteil (wird automatisch aus dem Anmeldeinformationsspeicher geholt)
+split-reconstruct-other = 2. Entweder die Datei mit dem sekundären Anteil ODER den QR-Code des Wiederherstellungsanteils
+split-select-secondary = Datei mit sekundärem Anteil wählen
+split-scan-qr = QR-Code des Wiederherstellungsanteils einlesen
+
+## Transfer
+
+transfer-key-fingerprint = Fingerabdruck des Schlüssels: { $fingerprint } (der Empfänger sieht denselben Fingerabdruck, sobald der Schlüssel wiederhergestellt ist)
+transfer-threshold = Schwelle: { $needed } von { $total } Anteilen nötig
+transfer-bound-to = Gebunden an { $binding }: Senden Sie mindestens einen Anteil, der mit seiner Siegelzeile gespeichert, kopiert oder weitergeleitet wurde.
+transfer-share-number = Anteil { $number }
+transfer-hide-mnemonic = Wortfolge ausblenden
+transfer-view-mnemonic = Als Wortfolge anzeigen
+transfer-hide-dictation = Diktat ausblenden
+transfer-read-out = Am Telefon vorlesen
+transfer-shares-needed = { $count } Anteile sind nötig. Mehrere in ein Feld eingefügte Anteile werden auf eigene Felder verteilt.
+transfer-sealed = ✓ Versiegelte Anteile des Transferpakets { $package } ({ $needed } von { $total } Anteilen nötig)
+transfer-groups-checked = { $checked } von { $total } Gruppen geprüft
+transfer-word-number = Wort { $number }
+transfer-words = { $words } von { $total } Wörtern
+transfer-title = Transfer vorbereiten
+transfer-about = Über den sicheren Transfer
+transfer-about-1 = Diese Funktion hilft Ihnen, verschlüsselte Dateien sicher an andere zu übertragen.
+transfer-about-2 = Sie erstellt einen eigenen Transferschlüssel, der in mehrere Anteile aufgeteilt wird.
+transfer-about-3 = Zur Sicherheit senden Sie die Anteile über verschiedene Kanäle.
+transfer-process = So funktioniert es:
+transfer-process-1 = 1. Datei zum Verschlüsseln für den Transfer wählen
+transfer-process-2 = 2. Transferpaket mit mehreren Schlüsselanteilen erstellen
+transfer-process-3 = 3. Verschlüsselte Datei über einen Kanal senden
+transfer-process-4 = 4. Schlüsselanteile über andere Kanäle senden
+transfer-process-5 = 5. Der Empfänger braucht zum Entschlüsseln die Datei und genug Anteile
+transfer-about-4 = Das erhöht die Sicherheit bei Übertragungen über getrennte Kanäle.
+transfer-create = Transferpaket erstellen
+transfer-bind = Paket an seinen Empfänger binden:
+transfer-not-bound = Nicht gebunden
+transfer-passphrase = Passphrase
+transfer-identity = Maschinenidentität
+transfer-passphrase-label = Passphrase:
+transfer-passphrase-channel = Vereinbaren Sie die Passphrase mit dem Empfänger über einen weiteren Kanal.
+transfer-identity-label = Maschinenidentität des Empfängers:
+transfer-identity-hint = Base64, vom Bildschirm „Schlüsselverwaltung“ des Empfängers
+transfer-shares = Transferanteile
+transfer-save-share = Anteil speichern
+transfer-dictation-hint = Den Anteil als nummerierte Gruppen mit je einem Prüfzeichen anzeigen
+transfer-send-relay = Über Relay senden
+transfer-offline = Im Offline-Modus nicht verfügbar
+transfer-share-error = Fehler beim Abrufen des Anteils
+transfer-one-time-link = Einmal-Link (über einen anderen Kanal als die Datei senden):
+transfer-copy = Kopieren
+transfer-receive-title = Transfer empfangen
+transfer-receive-about = Über den Empfang von Transfers
+transfer-receive-about-1 = Diese Funktion hilft Ihnen, an Sie gesendete Dateien zu entschlüsseln.
+transfer-receive-about-2 = Sie müssen die erhaltenen Schlüsselanteile eingeben.
+transfer-receive-about-3 = Sobald Sie genug Anteile haben, können Sie den Schlüssel wiederherstellen und die Datei entschlüsseln.
+transfer-receive-process-1 = 1. Erhaltene Schlüsselanteile eingeben
+transfer-receive-process-2 = 2. Schlüssel wiederherstellen
+transfer-receive-process-3 = 3. Erhaltene Datei mit dem Schlüssel entschlüsseln
+transfer-enter-shares = Schlüsselanteile eingeben
+transfer-share-hint = Schlüsselanteil hier eingeben oder einfügen...
+transfer-dictation = Anteil per Diktat eingeben
+transfer-dictation-explained = Tippen Sie jede Gruppe beim Vorlesen ein: ihre Zeichen, dann ihr Prüfzeichen.
+transfer-use-dictated = Diktierten Anteil verwenden
+transfer-load-share = Anteil aus Datei laden
+transfer-import-scan = Anteil aus Scan importieren...
+transfer-import-scan-hint = Einen Anteil aus einem Foto oder Scan seiner gedruckten Karte lesen
+transfer-relay-link = Relay-Link:
+transfer-relay-link-hint = Einmal-Link einfügen...
+transfer-fetch-share = Anteil abrufen
+transfer-bound-passphrase = Dieses Paket ist an eine Passphrase gebunden. Geben Sie die mit dem Absender vereinbarte Passphrase ein, um den Schlüssel zu entpacken:
+transfer-bound-identity = Dieses Paket ist an eine Maschinenidentität gebunden. Der Schlüssel wird mit der Identität dieses Computers entpackt.
+transfer-reconstruct = Schlüssel wiederherstellen
+transfer-decrypt-received = Empfangene Datei entschlüsseln
+transfer-decrypt-received-explained = Wählen Sie die erhaltene verschlüsselte Datei. Sie wird mit dem Transferschlüssel neben sich entschlüsselt, und ihr Ordner öffnet sich, wenn sie fertig ist.
+transfer-select-received = Verschlüsselte Datei zum Entschlüsseln wählen...
+transfer-valid-phrase = ✓ Gültige Anteils-Wortfolge
+
+## Encryption workflow
+
+workflow-files-to-encrypt =
+    Zu verschlüsselnde Dateien: { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    }
+workflow-encryption-key = Schlüssel: { $name }
+workflow-recipients = Empfänger: { $recipients }
+workflow-backend =
+    Verarbeitung: { $backend ->
+        [hardware] Hardware
+       *[software] Software
+    }
+workflow-estimated-time = Geschätzte Dauer: etwa { $duration }
+workflow-time-remaining = Verbleibende Zeit: etwa { $duration }
+workflow-title = Verschlüsselungsassistent
+workflow-finish = Fertigstellen
+workflow-step-files = Schritt 1: Dateien wählen
+workflow-step-key = Schritt 2: Schlüssel wählen
+workflow-create-key = Neuen Schlüssel erstellen
+workflow-key-name = Schlüsselname:
+workflow-key-name-hint = Namen für den neuen Schlüssel eingeben
+workflow-generate-key = Schlüssel erzeugen
+workflow-select-existing = Vorhandenen Schlüssel wählen
+workflow-no-saved-keys = Keine gespeicherten Schlüssel vorhanden
+workflow-select-a-key = Schlüssel wählen
+workflow-load-key = Schlüssel aus Datei laden
+workflow-step-options = Schritt 3: Verschlüsselungsoptionen
+workflow-recipient-options = Empfängeroptionen
+workflow-file-names = Dateinamen
+workflow-random-names = Dateien werden unter zufälligen Namen gespeichert; die ursprünglichen Namen werden beim Entschlüsseln wiederhergestellt.
+workflow-backend-heading = Verschlüsselungsverfahren
+workflow-device-id = Geräte-ID:
+workflow-hardware-explained = Bei der Hardware-Verschlüsselung übernimmt ein eigenes Gerät die kryptografischen Vorgänge.
+workflow-software-explained = Bei der Software-Verschlüsselung übernimmt der Prozessor Ihres Computers die kryptografischen Vorgänge.
+workflow-step-execute = Schritt 4: Verschlüsselung ausführen
+workflow-summary = Zusammenfassung
+workflow-replaced = Ausgabe: Die Originaldateien werden ersetzt
+workflow-estimate-unknown = Geschätzte Dauer: noch unbekannt (keine früheren Vorgänge mit diesem Verfahren)
+workflow-start = 🔒 Verschlüsselung starten
+
+## Key management
+
+keys-passphrase-hint = Mindestens { $count } Zeichen
+keys-kept-in = Ihre Schlüssel werden verschlüsselt in { $path } aufbewahrt
+keys-expired-on = { $date } (abgelaufen)
+keys-backups-on = Die gespeicherten Schlüssel werden nach jeder Änderung gesichert (die letzten { $count } Sicherungen werden behalten).
+keys-change-passphrase = Passphrase ändern
+keys-turn-on-backups = Sicherungen einschalten
+keys-backup-number = { $number ->
+    [1] Sicherung 1 (neueste)
+   *[other] Sicherung { $number }
+}
+keys-verify-every = Verwahrer sollten ihre Anteile alle { $days } Tage überprüfen.
+keys-never = Nie
+keys-protection-no-tpm = Schutz der Identität: { $protection } (kein TPM gefunden)
+keys-protection = Schutz der Identität: { $protection }
+keys-title = Schlüsselverwaltung
+keys-or-passphrase = Oder den Schlüssel aus einer Passphrase ableiten (keine Schlüsseldatei nötig):
+keys-confirm = Bestätigen:
+keys-derive = Schlüssel ableiten
+keys-derive-for-file = Schlüssel für Datei ableiten...
+keys-derive-for-file-hint = Den Schlüssel einer mit Passphrasen-Schlüssel verschlüsselten Datei ableiten (die Bestätigung ist nicht nötig)
+keys-disabled = Die Schlüsselverwaltung ist per Richtlinie deaktiviert. Nur vorab bereitgestellte Schlüssel sind verfügbar.
+keys-saved = Gespeicherte Schlüssel
+keys-not-saved = Schlüssel werden nicht gespeichert: Sie bleiben nur bis zum Schließen von CRUSTy erhalten
+keys-none-saved = Keine gespeicherten Schlüssel. Erstellen Sie einen neuen Schlüssel oder laden Sie einen aus einer Datei.
+keys-key-name = Schlüsselname
+keys-fingerprint = Fingerabdruck
+keys-fingerprint-hint = Vergleichen Sie Fingerabdrücke, um zu prüfen, dass Sie und jemand anderes denselben Schlüssel haben
+keys-created = Erstellt
+keys-expires = Läuft ab
+keys-last-used = Zuletzt verwendet
+keys-status = Status
+keys-actions = Aktionen
+keys-provenance-not-recorded = Herkunft nicht erfasst
+keys-unknown = Unbekannt
+keys-current = Aktuell
+keys-credential-store = Anmeldeinformationsspeicher
+keys-saved-status = Gespeichert
+keys-delete = Löschen
+keys-expiry = Ablauf des Schlüssels
+keys-expires-on = Läuft ab am:
+keys-expires-on-hint = JJJJ-MM-TT (leer: nie)
+keys-set-expiry = Ablauf festlegen
+keys-expired-still-decrypt = Abgelaufene Schlüssel können weiterhin entschlüsseln; CRUSTy warnt, wenn einer zum Verschlüsseln gewählt wird.
+keys-credential-store-explained = Markierte Schlüssel werden im Anmeldeinformationsspeicher des Betriebssystems aufbewahrt und nie in Dateien geschrieben; die gespeicherten Schlüssel verweisen nur per Name auf sie.
+keys-provenance = Herkunft des Schlüssels
+keys-provenance-old = Herkunft nicht erfasst (gespeichert, bevor die Herkunft aufgezeichnet wurde)
+keys-import-scan = Schlüssel aus Scan importieren...
+keys-import-scan-hint = Einen Schlüssel aus dem QR-Code in einem Foto oder Scan seines gedruckten Blatts lesen
+keys-import-age = age-Identitäten importieren...
+keys-import-age-hint = Die Identitäten einer age-Identitätsdatei (z. B. von age-keygen) als Schlüssel hinzufügen
+keys-your-age = Ihre age-Empfänger
+keys-your-age-explained = Geben Sie den Empfänger eines Schlüssels an Personen, die mit age Dateien für Sie verschlüsseln.
+keys-export-identity = Identität exportieren
+keys-export-identity-hint = Den Schlüssel als age-Identitätsdatei speichern, für age -d -i
+keys-other-age = Andere age-Empfänger
+keys-name-hint = Name
+keys-your-openpgp = Ihre OpenPGP-Schlüssel
+keys-your-openpgp-explained = Geben Sie den öffentlichen Schlüssel eines Schlüssels an Personen, die mit GnuPG Dateien für Sie verschlüsseln.
+keys-export-public = Öffentlichen Schlüssel exportieren
+keys-export-public-hint = Den öffentlichen OpenPGP-Schlüssel speichern, für gpg --import
+keys-other-openpgp = Andere OpenPGP-Schlüssel
+keys-import-openpgp = OpenPGP-Schlüssel importieren...
+keys-import-openpgp-hint = Einen mit gpg --export --armor exportierten öffentlichen Schlüssel hinzufügen
+keys-backups = Schlüsselsicherungen
+keys-backups-off = Die gespeicherten Schlüssel nach jeder Änderung sichern, verschlüsselt mit einer Sicherungs-Passphrase:
+keys-backup-passphrase = Sicherungs-Passphrase:
+keys-no-backups = Noch keine Sicherungen.
+keys-select-backup = Wählen Sie eine Sicherung und geben Sie ihre Passphrase ein (die Bestätigung ist nicht nötig), um sie wiederherzustellen:
+keys-restore = Wiederherstellen
+keys-restore-hint = Die gespeicherten Schlüssel durch die Schlüssel der gewählten Sicherung ersetzen
+keys-smartcard = Smartcard-Schlüssel
+keys-smartcard-explained = Diese Schlüssel werden beim Entschlüsseln mit der PIN der Smartcard entsperrt.
+keys-smartcard-heading = Smartcard
+keys-advanced = Erweiterte Schlüsselvorgänge
+keys-split = Geteilte Schlüssel verwalten
+keys-transfer = Transfer vorbereiten
+keys-share-backups = Sicherungen der Schlüsselanteile
+keys-last-verified = Zuletzt überprüft
+keys-verification-due = Überprüfung fällig
+keys-ok = OK
+keys-paste-share = Fügen Sie einen Anteil ein (Text, Wortfolge oder Inhalt eines eingelesenen QR-Codes), um ihn zu überprüfen:
+keys-verify-share = Anteil überprüfen
+keys-verify-scan = Aus Scan überprüfen...
+keys-verify-scan-hint = Den Anteil aus einem Foto oder Scan seiner gedruckten Karte lesen
+keys-deployment = Verteilung
+keys-deployment-explained = Administratoren brauchen die Identität dieses Computers, um ein Verteilungspaket für ihn zu erstellen.
+keys-show-identity = Maschinenidentität anzeigen
+keys-target-identity = Identität des Zielcomputers:
+keys-create-bundle = Verteilungspaket erstellen
+
+## File list
+
+files-in-progress = In Bearbeitung: { $percent }
+files-unknown = Unbekannte Datei
+files-pending = Ausstehend
+files-completed = Abgeschlossen
+files-failed = Fehlgeschlagen
+files-waiting = Wartet
+files-title = Dateiliste
+files-file = Datei
+files-algorithm = Algorithmus
+files-time = Zeit
+files-none = Keine Dateien in der Liste. Wählen Sie Dateien mit der Schaltfläche „Öffnen“.
+files-clear-all = Alle entfernen
+
+## Recipients
+
+recipients-count =
+    Jede Datei wird einmal für { $count ->
+        [one] { $count } Empfänger
+       *[other] { $count } Empfänger
+    } und Ihren Schlüssel verschlüsselt. E-Mail-Empfänger brauchen denselben Schlüssel; age-Empfänger entschlüsseln mit ihrem eigenen.
+recipients-age-choice = Verschlüsselt für den age-Empfänger des gewählten Schlüssels und für:
+recipients-no-age = Keine weiteren age-Empfänger gespeichert (unter „Schlüssel verwalten“ hinzufügen)
+recipients-list = E-Mail-Adressen und age-Empfänger (age1...), getrennt durch Kommas oder Zeilenumbrüche:
+recipients-add-saved = Gespeicherte hinzufügen:
+recipients-openpgp-choice = Verschlüsselt für den OpenPGP-Schlüssel des gewählten Schlüssels und für:
+recipients-no-openpgp = Keine OpenPGP-Schlüssel gespeichert (unter „Schlüssel verwalten“ importieren)
+
+## Progress
+
+device-progress = Gerät: { $done }/{ $total } Blöcke verarbeitet, { $queued } in der Warteschlange
+duplicate-scan-progress = Dateien werden auf Duplikate geprüft: { $percent }
+progress-resume = ▶ Fortsetzen
+progress-paused = Pausiert
+progress-pause = ⏸ Pause
+progress-pause-hint = Nach dem Lesen oder Abschließen der aktuellen Datei anhalten; nichts bleibt halb geschrieben
+files-no-files = Keine Dateien in der Liste
+
+## Menu
+
+menu-protect-shares-hint = Angezeigte Anteile verdecken, bis „Zum Anzeigen halten“ gedrückt wird, und das Fenster aus Bildschirmfotos und Bildschirmfreigaben heraushalten, solange Anteile angezeigt werden
+menu-idle-minutes = Minuten Leerlauf
+menu-lock-idle-hint = Bei 0 werden die Schlüssel im Leerlauf nie gesperrt
+menu-minutes = Minuten
+menu-shred-viewed-hint = Mit „Sicher ansehen“ geöffnete Dateien werden auch vernichtet, wenn die Schlüssel gesperrt werden oder CRUSTy geschlossen wird
+menu-hours = Stunden
+menu-check-folders-hint = Prüft die Dateien gesperrter gesicherter Ordner und warnt, wenn eine beschädigt ist oder fehlt; bei 0 wird nie geprüft
+menu-extension-hint = Dateiendung, z. B. docx
+menu-view-securely-hint = Andere Dateien werden nur ausgewählt
+menu-seconds-device = s (Gerät)
+menu-seconds-software = s (Software)
+menu-stop-hint = Bei 0 werden sie nie abgebrochen. Nicht fertige Dateien eines abgebrochenen Vorgangs werden als fehlgeschlagen markiert
+menu-low-memory-hint = Für Computer mit wenig Arbeitsspeicher. age-Dateien werden in Blöcken verarbeitet; Dateien in anderen Formaten und Ordnerarchive, die mehr Speicher bräuchten, werden abgelehnt
+menu-preflight-summary-hint = Schlüssel, Verfahren, Ziel und Optionen eines Stapels sowie die Änderungen seit dem letzten Stapel vor dem Start anzeigen
+menu-files = Dateien
+menu-log-format = Format des Vorgangsprotokolls
+menu-log-format-hint = Strukturiert schreibt pro Eintrag ein JSON-Objekt mit RFC-3339-Zeitstempel, Vorgang, Datei, Schlüssel-Fingerabdruck, Dauer und Ergebnis, für SIEM-Werkzeuge
+menu-system-log = Protokolleinträge an das Systemprotokoll senden
+menu-system-log-hint = Jeden Eintrag auch an syslog (Unix) oder die Windows-Ereignisanzeige (Anwendungsprotokoll, Quelle CRUSTy) senden, wo Protokollsammler ihn abholen können
+menu-system-log-required = Die Admin-Richtlinie sendet Protokolleinträge an das Systemprotokoll
+menu-rotate-log-at = Neues Vorgangsprotokoll beginnen bei
+menu-rotate-log-hint = Das volle Protokoll wird als operations.log.1 behalten, das davor als operations.log.2 und so weiter; bei 0 wird ein Protokoll ohne Grenze fortgeschrieben
+menu-keep-old-logs = Behalten:
+menu-keep-old-logs-for = für
+menu-days = Tage
+menu-keep-old-logs-hint = Die ältesten Protokolle über der behaltenen Anzahl werden gelöscht; bei 0 Tagen werden sie unabhängig vom Alter behalten
+menu-compress-old-logs = Alte Protokolle komprimieren (gzip)
+menu-control-api-hint = Anderen Programmen auf diesem Computer erlauben, Ver- und Entschlüsselungsaufträge mit dem Token aus control_api.json einzureihen
+menu-offline-hint = Netzwerkfunktionen sind ausgeschaltet (Einstellungen > Offline-Modus)
+
+## Tray icon
+
+menu-tray-icon = Symbol im Infobereich anzeigen
+menu-minimize-to-tray = In den Infobereich minimieren
+menu-minimize-to-tray-hint = Das Fenster beim Minimieren ausblenden; über das Symbol im Infobereich wieder öffnen
+menu-tray-unavailable = Auf diesem Desktop gibt es keinen Infobereich
+tray-open = CRUSTy öffnen
+tray-encrypt-clipboard = Zwischenablage verschlüsseln
+tray-exit = Beenden
+tray-progress =
+    CRUSTy: { $operation ->
+        [decrypt] Entschlüsseln
+       *[encrypt] Verschlüsseln
+    }, { $percent } ({ $done } von { $total ->
+        [one] { $total } Datei
+       *[other] { $total } Dateien
+    })
+
+## Status and error messages
+
+error-select-files = Bitte wählen Sie Dateien, Zielordner und Schlüssel aus
+error-decrypt-reason = Bitte geben Sie einen Grund für die Entschlüsselung an
+error-passphrases-differ = Die Passphrasen stimmen nicht überein
+error-no-key-selected = Kein Schlüssel ausgewählt
+error-generation-disabled = Das Erzeugen von Schlüsseln ist durch die Richtlinie deaktiviert
+error-import-disabled = Das Importieren von Schlüsseln ist durch die Richtlinie deaktiviert
+error-export-disabled = Das Exportieren von Schlüsseln ist durch die Richtlinie deaktiviert
+error-management-disabled = Die Schlüsselverwaltung ist durch die Richtlinie deaktiviert
+error-file-not-found = Datei nicht gefunden: { $path }
+error-in-file = { $path }: { $error }
+error-machine-identity = Die Gerätekennung konnte nicht ermittelt werden: { $error }
+error-key-in-credential-store = Der Schlüssel „{ $name }“ liegt im Anmeldeinformationsspeicher und wird nicht in Dateien gespeichert
+error-select-key-encrypted-with = Bitte wählen Sie den Schlüssel, mit dem die Dateien verschlüsselt sind
+error-settings-save = Die Einstellungen konnten nicht gespeichert werden: { $error }
+error-clipboard-no-key = Bitte wählen Sie den Schlüssel, mit dem die Zwischenablage verschlüsselt wird
+error-clipboard-open = Die Zwischenablage konnte nicht geöffnet werden: { $error }
+error-clipboard-empty = Die Zwischenablage enthält keinen Text zum Verschlüsseln
+error-clipboard-encrypted = Der Text in der Zwischenablage ist bereits verschlüsselt
+status-clipboard-encrypted = Text in der Zwischenablage verschlüsselt (entschlüsseln mit crusty --decrypt-stream oder age -d)
+error-clipboard-encrypt = Die Zwischenablage konnte nicht verschlüsselt werden: { $error }
+error-theme-name = Bitte geben Sie einen Namen für das Design ein
+status-theme-saved = Design „{ $name }“ gespeichert
+status-theme-deleted = Design „{ $name }“ gelöscht
+status-offline-on = Offline-Modus an: Netzwerkfunktionen sind ausgeschaltet
+status-offline-off = Offline-Modus aus
+error-system-log-open = Das Systemprotokoll konnte nicht geöffnet werden: { $error }
+status-watch-encrypted = Neue Datei { $path } verschlüsselt
+error-watch-no-key = Bitte wählen Sie den Schlüssel, mit dem neue Dateien des Ordners verschlüsselt werden
+error-watch-unsaved-key = Bitte wählen Sie einen gespeicherten Schlüssel; der Ordner wird damit überwacht, solange CRUSTy läuft
+error-watch-already = { $folder } wird bereits überwacht
+status-watch-started = { $folder } wird auf neue Dateien überwacht
+status-proxy-saved = Proxy-Einstellungen gespeichert
+error-proxy-apply = Die Proxy-Einstellungen konnten nicht angewendet werden: { $error }
+error-embedded-settings = Ungültige Einstellungen für das eingebettete Gerät: { $error }
+error-pinned-key-missing = Der gebundene Schlüssel „{ $name }“ ist auf diesem Computer nicht gespeichert
+error-pin-no-key = Bitte wählen Sie den Schlüssel, der gebunden werden soll
+status-key-pinned = Schlüssel „{ $name }“ an { $folder } gebunden
+status-partner-forgotten = Partner { $name } vergessen
+error-select-new-key = Bitte wählen Sie den neuen Schlüssel
+error-new-key-name = Bitte geben Sie einen Namen für den neuen Schlüssel ein
+error-select-manifest-key = Bitte wählen Sie den Schlüssel, mit dem die Dateien verschlüsselt wurden
+error-view-selection = Bitte wählen Sie eine Datei und den Schlüssel, mit dem sie verschlüsselt wurde
+error-select-audit-key = Bitte wählen Sie den Schlüssel, mit dem die Datei verschlüsselt ist
+error-deploy-no-keys = Keine Schlüssel zum Verteilen
+status-deployment-saved = Verteilungspaket gespeichert unter: { $path }
+error-deployment-create = Das Verteilungspaket konnte nicht erstellt werden: { $error }
+error-key-name = Bitte geben Sie einen Namen für den Schlüssel ein
+error-file-passphrase = Bitte geben Sie die Passphrase ein, mit der die Datei verschlüsselt wurde
+error-expiry-key = Bitte wählen Sie den Schlüssel, dessen Ablaufdatum festgelegt werden soll
+error-expiry-date = Bitte geben Sie das Ablaufdatum als JJJJ-MM-TT ein
+error-select-backup = Bitte wählen Sie eine Sicherung zum Wiederherstellen
+error-backup-passphrase = Bitte geben Sie die Passphrase der Sicherung ein
+error-operation-running = Warten Sie, bis der laufende Vorgang abgeschlossen ist
+error-transfer-key-first = Stellen Sie zuerst den Übertragungsschlüssel wieder her
+error-output-exists = { $path } existiert bereits; verschieben Sie die Datei und versuchen Sie es erneut
+status-decrypted-to = Entschlüsselt nach { $path }
+error-received-decrypt = Die empfangene Datei konnte mit dem Übertragungsschlüssel nicht entschlüsselt werden; Einzelheiten stehen im Protokoll
+error-secured-folder-gone = Der gesicherte Ordner existiert nicht mehr
+error-secured-folder-bound-key = { $folder } ist an den Schlüssel { $fingerprint } gebunden, der nicht unter den gespeicherten Schlüsseln ist
+error-secured-folder-locked-key = { $folder } ist mit dem Schlüssel { $fingerprint } gesperrt, der nicht unter den gespeicherten Schlüsseln ist
+error-secured-folder-lock-key = Bitte wählen Sie den Schlüssel, mit dem der Ordner gesperrt wird
+error-secured-folder-unlock-key = Bitte wählen Sie den Schlüssel, mit dem der Ordner gesperrt wurde
+status-secured-all-locked = Alle gesicherten Ordner sind gesperrt
+status-secured-all-unlocked = Alle gesicherten Ordner sind entsperrt
+error-secured-skipped =
+    { $count ->
+        [one] { $count } Ordner
+       *[other] { $count } Ordner
+    } übersprungen: { $error }
+status-key-saved-to = Schlüssel gespeichert unter: { $path }
+error-key-save = Der Schlüssel konnte nicht gespeichert werden: { $error }
+status-key-loaded = Schlüssel geladen: { $name }
+error-key-load = Der Schlüssel konnte nicht geladen werden: { $error }
+error-key-file-read = Die Schlüsseldatei konnte nicht gelesen werden: { $error }
+status-age-identity-saved = age-Identität gespeichert unter: { $path }
+error-age-identity-save = Die age-Identität konnte nicht gespeichert werden: { $error }
+status-age-identities-imported =
+    { $count ->
+        [one] { $count } age-Identität
+       *[other] { $count } age-Identitäten
+    } importiert
+error-age-identities-import = Die age-Identitäten konnten nicht importiert werden: { $error }
+error-age-recipient-name = Bitte geben Sie an, wem der age-Empfänger gehört
+error-age-recipient-saved = Dieser age-Empfänger ist bereits gespeichert
+status-age-recipient-saved = age-Empfänger gespeichert: { $name }
+status-openpgp-key-saved-to = Öffentlicher OpenPGP-Schlüssel gespeichert unter: { $path }
+error-openpgp-key-save = Der öffentliche OpenPGP-Schlüssel konnte nicht gespeichert werden: { $error }
+error-openpgp-key-saved = Dieser OpenPGP-Schlüssel ist bereits gespeichert
+status-openpgp-key-saved = OpenPGP-Schlüssel gespeichert: { $user } ({ $fingerprint })
+error-openpgp-key-import = Der OpenPGP-Schlüssel konnte nicht importiert werden: { $error }
+status-share-scanned-qr = Anteil aus dem QR-Code gelesen
+status-share-scanned-text = Anteil aus dem gedruckten Text gelesen: vergleichen Sie ihn vor der Verwendung mit dem Papier
+error-scan-key-not-share-import = Dies ist ein Schlüssel, kein Schlüsselanteil; importieren Sie ihn in der Schlüsselverwaltung
+error-scan-key-not-share = Dies ist ein Schlüssel, kein Schlüsselanteil
+status-key-scanned = Schlüssel „{ $name }“ aus dem QR-Code gelesen und ausgewählt
+error-scan-share-not-key = Dies ist ein Schlüsselanteil; geben Sie ihn unter „Übertragung empfangen“ ein, um den Schlüssel wiederherzustellen
+error-select-files-and-key = Bitte wählen Sie Dateien und Schlüssel aus
+status-key-management = Schlüsselverwaltung
+status-advanced-options = Erweiterte Optionen
+status-main-screen = Hauptansicht
+error-policy-load = Die Administratorrichtlinie konnte nicht gelesen werden, daher gelten eingeschränkte Standardwerte: { $error }
+error-old-logs-delete = Alte Protokolle konnten nicht gelöscht werden: { $error }
+error-select-files-decrypt = Bitte wählen Sie Dateien, Zielordner und Schlüssel zum Entschlüsseln aus
+status-pin-cancelled = Entsperren der Smartcard abgebrochen
+status-qr-scan-unavailable = Das Scannen von QR-Codes ist in dieser Version nicht verfügbar
+status-watch-stopped = { $folder } wird nicht mehr überwacht
+status-workflow-started = Verschlüsselungsassistent wird gestartet
+status-decryption-started = Entschlüsselung wird gestartet
+status-switched-to-main = Zur Hauptansicht gewechselt
+status-identity-copied = Gerätekennung in die Zwischenablage kopiert
+error-select-files-and-output = Bitte wählen Sie Dateien und Zielordner aus
+error-select-or-create-key = Bitte wählen oder erstellen Sie einen Schlüssel
+error-previous-steps = Bitte schließen Sie alle vorherigen Schritte ab
+error-rotation-files = Bitte fügen Sie die neu zu verschlüsselnden Dateien hinzu
+error-rotation-keys = Bitte wählen Sie den alten Schlüssel und einen anderen neuen Schlüssel (oder einen Namen für einen neuen)
+error-cold-storage-choices = Bitte wählen Sie den Ordner, eine Bezeichnung und das Ziel für den Satz
+error-cold-storage-step = Bitte wählen Sie gültige Optionen für diesen Schritt
+error-restore-manifest = Bitte öffnen Sie das Manifest des Satzes
+error-restore-volumes = Zum Wiederherstellen des Satzes müssen alle Datenträger gefunden und lesbar sein
+error-restore-key = Bitte stellen Sie zuerst den Schlüssel des Satzes wieder her
+status-logs-refreshed = Protokolle aktualisiert
+error-logs-clear = Die Protokolle konnten nicht gelöscht werden: { $error }
+status-logs-cleared = Protokolle gelöscht
+error-logger-missing = Protokollierung nicht eingerichtet
+status-compliance-report-saved =
+    Compliance-Bericht mit { $count ->
+        [one] { $count } Entschlüsselungseintrag
+       *[other] { $count } Entschlüsselungseinträgen
+    } gespeichert
+error-compliance-report-save = Der Compliance-Bericht konnte nicht gespeichert werden: { $error }
+status-logs-exported =
+    { $count ->
+        [one] { $count } Protokolleintrag
+       *[other] { $count } Protokolleinträge
+    } nach { $path } exportiert
+error-logs-export = Die Protokolle konnten nicht exportiert werden: { $error }
+status-share-file-dialog = Hier würde ein Dateidialog zur Auswahl einer Anteilsdatei geöffnet
+status-key-reconstructed-check = Schlüssel „{ $name }“ wiederhergestellt und ausgewählt (Fingerabdruck { $fingerprint }; prüfen Sie ihn mit dem Absender)
+status-viewing =
+    { $name } wird angezeigt; die Kopie wird in { $minutes ->
+        [one] { $minutes } Minute
+       *[other] { $minutes } Minuten
+    } vernichtet
+error-view = { $name } konnte nicht angezeigt werden: { $error }
+error-shred-viewed-all = Die angezeigten Dateien konnten nicht vernichtet werden: { $error }
+status-shredded-viewed = Die angezeigte Kopie von { $path } wurde vernichtet
+error-shred-viewed = Eine angezeigte Datei konnte nicht vernichtet werden: { $error }
+status-control-api-listening = Steuerungs-API wartet auf 127.0.0.1:{ $port }
+error-control-api-start = Die Steuerungs-API konnte auf Port { $port } nicht gestartet werden: { $error }
+status-control-api-stopped = Steuerungs-API beendet
+status-control-api-job = Auftrag { $id } der Steuerungs-API läuft
+error-catalog-folder = Bitte wählen Sie den Ordner mit den verschlüsselten Dateien
+error-catalog-running = Es wird bereits ein Katalog erstellt
+status-catalog-started = Katalog wird erstellt...
+status-catalog-saved = Katalog gespeichert unter { $path }
+error-catalog-save = Der Katalog konnte nicht gespeichert werden: { $error }
+status-catalog-made =
+    Katalog mit { $count ->
+        [one] { $count } verschlüsselten Datei
+       *[other] { $count } verschlüsselten Dateien
+    }
+status-catalog-made-unknown =
+    Katalog mit { $count ->
+        [one] { $count } verschlüsselten Datei
+       *[other] { $count } verschlüsselten Dateien
+    }; { $unknown } lassen sich mit keinem gespeicherten Schlüssel öffnen
+error-catalog-make = Der Katalog konnte nicht erstellt werden: { $error }
+error-cold-storage-choose = Wählen Sie zuerst den Ordner und das Ziel für den Satz
+error-cold-storage-running = Es wird bereits ein Archivsatz geschrieben
+status-cold-storage-started = Archivsatz wird geschrieben...
+status-cold-storage-written =
+    Archivsatz { $path } geschrieben ({ $volumes ->
+        [one] { $volumes } Datenträger
+       *[other] { $volumes } Datenträger
+    }, { $files ->
+        [one] { $files } Datei
+       *[other] { $files } Dateien
+    })
+error-cold-storage-write = Der Archivsatz konnte nicht geschrieben werden: { $error }
+status-deleted = { $path } gelöscht
+error-delete = { $path } konnte nicht gelöscht werden: { $error }
+error-benchmark-running = Es läuft bereits ein Leistungstest
+error-benchmark-sizes = Bitte wählen Sie mindestens eine Puffergröße
+error-benchmark-device = Das Gerät kann nicht getestet werden: { $error }
+status-benchmark-started = Leistungstest läuft...
+status-benchmark-stopped = Leistungstest angehalten
+error-benchmark-failed = Leistungstest fehlgeschlagen: { $error }
+error-benchmark-stopped = Der Leistungstest wurde unerwartet beendet
+status-benchmark-finished = Leistungstest abgeschlossen
+status-benchmark-software-faster = Leistungstest abgeschlossen: die Software-Verschlüsselung war bei jeder Größe schneller
+status-benchmark-device-faster = Leistungstest abgeschlossen: das Gerät war bei jeder Größe schneller
+status-benchmark-device-faster-for = Leistungstest abgeschlossen: das Gerät war schneller bei { $sizes }
+error-agreement-running = Es läuft bereits eine Schlüsselvereinbarung
+status-agreement-waiting = Warten auf die Verbindung des Partners an Port { $port }...
+error-agreement-address = Geben Sie die Adresse des Partnercomputers ein
+status-agreement-connecting = Verbindung mit { $address } wird hergestellt...
+status-agreement-agreed = Schlüssel mit { $address } vereinbart; vergleichen Sie die Codes
+status-agreement-cancelled = Warten auf den Partner beendet
+error-agreement-failed = Schlüsselvereinbarung fehlgeschlagen: { $error }
+error-agreement-partner-name = Geben Sie den Namen des Partners ein, um ihn zu speichern
+status-agreement-accepted = Schlüssel mit { $name } nach Vergleich des Codes { $code } vereinbart
+status-agreement-accepted-saved = Schlüssel mit dem gespeicherten Partner { $name } vereinbart
+error-agreement-rejected = Der Schlüssel wurde verworfen. Möglicherweise steht jemand zwischen den beiden Computern: prüfen Sie das Netzwerk, bevor Sie es erneut versuchen.
+status-rotation-added =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } hinzugefügt; nur noch nicht hinzugefügte .encrypted-Dateien und .crusty-Archive werden neu verschlüsselt
+error-no-encrypted-files = Keine verschlüsselten Dateien in { $folder }
+status-encrypted-files-found =
+    { $count ->
+        [one] { $count } verschlüsselte Datei
+       *[other] { $count } verschlüsselte Dateien
+    } in { $folder } gefunden
+error-read-path = { $path } konnte nicht gelesen werden: { $error }
+error-rotation-same-key = Der neue Schlüssel muss sich vom alten unterscheiden
+error-rotation-running = Die Dateien werden bereits neu verschlüsselt
+status-rotation-started =
+    { $count ->
+        [one] { $count } Datei wird
+       *[other] { $count } Dateien werden
+    } neu verschlüsselt...
+error-rotation-stopped = Der Schlüsselwechsel wurde unerwartet beendet; führen Sie ihn erneut aus, um die übrigen Dateien abzuschließen
+status-rotation-done = Alle Dateien verwenden jetzt den Schlüssel { $fingerprint }
+error-rotation-failed =
+    { $count ->
+        [one] { $count } Datei konnte
+       *[other] { $count } Dateien konnten
+    } nicht neu verschlüsselt werden und verwenden noch den alten Schlüssel; siehe Bericht
+status-report-saved = Bericht gespeichert unter { $path }
+error-report-save = Der Bericht konnte nicht gespeichert werden: { $error }
+error-secured-folders-load = Die gesicherten Ordner konnten nicht geladen werden: { $error }
+error-secured-folders-lock = Die gesicherten Ordner konnten nicht gesperrt werden: { $error }
+error-secured-folders-save = Die gesicherten Ordner konnten nicht gespeichert werden: { $error }
+error-secured-folder-exists = { $folder } ist bereits ein gesicherter Ordner
+error-secured-folder-nested = { $folder } liegt in einem anderen gesicherten Ordner oder enthält einen
+error-secured-folder-busy = Warten Sie, bis der Ordner fertig gesperrt oder entsperrt ist
+status-secured-folder-removed = { $folder } ist kein gesicherter Ordner mehr
+status-secured-folder-bound = { $folder } wird jetzt immer mit demselben Schlüssel gesperrt
+status-secured-folder-unbound = { $folder } wird jetzt mit dem aktuellen Schlüssel gesperrt
+error-secured-folder-other-busy = Ein anderer Ordner wird gerade gesperrt oder entsperrt
+status-secured-folder-locking = Ordner wird gesperrt...
+status-secured-folder-unlocking = Ordner wird entsperrt...
+status-secured-folder-locked =
+    { $folder } gesperrt ({ $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    })
+status-secured-folder-unlocked =
+    { $folder } entsperrt ({ $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    })
+error-secured-folder-lock-failed =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } in { $folder } gesperrt, aber { $failed } fehlgeschlagen (zuerst: { $file }: { $error }). Beheben Sie das Problem und sperren Sie den Ordner erneut, um abzuschließen.
+error-secured-folder-unlock-failed =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } in { $folder } entsperrt, aber { $failed } fehlgeschlagen (zuerst: { $file }: { $error }). Beheben Sie das Problem und entsperren Sie den Ordner erneut, um abzuschließen.
+status-integrity-nothing = Kein gesperrter gesicherter Ordner, der mit den gespeicherten Schlüsseln geprüft werden kann
+status-integrity-started =
+    Integrität von { $count ->
+        [one] { $count } gesichertem Ordner
+       *[other] { $count } gesicherten Ordnern
+    } wird geprüft...
+error-integrity-stopped = Die Integritätsprüfung wurde unerwartet beendet
+status-integrity-intact = Die gesperrten gesicherten Ordner sind unversehrt
+error-integrity-failed =
+    { $count ->
+        [one] { $count } gesicherter Ordner hat
+       *[other] { $count } gesicherte Ordner haben
+    } die Integritätsprüfung nicht bestanden; siehe Übersicht
+error-audit-files = Bitte wählen Sie die Originaldatei und die verschlüsselte Datei
+error-audit-running = Es läuft bereits eine Prüfung
+status-audit-started = Die verschlüsselte Datei wird geprüft...
+status-files-found =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } in { $folder } gefunden
+error-verify-files = Bitte fügen Sie die zu prüfenden Dateien hinzu
+status-verify-started =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } werden geprüft...
+status-audit-match = Die verschlüsselte Datei enthält genau das Original
+error-audit-mismatch = Die verschlüsselte Datei stimmt nicht mit dem Original überein; siehe Bericht
+error-audit-failed = Prüfung fehlgeschlagen: { $error }
+error-audit-stopped = Die Prüfung wurde unerwartet beendet
+error-verify-stopped = Die Verifizierung wurde unerwartet beendet
+status-verify-authentic =
+    { $count ->
+        [one] Die Datei ist echt
+       *[other] Alle { $count } Dateien sind echt
+    }
+error-verify-failed =
+    { $failed } von { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } haben die Verifizierung nicht bestanden
+status-shares-pasted = { $count } Anteile in getrennte Felder eingefügt
+error-bind-passphrase = Geben Sie die Passphrase ein, an die das Paket gebunden wird
+status-package-created = Übertragungspaket erfolgreich erstellt
+error-package-create = Das Übertragungspaket konnte nicht erstellt werden: { $error }
+status-share-saved = Anteil { $index } gespeichert unter: { $path }
+error-share-save = Der Anteil konnte nicht gespeichert werden: { $error }
+status-share-uploaded =
+    Anteil { $index } hochgeladen. Der Link läuft in { $hours ->
+        [one] { $hours } Stunde
+       *[other] { $hours } Stunden
+    } ab.
+status-share-fetched = Anteil vom Relay abgerufen. Der Link kann nicht mehr verwendet werden.
+error-dictation-group = Gruppe { $group }: { $error }
+status-dictated-share = Diktierter Anteil eingegeben
+error-shares-needed = Bitte geben Sie { $needed } Schlüsselanteile ein ({ $entered } eingegeben)
+error-unwrap-passphrase = Dieses Paket ist an eine Passphrase gebunden; geben Sie sie ein, um den Schlüssel zu entpacken
+error-read-identity = Die Identität dieses Rechners konnte nicht gelesen werden: { $error }
+error-key-reconstruct = Der Schlüssel konnte nicht wiederhergestellt werden: { $error }
+status-manifest-read = Manifest des Kaltlager-Satzes „{ $label }“ gelesen ({ $count } Datenträger)
+error-restore-manifest-first = Wählen Sie zuerst das Manifest des Satzes
+error-restore-running = Der Satz wird bereits geprüft oder wiederhergestellt
+status-volumes-checking = Die Datenträger werden geprüft...
+status-set-key-rebuilt = Schlüssel des Satzes wiederhergestellt ({ $fingerprint })
+error-restore-choose = Stellen Sie zuerst den Schlüssel des Satzes wieder her und wählen Sie das Ziel
+status-restore-started = Kaltlager-Satz wird wiederhergestellt...
+status-volumes-readable = Jeder Datenträger kann gelesen werden
+error-volumes-missing = Einige Datenträger fehlen oder sind zu stark beschädigt; siehe Liste
+status-set-restored =
+    Kaltlager-Satz { $label } nach { $folder } wiederhergestellt ({ $restored } von { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    })
+error-set-restore = Der Kaltlager-Satz konnte nicht wiederhergestellt werden: { $error }
+error-cold-storage-stopped = Der Kaltlager-Auftrag wurde unerwartet beendet
+status-history-cleared = Verlauf gelöscht
+error-operation-unfinished = Der Vorgang ist noch nicht abgeschlossen
+status-pausing = Wird angehalten...
+status-resumed = Fortgesetzt
+status-files-selected =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } ausgewählt
+error-no-files-to-decrypt = Keine zu entschlüsselnden Dateien in { $folder }
+error-no-files-to-process = Keine zu verarbeitenden Dateien in { $folder }
+status-folder-files-selected =
+    { $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    } in { $folder } ausgewählt
+status-files-removed = Alle Dateien entfernt
+status-file-removed = Datei entfernt, { $count } verbleibend
+status-files-cleared = Alle Dateien geleert
+status-output-dir = Ausgewähltes Ausgabeverzeichnis: { $folder }
+error-replace-folder = Dateien in { $folder } können nicht ersetzt werden, da der Ordner fehlt oder schreibgeschützt ist. Schalten Sie „Originaldateien ersetzen“ aus und wählen Sie einen Ausgabeordner.
+status-operation-stopped = Vorgang gestoppt
+error-archives-separately = Entschlüsseln Sie Archive (.crusty) getrennt von anderen Dateien
+error-archive-replace = Archive werden im Ausgabeverzeichnis gespeichert; schalten Sie „Originaldateien ersetzen“ aus, um eines zu erstellen
+error-archive-embedded = Archive werden auf diesem Computer erstellt und entpackt; schalten Sie die Hardware-Verschlüsselung aus, um sie zu verwenden
+error-recipients-format = Dateien für mehrere Empfänger werden im CRUSTy-Format gespeichert; schalten Sie die Formate age und OpenPGP aus
+error-recipients-archive = Archive werden nur mit dem Schlüssel verschlüsselt; schalten Sie „Ordner in ein Archiv packen“ aus, um für Empfänger zu verschlüsseln
+error-recipients-embedded = Dateien für mehrere Empfänger werden auf diesem Computer verschlüsselt; schalten Sie die Hardware-Verschlüsselung aus, um sie zu verwenden
+status-packing-folder = Ordner wird in ein Archiv gepackt...
+status-encrypt-started = Verschlüsselung wird gestartet...
+status-extract-started = Archiv wird entpackt...
+status-decrypt-started = Entschlüsselung wird gestartet...
+error-outputs-collide = { $first } und { $second } würden beide als { $output } gespeichert; verarbeiten Sie sie getrennt
+error-operation-stalled =
+    Der Vorgang kam { $seconds } s lang mit { $backend } nicht voran und wurde gestoppt; { $failed ->
+        [one] { $failed } Datei ist
+       *[other] { $failed } Dateien sind
+    } fehlgeschlagen
+error-manifest-running = Es wird bereits ein Manifest geprüft
+status-manifest-checking = Die Dateien werden mit dem Manifest verglichen...
+status-manifest-intact =
+    Jede Datei stimmt mit dem Manifest überein ({ $count ->
+        [one] { $count } Datei
+       *[other] { $count } Dateien
+    })
+error-manifest-mismatch = Einige Dateien stimmen nicht mit dem Manifest überein: { $intact } unverändert, { $changed } geändert, { $missing } fehlen
+error-manifest-check = Das Manifest konnte nicht geprüft werden: { $error }
+error-manifest-stopped = Die Manifestprüfung wurde unerwartet beendet
+status-connection-testing = Die Verbindung zum Gerät wird getestet...
+status-device-answered = Das Gerät antwortete in { $ms } ms (Firmware { $firmware })
+error-connection-stopped = Der Verbindungstest wurde unerwartet beendet
+error-add-disabled = Das Hinzufügen von Schlüsseln ist durch Richtlinie deaktiviert
+error-remove-disabled = Das Entfernen von Schlüsseln ist durch Richtlinie deaktiviert
+error-backups-disabled = Schlüsselsicherungen sind durch Richtlinie deaktiviert
+status-key-generated = Neuer Schlüssel erzeugt: { $name }
+error-passphrase-short = Die Passphrase muss mindestens { $length } Zeichen lang sein
+status-key-derived = Schlüssel aus Passphrase abgeleitet: { $name }
+error-key-derive = Der Schlüssel konnte nicht abgeleitet werden: { $error }
+error-not-passphrase-file = { $path } wurde nicht mit einem Passphrasen-Schlüssel verschlüsselt
+status-key-selected = Ausgewählter Schlüssel: { $name }
+status-key-selected-expired = Ausgewählter Schlüssel: { $name } (abgelaufen am { $date }; verwenden Sie besser einen neueren Schlüssel)
+status-key-expires = Schlüssel '{ $name }' läuft am { $date } ab
+status-key-no-expiry = Schlüssel '{ $name }' läuft nicht mehr ab
+error-key-distributed = Schlüssel '{ $name }' wird vom Administrator verteilt und nicht hier gespeichert
+error-credential-store-session = Schlüssel können nur im Anmeldeinformationsspeicher liegen, solange gespeicherte Schlüssel sitzungsübergreifend aufbewahrt werden
+error-credential-name-taken = Ein anderer Schlüssel namens '{ $name }' liegt bereits im Anmeldeinformationsspeicher
+error-credential-move = Der Schlüssel konnte nicht in den Anmeldeinformationsspeicher verschoben werden: { $error }
+error-credential-remove = Schlüssel '{ $name }' wurde in den Schlüsselspeicher verschoben, aber { $error }
+status-key-in-credential-store = Schlüssel '{ $name }' liegt im Anmeldeinformationsspeicher
+status-key-in-key-store = Schlüssel '{ $name }' liegt im Schlüsselspeicher
+error-key-removed-credential = Schlüssel '{ $name }' wurde entfernt, aber { $error }
+status-key-removed = Schlüssel entfernt: { $name }
+error-backups-off = Schlüsselsicherungen sind aus: { $error }
+error-backup-key-derive = Der Sicherungsschlüssel konnte nicht abgeleitet werden: { $error }
+error-backups-session = Schlüsselsicherungen sind an, bis CRUSTy geschlossen wird: { $error }
+status-backups-on = Schlüsselsicherungen eingeschaltet
+error-backup-restore = Die Schlüsselsicherung konnte nicht wiederhergestellt werden: { $error }
+status-backup-restored =
+    { $count ->
+        [one] { $count } Schlüssel
+       *[other] { $count } Schlüssel
+    } aus Sicherung { $generation } wiederhergestellt
+status-keys-loaded =
+    { $count ->
+        [one] { $count } gespeicherter Schlüssel
+       *[other] { $count } gespeicherte Schlüssel
+    } geladen
+error-keys-session = Gespeicherte Schlüssel werden aufbewahrt, bis CRUSTy geschlossen wird: { $error }
+status-keys-locked = Schlüssel gesperrt
+error-keys-save = Die Schlüssel konnten nicht gespeichert werden: { $error }
+error-keys-backup = Die Schlüssel konnten nicht gesichert werden: { $error }
+error-provisioned-key = Der bereitgestellte Schlüssel '{ $name }' konnte nicht geladen werden: { $error }
+error-deployed-keys = Die verteilten Schlüssel konnten nicht geladen werden: { $error }
+status-token-key-selected = Ausgewählter Smartcard-Schlüssel: { $name }
+error-split-create = Der geteilte Schlüssel konnte nicht erstellt werden: { $error }
+status-split-created =
+    Geteilter Schlüssel erstellt. Zweiter Anteil gespeichert unter: { $secondary }
+    Wiederherstellungsanteil gespeichert unter: { $recovery }
+error-split-store = Der geteilte Schlüssel konnte nicht gespeichert werden: { $error }
+status-key-reconstructed = Schlüssel '{ $name }' wiederhergestellt und ausgewählt
+error-shares-open = Die Schlüsselanteile konnten nicht geöffnet werden: { $error }
+error-primary-share-verify = Der primäre Schlüsselanteil konnte nicht geprüft werden: { $error }
+status-shares-due =
+    { $count ->
+        [one] { $count } Schlüsselanteil muss
+       *[other] { $count } Schlüsselanteile müssen
+    } geprüft werden. Siehe Schlüsselverwaltung.
+status-share-verified = { $label } geprüft
+error-share-verify = Die Prüfung des Anteils ist fehlgeschlagen: { $error }
+error-custodians-load = Das Verwahrerverzeichnis konnte nicht geladen werden: { $error }
+status-custodians-saved = Verwahrer der Anteile gespeichert
+error-custodians-save = Das Verwahrerverzeichnis konnte nicht gespeichert werden: { $error }
+status-ceremony-saved = Zeremoniebericht gespeichert unter: { $path }
+error-ceremony-write = Der Zeremoniebericht konnte nicht geschrieben werden: { $error }
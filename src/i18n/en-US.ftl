@@ -0,0 +1,1418 @@
+# English messages of the CRUSTy window, also used for messages missing from
+# another language. See src/i18n.rs.
+
+## Common
+
+button-remove = Remove
+button-add = Add
+parity-none = None
+parity-even = Even
+parity-odd = Odd
+button-back = Back
+reveal-hold = Hold to Reveal
+reveal-hidden = Hidden
+reveal-hold-hint = Shows the share while the button is held down
+
+## Menu bar
+
+menu-file = File
+menu-open = Open
+menu-open-folder = Open Folder...
+menu-cold-storage-export = Cold-Storage Export...
+menu-cold-storage-restore = Cold-Storage Restore...
+menu-audit = Audit Encrypted Files...
+menu-export-catalog = Export Catalog...
+menu-benchmark = Benchmark Backends...
+menu-lock-keys = Lock Keys
+menu-exit = Exit
+menu-settings = Settings
+menu-settings-screen = Theme, Defaults, and Performance...
+menu-completion-sound = Completion sound
+menu-choose-sound = Choose Sound File...
+menu-use-system-sound = Use System Sound
+menu-taskbar-progress = Show progress on taskbar
+menu-protect-shares = Hide key shares until revealed
+menu-lock-on-session-lock = Lock keys when the computer locks or sleeps
+menu-lock-after = Lock keys after
+menu-shred-viewed-after = Shred viewed files after
+menu-check-folders-every = Check secured folders every
+menu-open-actions = Default Actions by Extension
+menu-open-actions-explained = Opening an encrypted file from Recent Files or the file manager:
+menu-stop-without-progress = Stop operations without progress for
+menu-low-memory = Low-memory mode (64 MB per file)
+menu-preflight-summary = Summary before each batch
+menu-batch-limits = Warn before very large batches
+menu-language = Language
+menu-system-language = System default
+menu-offline = Offline mode
+menu-network-proxy = Network Proxy...
+menu-control-api = Local control API
+menu-copy-api-token = Copy API Token
+menu-help = Help
+menu-help-f1 = Help (F1)
+menu-about = About
+menu-view-logs = View Logs
+menu-offline-badge = OFFLINE
+menu-sound-file = Sound: { $file }
+menu-sound-system = Sound: System default
+menu-batch-limits-over = Over
+menu-batch-limits-or = or
+menu-control-api-port = Port
+menu-control-api-listening = Listening on 127.0.0.1:{ $port }
+
+## Help
+
+help-title = Help
+
+## Action bar
+
+action-encrypt = Encrypt
+action-decrypt = Decrypt
+action-stop = Stop
+action-keys = Keys
+action-advanced = Advanced
+action-open = Open
+
+## Main screen
+
+main-total-files =
+    Total: { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }
+main-no-key-selected = No key selected
+main-fingerprint = Fingerprint: { $fingerprint }
+main-tab-recent-files = Recent Files
+main-tab-secured-folders = Secured Folders
+main-processing-mode = Processing Mode:
+main-single-file = Single File
+main-multiple-files = Multiple Files
+main-select-output-dir = Select Output Directory
+main-selected-files = Selected Files
+main-clear-all = Clear All
+main-output-dir = Output Directory
+main-current-key = Current Key:
+main-select-key = Select
+main-new-key = New Key
+main-use-embedded = Use embedded system for cryptographic operations
+main-connection-type = Connection Type:
+main-serial = Serial/UART
+main-device-id = Device ID/Address:
+main-connection-settings = Connection Settings
+main-usb-interface = Interface:
+main-usb-endpoint = Endpoint:
+main-baud-rate = Baud Rate:
+main-parity = Parity:
+main-host = Host:
+main-port = Port:
+main-use-tls = Use TLS
+main-device-certificate = Device certificate:
+main-no-certificate = None (trust known certificate authorities)
+main-choose-certificate = Choose...
+main-clear-certificate = Clear
+main-device-certificate-hint = Only this certificate is accepted from the device. Without one, the device's certificate must be signed by a known certificate authority.
+main-test-connection = Test Connection
+main-testing-connection = Testing the connection...
+main-test-connection-hint = Check that the device answers, and what it runs, before starting an operation on it.
+main-device-firmware = Firmware { $version }, answered in { $milliseconds } ms
+main-device-algorithms = Algorithms: { $algorithms }
+main-device-resources = Free: { $memory } memory, { $slots } key slots, { $queue } queued chunks
+
+## Encrypt and decrypt screens
+
+ops-selected-count =
+    Selected { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }
+ops-output-dir-is = Output directory: { $dir }
+ops-unknown-key = Unknown key
+encrypt-key-expired = This key expired on { $date }. Consider encrypting with a newer key.
+encrypt-key-pinned = ⚠ The key '{ $name }' is pinned to { $folder }. Check that this is the right key.
+encrypt-low-memory-age = Low-memory mode: files over { $size } MB can only be encrypted in the age format
+ops-overall-progress = Overall Progress: { $percent }
+ops-file-number = File { $number }: { $file }
+encrypt-title = Encrypt Files
+ops-file-selection = File Selection
+ops-select-files = Select Files
+ops-select-file = Select File
+ops-select-folder = Select Folder
+ops-select-folder-hint = Select every file in a folder and its subfolders
+ops-batch-mode = Batch Mode
+ops-no-files = No files selected
+encrypt-archive-folder = Pack the folder into one archive (.crusty)
+encrypt-archive-folder-hint = Save one encrypted file holding the whole folder, instead of one per file
+ops-no-output-dir = No output directory selected
+encrypt-options = Encryption Options
+encrypt-key = Encryption Key:
+ops-select-key = Select Key
+ops-change-key = Change Key
+encrypt-hide-names = Hide file names
+encrypt-parity = Add parity for archival media
+encrypt-digest = Store a checksum to verify decrypted files
+encrypt-manifest = Write a signed manifest of the batch
+encrypt-age-format = Save in age format
+encrypt-openpgp-format = Save in OpenPGP format (for GnuPG)
+encrypt-for-recipients = Encrypt for recipients
+ops-use-hardware = Use hardware encryption
+ops-serial = Serial
+encrypt-progress = Encryption Progress
+ops-results = Results
+ops-export-report = Export Report…
+ops-export-report-hint = Save each file's input, output, status, time, size, and key fingerprint as CSV or JSON
+encrypt-duplicates = Duplicate Files
+ops-dismiss = Dismiss
+encrypt-duplicates-explained = These files of the last batch have the same contents, so each group was encrypted more than once:
+encrypt-start = 🔒 Encrypt
+encrypt-replace-originals = Replace original files instead
+encrypt-shred-originals = Overwrite the originals before deleting them
+encrypt-replace-originals-explained = Encrypted files are saved next to the originals, which are then deleted.
+decrypt-manifest-written = { $manifest } (written { $date }, key { $fingerprint })
+decrypt-manifest-intact = intact
+decrypt-manifest-changed = changed
+decrypt-manifest-missing = missing
+decrypt-viewed-file = { $file } (shredded in { $minutes } min)
+decrypt-title = Decrypt Files
+decrypt-select-files = Select Encrypted Files
+decrypt-select-file = Select Encrypted File
+decrypt-options = Decryption Options
+decrypt-key = Decryption Key:
+decrypt-reason-required = Reason for decryption (required):
+decrypt-reason = Reason for decryption:
+decrypt-reason-hint = e.g. Records request #1234
+decrypt-use-hardware = Use hardware decryption
+decrypt-progress = Decryption Progress
+decrypt-manifest-check = Manifest Check
+decrypt-viewed-files = Viewed Files
+decrypt-viewed-on-disk = No memory-backed folder was found; viewed files are on disk until shredded
+decrypt-viewed-done = Done
+decrypt-viewed-done-hint = Shred the decrypted copy now
+decrypt-start = 🔓 Decrypt
+decrypt-view-securely = View Securely
+decrypt-verify-manifest = Verify Against Manifest...
+
+## About
+
+about-title = About CRUSTy
+about-name = CRUSTy - Cryptographic Rust Utility
+about-version = Version 1.0.0
+about-description = Description
+about-description-1 = CRUSTy is a secure file encryption application using AES-256-GCM encryption.
+about-description-2 = It provides a user-friendly interface for encrypting and decrypting files.
+about-features = Features:
+about-feature-files = • Encrypt and decrypt individual files
+about-feature-batch = • Batch processing of multiple files
+about-feature-keys = • Key management (generation, saving, loading)
+about-feature-split-key = • Split-key functionality for enhanced security
+about-feature-recipients = • Recipient-specific encryption
+about-feature-logging = • Operation logging
+about-feature-progress = • Progress tracking
+about-feature-hardware = • Support for hardware encryption via embedded devices
+about-technical = Technical Details
+about-technical-rust = • Built with Rust and eframe/egui for the GUI
+about-technical-aes = • Uses AES-256-GCM for authenticated encryption
+about-technical-hkdf = • Implements HKDF for recipient-specific key derivation
+about-technical-backends = • Supports both software-based and hardware-based encryption backends
+about-technical-progress = • File operations are performed with progress tracking
+about-license = License
+about-license-mit = This software is licensed under the MIT License.
+about-copyright = Copyright © 2025 CRUSTy Team
+
+## Locked keys
+
+locked-title = 🔒 Keys Locked
+locked-explained = Your saved keys were locked, and text CRUSTy copied to the clipboard was cleared.
+locked-running = Operations already running continue.
+locked-unlock = Unlock Keys
+
+## Confirmations
+
+batch-warning-about-to =
+    You are about to { $operation ->
+        [decrypt] decrypt
+       *[encrypt] encrypt
+    } { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }, { $size } in total.
+batch-warning-over-limit = This is over the warning limit of { $size } GB or { $count } files.
+batch-warning-free-space = • Make sure the output directory has at least { $size } free
+batch-warning-duration = • Expect the operation to take about { $duration }
+batch-warning-title = Very Large Batch
+batch-warning-before = Before starting:
+batch-warning-memory = • Each file is read into memory in full, so close other programs before processing very large files
+batch-warning-split = • Files are processed one at a time; consider splitting the job into smaller batches
+batch-warning-settings = The limits can be changed in Settings.
+batch-warning-start = Start Anyway
+button-cancel = Cancel
+key-pin-warning-mismatch = The key '{ $name }' is pinned to { $folder }, but the selected key is '{ $selected }'.
+key-pin-warning-use-pinned = Use '{ $name }'
+key-pin-warning-title = Different Key Than Pinned
+key-pin-warning-explained = Files in this folder, or encrypted to it, are expected to be encrypted with the pinned key. Whoever they are for may not be able to open them.
+key-pin-warning-logged = Encrypting anyway is logged. Pins can be changed on the Settings screen.
+key-pin-warning-encrypt-anyway = Encrypt Anyway
+preflight-about-to =
+    You are about to { $operation ->
+        [decrypt] decrypt
+       *[encrypt] encrypt
+    } { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }, { $size } in total, with:
+preflight-note = Note: { $note }
+preflight-title = Ready to Start
+preflight-since-last = Since the last batch:
+preflight-turn-off = This summary can be turned off in Settings.
+preflight-start = Start
+
+## Settings screen
+
+settings-none = None
+settings-none-saved = None saved
+settings-theme-system = System
+settings-theme-light = Light
+settings-theme-dark = Dark
+settings-chunk-size-tuned = Tuned to the drive
+settings-device-over = { $device } over { $connection ->
+    [usb] USB
+    [serial] serial
+   *[ethernet] Ethernet
+}
+settings-title = Settings
+settings-explained = Saved as you change them, and used every time CRUSTy starts. More settings are in the Settings menu.
+settings-appearance = Appearance and Performance
+settings-theme = Theme:
+settings-chunk-size = Read files in chunks of:
+settings-chunk-size-hint = Tuned picks the size from how fast the drive reads. Encrypted files are the same whatever the size
+settings-startup = When CRUSTy Starts
+settings-output-dir = Output directory:
+settings-choose = Choose...
+settings-clear = Clear
+settings-file-selection = File selection:
+settings-multiple-files = Multiple files
+settings-embedded-device = Embedded device:
+settings-save-device = Save Current Device
+settings-save-device-hint = The device and connection settings entered on the main screen
+settings-use-device = Use the device for operations
+button-close = Close
+settings-theme-editor = Theme Editor
+settings-theme-editor-explained = Changes show at once. Save them as a theme to keep them.
+settings-theme-name = Name:
+settings-theme-name-hint = e.g. High contrast
+settings-save-theme = Save Theme
+settings-save-theme-hint = Replaces a saved theme of the same name
+settings-discard-theme = Discard Changes
+settings-delete-theme = Delete Saved Theme
+settings-key-pins = Key Pins
+settings-key-pins-explained = Encrypting files in a pinned folder, or to it, with another key asks for confirmation first.
+keys-pin-selected = Pin '{ $name }' to a Folder...
+keys-pin-selected-key = Pin the Selected Key to a Folder...
+
+## Dashboard
+
+dashboard-file-missing = { $file }: missing
+dashboard-title = CRUSTy Dashboard
+dashboard-subtitle = Secure file encryption with AES-256-GCM
+dashboard-integrity-failed = ⚠ Secured folder integrity check failed
+dashboard-integrity-explained = The failures are in the logs. Restore the files from a backup if they were not removed on purpose.
+dashboard-encryption = Encryption
+dashboard-encryption-explained = Encrypt files with AES-256-GCM
+dashboard-encrypt = 🔒 Encrypt Files
+dashboard-decryption = Decryption
+dashboard-decryption-explained = Decrypt previously encrypted files
+dashboard-decrypt = 🔓 Decrypt Files
+dashboard-main-screen = Go to Main Screen
+dashboard-integrity-report = { $folder }: { $failed ->
+    [one] { $failed } file
+   *[other] { $failed } files
+} failed verification, { $missing } missing
+dashboard-dismiss-hint = Missing files are no longer looked for; damaged files are reported again by the next check
+dashboard-history = Recent Activity
+dashboard-history-empty = Files you encrypt or decrypt are listed here.
+dashboard-encrypt-again = Encrypt Again
+dashboard-decrypt-again = Decrypt Again
+dashboard-upgraded = Upgraded
+dashboard-again-hint = Select this file again, with the same output folder
+dashboard-open-output = Open Output Folder
+dashboard-clear-history = Clear History
+
+## Logs
+
+logs-read-error = Error reading log file: { $error }
+logs-no-file = No log file found.
+logs-title = Operation Logs
+logs-location = Log file location:
+logs-open-dir = Open Log Directory
+logs-recent = Recent Logs
+logs-refresh = Refresh Logs
+logs-clear = Clear Logs
+logs-compliance-report = Compliance Report
+logs-filter-hint = Filter by operation, file, or message
+logs-failures-only = Failures only
+logs-export = Export Logs...
+logs-export-hint = Save the entries shown as CSV, JSON, or text (by the file name's extension)
+
+## Smartcard PIN
+
+pin-prompt-enter = Enter the PIN for smartcard '{ $card }' to use key '{ $name }'.
+pin-prompt-incorrect =
+    Incorrect PIN ({ $count ->
+        [one] { $count } failed attempt
+       *[other] { $count } failed attempts
+    })
+pin-prompt-title = Smartcard PIN
+pin-prompt-final-try = Final attempt: one more incorrect PIN will lock the card.
+pin-prompt-count-low = An incorrect PIN has been entered on this card before.
+pin-prompt-pin = PIN
+pin-prompt-unlock = Unlock
+
+## Network proxy
+
+proxy-title = Network Proxy
+proxy-explained = The proxy is used for relay links and central logging.
+proxy-system = Use system proxy settings
+proxy-manual = Manual proxy
+proxy-none = No proxy
+proxy-address = Address:
+proxy-user = User name:
+proxy-optional = Optional
+proxy-password = Password:
+proxy-unchanged = Unchanged
+proxy-clear-password = Remove the saved password
+button-save = Save
+
+## Audit
+
+audit-differs-at = The decrypted data differs from the original at byte { $offset }
+audit-bytes = { $count ->
+    [one] { $count } byte
+   *[other] { $count } bytes
+}
+audit-format-key = Format: { $format }    Key: { $fingerprint }
+audit-failed = Failed: { $error }
+audit-title = Audit Encrypted Files
+audit-key = Key:
+audit-choose-key = Choose a key
+audit-compare = Compare with Original
+audit-compare-explained = Check that an encrypted file decrypts to exactly its original. Nothing decrypted is written.
+audit-original-file = Original file:
+audit-encrypted-file = Encrypted file:
+audit-auditing = Auditing...
+audit-start = Audit
+audit-match = MATCH: the encrypted file holds exactly the original
+audit-mismatch = MISMATCH: the encrypted file does not hold the original
+audit-original = Original
+audit-encrypted = Encrypted
+audit-decrypted = Decrypted
+audit-reproduced = Encrypting the original again with the file's nonce reproduces the encrypted data exactly.
+audit-not-reproduced = Encrypting the original again with the file's nonce does not reproduce the encrypted data.
+audit-cannot-reproduce = This format cannot be reproduced; only the decrypted data was compared.
+audit-save-report = Save Report...
+audit-verify-only = Verify Only
+audit-verify-explained = Check that encrypted files, such as backups, are intact and open with the key. Nothing decrypted is written.
+audit-add-files = Add Files...
+audit-add-folder = Add Folder...
+audit-no-files = No files added
+audit-authentic = Authentic
+audit-verify = Verify
+
+## Catalog
+
+catalog-file-count = { $count ->
+    [one] { $count } file
+   *[other] { $count } files
+}
+catalog-id = ID
+catalog-name = Name
+catalog-size = Size
+catalog-modified = Modified
+catalog-key = Key
+catalog-title = Export Catalog
+catalog-explained = List the encrypted files of a folder, to share with collaborators so they can ask for specific files. Nothing decrypted is written.
+catalog-folder = Folder:
+catalog-names = Names:
+catalog-make = Make Catalog
+catalog-save = Save Catalog...
+catalog-locations-not-saved = The folder and the locations of the files are shown here only; they are not saved in the catalog.
+catalog-no-saved-key = No saved key
+catalog-find-by-id = Find a requested file by ID:
+catalog-show-in-folder = Show in Folder
+catalog-no-such-id = No file has this ID
+
+## Benchmark
+
+benchmark-title = Benchmark Backends
+benchmark-explained = Encrypt random data of several sizes with software encryption and the embedded device, to see which is faster for your files.
+benchmark-sizes = Buffer sizes:
+benchmark-include-device = Include the embedded device
+benchmark-device = { $device } ({ $connection }), with the settings of the main screen
+benchmark-no-device = Set up the device on the main screen first
+benchmark-run = Run Benchmark
+benchmark-running = Benchmarking...
+benchmark-stop = Stop
+benchmark-size = Size
+benchmark-software = Software
+benchmark-hardware = Device
+benchmark-latency = Latency
+benchmark-faster = Faster
+
+## Cold-storage export
+
+cold-storage-shares-of = { $needed } of { $total }
+cold-storage-wrote = Wrote { $folder }
+cold-storage-key-fingerprint = Key fingerprint: { $fingerprint }
+cold-storage-title = Cold-Storage Export
+button-previous = ← Previous
+button-next = Next →
+cold-storage-step-folder = Step 1: Choose the Folder
+cold-storage-folder-explained = The folder and its subfolders are packed into one encrypted archive, split into volumes.
+cold-storage-select-folder = Select Folder...
+cold-storage-no-folder = No folder selected
+cold-storage-label = Label:
+cold-storage-label-hint = e.g. Photos 2020
+cold-storage-label-explained = The label names the set's folder and volume files; write it on every disc.
+cold-storage-write-to = Write Set To...
+cold-storage-step-media = Step 2: Choose the Media
+cold-storage-media-explained = Each volume fits on one disc of this size, with its parity and the manifest.
+cold-storage-custom-size = Custom size
+cold-storage-parity-explained = Parity on every volume repairs scratches and unreadable sectors; choose archival-grade discs for long storage.
+cold-storage-step-shares = Step 3: Recovery Shares
+cold-storage-shares-explained = The set is encrypted with a new key of its own, which is only kept as shares on the recovery sheet.
+cold-storage-shares-custodians = Give the shares to different custodians, or keep them in different places.
+cold-storage-shares-needed = Shares needed to restore:
+cold-storage-shares-printed = Shares printed:
+cold-storage-shares-too-few = At least as many shares must be printed as are needed
+cold-storage-step-write = Step 4: Write the Set
+cold-storage-media = Media:
+cold-storage-recovery-shares = Recovery shares:
+cold-storage-volume = Volume
+cold-storage-burn = 1. Print MANIFEST.txt, and burn each "Volume" folder to its own disc.
+cold-storage-hand-out = 2. Print RECOVERY-SHEET.txt and hand out the shares, then delete the sheet.
+cold-storage-open-set = Open Set Folder
+cold-storage-delete-sheet = Delete Recovery Sheet
+cold-storage-start-another = Start Another Set
+cold-storage-sheet-warning = The recovery sheet holds the set's key: do not burn it with the volumes.
+cold-storage-write = Write Cold-Storage Set
+
+## Cold-storage restore
+
+restore-folder-files = { $folder } ({ $count ->
+    [one] { $count } file
+   *[other] { $count } files
+})
+restore-enter-shares = Enter { $count } shares from the recovery sheet (text or phrase). Several shares pasted into one field are split into their own fields.
+restore-share-number = Share { $number }:
+restore-restored = Restored { $folder }
+restore-files-restored = { $restored } of { $total } restored
+restore-archive-matches = { $size }, SHA-256 matches the manifest
+restore-key-matches = { $fingerprint } matches the manifest
+restore-title = Cold-Storage Restore
+restore-step-find = Step 1: Find the Volumes
+restore-find-explained = Open the manifest from any disc of the set. The volumes are looked for next to it, and in the folders added below.
+restore-open-manifest = Open Manifest...
+restore-no-manifest = No manifest opened
+restore-set = Set:
+restore-written = Written:
+restore-folders-searched = Folders searched (with their subfolders):
+restore-add-disc = Add Disc Folder...
+restore-not-found = Not found
+restore-step-check = Step 2: Check the Volumes
+restore-check-explained = Each volume is read, repaired with its parity where needed, and compared with the manifest.
+restore-add-missing = Add the missing discs on the previous step, or copy a good volume from another copy of the set.
+restore-check = Check Volumes
+restore-step-key = Step 3: Rebuild the Key
+restore-key-rebuilt = The set's key has been rebuilt and matches the manifest.
+restore-enter-passphrase = The set was encrypted with a passphrase key. Enter its passphrase:
+restore-share-hint = Enter or paste a share here...
+restore-add-share-field = Add Share Field
+restore-rebuild-key = Rebuild Key
+restore-step-restore = Step 4: Restore the Folder
+restore-files = Files:
+restore-archive = Archive:
+restore-repaired = Some volumes were repaired: their discs are decaying. Write the set to new media.
+restore-open-folder = Open Restored Folder
+restore-another = Restore Another Set
+restore-to = Restore To...
+restore-start = Restore Set
+
+## Key agreement
+
+agreement-waiting = Waiting for the partner to connect on port { $port }...
+agreement-connecting = Connecting to { $address }...
+agreement-partner-computer = Partner's computer: { $address }
+agreement-recognised = Recognised as { $name } (checked { $date })
+agreement-address-hint = host or host:port (default { $port })
+agreement-saved-partner = { $name } (checked { $date })
+agreement-title = Partner Key Agreement
+agreement-about = About Key Agreement
+agreement-about-1 = Two CRUSTy computers can agree a transfer key directly, with no shares to send.
+agreement-about-2 = One computer waits for the partner; the other connects to it.
+agreement-about-3 = Both then show the same six-digit code: compare it over the phone before saving the key.
+agreement-about-4 = A partner saved after a comparison is recognised next time, and needs no code.
+agreement-offline = Key agreement is unavailable in offline mode
+agreement-agreed-key = Agreed Key
+agreement-save-key = Save Key
+agreement-read-code = Read this code to the partner. Their computer must show the same code:
+agreement-partner-name = Partner's name:
+agreement-partner-name-hint = e.g. Branch Office
+agreement-codes-match = Codes Match — Save Key
+agreement-codes-differ = Codes Differ
+agreement-wait = Wait for the Partner
+agreement-firewall = The partner connects to this computer's address and port; allow it through the firewall.
+agreement-wait-button = Wait for Partner
+agreement-connect-heading = Connect to the Partner
+agreement-partner-address = Partner's address:
+agreement-connect = Connect
+agreement-saved-partners = Saved Partners
+agreement-no-partners = No partners saved yet
+agreement-forget = Forget
+agreement-forget-hint = Compare the codes again in the next agreement
+agreement-back = Back to Key Management
+
+## Key rotation
+
+rotation-outcome = { $rotated } re-encrypted, { $already } already used the new key, { $failed } failed
+rotation-old-key-is = Old key: { $fingerprint }
+rotation-new-key-is = New key: { $fingerprint }
+rotation-will-rotate = { $count ->
+    [one] { $count } file
+   *[other] { $count } files
+} will be decrypted with the old key and encrypted with the new key, in memory.
+rotation-title = Rotate Key
+rotation-step-files = Step 1: Choose the Files
+rotation-files-explained = Add the .encrypted files and .crusty archives to move to the new key. Each file is replaced where it is.
+rotation-step-keys = Step 2: Choose the Keys
+rotation-old-key = Old key:
+rotation-new-key = New key:
+rotation-generate = Generate a new key
+rotation-new-key-name = New key's name:
+rotation-new-key-name-hint = e.g. Finance 2026
+rotation-generation-disabled = Key generation is disabled by policy; choose a saved key as the new key.
+rotation-keys-same = The new key must differ from the old key.
+rotation-generated-on-start = The new key is generated and saved when the rotation starts. Back it up before retiring the old key.
+rotation-step-reencrypt = Step 3: Re-encrypt the Files
+rotation-complete = Every file now uses the new key. Once backups made with the old key are rotated too, the old key can be removed.
+rotation-failed-keep-old = The failed files still use the old key: keep it until they are re-encrypted.
+rotation-retry = Retry
+rotation-more = Rotate More Files
+rotation-checked-first = Each file is replaced only after it has been checked with the new key; no decrypted copy is written.
+rotation-start = Rotate Key
+
+## Secured folders
+
+folders-no-key = no key selected
+folders-current-key = Folders without a key of their own are locked with the current key ({ $name }). Locked folders are unlocked with the key they were locked with.
+folders-partly-locked = Partly locked: { $locked } of { $total } files encrypted. Lock or unlock again to finish.
+folders-add = Add Folder
+folders-refresh = Refresh
+folders-check = Check Integrity
+folders-check-hint = Verify the files of the locked folders with their keys, and look for missing files
+folders-none = No secured folders. Add a folder to keep its files encrypted.
+folders-locked = Locked
+folders-unlocked = Unlocked
+folders-empty = No files
+folders-missing = Folder not found
+folders-lock = Lock
+folders-lock-all = Lock All
+folders-unlock-all = Unlock All
+folders-lock-all-hint = Lock every folder that is not locked, one after the other
+folders-unlock-all-hint = Unlock every locked folder, one after the other
+folders-key = Key:
+folders-key-current = Current key
+folders-key-missing = Key not saved
+folders-key-hint = Always lock this folder with this key, whatever key is selected
+folders-queued = Waiting for the other folders…
+
+## Watched folders
+
+watch-heading = Watched Folders
+watch-add = Watch a Folder…
+watch-add-hint = Encrypt each new file put in a folder into another folder, with the current key
+watch-none = No watched folders. New files in a watched folder are encrypted automatically while CRUSTy runs.
+watch-paused = The keys are locked: the folders are not watched until they are unlocked.
+watch-key = Key: { $name }
+watch-activity = Recent activity
+watch-encrypted = { $time }: { $file } encrypted to { $output }
+watch-failed = { $time }: { $file }: { $error }
+
+## Split keys
+
+split-title = Split-Key Management
+split-about = About Split-Key Encryption
+split-about-1 = Split-key encryption divides your encryption key into multiple shares.
+split-about-2 = You need a minimum number of shares to reconstruct the key.
+split-about-3 = This provides enhanced security through multi-party authorization.
+split-scheme = CRUSTy uses a 2-of-3 scheme:
+split-primary = • Primary Share: Stored in your OS credential store
+split-secondary = • Secondary Share: Stored as a file in a location you choose
+split-recovery = • Recovery Share: Generated as a QR code for you to print or save
+split-any-two = You need any 2 of these 3 shares to decrypt your files.
+split-create = Create Split Key
+split-select-key-first = You need to select or create a key first
+split-custodians = Share Custodians
+split-share = Share
+split-custodian = Custodian
+split-contact = Contact
+split-storage = Storage Location
+split-save-custodians = Save Custodians
+split-export-report = Export Ceremony Report
+split-reconstruct = Reconstruct Key from Shares
+split-reconstruct-need = To reconstruct your key, you need:
+split-reconstruct-primary = 1. Your primary share (automatically retrieved from OS credential store)
+split-reconstruct-other = 2. Either your secondary share file OR your recovery share QR code
+split-select-secondary = Select Secondary Share File
+split-scan-qr = Scan Recovery Share QR Code
+
+## Transfer
+
+transfer-key-fingerprint = Key fingerprint: { $fingerprint } (the recipient sees the same fingerprint once the key is reconstructed)
+transfer-threshold = Threshold: { $needed } of { $total } shares needed
+transfer-bound-to = Bound to { $binding }: send at least one share saved, copied, or relayed with its seal line.
+transfer-share-number = Share { $number }
+transfer-hide-mnemonic = Hide Mnemonic
+transfer-view-mnemonic = View as Mnemonic
+transfer-hide-dictation = Hide Dictation
+transfer-read-out = Read Out by Phone
+transfer-shares-needed = { $count } shares are needed. Several shares pasted into one field are split into their own fields.
+transfer-sealed = ✓ Sealed shares of transfer package { $package } ({ $needed } of { $total } shares needed)
+transfer-groups-checked = { $checked } of { $total } groups checked
+transfer-word-number = Word { $number }
+transfer-words = { $words } of { $total } words
+transfer-title = Prepare for Transfer
+transfer-about = About Secure Transfer
+transfer-about-1 = This feature helps you securely transfer encrypted files to others.
+transfer-about-2 = It creates a special transfer key that is split into multiple shares.
+transfer-about-3 = You send different shares through different channels for security.
+transfer-process = The process works like this:
+transfer-process-1 = 1. Select a file to encrypt for transfer
+transfer-process-2 = 2. Create a transfer package with multiple key shares
+transfer-process-3 = 3. Send the encrypted file through one channel
+transfer-process-4 = 4. Send key shares through different channels
+transfer-process-5 = 5. The recipient needs the file and enough shares to decrypt
+transfer-about-4 = This provides enhanced security for out-of-band transfers.
+transfer-create = Create Transfer Package
+transfer-bind = Bind the package to its recipient:
+transfer-not-bound = Not bound
+transfer-passphrase = Passphrase
+transfer-identity = Machine identity
+transfer-passphrase-label = Passphrase:
+transfer-passphrase-channel = Agree the passphrase with the recipient through yet another channel.
+transfer-identity-label = Recipient's machine identity:
+transfer-identity-hint = Base64, from the recipient's Key Management screen
+transfer-shares = Transfer Shares
+transfer-save-share = Save Share
+transfer-dictation-hint = Show the share as numbered groups, each with a check character
+transfer-send-relay = Send via Relay
+transfer-offline = Unavailable in offline mode
+transfer-share-error = Error retrieving share
+transfer-one-time-link = One-time link (send it through a different channel than the file):
+transfer-copy = Copy
+transfer-receive-title = Receive Transfer
+transfer-receive-about = About Receiving Transfers
+transfer-receive-about-1 = This feature helps you decrypt files that were sent to you.
+transfer-receive-about-2 = You'll need to enter the key shares you received.
+transfer-receive-about-3 = Once you have enough shares, you can reconstruct the key and decrypt the file.
+transfer-receive-process-1 = 1. Enter the key shares you received
+transfer-receive-process-2 = 2. Reconstruct the encryption key
+transfer-receive-process-3 = 3. Decrypt the file you received with the key
+transfer-enter-shares = Enter Key Shares
+transfer-share-hint = Enter or paste a key share here...
+transfer-dictation = Enter Share by Dictation
+transfer-dictation-explained = Type each group as it is read out: its characters, then its check character.
+transfer-use-dictated = Use Dictated Share
+transfer-load-share = Load Share from File
+transfer-import-scan = Import Share from Scan...
+transfer-import-scan-hint = Read a share from a photo or scan of its printed card
+transfer-relay-link = Relay link:
+transfer-relay-link-hint = Paste a one-time link...
+transfer-fetch-share = Fetch Share
+transfer-bound-passphrase = This package is bound to a passphrase. Enter the passphrase agreed with the sender to unwrap the key:
+transfer-bound-identity = This package is bound to a machine identity. The key is unwrapped with this machine's identity.
+transfer-reconstruct = Reconstruct Key
+transfer-decrypt-received = Decrypt the Received File
+transfer-decrypt-received-explained = Pick the encrypted file you received. It is decrypted next to itself with the transfer key, and its folder opens when it is done.
+transfer-select-received = Select Encrypted File to Decrypt...
+transfer-valid-phrase = ✓ Valid share phrase
+
+## Encryption workflow
+
+workflow-files-to-encrypt =
+    Files to encrypt: { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }
+workflow-encryption-key = Encryption key: { $name }
+workflow-recipients = Recipients: { $recipients }
+workflow-backend =
+    Backend: { $backend ->
+        [hardware] Hardware
+       *[software] Software
+    }
+workflow-estimated-time = Estimated time: about { $duration }
+workflow-time-remaining = Time remaining: about { $duration }
+workflow-title = Encryption Workflow
+workflow-finish = Finish
+workflow-step-files = Step 1: Select Files
+workflow-step-key = Step 2: Select Encryption Key
+workflow-create-key = Create New Key
+workflow-key-name = Key Name:
+workflow-key-name-hint = Enter a name for the new key
+workflow-generate-key = Generate Key
+workflow-select-existing = Select Existing Key
+workflow-no-saved-keys = No saved keys available
+workflow-select-a-key = Select a key
+workflow-load-key = Load Key from File
+workflow-step-options = Step 3: Encryption Options
+workflow-recipient-options = Recipient Options
+workflow-file-names = File Names
+workflow-random-names = Files are saved under random names; the original names are restored on decryption.
+workflow-backend-heading = Encryption Backend
+workflow-device-id = Device ID:
+workflow-hardware-explained = Hardware encryption offloads cryptographic operations to a dedicated device.
+workflow-software-explained = Software encryption uses your computer's CPU for cryptographic operations.
+workflow-step-execute = Step 4: Execute Encryption
+workflow-summary = Encryption Summary
+workflow-replaced = Output: the original files are replaced
+workflow-estimate-unknown = Estimated time: not yet known (no earlier operations with this backend)
+workflow-start = 🔒 Start Encryption
+
+## Key management
+
+keys-passphrase-hint = At least { $count } characters
+keys-kept-in = Your keys are kept encrypted in { $path }
+keys-expired-on = { $date } (expired)
+keys-backups-on = The saved keys are backed up after every change (the last { $count } backups are kept).
+keys-change-passphrase = Change Passphrase
+keys-turn-on-backups = Turn On Backups
+keys-backup-number = { $number ->
+    [1] Backup 1 (newest)
+   *[other] Backup { $number }
+}
+keys-verify-every = Custodians should verify their shares every { $days } days.
+keys-never = Never
+keys-protection-no-tpm = Identity protection: { $protection } (no TPM found)
+keys-protection = Identity protection: { $protection }
+keys-title = Key Management
+keys-or-passphrase = Or derive the key from a passphrase (no key file to keep):
+keys-confirm = Confirm:
+keys-derive = Derive Key
+keys-derive-for-file = Derive Key for File...
+keys-derive-for-file-hint = Derive the key of a file encrypted with a passphrase key (the confirmation is not needed)
+keys-disabled = Key management is disabled by policy. Only pre-provisioned keys are available.
+keys-saved = Saved Keys
+keys-not-saved = Keys are not saved: they are kept until CRUSTy is closed
+keys-none-saved = No saved keys. Create a new key or load one from a file.
+keys-key-name = Key Name
+keys-fingerprint = Fingerprint
+keys-fingerprint-hint = Compare fingerprints to check that you and someone else hold the same key
+keys-created = Created
+keys-expires = Expires
+keys-last-used = Last Used
+keys-status = Status
+keys-actions = Actions
+keys-provenance-not-recorded = Provenance not recorded
+keys-unknown = Unknown
+keys-current = Current
+keys-credential-store = Credential Store
+keys-saved-status = Saved
+keys-delete = Delete
+keys-expiry = Key Expiry
+keys-expires-on = Expires on:
+keys-expires-on-hint = YYYY-MM-DD (empty: never)
+keys-set-expiry = Set Expiry
+keys-expired-still-decrypt = Expired keys can still decrypt; CRUSTy warns when one is selected for encryption.
+keys-credential-store-explained = Checked keys are kept in the OS credential store and never written to files; the saved keys only refer to them by name.
+keys-provenance = Key Provenance
+keys-provenance-old = Provenance not recorded (saved before provenance was kept)
+keys-import-scan = Import Key from Scan...
+keys-import-scan-hint = Read a key from the QR code in a photo or scan of its printed sheet
+keys-import-age = Import age Identities...
+keys-import-age-hint = Add the identities in an age identity file (e.g. from age-keygen) as keys
+keys-your-age = Your age Recipients
+keys-your-age-explained = Give a key's recipient to people who encrypt files for you with age.
+keys-export-identity = Export Identity
+keys-export-identity-hint = Save the key as an age identity file, for age -d -i
+keys-other-age = Other age Recipients
+keys-name-hint = Name
+keys-your-openpgp = Your OpenPGP Keys
+keys-your-openpgp-explained = Give a key's public key to people who encrypt files for you with GnuPG.
+keys-export-public = Export Public Key
+keys-export-public-hint = Save the key's OpenPGP public key, for gpg --import
+keys-other-openpgp = Other OpenPGP Keys
+keys-import-openpgp = Import OpenPGP Key...
+keys-import-openpgp-hint = Add a public key exported with gpg --export --armor
+keys-backups = Key Backups
+keys-backups-off = Back up the saved keys after every change, encrypted with a backup passphrase:
+keys-backup-passphrase = Backup passphrase:
+keys-no-backups = No backups yet.
+keys-select-backup = Select a backup and enter its passphrase (the confirmation is not needed) to restore it:
+keys-restore = Restore
+keys-restore-hint = Replace the saved keys with the keys in the selected backup
+keys-smartcard = Smartcard Keys
+keys-smartcard-explained = These keys are unlocked with the smartcard PIN when files are decrypted.
+keys-smartcard-heading = Smartcard
+keys-advanced = Advanced Key Operations
+keys-split = Split Key Management
+keys-transfer = Transfer Preparation
+keys-share-backups = Key Share Backups
+keys-last-verified = Last Verified
+keys-verification-due = Verification due
+keys-ok = OK
+keys-paste-share = Paste a share (text, word phrase, or scanned QR code contents) to verify it:
+keys-verify-share = Verify Share
+keys-verify-scan = Verify from Scan...
+keys-verify-scan-hint = Read the share from a photo or scan of its printed card
+keys-deployment = Deployment
+keys-deployment-explained = Administrators need this machine's identity to create a deployment bundle for it.
+keys-show-identity = Show Machine Identity
+keys-target-identity = Target machine identity:
+keys-create-bundle = Create Deployment Bundle
+
+## File list
+
+files-in-progress = In Progress: { $percent }
+files-unknown = Unknown file
+files-pending = Pending
+files-completed = Completed
+files-failed = Failed
+files-waiting = Waiting
+files-title = File List
+files-file = File
+files-algorithm = Algorithm
+files-time = Time
+files-none = No files in the list. Use the Open button to select files.
+files-clear-all = Clear All
+
+## Recipients
+
+recipients-count =
+    Each file is encrypted once for { $count ->
+        [one] { $count } recipient
+       *[other] { $count } recipients
+    } and your key. Email recipients need the same key; age recipients decrypt with their own.
+recipients-age-choice = Encrypted to the selected key's age recipient, and to:
+recipients-no-age = No other age recipients saved (add them under Manage Keys)
+recipients-list = Email addresses and age recipients (age1...), separated by commas or new lines:
+recipients-add-saved = Add saved:
+recipients-openpgp-choice = Encrypted to the selected key's OpenPGP key, and to:
+recipients-no-openpgp = No OpenPGP keys saved (import them under Manage Keys)
+
+## Progress
+
+device-progress = Device: { $done }/{ $total } chunks processed, { $queued } queued
+duplicate-scan-progress = Checking the files for duplicates: { $percent }
+progress-resume = ▶ Resume
+progress-paused = Paused
+progress-pause = ⏸ Pause
+progress-pause-hint = Pause after the current file is read or finished; nothing is left half written
+files-no-files = No files in the list
+
+## Menu
+
+menu-protect-shares-hint = Cover shown shares until "Hold to Reveal" is held, and keep the window out of screenshots and screen sharing while shares are shown
+menu-idle-minutes = idle minutes
+menu-lock-idle-hint = 0 never locks the keys when idle
+menu-minutes = minutes
+menu-shred-viewed-hint = Files opened with "View Securely" are also shredded when the keys lock or CRUSTy closes
+menu-hours = hours
+menu-check-folders-hint = Verifies the files of locked secured folders and alerts if any is damaged or missing; 0 never checks
+menu-extension-hint = Extension, e.g. docx
+menu-view-securely-hint = Other files are only selected
+menu-seconds-device = s (device)
+menu-seconds-software = s (software)
+menu-stop-hint = 0 never stops them. Unfinished files of a stopped operation are marked as failed
+menu-low-memory-hint = For machines with little memory. age files are processed in chunks; files in other formats and folder archives that would need more memory are refused
+menu-preflight-summary-hint = Show the key, backend, destination and options of a batch, and what changed since the last one, before it starts
+menu-files = files
+menu-log-format = Operation log format
+menu-log-format-hint = Structured writes one JSON object per entry with an RFC 3339 timestamp, operation, file, key fingerprint, duration, and outcome, for SIEM tools
+menu-system-log = Send log entries to the system log
+menu-system-log-hint = Also send each entry to syslog (Unix) or the Windows Event Log (Application log, source CRUSTy), where log collectors can pick it up
+menu-system-log-required = The admin policy sends log entries to the system log
+menu-rotate-log-at = Start a new operation log at
+menu-rotate-log-hint = The full log is kept as operations.log.1, the one before as operations.log.2, and so on; 0 keeps one log that grows without limit
+menu-keep-old-logs = Keep
+menu-keep-old-logs-for = for
+menu-days = days
+menu-keep-old-logs-hint = The oldest logs over the number kept are deleted; 0 days keeps them however old they are
+menu-compress-old-logs = Compress old logs (gzip)
+menu-control-api-hint = Let other programs on this computer queue encrypt and decrypt jobs, with the token in control_api.json
+menu-offline-hint = Network features are turned off (Settings > Offline mode)
+
+## Tray icon
+
+menu-tray-icon = Show icon in the system tray
+menu-minimize-to-tray = Minimize to the tray
+menu-minimize-to-tray-hint = Hide the window when it is minimized; open it again from the tray icon
+menu-tray-unavailable = The system tray is not available on this desktop
+tray-open = Open CRUSTy
+tray-encrypt-clipboard = Encrypt Clipboard
+tray-exit = Exit
+tray-progress =
+    CRUSTy: { $operation ->
+        [decrypt] decrypting
+       *[encrypt] encrypting
+    }, { $percent } ({ $done } of { $total ->
+        [one] { $total } file
+       *[other] { $total } files
+    })
+
+## Status and error messages
+
+error-select-files = Please select files, output directory, and encryption key
+error-decrypt-reason = Please enter a reason for decryption
+error-passphrases-differ = The passphrases do not match
+error-no-key-selected = No key selected
+error-generation-disabled = Key generation is disabled by policy
+error-import-disabled = Key import is disabled by policy
+error-export-disabled = Key export is disabled by policy
+error-management-disabled = Key management is disabled by policy
+error-file-not-found = File not found: { $path }
+error-in-file = { $path }: { $error }
+error-machine-identity = Failed to get machine identity: { $error }
+error-key-in-credential-store = Key '{ $name }' is kept in the credential store and is not saved to files
+error-select-key-encrypted-with = Please select the key the files are encrypted with
+error-settings-save = Failed to save settings: { $error }
+error-clipboard-no-key = Please select the key to encrypt the clipboard with
+error-clipboard-open = Failed to open the clipboard: { $error }
+error-clipboard-empty = The clipboard holds no text to encrypt
+error-clipboard-encrypted = The clipboard text is already encrypted
+status-clipboard-encrypted = Clipboard text encrypted (decrypt it with crusty --decrypt-stream or age -d)
+error-clipboard-encrypt = Failed to encrypt the clipboard: { $error }
+error-theme-name = Please enter a name for the theme
+status-theme-saved = Theme "{ $name }" saved
+status-theme-deleted = Theme "{ $name }" deleted
+status-offline-on = Offline mode on: network features are turned off
+status-offline-off = Offline mode off
+error-system-log-open = Failed to open the system log: { $error }
+status-watch-encrypted = Encrypted new file { $path }
+error-watch-no-key = Please select the key to encrypt the folder's new files with
+error-watch-unsaved-key = Please select a saved key; the folder is watched with it whenever CRUSTy runs
+error-watch-already = { $folder } is already watched
+status-watch-started = Watching { $folder } for new files
+status-proxy-saved = Proxy settings saved
+error-proxy-apply = Failed to apply proxy settings: { $error }
+error-embedded-settings = Invalid embedded device settings: { $error }
+error-pinned-key-missing = The pinned key '{ $name }' is not saved on this computer
+error-pin-no-key = Please select the key to pin
+status-key-pinned = Key '{ $name }' pinned to { $folder }
+status-partner-forgotten = Forgot partner { $name }
+error-select-new-key = Please select the new key
+error-new-key-name = Please enter a name for the new key
+error-select-manifest-key = Please select the key the files were encrypted with
+error-view-selection = Please select one file and the key it was encrypted with
+error-select-audit-key = Please select the key the file is encrypted with
+error-deploy-no-keys = No keys to deploy
+status-deployment-saved = Deployment bundle saved to: { $path }
+error-deployment-create = Failed to create deployment bundle: { $error }
+error-key-name = Please enter a name for the key
+error-file-passphrase = Please enter the passphrase the file was encrypted with
+error-expiry-key = Please choose the key to set the expiry date of
+error-expiry-date = Please enter the expiry date as YYYY-MM-DD
+error-select-backup = Please select a backup to restore
+error-backup-passphrase = Please enter the backup passphrase
+error-operation-running = Wait for the running operation to finish
+error-transfer-key-first = Reconstruct the transfer key first
+error-output-exists = { $path } already exists; move it away and try again
+status-decrypted-to = Decrypted to { $path }
+error-received-decrypt = The received file could not be decrypted with the transfer key; see the logs for details
+error-secured-folder-gone = The secured folder no longer exists
+error-secured-folder-bound-key = { $folder } is bound to key { $fingerprint }, which is not among the saved keys
+error-secured-folder-locked-key = { $folder } is locked with key { $fingerprint }, which is not among the saved keys
+error-secured-folder-lock-key = Please select the key to lock the folder with
+error-secured-folder-unlock-key = Please select the key the folder was locked with
+status-secured-all-locked = Every secured folder is locked
+status-secured-all-unlocked = Every secured folder is unlocked
+error-secured-skipped =
+    { $count ->
+        [one] { $count } folder
+       *[other] { $count } folders
+    } skipped: { $error }
+status-key-saved-to = Key saved to: { $path }
+error-key-save = Failed to save key: { $error }
+status-key-loaded = Loaded key: { $name }
+error-key-load = Failed to load key: { $error }
+error-key-file-read = Failed to read key file: { $error }
+status-age-identity-saved = age identity saved to: { $path }
+error-age-identity-save = Failed to save age identity: { $error }
+status-age-identities-imported =
+    Imported { $count ->
+        [one] { $count } age identity
+       *[other] { $count } age identities
+    }
+error-age-identities-import = Failed to import age identities: { $error }
+error-age-recipient-name = Please enter who the age recipient belongs to
+error-age-recipient-saved = This age recipient is already saved
+status-age-recipient-saved = Saved age recipient: { $name }
+status-openpgp-key-saved-to = OpenPGP public key saved to: { $path }
+error-openpgp-key-save = Failed to save OpenPGP public key: { $error }
+error-openpgp-key-saved = This OpenPGP key is already saved
+status-openpgp-key-saved = Saved OpenPGP key: { $user } ({ $fingerprint })
+error-openpgp-key-import = Failed to import OpenPGP key: { $error }
+status-share-scanned-qr = Share read from the QR code
+status-share-scanned-text = Share read from the printed text: compare it with the paper before using it
+error-scan-key-not-share-import = This is a key, not a key share; import it on the key management screen
+error-scan-key-not-share = This is a key, not a key share
+status-key-scanned = Key '{ $name }' read from the QR code and selected
+error-scan-share-not-key = This is a key share; enter it on the Receive Transfer screen to reconstruct the key
+error-select-files-and-key = Please select files and encryption key
+status-key-management = Key management
+status-advanced-options = Advanced options
+status-main-screen = Main screen
+error-policy-load = The admin policy could not be read, so restricted defaults apply: { $error }
+error-old-logs-delete = Failed to delete old logs: { $error }
+error-select-files-decrypt = Please select files, output directory, and decryption key
+status-pin-cancelled = Smartcard unlock cancelled
+status-qr-scan-unavailable = QR code scanning not implemented in this version
+status-watch-stopped = No longer watching { $folder }
+status-workflow-started = Starting encryption workflow
+status-decryption-started = Starting decryption
+status-switched-to-main = Switched to main screen
+status-identity-copied = Machine identity copied to clipboard
+error-select-files-and-output = Please select files and output directory
+error-select-or-create-key = Please select or create an encryption key
+error-previous-steps = Please complete all previous steps
+error-rotation-files = Please add the files to re-encrypt
+error-rotation-keys = Please choose the old key, and a different new key (or a name for a new one)
+error-cold-storage-choices = Please choose the folder, a label, and where to write the set
+error-cold-storage-step = Please choose valid options for this step
+error-restore-manifest = Please open the set's manifest
+error-restore-volumes = Every volume must be found and readable to restore the set
+error-restore-key = Please rebuild the set's key first
+status-logs-refreshed = Logs refreshed
+error-logs-clear = Failed to clear logs: { $error }
+status-logs-cleared = Logs cleared successfully
+error-logger-missing = Logger not initialized
+status-compliance-report-saved =
+    Compliance report saved with { $count ->
+        [one] { $count } decryption entry
+       *[other] { $count } decryption entries
+    }
+error-compliance-report-save = Failed to save compliance report: { $error }
+status-logs-exported =
+    Exported { $count ->
+        [one] { $count } log entry
+       *[other] { $count } log entries
+    } to { $path }
+error-logs-export = Failed to export logs: { $error }
+status-share-file-dialog = File dialog would open here to select a share file
+status-key-reconstructed-check = Key '{ $name }' reconstructed and selected (fingerprint { $fingerprint }; check it with the sender)
+status-viewing =
+    Viewing { $name }; it is shredded in { $minutes ->
+        [one] { $minutes } minute
+       *[other] { $minutes } minutes
+    }
+error-view = Failed to view { $name }: { $error }
+error-shred-viewed-all = Failed to shred the viewed files: { $error }
+status-shredded-viewed = Shredded the viewed copy of { $path }
+error-shred-viewed = Failed to shred a viewed file: { $error }
+status-control-api-listening = Control API listening on 127.0.0.1:{ $port }
+error-control-api-start = Failed to start the control API on port { $port }: { $error }
+status-control-api-stopped = Control API stopped
+status-control-api-job = Running control API job { $id }
+error-catalog-folder = Please choose the folder of encrypted files
+error-catalog-running = A catalog is already being made
+status-catalog-started = Making the catalog...
+status-catalog-saved = Catalog saved to { $path }
+error-catalog-save = Failed to save the catalog: { $error }
+status-catalog-made =
+    Catalog of { $count ->
+        [one] { $count } encrypted file
+       *[other] { $count } encrypted files
+    }
+status-catalog-made-unknown =
+    Catalog of { $count ->
+        [one] { $count } encrypted file
+       *[other] { $count } encrypted files
+    }; { $unknown } do not open with any saved key
+error-catalog-make = Failed to make the catalog: { $error }
+error-cold-storage-choose = Choose the folder and where to write the set first
+error-cold-storage-running = A cold-storage set is already being written
+status-cold-storage-started = Writing cold-storage set...
+status-cold-storage-written =
+    Wrote cold-storage set { $path } ({ $volumes ->
+        [one] { $volumes } volume
+       *[other] { $volumes } volumes
+    }, { $files ->
+        [one] { $files } file
+       *[other] { $files } files
+    })
+error-cold-storage-write = Failed to write the cold-storage set: { $error }
+status-deleted = Deleted { $path }
+error-delete = Failed to delete { $path }: { $error }
+error-benchmark-running = A benchmark is already running
+error-benchmark-sizes = Please choose at least one buffer size
+error-benchmark-device = Cannot benchmark the device: { $error }
+status-benchmark-started = Benchmarking...
+status-benchmark-stopped = Benchmark stopped
+error-benchmark-failed = Benchmark failed: { $error }
+error-benchmark-stopped = The benchmark stopped unexpectedly
+status-benchmark-finished = Benchmark finished
+status-benchmark-software-faster = Benchmark finished: software encryption was faster at every size
+status-benchmark-device-faster = Benchmark finished: the device was faster at every size
+status-benchmark-device-faster-for = Benchmark finished: the device was faster for { $sizes }
+error-agreement-running = A key agreement is already running
+status-agreement-waiting = Waiting for the partner to connect on port { $port }...
+error-agreement-address = Enter the address of the partner's computer
+status-agreement-connecting = Connecting to { $address }...
+status-agreement-agreed = Key agreed with { $address }; compare the codes
+status-agreement-cancelled = Stopped waiting for the partner
+error-agreement-failed = Key agreement failed: { $error }
+error-agreement-partner-name = Enter the partner's name to save them
+status-agreement-accepted = Key agreed with { $name } after comparing code { $code }
+status-agreement-accepted-saved = Key agreed with saved partner { $name }
+error-agreement-rejected = The key was discarded. Someone may be between the two computers: check the network before trying again.
+status-rotation-added =
+    Added { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }; only .encrypted files and .crusty archives not added yet are re-encrypted
+error-no-encrypted-files = No encrypted files in { $folder }
+status-encrypted-files-found =
+    Found { $count ->
+        [one] { $count } encrypted file
+       *[other] { $count } encrypted files
+    } in { $folder }
+error-read-path = Failed to read { $path }: { $error }
+error-rotation-same-key = The new key must differ from the old key
+error-rotation-running = The files are already being re-encrypted
+status-rotation-started =
+    Re-encrypting { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }...
+error-rotation-stopped = The key rotation stopped unexpectedly; run it again to finish the remaining files
+status-rotation-done = Every file now uses key { $fingerprint }
+error-rotation-failed =
+    { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } could not be re-encrypted and still use the old key; see the report
+status-report-saved = Report saved to { $path }
+error-report-save = Failed to save the report: { $error }
+error-secured-folders-load = Failed to load secured folders: { $error }
+error-secured-folders-lock = Failed to lock secured folders: { $error }
+error-secured-folders-save = Failed to save secured folders: { $error }
+error-secured-folder-exists = { $folder } is already a secured folder
+error-secured-folder-nested = { $folder } is inside another secured folder, or contains one
+error-secured-folder-busy = Wait for the folder to finish locking or unlocking
+status-secured-folder-removed = { $folder } is no longer a secured folder
+status-secured-folder-bound = { $folder } is now always locked with the same key
+status-secured-folder-unbound = { $folder } is now locked with the current key
+error-secured-folder-other-busy = Another folder is being locked or unlocked
+status-secured-folder-locking = Locking folder...
+status-secured-folder-unlocking = Unlocking folder...
+status-secured-folder-locked =
+    Locked { $folder } ({ $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    })
+status-secured-folder-unlocked =
+    Unlocked { $folder } ({ $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    })
+error-secured-folder-lock-failed =
+    Locked { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } in { $folder }, but { $failed } failed (first: { $file }: { $error }). Fix the problem and lock the folder again to finish.
+error-secured-folder-unlock-failed =
+    Unlocked { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } in { $folder }, but { $failed } failed (first: { $file }: { $error }). Fix the problem and unlock the folder again to finish.
+status-integrity-nothing = No locked secured folder to check with the saved keys
+status-integrity-started =
+    Checking the integrity of { $count ->
+        [one] { $count } secured folder
+       *[other] { $count } secured folders
+    }...
+error-integrity-stopped = The integrity check stopped unexpectedly
+status-integrity-intact = The locked secured folders are intact
+error-integrity-failed =
+    { $count ->
+        [one] { $count } secured folder
+       *[other] { $count } secured folders
+    } failed the integrity check; see the dashboard
+error-audit-files = Please choose the original file and the encrypted file
+error-audit-running = An audit is already running
+status-audit-started = Auditing the encrypted file...
+status-files-found =
+    Found { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } in { $folder }
+error-verify-files = Please add the files to verify
+status-verify-started =
+    Verifying { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }...
+status-audit-match = The encrypted file holds exactly the original
+error-audit-mismatch = The encrypted file does not match the original; see the report
+error-audit-failed = Audit failed: { $error }
+error-audit-stopped = The audit stopped unexpectedly
+error-verify-stopped = The verification stopped unexpectedly
+status-verify-authentic =
+    { $count ->
+        [one] The file is authentic
+       *[other] All { $count } files are authentic
+    }
+error-verify-failed =
+    { $failed } of { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } failed verification
+status-shares-pasted = { $count } shares pasted into separate fields
+error-bind-passphrase = Enter the passphrase to bind the package to
+status-package-created = Transfer package created successfully
+error-package-create = Failed to create transfer package: { $error }
+status-share-saved = Share { $index } saved to: { $path }
+error-share-save = Failed to save share: { $error }
+status-share-uploaded =
+    Share { $index } uploaded. The link expires in { $hours ->
+        [one] { $hours } hour
+       *[other] { $hours } hours
+    }.
+status-share-fetched = Share fetched from the relay. The link can no longer be used.
+error-dictation-group = Group { $group }: { $error }
+status-dictated-share = Dictated share entered
+error-shares-needed = Please enter { $needed } key shares ({ $entered } entered)
+error-unwrap-passphrase = This package is bound to a passphrase; enter it to unwrap the key
+error-read-identity = Failed to read this machine's identity: { $error }
+error-key-reconstruct = Failed to reconstruct key: { $error }
+status-manifest-read =
+    Read the manifest of cold-storage set "{ $label }" ({ $count ->
+        [one] { $count } volume
+       *[other] { $count } volumes
+    })
+error-restore-manifest-first = Choose the set's manifest first
+error-restore-running = The set is already being checked or restored
+status-volumes-checking = Checking the volumes...
+status-set-key-rebuilt = Rebuilt the set's key ({ $fingerprint })
+error-restore-choose = Rebuild the set's key and choose where to restore it first
+status-restore-started = Restoring cold-storage set...
+status-volumes-readable = Every volume can be read
+error-volumes-missing = Some volumes are missing or too damaged; see the list
+status-set-restored =
+    Restored cold-storage set { $label } to { $folder } ({ $restored } of { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    })
+error-set-restore = Failed to restore the cold-storage set: { $error }
+error-cold-storage-stopped = The cold-storage job stopped unexpectedly
+status-history-cleared = History cleared
+error-operation-unfinished = The operation is not finished yet
+status-pausing = Pausing...
+status-resumed = Resumed
+status-files-selected =
+    Selected { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    }
+error-no-files-to-decrypt = No files to decrypt in { $folder }
+error-no-files-to-process = No files to process in { $folder }
+status-folder-files-selected =
+    Selected { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } in { $folder }
+status-files-removed = All files removed
+status-file-removed = Removed file, { $count } remaining
+status-files-cleared = All files cleared
+status-output-dir = Selected output directory: { $folder }
+error-replace-folder = Files in { $folder } cannot be replaced, as the folder is missing or read-only. Turn off "Replace original files" and choose an output folder.
+status-operation-stopped = Operation stopped
+error-archives-separately = Decrypt archives (.crusty) separately from other files
+error-archive-replace = Archives are saved in the output directory; turn off "Replace original files" to make one
+error-archive-embedded = Archives are made and extracted on this computer; turn off hardware encryption to use them
+error-recipients-format = Files for several recipients are saved in CRUSTy's format; turn off the age and OpenPGP formats
+error-recipients-archive = Archives are encrypted with the key alone; turn off "Pack the folder into one archive" to encrypt for recipients
+error-recipients-embedded = Files for several recipients are encrypted on this computer; turn off hardware encryption to use them
+status-packing-folder = Packing folder into an archive...
+status-encrypt-started = Starting encryption...
+status-extract-started = Extracting archive...
+status-decrypt-started = Starting decryption...
+error-outputs-collide = { $first } and { $second } would both be saved as { $output }; process them separately
+error-operation-stalled =
+    The operation made no progress for { $seconds } s with { $backend } and was stopped; { $failed ->
+        [one] { $failed } file
+       *[other] { $failed } files
+    } failed
+error-manifest-running = A manifest is already being checked
+status-manifest-checking = Checking the files against the manifest...
+status-manifest-intact =
+    Every file matches the manifest ({ $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    })
+error-manifest-mismatch = Some files do not match the manifest: { $intact } intact, { $changed } changed, { $missing } missing
+error-manifest-check = Failed to check the manifest: { $error }
+error-manifest-stopped = The manifest check stopped unexpectedly
+status-connection-testing = Testing the connection to the device...
+status-device-answered = The device answered in { $ms } ms (firmware { $firmware })
+error-connection-stopped = The connection test stopped unexpectedly
+error-add-disabled = Adding keys is disabled by policy
+error-remove-disabled = Removing keys is disabled by policy
+error-backups-disabled = Key backups are disabled by policy
+status-key-generated = Generated new key: { $name }
+error-passphrase-short = Passphrase must be at least { $length } characters
+status-key-derived = Derived key from passphrase: { $name }
+error-key-derive = Failed to derive key: { $error }
+error-not-passphrase-file = { $path } was not encrypted with a passphrase key
+status-key-selected = Selected key: { $name }
+status-key-selected-expired = Selected key: { $name } (expired on { $date }; consider using a newer key)
+status-key-expires = Key '{ $name }' expires on { $date }
+status-key-no-expiry = Key '{ $name }' no longer expires
+error-key-distributed = Key '{ $name }' is distributed by the administrator and is not saved here
+error-credential-store-session = Keys can only be kept in the credential store while saved keys are kept between sessions
+error-credential-name-taken = Another key named '{ $name }' is already in the credential store
+error-credential-move = Failed to move key to the credential store: { $error }
+error-credential-remove = Key '{ $name }' was moved to the key store, but { $error }
+status-key-in-credential-store = Key '{ $name }' is kept in the credential store
+status-key-in-key-store = Key '{ $name }' is kept in the key store
+error-key-removed-credential = Key '{ $name }' was removed, but { $error }
+status-key-removed = Removed key: { $name }
+error-backups-off = Key backups are off: { $error }
+error-backup-key-derive = Failed to derive backup key: { $error }
+error-backups-session = Key backups are on until CRUSTy is closed: { $error }
+status-backups-on = Key backups turned on
+error-backup-restore = Failed to restore key backup: { $error }
+status-backup-restored =
+    Restored { $count ->
+        [one] { $count } key
+       *[other] { $count } keys
+    } from backup { $generation }
+status-keys-loaded =
+    Loaded { $count ->
+        [one] { $count } saved key
+       *[other] { $count } saved keys
+    }
+error-keys-session = Saved keys are kept until CRUSTy is closed: { $error }
+status-keys-locked = Keys locked
+error-keys-save = Failed to save keys: { $error }
+error-keys-backup = Failed to back up keys: { $error }
+error-provisioned-key = Failed to load provisioned key '{ $name }': { $error }
+error-deployed-keys = Failed to load deployed keys: { $error }
+status-token-key-selected = Selected smartcard key: { $name }
+error-split-create = Failed to create split key: { $error }
+status-split-created =
+    Split key created. Secondary share saved to: { $secondary }
+    Recovery share saved to: { $recovery }
+error-split-store = Failed to store split key: { $error }
+status-key-reconstructed = Key '{ $name }' reconstructed and selected
+error-shares-open = Failed to open key shares: { $error }
+error-primary-share-verify = Primary key share could not be verified: { $error }
+status-shares-due =
+    { $count ->
+        [one] { $count } key share is
+       *[other] { $count } key shares are
+    } due for verification. See Key Management.
+status-share-verified = { $label } verified
+error-share-verify = Share verification failed: { $error }
+error-custodians-load = Failed to load custodian registry: { $error }
+status-custodians-saved = Share custodians saved
+error-custodians-save = Failed to save custodian registry: { $error }
+status-ceremony-saved = Ceremony report saved to: { $path }
+error-ceremony-write = Failed to write ceremony report: { $error }
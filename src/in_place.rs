@@ -0,0 +1,190 @@
+/// In-place encryption module.
+///
+/// This module provides functionality for:
+/// - Replacing an original file with its encrypted version, so no copy of the plaintext
+///   is left next to the ciphertext
+/// - Overwriting (shredding) the original file before it is deleted
+/// - Replacing an encrypted file with its decrypted version (used to unlock secured folders)
+///
+/// The encrypted file is written atomically (see `encryption::write_destination`), so a
+/// crash or full disk leaves either the original alone or the complete encrypted file,
+/// never a partial one. The original is only removed once the encrypted file is in place.
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use rand::RngCore;
+
+use crate::backend::Backend;
+use crate::encryption::{EncryptionError, EncryptionKey};
+
+/// Size of the random blocks written over a file when it is shredded
+const SHRED_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Get the folder of an original file, where its encrypted version is written
+pub fn original_dir(original: &Path) -> PathBuf {
+    match original.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Encrypt a file and replace the original with the encrypted file
+///
+/// # Arguments
+/// * `dest` - The encrypted file, normally in the original's folder (see `original_dir`)
+/// * `store_name` - Store the original name in the file header (for hidden names)
+/// * `shred` - Overwrite the original with random data before deleting it
+///
+/// # Returns
+/// * `Result<(), EncryptionError>` - Ok once the encrypted file is in place and the
+///   original removed; on an error before the rename, the original is left unchanged
+pub fn encrypt_in_place(
+    backend: &Backend,
+    original: &Path,
+    dest: &Path,
+    key: &EncryptionKey,
+    store_name: bool,
+    shred: bool,
+    progress_callback: impl Fn(f32) + Send + 'static,
+) -> Result<(), EncryptionError> {
+    if store_name {
+        backend.encrypt_file_storing_name(original, dest, key, original.file_name().unwrap_or_default(), progress_callback)?;
+    } else {
+        backend.encrypt_file(original, dest, key, progress_callback)?;
+    }
+
+    let removed = if shred { shred_file(original) } else { fs::remove_file(original) };
+    removed.map_err(|e| EncryptionError::Io(io::Error::new(
+        e.kind(),
+        format!("Encrypted to {}, but the original could not be removed: {}", dest.display(), e),
+    )))
+}
+
+/// Decrypt a file and replace the encrypted file with the decrypted file
+///
+/// # Arguments
+/// * `dest` - The decrypted file, normally in the encrypted file's folder
+///
+/// # Returns
+/// * `Result<(), EncryptionError>` - Ok once the decrypted file is in place and the
+///   encrypted file removed; on an error before the rename, the encrypted file is left
+///   unchanged
+pub fn decrypt_in_place(
+    backend: &Backend,
+    encrypted: &Path,
+    dest: &Path,
+    key: &EncryptionKey,
+    progress_callback: impl Fn(f32) + Send + 'static,
+) -> Result<(), EncryptionError> {
+    backend.decrypt_file(encrypted, dest, key, progress_callback)?;
+
+    fs::remove_file(encrypted).map_err(|e| EncryptionError::Io(io::Error::new(
+        e.kind(),
+        format!("Decrypted to {}, but the encrypted file could not be removed: {}", dest.display(), e),
+    )))
+}
+
+/// Overwrite a file with random data, flush it to disk, and delete it
+///
+/// This is best effort: SSDs (wear leveling), copy-on-write file systems (Btrfs, ZFS,
+/// APFS), and snapshots or backups can keep the old blocks. Full-disk encryption is the
+/// reliable protection on such storage.
+pub fn shred_file(path: &Path) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut remaining = file.metadata()?.len();
+
+    let mut block = vec![0u8; SHRED_BLOCK_SIZE];
+    while remaining > 0 {
+        let len = remaining.min(SHRED_BLOCK_SIZE as u64) as usize;
+        rand::thread_rng().fill_bytes(&mut block[..len]);
+        file.write_all(&block[..len])?;
+        remaining -= len as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+
+    fs::remove_file(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::BackendFactory;
+    use crate::encryption;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_encrypt_in_place() {
+        let dir = tempdir().unwrap();
+        let backend = BackendFactory::create_local();
+        let key = EncryptionKey::generate();
+
+        for shred in [false, true] {
+            let original = dir.path().join(format!("report-{}.txt", shred));
+            std::fs::write(&original, b"Only one copy").unwrap();
+            let dest = dir.path().join(format!("report-{}.txt.encrypted", shred));
+
+            encrypt_in_place(&backend, &original, &dest, &key, false, shred, |_| {}).unwrap();
+            assert!(!original.exists());
+            assert!(!encryption::temp_destination(&dest).exists());
+
+            let restored = dir.path().join(format!("restored-{}.txt", shred));
+            encryption::decrypt_file(&dest, &restored, &key, |_| {}).unwrap();
+            assert_eq!(std::fs::read(&restored).unwrap(), b"Only one copy");
+        }
+    }
+
+    #[test]
+    fn test_encrypt_in_place_keeps_original_on_error() {
+        let dir = tempdir().unwrap();
+        let backend = BackendFactory::create_local();
+        let key = EncryptionKey::generate();
+
+        let original = dir.path().join("notes.txt");
+        std::fs::write(&original, b"Keep me").unwrap();
+        let dest = dir.path().join("notes.txt.encrypted");
+        std::fs::write(&dest, b"Already here").unwrap();
+
+        assert!(encrypt_in_place(&backend, &original, &dest, &key, false, true, |_| {}).is_err());
+        assert_eq!(std::fs::read(&original).unwrap(), b"Keep me");
+        assert_eq!(std::fs::read(&dest).unwrap(), b"Already here");
+
+        // A missing original leaves no temporary file behind
+        let missing = dir.path().join("missing.txt");
+        let dest = dir.path().join("missing.txt.encrypted");
+        assert!(encrypt_in_place(&backend, &missing, &dest, &key, false, false, |_| {}).is_err());
+        assert!(!encryption::temp_destination(&dest).exists());
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_decrypt_in_place() {
+        let dir = tempdir().unwrap();
+        let backend = BackendFactory::create_local();
+        let key = EncryptionKey::generate();
+
+        let original = dir.path().join("notes.txt");
+        std::fs::write(&original, b"Round trip").unwrap();
+        let encrypted = dir.path().join("notes.txt.encrypted");
+        encrypt_in_place(&backend, &original, &encrypted, &key, false, false, |_| {}).unwrap();
+
+        // The wrong key leaves the encrypted file as it was
+        let other_key = EncryptionKey::generate();
+        assert!(decrypt_in_place(&backend, &encrypted, &original, &other_key, |_| {}).is_err());
+        assert!(encrypted.exists() && !original.exists() && !encryption::temp_destination(&original).exists());
+
+        decrypt_in_place(&backend, &encrypted, &original, &key, |_| {}).unwrap();
+        assert!(!encrypted.exists());
+        assert_eq!(std::fs::read(&original).unwrap(), b"Round trip");
+    }
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(original_dir(Path::new("notes.txt")), PathBuf::from("."));
+        assert_eq!(original_dir(Path::new("/data/notes.txt")), PathBuf::from("/data"));
+        assert_eq!(encryption::temp_destination(Path::new("/data/a.encrypted")), PathBuf::from("/data/.a.encrypted.crusty-tmp"));
+        assert!(encryption::is_temp_destination(&encryption::temp_destination(Path::new("/data/a.encrypted"))));
+        assert!(!encryption::is_temp_destination(Path::new("/data/a.crusty-tmp")));
+    }
+}
@@ -0,0 +1,242 @@
+/// Single instance module.
+///
+/// This module provides functionality for:
+/// - Finding whether CRUSTy is already running for this user
+/// - Handing the files CRUSTy was started with to the running copy, which selects
+///   them and comes to the front, instead of opening a second window
+///
+/// The first copy holds a lock on `instance.lock` in the application data directory
+/// for as long as it runs, and listens on a local port recorded in `instance.json`
+/// with a random token. A later copy finds the lock held, sends its files with the
+/// token, and exits. If the running copy does not answer, the later copy starts
+/// normally.
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+
+use crate::shared_files;
+
+/// How long a later copy waits for the running copy to answer
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Largest hand-off message accepted
+const MAX_MESSAGE_LEN: u64 = 1024 * 1024;
+
+/// Where the running copy listens (instance.json)
+#[derive(Serialize, Deserialize)]
+struct InstanceInfo {
+    port: u16,
+    token: String,
+}
+
+/// Files handed off by a later copy
+#[derive(Serialize, Deserialize)]
+struct HandoffMessage {
+    token: String,
+    files: Vec<PathBuf>,
+}
+
+/// Result of claiming the single instance
+pub enum Claim {
+    /// No other copy is running; this copy receives later copies' files
+    Primary(PrimaryInstance),
+    /// The files were handed to the running copy; this copy should exit
+    HandedOff,
+}
+
+/// The running copy, before it starts listening for later copies
+pub struct PrimaryInstance {
+    lock: File,
+    listener: TcpListener,
+    token: String,
+}
+
+/// Files handed off by later copies, received in the background
+pub struct Handoff {
+    // Held for as long as this copy runs
+    _lock: File,
+    receiver: Receiver<Vec<PathBuf>>,
+}
+
+/// Default folder of the instance lock and information
+pub fn default_dir() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("crusty");
+    path
+}
+
+/// Become the running copy, or hand the files to the copy already running
+///
+/// # Arguments
+/// * `dir` - Folder of the instance lock and information
+/// * `files` - Files this copy was started with
+///
+/// # Returns
+/// * `io::Result<Claim>` - Whether this copy is the running copy, or an error if the
+///   running copy could not be reached (this copy should then start normally)
+pub fn claim(dir: &Path, files: &[PathBuf]) -> io::Result<Claim> {
+    fs::create_dir_all(dir)?;
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(dir.join("instance.lock"))?;
+
+    match lock.try_lock() {
+        Ok(()) => {},
+        Err(TryLockError::WouldBlock) => {
+            hand_off(dir, files)?;
+            return Ok(Claim::HandedOff);
+        },
+        Err(TryLockError::Error(e)) => return Err(e),
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let mut token = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut token);
+    let info = InstanceInfo {
+        port: listener.local_addr()?.port(),
+        token: token.iter().map(|b| format!("{:02x}", b)).collect(),
+    };
+    let content = serde_json::to_string(&info)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    shared_files::write_atomic(&dir.join("instance.json"), content)?;
+
+    Ok(Claim::Primary(PrimaryInstance { lock, listener, token: info.token }))
+}
+
+/// Send files to the running copy and wait for it to confirm
+fn hand_off(dir: &Path, files: &[PathBuf]) -> io::Result<()> {
+    let content = fs::read_to_string(dir.join("instance.json"))?;
+    let info: InstanceInfo = serde_json::from_str(&content)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid instance information: {}", e)))?;
+
+    // The running copy may have another working directory
+    let files = files.iter()
+        .map(std::path::absolute)
+        .collect::<io::Result<Vec<_>>>()?;
+    let message = serde_json::to_string(&HandoffMessage { token: info.token, files })
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, info.port));
+    let mut stream = TcpStream::connect_timeout(&address, HANDOFF_TIMEOUT)?;
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT))?;
+    stream.set_write_timeout(Some(HANDOFF_TIMEOUT))?;
+    stream.write_all(message.as_bytes())?;
+    stream.write_all(b"\n")?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::ConnectionRefused, "The running copy of CRUSTy did not accept the files"))
+    }
+}
+
+impl PrimaryInstance {
+    /// Start receiving files from later copies in the background
+    ///
+    /// # Arguments
+    /// * `wake` - Called after files are received (to repaint the window)
+    pub fn listen(self, wake: impl Fn() + Send + 'static) -> Handoff {
+        let (sender, receiver) = mpsc::channel();
+        let listener = self.listener;
+        let token = self.token;
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if let Some(files) = receive(stream, &token) {
+                    if sender.send(files).is_err() {
+                        break;
+                    }
+                    wake();
+                }
+            }
+        });
+
+        Handoff { _lock: self.lock, receiver }
+    }
+}
+
+/// Read a hand-off message, ignoring connections without the token
+fn receive(stream: TcpStream, token: &str) -> Option<Vec<PathBuf>> {
+    stream.set_read_timeout(Some(HANDOFF_TIMEOUT)).ok()?;
+    let mut reader = BufReader::new(stream.try_clone().ok()?.take(MAX_MESSAGE_LEN));
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+
+    let message: HandoffMessage = serde_json::from_str(&line).ok()?;
+    if message.token != token {
+        return None;
+    }
+
+    let _ = (&stream).write_all(b"ok\n");
+    Some(message.files)
+}
+
+impl Handoff {
+    /// Take the files received since the last call (None if no later copy was started)
+    pub fn take_files(&self) -> Option<Vec<PathBuf>> {
+        let mut received = None;
+        while let Ok(files) = self.receiver.try_recv() {
+            received.get_or_insert_with(Vec::new).extend(files);
+        }
+        received
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_second_copy_hands_off_files() {
+        let dir = tempdir().unwrap();
+        let primary = match claim(dir.path(), &[]).unwrap() {
+            Claim::Primary(primary) => primary,
+            Claim::HandedOff => panic!("No copy was running"),
+        };
+        let handoff = primary.listen(|| {});
+        assert!(handoff.take_files().is_none());
+
+        let file = dir.path().join("report.pdf");
+        assert!(matches!(claim(dir.path(), std::slice::from_ref(&file)).unwrap(), Claim::HandedOff));
+
+        let started = Instant::now();
+        let files = loop {
+            if let Some(files) = handoff.take_files() {
+                break files;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(files, vec![file]);
+    }
+
+    #[test]
+    fn test_wrong_token_is_ignored() {
+        let dir = tempdir().unwrap();
+        let primary = match claim(dir.path(), &[]).unwrap() {
+            Claim::Primary(primary) => primary,
+            Claim::HandedOff => panic!("No copy was running"),
+        };
+        let port = primary.listener.local_addr().unwrap().port();
+        let handoff = primary.listen(|| {});
+
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.write_all(b"{\"token\":\"guess\",\"files\":[\"/etc/passwd\"]}\n").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert!(reply.is_empty());
+        assert!(handoff.take_files().is_none());
+    }
+}
@@ -0,0 +1,369 @@
+/// Partner key agreement module.
+///
+/// This module provides functionality for:
+/// - Agreeing a transfer key directly between two CRUSTy installations over the
+///   network: one waits for its partner to connect, the other connects
+/// - A short authentication string (SAS) both users compare, e.g. over the phone, to
+///   check that nobody is between the two computers
+/// - Saving a partner's machine identity once the codes matched, so later agreements
+///   with that partner need no comparison
+///
+/// Each side sends its machine identity and a one-time X25519 key. The key is derived
+/// (HKDF-SHA256) from the exchange of the one-time keys and the exchange of the machine
+/// identities, so only the two machines can derive it. The connecting side commits to
+/// its one-time key before it sees the other's, so someone in between gets one guess
+/// at the six-digit code. Both sides then prove they derived the same key before it is
+/// offered for saving.
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use hkdf::Hkdf;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use x25519_dalek::{PublicKey, StaticSecret};
+use zeroize::Zeroizing;
+
+use crate::encryption::EncryptionKey;
+use crate::network::{self, NetworkError};
+
+/// Port a waiting computer listens on unless another is chosen
+pub const DEFAULT_PORT: u16 = 47047;
+
+/// Start of the messages opening an agreement
+const AGREEMENT_MAGIC: &[u8] = b"CRUSTYKA";
+
+/// Current protocol version
+const AGREEMENT_VERSION: u8 = 1;
+
+/// Context strings for the transcript and the derived values
+const TRANSCRIPT_CONTEXT: &[u8] = b"CRUSTy-Key-Agreement-v1";
+const TRANSFER_KEY_INFO: &[u8] = b"CRUSTy-Key-Agreement-Transfer-Key";
+const CONNECTOR_CONFIRM_INFO: &[u8] = b"CRUSTy-Key-Agreement-Connector-Confirmation";
+const LISTENER_CONFIRM_INFO: &[u8] = b"CRUSTy-Key-Agreement-Listener-Confirmation";
+const SAS_INFO: &[u8] = b"CRUSTy-Key-Agreement-SAS";
+
+/// How long to wait for the partner to connect
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How long to wait for the partner's computer to answer
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error type for key agreements
+#[derive(Debug, Error)]
+pub enum AgreementError {
+    /// The connection failed or was closed
+    #[error("Connection error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The other side is not CRUSTy, or sent something unexpected
+    #[error("The partner's computer sent an invalid message: {0}")]
+    Protocol(String),
+
+    /// The two sides derived different keys
+    #[error("The partner's computer derived a different key. Someone may be between the two computers; do not use this connection.")]
+    Confirmation,
+
+    /// Network features are turned off
+    #[error(transparent)]
+    Network(#[from] NetworkError),
+
+    /// Nobody connected in time
+    #[error("No partner connected within {} minutes", WAIT_TIMEOUT.as_secs() / 60)]
+    TimedOut,
+
+    /// Stopped by the user
+    #[error("The key agreement was cancelled")]
+    Cancelled,
+}
+
+/// Partner whose machine identity was checked by comparing codes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Partner {
+    /// Who the partner is
+    pub name: String,
+    /// The partner's machine identity (Base64)
+    pub identity: String,
+    /// When the codes were compared (local time)
+    pub verified_at: String,
+}
+
+/// Find the saved partner a machine identity belongs to
+pub fn find_partner<'a>(partners: &'a [Partner], identity: &str) -> Option<&'a Partner> {
+    partners.iter().find(|partner| partner.identity == identity)
+}
+
+/// Key agreed with a partner's computer, before the users accept it
+pub struct Agreement {
+    /// The transfer key
+    pub key: EncryptionKey,
+    /// Code both users compare (six digits, e.g. `482 915`)
+    pub sas: String,
+    /// The partner's machine identity (Base64)
+    pub partner_identity: String,
+    /// Address of the partner's computer
+    pub partner_address: String,
+}
+
+/// Which side of the agreement this computer is
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    /// Waited for the partner to connect
+    Listener,
+    /// Connected to the waiting partner
+    Connector,
+}
+
+/// Compare two values in constant time
+fn same_bytes(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Read a 32-byte value
+fn read_32(stream: &mut impl Read) -> Result<[u8; 32], AgreementError> {
+    let mut value = [0u8; 32];
+    stream.read_exact(&mut value)?;
+    Ok(value)
+}
+
+/// Read the start of the other side's first message
+fn read_hello(stream: &mut impl Read) -> Result<(), AgreementError> {
+    let mut hello = [0u8; 9];
+    stream.read_exact(&mut hello)?;
+    if &hello[..8] != AGREEMENT_MAGIC {
+        return Err(AgreementError::Protocol("it is not a CRUSTy key agreement".to_string()));
+    }
+    if hello[8] != AGREEMENT_VERSION {
+        return Err(AgreementError::Protocol(format!("unsupported version {}; update CRUSTy on both computers", hello[8])));
+    }
+    Ok(())
+}
+
+/// Run the agreement on a connected stream
+fn agree(stream: &mut (impl Read + Write), role: Role, identity: &StaticSecret) -> Result<(EncryptionKey, String, PublicKey), AgreementError> {
+    let own_identity = PublicKey::from(identity);
+    let ephemeral = StaticSecret::random_from_rng(rand::rngs::OsRng);
+    let own_ephemeral = PublicKey::from(&ephemeral);
+
+    let mut hello = AGREEMENT_MAGIC.to_vec();
+    hello.push(AGREEMENT_VERSION);
+    hello.extend_from_slice(own_identity.as_bytes());
+
+    // The connector commits to its one-time key, and reveals it once it has the listener's
+    let (connector_identity, listener_identity, connector_ephemeral, listener_ephemeral) = match role {
+        Role::Connector => {
+            hello.extend_from_slice(&Sha256::digest(own_ephemeral.as_bytes()));
+            stream.write_all(&hello)?;
+
+            read_hello(stream)?;
+            let listener_identity = read_32(stream)?;
+            let listener_ephemeral = read_32(stream)?;
+            stream.write_all(own_ephemeral.as_bytes())?;
+            (own_identity.to_bytes(), listener_identity, own_ephemeral.to_bytes(), listener_ephemeral)
+        },
+        Role::Listener => {
+            read_hello(stream)?;
+            let connector_identity = read_32(stream)?;
+            let commitment = read_32(stream)?;
+            hello.extend_from_slice(own_ephemeral.as_bytes());
+            stream.write_all(&hello)?;
+
+            let connector_ephemeral = read_32(stream)?;
+            if !same_bytes(&Sha256::digest(connector_ephemeral), &commitment) {
+                return Err(AgreementError::Protocol("its one-time key does not match its commitment".to_string()));
+            }
+            (connector_identity, own_identity.to_bytes(), connector_ephemeral, own_ephemeral.to_bytes())
+        },
+    };
+    if connector_identity == listener_identity {
+        return Err(AgreementError::Protocol("it has this computer's machine identity".to_string()));
+    }
+
+    let (partner_identity, partner_ephemeral) = match role {
+        Role::Connector => (listener_identity, listener_ephemeral),
+        Role::Listener => (connector_identity, connector_ephemeral),
+    };
+    let mut ikm = Zeroizing::new(Vec::with_capacity(64));
+    ikm.extend_from_slice(ephemeral.diffie_hellman(&PublicKey::from(partner_ephemeral)).as_bytes());
+    ikm.extend_from_slice(identity.diffie_hellman(&PublicKey::from(partner_identity)).as_bytes());
+
+    let transcript = Sha256::new()
+        .chain_update(TRANSCRIPT_CONTEXT)
+        .chain_update(connector_identity)
+        .chain_update(listener_identity)
+        .chain_update(connector_ephemeral)
+        .chain_update(listener_ephemeral)
+        .finalize();
+    let hkdf = Hkdf::<Sha256>::new(Some(&transcript), &ikm);
+    let expand = |info: &[u8]| {
+        let mut okm = [0u8; 32];
+        hkdf.expand(info, &mut okm).expect("32 bytes is a valid HKDF output length");
+        okm
+    };
+    let key = EncryptionKey { key: expand(TRANSFER_KEY_INFO), kdf: None };
+    let connector_confirmation = expand(CONNECTOR_CONFIRM_INFO);
+    let listener_confirmation = expand(LISTENER_CONFIRM_INFO);
+    let sas_bytes = expand(SAS_INFO);
+
+    // Each side proves it derived the same key (the listener only answers a valid proof)
+    match role {
+        Role::Connector => {
+            stream.write_all(&connector_confirmation)?;
+            let confirmation = read_32(stream).map_err(|_| AgreementError::Confirmation)?;
+            if !same_bytes(&confirmation, &listener_confirmation) {
+                return Err(AgreementError::Confirmation);
+            }
+        },
+        Role::Listener => {
+            if !same_bytes(&read_32(stream)?, &connector_confirmation) {
+                return Err(AgreementError::Confirmation);
+            }
+            stream.write_all(&listener_confirmation)?;
+        },
+    }
+    stream.flush()?;
+
+    let code = u32::from_be_bytes([sas_bytes[0], sas_bytes[1], sas_bytes[2], sas_bytes[3]]) % 1_000_000;
+    Ok((key, format!("{:03} {:03}", code / 1000, code % 1000), PublicKey::from(partner_identity)))
+}
+
+/// Set the timeouts of a connected stream and run the agreement
+fn agree_on(mut stream: TcpStream, role: Role, identity: &StaticSecret, partner_address: SocketAddr) -> Result<Agreement, AgreementError> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(MESSAGE_TIMEOUT))?;
+    stream.set_write_timeout(Some(MESSAGE_TIMEOUT))?;
+    let (key, sas, partner_identity) = agree(&mut stream, role, identity)?;
+
+    Ok(Agreement {
+        key,
+        sas,
+        partner_identity: STANDARD.encode(partner_identity.as_bytes()),
+        partner_address: partner_address.to_string(),
+    })
+}
+
+/// Wait for the partner to connect, and agree a key with them
+///
+/// # Arguments
+/// * `port` - Port to listen on (on every network interface)
+/// * `identity` - This machine's identity secret
+/// * `cancel` - Set to stop waiting
+///
+/// # Returns
+/// * `Result<Agreement, AgreementError>` - The agreed key and the code to compare
+pub fn listen(port: u16, identity: &StaticSecret, cancel: &AtomicBool) -> Result<Agreement, AgreementError> {
+    if network::is_offline() {
+        return Err(NetworkError::Offline.into());
+    }
+
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    listener.set_nonblocking(true)?;
+    let started = Instant::now();
+    loop {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(AgreementError::Cancelled);
+        }
+        match listener.accept() {
+            Ok((stream, address)) => return agree_on(stream, Role::Listener, identity, address),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if started.elapsed() > WAIT_TIMEOUT {
+                    return Err(AgreementError::TimedOut);
+                }
+                thread::sleep(Duration::from_millis(100));
+            },
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Connect to the waiting partner, and agree a key with them
+///
+/// # Arguments
+/// * `address` - The partner's computer: a host name or IP address, with `:port` when
+///   it is not the default port
+/// * `identity` - This machine's identity secret
+///
+/// # Returns
+/// * `Result<Agreement, AgreementError>` - The agreed key and the code to compare
+pub fn connect(address: &str, identity: &StaticSecret) -> Result<Agreement, AgreementError> {
+    if network::is_offline() {
+        return Err(NetworkError::Offline.into());
+    }
+
+    let address = address.trim();
+    let socket_address = address.to_socket_addrs()
+        .or_else(|_| (address, DEFAULT_PORT).to_socket_addrs())
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| AgreementError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("'{}' is not a reachable computer", address)
+        )))?;
+
+    let stream = TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT)?;
+    agree_on(stream, Role::Connector, identity, socket_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_both_sides_agree_the_same_key() {
+        let listener_identity = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let connector_identity = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let listener_public = STANDARD.encode(PublicKey::from(&listener_identity).as_bytes());
+        let connector_public = STANDARD.encode(PublicKey::from(&connector_identity).as_bytes());
+
+        // A free port for the listener
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let waiting = {
+            let cancel = cancel.clone();
+            thread::spawn(move || listen(port, &listener_identity, &cancel))
+        };
+        thread::sleep(Duration::from_millis(200));
+
+        let connected = connect(&format!("127.0.0.1:{}", port), &connector_identity).unwrap();
+        let waited = waiting.join().unwrap().unwrap();
+        assert_eq!(connected.key.key, waited.key.key);
+        assert_eq!(connected.sas, waited.sas);
+        assert_eq!(connected.sas.len(), 7);
+        assert_eq!(connected.partner_identity, listener_public);
+        assert_eq!(waited.partner_identity, connector_public);
+    }
+
+    #[test]
+    fn test_listener_rejects_a_broken_commitment() {
+        let identity = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let waiting = thread::spawn(move || listen(port, &identity, &AtomicBool::new(false)));
+        thread::sleep(Duration::from_millis(200));
+
+        // Someone in between who changes the one-time key after committing to another
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut hello = AGREEMENT_MAGIC.to_vec();
+        hello.push(AGREEMENT_VERSION);
+        hello.extend_from_slice(&[1u8; 32]);
+        hello.extend_from_slice(&Sha256::digest([2u8; 32]));
+        stream.write_all(&hello).unwrap();
+        let mut answer = [0u8; 9 + 64];
+        stream.read_exact(&mut answer).unwrap();
+        stream.write_all(&[3u8; 32]).unwrap();
+
+        assert!(matches!(waiting.join().unwrap(), Err(AgreementError::Protocol(_))));
+    }
+
+    #[test]
+    fn test_cancel_stops_waiting() {
+        let identity = StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        assert!(matches!(listen(port, &identity, &AtomicBool::new(true)), Err(AgreementError::Cancelled)));
+    }
+}
@@ -0,0 +1,226 @@
+/// Key backups module.
+///
+/// This module provides functionality for:
+/// - Writing a backup of the saved keys after every change, keeping the last few
+///   generations (keystore.bak.1 is the newest) in the application data directory
+/// - Encrypting the backups with a key derived from a backup passphrase, so a backup
+///   can be restored with the passphrase alone, on this machine or another
+/// - Keeping the backup key in the OS credential store, so backups are written without
+///   asking for the passphrase each time
+/// - Listing the backup generations and reading one back
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use keyring::Entry;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroizing;
+
+use crate::encryption::{self, EncryptionKey, EncryptionError, KdfParams};
+use crate::keystore::StoredKey;
+use crate::policy::{get_policy, KeyStoreKind};
+use crate::shared_files;
+use crate::tpm;
+
+/// Number of backup generations kept
+pub const BACKUP_GENERATIONS: usize = 5;
+
+/// Keyring entry holding the backup key
+const BACKUP_SERVICE: &str = "CRUSTy";
+const BACKUP_USER: &str = "keystore-backup";
+
+/// Contents of a backup
+#[derive(Serialize, Deserialize)]
+struct KeyBackup {
+    created_at: String,
+    keys: Vec<StoredKey>,
+}
+
+/// A backup generation on disk
+#[derive(Clone, Debug)]
+pub struct BackupGeneration {
+    /// 1 for the newest backup
+    pub generation: usize,
+    /// When the backup was written
+    pub modified: Option<DateTime<Local>>,
+}
+
+/// Default folder of the key backups
+pub fn default_dir() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("crusty");
+    path
+}
+
+/// Get the file of a backup generation
+pub fn backup_path(dir: &Path, generation: usize) -> PathBuf {
+    dir.join(format!("keystore.bak.{}", generation))
+}
+
+/// Derive a new backup key from a backup passphrase (with a new random salt)
+pub fn backup_key(passphrase: &str) -> Result<EncryptionKey, EncryptionError> {
+    EncryptionKey::from_passphrase(passphrase, &KdfParams::generate())
+}
+
+/// Write a backup of the saved keys as the newest generation, shifting the older
+/// generations along and dropping the oldest
+///
+/// # Arguments
+/// * `dir` - Folder of the key backups
+/// * `keys` - The saved keys with their names
+/// * `backup_key` - Key derived from the backup passphrase (see `backup_key`)
+pub fn write_backup(dir: &Path, keys: &[(String, EncryptionKey)], backup_key: &EncryptionKey) -> Result<(), EncryptionError> {
+    let backup = KeyBackup {
+        created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        keys: keys.iter().map(|(name, key)| StoredKey::new(name, key)).collect(),
+    };
+    let content = Zeroizing::new(serde_json::to_vec(&backup)
+        .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize key backup: {}", e)))?);
+
+    // The salt of the backup passphrase comes first, so the backup key can be derived
+    // again from the passphrase alone
+    let mut data = encryption::kdf_header(backup_key);
+    data.extend(encryption::encrypt_data(&content, backup_key)?);
+
+    // Another running copy of CRUSTy may be backing up at the same time
+    let _lock = shared_files::lock(&dir.join("keystore.bak"))?;
+    for generation in (1..BACKUP_GENERATIONS).rev() {
+        let path = backup_path(dir, generation);
+        if path.exists() {
+            fs::rename(&path, backup_path(dir, generation + 1))?;
+        }
+    }
+    shared_files::write_atomic(&backup_path(dir, 1), data)?;
+    Ok(())
+}
+
+/// List the backup generations, newest first
+pub fn list_backups(dir: &Path) -> Vec<BackupGeneration> {
+    (1..=BACKUP_GENERATIONS)
+        .filter_map(|generation| {
+            let metadata = fs::metadata(backup_path(dir, generation)).ok()?;
+            let modified = metadata.modified().ok().map(DateTime::<Local>::from);
+            Some(BackupGeneration { generation, modified })
+        })
+        .collect()
+}
+
+/// Read the saved keys from a backup
+///
+/// # Arguments
+/// * `path` - The backup file
+/// * `passphrase` - The backup passphrase the backup was written with
+///
+/// # Returns
+/// * `Result<Vec<(String, EncryptionKey)>, EncryptionError>` - The keys with their
+///   names, or an error if the passphrase is wrong or the backup is damaged
+pub fn read_backup(path: &Path, passphrase: &str) -> Result<Vec<(String, EncryptionKey)>, EncryptionError> {
+    let data = fs::read(path)?;
+    let (params, encrypted) = encryption::split_kdf_header(&data)?;
+    let params = params
+        .ok_or_else(|| EncryptionError::Decryption(format!("{} is not a key backup", path.display())))?;
+
+    let key = EncryptionKey::from_passphrase(passphrase, &params)?;
+    let content = Zeroizing::new(encryption::decrypt_data(encrypted, &key)
+        .map_err(|_| EncryptionError::Decryption("Wrong backup passphrase, or the backup is damaged".to_string()))?);
+    let backup: KeyBackup = serde_json::from_slice(&content)
+        .map_err(|e| EncryptionError::Decryption(format!("Invalid key backup: {}", e)))?;
+
+    backup.keys.into_iter().map(StoredKey::into_key).collect()
+}
+
+/// Load the backup key from the OS credential store
+///
+/// # Returns
+/// * `Result<Option<EncryptionKey>, EncryptionError>` - The backup key, None if backups
+///   have not been turned on, or an error if the credential store cannot be read
+pub fn load_backup_key() -> Result<Option<EncryptionKey>, EncryptionError> {
+    let entry = Entry::new(BACKUP_SERVICE, BACKUP_USER)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    let stored = match entry.get_password() {
+        Ok(stored) => stored,
+        Err(keyring::Error::NoEntry) => return Ok(None),
+        Err(e) => return Err(EncryptionError::KeyError(format!("Failed to read backup key: {}", e))),
+    };
+
+    // The KDF header of the backup key, followed by the key
+    let bytes = tpm::unprotect_and_upgrade(BACKUP_USER, &stored, &get_policy().protection_for(KeyStoreKind::KeyBackups), |stored| {
+        entry.set_password(stored)
+            .map_err(|e| EncryptionError::KeyError(format!("Failed to store backup key: {}", e)))
+    })?;
+    let (params, key) = encryption::split_kdf_header(&bytes)?;
+    let key: [u8; 32] = key.try_into()
+        .map_err(|_| EncryptionError::KeyError("Invalid backup key length".to_string()))?;
+    Ok(Some(EncryptionKey { key, kdf: params }))
+}
+
+/// Store the backup key in the OS credential store, at the protection level the admin
+/// policy sets for key backups
+pub fn store_backup_key(key: &EncryptionKey) -> Result<(), EncryptionError> {
+    let entry = Entry::new(BACKUP_SERVICE, BACKUP_USER)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    let mut bytes = Zeroizing::new(encryption::kdf_header(key));
+    bytes.extend_from_slice(&key.key);
+    let stored = tpm::protect(BACKUP_USER, &bytes, &get_policy().protection_for(KeyStoreKind::KeyBackups))?;
+    entry.set_password(&stored)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to store backup key: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    /// A backup key that is quick to derive
+    fn test_backup_key(passphrase: &str) -> EncryptionKey {
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1, salt: [7; 16] };
+        EncryptionKey::from_passphrase(passphrase, &params).unwrap()
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let dir = tempdir().unwrap();
+        let backup_key = test_backup_key("backup passphrase");
+        let passphrase_key = test_backup_key("key passphrase");
+        let keys = vec![
+            ("Random".to_string(), EncryptionKey::generate()),
+            ("Passphrase".to_string(), passphrase_key.clone()),
+        ];
+
+        write_backup(dir.path(), &keys, &backup_key).unwrap();
+        let restored = read_backup(&backup_path(dir.path(), 1), "backup passphrase").unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].0, "Random");
+        assert_eq!(restored[0].1.to_base64(), keys[0].1.to_base64());
+        assert!(restored[0].1.kdf.is_none());
+        assert_eq!(restored[1].1.kdf, passphrase_key.kdf);
+
+        assert!(read_backup(&backup_path(dir.path(), 1), "wrong passphrase").is_err());
+    }
+
+    #[test]
+    fn test_backup_generations_rotate() {
+        let dir = tempdir().unwrap();
+        let backup_key = test_backup_key("backup passphrase");
+        assert!(list_backups(dir.path()).is_empty());
+
+        // One more backup than is kept, with one more key each time
+        let mut keys = Vec::new();
+        for i in 0..=BACKUP_GENERATIONS {
+            keys.push((format!("Key {}", i), EncryptionKey::generate()));
+            write_backup(dir.path(), &keys, &backup_key).unwrap();
+        }
+
+        let backups = list_backups(dir.path());
+        assert_eq!(backups.len(), BACKUP_GENERATIONS);
+        assert_eq!(backups[0].generation, 1);
+        assert!(!backup_path(dir.path(), BACKUP_GENERATIONS + 1).exists());
+
+        // The newest generation has every key; the oldest kept lost the first backup
+        let newest = read_backup(&backup_path(dir.path(), 1), "backup passphrase").unwrap();
+        assert_eq!(newest.len(), BACKUP_GENERATIONS + 1);
+        let oldest = read_backup(&backup_path(dir.path(), BACKUP_GENERATIONS), "backup passphrase").unwrap();
+        assert_eq!(oldest.len(), 2);
+    }
+}
@@ -0,0 +1,252 @@
+/// Key rotation module.
+///
+/// This module provides functionality for:
+/// - Re-encrypting CRUSTy files (`.encrypted` files and `.crusty` archives) under a new
+///   key, replacing each file where it is
+/// - Reporting which files were re-encrypted, already used the new key, or failed
+///
+/// Each file is read, decrypted with the old key, and encrypted with the new key in
+/// memory: the plaintext never reaches the disk. The new contents are checked with the
+/// new key before they replace the file (through a temporary file and a rename), so
+/// every file can always be read with one of the two keys. A stored name is encrypted
+/// with the new key too, and a file with parity gets new parity. Files already under the
+/// new key are left alone, so an interrupted rotation can simply be run again.
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::archive;
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+use crate::file_names;
+use crate::parity;
+use crate::recipients;
+
+/// What happened to a file
+#[derive(Debug, Clone, PartialEq)]
+pub enum RotationOutcome {
+    /// Re-encrypted with the new key
+    Rotated,
+    /// Already encrypted with the new key (left alone)
+    AlreadyRotated,
+    /// Not changed, and still encrypted with the old key (or unreadable)
+    Failed(String),
+}
+
+impl fmt::Display for RotationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rotated => f.write_str("Re-encrypted"),
+            Self::AlreadyRotated => f.write_str("Already uses the new key"),
+            Self::Failed(error) => write!(f, "Failed: {}", error),
+        }
+    }
+}
+
+/// Result of one file in a rotation
+#[derive(Debug, Clone)]
+pub struct FileRotation {
+    pub path: PathBuf,
+    pub outcome: RotationOutcome,
+}
+
+/// Result of a rotation
+#[derive(Debug, Clone)]
+pub struct RotationReport {
+    pub old_fingerprint: String,
+    pub new_fingerprint: String,
+    pub files: Vec<FileRotation>,
+}
+
+impl RotationReport {
+    /// Count the files with an outcome like the given one (any error for failures)
+    pub fn count(&self, outcome: &RotationOutcome) -> usize {
+        self.files.iter()
+            .filter(|file| std::mem::discriminant(&file.outcome) == std::mem::discriminant(outcome))
+            .count()
+    }
+
+    /// Check whether every file now uses the new key (the old key can then be retired)
+    pub fn is_complete(&self) -> bool {
+        self.files.iter().all(|file| !matches!(file.outcome, RotationOutcome::Failed(_)))
+    }
+
+    /// Write the report as text, to keep with the rotation records
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        text.push_str("CRUSTy key rotation report\n");
+        text.push_str(&format!("Old key: {}\n", self.old_fingerprint));
+        text.push_str(&format!("New key: {}\n", self.new_fingerprint));
+        text.push_str(&format!(
+            "Files: {} re-encrypted, {} already used the new key, {} failed\n\n",
+            self.count(&RotationOutcome::Rotated),
+            self.count(&RotationOutcome::AlreadyRotated),
+            self.count(&RotationOutcome::Failed(String::new()))
+        ));
+        for file in &self.files {
+            text.push_str(&format!("{}: {}\n", file.path.display(), file.outcome));
+        }
+        text
+    }
+}
+
+/// Check whether a file can be rotated, by its name (encrypted files and archives)
+pub fn is_rotatable_name(path: &Path) -> bool {
+    file_names::is_encrypted_name(path) || archive::is_archive(path)
+}
+
+/// Re-encrypt a file with the new key, replacing it
+///
+/// # Returns
+/// * `Result<RotationOutcome, EncryptionError>` - Whether the file was re-encrypted or
+///   already used the new key, or an error if it could not be (the file is unchanged)
+pub fn rotate_file(path: &Path, old_key: &EncryptionKey, new_key: &EncryptionKey) -> Result<RotationOutcome, EncryptionError> {
+    let contents = fs::read(path)?;
+    let had_parity = parity::has_parity(&contents);
+    // Damaged parts are repaired; the new file is written without them
+    let (contents, _) = parity::remove_parity(contents)?;
+
+    if let (Some(_), _) = recipients::split_recipient_header(&contents)? {
+        return Err(EncryptionError::Encryption(
+            "Files for several recipients cannot be re-encrypted; encrypt the originals for the recipients again".to_string()
+        ));
+    }
+    let (_, rest) = encryption::split_kdf_header(&contents)?;
+    let (stored_name, rest) = encryption::split_name_header(rest)?;
+    let (digest, ciphertext) = encryption::split_digest_header(rest)?;
+
+    let plaintext = match encryption::decrypt_data(ciphertext, old_key) {
+        Ok(plaintext) => Zeroizing::new(plaintext),
+        Err(e) => {
+            // Left by an earlier, interrupted rotation
+            if encryption::decrypt_data(ciphertext, new_key).is_ok() {
+                return Ok(RotationOutcome::AlreadyRotated);
+            }
+            return Err(e);
+        },
+    };
+    let name = stored_name
+        .map(|name| encryption::decrypt_data(name, old_key).map(Zeroizing::new))
+        .transpose()?;
+
+    let mut rotated = encryption::kdf_header(new_key);
+    if let Some(name) = &name {
+        rotated.extend_from_slice(&encryption::name_header(&name[..], new_key)?);
+    }
+    // The hash of the original is kept, checked against the data first
+    if let Some(digest) = digest {
+        if encryption::decrypt_data(digest, old_key)?.as_slice() != Sha256::digest(&plaintext[..]).as_slice() {
+            return Err(EncryptionError::Corrupted("the decrypted data does not match the hash of the original".to_string()));
+        }
+        rotated.extend_from_slice(&encryption::digest_header(&plaintext, new_key)?);
+    }
+    let new_ciphertext = encryption::encrypt_data(&plaintext, new_key)?;
+    // Checked before the only copy of the file is replaced
+    if Zeroizing::new(encryption::decrypt_data(&new_ciphertext, new_key)?).as_slice() != plaintext.as_slice() {
+        return Err(EncryptionError::Encryption("The re-encrypted file could not be read back".to_string()));
+    }
+    rotated.extend_from_slice(&new_ciphertext);
+    if had_parity {
+        parity::append_parity(&mut rotated);
+    }
+
+    replace_file(path, &rotated)?;
+    Ok(RotationOutcome::Rotated)
+}
+
+/// Re-encrypt files with the new key, one after another
+///
+/// # Arguments
+/// * `files` - The files to re-encrypt
+/// * `old_key` - The key the files are encrypted with
+/// * `new_key` - The key to encrypt them with
+/// * `progress` - Called with the fraction of files done
+pub fn rotate_files(
+    files: &[PathBuf],
+    old_key: &EncryptionKey,
+    new_key: &EncryptionKey,
+    progress: impl Fn(f32),
+) -> RotationReport {
+    let mut report = RotationReport {
+        old_fingerprint: old_key.fingerprint(),
+        new_fingerprint: new_key.fingerprint(),
+        files: Vec::with_capacity(files.len()),
+    };
+
+    for (i, path) in files.iter().enumerate() {
+        let outcome = rotate_file(path, old_key, new_key)
+            .unwrap_or_else(|e| RotationOutcome::Failed(e.to_string()));
+        report.files.push(FileRotation { path: path.clone(), outcome });
+        progress((i + 1) as f32 / files.len() as f32);
+    }
+    report
+}
+
+/// Replace a file's contents atomically, keeping its permissions
+pub fn replace_file(path: &Path, contents: &[u8]) -> Result<(), EncryptionError> {
+    let temp = encryption::temp_destination(path);
+    let permissions = fs::metadata(path)?.permissions();
+
+    let written = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::set_permissions(&temp, permissions))
+        .and_then(|_| fs::rename(&temp, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(EncryptionError::Io(e));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use crate::backend::{BackendFactory, LocalBackend};
+
+    #[test]
+    fn test_rotate_files() {
+        let dir = tempdir().unwrap();
+        let old_key = EncryptionKey::generate();
+        let new_key = EncryptionKey::generate();
+
+        // A plain file, a file with a stored name and parity, and a file of another key
+        let source = dir.path().join("notes.txt");
+        fs::write(&source, b"meeting notes").unwrap();
+        let plain = dir.path().join("notes.txt.encrypted");
+        let named = dir.path().join("hidden.encrypted");
+        let other = dir.path().join("other.encrypted");
+        let backend = BackendFactory::create_local();
+        backend.encrypt_file(&source, &plain, &old_key, |_| {}).unwrap();
+        BackendFactory::create_local_with(LocalBackend { add_parity: true, ..LocalBackend::default() })
+            .encrypt_file_storing_name(&source, &named, &old_key, std::ffi::OsStr::new("notes.txt"), |_| {})
+            .unwrap();
+        backend.encrypt_file(&source, &other, &EncryptionKey::generate(), |_| {}).unwrap();
+        let other_contents = fs::read(&other).unwrap();
+
+        let files = vec![plain.clone(), named.clone(), other.clone()];
+        let report = rotate_files(&files, &old_key, &new_key, |_| {});
+        assert_eq!(report.count(&RotationOutcome::Rotated), 2);
+        assert!(matches!(report.files[2].outcome, RotationOutcome::Failed(_)));
+        assert!(!report.is_complete());
+        assert_eq!(fs::read(&other).unwrap(), other_contents);
+
+        // The files open with the new key only, with their names and parity
+        let decrypted = dir.path().join("decrypted.txt");
+        assert!(backend.decrypt_file(&plain, &decrypted, &old_key, |_| {}).is_err());
+        backend.decrypt_file(&plain, &decrypted, &new_key, |_| {}).unwrap();
+        assert_eq!(fs::read(&decrypted).unwrap(), b"meeting notes");
+        assert_eq!(encryption::read_stored_name(&named, &new_key).unwrap().unwrap(), b"notes.txt");
+        assert!(parity::has_parity(&fs::read(&named).unwrap()));
+
+        // Running it again leaves the rotated files alone
+        let report = rotate_files(&files[..2], &old_key, &new_key, |_| {});
+        assert_eq!(report.count(&RotationOutcome::AlreadyRotated), 2);
+        assert!(report.is_complete());
+    }
+}
@@ -0,0 +1,501 @@
+/// Key store module.
+///
+/// This module provides functionality for:
+/// - Keeping the saved keys between sessions, in `keystore` in the application data
+///   directory
+/// - Encrypting the key store at rest with a random store key kept in the OS credential
+///   store (at the protection level the admin policy sets for the saved keys), so only
+///   this user on this machine can read it
+/// - The form a saved key is written in, shared with the key backups
+/// - Key metadata: where each key came from (its provenance), for audits, and when it
+///   was created, expires, and was last used
+/// - Keeping chosen keys in the OS credential store instead (Windows Credential Manager,
+///   macOS Keychain, Secret Service on Linux), with only a reference by name written to
+///   the key store
+///
+/// The store key is created when keys are first saved. If the key store exists but its
+/// key is gone from the credential store (e.g. the user profile was moved), the key
+/// store is left untouched so its keys can still be restored from a key backup.
+use std::fs;
+use std::path::{Path, PathBuf};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{Local, NaiveDate};
+use keyring::Entry;
+use serde::{Serialize, Deserialize};
+use zeroize::Zeroizing;
+
+use crate::encryption::{self, EncryptionKey, EncryptionError};
+use crate::policy::{get_policy, KeyStoreKind};
+use crate::shared_files;
+use crate::tpm;
+
+/// Keyring entry holding the store key
+const STORE_SERVICE: &str = "CRUSTy";
+const STORE_USER: &str = "keystore";
+
+/// Prefix of the keyring entries holding keys kept in the credential store
+const CREDENTIAL_KEY_PREFIX: &str = "key:";
+
+/// Format of key creation and last use times
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Format of key expiry dates
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Marks a key store file
+const KEYSTORE_MAGIC: &[u8; 8] = b"CRUSTYKS";
+
+/// Where a key came from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyProvenance {
+    /// How the key came to this computer (e.g. "Reconstructed from transfer shares")
+    pub source: String,
+    /// File, bundle, or backup the key came from (empty if none)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub origin: String,
+    /// Transfer package the key was reconstructed from (hexadecimal ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_id: Option<String>,
+    /// Numbers of the shares the key was reconstructed from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub share_numbers: Vec<u8>,
+    /// User who added the key
+    pub added_by: String,
+    /// When the key was added (local time)
+    pub added_at: String,
+}
+
+impl KeyProvenance {
+    /// Record a key added now by the current user
+    pub fn new(source: &str) -> Self {
+        KeyProvenance {
+            source: source.to_string(),
+            origin: String::new(),
+            package_id: None,
+            share_numbers: Vec::new(),
+            added_by: std::env::var("USERNAME")
+                .or_else(|_| std::env::var("USER"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            added_at: Local::now().format(TIME_FORMAT).to_string(),
+        }
+    }
+
+    /// Record the file, bundle, or backup the key came from
+    pub fn with_origin(mut self, origin: &str) -> Self {
+        self.origin = origin.to_string();
+        self
+    }
+
+    /// Record the shares the key was reconstructed from
+    pub fn with_shares(mut self, share_numbers: Vec<u8>, package_id: Option<String>) -> Self {
+        self.share_numbers = share_numbers;
+        self.package_id = package_id;
+        self
+    }
+
+    /// Describe the provenance in one line
+    pub fn describe(&self) -> String {
+        let mut text = self.source.clone();
+        if !self.origin.is_empty() {
+            text.push_str(&format!(" ({})", self.origin));
+        }
+        if !self.share_numbers.is_empty() {
+            let numbers: Vec<String> = self.share_numbers.iter().map(u8::to_string).collect();
+            text.push_str(&format!(", shares {}", numbers.join(", ")));
+        }
+        if let Some(package_id) = &self.package_id {
+            text.push_str(&format!(" of package {}", package_id));
+        }
+        text.push_str(&format!(", added by {} on {}", self.added_by, self.added_at));
+        text
+    }
+}
+
+/// What is known about a saved key besides the key itself
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct KeyMetadata {
+    /// Where the key came from (None for keys saved before provenance was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<KeyProvenance>,
+    /// When the key was created or first saved here (local time; None for keys saved
+    /// before this was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Day the key expires (`DATE_FORMAT`; None if it does not expire)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_on: Option<String>,
+    /// When the key was last used to encrypt or decrypt (local time)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<String>,
+    /// Keyring entry holding the key, when it is kept in the OS credential store instead
+    /// of the key store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_name: Option<String>,
+}
+
+impl KeyMetadata {
+    /// Record the key as created now
+    pub fn created_now(&mut self) {
+        self.created_at = Some(Local::now().format(TIME_FORMAT).to_string());
+    }
+
+    /// Record the key as used now
+    pub fn used_now(&mut self) {
+        self.last_used = Some(Local::now().format(TIME_FORMAT).to_string());
+    }
+
+    /// Get the day the key expires (None if it does not expire)
+    pub fn expiry_date(&self) -> Option<NaiveDate> {
+        self.expires_on.as_deref()
+            .and_then(|date| NaiveDate::parse_from_str(date, DATE_FORMAT).ok())
+    }
+
+    /// Check whether the key has expired by the given day (a key expires at the start of
+    /// its expiry day)
+    pub fn expired_by(&self, today: NaiveDate) -> bool {
+        self.expiry_date().is_some_and(|expiry| expiry <= today)
+    }
+
+    /// Check whether the key has expired
+    pub fn is_expired(&self) -> bool {
+        self.expired_by(Local::now().date_naive())
+    }
+}
+
+/// A saved key as it is written to the key store and the key backups
+#[derive(Serialize, Deserialize)]
+pub struct StoredKey {
+    pub name: String,
+    /// The key (Base64)
+    pub key: String,
+    /// KDF header of a passphrase key (Base64; empty for random keys)
+    #[serde(default)]
+    pub kdf: String,
+    /// Provenance and other metadata of the key (not kept in key backups)
+    #[serde(default)]
+    pub metadata: KeyMetadata,
+}
+
+impl StoredKey {
+    /// Get the stored form of a saved key
+    pub fn new(name: &str, key: &EncryptionKey) -> Self {
+        StoredKey {
+            name: name.to_string(),
+            key: key.to_base64(),
+            kdf: STANDARD.encode(encryption::kdf_header(key)),
+            metadata: KeyMetadata::default(),
+        }
+    }
+
+    /// Get the stored form of a key kept in the OS credential store: its name only
+    pub fn reference(name: &str) -> Self {
+        StoredKey {
+            name: name.to_string(),
+            key: String::new(),
+            kdf: String::new(),
+            metadata: KeyMetadata::default(),
+        }
+    }
+
+    /// Store the key's metadata with it
+    pub fn with_metadata(mut self, metadata: &KeyMetadata) -> Self {
+        self.metadata = metadata.clone();
+        self
+    }
+
+    /// Get the saved key back with its name
+    pub fn into_key(self) -> Result<(String, EncryptionKey), EncryptionError> {
+        let mut key = EncryptionKey::from_base64(&self.key)?;
+        let kdf = STANDARD.decode(&self.kdf)
+            .map_err(|e| EncryptionError::KeyError(format!("Invalid saved key '{}': {}", self.name, e)))?;
+        key.kdf = encryption::split_kdf_header(&kdf)?.0;
+        Ok((self.name, key))
+    }
+}
+
+/// A saved key with its name and metadata
+pub type SavedKey = (String, EncryptionKey, KeyMetadata);
+
+/// Contents of the key store
+#[derive(Serialize, Deserialize)]
+struct StoredKeys {
+    keys: Vec<StoredKey>,
+}
+
+/// Default key store file
+pub fn default_path() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("crusty");
+    path.push("keystore");
+    path
+}
+
+/// The saved keys on disk, and the key they are encrypted with
+pub struct KeyStore {
+    path: PathBuf,
+    /// None until keys are first saved
+    store_key: Option<EncryptionKey>,
+}
+
+impl KeyStore {
+    /// Open the key store with the store key from the OS credential store
+    ///
+    /// # Returns
+    /// * `Result<(KeyStore, Vec<SavedKey>), EncryptionError>` - The key store and the
+    ///   keys saved in it, or an error if it cannot be read
+    pub fn open(path: PathBuf) -> Result<(Self, Vec<SavedKey>), EncryptionError> {
+        let store_key = load_store_key()?;
+        if store_key.is_none() && path.exists() {
+            return Err(EncryptionError::KeyError(
+                "the key of the saved keys is missing from the credential store. Restore them from a key backup".to_string()
+            ));
+        }
+
+        let store = KeyStore::new(path, store_key);
+        let keys = store.load()?;
+        Ok((store, keys))
+    }
+
+    /// Use a key store with the given store key (None if keys were never saved; a store
+    /// key is then created in the credential store on the first save)
+    pub fn new(path: PathBuf, store_key: Option<EncryptionKey>) -> Self {
+        KeyStore { path, store_key }
+    }
+
+    /// Get the key store file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the saved keys with their metadata (none if nothing was saved yet)
+    pub fn load(&self) -> Result<Vec<SavedKey>, EncryptionError> {
+        let store_key = match &self.store_key {
+            Some(store_key) if self.path.exists() => store_key,
+            _ => return Ok(Vec::new()),
+        };
+
+        let data = fs::read(&self.path)?;
+        let encrypted = data.strip_prefix(KEYSTORE_MAGIC.as_slice())
+            .ok_or_else(|| EncryptionError::Decryption(format!("{} is not a key store", self.path.display())))?;
+        let content = Zeroizing::new(encryption::decrypt_data(encrypted, store_key)
+            .map_err(|_| EncryptionError::Decryption("The saved keys are damaged, or were saved with another store key".to_string()))?);
+        let stored: StoredKeys = serde_json::from_slice(&content)
+            .map_err(|e| EncryptionError::Decryption(format!("Invalid key store: {}", e)))?;
+
+        stored.keys.into_iter()
+            .map(|stored| {
+                let metadata = stored.metadata.clone();
+                match &metadata.credential_name {
+                    Some(credential_name) => load_credential_key(credential_name)
+                        .map(|key| (stored.name, key, metadata)),
+                    None => stored.into_key().map(|(name, key)| (name, key, metadata)),
+                }
+            })
+            .collect()
+    }
+
+    /// Replace the saved keys and their metadata, creating the store key on the first save
+    ///
+    /// Keys kept in the OS credential store are written as a reference only.
+    pub fn save(&mut self, keys: &[SavedKey]) -> Result<(), EncryptionError> {
+        let store_key = match &self.store_key {
+            Some(store_key) => store_key.clone(),
+            None => {
+                let store_key = EncryptionKey::generate();
+                store_store_key(&store_key)?;
+                self.store_key = Some(store_key.clone());
+                store_key
+            },
+        };
+
+        let stored = StoredKeys {
+            keys: keys.iter()
+                .map(|(name, key, metadata)| match metadata.credential_name {
+                    Some(_) => StoredKey::reference(name).with_metadata(metadata),
+                    None => StoredKey::new(name, key).with_metadata(metadata),
+                })
+                .collect(),
+        };
+        let content = Zeroizing::new(serde_json::to_vec(&stored)
+            .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize saved keys: {}", e)))?);
+
+        let mut data = KEYSTORE_MAGIC.to_vec();
+        data.extend(encryption::encrypt_data(&content, &store_key)?);
+
+        let _lock = shared_files::lock(&self.path)?;
+        shared_files::write_atomic(&self.path, data)?;
+        Ok(())
+    }
+}
+
+/// Load the store key from the OS credential store (None if keys were never saved)
+fn load_store_key() -> Result<Option<EncryptionKey>, EncryptionError> {
+    let entry = Entry::new(STORE_SERVICE, STORE_USER)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    let stored = match entry.get_password() {
+        Ok(stored) => stored,
+        Err(keyring::Error::NoEntry) => return Ok(None),
+        Err(e) => return Err(EncryptionError::KeyError(format!("Failed to read the key store's key: {}", e))),
+    };
+
+    // Stored again if the policy now requires a different level
+    let bytes = tpm::unprotect_and_upgrade(STORE_USER, &stored, &get_policy().protection_for(KeyStoreKind::SavedKeys), |stored| {
+        entry.set_password(stored)
+            .map_err(|e| EncryptionError::KeyError(format!("Failed to store the key store's key: {}", e)))
+    })?;
+    let key: [u8; 32] = bytes.as_slice().try_into()
+        .map_err(|_| EncryptionError::KeyError("Invalid key store key length".to_string()))?;
+    Ok(Some(EncryptionKey { key, kdf: None }))
+}
+
+/// Store the store key in the OS credential store, at the protection level the admin
+/// policy sets for the saved keys
+fn store_store_key(key: &EncryptionKey) -> Result<(), EncryptionError> {
+    let entry = Entry::new(STORE_SERVICE, STORE_USER)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    let stored = tpm::protect(STORE_USER, &key.key, &get_policy().protection_for(KeyStoreKind::SavedKeys))?;
+    entry.set_password(&stored)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to store the key store's key: {}", e)))
+}
+
+/// Get the keyring entry name for a key kept in the credential store
+pub fn credential_name(name: &str) -> String {
+    format!("{}{}", CREDENTIAL_KEY_PREFIX, name)
+}
+
+/// Store a saved key in the OS credential store, at the protection level the admin
+/// policy sets for keys kept there
+///
+/// # Arguments
+/// * `credential_name` - The keyring entry (see `credential_name`)
+/// * `name` - The name of the key
+/// * `key` - The key
+pub fn store_credential_key(credential_name: &str, name: &str, key: &EncryptionKey) -> Result<(), EncryptionError> {
+    let entry = Entry::new(STORE_SERVICE, credential_name)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    let content = Zeroizing::new(serde_json::to_vec(&StoredKey::new(name, key))
+        .map_err(|e| EncryptionError::Encryption(format!("Failed to serialize key '{}': {}", name, e)))?);
+    let stored = tpm::protect(credential_name, &content, &get_policy().protection_for(KeyStoreKind::CredentialKeys))?;
+    entry.set_password(&stored)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to store key '{}' in the credential store: {}", name, e)))
+}
+
+/// Load a key kept in the OS credential store
+pub fn load_credential_key(credential_name: &str) -> Result<EncryptionKey, EncryptionError> {
+    let entry = Entry::new(STORE_SERVICE, credential_name)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    let stored = match entry.get_password() {
+        Ok(stored) => stored,
+        Err(keyring::Error::NoEntry) => return Err(EncryptionError::KeyError(format!(
+            "the saved key {} is missing from the credential store. Restore it from a key backup", credential_name
+        ))),
+        Err(e) => return Err(EncryptionError::KeyError(format!("Failed to read {} from the credential store: {}", credential_name, e))),
+    };
+
+    let protection = get_policy().protection_for(KeyStoreKind::CredentialKeys);
+    let content = tpm::unprotect_and_upgrade(credential_name, &stored, &protection, |stored| {
+        entry.set_password(stored)
+            .map_err(|e| EncryptionError::KeyError(format!("Failed to store {} in the credential store: {}", credential_name, e)))
+    })?;
+    let content = Zeroizing::new(content);
+    let stored: StoredKey = serde_json::from_slice(&content)
+        .map_err(|e| EncryptionError::KeyError(format!("Invalid key {} in the credential store: {}", credential_name, e)))?;
+    stored.into_key().map(|(_, key)| key)
+}
+
+/// Remove a key from the OS credential store (nothing to do if it is not there)
+pub fn remove_credential_key(credential_name: &str) -> Result<(), EncryptionError> {
+    let entry = Entry::new(STORE_SERVICE, credential_name)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to open credential store: {}", e)))?;
+
+    match entry.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(EncryptionError::KeyError(format!("Failed to remove {} from the credential store: {}", credential_name, e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::KdfParams;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("keystore");
+        let store_key = EncryptionKey::generate();
+
+        let mut store = KeyStore::new(path.clone(), Some(store_key.clone()));
+        assert!(store.load().unwrap().is_empty());
+
+        let params = KdfParams { memory_kib: 64, iterations: 1, parallelism: 1, salt: [3; 16] };
+        let passphrase_key = EncryptionKey::from_passphrase("key passphrase", &params).unwrap();
+        let provenance = KeyProvenance::new("Reconstructed from transfer shares")
+            .with_shares(vec![1, 3], Some("0011223344556677".to_string()));
+        let keys = vec![
+            ("Random".to_string(), EncryptionKey::generate(), KeyMetadata::default()),
+            ("Passphrase".to_string(), passphrase_key.clone(), KeyMetadata {
+                provenance: Some(provenance.clone()),
+                expires_on: Some("2030-01-31".to_string()),
+                ..KeyMetadata::default()
+            }),
+        ];
+        store.save(&keys).unwrap();
+
+        // Encrypted at rest
+        let data = fs::read(&path).unwrap();
+        assert!(data.starts_with(KEYSTORE_MAGIC));
+        assert!(!String::from_utf8_lossy(&data).contains("Random"));
+
+        let loaded = KeyStore::new(path.clone(), Some(store_key)).load().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0, "Random");
+        assert_eq!(loaded[0].1.to_base64(), keys[0].1.to_base64());
+        assert_eq!(loaded[1].1.kdf, passphrase_key.kdf);
+        assert_eq!(loaded[0].2, KeyMetadata::default());
+        assert_eq!(loaded[1].2.provenance, Some(provenance));
+        assert_eq!(loaded[1].2.expires_on.as_deref(), Some("2030-01-31"));
+
+        assert!(KeyStore::new(path, Some(EncryptionKey::generate())).load().is_err());
+    }
+
+    #[test]
+    fn test_credential_keys_are_referenced() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("keystore");
+        let store_key = EncryptionKey::generate();
+        let key = EncryptionKey::generate();
+
+        let metadata = KeyMetadata { credential_name: Some(credential_name("Vault")), ..KeyMetadata::default() };
+        let mut store = KeyStore::new(path.clone(), Some(store_key.clone()));
+        store.save(&[("Vault".to_string(), key.clone(), metadata)]).unwrap();
+
+        // Only the name and the keyring entry are written
+        let data = fs::read(&path).unwrap();
+        let content = encryption::decrypt_data(&data[KEYSTORE_MAGIC.len()..], &store_key).unwrap();
+        let stored: StoredKeys = serde_json::from_slice(&content).unwrap();
+        assert_eq!(stored.keys[0].name, "Vault");
+        assert!(stored.keys[0].key.is_empty());
+        assert_eq!(stored.keys[0].metadata.credential_name.as_deref(), Some("key:Vault"));
+        assert!(!String::from_utf8_lossy(&content).contains(&key.to_base64()));
+    }
+
+    #[test]
+    fn test_key_expiry() {
+        let day = |date: &str| NaiveDate::parse_from_str(date, DATE_FORMAT).unwrap();
+
+        let mut metadata = KeyMetadata::default();
+        assert!(!metadata.expired_by(day("2100-01-01")));
+
+        metadata.expires_on = Some("2030-01-31".to_string());
+        assert_eq!(metadata.expiry_date(), Some(day("2030-01-31")));
+        assert!(!metadata.expired_by(day("2030-01-30")));
+        assert!(metadata.expired_by(day("2030-01-31")));
+        assert!(metadata.expired_by(day("2030-02-01")));
+    }
+}
@@ -0,0 +1,313 @@
+/// Locale-aware formatting module.
+///
+/// This module provides functionality for:
+/// - Selecting the UI language (from the settings, or the system language)
+/// - Formatting numbers, file sizes, percentages, and dates for the selected language
+///
+/// Only what is shown to the user is formatted here. Log files, CSV reports, and other
+/// machine-readable output keep fixed formats (e.g. `2025-03-04 14:05:09` timestamps),
+/// so they can be parsed whatever language was selected when they were written.
+use std::sync::RwLock;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Serialize, Deserialize};
+
+/// Timestamp format used in log files and saved records
+pub const STORED_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// UI language
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    /// English (United States)
+    #[serde(rename = "en-US")]
+    EnglishUs,
+    /// English (United Kingdom)
+    #[serde(rename = "en-GB")]
+    EnglishUk,
+    /// German
+    #[serde(rename = "de-DE")]
+    German,
+    /// French
+    #[serde(rename = "fr-FR")]
+    French,
+    /// Spanish
+    #[serde(rename = "es-ES")]
+    Spanish,
+}
+
+impl Language {
+    /// All supported languages, in the order they are offered
+    pub const ALL: [Language; 5] = [
+        Language::EnglishUs,
+        Language::EnglishUk,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+    ];
+
+    /// Get the name of the language, in that language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::EnglishUs => "English (US)",
+            Language::EnglishUk => "English (UK)",
+            Language::German => "Deutsch",
+            Language::French => "Français",
+            Language::Spanish => "Español",
+        }
+    }
+
+    /// Get the language tag, e.g. `de-DE`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::EnglishUs => "en-US",
+            Language::EnglishUk => "en-GB",
+            Language::German => "de-DE",
+            Language::French => "fr-FR",
+            Language::Spanish => "es-ES",
+        }
+    }
+
+    /// Get the language for a locale name such as `de_DE.UTF-8` or `en-GB`
+    ///
+    /// # Returns
+    /// * `Option<Language>` - The language, or None if it is not supported
+    pub fn from_locale_name(name: &str) -> Option<Language> {
+        let name = name.split(['.', '@']).next().unwrap_or_default().replace('_', "-").to_ascii_lowercase();
+        let (language, region) = name.split_once('-').unwrap_or((&name, ""));
+
+        match (language, region) {
+            ("en", "gb" | "ie" | "au" | "nz" | "in") => Some(Language::EnglishUk),
+            ("en", _) => Some(Language::EnglishUs),
+            ("de", _) => Some(Language::German),
+            ("fr", _) => Some(Language::French),
+            ("es", _) => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// Get the language of the system, or English (US) if it is not supported
+    pub fn system() -> Language {
+        system_locale_name()
+            .and_then(|name| Language::from_locale_name(&name))
+            .unwrap_or(Language::EnglishUs)
+    }
+
+    /// Decimal separator
+    fn decimal_separator(&self) -> char {
+        match self {
+            Language::EnglishUs | Language::EnglishUk => '.',
+            Language::German | Language::French | Language::Spanish => ',',
+        }
+    }
+
+    /// Thousands separator, and the fewest integer digits that are grouped
+    fn grouping(&self) -> (char, usize) {
+        match self {
+            Language::EnglishUs | Language::EnglishUk => (',', 4),
+            Language::German => ('.', 4),
+            // No-break space, so numbers are not split across lines
+            Language::French => ('\u{a0}', 4),
+            // Spanish does not group four-digit numbers
+            Language::Spanish => ('.', 5),
+        }
+    }
+
+    /// Format a number with the given number of decimal places
+    pub fn format_number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+        let (separator, min_digits) = self.grouping();
+        let mut result = String::new();
+        if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && integer.len() >= min_digits && (integer.len() - i) % 3 == 0 {
+                result.push(separator);
+            }
+            result.push(digit);
+        }
+        if !fraction.is_empty() {
+            result.push(self.decimal_separator());
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Format a file size in bytes, KB, MB, or GB (1 KB = 1024 bytes)
+    pub fn format_size(&self, size_bytes: u64) -> String {
+        const KB: u64 = 1024;
+        const MB: u64 = KB * 1024;
+        const GB: u64 = MB * 1024;
+
+        // French uses octets
+        let units = match self {
+            Language::French => ["o", "Ko", "Mo", "Go"],
+            _ => ["B", "KB", "MB", "GB"],
+        };
+
+        let (value, unit) = if size_bytes >= GB {
+            (size_bytes as f64 / GB as f64, units[3])
+        } else if size_bytes >= MB {
+            (size_bytes as f64 / MB as f64, units[2])
+        } else if size_bytes >= KB {
+            (size_bytes as f64 / KB as f64, units[1])
+        } else {
+            return format!("{}\u{a0}{}", self.format_number(size_bytes as f64, 0), units[0]);
+        };
+        format!("{}\u{a0}{}", self.format_number(value, 1), unit)
+    }
+
+    /// Format a fraction (0.0 to 1.0) as a percentage with one decimal place
+    pub fn format_percent(&self, fraction: f32) -> String {
+        let number = self.format_number(fraction as f64 * 100.0, 1);
+        match self {
+            Language::EnglishUs | Language::EnglishUk => format!("{}%", number),
+            Language::German | Language::French | Language::Spanish => format!("{}\u{a0}%", number),
+        }
+    }
+
+    /// Format a duration, e.g. `4.2 s`, `3 min 05 s`, or `1 h 02 min`
+    pub fn format_duration(&self, duration: Duration) -> String {
+        let seconds = duration.as_secs();
+        if seconds < 60 {
+            format!("{}\u{a0}s", self.format_number(duration.as_secs_f64(), 1))
+        } else if seconds < 3600 {
+            format!("{}\u{a0}min {:02}\u{a0}s", seconds / 60, seconds % 60)
+        } else {
+            format!("{}\u{a0}h {:02}\u{a0}min", seconds / 3600, (seconds % 3600) / 60)
+        }
+    }
+
+    /// Format a date and time
+    pub fn format_datetime<Tz: TimeZone>(&self, datetime: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        let format = match self {
+            Language::EnglishUs => "%m/%d/%Y %I:%M:%S %p",
+            Language::EnglishUk | Language::French | Language::Spanish => "%d/%m/%Y %H:%M:%S",
+            Language::German => "%d.%m.%Y %H:%M:%S",
+        };
+        datetime.format(format).to_string()
+    }
+}
+
+// Language used for formatting, the system language until the settings are loaded
+lazy_static::lazy_static! {
+    static ref LANGUAGE: RwLock<Language> = RwLock::new(Language::system());
+}
+
+/// Get the selected UI language
+pub fn language() -> Language {
+    *LANGUAGE.read().unwrap()
+}
+
+/// Select the UI language (None for the system language)
+pub fn set_language(language: Option<Language>) {
+    *LANGUAGE.write().unwrap() = language.unwrap_or_else(Language::system);
+}
+
+/// Format a file size for the selected language
+pub fn format_size(size_bytes: u64) -> String {
+    language().format_size(size_bytes)
+}
+
+/// Format a fraction (0.0 to 1.0) as a percentage for the selected language
+pub fn format_percent(fraction: f32) -> String {
+    language().format_percent(fraction)
+}
+
+/// Format a duration for the selected language
+pub fn format_duration(duration: Duration) -> String {
+    language().format_duration(duration)
+}
+
+/// Format a local date and time for the selected language
+pub fn format_datetime(datetime: &DateTime<Local>) -> String {
+    language().format_datetime(datetime)
+}
+
+/// Format a stored timestamp (`STORED_TIMESTAMP_FORMAT`) for the selected language
+///
+/// Timestamps in another format are returned unchanged.
+pub fn format_stored_timestamp(timestamp: &str) -> String {
+    match NaiveDateTime::parse_from_str(timestamp, STORED_TIMESTAMP_FORMAT) {
+        Ok(datetime) => language().format_datetime(&datetime.and_utc()),
+        Err(_) => timestamp.to_string(),
+    }
+}
+
+/// Get the locale name of the system (e.g. `de_DE.UTF-8`)
+#[cfg(not(windows))]
+fn system_locale_name() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+}
+
+/// Get the locale name of the system (e.g. `de-DE`)
+#[cfg(windows)]
+fn system_locale_name() -> Option<String> {
+    use winapi::um::winnls::GetUserDefaultLocaleName;
+    use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+    let mut buffer = [0u16; LOCALE_NAME_MAX_LENGTH];
+    let length = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    if length <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..length as usize - 1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_number_and_size_formatting() {
+        assert_eq!(Language::EnglishUs.format_number(1234567.891, 2), "1,234,567.89");
+        assert_eq!(Language::German.format_number(1234567.891, 2), "1.234.567,89");
+        assert_eq!(Language::French.format_number(1234.5, 1), "1\u{a0}234,5");
+        assert_eq!(Language::Spanish.format_number(1234.5, 1), "1234,5");
+        assert_eq!(Language::Spanish.format_number(12345.0, 0), "12.345");
+        assert_eq!(Language::EnglishUk.format_number(-0.04, 1), "0.0");
+
+        assert_eq!(Language::EnglishUs.format_size(512), "512\u{a0}B");
+        assert_eq!(Language::EnglishUs.format_size(1536), "1.5\u{a0}KB");
+        assert_eq!(Language::German.format_size(5 * 1024 * 1024 * 1024), "5,0\u{a0}GB");
+        assert_eq!(Language::French.format_size(3 * 1024 * 1024), "3,0\u{a0}Mo");
+    }
+
+    #[test]
+    fn test_percent_and_date_formatting() {
+        assert_eq!(Language::EnglishUs.format_percent(0.125), "12.5%");
+        assert_eq!(Language::German.format_percent(1.0), "100,0\u{a0}%");
+
+        assert_eq!(Language::German.format_duration(Duration::from_millis(4250)), "4,2\u{a0}s");
+        assert_eq!(Language::EnglishUs.format_duration(Duration::from_secs(185)), "3\u{a0}min 05\u{a0}s");
+        assert_eq!(Language::EnglishUs.format_duration(Duration::from_secs(3720)), "1\u{a0}h 02\u{a0}min");
+
+        let datetime = NaiveDate::from_ymd_opt(2025, 3, 4).unwrap()
+            .and_hms_opt(14, 5, 9).unwrap()
+            .and_utc();
+        assert_eq!(Language::EnglishUs.format_datetime(&datetime), "03/04/2025 02:05:09 PM");
+        assert_eq!(Language::EnglishUk.format_datetime(&datetime), "04/03/2025 14:05:09");
+        assert_eq!(Language::German.format_datetime(&datetime), "04.03.2025 14:05:09");
+
+        // Stored timestamps that cannot be parsed are shown as they are
+        assert_eq!(format_stored_timestamp("yesterday"), "yesterday");
+    }
+
+    #[test]
+    fn test_locale_names() {
+        assert_eq!(Language::from_locale_name("de_DE.UTF-8"), Some(Language::German));
+        assert_eq!(Language::from_locale_name("en-GB"), Some(Language::EnglishUk));
+        assert_eq!(Language::from_locale_name("en_US"), Some(Language::EnglishUs));
+        assert_eq!(Language::from_locale_name("fr_CA@euro"), Some(Language::French));
+        assert_eq!(Language::from_locale_name("ja_JP.UTF-8"), None);
+    }
+}
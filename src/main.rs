@@ -8,37 +8,224 @@
 /// - Encrypt and decrypt individual files
 /// - Batch processing of multiple files
 /// - Key management (generation, saving, loading)
-/// - Operation logging
+/// - Operation logging (with optional central forwarding)
 /// - Progress tracking
-mod encryption;
-mod logger;
+/// - Encrypting and decrypting piped data (`--encrypt-stream`, `--decrypt-stream`)
+/// - Upgrading older encrypted files to the current format (`--migrate`)
+mod archive;
+mod in_place;
+mod cold_storage;
+mod paper_import;
+mod keystore;
+mod key_backups;
+mod instance;
+mod control_api;
+mod session_watch;
+mod folder_watch;
+mod tray;
+mod screen_capture;
+mod policy;
+mod settings;
+mod cues;
+mod deployment;
+mod key_agreement;
+mod key_rotation;
+mod audit;
+mod catalog;
+mod batch_manifest;
+mod batch_report;
+mod duplicates;
+mod secure_viewer;
+mod tpm;
+mod smartcard;
+mod remote_log;
 mod gui;
-mod backend;
-mod backend_local;
-mod backend_embedded;
 mod start_operation;
-mod split_key;
-mod split_key_gui;
-mod transfer_gui;
-mod gui_impl;
+mod services;
+mod custodians;
+mod relay;
+mod network;
+mod locale;
+mod i18n;
+mod speed_history;
+mod history;
+mod migration;
+mod run_summary;
+mod secured_folders;
 mod test_transfer;
+mod pipe;
+
+// The encryption engine, a library other programs can use too
+use crusty_core::{
+    age_format, backend, benchmark, chunk_tuning, container, embedded_protocol, encryption, file_names,
+    logger, openpgp, parity, recipients, shared_files, split_key, system_log,
+};
 
 use eframe::{run_native, NativeOptions};
 use gui::CrustyApp;
-use std::path::PathBuf;
+use settings::Settings;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 
-/// Application entry point
-fn main() -> Result<(), eframe::Error> {
-    // Initialize logger
+/// Get the operation log, creating its folder
+fn log_path() -> PathBuf {
     let mut log_path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
     log_path.push("crusty");
     log_path.push("logs");
     std::fs::create_dir_all(&log_path).expect("Failed to create log directory");
     log_path.push("operations.log");
+    log_path
+}
+
+/// Initialize the operation log as the settings and the admin policy say
+fn init_logger(log_path: &Path) {
+    let settings = Settings::load_from(&Settings::default_path()).unwrap_or_default();
+    remote_log::init_logger(log_path, &settings).expect("Failed to initialize logger");
+}
+
+/// How CRUSTy is started, shown for arguments it does not know
+const USAGE: &str = "Usage: crusty [--] [FILE]...
+       crusty --encrypt-stream <saved key name>
+       crusty --decrypt-stream <saved key name>
+       crusty --install-deployment-policy <bundle>
+       crusty --migrate <folder> <saved key name>";
+
+/// Get the files to open from CRUSTy's arguments (from a file association, or
+/// `crusty file1 file2`)
+///
+/// Arguments after `--` are always files, so files whose names start with `-` can
+/// be opened.
+///
+/// # Returns
+/// * `Result<Vec<PathBuf>, String>` - The files, or the usage if an argument is an
+///   unknown option
+fn launch_files(args: Vec<OsString>) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut options_ended = false;
+    for arg in args {
+        if !options_ended {
+            match arg.to_str() {
+                Some("--") => {
+                    options_ended = true;
+                    continue;
+                },
+                // Process serial number older versions of macOS add when started from Finder
+                Some(option) if cfg!(target_os = "macos") && option.starts_with("-psn_") => continue,
+                Some(option) if option.starts_with('-') && option.len() > 1 => {
+                    return Err(format!("Unknown option {}\n{}", option, USAGE));
+                },
+                _ => {},
+            }
+        }
+        files.push(PathBuf::from(arg));
+    }
+    Ok(files)
+}
+
+/// Application entry point
+fn main() -> Result<(), eframe::Error> {
+    // Data piped through CRUSTy is handled without opening a window
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    match pipe::PipeCommand::from_args(&args) {
+        Some(Ok(command)) => {
+            init_logger(&log_path());
+            std::process::exit(if command.run() { 0 } else { 1 });
+        },
+        Some(Err(usage)) => {
+            eprintln!("{}", usage);
+            std::process::exit(2);
+        },
+        None => {},
+    }
+    
+    // The administrator's step of a deployment: install the bundle's policy, without
+    // opening a window
+    if args.first().and_then(|arg| arg.to_str()) == Some("--install-deployment-policy") {
+        let [_, bundle] = args.as_slice() else {
+            eprintln!("Usage: crusty --install-deployment-policy <bundle>");
+            std::process::exit(2);
+        };
+        init_logger(&log_path());
+        let bundle = Path::new(bundle);
+        let result = deployment::install_bundle_policy(bundle);
+        if let Some(logger) = logger::get_logger() {
+            match &result {
+                Ok(policy_path) => logger.log_success(
+                    "Install Deployment Policy",
+                    &bundle.to_string_lossy(),
+                    &format!("Installed the policy as {}", policy_path.display())
+                ).ok(),
+                Err(e) => logger.log_error("Install Deployment Policy", &bundle.to_string_lossy(), &e.to_string()).ok(),
+            };
+        }
+        match result {
+            Ok(policy_path) => println!("Installed the deployment policy as {}", policy_path.display()),
+            Err(e) => {
+                eprintln!("Failed to install the deployment policy: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    
+    // Older encrypted files upgraded to the current format, without opening a window
+    if args.first().and_then(|arg| arg.to_str()) == Some("--migrate") {
+        let [_, folder, key_name] = args.as_slice() else {
+            eprintln!("Usage: crusty --migrate <folder> <saved key name>");
+            std::process::exit(2);
+        };
+        init_logger(&log_path());
+        std::process::exit(if migration::run(Path::new(folder), &key_name.to_string_lossy()) { 0 } else { 1 });
+    }
+    
+    // Files to open; if CRUSTy is already running, they are handed to it instead
+    let files = match launch_files(args) {
+        Ok(files) => files,
+        Err(usage) => {
+            eprintln!("{}", usage);
+            std::process::exit(2);
+        }
+    };
+    let primary = match instance::claim(&instance::default_dir(), &files) {
+        Ok(instance::Claim::Primary(primary)) => Some(primary),
+        Ok(instance::Claim::HandedOff) => return Ok(()),
+        Err(e) => {
+            eprintln!("CRUSTy is already running but did not respond, starting another copy: {}", e);
+            None
+        }
+    };
+    
+    // Initialize logger
+    let log_path = log_path();
+    
+    // Import the keys of a deployment bundle whose policy the administrator installed
+    let deployment_result = deployment::import_on_first_run();
+    
+    init_logger(&log_path);
     
-    logger::init_logger(&log_path).expect("Failed to initialize logger");
+    if let Some(logger) = logger::get_logger() {
+        let bundle_path = deployment::bundle_path();
+        match deployment_result {
+            Ok(Some(record)) => {
+                logger.log_success(
+                    "Import Deployment",
+                    &bundle_path.to_string_lossy(),
+                    &format!("Imported deployment bundle {}", record.bundle_id)
+                ).ok();
+            },
+            Ok(None) => {},
+            Err(e) => {
+                logger.log_error("Import Deployment", &bundle_path.to_string_lossy(), &e.to_string()).ok();
+            }
+        }
+    }
     
-    let app = CrustyApp::default();
+    let mut app = CrustyApp::default();
+    // Not loaded by `default`, so the headless GUI tests never read or change the
+    // user's saved keys and history
+    app.load_saved_keys();
+    app.operations.load_history(history::History::default_path());
+    app.open_files(files);
     
     // Configure window options
     let window_options = NativeOptions {
@@ -52,6 +239,18 @@ fn main() -> Result<(), eframe::Error> {
     run_native(
         "CRUSTy",
         window_options,
-        Box::new(|_cc| Box::new(app)),
+        Box::new(move |cc| {
+            if let Some(primary) = primary {
+                let ctx = cc.egui_ctx.clone();
+                app.handoff = Some(primary.listen(move || ctx.request_repaint()));
+            }
+            let ctx = cc.egui_ctx.clone();
+            app.session_watch = Some(session_watch::SessionWatch::start(move || ctx.request_repaint()));
+            let ctx = cc.egui_ctx.clone();
+            app.control_api.set_wake(move || ctx.request_repaint());
+            app.apply_control_api_setting();
+            app.apply_tray_setting(&cc.egui_ctx);
+            Box::new(app)
+        }),
     )
 }
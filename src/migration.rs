@@ -0,0 +1,255 @@
+/// File migration module.
+///
+/// This module provides functionality for:
+/// - Upgrading `.encrypted` files written by earlier versions of CRUSTy to the current
+///   format, replacing each file where it is: files without the hash of the original
+///   get it, so damage is told apart from a wrong key when they are decrypted
+/// - Recording each upgraded file in the history, so where it came from is not lost
+/// - Upgrading a folder from the command line (`crusty --migrate <folder> <saved key
+///   name>`)
+///
+/// The name, the headers, and the encrypted data of a file are kept as they are, and
+/// the upgraded file is written through a temporary file and a rename, so it can always
+/// be read with the same key. Files already in the current format are left alone, so a
+/// migration can simply be run again.
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+use crate::file_names;
+use crate::history::{History, HistoryEntry, HistoryOperation};
+use crate::key_rotation;
+use crate::keystore::{self, KeyStore};
+use crate::logger;
+use crate::parity;
+use crate::recipients;
+
+/// Operation name used in the log
+const OPERATION: &str = "Migrate";
+
+/// What happened to a file
+#[derive(Debug, Clone, PartialEq)]
+pub enum MigrationOutcome {
+    /// Upgraded to the current format
+    Upgraded,
+    /// Already in the current format (left alone)
+    AlreadyCurrent,
+    /// Not changed (or unreadable)
+    Failed(String),
+}
+
+impl fmt::Display for MigrationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Upgraded => f.write_str("Upgraded"),
+            Self::AlreadyCurrent => f.write_str("Already in the current format"),
+            Self::Failed(error) => write!(f, "Failed: {}", error),
+        }
+    }
+}
+
+/// Result of one file in a migration
+#[derive(Debug, Clone)]
+pub struct FileMigration {
+    pub path: PathBuf,
+    pub outcome: MigrationOutcome,
+}
+
+/// Result of a migration
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    pub files: Vec<FileMigration>,
+}
+
+impl MigrationReport {
+    /// Count the files with an outcome like the given one (any error for failures)
+    pub fn count(&self, outcome: &MigrationOutcome) -> usize {
+        self.files.iter()
+            .filter(|file| std::mem::discriminant(&file.outcome) == std::mem::discriminant(outcome))
+            .count()
+    }
+}
+
+/// Upgrade an encrypted file to the current format, replacing it
+///
+/// # Returns
+/// * `Result<MigrationOutcome, EncryptionError>` - Whether the file was upgraded or
+///   already in the current format, or an error if it could not be (the file is
+///   unchanged)
+pub fn migrate_file(path: &Path, key: &EncryptionKey) -> Result<MigrationOutcome, EncryptionError> {
+    let contents = fs::read(path)?;
+    let had_parity = parity::has_parity(&contents);
+    // Damaged parts are repaired; the new file is written without them
+    let (contents, _) = parity::remove_parity(contents)?;
+
+    if let (Some(_), _) = recipients::split_recipient_header(&contents)? {
+        return Err(EncryptionError::Encryption(
+            "Files for several recipients cannot be upgraded; encrypt the originals for the recipients again".to_string()
+        ));
+    }
+    let (_, rest) = encryption::split_kdf_header(&contents)?;
+    let (_, rest) = encryption::split_name_header(rest)?;
+    let (digest, ciphertext) = encryption::split_digest_header(rest)?;
+    if digest.is_some() {
+        return Ok(MigrationOutcome::AlreadyCurrent);
+    }
+
+    // The hash is taken of the data the file holds now, so it must open with the key
+    let plaintext = Zeroizing::new(encryption::decrypt_data(ciphertext, key)?);
+    let headers = &contents[..contents.len() - ciphertext.len()];
+    let mut upgraded = headers.to_vec();
+    upgraded.extend_from_slice(&encryption::digest_header(&plaintext, key)?);
+    upgraded.extend_from_slice(ciphertext);
+    if had_parity {
+        parity::append_parity(&mut upgraded);
+    }
+
+    key_rotation::replace_file(path, &upgraded)?;
+    Ok(MigrationOutcome::Upgraded)
+}
+
+/// Upgrade the encrypted files in a folder, recording each upgraded file in the history
+///
+/// # Arguments
+/// * `folder` - The folder to upgrade (subfolders are not)
+/// * `key` - The key the files are encrypted with
+/// * `history` - The history to record the upgraded files in
+///
+/// # Returns
+/// * `io::Result<MigrationReport>` - What happened to each `.encrypted` file, or an error
+///   if the folder cannot be read
+pub fn migrate_folder(folder: &Path, key: &EncryptionKey, history: &mut History) -> io::Result<MigrationReport> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path.is_file() && file_names::is_encrypted_name(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    let mut report = MigrationReport::default();
+    for path in files {
+        let outcome = migrate_file(&path, key)
+            .unwrap_or_else(|e| MigrationOutcome::Failed(e.to_string()));
+        if outcome == MigrationOutcome::Upgraded {
+            history.record(HistoryEntry::new(HistoryOperation::Migrate, path.clone(), Some(folder.to_path_buf())));
+        }
+        report.files.push(FileMigration { path, outcome });
+    }
+    Ok(report)
+}
+
+/// Upgrade a folder from the command line, with a saved key
+///
+/// Each file is logged, and the history saved with the upgraded files.
+///
+/// # Returns
+/// * `bool` - Whether every encrypted file in the folder is now in the current format
+pub fn run(folder: &Path, key_name: &str) -> bool {
+    let (_, saved_keys) = match KeyStore::open(keystore::default_path()) {
+        Ok(opened) => opened,
+        Err(e) => {
+            eprintln!("Failed to read the saved keys: {}", e);
+            return false;
+        }
+    };
+    let Some((_, key, _)) = saved_keys.iter().find(|(name, _, _)| *name == key_name) else {
+        eprintln!("No saved key is named \"{}\"", key_name);
+        return false;
+    };
+
+    let history_path = History::default_path();
+    let mut history = match History::load_from(&history_path) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Failed to load the history: {}", e);
+            return false;
+        }
+    };
+    let report = match migrate_folder(folder, key, &mut history) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", folder.display(), e);
+            return false;
+        }
+    };
+    if let Err(e) = history.save_to(&history_path) {
+        eprintln!("Failed to save the history: {}", e);
+    }
+
+    let logger = logger::get_logger();
+    for file in &report.files {
+        let name = file.path.to_string_lossy();
+        if let Some(logger) = &logger {
+            match &file.outcome {
+                MigrationOutcome::Failed(error) => logger.log_error(OPERATION, &name, error).ok(),
+                outcome => logger.log_success(OPERATION, &name, &outcome.to_string()).ok(),
+            };
+        }
+        println!("{}: {}", file.path.display(), file.outcome);
+    }
+    let failed = report.count(&MigrationOutcome::Failed(String::new()));
+    println!(
+        "{} upgraded, {} already in the current format, {} failed",
+        report.count(&MigrationOutcome::Upgraded),
+        report.count(&MigrationOutcome::AlreadyCurrent),
+        failed
+    );
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use crate::backend::{BackendFactory, LocalBackend};
+
+    #[test]
+    fn test_migrate_folder() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let source = dir.path().join("notes.txt");
+        fs::write(&source, b"meeting notes").unwrap();
+
+        // A file from before the hash was stored (with parity), one from now, and one
+        // of another key
+        let older = BackendFactory::create_local_with(LocalBackend { add_parity: true, ..LocalBackend::default() });
+        let legacy = dir.path().join("legacy.txt.encrypted");
+        older.encrypt_file(&source, &legacy, &key, |_| {}).unwrap();
+        let current = dir.path().join("current.txt.encrypted");
+        BackendFactory::create_local_with(LocalBackend { add_digest: true, ..LocalBackend::default() })
+            .encrypt_file(&source, &current, &key, |_| {}).unwrap();
+        let other = dir.path().join("other.txt.encrypted");
+        older.encrypt_file(&source, &other, &EncryptionKey::generate(), |_| {}).unwrap();
+        let other_before = fs::read(&other).unwrap();
+
+        let mut history = History::default();
+        let report = migrate_folder(dir.path(), &key, &mut history).unwrap();
+        let outcomes: Vec<_> = report.files.iter().map(|file| (file.path.clone(), file.outcome.clone())).collect();
+        assert_eq!(outcomes[0], (current.clone(), MigrationOutcome::AlreadyCurrent));
+        assert_eq!(outcomes[1], (legacy.clone(), MigrationOutcome::Upgraded));
+        assert!(matches!(outcomes[2].1, MigrationOutcome::Failed(_)));
+        assert_eq!(fs::read(&other).unwrap(), other_before);
+
+        // The upgraded file stores the hash, keeps its parity, and still decrypts
+        let upgraded = fs::read(&legacy).unwrap();
+        assert!(parity::has_parity(&upgraded));
+        let (upgraded, _) = parity::remove_parity(upgraded).unwrap();
+        let (_, rest) = encryption::split_kdf_header(&upgraded).unwrap();
+        assert!(encryption::split_digest_header(rest).unwrap().0.is_some());
+        let restored = dir.path().join("restored.txt");
+        older.decrypt_file(&legacy, &restored, &key, |_| {}).unwrap();
+        assert_eq!(fs::read(&restored).unwrap(), b"meeting notes");
+
+        // Only the upgraded file is in the history; running again changes nothing
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].operation, HistoryOperation::Migrate);
+        assert_eq!(history.entries[0].input, legacy);
+        let again = migrate_folder(dir.path(), &key, &mut history).unwrap();
+        assert_eq!(again.count(&MigrationOutcome::Upgraded), 0);
+    }
+}
@@ -0,0 +1,238 @@
+/// Network access module.
+///
+/// This module provides functionality for:
+/// - Building the HTTP client shared by the network features (relay links, central logging)
+/// - Routing requests through the proxy from the settings: the system proxy, a manual
+///   proxy (with optional authentication), or none
+/// - Keeping the proxy password in the OS credential store
+/// - Offline mode, which refuses every network request
+///
+/// Network features get their client from `http_agent` for every request, so a proxy
+/// or offline mode changed in the settings applies to the next request without a restart.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+use keyring::Entry;
+use thiserror::Error;
+
+use crate::settings::{ProxyMode, ProxySettings, Settings};
+
+/// Credential store service for the proxy password
+const PROXY_SERVICE: &str = "CRUSTy";
+/// Credential store user for the proxy password
+const PROXY_USER: &str = "proxy-password";
+
+/// Error type for network settings
+#[derive(Debug, Error)]
+pub enum NetworkError {
+    /// The manual proxy settings are invalid
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(String),
+
+    /// The proxy password could not be read or stored
+    #[error("Credential store error: {0}")]
+    CredentialStore(String),
+
+    /// Network features are turned off
+    #[error("CRUSTy is in offline mode. Turn off offline mode in Settings to use network features.")]
+    Offline,
+}
+
+// Client built from the settings at startup, replaced when the proxy settings change.
+// Offline mode is also read from the settings at startup, so nothing is sent before
+// the GUI has loaded its settings.
+lazy_static::lazy_static! {
+    static ref HTTP_AGENT: RwLock<ureq::Agent> = RwLock::new(startup_agent());
+    static ref OFFLINE: AtomicBool = AtomicBool::new(
+        Settings::load_from(&Settings::default_path()).unwrap_or_default().offline
+    );
+}
+
+/// Get the HTTP client to use for a request
+///
+/// # Returns
+/// * `Result<ureq::Agent, NetworkError>` - A client that goes through the configured
+///   proxy, or `NetworkError::Offline` in offline mode
+pub fn http_agent() -> Result<ureq::Agent, NetworkError> {
+    if is_offline() {
+        return Err(NetworkError::Offline);
+    }
+    Ok(HTTP_AGENT.read().unwrap().clone())
+}
+
+/// Check whether offline mode is on
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::SeqCst)
+}
+
+/// Turn offline mode on or off for the following requests
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
+
+/// Use new proxy settings for the following requests
+///
+/// # Arguments
+/// * `settings` - The proxy settings (the password is read from the credential store)
+///
+/// # Returns
+/// * `Result<(), NetworkError>` - Success, or an error if the settings are invalid (the
+///   previous proxy stays in use)
+pub fn apply_proxy(settings: &ProxySettings) -> Result<(), NetworkError> {
+    let password = proxy_password(settings)?;
+    let agent = build_agent(settings, password.as_deref())?;
+    *HTTP_AGENT.write().unwrap() = agent;
+    Ok(())
+}
+
+/// Store the proxy password in the OS credential store (an empty password removes it)
+pub fn set_proxy_password(password: &str) -> Result<(), NetworkError> {
+    let entry = Entry::new(PROXY_SERVICE, PROXY_USER)
+        .map_err(|e| NetworkError::CredentialStore(e.to_string()))?;
+
+    let result = if password.is_empty() {
+        match entry.delete_password() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            result => result,
+        }
+    } else {
+        entry.set_password(password)
+    };
+    result.map_err(|e| NetworkError::CredentialStore(e.to_string()))
+}
+
+/// Check the manual proxy settings
+///
+/// # Returns
+/// * `Result<(), NetworkError>` - Ok if the settings can be used, or what is wrong with them
+pub fn validate_proxy(settings: &ProxySettings) -> Result<(), NetworkError> {
+    proxy_url(settings, None).map(|_| ())
+}
+
+/// Build the client for the settings read at startup
+///
+/// If the proxy cannot be set up, the system proxy is used and the error is reported
+/// on the console, like other settings problems at startup.
+fn startup_agent() -> ureq::Agent {
+    let settings = Settings::load_from(&Settings::default_path()).unwrap_or_default();
+
+    proxy_password(&settings.proxy)
+        .and_then(|password| build_agent(&settings.proxy, password.as_deref()))
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to set up the proxy, using the system proxy: {}", e);
+            ureq::AgentBuilder::new().try_proxy_from_env(true).build()
+        })
+}
+
+/// Read the proxy password from the credential store, if the proxy needs one
+fn proxy_password(settings: &ProxySettings) -> Result<Option<String>, NetworkError> {
+    if settings.mode != ProxyMode::Manual || settings.username.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let entry = Entry::new(PROXY_SERVICE, PROXY_USER)
+        .map_err(|e| NetworkError::CredentialStore(e.to_string()))?;
+    match entry.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(NetworkError::CredentialStore(e.to_string())),
+    }
+}
+
+/// Build a client for the proxy settings
+fn build_agent(settings: &ProxySettings, password: Option<&str>) -> Result<ureq::Agent, NetworkError> {
+    let builder = ureq::AgentBuilder::new();
+
+    let builder = match settings.mode {
+        ProxyMode::System => builder.try_proxy_from_env(true),
+        ProxyMode::None => builder.try_proxy_from_env(false),
+        ProxyMode::Manual => {
+            // proxy_url always gives a URL for a manual proxy
+            let url = proxy_url(settings, password)?.unwrap_or_default();
+            let proxy = ureq::Proxy::new(url)
+                .map_err(|e| NetworkError::InvalidProxy(e.to_string()))?;
+            builder.proxy(proxy)
+        },
+    };
+
+    Ok(builder.build())
+}
+
+/// Get the proxy URL for manual proxy settings
+///
+/// # Returns
+/// * `Result<Option<String>, NetworkError>` - The URL (with the credentials), None if no
+///   manual proxy is used, or what is wrong with the settings
+fn proxy_url(settings: &ProxySettings, password: Option<&str>) -> Result<Option<String>, NetworkError> {
+    if settings.mode != ProxyMode::Manual {
+        return Ok(None);
+    }
+
+    let address = settings.address.trim().trim_end_matches('/');
+    let address = match address.split_once("://") {
+        Some(("http", address)) => address,
+        Some((scheme, _)) => {
+            return Err(NetworkError::InvalidProxy(format!("{}:// proxies are not supported, use an HTTP proxy", scheme)));
+        },
+        None => address,
+    };
+
+    let (host, port) = address.rsplit_once(':')
+        .ok_or_else(|| NetworkError::InvalidProxy("Enter the proxy address as host:port".to_string()))?;
+    if host.is_empty() || host.contains(['@', '/', ':']) || host.contains(char::is_whitespace) {
+        return Err(NetworkError::InvalidProxy(format!("'{}' is not a valid proxy host", host)));
+    }
+    if !port.parse::<u16>().is_ok_and(|port| port > 0) {
+        return Err(NetworkError::InvalidProxy("Port must be a number from 1 to 65535".to_string()));
+    }
+
+    let username = settings.username.trim();
+    if username.is_empty() {
+        return Ok(Some(format!("http://{}:{}", host, port)));
+    }
+    if username.contains([':', '@']) {
+        return Err(NetworkError::InvalidProxy("User name cannot contain ':' or '@'".to_string()));
+    }
+    Ok(Some(format!("http://{}:{}@{}:{}", username, password.unwrap_or_default(), host, port)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_url() {
+        let mut settings = ProxySettings::default();
+        assert_eq!(proxy_url(&settings, None).unwrap(), None);
+
+        settings.mode = ProxyMode::Manual;
+        assert!(validate_proxy(&settings).is_err());
+
+        settings.address = "http://proxy.example.com:3128/".to_string();
+        assert_eq!(proxy_url(&settings, None).unwrap().as_deref(), Some("http://proxy.example.com:3128"));
+
+        settings.username = "alice".to_string();
+        assert_eq!(proxy_url(&settings, Some("p@ss:word")).unwrap().as_deref(), Some("http://alice:p@ss:word@proxy.example.com:3128"));
+        assert!(build_agent(&settings, Some("p@ss:word")).is_ok());
+
+        settings.address = "socks5://proxy.example.com:1080".to_string();
+        assert!(validate_proxy(&settings).is_err());
+        settings.address = "proxy.example.com".to_string();
+        assert!(validate_proxy(&settings).is_err());
+        settings.address = "proxy.example.com:0".to_string();
+        assert!(validate_proxy(&settings).is_err());
+
+        settings.address = "proxy.example.com:3128".to_string();
+        settings.username = "domain:alice".to_string();
+        assert!(validate_proxy(&settings).is_err());
+    }
+
+    #[test]
+    fn test_offline_mode() {
+        set_offline(true);
+        assert!(matches!(http_agent(), Err(NetworkError::Offline)));
+
+        set_offline(false);
+        assert!(http_agent().is_ok());
+    }
+}
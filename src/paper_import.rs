@@ -0,0 +1,245 @@
+/// Paper backup import module.
+///
+/// This module provides functionality for:
+/// - Reading a key share or a key from a photo or scan of its paper backup
+/// - Reading the QR code first (with zbarimg from zbar-tools), and the printed text
+///   (with the tesseract OCR engine) when no usable QR code is found
+/// - Checking what was read before it is used: share checksums and lengths, the word
+///   list of share phrases, and key lengths
+///
+/// QR codes carry their own error correction, so anything read from one is exact.
+/// Printed text is only accepted for shares, whose text format has a checksum, and the
+/// text read should still be compared with the paper. Printed keys have no checksum, so
+/// keys are only imported from QR codes.
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use thiserror::Error;
+
+use crate::encryption::EncryptionKey;
+use crate::split_key::{self, SplitEncryptionKey, KEY_SHARE_LEN};
+
+/// Error type for importing a paper backup
+#[derive(Debug, Error)]
+pub enum ScanError {
+    /// The file is not an image
+    #[error("Not a readable image: {0}")]
+    Image(String),
+
+    /// Neither zbarimg nor tesseract could be run
+    #[error("Scans cannot be read: install zbar-tools (for QR codes) or tesseract (for printed text)")]
+    ToolsMissing,
+
+    /// Nothing valid was found in the image
+    #[error("No valid key share or key was found in the scan. {0}")]
+    NotFound(String),
+}
+
+/// How a paper backup was read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanMethod {
+    /// From the QR code
+    QrCode,
+    /// From the printed text (OCR)
+    Text,
+}
+
+/// Contents of a paper backup
+#[derive(Clone)]
+pub enum ScannedBackup {
+    /// A key share, as text any share field accepts
+    Share(String),
+    /// A key
+    Key(EncryptionKey),
+}
+
+/// A paper backup read from an image
+#[derive(Clone)]
+pub struct Scan {
+    pub backup: ScannedBackup,
+    pub method: ScanMethod,
+}
+
+/// Read a key share or key from a photo or scan of its paper backup
+///
+/// # Arguments
+/// * `image` - The photo or scan (any format zbarimg and tesseract read: PNG, JPEG, TIFF, ...)
+///
+/// # Returns
+/// * `Result<Scan, ScanError>` - The share or key and how it was read, or an error if
+///   nothing valid was found
+pub fn scan_backup(image: &Path) -> Result<Scan, ScanError> {
+    image::image_dimensions(image).map_err(|e| ScanError::Image(e.to_string()))?;
+
+    let qr_codes = read_qr_codes(image);
+    if let Some(codes) = &qr_codes {
+        if let Some(backup) = codes.iter().find_map(|code| backup_from_qr_code(code)) {
+            return Ok(Scan { backup, method: ScanMethod::QrCode });
+        }
+    }
+
+    let text = read_text(image);
+    if let Some(text) = &text {
+        if let Some(share) = share_from_printed_text(text) {
+            return Ok(Scan { backup: ScannedBackup::Share(share), method: ScanMethod::Text });
+        }
+    }
+
+    match (qr_codes, text) {
+        (None, None) => Err(ScanError::ToolsMissing),
+        (Some(codes), _) if !codes.is_empty() => Err(ScanError::NotFound(
+            "The QR code found is not a CRUSTy key share or key.".to_string()
+        )),
+        (_, Some(_)) => Err(ScanError::NotFound(
+            "Make sure the whole card is in the picture, sharp, and evenly lit.".to_string()
+        )),
+        _ => Err(ScanError::NotFound(
+            "No QR code was found; install tesseract to read the printed text instead.".to_string()
+        )),
+    }
+}
+
+/// Read the QR codes in an image with zbarimg
+///
+/// # Returns
+/// * `Option<Vec<String>>` - The contents of each QR code, or None if zbarimg could not
+///   be run
+fn read_qr_codes(image: &Path) -> Option<Vec<String>> {
+    let output = Command::new("zbarimg")
+        .args(["--quiet", "--raw", "-Sdisable", "-Sqrcode.enable"])
+        .arg(image)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+
+    // zbarimg exits with 4 when the image has no QR code
+    if !output.status.success() && output.status.code() != Some(4) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// Read the printed text in an image with tesseract
+///
+/// # Returns
+/// * `Option<String>` - The text, or None if tesseract could not be run
+fn read_text(image: &Path) -> Option<String> {
+    // Page segmentation mode 6: a single uniform block of text
+    let output = Command::new("tesseract")
+        .arg(image)
+        .args(["stdout", "--psm", "6"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Check a share read from a paper backup: it must parse (which checks the checksum of
+/// the text format and the word list of phrases) and have the length of a key share
+fn validated_share(content: &str) -> Option<String> {
+    let share = SplitEncryptionKey::parse_share(content).ok()?;
+    (Vec::from(&share).len() == KEY_SHARE_LEN).then(|| content.trim().to_string())
+}
+
+/// Get the share or key in the contents of a QR code
+pub fn backup_from_qr_code(content: &str) -> Option<ScannedBackup> {
+    if let Some(share) = validated_share(content) {
+        return Some(ScannedBackup::Share(share));
+    }
+    EncryptionKey::from_base64(content.trim()).ok().map(ScannedBackup::Key)
+}
+
+/// Find a share in text read by OCR
+///
+/// The share may be wrapped over several lines among other printed text. Letters OCR
+/// often confuses with digits that the share text format does not use (0, 1, 8) are
+/// read as the letters (O, I, B); the checksum then decides whether the text is right.
+pub fn share_from_printed_text(text: &str) -> Option<String> {
+    // Share text: runs of lines made only of Base32 characters and dashes
+    let normalized: Vec<String> = text.lines()
+        .map(|line| line.chars()
+            .filter(|c| !c.is_whitespace())
+            .map(split_key::normalize_share_char)
+            .collect())
+        .collect();
+    let is_share_line = |line: &String| line.len() >= 5
+        && line.chars().all(|c| matches!(c, 'A'..='Z' | '2'..='7' | '-' | '='));
+    if let Some(share) = line_groups(&normalized, is_share_line).into_iter().find_map(|lines| validated_share(&lines.concat())) {
+        return Some(share);
+    }
+
+    // Share phrase: runs of lines made only of words
+    let lines: Vec<String> = text.lines().map(|line| line.trim().to_lowercase()).collect();
+    let is_phrase_line = |line: &String| !line.is_empty()
+        && line.chars().all(|c| c.is_ascii_lowercase() || c.is_whitespace());
+    line_groups(&lines, is_phrase_line).into_iter().find_map(|lines| validated_share(&lines.join(" ")))
+}
+
+/// Get every group of consecutive lines that match, longest first (a heading or a note
+/// printed right next to the share can look like part of it)
+fn line_groups(lines: &[String], matches: impl Fn(&String) -> bool) -> Vec<&[String]> {
+    let mut groups: Vec<&[String]> = lines.split(|line| !matches(line))
+        .flat_map(|run| (0..run.len()).flat_map(move |start| (start + 1..=run.len()).map(move |end| &run[start..end])))
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_key::{share_fingerprint, KeyPurpose};
+
+    fn split_key() -> SplitEncryptionKey {
+        SplitEncryptionKey::new(&EncryptionKey::generate(), 2, 3, KeyPurpose::Standard).unwrap()
+    }
+
+    #[test]
+    fn test_share_from_printed_text() {
+        let split_key = split_key();
+        let share_text = split_key.share_to_text(2).unwrap();
+        let fingerprint = share_fingerprint(split_key.get_share(2).unwrap());
+
+        // Wrapped over lines among other text, in lower case, with letters read as digits
+        let (first, second) = share_text.split_at(30);
+        let ocr_text = format!(
+            "CRUSTy Recovery Share\n{}\n{}\nKeep this card safe\n",
+            first.replace('O', "0").replace('I', "1").replace('B', "8"), second.to_lowercase()
+        );
+        let share = share_from_printed_text(&ocr_text).unwrap();
+        let parsed = SplitEncryptionKey::parse_share(&share).unwrap();
+        assert_eq!(share_fingerprint(&parsed), fingerprint);
+
+        // A misread character in the checksummed header, or a dropped character, is rejected
+        let damaged = format!("X{}", &share_text[1..]);
+        assert!(share_from_printed_text(&damaged).is_none());
+        assert!(share_from_printed_text(&share_text[..share_text.len() - 2]).is_none());
+
+        // Share phrases are found too
+        let mnemonic = split_key.share_to_mnemonic(0).unwrap();
+        let ocr_text = format!("Share 1\n{}\n", mnemonic.to_uppercase());
+        assert!(share_from_printed_text(&ocr_text).is_some());
+    }
+
+    #[test]
+    fn test_backup_from_qr_code() {
+        let split_key = split_key();
+        let share_bytes = Vec::from(split_key.get_share(0).unwrap());
+        let share_content = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, share_bytes);
+        assert!(matches!(backup_from_qr_code(&share_content), Some(ScannedBackup::Share(_))));
+
+        let key = EncryptionKey::generate();
+        match backup_from_qr_code(&key.to_base64()) {
+            Some(ScannedBackup::Key(scanned)) => assert_eq!(scanned.key, key.key),
+            _ => panic!("The key was not read"),
+        }
+
+        assert!(backup_from_qr_code("https://example.com").is_none());
+    }
+}
@@ -0,0 +1,135 @@
+/// Pipe mode.
+///
+/// This module provides functionality for:
+/// - Encrypting or decrypting standard input to standard output with a saved key, so
+///   backup tools and scripts can pipe data through CRUSTy without a file on disk at
+///   either end, e.g. `tar -c docs | crusty --encrypt-stream "Backup" > docs.tar.encrypted`
+/// - Logging each run as the operations of the window are logged
+///
+/// No window is opened. Errors are written to standard error, and the exit code is 0
+/// only if all the data was encrypted or decrypted.
+use std::ffi::OsString;
+use std::io;
+
+use crate::backend::{BackendFactory, LocalBackend};
+use crate::keystore::{self, KeyStore};
+use crate::logger;
+
+/// Name of the standard streams in the operation log
+const STREAMS: &str = "standard input";
+
+/// Which way data is piped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PipeMode {
+    Encrypt,
+    Decrypt,
+}
+
+/// A run of CRUSTy in pipe mode
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipeCommand {
+    pub mode: PipeMode,
+    /// Name of the saved key to use
+    pub key_name: String,
+}
+
+impl PipeCommand {
+    /// Read the command from CRUSTy's arguments
+    ///
+    /// # Returns
+    /// * `Option<Result<PipeCommand, String>>` - None if CRUSTy was not started in pipe
+    ///   mode (the arguments are files to open), or the command or how it is wrong
+    pub fn from_args(args: &[OsString]) -> Option<Result<Self, String>> {
+        let mode = match args.first()?.to_str()? {
+            "--encrypt-stream" => PipeMode::Encrypt,
+            "--decrypt-stream" => PipeMode::Decrypt,
+            _ => return None,
+        };
+        Some(match &args[1..] {
+            [key_name] => Ok(PipeCommand { mode, key_name: key_name.to_string_lossy().to_string() }),
+            _ => Err(format!("Usage: crusty {} <saved key name>", args[0].to_string_lossy())),
+        })
+    }
+
+    /// Get the operation name used in the log
+    fn operation(&self) -> &'static str {
+        match self.mode {
+            PipeMode::Encrypt => "Encrypt Stream",
+            PipeMode::Decrypt => "Decrypt Stream",
+        }
+    }
+
+    /// Encrypt or decrypt standard input to standard output
+    ///
+    /// Data is encrypted in the CRUSTy format with the hash of the original, so damage
+    /// is told apart from a wrong key when it is decrypted. Every format CRUSTy reads
+    /// is decrypted.
+    ///
+    /// # Returns
+    /// * `bool` - Whether all the data was encrypted or decrypted
+    pub fn run(&self) -> bool {
+        let (_, saved_keys) = match KeyStore::open(keystore::default_path()) {
+            Ok(opened) => opened,
+            Err(e) => {
+                eprintln!("Failed to read the saved keys: {}", e);
+                return false;
+            }
+        };
+        let Some((_, key, metadata)) = saved_keys.iter().find(|(name, _, _)| *name == self.key_name) else {
+            eprintln!("No saved key is named \"{}\"", self.key_name);
+            return false;
+        };
+        if self.mode == PipeMode::Encrypt && metadata.is_expired() {
+            eprintln!(
+                "The key \"{}\" expired on {}. Consider encrypting with a newer key.",
+                self.key_name, metadata.expires_on.as_deref().unwrap_or_default()
+            );
+        }
+
+        let backend = BackendFactory::create_local_with(LocalBackend { add_digest: true, ..LocalBackend::default() });
+        let result = match self.mode {
+            PipeMode::Encrypt => backend.encrypt_stream(io::stdin().lock(), io::stdout().lock(), key),
+            PipeMode::Decrypt => backend.decrypt_stream(io::stdin().lock(), io::stdout().lock(), key),
+        };
+
+        let logger = logger::get_logger();
+        match result {
+            Ok(()) => {
+                if let Some(logger) = logger {
+                    logger.log_success(
+                        self.operation(),
+                        STREAMS,
+                        &format!("Piped to standard output with key {}", self.key_name),
+                    ).ok();
+                }
+                true
+            },
+            Err(e) => {
+                if let Some(logger) = logger {
+                    logger.log_error(self.operation(), STREAMS, &e.to_string()).ok();
+                }
+                eprintln!("Failed to {} the data: {}", if self.mode == PipeMode::Encrypt { "encrypt" } else { "decrypt" }, e);
+                false
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_args() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(PipeCommand::from_args(&args(&[])), None);
+        assert_eq!(PipeCommand::from_args(&args(&["report.pdf.encrypted"])), None);
+        assert_eq!(
+            PipeCommand::from_args(&args(&["--decrypt-stream", "Backup"])),
+            Some(Ok(PipeCommand { mode: PipeMode::Decrypt, key_name: "Backup".to_string() }))
+        );
+        assert!(matches!(PipeCommand::from_args(&args(&["--encrypt-stream"])), Some(Err(_))));
+        assert!(matches!(PipeCommand::from_args(&args(&["--encrypt-stream", "Backup", "extra"])), Some(Err(_))));
+    }
+}
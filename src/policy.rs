@@ -0,0 +1,296 @@
+/// Admin policy module for centrally managed deployments.
+///
+/// This module provides functionality for:
+/// - Loading the admin policy file (policy.json) from the machine-wide policy folder
+/// - Exposing policy settings (such as remote logging) to the rest of the application
+///
+/// The policy file is written by administrators, never by CRUSTy itself, in a folder
+/// only administrators can write to (`%ProgramData%\CRUSTy` on Windows, `/etc/crusty`
+/// elsewhere), so users cannot loosen it. When it is missing, the default (unrestricted,
+/// local-only) policy applies; when it is there but cannot be read, restricted defaults
+/// apply rather than no restrictions at all.
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+
+use crate::logger::PathRedaction;
+use crate::smartcard::TokenWrappedKey;
+use crate::tpm::ProtectionLevel;
+
+/// Remote logging settings from the admin policy
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RemoteLoggingPolicy {
+    /// HTTPS endpoint that receives batches of log records
+    pub endpoint: String,
+    /// Optional bearer token sent with every request
+    pub auth_token: Option<String>,
+    /// Maximum number of records sent in one request
+    pub batch_size: usize,
+    /// Maximum time (in seconds) a record waits before its batch is flushed
+    pub flush_interval_secs: u64,
+    /// Number of attempts before a batch is written to the offline spool
+    pub max_retries: u32,
+}
+
+impl Default for RemoteLoggingPolicy {
+    fn default() -> Self {
+        RemoteLoggingPolicy {
+            endpoint: String::new(),
+            auth_token: None,
+            batch_size: 50,
+            flush_interval_secs: 10,
+            max_retries: 3,
+        }
+    }
+}
+
+/// Relay service used to send transfer shares as one-time links
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RelayPolicy {
+    /// HTTPS base URL of the relay service
+    pub endpoint: String,
+    /// Optional bearer token sent with every request
+    pub auth_token: Option<String>,
+    /// Seconds until an unused link expires
+    pub ttl_secs: u64,
+}
+
+impl Default for RelayPolicy {
+    fn default() -> Self {
+        RelayPolicy {
+            endpoint: String::new(),
+            auth_token: None,
+            ttl_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Key file distributed to the workstation by an administrator
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProvisionedKey {
+    /// Display name of the key
+    pub name: String,
+    /// Path to the key file (Base64 encoded key)
+    pub path: PathBuf,
+}
+
+/// Secrets CRUSTy keeps in the OS credential store, each protected at its own level
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStoreKind {
+    /// Key the saved keys are encrypted with
+    SavedKeys,
+    /// Keys kept in the credential store instead of the saved keys
+    CredentialKeys,
+    /// Key the automatic key backups are encrypted with
+    KeyBackups,
+    /// Key the share custodian directory is encrypted with
+    Custodians,
+    /// Machine identity used for deployment bundles
+    MachineIdentity,
+}
+
+/// Admin policy applied to this installation
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct AdminPolicy {
+    /// Forward log records to a central endpoint (disabled when None)
+    pub remote_logging: Option<RemoteLoggingPolicy>,
+    /// Operator mode: hide key generation, import, and export
+    pub restricted_mode: bool,
+    /// Keys loaded at startup (the only keys available in restricted mode)
+    pub provisioned_keys: Vec<ProvisionedKey>,
+    /// Protection level for secrets kept in the OS credential store
+    pub key_protection: ProtectionLevel,
+    /// Protection level of particular key stores, instead of `key_protection`
+    pub key_store_protection: HashMap<KeyStoreKind, ProtectionLevel>,
+    /// Keys wrapped by a smartcard, unwrapped with the card PIN when needed
+    pub token_keys: Vec<TokenWrappedKey>,
+    /// How long (in seconds) an entered smartcard PIN may be reused; 0 never caches it
+    pub pin_cache_secs: u64,
+    /// Require a reason before any file is decrypted
+    pub require_decrypt_reason: bool,
+    /// How file paths are written to the log
+    pub path_redaction: PathRedaction,
+    /// Secret mixed into redacted path IDs (use the same value on all machines to correlate
+    /// them); when empty, each installation makes a random one
+    pub redaction_salt: String,
+    /// How often (in days) custodians are reminded to verify their key shares; 0 uses the default
+    pub share_verification_days: u64,
+    /// Relay for sending transfer shares as one-time links (disabled when None)
+    pub relay: Option<RelayPolicy>,
+    /// Never start the local control API, whatever the user settings say
+    pub disable_control_api: bool,
+    /// Send log entries to the system log (syslog or the Windows Event Log), whatever
+    /// the user settings say
+    pub system_log: bool,
+}
+
+impl AdminPolicy {
+    /// Days between key share verifications when the policy does not set them
+    pub const DEFAULT_SHARE_VERIFICATION_DAYS: u64 = 90;
+
+    /// How often (in days) key shares should be verified
+    pub fn share_verification_interval(&self) -> u64 {
+        if self.share_verification_days == 0 {
+            Self::DEFAULT_SHARE_VERIFICATION_DAYS
+        } else {
+            self.share_verification_days
+        }
+    }
+
+    /// Protection level a key store's secret is required to have
+    pub fn protection_for(&self, store: KeyStoreKind) -> ProtectionLevel {
+        self.key_store_protection.get(&store).cloned().unwrap_or_else(|| self.key_protection.clone())
+    }
+
+    /// Default location of the policy file, in the machine-wide policy folder
+    pub fn default_path() -> PathBuf {
+        policy_dir().join("policy.json")
+    }
+
+    /// Policy applied when the policy file exists but cannot be read or parsed
+    ///
+    /// Whatever the file was meant to restrict, the most restrictive settings apply:
+    /// keys cannot be managed, decrypting needs a reason, paths are not logged, and
+    /// the control API stays off.
+    pub fn restricted_defaults() -> Self {
+        AdminPolicy {
+            restricted_mode: true,
+            require_decrypt_reason: true,
+            path_redaction: PathRedaction::Hash,
+            disable_control_api: true,
+            system_log: true,
+            ..AdminPolicy::default()
+        }
+    }
+
+    /// Load the policy from the specified file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the policy file
+    ///
+    /// # Returns
+    /// * `io::Result<AdminPolicy>` - The parsed policy, the default policy if the file
+    ///   does not exist, or an error if the file cannot be read or parsed
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(AdminPolicy::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid policy file: {}", e)))
+    }
+}
+
+/// Machine-wide folder of the policy file, writable by administrators only
+pub fn policy_dir() -> PathBuf {
+    #[cfg(windows)]
+    {
+        let program_data = std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(program_data).join("CRUSTy")
+    }
+    #[cfg(not(windows))]
+    {
+        PathBuf::from("/etc/crusty")
+    }
+}
+
+/// Load a policy file, applying restricted defaults if it cannot be read
+///
+/// # Returns
+/// * `(AdminPolicy, Option<String>)` - The policy to apply, and why the file could not
+///   be read (if it could not)
+fn load_or_restrict(path: &Path) -> (AdminPolicy, Option<String>) {
+    match AdminPolicy::load_from(path) {
+        Ok(policy) => (policy, None),
+        Err(e) => (AdminPolicy::restricted_defaults(), Some(format!("{}: {}", path.display(), e))),
+    }
+}
+
+// Policy loaded once at startup
+lazy_static::lazy_static! {
+    static ref APP_POLICY: (AdminPolicy, Option<String>) = load_or_restrict(&AdminPolicy::default_path());
+}
+
+/// Get the admin policy for this installation
+///
+/// # Returns
+/// * `&'static AdminPolicy` - The policy loaded at startup
+pub fn get_policy() -> &'static AdminPolicy {
+    &APP_POLICY.0
+}
+
+/// Get why the policy file could not be read, if restricted defaults apply instead
+///
+/// The error is shown in the UI, and logged when the logger is initialized.
+pub fn load_error() -> Option<&'static str> {
+    APP_POLICY.1.as_deref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_from() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("policy.json");
+
+        // No policy file: no restrictions
+        let policy = AdminPolicy::load_from(&path).unwrap();
+        assert!(!policy.restricted_mode);
+        assert!(policy.remote_logging.is_none());
+
+        // Missing settings take their defaults
+        fs::write(&path, r#"{ "restricted_mode": true, "remote_logging": { "endpoint": "https://logs.example.com" } }"#).unwrap();
+        let policy = AdminPolicy::load_from(&path).unwrap();
+        assert!(policy.restricted_mode);
+        assert_eq!(policy.share_verification_interval(), AdminPolicy::DEFAULT_SHARE_VERIFICATION_DAYS);
+        let remote = policy.remote_logging.unwrap();
+        assert_eq!(remote.endpoint, "https://logs.example.com");
+        assert_eq!(remote.batch_size, 50);
+
+        fs::write(&path, r#"{ "share_verification_days": 30 }"#).unwrap();
+        assert_eq!(AdminPolicy::load_from(&path).unwrap().share_verification_interval(), 30);
+
+        // Key stores use the default protection unless the policy sets their own
+        fs::write(&path, r#"{ "key_protection": { "type": "tpm" }, "key_store_protection": { "key_backups": { "type": "software" } } }"#).unwrap();
+        let policy = AdminPolicy::load_from(&path).unwrap();
+        assert_eq!(policy.protection_for(KeyStoreKind::SavedKeys), ProtectionLevel::Tpm { pcrs: Vec::new() });
+        assert_eq!(policy.protection_for(KeyStoreKind::KeyBackups), ProtectionLevel::Software);
+
+        fs::write(&path, r#"{ "restricted_mode": tru"#).unwrap();
+        assert_eq!(AdminPolicy::load_from(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_unreadable_policy_restricts() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("policy.json");
+
+        let (policy, error) = load_or_restrict(&path);
+        assert!(!policy.restricted_mode);
+        assert!(error.is_none());
+
+        // A damaged policy does not lift the restrictions it held
+        fs::write(&path, r#"{ "restricted_mode": false, "require_decrypt_reason": fals"#).unwrap();
+        let (policy, error) = load_or_restrict(&path);
+        assert!(policy.restricted_mode);
+        assert!(policy.require_decrypt_reason);
+        assert!(policy.disable_control_api);
+        assert_eq!(policy.path_redaction, PathRedaction::Hash);
+        assert!(error.unwrap().contains("Invalid policy file"));
+
+        // So does a policy that cannot be read at all
+        fs::remove_file(&path).unwrap();
+        fs::create_dir(&path).unwrap();
+        assert!(load_or_restrict(&path).0.restricted_mode);
+    }
+}
@@ -0,0 +1,331 @@
+/// Relay service client for short-lived transfer links.
+///
+/// This module provides functionality for:
+/// - Uploading an encrypted share bundle to the relay configured in the admin policy
+/// - Creating one-time links that expire after a time-to-live
+/// - Fetching a bundle from a link and deleting it from the relay
+///
+/// Bundles are encrypted with AES-256-GCM before upload. The key is only carried in
+/// the link's fragment (after `#`), which is never sent to the relay, so the relay
+/// operator cannot read the shares it stores.
+use std::time::Duration;
+
+use base64::{Engine as _, engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD}};
+use serde::{Serialize, Deserialize};
+
+use crate::encryption::{self, EncryptionKey};
+use crate::network::http_agent;
+use crate::policy::RelayPolicy;
+use crate::split_key::SplitKeyError;
+
+/// Shares carried by a relay link
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ShareBundle {
+    /// Shares in text format
+    pub shares: Vec<String>,
+}
+
+/// Upload request sent to the relay
+#[derive(Serialize)]
+struct UploadRequest {
+    /// Encrypted bundle (Base64)
+    ciphertext: String,
+    /// Seconds until the relay deletes the bundle
+    ttl_secs: u64,
+}
+
+/// Relay response to an upload
+#[derive(Deserialize)]
+struct UploadResponse {
+    /// ID of the stored bundle
+    id: String,
+}
+
+/// Relay response to a fetch
+#[derive(Deserialize)]
+struct FetchResponse {
+    /// Encrypted bundle (Base64)
+    ciphertext: String,
+}
+
+/// Add the relay's bearer token to a request, if configured
+fn authorize(request: ureq::Request, policy: &RelayPolicy) -> ureq::Request {
+    match &policy.auth_token {
+        Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+/// URL of a stored bundle on the relay
+fn item_url(policy: &RelayPolicy, id: &str) -> String {
+    format!("{}/v1/items/{}", policy.endpoint.trim_end_matches('/'), id)
+}
+
+/// Upload a share bundle and create a one-time link for it
+///
+/// # Arguments
+/// * `policy` - Relay settings from the admin policy
+/// * `bundle` - The shares to send
+///
+/// # Returns
+/// * `Result<String, SplitKeyError>` - The link to give to the recipient
+pub fn upload(policy: &RelayPolicy, bundle: &ShareBundle) -> Result<String, SplitKeyError> {
+    if !policy.endpoint.starts_with("https://") {
+        return Err(SplitKeyError::Transfer(format!("Relay endpoint must use HTTPS: {}", policy.endpoint)));
+    }
+
+    let agent = http_agent().map_err(|e| SplitKeyError::Transfer(e.to_string()))?;
+    upload_with(&agent, policy, bundle)
+}
+
+/// Upload a share bundle through the given client (see `upload`)
+fn upload_with(agent: &ureq::Agent, policy: &RelayPolicy, bundle: &ShareBundle) -> Result<String, SplitKeyError> {
+    let key = EncryptionKey::generate();
+    let content = serde_json::to_vec(bundle)
+        .map_err(|e| SplitKeyError::Transfer(format!("Failed to serialize share bundle: {}", e)))?;
+    let ciphertext = encryption::encrypt_data(&content, &key)
+        .map_err(|e| SplitKeyError::Transfer(e.to_string()))?;
+
+    let body = serde_json::to_string(&UploadRequest {
+        ciphertext: STANDARD.encode(ciphertext),
+        ttl_secs: policy.ttl_secs,
+    }).map_err(|e| SplitKeyError::Transfer(e.to_string()))?;
+
+    let request = agent
+        .post(&format!("{}/v1/items", policy.endpoint.trim_end_matches('/')))
+        .timeout(Duration::from_secs(30))
+        .set("Content-Type", "application/json");
+    let response = authorize(request, policy)
+        .send_string(&body)
+        .map_err(|e| SplitKeyError::Transfer(format!("Relay upload failed: {}", e)))?
+        .into_string()?;
+
+    let response: UploadResponse = serde_json::from_str(&response)
+        .map_err(|e| SplitKeyError::Transfer(format!("Invalid relay response: {}", e)))?;
+
+    Ok(format!("{}#{}", item_url(policy, &response.id), URL_SAFE_NO_PAD.encode(key.key)))
+}
+
+/// Fetch a share bundle from a link and delete it from the relay
+///
+/// # Arguments
+/// * `policy` - Relay settings from the admin policy
+/// * `link` - The link created by `upload`
+///
+/// # Returns
+/// * `Result<ShareBundle, SplitKeyError>` - The shares, or an error if the link is
+///   invalid, expired, or was already used
+pub fn fetch(policy: &RelayPolicy, link: &str) -> Result<ShareBundle, SplitKeyError> {
+    let agent = http_agent().map_err(|e| SplitKeyError::Transfer(e.to_string()))?;
+    fetch_with(&agent, policy, link)
+}
+
+/// Fetch and delete a share bundle through the given client (see `fetch`)
+fn fetch_with(agent: &ureq::Agent, policy: &RelayPolicy, link: &str) -> Result<ShareBundle, SplitKeyError> {
+    let (url, key) = link.trim().split_once('#')
+        .ok_or_else(|| SplitKeyError::Transfer("Relay link has no key".to_string()))?;
+
+    // Only follow links to the configured relay, so the bundle key is never sent elsewhere
+    if !url.starts_with(&item_url(policy, "")) {
+        return Err(SplitKeyError::Transfer("Link does not point to the configured relay".to_string()));
+    }
+
+    let key: [u8; 32] = URL_SAFE_NO_PAD.decode(key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SplitKeyError::Transfer("Invalid key in relay link".to_string()))?;
+
+    let response = match authorize(agent.get(url).timeout(Duration::from_secs(30)), policy).call() {
+        Ok(response) => response.into_string()?,
+        Err(ureq::Error::Status(404, _)) | Err(ureq::Error::Status(410, _)) => {
+            return Err(SplitKeyError::Transfer("The link has expired or was already used".to_string()));
+        },
+        Err(e) => return Err(SplitKeyError::Transfer(format!("Relay fetch failed: {}", e))),
+    };
+
+    // Delete the bundle before using it (relays that delete on fetch answer 404)
+    match authorize(agent.delete(url).timeout(Duration::from_secs(30)), policy).call() {
+        Ok(_) | Err(ureq::Error::Status(404, _)) => {},
+        Err(e) => return Err(SplitKeyError::Transfer(format!("Failed to delete the bundle from the relay: {}", e))),
+    }
+
+    let response: FetchResponse = serde_json::from_str(&response)
+        .map_err(|e| SplitKeyError::Transfer(format!("Invalid relay response: {}", e)))?;
+    let ciphertext = STANDARD.decode(response.ciphertext)
+        .map_err(|e| SplitKeyError::Transfer(format!("Invalid relay response: {}", e)))?;
+    let content = encryption::decrypt_data(&ciphertext, &EncryptionKey { key, kdf: None })
+        .map_err(|e| SplitKeyError::Transfer(e.to_string()))?;
+
+    serde_json::from_slice(&content)
+        .map_err(|e| SplitKeyError::Transfer(format!("Invalid share bundle: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Instant;
+
+    /// What the mock relay stored and was sent
+    #[derive(Default)]
+    struct RelayState {
+        /// Stored ciphertexts by ID, with when they expire
+        items: HashMap<String, (String, Instant)>,
+        /// Each request as method, path, authorization, and body
+        requests: Vec<(String, String, Option<String>, String)>,
+    }
+
+    /// Relay on a local port, storing bundles in memory until fetched or expired
+    struct MockRelay {
+        endpoint: String,
+        state: Arc<Mutex<RelayState>>,
+    }
+
+    impl MockRelay {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+            let state = Arc::new(Mutex::new(RelayState::default()));
+            let server_state = state.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    handle(stream.unwrap(), &server_state);
+                }
+            });
+            MockRelay { endpoint, state }
+        }
+
+        fn policy(&self, ttl_secs: u64) -> RelayPolicy {
+            RelayPolicy {
+                endpoint: self.endpoint.clone(),
+                auth_token: Some("relay-token".to_string()),
+                ttl_secs,
+            }
+        }
+    }
+
+    /// Answer one request, closing the connection after it
+    fn handle(mut stream: TcpStream, state: &Mutex<RelayState>) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut parts = request_line.split_whitespace();
+        let (method, path) = (parts.next().unwrap().to_string(), parts.next().unwrap().to_string());
+
+        let (mut length, mut authorization) = (0, None);
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            let Some((name, value)) = header.trim_end().split_once(": ") else { break };
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => length = value.parse().unwrap(),
+                "authorization" => authorization = Some(value.to_string()),
+                _ => {},
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        let body = String::from_utf8(body).unwrap();
+
+        let mut state = state.lock().unwrap();
+        state.requests.push((method.clone(), path.clone(), authorization, body.clone()));
+        let id = path.strip_prefix("/v1/items/").unwrap_or("").to_string();
+        let (status, response) = match method.as_str() {
+            "POST" => {
+                let upload: serde_json::Value = serde_json::from_str(&body).unwrap();
+                let id = format!("item-{}", state.requests.len());
+                let expires = Instant::now() + Duration::from_secs(upload["ttl_secs"].as_u64().unwrap());
+                state.items.insert(id.clone(), (upload["ciphertext"].as_str().unwrap().to_string(), expires));
+                ("201 Created", serde_json::json!({ "id": id }).to_string())
+            },
+            "GET" => match state.items.get(&id) {
+                Some((_, expires)) if *expires <= Instant::now() => {
+                    state.items.remove(&id);
+                    ("410 Gone", String::new())
+                },
+                Some((ciphertext, _)) => ("200 OK", serde_json::json!({ "ciphertext": ciphertext }).to_string()),
+                None => ("404 Not Found", String::new()),
+            },
+            "DELETE" => match state.items.remove(&id) {
+                Some(_) => ("204 No Content", String::new()),
+                None => ("404 Not Found", String::new()),
+            },
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, response.len(), response
+        ).unwrap();
+    }
+
+    fn bundle() -> ShareBundle {
+        ShareBundle { shares: vec!["CRUSTY-SHARE-2-ABCDEFGH".to_string()] }
+    }
+
+    #[test]
+    fn test_upload_and_fetch_once() {
+        let relay = MockRelay::start();
+        let policy = relay.policy(3600);
+        let agent = ureq::Agent::new();
+
+        // Only HTTPS relays are used outside the tests
+        assert!(upload(&policy, &bundle()).unwrap_err().to_string().contains("must use HTTPS"));
+
+        let link = upload_with(&agent, &policy, &bundle()).unwrap();
+        let (url, key) = link.split_once('#').unwrap();
+        assert!(url.starts_with(&format!("{}/v1/items/", relay.endpoint)));
+        {
+            // The relay gets the time-to-live and the token, but neither the key nor the shares
+            let state = relay.state.lock().unwrap();
+            let (method, _, authorization, body) = &state.requests[0];
+            assert_eq!(method, "POST");
+            assert_eq!(authorization.as_deref(), Some("Bearer relay-token"));
+            assert_eq!(serde_json::from_str::<serde_json::Value>(body).unwrap()["ttl_secs"], 3600);
+            assert!(!body.contains(key) && !body.contains("CRUSTY-SHARE"));
+        }
+
+        // Fetching deletes the bundle, so the link works once
+        assert_eq!(fetch_with(&agent, &policy, &link).unwrap().shares, bundle().shares);
+        {
+            let state = relay.state.lock().unwrap();
+            assert!(state.items.is_empty());
+            let methods: Vec<&str> = state.requests.iter().map(|(method, ..)| method.as_str()).collect();
+            assert_eq!(methods, ["POST", "GET", "DELETE"]);
+            assert!(state.requests.iter().all(|(_, path, ..)| !path.contains(key)));
+        }
+        let error = fetch_with(&agent, &policy, &link).unwrap_err();
+        assert!(error.to_string().contains("expired or was already used"));
+    }
+
+    #[test]
+    fn test_expired_and_foreign_links() {
+        let relay = MockRelay::start();
+        let agent = ureq::Agent::new();
+
+        // A link is not fetched after its time-to-live
+        let policy = relay.policy(0);
+        let link = upload_with(&agent, &policy, &bundle()).unwrap();
+        let error = fetch_with(&agent, &policy, &link).unwrap_err();
+        assert!(error.to_string().contains("expired or was already used"));
+
+        // Links to another server, or without their key, are not followed
+        let policy = relay.policy(3600);
+        let link = upload_with(&agent, &policy, &bundle()).unwrap();
+        let requests = relay.state.lock().unwrap().requests.len();
+        let (url, key) = link.split_once('#').unwrap();
+        let foreign = link.replacen(&relay.endpoint, "http://relay.example.com", 1);
+        assert!(fetch_with(&agent, &policy, &foreign).unwrap_err().to_string().contains("configured relay"));
+        assert!(fetch_with(&agent, &policy, url).unwrap_err().to_string().contains("no key"));
+        assert!(fetch_with(&agent, &policy, &format!("{}#{}", url, &key[1..])).unwrap_err().to_string().contains("Invalid key"));
+        assert_eq!(relay.state.lock().unwrap().requests.len(), requests);
+
+        // A link with the wrong key cannot open the bundle
+        let wrong_key = URL_SAFE_NO_PAD.encode([7u8; 32]);
+        assert!(fetch_with(&agent, &policy, &format!("{}#{}", url, wrong_key)).is_err());
+    }
+}
@@ -0,0 +1,502 @@
+/// Remote log forwarding for centrally managed deployments.
+///
+/// This module provides functionality for:
+/// - Forwarding log entries to a central HTTPS endpoint configured in the admin policy
+/// - Batching records to limit the number of requests
+/// - Retrying failed requests with backoff
+/// - Spooling undeliverable batches to disk and resending them once the endpoint is reachable
+/// - Starting the app's logger with the path redaction and forwarding of the admin policy
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+
+use crate::logger::{self, LogEntry, LogForwarder, Logger};
+use crate::network::{http_agent, is_offline};
+use crate::policy::{self, get_policy, AdminPolicy, RemoteLoggingPolicy};
+use crate::settings::Settings;
+use crate::system_log::SystemLog;
+
+/// Structured record sent to the central endpoint
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RemoteLogRecord {
+    /// Name of the workstation that produced the record
+    pub host: String,
+    /// Name of the user running CRUSTy
+    pub user: String,
+    /// CRUSTy version
+    pub app_version: String,
+    /// The original log entry
+    #[serde(flatten)]
+    pub entry: LogEntry,
+}
+
+impl RemoteLogRecord {
+    /// Create a record for the current workstation and user
+    pub fn new(entry: LogEntry) -> Self {
+        RemoteLogRecord {
+            host: std::env::var("COMPUTERNAME")
+                .or_else(|_| std::env::var("HOSTNAME"))
+                .or_else(|_| fs::read_to_string("/etc/hostname").map(|s| s.trim().to_string()))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            user: std::env::var("USERNAME")
+                .or_else(|_| std::env::var("USER"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            entry,
+        }
+    }
+}
+
+/// Forwards log entries to the central endpoint from a background thread
+pub struct RemoteLogForwarder {
+    /// Channel to the forwarding thread
+    sender: Sender<RemoteLogRecord>,
+}
+
+impl RemoteLogForwarder {
+    /// Start forwarding log entries according to the policy
+    ///
+    /// # Arguments
+    /// * `policy` - Remote logging settings from the admin policy
+    /// * `spool_path` - File used to store batches that could not be delivered
+    ///
+    /// # Returns
+    /// * `io::Result<RemoteLogForwarder>` - A running forwarder or an error if the
+    ///   endpoint is not a valid HTTPS URL
+    pub fn start(policy: RemoteLoggingPolicy, spool_path: PathBuf) -> io::Result<Self> {
+        if !policy.endpoint.starts_with("https://") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Remote logging endpoint must use HTTPS: {}", policy.endpoint)
+            ));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("crusty-remote-log".to_string())
+            .spawn(move || {
+                let mut endpoint = HttpsEndpoint { policy: policy.clone() };
+                run_forwarder(&policy, &spool_path, receiver, &mut endpoint)
+            })?;
+
+        Ok(RemoteLogForwarder { sender })
+    }
+
+}
+
+impl LogForwarder for RemoteLogForwarder {
+    /// Queue a log entry for forwarding
+    fn forward(&self, entry: &LogEntry) {
+        // If the forwarding thread has stopped there is nothing useful to do here;
+        // the entry is still in the local log file.
+        let _ = self.sender.send(RemoteLogRecord::new(entry.clone()));
+    }
+}
+
+/// Initialize the global logger as the settings and the admin policy require
+///
+/// # Arguments
+/// * `log_path` - Path to the log file
+/// * `settings` - The settings (log rotation, format, and system log)
+///
+/// # Returns
+/// * `io::Result<()>` - Success or an error
+pub fn init_logger(log_path: &Path, settings: &Settings) -> io::Result<()> {
+    let mut logger = Logger::new(log_path)?;
+    logger.set_format(settings.log_format);
+    if let Err(e) = logger.set_rotation(settings.log_rotation.clone()) {
+        eprintln!("Failed to delete old logs: {}", e);
+    }
+    
+    let policy = get_policy();
+    
+    // Send entries to the system log if the settings or the admin policy ask for it
+    if settings.system_log || policy.system_log {
+        match SystemLog::open() {
+            Ok(system_log) => logger.set_system_log(Some(system_log)),
+            Err(e) => eprintln!("System log disabled: {}", e),
+        }
+    }
+    // Without a salt from the policy, path IDs could be matched against guessed paths, so
+    // each installation makes its own
+    let salt = if policy.redaction_salt.is_empty() {
+        match install_salt(&log_path.with_file_name("redaction_salt")) {
+            Ok(salt) => salt,
+            Err(e) => {
+                eprintln!("Failed to read the path redaction salt, using a new one: {}", e);
+                new_salt()
+            }
+        }
+    } else {
+        policy.redaction_salt.clone()
+    };
+    logger.set_path_redaction(policy.path_redaction, &salt);
+    
+    // Enable remote forwarding if the admin policy requires it
+    if let Some(remote_policy) = &policy.remote_logging {
+        let spool_path = log_path.with_file_name("remote_spool.jsonl");
+        match RemoteLogForwarder::start(remote_policy.clone(), spool_path) {
+            Ok(forwarder) => logger.set_remote_forwarder(forwarder),
+            Err(e) => eprintln!("Remote logging disabled: {}", e),
+        }
+    }
+    
+    // Restricted defaults apply when the policy file cannot be read; the log (and the
+    // system log, where administrators look) says why
+    if let Some(error) = policy::load_error() {
+        let message = format!("Failed to load admin policy, applying restricted defaults: {}", error);
+        logger.log_error("Load Policy", &AdminPolicy::default_path().to_string_lossy(), &message).ok();
+    }
+    
+    logger::init_logger(logger);
+    Ok(())
+}
+
+/// Make a random salt for redacted path IDs
+fn new_salt() -> String {
+    let mut salt = [0u8; 32];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut salt);
+    salt.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Read this installation's salt for redacted path IDs, making it on first use
+///
+/// The salt is kept in a file only the user can read, so the same path keeps the same
+/// ID from one start to the next.
+fn install_salt(path: &Path) -> io::Result<String> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    match options.open(path) {
+        Ok(mut file) => {
+            let salt = new_salt();
+            file.write_all(salt.as_bytes())?;
+            file.sync_all()?;
+            Ok(salt)
+        },
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+            let salt = fs::read_to_string(path)?.trim().to_string();
+            if salt.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is empty", path.display())));
+            }
+            Ok(salt)
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// Where batches of records are delivered
+trait BatchSender {
+    /// Whether batches should wait in the spool rather than be sent
+    fn offline(&self) -> bool;
+
+    /// Delay before the first retry of a failed batch (doubled for each further retry)
+    fn retry_delay(&self) -> Duration;
+
+    /// Send one batch
+    fn send(&mut self, batch: &[RemoteLogRecord]) -> Result<(), String>;
+}
+
+/// The central HTTPS endpoint of the policy
+struct HttpsEndpoint {
+    policy: RemoteLoggingPolicy,
+}
+
+impl BatchSender for HttpsEndpoint {
+    fn offline(&self) -> bool {
+        is_offline()
+    }
+
+    fn retry_delay(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+
+    fn send(&mut self, batch: &[RemoteLogRecord]) -> Result<(), String> {
+        send_batch(&self.policy, batch)
+    }
+}
+
+/// Forwarding thread: collect records into batches and deliver them
+fn run_forwarder(
+    policy: &RemoteLoggingPolicy,
+    spool_path: &Path,
+    receiver: Receiver<RemoteLogRecord>,
+    sender: &mut impl BatchSender,
+) {
+    let flush_interval = Duration::from_secs(policy.flush_interval_secs.max(1));
+    let batch_size = policy.batch_size.max(1);
+    let mut batch: Vec<RemoteLogRecord> = Vec::with_capacity(batch_size);
+    let mut batch_started = Instant::now();
+
+    loop {
+        let disconnected = match receiver.recv_timeout(flush_interval) {
+            Ok(record) => {
+                if batch.is_empty() {
+                    batch_started = Instant::now();
+                }
+                batch.push(record);
+                false
+            },
+            Err(RecvTimeoutError::Timeout) => false,
+            Err(RecvTimeoutError::Disconnected) => true,
+        };
+
+        let due = batch.len() >= batch_size || batch_started.elapsed() >= flush_interval;
+
+        if !batch.is_empty() && (due || disconnected) {
+            // In offline mode the batch waits in the spool until offline mode is turned off.
+            // Otherwise, deliver anything left over from earlier outages first to keep ordering.
+            if sender.offline() || !resend_spool(policy, spool_path, sender) || !send_with_retry(policy, sender, &batch) {
+                if let Err(e) = append_to_spool(spool_path, &batch) {
+                    eprintln!("Failed to spool remote log records: {}", e);
+                }
+            }
+            batch.clear();
+        }
+
+        if disconnected {
+            break;
+        }
+    }
+}
+
+/// Send a batch, retrying with exponential backoff
+fn send_with_retry(policy: &RemoteLoggingPolicy, sender: &mut impl BatchSender, batch: &[RemoteLogRecord]) -> bool {
+    let mut delay = sender.retry_delay();
+
+    for attempt in 0..policy.max_retries.max(1) {
+        if attempt > 0 {
+            thread::sleep(delay);
+            delay *= 2;
+        }
+
+        match sender.send(batch) {
+            Ok(()) => return true,
+            Err(e) => eprintln!("Remote log delivery failed (attempt {}): {}", attempt + 1, e),
+        }
+    }
+
+    false
+}
+
+/// Send a single batch to the endpoint
+fn send_batch(policy: &RemoteLoggingPolicy, batch: &[RemoteLogRecord]) -> Result<(), String> {
+    let body = serde_json::to_string(batch).map_err(|e| e.to_string())?;
+
+    let mut request = http_agent()
+        .map_err(|e| e.to_string())?
+        .post(&policy.endpoint)
+        .timeout(Duration::from_secs(30))
+        .set("Content-Type", "application/json");
+
+    if let Some(token) = &policy.auth_token {
+        request = request.set("Authorization", &format!("Bearer {}", token));
+    }
+
+    request.send_string(&body)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Append undeliverable records to the spool file (one JSON record per line)
+fn append_to_spool(spool_path: &Path, batch: &[RemoteLogRecord]) -> io::Result<()> {
+    if let Some(parent) = spool_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(spool_path)?;
+
+    for record in batch {
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+    }
+    file.flush()
+}
+
+/// Resend spooled records, returning true if the spool is empty afterwards
+fn resend_spool(policy: &RemoteLoggingPolicy, spool_path: &Path, sender: &mut impl BatchSender) -> bool {
+    let content = match fs::read_to_string(spool_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return true,
+        Err(e) => {
+            eprintln!("Failed to read remote log spool: {}", e);
+            return false;
+        }
+    };
+
+    // Skip lines that cannot be parsed rather than blocking the spool forever
+    let records: Vec<RemoteLogRecord> = content.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+
+    for (i, chunk) in records.chunks(policy.batch_size.max(1)).enumerate() {
+        if sender.send(chunk).is_err() {
+            // Keep the records that were not delivered
+            let remaining = &records[i * policy.batch_size.max(1)..];
+            let _ = fs::remove_file(spool_path);
+            if let Err(e) = append_to_spool(spool_path, remaining) {
+                eprintln!("Failed to rewrite remote log spool: {}", e);
+            }
+            return false;
+        }
+    }
+
+    let _ = fs::remove_file(spool_path);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use tempfile::tempdir;
+
+    /// Endpoint that records what it receives, failing the sends it is told to
+    #[derive(Default)]
+    struct MockEndpoint {
+        /// Result of each send in turn (true delivers); sends past the end deliver
+        results: VecDeque<bool>,
+        attempts: usize,
+        /// Operations of the records of each delivered batch
+        delivered: Vec<Vec<String>>,
+    }
+
+    impl BatchSender for MockEndpoint {
+        fn offline(&self) -> bool {
+            false
+        }
+
+        fn retry_delay(&self) -> Duration {
+            Duration::from_millis(10)
+        }
+
+        fn send(&mut self, batch: &[RemoteLogRecord]) -> Result<(), String> {
+            self.attempts += 1;
+            if !self.results.pop_front().unwrap_or(true) {
+                return Err("503 Service Unavailable".to_string());
+            }
+            self.delivered.push(batch.iter().map(|record| record.entry.operation.clone()).collect());
+            Ok(())
+        }
+    }
+
+    fn policy(batch_size: usize, max_retries: u32) -> RemoteLoggingPolicy {
+        RemoteLoggingPolicy {
+            endpoint: "https://logs.example.com".to_string(),
+            batch_size,
+            max_retries,
+            ..RemoteLoggingPolicy::default()
+        }
+    }
+
+    fn records(count: usize) -> Vec<RemoteLogRecord> {
+        (0..count)
+            .map(|i| RemoteLogRecord::new(LogEntry::new(&format!("Op {}", i), "file.txt", true, "ok")))
+            .collect()
+    }
+
+    fn spooled_operations(spool_path: &Path) -> Vec<String> {
+        fs::read_to_string(spool_path).unwrap().lines()
+            .map(|line| serde_json::from_str::<RemoteLogRecord>(line).unwrap().entry.operation)
+            .collect()
+    }
+
+    #[test]
+    fn test_batching() {
+        let dir = tempdir().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        for record in records(7) {
+            sender.send(record).unwrap();
+        }
+        drop(sender);
+
+        // Full batches are sent as they fill; the rest when the logger goes away
+        let mut endpoint = MockEndpoint::default();
+        run_forwarder(&policy(3, 3), &dir.path().join("spool.jsonl"), receiver, &mut endpoint);
+        let sizes: Vec<usize> = endpoint.delivered.iter().map(Vec::len).collect();
+        assert_eq!(sizes, [3, 3, 1]);
+        assert_eq!(endpoint.delivered[2], ["Op 6"]);
+    }
+
+    #[test]
+    fn test_retry_with_backoff() {
+        let batch = records(2);
+
+        // Two failures, then delivered: waits 10 ms, then 20 ms
+        let mut endpoint = MockEndpoint { results: VecDeque::from([false, false]), ..Default::default() };
+        let started = Instant::now();
+        assert!(send_with_retry(&policy(50, 3), &mut endpoint, &batch));
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        assert_eq!(endpoint.attempts, 3);
+        assert_eq!(endpoint.delivered.len(), 1);
+
+        // Gives up after the policy's number of attempts
+        let mut endpoint = MockEndpoint { results: VecDeque::from([false; 5]), ..Default::default() };
+        assert!(!send_with_retry(&policy(50, 2), &mut endpoint, &batch));
+        assert_eq!(endpoint.attempts, 2);
+        assert!(endpoint.delivered.is_empty());
+    }
+
+    #[test]
+    fn test_undelivered_batch_is_spooled() {
+        let dir = tempdir().unwrap();
+        let spool_path = dir.path().join("spool.jsonl");
+        let (sender, receiver) = mpsc::channel();
+        for record in records(2) {
+            sender.send(record).unwrap();
+        }
+        drop(sender);
+
+        let mut endpoint = MockEndpoint { results: VecDeque::from([false; 2]), ..Default::default() };
+        run_forwarder(&policy(50, 2), &spool_path, receiver, &mut endpoint);
+        assert_eq!(spooled_operations(&spool_path), ["Op 0", "Op 1"]);
+    }
+
+    #[test]
+    fn test_resend_spool_after_partial_delivery() {
+        let dir = tempdir().unwrap();
+        let spool_path = dir.path().join("spool.jsonl");
+        append_to_spool(&spool_path, &records(5)).unwrap();
+
+        // The first batch is delivered, the second fails: only the undelivered records stay
+        let mut endpoint = MockEndpoint { results: VecDeque::from([true, false]), ..Default::default() };
+        assert!(!resend_spool(&policy(2, 3), &spool_path, &mut endpoint));
+        assert_eq!(endpoint.delivered, [["Op 0", "Op 1"]]);
+        assert_eq!(spooled_operations(&spool_path), ["Op 2", "Op 3", "Op 4"]);
+
+        // Once the endpoint is back, the rest is sent in order and the spool removed
+        let mut endpoint = MockEndpoint::default();
+        assert!(resend_spool(&policy(2, 3), &spool_path, &mut endpoint));
+        assert_eq!(endpoint.delivered, [vec!["Op 2", "Op 3"], vec!["Op 4"]]);
+        assert!(!spool_path.exists());
+
+        // Nothing spooled is nothing to resend
+        assert!(resend_spool(&policy(2, 3), &spool_path, &mut endpoint));
+    }
+
+    #[test]
+    fn test_install_salt() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("redaction_salt");
+
+        // Made once, then kept, so path IDs stay the same between starts
+        let salt = install_salt(&path).unwrap();
+        assert_eq!(salt.len(), 64);
+        assert_eq!(install_salt(&path).unwrap(), salt);
+        assert_ne!(install_salt(&dir.path().join("other_salt")).unwrap(), salt);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+    }
+}
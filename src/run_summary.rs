@@ -0,0 +1,167 @@
+/// Run summary module.
+///
+/// This module provides functionality for:
+/// - Summarizing what a batch is started with: key, backend, destination, and options
+/// - Keeping the summary of the last batch of each operation (last_runs.json) in the
+///   application data directory
+/// - Listing what changed since that batch, so a setting changed by mistake (e.g. hidden
+///   file names turned off) is noticed before the batch starts
+///
+/// Only names, fingerprints and choices are kept; never keys or file contents.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+
+use crate::shared_files;
+
+/// What a batch is started with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    /// Operation of the batch (e.g. "Encrypt"); batches are compared with the last one
+    /// of the same operation
+    pub operation: String,
+    /// Choices by name, in the order they are shown (e.g. "Hide file names", "On")
+    pub items: Vec<(String, String)>,
+}
+
+impl RunSummary {
+    /// Create a summary with no choices
+    pub fn new(operation: &str) -> Self {
+        RunSummary { operation: operation.to_string(), items: Vec::new() }
+    }
+
+    /// Add a choice
+    pub fn add(&mut self, name: &str, value: impl Into<String>) {
+        self.items.push((name.to_string(), value.into()));
+    }
+
+    /// Add a choice that is on or off
+    pub fn add_flag(&mut self, name: &str, on: bool) {
+        self.add(name, if on { "On" } else { "Off" });
+    }
+
+    /// List the choices that changed since an earlier batch
+    pub fn changes_from(&self, previous: &RunSummary) -> Vec<RunChange> {
+        self.items.iter()
+            .filter_map(|(name, value)| {
+                let note = match previous.items.iter().find(|(previous_name, _)| previous_name == name) {
+                    Some((_, previous_value)) if previous_value != value => {
+                        format!("{} is now {} (was {})", name, value, previous_value)
+                    },
+                    Some(_) => return None,
+                    None => format!("{} is now {}", name, value),
+                };
+                Some(RunChange { name: name.clone(), note })
+            })
+            .collect()
+    }
+}
+
+/// A choice that changed since an earlier batch
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunChange {
+    /// Name of the choice
+    pub name: String,
+    /// What changed, e.g. "Hide file names is now Off (was On)"
+    pub note: String,
+}
+
+/// Summaries of the last batch of each operation
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct LastRuns {
+    pub runs: Vec<RunSummary>,
+}
+
+impl LastRuns {
+    /// Location of the last runs in the app's data folder
+    pub fn path_in(data_dir: &Path) -> PathBuf {
+        data_dir.join("last_runs.json")
+    }
+
+    /// Load the last runs from the specified file
+    ///
+    /// # Returns
+    /// * `io::Result<LastRuns>` - The last runs, none if the file does not exist, or an
+    ///   error if the file cannot be read or parsed
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(LastRuns::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid last runs: {}", e)))
+    }
+
+    /// Save the last runs to the specified file
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        shared_files::write_atomic(path, content)
+    }
+
+    /// Get the last batch of an operation
+    pub fn last(&self, operation: &str) -> Option<&RunSummary> {
+        self.runs.iter().find(|run| run.operation == operation)
+    }
+
+    /// Record a batch, replacing the last one of its operation
+    pub fn record(&mut self, summary: RunSummary) {
+        self.runs.retain(|run| run.operation != summary.operation);
+        self.runs.push(summary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn summary(hide_names: bool, backend: &str) -> RunSummary {
+        let mut summary = RunSummary::new("Encrypt");
+        summary.add("Key", "Client Key");
+        summary.add("Backend", backend);
+        summary.add_flag("Hide file names", hide_names);
+        summary
+    }
+
+    #[test]
+    fn test_changes_from() {
+        let notes = |changes: Vec<RunChange>| changes.into_iter().map(|change| change.note).collect::<Vec<_>>();
+
+        let previous = summary(true, "Software");
+        assert!(summary(true, "Software").changes_from(&previous).is_empty());
+        let changes = summary(false, "Software").changes_from(&previous);
+        assert_eq!(changes[0].name, "Hide file names");
+        assert_eq!(notes(changes), vec!["Hide file names is now Off (was On)"]);
+
+        // Choices the earlier batch did not have are listed too
+        let mut current = summary(true, "Embedded device");
+        current.add_flag("Parity", true);
+        assert_eq!(notes(current.changes_from(&previous)), vec![
+            "Backend is now Embedded device (was Software)",
+            "Parity is now On",
+        ]);
+    }
+
+    #[test]
+    fn test_last_runs() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("last_runs.json");
+        assert!(LastRuns::load_from(&path).unwrap().runs.is_empty());
+
+        let mut runs = LastRuns::default();
+        runs.record(summary(true, "Software"));
+        runs.record(RunSummary::new("Decrypt"));
+        runs.record(summary(false, "Software"));
+        runs.save_to(&path).unwrap();
+
+        let loaded = LastRuns::load_from(&path).unwrap();
+        assert_eq!(loaded.runs.len(), 2);
+        assert_eq!(loaded.last("Encrypt"), Some(&summary(false, "Software")));
+        assert_eq!(loaded.last("Decrypt"), Some(&RunSummary::new("Decrypt")));
+    }
+}
@@ -0,0 +1,72 @@
+/// Screen capture module.
+///
+/// This module provides functionality for:
+/// - Keeping the CRUSTy window out of screenshots, screen recordings, and screen
+///   sharing while key shares are shown (Windows: SetWindowDisplayAffinity)
+///
+/// Windows 10 version 2004 and later leave the window out of captures entirely; older
+/// versions show it as a black rectangle. Other platforms offer no such flag to
+/// applications, so there the window is only protected by covering the shares until
+/// they are revealed.
+#[cfg(windows)]
+use std::ptr;
+#[cfg(windows)]
+use winapi::um::winuser::{FindWindowW, SetWindowDisplayAffinity, WDA_MONITOR, WDA_NONE};
+
+/// Leaves the window out of captures (Windows 10 version 2004 and later)
+#[cfg(windows)]
+const WDA_EXCLUDEFROMCAPTURE: u32 = 0x00000011;
+
+/// Keeps the CRUSTy window out of screen captures while enabled
+pub struct CaptureExclusion {
+    /// Whether the window is currently excluded
+    excluded: bool,
+}
+
+impl CaptureExclusion {
+    /// Create a guard that leaves the window capturable
+    pub fn new() -> Self {
+        CaptureExclusion { excluded: false }
+    }
+
+    /// Exclude the window from screen captures, or allow captures again
+    pub fn set(&mut self, exclude: bool) {
+        if exclude == self.excluded {
+            return;
+        }
+
+        #[cfg(windows)]
+        {
+            if !set_display_affinity("CRUSTy", exclude) {
+                return;
+            }
+        }
+
+        self.excluded = exclude;
+    }
+}
+
+/// Set the display affinity of the window with the given title
+///
+/// # Returns
+/// * `bool` - Whether the affinity was set
+#[cfg(windows)]
+fn set_display_affinity(title: &str, exclude: bool) -> bool {
+    let title: Vec<u16> = title.encode_utf16().chain(Some(0)).collect();
+
+    // SAFETY: the title is a NUL-terminated wide string that outlives the calls
+    unsafe {
+        let hwnd = FindWindowW(ptr::null(), title.as_ptr());
+        if hwnd.is_null() {
+            return false;
+        }
+
+        if !exclude {
+            return SetWindowDisplayAffinity(hwnd, WDA_NONE) != 0;
+        }
+
+        // Older versions only support showing the window as black in captures
+        SetWindowDisplayAffinity(hwnd, WDA_EXCLUDEFROMCAPTURE) != 0
+            || SetWindowDisplayAffinity(hwnd, WDA_MONITOR) != 0
+    }
+}
@@ -0,0 +1,319 @@
+/// Secure viewer module.
+///
+/// This module provides functionality for:
+/// - Decrypting a file to view into a private folder, in memory where the system has a
+///   RAM-backed folder (`/dev/shm`, or the tmpfs user runtime folder on Linux)
+/// - Shredding the viewed files when they are closed, when they expire, and when the
+///   viewer is dropped (CRUSTy closes)
+///
+/// The folder is only readable by the user, and files are shredded rather than moved to
+/// the trash, so checking a document leaves no plaintext in Downloads or the trash.
+/// Each viewer holds a lock on a file in its folder while the folder exists. Folders
+/// left by a CRUSTy that did not close normally (their lock is no longer held) are
+/// shredded when the next viewer starts; the folders of viewers still running are left
+/// alone. Where no RAM-backed folder exists, the files are on disk while they
+/// are viewed, and shredding them is best effort (see `in_place::shred_file`).
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::backend::LocalBackend;
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::file_names;
+use crate::in_place;
+
+/// Start of the name of a viewer's folder
+const FOLDER_PREFIX: &str = "crusty-view-";
+
+/// File in a viewer's folder locked for as long as the viewer uses the folder
+const LOCK_FILE: &str = "viewer.lock";
+
+/// A decrypted file being viewed
+#[derive(Debug, Clone)]
+pub struct ViewedFile {
+    /// The encrypted file
+    pub source: PathBuf,
+    /// The decrypted copy being viewed
+    pub path: PathBuf,
+    /// When the decrypted copy is shredded
+    pub expires_at: Instant,
+}
+
+/// Decrypted copies of files being viewed
+pub struct SecureViewer {
+    /// Folder the viewer's folder is created in
+    root: PathBuf,
+    /// Whether the root folder is RAM-backed
+    in_memory: bool,
+    /// The viewer's folder (None until a file is viewed)
+    folder: Option<PathBuf>,
+    /// Lock held on the folder's lock file, so other viewers leave the folder alone
+    lock: Option<File>,
+    files: Vec<ViewedFile>,
+    /// Number of files viewed, naming the subfolder of each
+    viewed: usize,
+}
+
+impl SecureViewer {
+    /// Create a viewer in the RAM-backed folder of the system (or the temporary folder)
+    pub fn new() -> Self {
+        let (root, in_memory) = default_root();
+        let mut viewer = Self::in_folder(root);
+        viewer.in_memory = in_memory;
+        viewer
+    }
+
+    /// Create a viewer whose folder is created in the given folder
+    ///
+    /// Folders left there by earlier viewers that are no longer running are shredded.
+    pub fn in_folder(root: PathBuf) -> Self {
+        if let Ok(entries) = fs::read_dir(&root) {
+            for entry in entries.flatten() {
+                if entry.file_name().to_string_lossy().starts_with(FOLDER_PREFIX) && is_abandoned(&entry.path()) {
+                    let _ = shred_folder(&entry.path());
+                }
+            }
+        }
+        SecureViewer { root, in_memory: false, folder: None, lock: None, files: Vec::new(), viewed: 0 }
+    }
+
+    /// Check whether viewed files are kept in memory rather than on disk
+    pub fn in_memory(&self) -> bool {
+        self.in_memory
+    }
+
+    /// Get the files being viewed
+    pub fn files(&self) -> &[ViewedFile] {
+        &self.files
+    }
+
+    /// Decrypt a file into the viewer's folder, under its original name
+    ///
+    /// # Arguments
+    /// * `source` - The encrypted file
+    /// * `key` - The key to decrypt it with
+    /// * `timeout` - How long the decrypted copy is kept
+    ///
+    /// # Returns
+    /// * `Result<&ViewedFile, EncryptionError>` - The decrypted copy, to open
+    pub fn decrypt(&mut self, source: &Path, key: &EncryptionKey, timeout: Duration) -> Result<&ViewedFile, EncryptionError> {
+        let name = file_names::restored_name(source, key)
+            .ok_or_else(|| EncryptionError::Decryption(format!("{} is not a file", source.display())))?
+            .name;
+        let data = LocalBackend::default().decrypt_to_memory(source, key, |_| {})?;
+
+        // Each file has its own subfolder, so files of the same name can be viewed together
+        let folder = self.folder()?.join(self.viewed.to_string());
+        private_dir(&folder)?;
+        self.viewed += 1;
+        let path = folder.join(name);
+        let written = private_file(&path).and_then(|mut file| {
+            file.write_all(&data)?;
+            file.sync_all()
+        });
+        if let Err(e) = written {
+            let _ = shred_folder(&folder);
+            return Err(EncryptionError::Io(e));
+        }
+
+        self.files.push(ViewedFile {
+            source: source.to_path_buf(),
+            path,
+            expires_at: Instant::now() + timeout,
+        });
+        Ok(self.files.last().unwrap())
+    }
+
+    /// Shred a viewed file
+    pub fn close(&mut self, index: usize) -> io::Result<ViewedFile> {
+        if index >= self.files.len() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No such viewed file"));
+        }
+        let file = self.files.remove(index);
+        match file.path.parent() {
+            Some(folder) => shred_folder(folder)?,
+            None => in_place::shred_file(&file.path)?,
+        }
+        Ok(file)
+    }
+
+    /// Shred the viewed files that have expired
+    ///
+    /// # Returns
+    /// * `Vec<io::Result<ViewedFile>>` - The files shredded, or why one could not be
+    pub fn close_expired(&mut self, now: Instant) -> Vec<io::Result<ViewedFile>> {
+        let mut closed = Vec::new();
+        while let Some(index) = self.files.iter().position(|file| file.expires_at <= now) {
+            closed.push(self.close(index));
+        }
+        closed
+    }
+
+    /// Shred every viewed file, and the viewer's folder
+    pub fn close_all(&mut self) -> io::Result<()> {
+        self.files.clear();
+        // The lock file is shredded with the folder, which Windows does not allow while
+        // it is open
+        self.lock = None;
+        match self.folder.take() {
+            Some(folder) => shred_folder(&folder),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the viewer's folder, creating it if needed
+    fn folder(&mut self) -> io::Result<PathBuf> {
+        if let Some(folder) = &self.folder {
+            return Ok(folder.clone());
+        }
+        let folder = self.root.join(format!("{}{:016x}", FOLDER_PREFIX, rand::random::<u64>()));
+        private_dir(&folder)?;
+        let locked = private_file(&folder.join(LOCK_FILE)).and_then(|lock| {
+            lock.try_lock().map_err(io::Error::from)?;
+            Ok(lock)
+        });
+        match locked {
+            Ok(lock) => self.lock = Some(lock),
+            Err(e) => {
+                let _ = shred_folder(&folder);
+                return Err(e);
+            },
+        }
+        self.folder = Some(folder.clone());
+        Ok(folder)
+    }
+}
+
+impl Default for SecureViewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SecureViewer {
+    fn drop(&mut self) {
+        let _ = self.close_all();
+    }
+}
+
+/// Get the folder viewers are created in, and whether it is RAM-backed
+fn default_root() -> (PathBuf, bool) {
+    #[cfg(target_os = "linux")]
+    {
+        let shm = Path::new("/dev/shm");
+        if shm.is_dir() {
+            return (shm.to_path_buf(), true);
+        }
+        if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from).filter(|dir| dir.is_dir()) {
+            return (runtime, true);
+        }
+    }
+    (std::env::temp_dir(), false)
+}
+
+/// Check whether a viewer's folder was left by a viewer that is no longer running
+///
+/// A folder without a lock file is left alone: its viewer may be creating it.
+fn is_abandoned(folder: &Path) -> bool {
+    match File::open(folder.join(LOCK_FILE)) {
+        Ok(lock) => lock.try_lock().is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Create a folder only the user can open
+fn private_dir(path: &Path) -> io::Result<()> {
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(path)
+}
+
+/// Create a new file only the user can read
+fn private_file(path: &Path) -> io::Result<fs::File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+/// Shred every file in a folder and its subfolders, then remove the folder
+fn shred_folder(folder: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(folder)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            shred_folder(&entry.path())?;
+        } else {
+            in_place::shred_file(&entry.path())?;
+        }
+    }
+    fs::remove_dir(folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use crate::backend::BackendFactory;
+
+    #[test]
+    fn test_view_and_shred() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let source = dir.path().join("contract.pdf");
+        fs::write(&source, b"%PDF signed contract").unwrap();
+        let encrypted = dir.path().join("contract.pdf.encrypted");
+        BackendFactory::create_local().encrypt_file(&source, &encrypted, &key, |_| {}).unwrap();
+        let root = dir.path().join("view");
+        fs::create_dir(&root).unwrap();
+
+        let mut viewer = SecureViewer::in_folder(root.clone());
+        let viewed = viewer.decrypt(&encrypted, &key, Duration::from_secs(60)).unwrap().clone();
+        assert_eq!(viewed.path.file_name().unwrap(), "contract.pdf");
+        assert_eq!(fs::read(&viewed.path).unwrap(), b"%PDF signed contract");
+        let second = viewer.decrypt(&encrypted, &key, Duration::ZERO).unwrap().path.clone();
+        assert_ne!(second, viewed.path);
+
+        // The expired copy is shredded, then the rest when the viewer is dropped
+        let closed = viewer.close_expired(Instant::now());
+        assert_eq!(closed.len(), 1);
+        assert!(!second.exists());
+        assert!(viewed.path.exists());
+        drop(viewer);
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_leaves_running_viewers() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let source = dir.path().join("notes.txt");
+        fs::write(&source, b"notes").unwrap();
+        let encrypted = dir.path().join("notes.txt.encrypted");
+        BackendFactory::create_local().encrypt_file(&source, &encrypted, &key, |_| {}).unwrap();
+        let root = dir.path().join("view");
+        fs::create_dir(&root).unwrap();
+
+        // A folder whose lock is no longer held, and one without a lock file
+        let abandoned = root.join(format!("{}abandoned", FOLDER_PREFIX));
+        fs::create_dir(&abandoned).unwrap();
+        fs::write(abandoned.join(LOCK_FILE), b"").unwrap();
+        fs::write(abandoned.join("plan.pdf"), b"plan").unwrap();
+        let unlocked = root.join(format!("{}unlocked", FOLDER_PREFIX));
+        fs::create_dir(&unlocked).unwrap();
+
+        let mut running = SecureViewer::in_folder(root.clone());
+        let viewed = running.decrypt(&encrypted, &key, Duration::from_secs(60)).unwrap().path.clone();
+        assert!(!abandoned.exists());
+        assert!(unlocked.exists());
+
+        // Another viewer starting does not shred the running viewer's files
+        let other = SecureViewer::in_folder(root.clone());
+        assert_eq!(fs::read(&viewed).unwrap(), b"notes");
+        drop(running);
+        assert!(!viewed.exists());
+        drop(other);
+    }
+}
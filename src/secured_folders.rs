@@ -0,0 +1,409 @@
+/// Secured folders module.
+///
+/// This module provides functionality for:
+/// - Keeping the list of secured folders (secured_folders.json) in the application data
+///   directory
+/// - Finding whether a folder is locked (every file encrypted), unlocked, or partly locked
+/// - Binding a saved key to a folder, so it is always locked with that key
+/// - Locking a folder (encrypting every file in place and shredding the originals) and
+///   unlocking it (decrypting every file in place)
+/// - Checking a locked folder's integrity: verifying every encrypted file with the key,
+///   and finding files locked there that have disappeared
+///
+/// Locking and unlocking carry on past files that fail and report them, so a folder left
+/// partly locked (by a failure, a wrong key, or a crash) is recovered by running the
+/// same action again: files already done are skipped, and temporary files left by an
+/// interrupted run are removed.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+
+use crate::backend::BackendFactory;
+use crate::encryption::{self, EncryptionKey};
+use crate::file_names;
+use crate::in_place;
+use crate::shared_files;
+
+/// A folder whose contents CRUSTy keeps encrypted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SecuredFolder {
+    /// The folder
+    pub path: PathBuf,
+    /// Fingerprint of the key the folder was last locked with (None while unlocked)
+    #[serde(default)]
+    pub key_fingerprint: Option<String>,
+    /// The encrypted files of the folder when it was last locked, relative to it
+    #[serde(default)]
+    pub locked_files: Vec<PathBuf>,
+    /// Fingerprint of the saved key the folder is always locked with (None to lock it
+    /// with the current key)
+    #[serde(default)]
+    pub bound_key: Option<String>,
+}
+
+impl SecuredFolder {
+    /// Create a folder that has not been locked yet
+    pub fn new(path: PathBuf) -> Self {
+        SecuredFolder { path, key_fingerprint: None, locked_files: Vec::new(), bound_key: None }
+    }
+
+    /// Get the fingerprint of the key to unlock the folder with: the key it was locked
+    /// with, or else the key bound to it (None to unlock it with the current key)
+    pub fn unlock_key(&self) -> Option<&str> {
+        self.key_fingerprint.as_deref().or(self.bound_key.as_deref())
+    }
+}
+
+/// The secured folders of this installation
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct SecuredFolders {
+    pub folders: Vec<SecuredFolder>,
+}
+
+/// Whether the files in a secured folder are encrypted
+#[derive(Clone, Debug, PartialEq)]
+pub enum FolderStatus {
+    /// Every file is encrypted
+    Locked,
+    /// No file is encrypted
+    Unlocked,
+    /// Some files are encrypted (after a failure or an interrupted lock or unlock)
+    PartlyLocked {
+        /// Number of encrypted files
+        locked: usize,
+        /// Number of files
+        total: usize,
+    },
+    /// The folder has no files
+    Empty,
+    /// The folder does not exist or cannot be read
+    Missing,
+}
+
+/// Outcome of locking or unlocking a folder
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FolderReport {
+    /// Number of files encrypted or decrypted
+    pub processed: usize,
+    /// Files that failed, and why
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Outcome of checking the integrity of a locked folder
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IntegrityReport {
+    /// The folder
+    pub folder: PathBuf,
+    /// Number of files that verified with the key
+    pub intact: usize,
+    /// Files that failed verification, and why
+    pub failed: Vec<(PathBuf, String)>,
+    /// Files locked in the folder that are gone (or the folder itself, if it is gone)
+    pub missing: Vec<PathBuf>,
+}
+
+impl IntegrityReport {
+    /// Check whether every file verified and none is missing
+    pub fn is_intact(&self) -> bool {
+        self.failed.is_empty() && self.missing.is_empty()
+    }
+}
+
+impl SecuredFolders {
+    /// Location of the secured folder list in the app's data folder
+    pub fn path_in(data_dir: &Path) -> PathBuf {
+        data_dir.join("secured_folders.json")
+    }
+
+    /// Load the list from the specified file
+    ///
+    /// # Returns
+    /// * `io::Result<SecuredFolders>` - The list, an empty list if the file does not
+    ///   exist, or an error if the file cannot be read or parsed
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(SecuredFolders::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid secured folder list: {}", e)))
+    }
+
+    /// Save the list to the specified file
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        shared_files::write_atomic(path, content)
+    }
+}
+
+/// List the files in a folder and its subfolders
+///
+/// Symbolic links are not followed, so locking a folder never encrypts files outside it.
+/// Temporary files left by an interrupted lock or unlock are removed.
+pub fn folder_files(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![folder.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+
+            if file_type.is_dir() {
+                dirs.push(path);
+            } else if file_type.is_file() {
+                if encryption::is_temp_destination(&path) {
+                    let _ = fs::remove_file(&path);
+                } else {
+                    files.push(path);
+                }
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Find whether the files in a folder are encrypted
+pub fn folder_status(folder: &Path) -> FolderStatus {
+    let files = match folder_files(folder) {
+        Ok(files) => files,
+        Err(_) => return FolderStatus::Missing,
+    };
+
+    let locked = files.iter().filter(|file| file_names::is_encrypted_name(file)).count();
+    match (locked, files.len()) {
+        (_, 0) => FolderStatus::Empty,
+        (0, _) => FolderStatus::Unlocked,
+        (locked, total) if locked == total => FolderStatus::Locked,
+        (locked, total) => FolderStatus::PartlyLocked { locked, total },
+    }
+}
+
+/// Lock a folder: encrypt every file in place and shred the originals
+///
+/// # Arguments
+/// * `progress` - Called with the number of files done and the number to do
+///
+/// # Returns
+/// * `io::Result<FolderReport>` - The files locked and the files that failed, or an
+///   error if the folder cannot be read
+pub fn lock_folder(folder: &Path, key: &EncryptionKey, progress: impl Fn(usize, usize)) -> io::Result<FolderReport> {
+    let backend = BackendFactory::create_local();
+    let files: Vec<PathBuf> = folder_files(folder)?.into_iter()
+        .filter(|file| !file_names::is_encrypted_name(file))
+        .collect();
+
+    let mut report = FolderReport::default();
+    for (index, file) in files.iter().enumerate() {
+        progress(index, files.len());
+
+        let result = match file_names::encrypted_name(file) {
+            Some(name) => in_place::encrypt_in_place(&backend, file, &file.with_file_name(name), key, false, true, |_| {})
+                .map_err(|e| e.to_string()),
+            None => Err("Invalid file name".to_string()),
+        };
+        match result {
+            Ok(()) => report.processed += 1,
+            Err(e) => report.failures.push((file.clone(), e)),
+        }
+    }
+
+    progress(files.len(), files.len());
+    Ok(report)
+}
+
+/// Unlock a folder: decrypt every encrypted file in place
+///
+/// Files encrypted with another key fail and are left encrypted.
+///
+/// # Arguments
+/// * `progress` - Called with the number of files done and the number to do
+///
+/// # Returns
+/// * `io::Result<FolderReport>` - The files unlocked and the files that failed, or an
+///   error if the folder cannot be read
+pub fn unlock_folder(folder: &Path, key: &EncryptionKey, progress: impl Fn(usize, usize)) -> io::Result<FolderReport> {
+    let backend = BackendFactory::create_local();
+    let files: Vec<PathBuf> = folder_files(folder)?.into_iter()
+        .filter(|file| file_names::is_encrypted_name(file))
+        .collect();
+
+    let mut report = FolderReport::default();
+    for (index, file) in files.iter().enumerate() {
+        progress(index, files.len());
+
+        let result = match file_names::restored_name(file, key) {
+            Some(output_name) => in_place::decrypt_in_place(&backend, file, &file.with_file_name(&output_name.name), key, |_| {})
+                .map_err(|e| e.to_string()),
+            None => Err("Invalid file name".to_string()),
+        };
+        match result {
+            Ok(()) => report.processed += 1,
+            Err(e) => report.failures.push((file.clone(), e)),
+        }
+    }
+
+    progress(files.len(), files.len());
+    Ok(report)
+}
+
+/// Get the encrypted files of a folder, relative to it, to record when it is locked
+pub fn encrypted_files(folder: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(folder_files(folder)?.into_iter()
+        .filter(|file| file_names::is_encrypted_name(file))
+        .filter_map(|file| file.strip_prefix(folder).ok().map(Path::to_path_buf))
+        .collect())
+}
+
+/// Check the integrity of a locked folder
+///
+/// Every encrypted file is verified with the key (its authentication tag is checked;
+/// nothing decrypted is written), and the files recorded when the folder was locked
+/// are looked for, so damage, tampering, and deleted files are all found.
+///
+/// # Arguments
+/// * `folder` - The secured folder, with the files recorded when it was locked
+/// * `key` - The key the folder was locked with
+pub fn check_integrity(folder: &SecuredFolder, key: &EncryptionKey) -> IntegrityReport {
+    let mut report = IntegrityReport { folder: folder.path.clone(), ..IntegrityReport::default() };
+    let files = match encrypted_files(&folder.path) {
+        Ok(files) => files,
+        Err(_) => {
+            report.missing.push(folder.path.clone());
+            return report;
+        },
+    };
+
+    let backend = BackendFactory::create_local();
+    for file in &files {
+        let path = folder.path.join(file);
+        match backend.verify_file(&path, key, |_| {}) {
+            Ok(()) => report.intact += 1,
+            Err(e) => report.failed.push((path, e.to_string())),
+        }
+    }
+    report.missing = folder.locked_files.iter()
+        .filter(|file| !files.contains(file))
+        .map(|file| folder.path.join(file))
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_lock_and_unlock() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path();
+        fs::create_dir(folder.join("sub")).unwrap();
+        fs::write(folder.join("a.txt"), b"A").unwrap();
+        fs::write(folder.join("sub").join("b.txt"), b"B").unwrap();
+        assert_eq!(folder_status(folder), FolderStatus::Unlocked);
+
+        let key = EncryptionKey::generate();
+        let report = lock_folder(folder, &key, |_, _| {}).unwrap();
+        assert_eq!(report, FolderReport { processed: 2, failures: Vec::new() });
+        assert_eq!(folder_status(folder), FolderStatus::Locked);
+        assert!(folder.join("sub").join("b.txt.encrypted").exists());
+
+        // Locking again has nothing to do
+        assert_eq!(lock_folder(folder, &key, |_, _| {}).unwrap().processed, 0);
+
+        unlock_folder(folder, &key, |_, _| {}).unwrap();
+        assert_eq!(folder_status(folder), FolderStatus::Unlocked);
+        assert_eq!(fs::read(folder.join("sub").join("b.txt")).unwrap(), b"B");
+    }
+
+    #[test]
+    fn test_partial_unlock_recovery() {
+        let dir = tempdir().unwrap();
+        let folder = dir.path();
+        let key = EncryptionKey::generate();
+        let other_key = EncryptionKey::generate();
+
+        fs::write(folder.join("a.txt"), b"A").unwrap();
+        lock_folder(folder, &key, |_, _| {}).unwrap();
+        fs::write(folder.join("b.txt"), b"B").unwrap();
+        lock_folder(folder, &other_key, |_, _| {}).unwrap();
+
+        // A file encrypted with another key fails and stays encrypted
+        let report = unlock_folder(folder, &key, |_, _| {}).unwrap();
+        assert_eq!(report.processed, 1);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].0, folder.join("b.txt.encrypted"));
+        assert_eq!(folder_status(folder), FolderStatus::PartlyLocked { locked: 1, total: 2 });
+
+        // Running the unlock again with the other key finishes it; stale temporary
+        // files from an interrupted run are cleaned up
+        fs::write(encryption::temp_destination(&folder.join("b.txt")), b"partial").unwrap();
+        let report = unlock_folder(folder, &other_key, |_, _| {}).unwrap();
+        assert_eq!(report, FolderReport { processed: 1, failures: Vec::new() });
+        assert_eq!(folder_status(folder), FolderStatus::Unlocked);
+        assert_eq!(folder_files(folder).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_check_integrity() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("records");
+        fs::create_dir_all(path.join("2024")).unwrap();
+        fs::write(path.join("a.txt"), b"A").unwrap();
+        fs::write(path.join("2024").join("b.txt"), b"B").unwrap();
+        fs::write(path.join("c.txt"), b"C").unwrap();
+        let key = EncryptionKey::generate();
+        lock_folder(&path, &key, |_, _| {}).unwrap();
+
+        let folder = SecuredFolder {
+            path: path.clone(),
+            key_fingerprint: Some(key.fingerprint()),
+            locked_files: encrypted_files(&path).unwrap(),
+            bound_key: None,
+        };
+        assert_eq!(check_integrity(&folder, &key), IntegrityReport { folder: path.clone(), intact: 3, ..IntegrityReport::default() });
+
+        // A changed byte and a deleted file are both reported
+        let damaged = path.join("2024").join("b.txt.encrypted");
+        let mut contents = fs::read(&damaged).unwrap();
+        *contents.last_mut().unwrap() ^= 1;
+        fs::write(&damaged, contents).unwrap();
+        fs::remove_file(path.join("c.txt.encrypted")).unwrap();
+        let report = check_integrity(&folder, &key);
+        assert!(!report.is_intact());
+        assert_eq!(report.intact, 1);
+        assert_eq!(report.failed[0].0, damaged);
+        assert_eq!(report.missing, [path.join("c.txt.encrypted")]);
+
+        // A folder that is gone is reported missing as a whole
+        fs::remove_dir_all(&path).unwrap();
+        assert_eq!(check_integrity(&folder, &key).missing, [path]);
+    }
+
+    #[test]
+    fn test_secured_folders_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("secured_folders.json");
+        assert!(SecuredFolders::load_from(&path).unwrap().folders.is_empty());
+        assert_eq!(folder_status(&dir.path().join("missing")), FolderStatus::Missing);
+
+        let mut folder = SecuredFolder::new(dir.path().join("docs"));
+        folder.bound_key = Some("ab12".to_string());
+        let folders = SecuredFolders { folders: vec![folder, SecuredFolder::new(dir.path().join("photos"))] };
+        folders.save_to(&path).unwrap();
+        assert_eq!(SecuredFolders::load_from(&path).unwrap().folders, folders.folders);
+
+        // Lists saved before keys could be bound to folders still load
+        fs::write(&path, format!(r#"{{"folders":[{{"path":{:?}}}]}}"#, dir.path().join("docs"))).unwrap();
+        assert_eq!(SecuredFolders::load_from(&path).unwrap().folders[0].bound_key, None);
+    }
+}
@@ -0,0 +1,311 @@
+/// Audit service.
+///
+/// This module provides functionality for:
+/// - Keeping the files and key chosen on the audit screen
+/// - Auditing an encrypted file against its original in the background, and logging it
+/// - Verifying encrypted files (such as backups) without decrypting them to disk
+/// - The report of the last audit, and the result of the last verification
+///
+/// The key is chosen by its place in the saved keys; the app looks it up when the audit
+/// or verification starts.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::audit::{self, AuditReport};
+use crate::backend::BackendFactory;
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::i18n::{tr, tr_args};
+use crate::secured_folders;
+use crate::services::{log_error, log_key_success, log_path, log_success, EventQueue, ServiceEvent};
+
+/// Result of verifying one file
+#[derive(Debug, Clone)]
+pub struct FileVerification {
+    pub path: PathBuf,
+    /// Ok if the file is authentic, or why it could not be verified
+    pub result: Result<(), String>,
+}
+
+/// Verification running in the background
+struct VerifyJob {
+    /// Fraction of the files done
+    progress: Arc<Mutex<f32>>,
+    handle: JoinHandle<Vec<FileVerification>>,
+}
+
+/// Choices of the audit screen, and the audit in progress
+pub struct AuditService {
+    /// Original (unencrypted) file
+    pub original: Option<PathBuf>,
+    /// Encrypted file to check against the original
+    pub encrypted: Option<PathBuf>,
+    /// Saved key the files are encrypted with
+    pub key: Option<usize>,
+    job: Option<JoinHandle<Result<AuditReport, EncryptionError>>>,
+    report: Option<AuditReport>,
+    verify_files: Vec<PathBuf>,
+    verify_job: Option<VerifyJob>,
+    verifications: Vec<FileVerification>,
+    events: EventQueue,
+}
+
+impl AuditService {
+    /// Create a service with nothing chosen
+    pub fn new() -> Self {
+        AuditService {
+            original: None,
+            encrypted: None,
+            key: None,
+            job: None,
+            report: None,
+            verify_files: Vec::new(),
+            verify_job: None,
+            verifications: Vec::new(),
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Audit the encrypted file against the original in the background
+    pub fn start(&mut self, key: EncryptionKey) {
+        let (Some(original), Some(encrypted)) = (self.original.clone(), self.encrypted.clone()) else {
+            self.events.error(tr("error-audit-files"));
+            return;
+        };
+        if self.is_running() {
+            self.events.error(tr("error-audit-running"));
+            return;
+        }
+
+        self.report = None;
+        self.job = Some(thread::spawn(move || audit::audit_file(&original, &encrypted, &key)));
+        self.events.status(tr("status-audit-started"));
+    }
+
+    /// Check whether an audit or verification is running
+    pub fn is_running(&self) -> bool {
+        self.job.is_some() || self.verify_job.is_some()
+    }
+
+    /// Get the files to verify
+    pub fn verify_files(&self) -> &[PathBuf] {
+        &self.verify_files
+    }
+
+    /// Add files to verify
+    pub fn add_verify_files(&mut self, files: Vec<PathBuf>) {
+        for file in files {
+            if !self.verify_files.contains(&file) {
+                self.verify_files.push(file);
+            }
+        }
+    }
+
+    /// Add the files in a folder and its subfolders to verify
+    pub fn add_verify_folder(&mut self, folder: &Path) {
+        match secured_folders::folder_files(folder) {
+            Ok(mut files) => {
+                files.sort();
+                self.events.status(tr_args("status-files-found", &[("count", files.len().into()), ("folder", folder.display().to_string().into())]));
+                self.add_verify_files(files);
+            },
+            Err(e) => self.events.error(tr_args("error-read-path", &[("path", folder.display().to_string().into()), ("error", e.to_string().into())])),
+        }
+    }
+
+    /// Remove every file from the files to verify, and the last results
+    pub fn clear_verify_files(&mut self) {
+        self.verify_files.clear();
+        self.verifications.clear();
+    }
+
+    /// Verify the files in the background, without writing any decrypted data
+    pub fn start_verify(&mut self, key: EncryptionKey) {
+        if self.verify_files.is_empty() {
+            self.events.error(tr("error-verify-files"));
+            return;
+        }
+        if self.is_running() {
+            self.events.error(tr("error-audit-running"));
+            return;
+        }
+
+        let files = self.verify_files.clone();
+        let progress = Arc::new(Mutex::new(0.0));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            let backend = BackendFactory::create_local();
+            let mut verifications = Vec::with_capacity(files.len());
+            for (i, path) in files.iter().enumerate() {
+                let result = backend.verify_file(path, &key, |_| {}).map_err(|e| e.to_string());
+                verifications.push(FileVerification { path: path.clone(), result });
+                *job_progress.lock().unwrap() = (i + 1) as f32 / files.len() as f32;
+            }
+            verifications
+        });
+
+        self.verifications.clear();
+        self.verify_job = Some(VerifyJob { progress, handle });
+        self.events.status(tr_args("status-verify-started", &[("count", self.verify_files.len().into())]));
+    }
+
+    /// Get the fraction done of the verification in progress
+    pub fn verify_progress(&self) -> Option<f32> {
+        self.verify_job.as_ref().map(|job| *job.progress.lock().unwrap())
+    }
+
+    /// Get the result of each file of the last verification
+    pub fn verifications(&self) -> &[FileVerification] {
+        &self.verifications
+    }
+
+    /// Check whether the audit or verification has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether an audit or verification is still running
+    pub fn poll(&mut self) -> bool {
+        let verifying = self.poll_verify();
+        match &self.job {
+            Some(job) if !job.is_finished() => return true,
+            Some(_) => {},
+            None => return verifying,
+        }
+
+        let path = self.encrypted.as_deref().map(Path::to_string_lossy).unwrap_or_default().to_string();
+        match self.job.take().unwrap().join() {
+            Ok(Ok(report)) => {
+                if report.matches() {
+                    log_key_success(
+                        "Audit",
+                        &path,
+                        &format!("Matches {} (SHA-256 {})", log_path(&report.original_path), report.original.sha256),
+                        &report.key_fingerprint,
+                    );
+                    self.events.status(tr("status-audit-match"));
+                } else {
+                    log_error("Audit", &path, &format!("Does not match {}", log_path(&report.original_path)));
+                    self.events.error(tr("error-audit-mismatch"));
+                }
+                self.report = Some(report);
+            },
+            Ok(Err(e)) => {
+                log_error("Audit", &path, &e.to_string());
+                self.events.error(tr_args("error-audit-failed", &[("error", e.to_string().into())]));
+            },
+            Err(_) => self.events.error(tr("error-audit-stopped")),
+        }
+        verifying
+    }
+
+    /// Check whether the verification has finished, and report the result if so
+    fn poll_verify(&mut self) -> bool {
+        match &self.verify_job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let verifications = match self.verify_job.take().unwrap().handle.join() {
+            Ok(verifications) => verifications,
+            Err(_) => {
+                self.events.error(tr("error-verify-stopped"));
+                return false;
+            },
+        };
+
+        let mut failed = 0;
+        for file in &verifications {
+            let path = file.path.to_string_lossy();
+            match &file.result {
+                Ok(()) => log_success("Verify", &path, "Authentic; nothing was decrypted to disk"),
+                Err(error) => {
+                    log_error("Verify", &path, error);
+                    failed += 1;
+                },
+            }
+        }
+        if failed == 0 {
+            self.events.status(tr_args("status-verify-authentic", &[("count", verifications.len().into())]));
+        } else {
+            self.events.error(tr_args("error-verify-failed", &[("failed", failed.into()), ("count", verifications.len().into())]));
+        }
+        self.verifications = verifications;
+        false
+    }
+
+    /// Get the report of the last audit
+    pub fn report(&self) -> Option<&AuditReport> {
+        self.report.as_ref()
+    }
+
+    /// Save the report of the last audit
+    pub fn save_report(&mut self, path: &Path) {
+        let Some(report) = &self.report else { return };
+        match std::fs::write(path, report.to_text()) {
+            Ok(()) => self.events.status(tr_args("status-report-saved", &[("path", path.display().to_string().into())])),
+            Err(e) => self.events.error(tr_args("error-report-save", &[("error", e.to_string().into())])),
+        }
+    }
+}
+
+impl Default for AuditService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+    use crate::backend::BackendFactory;
+
+    #[test]
+    fn test_audit_in_background() {
+        let dir = tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        let original = dir.path().join("ledger.csv");
+        fs::write(&original, b"date,amount\n").unwrap();
+        let encrypted = dir.path().join("ledger.csv.encrypted");
+        BackendFactory::create_local().encrypt_file(&original, &encrypted, &key, |_| {}).unwrap();
+
+        let mut service = AuditService::new();
+        service.start(key.clone());
+        assert!(!service.is_running());
+
+        service.original = Some(original);
+        service.encrypted = Some(encrypted);
+        let service_key = key.clone();
+        service.start(key);
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(service.report().unwrap().matches());
+
+        // Verifying checks the tags only, and reports a changed file
+        let damaged = dir.path().join("damaged.encrypted");
+        let mut contents = fs::read(service.encrypted.as_ref().unwrap()).unwrap();
+        *contents.last_mut().unwrap() ^= 1;
+        fs::write(&damaged, contents).unwrap();
+        service.add_verify_files(vec![service.encrypted.clone().unwrap(), damaged]);
+        service.start_verify(EncryptionKey::generate());
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(service.verifications().iter().all(|file| file.result.is_err()));
+        service.start_verify(service_key);
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let results: Vec<bool> = service.verifications().iter().map(|file| file.result.is_ok()).collect();
+        assert_eq!(results, [true, false]);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+    }
+}
@@ -0,0 +1,247 @@
+/// Benchmark service.
+///
+/// This module provides functionality for:
+/// - Keeping the buffer sizes and backends chosen on the benchmark screen
+/// - Benchmarking the local backend and the embedded device in the background, one size
+///   at a time, with the results shown as they come
+/// - Stopping a benchmark, keeping what was measured
+///
+/// The buffers are encrypted with a key generated for the benchmark, so no saved key is
+/// needed. The device is benchmarked with the settings of the main screen.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::backend::{Backend, BackendFactory, EmbeddedConfig};
+use crate::benchmark::{self, BenchmarkResult};
+use crate::encryption::EncryptionKey;
+use crate::i18n::{tr, tr_args};
+use crate::locale;
+use crate::services::{EventQueue, ServiceEvent};
+use crate::speed_history::BackendKind;
+
+/// Result of one backend at one buffer size
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkRow {
+    pub backend: BackendKind,
+    pub result: BenchmarkResult,
+}
+
+/// Benchmark running in the background
+struct BenchmarkJob {
+    /// Results measured so far
+    rows: Arc<Mutex<Vec<BenchmarkRow>>>,
+    stop: Arc<AtomicBool>,
+    /// Why the benchmark could not finish, if it could not
+    handle: JoinHandle<Result<(), String>>,
+}
+
+/// Choices of the benchmark screen, and the benchmark in progress
+pub struct BenchmarkService {
+    /// Whether each of `benchmark::SIZES` is measured
+    pub sizes: [bool; benchmark::SIZES.len()],
+    /// Whether the embedded device is benchmarked too
+    pub include_device: bool,
+    job: Option<BenchmarkJob>,
+    rows: Vec<BenchmarkRow>,
+    events: EventQueue,
+}
+
+impl BenchmarkService {
+    /// Create a service measuring every size but the largest, on both backends
+    pub fn new() -> Self {
+        let mut sizes = [true; benchmark::SIZES.len()];
+        // The largest buffer takes minutes over a slow serial link
+        sizes[benchmark::SIZES.len() - 1] = false;
+        BenchmarkService {
+            sizes,
+            include_device: true,
+            job: None,
+            rows: Vec::new(),
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Benchmark the local backend, and the device if it is included, in the background
+    ///
+    /// # Arguments
+    /// * `device` - Configuration of the embedded device (used if the device is included)
+    pub fn start(&mut self, device: EmbeddedConfig) {
+        if self.is_running() {
+            self.events.error(tr("error-benchmark-running"));
+            return;
+        }
+        let sizes: Vec<usize> = benchmark::SIZES.iter().zip(self.sizes)
+            .filter(|&(_, measured)| measured)
+            .map(|(&size, _)| size)
+            .collect();
+        if sizes.is_empty() {
+            self.events.error(tr("error-benchmark-sizes"));
+            return;
+        }
+        if self.include_device {
+            if let Err(e) = device.validate() {
+                self.events.error(tr_args("error-benchmark-device", &[("error", e.to_string().into())]));
+                return;
+            }
+        }
+
+        let include_device = self.include_device;
+        let rows = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (job_rows, job_stop) = (rows.clone(), stop.clone());
+        let handle = thread::spawn(move || {
+            let key = EncryptionKey::generate();
+            let mut backends = vec![(BackendKind::Local, BackendFactory::create_local())];
+            if include_device {
+                let mut device = BackendFactory::create_embedded(device, Arc::new(Mutex::new(None)));
+                device.connect().map_err(|e| e.to_string())?;
+                backends.push((BackendKind::Embedded, device));
+            }
+            for size in sizes {
+                for (kind, backend) in &backends {
+                    if job_stop.load(Ordering::Relaxed) {
+                        return Ok(());
+                    }
+                    let result = measure(backend, &key, size, &job_stop)?;
+                    job_rows.lock().unwrap().push(BenchmarkRow { backend: *kind, result });
+                }
+            }
+            Ok(())
+        });
+
+        self.rows.clear();
+        self.job = Some(BenchmarkJob { rows, stop, handle });
+        self.events.status(tr("status-benchmark-started"));
+    }
+
+    /// Stop the benchmark after the buffer being encrypted
+    pub fn stop(&mut self) {
+        if let Some(job) = &self.job {
+            job.stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Check whether a benchmark is running
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Get the results measured so far, or those of the last benchmark
+    pub fn rows(&self) -> Vec<BenchmarkRow> {
+        match &self.job {
+            Some(job) => job.rows.lock().unwrap().clone(),
+            None => self.rows.clone(),
+        }
+    }
+
+    /// Get the result of a backend at a buffer size
+    pub fn result(&self, backend: BackendKind, size: usize) -> Option<BenchmarkResult> {
+        self.rows().into_iter()
+            .find(|row| row.backend == backend && row.result.size == size)
+            .map(|row| row.result)
+    }
+
+    /// Check whether the benchmark has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether the benchmark is still running
+    pub fn poll(&mut self) -> bool {
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let job = self.job.take().unwrap();
+        self.rows = job.rows.lock().unwrap().clone();
+        match job.handle.join() {
+            Ok(Ok(())) if job.stop.load(Ordering::Relaxed) => self.events.status(tr("status-benchmark-stopped")),
+            Ok(Ok(())) => self.events.status(self.summary()),
+            Ok(Err(e)) => self.events.error(tr_args("error-benchmark-failed", &[("error", e.to_string().into())])),
+            Err(_) => self.events.error(tr("error-benchmark-stopped")),
+        }
+        false
+    }
+
+    /// Describe which backend was faster, at which sizes
+    fn summary(&self) -> String {
+        let mut device_faster = Vec::new();
+        let mut compared = 0;
+        for size in benchmark::SIZES {
+            let (Some(local), Some(device)) = (
+                self.result(BackendKind::Local, size),
+                self.result(BackendKind::Embedded, size),
+            ) else { continue };
+            compared += 1;
+            if device.megabytes_per_sec() > local.megabytes_per_sec() {
+                device_faster.push(locale::format_size(size as u64));
+            }
+        }
+
+        match (compared, device_faster.len()) {
+            (0, _) => tr("status-benchmark-finished"),
+            (_, 0) => tr("status-benchmark-software-faster"),
+            (compared, faster) if faster == compared => tr("status-benchmark-device-faster"),
+            _ => tr_args("status-benchmark-device-faster-for", &[("sizes", device_faster.join(", ").into())]),
+        }
+    }
+}
+
+impl Default for BenchmarkService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Measure a backend at one size, with the error as text
+fn measure(backend: &Backend, key: &EncryptionKey, size: usize, stop: &AtomicBool) -> Result<BenchmarkResult, String> {
+    benchmark::measure(backend, key, size, benchmark::TIME_PER_SIZE, stop).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::backend::{ConnectionSettings, ConnectionType};
+
+    /// Configuration of a device that is not there
+    fn missing_device() -> EmbeddedConfig {
+        EmbeddedConfig {
+            device_id: String::new(),
+            connection: ConnectionSettings::default_for(&ConnectionType::Usb),
+        }
+    }
+
+    #[test]
+    fn test_local_benchmark() {
+        let mut service = BenchmarkService::new();
+        service.sizes = [true, false, false, false];
+
+        // The device settings are checked before anything runs
+        service.start(missing_device());
+        assert!(!service.is_running());
+        assert!(matches!(service.take_events().last(), Some(ServiceEvent::Error(_))));
+
+        service.include_device = false;
+        service.start(missing_device());
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let rows = service.rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].backend, BackendKind::Local);
+        assert!(service.result(BackendKind::Local, benchmark::SIZES[0]).is_some());
+        assert!(service.result(BackendKind::Embedded, benchmark::SIZES[0]).is_none());
+        assert_eq!(service.take_events().last(), Some(&ServiceEvent::Status("Benchmark finished".to_string())));
+
+        service.sizes = [false; benchmark::SIZES.len()];
+        service.start(missing_device());
+        assert!(!service.is_running());
+    }
+}
@@ -0,0 +1,150 @@
+/// Catalog service.
+///
+/// This module provides functionality for:
+/// - Keeping the folder and name choice of the catalog screen
+/// - Making the catalog of a folder of encrypted files in the background, with progress
+/// - Finding the file a collaborator asks for by its ID
+/// - Saving the catalog to share it, and logging the export
+///
+/// The saved keys are passed in when the catalog is made, to find each file's key.
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::catalog::{self, Catalog, CatalogNames};
+use crate::encryption::EncryptionKey;
+use crate::i18n::{tr, tr_args};
+use crate::services::{log_error, log_path, log_success, EventQueue, ServiceEvent};
+
+/// Catalog being made in the background
+struct CatalogJob {
+    /// Files done and files to do
+    progress: Arc<Mutex<(usize, usize)>>,
+    handle: JoinHandle<io::Result<Catalog>>,
+}
+
+/// Choices of the catalog screen, and the last catalog made
+pub struct CatalogService {
+    /// Folder of encrypted files
+    pub folder: Option<PathBuf>,
+    /// What the catalog says about file names
+    pub names: CatalogNames,
+    /// ID (or the start of one) of a requested file to find
+    pub find_id: String,
+    job: Option<CatalogJob>,
+    catalog: Option<Catalog>,
+    events: EventQueue,
+}
+
+impl CatalogService {
+    /// Create a service with no folder chosen, hashing names
+    pub fn new() -> Self {
+        CatalogService {
+            folder: None,
+            names: CatalogNames::Hashed,
+            find_id: String::new(),
+            job: None,
+            catalog: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Make the catalog of the chosen folder in the background
+    ///
+    /// # Arguments
+    /// * `keys` - The saved keys, to find the key of each file
+    pub fn start(&mut self, keys: Vec<EncryptionKey>) {
+        let Some(folder) = self.folder.clone() else {
+            self.events.error(tr("error-catalog-folder"));
+            return;
+        };
+        if self.is_running() {
+            self.events.error(tr("error-catalog-running"));
+            return;
+        }
+
+        let names = self.names;
+        let progress = Arc::new(Mutex::new((0, 0)));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            catalog::build_catalog(&folder, names, &keys, |done, total| *job_progress.lock().unwrap() = (done, total))
+        });
+
+        self.catalog = None;
+        self.job = Some(CatalogJob { progress, handle });
+        self.events.status(tr("status-catalog-started"));
+    }
+
+    /// Check whether a catalog is being made
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Get the fraction of files done of the catalog being made
+    pub fn progress(&self) -> Option<f32> {
+        let (done, total) = *self.job.as_ref()?.progress.lock().unwrap();
+        Some(if total == 0 { 0.0 } else { done as f32 / total as f32 })
+    }
+
+    /// Get the last catalog made
+    pub fn catalog(&self) -> Option<&Catalog> {
+        self.catalog.as_ref()
+    }
+
+    /// Save the last catalog, as JSON or CSV by the file's extension
+    pub fn save(&mut self, path: &Path) {
+        let Some(catalog) = &self.catalog else { return };
+        let folder = catalog.folder.to_string_lossy().to_string();
+        match catalog.save(path) {
+            Ok(()) => {
+                log_success(
+                    "Export Catalog",
+                    &folder,
+                    &format!("Catalog of {} file(s) ({}) saved to {}", catalog.files.len(), catalog.names.name(), log_path(path)),
+                );
+                self.events.status(tr_args("status-catalog-saved", &[("path", path.display().to_string().into())]));
+            },
+            Err(e) => {
+                log_error("Export Catalog", &folder, &e.to_string());
+                self.events.error(tr_args("error-catalog-save", &[("error", e.to_string().into())]));
+            },
+        }
+    }
+
+    /// Check whether the catalog has been made, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether a catalog is still being made
+    pub fn poll(&mut self) -> bool {
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let result = self.job.take().unwrap().handle.join()
+            .unwrap_or_else(|_| Err(io::Error::other("Making the catalog stopped unexpectedly")));
+        match result {
+            Ok(catalog) => {
+                let unknown = catalog.files.iter().filter(|entry| entry.key_id.is_none()).count();
+                let args = [("count", catalog.files.len().into()), ("unknown", unknown.into())];
+                self.events.status(tr_args(if unknown > 0 { "status-catalog-made-unknown" } else { "status-catalog-made" }, &args));
+                self.catalog = Some(catalog);
+            },
+            Err(e) => self.events.error(tr_args("error-catalog-make", &[("error", e.to_string().into())])),
+        }
+        false
+    }
+}
+
+impl Default for CatalogService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
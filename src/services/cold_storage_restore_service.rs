@@ -0,0 +1,392 @@
+/// Cold-storage restore service.
+///
+/// This module provides functionality for:
+/// - Keeping the choices made in the cold-storage restore wizard: the manifest, the
+///   folders the volumes are found in, and the shares or passphrase entered
+/// - Checking and repairing the volumes, and restoring the set, in the background
+/// - The verification report of the last restore
+///
+/// The key rebuilt from the shares is only kept until the restore is done, and is
+/// wiped with the shares when the keys are locked.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use zeroize::Zeroizing;
+
+use crate::cold_storage::{self, Manifest, RestoreReport, SetCheck};
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::i18n::{tr, tr_args};
+use crate::services::{log_error, log_path, log_success, EventQueue, ServiceEvent};
+use crate::split_key;
+
+/// What a background job of the restore found
+enum JobResult {
+    Checked(SetCheck),
+    Restored(Result<RestoreReport, EncryptionError>),
+}
+
+/// Check or restore running in the background
+struct RestoreJob {
+    /// Fraction done
+    progress: Arc<Mutex<f32>>,
+    handle: JoinHandle<JobResult>,
+}
+
+/// Choices of the cold-storage restore wizard, and the check or restore in progress
+pub struct ColdStorageRestoreService {
+    manifest_path: Option<PathBuf>,
+    manifest: Option<Manifest>,
+    /// Folders searched for the volumes (with their subfolders), the manifest's first
+    folders: Vec<PathBuf>,
+    check: Option<SetCheck>,
+    /// Shares entered from the recovery sheet
+    pub shares: Vec<String>,
+    /// Passphrase of a set encrypted with a passphrase key
+    pub passphrase: Zeroizing<String>,
+    key: Option<EncryptionKey>,
+    /// Where the set's folder is restored
+    pub output_dir: Option<PathBuf>,
+    job: Option<RestoreJob>,
+    report: Option<RestoreReport>,
+    events: EventQueue,
+}
+
+impl ColdStorageRestoreService {
+    /// Create a service with nothing chosen
+    pub fn new() -> Self {
+        ColdStorageRestoreService {
+            manifest_path: None,
+            manifest: None,
+            folders: Vec::new(),
+            check: None,
+            shares: Vec::new(),
+            passphrase: Zeroizing::new(String::new()),
+            key: None,
+            output_dir: None,
+            job: None,
+            report: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Read the manifest of the set to restore; its folder is searched for the volumes
+    pub fn select_manifest(&mut self, path: PathBuf) {
+        match cold_storage::read_manifest(&path) {
+            Ok(manifest) => {
+                self.reset();
+                self.folders = path.parent().map(Path::to_path_buf).into_iter().collect();
+                self.shares = vec![String::new(); manifest.threshold as usize];
+                self.events.status(tr_args(
+                    "status-manifest-read",
+                    &[("label", manifest.label.as_str().into()), ("count", manifest.volumes.len().into())],
+                ));
+                self.manifest = Some(manifest);
+                self.manifest_path = Some(path);
+            },
+            Err(e) => self.events.error(tr_args("error-read-path", &[("path", path.display().to_string().into()), ("error", e.to_string().into())])),
+        }
+    }
+
+    /// Get the manifest of the set to restore
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Get the manifest file read
+    pub fn manifest_path(&self) -> Option<&Path> {
+        self.manifest_path.as_deref()
+    }
+
+    /// Get the folders searched for the volumes
+    pub fn folders(&self) -> &[PathBuf] {
+        &self.folders
+    }
+
+    /// Search another folder for the volumes, such as another disc
+    pub fn add_folder(&mut self, folder: PathBuf) {
+        if !self.folders.contains(&folder) {
+            self.folders.push(folder);
+            self.check = None;
+        }
+    }
+
+    /// Stop searching a folder for the volumes
+    pub fn remove_folder(&mut self, index: usize) {
+        if index < self.folders.len() {
+            self.folders.remove(index);
+            self.check = None;
+        }
+    }
+
+    /// Get the volumes found, each with its path or None if it is missing
+    pub fn found_volumes(&self) -> Vec<(String, Option<PathBuf>)> {
+        self.manifest.iter()
+            .flat_map(|manifest| &manifest.volumes)
+            .map(|volume| (volume.file_name.clone(), cold_storage::find_volume(&self.folders, &volume.file_name)))
+            .collect()
+    }
+
+    /// Check and repair the volumes in the background
+    pub fn start_check(&mut self) {
+        let Some(manifest) = self.manifest.clone() else {
+            self.events.error(tr("error-restore-manifest-first"));
+            return;
+        };
+        if self.job.is_some() {
+            self.events.error(tr("error-restore-running"));
+            return;
+        }
+
+        let folders = self.folders.clone();
+        let progress = Arc::new(Mutex::new(0.0));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            JobResult::Checked(cold_storage::check_set(&manifest, &folders, |fraction| *job_progress.lock().unwrap() = fraction))
+        });
+
+        self.check = None;
+        self.job = Some(RestoreJob { progress, handle });
+        self.events.status(tr("status-volumes-checking"));
+    }
+
+    /// Get the result of the last check of the volumes
+    pub fn check(&self) -> Option<&SetCheck> {
+        self.check.as_ref()
+    }
+
+    /// Check whether the set was encrypted with a passphrase key instead of shares
+    pub fn needs_passphrase(&self) -> bool {
+        self.check.as_ref().is_some_and(|check| check.kdf.is_some())
+    }
+
+    /// Handle a share field that was edited (several shares pasted into the field are
+    /// spread over their own fields)
+    pub fn share_changed(&mut self, index: usize) {
+        let pasted = match self.shares.get(index) {
+            Some(content) => split_key::split_pasted_shares(content),
+            None => return,
+        };
+        if pasted.len() > 1 {
+            let count = pasted.len();
+            self.shares.splice(index..=index, pasted);
+            self.events.status(tr_args("status-shares-pasted", &[("count", count.into())]));
+        }
+    }
+
+    /// Add an empty share field
+    pub fn add_share_field(&mut self) {
+        self.shares.push(String::new());
+    }
+
+    /// Rebuild the set's key from the shares or passphrase entered, and check it against
+    /// the manifest
+    pub fn recover_key(&mut self) {
+        let Some(manifest) = &self.manifest else { return };
+        let result = match self.check.as_ref().and_then(|check| check.kdf.as_ref()) {
+            Some(params) => cold_storage::passphrase_key(manifest, &self.passphrase, params),
+            None => {
+                let shares: Vec<String> = self.shares.iter()
+                    .filter(|share| !share.trim().is_empty())
+                    .cloned()
+                    .collect();
+                cold_storage::recover_key(manifest, &shares)
+            },
+        };
+
+        match result {
+            Ok(key) => {
+                self.events.status(tr_args("status-set-key-rebuilt", &[("fingerprint", key.fingerprint().into())]));
+                self.key = Some(key);
+                self.clear_entered_secrets();
+            },
+            Err(e) => self.events.error(e.to_string()),
+        }
+    }
+
+    /// Check whether the set's key has been rebuilt
+    pub fn has_key(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Restore the set in the background, with the key rebuilt
+    pub fn start_restore(&mut self) {
+        let (manifest, key, output_dir) = match (self.manifest.clone(), self.key.clone(), self.output_dir.clone()) {
+            (Some(manifest), Some(key), Some(output_dir)) => (manifest, key, output_dir),
+            _ => {
+                self.events.error(tr("error-restore-choose"));
+                return;
+            },
+        };
+        if self.job.is_some() {
+            self.events.error(tr("error-restore-running"));
+            return;
+        }
+
+        let folders = self.folders.clone();
+        let progress = Arc::new(Mutex::new(0.0));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            JobResult::Restored(cold_storage::restore_set(&manifest, &folders, &key, &output_dir, |fraction| {
+                *job_progress.lock().unwrap() = fraction
+            }))
+        });
+
+        self.report = None;
+        self.job = Some(RestoreJob { progress, handle });
+        self.events.status(tr("status-restore-started"));
+    }
+
+    /// Get the fraction done of the check or restore in progress
+    pub fn job_progress(&self) -> Option<f32> {
+        self.job.as_ref().map(|job| *job.progress.lock().unwrap())
+    }
+
+    /// Check whether the check or restore has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether a check or restore is still running
+    pub fn poll(&mut self) -> bool {
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let job = self.job.take().unwrap();
+        let label = self.manifest.as_ref().map(|manifest| manifest.label.clone()).unwrap_or_default();
+        match job.handle.join() {
+            Ok(JobResult::Checked(check)) => {
+                if check.is_complete() {
+                    self.events.status(tr("status-volumes-readable"));
+                } else {
+                    self.events.error(tr("error-volumes-missing"));
+                }
+                self.check = Some(check);
+            },
+            Ok(JobResult::Restored(Ok(report))) => {
+                log_success("Cold Storage Restore", &label, &format!(
+                    "Restored cold-storage set {} to {} ({} of {} file(s))",
+                    label, log_path(&report.restored_dir), report.files_restored, report.manifest.file_count
+                ));
+                self.events.status(tr_args("status-set-restored", &[
+                    ("label", label.as_str().into()),
+                    ("folder", report.restored_dir.display().to_string().into()),
+                    ("restored", report.files_restored.into()),
+                    ("count", report.manifest.file_count.into()),
+                ]));
+                // The key is no longer needed once the set is restored
+                self.key = None;
+                self.report = Some(report);
+            },
+            Ok(JobResult::Restored(Err(e))) => {
+                log_error("Cold Storage Restore", &label, &e.to_string());
+                self.events.error(tr_args("error-set-restore", &[("error", e.to_string().into())]));
+            },
+            Err(_) => self.events.error(tr("error-cold-storage-stopped")),
+        }
+        false
+    }
+
+    /// Get the verification report of the last restore
+    pub fn report(&self) -> Option<&RestoreReport> {
+        self.report.as_ref()
+    }
+
+    /// Save the verification report of the last restore
+    pub fn save_report(&mut self, path: &Path) {
+        let Some(report) = &self.report else { return };
+        match std::fs::write(path, report.to_text()) {
+            Ok(()) => self.events.status(tr_args("status-report-saved", &[("path", path.display().to_string().into())])),
+            Err(e) => self.events.error(tr_args("error-report-save", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Wipe the shares and passphrase entered
+    fn clear_entered_secrets(&mut self) {
+        let fields = self.manifest.as_ref().map_or(0, |manifest| manifest.threshold as usize);
+        self.shares = vec![String::new(); fields];
+        self.passphrase = Zeroizing::new(String::new());
+    }
+
+    /// Wipe the shares, passphrase, and key rebuilt (when the keys are locked)
+    pub fn clear_secrets(&mut self) {
+        self.clear_entered_secrets();
+        self.key = None;
+    }
+
+    /// Forget the set, to restore another
+    pub fn reset(&mut self) {
+        self.manifest_path = None;
+        self.manifest = None;
+        self.folders.clear();
+        self.check = None;
+        self.clear_secrets();
+        self.report = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::cold_storage::{ExportOptions, Media, MANIFEST_NAME};
+
+    fn wait(service: &mut ColdStorageRestoreService) {
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_check_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Letters");
+        std::fs::create_dir(&folder).unwrap();
+        std::fs::write(folder.join("1998.txt"), b"Dear Ann").unwrap();
+        let options = ExportOptions { label: "Letters 1998".to_string(), media: Media::Cd, threshold: 2, shares: 3 };
+        let export = cold_storage::export_set(&folder, dir.path(), &EncryptionKey::generate(), &options, |_| {}).unwrap();
+        let sheet = std::fs::read_to_string(&export.recovery_sheet).unwrap();
+        let lines: Vec<&str> = sheet.lines().collect();
+        let pasted = lines.windows(2)
+            .filter(|pair| pair[0] == "Text:")
+            .map(|pair| pair[1])
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut service = ColdStorageRestoreService::new();
+        service.select_manifest(export.set_dir.join("Volume 01").join(MANIFEST_NAME));
+        assert_eq!(service.found_volumes().len(), 1);
+        assert!(service.found_volumes()[0].1.is_some());
+        service.start_check();
+        wait(&mut service);
+        assert!(service.check().unwrap().is_complete());
+        assert!(!service.needs_passphrase());
+
+        // All three shares pasted into the first field
+        service.shares[0] = pasted;
+        service.share_changed(0);
+        assert_eq!(service.shares.len(), 4);
+        service.recover_key();
+        assert!(service.has_key());
+        assert!(service.shares.iter().all(String::is_empty));
+
+        let restore_dir = dir.path().join("restored");
+        std::fs::create_dir(&restore_dir).unwrap();
+        service.output_dir = Some(restore_dir.clone());
+        service.start_restore();
+        wait(&mut service);
+        assert!(service.report().unwrap().is_complete());
+        assert!(!service.has_key());
+        assert_eq!(std::fs::read(restore_dir.join("Letters").join("1998.txt")).unwrap(), b"Dear Ann");
+
+        let report_path = dir.path().join("report.txt");
+        service.save_report(&report_path);
+        assert!(std::fs::read_to_string(&report_path).unwrap().contains("1 of 1 restored"));
+    }
+}
@@ -0,0 +1,220 @@
+/// Cold-storage service.
+///
+/// This module provides functionality for:
+/// - Keeping the choices made in the cold-storage export wizard
+/// - Writing a cold-storage set in the background, with progress
+/// - Shredding the set's recovery sheet once it has been printed
+///
+/// Every set is encrypted with a new key of its own, which is only kept as the shares
+/// on its recovery sheet: the set can be restored with the shares alone, and losing a
+/// saved key never makes it unreadable.
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::cold_storage::{self, ExportOptions, ExportReport, Media};
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::i18n::{tr, tr_args};
+use crate::in_place;
+use crate::services::{log_error, log_path, log_success, EventQueue, ServiceEvent};
+
+/// Export running in the background
+struct ExportJob {
+    /// Fraction done
+    progress: Arc<Mutex<f32>>,
+    handle: JoinHandle<Result<ExportReport, EncryptionError>>,
+}
+
+/// Choices of the cold-storage export wizard, and the export in progress
+pub struct ColdStorageService {
+    /// Folder to archive
+    pub folder: Option<PathBuf>,
+    /// Where the set's folder is created
+    pub output_dir: Option<PathBuf>,
+    /// Name of the set
+    pub label: String,
+    /// Media the volumes are sized for
+    pub media: Media,
+    /// Shares needed to recover the key
+    pub threshold: u8,
+    /// Shares printed on the recovery sheet
+    pub shares: u8,
+    job: Option<ExportJob>,
+    report: Option<ExportReport>,
+    events: EventQueue,
+}
+
+impl ColdStorageService {
+    /// Create a service with nothing chosen
+    pub fn new() -> Self {
+        ColdStorageService {
+            folder: None,
+            output_dir: None,
+            label: String::new(),
+            media: Media::Dvd,
+            threshold: 3,
+            shares: 5,
+            job: None,
+            report: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Choose the folder to archive (the label defaults to its name)
+    pub fn select_folder(&mut self, folder: PathBuf) {
+        if self.label.is_empty() {
+            self.label = folder.file_name()
+                .map(|name| name.to_string_lossy().chars()
+                    .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
+                    .take(64)
+                    .collect())
+                .unwrap_or_default();
+        }
+        self.folder = Some(folder);
+    }
+
+    /// Check whether everything needed for an export has been chosen
+    pub fn is_ready(&self) -> bool {
+        self.folder.is_some()
+            && self.output_dir.is_some()
+            && !self.label.trim().is_empty()
+            && self.threshold >= 2
+            && self.shares >= self.threshold
+    }
+
+    /// Write the set in the background, with a new key
+    pub fn start(&mut self) {
+        let (folder, output_dir) = match (self.folder.clone(), self.output_dir.clone()) {
+            (Some(folder), Some(output_dir)) => (folder, output_dir),
+            _ => {
+                self.events.error(tr("error-cold-storage-choose"));
+                return;
+            },
+        };
+        if self.job.is_some() {
+            self.events.error(tr("error-cold-storage-running"));
+            return;
+        }
+
+        let options = ExportOptions {
+            label: self.label.clone(),
+            media: self.media,
+            threshold: self.threshold,
+            shares: self.shares,
+        };
+        let progress = Arc::new(Mutex::new(0.0));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            let key = EncryptionKey::generate();
+            cold_storage::export_set(&folder, &output_dir, &key, &options, |fraction| *job_progress.lock().unwrap() = fraction)
+        });
+
+        self.report = None;
+        self.job = Some(ExportJob { progress, handle });
+        self.events.status(tr("status-cold-storage-started"));
+    }
+
+    /// Get the fraction done of the export in progress
+    pub fn job_progress(&self) -> Option<f32> {
+        self.job.as_ref().map(|job| *job.progress.lock().unwrap())
+    }
+
+    /// Check whether the export has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether an export is still running
+    pub fn poll(&mut self) -> bool {
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let job = self.job.take().unwrap();
+        let result = job.handle.join()
+            .unwrap_or_else(|_| Err(EncryptionError::Encryption("The export stopped unexpectedly".to_string())));
+        let folder = self.folder.as_ref().map(|folder| folder.to_string_lossy().into_owned()).unwrap_or_default();
+        match result {
+            Ok(report) => {
+                let message = format!(
+                    "Wrote cold-storage set {} ({} volume(s), {} file(s))",
+                    log_path(&report.set_dir), report.manifest.volumes.len(), report.manifest.file_count
+                );
+                log_success("Cold Storage Export", &folder, &message);
+                self.events.status(tr_args("status-cold-storage-written", &[
+                    ("path", report.set_dir.display().to_string().into()),
+                    ("volumes", report.manifest.volumes.len().into()),
+                    ("files", report.manifest.file_count.into()),
+                ]));
+                self.report = Some(report);
+            },
+            Err(e) => {
+                log_error("Cold Storage Export", &folder, &e.to_string());
+                self.events.error(tr_args("error-cold-storage-write", &[("error", e.to_string().into())]));
+            },
+        }
+        false
+    }
+
+    /// Get what the last export wrote
+    pub fn report(&self) -> Option<&ExportReport> {
+        self.report.as_ref()
+    }
+
+    /// Shred the recovery sheet of the last export, once it has been printed
+    pub fn delete_recovery_sheet(&mut self) {
+        let sheet = match &self.report {
+            Some(report) => report.recovery_sheet.clone(),
+            None => return,
+        };
+        match in_place::shred_file(&sheet) {
+            Ok(()) => self.events.status(tr_args("status-deleted", &[("path", sheet.display().to_string().into())])),
+            Err(e) => self.events.error(tr_args("error-delete", &[("path", sheet.display().to_string().into()), ("error", e.to_string().into())])),
+        }
+    }
+
+    /// Forget the last export, to start another set
+    pub fn reset(&mut self) {
+        self.folder = None;
+        self.label.clear();
+        self.report = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_export_and_delete_sheet() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Tax Records (2019)");
+        std::fs::create_dir(&folder).unwrap();
+        std::fs::write(folder.join("return.pdf"), b"Return").unwrap();
+
+        let mut service = ColdStorageService::new();
+        service.select_folder(folder);
+        assert_eq!(service.label, "Tax Records 2019");
+        assert!(!service.is_ready());
+        service.output_dir = Some(dir.path().to_path_buf());
+        service.media = Media::Cd;
+        assert!(service.is_ready());
+
+        service.start();
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let sheet = service.report().unwrap().recovery_sheet.clone();
+        assert!(sheet.exists());
+        assert!(matches!(service.take_events().last(), Some(ServiceEvent::Status(message)) if message.contains("1 volume,")));
+
+        service.delete_recovery_sheet();
+        assert!(!sheet.exists());
+    }
+}
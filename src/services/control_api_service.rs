@@ -0,0 +1,287 @@
+/// Control API service.
+///
+/// This module provides functionality for:
+/// - Starting and stopping the local control API
+/// - Running the jobs queued through it one at a time, in the background, with the
+///   app's current key
+/// - Reporting the app's state and the jobs' progress and results to the API
+///
+/// Jobs run on this computer, with the default options: names are not hidden and the
+/// originals are kept. They do not change the files selected in the window.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::backend::BackendFactory;
+use crate::control_api::{self, AppStatus, ControlApi, JobOperation};
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::file_names;
+use crate::i18n::{tr, tr_args};
+use crate::logger::{get_logger, LogEntry};
+use crate::services::{EventQueue, ServiceEvent};
+use crate::start_operation::{logged_error, logged_result};
+
+/// Job running in the background
+struct RunningJob {
+    id: u64,
+    /// Progress of each file, updated by the job thread
+    progress: Arc<Mutex<Vec<f32>>>,
+    handle: JoinHandle<Result<Vec<String>, EncryptionError>>,
+}
+
+/// Local control API and the job it is running
+pub struct ControlApiService {
+    api: Option<ControlApi>,
+    job: Option<RunningJob>,
+    /// Called when a job is queued, to repaint the window
+    wake: Arc<dyn Fn() + Send + Sync>,
+    events: EventQueue,
+}
+
+impl ControlApiService {
+    /// Create a service with the API stopped
+    pub fn new() -> Self {
+        ControlApiService {
+            api: None,
+            job: None,
+            wake: Arc::new(|| {}),
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Set what is called when a client queues a job (to repaint the window)
+    pub fn set_wake(&mut self, wake: impl Fn() + Send + Sync + 'static) {
+        self.wake = Arc::new(wake);
+    }
+
+    /// Start the API on a loopback port
+    ///
+    /// # Arguments
+    /// * `port` - Port to listen on
+    /// * `dir` - Folder to write the port and token to (control_api.json)
+    pub fn start(&mut self, port: u16, dir: &Path) {
+        if self.api.as_ref().is_some_and(|api| api.port() == port) {
+            return;
+        }
+        self.api = None;
+
+        let wake = self.wake.clone();
+        match control_api::start(port, dir, move || wake()) {
+            Ok(api) => {
+                self.events.status(tr_args("status-control-api-listening", &[("port", api.port().to_string().into())]));
+                self.api = Some(api);
+            },
+            Err(e) => self.events.error(tr_args("error-control-api-start", &[("port", port.to_string().into()), ("error", e.to_string().into())])),
+        }
+    }
+
+    /// Stop the API; a running job still finishes, and queued jobs are dropped
+    pub fn stop(&mut self) {
+        if self.api.take().is_some() {
+            self.events.status(tr("status-control-api-stopped"));
+        }
+    }
+
+    /// Get the port the API listens on (None when stopped)
+    pub fn port(&self) -> Option<u16> {
+        self.api.as_ref().map(ControlApi::port)
+    }
+
+    /// Get the token clients must send (None when stopped)
+    pub fn token(&self) -> Option<&str> {
+        self.api.as_ref().map(ControlApi::token)
+    }
+
+    /// Report the app's state, follow the running job, and start the next queued job
+    ///
+    /// # Arguments
+    /// * `key` - The current key, that jobs run with
+    /// * `keys_locked` - Whether the keys are locked
+    ///
+    /// # Returns
+    /// * `bool` - Whether a job is running
+    pub fn poll(&mut self, key: Option<&EncryptionKey>, keys_locked: bool) -> bool {
+        let api = match &self.api {
+            Some(api) => api,
+            None => {
+                // Jobs of a stopped API still finish, but nobody can ask for their results
+                if self.job.as_ref().is_some_and(|job| job.handle.is_finished()) {
+                    self.job = None;
+                }
+                return self.job.is_some();
+            },
+        };
+        api.set_status(AppStatus { key_selected: key.is_some(), keys_locked });
+
+        if let Some(job) = &self.job {
+            if !job.handle.is_finished() {
+                let progress = job.progress.lock().unwrap();
+                if !progress.is_empty() {
+                    api.set_progress(job.id, progress.iter().sum::<f32>() / progress.len() as f32);
+                }
+                return true;
+            }
+
+            let job = self.job.take().unwrap();
+            match job.handle.join() {
+                Ok(Ok(results)) => api.finish_job(job.id, results),
+                Ok(Err(e)) => api.fail_job(job.id, &e.to_string()),
+                Err(_) => api.fail_job(job.id, "The job stopped unexpectedly"),
+            }
+        }
+
+        let job = match api.next_job() {
+            Some(job) => job,
+            None => return false,
+        };
+        let key = match key {
+            _ if keys_locked => {
+                api.fail_job(job.id, "The keys are locked; unlock them in CRUSTy");
+                return false;
+            },
+            Some(key) => key.clone(),
+            None => {
+                api.fail_job(job.id, "No key is selected in CRUSTy");
+                return false;
+            },
+        };
+
+        let progress = Arc::new(Mutex::new(vec![0.0; job.files.len()]));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || run_job(job.operation, &job.files, &job.output_dir, job.reason.as_deref(), &key, job_progress));
+        self.job = Some(RunningJob { id: job.id, progress, handle });
+        self.events.status(tr_args("status-control-api-job", &[("id", job.id.to_string().into())]));
+        true
+    }
+}
+
+/// Encrypt or decrypt a job's files, logging each result
+fn run_job(
+    operation: JobOperation,
+    files: &[PathBuf],
+    output_dir: &Path,
+    reason: Option<&str>,
+    key: &EncryptionKey,
+    progress: Arc<Mutex<Vec<f32>>>,
+) -> Result<Vec<String>, EncryptionError> {
+    let logger = get_logger();
+    let key_fingerprint = key.fingerprint();
+    let paths: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    let progress_callback = move |index: usize, p: f32| {
+        if let Some(file_progress) = progress.lock().unwrap().get_mut(index) {
+            *file_progress = p;
+        }
+    };
+
+    let backend = BackendFactory::create_local();
+    let (operation_name, results) = match operation {
+        JobOperation::Encrypt => ("API Encrypt", backend.encrypt_files(&paths, output_dir, key, false, progress_callback)),
+        JobOperation::Decrypt => {
+            // Recorded (with the reason, if given) for auditing, as in the window
+            if let Some(logger) = &logger {
+                for file in files {
+                    let mut entry = LogEntry::new("Decrypt Request", &file.to_string_lossy(), true, "Decryption requested through the control API");
+                    if let Some(reason) = reason.filter(|reason| !reason.trim().is_empty()) {
+                        entry = entry.with_reason(reason.trim());
+                    }
+                    logger.log(entry).ok();
+                }
+            }
+            ("API Decrypt", backend.decrypt_files(&paths, output_dir, key, progress_callback))
+        },
+    };
+
+    if let Some(logger) = &logger {
+        match &results {
+            Ok(results) => {
+                for (file, result) in files.iter().zip(results) {
+                    // The file written, and the folder named by errors writing it
+                    let output = match operation {
+                        JobOperation::Encrypt => file_names::encrypted_name(file),
+                        JobOperation::Decrypt => file_names::restored_name(file, key).map(|output_name| output_name.name),
+                    }.map(|name| output_dir.join(name));
+                    let paths: Vec<&Path> = [Some(file.as_path()), output.as_deref(), Some(output_dir)].into_iter().flatten().collect();
+                    let message = logged_result(logger, result, &paths);
+                    if result.contains("Successfully") {
+                        logger.log_key_success(operation_name, &file.to_string_lossy(), &message, &key_fingerprint).ok();
+                    } else {
+                        logger.log_key_error(operation_name, &file.to_string_lossy(), &message, &key_fingerprint).ok();
+                    }
+                }
+            },
+            Err(e) => {
+                logger.log_key_error(operation_name, "multiple files", &logged_error(logger, e), &key_fingerprint).ok();
+            },
+        }
+    }
+    results
+}
+
+impl Default for ControlApiService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::{Ipv4Addr, TcpStream};
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    /// Send a request with the service's token and return the response body
+    fn send(service: &ControlApiService, method: &str, path: &str, body: &str) -> serde_json::Value {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, service.port().unwrap())).unwrap();
+        write!(stream, "{} {} HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+            method, path, service.token().unwrap(), body.len(), body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_api_job_encrypts_and_decrypts() {
+        let dir = tempdir().unwrap();
+        let encrypted_dir = dir.path().join("encrypted");
+        let decrypted_dir = dir.path().join("decrypted");
+        fs::create_dir(&encrypted_dir).unwrap();
+        fs::create_dir(&decrypted_dir).unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, b"meeting notes").unwrap();
+        let key = EncryptionKey::generate();
+
+        let mut service = ControlApiService::new();
+        service.start(0, dir.path());
+        assert!(!service.poll(Some(&key), false));
+
+        let body = serde_json::json!({ "operation": "encrypt", "files": [file], "output_dir": encrypted_dir }).to_string();
+        let job = send(&service, "POST", "/v1/jobs", &body);
+        while service.poll(Some(&key), false) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let job = send(&service, "GET", &format!("/v1/jobs/{}", job["id"]), "");
+        assert_eq!(job["state"], "finished", "{}", job);
+
+        let encrypted = encrypted_dir.join("notes.txt.encrypted");
+        let body = serde_json::json!({ "operation": "decrypt", "files": [encrypted], "output_dir": decrypted_dir }).to_string();
+        send(&service, "POST", "/v1/jobs", &body);
+        while service.poll(Some(&key), false) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(fs::read(decrypted_dir.join("notes.txt")).unwrap(), b"meeting notes");
+
+        // Jobs fail while the keys are locked
+        send(&service, "POST", "/v1/jobs", &body);
+        assert!(!service.poll(Some(&key), true));
+        let jobs = send(&service, "GET", "/v1/jobs", "");
+        assert_eq!(jobs["jobs"][2]["state"], "failed");
+    }
+}
@@ -0,0 +1,616 @@
+/// Folder service.
+///
+/// This module provides functionality for:
+/// - Keeping the list of secured folders and the status of each
+/// - Locking and unlocking a secured folder in the background, with progress
+/// - Locking or unlocking several folders one after the other
+/// - Binding a saved key to a folder
+/// - Reporting files that failed, so a partly locked folder can be recovered
+/// - Checking the integrity of the locked folders in the background, and keeping the
+///   folders that failed as alerts until they are dismissed
+///
+/// The list is shared with other running copies of CRUSTy: each change is made to the
+/// list as saved, under its lock, so folders added or removed by another copy are kept.
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::encryption::EncryptionKey;
+use crate::i18n::{tr, tr_args};
+use crate::secured_folders::{self, FolderReport, FolderStatus, IntegrityReport, SecuredFolder, SecuredFolders};
+use crate::services::{log_error, log_success, EventQueue, ServiceEvent};
+use crate::shared_files;
+
+/// Lock or unlock running in the background
+struct FolderJob {
+    /// The folder
+    path: PathBuf,
+    /// Locking (true) or unlocking (false)
+    lock: bool,
+    /// Fingerprint of the key, recorded once the folder is locked
+    key_fingerprint: String,
+    /// Files done and files to do
+    progress: Arc<Mutex<(usize, usize)>>,
+    handle: JoinHandle<io::Result<FolderReport>>,
+}
+
+/// Secured folders and the lock or unlock in progress
+pub struct FolderService {
+    folders: SecuredFolders,
+    folders_path: Option<PathBuf>,
+    statuses: Vec<FolderStatus>,
+    job: Option<FolderJob>,
+    /// Folders to lock (true) or unlock (false) once the running job finishes, with
+    /// their keys
+    queue: VecDeque<(PathBuf, EncryptionKey, bool)>,
+    integrity_job: Option<JoinHandle<Vec<IntegrityReport>>>,
+    /// Folders that failed their last integrity check, until dismissed
+    integrity_alerts: Vec<IntegrityReport>,
+    last_integrity_check: Option<Instant>,
+    events: EventQueue,
+}
+
+impl FolderService {
+    /// Create a service with no secured folders
+    pub fn new() -> Self {
+        FolderService {
+            folders: SecuredFolders::default(),
+            folders_path: None,
+            statuses: Vec::new(),
+            job: None,
+            queue: VecDeque::new(),
+            integrity_job: None,
+            integrity_alerts: Vec::new(),
+            last_integrity_check: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Load the secured folders from the specified file, where changes are also saved
+    pub fn load_folders(&mut self, path: PathBuf) {
+        match SecuredFolders::load_from(&path) {
+            Ok(folders) => self.folders = folders,
+            Err(e) => self.events.error(tr_args("error-secured-folders-load", &[("error", e.to_string().into())])),
+        }
+        self.folders_path = Some(path);
+        self.refresh();
+    }
+
+    /// Get the secured folders
+    pub fn folders(&self) -> &[SecuredFolder] {
+        &self.folders.folders
+    }
+
+    /// Get the status of a secured folder (as of the last refresh)
+    pub fn status(&self, index: usize) -> FolderStatus {
+        self.statuses.get(index).cloned().unwrap_or(FolderStatus::Missing)
+    }
+
+    /// Check the status of every secured folder again
+    pub fn refresh(&mut self) {
+        self.statuses = self.folders.folders.iter()
+            .map(|folder| secured_folders::folder_status(&folder.path))
+            .collect();
+    }
+
+    /// Add a secured folder
+    pub fn add_folder(&mut self, path: PathBuf) {
+        self.update(|folders| {
+            if folders.folders.iter().any(|folder| folder.path == path) {
+                return Err(tr_args("error-secured-folder-exists", &[("folder", path.display().to_string().into())]));
+            }
+            if folders.folders.iter().any(|folder| path.starts_with(&folder.path) || folder.path.starts_with(&path)) {
+                return Err(tr_args("error-secured-folder-nested", &[("folder", path.display().to_string().into())]));
+            }
+
+            folders.folders.push(SecuredFolder::new(path));
+            Ok(())
+        });
+    }
+
+    /// Remove a secured folder from the list (its files are not changed)
+    pub fn remove_folder(&mut self, index: usize) {
+        let path = match self.folders.folders.get(index) {
+            Some(folder) => folder.path.clone(),
+            None => return,
+        };
+        if self.job.as_ref().is_some_and(|job| job.path == path) {
+            self.events.error(tr("error-secured-folder-busy"));
+            return;
+        }
+        self.queue.retain(|(queued, _, _)| *queued != path);
+
+        let removed = self.update(|folders| {
+            folders.folders.retain(|folder| folder.path != path);
+            Ok(())
+        });
+        if removed {
+            self.events.status(tr_args("status-secured-folder-removed", &[("folder", path.display().to_string().into())]));
+        }
+    }
+
+    /// Lock a secured folder in the background: encrypt every file in place and shred
+    /// the originals
+    pub fn lock(&mut self, index: usize, key: EncryptionKey) {
+        self.start(index, key, true);
+    }
+
+    /// Unlock a secured folder in the background: decrypt every file in place
+    pub fn unlock(&mut self, index: usize, key: EncryptionKey) {
+        self.start(index, key, false);
+    }
+
+    /// Lock or unlock several secured folders, one after the other
+    ///
+    /// # Arguments
+    /// * `folders` - Index of each folder and the key to use for it
+    /// * `lock` - Lock (true) or unlock (false) the folders
+    pub fn queue(&mut self, folders: Vec<(usize, EncryptionKey)>, lock: bool) {
+        for (index, key) in folders {
+            let Some(folder) = self.folders.folders.get(index) else { continue };
+            let busy = self.job.as_ref().is_some_and(|job| job.path == folder.path)
+                || self.queue.iter().any(|(queued, _, _)| *queued == folder.path);
+            if !busy {
+                self.queue.push_back((folder.path.clone(), key, lock));
+            }
+        }
+        self.start_queued();
+    }
+
+    /// Check whether a folder is waiting to be locked or unlocked
+    pub fn is_queued(&self, index: usize) -> bool {
+        self.folders.folders.get(index)
+            .is_some_and(|folder| self.queue.iter().any(|(queued, _, _)| *queued == folder.path))
+    }
+
+    /// Start the next queued folder, if no folder is being locked or unlocked
+    fn start_queued(&mut self) {
+        while self.job.is_none() {
+            let Some((path, key, lock)) = self.queue.pop_front() else { return };
+            // Folders removed meanwhile are skipped
+            if let Some(index) = self.folders.folders.iter().position(|folder| folder.path == path) {
+                self.start(index, key, lock);
+            }
+        }
+    }
+
+    /// Bind a saved key to a secured folder, so it is always locked with that key
+    ///
+    /// # Arguments
+    /// * `index` - The folder
+    /// * `fingerprint` - Fingerprint of the key (None to lock it with the current key)
+    pub fn bind_key(&mut self, index: usize, fingerprint: Option<String>) {
+        let path = match self.folders.folders.get(index) {
+            Some(folder) => folder.path.clone(),
+            None => return,
+        };
+        let bound = fingerprint.is_some();
+        let changed = self.update(|folders| {
+            if let Some(folder) = folders.folders.iter_mut().find(|folder| folder.path == path) {
+                folder.bound_key = fingerprint;
+            }
+            Ok(())
+        });
+        if changed {
+            self.events.status(tr_args(
+                if bound { "status-secured-folder-bound" } else { "status-secured-folder-unbound" },
+                &[("folder", path.display().to_string().into())],
+            ));
+        }
+    }
+
+    fn start(&mut self, index: usize, key: EncryptionKey, lock: bool) {
+        let path = match self.folders.folders.get(index) {
+            Some(folder) => folder.path.clone(),
+            None => return,
+        };
+        if self.job.is_some() {
+            self.events.error(tr("error-secured-folder-other-busy"));
+            return;
+        }
+
+        let progress = Arc::new(Mutex::new((0, 0)));
+        let job_progress = progress.clone();
+        let job_path = path.clone();
+        let key_fingerprint = key.fingerprint();
+        let handle = thread::spawn(move || {
+            let report_progress = |done, total| *job_progress.lock().unwrap() = (done, total);
+            if lock {
+                secured_folders::lock_folder(&path, &key, report_progress)
+            } else {
+                secured_folders::unlock_folder(&path, &key, report_progress)
+            }
+        });
+
+        self.job = Some(FolderJob { path: job_path, lock, key_fingerprint, progress, handle });
+        self.events.status(tr(if lock { "status-secured-folder-locking" } else { "status-secured-folder-unlocking" }));
+    }
+
+    /// Get the folder being locked or unlocked, and the fraction of files done
+    pub fn job_progress(&self) -> Option<(usize, f32)> {
+        let job = self.job.as_ref()?;
+        let index = self.folders.folders.iter().position(|folder| folder.path == job.path)?;
+        let (done, total) = *job.progress.lock().unwrap();
+        let fraction = if total == 0 { 0.0 } else { done as f32 / total as f32 };
+        Some((index, fraction))
+    }
+
+    /// Check whether the lock or unlock has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether a lock or unlock is still running
+    pub fn poll(&mut self) -> bool {
+        let checking = self.poll_integrity();
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return checking,
+        }
+
+        let job = self.job.take().unwrap();
+        let result = job.handle.join()
+            .unwrap_or_else(|_| Err(io::Error::other("The operation stopped unexpectedly")));
+        if self.report(&job.path, job.lock, result) {
+            self.record_lock(&job.path, job.lock.then_some(job.key_fingerprint));
+        }
+        self.refresh();
+        self.start_queued();
+        checking || self.job.is_some()
+    }
+
+    /// Record the key and the encrypted files of a folder that was locked, so its
+    /// integrity can be checked, or forget them once it is unlocked
+    fn record_lock(&mut self, path: &Path, key_fingerprint: Option<String>) {
+        let locked_files = match key_fingerprint {
+            Some(_) => secured_folders::encrypted_files(path).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        self.update(|folders| {
+            if let Some(folder) = folders.folders.iter_mut().find(|folder| folder.path == path) {
+                folder.key_fingerprint = key_fingerprint;
+                folder.locked_files = locked_files;
+            }
+            Ok(())
+        });
+    }
+
+    /// Raise events and log the result of a lock or unlock
+    ///
+    /// # Returns
+    /// * `bool` - Whether every file of the folder was done
+    fn report(&mut self, path: &Path, lock: bool, result: io::Result<FolderReport>) -> bool {
+        let (operation, done) = if lock { ("Lock Folder", "Locked") } else { ("Unlock Folder", "Unlocked") };
+        let folder = path.to_string_lossy();
+
+        let report = match result {
+            Ok(report) => report,
+            Err(e) => {
+                log_error(operation, &folder, &e.to_string());
+                self.events.error(tr_args("error-read-path", &[("path", path.display().to_string().into()), ("error", e.to_string().into())]));
+                return false;
+            },
+        };
+
+        for (file, error) in &report.failures {
+            log_error(operation, &file.to_string_lossy(), error);
+        }
+
+        if report.failures.is_empty() {
+            log_success(operation, &folder, &format!("{} {} file(s)", done, report.processed));
+            self.events.status(tr_args(
+                if lock { "status-secured-folder-locked" } else { "status-secured-folder-unlocked" },
+                &[("folder", path.display().to_string().into()), ("count", report.processed.into())],
+            ));
+            true
+        } else {
+            let (file, error) = &report.failures[0];
+            let message = tr_args(
+                if lock { "error-secured-folder-lock-failed" } else { "error-secured-folder-unlock-failed" },
+                &[
+                    ("count", report.processed.into()),
+                    ("folder", path.display().to_string().into()),
+                    ("failed", report.failures.len().into()),
+                    ("file", file.display().to_string().into()),
+                    ("error", error.as_str().into()),
+                ],
+            );
+            // Each failed file was logged above
+            log_error(operation, &folder, &format!("{} {} file(s), but {} failed", done, report.processed, report.failures.len()));
+            self.events.error(message);
+            false
+        }
+    }
+
+    /// Check the integrity of the locked secured folders in the background
+    ///
+    /// Only folders locked by CRUSTy (so their key and files were recorded) are checked,
+    /// each with the saved key it was locked with; folders whose key is not among the
+    /// keys, and partly locked folders, are skipped.
+    ///
+    /// # Arguments
+    /// * `keys` - The saved keys
+    pub fn check_integrity(&mut self, keys: &[EncryptionKey]) {
+        if self.integrity_job.is_some() {
+            return;
+        }
+        // Another running copy of CRUSTy may have locked or unlocked folders meanwhile
+        if let Some(path) = self.folders_path.clone() {
+            if let Ok(folders) = SecuredFolders::load_from(&path) {
+                self.folders = folders;
+            }
+        }
+        self.refresh();
+        self.last_integrity_check = Some(Instant::now());
+
+        let busy = self.job.as_ref().map(|job| job.path.clone());
+        let checks: Vec<(SecuredFolder, EncryptionKey)> = self.folders.folders.iter()
+            .zip(&self.statuses)
+            .filter(|(folder, status)| matches!(status, FolderStatus::Locked | FolderStatus::Missing)
+                && busy.as_ref() != Some(&folder.path))
+            .filter_map(|(folder, _)| {
+                let fingerprint = folder.key_fingerprint.as_ref()?;
+                let key = keys.iter().find(|key| &key.fingerprint() == fingerprint)?;
+                Some((folder.clone(), key.clone()))
+            })
+            .collect();
+        if checks.is_empty() {
+            self.events.status(tr("status-integrity-nothing"));
+            return;
+        }
+
+        self.events.status(tr_args("status-integrity-started", &[("count", checks.len().into())]));
+        self.integrity_job = Some(thread::spawn(move || {
+            checks.iter().map(|(folder, key)| secured_folders::check_integrity(folder, key)).collect()
+        }));
+    }
+
+    /// Get how long until the next integrity check is due
+    ///
+    /// # Arguments
+    /// * `interval` - Time between checks (zero never checks)
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - Time until the check is due (zero if it is), or None if
+    ///   checks are off or one is running
+    pub fn time_to_integrity_check(&self, interval: Duration) -> Option<Duration> {
+        if interval.is_zero() || self.integrity_job.is_some() {
+            return None;
+        }
+        Some(match self.last_integrity_check {
+            Some(last) => interval.saturating_sub(last.elapsed()),
+            None => Duration::ZERO,
+        })
+    }
+
+    /// Check whether an integrity check is running
+    pub fn is_checking_integrity(&self) -> bool {
+        self.integrity_job.is_some()
+    }
+
+    /// Get the folders that failed their last integrity check
+    pub fn integrity_alerts(&self) -> &[IntegrityReport] {
+        &self.integrity_alerts
+    }
+
+    /// Dismiss the integrity alerts
+    ///
+    /// Missing files are taken as removed on purpose and no longer looked for; files
+    /// that failed verification are reported again by the next check.
+    pub fn dismiss_integrity_alerts(&mut self) {
+        let alerts = std::mem::take(&mut self.integrity_alerts);
+        if alerts.iter().all(|report| report.missing.is_empty()) {
+            return;
+        }
+        self.update(|folders| {
+            for folder in &mut folders.folders {
+                let Some(report) = alerts.iter().find(|report| report.folder == folder.path) else { continue };
+                let path = folder.path.clone();
+                folder.locked_files.retain(|file| !report.missing.contains(&path.join(file)));
+            }
+            Ok(())
+        });
+    }
+
+    /// Check whether the integrity check has finished, and report the result if so
+    fn poll_integrity(&mut self) -> bool {
+        match &self.integrity_job {
+            Some(job) if !job.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let reports = match self.integrity_job.take().unwrap().join() {
+            Ok(reports) => reports,
+            Err(_) => {
+                self.events.error(tr("error-integrity-stopped"));
+                return false;
+            },
+        };
+
+        for report in &reports {
+            for (file, error) in &report.failed {
+                log_error("Integrity Check", &file.to_string_lossy(), error);
+            }
+            for file in &report.missing {
+                log_error("Integrity Check", &file.to_string_lossy(), "Missing");
+            }
+            if report.is_intact() {
+                log_success("Integrity Check", &report.folder.to_string_lossy(), &format!("{} file(s) intact", report.intact));
+            }
+        }
+
+        self.integrity_alerts = reports.into_iter().filter(|report| !report.is_intact()).collect();
+        if self.integrity_alerts.is_empty() {
+            self.events.status(tr("status-integrity-intact"));
+        } else {
+            self.events.error(tr_args("error-integrity-failed", &[("count", self.integrity_alerts.len().into())]));
+        }
+        false
+    }
+
+    /// Change the secured folders and save them to the file they were loaded from
+    ///
+    /// The change is made to the list as saved, under its lock, so folders added or
+    /// removed meanwhile by another running copy of CRUSTy are kept.
+    ///
+    /// # Returns
+    /// * `bool` - Whether the change was made (errors are raised as events)
+    fn update(&mut self, change: impl FnOnce(&mut SecuredFolders) -> Result<(), String>) -> bool {
+        let result = match self.folders_path.clone() {
+            Some(path) => Self::update_file(&path, change).map(|folders| self.folders = folders),
+            None => change(&mut self.folders),
+        };
+
+        self.refresh();
+        match result {
+            Ok(()) => true,
+            Err(e) => {
+                self.events.error(e);
+                false
+            },
+        }
+    }
+
+    /// Load, change, and save the secured folder list while holding its lock
+    fn update_file(path: &Path, change: impl FnOnce(&mut SecuredFolders) -> Result<(), String>) -> Result<SecuredFolders, String> {
+        let _lock = shared_files::lock(path)
+            .map_err(|e| tr_args("error-secured-folders-lock", &[("error", e.to_string().into())]))?;
+        let mut folders = SecuredFolders::load_from(path)
+            .map_err(|e| tr_args("error-secured-folders-load", &[("error", e.to_string().into())]))?;
+        change(&mut folders)?;
+        folders.save_to(path)
+            .map_err(|e| tr_args("error-secured-folders-save", &[("error", e.to_string().into())]))?;
+        Ok(folders)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn wait(folders: &mut FolderService) {
+        while folders.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_folder_lock_toggle() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("docs");
+        std::fs::create_dir(&folder).unwrap();
+        std::fs::write(folder.join("a.txt"), b"A").unwrap();
+
+        let mut folders = FolderService::new();
+        folders.load_folders(dir.path().join("secured_folders.json"));
+        folders.add_folder(folder.clone());
+        folders.add_folder(folder.join("sub"));
+        assert!(matches!(folders.take_events().as_slice(), [ServiceEvent::Error(_)]));
+        assert_eq!(folders.status(0), FolderStatus::Unlocked);
+
+        let key = EncryptionKey::generate();
+        folders.lock(0, key.clone());
+        assert_eq!(folders.job_progress().map(|(index, _)| index), Some(0));
+        wait(&mut folders);
+        assert_eq!(folders.status(0), FolderStatus::Locked);
+        assert_eq!(folders.take_events().last(), Some(&ServiceEvent::Status(format!("Locked {} (1 file)", folder.display()))));
+
+        // The key and files are recorded, so the folder's integrity can be checked
+        assert_eq!(folders.folders()[0].key_fingerprint, Some(key.fingerprint()));
+        assert_eq!(folders.time_to_integrity_check(Duration::from_secs(3600)), Some(Duration::ZERO));
+        folders.check_integrity(&[EncryptionKey::generate(), key.clone()]);
+        wait(&mut folders);
+        assert!(folders.integrity_alerts().is_empty());
+        assert!(folders.time_to_integrity_check(Duration::from_secs(3600)).unwrap() > Duration::ZERO);
+
+        // A wrong key leaves the folder locked and says how to finish
+        folders.unlock(0, EncryptionKey::generate());
+        wait(&mut folders);
+        assert_eq!(folders.status(0), FolderStatus::Locked);
+        assert!(matches!(folders.take_events().last(), Some(ServiceEvent::Error(message)) if message.contains("unlock the folder again")));
+
+        folders.unlock(0, key);
+        wait(&mut folders);
+        assert_eq!(folders.status(0), FolderStatus::Unlocked);
+        assert!(folders.folders()[0].locked_files.is_empty());
+
+        // The list is saved
+        let mut reloaded = FolderService::new();
+        reloaded.load_folders(dir.path().join("secured_folders.json"));
+        assert_eq!(reloaded.folders().len(), 1);
+        reloaded.remove_folder(0);
+        assert!(reloaded.folders().is_empty());
+        assert!(folder.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_lock_several_folders() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = ["docs", "photos"].iter().map(|name| dir.path().join(name)).collect();
+        for path in &paths {
+            std::fs::create_dir(path).unwrap();
+            std::fs::write(path.join("a.txt"), b"A").unwrap();
+        }
+
+        let mut folders = FolderService::new();
+        folders.load_folders(dir.path().join("secured_folders.json"));
+        for path in &paths {
+            folders.add_folder(path.clone());
+        }
+
+        // A bound key is saved with the folder
+        let (docs_key, photos_key) = (EncryptionKey::generate(), EncryptionKey::generate());
+        folders.bind_key(1, Some(photos_key.fingerprint()));
+        assert_eq!(folders.folders()[1].bound_key, Some(photos_key.fingerprint()));
+        assert_eq!(folders.folders()[1].unlock_key(), Some(photos_key.fingerprint().as_str()));
+
+        // The second folder waits for the first
+        folders.queue(vec![(0, docs_key.clone()), (1, photos_key.clone())], true);
+        assert_eq!(folders.job_progress().map(|(index, _)| index), Some(0));
+        assert!(folders.is_queued(1));
+        wait(&mut folders);
+        assert_eq!(folders.status(0), FolderStatus::Locked);
+        assert_eq!(folders.status(1), FolderStatus::Locked);
+        assert_eq!(folders.folders()[1].unlock_key(), Some(photos_key.fingerprint().as_str()));
+
+        folders.queue(vec![(0, docs_key), (1, photos_key)], false);
+        wait(&mut folders);
+        assert_eq!(folders.status(0), FolderStatus::Unlocked);
+        assert_eq!(folders.status(1), FolderStatus::Unlocked);
+
+        let mut reloaded = FolderService::new();
+        reloaded.load_folders(dir.path().join("secured_folders.json"));
+        assert!(reloaded.folders()[1].bound_key.is_some());
+        reloaded.bind_key(1, None);
+        assert_eq!(reloaded.folders()[1].bound_key, None);
+    }
+
+    #[test]
+    fn test_changes_from_another_copy_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secured_folders.json");
+
+        // Two running copies of CRUSTy loaded the same (empty) list
+        let mut first = FolderService::new();
+        first.load_folders(path.clone());
+        let mut second = FolderService::new();
+        second.load_folders(path.clone());
+
+        first.add_folder(dir.path().join("a"));
+        second.add_folder(dir.path().join("b"));
+        assert_eq!(second.folders().len(), 2);
+
+        first.remove_folder(0);
+        assert_eq!(first.folders(), &[SecuredFolder::new(dir.path().join("b"))]);
+        assert_eq!(SecuredFolders::load_from(&path).unwrap().folders, first.folders());
+    }
+}
@@ -0,0 +1,258 @@
+/// Key agreement service.
+///
+/// This module provides functionality for:
+/// - Waiting for a partner's computer, or connecting to it, in the background
+/// - Keeping the agreed key until the users have compared the codes
+/// - Accepting the key (and saving a new partner), or rejecting it
+///
+/// A key agreed with a saved partner needs no comparison: only the partner's machine
+/// could have derived it. The agreed key is wiped when the keys are locked.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use chrono::Local;
+use x25519_dalek::StaticSecret;
+
+use crate::encryption::EncryptionKey;
+use crate::i18n::{tr, tr_args};
+use crate::key_agreement::{self, Agreement, AgreementError, Partner};
+use crate::keystore::KeyProvenance;
+use crate::services::{log_error, log_key_success, EventQueue, ServiceEvent};
+
+/// Agreement running in the background
+struct AgreementJob {
+    /// Set to stop waiting for the partner
+    cancel: Arc<AtomicBool>,
+    /// Whether this computer is waiting for the partner (not connecting to them)
+    listening: bool,
+    handle: JoinHandle<Result<Agreement, AgreementError>>,
+}
+
+/// Key accepted by the users, to be saved
+pub struct AcceptedKey {
+    /// Name to save the key under
+    pub name: String,
+    pub key: EncryptionKey,
+    pub provenance: KeyProvenance,
+    /// The partner, when the codes were compared (None for a saved partner)
+    pub new_partner: Option<Partner>,
+}
+
+/// Partner key agreement and the agreed key awaiting comparison
+pub struct KeyAgreementService {
+    /// Port to wait on
+    pub port: u16,
+    /// Address of the partner's computer to connect to
+    pub address: String,
+    /// Name for a partner not saved yet
+    pub partner_name: String,
+    job: Option<AgreementJob>,
+    agreement: Option<Agreement>,
+    events: EventQueue,
+}
+
+impl KeyAgreementService {
+    /// Create a service with no agreement
+    pub fn new() -> Self {
+        KeyAgreementService {
+            port: key_agreement::DEFAULT_PORT,
+            address: String::new(),
+            partner_name: String::new(),
+            job: None,
+            agreement: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Wait in the background for the partner's computer to connect
+    pub fn start_listening(&mut self, identity: StaticSecret) {
+        if self.job.is_some() {
+            self.events.error(tr("error-agreement-running"));
+            return;
+        }
+
+        let port = self.port;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let job_cancel = cancel.clone();
+        let handle = thread::spawn(move || key_agreement::listen(port, &identity, &job_cancel));
+
+        self.agreement = None;
+        self.job = Some(AgreementJob { cancel, listening: true, handle });
+        self.events.status(tr_args("status-agreement-waiting", &[("port", port.to_string().into())]));
+    }
+
+    /// Connect in the background to the partner's waiting computer
+    pub fn start_connecting(&mut self, identity: StaticSecret) {
+        if self.address.trim().is_empty() {
+            self.events.error(tr("error-agreement-address"));
+            return;
+        }
+        if self.job.is_some() {
+            self.events.error(tr("error-agreement-running"));
+            return;
+        }
+
+        let address = self.address.trim().to_string();
+        let handle = thread::spawn(move || key_agreement::connect(&address, &identity));
+
+        self.agreement = None;
+        self.job = Some(AgreementJob { cancel: Arc::new(AtomicBool::new(false)), listening: false, handle });
+        self.events.status(tr_args("status-agreement-connecting", &[("address", self.address.trim().into())]));
+    }
+
+    /// Check whether an agreement is running, and whether it is waiting for the partner
+    pub fn running(&self) -> Option<bool> {
+        self.job.as_ref().map(|job| job.listening)
+    }
+
+    /// Stop waiting for the partner (a connection already made finishes first)
+    pub fn cancel(&mut self) {
+        if let Some(job) = &self.job {
+            job.cancel.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Check whether the agreement has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether an agreement is still running
+    pub fn poll(&mut self) -> bool {
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let job = self.job.take().unwrap();
+        let result = job.handle.join()
+            .unwrap_or_else(|_| Err(AgreementError::Protocol("the agreement stopped unexpectedly".to_string())));
+        match result {
+            Ok(agreement) => {
+                self.events.status(tr_args("status-agreement-agreed", &[("address", agreement.partner_address.as_str().into())]));
+                self.agreement = Some(agreement);
+            },
+            Err(AgreementError::Cancelled) => self.events.status(tr("status-agreement-cancelled")),
+            Err(e) => {
+                log_error("Key Agreement", self.address.trim(), &e.to_string());
+                self.events.error(tr_args("error-agreement-failed", &[("error", e.to_string().into())]));
+            },
+        }
+        false
+    }
+
+    /// Get the agreed key awaiting comparison
+    pub fn agreement(&self) -> Option<&Agreement> {
+        self.agreement.as_ref()
+    }
+
+    /// Accept the agreed key, once the codes matched or the partner is saved
+    ///
+    /// # Arguments
+    /// * `partner` - The saved partner with the agreement's machine identity, if any
+    ///
+    /// # Returns
+    /// * `Option<AcceptedKey>` - The key to save, or None if a new partner has no name
+    pub fn accept(&mut self, partner: Option<&Partner>) -> Option<AcceptedKey> {
+        let name = match partner {
+            Some(partner) => partner.name.clone(),
+            None if self.partner_name.trim().is_empty() => {
+                self.events.error(tr("error-agreement-partner-name"));
+                return None;
+            },
+            None => self.partner_name.trim().to_string(),
+        };
+        let agreement = self.agreement.take()?;
+
+        let now = Local::now();
+        let new_partner = partner.is_none().then(|| Partner {
+            name: name.clone(),
+            identity: agreement.partner_identity.clone(),
+            verified_at: now.format("%Y-%m-%d %H:%M").to_string(),
+        });
+        let provenance = KeyProvenance::new("Agreed with a partner's computer")
+            .with_origin(&format!("{} ({})", name, agreement.partner_address));
+
+        let message = match &new_partner {
+            Some(_) => format!("Key agreed with {} after comparing code {}", name, agreement.sas),
+            None => format!("Key agreed with saved partner {}", name),
+        };
+        log_key_success("Key Agreement", &agreement.partner_address, &message, &agreement.key.fingerprint());
+        self.events.status(match &new_partner {
+            Some(_) => tr_args("status-agreement-accepted", &[("name", name.as_str().into()), ("code", agreement.sas.to_string().into())]),
+            None => tr_args("status-agreement-accepted-saved", &[("name", name.as_str().into())]),
+        });
+        self.partner_name.clear();
+
+        Some(AcceptedKey {
+            name: format!("{} {}", name, now.format("%Y-%m-%d %H:%M")),
+            key: agreement.key,
+            provenance,
+            new_partner,
+        })
+    }
+
+    /// Reject the agreed key because the codes differ
+    pub fn reject(&mut self) {
+        if let Some(agreement) = self.agreement.take() {
+            log_error("Key Agreement", &agreement.partner_address, "The codes did not match; the key was discarded");
+            self.events.error(tr("error-agreement-rejected"));
+        }
+    }
+
+    /// Stop waiting for the partner and wipe the agreed key (when the keys are locked)
+    pub fn clear_secrets(&mut self) {
+        self.cancel();
+        self.agreement = None;
+    }
+}
+
+impl Default for KeyAgreementService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    #[test]
+    fn test_agree_and_save_partner() {
+        let mut waiting = KeyAgreementService::new();
+        let mut connecting = KeyAgreementService::new();
+        waiting.port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        connecting.address = format!("127.0.0.1:{}", waiting.port);
+
+        waiting.start_listening(StaticSecret::random_from_rng(rand::rngs::OsRng));
+        thread::sleep(Duration::from_millis(200));
+        connecting.start_connecting(StaticSecret::random_from_rng(rand::rngs::OsRng));
+        while connecting.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        while waiting.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(waiting.agreement().unwrap().sas, connecting.agreement().unwrap().sas);
+
+        // A new partner needs a name; the codes matched, so the partner is saved
+        assert!(connecting.accept(None).is_none());
+        connecting.partner_name = "Branch Office".to_string();
+        let accepted = connecting.accept(None).unwrap();
+        let partner = accepted.new_partner.unwrap();
+        assert_eq!(partner.name, "Branch Office");
+        assert!(accepted.name.starts_with("Branch Office "));
+
+        // The other side saw different codes, and discards its key
+        waiting.reject();
+        assert!(waiting.agreement().is_none());
+        assert!(matches!(waiting.take_events().last(), Some(ServiceEvent::Error(_))));
+    }
+}
@@ -0,0 +1,259 @@
+/// Key rotation service.
+///
+/// This module provides functionality for:
+/// - Keeping the choices made in the key rotation wizard: the files to re-encrypt, and
+///   the old and new keys
+/// - Re-encrypting the files in the background, and logging each file
+/// - The report of the last rotation
+///
+/// The keys are chosen by their place in the saved keys; the app looks them up (and
+/// generates the new key if asked) when the rotation starts.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::encryption::EncryptionKey;
+use crate::i18n::{tr, tr_args};
+use crate::key_rotation::{self, RotationOutcome, RotationReport};
+use crate::secured_folders;
+use crate::services::{log_error, log_key_success, EventQueue, ServiceEvent};
+
+/// Rotation running in the background
+struct RotationJob {
+    /// Fraction of the files done
+    progress: Arc<Mutex<f32>>,
+    handle: JoinHandle<RotationReport>,
+}
+
+/// Choices of the key rotation wizard, and the rotation in progress
+pub struct KeyRotationService {
+    files: Vec<PathBuf>,
+    /// Saved key the files are encrypted with
+    pub old_key: Option<usize>,
+    /// Saved key to re-encrypt them with (None to generate a new key)
+    pub new_key: Option<usize>,
+    /// Name of the key generated for the rotation
+    pub new_key_name: String,
+    job: Option<RotationJob>,
+    report: Option<RotationReport>,
+    events: EventQueue,
+}
+
+impl KeyRotationService {
+    /// Create a service with nothing chosen
+    pub fn new() -> Self {
+        KeyRotationService {
+            files: Vec::new(),
+            old_key: None,
+            new_key: None,
+            new_key_name: String::new(),
+            job: None,
+            report: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Get the files to re-encrypt
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Add encrypted files and archives to re-encrypt (other files are skipped)
+    pub fn add_files(&mut self, files: Vec<PathBuf>) {
+        let count = files.len();
+        let mut added = 0;
+        for file in files {
+            if key_rotation::is_rotatable_name(&file) && !self.files.contains(&file) {
+                self.files.push(file);
+                added += 1;
+            }
+        }
+        if added < count {
+            self.events.status(tr_args("status-rotation-added", &[("count", added.into())]));
+        }
+    }
+
+    /// Add the encrypted files and archives in a folder and its subfolders
+    pub fn add_folder(&mut self, folder: &Path) {
+        match secured_folders::folder_files(folder) {
+            Ok(mut files) => {
+                files.retain(|file| key_rotation::is_rotatable_name(file));
+                if files.is_empty() {
+                    self.events.error(tr_args("error-no-encrypted-files", &[("folder", folder.display().to_string().into())]));
+                    return;
+                }
+                files.sort();
+                let count = files.len();
+                self.add_files(files);
+                self.events.status(tr_args("status-encrypted-files-found", &[("count", count.into()), ("folder", folder.display().to_string().into())]));
+            },
+            Err(e) => self.events.error(tr_args("error-read-path", &[("path", folder.display().to_string().into()), ("error", e.to_string().into())])),
+        }
+    }
+
+    /// Remove a file from the files to re-encrypt
+    pub fn remove_file(&mut self, index: usize) {
+        if index < self.files.len() {
+            self.files.remove(index);
+        }
+    }
+
+    /// Remove every file from the files to re-encrypt
+    pub fn clear_files(&mut self) {
+        self.files.clear();
+    }
+
+    /// Re-encrypt the files in the background
+    ///
+    /// # Arguments
+    /// * `old_key` - The key the files are encrypted with
+    /// * `new_key` - The key to encrypt them with
+    pub fn start(&mut self, old_key: EncryptionKey, new_key: EncryptionKey) {
+        if self.files.is_empty() {
+            self.events.error(tr("error-rotation-files"));
+            return;
+        }
+        if old_key.fingerprint() == new_key.fingerprint() {
+            self.events.error(tr("error-rotation-same-key"));
+            return;
+        }
+        if self.job.is_some() {
+            self.events.error(tr("error-rotation-running"));
+            return;
+        }
+
+        let files = self.files.clone();
+        let progress = Arc::new(Mutex::new(0.0));
+        let job_progress = progress.clone();
+        let handle = thread::spawn(move || {
+            key_rotation::rotate_files(&files, &old_key, &new_key, |fraction| *job_progress.lock().unwrap() = fraction)
+        });
+
+        self.report = None;
+        self.job = Some(RotationJob { progress, handle });
+        self.events.status(tr_args("status-rotation-started", &[("count", self.files.len().into())]));
+    }
+
+    /// Get the fraction done of the rotation in progress
+    pub fn job_progress(&self) -> Option<f32> {
+        self.job.as_ref().map(|job| *job.progress.lock().unwrap())
+    }
+
+    /// Check whether the rotation has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether the rotation is still running
+    pub fn poll(&mut self) -> bool {
+        match &self.job {
+            Some(job) if !job.handle.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        let job = self.job.take().unwrap();
+        let report = match job.handle.join() {
+            Ok(report) => report,
+            Err(_) => {
+                self.events.error(tr("error-rotation-stopped"));
+                return false;
+            },
+        };
+
+        for file in &report.files {
+            let path = file.path.to_string_lossy();
+            match &file.outcome {
+                RotationOutcome::Rotated => log_key_success(
+                    "Rotate Key",
+                    &path,
+                    &format!("Re-encrypted from key {}", report.old_fingerprint),
+                    &report.new_fingerprint,
+                ),
+                RotationOutcome::AlreadyRotated => {},
+                RotationOutcome::Failed(error) => log_error("Rotate Key", &path, error),
+            }
+        }
+
+        let failed = report.count(&RotationOutcome::Failed(String::new()));
+        if failed == 0 {
+            self.events.status(tr_args("status-rotation-done", &[("fingerprint", report.new_fingerprint.as_str().into())]));
+        } else {
+            self.events.error(tr_args("error-rotation-failed", &[("count", failed.into())]));
+        }
+        self.report = Some(report);
+        false
+    }
+
+    /// Get the report of the last rotation
+    pub fn report(&self) -> Option<&RotationReport> {
+        self.report.as_ref()
+    }
+
+    /// Save the report of the last rotation
+    pub fn save_report(&mut self, path: &Path) {
+        let Some(report) = &self.report else { return };
+        match std::fs::write(path, report.to_text()) {
+            Ok(()) => self.events.status(tr_args("status-report-saved", &[("path", path.display().to_string().into())])),
+            Err(e) => self.events.error(tr_args("error-report-save", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Forget the files, keys, and report, to rotate other files
+    pub fn reset(&mut self) {
+        self.files.clear();
+        self.old_key = None;
+        self.new_key = None;
+        self.new_key_name.clear();
+        self.report = None;
+    }
+}
+
+impl Default for KeyRotationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
+    use crate::backend::BackendFactory;
+
+    #[test]
+    fn test_rotate_folder() {
+        let dir = tempdir().unwrap();
+        let old_key = EncryptionKey::generate();
+        let new_key = EncryptionKey::generate();
+        let source = dir.path().join("notes.txt");
+        fs::write(&source, b"meeting notes").unwrap();
+        let folder = dir.path().join("Archive");
+        fs::create_dir(&folder).unwrap();
+        let encrypted = folder.join("notes.txt.encrypted");
+        BackendFactory::create_local().encrypt_file(&source, &encrypted, &old_key, |_| {}).unwrap();
+        fs::write(folder.join("readme.txt"), b"not encrypted").unwrap();
+
+        let mut service = KeyRotationService::new();
+        service.add_folder(&folder);
+        assert_eq!(service.files(), std::slice::from_ref(&encrypted));
+
+        // The same key on both sides is refused
+        service.start(old_key.clone(), old_key.clone());
+        assert!(service.job_progress().is_none());
+
+        service.start(old_key, new_key.clone());
+        while service.poll() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let report = service.report().unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.count(&RotationOutcome::Rotated), 1);
+        assert!(report.to_text().contains(&new_key.fingerprint()));
+    }
+}
@@ -0,0 +1,1190 @@
+/// Key service.
+///
+/// This module provides functionality for:
+/// - Keeping the saved keys (generated, derived from a passphrase, loaded, provisioned,
+///   and deployed) and the current key
+/// - Saving the user's keys to the key store, so they are kept between sessions (keys
+///   distributed by the administrator are loaded again at every start instead)
+/// - Selecting smartcard keys and unlocking them with the card PIN
+/// - Splitting the current key into shares, and tracking share verification and custodians
+/// - Recording where each key came from (its provenance), for audits
+/// - Keeping chosen keys in the OS credential store instead of the key store file
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::NaiveDate;
+
+use crate::custodians::{self, Custodian, CustodianRegistry};
+use crate::deployment;
+use crate::key_backups::{self, BackupGeneration};
+use crate::keystore::{self, KeyMetadata, KeyProvenance, KeyStore, SavedKey};
+use crate::encryption::{self, EncryptionError, EncryptionKey, KdfParams};
+use crate::i18n::{tr, tr_args};
+use crate::policy::get_policy;
+use crate::services::{log_error, log_key_success, log_path, log_success, EventQueue, ServiceEvent};
+use crate::shared_files;
+use crate::smartcard::{self, PinCache, PinStatus, TokenError, TokenWrappedKey};
+use crate::split_key::{self, KeyPurpose, KeyShareManager, ShareFormat, ShareRecord, SplitEncryptionKey, SplitKeyError};
+
+/// Shortest passphrase accepted for a new passphrase key
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+/// Result of unlocking a smartcard key without asking for the PIN
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenUnlock {
+    /// The key was unwrapped with the cached PIN
+    Unlocked,
+    /// The PIN must be entered; the card reported this retry state
+    PinRequired(PinStatus),
+    /// The key cannot be unlocked (an error event was raised)
+    Failed,
+}
+
+/// Keys available to the user and the key used for the next operation
+pub struct KeyService {
+    current_key: Option<EncryptionKey>,
+    saved_keys: Vec<(String, EncryptionKey)>,
+    /// Saved keys distributed by the administrator, which are not kept in the key store
+    distributed_keys: Vec<EncryptionKey>,
+    /// Metadata of the saved keys, by key fingerprint
+    key_metadata: HashMap<String, KeyMetadata>,
+    /// Where the user's keys are kept between sessions (None to keep them in memory only)
+    keystore: Option<KeyStore>,
+    /// Key store file while the keys are locked
+    locked_keystore: Option<PathBuf>,
+    token_keys: Vec<TokenWrappedKey>,
+    selected_token_key: Option<usize>,
+    token_key_unlocked: bool,
+    pin_cache: PinCache,
+    /// Whether keys may be added and removed (not in the restricted mode of the admin policy)
+    management_allowed: bool,
+    /// Key derived from the backup passphrase (None when key backups are off)
+    backup_key: Option<EncryptionKey>,
+    /// Folder of the key backups
+    backup_dir: PathBuf,
+    share_records: Vec<ShareRecord>,
+    /// Custodians of the tracked shares, one per share
+    pub custodians: Vec<Custodian>,
+    /// Share pasted by a custodian for verification
+    pub share_verification_input: String,
+    events: EventQueue,
+}
+
+impl KeyService {
+    /// Create a service with the given smartcard keys and PIN cache
+    pub fn new(token_keys: Vec<TokenWrappedKey>, pin_cache: PinCache) -> Self {
+        KeyService {
+            current_key: None,
+            saved_keys: Vec::new(),
+            distributed_keys: Vec::new(),
+            key_metadata: HashMap::new(),
+            keystore: None,
+            locked_keystore: None,
+            token_keys,
+            selected_token_key: None,
+            token_key_unlocked: false,
+            pin_cache,
+            management_allowed: !get_policy().restricted_mode,
+            backup_key: None,
+            backup_dir: key_backups::default_dir(),
+            share_records: Vec::new(),
+            custodians: Vec::new(),
+            share_verification_input: String::new(),
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Create a service with the smartcard keys and PIN cache window from the admin policy
+    pub fn from_policy() -> Self {
+        let policy = get_policy();
+        Self::new(policy.token_keys.clone(), PinCache::new(Duration::from_secs(policy.pin_cache_secs)))
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Check whether the admin policy allows managing keys (generate, import, export)
+    pub fn management_allowed(&self) -> bool {
+        self.management_allowed
+    }
+
+    /// Get the key used for the next operation
+    pub fn current_key(&self) -> Option<&EncryptionKey> {
+        self.current_key.as_ref()
+    }
+
+    /// Get the saved keys with their names
+    pub fn saved_keys(&self) -> &[(String, EncryptionKey)] {
+        &self.saved_keys
+    }
+
+    /// Get where a saved key came from (None if it was saved before provenance was
+    /// recorded)
+    pub fn provenance(&self, key: &EncryptionKey) -> Option<&KeyProvenance> {
+        self.key_metadata.get(&key.fingerprint())
+            .and_then(|metadata| metadata.provenance.as_ref())
+    }
+
+    /// Get the metadata of a saved key (None for keys without any)
+    pub fn metadata(&self, key: &EncryptionKey) -> Option<&KeyMetadata> {
+        self.key_metadata.get(&key.fingerprint())
+    }
+
+    /// Get the day the current key expired (None if it has not expired)
+    pub fn current_key_expired_on(&self) -> Option<&str> {
+        self.current_key.as_ref()
+            .and_then(|key| self.metadata(key))
+            .filter(|metadata| metadata.is_expired())
+            .and_then(|metadata| metadata.expires_on.as_deref())
+    }
+
+    /// Get the smartcard keys from the admin policy
+    pub fn token_keys(&self) -> &[TokenWrappedKey] {
+        &self.token_keys
+    }
+
+    /// Get the index of the selected smartcard key
+    pub fn selected_token_key(&self) -> Option<usize> {
+        self.selected_token_key
+    }
+
+    /// Check whether a key or a smartcard key is selected
+    pub fn has_key(&self) -> bool {
+        self.current_key.is_some() || self.selected_token_key.is_some()
+    }
+
+    /// Check whether the saved key at the index is the current key
+    pub fn is_current(&self, index: usize) -> bool {
+        match (&self.current_key, self.saved_keys.get(index)) {
+            (Some(current), Some((_, key))) => current.to_base64() == key.to_base64(),
+            _ => false,
+        }
+    }
+
+    /// Get the name of the current key (saved keys first, then the smartcard key)
+    pub fn current_key_name(&self) -> Option<String> {
+        let token_key_name = self.selected_token_key
+            .and_then(|i| self.token_keys.get(i))
+            .map(|token_key| format!("{} (smartcard)", token_key.name));
+
+        match &self.current_key {
+            Some(current) => Some(self.saved_keys.iter()
+                .find(|(_, key)| key.to_base64() == current.to_base64())
+                .map(|(name, _)| name.clone())
+                .or(token_key_name)
+                .unwrap_or_else(|| "Unknown key".to_string())),
+            None => token_key_name,
+        }
+    }
+
+    /// Get the tracked shares of split keys
+    pub fn share_records(&self) -> &[ShareRecord] {
+        &self.share_records
+    }
+
+    /// Save a key with where it came from, and make it the current key
+    ///
+    /// # Returns
+    /// * `bool` - Whether the key was added (not when the admin policy forbids it)
+    pub fn add_key(&mut self, name: &str, key: EncryptionKey, provenance: KeyProvenance) -> bool {
+        if !self.management_allowed() {
+            self.events.error(tr("error-add-disabled"));
+            return false;
+        }
+
+        let fingerprint = key.fingerprint();
+        let logged = KeyProvenance { origin: log_path(Path::new(&provenance.origin)), ..provenance.clone() };
+        log_key_success("Add Key", &provenance.origin, &format!("Key '{}' added: {}", name, logged.describe()), &fingerprint);
+        let metadata = self.key_metadata.entry(fingerprint).or_default();
+        metadata.provenance = Some(provenance);
+        if metadata.created_at.is_none() {
+            metadata.created_now();
+        }
+
+        self.current_key = Some(key.clone());
+        self.selected_token_key = None;
+        self.token_key_unlocked = false;
+        self.saved_keys.push((name.to_string(), key));
+        self.keys_changed();
+        true
+    }
+
+    /// Generate a new key and make it the current key
+    pub fn generate_key(&mut self, name: &str) {
+        if !self.management_allowed() {
+            self.events.error(tr("error-generation-disabled"));
+            return;
+        }
+
+        self.add_key(name, EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        self.events.status(tr_args("status-key-generated", &[("name", name.into())]));
+    }
+
+    /// Derive a new key from a passphrase and make it the current key
+    ///
+    /// A new random salt is used, so the same passphrase gives a different key each time;
+    /// files encrypted with the key record the salt to derive the key again.
+    pub fn derive_key(&mut self, name: &str, passphrase: &str) {
+        if !self.management_allowed() {
+            self.events.error(tr("error-generation-disabled"));
+            return;
+        }
+
+        if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            self.events.error(tr_args("error-passphrase-short", &[("length", MIN_PASSPHRASE_LENGTH.into())]));
+            return;
+        }
+
+        match EncryptionKey::from_passphrase(passphrase, &KdfParams::generate()) {
+            Ok(key) => {
+                self.add_key(name, key, KeyProvenance::new("Derived from a passphrase"));
+                self.events.status(tr_args("status-key-derived", &[("name", name.into())]));
+            },
+            Err(e) => self.events.error(tr_args("error-key-derive", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Derive the key of a file encrypted with a passphrase key, and make it the current key
+    ///
+    /// # Arguments
+    /// * `name` - Name for the derived key
+    /// * `passphrase` - The passphrase the key was derived from
+    /// * `encrypted_file` - A file encrypted with the key (its header holds the salt)
+    pub fn derive_key_for_file(&mut self, name: &str, passphrase: &str, encrypted_file: &Path) {
+        if !self.management_allowed() {
+            self.events.error(tr("error-import-disabled"));
+            return;
+        }
+
+        let params = match encryption::read_kdf_params(encrypted_file) {
+            Ok(Some(params)) => params,
+            Ok(None) => {
+                self.events.error(tr_args("error-not-passphrase-file", &[("path", encrypted_file.display().to_string().into())]));
+                return;
+            },
+            Err(e) => {
+                self.events.error(tr_args("error-read-path", &[("path", encrypted_file.display().to_string().into()), ("error", e.to_string().into())]));
+                return;
+            },
+        };
+
+        match EncryptionKey::from_passphrase(passphrase, &params) {
+            Ok(key) => {
+                let provenance = KeyProvenance::new("Derived from a passphrase for an encrypted file")
+                    .with_origin(&encrypted_file.to_string_lossy());
+                if self.add_key(name, key, provenance) {
+                    self.events.status(tr_args("status-key-derived", &[("name", name.into())]));
+                }
+            },
+            Err(e) => self.events.error(tr_args("error-key-derive", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Make the saved key at the index the current key
+    pub fn select_key(&mut self, index: usize) {
+        if let Some((name, key)) = self.saved_keys.get(index) {
+            let message = match self.metadata(key).filter(|metadata| metadata.is_expired())
+                .and_then(|metadata| metadata.expires_on.as_deref()) {
+                Some(expires_on) => tr_args("status-key-selected-expired", &[("name", name.as_str().into()), ("date", expires_on.into())]),
+                None => tr_args("status-key-selected", &[("name", name.as_str().into())]),
+            };
+            self.current_key = Some(key.clone());
+            self.selected_token_key = None;
+            self.token_key_unlocked = false;
+            self.events.status(message);
+        }
+    }
+
+    /// Set or clear the day the saved key at the index expires
+    pub fn set_expiry(&mut self, index: usize, expires_on: Option<NaiveDate>) {
+        if !self.management_allowed() {
+            self.events.error(tr("error-management-disabled"));
+            return;
+        }
+
+        let (name, key) = match self.saved_keys.get(index) {
+            Some((name, key)) => (name.clone(), key.clone()),
+            None => return,
+        };
+
+        let expires_on = expires_on.map(|date| date.format(keystore::DATE_FORMAT).to_string());
+        let (log_message, message) = match &expires_on {
+            Some(date) => (
+                format!("Key '{}' expires on {}", name, date),
+                tr_args("status-key-expires", &[("name", name.as_str().into()), ("date", date.as_str().into())]),
+            ),
+            None => (
+                format!("Key '{}' no longer expires", name),
+                tr_args("status-key-no-expiry", &[("name", name.as_str().into())]),
+            ),
+        };
+        self.key_metadata.entry(key.fingerprint()).or_default().expires_on = expires_on;
+        log_key_success("Set Key Expiry", "", &log_message, &key.fingerprint());
+        self.keys_changed();
+        self.events.status(message);
+    }
+
+    /// Check whether the saved key is kept in the OS credential store
+    pub fn in_credential_store(&self, key: &EncryptionKey) -> bool {
+        self.metadata(key).is_some_and(|metadata| metadata.credential_name.is_some())
+    }
+
+    /// Keep the saved key at the index in the OS credential store, referenced by name
+    /// from the key store, or move it back into the key store
+    pub fn set_in_credential_store(&mut self, index: usize, in_credential_store: bool) {
+        if !self.management_allowed() {
+            self.events.error(tr("error-management-disabled"));
+            return;
+        }
+
+        let (name, key) = match self.saved_keys.get(index) {
+            Some((name, key)) => (name.clone(), key.clone()),
+            None => return,
+        };
+        if self.distributed_keys.iter().any(|distributed| distributed.key == key.key) {
+            self.events.error(tr_args("error-key-distributed", &[("name", name.as_str().into())]));
+            return;
+        }
+        if self.keystore.is_none() {
+            self.events.error(tr("error-credential-store-session"));
+            return;
+        }
+        if self.in_credential_store(&key) == in_credential_store {
+            return;
+        }
+
+        let fingerprint = key.fingerprint();
+        let moved_to = if in_credential_store {
+            let credential_name = keystore::credential_name(&name);
+            let taken = self.key_metadata.iter()
+                .any(|(other, metadata)| *other != fingerprint && metadata.credential_name.as_ref() == Some(&credential_name));
+            if taken {
+                self.events.error(tr_args("error-credential-name-taken", &[("name", name.as_str().into())]));
+                return;
+            }
+            if let Err(e) = keystore::store_credential_key(&credential_name, &name, &key) {
+                self.events.error(tr_args("error-credential-move", &[("error", e.to_string().into())]));
+                return;
+            }
+            self.key_metadata.entry(fingerprint.clone()).or_default().credential_name = Some(credential_name);
+            self.keys_changed();
+            "credential store"
+        } else {
+            let credential_name = self.key_metadata.get_mut(&fingerprint)
+                .and_then(|metadata| metadata.credential_name.take());
+            // The key is written to the key store before it leaves the credential store
+            self.keys_changed();
+            if let Some(Err(e)) = credential_name.as_deref().map(keystore::remove_credential_key) {
+                self.events.error(tr_args("error-credential-remove", &[("name", name.as_str().into()), ("error", e.to_string().into())]));
+            }
+            "key store"
+        };
+
+        log_key_success("Move Key", "", &format!("Key '{}' is kept in the {}", name, moved_to), &fingerprint);
+        self.events.status(tr_args(
+            if in_credential_store { "status-key-in-credential-store" } else { "status-key-in-key-store" },
+            &[("name", name.as_str().into())],
+        ));
+    }
+
+    /// Record that a saved key was used to encrypt or decrypt
+    ///
+    /// Only the key store is updated; the key backups are not written again for this.
+    pub fn mark_key_used(&mut self, key: &EncryptionKey) {
+        if let Some(metadata) = self.key_metadata.get_mut(&key.fingerprint()) {
+            metadata.used_now();
+            self.save_keystore();
+        }
+    }
+
+    /// Remove the saved key at the index
+    pub fn remove_key(&mut self, index: usize) {
+        if index >= self.saved_keys.len() {
+            return;
+        }
+        if !self.management_allowed() {
+            self.events.error(tr("error-remove-disabled"));
+            return;
+        }
+
+        // If we remove the current key, clear it
+        if self.is_current(index) {
+            self.current_key = None;
+        }
+
+        let (name, key) = self.saved_keys.remove(index);
+        let mut credential_name = None;
+        if !self.saved_keys.iter().any(|(_, saved)| saved.key == key.key) {
+            credential_name = self.key_metadata.remove(&key.fingerprint())
+                .and_then(|metadata| metadata.credential_name);
+        }
+        self.keys_changed();
+        if let Some(Err(e)) = credential_name.as_deref().map(keystore::remove_credential_key) {
+            self.events.error(tr_args("error-key-removed-credential", &[("name", name.as_str().into()), ("error", e.to_string().into())]));
+        }
+        self.events.status(tr_args("status-key-removed", &[("name", name.into())]));
+    }
+
+    /// Load the backup key from the OS credential store, so changes to the saved keys
+    /// are backed up
+    pub fn load_backup_key(&mut self) {
+        // Turning backups on writes the first backup, so without one there is no key
+        if self.key_backups().is_empty() {
+            return;
+        }
+
+        match key_backups::load_backup_key() {
+            Ok(key) => self.backup_key = key,
+            Err(e) => self.events.error(tr_args("error-backups-off", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Check whether changes to the saved keys are backed up
+    pub fn backups_enabled(&self) -> bool {
+        self.backup_key.is_some()
+    }
+
+    /// Turn on key backups with a backup passphrase (or change the passphrase), and
+    /// back up the saved keys now
+    ///
+    /// # Returns
+    /// * `bool` - Whether the passphrase was accepted (errors are raised as events)
+    pub fn set_backup_passphrase(&mut self, passphrase: &str) -> bool {
+        if !self.management_allowed() {
+            self.events.error(tr("error-backups-disabled"));
+            return false;
+        }
+
+        if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+            self.events.error(tr_args("error-passphrase-short", &[("length", MIN_PASSPHRASE_LENGTH.into())]));
+            return false;
+        }
+
+        let key = match key_backups::backup_key(passphrase) {
+            Ok(key) => key,
+            Err(e) => {
+                self.events.error(tr_args("error-backup-key-derive", &[("error", e.to_string().into())]));
+                return false;
+            },
+        };
+
+        // Without the credential store, backups still run until CRUSTy is closed
+        if let Err(e) = key_backups::store_backup_key(&key) {
+            self.events.error(tr_args("error-backups-session", &[("error", e.to_string().into())]));
+        }
+        self.backup_key = Some(key);
+        self.back_up_keys();
+        self.events.status(tr("status-backups-on"));
+        true
+    }
+
+    /// Get the backup generations, newest first
+    pub fn key_backups(&self) -> Vec<BackupGeneration> {
+        key_backups::list_backups(&self.backup_dir)
+    }
+
+    /// Replace the saved keys with the keys in a backup generation
+    ///
+    /// When key backups are on, the restored keys are backed up as the newest
+    /// generation, so the keys replaced are still in the next one.
+    ///
+    /// # Arguments
+    /// * `generation` - The backup generation (1 for the newest)
+    /// * `passphrase` - The backup passphrase the backup was written with
+    pub fn restore_backup(&mut self, generation: usize, passphrase: &str) {
+        if !self.management_allowed() {
+            self.events.error(tr("error-import-disabled"));
+            return;
+        }
+
+        let path = key_backups::backup_path(&self.backup_dir, generation);
+        let keys = match key_backups::read_backup(&path, passphrase) {
+            Ok(keys) => keys,
+            Err(e) => {
+                log_error("Restore Key Backup", &path.to_string_lossy(), &e.to_string());
+                self.events.error(tr_args("error-backup-restore", &[("error", e.to_string().into())]));
+                return;
+            },
+        };
+
+        // Keys not saved here before are recorded as coming from the backup
+        for (_, key) in &keys {
+            let metadata = self.key_metadata.entry(key.fingerprint()).or_default();
+            if metadata.provenance.is_none() {
+                metadata.provenance = Some(KeyProvenance::new(&format!("Restored from key backup {}", generation))
+                    .with_origin(&path.to_string_lossy()));
+            }
+        }
+        self.saved_keys = keys;
+        let current_kept = self.current_key.as_ref()
+            .is_some_and(|current| self.saved_keys.iter().any(|(_, key)| key.to_base64() == current.to_base64()));
+        if !current_kept {
+            self.current_key = None;
+        }
+        self.keys_changed();
+
+        let message = format!("Restored {} key(s) from backup {}", self.saved_keys.len(), generation);
+        log_success("Restore Key Backup", &path.to_string_lossy(), &message);
+        self.events.status(tr_args(
+            "status-backup-restored",
+            &[("count", self.saved_keys.len().into()), ("generation", generation.into())],
+        ));
+    }
+
+    /// Load the keys saved in earlier sessions, and keep saving the user's keys there
+    ///
+    /// If the key store cannot be used, keys are kept until CRUSTy is closed.
+    pub fn load_keystore(&mut self, path: PathBuf) {
+        match KeyStore::open(path) {
+            Ok((store, keys)) => {
+                if !keys.is_empty() {
+                    self.events.status(tr_args("status-keys-loaded", &[("count", keys.len().into())]));
+                }
+                for (name, key, metadata) in keys {
+                    self.key_metadata.insert(key.fingerprint(), metadata);
+                    self.saved_keys.push((name, key));
+                }
+                self.keystore = Some(store);
+            },
+            Err(e) => self.events.error(tr_args("error-keys-session", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Lock the keys: forget the user's saved keys, the store key, the current key, and
+    /// the cached smartcard PINs until `unlock_keys` loads them from the key store again
+    ///
+    /// Keys from the administrator stay available. Keys kept in memory only (when the key
+    /// store cannot be used) are not forgotten, as they could not be loaded again.
+    ///
+    /// # Arguments
+    /// * `reason` - Why the keys are locked (for the log)
+    pub fn lock_keys(&mut self, reason: &str) {
+        self.pin_cache.clear();
+        self.current_key = None;
+        self.token_key_unlocked = false;
+
+        let store = match self.keystore.take() {
+            Some(store) => store,
+            None => return,
+        };
+
+        let distributed_keys = &self.distributed_keys;
+        self.saved_keys.retain(|(_, key)| distributed_keys.iter().any(|distributed| distributed.key == key.key));
+        let kept: Vec<String> = self.saved_keys.iter().map(|(_, key)| key.fingerprint()).collect();
+        self.key_metadata.retain(|fingerprint, _| kept.contains(fingerprint));
+
+        let path = store.path().to_path_buf();
+        log_success("Lock Keys", &path.to_string_lossy(), &format!("Keys locked: {}", reason));
+        self.locked_keystore = Some(path);
+        self.events.status(tr("status-keys-locked"));
+    }
+
+    /// Check whether the keys are locked
+    pub fn is_locked(&self) -> bool {
+        self.locked_keystore.is_some()
+    }
+
+    /// Load the keys locked by `lock_keys` from the key store again
+    pub fn unlock_keys(&mut self) {
+        if let Some(path) = self.locked_keystore.take() {
+            log_success("Unlock Keys", &path.to_string_lossy(), "Keys unlocked");
+            self.load_keystore(path);
+        }
+    }
+
+    /// Get the key store file (None when keys are kept in memory only)
+    pub fn keystore_path(&self) -> Option<&Path> {
+        self.keystore.as_ref().map(KeyStore::path)
+    }
+
+    /// Save the user's keys to the key store and back up the saved keys, after a change
+    fn keys_changed(&mut self) {
+        self.save_keystore();
+        self.back_up_keys();
+    }
+
+    /// Save the user's keys and their metadata to the key store
+    fn save_keystore(&mut self) {
+        if let Some(store) = &mut self.keystore {
+            let user_keys: Vec<SavedKey> = self.saved_keys.iter()
+                .filter(|(_, key)| !self.distributed_keys.iter().any(|distributed| distributed.key == key.key))
+                .map(|(name, key)| {
+                    let metadata = self.key_metadata.get(&key.fingerprint()).cloned().unwrap_or_default();
+                    (name.clone(), key.clone(), metadata)
+                })
+                .collect();
+            if let Err(e) = store.save(&user_keys) {
+                self.events.error(tr_args("error-keys-save", &[("error", e.to_string().into())]));
+            }
+        }
+    }
+
+    /// Back up the saved keys as the newest generation (when key backups are on)
+    fn back_up_keys(&mut self) {
+        if let Some(backup_key) = &self.backup_key {
+            if let Err(e) = key_backups::write_backup(&self.backup_dir, &self.saved_keys, backup_key) {
+                self.events.error(tr_args("error-keys-backup", &[("error", e.to_string().into())]));
+            }
+        }
+    }
+
+    /// Save a key distributed by the administrator (current only if no key is selected yet)
+    fn add_distributed_key(&mut self, name: String, key: EncryptionKey, provenance: KeyProvenance) {
+        self.key_metadata.entry(key.fingerprint()).or_default().provenance = Some(provenance);
+        if self.current_key.is_none() {
+            self.current_key = Some(key.clone());
+        }
+        self.distributed_keys.push(key.clone());
+        self.saved_keys.push((name, key));
+    }
+
+    /// Load the keys provisioned by the admin policy
+    pub fn load_provisioned_keys(&mut self) {
+        for provisioned in &get_policy().provisioned_keys {
+            let result = std::fs::read_to_string(&provisioned.path)
+                .map_err(|e| e.to_string())
+                .and_then(|key_base64| EncryptionKey::from_base64(key_base64.trim()).map_err(|e| e.to_string()));
+
+            match result {
+                Ok(key) => {
+                    let provenance = KeyProvenance::new("Provisioned by the administrator")
+                        .with_origin(&provisioned.path.to_string_lossy());
+                    self.add_distributed_key(provisioned.name.clone(), key, provenance);
+                },
+                Err(e) => {
+                    let message = format!("Failed to load provisioned key '{}': {}", provisioned.name, e);
+                    log_error("Load Key", &provisioned.path.to_string_lossy(), &message);
+                    self.events.error(tr_args(
+                        "error-provisioned-key",
+                        &[("name", provisioned.name.as_str().into()), ("error", e.into())],
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Load the keys from the installed deployment bundle
+    pub fn load_deployed_keys(&mut self) {
+        match deployment::load_deployed_keys() {
+            Ok(keys) => {
+                let origin = deployment::bundle_path();
+                for (name, key) in keys {
+                    let provenance = KeyProvenance::new("Deployed by the administrator")
+                        .with_origin(&origin.to_string_lossy());
+                    self.add_distributed_key(name, key, provenance);
+                }
+            },
+            Err(e) => {
+                let message = format!("Failed to load deployed keys: {}", e);
+                log_error("Load Key", &deployment::bundle_path().to_string_lossy(), &message);
+                self.events.error(tr_args("error-deployed-keys", &[("error", e.to_string().into())]));
+            }
+        }
+    }
+
+    /// Select a smartcard key; it is unwrapped with the card PIN when files are decrypted
+    pub fn select_token_key(&mut self, index: usize) {
+        if let Some(token_key) = self.token_keys.get(index) {
+            let message = tr_args("status-token-key-selected", &[("name", token_key.name.as_str().into())]);
+            self.selected_token_key = Some(index);
+            self.current_key = None;
+            self.token_key_unlocked = false;
+            self.events.status(message);
+        }
+    }
+
+    /// Unwrap a smartcard key with the cached PIN, or report that the PIN is needed
+    pub fn unlock_token_key(&mut self, index: usize) -> TokenUnlock {
+        let token_key = match self.token_keys.get(index) {
+            Some(token_key) => token_key.clone(),
+            None => return TokenUnlock::Failed,
+        };
+
+        if let Some(pin) = self.pin_cache.get(&token_key.token_label) {
+            match smartcard::unwrap_key(&token_key, &pin) {
+                Ok(key) => {
+                    self.use_unwrapped_key(&token_key, key);
+                    return TokenUnlock::Unlocked;
+                },
+                // The PIN was changed since it was cached; ask again
+                Err(TokenError::PinIncorrect(_)) => self.pin_cache.remove(&token_key.token_label),
+                Err(e) => {
+                    log_error("Smartcard Unlock", &token_key.token_label, &e.to_string());
+                    self.events.error(e.to_string());
+                    return TokenUnlock::Failed;
+                }
+            }
+        }
+
+        match smartcard::pin_status(&token_key) {
+            Ok(PinStatus::Locked) => {
+                let message = TokenError::PinLocked.to_string();
+                log_error("Smartcard Unlock", &token_key.token_label, &message);
+                self.events.error(message);
+                TokenUnlock::Failed
+            },
+            Ok(status) => TokenUnlock::PinRequired(status),
+            Err(e) => {
+                self.events.error(e.to_string());
+                TokenUnlock::Failed
+            }
+        }
+    }
+
+    /// Unwrap a smartcard key with an entered PIN
+    ///
+    /// # Returns
+    /// * `Result<(), TokenError>` - Ok if the key was unwrapped. An incorrect PIN is
+    ///   returned without an event so it can be entered again; other errors raise one.
+    pub fn unlock_token_key_with_pin(&mut self, index: usize, pin: &str) -> Result<(), TokenError> {
+        let token_key = match self.token_keys.get(index) {
+            Some(token_key) => token_key.clone(),
+            None => return Err(TokenError::Token("Unknown smartcard key".to_string())),
+        };
+
+        match smartcard::unwrap_key(&token_key, pin) {
+            Ok(key) => {
+                self.pin_cache.store(&token_key.token_label, pin);
+                self.use_unwrapped_key(&token_key, key);
+                Ok(())
+            },
+            Err(TokenError::PinIncorrect(status)) => {
+                log_error("Smartcard Unlock", &token_key.token_label, "Incorrect PIN");
+                Err(TokenError::PinIncorrect(status))
+            },
+            Err(e) => {
+                log_error("Smartcard Unlock", &token_key.token_label, &e.to_string());
+                self.events.error(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Use an unwrapped smartcard key for the pending decryption
+    fn use_unwrapped_key(&mut self, token_key: &TokenWrappedKey, key: EncryptionKey) {
+        self.current_key = Some(key);
+        self.token_key_unlocked = true;
+        log_success("Smartcard Unlock", &token_key.token_label, &format!("Unlocked key '{}'", token_key.name));
+    }
+
+    /// Forget expired PINs, and the unwrapped smartcard key once no operation uses it
+    pub fn expire_token_session(&mut self, operation_idle: bool) {
+        self.pin_cache.expire();
+        if self.token_key_unlocked && operation_idle {
+            self.current_key = None;
+            self.token_key_unlocked = false;
+        }
+    }
+
+    /// Split the current key into shares (2 of 3) and store them
+    pub fn create_split_key(&mut self) {
+        let result = match &self.current_key {
+            Some(key) => SplitEncryptionKey::new(key, 2, 3, KeyPurpose::Standard),
+            None => Err(SplitKeyError::Key("No key selected".to_string())),
+        };
+
+        let split_key = match result {
+            Ok(split_key) => split_key,
+            Err(e) => {
+                self.events.error(tr_args("error-split-create", &[("error", e.to_string().into())]));
+                return;
+            }
+        };
+
+        match Self::store_split_key(&split_key) {
+            Ok((secondary_share_path, recovery_share_path)) => {
+                self.check_share_verification();
+                self.events.status(tr_args("status-split-created", &[
+                    ("secondary", secondary_share_path.display().to_string().into()),
+                    ("recovery", recovery_share_path.display().to_string().into()),
+                ]));
+            },
+            Err(e) => self.events.error(tr_args("error-split-store", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Store the shares of a split key and start tracking them
+    ///
+    /// # Returns
+    /// * `Result<(PathBuf, PathBuf), SplitKeyError>` - Paths of the secondary and recovery shares
+    fn store_split_key(split_key: &SplitEncryptionKey) -> Result<(PathBuf, PathBuf), SplitKeyError> {
+        let key_share_manager = KeyShareManager::open_default()?;
+
+        // Store the primary share in the OS credential store
+        key_share_manager.store_primary_share(split_key)?;
+
+        // Save the secondary share to a file
+        let secondary_share_path = key_share_manager.save_secondary_share(
+            split_key,
+            "secondary_share.key",
+            ShareFormat::Binary
+        )?;
+
+        // Generate and save a recovery share in text format
+        let recovery_share_path = key_share_manager.save_recovery_share(
+            split_key,
+            "recovery_share.txt",
+            ShareFormat::Text
+        )?;
+
+        // Track the shares so custodians can be reminded to verify them
+        key_share_manager.record_shares(split_key)?;
+
+        Ok((secondary_share_path, recovery_share_path))
+    }
+
+    /// Reconstruct a key from the primary share and a secondary share file, and select it
+    pub fn reconstruct_key(&mut self, secondary_share_path: &Path) {
+        let result = KeyShareManager::open_default()
+            .and_then(|manager| manager.reconstruct_key(secondary_share_path));
+
+        match result {
+            Ok(key) => {
+                let name = "Reconstructed Key";
+                // The primary share is share 1
+                let share_numbers = SplitEncryptionKey::load_share_from_file(secondary_share_path)
+                    .map(|share| vec![1, split_key::share_number(&share)])
+                    .unwrap_or_default();
+                let provenance = KeyProvenance::new("Reconstructed from the primary share and a share file")
+                    .with_origin(&secondary_share_path.to_string_lossy())
+                    .with_shares(share_numbers, None);
+                if self.add_key(name, key, provenance) {
+                    self.events.status(tr_args("status-key-reconstructed", &[("name", name.into())]));
+                }
+            },
+            Err(e) => self.events.error(tr_args("error-key-reconstruct", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Verify the credential store share and remind custodians of shares due for verification
+    pub fn check_share_verification(&mut self) {
+        let manager = match KeyShareManager::open_default() {
+            Ok(manager) => manager,
+            Err(e) => {
+                self.events.error(tr_args("error-shares-open", &[("error", e.to_string().into())]));
+                return;
+            }
+        };
+
+        let interval = get_policy().share_verification_interval();
+
+        // The primary share is stored locally, so it is verified without asking anyone
+        let records = manager.load_share_records().unwrap_or_default();
+        if records.iter().any(|record| record.index == 0 && record.is_due(interval)) {
+            match manager.verify_primary_share() {
+                Ok(record) => log_success("Verify Share", &record.label, "Share verified automatically"),
+                Err(e) => {
+                    let message = format!("Primary key share could not be verified: {}", e);
+                    log_error("Verify Share", "Primary share (credential store)", &message);
+                    self.events.error(tr_args("error-primary-share-verify", &[("error", e.to_string().into())]));
+                }
+            }
+        }
+
+        self.share_records = manager.load_share_records().unwrap_or_default();
+        self.load_custodians();
+
+        let due = self.share_records.iter().filter(|record| record.is_due(interval)).count();
+        if due > 0 {
+            self.events.status(tr_args("status-shares-due", &[("count", due.into())]));
+        }
+    }
+
+    /// Verify the share entered by a custodian
+    pub fn verify_share(&mut self) {
+        let result = KeyShareManager::open_default()
+            .and_then(|manager| manager.verify_share(&self.share_verification_input)
+                .map(|record| (record, manager.load_share_records())));
+
+        match result {
+            Ok((record, records)) => {
+                log_success("Verify Share", &record.label, "Share verified by custodian");
+                self.share_records = records.unwrap_or_default();
+                self.share_verification_input.clear();
+                self.events.status(tr_args("status-share-verified", &[("label", record.label.as_str().into())]));
+            },
+            Err(e) => {
+                log_error("Verify Share", "", &e.to_string());
+                self.events.error(tr_args("error-share-verify", &[("error", e.to_string().into())]));
+            }
+        }
+    }
+
+    /// Load the custodians of the tracked shares from the registry
+    pub fn load_custodians(&mut self) {
+        let registry = match CustodianRegistry::load_from(&CustodianRegistry::default_path()) {
+            Ok(registry) => registry,
+            Err(e) => {
+                self.events.error(tr_args("error-custodians-load", &[("error", e.to_string().into())]));
+                CustodianRegistry::default()
+            }
+        };
+
+        // One entry per share, so shares without a custodian can be assigned one
+        self.custodians = self.share_records.iter()
+            .map(|record| registry.for_share(&record.fingerprint).cloned().unwrap_or_else(|| Custodian {
+                share_fingerprint: record.fingerprint.clone(),
+                ..Custodian::default()
+            }))
+            .collect();
+    }
+
+    /// Save the edited custodians to the registry
+    pub fn save_custodians(&mut self) {
+        let path = CustodianRegistry::default_path();
+        let result = shared_files::lock(&path).map_err(EncryptionError::from).and_then(|_lock| {
+            let mut registry = CustodianRegistry::load_from(&path)?;
+            for custodian in &self.custodians {
+                registry.set(custodian.clone());
+            }
+            registry.save_to(&path)
+        });
+
+        match result {
+            Ok(()) => {
+                log_success("Update Custodians", &path.to_string_lossy(), "Share custodian directory updated");
+                self.events.status(tr("status-custodians-saved"));
+            },
+            Err(e) => self.events.error(tr_args("error-custodians-save", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Write a key ceremony report with the shares and their custodians
+    pub fn write_ceremony_report(&mut self, path: &Path) {
+        let registry = match CustodianRegistry::load_from(&CustodianRegistry::default_path()) {
+            Ok(registry) => registry,
+            Err(e) => {
+                self.events.error(tr_args("error-custodians-load", &[("error", e.to_string().into())]));
+                return;
+            }
+        };
+
+        let result = std::fs::File::create(path)
+            .and_then(|mut file| custodians::write_ceremony_report(&self.share_records, &registry, &mut file));
+
+        match result {
+            Ok(()) => self.events.status(tr_args("status-ceremony-saved", &[("path", path.display().to_string().into())])),
+            Err(e) => self.events.error(tr_args("error-ceremony-write", &[("error", e.to_string().into())])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> KeyService {
+        KeyService::new(Vec::new(), PinCache::new(Duration::ZERO))
+    }
+
+    #[test]
+    fn test_select_and_remove_keys() {
+        let mut keys = service();
+        keys.add_key("First", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.add_key("Second", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+
+        // The last added key is current
+        assert!(keys.is_current(1));
+        assert_eq!(keys.current_key_name(), Some("Second".to_string()));
+
+        keys.select_key(0);
+        assert!(keys.is_current(0));
+        assert_eq!(keys.take_events(), vec![ServiceEvent::Status("Selected key: First".to_string())]);
+
+        // Removing the current key clears it
+        keys.remove_key(0);
+        assert!(keys.current_key().is_none());
+        assert_eq!(keys.saved_keys().len(), 1);
+        assert_eq!(keys.take_events(), vec![ServiceEvent::Status("Removed key: First".to_string())]);
+    }
+
+    #[test]
+    fn test_restricted_mode_keeps_keys() {
+        let mut keys = service();
+        assert!(keys.add_key("Work", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer")));
+
+        // In restricted mode, keys can neither be added nor removed
+        keys.management_allowed = false;
+        assert!(!keys.add_key("Home", EncryptionKey::generate(), KeyProvenance::new("Loaded from a key file")));
+        assert_eq!(keys.take_events().last(), Some(&ServiceEvent::Error("Adding keys is disabled by policy".to_string())));
+        keys.remove_key(0);
+        assert_eq!(keys.take_events(), vec![ServiceEvent::Error("Removing keys is disabled by policy".to_string())]);
+        assert_eq!(keys.saved_keys().len(), 1);
+        assert_eq!(keys.current_key_name(), Some("Work".to_string()));
+    }
+
+    #[test]
+    fn test_saved_keys_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore");
+        let store_key = EncryptionKey::generate();
+
+        let mut keys = service();
+        keys.keystore = Some(KeyStore::new(path.clone(), Some(store_key.clone())));
+        keys.add_distributed_key("Provisioned".to_string(), EncryptionKey::generate(), KeyProvenance::new("Provisioned by the administrator"));
+        keys.add_key("First", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.add_key("Second", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.remove_key(1);
+
+        // The next session has the user's keys, but not the administrator's
+        let stored = KeyStore::new(path, Some(store_key)).load().unwrap();
+        let names: Vec<&str> = stored.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, ["Second"]);
+        assert!(keys.take_events().iter().all(|event| !matches!(event, ServiceEvent::Error(_))));
+    }
+
+    #[test]
+    fn test_lock_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut keys = service();
+
+        // Keys kept in memory only could not be loaded again, so they stay
+        keys.add_key("Memory Key", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.lock_keys("the session was locked");
+        assert!(!keys.is_locked());
+        assert_eq!(keys.saved_keys().len(), 1);
+        assert!(keys.current_key().is_none());
+
+        let mut keys = service();
+        keys.keystore = Some(KeyStore::new(dir.path().join("keystore"), Some(EncryptionKey::generate())));
+        keys.add_distributed_key("Provisioned".to_string(), EncryptionKey::generate(), KeyProvenance::new("Provisioned by the administrator"));
+        let key = EncryptionKey::generate();
+        keys.add_key("User Key", key.clone(), KeyProvenance::new("Generated on this computer"));
+
+        keys.lock_keys("the session was locked");
+        assert!(keys.is_locked());
+        assert!(keys.current_key().is_none());
+        assert_eq!(keys.saved_keys().len(), 1);
+        assert_eq!(keys.saved_keys()[0].0, "Provisioned");
+        assert!(keys.metadata(&key).is_none());
+        assert!(keys.keystore_path().is_none());
+    }
+
+    #[test]
+    fn test_credential_store_needs_key_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut keys = service();
+
+        // Without a key store nothing would refer to the key in the next session
+        keys.add_key("Memory Key", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.take_events();
+        keys.set_in_credential_store(0, true);
+        assert!(matches!(keys.take_events().as_slice(), [ServiceEvent::Error(_)]));
+        assert!(!keys.in_credential_store(&keys.saved_keys()[0].1.clone()));
+
+        // Keys from the administrator are not saved here at all
+        keys.keystore = Some(KeyStore::new(dir.path().join("keystore"), Some(EncryptionKey::generate())));
+        keys.add_distributed_key("Provisioned".to_string(), EncryptionKey::generate(), KeyProvenance::new("Provisioned by the administrator"));
+        keys.set_in_credential_store(1, true);
+        assert!(matches!(keys.take_events().as_slice(), [ServiceEvent::Error(_)]));
+
+        // Moving a key back that is not in the credential store changes nothing
+        keys.set_in_credential_store(0, false);
+        assert!(keys.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_key_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore");
+        let store_key = EncryptionKey::generate();
+
+        let mut keys = service();
+        keys.keystore = Some(KeyStore::new(path.clone(), Some(store_key.clone())));
+        let key = EncryptionKey::generate();
+        let provenance = KeyProvenance::new("Reconstructed from transfer shares")
+            .with_shares(vec![2, 3], Some("0011223344556677".to_string()));
+        keys.add_key("Transfer Key", key.clone(), provenance.clone());
+        assert_eq!(keys.provenance(&key), Some(&provenance));
+        assert!(provenance.describe().contains("shares 2, 3 of package 0011223344556677"));
+
+        // The provenance is kept with the key in the key store
+        let stored = KeyStore::new(path, Some(store_key)).load().unwrap();
+        assert_eq!(stored[0].2.provenance, Some(provenance));
+
+        keys.remove_key(0);
+        assert!(keys.provenance(&key).is_none());
+    }
+
+    #[test]
+    fn test_key_expiry_and_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore");
+        let store_key = EncryptionKey::generate();
+
+        let mut keys = service();
+        keys.keystore = Some(KeyStore::new(path.clone(), Some(store_key.clone())));
+        let key = EncryptionKey::generate();
+        keys.add_key("Old Key", key.clone(), KeyProvenance::new("Generated on this computer"));
+        assert!(keys.metadata(&key).unwrap().created_at.is_some());
+        assert!(keys.metadata(&key).unwrap().last_used.is_none());
+        assert!(keys.current_key_expired_on().is_none());
+
+        keys.set_expiry(0, NaiveDate::from_ymd_opt(2000, 1, 1));
+        assert_eq!(keys.current_key_expired_on(), Some("2000-01-01"));
+        keys.mark_key_used(&key);
+
+        // Kept with the key in the key store
+        let stored = KeyStore::new(path, Some(store_key)).load().unwrap();
+        assert_eq!(stored[0].2.expires_on.as_deref(), Some("2000-01-01"));
+        assert!(stored[0].2.last_used.is_some());
+
+        keys.set_expiry(0, None);
+        assert!(keys.current_key_expired_on().is_none());
+    }
+
+    #[test]
+    fn test_derive_passphrase_keys() {
+        let mut keys = service();
+        keys.derive_key("Short", "short");
+        assert!(keys.saved_keys().is_empty());
+        assert_eq!(keys.take_events(), vec![ServiceEvent::Error("Passphrase must be at least 8 characters".to_string())]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("plain.txt");
+        let encrypted = dir.path().join("plain.txt.encrypted");
+        std::fs::write(&plain, b"Passphrase protected").unwrap();
+
+        // Files encrypted with a random key cannot give a passphrase key
+        encryption::encrypt_file(&plain, &encrypted, &EncryptionKey::generate(), |_| {}).unwrap();
+        keys.derive_key_for_file("From file", "a long passphrase", &encrypted);
+        assert!(keys.saved_keys().is_empty());
+        std::fs::remove_file(&encrypted).unwrap();
+
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1, salt: [3; 16] };
+        let key = EncryptionKey::from_passphrase("a long passphrase", &params).unwrap();
+        encryption::encrypt_file(&plain, &encrypted, &key, |_| {}).unwrap();
+
+        keys.derive_key_for_file("From file", "a long passphrase", &encrypted);
+        assert_eq!(keys.current_key().map(|k| k.key), Some(key.key));
+        assert_eq!(keys.current_key_name(), Some("From file".to_string()));
+    }
+
+    #[test]
+    fn test_key_changes_are_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut keys = service();
+        keys.backup_dir = dir.path().to_path_buf();
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1, salt: [5; 16] };
+        keys.backup_key = Some(EncryptionKey::from_passphrase("backup passphrase", &params).unwrap());
+
+        keys.add_key("First", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.add_key("Second", EncryptionKey::generate(), KeyProvenance::new("Generated on this computer"));
+        keys.remove_key(0);
+        assert_eq!(keys.key_backups().len(), 3);
+
+        // Restoring the backup from before the removal brings the key back, and the
+        // keys it replaced are kept in the next generation
+        keys.restore_backup(2, "wrong passphrase");
+        assert_eq!(keys.saved_keys().len(), 1);
+        keys.restore_backup(2, "backup passphrase");
+        assert_eq!(keys.saved_keys().len(), 2);
+        assert_eq!(keys.current_key_name(), Some("Second".to_string()));
+        assert_eq!(keys.key_backups().len(), 4);
+
+        let replaced = key_backups::read_backup(&key_backups::backup_path(dir.path(), 2), "backup passphrase").unwrap();
+        assert_eq!(replaced.len(), 1);
+    }
+}
@@ -0,0 +1,112 @@
+/// Application services.
+///
+/// This module provides the state and commands behind the GUI:
+/// - `KeyService`: saved, provisioned, and smartcard keys, and split-key shares
+/// - `OperationService`: selected files, output directory, and encryption progress
+/// - `FolderService`: secured folders, and locking and unlocking them
+/// - `TransferService`: transfer packages, received shares, and relay links
+/// - `ColdStorageService`: the cold-storage export wizard, and writing sets
+/// - `ColdStorageRestoreService`: the cold-storage restore wizard, and restoring sets
+/// - `KeyAgreementService`: transfer keys agreed directly with a partner's computer
+/// - `KeyRotationService`: the key rotation wizard, and re-encrypting files under a new key
+/// - `AuditService`: checking encrypted files against their originals for audits
+/// - `BenchmarkService`: comparing the speed of software encryption and the embedded device
+/// - `CatalogService`: catalogs of encrypted collections, to share with collaborators
+/// - `ViewerService`: files decrypted for viewing, and shredding them afterwards
+/// - `ControlApiService`: the local control API, and the jobs queued through it
+///
+/// Screens render service state and call service commands. Commands report what
+/// happened as events, which the app drains every frame into its status bar, so
+/// the services can be used (and tested) without a window.
+use std::path::Path;
+
+use crate::logger::get_logger;
+
+pub mod audit_service;
+pub mod benchmark_service;
+pub mod catalog_service;
+pub mod cold_storage_restore_service;
+pub mod cold_storage_service;
+pub mod control_api_service;
+pub mod folder_service;
+pub mod key_agreement_service;
+pub mod key_rotation_service;
+pub mod key_service;
+pub mod operation_service;
+pub mod transfer_service;
+pub mod viewer_service;
+
+pub use audit_service::AuditService;
+pub use benchmark_service::BenchmarkService;
+pub use catalog_service::CatalogService;
+pub use cold_storage_restore_service::ColdStorageRestoreService;
+pub use cold_storage_service::ColdStorageService;
+pub use control_api_service::ControlApiService;
+pub use folder_service::FolderService;
+pub use key_agreement_service::KeyAgreementService;
+pub use key_rotation_service::KeyRotationService;
+pub use key_service::KeyService;
+pub use operation_service::OperationService;
+pub use transfer_service::TransferService;
+pub use viewer_service::ViewerService;
+
+/// Outcome of a service command, shown to the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceEvent {
+    /// Status message
+    Status(String),
+    /// Error message
+    Error(String),
+}
+
+/// Events raised by a service since they were last taken
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    events: Vec<ServiceEvent>,
+}
+
+impl EventQueue {
+    /// Raise a status event
+    pub fn status(&mut self, message: impl Into<String>) {
+        self.events.push(ServiceEvent::Status(message.into()));
+    }
+
+    /// Raise an error event
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.events.push(ServiceEvent::Error(message.into()));
+    }
+
+    /// Take the raised events, oldest first
+    pub fn take(&mut self) -> Vec<ServiceEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Record a successful command in the operation log
+fn log_success(operation: &str, file_path: &str, message: &str) {
+    if let Some(logger) = get_logger() {
+        logger.log_success(operation, file_path, message).ok();
+    }
+}
+
+/// Record a successful command with a key in the operation log
+fn log_key_success(operation: &str, file_path: &str, message: &str, key_fingerprint: &str) {
+    if let Some(logger) = get_logger() {
+        logger.log_key_success(operation, file_path, message, key_fingerprint).ok();
+    }
+}
+
+/// Record a failed command in the operation log
+fn log_error(operation: &str, file_path: &str, message: &str) {
+    if let Some(logger) = get_logger() {
+        logger.log_error(operation, file_path, message).ok();
+    }
+}
+
+/// Write a path into an operation log message, redacted as file paths are
+fn log_path(path: &Path) -> String {
+    match get_logger() {
+        Some(logger) => logger.redact_path(path),
+        None => path.display().to_string(),
+    }
+}
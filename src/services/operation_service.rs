@@ -0,0 +1,1860 @@
+/// Operation service.
+///
+/// This module provides functionality for:
+/// - Keeping the selected files and output directory
+/// - Selecting every file in a folder, which keep their place in the folder's tree
+///   under the output directory
+/// - Queuing encryption and decryption of the selected files in the file list
+/// - Packing a selected folder into one archive, and extracting archives
+/// - Tracking the progress and results of the running operation
+/// - Exporting a report of the files of the last operation, as CSV or JSON
+/// - Keeping the history of the files processed, which is saved across restarts
+/// - Checking encrypted files against the signed manifest of their batch
+/// - Summarizing the choices a batch starts with, and what changed since the last batch
+/// - Stopping an operation that makes no progress for the timeout of its backend (the
+///   watchdog), failing its unfinished files so the next operation can start
+/// - Testing the connection to the embedded device before an operation is started on it
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::backend::{
+    Backend, BackendFactory, ConnectionSettings, ConnectionSettingsError, ConnectionType, EmbeddedConfig,
+    EthernetSettings, SerialSettings, UsbSettings,
+};
+use crate::gui::file_list::{FileEntry, FileOperationType, FileStatus};
+use crate::policy::get_policy;
+use crate::archive;
+use crate::batch_report::BatchReport;
+use crate::batch_manifest::{self, EntryStatus, ManifestCheck};
+use crate::embedded_protocol::{DeviceHealth, DeviceProgress};
+use crate::encryption::{self, EncryptionError, EncryptionKey};
+use crate::file_names;
+use crate::history::{History, HistoryEntry, HistoryOperation};
+use crate::i18n::{tr, tr_args};
+use crate::in_place;
+use crate::recipients::{self, Recipient};
+use crate::run_summary::{LastRuns, RunChange, RunSummary};
+use crate::secured_folders;
+use crate::services::{log_error, log_key_success, log_success, EventQueue, ServiceEvent};
+use crate::speed_history::{BackendKind, SpeedHistory, CIPHER_AES_256_GCM};
+use crate::start_operation::{FileOperation, FileResults, PauseControl};
+
+/// Time a batch must run before its own speed is used in the time-remaining estimate
+const MIN_MEASURED_TIME: Duration = Duration::from_secs(1);
+
+/// Progress of every file and of the embedded device, as the watchdog last saw it
+type ProgressSnapshot = (Vec<f32>, Option<DeviceProgress>, Option<f32>);
+
+/// Options for the embedded (hardware) encryption backend
+///
+/// The settings for every connection type are kept, so switching the connection type
+/// back and forth does not lose what was entered.
+pub struct BackendOptions {
+    /// Use the embedded device instead of software encryption
+    pub use_embedded: bool,
+    /// How the device is connected
+    pub connection_type: ConnectionType,
+    /// Device identifier or address
+    pub device_id: String,
+    /// Settings used when connected over USB
+    pub usb: UsbSettings,
+    /// Settings used when connected over serial/UART
+    pub serial: SerialSettings,
+    /// Settings used when connected over Ethernet
+    pub ethernet: EthernetSettings,
+}
+
+impl Default for BackendOptions {
+    fn default() -> Self {
+        BackendOptions {
+            use_embedded: false,
+            connection_type: ConnectionType::Usb,
+            device_id: String::new(),
+            usb: UsbSettings::default(),
+            serial: SerialSettings::default(),
+            ethernet: EthernetSettings::default(),
+        }
+    }
+}
+
+impl BackendOptions {
+    /// Get the settings for the selected connection type
+    pub fn connection_settings(&self) -> ConnectionSettings {
+        match self.connection_type {
+            ConnectionType::Usb => ConnectionSettings::Usb(self.usb.clone()),
+            ConnectionType::Serial => ConnectionSettings::Serial(self.serial.clone()),
+            ConnectionType::Ethernet => ConnectionSettings::Ethernet(self.ethernet.clone()),
+        }
+    }
+
+    /// Get the configuration for the embedded device
+    pub fn config(&self) -> EmbeddedConfig {
+        EmbeddedConfig {
+            device_id: self.device_id.trim().to_string(),
+            connection: self.connection_settings(),
+        }
+    }
+
+    /// Use a saved configuration for the embedded device
+    ///
+    /// Only the settings of its connection type are replaced; those entered for the
+    /// other types are kept.
+    pub fn set_config(&mut self, config: &EmbeddedConfig) {
+        self.device_id = config.device_id.clone();
+        self.connection_type = config.connection.connection_type();
+        match &config.connection {
+            ConnectionSettings::Usb(usb) => self.usb = usb.clone(),
+            ConnectionSettings::Serial(serial) => self.serial = serial.clone(),
+            ConnectionSettings::Ethernet(ethernet) => self.ethernet = ethernet.clone(),
+        }
+    }
+
+    /// Check the options before an operation is started
+    ///
+    /// # Returns
+    /// * `Result<(), ConnectionSettingsError>` - Ok if software encryption is used or the
+    ///   embedded device configuration is valid, or what is wrong with it
+    pub fn validate(&self) -> Result<(), ConnectionSettingsError> {
+        if self.use_embedded {
+            self.config().validate()
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Files to process and the state of the running operation
+pub struct OperationService {
+    /// Select several files at once
+    pub batch_mode: bool,
+    /// Save encrypted files under random names, storing the original name encrypted
+    /// in the file header
+    pub hide_file_names: bool,
+    /// Replace the original files with the encrypted files (no output directory is used)
+    pub replace_originals: bool,
+    /// Overwrite the original files with random data before deleting them
+    pub shred_originals: bool,
+    /// Append parity to encrypted files, so damage from archival media can be repaired
+    pub add_parity: bool,
+    /// Store the SHA-256 of each original in its encrypted file, to check decrypted files
+    pub add_digest: bool,
+    /// Write a signed manifest of the encrypted files into the output directory of a batch
+    pub write_manifest: bool,
+    /// Encrypt files in the age format, readable by the age CLI
+    pub age_format: bool,
+    /// Other age recipients (`age1...`) age files are encrypted to
+    pub age_recipients: Vec<String>,
+    /// Encrypt files as OpenPGP messages, readable by GnuPG
+    pub openpgp_format: bool,
+    /// Other people's armored OpenPGP public keys OpenPGP messages are encrypted to
+    pub openpgp_recipients: Vec<String>,
+    /// Encrypt each file once for several recipients
+    pub use_recipient: bool,
+    /// Email addresses and age recipients (`age1...`) files are encrypted for, separated
+    /// by commas, spaces, or new lines
+    pub recipient_list: String,
+    /// Pack a selected folder into one `.crusty` archive instead of encrypting each file
+    pub archive_folder: bool,
+    /// Reason for the next decryption (recorded in the audit log)
+    pub decrypt_reason: String,
+    /// Backend used for the next operation
+    pub backend: BackendOptions,
+    /// Most memory a file may take in the next operation (None for no limit)
+    pub memory_limit: Option<u64>,
+    selected_files: Vec<PathBuf>,
+    /// Folder the selected files were selected from (None for files selected on their own)
+    selected_folder: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+    operation: FileOperation,
+    progress: Arc<Mutex<Vec<f32>>>,
+    /// Result of each file, reported by the operation thread once the file is done
+    file_results: FileResults,
+    /// Groups of files with the same contents in the last batch encrypted
+    duplicates: Arc<Mutex<Vec<Vec<PathBuf>>>>,
+    /// Fraction of the batch's files compared, while they are checked for duplicates
+    duplicate_scan: Arc<Mutex<Option<f32>>>,
+    /// Progress reported by the embedded device for the job it is running
+    device_progress: Arc<Mutex<Option<DeviceProgress>>>,
+    /// Why the embedded device could not be connected to, for the status area
+    device_error: Arc<Mutex<Option<String>>>,
+    pause: PauseControl,
+    /// Whether the running operation was paused (its files' times include the pause)
+    paused_this_run: bool,
+    /// Progress of the running operation when the watchdog last saw it change, and when
+    last_activity: Option<(ProgressSnapshot, Instant)>,
+    operation_results: Vec<String>,
+    file_entries: Vec<FileEntry>,
+    batch_backend: BackendKind,
+    speed_history: SpeedHistory,
+    speed_history_path: Option<PathBuf>,
+    last_runs: LastRuns,
+    last_runs_path: Option<PathBuf>,
+    history: History,
+    history_path: Option<PathBuf>,
+    manifest_job: Option<JoinHandle<Result<ManifestCheck, EncryptionError>>>,
+    manifest_check: Option<ManifestCheck>,
+    connection_test: Option<JoinHandle<Result<DeviceHealth, EncryptionError>>>,
+    /// What the device reported when its connection was last tested
+    device_health: Option<DeviceHealth>,
+    events: EventQueue,
+}
+
+impl OperationService {
+    /// Create a service with no files selected
+    pub fn new() -> Self {
+        OperationService {
+            batch_mode: false,
+            hide_file_names: false,
+            replace_originals: false,
+            shred_originals: false,
+            add_parity: false,
+            add_digest: false,
+            write_manifest: false,
+            age_format: false,
+            age_recipients: Vec::new(),
+            openpgp_format: false,
+            openpgp_recipients: Vec::new(),
+            use_recipient: false,
+            recipient_list: String::new(),
+            archive_folder: false,
+            decrypt_reason: String::new(),
+            backend: BackendOptions::default(),
+            memory_limit: None,
+            selected_files: Vec::new(),
+            selected_folder: None,
+            output_dir: None,
+            operation: FileOperation::None,
+            progress: Arc::new(Mutex::new(Vec::new())),
+            file_results: Arc::new(Mutex::new(Vec::new())),
+            duplicates: Arc::new(Mutex::new(Vec::new())),
+            duplicate_scan: Arc::new(Mutex::new(None)),
+            device_progress: Arc::new(Mutex::new(None)),
+            device_error: Arc::new(Mutex::new(None)),
+            pause: PauseControl::default(),
+            paused_this_run: false,
+            last_activity: None,
+            operation_results: Vec::new(),
+            file_entries: Vec::new(),
+            batch_backend: BackendKind::Local,
+            speed_history: SpeedHistory::default(),
+            speed_history_path: None,
+            last_runs: LastRuns::default(),
+            last_runs_path: None,
+            history: History::default(),
+            history_path: None,
+            manifest_job: None,
+            manifest_check: None,
+            connection_test: None,
+            device_health: None,
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Load the speed history, and save the speeds of finished files to it from now on
+    pub fn load_speed_history(&mut self, path: PathBuf) {
+        self.speed_history = SpeedHistory::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load speed history, starting a new one: {}", e);
+            SpeedHistory::default()
+        });
+        self.speed_history_path = Some(path);
+    }
+
+    /// Load the summaries of the last batches, and save each batch started to them from
+    /// now on
+    pub fn load_last_runs(&mut self, path: PathBuf) {
+        self.last_runs = LastRuns::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load the last runs, starting over: {}", e);
+            LastRuns::default()
+        });
+        self.last_runs_path = Some(path);
+    }
+
+    /// Load the history of the files processed, and save each file that finishes to it
+    /// from now on
+    pub fn load_history(&mut self, path: PathBuf) {
+        self.history = History::load_from(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load the history, starting a new one: {}", e);
+            History::default()
+        });
+        self.history_path = Some(path);
+    }
+
+    /// Get the files processed in this and earlier sessions, newest first
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Forget the files processed
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+        self.save_history();
+        self.events.status(tr("status-history-cleared"));
+    }
+
+    /// Save the history, if it was loaded from a file
+    fn save_history(&self) {
+        if let Some(path) = &self.history_path {
+            if let Err(e) = self.history.save_to(path) {
+                eprintln!("Failed to save the history: {}", e);
+            }
+        }
+    }
+
+    /// Summarize the choices the selected files would be encrypted or decrypted with
+    ///
+    /// # Arguments
+    /// * `operation_type` - Encrypt or decrypt
+    /// * `key` - Name and fingerprint of the key, as shown
+    ///
+    /// # Returns
+    /// * `(RunSummary, Vec<RunChange>)` - The summary, and what changed since the last
+    ///   batch of the same operation (nothing for the first batch)
+    pub fn run_summary(&self, operation_type: &FileOperationType, key: &str) -> (RunSummary, Vec<RunChange>) {
+        let encrypt = matches!(operation_type, FileOperationType::Encrypt);
+        let mut summary = RunSummary::new(if encrypt { "Encrypt" } else { "Decrypt" });
+        summary.add("Key", key);
+        summary.add("Backend", if self.backend.use_embedded {
+            format!("Embedded device {}", self.backend.device_id.trim())
+        } else {
+            "Software".to_string()
+        });
+        summary.add("Destination", match (&self.output_dir, encrypt && self.replace_originals) {
+            (_, true) => "Replace the originals".to_string(),
+            (Some(dir), false) => dir.display().to_string(),
+            (None, false) => "None".to_string(),
+        });
+
+        if encrypt {
+            summary.add("Format", if self.age_format {
+                "age"
+            } else if self.openpgp_format {
+                "OpenPGP"
+            } else {
+                "CRUSTy"
+            });
+            summary.add("Recipients", match self.recipients() {
+                Some(recipients) => format!("{} recipient(s)", recipients.len()),
+                None => "Key only".to_string(),
+            });
+            summary.add_flag("Pack the folder into one archive", self.archives_folder());
+            summary.add_flag("Hide file names", self.hide_file_names);
+            summary.add_flag("Parity", self.add_parity);
+            summary.add_flag("Hash of the original", self.add_digest);
+            summary.add_flag("Signed manifest", self.write_manifest);
+            summary.add_flag("Shred the originals", self.replace_originals && self.shred_originals);
+        }
+
+        let changes = self.last_runs.last(&summary.operation)
+            .map(|previous| summary.changes_from(previous))
+            .unwrap_or_default();
+        (summary, changes)
+    }
+
+    /// Record the summary of a batch that is starting, to compare the next batch with
+    pub fn record_run(&mut self, summary: RunSummary) {
+        self.last_runs.record(summary);
+        if let Some(path) = &self.last_runs_path {
+            if let Err(e) = self.last_runs.save_to(path) {
+                eprintln!("Failed to save the last runs: {}", e);
+            }
+        }
+    }
+
+    /// Get the backend the next operation will use
+    fn selected_backend(&self) -> BackendKind {
+        if self.backend.use_embedded {
+            BackendKind::Embedded
+        } else {
+            BackendKind::Local
+        }
+    }
+
+    /// Estimate how long processing the selected files will take, from earlier operations
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - The estimate, or None if the selected backend has no history
+    pub fn estimated_duration(&self) -> Option<Duration> {
+        let files: Vec<(u64, f32)> = self.selected_file_sizes().into_iter()
+            .map(|size| (size, 0.0))
+            .collect();
+        self.speed_history.estimate(self.selected_backend(), CIPHER_AES_256_GCM, &files)
+    }
+
+    /// Get the total size of the selected files in bytes
+    pub fn selected_size(&self) -> u64 {
+        self.selected_file_sizes().into_iter().sum()
+    }
+
+    /// Get the size of each selected file (0 if it cannot be read)
+    fn selected_file_sizes(&self) -> Vec<u64> {
+        self.selected_files.iter()
+            .map(|file| std::fs::metadata(file).map(|m| m.len()).unwrap_or(0))
+            .collect()
+    }
+
+    /// Estimate the time left in the running operation
+    ///
+    /// The estimate starts from the speeds of earlier operations and moves towards the
+    /// speed measured in this operation as more of it is done.
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - The estimate, or None if no operation is running or there
+    ///   is nothing to base an estimate on yet
+    pub fn time_remaining(&self) -> Option<Duration> {
+        let batch: Vec<&FileEntry> = self.file_entries.iter()
+            .filter(|entry| entry.progress_index.is_some())
+            .collect();
+        if batch.iter().all(|entry| entry.is_finished()) {
+            return None;
+        }
+
+        let files: Vec<(u64, f32)> = batch.iter()
+            .map(|entry| (entry.file_size.unwrap_or(0), entry.fraction_done()))
+            .collect();
+        let total: f64 = files.iter().map(|&(size, _)| size as f64).sum();
+        let done: f64 = files.iter().map(|&(size, fraction)| size as f64 * fraction as f64).sum();
+
+        let historical = self.speed_history.estimate(self.batch_backend, CIPHER_AES_256_GCM, &files);
+        let measured = batch.iter()
+            .filter_map(|entry| entry.started)
+            .min()
+            .map(|started| started.elapsed())
+            .filter(|elapsed| *elapsed >= MIN_MEASURED_TIME && done > 0.0)
+            .map(|elapsed| Duration::from_secs_f64((total - done) * elapsed.as_secs_f64() / done));
+
+        match (historical, measured) {
+            (Some(historical), Some(measured)) => {
+                let fraction = done / total;
+                Some(historical.mul_f64(1.0 - fraction) + measured.mul_f64(fraction))
+            },
+            (historical, measured) => historical.or(measured),
+        }
+    }
+
+    /// Get the selected files
+    pub fn selected_files(&self) -> &[PathBuf] {
+        &self.selected_files
+    }
+
+    /// Get the output directory
+    pub fn output_dir(&self) -> Option<&Path> {
+        self.output_dir.as_deref()
+    }
+
+    /// Get the running (or last requested) operation
+    pub fn operation(&self) -> &FileOperation {
+        &self.operation
+    }
+
+    /// Check whether no operation is running
+    pub fn is_idle(&self) -> bool {
+        matches!(self.operation, FileOperation::None)
+    }
+
+    /// Check whether files and an output directory are selected
+    pub fn is_ready(&self) -> bool {
+        !self.selected_files.is_empty() && self.output_dir.is_some()
+    }
+
+    /// Check whether the selected files can be encrypted (no output directory is needed
+    /// when the originals are replaced)
+    pub fn is_ready_to_encrypt(&self) -> bool {
+        !self.selected_files.is_empty() && (self.output_dir.is_some() || self.replace_originals)
+    }
+
+    /// Get the results of the last operation
+    pub fn results(&self) -> &[String] {
+        &self.operation_results
+    }
+
+    /// Get the groups of files with the same contents in the last batch encrypted
+    pub fn duplicates(&self) -> Vec<Vec<PathBuf>> {
+        self.duplicates.lock().unwrap().clone()
+    }
+
+    /// Get the shared duplicate groups, set by the operation thread after a batch
+    pub fn duplicates_handle(&self) -> Arc<Mutex<Vec<Vec<PathBuf>>>> {
+        self.duplicates.clone()
+    }
+
+    /// Get the fraction of the batch's files compared, while they are checked for
+    /// duplicates before they are encrypted (None otherwise)
+    pub fn duplicate_scan(&self) -> Option<f32> {
+        *self.duplicate_scan.lock().unwrap()
+    }
+
+    /// Get the shared progress of the duplicate check, updated by the operation thread
+    pub fn duplicate_scan_handle(&self) -> Arc<Mutex<Option<f32>>> {
+        self.duplicate_scan.clone()
+    }
+
+    /// Forget the duplicate groups of the last batch
+    pub fn dismiss_duplicates(&mut self) {
+        self.duplicates.lock().unwrap().clear();
+    }
+
+    /// Get the shared progress of each file, updated by the operation thread
+    pub fn progress_handle(&self) -> Arc<Mutex<Vec<f32>>> {
+        self.progress.clone()
+    }
+
+    /// Check whether the last operation is finished, so its report can be exported
+    pub fn report_ready(&self) -> bool {
+        let mut queued = self.file_entries.iter().filter(|entry| entry.progress_index.is_some()).peekable();
+        queued.peek().is_some() && queued.all(FileEntry::is_finished)
+    }
+
+    /// Make the report of the files of the last operation (None while it is running)
+    pub fn batch_report(&self) -> Option<BatchReport> {
+        if !self.report_ready() {
+            return None;
+        }
+        BatchReport::from_entries(self.file_entries.iter().filter(|entry| entry.progress_index.is_some()))
+    }
+
+    /// Save the report of the last operation, as JSON or CSV by the file's extension
+    pub fn save_report(&mut self, path: &Path) {
+        let Some(report) = self.batch_report() else {
+            self.events.error(tr("error-operation-unfinished"));
+            return;
+        };
+        let (succeeded, failed) = report.counts();
+        match report.save(path) {
+            Ok(()) => {
+                log_success(
+                    "Export Report",
+                    &path.to_string_lossy(),
+                    &format!("Report of {} file(s) ({} succeeded, {} failed) saved", report.files.len(), succeeded, failed),
+                );
+                self.events.status(tr_args("status-report-saved", &[("path", path.display().to_string().into())]));
+            },
+            Err(e) => {
+                log_error("Export Report", &path.to_string_lossy(), &e.to_string());
+                self.events.error(tr_args("error-report-save", &[("error", e.to_string().into())]));
+            },
+        }
+    }
+
+    /// Get the shared result of each file, reported by the operation thread
+    pub fn results_handle(&self) -> FileResults {
+        self.file_results.clone()
+    }
+
+    /// Get the shared progress of the embedded device, updated by the embedded backend
+    pub fn device_progress_handle(&self) -> Arc<Mutex<Option<DeviceProgress>>> {
+        self.device_progress.clone()
+    }
+
+    /// Get the progress of the job running on the embedded device (None when the device
+    /// is not running one)
+    pub fn device_progress(&self) -> Option<DeviceProgress> {
+        *self.device_progress.lock().unwrap()
+    }
+
+    /// Get the handle the operation thread reports a failed device connection through
+    pub fn device_error_handle(&self) -> Arc<Mutex<Option<String>>> {
+        self.device_error.clone()
+    }
+
+    /// Get the pause control of the running operation, used by the operation thread
+    pub fn pause_handle(&self) -> PauseControl {
+        self.pause.clone()
+    }
+
+    /// Pause the running operation at its next progress report (after a file is read,
+    /// or after a file is finished)
+    pub fn pause(&mut self) {
+        if self.overall_progress().is_some() {
+            self.pause.pause();
+            self.paused_this_run = true;
+            self.events.status(tr("status-pausing"));
+        }
+    }
+
+    /// Resume the paused operation
+    pub fn resume(&mut self) {
+        if self.pause.is_paused() {
+            self.pause.resume();
+            self.events.status(tr("status-resumed"));
+        }
+    }
+
+    /// Check whether the running operation is paused
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_paused()
+    }
+
+    /// Get the progress of each file in the running operation (empty when none is running)
+    pub fn file_progress(&self) -> Vec<f32> {
+        self.progress.lock().unwrap().clone()
+    }
+
+    /// Get the overall progress of the running operation (None when none is running)
+    pub fn overall_progress(&self) -> Option<f32> {
+        let progress = self.progress.lock().unwrap();
+        if progress.is_empty() {
+            None
+        } else {
+            Some(progress.iter().sum::<f32>() / progress.len() as f32)
+        }
+    }
+
+    /// Check whether the policy requires a reason for decryption that has not been entered
+    pub fn decrypt_reason_missing(&self) -> bool {
+        get_policy().require_decrypt_reason && self.decrypt_reason.trim().is_empty()
+    }
+
+    /// Replace the selected files
+    pub fn select_files(&mut self, files: Vec<PathBuf>) {
+        self.selected_files = files;
+        self.selected_folder = None;
+        self.events.status(tr_args("status-files-selected", &[("count", self.selected_files.len().into())]));
+    }
+
+    /// Replace the selected files with every file in a folder and its subfolders
+    ///
+    /// Only encrypted files are selected for decryption. The files are saved in the same
+    /// subfolders under the output directory, inside a folder named like the selected one.
+    pub fn select_folder(&mut self, folder: PathBuf, operation_type: FileOperationType) {
+        let mut files = match secured_folders::folder_files(&folder) {
+            Ok(files) => files,
+            Err(e) => {
+                self.events.error(tr_args("error-read-path", &[("path", folder.display().to_string().into()), ("error", e.to_string().into())]));
+                return;
+            },
+        };
+        if operation_type == FileOperationType::Decrypt {
+            files.retain(|file| file_names::is_encrypted_name(file)
+                || file.extension().is_some_and(|extension| extension == file_names::AGE_EXTENSION)
+                || file_names::is_openpgp_name(file));
+        }
+        if files.is_empty() {
+            let id = match operation_type {
+                FileOperationType::Decrypt => "error-no-files-to-decrypt",
+                _ => "error-no-files-to-process",
+            };
+            self.events.error(tr_args(id, &[("folder", folder.display().to_string().into())]));
+            return;
+        }
+
+        // The files of each subfolder together, as they are listed and processed
+        files.sort_by(|a, b| (a.parent(), a.file_name()).cmp(&(b.parent(), b.file_name())));
+        self.events.status(tr_args(
+            "status-folder-files-selected",
+            &[("count", files.len().into()), ("folder", folder.display().to_string().into())],
+        ));
+        self.selected_files = files;
+        self.selected_folder = Some(folder);
+    }
+
+    /// Get the path of a selected file as it is shown and saved under the output
+    /// directory: from the selected folder's name down ("Photos/2020/beach.jpg"), or the
+    /// file name for files selected on their own
+    pub fn relative_path(&self, file: &Path) -> PathBuf {
+        self.selected_folder.as_deref()
+            .filter(|folder| file.starts_with(folder))
+            .and_then(|folder| file.strip_prefix(folder.parent().unwrap_or(folder)).ok())
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from(file.file_name().unwrap_or_default()))
+    }
+
+    /// Get the subfolder of the output directory a selected file is saved in (empty for
+    /// files selected on their own)
+    pub fn output_subdir(&self, file: &Path) -> PathBuf {
+        self.relative_path(file).parent().map(Path::to_path_buf).unwrap_or_default()
+    }
+
+    /// Remove a file from the selection
+    pub fn remove_selected_file(&mut self, index: usize) {
+        if index >= self.selected_files.len() {
+            return;
+        }
+
+        self.selected_files.remove(index);
+        if self.selected_files.is_empty() {
+            self.events.status(tr("status-files-removed"));
+        } else {
+            self.events.status(tr_args("status-file-removed", &[("count", self.selected_files.len().into())]));
+        }
+    }
+
+    /// Clear the selected files
+    pub fn clear_selected_files(&mut self) {
+        self.selected_files.clear();
+        self.selected_folder = None;
+        self.events.status(tr("status-files-cleared"));
+    }
+
+    /// Set the output directory
+    ///
+    /// A directory that cannot be written to is reported right away; operations do not
+    /// start until another directory is selected or the problem is fixed.
+    pub fn set_output_dir(&mut self, dir: PathBuf) {
+        self.events.status(tr_args("status-output-dir", &[("folder", dir.display().to_string().into())]));
+        if let Err(e) = encryption::check_output_dir(&dir) {
+            self.events.error(e.to_string());
+        }
+        self.output_dir = Some(dir);
+    }
+
+    /// Check that files can still be created in the output directory
+    ///
+    /// The directory is checked again before every operation, since it may have become
+    /// read-only (or been removed) since it was selected.
+    ///
+    /// # Returns
+    /// * `bool` - Whether the directory is writable (an error event is raised if not)
+    pub fn output_dir_writable(&mut self) -> bool {
+        let dir = match &self.output_dir {
+            Some(dir) => dir,
+            None => return false,
+        };
+
+        match encryption::check_output_dir(dir) {
+            Ok(()) => true,
+            Err(e) => {
+                self.events.error(e.to_string());
+                false
+            },
+        }
+    }
+
+    /// Check that the encrypted files can be created: in the output directory, or in the
+    /// folder of each original when the originals are replaced
+    ///
+    /// # Returns
+    /// * `bool` - Whether every folder is writable (an error event is raised if not)
+    pub fn encrypt_destination_writable(&mut self) -> bool {
+        if !self.replace_originals {
+            return self.output_dir_writable();
+        }
+
+        let dirs: BTreeSet<PathBuf> = self.selected_files.iter()
+            .map(|file| in_place::original_dir(file))
+            .collect();
+        for dir in dirs {
+            if encryption::check_output_dir(&dir).is_err() {
+                self.events.error(tr_args("error-replace-folder", &[("folder", dir.display().to_string().into())]));
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Set the operation without queuing files (e.g. when a workflow starts)
+    pub fn set_operation(&mut self, operation: FileOperation) {
+        self.operation = operation;
+    }
+
+    /// Stop the running operation
+    pub fn stop(&mut self) {
+        self.operation = FileOperation::None;
+        self.events.status(tr("status-operation-stopped"));
+    }
+
+    /// Get the folder the selected files were selected from (None for files selected on
+    /// their own)
+    pub fn selected_folder(&self) -> Option<&Path> {
+        self.selected_folder.as_deref()
+    }
+
+    /// Check whether the selected folder is packed into an archive when encrypted
+    pub fn archives_folder(&self) -> bool {
+        self.archive_folder && self.selected_folder.is_some()
+    }
+
+    /// Check that the archive options fit the other options of the operation
+    ///
+    /// Archives are made and extracted on this computer and saved in the output
+    /// directory, and archives are decrypted on their own (not with other files).
+    ///
+    /// # Returns
+    /// * `bool` - Whether the operation can start (an error event is raised if not)
+    pub fn archive_options_valid(&mut self, operation_type: FileOperationType) -> bool {
+        let archives = match operation_type {
+            FileOperationType::Decrypt => {
+                let count = self.selected_files.iter().filter(|file| archive::is_archive(file)).count();
+                if count > 0 && count < self.selected_files.len() {
+                    self.events.error(tr("error-archives-separately"));
+                    return false;
+                }
+                count > 0
+            },
+            _ => {
+                if self.archives_folder() && self.replace_originals {
+                    self.events.error(tr("error-archive-replace"));
+                    return false;
+                }
+                self.archives_folder()
+            },
+        };
+
+        if archives && self.backend.use_embedded {
+            self.events.error(tr("error-archive-embedded"));
+            return false;
+        }
+        true
+    }
+
+    /// Get the recipients the next encryption is for (None when not encrypting for
+    /// recipients, or when the list is not valid: `recipient_options_valid` says why)
+    pub fn recipients(&self) -> Option<Vec<Recipient>> {
+        self.use_recipient.then(|| recipients::parse_list(&self.recipient_list).ok()).flatten()
+    }
+
+    /// Check the recipients of the next encryption
+    ///
+    /// Files for several recipients are encrypted on this computer in CRUSTy's format,
+    /// one file at a time.
+    ///
+    /// # Returns
+    /// * `bool` - Whether the encryption can start (an error event is raised if not)
+    pub fn recipient_options_valid(&mut self) -> bool {
+        if !self.use_recipient {
+            return true;
+        }
+
+        let error = if let Err(e) = recipients::parse_list(&self.recipient_list) {
+            e.to_string()
+        } else if self.age_format || self.openpgp_format {
+            tr("error-recipients-format")
+        } else if self.archives_folder() {
+            tr("error-recipients-archive")
+        } else if self.backend.use_embedded {
+            tr("error-recipients-embedded")
+        } else {
+            return true;
+        };
+        self.events.error(error);
+        false
+    }
+
+    /// Queue the selected files for encryption (or for packing into an archive)
+    pub fn begin_encrypt(&mut self) {
+        if self.archives_folder() {
+            self.operation = FileOperation::Archive;
+            self.queue_selected_files(FileOperationType::Encrypt);
+            self.events.status(tr("status-packing-folder"));
+            return;
+        }
+
+        self.operation = if self.selected_files.len() > 1 { FileOperation::BatchEncrypt } else { FileOperation::Encrypt };
+        self.queue_selected_files(FileOperationType::Encrypt);
+        self.events.status(tr("status-encrypt-started"));
+    }
+
+    /// Queue the selected files for decryption
+    ///
+    /// The decryption reason applies to this operation only and is cleared.
+    pub fn begin_decrypt(&mut self) {
+        let archives = !self.selected_files.is_empty()
+            && self.selected_files.iter().all(|file| archive::is_archive(file));
+        self.operation = if archives {
+            FileOperation::ExtractArchive
+        } else if self.selected_files.len() > 1 {
+            FileOperation::BatchDecrypt
+        } else {
+            FileOperation::Decrypt
+        };
+        self.queue_selected_files(FileOperationType::Decrypt);
+        self.decrypt_reason.clear();
+        self.events.status(tr(if archives { "status-extract-started" } else { "status-decrypt-started" }));
+    }
+
+    /// Add the selected files to the file list
+    ///
+    /// Only the files queued here follow the progress of the new operation.
+    fn queue_selected_files(&mut self, operation_type: FileOperationType) {
+        for entry in &mut self.file_entries {
+            entry.progress_index = None;
+        }
+        self.batch_backend = self.selected_backend();
+
+        for (index, file) in self.selected_files.iter().enumerate() {
+            let mut entry = FileEntry::new(file.clone(), operation_type.clone());
+            entry.progress_index = Some(index);
+            if self.selected_folder.is_some() {
+                entry.display_name = Some(self.relative_path(file).to_string_lossy().into_owned());
+            }
+            entry.output_folder = self.output_folder(file, &operation_type);
+            self.file_entries.push(entry);
+        }
+
+        // An archive keeps its folder's tree, and each archive extracts to its own folder
+        if !matches!(self.operation, FileOperation::Archive | FileOperation::ExtractArchive) {
+            self.warn_colliding_outputs(&operation_type);
+        }
+    }
+
+    /// Get the folder a selected file's output is saved in (None without an output
+    /// directory)
+    fn output_folder(&self, file: &Path, operation_type: &FileOperationType) -> Option<PathBuf> {
+        if *operation_type == FileOperationType::Encrypt && self.replace_originals {
+            return file.parent().map(Path::to_path_buf);
+        }
+        let output_dir = self.output_dir.as_ref()?;
+        Some(match self.operation {
+            FileOperation::Archive | FileOperation::ExtractArchive => output_dir.clone(),
+            _ => output_dir.join(self.output_subdir(file)),
+        })
+    }
+
+    /// Warn about selected files whose outputs would be the same file
+    ///
+    /// Names that differ only in Unicode normalization or case are one file on APFS
+    /// and NTFS, so the later file would fail with "Destination file already exists".
+    /// Hidden (random) names for encrypted files cannot collide.
+    fn warn_colliding_outputs(&mut self, operation_type: &FileOperationType) {
+        if self.hide_file_names && *operation_type != FileOperationType::Decrypt {
+            return;
+        }
+
+        let output_names: Vec<OsString> = self.selected_files.iter()
+            .map(|file| {
+                let name = match operation_type {
+                    FileOperationType::Decrypt => file_names::decrypted_name(file).map(|output| output.name),
+                    _ => file_names::encrypted_name(file),
+                }.unwrap_or_default();
+                self.output_subdir(file).join(name).into_os_string()
+            })
+            .collect();
+
+        for (first, second) in file_names::colliding_names(&output_names) {
+            self.events.error(tr_args("error-outputs-collide", &[
+                ("first", self.selected_files[first].display().to_string().into()),
+                ("second", self.selected_files[second].display().to_string().into()),
+                ("output", output_names[first].to_string_lossy().into_owned().into()),
+            ]));
+        }
+    }
+
+    /// Update the file list from the progress and results reported by the operation thread
+    ///
+    /// A file starts its timer when it first reports progress and stops it when its
+    /// result is reported: it is then completed or failed with its result's message,
+    /// which is also added to the results of the operation. Times of an operation that
+    /// was paused are not added to the speed history. Files that succeeded are added to
+    /// the history. A failed connection to the embedded device is shown as an error.
+    ///
+    /// # Returns
+    /// * `bool` - Whether any file in the list is still being processed
+    pub fn sync_file_progress(&mut self) -> bool {
+        let progress = self.file_progress();
+        let file_results = self.file_results.lock().unwrap().clone();
+        let mut running = false;
+        let mut measured = false;
+        let mut finished = false;
+        let paused = self.paused_this_run;
+
+        if let Some(error) = self.device_error.lock().unwrap().take() {
+            self.events.error(error);
+        }
+
+        for entry in &mut self.file_entries {
+            if entry.is_finished() {
+                continue;
+            }
+
+            let Some(index) = entry.progress_index else { continue };
+            let file_result = file_results.get(index).cloned().flatten();
+            if let Some(file_result) = &file_result {
+                entry.output_path = file_result.output.clone();
+                entry.key_fingerprint = Some(file_result.key_fingerprint.clone());
+            }
+            match (file_result.map(|file_result| file_result.outcome), progress.get(index)) {
+                (Some(Err(error)), _) => {
+                    self.operation_results.push(error.clone());
+                    entry.set_failed(error);
+                },
+                (Some(Ok(message)), _) => {
+                    // Only files seen in progress were timed; others finished between frames
+                    let timed = matches!(entry.status, FileStatus::InProgress(_));
+                    self.operation_results.push(message.clone());
+                    entry.set_completed(message);
+                    let operation = match entry.operation_type {
+                        FileOperationType::Encrypt => Some(HistoryOperation::Encrypt),
+                        FileOperationType::Decrypt => Some(HistoryOperation::Decrypt),
+                        FileOperationType::None => None,
+                    };
+                    if let Some(operation) = operation {
+                        self.history.record(HistoryEntry::new(operation, entry.path.clone(), entry.output_folder.clone()));
+                        finished = true;
+                    }
+
+                    if let (true, Some(size), Some(duration)) = (timed && !paused, entry.file_size, entry.elapsed_time()) {
+                        self.speed_history.record(self.batch_backend, CIPHER_AES_256_GCM, size, duration);
+                        measured = true;
+                    }
+                },
+                // Done files wait at full progress for their result
+                (None, Some(&p)) if p > 0.0 => {
+                    entry.set_progress(p.min(1.0));
+                    running = true;
+                },
+                _ => {},
+            }
+        }
+
+        if let (true, Some(path)) = (measured, &self.speed_history_path) {
+            if let Err(e) = self.speed_history.save_to(path) {
+                eprintln!("Failed to save speed history: {}", e);
+            }
+        }
+
+        if finished {
+            self.save_history();
+        }
+
+        running
+    }
+
+    /// Get the backend of the running (or last) operation
+    pub fn running_backend(&self) -> BackendKind {
+        self.batch_backend
+    }
+
+    /// Check the running operation for progress, and stop it if it made none for the
+    /// timeout (the watchdog; paused operations are not stopped)
+    ///
+    /// The operation thread is stuck in a backend call that cannot be interrupted, so it
+    /// is left behind: its progress is no longer followed, its unfinished files are
+    /// failed, and the next operation can start.
+    ///
+    /// # Arguments
+    /// * `timeout` - Time without progress before the operation is stopped (zero to
+    ///   never stop it)
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - Time left before the operation is stopped if it makes no
+    ///   progress (None when no operation is watched)
+    pub fn check_stalled(&mut self, timeout: Duration) -> Option<Duration> {
+        let progress = self.file_progress();
+        if progress.is_empty() || timeout.is_zero() {
+            self.last_activity = None;
+            return None;
+        }
+
+        let now = Instant::now();
+        let seen = (progress, self.device_progress(), self.duplicate_scan());
+        match &self.last_activity {
+            Some((last, since)) if *last == seen && !self.is_paused() => {
+                let stalled_for = now.duration_since(*since);
+                if stalled_for < timeout {
+                    return Some(timeout - stalled_for);
+                }
+            },
+            _ => {
+                self.last_activity = Some((seen, now));
+                return Some(timeout);
+            },
+        }
+
+        self.stop_stalled(timeout);
+        None
+    }
+
+    /// Fail the unfinished files of a stalled operation, log why, and stop following it
+    fn stop_stalled(&mut self, timeout: Duration) {
+        let progress = self.file_progress();
+        let device = self.device_progress();
+        let backend = match self.batch_backend {
+            BackendKind::Local => "software encryption".to_string(),
+            BackendKind::Embedded => format!("the embedded device '{}' ({:?})", self.backend.device_id.trim(), self.backend.connection_type),
+        };
+        let stalled = progress.iter().position(|&p| p > 0.0 && p < 1.0)
+            .or_else(|| progress.iter().position(|&p| p < 1.0));
+
+        let mut failed = 0;
+        for entry in &mut self.file_entries {
+            let index = match entry.progress_index {
+                Some(index) if !entry.is_finished() && progress.get(index).is_some_and(|&p| p < 1.0) => index,
+                _ => continue,
+            };
+
+            let reason = if Some(index) == stalled {
+                let mut diagnostics = format!(
+                    "No progress for {} s with {} (stopped at {:.0}%",
+                    timeout.as_secs(), backend, progress[index] * 100.0
+                );
+                if let Some(device) = device {
+                    diagnostics.push_str(&format!(
+                        "; device processed {}/{} chunks, {} queued",
+                        device.chunks_done, device.chunks_total, device.queue_depth
+                    ));
+                }
+                diagnostics.push(')');
+                diagnostics
+            } else {
+                "Not processed: the operation stalled on an earlier file".to_string()
+            };
+            log_error("Watchdog", &entry.path.to_string_lossy(), &reason);
+            entry.set_failed(format!("Timed out: {}", reason));
+            failed += 1;
+        }
+
+        // The stuck operation thread keeps the old handles; new operations get new ones
+        self.progress = Arc::new(Mutex::new(Vec::new()));
+        self.file_results = Arc::new(Mutex::new(Vec::new()));
+        self.device_progress = Arc::new(Mutex::new(None));
+        self.duplicate_scan = Arc::new(Mutex::new(None));
+        self.pause = PauseControl::default();
+        self.last_activity = None;
+
+        self.events.error(tr_args("error-operation-stalled", &[
+            ("seconds", timeout.as_secs().into()),
+            ("backend", backend.into()),
+            ("failed", failed.into()),
+        ]));
+    }
+
+    /// Get the file list for editing
+    pub fn file_entries_mut(&mut self) -> &mut Vec<FileEntry> {
+        &mut self.file_entries
+    }
+
+    /// Reset the progress and results for a new operation on the selected files
+    pub fn reset_progress(&mut self) {
+        {
+            let mut progress = self.progress.lock().unwrap();
+            progress.clear();
+            progress.resize(self.selected_files.len(), 0.0);
+        }
+        {
+            let mut file_results = self.file_results.lock().unwrap();
+            file_results.clear();
+            file_results.resize(self.selected_files.len(), None);
+        }
+        *self.device_progress.lock().unwrap() = None;
+        self.operation_results.clear();
+        self.duplicates.lock().unwrap().clear();
+        *self.duplicate_scan.lock().unwrap() = None;
+        // A new operation starts running, even if the last one was left paused
+        self.pause.resume();
+        self.paused_this_run = false;
+    }
+
+    /// Check the files listed in a batch manifest in the background
+    pub fn check_manifest(&mut self, manifest_path: PathBuf, key: EncryptionKey) {
+        if self.manifest_job.is_some() {
+            self.events.error(tr("error-manifest-running"));
+            return;
+        }
+        self.manifest_check = None;
+        self.manifest_job = Some(thread::spawn(move || batch_manifest::check_manifest(&manifest_path, &key)));
+        self.events.status(tr("status-manifest-checking"));
+    }
+
+    /// Check whether the manifest check has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether the manifest is still being checked
+    pub fn poll_manifest_check(&mut self) -> bool {
+        match &self.manifest_job {
+            Some(job) if !job.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        match self.manifest_job.take().unwrap().join() {
+            Ok(Ok(check)) => {
+                let path = check.manifest_path.to_string_lossy();
+                let (intact, changed, missing) = (
+                    check.count(&EntryStatus::Intact),
+                    check.count(&EntryStatus::Changed),
+                    check.count(&EntryStatus::Missing),
+                );
+                let summary = format!("{} intact, {} changed, {} missing", intact, changed, missing);
+                if check.is_intact() {
+                    log_key_success("Check Manifest", &path, &summary, &check.manifest.key_fingerprint);
+                    self.events.status(tr_args("status-manifest-intact", &[("count", check.files.len().into())]));
+                } else {
+                    log_error("Check Manifest", &path, &summary);
+                    self.events.error(tr_args(
+                        "error-manifest-mismatch",
+                        &[("intact", intact.into()), ("changed", changed.into()), ("missing", missing.into())],
+                    ));
+                }
+                self.manifest_check = Some(check);
+            },
+            Ok(Err(e)) => self.events.error(tr_args("error-manifest-check", &[("error", e.to_string().into())])),
+            Err(_) => self.events.error(tr("error-manifest-stopped")),
+        }
+        false
+    }
+
+    /// Check whether a manifest is being checked
+    pub fn is_checking_manifest(&self) -> bool {
+        self.manifest_job.is_some()
+    }
+
+    /// Get the result of the last manifest check
+    pub fn manifest_check(&self) -> Option<&ManifestCheck> {
+        self.manifest_check.as_ref()
+    }
+
+    /// Connect to the embedded device in the background, check that it answers, and ask
+    /// what it runs and has free
+    pub fn test_connection(&mut self) {
+        if self.connection_test.is_some() {
+            return;
+        }
+        let config = self.backend.config();
+        if let Err(e) = config.validate() {
+            self.events.error(e.to_string());
+            return;
+        }
+        self.device_health = None;
+        self.connection_test = Some(thread::spawn(move || {
+            match BackendFactory::create_embedded(config, Arc::new(Mutex::new(None))) {
+                Backend::Embedded(mut backend) => {
+                    backend.connect()?;
+                    backend.health_check()
+                },
+                Backend::Local(_) => unreachable!(),
+            }
+        }));
+        self.events.status(tr("status-connection-testing"));
+    }
+
+    /// Check whether the connection test has finished, and report the result if so
+    ///
+    /// # Returns
+    /// * `bool` - Whether the connection is still being tested
+    pub fn poll_connection_test(&mut self) -> bool {
+        match &self.connection_test {
+            Some(job) if !job.is_finished() => return true,
+            Some(_) => {},
+            None => return false,
+        }
+
+        match self.connection_test.take().unwrap().join() {
+            Ok(Ok(health)) => {
+                let summary = format!(
+                    "The device answered in {} ms (firmware {})",
+                    health.round_trip.as_millis(),
+                    health.info.firmware_version
+                );
+                log_success("Test Connection", self.backend.device_id.trim(), &summary);
+                self.events.status(tr_args("status-device-answered", &[
+                    ("ms", health.round_trip.as_millis().to_string().into()),
+                    ("firmware", health.info.firmware_version.as_str().into()),
+                ]));
+                self.device_health = Some(health);
+            },
+            Ok(Err(e)) => {
+                log_error("Test Connection", self.backend.device_id.trim(), &e.to_string());
+                self.events.error(e.to_string());
+            },
+            Err(_) => self.events.error(tr("error-connection-stopped")),
+        }
+        false
+    }
+
+    /// Check whether the connection to the device is being tested
+    pub fn is_testing_connection(&self) -> bool {
+        self.connection_test.is_some()
+    }
+
+    /// Get what the device reported when its connection was last tested (None if the
+    /// last test failed)
+    pub fn device_health(&self) -> Option<&DeviceHealth> {
+        self.device_health.as_ref()
+    }
+}
+
+impl Default for OperationService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::{Logger, PathRedaction};
+    use crate::start_operation::FileResult;
+
+    /// Result of a file as the operation thread reports it
+    fn file_result(outcome: Result<&str, &str>) -> Option<FileResult> {
+        Some(FileResult {
+            outcome: outcome.map(str::to_string).map_err(str::to_string),
+            output: None,
+            key_fingerprint: "0123456789abcdef".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_queue_and_progress() {
+        let mut operations = OperationService::new();
+        operations.select_files(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+        assert!(!operations.is_ready());
+
+        operations.set_output_dir(PathBuf::from("out"));
+        assert!(operations.is_ready());
+
+        // Several files are encrypted as a batch
+        operations.begin_encrypt();
+        assert!(matches!(operations.operation(), FileOperation::BatchEncrypt));
+        assert_eq!(operations.file_entries.len(), 2);
+        assert_eq!(operations.take_events().last(), Some(&ServiceEvent::Status("Starting encryption...".to_string())));
+
+        // Progress is reported by the operation thread through the shared handle
+        assert_eq!(operations.overall_progress(), None);
+        operations.reset_progress();
+        operations.progress_handle().lock().unwrap()[0] = 1.0;
+        assert_eq!(operations.overall_progress(), Some(0.5));
+    }
+
+    #[test]
+    fn test_output_dir_checks() {
+        let mut operations = OperationService::new();
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+
+        // Reported when selected, and again before an operation starts
+        operations.set_output_dir(missing.clone());
+        let expected = ServiceEvent::Error(format!(
+            "The output folder {} does not exist. Choose another output folder.", missing.display()
+        ));
+        assert_eq!(operations.take_events().last(), Some(&expected));
+        assert!(!operations.output_dir_writable());
+        assert_eq!(operations.take_events(), vec![expected]);
+
+        operations.set_output_dir(dir.path().to_path_buf());
+        assert!(operations.output_dir_writable());
+    }
+
+    #[test]
+    fn test_replace_originals() {
+        let mut operations = OperationService::new();
+        let dir = tempfile::tempdir().unwrap();
+        operations.select_files(vec![dir.path().join("a.txt")]);
+        assert!(!operations.is_ready_to_encrypt());
+
+        // No output directory is needed; the originals' folder must be writable
+        operations.replace_originals = true;
+        assert!(operations.is_ready_to_encrypt());
+        assert!(!operations.is_ready());
+        assert!(operations.encrypt_destination_writable());
+
+        operations.select_files(vec![dir.path().join("missing").join("a.txt")]);
+        assert!(!operations.encrypt_destination_writable());
+        assert!(matches!(operations.take_events().last(), Some(ServiceEvent::Error(_))));
+    }
+
+    #[test]
+    fn test_device_connection_error() {
+        let mut operations = OperationService::new();
+        operations.select_files(vec![PathBuf::from("a.txt")]);
+        operations.begin_encrypt();
+        operations.reset_progress();
+        operations.take_events();
+
+        let error = "Embedded device error: Could not connect to 10.0.0.2:5683: connection refused";
+        *operations.device_error_handle().lock().unwrap() = Some(error.to_string());
+        operations.sync_file_progress();
+        assert_eq!(operations.take_events(), vec![ServiceEvent::Error(error.to_string())]);
+
+        // Shown once
+        operations.sync_file_progress();
+        assert!(operations.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_connection_test() {
+        let mut operations = OperationService::new();
+        operations.backend.use_embedded = true;
+        operations.backend.connection_type = ConnectionType::Ethernet;
+
+        // Settings that cannot work are not tried
+        operations.test_connection();
+        assert!(!operations.is_testing_connection());
+        assert!(matches!(operations.take_events().last(), Some(ServiceEvent::Error(_))));
+
+        // Nothing listens on the port, so the device cannot be reached
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        operations.backend.device_id = "crusty-01".to_string();
+        operations.backend.ethernet.host = "127.0.0.1".to_string();
+        operations.backend.ethernet.port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        operations.test_connection();
+        assert!(operations.is_testing_connection());
+        while operations.poll_connection_test() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let events = operations.take_events();
+        assert!(matches!(events.last(), Some(ServiceEvent::Error(e)) if e.contains("Could not connect")));
+        assert!(operations.device_health().is_none());
+    }
+
+    #[test]
+    fn test_file_timing() {
+        let mut operations = OperationService::new();
+        operations.select_files(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+        operations.begin_encrypt();
+        operations.reset_progress();
+
+        // Nothing has reported progress yet
+        assert!(!operations.sync_file_progress());
+        assert_eq!(operations.file_entries[0].elapsed_time(), None);
+
+        operations.progress_handle().lock().unwrap()[0] = 0.5;
+        assert!(operations.sync_file_progress());
+        assert!(operations.file_entries[0].started.is_some());
+        assert_eq!(operations.file_entries[1].started, None);
+
+        // A done file waits for its result; the total stays fixed once it is reported
+        operations.progress_handle().lock().unwrap()[0] = 1.0;
+        assert!(operations.sync_file_progress());
+        operations.results_handle().lock().unwrap()[0] = file_result(Ok("Successfully encrypted: a.txt"));
+        assert!(!operations.sync_file_progress());
+        let total = operations.file_entries[0].elapsed_time().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(operations.file_entries[0].elapsed_time(), Some(total));
+
+        // Files from the next operation follow its progress, the earlier ones are left alone
+        operations.begin_decrypt();
+        operations.reset_progress();
+        operations.progress_handle().lock().unwrap()[1] = 0.25;
+        assert!(operations.sync_file_progress());
+        assert_eq!(operations.file_entries[1].status, FileStatus::Pending);
+        assert_eq!(operations.file_entries[3].status, FileStatus::InProgress(0.25));
+    }
+
+    #[test]
+    fn test_file_results() {
+        let mut operations = OperationService::new();
+        operations.select_files(vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+        operations.set_output_dir(PathBuf::from("out"));
+        operations.begin_encrypt();
+        operations.reset_progress();
+
+        // Each file's result reaches the file list and the results of the operation
+        let results = operations.results_handle();
+        results.lock().unwrap()[1] = file_result(Err("Failed to encrypt b.txt: Permission denied"));
+        operations.sync_file_progress();
+        results.lock().unwrap()[0] = file_result(Ok("Successfully encrypted: a.txt"));
+        assert!(!operations.sync_file_progress());
+
+        let entries = &operations.file_entries;
+        assert_eq!(entries[0].status, FileStatus::Completed);
+        assert_eq!(entries[0].result.as_deref(), Some("Successfully encrypted: a.txt"));
+        assert_eq!(entries[1].status, FileStatus::Failed);
+        assert_eq!(entries[1].error.as_deref(), Some("Failed to encrypt b.txt: Permission denied"));
+        assert_eq!(operations.results(), ["Failed to encrypt b.txt: Permission denied", "Successfully encrypted: a.txt"]);
+
+        // The finished operation's report lists both files
+        let dir = tempfile::tempdir().unwrap();
+        operations.save_report(&dir.path().join("report.json"));
+        assert_eq!(operations.take_events().last(), Some(&ServiceEvent::Status(format!("Report saved to {}", dir.path().join("report.json").display()))));
+        assert_eq!(operations.batch_report().unwrap().counts(), (1, 1));
+
+        // A new operation starts with no results
+        operations.reset_progress();
+        assert!(operations.results().is_empty());
+        assert!(operations.results_handle().lock().unwrap().iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_stalled_operation_is_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..2).map(|i| dir.path().join(format!("{}.txt", i))).collect();
+        let mut operations = OperationService::new();
+        operations.select_files(files);
+        operations.begin_encrypt();
+        operations.reset_progress();
+        operations.take_events();
+
+        let timeout = Duration::from_millis(50);
+        let stuck = operations.progress_handle();
+        stuck.lock().unwrap()[0] = 0.5;
+        assert_eq!(operations.check_stalled(timeout), Some(timeout));
+
+        // Progress restarts the timeout, and so does the check for duplicates
+        std::thread::sleep(Duration::from_millis(60));
+        stuck.lock().unwrap()[0] = 0.6;
+        assert_eq!(operations.check_stalled(timeout), Some(timeout));
+        std::thread::sleep(Duration::from_millis(60));
+        *operations.duplicate_scan_handle().lock().unwrap() = Some(0.5);
+        assert_eq!(operations.check_stalled(timeout), Some(timeout));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(operations.check_stalled(timeout), None);
+        assert!(operations.overall_progress().is_none());
+        let entries = &operations.file_entries;
+        assert!(entries.iter().all(|entry| entry.status == FileStatus::Failed));
+        assert!(entries[0].error.as_deref().unwrap().contains("No progress for 0 s with software encryption (stopped at 60%)"));
+        assert!(entries[1].error.as_deref().unwrap().contains("Not processed"));
+        assert!(matches!(operations.take_events().as_slice(), [ServiceEvent::Error(_)]));
+
+        // The stuck operation finishing later leaves the failed files alone
+        stuck.lock().unwrap().iter_mut().for_each(|p| *p = 1.0);
+        assert!(!operations.sync_file_progress());
+        assert!(operations.file_entries.iter().all(|entry| entry.status == FileStatus::Failed));
+    }
+
+    #[test]
+    fn test_time_estimates() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..2).map(|i| dir.path().join(format!("{}.txt", i))).collect();
+        for file in &files {
+            std::fs::write(file, vec![0u8; 1000]).unwrap();
+        }
+
+        let mut operations = OperationService::new();
+        operations.load_speed_history(dir.path().join("speed_history.json"));
+        operations.select_files(files);
+        assert_eq!(operations.estimated_duration(), None);
+
+        // 1000 bytes per second measured earlier
+        operations.speed_history.record(BackendKind::Local, CIPHER_AES_256_GCM, 1000, Duration::from_secs(1));
+        assert_eq!(operations.estimated_duration(), Some(Duration::from_secs(2)));
+
+        // Too early to measure this batch, so the history is used for what is left
+        operations.begin_encrypt();
+        operations.reset_progress();
+        operations.progress_handle().lock().unwrap()[0] = 0.5;
+        operations.sync_file_progress();
+        assert_eq!(operations.time_remaining(), Some(Duration::from_millis(1500)));
+
+        // Finished files are added to the saved history
+        operations.progress_handle().lock().unwrap()[0] = 1.0;
+        operations.results_handle().lock().unwrap()[0] = file_result(Ok("Successfully encrypted"));
+        operations.sync_file_progress();
+        let saved = SpeedHistory::load_from(&dir.path().join("speed_history.json")).unwrap();
+        assert_eq!(saved.records[0].samples, 2);
+    }
+
+    #[test]
+    fn test_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = (0..2).map(|i| dir.path().join(format!("{}.txt", i))).collect();
+        let output_dir = dir.path().join("out");
+        let mut operations = OperationService::new();
+        operations.load_history(dir.path().join("history.json"));
+        operations.select_files(files.clone());
+        operations.set_output_dir(output_dir.clone());
+        operations.begin_encrypt();
+        operations.reset_progress();
+
+        // Only files that succeeded are added, and saved as they finish
+        operations.progress_handle().lock().unwrap()[1] = 1.0;
+        operations.results_handle().lock().unwrap()[1] = file_result(Ok("Successfully encrypted"));
+        operations.results_handle().lock().unwrap()[0] = file_result(Err("Failed to encrypt"));
+        operations.sync_file_progress();
+        let saved = History::load_from(&dir.path().join("history.json")).unwrap();
+        assert_eq!(saved.entries.len(), 1);
+        assert_eq!(saved.entries[0].operation, HistoryOperation::Encrypt);
+        assert_eq!(saved.entries[0].input, files[1]);
+        assert_eq!(saved.entries[0].output_folder.as_deref(), Some(output_dir.as_path()));
+
+        operations.clear_history();
+        assert!(History::load_from(&dir.path().join("history.json")).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_connection_settings() {
+        let mut backend = BackendOptions::default();
+        assert_eq!(backend.validate(), Ok(()));
+
+        // The embedded device needs an ID, then settings valid for the connection type
+        backend.use_embedded = true;
+        assert_eq!(backend.validate(), Err(ConnectionSettingsError::MissingDeviceId));
+
+        backend.device_id = "crusty-01".to_string();
+        backend.connection_type = ConnectionType::Ethernet;
+        assert_eq!(backend.validate(), Err(ConnectionSettingsError::MissingHost));
+
+        backend.ethernet.host = "192.168.1.30".to_string();
+        assert_eq!(backend.validate(), Ok(()));
+
+        // Settings for the other connection types are kept while they are not used
+        backend.serial.baud_rate = 0;
+        assert_eq!(backend.validate(), Ok(()));
+        backend.connection_type = ConnectionType::Serial;
+        assert_eq!(backend.validate(), Err(ConnectionSettingsError::InvalidBaudRate(0)));
+        assert_eq!(backend.config().connection.connection_type(), ConnectionType::Serial);
+
+        // A saved configuration comes back as it was saved
+        let mut restored = BackendOptions::default();
+        restored.serial.baud_rate = 9_600;
+        restored.set_config(&backend.config());
+        assert_eq!(restored.config(), backend.config());
+        restored.connection_type = ConnectionType::Ethernet;
+        assert_eq!(restored.ethernet.host, "");
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let inputs: Vec<PathBuf> = (0..3).map(|i| dir.path().join(format!("{}.bin", i))).collect();
+        for input in &inputs {
+            std::fs::write(input, vec![7u8; 1024 * 1024]).unwrap();
+        }
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+
+        let mut operations = OperationService::new();
+        operations.select_files(inputs);
+        operations.set_output_dir(output_dir.clone());
+        operations.set_operation(FileOperation::BatchEncrypt);
+        crate::start_operation::start_operation(&mut operations, encryption::EncryptionKey::generate());
+        operations.pause();
+        assert!(operations.is_paused());
+
+        // The operation stops at its next progress report, before the last file
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(!output_dir.join("2.bin.encrypted").exists());
+        assert!(operations.overall_progress().is_some_and(|p| p < 1.0));
+
+        operations.resume();
+        let started = std::time::Instant::now();
+        while operations.overall_progress().is_some() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(std::fs::read_dir(&output_dir).unwrap().count(), 3);
+    }
+
+    fn wait_for_operation(operations: &OperationService) {
+        let started = std::time::Instant::now();
+        while operations.overall_progress().is_some() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_batch_reports_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let inputs: Vec<PathBuf> = ["report.pdf", "notes.txt", "report copy.pdf"].iter().map(|name| dir.path().join(name)).collect();
+        std::fs::write(&inputs[0], b"%PDF quarterly").unwrap();
+        std::fs::write(&inputs[1], b"notes").unwrap();
+        std::fs::write(&inputs[2], b"%PDF quarterly").unwrap();
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+
+        let mut operations = OperationService::new();
+        operations.select_files(inputs.clone());
+        operations.set_output_dir(output_dir);
+        operations.set_operation(FileOperation::BatchEncrypt);
+        crate::start_operation::start_operation(&mut operations, encryption::EncryptionKey::generate());
+        wait_for_operation(&operations);
+        assert_eq!(operations.duplicates(), vec![vec![inputs[0].clone(), inputs[2].clone()]]);
+
+        operations.dismiss_duplicates();
+        assert!(operations.duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_folder_keeps_its_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Photos");
+        std::fs::create_dir_all(folder.join("2020").join("beach")).unwrap();
+        std::fs::write(folder.join("a.jpg"), b"A").unwrap();
+        std::fs::write(folder.join("2020").join("b.jpg"), b"B").unwrap();
+        std::fs::write(folder.join("2020").join("beach").join("c.jpg"), b"C").unwrap();
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        let key = encryption::EncryptionKey::generate();
+
+        let mut operations = OperationService::new();
+        operations.select_folder(folder.clone(), FileOperationType::Encrypt);
+        assert_eq!(operations.selected_files().len(), 3);
+        let nested = folder.join("2020").join("beach").join("c.jpg");
+        assert_eq!(operations.relative_path(&nested), Path::new("Photos/2020/beach/c.jpg"));
+        operations.set_output_dir(output_dir.clone());
+        operations.begin_encrypt();
+        assert!(operations.file_entries.iter().any(|entry| entry.file_name() == "Photos/2020/beach/c.jpg"));
+        crate::start_operation::start_operation(&mut operations, key.clone());
+        wait_for_operation(&operations);
+
+        let encrypted = output_dir.join("Photos");
+        assert!(encrypted.join("a.jpg.encrypted").exists());
+        assert!(encrypted.join("2020").join("b.jpg.encrypted").exists());
+        assert!(encrypted.join("2020").join("beach").join("c.jpg.encrypted").exists());
+
+        // Decrypting the encrypted folder restores the tree
+        std::fs::write(encrypted.join("notes.txt"), b"Not encrypted").unwrap();
+        let restored_dir = dir.path().join("restored");
+        std::fs::create_dir(&restored_dir).unwrap();
+        operations.select_folder(encrypted, FileOperationType::Decrypt);
+        assert_eq!(operations.selected_files().len(), 3);
+        operations.set_output_dir(restored_dir.clone());
+        operations.begin_decrypt();
+        crate::start_operation::start_operation(&mut operations, key);
+        wait_for_operation(&operations);
+        assert_eq!(std::fs::read(restored_dir.join("Photos").join("2020").join("beach").join("c.jpg")).unwrap(), b"C");
+        assert_eq!(std::fs::read(restored_dir.join("Photos").join("a.jpg")).unwrap(), b"A");
+    }
+
+    #[test]
+    fn test_encrypt_for_several_recipients() {
+        let dir = tempfile::tempdir().unwrap();
+        let inputs: Vec<PathBuf> = (0..2).map(|i| dir.path().join(format!("{}.txt", i))).collect();
+        for (i, input) in inputs.iter().enumerate() {
+            std::fs::write(input, format!("Minutes {}", i)).unwrap();
+        }
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        let key = encryption::EncryptionKey::generate();
+        let colleague = encryption::EncryptionKey::generate();
+
+        let mut operations = OperationService::new();
+        operations.select_files(inputs);
+        operations.set_output_dir(output_dir.clone());
+        operations.use_recipient = true;
+        operations.recipient_list = "alice@example.com, not an address".to_string();
+        assert!(!operations.recipient_options_valid());
+        operations.recipient_list = format!("alice@example.com\n{}", crate::age_format::recipient_string(&colleague));
+        assert!(operations.recipient_options_valid());
+
+        // The names cannot be hidden, as the recipients could not read them
+        operations.hide_file_names = true;
+        operations.begin_encrypt();
+        crate::start_operation::start_operation(&mut operations, key.clone());
+        wait_for_operation(&operations);
+        let encrypted = output_dir.join("1.txt.encrypted");
+        assert!(encrypted.exists());
+
+        // Both the sender and the colleague's own key decrypt the file
+        for (i, decrypting_key) in [key, colleague].into_iter().enumerate() {
+            let restored_dir = dir.path().join(format!("restored-{}", i));
+            std::fs::create_dir(&restored_dir).unwrap();
+            operations.select_files(vec![encrypted.clone()]);
+            operations.set_output_dir(restored_dir.clone());
+            operations.begin_decrypt();
+            crate::start_operation::start_operation(&mut operations, decrypting_key);
+            wait_for_operation(&operations);
+            assert_eq!(std::fs::read(restored_dir.join("1.txt")).unwrap(), b"Minutes 1");
+        }
+    }
+
+    #[test]
+    fn test_folder_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Project");
+        std::fs::create_dir_all(folder.join("src")).unwrap();
+        std::fs::write(folder.join("src").join("main.rs"), b"fn main() {}").unwrap();
+        std::fs::write(folder.join("notes.txt"), b"Notes").unwrap();
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        let key = encryption::EncryptionKey::generate();
+
+        let mut operations = OperationService::new();
+        operations.select_folder(folder, FileOperationType::Encrypt);
+        operations.set_output_dir(output_dir.clone());
+        operations.archive_folder = true;
+
+        // Archives are saved in the output directory, never in place of the originals
+        operations.replace_originals = true;
+        assert!(!operations.archive_options_valid(FileOperationType::Encrypt));
+        operations.replace_originals = false;
+        assert!(operations.archive_options_valid(FileOperationType::Encrypt));
+
+        operations.begin_encrypt();
+        assert!(matches!(operations.operation(), FileOperation::Archive));
+        crate::start_operation::start_operation(&mut operations, key.clone());
+        wait_for_operation(&operations);
+        let archive = output_dir.join("Project.crusty");
+        assert_eq!(std::fs::read_dir(&output_dir).unwrap().count(), 1);
+
+        // Archives are extracted, and not decrypted along with other files
+        let restored_dir = dir.path().join("restored");
+        std::fs::create_dir(&restored_dir).unwrap();
+        operations.select_files(vec![archive.clone(), dir.path().join("other.encrypted")]);
+        assert!(!operations.archive_options_valid(FileOperationType::Decrypt));
+        operations.select_files(vec![archive]);
+        assert!(operations.archive_options_valid(FileOperationType::Decrypt));
+        operations.set_output_dir(restored_dir.clone());
+        operations.begin_decrypt();
+        assert!(matches!(operations.operation(), FileOperation::ExtractArchive));
+        crate::start_operation::start_operation(&mut operations, key);
+        wait_for_operation(&operations);
+        assert_eq!(std::fs::read(restored_dir.join("Project").join("src").join("main.rs")).unwrap(), b"fn main() {}");
+        assert_eq!(std::fs::read(restored_dir.join("Project").join("notes.txt")).unwrap(), b"Notes");
+    }
+
+    #[test]
+    fn test_logged_paths_are_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Merger");
+        std::fs::create_dir(&folder).unwrap();
+        let inputs: Vec<PathBuf> = ["plan.pdf", "plan copy.pdf"].iter().map(|name| folder.join(name)).collect();
+        for input in &inputs {
+            std::fs::write(input, b"%PDF draft").unwrap();
+        }
+        let output_dir = dir.path().join("out");
+        std::fs::create_dir(&output_dir).unwrap();
+        let key = encryption::EncryptionKey::generate();
+
+        // Other tests may log here too, but never under this directory
+        let log_path = dir.path().join("operations.log");
+        let mut logger = Logger::new(&log_path).unwrap();
+        logger.set_path_redaction(PathRedaction::Hash, "salt");
+        crate::logger::init_logger(logger);
+
+        // Hidden names, the manifest, and the archive are saved under paths of their own,
+        // and the duplicates are listed in one entry
+        let mut operations = OperationService::new();
+        operations.select_files(inputs);
+        operations.set_output_dir(output_dir.clone());
+        operations.hide_file_names = true;
+        operations.write_manifest = true;
+        operations.begin_encrypt();
+        crate::start_operation::start_operation(&mut operations, key.clone());
+        wait_for_operation(&operations);
+
+        operations.select_folder(folder, FileOperationType::Encrypt);
+        operations.archive_folder = true;
+        operations.begin_encrypt();
+        crate::start_operation::start_operation(&mut operations, key);
+        wait_for_operation(&operations);
+
+        let entries = crate::logger::read_log_file(&log_path).unwrap();
+        for operation in ["Batch Encrypt", "Find Duplicates", "Write Manifest", "Archive"] {
+            assert!(entries.iter().any(|entry| entry.operation == operation), "{} was not logged", operation);
+        }
+        let directory = dir.path().to_string_lossy();
+        for entry in &entries {
+            for field in [&entry.file_path, &entry.message] {
+                assert!(!field.contains(&*directory), "{}: {}", entry.operation, field);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_only_destination_is_redacted() {
+        use crate::start_operation::{logged_error, logged_result};
+
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("Merger");
+        let file = output_dir.join("plan.pdf");
+        let mut logger = Logger::new(&dir.path().join("operations.log")).unwrap();
+        logger.set_path_redaction(PathRedaction::Truncate, "salt");
+
+        // As the backend reports a file it could not save, with the file inside the
+        // output folder the error names
+        let error = EncryptionError::ReadOnlyDestination(output_dir.clone());
+        let result = format!("Failed to encrypt {}: {}", file.display(), error);
+        let message = logged_result(&logger, &result, &[&file, &output_dir]);
+        assert!(!message.contains(&*dir.path().to_string_lossy()), "{}", message);
+        assert!(!message.contains("plan.pdf"), "{}", message);
+        assert!(message.contains(&logger.redact_path(&output_dir)) && message.contains("read-only"), "{}", message);
+
+        let message = logged_error(&logger, &error);
+        assert_eq!(message, EncryptionError::ReadOnlyDestination(PathBuf::from(logger.redact_path(&output_dir))).to_string());
+        let message = logged_error(&logger, &EncryptionError::MissingDestination(output_dir.clone()));
+        assert!(!message.contains(&*dir.path().to_string_lossy()), "{}", message);
+    }
+}
@@ -0,0 +1,520 @@
+/// Transfer service.
+///
+/// This module provides functionality for:
+/// - Creating transfer packages that split a key into shares for out-of-band transfer,
+///   optionally bound to the recipient's passphrase or machine identity
+/// - Saving transfer shares and sending them as one-time relay links
+/// - Taking a share down group by group as it is read out over the phone
+/// - Keeping as many share fields as the package needs, splitting pasted shares into
+///   their own fields
+/// - Reconstructing a transfer key from received shares, and unwrapping it with the
+///   recipient's credential when the package is bound to them
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+use crate::deployment;
+use crate::encryption::{EncryptionKey, KdfParams};
+use crate::i18n::{tr, tr_args};
+use crate::keystore::KeyProvenance;
+use crate::policy::get_policy;
+use crate::relay::{self, ShareBundle};
+use crate::services::{log_success, EventQueue, ServiceEvent};
+use crate::split_key::{
+    self, KeyShareManager, RecipientBinding, RecipientCredential, ShareSeal, SplitEncryptionKey,
+    TransferPackage, TransferRecipient, KEY_SHARE_DICTATION_GROUPS,
+};
+
+/// Share fields shown before the package's threshold is known
+const MIN_SHARE_FIELDS: usize = 2;
+
+/// What an outgoing transfer package is bound to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindTo {
+    /// Not bound: enough shares give the key
+    Nobody,
+    /// A passphrase agreed with the recipient
+    Passphrase,
+    /// The recipient's machine identity
+    Identity,
+}
+
+/// Transfer state for the GUI
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferState {
+    /// Initial state
+    Initial,
+    /// Transfer package created
+    Created,
+    /// Shares saved
+    SharesSaved,
+    /// Error state
+    Error(String),
+}
+
+/// Transfer receive state for the GUI
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferReceiveState {
+    /// Initial state
+    Initial,
+    /// Key reconstructed
+    Reconstructed,
+    /// Error state
+    Error(String),
+}
+
+/// Outgoing transfer package and incoming transfer shares
+pub struct TransferService {
+    package: Option<TransferPackage>,
+    state: TransferState,
+    receive_state: TransferReceiveState,
+    relay_link: Option<String>,
+    /// What the next package is bound to
+    pub bind_to: BindTo,
+    /// Passphrase the next package is bound to
+    pub recipient_passphrase: Zeroizing<String>,
+    /// Machine identity (Base64) the next package is bound to
+    pub recipient_identity: String,
+    /// Received shares, one per share field
+    pub shares: Vec<String>,
+    /// Relay link pasted by the recipient
+    pub relay_link_input: String,
+    /// Dictation groups entered by the recipient (characters and check character)
+    pub dictation_groups: Vec<String>,
+    /// Share of the package shown as dictation groups
+    pub dictation_share: Option<usize>,
+    /// Share of the package shown as a mnemonic
+    pub mnemonic_share: Option<usize>,
+    /// Passphrase entered by the recipient to unwrap the key of a bound package
+    pub unwrap_passphrase: Zeroizing<String>,
+    events: EventQueue,
+}
+
+impl TransferService {
+    /// Create a service with no transfer in progress
+    pub fn new() -> Self {
+        TransferService {
+            package: None,
+            state: TransferState::Initial,
+            receive_state: TransferReceiveState::Initial,
+            relay_link: None,
+            bind_to: BindTo::Nobody,
+            recipient_passphrase: Zeroizing::new(String::new()),
+            recipient_identity: String::new(),
+            shares: vec![String::new(); MIN_SHARE_FIELDS],
+            relay_link_input: String::new(),
+            dictation_groups: vec![String::new(); KEY_SHARE_DICTATION_GROUPS],
+            dictation_share: None,
+            mnemonic_share: None,
+            unwrap_passphrase: Zeroizing::new(String::new()),
+            events: EventQueue::default(),
+        }
+    }
+
+    /// Forget the outgoing package and everything entered to receive one (its shares
+    /// and passphrases)
+    pub fn clear_secrets(&mut self) {
+        self.package = None;
+        self.state = TransferState::Initial;
+        self.receive_state = TransferReceiveState::Initial;
+        self.relay_link = None;
+        self.recipient_passphrase = Zeroizing::new(String::new());
+        self.shares = vec![String::new(); MIN_SHARE_FIELDS];
+        self.relay_link_input.clear();
+        self.dictation_groups = vec![String::new(); KEY_SHARE_DICTATION_GROUPS];
+        self.dictation_share = None;
+        self.mnemonic_share = None;
+        self.unwrap_passphrase = Zeroizing::new(String::new());
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Get the transfer package being sent
+    pub fn package(&self) -> Option<&TransferPackage> {
+        self.package.as_ref()
+    }
+
+    /// Get the state of the outgoing transfer
+    pub fn state(&self) -> &TransferState {
+        &self.state
+    }
+
+    /// Get the state of the incoming transfer
+    pub fn receive_state(&self) -> &TransferReceiveState {
+        &self.receive_state
+    }
+
+    /// Get the one-time link for the last share sent via the relay
+    pub fn relay_link(&self) -> Option<&str> {
+        self.relay_link.as_deref()
+    }
+
+    /// Get the number of shares needed to reconstruct the key: the threshold of the
+    /// package the shares are sealed with, or 2 until a sealed share is entered
+    pub fn shares_needed(&self) -> usize {
+        match self.seal_status() {
+            Some(Ok(seal)) => seal.threshold as usize,
+            _ => MIN_SHARE_FIELDS,
+        }
+    }
+
+    /// Add an empty share field
+    pub fn add_share_field(&mut self) {
+        self.shares.push(String::new());
+    }
+
+    /// Remove a share field, keeping at least two
+    pub fn remove_share_field(&mut self, index: usize) {
+        if index < self.shares.len() && self.shares.len() > MIN_SHARE_FIELDS {
+            self.shares.remove(index);
+        }
+    }
+
+    /// Handle a share field that was edited
+    ///
+    /// Several shares pasted into the field are spread over their own fields, and
+    /// fields are added until there are as many as the package needs.
+    pub fn share_changed(&mut self, index: usize) {
+        let pasted = match self.shares.get(index) {
+            Some(content) => split_key::split_pasted_shares(content),
+            None => return,
+        };
+
+        if pasted.len() > 1 {
+            let count = pasted.len();
+            self.shares.splice(index..=index, pasted);
+            self.events.status(tr_args("status-shares-pasted", &[("count", count.into())]));
+        }
+        self.add_needed_share_fields();
+    }
+
+    /// Add share fields until there are as many as the package needs
+    fn add_needed_share_fields(&mut self) {
+        let needed = self.shares_needed();
+        if self.shares.len() < needed {
+            self.shares.resize(needed, String::new());
+        }
+    }
+
+    /// Get the first empty share field, adding one if all are filled in
+    pub fn empty_share_field(&mut self) -> &mut String {
+        let index = match self.shares.iter().position(|share| share.trim().is_empty()) {
+            Some(index) => index,
+            None => {
+                self.shares.push(String::new());
+                self.shares.len() - 1
+            },
+        };
+        &mut self.shares[index]
+    }
+
+    /// Default location of a saved transfer share
+    pub fn share_path(index: usize) -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("crusty")
+            .join("shares")
+            .join(format!("transfer_share_{}.txt", index + 1))
+    }
+
+    /// Create a transfer package (2 of 3 shares) for a key, bound to the recipient as
+    /// chosen
+    pub fn create_package(&mut self, key: &EncryptionKey) {
+        let recipient = match self.recipient() {
+            Ok(recipient) => recipient,
+            Err(e) => {
+                self.events.error(e);
+                return;
+            },
+        };
+
+        let result = KeyShareManager::open_default().and_then(|manager| match &recipient {
+            Some(recipient) => manager.create_bound_transfer_package(key, 2, 3, recipient),
+            None => manager.create_transfer_package(key, 2, 3),
+        });
+
+        match result {
+            Ok(package) => {
+                self.package = Some(package);
+                self.state = TransferState::Created;
+                self.relay_link = None;
+                self.recipient_passphrase.clear();
+                self.events.status(tr("status-package-created"));
+            },
+            Err(e) => {
+                self.state = TransferState::Error(e.to_string());
+                self.events.error(tr_args("error-package-create", &[("error", e.to_string().into())]));
+            }
+        }
+    }
+
+    /// Get the recipient the next package is bound to (None if it is not bound)
+    fn recipient(&self) -> Result<Option<TransferRecipient>, String> {
+        match self.bind_to {
+            BindTo::Nobody => Ok(None),
+            BindTo::Passphrase => {
+                if self.recipient_passphrase.is_empty() {
+                    return Err(tr("error-bind-passphrase"));
+                }
+                EncryptionKey::from_passphrase(&self.recipient_passphrase, &KdfParams::generate())
+                    .map(|key| Some(TransferRecipient::Passphrase(key)))
+                    .map_err(|e| e.to_string())
+            },
+            BindTo::Identity => deployment::decode_identity(&self.recipient_identity)
+                .map(|identity| Some(TransferRecipient::Identity(identity)))
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Get how the received package is bound to its recipient, as its seals say (None if
+    /// it is not bound, or no sealed share was entered)
+    pub fn received_binding(&self) -> Option<RecipientBinding> {
+        match self.seal_status() {
+            Some(Ok(seal)) => seal.binding,
+            _ => None,
+        }
+    }
+
+    /// Save a share of the transfer package to a file
+    pub fn save_share(&mut self, index: usize, path: &Path) {
+        let result = match &self.package {
+            Some(package) => package.save_share_to_file(index, path),
+            None => return,
+        };
+
+        match result {
+            Ok(()) => {
+                self.state = TransferState::SharesSaved;
+                self.events.status(tr_args("status-share-saved", &[("index", (index + 1).into()), ("path", path.display().to_string().into())]));
+            },
+            Err(e) => self.events.error(tr_args("error-share-save", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Upload a share of the transfer package to the relay and keep its one-time link
+    pub fn send_share_via_relay(&mut self, index: usize) {
+        let policy = match &get_policy().relay {
+            Some(policy) => policy,
+            None => return,
+        };
+
+        let share = match self.package.as_ref().map(|package| package.get_sealed_share_text(index)) {
+            Some(Ok(share)) => share,
+            Some(Err(e)) => {
+                self.events.error(e.to_string());
+                return;
+            },
+            None => return,
+        };
+
+        match relay::upload(policy, &ShareBundle { shares: vec![share] }) {
+            Ok(link) => {
+                log_success("Relay Upload", "", &format!("Share {} uploaded to the relay", index + 1));
+                self.relay_link = Some(link);
+                self.events.status(tr_args(
+                    "status-share-uploaded",
+                    &[("index", (index + 1).into()), ("hours", (policy.ttl_secs / 3600).into())],
+                ));
+            },
+            Err(e) => self.events.error(e.to_string()),
+        }
+    }
+
+    /// Fetch shares from the pasted relay link into the empty share fields
+    pub fn fetch_shares_from_relay(&mut self) {
+        let policy = match &get_policy().relay {
+            Some(policy) => policy,
+            None => return,
+        };
+
+        match relay::fetch(policy, &self.relay_link_input) {
+            Ok(bundle) => {
+                for share in bundle.shares {
+                    *self.empty_share_field() = share;
+                }
+                self.add_needed_share_fields();
+                self.relay_link_input.clear();
+                log_success("Relay Fetch", "", "Share fetched and deleted from the relay");
+                self.events.status(tr("status-share-fetched"));
+            },
+            Err(e) => self.events.error(e.to_string()),
+        }
+    }
+
+    /// Check the received shares against their transfer seals
+    ///
+    /// # Returns
+    /// * `Option<Result<ShareSeal, String>>` - The seal of the package, what is wrong
+    ///   with the shares, or None if no share entered is sealed
+    pub fn seal_status(&self) -> Option<Result<ShareSeal, String>> {
+        split_key::check_sealed_shares(&self.shares)
+            .map_err(|e| e.to_string())
+            .transpose()
+    }
+
+    /// Use the dictation groups entered as the next received share
+    ///
+    /// The share is put in the first empty share field (a field is added if all are
+    /// filled in), and the groups are cleared for the next share.
+    pub fn use_dictated_share(&mut self) {
+        let mut text = String::new();
+        for (i, group) in self.dictation_groups.iter().enumerate() {
+            match split_key::check_dictation_group(i + 1, group) {
+                Ok(chars) => text.push_str(&chars),
+                Err(e) => {
+                    self.events.error(tr_args("error-dictation-group", &[("group", (i + 1).into()), ("error", e.to_string().into())]));
+                    return;
+                },
+            }
+        }
+
+        *self.empty_share_field() = text;
+        self.dictation_groups.iter_mut().for_each(String::clear);
+        self.events.status(tr("status-dictated-share"));
+    }
+
+    /// Reconstruct the transfer key from the received shares
+    ///
+    /// # Returns
+    /// * `Option<(EncryptionKey, KeyProvenance)>` - The key and the shares and package
+    ///   it came from, or None if it could not be reconstructed (an error event was raised)
+    pub fn reconstruct_key(&mut self) -> Option<(EncryptionKey, KeyProvenance)> {
+        let shares: Vec<String> = self.shares.iter()
+            .filter(|share| !share.trim().is_empty())
+            .cloned()
+            .collect();
+        let needed = self.shares_needed();
+        if shares.len() < needed {
+            self.events.error(tr_args("error-shares-needed", &[("needed", needed.into()), ("entered", shares.len().into())]));
+            return None;
+        }
+
+        // A bound package is unwrapped with the passphrase entered or this machine's identity
+        let credential = match self.received_binding() {
+            Some(RecipientBinding::Passphrase(_)) if self.unwrap_passphrase.is_empty() => {
+                self.events.error(tr("error-unwrap-passphrase"));
+                return None;
+            },
+            Some(RecipientBinding::Passphrase(_)) => Some(RecipientCredential::Passphrase(self.unwrap_passphrase.to_string())),
+            Some(RecipientBinding::Identity(_)) => match deployment::machine_identity_secret() {
+                Ok(secret) => Some(RecipientCredential::Identity(secret)),
+                Err(e) => {
+                    self.events.error(tr_args("error-read-identity", &[("error", e.to_string().into())]));
+                    return None;
+                },
+            },
+            None => None,
+        };
+
+        let result = KeyShareManager::open_default().and_then(|manager| match &credential {
+            Some(credential) => manager.reconstruct_bound_key_from_text_shares(&shares, Some(credential)),
+            None => manager.reconstruct_key_from_text_shares(&shares),
+        });
+
+        match result {
+            Ok(key) => {
+                self.receive_state = TransferReceiveState::Reconstructed;
+                self.unwrap_passphrase.clear();
+
+                let share_numbers = shares.iter()
+                    .filter_map(|share| SplitEncryptionKey::parse_share(share).ok())
+                    .map(|share| split_key::share_number(&share))
+                    .collect();
+                let package_id = match self.seal_status() {
+                    Some(Ok(seal)) => Some(seal.package_id_text()),
+                    _ => None,
+                };
+                let provenance = KeyProvenance::new("Reconstructed from transfer shares")
+                    .with_shares(share_numbers, package_id);
+                Some((key, provenance))
+            },
+            Err(e) => {
+                self.receive_state = TransferReceiveState::Error(e.to_string());
+                self.events.error(tr_args("error-key-reconstruct", &[("error", e.to_string().into())]));
+                None
+            }
+        }
+    }
+}
+
+impl Default for TransferService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::split_key::{KeyPurpose, SplitEncryptionKey};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_use_dictated_share() {
+        let split_key = SplitEncryptionKey::new(&EncryptionKey::generate(), 2, 3, KeyPurpose::Standard).unwrap();
+        let groups = split_key.share_to_dictation(1).unwrap();
+
+        let mut transfer = TransferService::new();
+        transfer.shares[0] = split_key.share_to_text(0).unwrap();
+        for (entry, group) in transfer.dictation_groups.iter_mut().zip(&groups) {
+            *entry = format!("{} {}", group.chars, group.check);
+        }
+
+        // A wrong group is reported and nothing is entered
+        transfer.dictation_groups[5] = "AAAAA".to_string();
+        transfer.use_dictated_share();
+        assert!(matches!(transfer.take_events().as_slice(), [ServiceEvent::Error(_)]));
+        assert!(transfer.shares[1].is_empty());
+
+        transfer.dictation_groups[5] = format!("{}{}", groups[5].chars, groups[5].check);
+        transfer.use_dictated_share();
+        assert_eq!(transfer.shares[1].replace('-', ""), split_key.share_to_text(1).unwrap().replace('-', ""));
+        assert!(transfer.dictation_groups.iter().all(String::is_empty));
+    }
+
+    #[test]
+    fn test_share_fields_follow_package() {
+        let dir = tempdir().unwrap();
+        let manager = KeyShareManager::new("CRUSTy-test", dir.path()).unwrap();
+        let key = EncryptionKey::generate();
+        let package = manager.create_transfer_package(&key, 3, 5).unwrap();
+        let sealed: Vec<String> = (0..5).map(|i| package.get_sealed_share_text(i).unwrap()).collect();
+
+        // A sealed share brings up as many fields as the package needs
+        let mut transfer = TransferService::new();
+        transfer.shares[0] = sealed[0].clone();
+        transfer.share_changed(0);
+        assert_eq!(transfer.shares_needed(), 3);
+        assert_eq!(transfer.shares.len(), 3);
+
+        // Two shares pasted into one field are spread over their own fields
+        transfer.shares[1] = format!("{}\n{}", sealed[3], sealed[4]);
+        transfer.share_changed(1);
+        assert!(matches!(transfer.take_events().as_slice(), [ServiceEvent::Status(_)]));
+        assert_eq!(transfer.shares.len(), 4);
+        assert_eq!(transfer.shares[1], sealed[3]);
+        assert_eq!(transfer.shares[2], sealed[4]);
+        assert!(transfer.shares[3].is_empty());
+
+        // Unsealed shares, one per line, are split too; a single share is left whole
+        let lines = format!("{}\n{}", package.get_share_text(1).unwrap(), package.get_share_text(2).unwrap());
+        assert_eq!(split_key::split_pasted_shares(&lines).len(), 2);
+        assert_eq!(split_key::split_pasted_shares(&sealed[0]), vec![sealed[0].clone()]);
+
+        // Too few shares are reported before reconstructing
+        transfer.remove_share_field(2);
+        transfer.remove_share_field(2);
+        assert!(transfer.reconstruct_key().is_none());
+        assert!(matches!(transfer.take_events().as_slice(), [ServiceEvent::Error(e)] if e.contains("3 key shares")));
+
+        transfer.add_share_field();
+        transfer.shares[2] = sealed[4].clone();
+        let (reconstructed, provenance) = transfer.reconstruct_key().unwrap();
+        assert_eq!(reconstructed.key, key.key);
+        assert_eq!(provenance.share_numbers, vec![1, 4, 5]);
+        let seal = split_key::check_sealed_shares(&sealed).unwrap().unwrap();
+        assert_eq!(provenance.package_id, Some(seal.package_id_text()));
+    }
+}
@@ -0,0 +1,120 @@
+/// Secure viewer service.
+///
+/// This module provides functionality for:
+/// - Decrypting a file to view it in its default app, without leaving plaintext behind
+/// - Shredding the viewed files when they are done, expire, or the keys are locked,
+///   and logging each
+///
+/// Whether the viewing app has closed the file cannot be told reliably, so the files
+/// stay until they are marked done, expire, the keys are locked, or CRUSTy closes.
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::encryption::EncryptionKey;
+use crate::i18n::tr_args;
+use crate::secure_viewer::{SecureViewer, ViewedFile};
+use crate::services::{log_error, log_key_success, log_success, EventQueue, ServiceEvent};
+
+/// Files decrypted for viewing
+pub struct ViewerService {
+    viewer: SecureViewer,
+    events: EventQueue,
+}
+
+impl ViewerService {
+    /// Create a service viewing files in the RAM-backed folder of the system
+    pub fn new() -> Self {
+        ViewerService { viewer: SecureViewer::new(), events: EventQueue::default() }
+    }
+
+    /// Take the events raised since the last call
+    pub fn take_events(&mut self) -> Vec<ServiceEvent> {
+        self.events.take()
+    }
+
+    /// Get the files being viewed
+    pub fn files(&self) -> &[ViewedFile] {
+        self.viewer.files()
+    }
+
+    /// Check whether viewed files are kept in memory rather than on disk
+    pub fn in_memory(&self) -> bool {
+        self.viewer.in_memory()
+    }
+
+    /// Decrypt a file for viewing
+    ///
+    /// # Arguments
+    /// * `source` - The encrypted file
+    /// * `key` - The key to decrypt it with
+    /// * `timeout` - How long the decrypted copy is kept
+    ///
+    /// # Returns
+    /// * `Option<PathBuf>` - The decrypted copy to open, or None if it failed
+    pub fn view(&mut self, source: &Path, key: &EncryptionKey, timeout: Duration) -> Option<PathBuf> {
+        let source_name = source.to_string_lossy().to_string();
+        let where_to = if self.viewer.in_memory() { "memory" } else { "a private temporary folder" };
+        match self.viewer.decrypt(source, key, timeout) {
+            Ok(file) => {
+                log_key_success("View", &source_name, &format!("Decrypted to {} for viewing", where_to), &key.fingerprint());
+                self.events.status(tr_args("status-viewing", &[("name", source_name.as_str().into()), ("minutes", (timeout.as_secs() / 60).into())]));
+                Some(file.path.clone())
+            },
+            Err(e) => {
+                log_error("View", &source_name, &e.to_string());
+                self.events.error(tr_args("error-view", &[("name", source_name.as_str().into()), ("error", e.to_string().into())]));
+                None
+            },
+        }
+    }
+
+    /// Shred a viewed file once it is done with
+    pub fn close(&mut self, index: usize) {
+        let result = self.viewer.close(index);
+        self.report_closed(result, "Done viewing");
+    }
+
+    /// Shred every viewed file
+    ///
+    /// # Arguments
+    /// * `reason` - Why the files are shredded (for the log)
+    pub fn close_all(&mut self, reason: &str) {
+        let sources: Vec<String> = self.files().iter().map(|file| file.source.to_string_lossy().to_string()).collect();
+        match self.viewer.close_all() {
+            Ok(()) => {
+                for source in &sources {
+                    log_success("Shred Viewed", source, reason);
+                }
+            },
+            Err(e) => self.events.error(tr_args("error-shred-viewed-all", &[("error", e.to_string().into())])),
+        }
+    }
+
+    /// Shred the viewed files that have expired
+    ///
+    /// # Returns
+    /// * `bool` - Whether files are still being viewed
+    pub fn poll(&mut self) -> bool {
+        for result in self.viewer.close_expired(Instant::now()) {
+            self.report_closed(result, "Viewing time ran out");
+        }
+        !self.files().is_empty()
+    }
+
+    /// Log a shredded file, or why it could not be shredded
+    fn report_closed(&mut self, result: std::io::Result<ViewedFile>, reason: &str) {
+        match result {
+            Ok(file) => {
+                log_success("Shred Viewed", &file.source.to_string_lossy(), reason);
+                self.events.status(tr_args("status-shredded-viewed", &[("path", file.source.display().to_string().into())]));
+            },
+            Err(e) => self.events.error(tr_args("error-shred-viewed", &[("error", e.to_string().into())])),
+        }
+    }
+}
+
+impl Default for ViewerService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
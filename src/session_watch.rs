@@ -0,0 +1,170 @@
+/// Session watch module.
+///
+/// This module provides functionality for:
+/// - Watching in the background for the computer being locked or suspended (including
+///   closing the lid), so CRUSTy can lock its keys
+/// - Clearing text CRUSTy copied to the clipboard, if it is still there
+///
+/// A locked session is found by asking the system every few seconds (Windows: the
+/// input desktop cannot be opened; Linux: systemd-logind reports `LockedHint` for the
+/// session; not detected on macOS). A suspend is found by the clock jumping forward
+/// between two checks, as the watch does not run while the computer sleeps. Both are
+/// best effort: CRUSTy may only notice a suspend after the computer wakes up.
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::process::{Command, Stdio};
+
+/// How often the session is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Clock jump between two checks taken as a suspend
+const SUSPEND_GAP: Duration = Duration::from_secs(30);
+
+/// What happened to the session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionEvent {
+    /// The session was locked
+    Locked,
+    /// The computer was suspended (and has woken up)
+    Suspended,
+}
+
+impl SessionEvent {
+    /// Describe the event for the log and the status message
+    pub fn describe(&self) -> &'static str {
+        match self {
+            SessionEvent::Locked => "the session was locked",
+            SessionEvent::Suspended => "the computer was suspended",
+        }
+    }
+}
+
+/// Session events, received in the background
+pub struct SessionWatch {
+    receiver: Receiver<SessionEvent>,
+}
+
+impl SessionWatch {
+    /// Start watching the session in the background
+    ///
+    /// # Arguments
+    /// * `wake` - Called after an event (to repaint the window)
+    pub fn start(wake: impl Fn() + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut was_locked = false;
+            loop {
+                let before = SystemTime::now();
+                thread::sleep(CHECK_INTERVAL);
+
+                let locked = session_locked();
+                let event = if slept_through(before, SystemTime::now()) {
+                    Some(SessionEvent::Suspended)
+                } else if locked && !was_locked {
+                    Some(SessionEvent::Locked)
+                } else {
+                    None
+                };
+                was_locked = locked;
+
+                if let Some(event) = event {
+                    if sender.send(event).is_err() {
+                        break;
+                    }
+                    wake();
+                }
+            }
+        });
+
+        SessionWatch { receiver }
+    }
+
+    /// Take the first event since the last call (None if nothing happened)
+    pub fn take_event(&self) -> Option<SessionEvent> {
+        let mut first = None;
+        while let Ok(event) = self.receiver.try_recv() {
+            first.get_or_insert(event);
+        }
+        first
+    }
+}
+
+/// Check whether the clock jumped forward between two checks, as it does when the
+/// computer sleeps between them
+fn slept_through(before: SystemTime, after: SystemTime) -> bool {
+    after.duration_since(before)
+        .is_ok_and(|elapsed| elapsed >= CHECK_INTERVAL + SUSPEND_GAP)
+}
+
+/// Check whether the session is locked (false where this cannot be found out)
+fn session_locked() -> bool {
+    #[cfg(windows)]
+    {
+        use winapi::um::winuser::{CloseDesktop, OpenInputDesktop, DESKTOP_SWITCHDESKTOP};
+
+        // SAFETY: the desktop handle is closed right after it is opened
+        unsafe {
+            let desktop = OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP);
+            if desktop.is_null() {
+                return true;
+            }
+            CloseDesktop(desktop);
+            false
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let session = match std::env::var("XDG_SESSION_ID") {
+            Ok(session) => session,
+            Err(_) => return false,
+        };
+
+        Command::new("loginctl")
+            .args(["show-session", &session, "--property=LockedHint", "--value"])
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "yes")
+    }
+
+    #[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+    {
+        false
+    }
+}
+
+/// Clear the clipboard if it still holds the text CRUSTy copied to it
+///
+/// # Returns
+/// * `bool` - Whether the clipboard was cleared (false if it holds something else now,
+///   or cannot be used)
+pub fn clear_clipboard(copied: &str) -> bool {
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(_) => return false,
+    };
+
+    match clipboard.get_text() {
+        Ok(text) if text == copied => clipboard.clear().is_ok(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspend_detection() {
+        let before = SystemTime::now();
+        assert!(!slept_through(before, before + CHECK_INTERVAL));
+        assert!(!slept_through(before, before + CHECK_INTERVAL + Duration::from_secs(5)));
+        assert!(slept_through(before, before + Duration::from_secs(600)));
+
+        // The clock set back is not a suspend
+        assert!(!slept_through(before, before - Duration::from_secs(600)));
+    }
+}
@@ -0,0 +1,724 @@
+/// Application settings module.
+///
+/// This module provides functionality for:
+/// - Loading user settings (settings.toml) from the application data directory
+/// - Saving settings changed in the GUI
+///
+/// Unlike the admin policy, the settings file is written by CRUSTy itself and only
+/// holds user preferences. When it is missing, the default settings apply.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::age_format::AgeRecipient;
+use crate::backend::EmbeddedConfig;
+use crate::control_api;
+use crate::key_agreement::Partner;
+use crate::locale::Language;
+use crate::logger::{LogFormat, LogRotation};
+use crate::openpgp::OpenPgpRecipient;
+use crate::shared_files;
+use crate::speed_history::BackendKind;
+
+/// Most memory a file may take in low-memory mode, with its encrypted or decrypted copy
+pub const LOW_MEMORY_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// User settings for this installation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Play a sound when an operation completes
+    pub completion_sound: bool,
+    /// Sound file played on completion (the system sound when None)
+    pub completion_sound_file: Option<PathBuf>,
+    /// Show operation progress on the taskbar or launcher icon
+    pub taskbar_progress: bool,
+    /// Show an icon with quick actions in the system tray
+    pub tray_icon: bool,
+    /// Hide the window when it is minimized, leaving only the tray icon
+    pub minimize_to_tray: bool,
+    /// Proxy used for network features (relay links, central logging)
+    pub proxy: ProxySettings,
+    /// Turn off every network feature (for air-gapped systems)
+    pub offline: bool,
+    /// UI language (the system language when None)
+    pub language: Option<Language>,
+    /// Batches that need confirmation before they start
+    pub batch_limits: BatchLimits,
+    /// When the keys are locked automatically
+    pub auto_lock: AutoLock,
+    /// Minutes a file decrypted for viewing is kept before it is shredded
+    pub viewer_minutes: u32,
+    /// What opening an encrypted file does, by the extension of its original name
+    pub open_actions: Vec<ExtensionAction>,
+    /// Hours between integrity checks of the locked secured folders (0 never checks)
+    pub integrity_check_hours: u32,
+    /// Cover key shares until revealed, and keep the window out of screen captures
+    /// while they are shown
+    pub protect_shares: bool,
+    /// How long an operation may run without progress before it is stopped
+    pub timeouts: OperationTimeouts,
+    /// Other people's age recipients, to encrypt age files for them
+    pub age_recipients: Vec<AgeRecipient>,
+    /// Other people's OpenPGP public keys, to encrypt OpenPGP messages for them
+    pub openpgp_recipients: Vec<OpenPgpRecipient>,
+    /// Partners whose machine identity was checked in a key agreement
+    pub partners: Vec<Partner>,
+    /// Local HTTP API for other programs to queue jobs
+    pub control_api: ControlApiSettings,
+    /// Keep the memory operations use under `LOW_MEMORY_LIMIT` (for machines with
+    /// little memory)
+    pub low_memory: bool,
+    /// Colors of the window
+    pub theme: Theme,
+    /// Themes saved from the theme editor
+    pub saved_themes: Vec<SavedTheme>,
+    /// Name of the saved theme used instead of `theme` (None to use `theme`)
+    pub saved_theme: Option<String>,
+    /// Size of the reads from disk, in KiB (0 picks it from the measured throughput)
+    pub chunk_size_kb: u32,
+    /// Choices of the main screen when CRUSTy starts
+    pub defaults: OperationDefaults,
+    /// Keys pinned to folders, to warn before files there are encrypted with another key
+    pub key_pins: Vec<KeyPin>,
+    /// Show the key, backend, destination and options of a batch, and what changed
+    /// since the last one, before it starts
+    pub preflight_summary: bool,
+    /// Folders whose new files are encrypted automatically
+    pub watched_folders: Vec<WatchedFolder>,
+    /// When the operation log is rotated, and how long old logs are kept
+    pub log_rotation: LogRotation,
+    /// How entries are written to the operation log
+    pub log_format: LogFormat,
+    /// Send log entries to the system log (syslog or the Windows Event Log) too
+    pub system_log: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            completion_sound: true,
+            completion_sound_file: None,
+            taskbar_progress: true,
+            tray_icon: true,
+            minimize_to_tray: false,
+            proxy: ProxySettings::default(),
+            offline: false,
+            language: None,
+            batch_limits: BatchLimits::default(),
+            auto_lock: AutoLock::default(),
+            viewer_minutes: 15,
+            open_actions: Vec::new(),
+            integrity_check_hours: 24,
+            protect_shares: true,
+            timeouts: OperationTimeouts::default(),
+            age_recipients: Vec::new(),
+            openpgp_recipients: Vec::new(),
+            partners: Vec::new(),
+            control_api: ControlApiSettings::default(),
+            low_memory: false,
+            theme: Theme::System,
+            saved_themes: Vec::new(),
+            saved_theme: None,
+            chunk_size_kb: 0,
+            defaults: OperationDefaults::default(),
+            key_pins: Vec::new(),
+            preflight_summary: true,
+            watched_folders: Vec::new(),
+            log_rotation: LogRotation::default(),
+            log_format: LogFormat::Standard,
+            system_log: false,
+        }
+    }
+}
+
+/// Colors of the window
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Light or dark, following the system
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// All themes, in the order they are offered
+    pub const ALL: [Theme; 3] = [Theme::System, Theme::Light, Theme::Dark];
+}
+
+/// Colors saved from the theme editor under a name
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedTheme {
+    pub name: String,
+    /// Built on the dark theme (the light one when false)
+    pub dark: bool,
+    /// Colors by name, as `#RRGGBB` (e.g. `accent = "#FF8C00"`). Colors left out are
+    /// those of the theme it is built on
+    pub colors: BTreeMap<String, String>,
+}
+
+/// A key pinned to a folder
+///
+/// Files in the folder, or encrypted to it, are expected to be encrypted with this key
+/// (e.g. a client's deliveries with the client's key, not a personal one).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyPin {
+    /// Folder the key is pinned to, with its subfolders
+    pub folder: PathBuf,
+    /// Name of the key when it was pinned
+    pub key_name: String,
+    /// Fingerprint of the key
+    pub fingerprint: String,
+}
+
+/// A folder whose new files are encrypted automatically
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WatchedFolder {
+    /// Folder watched for new files (not its subfolders)
+    pub folder: PathBuf,
+    /// Folder the encrypted files are saved in
+    pub output_dir: PathBuf,
+    /// Name of the key when the folder was added
+    pub key_name: String,
+    /// Fingerprint of the key the files are encrypted with
+    pub fingerprint: String,
+}
+
+/// Choices of the main screen when CRUSTy starts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+#[serde(default)]
+pub struct OperationDefaults {
+    /// Output directory (none selected when None, or when it no longer exists)
+    pub output_dir: Option<PathBuf>,
+    /// Select several files at once
+    pub batch_mode: bool,
+    /// Use the embedded device instead of software encryption
+    pub use_embedded: bool,
+    /// Device and connection settings of the embedded device
+    pub embedded: Option<EmbeddedConfig>,
+}
+
+/// Limits above which a batch needs confirmation before it starts
+///
+/// Guards against starting a huge job by accident (e.g. selecting a whole drive).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct BatchLimits {
+    /// Warn before batches over the limits
+    pub enabled: bool,
+    /// Total size of the files, in GB (1 GB = 1024 MB)
+    pub max_total_gb: u64,
+    /// Number of files
+    pub max_files: usize,
+}
+
+impl Default for BatchLimits {
+    fn default() -> Self {
+        BatchLimits {
+            enabled: true,
+            max_total_gb: 100,
+            max_files: 10_000,
+        }
+    }
+}
+
+impl BatchLimits {
+    /// Check whether a batch is over the limits
+    pub fn exceeded_by(&self, file_count: usize, total_bytes: u64) -> bool {
+        const GB: u64 = 1024 * 1024 * 1024;
+        self.enabled && (file_count > self.max_files || total_bytes > self.max_total_gb.saturating_mul(GB))
+    }
+}
+
+/// When the keys are locked automatically
+///
+/// Locking forgets the saved keys until they are unlocked, clears text CRUSTy copied
+/// to the clipboard, and wipes entered shares and passphrases.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AutoLock {
+    /// Lock when the computer is locked or suspended
+    pub on_session_lock: bool,
+    /// Lock after this many minutes without input (0 to never lock when idle)
+    pub idle_minutes: u32,
+}
+
+impl Default for AutoLock {
+    fn default() -> Self {
+        AutoLock {
+            on_session_lock: true,
+            idle_minutes: 0,
+        }
+    }
+}
+
+/// What opening an encrypted file (double-clicking it in the Recent Files list, or
+/// opening it with CRUSTy from the file manager) does
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OpenAction {
+    /// Select it on the main screen
+    #[default]
+    Select,
+    /// Decrypt it to the secure viewer and open it in its default app
+    ViewSecurely,
+    /// Decrypt it to the output directory
+    Decrypt,
+}
+
+impl OpenAction {
+    /// All actions, in the order they are offered
+    pub const ALL: [OpenAction; 3] = [OpenAction::Select, OpenAction::ViewSecurely, OpenAction::Decrypt];
+
+    /// Get the name of the action, as shown in the settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            OpenAction::Select => "Select",
+            OpenAction::ViewSecurely => "View securely",
+            OpenAction::Decrypt => "Decrypt to output directory",
+        }
+    }
+}
+
+/// The action for encrypted files whose original name has an extension
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExtensionAction {
+    /// Extension, in lower case without the dot (e.g. `docx`)
+    pub extension: String,
+    pub action: OpenAction,
+}
+
+/// Local control API, for other programs and test automation to queue jobs
+///
+/// The API only listens on the loopback interface, and needs the token CRUSTy writes
+/// to control_api.json when it starts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ControlApiSettings {
+    /// Start the API with CRUSTy
+    pub enabled: bool,
+    /// Loopback port to listen on
+    pub port: u16,
+}
+
+impl Default for ControlApiSettings {
+    fn default() -> Self {
+        ControlApiSettings {
+            enabled: false,
+            port: control_api::DEFAULT_PORT,
+        }
+    }
+}
+
+/// How long an operation may run without progress before it is stopped, by backend
+///
+/// A stalled operation (e.g. an embedded device that stopped answering) has its
+/// unfinished files failed, so the next operation can start.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OperationTimeouts {
+    /// Seconds without progress with software encryption (0 to never stop)
+    pub local_secs: u32,
+    /// Seconds without progress with the embedded device (0 to never stop)
+    pub embedded_secs: u32,
+}
+
+impl Default for OperationTimeouts {
+    fn default() -> Self {
+        OperationTimeouts {
+            local_secs: 600,
+            embedded_secs: 60,
+        }
+    }
+}
+
+impl OperationTimeouts {
+    /// Get the timeout for an operation with the given backend (zero to never stop it)
+    pub fn for_backend(&self, backend: BackendKind) -> Duration {
+        let secs = match backend {
+            BackendKind::Local => self.local_secs,
+            BackendKind::Embedded => self.embedded_secs,
+        };
+        Duration::from_secs(u64::from(secs))
+    }
+}
+
+/// How network requests reach the internet
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    /// Use the system proxy (the HTTPS_PROXY, HTTP_PROXY, or ALL_PROXY environment variable)
+    System,
+    /// Use the proxy entered in the settings
+    Manual,
+    /// Connect directly, ignoring any system proxy
+    None,
+}
+
+/// Proxy settings
+///
+/// The proxy password is not stored here; it is kept in the OS credential store.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct ProxySettings {
+    /// How network requests reach the internet
+    pub mode: ProxyMode,
+    /// Manual proxy address (host:port)
+    pub address: String,
+    /// User name for proxy authentication (none when empty)
+    pub username: String,
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        ProxySettings {
+            mode: ProxyMode::System,
+            address: String::new(),
+            username: String::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Default location of the settings file
+    pub fn default_path() -> PathBuf {
+        let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("crusty");
+        Self::path_in(&path)
+    }
+
+    /// Location of the settings file in the app's data folder
+    pub fn path_in(data_dir: &Path) -> PathBuf {
+        data_dir.join("settings.toml")
+    }
+
+    /// Load the settings from the specified file
+    ///
+    /// # Arguments
+    /// * `path` - Path to the settings file
+    ///
+    /// # Returns
+    /// * `io::Result<Settings>` - The parsed settings, the default settings if the file
+    ///   does not exist, or an error if the file cannot be read or parsed
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid settings file: {}", e)))
+    }
+
+    /// Get the memory limit of operations (None if low-memory mode is off)
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.low_memory.then_some(LOW_MEMORY_LIMIT)
+    }
+
+    /// Get the size of the reads from disk (None to pick it from the measured throughput)
+    pub fn chunk_size(&self) -> Option<usize> {
+        (self.chunk_size_kb > 0).then(|| self.chunk_size_kb as usize * 1024)
+    }
+
+    /// Get the saved theme in use (None if a built-in theme is used)
+    pub fn saved_theme(&self) -> Option<&SavedTheme> {
+        let name = self.saved_theme.as_ref()?;
+        self.saved_themes.iter().find(|theme| &theme.name == name)
+    }
+
+    /// Save a theme, replacing the saved theme of the same name, and use it
+    pub fn save_theme(&mut self, theme: SavedTheme) {
+        self.saved_themes.retain(|saved| saved.name != theme.name);
+        self.saved_theme = Some(theme.name.clone());
+        self.saved_themes.push(theme);
+    }
+
+    /// Get the key pinned to the folder a file or folder is in
+    ///
+    /// When the folders of several pins hold the path, the pin of the innermost one is
+    /// used.
+    pub fn key_pin(&self, path: &Path) -> Option<&KeyPin> {
+        self.key_pins.iter()
+            .filter(|pin| path.starts_with(&pin.folder))
+            .max_by_key(|pin| pin.folder.components().count())
+    }
+
+    /// Pin a key to a folder, replacing the key pinned to it before
+    pub fn pin_key(&mut self, pin: KeyPin) {
+        self.key_pins.retain(|pinned| pinned.folder != pin.folder);
+        self.key_pins.push(pin);
+    }
+
+    /// Get what opening an encrypted file does
+    ///
+    /// # Arguments
+    /// * `original_name` - The name the file decrypts to (its extension chooses the action)
+    pub fn open_action(&self, original_name: &Path) -> OpenAction {
+        let Some(extension) = original_name.extension().map(|extension| extension.to_string_lossy().to_lowercase()) else {
+            return OpenAction::Select;
+        };
+        self.open_actions.iter()
+            .find(|rule| rule.extension == extension)
+            .map_or(OpenAction::Select, |rule| rule.action)
+    }
+
+    /// Set the action for an extension, replacing its current action
+    ///
+    /// The extension may be given with a leading dot and in any case.
+    pub fn set_open_action(&mut self, extension: &str, action: OpenAction) {
+        let extension = extension.trim().trim_start_matches('.').to_lowercase();
+        if extension.is_empty() {
+            return;
+        }
+        self.open_actions.retain(|rule| rule.extension != extension);
+        self.open_actions.push(ExtensionAction { extension, action });
+    }
+
+    /// Save the settings to the specified file
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Failed to serialize settings: {}", e)))?;
+
+        shared_files::write_atomic(path, content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{ConnectionSettings, EthernetSettings};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("settings.toml");
+
+        // Missing file gives the defaults
+        assert!(Settings::load_from(&path).unwrap().completion_sound);
+
+        let settings = Settings {
+            completion_sound: false,
+            completion_sound_file: Some(PathBuf::from("done.wav")),
+            taskbar_progress: false,
+            tray_icon: false,
+            minimize_to_tray: true,
+            proxy: ProxySettings {
+                mode: ProxyMode::Manual,
+                address: "proxy.example.com:3128".to_string(),
+                username: "alice".to_string(),
+            },
+            offline: true,
+            language: Some(Language::German),
+            batch_limits: BatchLimits {
+                enabled: true,
+                max_total_gb: 20,
+                max_files: 500,
+            },
+            auto_lock: AutoLock {
+                on_session_lock: false,
+                idle_minutes: 15,
+            },
+            viewer_minutes: 5,
+            open_actions: vec![ExtensionAction { extension: "docx".to_string(), action: OpenAction::ViewSecurely }],
+            integrity_check_hours: 6,
+            protect_shares: false,
+            timeouts: OperationTimeouts {
+                local_secs: 0,
+                embedded_secs: 30,
+            },
+            age_recipients: vec![AgeRecipient {
+                name: "Backup server".to_string(),
+                recipient: "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p".to_string(),
+            }],
+            openpgp_recipients: vec![OpenPgpRecipient {
+                name: "Colleague <colleague@example.com>".to_string(),
+                fingerprint: "6759E443D35FA8C8D553EF11CD1D67A616592321".to_string(),
+                certificate: "-----BEGIN PGP PUBLIC KEY BLOCK-----\n...".to_string(),
+            }],
+            partners: vec![Partner {
+                name: "Branch Office".to_string(),
+                identity: "hSDwCYkwp1R0i33ctD73Wg2/Og0mOBr066SpjqqbTmo=".to_string(),
+                verified_at: "2024-05-01 09:30".to_string(),
+            }],
+            control_api: ControlApiSettings {
+                enabled: true,
+                port: 50000,
+            },
+            low_memory: true,
+            theme: Theme::Dark,
+            saved_themes: vec![SavedTheme {
+                name: "High contrast".to_string(),
+                dark: true,
+                colors: BTreeMap::from([("accent".to_string(), "#FFFF00".to_string())]),
+            }],
+            saved_theme: Some("High contrast".to_string()),
+            chunk_size_kb: 1024,
+            defaults: OperationDefaults {
+                output_dir: Some(PathBuf::from("/home/alice/Encrypted")),
+                batch_mode: true,
+                use_embedded: true,
+                embedded: Some(EmbeddedConfig {
+                    device_id: "crusty-01".to_string(),
+                    connection: ConnectionSettings::Ethernet(EthernetSettings::default()),
+                }),
+            },
+            key_pins: vec![KeyPin {
+                folder: PathBuf::from("/work/clients/acme"),
+                key_name: "Acme".to_string(),
+                fingerprint: "3f2a9c1d".to_string(),
+            }],
+            preflight_summary: false,
+            watched_folders: vec![WatchedFolder {
+                folder: PathBuf::from("/work/inbox"),
+                output_dir: PathBuf::from("/work/encrypted"),
+                key_name: "Acme".to_string(),
+                fingerprint: "3f2a9c1d".to_string(),
+            }],
+            log_rotation: LogRotation {
+                max_size_mb: 50,
+                max_files: 10,
+                max_age_days: 90,
+                compress: false,
+            },
+            log_format: LogFormat::Structured,
+            system_log: true,
+        };
+        settings.save_to(&path).unwrap();
+
+        let loaded = Settings::load_from(&path).unwrap();
+        assert!(!loaded.completion_sound);
+        assert_eq!(loaded.completion_sound_file, Some(PathBuf::from("done.wav")));
+        assert!(!loaded.taskbar_progress);
+        assert!(!loaded.tray_icon);
+        assert!(loaded.minimize_to_tray);
+        assert_eq!(loaded.proxy, settings.proxy);
+        assert!(loaded.offline);
+        assert_eq!(loaded.language, Some(Language::German));
+        assert_eq!(loaded.batch_limits, settings.batch_limits);
+        assert_eq!(loaded.auto_lock, settings.auto_lock);
+        assert_eq!(loaded.viewer_minutes, 5);
+        assert_eq!(loaded.open_actions, settings.open_actions);
+        assert_eq!(loaded.integrity_check_hours, 6);
+        assert!(!loaded.protect_shares);
+        assert_eq!(loaded.timeouts, settings.timeouts);
+        assert_eq!(loaded.timeouts.for_backend(BackendKind::Embedded), Duration::from_secs(30));
+        assert!(loaded.timeouts.for_backend(BackendKind::Local).is_zero());
+        assert_eq!(loaded.age_recipients, settings.age_recipients);
+        assert_eq!(loaded.openpgp_recipients, settings.openpgp_recipients);
+        assert_eq!(loaded.partners, settings.partners);
+        assert_eq!(loaded.control_api, settings.control_api);
+        assert_eq!(loaded.memory_limit(), Some(LOW_MEMORY_LIMIT));
+        assert_eq!(loaded.theme, Theme::Dark);
+        assert_eq!(loaded.saved_theme(), settings.saved_themes.first());
+        assert_eq!(loaded.chunk_size(), Some(1024 * 1024));
+        assert_eq!(loaded.defaults, settings.defaults);
+        assert_eq!(loaded.key_pins, settings.key_pins);
+        assert!(!loaded.preflight_summary);
+        assert_eq!(loaded.watched_folders, settings.watched_folders);
+        assert_eq!(loaded.log_rotation, settings.log_rotation);
+        assert_eq!(loaded.log_format, LogFormat::Structured);
+        assert!(loaded.system_log);
+        assert_eq!(Settings::default().chunk_size(), None);
+    }
+
+    #[test]
+    fn test_settings_without_appearance() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("settings.toml");
+
+        // A file saved before the theme, read size and startup defaults were added
+        fs::write(&path, "completion_sound = false\noffline = true\n").unwrap();
+        let loaded = Settings::load_from(&path).unwrap();
+        assert!(!loaded.completion_sound);
+        assert!(loaded.offline);
+        assert_eq!(loaded.theme, Theme::System);
+        assert_eq!(loaded.saved_theme(), None);
+        assert_eq!(loaded.chunk_size(), None);
+        assert_eq!(loaded.defaults, OperationDefaults::default());
+
+        // A saved theme that is not in the file is not used
+        fs::write(&path, "theme = \"Light\"\nsaved_theme = \"Gone\"\n").unwrap();
+        let loaded = Settings::load_from(&path).unwrap();
+        assert_eq!(loaded.theme, Theme::Light);
+        assert_eq!(loaded.saved_theme(), None);
+    }
+
+    #[test]
+    fn test_save_theme() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.saved_theme(), None);
+
+        let theme = |name: &str, accent: &str| SavedTheme {
+            name: name.to_string(),
+            dark: false,
+            colors: BTreeMap::from([("accent".to_string(), accent.to_string())]),
+        };
+        settings.save_theme(theme("Print", "#000000"));
+        settings.save_theme(theme("Bright", "#FFFF00"));
+        assert_eq!(settings.saved_theme().map(|saved| saved.name.as_str()), Some("Bright"));
+
+        // Saving under a name again replaces the theme
+        settings.save_theme(theme("Print", "#333333"));
+        assert_eq!(settings.saved_themes.len(), 2);
+        assert_eq!(settings.saved_theme(), Some(&theme("Print", "#333333")));
+
+        // A theme that is no longer saved is not used
+        settings.saved_themes.clear();
+        assert_eq!(settings.saved_theme(), None);
+    }
+
+    #[test]
+    fn test_key_pins() {
+        let mut settings = Settings::default();
+        let pin = |folder: &str, key_name: &str| KeyPin {
+            folder: PathBuf::from(folder),
+            key_name: key_name.to_string(),
+            fingerprint: format!("{}-fingerprint", key_name),
+        };
+        settings.pin_key(pin("/work/clients", "Clients"));
+        settings.pin_key(pin("/work/clients/acme", "Old Acme"));
+        assert_eq!(settings.key_pin(Path::new("/home/alice/notes.txt")), None);
+        assert_eq!(settings.key_pin(Path::new("/work/clients/globex/plan.pdf")).unwrap().key_name, "Clients");
+
+        // The innermost folder's pin is used, and pinning a folder again replaces its pin
+        settings.pin_key(pin("/work/clients/acme", "Acme"));
+        assert_eq!(settings.key_pins.len(), 2);
+        assert_eq!(settings.key_pin(Path::new("/work/clients/acme/delivery/report.pdf")).unwrap().key_name, "Acme");
+        assert_eq!(settings.key_pin(Path::new("/work/clients/acme")).unwrap().key_name, "Acme");
+
+        // Folders are compared by whole names
+        assert_eq!(settings.key_pin(Path::new("/work/clients-archive/old.pdf")), None);
+    }
+
+    #[test]
+    fn test_batch_limits() {
+        let mut limits = BatchLimits::default();
+        assert!(!limits.exceeded_by(10_000, 100 * 1024 * 1024 * 1024));
+        assert!(limits.exceeded_by(10_001, 0));
+        assert!(limits.exceeded_by(1, 100 * 1024 * 1024 * 1024 + 1));
+
+        limits.enabled = false;
+        assert!(!limits.exceeded_by(10_001, 0));
+    }
+
+    #[test]
+    fn test_open_action() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.open_action(Path::new("report.docx")), OpenAction::Select);
+
+        settings.set_open_action(".DOCX", OpenAction::ViewSecurely);
+        settings.set_open_action("zip", OpenAction::Decrypt);
+        assert_eq!(settings.open_action(Path::new("Report.Docx")), OpenAction::ViewSecurely);
+        assert_eq!(settings.open_action(Path::new("photos.zip")), OpenAction::Decrypt);
+        assert_eq!(settings.open_action(Path::new("README")), OpenAction::Select);
+
+        // Setting an extension again replaces its action
+        settings.set_open_action("docx", OpenAction::Decrypt);
+        assert_eq!(settings.open_actions.len(), 2);
+        assert_eq!(settings.open_action(Path::new("report.docx")), OpenAction::Decrypt);
+    }
+}
@@ -0,0 +1,250 @@
+/// Smartcard (PKCS#11) protected keys.
+///
+/// This module provides functionality for:
+/// - Unwrapping keys that are encrypted to a private key on a PKCS#11 token
+/// - Reporting the token's PIN retry state (count low, final try, locked)
+/// - Caching the PIN for a limited session window
+///
+/// Wrapped keys are encrypted with RSA-OAEP (SHA-256) to the token's key pair.
+/// The unwrapped key is only produced when an operation needs it, after the
+/// user has entered the token PIN.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::error::{Error as Pkcs11Error, RvError};
+use cryptoki::mechanism::{Mechanism, MechanismType};
+use cryptoki::mechanism::rsa::{PkcsMgfType, PkcsOaepParams, PkcsOaepSource};
+use cryptoki::object::{Attribute, ObjectClass};
+use cryptoki::session::UserType;
+use cryptoki::slot::Slot;
+use cryptoki::types::AuthPin;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::encryption::EncryptionKey;
+
+/// Key wrapped by a private key on a PKCS#11 token
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TokenWrappedKey {
+    /// Display name of the key
+    pub name: String,
+    /// Path to the token's PKCS#11 module
+    pub module: PathBuf,
+    /// Label of the token holding the unwrapping key
+    pub token_label: String,
+    /// Label of the private key on the token
+    pub key_label: String,
+    /// Wrapped key (Base64, RSA-OAEP with SHA-256)
+    pub wrapped_key: String,
+}
+
+/// PIN retry state reported by the token
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinStatus {
+    /// No failed attempts reported
+    Ok,
+    /// At least one incorrect PIN has been entered
+    CountLow,
+    /// The next incorrect PIN locks the token
+    FinalTry,
+    /// The PIN is locked
+    Locked,
+}
+
+/// Error type for smartcard operations
+#[derive(Debug, Error)]
+pub enum TokenError {
+    /// The PIN was rejected by the token
+    #[error("Incorrect PIN")]
+    PinIncorrect(PinStatus),
+
+    /// The token no longer accepts PIN attempts
+    #[error("The smartcard PIN is locked. Contact your administrator to unblock it.")]
+    PinLocked,
+
+    /// The token is not inserted
+    #[error("Smartcard not found: {0}")]
+    TokenNotFound(String),
+
+    /// The unwrapping key is not on the token
+    #[error("Key not found on smartcard: {0}")]
+    KeyNotFound(String),
+
+    /// Any other PKCS#11 or key format error
+    #[error("Smartcard error: {0}")]
+    Token(String),
+}
+
+impl From<Pkcs11Error> for TokenError {
+    fn from(e: Pkcs11Error) -> Self {
+        TokenError::Token(e.to_string())
+    }
+}
+
+/// Open the PKCS#11 module and find the slot holding the token
+fn open_token(key: &TokenWrappedKey) -> Result<(Pkcs11, Slot), TokenError> {
+    let pkcs11 = Pkcs11::new(&key.module)
+        .map_err(|e| TokenError::Token(format!("Failed to load {}: {}", key.module.display(), e)))?;
+    pkcs11.initialize(CInitializeArgs::OsThreads)?;
+
+    for slot in pkcs11.get_slots_with_token()? {
+        if pkcs11.get_token_info(slot)?.label().trim() == key.token_label {
+            return Ok((pkcs11, slot));
+        }
+    }
+
+    Err(TokenError::TokenNotFound(key.token_label.clone()))
+}
+
+/// Read the PIN retry state of an open token
+fn read_pin_status(pkcs11: &Pkcs11, slot: Slot) -> Result<PinStatus, TokenError> {
+    let info = pkcs11.get_token_info(slot)?;
+
+    Ok(if info.user_pin_locked() {
+        PinStatus::Locked
+    } else if info.user_pin_final_try() {
+        PinStatus::FinalTry
+    } else if info.user_pin_count_low() {
+        PinStatus::CountLow
+    } else {
+        PinStatus::Ok
+    })
+}
+
+/// Get the PIN retry state of the token holding the key
+pub fn pin_status(key: &TokenWrappedKey) -> Result<PinStatus, TokenError> {
+    let (pkcs11, slot) = open_token(key)?;
+    read_pin_status(&pkcs11, slot)
+}
+
+/// Unwrap a key using the token
+///
+/// # Arguments
+/// * `key` - The wrapped key
+/// * `pin` - The token's user PIN
+///
+/// # Returns
+/// * `Result<EncryptionKey, TokenError>` - The unwrapped key, or an error (including
+///   the token's retry state if the PIN was rejected)
+pub fn unwrap_key(key: &TokenWrappedKey, pin: &str) -> Result<EncryptionKey, TokenError> {
+    let wrapped = STANDARD.decode(key.wrapped_key.as_bytes())
+        .map_err(|e| TokenError::Token(format!("Invalid wrapped key: {}", e)))?;
+
+    let (pkcs11, slot) = open_token(key)?;
+
+    // Never send a PIN to a locked token
+    if read_pin_status(&pkcs11, slot)? == PinStatus::Locked {
+        return Err(TokenError::PinLocked);
+    }
+
+    let session = pkcs11.open_ro_session(slot)?;
+    match session.login(UserType::User, Some(&AuthPin::new(pin.to_string()))) {
+        Ok(()) | Err(Pkcs11Error::Pkcs11(RvError::UserAlreadyLoggedIn)) => {},
+        Err(Pkcs11Error::Pkcs11(RvError::PinIncorrect)) => {
+            return match read_pin_status(&pkcs11, slot)? {
+                PinStatus::Locked => Err(TokenError::PinLocked),
+                status => Err(TokenError::PinIncorrect(status)),
+            };
+        },
+        Err(Pkcs11Error::Pkcs11(RvError::PinLocked)) => return Err(TokenError::PinLocked),
+        Err(e) => return Err(e.into()),
+    }
+
+    let template = [
+        Attribute::Class(ObjectClass::PRIVATE_KEY),
+        Attribute::Label(key.key_label.as_bytes().to_vec()),
+    ];
+    let private_key = session.find_objects(&template)?
+        .first()
+        .copied()
+        .ok_or_else(|| TokenError::KeyNotFound(key.key_label.clone()))?;
+
+    let mechanism = Mechanism::RsaPkcsOaep(PkcsOaepParams::new(
+        MechanismType::SHA256,
+        PkcsMgfType::MGF1_SHA256,
+        PkcsOaepSource::empty(),
+    ));
+    let unwrapped = Zeroizing::new(session.decrypt(&mechanism, private_key, &wrapped)?);
+    let _ = session.logout();
+
+    let key: [u8; 32] = unwrapped.as_slice().try_into()
+        .map_err(|_| TokenError::Token(format!("Invalid unwrapped key length: {} bytes", unwrapped.len())))?;
+
+    Ok(EncryptionKey { key, kdf: None })
+}
+
+/// PINs kept for a limited session window, per token
+pub struct PinCache {
+    /// How long a PIN may be reused after it was entered
+    window: Duration,
+    /// Cached PINs by token label, with the time they were entered
+    entries: HashMap<String, (Zeroizing<String>, Instant)>,
+}
+
+impl PinCache {
+    /// Create a cache with the given session window (zero disables caching)
+    pub fn new(window: Duration) -> Self {
+        PinCache {
+            window,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Get the cached PIN for a token, if it is still within the session window
+    pub fn get(&mut self, token_label: &str) -> Option<Zeroizing<String>> {
+        self.expire();
+        self.entries.get(token_label).map(|(pin, _)| pin.clone())
+    }
+
+    /// Remember a PIN for the session window
+    pub fn store(&mut self, token_label: &str, pin: &str) {
+        if !self.window.is_zero() {
+            self.entries.insert(token_label.to_string(), (Zeroizing::new(pin.to_string()), Instant::now()));
+        }
+    }
+
+    /// Forget the PIN for a token
+    pub fn remove(&mut self, token_label: &str) {
+        self.entries.remove(token_label);
+    }
+
+    /// Forget every cached PIN
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Forget PINs whose session window has passed
+    pub fn expire(&mut self) {
+        let window = self.window;
+        self.entries.retain(|_, (_, entered)| entered.elapsed() < window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_cache_window() {
+        let mut cache = PinCache::new(Duration::from_secs(60));
+        cache.store("Token", "1234");
+
+        assert_eq!(cache.get("Token").as_deref().map(String::as_str), Some("1234"));
+        assert!(cache.get("Other Token").is_none());
+
+        cache.remove("Token");
+        assert!(cache.get("Token").is_none());
+    }
+
+    #[test]
+    fn test_pin_cache_disabled() {
+        let mut cache = PinCache::new(Duration::ZERO);
+        cache.store("Token", "1234");
+
+        assert!(cache.get("Token").is_none());
+    }
+}
@@ -0,0 +1,204 @@
+/// Operation speed history module.
+///
+/// This module provides functionality for:
+/// - Recording the throughput of finished files per backend, cipher, and file size
+/// - Saving the history (speed_history.json) in the application data directory
+/// - Estimating how long files will take, for time-remaining estimates
+///
+/// Small files are dominated by fixed costs (opening files, deriving nonces, device
+/// round trips), so speeds are kept per file-size bucket rather than as one average.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::shared_files;
+
+/// Cipher used for all operations
+pub const CIPHER_AES_256_GCM: &str = "AES-256-GCM";
+
+/// Weight of a new sample once a speed has a few samples
+const SMOOTHING: f64 = 0.3;
+
+/// Upper bounds of the file-size buckets (the last bucket has no bound)
+const BUCKET_LIMITS: [u64; 4] = [
+    1024 * 1024,             // < 1 MB
+    16 * 1024 * 1024,        // < 16 MB
+    256 * 1024 * 1024,       // < 256 MB
+    4 * 1024 * 1024 * 1024,  // < 4 GB
+];
+
+/// Backend that processed a file
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    /// Software encryption on this computer
+    Local,
+    /// Embedded encryption device
+    Embedded,
+}
+
+/// Average speed for one backend, cipher, and file-size bucket
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SpeedRecord {
+    pub backend: BackendKind,
+    pub cipher: String,
+    /// File-size bucket (see `size_bucket`)
+    pub bucket: usize,
+    /// Smoothed throughput in bytes per second
+    pub bytes_per_sec: f64,
+    /// Number of files measured
+    pub samples: u32,
+}
+
+/// Throughput measured in earlier operations
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct SpeedHistory {
+    pub records: Vec<SpeedRecord>,
+}
+
+/// Get the file-size bucket of a file
+pub fn size_bucket(size_bytes: u64) -> usize {
+    BUCKET_LIMITS.iter()
+        .position(|&limit| size_bytes < limit)
+        .unwrap_or(BUCKET_LIMITS.len())
+}
+
+impl SpeedHistory {
+    /// Location of the speed history in the app's data folder
+    pub fn path_in(data_dir: &Path) -> PathBuf {
+        data_dir.join("speed_history.json")
+    }
+
+    /// Load the history from the specified file
+    ///
+    /// # Returns
+    /// * `io::Result<SpeedHistory>` - The history, an empty history if the file does not
+    ///   exist, or an error if the file cannot be read or parsed
+    pub fn load_from(path: &Path) -> io::Result<Self> {
+        if !path.exists() {
+            return Ok(SpeedHistory::default());
+        }
+
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid speed history: {}", e)))
+    }
+
+    /// Save the history to the specified file
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        shared_files::write_atomic(path, content)
+    }
+
+    /// Record the time taken to process a file
+    ///
+    /// The first few samples are averaged; after that, newer samples weigh more, so the
+    /// history follows changes such as a faster disk or a new device firmware.
+    pub fn record(&mut self, backend: BackendKind, cipher: &str, size_bytes: u64, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if size_bytes == 0 || seconds <= 0.0 {
+            return;
+        }
+        let speed = size_bytes as f64 / seconds;
+        let bucket = size_bucket(size_bytes);
+
+        match self.records.iter_mut().find(|r| r.backend == backend && r.cipher == cipher && r.bucket == bucket) {
+            Some(record) => {
+                record.samples += 1;
+                let weight = SMOOTHING.max(1.0 / record.samples as f64);
+                record.bytes_per_sec += (speed - record.bytes_per_sec) * weight;
+            },
+            None => self.records.push(SpeedRecord {
+                backend,
+                cipher: cipher.to_string(),
+                bucket,
+                bytes_per_sec: speed,
+                samples: 1,
+            }),
+        }
+    }
+
+    /// Get the expected throughput for a file
+    ///
+    /// Without a record for the file's size bucket, the record of the nearest bucket
+    /// for the same backend and cipher is used.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - Bytes per second, or None if the backend and cipher were never measured
+    pub fn speed(&self, backend: BackendKind, cipher: &str, size_bytes: u64) -> Option<f64> {
+        let bucket = size_bucket(size_bytes);
+        self.records.iter()
+            .filter(|r| r.backend == backend && r.cipher == cipher)
+            .min_by_key(|r| r.bucket.abs_diff(bucket))
+            .map(|r| r.bytes_per_sec)
+    }
+
+    /// Estimate the time to process files
+    ///
+    /// # Arguments
+    /// * `files` - Size of each file and the fraction of it already processed
+    ///
+    /// # Returns
+    /// * `Option<Duration>` - The estimate, or None if there is no history for the backend
+    pub fn estimate(&self, backend: BackendKind, cipher: &str, files: &[(u64, f32)]) -> Option<Duration> {
+        let mut seconds = 0.0;
+        for &(size, done) in files {
+            let remaining = size as f64 * (1.0 - done.clamp(0.0, 1.0) as f64);
+            if remaining > 0.0 {
+                seconds += remaining / self.speed(backend, cipher, size)?;
+            }
+        }
+        Some(Duration::from_secs_f64(seconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_speed_buckets() {
+        assert_eq!(size_bucket(0), 0);
+        assert_eq!(size_bucket(MB), 1);
+        assert_eq!(size_bucket(100 * MB), 2);
+        assert_eq!(size_bucket(10 * 1024 * MB), 4);
+
+        let mut history = SpeedHistory::default();
+        assert_eq!(history.speed(BackendKind::Local, CIPHER_AES_256_GCM, MB), None);
+
+        // 8 MB/s for a 4 MB file, then 12 MB/s: the first samples are averaged
+        history.record(BackendKind::Local, CIPHER_AES_256_GCM, 4 * MB, Duration::from_millis(500));
+        history.record(BackendKind::Local, CIPHER_AES_256_GCM, 6 * MB, Duration::from_millis(500));
+        assert_eq!(history.speed(BackendKind::Local, CIPHER_AES_256_GCM, 2 * MB), Some(10.0 * MB as f64));
+
+        // Other sizes use the nearest bucket, other backends have no history
+        assert_eq!(history.speed(BackendKind::Local, CIPHER_AES_256_GCM, 1024 * MB), Some(10.0 * MB as f64));
+        assert_eq!(history.speed(BackendKind::Embedded, CIPHER_AES_256_GCM, 2 * MB), None);
+
+        let estimate = history.estimate(BackendKind::Local, CIPHER_AES_256_GCM, &[(20 * MB, 0.0), (20 * MB, 0.5)]);
+        assert_eq!(estimate, Some(Duration::from_secs(3)));
+        assert_eq!(history.estimate(BackendKind::Embedded, CIPHER_AES_256_GCM, &[(MB, 0.0)]), None);
+    }
+
+    #[test]
+    fn test_speed_history_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("speed_history.json");
+        assert!(SpeedHistory::load_from(&path).unwrap().records.is_empty());
+
+        let mut history = SpeedHistory::default();
+        history.record(BackendKind::Embedded, CIPHER_AES_256_GCM, 100 * MB, Duration::from_secs(4));
+        history.save_to(&path).unwrap();
+
+        let loaded = SpeedHistory::load_from(&path).unwrap();
+        assert_eq!(loaded.records, history.records);
+    }
+}
@@ -1,9 +1,19 @@
+use std::ffi::OsString;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
+use std::time::Instant;
 
-use crate::backend::BackendFactory;
-use crate::gui::CrustyApp;
-use crate::logger::get_logger;
+use crate::archive;
+use crate::batch_manifest;
+use crate::duplicates;
+use crate::backend::{Backend, BackendFactory, LocalBackend};
+use crate::encryption::{EncryptionError, EncryptionKey};
+use crate::file_names::{self, OutputName};
+use crate::in_place;
+use crate::logger::{get_logger, LogEntry, Logger};
+use crate::services::OperationService;
 
 /// Enum for file operations
 #[derive(Clone)]
@@ -13,286 +23,436 @@ pub enum FileOperation {
     Decrypt,
     BatchEncrypt,
     BatchDecrypt,
+    /// Pack the selected folder into one archive
+    Archive,
+    /// Extract the selected archives
+    ExtractArchive,
 }
 
-/// Start the selected operation using the appropriate backend
-pub fn start_operation(app: &mut CrustyApp) {
-        // Reset the progress and results
+/// Pauses and resumes a running operation
+///
+/// The operation thread waits while paused each time it reports progress: after a
+/// file is read (before it is encrypted or decrypted and written) and after each file.
+/// Files are never left half written while paused.
+#[derive(Clone, Default)]
+pub struct PauseControl {
+    state: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl PauseControl {
+    /// Pause the operation at its next progress report
+    pub fn pause(&self) {
+        *self.state.0.lock().unwrap() = true;
+    }
+    
+    /// Resume a paused operation
+    pub fn resume(&self) {
+        *self.state.0.lock().unwrap() = false;
+        self.state.1.notify_all();
+    }
+    
+    /// Check whether the operation is paused (or will pause at its next progress report)
+    pub fn is_paused(&self) -> bool {
+        *self.state.0.lock().unwrap()
+    }
+    
+    /// Wait until the operation is resumed (returns at once when not paused)
+    pub fn wait_while_paused(&self) {
+        let (paused, resumed) = &*self.state;
+        let _guard = resumed.wait_while(paused.lock().unwrap(), |paused| *paused).unwrap();
+    }
+}
+
+/// Progress callback for a single file operation, which waits while paused
+fn file_progress(progress: &Arc<Mutex<Vec<f32>>>, pause: &PauseControl) -> impl Fn(f32) + Send + 'static {
+    let progress = progress.clone();
+    let pause = pause.clone();
+    move |p| {
         {
-            let mut progress = app.progress.lock().unwrap();
-            progress.clear();
-            progress.resize(app.selected_files.len(), 0.0);
+            let mut guard = progress.lock().unwrap();
+            if !guard.is_empty() {
+                guard[0] = p;
+            }
         }
+        pause.wait_while_paused();
+    }
+}
+
+/// Progress callback for a batch operation, which waits while paused
+///
+/// `offset` is the index of the first file of the batch among the selected files.
+fn batch_progress(progress: &Arc<Mutex<Vec<f32>>>, pause: &PauseControl, offset: usize) -> impl Fn(usize, f32) + Clone + Send + 'static {
+    let progress = progress.clone();
+    let pause = pause.clone();
+    move |idx, p| {
+        {
+            let mut guard = progress.lock().unwrap();
+            if offset + idx < guard.len() {
+                guard[offset + idx] = p;
+            }
+        }
+        pause.wait_while_paused();
+    }
+}
+
+/// Progress callback for an archive, which is one file however many files it holds:
+/// every selected file shows its progress
+fn archive_progress(progress: &Arc<Mutex<Vec<f32>>>, pause: &PauseControl) -> impl Fn(f32) + Send + 'static {
+    let progress = progress.clone();
+    let pause = pause.clone();
+    move |p| {
+        progress.lock().unwrap().iter_mut().for_each(|file_progress| *file_progress = p);
+        pause.wait_while_paused();
+    }
+}
+
+/// Progress callback for the duplicate check before a batch is encrypted, which waits
+/// while paused
+fn scan_progress(scan: &Arc<Mutex<Option<f32>>>, pause: &PauseControl) -> impl Fn(f32) + Send + 'static {
+    let scan = scan.clone();
+    let pause = pause.clone();
+    move |p| {
+        *scan.lock().unwrap() = Some(p);
+        pause.wait_while_paused();
+    }
+}
+
+/// Result of a file, reported by the operation thread once the file is done
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileResult {
+    /// Success message, or failure message
+    pub outcome: Result<String, String>,
+    /// File written (None if the file failed)
+    pub output: Option<PathBuf>,
+    /// Fingerprint of the key the file was processed with
+    pub key_fingerprint: String,
+}
+
+/// Result of each selected file (None while it is not done)
+pub type FileResults = Arc<Mutex<Vec<Option<FileResult>>>>;
+
+/// Reports the result of each file of an operation to the file list
+struct ResultReporter {
+    results: FileResults,
+    key_fingerprint: String,
+}
+
+impl ResultReporter {
+    /// Record the result of a file, unless it already has one
+    fn report(&self, index: usize, outcome: Result<String, String>, output: Option<PathBuf>) {
+        let mut guard = self.results.lock().unwrap();
+        if let Some(slot @ None) = guard.get_mut(index) {
+            let output = output.filter(|_| outcome.is_ok());
+            *slot = Some(FileResult { outcome, output, key_fingerprint: self.key_fingerprint.clone() });
+        }
+    }
+    
+    /// Record the results of a batch from the backend's messages
+    ///
+    /// `offset` is the index of the first file of the batch among the selected files;
+    /// `output_of` gives the file written for each file of the batch and its message.
+    fn report_batch(&self, offset: usize, messages: &[String], output_of: impl Fn(usize, &str) -> Option<PathBuf>) {
+        for (i, message) in messages.iter().enumerate() {
+            let outcome = if message.contains("Successfully") { Ok(message.clone()) } else { Err(message.clone()) };
+            self.report(offset + i, outcome, output_of(i, message));
+        }
+    }
+    
+    /// Get the file written for a file, if it succeeded
+    fn output(&self, index: usize) -> Option<PathBuf> {
+        self.results.lock().unwrap().get(index)?.as_ref()?.output.clone()
+    }
+    
+    /// Fail every file that has no result yet (files not processed after an error that
+    /// stopped the operation)
+    fn fail_unreported(&self, reason: impl Fn(usize) -> String) {
+        let len = self.results.lock().unwrap().len();
+        for index in 0..len {
+            self.report(index, Err(reason(index)), None);
+        }
+    }
+}
+
+/// Get the folder under the output directory a file is saved in, creating it if needed
+/// (if it cannot be created, writing the file fails and says why)
+fn output_folder(output_dir: &Path, subdir: &Path) -> PathBuf {
+    let folder = output_dir.join(subdir);
+    let _ = fs::create_dir_all(&folder);
+    folder
+}
+
+/// Run a batch operation one output folder at a time
+///
+/// Files selected from a folder are saved in the same subfolders under the output
+/// directory. Each run of consecutive files with the same subfolder is passed to `run`
+/// with its output folder and the index of its first file (for progress).
+fn batch_by_output_folder(
+    files: &[PathBuf],
+    subdirs: &[PathBuf],
+    output_dir: &Path,
+    mut run: impl FnMut(&[&Path], &Path, usize) -> Result<Vec<String>, EncryptionError>,
+) -> Result<Vec<String>, EncryptionError> {
+    let mut results = Vec::new();
+    let mut start = 0;
+    while start < files.len() {
+        let end = (start..files.len())
+            .find(|&i| subdirs[i] != subdirs[start])
+            .unwrap_or(files.len());
+        let paths: Vec<&Path> = files[start..end].iter().map(PathBuf::as_path).collect();
+        results.extend(run(&paths, &output_folder(output_dir, &subdirs[start]), start)?);
+        start = end;
+    }
+    Ok(results)
+}
+
+/// Start the selected operation with the given key using the appropriate backend
+pub fn start_operation(operations: &mut OperationService, key: EncryptionKey) {
+        // Reset the progress and results
+        operations.reset_progress();
         
-        // Clear results
-        app.operation_results.clear();
-        
-        let key = app.current_key.clone().unwrap();
-        let files: Vec<PathBuf> = app.selected_files.clone();
-        let output_dir = app.output_dir.clone().unwrap();
-        let progress = app.progress.clone();
-        let operation = app.operation.clone();
-        let use_recipient = app.use_recipient;
-        let recipient_email = app.recipient_email.clone();
+        let files: Vec<PathBuf> = operations.selected_files().to_vec();
+        // Subfolders of the output directory, for files selected from a folder
+        let subdirs: Vec<PathBuf> = files.iter().map(|file| operations.output_subdir(file)).collect();
+        // Not set when the originals are replaced
+        let output_dir = operations.output_dir().map(Path::to_path_buf).unwrap_or_default();
+        let progress = operations.progress_handle();
+        let duplicate_groups = operations.duplicates_handle();
+        let duplicate_scan = operations.duplicate_scan_handle();
+        let pause = operations.pause_handle();
+        let operation = operations.operation().clone();
+        // age files and OpenPGP messages have no header to store names in, and other tools
+        // cannot read parity
+        let age_recipients = operations.age_format.then(|| operations.age_recipients.clone());
+        let openpgp_recipients = (operations.openpgp_format && age_recipients.is_none())
+            .then(|| operations.openpgp_recipients.clone());
+        let other_format = age_recipients.is_some() || openpgp_recipients.is_some();
+        // Files for several recipients are read with the recipients' keys, which cannot
+        // open a stored name, and are never written in place of the originals
+        let recipients = operations.recipients().filter(|_| !other_format);
+        let recipient_names = recipients.as_ref()
+            .map(|recipients| recipients.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "));
+        let hide_file_names = operations.hide_file_names && !other_format && recipients.is_none();
+        let replace_originals = operations.replace_originals && recipients.is_none();
+        let shred_originals = operations.shred_originals;
+        let add_parity = operations.add_parity;
+        let memory_limit = operations.memory_limit;
+        let add_digest = operations.add_digest;
+        // Originals replaced in place have no output directory to list them in
+        let write_manifest = operations.write_manifest && !replace_originals;
+        let archive_folder = operations.selected_folder().map(Path::to_path_buf);
         
         // Create the appropriate backend
-        let backend = if app.use_embedded_backend {
-            // Use embedded backend with connection type and device ID
-            let config = crate::backend::EmbeddedConfig {
-                connection_type: app.embedded_connection_type.clone(),
-                device_id: app.embedded_device_id.clone(),
-                parameters: std::collections::HashMap::new(),
-            };
-            BackendFactory::create_embedded(config)
+        let mut backend = if operations.backend.use_embedded && !other_format && recipients.is_none() {
+            // Use embedded backend with the settings for the connection type
+            BackendFactory::create_embedded(operations.backend.config(), operations.device_progress_handle())
         } else {
-            // Use local backend by default
-            BackendFactory::create_local()
+            // Use local backend by default (and always for the age and OpenPGP formats, and
+            // for several recipients)
+            BackendFactory::create_local_with(LocalBackend {
+                add_parity: add_parity && !other_format,
+                add_digest: add_digest && !other_format,
+                age_recipients,
+                openpgp_recipients,
+                recipients,
+                memory_limit,
+            })
         };
         
+        // Operations are logged with the key's fingerprint, never the key
+        let key_fingerprint = key.fingerprint();
+        let reporter = ResultReporter { results: operations.results_handle(), key_fingerprint: key_fingerprint.clone() };
+        let device_error = operations.device_error_handle();
+        
         // Start an async operation based on selected operation type
         thread::spawn(move || {
+            // The embedded device is opened here, as finding it may take a while; if it
+            // cannot be, no file is processed and the status area says why
+            let connection = backend.connect();
+            if let Err(e) = &connection {
+                *device_error.lock().unwrap() = Some(e.to_string());
+                reporter.fail_unreported(|i| format!("Not processed: {}: {}", files[i].display(), e));
+                if let Some(logger) = get_logger() {
+                    logger.log_error("Connect Device", "", &e.to_string()).ok();
+                }
+            }
+            
             match operation {
+                _ if connection.is_err() => {},
                 FileOperation::Encrypt => {
                     if let Some(file_path) = files.first() {
                         let file_path = file_path.clone(); // Clone the PathBuf
                         
-                        let file_name = file_path.file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy();
-                            
-                        let mut output_path = output_dir.clone();
-                        output_path.push(format!("{}.encrypted", file_name));
+                        // The name is kept exactly, whatever its characters or encoding, or
+                        // replaced with a random name when it is stored in the file instead
+                        let hide_name = hide_file_names;
+                        let in_place = replace_originals;
+                        let mut output_path = if in_place {
+                            in_place::original_dir(&file_path)
+                        } else {
+                            output_folder(&output_dir, &subdirs[0])
+                        };
+                        if hide_name {
+                            output_path.push(file_names::opaque_encrypted_name());
+                        } else {
+                            output_path.push(file_names::encrypted_name(&file_path).unwrap_or_default());
+                        }
                         
-                        let result = if use_recipient && !recipient_email.trim().is_empty() {
-                            // Use recipient-based encryption
-                            let progress_clone = progress.clone();
-                            backend.encrypt_file_for_recipient(
+                        let started = Instant::now();
+                        let result = if in_place {
+                            in_place::encrypt_in_place(
+                                &backend,
                                 &file_path,
                                 &output_path,
                                 &key,
-                                &recipient_email,
-                                move |p| {
-                                    let mut guard = progress_clone.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = p;
-                                    }
-                                }
+                                hide_name,
+                                shred_originals,
+                                file_progress(&progress, &pause)
+                            )
+                        } else if hide_name {
+                            backend.encrypt_file_storing_name(
+                                &file_path,
+                                &output_path,
+                                &key,
+                                file_path.file_name().unwrap_or_default(),
+                                file_progress(&progress, &pause)
                             )
                         } else {
                             // Use standard encryption
-                            let progress_clone = progress.clone();
                             backend.encrypt_file(
                                 &file_path,
                                 &output_path,
                                 &key,
-                                move |p| {
-                                    let mut guard = progress_clone.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = p;
-                                    }
-                                }
+                                file_progress(&progress, &pause)
                             )
                         };
+                        let duration = started.elapsed();
+                            
+                        // Report the result to the file list
+                        let file_result = match &result {
+                            Ok(_) => Ok(match &recipient_names {
+                                Some(names) => format!("Successfully encrypted for {}: {}", names, file_path.display()),
+                                None => format!("Successfully encrypted: {}", file_path.display()),
+                            }),
+                            Err(e) => Err(format!("Failed to encrypt {}: {}", file_path.display(), e)),
+                        };
+                        reporter.report(0, file_result, Some(output_path.clone()));
                             
-                        // Log the result
+                        // Log the result, with the time it took
                         if let Some(logger) = get_logger() {
-                            match &result {
+                            let entry = match &result {
                                 Ok(_) => {
-                                    let operation_name = if use_recipient {
-                                        format!("Encrypt for {}", recipient_email)
-                                    } else {
-                                        "Encrypt".to_string()
+                                    let operation_name = match &recipient_names {
+                                        Some(names) => format!("Encrypt for {}", names),
+                                        None => "Encrypt".to_string(),
                                     };
                                     
-                                    logger.log_success(
-                                        &operation_name,
-                                        &file_path.to_string_lossy(),
-                                        "Encryption successful"
-                                    ).ok();
-                                    
-                                    // Store result
-                                    let _result_msg = if use_recipient {
-                                        format!("Successfully encrypted for {}: {}", recipient_email, file_path.display())
+                                    let message = if in_place {
+                                        format!("Encryption successful, original replaced by {}", logger.redact_path(&output_path))
+                                    } else if hide_name {
+                                        format!("Encryption successful, saved as {}", logger.redact_path(&output_path))
                                     } else {
-                                        format!("Successfully encrypted: {}", file_path.display())
+                                        "Encryption successful".to_string()
                                     };
-                                    
-                                    // Add to operation_results in the next UI update
-                                    let mut guard = progress.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = 1.0; // Mark as complete
-                                    }
+                                    LogEntry::new(&operation_name, &file_path.to_string_lossy(), true, &message)
                                 },
-                                Err(e) => {
-                                    let error_str = e.to_string();
-                                    logger.log_error(
-                                        "Encrypt",
-                                        &file_path.to_string_lossy(),
-                                        &error_str
-                                    ).ok();
-                                    
-                                    // Store error
-                                    let _error_msg = format!("Failed to encrypt {}: {}", file_path.display(), error_str);
-                                    
-                                    // Add to operation_results in the next UI update
-                                    let mut guard = progress.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = 1.0; // Mark as complete
-                                    }
-                                }
-                            }
+                                Err(e) => LogEntry::new("Encrypt", &file_path.to_string_lossy(), false, &logged_error(&logger, e)),
+                            };
+                            logger.log(entry.with_key_fingerprint(&key_fingerprint).with_duration(duration)).ok();
                         }
                     }
                 },
                 FileOperation::Decrypt => {
                     if let Some(file_path) = files.first() {
-                        let file_name = file_path.file_name()
-                            .unwrap_or_default()
-                            .to_string_lossy();
-                            
-                        let file_stem = file_name.to_string();
-                        let output_name = if file_stem.ends_with(".encrypted") {
-                            file_stem.trim_end_matches(".encrypted").to_string()
-                        } else {
-                            format!("{}.decrypted", file_stem)
-                        };
+                        let output_name = file_names::restored_name(file_path, &key).unwrap_or(OutputName {
+                            name: OsString::from(file_names::DECRYPTED_EXTENSION),
+                            renamed: None,
+                        });
                         
-                        let mut output_path = output_dir.clone();
-                        output_path.push(output_name);
+                        let mut output_path = output_folder(&output_dir, &subdirs[0]);
+                        output_path.push(&output_name.name);
                         
-                        // Try recipient-based decryption first, fall back to standard decryption if it fails
-                        let result = if use_recipient {
-                            let progress_clone = progress.clone();
-                            match backend.decrypt_file_with_recipient(
-                                file_path,
-                                &output_path,
-                                &key,
-                                move |p| {
-                                    let mut guard = progress_clone.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = p;
-                                    }
-                                }
-                            ) {
-                                Ok((_email, _)) => {
-                                    // Store the detected recipient email
-                                    // Add to operation_results in the next UI update
-                                    let mut guard = progress.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = 1.0; // Mark as complete
-                                    }
-                                    Ok(())
-                                },
-                                Err(_e) => {
-                                    // Fall back to standard decryption
-                                    let progress_clone = progress.clone();
-                                    backend.decrypt_file(
-                                        file_path,
-                                        &output_path,
-                                        &key,
-                                        move |p| {
-                                            let mut guard = progress_clone.lock().unwrap();
-                                            if !guard.is_empty() {
-                                                guard[0] = p;
-                                            }
-                                        }
-                                    )
-                                }
-                            }
-                        } else {
-                            // Use standard decryption
-                            let progress_clone = progress.clone();
-                            backend.decrypt_file(
-                                file_path,
-                                &output_path,
-                                &key,
-                                move |p| {
-                                    let mut guard = progress_clone.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = p;
-                                    }
-                                }
-                            )
+                        // Files for several recipients are recognised by their header
+                        let started = Instant::now();
+                        let result = backend.decrypt_file(
+                            file_path,
+                            &output_path,
+                            &key,
+                            file_progress(&progress, &pause)
+                        );
+                        let duration = started.elapsed();
+                        
+                        // Report the result to the file list, with a specific message for a wrong key
+                        let file_result = match &result {
+                            Ok(_) => Ok(match &output_name.renamed {
+                                Some(reason) => format!("Successfully decrypted: {} (saved as {} because {})", file_path.display(), output_path.display(), reason),
+                                None => format!("Successfully decrypted: {}", file_path.display()),
+                            }),
+                            Err(e) => {
+                                let error_str = e.to_string();
+                                Err(if error_str.contains("authentication") || error_str.contains("tag mismatch") {
+                                    format!("Failed to decrypt {}: Wrong encryption key used. Please try a different key.", file_path.display())
+                                } else {
+                                    format!("Failed to decrypt {}: {}", file_path.display(), error_str)
+                                })
+                            },
                         };
+                        reporter.report(0, file_result, Some(output_path.clone()));
                         
-                        // Log the result
+                        // Log the result, with the time it took
                         if let Some(logger) = get_logger() {
-                            match &result {
+                            let entry = match &result {
                                 Ok(_) => {
-                                    logger.log_success(
-                                        "Decrypt",
-                                        &file_path.to_string_lossy(),
-                                        "Decryption successful"
-                                    ).ok();
-                                    
-                                    // Store result
-                                    let _result_msg = format!("Successfully decrypted: {}", file_path.display());
-                                    
-                                    // Add to operation_results in the next UI update
-                                    let mut guard = progress.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = 1.0; // Mark as complete
-                                    }
-                                },
-                                Err(e) => {
-                                    let error_str = e.to_string();
-                                    logger.log_error(
-                                        "Decrypt",
-                                        &file_path.to_string_lossy(),
-                                        &error_str
-                                    ).ok();
-                                    
-                                    // Store error with specific message for wrong key
-                                    let _error_msg = if error_str.contains("authentication") || error_str.contains("tag mismatch") {
-                                        format!("Failed to decrypt {}: Wrong encryption key used. Please try a different key.", file_path.display())
-                                    } else {
-                                        format!("Failed to decrypt {}: {}", file_path.display(), error_str)
+                                    let message = match &output_name.renamed {
+                                        Some(reason) => format!("Decryption successful, saved as {} because {}", logger.redact_path(&output_path), reason),
+                                        None => "Decryption successful".to_string(),
                                     };
-                                    
-                                    // Add to operation_results in the next UI update
-                                    let mut guard = progress.lock().unwrap();
-                                    if !guard.is_empty() {
-                                        guard[0] = 1.0; // Mark as complete
-                                    }
-                                }
-                            }
+                                    LogEntry::new("Decrypt", &file_path.to_string_lossy(), true, &message)
+                                },
+                                Err(e) => LogEntry::new("Decrypt", &file_path.to_string_lossy(), false, &logged_error(&logger, e)),
+                            };
+                            logger.log(entry.with_key_fingerprint(&key_fingerprint).with_duration(duration)).ok();
                         }
                     }
                 },
                 FileOperation::BatchEncrypt => {
-                    let progress_clone = progress.clone();
                     
                     // Convert Vec<PathBuf> to Vec<&Path>
                     let path_refs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
                     
-                    let results = if use_recipient && !recipient_email.trim().is_empty() {
-                        // Use recipient-based batch encryption
-                        backend.encrypt_files_for_recipient(
+                    // Compared before encrypting, as originals may be replaced
+                    let duplicates = duplicates::find_duplicates(&files, scan_progress(&duplicate_scan, &pause));
+                    *duplicate_scan.lock().unwrap() = None;
+                    
+                    let results = if replace_originals {
+                        let results = encrypt_files_in_place(
+                            &backend,
                             &path_refs,
-                            &output_dir,
                             &key,
-                            &recipient_email,
-                            move |idx, p| {
-                                let mut guard = progress_clone.lock().unwrap();
-                                if idx < guard.len() {
-                                    guard[idx] = p;
-                                }
-                            }
-                        )
+                            hide_file_names,
+                            shred_originals,
+                            batch_progress(&progress, &pause, 0)
+                        );
+                        reporter.report_batch(0, &results, |_, message| saved_path(message));
+                        Ok(results)
                     } else {
-                        // Use standard batch encryption
-                        backend.encrypt_files(
-                            &path_refs,
-                            &output_dir,
-                            &key,
-                            move |idx, p| {
-                                let mut guard = progress_clone.lock().unwrap();
-                                if idx < guard.len() {
-                                    guard[idx] = p;
-                                }
-                            }
-                        )
+                        // Use standard batch encryption (the backend adds the recipients)
+                        batch_by_output_folder(&files, &subdirs, &output_dir, |paths, dest_dir, offset| {
+                            let results = backend.encrypt_files(
+                                paths,
+                                dest_dir,
+                                &key,
+                                hide_file_names,
+                                batch_progress(&progress, &pause, offset)
+                            )?;
+                            reporter.report_batch(offset, &results, |i, message| saved_path(message).or_else(|| {
+                                file_names::encrypted_name(paths[i]).map(|name| dest_dir.join(name))
+                            }));
+                            Ok(results)
+                        })
                     };
+                    if let Err(e) = &results {
+                        reporter.fail_unreported(|i| format!("Failed to encrypt {}: {}", files[i].display(), e));
+                    }
                 
                     // Log the results
                     if let Some(logger) = get_logger() {
@@ -304,47 +464,96 @@ pub fn start_operation(app: &mut CrustyApp) {
                                     "Unknown file".to_string()
                                 };
                                 
+                                // The file written, and the folder named by errors writing it
+                                let folder = files.get(i).map(|file| if replace_originals {
+                                    in_place::original_dir(file)
+                                } else {
+                                    output_dir.join(&subdirs[i])
+                                });
+                                let output = reporter.output(i);
+                                let paths: Vec<&Path> = [files.get(i).map(PathBuf::as_path), output.as_deref(), folder.as_deref()]
+                                    .into_iter().flatten().collect();
+                                let message = logged_result(&logger, result, &paths);
+                                
                                 if result.contains("Successfully") {
-                                    let operation_name = if use_recipient {
-                                        format!("Batch Encrypt for {}", recipient_email)
-                                    } else {
-                                        "Batch Encrypt".to_string()
+                                    let operation_name = match &recipient_names {
+                                        Some(names) => format!("Batch Encrypt for {}", names),
+                                        None => "Batch Encrypt".to_string(),
                                     };
                                     
-                                    logger.log_success(&operation_name, &file_path, result).ok();
+                                    logger.log_key_success(&operation_name, &file_path, &message, &key_fingerprint).ok();
                                 } else {
-                                    logger.log_error("Batch Encrypt", &file_path, result).ok();
+                                    logger.log_key_error("Batch Encrypt", &file_path, &message, &key_fingerprint).ok();
                                 }
                             }
                         } else if let Err(e) = &results {
-                            let error_str = e.to_string();
-                            logger.log_error(
+                            let error_str = logged_error(&logger, e);
+                            logger.log_key_error(
                                 "Batch Encrypt",
                                 "multiple files",
-                                &error_str
+                                &error_str,
+                                &key_fingerprint
+                            ).ok();
+                        }
+                        
+                        if !duplicates.is_empty() {
+                            logger.log_success(
+                                "Find Duplicates",
+                                "multiple files",
+                                &format!("{} group(s) of files with the same contents: {}", duplicates.len(), duplicates::describe(&duplicates, |file| logger.redact_path(file))),
                             ).ok();
                         }
                     }
-                },
-                FileOperation::BatchDecrypt => {
-                    let progress_clone = progress.clone();
+                    *duplicate_groups.lock().unwrap() = duplicates;
                     
-                    // Convert Vec<PathBuf> to Vec<&Path>
-                    let path_refs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
-                    
-                    // For batch decryption, we always use standard decryption
-                    // as we can't know which files might be recipient-encrypted
-                    let results = backend.decrypt_files(
-                        &path_refs,
-                        &output_dir,
-                        &key,
-                        move |idx, p| {
-                            let mut guard = progress_clone.lock().unwrap();
-                            if idx < guard.len() {
-                                guard[idx] = p;
+                    // Sign a manifest of the encrypted files, to check them against later
+                    if let (true, Ok(results)) = (write_manifest, &results) {
+                        let outputs: Vec<PathBuf> = results.iter().enumerate()
+                            .filter(|(_, result)| result.contains("Successfully"))
+                            .map(|(i, result)| saved_path(result).unwrap_or_else(|| {
+                                output_folder(&output_dir, &subdirs[i]).join(file_names::encrypted_name(&files[i]).unwrap_or_default())
+                            }))
+                            .collect();
+                        let manifest = batch_manifest::write_manifest(&output_dir, &outputs, &key);
+                        
+                        if let Some(logger) = get_logger() {
+                            let dir_name = output_dir.to_string_lossy();
+                            match manifest {
+                                Ok(path) => {
+                                    logger.log_key_success(
+                                        "Write Manifest",
+                                        &dir_name,
+                                        &format!("Listed {} file(s) in {}", outputs.len(), logger.redact_path(&path)),
+                                        &key_fingerprint
+                                    ).ok();
+                                },
+                                Err(e) => {
+                                    logger.log_key_error("Write Manifest", &dir_name, &logged_error(&logger, &e), &key_fingerprint).ok();
+                                }
                             }
                         }
-                    );
+                    }
+                },
+                FileOperation::BatchDecrypt => {
+                    
+                    // Files for several recipients are recognised by their header, so
+                    // they can be decrypted with other files
+                    let results = batch_by_output_folder(&files, &subdirs, &output_dir, |paths, dest_dir, offset| {
+                        let results = backend.decrypt_files(
+                            paths,
+                            dest_dir,
+                            &key,
+                            batch_progress(&progress, &pause, offset)
+                        )?;
+                        // Saved under the name the backend restores, as it decrypts
+                        reporter.report_batch(offset, &results, |i, _| {
+                            file_names::restored_name(paths[i], &key).map(|output_name| dest_dir.join(output_name.name))
+                        });
+                        Ok(results)
+                    });
+                    if let Err(e) = &results {
+                        reporter.fail_unreported(|i| format!("Failed to decrypt {}: {}", files[i].display(), e));
+                    }
                     
                     // Log the results
                     if let Some(logger) = get_logger() {
@@ -356,25 +565,102 @@ pub fn start_operation(app: &mut CrustyApp) {
                                     "Unknown file".to_string()
                                 };
                                 
+                                // The file written, and the folder named by errors writing it
+                                let folder = files.get(i).map(|_| output_dir.join(&subdirs[i]));
+                                let output = reporter.output(i);
+                                let paths: Vec<&Path> = [files.get(i).map(PathBuf::as_path), output.as_deref(), folder.as_deref()]
+                                    .into_iter().flatten().collect();
+                                let message = logged_result(&logger, result, &paths);
+                                
                                 if result.contains("Successfully") {
-                                    logger.log_success("Batch Decrypt", &file_path, result).ok();
+                                    logger.log_key_success("Batch Decrypt", &file_path, &message, &key_fingerprint).ok();
                                 } else {
-                                    logger.log_error("Batch Decrypt", &file_path, result).ok();
+                                    logger.log_key_error("Batch Decrypt", &file_path, &message, &key_fingerprint).ok();
                                 }
                             }
                         } else if let Err(e) = &results {
-                            let error_str = e.to_string();
-                            logger.log_error(
+                            let error_str = logged_error(&logger, e);
+                            logger.log_key_error(
                                 "Batch Decrypt",
                                 "multiple files",
-                                &error_str
+                                &error_str,
+                                &key_fingerprint
                             ).ok();
                         }
                     }
                 },
+                FileOperation::Archive => {
+                    if let Some(folder) = archive_folder {
+                        let folder_name = folder.to_string_lossy().to_string();
+                        let started = Instant::now();
+                        let result = archive::archive_name(&folder, hide_file_names)
+                            .ok_or_else(|| EncryptionError::Encryption(format!("{} cannot be archived", folder_name)))
+                            .and_then(|name| {
+                                let dest_path = output_dir.join(name);
+                                archive::create_archive(&folder, &dest_path, &key, add_parity, memory_limit, archive_progress(&progress, &pause))
+                                    .map(|count| (dest_path, count))
+                            });
+                        
+                        // Every selected file is reported with the archive
+                        match &result {
+                            Ok((dest_path, count)) => {
+                                let message = format!("Successfully archived {} file(s) to {}", count, dest_path.display());
+                                for i in 0..files.len() {
+                                    reporter.report(i, Ok(message.clone()), Some(dest_path.clone()));
+                                }
+                            },
+                            Err(e) => reporter.fail_unreported(|_| format!("Failed to archive {}: {}", folder_name, e)),
+                        }
+                        
+                        if let Some(logger) = get_logger() {
+                            let entry = match result {
+                                Ok((dest_path, count)) => LogEntry::new(
+                                    "Archive",
+                                    &folder_name,
+                                    true,
+                                    &format!("Successfully archived {} file(s) to {}", count, logger.redact_path(&dest_path))
+                                ),
+                                Err(e) => LogEntry::new("Archive", &folder_name, false, &logged_error(&logger, &e)),
+                            };
+                            logger.log(entry.with_key_fingerprint(&key_fingerprint).with_duration(started.elapsed())).ok();
+                        }
+                    }
+                },
+                FileOperation::ExtractArchive => {
+                    for (i, file_path) in files.iter().enumerate() {
+                        let progress_cb = {
+                            let cb = batch_progress(&progress, &pause, i);
+                            move |p: f32| cb(0, p)
+                        };
+                        let started = Instant::now();
+                        let result = archive::extract_archive(file_path, &output_folder(&output_dir, &subdirs[i]), &key, memory_limit, progress_cb);
+                        let duration = started.elapsed();
+                        match &result {
+                            Ok(folder) => reporter.report(i, Ok(format!("Successfully extracted {} to {}", file_path.display(), folder.display())), Some(folder.clone())),
+                            Err(e) => reporter.report(i, Err(format!("Failed to extract {}: {}", file_path.display(), e)), None),
+                        }
+                        
+                        if let Some(logger) = get_logger() {
+                            let file_name = file_path.to_string_lossy().to_string();
+                            let entry = match result {
+                                Ok(folder) => LogEntry::new(
+                                    "Extract Archive",
+                                    &file_name,
+                                    true,
+                                    &format!("Successfully extracted to {}", logger.redact_path(&folder))
+                                ),
+                                Err(e) => LogEntry::new("Extract Archive", &file_name, false, &logged_error(&logger, &e)),
+                            };
+                            logger.log(entry.with_key_fingerprint(&key_fingerprint).with_duration(duration)).ok();
+                        }
+                    }
+                },
                 _ => {}
             }
             
+            // Every file gets a result, so none is left waiting in the file list
+            reporter.fail_unreported(|i| format!("Not processed: {}", files[i].display()));
+            
             // Set all progress values to 1.0 to indicate completion
             {
                 let mut guard = progress.lock().unwrap();
@@ -391,3 +677,75 @@ pub fn start_operation(app: &mut CrustyApp) {
             guard.clear();
         });
 }
+
+/// Get the path a file was saved under from its result, when it was saved under a
+/// hidden (random) name or replaced its original
+fn saved_path(result: &str) -> Option<PathBuf> {
+    let (_, saved) = result.rsplit_once(" (saved as ").or_else(|| result.rsplit_once(" (replaced by "))?;
+    saved.strip_suffix(')').map(PathBuf::from)
+}
+
+/// Redact the paths a file's result names, for the operation log
+///
+/// The paths are given by the caller rather than looked for in the message: the file,
+/// the file it was saved as, and the output folder (which errors such as a read-only
+/// destination name). Longer paths are redacted first, so a folder is never redacted
+/// inside the path of a file in it, leaving the file's name behind.
+pub(crate) fn logged_result(logger: &Logger, result: &str, paths: &[&Path]) -> String {
+    let mut paths: Vec<&Path> = paths.iter().copied().filter(|path| !path.as_os_str().is_empty()).collect();
+    paths.sort_by_key(|path| std::cmp::Reverse(path.as_os_str().len()));
+    paths.into_iter().fold(result.to_string(), |message, path| {
+        message.replace(&*path.to_string_lossy(), &logger.redact_path(path))
+    })
+}
+
+/// Describe an error for the operation log, with the output folder it names redacted
+pub(crate) fn logged_error(logger: &Logger, error: &EncryptionError) -> String {
+    match error {
+        EncryptionError::ReadOnlyDestination(dir) => {
+            EncryptionError::ReadOnlyDestination(PathBuf::from(logger.redact_path(dir))).to_string()
+        },
+        EncryptionError::MissingDestination(dir) => {
+            EncryptionError::MissingDestination(PathBuf::from(logger.redact_path(dir))).to_string()
+        },
+        error => error.to_string(),
+    }
+}
+
+/// Encrypt files in place, replacing each original with its encrypted file
+///
+/// # Returns
+/// * `Vec<String>` - The result for each file
+fn encrypt_files_in_place(
+    backend: &Backend,
+    source_paths: &[&Path],
+    key: &EncryptionKey,
+    hide_names: bool,
+    shred: bool,
+    progress_callback: impl Fn(usize, f32) + Clone + Send + 'static,
+) -> Vec<String> {
+    let mut results = Vec::new();
+    
+    for (i, &source_path) in source_paths.iter().enumerate() {
+        let file_name = if hide_names {
+            file_names::opaque_encrypted_name()
+        } else {
+            file_names::encrypted_name(source_path).unwrap_or_default()
+        };
+        let dest_path = in_place::original_dir(source_path).join(file_name);
+        
+        let progress_cb = {
+            let cb = progress_callback.clone();
+            move |p: f32| cb(i, p)
+        };
+        
+        match in_place::encrypt_in_place(backend, source_path, &dest_path, key, hide_names, shred, progress_cb) {
+            Ok(()) => results.push(format!(
+                "Successfully encrypted: {} (replaced by {})", source_path.display(), dest_path.display()
+            )),
+            Err(e) => results.push(format!("Failed to encrypt {}: {}", source_path.display(), e)),
+        }
+    }
+    
+    results
+}
@@ -0,0 +1,633 @@
+/// TPM sealing for secrets kept in the OS credential store.
+///
+/// This module provides functionality for:
+/// - Sealing a secret to this machine's TPM, optionally bound to PCR values
+/// - Unsealing it again at startup
+/// - Encoding the protection level alongside the stored secret
+/// - Refusing a stored secret protected below the level its key store requires, except
+///   once, to seal it at that level when the policy first raises it
+///
+/// A sealed secret can only be unsealed by the TPM that sealed it, so copying the
+/// credential store (or the disk) to another machine does not expose the secret. The
+/// level recorded with a secret is not trusted on its own: a secret written in the clear
+/// in place of a sealed one would otherwise be used as if it were the original. Each
+/// store sealed at a level is recorded in `protection.json` in the application data
+/// directory, and a lower level is never accepted for it again.
+/// On Linux sealing uses tpm2-tools; on Windows it uses the Platform Crypto Provider.
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Serialize, Deserialize};
+
+use crate::encryption::EncryptionError;
+
+/// How a stored secret is protected
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProtectionLevel {
+    /// Stored as-is in the OS credential store
+    #[default]
+    Software,
+    /// Sealed to this machine's TPM
+    Tpm {
+        /// PCRs (SHA-256 bank) the secret is bound to; empty for no PCR policy
+        #[serde(default)]
+        pcrs: Vec<u32>,
+    },
+}
+
+impl fmt::Display for ProtectionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtectionLevel::Software => write!(f, "Software"),
+            ProtectionLevel::Tpm { pcrs } if pcrs.is_empty() => write!(f, "TPM"),
+            ProtectionLevel::Tpm { pcrs } => write!(f, "TPM (PCR {})", pcr_list(pcrs)),
+        }
+    }
+}
+
+impl ProtectionLevel {
+    /// Check whether a secret stored at this level is protected at least as required
+    ///
+    /// Any TPM seal meets a Software requirement, and a PCR policy meets a requirement
+    /// for any of its PCRs.
+    pub fn meets(&self, required: &ProtectionLevel) -> bool {
+        match (self, required) {
+            (_, ProtectionLevel::Software) => true,
+            (ProtectionLevel::Software, ProtectionLevel::Tpm { .. }) => false,
+            (ProtectionLevel::Tpm { pcrs }, ProtectionLevel::Tpm { pcrs: required }) => {
+                required.iter().all(|pcr| pcrs.contains(pcr))
+            },
+        }
+    }
+}
+
+/// Secret as written to the credential store
+#[derive(Serialize, Deserialize)]
+struct ProtectedSecret {
+    protection: ProtectionLevel,
+    /// Base64 encoded secret (Software) or sealed blob (TPM)
+    data: String,
+}
+
+/// Format a PCR selection as a comma separated list
+fn pcr_list(pcrs: &[u32]) -> String {
+    pcrs.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// Check that the PCR selection is valid
+fn validate_pcrs(pcrs: &[u32]) -> Result<(), EncryptionError> {
+    match pcrs.iter().find(|&&p| p > 23) {
+        Some(p) => Err(EncryptionError::KeyError(format!("Invalid PCR index: {}", p))),
+        None => Ok(()),
+    }
+}
+
+/// Protect a secret for storage
+///
+/// # Arguments
+/// * `label` - Name of the key store the secret belongs to
+/// * `secret` - The secret to protect
+/// * `level` - Protection level to apply
+///
+/// # Returns
+/// * `Result<String, EncryptionError>` - Value to write to the credential store
+pub fn protect(label: &str, secret: &[u8], level: &ProtectionLevel) -> Result<String, EncryptionError> {
+    let data = match level {
+        ProtectionLevel::Software => secret.to_vec(),
+        ProtectionLevel::Tpm { pcrs } => {
+            validate_pcrs(pcrs)?;
+            platform::seal(label, secret, pcrs)?
+        }
+    };
+
+    record_sealed(&sealed_record_path(), label, level)?;
+
+    let stored = ProtectedSecret {
+        protection: level.clone(),
+        data: STANDARD.encode(data),
+    };
+    serde_json::to_string(&stored)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to encode protected secret: {}", e)))
+}
+
+/// Path of the record of the levels each key store has been sealed at
+fn sealed_record_path() -> PathBuf {
+    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("crusty");
+    path.push("protection.json");
+    path
+}
+
+/// Read the levels each key store has been sealed at (none if nothing was sealed)
+fn sealed_levels(record: &Path) -> HashMap<String, ProtectionLevel> {
+    fs::read_to_string(record)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Record the level a key store's secret was last stored at
+///
+/// Storing it at Software (after the policy lowered the level) removes the record, so
+/// raising the level again seals it again.
+fn record_sealed(record: &Path, label: &str, level: &ProtectionLevel) -> Result<(), EncryptionError> {
+    let mut levels = sealed_levels(record);
+    let changed = match level {
+        ProtectionLevel::Software => levels.remove(label).is_some(),
+        level => levels.insert(label.to_string(), level.clone()).as_ref() != Some(level),
+    };
+    if !changed {
+        return Ok(());
+    }
+    let content = serde_json::to_string_pretty(&levels)
+        .map_err(|e| EncryptionError::KeyError(format!("Failed to encode the protection record: {}", e)))?;
+    crate::shared_files::write_atomic(record, content)?;
+    Ok(())
+}
+
+/// Recover a secret written by `protect`
+///
+/// Values stored before protection levels existed (plain Base64) are read as Software.
+///
+/// # Arguments
+/// * `label` - Name of the key store the secret belongs to
+/// * `stored` - Value read from the credential store
+/// * `required` - Protection level the key store requires; a secret stored below it is
+///   refused, not used, unless the store has never been sealed at that level and a TPM
+///   is available to seal it now (the caller stores it again at the required level,
+///   see `unprotect_and_upgrade`)
+///
+/// # Returns
+/// * `Result<(Vec<u8>, ProtectionLevel), EncryptionError>` - The secret and the level it was stored with
+pub fn unprotect(label: &str, stored: &str, required: &ProtectionLevel) -> Result<(Vec<u8>, ProtectionLevel), EncryptionError> {
+    unprotect_with_record(label, stored, required, &sealed_record_path(), is_available())
+}
+
+/// Recover a secret, storing it again if it is not stored at the required level
+///
+/// A secret accepted below the required level is sealed at that level before it is
+/// used, so the lower level is only ever accepted once. A secret stored above the
+/// required level (after the policy lowered it) is stored at the lower level.
+///
+/// # Arguments
+/// * `label` - Name of the key store the secret belongs to
+/// * `stored` - Value read from the credential store
+/// * `required` - Protection level the key store requires
+/// * `store` - Writes the value protected at the required level to the credential store
+///
+/// # Returns
+/// * `Result<Vec<u8>, EncryptionError>` - The secret
+pub fn unprotect_and_upgrade(
+    label: &str,
+    stored: &str,
+    required: &ProtectionLevel,
+    store: impl FnOnce(&str) -> Result<(), EncryptionError>,
+) -> Result<Vec<u8>, EncryptionError> {
+    let (secret, stored_protection) = unprotect(label, stored, required)?;
+    if &stored_protection != required {
+        store(&protect(label, &secret, required)?)?;
+    }
+    Ok(secret)
+}
+
+/// Recover a secret, with the record of sealed levels and TPM availability given
+fn unprotect_with_record(
+    label: &str,
+    stored: &str,
+    required: &ProtectionLevel,
+    record: &Path,
+    tpm_available: bool,
+) -> Result<(Vec<u8>, ProtectionLevel), EncryptionError> {
+    let stored = match serde_json::from_str::<ProtectedSecret>(stored) {
+        Ok(stored) => stored,
+        Err(_) => ProtectedSecret { protection: ProtectionLevel::Software, data: stored.trim().to_string() },
+    };
+    // A raised requirement is met by sealing the secret now, once: after that, the
+    // record refuses anything lower in place of the sealed secret
+    let upgrade = tpm_available
+        && !sealed_levels(record).get(label).map_or(false, |sealed| sealed.meets(required));
+    if !stored.protection.meets(required) && !upgrade {
+        return Err(EncryptionError::KeyError(format!(
+            "The secret of {} is stored with {} protection, but {} protection is required. Create it again",
+            label, stored.protection, required
+        )));
+    }
+
+    let data = STANDARD.decode(stored.data.as_bytes())
+        .map_err(|e| EncryptionError::KeyError(format!("Invalid protected secret: {}", e)))?;
+
+    let secret = match &stored.protection {
+        ProtectionLevel::Software => data,
+        ProtectionLevel::Tpm { pcrs } => platform::unseal(label, &data, pcrs)?,
+    };
+
+    Ok((secret, stored.protection))
+}
+
+// TPM availability, checked once
+lazy_static::lazy_static! {
+    static ref TPM_AVAILABLE: bool = platform::is_available();
+}
+
+/// Check whether a TPM is usable on this machine
+pub fn is_available() -> bool {
+    *TPM_AVAILABLE
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+    use std::io::Write;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+
+    use super::pcr_list;
+    use crate::encryption::EncryptionError;
+
+    fn tpm_error(message: String) -> EncryptionError {
+        EncryptionError::KeyError(format!("TPM error: {}", message))
+    }
+
+    /// Run a tpm2-tools command in the working directory
+    fn run(dir: &Path, tool: &str, args: &[&str], input: Option<&[u8]>) -> Result<Vec<u8>, EncryptionError> {
+        let mut child = Command::new(tool)
+            .args(args)
+            .current_dir(dir)
+            .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| tpm_error(format!("Failed to run {} (is tpm2-tools installed?): {}", tool, e)))?;
+
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            stdin.write_all(input).map_err(|e| tpm_error(e.to_string()))?;
+        }
+
+        let output = child.wait_with_output().map_err(|e| tpm_error(e.to_string()))?;
+        if !output.status.success() {
+            return Err(tpm_error(format!("{} failed: {}", tool, String::from_utf8_lossy(&output.stderr).trim())));
+        }
+
+        Ok(output.stdout)
+    }
+
+    /// Create the storage primary key (deterministic for the owner hierarchy)
+    fn create_primary(dir: &Path) -> Result<(), EncryptionError> {
+        run(dir, "tpm2_createprimary", &["-Q", "-C", "o", "-c", "primary.ctx"], None).map(|_| ())
+    }
+
+    /// Start a session bound to the current PCR values, writing its policy digest
+    fn start_pcr_session(dir: &Path, pcrs: &[u32], policy_session: bool) -> Result<(), EncryptionError> {
+        let selection = format!("sha256:{}", pcr_list(pcrs));
+        let mut args = vec!["-S", "session.ctx"];
+        if policy_session {
+            args.push("--policy-session");
+        }
+        run(dir, "tpm2_startauthsession", &args, None)?;
+        run(dir, "tpm2_policypcr", &["-Q", "-S", "session.ctx", "-l", &selection, "-L", "pcr.policy"], None)?;
+        Ok(())
+    }
+
+    pub fn seal(_label: &str, secret: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, EncryptionError> {
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+
+        create_primary(dir)?;
+
+        let mut args = vec!["-Q", "-C", "primary.ctx", "-i", "-", "-u", "seal.pub", "-r", "seal.priv"];
+        if !pcrs.is_empty() {
+            start_pcr_session(dir, pcrs, false)?;
+            run(dir, "tpm2_flushcontext", &["session.ctx"], None)?;
+            args.extend_from_slice(&["-L", "pcr.policy"]);
+        }
+        run(dir, "tpm2_create", &args, Some(secret))?;
+
+        // Blob layout: public length (4 bytes, big-endian) + public area + private area
+        let public = fs::read(dir.join("seal.pub"))?;
+        let private = fs::read(dir.join("seal.priv"))?;
+
+        let mut blob = Vec::with_capacity(4 + public.len() + private.len());
+        blob.extend_from_slice(&(public.len() as u32).to_be_bytes());
+        blob.extend_from_slice(&public);
+        blob.extend_from_slice(&private);
+        Ok(blob)
+    }
+
+    pub fn unseal(_label: &str, blob: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, EncryptionError> {
+        if blob.len() < 4 {
+            return Err(tpm_error("Sealed data is too short".to_string()));
+        }
+        let public_len = u32::from_be_bytes([blob[0], blob[1], blob[2], blob[3]]) as usize;
+        if blob.len() < 4 + public_len {
+            return Err(tpm_error("Sealed data is truncated".to_string()));
+        }
+
+        let dir = tempfile::tempdir()?;
+        let dir = dir.path();
+        fs::write(dir.join("seal.pub"), &blob[4..4 + public_len])?;
+        fs::write(dir.join("seal.priv"), &blob[4 + public_len..])?;
+
+        create_primary(dir)?;
+        run(dir, "tpm2_load", &["-Q", "-C", "primary.ctx", "-u", "seal.pub", "-r", "seal.priv", "-c", "seal.ctx"], None)?;
+
+        if pcrs.is_empty() {
+            return run(dir, "tpm2_unseal", &["-c", "seal.ctx"], None);
+        }
+
+        start_pcr_session(dir, pcrs, true)?;
+        let result = run(dir, "tpm2_unseal", &["-c", "seal.ctx", "-p", "session:session.ctx"], None);
+        let _ = run(dir, "tpm2_flushcontext", &["session.ctx"], None);
+
+        result.map_err(|e| tpm_error(format!("Unseal failed; PCR values may have changed ({})", e)))
+    }
+
+    pub fn is_available() -> bool {
+        Path::new("/dev/tpmrm0").exists()
+            && Command::new("tpm2_getcap")
+                .arg("properties-fixed")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|s| s.success())
+                .unwrap_or(false)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    use super::pcr_list;
+    use crate::encryption::EncryptionError;
+
+    type SecurityStatus = i32;
+
+    const NCRYPT_PAD_OAEP_FLAG: u32 = 0x0000_0004;
+    const NTE_BAD_KEYSET: SecurityStatus = 0x8009_0016u32 as i32;
+
+    /// BCRYPT_OAEP_PADDING_INFO
+    #[repr(C)]
+    struct OaepPaddingInfo {
+        alg_id: *const u16,
+        label: *mut u8,
+        label_len: u32,
+    }
+
+    #[link(name = "ncrypt")]
+    extern "system" {
+        fn NCryptOpenStorageProvider(provider: *mut usize, provider_name: *const u16, flags: u32) -> SecurityStatus;
+        fn NCryptOpenKey(provider: usize, key: *mut usize, key_name: *const u16, legacy_key_spec: u32, flags: u32) -> SecurityStatus;
+        fn NCryptCreatePersistedKey(provider: usize, key: *mut usize, alg_id: *const u16, key_name: *const u16, legacy_key_spec: u32, flags: u32) -> SecurityStatus;
+        fn NCryptSetProperty(object: usize, property: *const u16, input: *const u8, input_len: u32, flags: u32) -> SecurityStatus;
+        fn NCryptFinalizeKey(key: usize, flags: u32) -> SecurityStatus;
+        fn NCryptEncrypt(key: usize, input: *const u8, input_len: u32, padding: *const c_void, output: *mut u8, output_len: u32, result_len: *mut u32, flags: u32) -> SecurityStatus;
+        fn NCryptDecrypt(key: usize, input: *const u8, input_len: u32, padding: *const c_void, output: *mut u8, output_len: u32, result_len: *mut u32, flags: u32) -> SecurityStatus;
+        fn NCryptFreeObject(object: usize) -> SecurityStatus;
+    }
+
+    /// NCrypt handle that is freed when dropped
+    struct Handle(usize);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            if self.0 != 0 {
+                unsafe { NCryptFreeObject(self.0) };
+            }
+        }
+    }
+
+    fn tpm_error(operation: &str, status: i32) -> EncryptionError {
+        EncryptionError::KeyError(format!("TPM error: {} failed (0x{:08X})", operation, status as u32))
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    fn open_provider() -> Result<Handle, EncryptionError> {
+        let mut provider = Handle(0);
+        let name = wide("Microsoft Platform Crypto Provider");
+        let status = unsafe { NCryptOpenStorageProvider(&mut provider.0, name.as_ptr(), 0) };
+        if status != 0 {
+            return Err(tpm_error("Opening the Platform Crypto Provider", status));
+        }
+        Ok(provider)
+    }
+
+    /// Open the TPM key for a key store, creating it if requested
+    ///
+    /// PCR-bound keys get their own name since the binding is fixed at creation.
+    fn open_key(label: &str, pcrs: &[u32], create: bool) -> Result<Handle, EncryptionError> {
+        let provider = open_provider()?;
+
+        let name = if pcrs.is_empty() {
+            format!("CRUSTy {}", label)
+        } else {
+            format!("CRUSTy {} (PCR {})", label, pcr_list(pcrs))
+        };
+        let name = wide(&name);
+
+        let mut key = Handle(0);
+        let status = unsafe { NCryptOpenKey(provider.0, &mut key.0, name.as_ptr(), 0, 0) };
+        if status == 0 {
+            return Ok(key);
+        }
+        if status != NTE_BAD_KEYSET || !create {
+            return Err(tpm_error("Opening the TPM key", status));
+        }
+
+        let algorithm = wide("RSA");
+        let status = unsafe {
+            NCryptCreatePersistedKey(provider.0, &mut key.0, algorithm.as_ptr(), name.as_ptr(), 0, 0)
+        };
+        if status != 0 {
+            return Err(tpm_error("Creating the TPM key", status));
+        }
+
+        let length: u32 = 2048;
+        let property = wide("Length");
+        let status = unsafe {
+            NCryptSetProperty(key.0, property.as_ptr(), length.to_le_bytes().as_ptr(), 4, 0)
+        };
+        if status != 0 {
+            return Err(tpm_error("Setting the TPM key length", status));
+        }
+
+        if !pcrs.is_empty() {
+            // 24-bit PCR mask, bound to the current PCR values
+            let mask = pcrs.iter().fold(0u32, |mask, p| mask | (1 << p));
+            let property = wide("PCP_PLATFORM_BINDING_PCRMASK");
+            let status = unsafe {
+                NCryptSetProperty(key.0, property.as_ptr(), mask.to_le_bytes().as_ptr(), 3, 0)
+            };
+            if status != 0 {
+                return Err(tpm_error("Binding the TPM key to PCRs", status));
+            }
+        }
+
+        let status = unsafe { NCryptFinalizeKey(key.0, 0) };
+        if status != 0 {
+            return Err(tpm_error("Finalizing the TPM key", status));
+        }
+
+        Ok(key)
+    }
+
+    /// OAEP padding with SHA-256; the algorithm name must outlive the padding info
+    fn padding(alg_id: &[u16]) -> OaepPaddingInfo {
+        OaepPaddingInfo {
+            alg_id: alg_id.as_ptr(),
+            label: ptr::null_mut(),
+            label_len: 0,
+        }
+    }
+
+    pub fn seal(label: &str, secret: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, EncryptionError> {
+        let key = open_key(label, pcrs, true)?;
+        let sha256 = wide("SHA256");
+        let padding = padding(&sha256);
+        let padding_ptr = &padding as *const OaepPaddingInfo as *const c_void;
+
+        let mut size = 0u32;
+        let status = unsafe {
+            NCryptEncrypt(key.0, secret.as_ptr(), secret.len() as u32, padding_ptr,
+                ptr::null_mut(), 0, &mut size, NCRYPT_PAD_OAEP_FLAG)
+        };
+        if status != 0 {
+            return Err(tpm_error("Sealing", status));
+        }
+
+        let mut sealed = vec![0u8; size as usize];
+        let status = unsafe {
+            NCryptEncrypt(key.0, secret.as_ptr(), secret.len() as u32, padding_ptr,
+                sealed.as_mut_ptr(), size, &mut size, NCRYPT_PAD_OAEP_FLAG)
+        };
+        if status != 0 {
+            return Err(tpm_error("Sealing", status));
+        }
+
+        sealed.truncate(size as usize);
+        Ok(sealed)
+    }
+
+    pub fn unseal(label: &str, blob: &[u8], pcrs: &[u32]) -> Result<Vec<u8>, EncryptionError> {
+        let key = open_key(label, pcrs, false)?;
+        let sha256 = wide("SHA256");
+        let padding = padding(&sha256);
+        let padding_ptr = &padding as *const OaepPaddingInfo as *const c_void;
+
+        let mut secret = vec![0u8; blob.len()];
+        let mut size = 0u32;
+        let status = unsafe {
+            NCryptDecrypt(key.0, blob.as_ptr(), blob.len() as u32, padding_ptr,
+                secret.as_mut_ptr(), secret.len() as u32, &mut size, NCRYPT_PAD_OAEP_FLAG)
+        };
+        if status != 0 {
+            return Err(tpm_error("Unsealing (PCR values may have changed)", status));
+        }
+
+        secret.truncate(size as usize);
+        Ok(secret)
+    }
+
+    pub fn is_available() -> bool {
+        open_provider().is_ok()
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod platform {
+    use crate::encryption::EncryptionError;
+
+    fn unsupported() -> EncryptionError {
+        EncryptionError::KeyError("TPM protection is not supported on this platform".to_string())
+    }
+
+    pub fn seal(_label: &str, _secret: &[u8], _pcrs: &[u32]) -> Result<Vec<u8>, EncryptionError> {
+        Err(unsupported())
+    }
+
+    pub fn unseal(_label: &str, _blob: &[u8], _pcrs: &[u32]) -> Result<Vec<u8>, EncryptionError> {
+        Err(unsupported())
+    }
+
+    pub fn is_available() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_software_round_trip() {
+        let stored = protect("test", b"secret bytes", &ProtectionLevel::Software).unwrap();
+        let (secret, level) = unprotect("test", &stored, &ProtectionLevel::Software).unwrap();
+
+        assert_eq!(secret, b"secret bytes");
+        assert_eq!(level, ProtectionLevel::Software);
+    }
+
+    #[test]
+    fn test_legacy_plain_value() {
+        let (secret, level) = unprotect("test", &STANDARD.encode(b"legacy"), &ProtectionLevel::Software).unwrap();
+
+        assert_eq!(secret, b"legacy");
+        assert_eq!(level, ProtectionLevel::Software);
+    }
+
+    #[test]
+    fn test_below_required_level() {
+        let tpm = ProtectionLevel::Tpm { pcrs: Vec::new() };
+        let pcr_bound = ProtectionLevel::Tpm { pcrs: vec![0, 7] };
+        assert!(pcr_bound.meets(&tpm) && tpm.meets(&ProtectionLevel::Software));
+        assert!(!tpm.meets(&pcr_bound));
+        assert!(!ProtectionLevel::Software.meets(&tpm));
+
+        // Neither a Software secret nor a plain value is used where a seal is required,
+        // whatever it holds
+        let software = protect("test", b"planted", &ProtectionLevel::Software).unwrap();
+        assert!(matches!(unprotect("test", &software, &tpm), Err(EncryptionError::KeyError(_))));
+        assert!(matches!(unprotect("test", &STANDARD.encode(b"planted"), &tpm), Err(EncryptionError::KeyError(_))));
+        let forged = serde_json::json!({ "protection": { "type": "tpm", "pcrs": [7] }, "data": "" }).to_string();
+        assert!(matches!(unprotect("test", &forged, &pcr_bound), Err(EncryptionError::KeyError(_))));
+    }
+
+    #[test]
+    fn test_upgrade_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = dir.path().join("protection.json");
+        let tpm = ProtectionLevel::Tpm { pcrs: vec![7] };
+        let software = protect("test", b"secret", &ProtectionLevel::Software).unwrap();
+
+        // Without a TPM to seal it, the lower level is refused
+        assert!(unprotect_with_record("test", &software, &tpm, &record, false).is_err());
+
+        // When the level is first raised, the secret is accepted to be sealed again
+        let (secret, level) = unprotect_with_record("test", &software, &tpm, &record, true).unwrap();
+        assert_eq!((secret.as_slice(), level), (b"secret".as_slice(), ProtectionLevel::Software));
+
+        // Once sealed at the level, a lower level is refused for that store only
+        record_sealed(&record, "test", &tpm).unwrap();
+        assert!(unprotect_with_record("test", &software, &tpm, &record, true).is_err());
+        assert!(unprotect_with_record("test", &software, &ProtectionLevel::Tpm { pcrs: Vec::new() }, &record, true).is_err());
+        assert!(unprotect_with_record("other", &software, &tpm, &record, true).is_ok());
+        assert!(unprotect_with_record("test", &software, &ProtectionLevel::Tpm { pcrs: vec![0, 7] }, &record, true).is_ok());
+
+        // Lowering the level to Software lets it be raised again
+        record_sealed(&record, "test", &ProtectionLevel::Software).unwrap();
+        assert!(unprotect_with_record("test", &software, &tpm, &record, true).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_pcr() {
+        let level = ProtectionLevel::Tpm { pcrs: vec![0, 24] };
+        assert!(matches!(protect("test", b"secret", &level), Err(EncryptionError::KeyError(_))));
+    }
+}
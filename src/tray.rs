@@ -0,0 +1,305 @@
+/// System tray module.
+///
+/// This module provides functionality for:
+/// - Showing CRUSTy's icon in the system tray (Windows notification area, macOS menu
+///   bar, Linux AppIndicator), so the window can be hidden while long operations run
+/// - Quick actions from the icon's menu: open the window, encrypt the text on the
+///   clipboard, and exit
+/// - Showing the progress of the running batch on the icon (Windows and macOS: its
+///   tooltip; Linux: a label next to it, as AppIndicator icons have no tooltip)
+///
+/// The icon is best effort: where it cannot be shown (no tray, or no AppIndicator
+/// library on Linux), `Tray::start` returns None and the window is never hidden.
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+use crate::i18n::tr;
+
+/// Width and height of the icon, in pixels
+const ICON_SIZE: u32 = 32;
+
+/// Color of the icon (the theme's accent)
+const ICON_COLOR: [u8; 4] = [255, 140, 0, 255];
+
+/// Menu item IDs
+const OPEN_ID: &str = "open";
+const ENCRYPT_CLIPBOARD_ID: &str = "encrypt-clipboard";
+const EXIT_ID: &str = "exit";
+
+/// A quick action chosen from the tray icon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrayAction {
+    /// Show the window
+    Open,
+    /// Encrypt the text on the clipboard with the current key
+    EncryptClipboard,
+    /// Exit CRUSTy
+    Exit,
+}
+
+impl TrayAction {
+    /// Get the action of a menu item
+    fn from_menu_id(id: &str) -> Option<Self> {
+        match id {
+            OPEN_ID => Some(TrayAction::Open),
+            ENCRYPT_CLIPBOARD_ID => Some(TrayAction::EncryptClipboard),
+            EXIT_ID => Some(TrayAction::Exit),
+            _ => None,
+        }
+    }
+}
+
+/// Where the icon's actions go, and what wakes the window for them
+///
+/// The menu and click handlers can only be installed once per process, so they send
+/// to the icon started last.
+type ActionTarget = (Sender<TrayAction>, Box<dyn Fn() + Send>);
+static ACTION_TARGET: Mutex<Option<ActionTarget>> = Mutex::new(None);
+
+/// Send an action to the icon started last
+fn send_action(action: TrayAction) {
+    if let Some((sender, wake)) = ACTION_TARGET.lock().unwrap().as_ref() {
+        if sender.send(action).is_ok() {
+            wake();
+        }
+    }
+
+    // A hidden window may not be drawn until it is shown again, so it is shown here
+    #[cfg(windows)]
+    if action == TrayAction::Open {
+        show_window("CRUSTy");
+    }
+}
+
+/// CRUSTy's icon in the system tray
+pub struct Tray {
+    actions: Receiver<TrayAction>,
+    /// Progress last shown on the icon
+    status: Option<(String, Option<u32>)>,
+    #[cfg(not(target_os = "linux"))]
+    icon: TrayIcon,
+    /// Progress to show, sent to the GTK thread that owns the icon
+    #[cfg(target_os = "linux")]
+    updates: Sender<Option<u32>>,
+}
+
+impl Tray {
+    /// Show the icon in the system tray
+    ///
+    /// On Windows and macOS this must be called on the thread running the window's
+    /// event loop; on Linux the icon runs its own GTK event loop in the background.
+    ///
+    /// # Arguments
+    /// * `wake` - Called after an action is chosen (to repaint the window)
+    ///
+    /// # Returns
+    /// * `Option<Tray>` - The icon, or None if it cannot be shown
+    pub fn start(wake: impl Fn() + Send + 'static) -> Option<Self> {
+        let (sender, actions) = mpsc::channel();
+        *ACTION_TARGET.lock().unwrap() = Some((sender, Box::new(wake)));
+
+        MenuEvent::set_event_handler(Some(|event: MenuEvent| {
+            if let Some(action) = TrayAction::from_menu_id(&event.id.0) {
+                send_action(action);
+            }
+        }));
+        TrayIconEvent::set_event_handler(Some(|event: TrayIconEvent| {
+            match event {
+                TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. }
+                | TrayIconEvent::DoubleClick { button: MouseButton::Left, .. } => send_action(TrayAction::Open),
+                _ => {},
+            }
+        }));
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            match build_icon() {
+                Ok(icon) => Some(Tray { actions, status: None, icon }),
+                Err(e) => {
+                    eprintln!("Failed to show the tray icon: {}", e);
+                    None
+                }
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let updates = linux::spawn()?;
+            Some(Tray { actions, status: None, updates })
+        }
+    }
+
+    /// Take the first action chosen since the last call (None if nothing was chosen)
+    pub fn take_action(&self) -> Option<TrayAction> {
+        self.actions.try_recv().ok()
+    }
+
+    /// Show the progress of the running operation on the icon
+    ///
+    /// # Arguments
+    /// * `tooltip` - Description of the operation and its progress (or just "CRUSTy")
+    /// * `percent` - Progress in percent (None when no operation is running)
+    pub fn set_status(&mut self, tooltip: String, percent: Option<u32>) {
+        let status = (tooltip, percent);
+        if self.status.as_ref() == Some(&status) {
+            return;
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.icon.set_tooltip(Some(&status.0)).ok();
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            self.updates.send(status.1).ok();
+        }
+
+        self.status = Some(status);
+    }
+}
+
+/// Create the icon with its menu
+fn build_icon() -> Result<TrayIcon, Box<dyn std::error::Error>> {
+    let menu = Menu::new();
+    menu.append_items(&[
+        &MenuItem::with_id(OPEN_ID, tr("tray-open"), true, None),
+        &MenuItem::with_id(ENCRYPT_CLIPBOARD_ID, tr("tray-encrypt-clipboard"), true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(EXIT_ID, tr("tray-exit"), true, None),
+    ])?;
+
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_menu_on_left_click(false)
+        .with_tooltip("CRUSTy")
+        .with_icon(Icon::from_rgba(icon_pixels(), ICON_SIZE, ICON_SIZE)?)
+        .build()
+        .map_err(Into::into)
+}
+
+/// Draw the icon: a padlock, in RGBA
+fn icon_pixels() -> Vec<u8> {
+    let mut pixels = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE as i32 {
+        for x in 0..ICON_SIZE as i32 {
+            // Shackle: a half ring above the body
+            let (dx, dy) = (x - 16, y - 14);
+            let distance = dx * dx + dy * dy;
+            let shackle = (y <= 14 && (36..=81).contains(&distance))
+                || ((14..=16).contains(&y) && ((7..=9).contains(&x) || (23..=25).contains(&x)));
+            // Body, with a keyhole
+            let keyhole = (x - 16).pow(2) + (y - 20).pow(2) <= 4 || (x == 16 && (20..=25).contains(&y));
+            let body = (5..=27).contains(&x) && (15..=29).contains(&y) && !keyhole;
+
+            pixels.extend_from_slice(if shackle || body { &ICON_COLOR } else { &[0, 0, 0, 0] });
+        }
+    }
+    pixels
+}
+
+/// Show the CRUSTy window and bring it to the front
+#[cfg(windows)]
+fn show_window(title: &str) {
+    use winapi::um::winuser::{FindWindowW, SetForegroundWindow, ShowWindow, SW_RESTORE};
+
+    let title: Vec<u16> = title.encode_utf16().chain(Some(0)).collect();
+    // SAFETY: the title is a NUL-terminated wide string that outlives the call
+    unsafe {
+        let hwnd = FindWindowW(std::ptr::null(), title.as_ptr());
+        if !hwnd.is_null() {
+            ShowWindow(hwnd, SW_RESTORE);
+            SetForegroundWindow(hwnd);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::sync::mpsc::{self, Sender, TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+    use gtk::glib;
+
+    /// How often the progress sent to the icon is looked for
+    const UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// Show the icon from a background thread running GTK
+    ///
+    /// # Returns
+    /// * `Option<Sender>` - Where to send the progress in percent, or None if the icon
+    ///   cannot be shown. When it is dropped, the icon is removed.
+    pub fn spawn() -> Option<Sender<Option<u32>>> {
+        let (updates, received) = mpsc::channel::<Option<u32>>();
+        let (started, result) = mpsc::channel();
+
+        thread::spawn(move || {
+            if let Err(e) = gtk::init() {
+                eprintln!("Failed to show the tray icon: {}", e);
+                started.send(false).ok();
+                return;
+            }
+            let icon = match super::build_icon() {
+                Ok(icon) => icon,
+                Err(e) => {
+                    eprintln!("Failed to show the tray icon: {}", e);
+                    started.send(false).ok();
+                    return;
+                }
+            };
+            started.send(true).ok();
+
+            let mut icon = Some(icon);
+            glib::timeout_add_local(UPDATE_INTERVAL, move || {
+                loop {
+                    match received.try_recv() {
+                        Ok(percent) => {
+                            if let Some(icon) = &icon {
+                                icon.set_title(percent.map(|percent| format!("{}%", percent)));
+                            }
+                        },
+                        Err(TryRecvError::Empty) => return glib::ControlFlow::Continue,
+                        Err(TryRecvError::Disconnected) => {
+                            drop(icon.take());
+                            gtk::main_quit();
+                            return glib::ControlFlow::Break;
+                        },
+                    }
+                }
+            });
+            gtk::main();
+        });
+
+        result.recv().unwrap_or(false).then_some(updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_actions() {
+        assert_eq!(TrayAction::from_menu_id(OPEN_ID), Some(TrayAction::Open));
+        assert_eq!(TrayAction::from_menu_id(ENCRYPT_CLIPBOARD_ID), Some(TrayAction::EncryptClipboard));
+        assert_eq!(TrayAction::from_menu_id(EXIT_ID), Some(TrayAction::Exit));
+        assert_eq!(TrayAction::from_menu_id("1001"), None);
+    }
+
+    #[test]
+    fn test_icon_pixels() {
+        let pixels = icon_pixels();
+        assert_eq!(pixels.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+        assert!(Icon::from_rgba(pixels.clone(), ICON_SIZE, ICON_SIZE).is_ok());
+
+        let pixel = |x: u32, y: u32| &pixels[((y * ICON_SIZE + x) * 4) as usize..][..4];
+        // Body, keyhole, shackle, and the corners left transparent
+        assert_eq!(pixel(8, 26), ICON_COLOR);
+        assert_eq!(pixel(16, 20)[3], 0);
+        assert_eq!(pixel(16, 6), ICON_COLOR);
+        assert_eq!(pixel(0, 0)[3], 0);
+        assert_eq!(pixel(16, 12)[3], 0);
+    }
+}